@@ -0,0 +1,164 @@
+//! Analysis of many binaries in one run of the cwe_checker.
+//!
+//! The binaries are given either as a directory, which is searched recursively for executable files,
+//! or as a text file listing one binary path per line.
+//! They are analyzed by a bounded pool of worker threads.
+//! The failure of the analysis of one binary (including panics) is recorded as the result for that binary
+//! and does not abort the analysis of the other binaries.
+
+use anyhow::{anyhow, Error};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The magic bytes at the start of ELF files.
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+/// The magic bytes at the start of PE files.
+const PE_MAGIC: &[u8] = b"MZ";
+
+/// Get the paths of all binaries to analyze.
+///
+/// If `path` is a directory, it is searched recursively for ELF and PE files.
+/// If `all_files` is set, all regular files contained in the directory are returned instead,
+/// e.g. for bare metal binaries that do not have a known file format.
+///
+/// Otherwise `path` has to be a text file containing one binary path per line.
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// The returned paths are sorted.
+pub fn collect_binaries(path: &Path, all_files: bool) -> Result<Vec<PathBuf>, Error> {
+    let mut binaries = Vec::new();
+    if path.is_dir() {
+        let mut dirs_to_visit = vec![path.to_path_buf()];
+        while let Some(dir) = dirs_to_visit.pop() {
+            for entry in std::fs::read_dir(&dir)
+                .map_err(|err| anyhow!("Could not read directory {}: {}", dir.display(), err))?
+            {
+                let entry_path = entry?.path();
+                if entry_path.is_dir() {
+                    dirs_to_visit.push(entry_path);
+                } else if entry_path.is_file() && (all_files || is_executable_file(&entry_path)) {
+                    binaries.push(entry_path);
+                }
+            }
+        }
+    } else {
+        let file_list = std::fs::read_to_string(path)
+            .map_err(|err| anyhow!("Could not read file list {}: {}", path.display(), err))?;
+        binaries = file_list
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(PathBuf::from)
+            .collect();
+    }
+    binaries.sort();
+    Ok(binaries)
+}
+
+/// Returns `true` if the file starts with the magic bytes of an ELF or PE file.
+fn is_executable_file(path: &Path) -> bool {
+    use std::io::Read;
+    let mut magic = [0u8; 4];
+    match std::fs::File::open(path).and_then(|mut file| file.read_exact(&mut magic)) {
+        Ok(()) => magic.starts_with(ELF_MAGIC) || magic.starts_with(PE_MAGIC),
+        Err(_) => false,
+    }
+}
+
+/// Apply `analyze` to all given binaries using at most `jobs` worker threads.
+///
+/// The results are returned in the order of the given binaries.
+/// A panic during the analysis of a binary is converted into an error for that binary.
+pub fn analyze_all<T, F>(binaries: &[PathBuf], jobs: usize, analyze: F) -> Vec<Result<T, Error>>
+where
+    T: Send,
+    F: Fn(&Path) -> Result<T, Error> + Sync,
+{
+    let next_index = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Result<T, Error>>>> =
+        Mutex::new(binaries.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, binaries.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let binary = match binaries.get(index) {
+                    Some(binary) => binary,
+                    None => break,
+                };
+                let result = catch_unwind(AssertUnwindSafe(|| analyze(binary)))
+                    .unwrap_or_else(|panic| Err(anyhow!("{}", get_panic_message(&*panic))));
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect()
+}
+
+/// Get the message of a caught panic.
+fn get_panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        format!("The analysis panicked: {}", message)
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        format!("The analysis panicked: {}", message)
+    } else {
+        "The analysis panicked.".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_and_analyze_binaries() {
+        let dir =
+            std::env::temp_dir().join(format!("cwe_checker_batch_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        std::fs::write(dir.join("first"), b"\x7fELF\x02\x01\x01").unwrap();
+        std::fs::write(dir.join("lib/second.so"), b"\x7fELF\x01\x01\x01").unwrap();
+        std::fs::write(dir.join("README"), b"no binary").unwrap();
+        let file_list = dir.join("binaries.txt");
+        std::fs::write(&file_list, "# comment\n\n/bin/true\n/bin/false\n").unwrap();
+
+        let binaries = collect_binaries(&dir, false).unwrap();
+        assert_eq!(binaries, vec![dir.join("first"), dir.join("lib/second.so")]);
+        assert_eq!(collect_binaries(&dir, true).unwrap().len(), 4);
+        assert_eq!(
+            collect_binaries(&file_list, false).unwrap(),
+            vec![PathBuf::from("/bin/false"), PathBuf::from("/bin/true")]
+        );
+        assert!(collect_binaries(&dir.join("nonexisting"), false).is_err());
+
+        let binaries: Vec<PathBuf> = ["a", "fail", "b", "panic", "c"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let results = analyze_all(&binaries, 3, |binary| match binary.to_str().unwrap() {
+            "fail" => Err(anyhow!("Analysis failed")),
+            "panic" => panic!("Unexpected input"),
+            name => Ok(name.to_uppercase()),
+        });
+        let results: Vec<Result<String, String>> = results
+            .into_iter()
+            .map(|result| result.map_err(|err| format!("{}", err)))
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                Ok("A".to_string()),
+                Err("Analysis failed".to_string()),
+                Ok("B".to_string()),
+                Err("The analysis panicked: Unexpected input".to_string()),
+                Ok("C".to_string()),
+            ]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,399 @@
+//! The interactive query mode of the command line interface.
+//!
+//! The commands are thin wrappers around the queries of [`cwe_checker_lib::utils::query`].
+
+use cwe_checker_lib::analysis::pointer_inference::PointerInference;
+use cwe_checker_lib::intermediate_representation::Project;
+use cwe_checker_lib::utils::log::CweWarning;
+use cwe_checker_lib::utils::query;
+use std::io::{BufRead, Write};
+
+/// The help text listing all commands of the interactive mode.
+const HELP: &str = "\
+Commands:
+  state <address>                Print the pointer inference state before the instruction at the address.
+  points-to <address> <register> List the memory objects that the register may point to before the instruction.
+  callers <function>             List all call sites of the function or extern symbol.
+  warnings <function>            List the CWE warnings in the function.
+  ir <function>                  Print the intermediate representation of the function annotated with CWE warnings.
+  help                           Print this help text.
+  quit                           Leave the interactive mode.
+Functions can be given by name, TID or address. Addresses are hexadecimal numbers.
+";
+
+/// A command of the interactive mode.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Command {
+    State(u64),
+    PointsTo(u64, String),
+    Callers(String),
+    Warnings(String),
+    Ir(String),
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parse a line of user input into a command.
+    /// Returns `Ok(None)` for empty lines.
+    fn parse(line: &str) -> Result<Option<Command>, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let address = |word: &str| query::parse_address(word).map_err(|err| err.to_string());
+        let command = match words[..] {
+            [] => return Ok(None),
+            ["state", addr] => Command::State(address(addr)?),
+            ["points-to", addr, register] => {
+                Command::PointsTo(address(addr)?, register.to_string())
+            }
+            ["callers", function] => Command::Callers(function.to_string()),
+            ["warnings", function] => Command::Warnings(function.to_string()),
+            ["ir", function] => Command::Ir(function.to_string()),
+            ["help"] => Command::Help,
+            ["quit"] | ["exit"] => Command::Quit,
+            _ => {
+                return Err(format!(
+                    "Unknown command or wrong number of arguments: {}",
+                    line.trim()
+                ))
+            }
+        };
+        Ok(Some(command))
+    }
+}
+
+/// The loaded analysis results that the commands of the interactive mode are executed on.
+pub struct Session<'a, 'b> {
+    project: &'a Project,
+    pi_results: &'a PointerInference<'b>,
+    cwes: &'a [CweWarning],
+}
+
+impl<'a, 'b> Session<'a, 'b> {
+    /// Create a new session over the given analysis results.
+    pub fn new(
+        project: &'a Project,
+        pi_results: &'a PointerInference<'b>,
+        cwes: &'a [CweWarning],
+    ) -> Session<'a, 'b> {
+        Session {
+            project,
+            pi_results,
+            cwes,
+        }
+    }
+
+    /// Execute the command and return its output.
+    fn execute(&self, command: &Command) -> Result<String, String> {
+        let to_lines = |items: Vec<String>, empty_message: &str| {
+            if items.is_empty() {
+                format!("{}\n", empty_message)
+            } else {
+                items.into_iter().map(|item| item + "\n").collect()
+            }
+        };
+        let output = match command {
+            Command::State(address) => {
+                let state =
+                    query::get_state_before_address(self.project, self.pi_results, *address)
+                        .map_err(|err| err.to_string())?;
+                format!("{:#}\n", state.to_json_compact())
+            }
+            Command::PointsTo(address, register) => {
+                let targets = query::get_pointer_targets_at_address(
+                    self.project,
+                    self.pi_results,
+                    *address,
+                    register,
+                )
+                .map_err(|err| err.to_string())?;
+                to_lines(
+                    targets.iter().map(|target| target.to_string()).collect(),
+                    "No known pointer targets.",
+                )
+            }
+            Command::Callers(function) => to_lines(
+                query::get_callers(self.project, function)
+                    .iter()
+                    .map(|caller| caller.to_string())
+                    .collect(),
+                "No callers found.",
+            ),
+            Command::Warnings(function) => to_lines(
+                query::get_warnings_in_function(self.project, function, self.cwes)
+                    .map_err(|err| err.to_string())?
+                    .iter()
+                    .map(|warning| warning.to_string())
+                    .collect(),
+                "No warnings found.",
+            ),
+            Command::Ir(function) => {
+                query::format_annotated_function(self.project, function, self.cwes)
+                    .map_err(|err| err.to_string())?
+            }
+            Command::Help => HELP.to_string(),
+            Command::Quit => String::new(),
+        };
+        Ok(output)
+    }
+
+    /// Parse and execute a line of user input.
+    ///
+    /// Returns the output of the command or `None` if the user wants to quit the interactive mode.
+    pub fn dispatch(&self, line: &str) -> Option<String> {
+        match Command::parse(line) {
+            Ok(Some(Command::Quit)) => None,
+            Ok(Some(command)) => Some(
+                self.execute(&command)
+                    .unwrap_or_else(|err| format!("Error: {}\n", err)),
+            ),
+            Ok(None) => Some(String::new()),
+            Err(err) => Some(format!(
+                "Error: {}\nType 'help' for a list of commands.\n",
+                err
+            )),
+        }
+    }
+
+    /// Read commands line by line from the input and write their results to the output
+    /// until the user quits or the input ends.
+    pub fn run(&self, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+        write!(output, "Type 'help' for a list of commands.\n> ")?;
+        output.flush()?;
+        for line in input.lines() {
+            match self.dispatch(&line?) {
+                Some(result) => write!(output, "{}> ", result)?,
+                None => break,
+            }
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cwe_checker_lib::analysis::graph::get_program_cfg;
+    use cwe_checker_lib::analysis::pointer_inference;
+    use cwe_checker_lib::intermediate_representation::*;
+    use cwe_checker_lib::utils::binary::{BareMetalConfig, RuntimeMemoryImage};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn var(name: &str) -> Variable {
+        Variable {
+            name: name.to_string(),
+            size: ByteSize::new(8),
+            is_temp: false,
+        }
+    }
+
+    fn tid(id: &str, address: &str) -> Tid {
+        let mut tid = Tid::new(id);
+        tid.address = address.to_string();
+        tid
+    }
+
+    fn call(id: &str, address: &str, target: &str, return_: &str) -> Term<Jmp> {
+        Term {
+            tid: tid(id, address),
+            term: Jmp::Call {
+                target: Tid::new(target),
+                return_: Some(Tid::new(return_)),
+            },
+        }
+    }
+
+    fn block(id: &str, defs: Vec<Term<Def>>, jmps: Vec<Term<Jmp>>) -> Term<Blk> {
+        Term {
+            tid: Tid::new(id),
+            term: Blk {
+                defs,
+                jmps,
+                indirect_jmp_targets: Vec::new(),
+            },
+        }
+    }
+
+    /// Create a project with a function `main` at address 0x1000 that allocates memory with `malloc`,
+    /// moves the pointer to RDI at 0x1008 and frees it at 0x100c.
+    fn mock_project() -> Project {
+        let cconv = CallingConvention {
+            name: "__stdcall".to_string(),
+            integer_parameter_register: vec![var("RDI")],
+            float_parameter_register: Vec::new(),
+            integer_return_register: vec![var("RAX")],
+            float_return_register: Vec::new(),
+            callee_saved_register: vec![var("RBP")],
+            varargs_layout: None,
+        };
+        let symbol = |name: &str| ExternSymbol {
+            tid: Tid::new(name),
+            addresses: vec!["UNKNOWN".to_string()],
+            name: name.to_string(),
+            calling_convention: Some(cconv.name.clone()),
+            parameters: vec![Arg::Register {
+                expr: Expression::Var(var("RDI")),
+                data_type: None,
+            }],
+            return_values: vec![Arg::Register {
+                expr: Expression::Var(var("RAX")),
+                data_type: None,
+            }],
+            no_return: false,
+            has_var_args: false,
+        };
+        let move_def = Term {
+            tid: tid("move", "00001008"),
+            term: Def::Assign {
+                var: var("RDI"),
+                value: Expression::Var(var("RAX")),
+            },
+        };
+        let main = Term {
+            tid: tid("main", "00001000"),
+            term: Sub {
+                name: "main".to_string(),
+                blocks: vec![
+                    block(
+                        "alloc_blk",
+                        Vec::new(),
+                        vec![call("call_malloc", "00001004", "malloc", "free_blk")],
+                    ),
+                    block(
+                        "free_blk",
+                        vec![move_def],
+                        vec![call("call_free", "0000100c", "free", "end_blk")],
+                    ),
+                    block("end_blk", Vec::new(), Vec::new()),
+                ],
+                calling_convention: None,
+                is_thunk: false,
+            },
+        };
+        let byte_size = ByteSize::new;
+        Project {
+            program: Term {
+                tid: Tid::new("program"),
+                term: Program {
+                    subs: BTreeMap::from([(main.tid.clone(), main.clone())]),
+                    extern_symbols: BTreeMap::from([
+                        (Tid::new("malloc"), symbol("malloc")),
+                        (Tid::new("free"), symbol("free")),
+                    ]),
+                    entry_points: BTreeSet::from([main.tid]),
+                    address_base_offset: 0,
+                    symbol_aliases: BTreeMap::new(),
+                    non_returning_subs: BTreeSet::new(),
+                },
+            },
+            cpu_architecture: "x86_64".to_string(),
+            architecture: Arch::X86_64,
+            stack_pointer_register: var("RSP"),
+            calling_conventions: BTreeMap::from([(cconv.name.clone(), cconv)]),
+            register_set: ["RAX", "RBP", "RDI", "RSP"].into_iter().map(var).collect(),
+            datatype_properties: DatatypeProperties {
+                char_size: byte_size(1),
+                double_size: byte_size(8),
+                float_size: byte_size(4),
+                integer_size: byte_size(4),
+                long_double_size: byte_size(16),
+                long_long_size: byte_size(8),
+                long_size: byte_size(8),
+                pointer_size: byte_size(8),
+                short_size: byte_size(2),
+            },
+            stack_canary_artifacts: BTreeSet::new(),
+            duplicated_term_origins: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn dispatch_commands() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::new_from_bare_metal(
+            &[],
+            &BareMetalConfig {
+                processor_id: "x86:LE:32:default".to_string(),
+                flash_base_address: "0x100000".to_string(),
+                ram_base_address: "0x200000".to_string(),
+                ram_size: "0x1000".to_string(),
+            },
+        )
+        .unwrap();
+        let graph = get_program_cfg(
+            &project.program,
+            project
+                .program
+                .term
+                .extern_symbols
+                .keys()
+                .cloned()
+                .collect(),
+        );
+        let config = serde_json::from_value(serde_json::json!({
+            "allocation_symbols": ["malloc"],
+            "deallocation_symbols": ["free"],
+        }))
+        .unwrap();
+        let pi_results = pointer_inference::run(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            config,
+            false,
+            false,
+        );
+        let cwes = vec![
+            CweWarning::new("CWE_TEST", "0.1", "Test warning at 00001008")
+                .addresses(vec!["00001008".to_string()]),
+        ];
+        let session = Session::new(&project, &pi_results, &cwes);
+
+        assert!(session.dispatch("state 0x1008").unwrap().contains("RSP"));
+        assert!(session
+            .dispatch("points-to 0x100c RDI")
+            .unwrap()
+            .contains("call_malloc"));
+        assert_eq!(
+            session.dispatch("points-to 0x1008 RBP").unwrap(),
+            "No known pointer targets.\n"
+        );
+        assert_eq!(
+            session.dispatch("callers free").unwrap(),
+            "main (main) at 0000100c\n"
+        );
+        assert!(session
+            .dispatch("warnings main")
+            .unwrap()
+            .contains("Test warning at 00001008"));
+        let listing = session.dispatch("ir 0x1000").unwrap();
+        assert!(listing.contains("00001008: Assign"));
+        assert!(listing.contains("[CWE_TEST]"));
+        assert!(session.dispatch("help").unwrap().contains("points-to"));
+        assert_eq!(session.dispatch("").unwrap(), "");
+        assert!(session
+            .dispatch("state 0xzz")
+            .unwrap()
+            .starts_with("Error: 0xzz is not a valid address."));
+        assert!(session
+            .dispatch("ir unknown")
+            .unwrap()
+            .starts_with("Error: No function unknown found."));
+        assert!(session
+            .dispatch("frobnicate")
+            .unwrap()
+            .starts_with("Error: Unknown command"));
+        assert_eq!(session.dispatch("quit"), None);
+
+        let mut output = Vec::new();
+        session
+            .run(
+                "callers malloc\nquit\ncallers free\n".as_bytes(),
+                &mut output,
+            )
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("at 00001004"));
+        assert!(!output.contains("at 0000100c"));
+    }
+}
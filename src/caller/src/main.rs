@@ -3,16 +3,35 @@
 
 extern crate cwe_checker_lib; // Needed for the docstring-link to work
 
-use cwe_checker_lib::analysis::graph;
-use cwe_checker_lib::utils::binary::{BareMetalConfig, RuntimeMemoryImage};
-use cwe_checker_lib::utils::log::{print_all_messages, LogLevel};
+mod batch;
+mod interactive;
+
+use anyhow::{anyhow, Error};
+use cwe_checker_lib::analysis::backward_slicing;
+use cwe_checker_lib::analysis::patch_diff::PatchDiffConfig;
+use cwe_checker_lib::analysis::pointer_inference::harness::HarnessSpec;
+use cwe_checker_lib::analysis::pointer_inference::{NodeValueRetention, PointerInference};
+use cwe_checker_lib::pcode::ParsingConfig;
+use cwe_checker_lib::utils::binary::BareMetalConfig;
+use cwe_checker_lib::utils::checkpoint::CheckpointOptions;
+use cwe_checker_lib::utils::log::{CweWarning, LogLevel};
+use cwe_checker_lib::utils::output::{
+    AnalysisResultsOutput, BatchReport, JsonReport, OutputFormat, OutputSink,
+};
+use cwe_checker_lib::utils::pipeline::{
+    analyze_patch_diff, analyze_project, analyze_project_with_checkpoint,
+    analyze_project_with_inspection, convert_parsed_project, get_pointer_inference_config,
+    set_node_value_retention, PipelineConfig,
+};
+use cwe_checker_lib::utils::results_diff::{DiffConfig, ResultsDiff};
 use cwe_checker_lib::utils::{get_ghidra_plugin_path, read_config_file};
-use cwe_checker_lib::AnalysisResults;
+use cwe_checker_lib::MODULES_DISABLED_ON_STANDARD_RUNS;
 use cwe_checker_lib::{intermediate_representation::Project, utils::log::LogMessage};
 use nix::{sys::stat, unistd};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
 use structopt::StructOpt;
 
@@ -20,7 +39,10 @@ use structopt::StructOpt;
 /// Find vulnerable patterns in binary executables
 struct CmdlineArgs {
     /// The path to the binary.
-    #[structopt(required_unless("module-versions"), validator(check_file_existence))]
+    #[structopt(
+        required_unless_one(&["module-versions", "batch"]),
+        validator(check_file_existence)
+    )]
     binary: Option<String>,
 
     /// Path to a custom configuration file to use instead of the standard one.
@@ -29,8 +51,16 @@ struct CmdlineArgs {
 
     /// Write the results to a file instead of stdout.
     /// This only affects CWE warnings. Log messages are still printed to stdout.
-    #[structopt(long, short)]
-    out: Option<String>,
+    ///
+    /// The option can be given several times to write the results in different formats at once.
+    /// Each value has the form '<format>:<path>', where the format is one of 'text', 'json', 'sarif' or 'summary'
+    /// and the path '-' denotes stdout, e.g. '--out text:- --out json:results.json --out sarif:results.sarif'.
+    /// If no format is given, the format is determined by the '--json' and '--sarif' flags.
+    ///
+    /// The exit code is 1 if CWE warnings were found and 0 otherwise.
+    /// If writing to one of the outputs fails, the other outputs are still written and 2 is added to the exit code.
+    #[structopt(long, short, number_of_values = 1)]
+    out: Vec<String>,
 
     /// Specify a specific set of checks to be run as a comma separated list, e.g. 'CWE332,CWE476,CWE782'.
     ///
@@ -39,9 +69,14 @@ struct CmdlineArgs {
     partial: Option<String>,
 
     /// Generate JSON output.
+    /// The version of the output schema is contained in the 'schema_version' field of the output.
     #[structopt(long, short)]
     json: bool,
 
+    /// Generate output in the Static Analysis Results Interchange Format (SARIF), version 2.1.0.
+    #[structopt(long, conflicts_with_all(&["json", "batch", "diff"]))]
+    sarif: bool,
+
     /// Do not print log messages. This prevents polluting stdout for json output.
     #[structopt(long, short)]
     quiet: bool,
@@ -55,6 +90,106 @@ struct CmdlineArgs {
     #[structopt(long, conflicts_with("quiet"))]
     statistics: bool,
 
+    /// Add a ranked report of the program points where checks gave up because of unknown values
+    /// to the JSON output (under the 'feedback' key).
+    /// It shows for which extern functions or code regions annotations would improve the results the most.
+    #[structopt(long)]
+    feedback: bool,
+
+    /// Compare the CWE warnings with the results of a previous run and only print the changes.
+    ///
+    /// The path must point to the JSON output of a previous run.
+    /// The changes are printed to stdout as JSON if the '--json' flag is set and as text otherwise.
+    #[structopt(long, validator(check_file_existence), conflicts_with("out"))]
+    diff: Option<String>,
+
+    /// Only analyze the functions changed with respect to an older version of the binary.
+    ///
+    /// The path must point to the old version of the binary.
+    /// All unchanged functions are summarized instead of analyzed.
+    /// Only findings in changed functions are reported and findings without a counterpart
+    /// in the old binary are marked as new.
+    /// The analyzed call depth around changed functions can be set in the 'PatchDiff' section of the configuration file.
+    #[structopt(
+        long,
+        validator(check_file_existence),
+        conflicts_with_all(&[
+            "batch",
+            "diff",
+            "slice",
+            "interactive",
+            "checkpoint",
+            "resume",
+            "debug",
+            "debug-stack-frames"
+        ])
+    )]
+    patch_diff: Option<String>,
+
+    /// Print the backward slice for the value of a register at an instruction and then quit.
+    ///
+    /// The value has the form '<address>:<register>', e.g. '0x401000:RDI'.
+    /// The slice lists all instructions that may contribute to the value of the register
+    /// just before the instruction at the given address.
+    #[structopt(long, conflicts_with_all(&["diff", "debug"]))]
+    slice: Option<String>,
+
+    /// Start an interactive query mode over the analysis results after all checks were run.
+    ///
+    /// The commands are read from stdin. Type 'help' for a list of the available commands.
+    #[structopt(long, conflicts_with_all(&["diff", "slice", "debug"]))]
+    interactive: bool,
+
+    /// Analyze many binaries and write one aggregated JSON report.
+    ///
+    /// The path is either a directory, which is searched recursively for ELF and PE files,
+    /// or a text file containing one binary path per line.
+    /// If a bare metal configuration is given, all files in the directory are analyzed.
+    /// The report contains the results and a summary for each binary.
+    /// Binaries whose analysis failed are recorded in the report without aborting the batch.
+    /// The report is written to stdout or to the JSON outputs given by the '--out' option.
+    /// Log messages are printed to stderr.
+    #[structopt(
+        long,
+        conflicts_with_all(&[
+            "binary",
+            "diff",
+            "slice",
+            "interactive",
+            "debug",
+            "debug-stack-frames"
+        ])
+    )]
+    batch: Option<String>,
+
+    /// The number of binaries analyzed in parallel in batch mode.
+    #[structopt(long, default_value = "1", requires("batch"))]
+    jobs: usize,
+
+    /// Path to a JSON file containing a harness specification.
+    ///
+    /// Only the function named in the harness and the functions reachable from it are analyzed.
+    /// The analysis of the function starts with the parameter facts given in the harness,
+    /// e.g. that a parameter points to a buffer whose size is given by another parameter.
+    /// The CWE warnings are marked as found under the harness assumptions.
+    #[structopt(long, validator(check_file_existence))]
+    harness: Option<String>,
+
+    /// Save the results of each completed analysis phase to a checkpoint in the given directory.
+    ///
+    /// An interrupted analysis can then be resumed from the checkpoint with the '--resume' option.
+    /// Results of previous analyses in the directory are replaced.
+    #[structopt(long, conflicts_with_all(&["resume", "batch"]))]
+    checkpoint: Option<String>,
+
+    /// Resume an interrupted analysis from the checkpoint in the given directory.
+    ///
+    /// The analysis phases completed in the checkpoint are skipped
+    /// and the results of the remaining phases are added to the checkpoint.
+    /// The analysis fails if the checkpoint was created for a different binary or with different settings.
+    #[structopt(long, conflicts_with("batch"))]
+    resume: Option<String>,
+
     /// Path to a configuration file for analysis of bare metal binaries.
     ///
     /// If this option is set then the input binary is treated as a bare metal binary regardless of its format.
@@ -69,12 +204,39 @@ struct CmdlineArgs {
     /// The current behavior of this flag is unstable and subject to change.
     #[structopt(long, hidden = true)]
     debug: bool,
+
+    /// Print the stack frame layouts recovered from the pointer inference results as JSON and then quit.
+    /// The output is intended for debugging and its format is subject to change.
+    #[structopt(
+        long,
+        hidden = true,
+        conflicts_with_all(&["diff", "slice", "interactive", "debug"])
+    )]
+    debug_stack_frames: bool,
 }
 
+/// Exit code flag that is set if CWE warnings were found.
+const EXIT_CODE_FINDINGS: i32 = 1;
+/// Exit code flag that is set if writing the results to at least one of the outputs failed.
+const EXIT_CODE_OUTPUT_ERROR: i32 = 2;
+
 fn main() {
     let cmdline_args = CmdlineArgs::from_args();
 
-    run_with_ghidra(&cmdline_args);
+    std::process::exit(run_with_ghidra(&cmdline_args));
+}
+
+/// Get the exit code of a run from whether CWE warnings were found
+/// and whether writing the results to an output failed.
+fn get_exit_code(has_findings: bool, has_output_errors: bool) -> i32 {
+    let mut exit_code = 0;
+    if has_findings {
+        exit_code |= EXIT_CODE_FINDINGS;
+    }
+    if has_output_errors {
+        exit_code |= EXIT_CODE_OUTPUT_ERROR;
+    }
+    exit_code
 }
 
 /// Check the existence of a file
@@ -89,8 +251,8 @@ fn check_file_existence(file_path: String) -> Result<(), String> {
     }
 }
 
-/// Run the cwe_checker with Ghidra as its backend.
-fn run_with_ghidra(args: &CmdlineArgs) {
+/// Run the cwe_checker with Ghidra as its backend and return the exit code of the run.
+fn run_with_ghidra(args: &CmdlineArgs) -> i32 {
     let mut modules = cwe_checker_lib::get_modules();
     if args.module_versions {
         // Only print the module versions and then quit.
@@ -98,7 +260,7 @@ fn run_with_ghidra(args: &CmdlineArgs) {
         for module in modules.iter() {
             println!("{}", module);
         }
-        return;
+        return 0;
     }
 
     // Get the configuration file
@@ -121,12 +283,46 @@ fn run_with_ghidra(args: &CmdlineArgs) {
     if let Some(ref partial_module_list) = args.partial {
         filter_modules_for_partial_run(&mut modules, partial_module_list);
     } else {
-        // TODO: CWE78 is disabled on a standard run for now,
-        // because it uses up huge amounts of RAM and computation time on some binaries.
-        modules = modules
-            .into_iter()
-            .filter(|module| module.name != "CWE78")
-            .collect();
+        modules.retain(|module| !MODULES_DISABLED_ON_STANDARD_RUNS.contains(&module.name));
+    }
+
+    // Get the harness specification if it is provided
+    let harness: Option<HarnessSpec> = args.harness.as_ref().map(|harness_path| {
+        let file = std::io::BufReader::new(std::fs::File::open(harness_path).unwrap());
+        serde_json::from_reader(file).expect("Parsing of the harness specification failed")
+    });
+
+    let parsing_config: ParsingConfig =
+        serde_json::from_value(config["PcodeParsing"].clone()).unwrap_or_default();
+
+    let pipeline_config = PipelineConfig {
+        modules: &modules,
+        config: &config,
+        bare_metal_config: bare_metal_config_opt.as_ref(),
+        statistics: args.statistics,
+        feedback: args.feedback,
+        harness: harness.as_ref(),
+    };
+
+    if let Some(batch_path) = &args.batch {
+        return run_batch(
+            args,
+            Path::new(batch_path),
+            &pipeline_config,
+            &parsing_config,
+        );
+    }
+
+    if let Some(old_binary_path) = &args.patch_diff {
+        let patch_diff_config: PatchDiffConfig =
+            serde_json::from_value(config["PatchDiff"].clone()).unwrap_or_default();
+        return run_patch_diff(
+            args,
+            Path::new(old_binary_path),
+            &pipeline_config,
+            &parsing_config,
+            &patch_diff_config,
+        );
     }
 
     let binary_file_path = PathBuf::from(args.binary.clone().unwrap());
@@ -136,114 +332,366 @@ fn run_with_ghidra(args: &CmdlineArgs) {
             binary_file_path.display()
         )
     });
-    let (mut project, mut all_logs) = get_project_from_ghidra(
+    let (project, mut logs, is_partial_run) = get_project_from_ghidra(
         &binary_file_path,
         &binary[..],
         bare_metal_config_opt.clone(),
-    );
-    // Normalize the project and gather log messages generated from it.
-    all_logs.append(&mut project.normalize());
+        &parsing_config,
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(101);
+    });
 
-    // Generate the representation of the runtime memory image of the binary
-    let mut runtime_memory_image = if let Some(bare_metal_config) = bare_metal_config_opt.as_ref() {
-        RuntimeMemoryImage::new_from_bare_metal(&binary, bare_metal_config).unwrap_or_else(|err| {
-            panic!("Error while generating runtime memory image: {}", err);
-        })
-    } else {
-        RuntimeMemoryImage::new(&binary).unwrap_or_else(|err| {
-            panic!("Error while generating runtime memory image: {}", err);
-        })
+    // Create the checkpoint or open it to resume from it.
+    let checkpoint_options = match (&args.checkpoint, &args.resume) {
+        (Some(directory), _) => Some(CheckpointOptions {
+            directory: Path::new(directory),
+            resume: false,
+        }),
+        (None, Some(directory)) => Some(CheckpointOptions {
+            directory: Path::new(directory),
+            resume: true,
+        }),
+        (None, None) => None,
     };
-    if project.program.term.address_base_offset != 0 {
-        // We adjust the memory addresses once globally
-        // so that other analyses do not have to adjust their addresses.
-        runtime_memory_image.add_global_memory_offset(project.program.term.address_base_offset);
+
+    if args.slice.is_some() || args.interactive || args.debug || args.debug_stack_frames {
+        run_inspection_mode(
+            args,
+            project,
+            &binary,
+            &pipeline_config,
+            checkpoint_options.as_ref(),
+            logs,
+        );
+        return 0;
     }
-    // Generate the control flow graph of the program
-    let extern_sub_tids = project
-        .program
-        .term
-        .extern_symbols
-        .keys()
-        .cloned()
-        .collect();
-    let control_flow_graph = graph::get_program_cfg(&project.program, extern_sub_tids);
 
-    let analysis_results = AnalysisResults::new(
+    let mut output = analyze_project_with_checkpoint(
+        project,
         &binary,
-        &runtime_memory_image,
-        &control_flow_graph,
-        &project,
-    );
+        &pipeline_config,
+        checkpoint_options.as_ref(),
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(101);
+    });
+    logs.append(&mut output.logs);
+    filter_logs(args, &mut logs);
+    output.logs = logs;
+    let output = output.partial(is_partial_run).binary(args.binary.clone());
+    output.print_logs();
+    if let Some(old_results_path) = &args.diff {
+        print_results_diff(old_results_path, &output.cwes, args.json);
+        return get_exit_code(!output.cwes.is_empty(), false);
+    }
+    write_output(&output, &get_output_sinks(args))
+}
 
-    let modules_depending_on_string_abstraction = vec!["CWE78"];
-    let modules_depending_on_pointer_inference = vec!["CWE134", "CWE476", "Memory"];
+/// Write the output to all sinks and return the exit code of the run.
+///
+/// Errors while writing to one output sink do not prevent writing to the other sinks.
+/// They are reported separately and do not change whether the exit code indicates findings.
+fn write_output(output: &AnalysisResultsOutput, sinks: &[OutputSink]) -> i32 {
+    let errors = output.write_all(sinks);
+    for (sink, error) in errors.iter() {
+        eprintln!("Error while writing output {}: {}", sink, error);
+    }
+    get_exit_code(!output.cwes.is_empty(), !errors.is_empty())
+}
 
-    let string_abstraction_needed = modules
-        .iter()
-        .any(|module| modules_depending_on_string_abstraction.contains(&module.name));
+/// Run the analysis pipeline for the `--slice`, `--interactive`, `--debug` and `--debug-stack-frames` modes,
+/// which inspect the analysis results after the pipeline finished.
+///
+/// Except for the interactive mode, the CWE modules are not executed.
+fn run_inspection_mode(
+    args: &CmdlineArgs,
+    project: Project,
+    binary: &[u8],
+    pipeline_config: &PipelineConfig,
+    checkpoint_options: Option<&CheckpointOptions>,
+    mut logs: Vec<LogMessage>,
+) {
+    let pipeline_config = PipelineConfig {
+        modules: if args.interactive {
+            pipeline_config.modules
+        } else {
+            &[]
+        },
+        ..*pipeline_config
+    };
+    analyze_project_with_inspection(
+        project,
+        binary,
+        &pipeline_config,
+        checkpoint_options,
+        &mut |analysis_results, output| {
+            let pi_analysis_results = analysis_results.pointer_inference.unwrap();
+            if let Some(slice_spec) = &args.slice {
+                print_backward_slice(analysis_results.project, pi_analysis_results, slice_spec);
+            } else if args.debug_stack_frames {
+                let stack_frame_layouts = analysis_results.compute_stack_frame_layouts();
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&stack_frame_layouts).unwrap()
+                );
+            } else if args.debug {
+                // Right now there is only one debug printing function.
+                // When more debug printing modes exist, this behaviour will change!
+                let mut pointer_inference_config =
+                    get_pointer_inference_config(pipeline_config.config, pipeline_config.harness);
+                set_node_value_retention(&mut pointer_inference_config, NodeValueRetention::All);
+                cwe_checker_lib::analysis::pointer_inference::run(
+                    analysis_results.project,
+                    analysis_results.runtime_memory_image,
+                    analysis_results.control_flow_graph,
+                    serde_json::from_value(pointer_inference_config).unwrap(),
+                    true,
+                    false,
+                );
+            } else {
+                logs.extend(output.logs.iter().cloned());
+                filter_logs(args, &mut logs);
+                for log in logs.iter() {
+                    println!("{}", log);
+                }
+                let session = interactive::Session::new(
+                    analysis_results.project,
+                    pi_analysis_results,
+                    &output.cwes,
+                );
+                if let Err(err) = session.run(std::io::stdin().lock(), std::io::stdout()) {
+                    eprintln!("Error in interactive mode: {}", err);
+                }
+            }
+        },
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Error: {}", err);
+        std::process::exit(101);
+    });
+}
 
-    let pi_analysis_needed = string_abstraction_needed
-        || modules
-            .iter()
-            .any(|module| modules_depending_on_pointer_inference.contains(&module.name));
+/// Remove the log messages not requested by the `--quiet`, `--verbose` and `--statistics` flags.
+fn filter_logs(args: &CmdlineArgs, logs: &mut Vec<LogMessage>) {
+    if args.quiet {
+        logs.clear();
+    } else {
+        if args.statistics {
+            cwe_checker_lib::utils::log::add_debug_log_statistics(logs);
+        }
+        if !args.verbose {
+            logs.retain(|log_msg| log_msg.level != LogLevel::Debug);
+        }
+    }
+}
 
-    let pi_analysis_results = if pi_analysis_needed {
-        Some(analysis_results.compute_pointer_inference(&config["Memory"], args.statistics))
+/// Get the output sinks given by the `--out` parameter
+/// or the standard output in the format given by the `--json` and `--sarif` flags.
+fn get_output_sinks(args: &CmdlineArgs) -> Vec<OutputSink> {
+    let default_format = if args.json {
+        OutputFormat::Json
+    } else if args.sarif {
+        OutputFormat::Sarif
     } else {
-        None
+        OutputFormat::Text
     };
+    if args.out.is_empty() {
+        vec![OutputSink {
+            format: default_format,
+            path: None,
+        }]
+    } else {
+        args.out
+            .iter()
+            .map(|spec| OutputSink::parse(spec, default_format))
+            .collect()
+    }
+}
 
-    let analysis_results = analysis_results.set_pointer_inference(pi_analysis_results.as_ref());
-
-    let string_abstraction_results =
-        if string_abstraction_needed {
-            Some(analysis_results.compute_string_abstraction(
-                &config["StringAbstraction"],
-                pi_analysis_results.as_ref(),
-            ))
-        } else {
-            None
-        };
+/// Analyze the functions of the binary changed with respect to the old binary given by the `--patch-diff` parameter
+/// and write the findings in the changed functions.
+/// Returns the exit code of the run.
+fn run_patch_diff(
+    args: &CmdlineArgs,
+    old_binary_path: &Path,
+    pipeline_config: &PipelineConfig,
+    parsing_config: &ParsingConfig,
+    patch_diff_config: &PatchDiffConfig,
+) -> i32 {
+    let load_project = |binary_path: &Path| {
+        let binary: Vec<u8> = std::fs::read(binary_path).unwrap_or_else(|_| {
+            panic!(
+                "Error: Could not read from file path {}",
+                binary_path.display()
+            )
+        });
+        let (project, logs, is_partial_run) = get_project_from_ghidra(
+            binary_path,
+            &binary[..],
+            pipeline_config.bare_metal_config.cloned(),
+            parsing_config,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Error: {}", err);
+            std::process::exit(101);
+        });
+        (binary, project, logs, is_partial_run)
+    };
+    let (old_binary, old_project, _, _) = load_project(old_binary_path);
+    let (binary, project, mut logs, is_partial_run) =
+        load_project(Path::new(args.binary.as_ref().unwrap()));
+    let (patch_diff, mut output) = analyze_patch_diff(
+        old_project,
+        &old_binary,
+        project,
+        &binary,
+        pipeline_config,
+        patch_diff_config,
+    )
+    .unwrap_or_else(|err| panic!("{}", err));
+    logs.push(LogMessage::new_info(format!(
+        "Patch diff: {} changed functions, {} analyzed functions",
+        patch_diff.changed_subs.len(),
+        patch_diff.analyzed_subs.len()
+    )));
+    logs.append(&mut output.logs);
+    filter_logs(args, &mut logs);
+    output.logs = logs;
+    let output = output.partial(is_partial_run).binary(args.binary.clone());
+    output.print_logs();
+    write_output(&output, &get_output_sinks(args))
+}
 
-    let analysis_results =
-        analysis_results.set_string_abstraction(string_abstraction_results.as_ref());
-
-    // Print debug and then return.
-    // Right now there is only one debug printing function.
-    // When more debug printing modes exist, this behaviour will change!
-    if args.debug {
-        cwe_checker_lib::analysis::pointer_inference::run(
-            &project,
-            &runtime_memory_image,
-            &control_flow_graph,
-            serde_json::from_value(config["Memory"].clone()).unwrap(),
-            true,
-            false,
+/// Analyze all binaries given by the `--batch` parameter and write the aggregated report.
+/// Returns the exit code of the run.
+fn run_batch(
+    args: &CmdlineArgs,
+    batch_path: &Path,
+    pipeline_config: &PipelineConfig,
+    parsing_config: &ParsingConfig,
+) -> i32 {
+    let binaries = batch::collect_binaries(batch_path, pipeline_config.bare_metal_config.is_some())
+        .unwrap_or_else(|err| panic!("Error: {}", err));
+    let sinks: Vec<OutputSink> = if args.out.is_empty() {
+        vec![OutputSink {
+            format: OutputFormat::Json,
+            path: None,
+        }]
+    } else {
+        args.out
+            .iter()
+            .map(|spec| OutputSink::parse(spec, OutputFormat::Json))
+            .collect()
+    };
+    if let Some(sink) = sinks.iter().find(|sink| sink.format != OutputFormat::Json) {
+        panic!(
+            "Error: Output {} is not supported in batch mode. Only JSON output is supported.",
+            sink
         );
-        return;
     }
-
-    // Execute the modules and collect their logs and CWE-warnings.
-    let mut all_cwes = Vec::new();
-    for module in modules {
-        let (mut logs, mut cwes) = (module.run)(&analysis_results, &config[&module.name]);
-        all_logs.append(&mut logs);
-        all_cwes.append(&mut cwes);
+    let results = batch::analyze_all(&binaries, args.jobs, |binary_path| {
+        let output = analyze_binary(binary_path, pipeline_config, parsing_config)?;
+        if !args.quiet {
+            for log in output.logs.iter() {
+                if args.verbose || log.level != LogLevel::Debug {
+                    eprintln!("{}: {}", binary_path.display(), log);
+                }
+            }
+        }
+        Ok(output)
+    });
+    let has_findings = results
+        .iter()
+        .any(|result| matches!(result, Ok(output) if !output.cwes.is_empty()));
+    let mut report = BatchReport::new();
+    for (binary_path, result) in binaries.iter().zip(results) {
+        if let Err(err) = &result {
+            eprintln!("Error while analyzing {}: {}", binary_path.display(), err);
+        }
+        report.add_result(binary_path.display().to_string(), &result);
+    }
+    let report_json = report.to_json_string();
+    let mut has_output_errors = false;
+    for sink in sinks {
+        let write_result = match &sink.path {
+            Some(path) => std::fs::write(path, &report_json).map_err(Error::from),
+            None => {
+                println!("{}", report_json);
+                Ok(())
+            }
+        };
+        if let Err(err) = write_result {
+            eprintln!("Error while writing output {}: {}", sink, err);
+            has_output_errors = true;
+        }
     }
+    get_exit_code(has_findings, has_output_errors)
+}
 
-    // Print the results of the modules.
-    if args.quiet {
-        all_logs = Vec::new(); // Suppress all log messages since the `--quiet` flag is set.
+/// Run the whole analysis of one binary in batch mode.
+fn analyze_binary(
+    binary_path: &Path,
+    pipeline_config: &PipelineConfig,
+    parsing_config: &ParsingConfig,
+) -> Result<AnalysisResultsOutput, Error> {
+    let binary: Vec<u8> = std::fs::read(binary_path).map_err(|err| {
+        anyhow!(
+            "Could not read from file path {}: {}",
+            binary_path.display(),
+            err
+        )
+    })?;
+    let (project, mut logs, is_partial_run) = get_project_from_ghidra(
+        binary_path,
+        &binary[..],
+        pipeline_config.bare_metal_config.cloned(),
+        parsing_config,
+    )?;
+    let mut output = analyze_project(project, &binary, pipeline_config)?.partial(is_partial_run);
+    logs.append(&mut output.logs);
+    output.logs = logs;
+    Ok(output)
+}
+
+/// Print the changes between the CWE warnings of a previous run and the given CWE warnings.
+fn print_results_diff(old_results_path: &str, cwes: &[CweWarning], json: bool) {
+    let old_results = std::fs::read_to_string(old_results_path)
+        .unwrap_or_else(|err| panic!("Error: Could not read {}: {}", old_results_path, err));
+    let old_report = JsonReport::from_json_str(&old_results)
+        .unwrap_or_else(|err| panic!("Error: Parsing of {} failed: {}", old_results_path, err));
+    let diff = ResultsDiff::compute(&old_report.cwes, cwes, &DiffConfig::default());
+    if json {
+        println!("{}", diff.to_json());
     } else {
-        if args.statistics {
-            cwe_checker_lib::utils::log::add_debug_log_statistics(&mut all_logs);
-        }
-        if !args.verbose {
-            all_logs.retain(|log_msg| log_msg.level != LogLevel::Debug);
-        }
+        print!("{}", diff);
+    }
+}
+
+/// Print the backward slice specified by the `--slice` parameter.
+/// The parameter has the form '<address>:<register>', e.g. '0x401000:RDI'.
+fn print_backward_slice(project: &Project, pi_results: &PointerInference, slice_spec: &str) {
+    let (address, register_name) = slice_spec.split_once(':').unwrap_or_else(|| {
+        panic!(
+            "Error: {} is not of the form <address>:<register>.",
+            slice_spec
+        )
+    });
+    let address = u64::from_str_radix(address.trim_start_matches("0x"), 16)
+        .unwrap_or_else(|_| panic!("Error: {} is not a valid address.", address));
+    let register = project
+        .register_set
+        .iter()
+        .find(|register| register.name == register_name)
+        .unwrap_or_else(|| panic!("Error: {} is not a valid register name.", register_name));
+    let start_tid = backward_slicing::get_first_term_at_address(project, address)
+        .unwrap_or_else(|| panic!("Error: No instruction at address {:#x} found.", address));
+    match backward_slicing::slice(project, pi_results, start_tid, register) {
+        Ok(slice) => print!("{}", slice.format(project)),
+        Err(err) => eprintln!("Error: {}", err),
     }
-    print_all_messages(all_logs, all_cwes, args.out.as_deref(), args.json);
 }
 
 /// Only keep the modules specified by the `--partial` parameter in the `modules` list.
@@ -267,12 +715,22 @@ fn filter_modules_for_partial_run(
         .collect();
 }
 
+/// Counts the calls to [`get_project_from_ghidra`] in this process.
+///
+/// The counter is part of the names of temporary files
+/// so that several binaries can be analyzed in parallel by the same process.
+static GHIDRA_RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
 /// Execute the `p_code_extractor` plugin in ghidra and parse its output into the `Project` data structure.
+///
+/// Functions and blocks that cannot be parsed are skipped.
+/// The returned flag is set if this happened, i.e. if the analysis results are only partial.
 fn get_project_from_ghidra(
     file_path: &Path,
     binary: &[u8],
     bare_metal_config_opt: Option<BareMetalConfig>,
-) -> (Project, Vec<LogMessage>) {
+    parsing_config: &ParsingConfig,
+) -> Result<(Project, Vec<LogMessage>, bool), Error> {
     let bare_metal_base_address_opt = bare_metal_config_opt
         .as_ref()
         .map(|config| config.parse_binary_base_address());
@@ -290,21 +748,28 @@ fn get_project_from_ghidra(
         Path::new("/tmp/cwe_checker")
     };
     if !tmp_folder.exists() {
-        std::fs::create_dir(tmp_folder).expect("Unable to create temporary folder");
+        // Another thread may have created the folder in the meantime.
+        if let Err(err) = std::fs::create_dir(tmp_folder) {
+            if !tmp_folder.exists() {
+                return Err(anyhow!("Unable to create temporary folder: {}", err));
+            }
+        }
     }
-    // We add a timestamp suffix to file names
-    // so that if two instances of the cwe_checker are running in parallel on the same file
-    // they do not interfere with each other.
+    // We add a timestamp, the process ID and a counter as suffix to file names
+    // so that if two instances of the cwe_checker (or two threads of one instance)
+    // are running in parallel on the same file they do not interfere with each other.
     let timestamp_suffix = format!(
-        "{:?}",
+        "{:?}_{}_{}",
         std::time::SystemTime::now()
             .duration_since(std::time::SystemTime::UNIX_EPOCH)
             .unwrap()
-            .as_millis()
+            .as_millis(),
+        std::process::id(),
+        GHIDRA_RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
     );
     let filename = file_path
         .file_name()
-        .expect("Invalid file name")
+        .ok_or_else(|| anyhow!("Invalid file name {}", file_path.display()))?
         .to_string_lossy()
         .to_string();
     let ghidra_plugin_path = get_ghidra_plugin_path("p_code_extractor");
@@ -313,10 +778,8 @@ fn get_project_from_ghidra(
     let fifo_path = tmp_folder.join(format!("pcode_{}.pipe", timestamp_suffix));
 
     // Create a new fifo and give read and write rights to the owner
-    if let Err(err) = unistd::mkfifo(&fifo_path, stat::Mode::from_bits(0o600).unwrap()) {
-        eprintln!("Error creating FIFO pipe: {}", err);
-        std::process::exit(101);
-    }
+    unistd::mkfifo(&fifo_path, stat::Mode::from_bits(0o600).unwrap())
+        .map_err(|err| anyhow!("Error creating FIFO pipe: {}", err))?;
 
     let thread_fifo_path = fifo_path.clone();
     let thread_file_path = file_path.to_path_buf();
@@ -324,103 +787,126 @@ fn get_project_from_ghidra(
     // Execute Ghidra in a new thread and return a Join Handle, so that the thread is only joined
     // after the output has been read into the cwe_checker
     let ghidra_subprocess = thread::spawn(move || {
-        let mut ghidra_command = Command::new(&headless_path);
-        ghidra_command
-            .arg(&thread_tmp_folder) // The folder where temporary files should be stored
-            .arg(format!("PcodeExtractor_{}_{}", filename, timestamp_suffix)) // The name of the temporary Ghidra Project.
-            .arg("-import") // Import a file into the Ghidra project
-            .arg(thread_file_path) // File import path
-            .arg("-postScript") // Execute a script after standard analysis by Ghidra finished
-            .arg(ghidra_plugin_path.join("PcodeExtractor.java")) // Path to the PcodeExtractor.java
-            .arg(thread_fifo_path) // The path to the named pipe (fifo)
-            .arg("-scriptPath") // Add a folder containing additional script files to the Ghidra script file search paths
-            .arg(ghidra_plugin_path) // Path to the folder containing the PcodeExtractor.java (so that the other java files can be found.)
-            .arg("-deleteProject") // Delete the temporary project after the script finished
-            .arg("-analysisTimeoutPerFile") // Set a timeout for how long the standard analysis can run before getting aborted
-            .arg("3600"); // Timeout of one hour (=3600 seconds) // TODO: The post-script can detect that the timeout fired and react accordingly.
-        if let Some(bare_metal_config) = bare_metal_config_opt {
-            let mut base_address: &str = &bare_metal_config.flash_base_address;
-            if let Some(stripped_address) = base_address.strip_prefix("0x") {
-                base_address = stripped_address;
-            }
-            ghidra_command
-                .arg("-loader") // Tell Ghidra to use a specific loader
-                .arg("BinaryLoader") // Use the BinaryLoader for bare metal binaries
-                .arg("-loader-baseAddr") // Provide the base address where the binary should be mapped in memory
-                .arg(base_address)
-                .arg("-processor") // Provide the processor type ID, for which the binary was compiled.
-                .arg(bare_metal_config.processor_id);
-        }
-        let output = match ghidra_command.output() // Execute the command and catch its output.
-        {
-            Ok(output) => output,
-            Err(err) => {
-                eprintln!("Error: Ghidra could not be executed:\n{}", err);
-                std::process::exit(101);
-            }
-        };
-
-        match String::from_utf8(output.stdout.clone()) {
-            Ok(standard_out) => {
-                if !standard_out.contains("Pcode was successfully extracted!") {
-                    eprintln!("Execution of Ghidra plugin failed: Process was terminated.");
-                    let error_message: String =
-                        standard_out.lines().rev().collect::<Vec<&str>>()[..2].join("\n");
-                    eprintln!("{}", error_message);
-                    std::process::exit(101);
-                }
-            }
-            Err(_) => {
-                eprintln!("Execution of Ghidra plugin failed: Process was terminated.");
-                std::process::exit(101);
-            }
-        }
-
-        if !output.status.success() {
-            match output.status.code() {
-                Some(code) => {
-                    eprintln!("{}", String::from_utf8(output.stdout).unwrap());
-                    eprintln!("{}", String::from_utf8(output.stderr).unwrap());
-                    eprintln!("Execution of Ghidra plugin failed with exit code {}", code);
-                    std::process::exit(101);
-                }
-                None => {
-                    eprintln!("Execution of Ghidra plugin failed: Process was terminated.");
-                    std::process::exit(101);
-                }
-            }
+        let result = run_ghidra_subprocess(
+            &headless_path,
+            &thread_tmp_folder,
+            format!("PcodeExtractor_{}_{}", filename, timestamp_suffix),
+            &thread_file_path,
+            &thread_fifo_path,
+            &ghidra_plugin_path,
+            bare_metal_config_opt,
+        );
+        if result.is_err() {
+            // Ghidra may have failed before writing to the FIFO.
+            // Opening the FIFO for writing unblocks the reading side, which then reads an empty input.
+            let _ = std::fs::OpenOptions::new()
+                .write(true)
+                .open(&thread_fifo_path);
         }
+        result
     });
 
     // Open the FIFO
-    let file = std::fs::File::open(fifo_path.clone()).expect("Could not open FIFO.");
-
-    let mut project_pcode: cwe_checker_lib::pcode::Project =
-        serde_json::from_reader(std::io::BufReader::new(file)).unwrap();
-    let mut log_messages = project_pcode.normalize();
-    let project: Project = match cwe_checker_lib::utils::get_binary_base_address(binary) {
-        Ok(binary_base_address) => project_pcode.into_ir_project(binary_base_address),
-        Err(_err) => {
-            if let Some(binary_base_address) = bare_metal_base_address_opt {
-                let mut project = project_pcode.into_ir_project(binary_base_address);
-                project.program.term.address_base_offset = 0;
-                project
-            } else {
-                log_messages.push(LogMessage::new_info("Could not determine binary base address. Using base address of Ghidra output as fallback."));
-                let mut project = project_pcode.into_ir_project(0);
-                // For PE files setting the address_base_offset to zero is a hack, which worked for the tested PE files.
-                // But this hack will probably not work in general!
-                project.program.term.address_base_offset = 0;
-                project
-            }
-        }
-    };
-
-    ghidra_subprocess
+    let parse_result = std::fs::File::open(fifo_path.clone())
+        .map_err(|err| anyhow!("Could not open FIFO: {}", err))
+        .and_then(|file| {
+            cwe_checker_lib::pcode::Project::parse_resilient(
+                std::io::BufReader::new(file),
+                parsing_config,
+            )
+            .map_err(|err| anyhow!("Parsing of the Ghidra output failed: {}", err))
+        });
+    let ghidra_result = ghidra_subprocess
         .join()
-        .expect("The Ghidra thread to be joined has panicked!");
+        .map_err(|_| anyhow!("The Ghidra thread to be joined has panicked!"));
+    let _ = std::fs::remove_file(fifo_path);
+    // An error of Ghidra itself is more informative than the resulting parsing error.
+    ghidra_result??;
+    let parsed_project = parse_result?;
+
+    Ok(convert_parsed_project(
+        parsed_project,
+        binary,
+        bare_metal_base_address_opt,
+    ))
+}
 
-    std::fs::remove_file(fifo_path).unwrap();
+/// Run Ghidra in headless mode to write the P-Code of the binary at `file_path` into the FIFO at `fifo_path`.
+fn run_ghidra_subprocess(
+    headless_path: &Path,
+    tmp_folder: &Path,
+    ghidra_project_name: String,
+    file_path: &Path,
+    fifo_path: &Path,
+    ghidra_plugin_path: &Path,
+    bare_metal_config_opt: Option<BareMetalConfig>,
+) -> Result<(), Error> {
+    let mut ghidra_command = Command::new(headless_path);
+    ghidra_command
+        .arg(tmp_folder) // The folder where temporary files should be stored
+        .arg(ghidra_project_name) // The name of the temporary Ghidra Project.
+        .arg("-import") // Import a file into the Ghidra project
+        .arg(file_path) // File import path
+        .arg("-postScript") // Execute a script after standard analysis by Ghidra finished
+        .arg(ghidra_plugin_path.join("PcodeExtractor.java")) // Path to the PcodeExtractor.java
+        .arg(fifo_path) // The path to the named pipe (fifo)
+        .arg("-scriptPath") // Add a folder containing additional script files to the Ghidra script file search paths
+        .arg(ghidra_plugin_path) // Path to the folder containing the PcodeExtractor.java (so that the other java files can be found.)
+        .arg("-deleteProject") // Delete the temporary project after the script finished
+        .arg("-analysisTimeoutPerFile") // Set a timeout for how long the standard analysis can run before getting aborted
+        .arg("3600"); // Timeout of one hour (=3600 seconds) // TODO: The post-script can detect that the timeout fired and react accordingly.
+    if let Some(bare_metal_config) = bare_metal_config_opt {
+        let mut base_address: &str = &bare_metal_config.flash_base_address;
+        if let Some(stripped_address) = base_address.strip_prefix("0x") {
+            base_address = stripped_address;
+        }
+        ghidra_command
+            .arg("-loader") // Tell Ghidra to use a specific loader
+            .arg("BinaryLoader") // Use the BinaryLoader for bare metal binaries
+            .arg("-loader-baseAddr") // Provide the base address where the binary should be mapped in memory
+            .arg(base_address)
+            .arg("-processor") // Provide the processor type ID, for which the binary was compiled.
+            .arg(bare_metal_config.processor_id);
+    }
+    // Execute the command and catch its output.
+    let output = ghidra_command
+        .output()
+        .map_err(|err| anyhow!("Ghidra could not be executed:\n{}", err))?;
+
+    match String::from_utf8(output.stdout.clone()) {
+        Ok(standard_out) => {
+            if !standard_out.contains("Pcode was successfully extracted!") {
+                let error_message: String = standard_out
+                    .lines()
+                    .rev()
+                    .take(2)
+                    .collect::<Vec<&str>>()
+                    .join("\n");
+                return Err(anyhow!(
+                    "Execution of Ghidra plugin failed: Process was terminated.\n{}",
+                    error_message
+                ));
+            }
+        }
+        Err(_) => {
+            return Err(anyhow!(
+                "Execution of Ghidra plugin failed: Process was terminated."
+            ))
+        }
+    }
 
-    (project, log_messages)
+    if !output.status.success() {
+        return match output.status.code() {
+            Some(code) => Err(anyhow!(
+                "{}\n{}\nExecution of Ghidra plugin failed with exit code {}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr),
+                code
+            )),
+            None => Err(anyhow!(
+                "Execution of Ghidra plugin failed: Process was terminated."
+            )),
+        };
+    }
+    Ok(())
 }
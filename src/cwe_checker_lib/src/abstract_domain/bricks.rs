@@ -24,7 +24,7 @@
 
 use std::{collections::BTreeSet, fmt};
 
-use super::{AbstractDomain, DomainInsertion, HasTop};
+use super::{AbstractDomain, CharacterInclusionDomain, CharacterSet, DomainInsertion, HasTop};
 use crate::prelude::*;
 use std::fmt::Debug;
 
@@ -188,6 +188,56 @@ impl BricksDomain {
             _ => panic!("Unexpected Brick Domain type."),
         }
     }
+
+    /// Returns the bricks representing the first `length` characters of the string.
+    ///
+    /// The prefix is exact as long as the bricks only contain sequences of a fixed length.
+    /// Otherwise, the position of the prefix end is unknown
+    /// and a *Top* brick is appended to the known part of the prefix.
+    /// Returns `None` if the string is known to be shorter than `length`.
+    fn get_prefix_bricks(&self, length: usize) -> Option<Vec<BrickDomain>> {
+        let bricks = match self {
+            BricksDomain::Top => return Some(vec![BrickDomain::Top]),
+            BricksDomain::Value(bricks) => bricks,
+        };
+        let mut prefix = Vec::new();
+        let mut remaining_length = length;
+        for brick_domain in bricks.iter() {
+            if remaining_length == 0 {
+                return Some(prefix);
+            }
+            match brick_domain.get_fixed_length() {
+                Some(0) => (),
+                Some(brick_length) if brick_length <= remaining_length => {
+                    prefix.push(brick_domain.clone());
+                    remaining_length -= brick_length;
+                }
+                Some(_) => {
+                    let truncated_sequence = brick_domain
+                        .unwrap_value()
+                        .get_sequence()
+                        .iter()
+                        .map(|sequence| sequence.chars().take(remaining_length).collect())
+                        .collect();
+                    let mut truncated_brick = Brick::new();
+                    truncated_brick.set_sequence(truncated_sequence);
+                    truncated_brick.set_min(1);
+                    truncated_brick.set_max(1);
+                    prefix.push(BrickDomain::Value(truncated_brick));
+                    return Some(prefix);
+                }
+                None => {
+                    prefix.push(BrickDomain::Top);
+                    return Some(prefix);
+                }
+            }
+        }
+        if remaining_length == 0 {
+            Some(prefix)
+        } else {
+            None
+        }
+    }
 }
 
 impl fmt::Display for BricksDomain {
@@ -234,6 +284,49 @@ impl DomainInsertion for BricksDomain {
         }
     }
 
+    /// Keeps the bricks up to the offset and appends the new bricks to them.
+    /// If the string is known to end before the offset, it is not changed by the insertion.
+    fn insert_string_domain_at_offset(&self, offset: usize, string_domain: &Self) -> Self {
+        match self.get_prefix_bricks(offset) {
+            Some(prefix) => BricksDomain::Value(prefix).append_string_domain(string_domain),
+            None => self.clone(),
+        }
+    }
+
+    /// Keeps the bricks up to the minimal offset and appends a *Top* brick to them.
+    fn widen_suffix_from_offset(&self, min_offset: usize) -> Self {
+        if self.is_top() {
+            return BricksDomain::Top;
+        }
+        match self.get_prefix_bricks(min_offset) {
+            Some(prefix) if prefix.last() == Some(&BrickDomain::Top) => BricksDomain::Value(prefix),
+            Some(prefix) => BricksDomain::Value(prefix).append_string_domain(&BricksDomain::Top),
+            None => self.clone(),
+        }
+    }
+
+    /// Sums up the minimal lengths of all bricks.
+    fn get_minimal_length(&self) -> usize {
+        match self {
+            BricksDomain::Top => 0,
+            BricksDomain::Value(bricks) => bricks
+                .iter()
+                .map(|brick_domain| match brick_domain {
+                    BrickDomain::Top => 0,
+                    BrickDomain::Value(brick) => {
+                        let shortest_sequence = brick
+                            .get_sequence()
+                            .iter()
+                            .map(|sequence| sequence.chars().count())
+                            .min()
+                            .unwrap_or(0);
+                        brick.get_min() as usize * shortest_sequence
+                    }
+                })
+                .sum(),
+        }
+    }
+
     /// Create a string domain that approximates float values.
     fn create_float_value_domain() -> Self {
         BricksDomain::from("[float inserted]".to_string())
@@ -327,6 +420,29 @@ impl BrickDomain {
         BrickDomain::Value(Brick::new())
     }
 
+    /// Returns the number of characters of the strings represented by the brick
+    /// if all of them have the same length.
+    fn get_fixed_length(&self) -> Option<usize> {
+        let brick = match self {
+            BrickDomain::Top => return None,
+            BrickDomain::Value(brick) => brick,
+        };
+        if brick.is_empty_string() {
+            return Some(0);
+        }
+        if brick.get_min() != 1 || brick.get_max() != 1 {
+            return None;
+        }
+        let mut lengths = brick
+            .get_sequence()
+            .iter()
+            .map(|sequence| sequence.chars().count());
+        let length = lengths.next()?;
+        lengths
+            .all(|other_length| other_length == length)
+            .then_some(length)
+    }
+
     /// Unwraps a brick value and panics if it's *Top*.
     fn unwrap_value(&self) -> Brick {
         match self {
@@ -343,6 +459,52 @@ impl From<String> for BricksDomain {
     }
 }
 
+impl From<&BricksDomain> for CharacterInclusionDomain {
+    /// Summarize the characters of the strings represented by the bricks.
+    ///
+    /// The characters contained in all strings of a brick that occurs at least once are certainly contained.
+    /// All characters of all strings of the bricks are possibly contained.
+    fn from(bricks: &BricksDomain) -> Self {
+        let bricks = match bricks {
+            BricksDomain::Top => return CharacterInclusionDomain::Top,
+            BricksDomain::Value(bricks) => bricks,
+        };
+        let mut characters = CharacterInclusionDomain::from(String::new());
+        for brick in bricks {
+            let brick_characters = match brick {
+                BrickDomain::Top => CharacterInclusionDomain::Top,
+                BrickDomain::Value(brick) => {
+                    let sequences: Vec<BTreeSet<char>> = brick
+                        .get_sequence()
+                        .iter()
+                        .map(|sequence| sequence.chars().collect())
+                        .collect();
+                    let possible: BTreeSet<char> = sequences.iter().flatten().copied().collect();
+                    let certain: BTreeSet<char> = if brick.get_min() == 0 {
+                        BTreeSet::new()
+                    } else {
+                        possible
+                            .iter()
+                            .filter(|character| {
+                                sequences
+                                    .iter()
+                                    .all(|sequence| sequence.contains(character))
+                            })
+                            .copied()
+                            .collect()
+                    };
+                    CharacterInclusionDomain::Value((
+                        CharacterSet::Value(certain),
+                        CharacterSet::Value(possible),
+                    ))
+                }
+            };
+            characters = characters.append_string_domain(&brick_characters);
+        }
+        characters
+    }
+}
+
 impl AbstractDomain for BrickDomain {
     /// Takes care of merging single bricks by taking the union
     /// of the two brick's string sequences and the minimum and maximum
@@ -483,3 +483,85 @@ fn test_append_string_domain() {
         bricks_one.append_string_domain(&bricks_two)
     );
 }
+
+#[test]
+fn test_insert_string_domain_at_offset() {
+    let command = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["/bin/".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(vec!["ping localhost".to_string()], 1, 1)),
+    ]);
+    let argument = BricksDomain::from("8.8.8.8".to_string());
+
+    // Test Case 1: The offset lies inside of a brick with a fixed length.
+    let expected_bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["/bin/".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(vec!["ping ".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(vec!["8.8.8.8".to_string()], 1, 1)),
+    ]);
+    assert_eq!(
+        expected_bricks,
+        command.insert_string_domain_at_offset(10, &argument)
+    );
+
+    // Test Case 2: The offset lies behind a brick with a variable length.
+    let variable_bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["/bin/".to_string()], 1, 1)),
+        BrickDomain::Value(Brick::mock_brick(vec!["a".to_string()], 0, 3)),
+    ]);
+    let expected_bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["/bin/".to_string()], 1, 1)),
+        BrickDomain::Top,
+        BrickDomain::Value(Brick::mock_brick(vec!["8.8.8.8".to_string()], 1, 1)),
+    ]);
+    assert_eq!(
+        expected_bricks,
+        variable_bricks.insert_string_domain_at_offset(7, &argument)
+    );
+
+    // Test Case 3: The string ends before the offset.
+    assert_eq!(
+        command,
+        command.insert_string_domain_at_offset(30, &argument)
+    );
+}
+
+#[test]
+fn test_widen_suffix_from_offset() {
+    let command = BricksDomain::from("/bin/ping localhost".to_string());
+    let expected_bricks = BricksDomain::Value(vec![
+        BrickDomain::Value(Brick::mock_brick(vec!["/bin/ping ".to_string()], 1, 1)),
+        BrickDomain::Top,
+    ]);
+    assert_eq!(expected_bricks, command.widen_suffix_from_offset(10));
+    assert_eq!(
+        BricksDomain::Value(vec![BrickDomain::Top]),
+        command.widen_suffix_from_offset(0)
+    );
+    assert_eq!(
+        BricksDomain::Top,
+        BricksDomain::Top.widen_suffix_from_offset(4)
+    );
+}
+
+#[test]
+fn test_character_inclusion_of_bricks() {
+    let setup = Setup::new();
+    let bricks = BricksDomain::Value(vec![setup.brick5.clone(), setup.brick4.clone()]);
+    let characters = CharacterInclusionDomain::from(&bricks);
+    assert_eq!(
+        characters,
+        CharacterInclusionDomain::Value((
+            CharacterSet::Value(BTreeSet::from(['a'])),
+            CharacterSet::Value(BTreeSet::from(['a', 'b'])),
+        ))
+    );
+    assert_eq!(characters.may_contain_character(|c| c == 'b'), Some(true));
+    assert_eq!(
+        characters.may_contain_character(char::is_control),
+        Some(false)
+    );
+
+    let bricks = BricksDomain::Value(vec![setup.brick5, BrickDomain::Top]);
+    let characters = CharacterInclusionDomain::from(&bricks);
+    assert_eq!(characters.may_contain_character(char::is_control), None);
+}
@@ -49,6 +49,18 @@ impl CharacterInclusionDomain {
             _ => panic!("Unexpected Character Inclusion type."),
         }
     }
+
+    /// Returns `true` if the set of possibly contained characters is known
+    /// and contains a character satisfying the given predicate.
+    /// Returns `None` if the possibly contained characters are unknown.
+    pub fn may_contain_character(&self, predicate: impl Fn(char) -> bool) -> Option<bool> {
+        match self {
+            CharacterInclusionDomain::Value((_, CharacterSet::Value(possible))) => {
+                Some(possible.iter().any(|character| predicate(*character)))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl DomainInsertion for CharacterInclusionDomain {
@@ -75,6 +87,43 @@ impl DomainInsertion for CharacterInclusionDomain {
         }
     }
 
+    /// Insert a string domain at a positive offset.
+    /// Since the domain does not track the positions of characters,
+    /// the certainly contained characters may be overwritten and become possibly contained characters.
+    fn insert_string_domain_at_offset(&self, offset: usize, string_domain: &Self) -> Self {
+        if offset == 0 {
+            return string_domain.clone();
+        }
+        match self {
+            CharacterInclusionDomain::Value((_, self_possible)) => CharacterInclusionDomain::Value(
+                (CharacterSet::Value(BTreeSet::new()), self_possible.clone()),
+            )
+            .append_string_domain(string_domain),
+            CharacterInclusionDomain::Top => {
+                CharacterInclusionDomain::Top.append_string_domain(string_domain)
+            }
+        }
+    }
+
+    /// Widen the suffix starting at the minimal offset.
+    /// The certainly contained characters are assumed to belong to the known prefix of the string
+    /// and are kept, while the possibly contained characters are set to *Top*.
+    /// If the whole string may be overwritten, *Top* is returned.
+    fn widen_suffix_from_offset(&self, min_offset: usize) -> Self {
+        if min_offset == 0 {
+            return CharacterInclusionDomain::Top;
+        }
+        self.append_string_domain(&CharacterInclusionDomain::Top)
+    }
+
+    /// Each certainly contained character occurs at least once in the string.
+    fn get_minimal_length(&self) -> usize {
+        match self {
+            CharacterInclusionDomain::Value((CharacterSet::Value(certain), _)) => certain.len(),
+            _ => 0,
+        }
+    }
+
     /// Create a string domain that approximates float values.
     fn create_float_value_domain() -> Self {
         let float_character_set: BTreeSet<char> = vec![
@@ -288,4 +337,35 @@ pub mod tests {
             CharacterInclusionDomain::Top
         );
     }
+
+    #[test]
+    fn insert_and_widen_suffix() {
+        let command = CharacterInclusionDomain::ci("/bin/ping x");
+        let argument = CharacterInclusionDomain::ci("8.");
+
+        let expected_possible = CharacterSet::Value("/bin/pg x8.".chars().collect());
+        assert_eq!(
+            command.insert_string_domain_at_offset(10, &argument),
+            CharacterInclusionDomain::Value((
+                CharacterSet::Value("8.".chars().collect()),
+                expected_possible
+            ))
+        );
+        assert_eq!(
+            command.insert_string_domain_at_offset(0, &argument),
+            argument
+        );
+
+        assert_eq!(
+            command.widen_suffix_from_offset(10),
+            CharacterInclusionDomain::Value((
+                CharacterSet::Value("/bin/pg x".chars().collect()),
+                CharacterSet::Top
+            ))
+        );
+        assert_eq!(
+            command.widen_suffix_from_offset(0),
+            CharacterInclusionDomain::Top
+        );
+    }
 }
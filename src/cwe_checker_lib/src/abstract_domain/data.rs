@@ -1,9 +1,10 @@
 use super::{
-    AbstractDomain, AbstractIdentifier, HasTop, Interval, RegisterDomain, SizedDomain,
-    SpecializeByConditional, TryToBitvec, TryToInterval,
+    AbstractDomain, AbstractIdentifier, HasTop, Interval, IntervalDomain, KnownBits,
+    RegisterDomain, SizedDomain, SpecializeByConditional, TryToBitvec, TryToInterval,
 };
 use crate::intermediate_representation::*;
 use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 use std::iter::FromIterator;
@@ -65,6 +66,11 @@ impl<T: RegisterDomain> DataDomain<T> {
 
     /// For pointer values replace an abstract identifier with another one and add the offset_adjustment to the pointer offset.
     /// This is needed to adjust stack pointer on call and return instructions.
+    ///
+    /// If `old_id` was collapsed into its summary ID (see [`DataDomain::summarize_targets`]),
+    /// the pointer may also point to the object now identified by `new_id`.
+    /// Thus `new_id` is added as a target with the offset of the summary ID,
+    /// while the summary ID is kept for the other collapsed targets.
     pub fn replace_abstract_id(
         &mut self,
         old_id: &AbstractIdentifier,
@@ -75,9 +81,75 @@ impl<T: RegisterDomain> DataDomain<T> {
             let new_offset = old_offset.bin_op(BinOpType::IntAdd, offset_adjustment);
             self.relative_values.remove(old_id);
             self.relative_values.insert(new_id.clone(), new_offset);
+        } else if let Some(summary_offset) = old_id
+            .get_summary_id()
+            .filter(|summary_id| summary_id != old_id)
+            .and_then(|summary_id| self.relative_values.get(&summary_id))
+        {
+            let new_offset = summary_offset.bin_op(BinOpType::IntAdd, offset_adjustment);
+            let new_offset = match self.relative_values.get(new_id) {
+                Some(offset) => offset.merge(&new_offset),
+                None => new_offset,
+            };
+            self.relative_values.insert(new_id.clone(), new_offset);
         }
     }
 
+    /// Limit the number of relative targets to `max_targets`.
+    ///
+    /// If there are more relative targets,
+    /// all targets sharing the same [summary ID](AbstractIdentifier::get_summary_id)
+    /// are collapsed into the summary ID with their offsets merged.
+    /// Targets are also always collapsed into a summary ID that is already contained in `self`.
+    /// If the number of targets still exceeds the limit,
+    /// all relative targets are removed and the `Top` flag is set as a last resort.
+    /// IDs contained in `excluded_ids` are never collapsed.
+    ///
+    /// The result only depends on the set of targets and not on the order in which they were added.
+    /// As long as the number of allocation sites does not exceed the limit,
+    /// summarizing after each merge also yields the same result regardless of the order of the merges.
+    ///
+    /// Returns a map from the used summary IDs to the IDs collapsed into them,
+    /// so that the caller can create the objects corresponding to the summary IDs.
+    pub fn summarize_targets(
+        &mut self,
+        max_targets: usize,
+        excluded_ids: &BTreeSet<AbstractIdentifier>,
+    ) -> BTreeMap<AbstractIdentifier, BTreeSet<AbstractIdentifier>> {
+        let limit_exceeded = self.relative_values.len() > max_targets;
+        let mut groups: BTreeMap<AbstractIdentifier, BTreeSet<AbstractIdentifier>> =
+            BTreeMap::new();
+        for id in self.relative_values.keys() {
+            if excluded_ids.contains(id) {
+                continue;
+            }
+            if let Some(summary_id) = id.get_summary_id().filter(|summary_id| summary_id != id) {
+                groups.entry(summary_id).or_default().insert(id.clone());
+            }
+        }
+        groups.retain(|summary_id, collapsed_ids| {
+            self.relative_values.contains_key(summary_id)
+                || (limit_exceeded && collapsed_ids.len() > 1)
+        });
+        for (summary_id, collapsed_ids) in groups.iter() {
+            let mut summary_offset = self.relative_values.remove(summary_id);
+            for id in collapsed_ids {
+                let offset = self.relative_values.remove(id).unwrap();
+                summary_offset = Some(match summary_offset {
+                    Some(summary_offset) => summary_offset.merge(&offset),
+                    None => offset,
+                });
+            }
+            self.relative_values
+                .insert(summary_id.clone(), summary_offset.unwrap());
+        }
+        if self.relative_values.len() > max_targets {
+            self.relative_values = BTreeMap::new();
+            self.contains_top_values = true;
+        }
+        groups
+    }
+
     /// Return an iterator over all referenced abstract IDs.
     pub fn referenced_ids(&self) -> impl Iterator<Item = &AbstractIdentifier> {
         self.relative_values.keys()
@@ -187,6 +259,90 @@ impl<T: RegisterDomain> DataDomain<T> {
     }
 }
 
+impl DataDomain<IntervalDomain> {
+    /// Use the given thresholds as widening hints for the absolute value of `self`
+    /// (see [`IntervalDomain::add_widening_thresholds`]).
+    ///
+    /// Pointer offsets get no widening hints,
+    /// since the thresholds are compared to absolute values in the program.
+    pub fn add_widening_thresholds(&mut self, thresholds: &[Bitvector]) {
+        if let Some(value) = self.absolute_value.as_mut() {
+            value.add_widening_thresholds(thresholds);
+        }
+    }
+
+    /// Get the bits known to be set resp. unset in all values represented by `self`.
+    ///
+    /// Bits are only known if `self` represents only absolute values,
+    /// since nothing is known about the bits of pointers.
+    pub fn get_known_bits(&self) -> KnownBits {
+        match &self.absolute_value {
+            Some(value) if self.relative_values.is_empty() && !self.contains_top_values => {
+                value.get_known_bits()
+            }
+            _ => KnownBits::new_unknown(self.size),
+        }
+    }
+
+    /// Check whether the absolute values of `self` may point to read-only global memory
+    /// when interpreted as pointers to a memory access of the given size.
+    ///
+    /// Only the first and last byte of the accessed interval are checked
+    /// if the interval intersects more than one memory segment.
+    /// The access is only classified as [`GlobalWriteability::ReadOnly`]
+    /// if `self` represents no other values than pointers into read-only memory.
+    pub fn get_global_writeability(
+        &self,
+        access_size: ByteSize,
+        global_memory: &RuntimeMemoryImage,
+    ) -> GlobalWriteability {
+        let (start, end) = match self
+            .absolute_value
+            .as_ref()
+            .map(|value| value.try_to_offset_interval())
+        {
+            Some(Ok((start, end))) if start >= 0 => (start as u64, end as u64),
+            _ => return GlobalWriteability::Writeable,
+        };
+        let last_byte = end.saturating_add(u64::from(access_size).saturating_sub(1));
+        match global_memory.is_interval_writeable(start, last_byte) {
+            Ok(true) => GlobalWriteability::Writeable,
+            Ok(false) => {
+                if self.relative_values.is_empty() && !self.contains_top_values {
+                    GlobalWriteability::ReadOnly
+                } else {
+                    GlobalWriteability::PossiblyReadOnly
+                }
+            }
+            Err(_) => {
+                let is_read_only = |address: u64| {
+                    matches!(
+                        global_memory.is_address_writeable(&Bitvector::from_u64(address)),
+                        Ok(false)
+                    )
+                };
+                if is_read_only(start) || is_read_only(last_byte) {
+                    GlobalWriteability::PossiblyReadOnly
+                } else {
+                    GlobalWriteability::Writeable
+                }
+            }
+        }
+    }
+}
+
+/// The writeability of the global memory targeted by a memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlobalWriteability {
+    /// The access does not target read-only global memory
+    /// or its target is unknown.
+    Writeable,
+    /// Some possible targets of the access lie in read-only global memory.
+    PossiblyReadOnly,
+    /// All possible targets of the access lie in read-only global memory.
+    ReadOnly,
+}
+
 impl<T: RegisterDomain + Display> DataDomain<T> {
     /// Get a more compact json-representation of the data domain.
     /// Intended for pretty printing, not useable for serialization/deserialization.
@@ -295,4 +451,131 @@ mod tests {
         data.remove_ids(&ids_to_remove);
         assert_eq!(data, bv(42).into());
     }
+
+    fn new_allocation_id(name: &str, address: &str) -> AbstractIdentifier {
+        let mut time = Tid::new(name);
+        time.address = address.to_string();
+        AbstractIdentifier::new(
+            time,
+            AbstractLocation::Register(Variable::mock("RAX", ByteSize::new(8))),
+        )
+    }
+
+    #[test]
+    fn summarize_targets() {
+        let excluded_ids = BTreeSet::new();
+        let first_id = new_allocation_id("malloc_call", "0x100");
+        let second_id = new_allocation_id("malloc_call_dup", "0x100");
+        let other_id = new_allocation_id("other_call", "0x200");
+        let summary_id = first_id.get_summary_id().unwrap();
+        assert_eq!(second_id.get_summary_id().unwrap(), summary_id);
+        assert!(new_id("RAX").get_summary_id().is_none());
+
+        let mut data = DataDomain::mock_from_target_map(BTreeMap::from([
+            (first_id.clone(), bv(0)),
+            (second_id.clone(), bv(8)),
+            (other_id.clone(), bv(4)),
+        ]));
+        // Nothing is collapsed below the limit.
+        assert!(data.summarize_targets(3, &excluded_ids).is_empty());
+        assert_eq!(data.relative_values.len(), 3);
+        // Exceeding the limit collapses targets with the same allocation site.
+        let summaries = data.summarize_targets(2, &excluded_ids);
+        assert_eq!(
+            summaries,
+            BTreeMap::from([(
+                summary_id.clone(),
+                BTreeSet::from([first_id.clone(), second_id.clone()])
+            )])
+        );
+        assert_eq!(data.relative_values.len(), 2);
+        assert_eq!(data.relative_values[&summary_id], bv(0).merge(&bv(8)));
+        assert!(!data.contains_top());
+        // Targets are always collapsed into an already existing summary target.
+        data.insert_relative_value(first_id.clone(), bv(16));
+        data.summarize_targets(10, &excluded_ids);
+        assert_eq!(data.relative_values.len(), 2);
+        // Excluded IDs are never collapsed and the last resort is to remove all targets.
+        let mut data = DataDomain::mock_from_target_map(BTreeMap::from([
+            (first_id.clone(), bv(0)),
+            (second_id.clone(), bv(8)),
+            (other_id, bv(4)),
+        ]));
+        data.summarize_targets(2, &BTreeSet::from([first_id.clone()]));
+        assert!(data.relative_values.is_empty());
+        assert!(data.contains_top());
+
+        // Replacing a collapsed ID keeps the summary ID.
+        let mut data = DataDomain::from_target(summary_id.clone(), bv(8));
+        let new_target = new_id("replaced");
+        data.replace_abstract_id(&first_id, &new_target, &bv(2));
+        assert_eq!(data.relative_values[&new_target], bv(10));
+        assert_eq!(data.relative_values[&summary_id], bv(8));
+    }
+
+    #[test]
+    fn summarize_targets_is_deterministic() {
+        let ids: Vec<AbstractIdentifier> = (0..6)
+            .map(|i| new_allocation_id(&format!("call_{}", i), &format!("0x{}", i % 3)))
+            .collect();
+        let values: Vec<DataDomain<BitvectorDomain>> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| DataDomain::from_target(id.clone(), bv(i as i64)))
+            .collect();
+        let merge_all = |order: &[usize]| {
+            order.iter().fold(None, |merged: Option<DataDomain<_>>, i| {
+                let mut merged = match merged {
+                    Some(merged) => merged.merge(&values[*i]),
+                    None => values[*i].clone(),
+                };
+                merged.summarize_targets(3, &BTreeSet::new());
+                Some(merged)
+            })
+        };
+        let merged = merge_all(&[0, 1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(merge_all(&[5, 4, 3, 2, 1, 0]).unwrap(), merged);
+        assert_eq!(merge_all(&[3, 0, 5, 1, 4, 2]).unwrap(), merged);
+        assert_eq!(merged.relative_values.len(), 3);
+        assert!(merged
+            .relative_values
+            .keys()
+            .all(|id| id.get_summary_id().as_ref() == Some(id)));
+    }
+
+    #[test]
+    fn global_writeability() {
+        let global_memory = RuntimeMemoryImage::mock();
+        let pointer = |start, end| DataDomain::from(IntervalDomain::mock(start, end));
+        let size = ByteSize::new(4);
+        // The segment at 0x1000 is read-only, the segment at 0x2000 is writeable.
+        assert_eq!(
+            pointer(0x1000, 0x1000).get_global_writeability(ByteSize::new(1), &global_memory),
+            GlobalWriteability::ReadOnly
+        );
+        assert_eq!(
+            pointer(0x2000, 0x2004).get_global_writeability(size, &global_memory),
+            GlobalWriteability::Writeable
+        );
+        assert_eq!(
+            pointer(0x1000, 0x2000).get_global_writeability(size, &global_memory),
+            GlobalWriteability::PossiblyReadOnly
+        );
+        let mut data = pointer(0x1000, 0x1000);
+        data.set_contains_top_flag();
+        assert_eq!(
+            data.get_global_writeability(ByteSize::new(1), &global_memory),
+            GlobalWriteability::PossiblyReadOnly
+        );
+        // Values outside of global memory are not classified as read-only.
+        assert_eq!(
+            pointer(-4, 0x10).get_global_writeability(size, &global_memory),
+            GlobalWriteability::Writeable
+        );
+        assert_eq!(
+            DataDomain::<IntervalDomain>::new_top(ByteSize::new(8))
+                .get_global_writeability(size, &global_memory),
+            GlobalWriteability::Writeable
+        );
+    }
 }
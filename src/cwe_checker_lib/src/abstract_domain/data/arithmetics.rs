@@ -211,15 +211,24 @@ impl<T: RegisterDomain> RegisterDomain for DataDomain<T> {
     }
 
     /// Cast a bitvector using the given cast type
+    ///
+    /// If the sign extension of a value with unknown parts is computed,
+    /// the unknown parts are approximated by the range of signed integers of the input size.
+    /// This way the result remembers that it may be negative
+    /// although nothing is known about the input value.
     fn cast(&self, kind: CastOpType, width: ByteSize) -> Self {
+        let contains_top_values = self.contains_top_values || !self.relative_values.is_empty();
+        let absolute_value = match (&self.absolute_value, kind) {
+            (None, CastOpType::IntSExt) if contains_top_values => {
+                Some(T::new_top(self.size).cast(kind, width)).filter(|value| !value.is_top())
+            }
+            (absolute_value, _) => absolute_value.as_ref().map(|val| val.cast(kind, width)),
+        };
         DataDomain {
             size: width,
             relative_values: BTreeMap::new(),
-            absolute_value: self
-                .absolute_value
-                .as_ref()
-                .map(|val| val.cast(kind, width)),
-            contains_top_values: self.contains_top_values || !self.relative_values.is_empty(),
+            absolute_value,
+            contains_top_values,
         }
     }
 }
@@ -327,6 +336,20 @@ mod tests {
         assert_eq!(one.bin_op(Piece, &two), concat);
     }
 
+    #[test]
+    fn sign_extension_of_unknown_values() {
+        let unknown: DataDomain<IntervalDomain> = DataDomain::new_top(ByteSize::new(4));
+        let extended = unknown.cast(CastOpType::IntSExt, ByteSize::new(8));
+        assert!(extended.contains_top());
+        assert_eq!(
+            extended.get_absolute_value(),
+            Some(&IntervalDomain::mock(i32::MIN as i64, i32::MAX as i64))
+        );
+        // Zero extensions do not remember anything about unknown values.
+        let extended = unknown.cast(CastOpType::IntZExt, ByteSize::new(8));
+        assert!(extended.is_top());
+    }
+
     #[test]
     fn float_nan_bytesize() {
         let top_value: DataDomain<BitvectorDomain> = DataDomain::new_top(ByteSize::new(8));
@@ -22,53 +22,57 @@ fn intersect_relative_values<T: SpecializeByConditional + RegisterDomain>(
         .collect()
 }
 
-impl<T: SpecializeByConditional + RegisterDomain> SpecializeByConditional for DataDomain<T> {
-    fn add_signed_less_equal_bound(mut self, bound: &Bitvector) -> Result<Self, Error> {
-        self.absolute_value = self
-            .absolute_value
-            .map(|value| value.add_signed_less_equal_bound(bound).ok())
-            .flatten();
-        if self.is_empty() {
-            Err(anyhow!("Empty value"))
-        } else {
-            Ok(self)
+impl<T: SpecializeByConditional + RegisterDomain> DataDomain<T> {
+    /// Return the absolute value contained in `self`.
+    /// If `self` contains no absolute value but `Top` values,
+    /// then the `Top` values may be absolute values, so a `Top` absolute value is returned.
+    fn get_absolute_value_or_unknown(&self) -> Option<T> {
+        match &self.absolute_value {
+            Some(value) => Some(value.clone()),
+            None if self.contains_top_values => Some(T::new_top(self.size)),
+            None => None,
         }
     }
 
-    fn add_unsigned_less_equal_bound(mut self, bound: &Bitvector) -> Result<Self, Error> {
+    /// Restrict the absolute value of `self` with the given specialization function.
+    ///
+    /// If `self` contains `Top` values but no absolute value,
+    /// the specialization is applied to a `Top` absolute value.
+    /// This way bounds can be added to completely unknown values,
+    /// e.g. to the return value of an extern function that was compared against a constant.
+    /// The resulting value still contains `Top` values, since the unknown values may also be pointers.
+    fn specialize_absolute_value(
+        mut self,
+        specialize: impl FnOnce(T) -> Result<T, Error>,
+    ) -> Result<Self, Error> {
+        let had_absolute_value = self.absolute_value.is_some();
         self.absolute_value = self
-            .absolute_value
-            .map(|value| value.add_unsigned_less_equal_bound(bound).ok())
-            .flatten();
+            .get_absolute_value_or_unknown()
+            .and_then(|value| specialize(value).ok())
+            .filter(|value| had_absolute_value || !value.is_top());
         if self.is_empty() {
             Err(anyhow!("Empty value"))
         } else {
             Ok(self)
         }
     }
+}
 
-    fn add_signed_greater_equal_bound(mut self, bound: &Bitvector) -> Result<Self, Error> {
-        self.absolute_value = self
-            .absolute_value
-            .map(|value| value.add_signed_greater_equal_bound(bound).ok())
-            .flatten();
-        if self.is_empty() {
-            Err(anyhow!("Empty value"))
-        } else {
-            Ok(self)
-        }
+impl<T: SpecializeByConditional + RegisterDomain> SpecializeByConditional for DataDomain<T> {
+    fn add_signed_less_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        self.specialize_absolute_value(|value| value.add_signed_less_equal_bound(bound))
     }
 
-    fn add_unsigned_greater_equal_bound(mut self, bound: &Bitvector) -> Result<Self, Error> {
-        self.absolute_value = self
-            .absolute_value
-            .map(|value| value.add_unsigned_greater_equal_bound(bound).ok())
-            .flatten();
-        if self.is_empty() {
-            Err(anyhow!("Empty value"))
-        } else {
-            Ok(self)
-        }
+    fn add_unsigned_less_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        self.specialize_absolute_value(|value| value.add_unsigned_less_equal_bound(bound))
+    }
+
+    fn add_signed_greater_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        self.specialize_absolute_value(|value| value.add_signed_greater_equal_bound(bound))
+    }
+
+    fn add_unsigned_greater_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
+        self.specialize_absolute_value(|value| value.add_unsigned_greater_equal_bound(bound))
     }
 
     fn add_not_equal_bound(mut self, bound: &Bitvector) -> Result<Self, Error> {
@@ -92,13 +96,17 @@ impl<T: SpecializeByConditional + RegisterDomain> SpecializeByConditional for Da
             (true, true) | (false, false) => {
                 let relative_values =
                     intersect_relative_values(&self.relative_values, &other.relative_values);
-                let absolute_value = if let (Some(value), Some(other_value)) =
-                    (&self.absolute_value, &other.absolute_value)
-                {
-                    value.clone().intersect(other_value).ok()
-                } else {
-                    None
-                };
+                let absolute_value =
+                    if self.absolute_value.is_none() && other.absolute_value.is_none() {
+                        None
+                    } else if let (Some(value), Some(other_value)) = (
+                        self.get_absolute_value_or_unknown(),
+                        other.get_absolute_value_or_unknown(),
+                    ) {
+                        value.intersect(&other_value).ok()
+                    } else {
+                        None
+                    };
                 DataDomain {
                     size: self.bytesize(),
                     relative_values,
@@ -161,4 +169,22 @@ mod tests {
         let data_absolute_val = IntervalDomain::mock(100, 100).into();
         assert!(data_right.intersect(&data_absolute_val).is_err());
     }
+
+    #[test]
+    fn bounds_on_unknown_values() {
+        let top = DataDomain::<IntervalDomain>::new_top(ByteSize::new(8));
+        let bounded = top
+            .clone()
+            .add_unsigned_less_equal_bound(&Bitvector::from_u64(1023))
+            .unwrap();
+        assert!(bounded.contains_top());
+        assert_eq!(bounded.absolute_value, Some(IntervalDomain::mock(0, 1023)));
+        assert_eq!(top.clone().intersect(&bounded).unwrap(), bounded);
+        // Bounds that do not restrict the value keep it unknown.
+        let unbounded = top
+            .clone()
+            .add_signed_less_equal_bound(&Bitvector::signed_max_value(ByteSize::new(8).into()))
+            .unwrap();
+        assert_eq!(unbounded, top);
+    }
 }
@@ -70,6 +70,21 @@ impl AbstractIdentifier {
     pub fn get_location(&self) -> &AbstractLocation {
         &self.location
     }
+
+    /// Get the ID summarizing all IDs with the same location
+    /// whose time components share the address of the time component of `self`.
+    ///
+    /// Terms generated for the same instruction (e.g. copies of a block made during normalization)
+    /// share their address, so all IDs created at the same site get the same summary ID.
+    /// Returns `None` if the address of the time component is unknown.
+    pub fn get_summary_id(&self) -> Option<AbstractIdentifier> {
+        if self.time.address == "UNKNOWN" {
+            return None;
+        }
+        let mut time = Tid::new(format!("summary_{}", self.time.address));
+        time.address = self.time.address.clone();
+        Some(AbstractIdentifier::new(time, self.location.clone()))
+    }
 }
 
 impl std::fmt::Display for AbstractIdentifier {
@@ -10,6 +10,10 @@ mod simple_interval;
 pub use simple_interval::*;
 
 mod bin_ops;
+mod exclusions;
+pub use exclusions::MAX_EXCLUDED_VALUES;
+mod known_bits;
+pub use known_bits::KnownBits;
 
 /// An abstract domain representing values in an interval range with strides and widening hints.
 ///
@@ -23,6 +27,18 @@ mod bin_ops;
 /// See the [`IntervalDomain::signed_merge_and_widen`] method for details on the widening strategy.
 /// Note that the widening hints may not respect the stride,
 /// i.e. they may be contained in different residue classes than the interval bounds.
+///
+/// Furthermore, the domain can exclude up to [`MAX_EXCLUDED_VALUES`] values inside the interval bounds
+/// from the represented value set, e.g. after specialization by a `x != 0` check.
+/// The exclusions are preserved by additions and subtractions of constants
+/// and by operations that map distinct values to distinct values like sign extension.
+/// All other operations drop them.
+/// Use [`IntervalDomain::contains`] to check whether a value may be contained in the value set.
+///
+/// Finally, the domain tracks bits known to be set or unset in all represented values (see [`KnownBits`]).
+/// They are maintained through bitwise operations and merges
+/// and allow to check for flags in values built by OR-ing constants.
+/// Use [`IntervalDomain::get_known_bits`] to query them.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct IntervalDomain {
     /// The underlying interval.
@@ -34,6 +50,12 @@ pub struct IntervalDomain {
     /// A delay counter to prevent unnecessary widenings.
     /// See the [`IntervalDomain::signed_merge_and_widen`] method for its usage in the widening strategy.
     widening_delay: u64,
+    /// Values strictly inside the interval bounds that are not contained in the represented value set.
+    /// The values are sorted and there are at most [`MAX_EXCLUDED_VALUES`] of them.
+    excluded_values: Vec<Bitvector>,
+    /// Bits known to be set or unset in all represented values.
+    /// `None` if no bits are known beyond those implied by an exact interval.
+    known_bits: Option<KnownBits>,
 }
 
 impl From<Interval> for IntervalDomain {
@@ -44,6 +66,8 @@ impl From<Interval> for IntervalDomain {
             widening_lower_bound: None,
             widening_upper_bound: None,
             widening_delay: 0,
+            excluded_values: Vec::new(),
+            known_bits: None,
         }
     }
 }
@@ -59,6 +83,8 @@ impl IntervalDomain {
             widening_upper_bound: None,
             widening_lower_bound: None,
             widening_delay: 0,
+            excluded_values: Vec::new(),
+            known_bits: None,
         }
     }
 
@@ -66,6 +92,8 @@ impl IntervalDomain {
     /// This function ignores differences in the widening hints of the two intervals.
     pub fn equal_as_value_sets(&self, other: &IntervalDomain) -> bool {
         self.interval == other.interval
+            && self.excluded_values == other.excluded_values
+            && self.known_bits == other.known_bits
     }
 
     /// If `bound` is more exact/restrictive than the current lower bound of `self`,
@@ -110,6 +138,31 @@ impl IntervalDomain {
         }
     }
 
+    /// Use the given thresholds as widening hints for the interval.
+    ///
+    /// The upper widening bound is set to the smallest threshold above the interval
+    /// unless the existing upper widening bound is closer to the interval.
+    /// Thus the next widening of the upper bound stops at the nearest threshold
+    /// instead of widening to the `Top` value.
+    /// Thresholds with a different bytesize than the interval are ignored.
+    ///
+    /// No lower widening bound is derived from the thresholds,
+    /// since an existing lower widening bound causes the lower bound to be widened as well
+    /// (see [`IntervalDomain::signed_merge_and_widen`]).
+    /// This would lose the exact lower bound of loop counters counting upwards from a constant.
+    pub fn add_widening_thresholds(&mut self, thresholds: &[Bitvector]) {
+        if self.is_top() {
+            return;
+        }
+        let bytesize = self.bytesize();
+        for threshold in thresholds
+            .iter()
+            .filter(|threshold| threshold.bytesize() == bytesize)
+        {
+            self.update_widening_upper_bound(&Some(threshold.clone()));
+        }
+    }
+
     /// Merge as signed intervals without performing widenings.
     pub fn signed_merge(&self, other: &IntervalDomain) -> IntervalDomain {
         let mut merged_domain: IntervalDomain = self.interval.signed_merge(&other.interval).into();
@@ -118,6 +171,8 @@ impl IntervalDomain {
         merged_domain.update_widening_upper_bound(&self.widening_upper_bound);
         merged_domain.update_widening_upper_bound(&other.widening_upper_bound);
         merged_domain.widening_delay = std::cmp::max(self.widening_delay, other.widening_delay);
+        merged_domain.set_merged_excluded_values(self, other);
+        merged_domain.set_merged_known_bits(self, other);
 
         merged_domain
     }
@@ -197,12 +252,16 @@ impl IntervalDomain {
             merged_domain
         } else {
             // No widening bounds could be used for widening, so we have to widen to the `Top` value.
-            IntervalDomain::new_top(merged_domain.bytesize())
+            // The known bits of the merged domain remain valid.
+            let mut widened_domain = IntervalDomain::new_top(merged_domain.bytesize());
+            widened_domain.known_bits = merged_domain.known_bits;
+            widened_domain
         }
     }
 
     /// Zero-extend the values in the interval to the given width.
     pub fn zero_extend(self, width: ByteSize) -> IntervalDomain {
+        let known_bits = self.get_zero_extended_known_bits(width);
         let lower_bound = match self.widening_lower_bound {
             Some(bound)
                 if (bound.sign_bit().to_bool() == self.interval.start.sign_bit().to_bool())
@@ -224,12 +283,23 @@ impl IntervalDomain {
             _ => None,
         };
         let new_interval = self.interval.zero_extend(width);
-        IntervalDomain {
+        // Zero extension maps distinct values to distinct values, so exclusions remain valid.
+        let excluded_values = self
+            .excluded_values
+            .into_iter()
+            .map(|value| value.into_zero_extend(width).unwrap())
+            .collect();
+        let mut extended_domain = IntervalDomain {
             interval: new_interval,
             widening_lower_bound: lower_bound,
             widening_upper_bound: upper_bound,
             widening_delay: self.widening_delay,
-        }
+            excluded_values,
+            known_bits: None,
+        };
+        extended_domain.retain_inner_excluded_values();
+        extended_domain.set_known_bits(known_bits);
+        extended_domain
     }
 
     /// Sign-extend the values in the interval to the given width.
@@ -248,6 +318,12 @@ impl IntervalDomain {
                 .widening_upper_bound
                 .map(|bitvec| bitvec.into_sign_extend(width).unwrap()),
             widening_delay: self.widening_delay,
+            excluded_values: self
+                .excluded_values
+                .into_iter()
+                .map(|bitvec| bitvec.into_sign_extend(width).unwrap())
+                .collect(),
+            known_bits: None,
         }
     }
 
@@ -293,6 +369,8 @@ impl IntervalDomain {
             widening_lower_bound: lower_bound,
             widening_upper_bound: upper_bound,
             widening_delay: self.widening_delay >> low_byte.as_bit_length(),
+            excluded_values: Vec::new(),
+            known_bits: None,
         }
     }
 
@@ -338,6 +416,8 @@ impl IntervalDomain {
             widening_lower_bound: lower_bound,
             widening_upper_bound: upper_bound,
             widening_delay: self.widening_delay,
+            excluded_values: Vec::new(),
+            known_bits: None,
         }
     }
 
@@ -371,6 +451,8 @@ impl IntervalDomain {
             widening_lower_bound: lower_bound,
             widening_upper_bound: upper_bound,
             widening_delay,
+            excluded_values: Vec::new(),
+            known_bits: None,
         }
     }
 }
@@ -398,6 +480,7 @@ impl SpecializeByConditional for IntervalDomain {
         if self.interval.start.checked_sle(&bound).unwrap() {
             self.interval.end = bound;
             self.interval.adjust_end_to_value_in_stride();
+            self.normalize_excluded_values()?;
             Ok(self)
         } else {
             Err(anyhow!("Empty interval"))
@@ -426,6 +509,7 @@ impl SpecializeByConditional for IntervalDomain {
         if self.interval.end.checked_sge(&bound).unwrap() {
             self.interval.start = bound;
             self.interval.adjust_start_to_value_in_stride();
+            self.normalize_excluded_values()?;
             Ok(self)
         } else {
             Err(anyhow!("Empty interval"))
@@ -460,24 +544,19 @@ impl SpecializeByConditional for IntervalDomain {
         }
     }
 
-    fn add_not_equal_bound(mut self, bound: &Bitvector) -> Result<Self, Error> {
+    /// Restrict `self` to values unequal to `bound`.
+    ///
+    /// If `bound` lies strictly inside the interval, it is added to the excluded values of `self`.
+    fn add_not_equal_bound(self, bound: &Bitvector) -> Result<Self, Error> {
         if self.interval.start == *bound && self.interval.end == *bound {
             return Err(anyhow!("Empty interval"));
         }
         if self.interval.start.checked_sgt(bound).unwrap() {
             self.add_signed_greater_equal_bound(&(bound + &Bitvector::one(bound.width())))
-        } else if self.interval.start == *bound {
-            self.interval.start += &Bitvector::one(bound.width());
-            self.interval.adjust_start_to_value_in_stride();
-            Ok(self)
         } else if self.interval.end.checked_slt(bound).unwrap() {
             self.add_signed_less_equal_bound(&(bound - &Bitvector::one(bound.width())))
-        } else if self.interval.end == *bound {
-            self.interval.end -= &Bitvector::one(bound.width());
-            self.interval.adjust_end_to_value_in_stride();
-            Ok(self)
         } else {
-            Ok(self)
+            self.exclude_value(bound)
         }
     }
 
@@ -500,6 +579,15 @@ impl SpecializeByConditional for IntervalDomain {
             intersected_domain.widening_delay =
                 std::cmp::min(intersected_domain.widening_delay, interval_length);
         }
+        for value in self
+            .excluded_values
+            .iter()
+            .chain(other.excluded_values.iter())
+        {
+            if intersected_domain.interval.contains(value) {
+                intersected_domain = intersected_domain.exclude_value(value)?;
+            }
+        }
 
         Ok(intersected_domain)
     }
@@ -512,9 +600,10 @@ impl AbstractDomain for IntervalDomain {
         self.signed_merge_and_widen(other)
     }
 
-    /// Return `true` if the interval spans all possible values.
+    /// Return `true` if the interval spans all possible values, no values are excluded from it
+    /// and no bits of the values are known.
     fn is_top(&self) -> bool {
-        self.interval.is_top()
+        self.interval.is_top() && self.excluded_values.is_empty() && self.known_bits.is_none()
     }
 }
 
@@ -535,6 +624,8 @@ impl SizedDomain for IntervalDomain {
             widening_lower_bound: None,
             widening_upper_bound: None,
             widening_delay: 0,
+            excluded_values: Vec::new(),
+            known_bits: None,
         }
     }
 }
@@ -554,34 +645,28 @@ impl RegisterDomain for IntervalDomain {
     fn bin_op(&self, op: BinOpType, rhs: &Self) -> Self {
         use BinOpType::*;
         match op {
-            IntEqual | IntNotEqual | IntLess | IntSLess | IntLessEqual | IntSLessEqual
-            | IntCarry | IntSCarry | IntSBorrow | IntAnd | IntOr | IntXOr | IntRight
-            | IntSRight | IntDiv | IntSDiv | IntRem | IntSRem | BoolAnd | BoolOr | BoolXOr
-            | FloatEqual | FloatNotEqual | FloatLess | FloatLessEqual | FloatAdd | FloatSub
-            | FloatMult | FloatDiv => {
-                let new_interval = if self.interval.start == self.interval.end
-                    && rhs.interval.start == rhs.interval.end
-                {
-                    if let Ok(bitvec) = self.interval.start.bin_op(op, &rhs.interval.start) {
-                        bitvec.into()
-                    } else {
-                        Interval::new_top(self.bin_op_bytesize(op, rhs))
-                    }
-                } else {
-                    Interval::new_top(self.bin_op_bytesize(op, rhs))
-                };
-                IntervalDomain {
-                    interval: new_interval,
-                    widening_lower_bound: None,
-                    widening_upper_bound: None,
-                    widening_delay: std::cmp::max(self.widening_delay, rhs.widening_delay),
-                }
+            IntEqual | IntNotEqual
+                if self.excludes_value_of(rhs) || rhs.excludes_value_of(self) =>
+            {
+                // The compared values are known to be unequal.
+                let mut result: IntervalDomain =
+                    Bitvector::from_u8((op == IntNotEqual) as u8).into();
+                result.widening_delay = std::cmp::max(self.widening_delay, rhs.widening_delay);
+                result
             }
+            IntAnd | IntOr | IntXOr => self.bitwise_op(op, rhs),
+            IntEqual | IntNotEqual | IntLess | IntSLess | IntLessEqual | IntSLessEqual
+            | IntCarry | IntSCarry | IntSBorrow | IntDiv | IntSDiv | IntRem | IntSRem | BoolAnd
+            | BoolOr | BoolXOr | FloatEqual | FloatNotEqual | FloatLess | FloatLessEqual
+            | FloatAdd | FloatSub | FloatMult | FloatDiv => self.bin_op_on_single_values(op, rhs),
             Piece => self.piece(rhs),
             IntAdd => self.add(rhs),
             IntSub => self.sub(rhs),
             IntMult => self.signed_mul(rhs),
-            IntLeft => self.shift_left(rhs),
+            IntLeft => self.shift_left(rhs).with_shifted_known_bits(self, op, rhs),
+            IntRight | IntSRight => self
+                .bin_op_on_single_values(op, rhs)
+                .with_shifted_known_bits(self, op, rhs),
         }
     }
 
@@ -601,18 +686,29 @@ impl RegisterDomain for IntervalDomain {
                     }
                 };
                 let new_lower_bound = self.widening_upper_bound.clone().map(|bound| -bound);
-                IntervalDomain {
+                let mut negated_domain = IntervalDomain {
                     interval,
                     widening_lower_bound: new_lower_bound,
                     widening_upper_bound: new_upper_bound,
                     widening_delay: self.widening_delay,
-                }
+                    // The two's complement maps distinct values to distinct values.
+                    excluded_values: self
+                        .excluded_values
+                        .iter()
+                        .map(|value| -value.clone())
+                        .collect(),
+                    known_bits: None,
+                };
+                negated_domain.retain_inner_excluded_values();
+                negated_domain
             }
             IntNegate => IntervalDomain {
                 interval: self.interval.clone().bitwise_not(),
                 widening_lower_bound: None,
                 widening_upper_bound: None,
                 widening_delay: self.widening_delay,
+                excluded_values: Vec::new(),
+                known_bits: None,
             },
             BoolNegate => {
                 if self.interval.start == self.interval.end {
@@ -641,6 +737,7 @@ impl RegisterDomain for IntervalDomain {
         if interval_domain.bytesize() > size {
             interval_domain = interval_domain.subpiece_lower(size);
         }
+        interval_domain.set_known_bits(self.get_subpiece_known_bits(low_byte, size));
         interval_domain
     }
 
@@ -708,6 +805,8 @@ impl From<Bitvector> for IntervalDomain {
             widening_lower_bound: None,
             widening_upper_bound: None,
             widening_delay: 0,
+            excluded_values: Vec::new(),
+            known_bits: None,
         }
     }
 }
@@ -736,7 +835,16 @@ impl TryToInterval for IntervalDomain {
 
 impl Display for IntervalDomain {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.is_top() {
+        if !self.excluded_values.is_empty() {
+            let excluded_values: Vec<String> = self
+                .excluded_values
+                .iter()
+                .map(|value| format!("0x{:x}", apint::Int::from(value.clone())))
+                .collect();
+            let mut interval = self.clone();
+            interval.excluded_values = Vec::new();
+            write!(f, "{} \\ {{{}}}", interval, excluded_values.join(", "))
+        } else if self.is_top() {
             write!(f, "Top:i{}", self.bytesize().as_bit_length())
         } else if self.interval.start == self.interval.end {
             write!(
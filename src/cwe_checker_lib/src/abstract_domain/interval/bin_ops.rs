@@ -3,6 +3,8 @@ use super::*;
 impl IntervalDomain {
     /// Compute the interval of possible results
     /// if one adds a value from `self` to a value from `rhs`.
+    ///
+    /// If one of the summands is a constant, the excluded values of the other summand are preserved.
     pub fn add(&self, rhs: &Self) -> Self {
         let mut interval: IntervalDomain = self.interval.add(&rhs.interval).into();
         if !interval.is_top() {
@@ -30,11 +32,19 @@ impl IntervalDomain {
                     .and_then(|bound| bound.signed_add_overflow_checked(&self.interval.end)),
             );
         }
+        if let Ok(offset) = rhs.try_to_bitvec() {
+            interval.set_shifted_excluded_values(self, &offset);
+        } else if let Ok(offset) = self.try_to_bitvec() {
+            interval.set_shifted_excluded_values(rhs, &offset);
+        }
         interval
     }
 
     /// Compute the interval of possible results
     /// if one subtracts a value in `rhs` from a value in `self`.
+    ///
+    /// If `rhs` is a constant, the excluded values of `self` are preserved.
+    /// If `self` is a constant, the excluded values of `rhs` are preserved.
     pub fn sub(&self, rhs: &Self) -> Self {
         let mut interval: IntervalDomain = self.interval.sub(&rhs.interval).into();
         if !interval.is_top() {
@@ -62,6 +72,11 @@ impl IntervalDomain {
                     .and_then(|bound| self.interval.end.signed_sub_overflow_checked(bound)),
             );
         }
+        if let Ok(offset) = rhs.try_to_bitvec() {
+            interval.set_shifted_excluded_values(self, &(-offset));
+        } else if let Ok(minuend) = self.try_to_bitvec() {
+            interval.set_shifted_excluded_values(&rhs.un_op(UnOpType::Int2Comp), &minuend);
+        }
         interval
     }
 
@@ -130,6 +145,8 @@ impl IntervalDomain {
                 widening_lower_bound: lower_bound,
                 widening_upper_bound: upper_bound,
                 widening_delay: std::cmp::max(self.widening_delay, rhs.widening_delay),
+                excluded_values: Vec::new(),
+                known_bits: None,
             }
         }
     }
@@ -151,4 +168,27 @@ impl IntervalDomain {
             Self::new_top(self.bytesize())
         }
     }
+
+    /// Compute the result of a binary operation without a specialized implementation for intervals.
+    /// The result is only exact if both intervals contain exactly one value.
+    pub(super) fn bin_op_on_single_values(&self, op: BinOpType, rhs: &Self) -> Self {
+        let new_interval =
+            if self.interval.start == self.interval.end && rhs.interval.start == rhs.interval.end {
+                if let Ok(bitvec) = self.interval.start.bin_op(op, &rhs.interval.start) {
+                    bitvec.into()
+                } else {
+                    Interval::new_top(self.bin_op_bytesize(op, rhs))
+                }
+            } else {
+                Interval::new_top(self.bin_op_bytesize(op, rhs))
+            };
+        IntervalDomain {
+            interval: new_interval,
+            widening_lower_bound: None,
+            widening_upper_bound: None,
+            widening_delay: std::cmp::max(self.widening_delay, rhs.widening_delay),
+            excluded_values: Vec::new(),
+            known_bits: None,
+        }
+    }
 }
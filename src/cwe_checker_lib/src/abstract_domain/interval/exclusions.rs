@@ -0,0 +1,294 @@
+use super::*;
+
+/// The maximal number of values that an [`IntervalDomain`] can exclude from its interval.
+///
+/// Additional exclusions are dropped, which is sound since it only enlarges the represented value set.
+pub const MAX_EXCLUDED_VALUES: usize = 4;
+
+impl IntervalDomain {
+    /// Return `true` if `value` may be contained in the value set represented by `self`.
+    ///
+    /// In contrast to [`Interval::contains`] this also respects the values excluded from the interval,
+    /// e.g. an interval specialized by a `x != 0` check answers that it cannot contain zero.
+    pub fn contains(&self, value: &Bitvector) -> bool {
+        self.interval.contains(value) && !self.excluded_values.contains(value)
+    }
+
+    /// Return `true` if `value` lies inside the bounds of the interval
+    /// but is explicitly excluded from the represented value set,
+    /// e.g. because of a `x != value` check.
+    pub fn is_excluded(&self, value: &Bitvector) -> bool {
+        self.excluded_values.contains(value)
+    }
+
+    /// Get the values inside the interval bounds that are excluded from the represented value set.
+    pub fn get_excluded_values(&self) -> &[Bitvector] {
+        &self.excluded_values
+    }
+
+    /// Exclude `value` from the represented value set.
+    ///
+    /// If `value` is an interval bound, the interval is shrunk accordingly.
+    /// If the maximal number of excluded values is already reached, the value is not excluded.
+    /// Returns an error if the resulting value set is empty.
+    pub(super) fn exclude_value(mut self, value: &Bitvector) -> Result<Self, Error> {
+        if self.excluded_values.len() < MAX_EXCLUDED_VALUES && !self.is_excluded(value) {
+            self.excluded_values.push(value.clone());
+        }
+        self.normalize_excluded_values()?;
+        Ok(self)
+    }
+
+    /// Shrink the interval bounds so that they are not excluded values themselves
+    /// and remove all excluded values that are not contained in the interval anymore.
+    /// Returns an error if the resulting value set is empty.
+    pub(super) fn normalize_excluded_values(&mut self) -> Result<(), Error> {
+        while self.excluded_values.contains(&self.interval.start) {
+            if self.interval.start == self.interval.end {
+                return Err(anyhow!("Empty interval"));
+            }
+            self.interval.start += &Bitvector::one(self.interval.start.width());
+            self.interval.adjust_start_to_value_in_stride();
+        }
+        while self.excluded_values.contains(&self.interval.end) {
+            if self.interval.start == self.interval.end {
+                return Err(anyhow!("Empty interval"));
+            }
+            self.interval.end -= &Bitvector::one(self.interval.end.width());
+            self.interval.adjust_end_to_value_in_stride();
+        }
+        self.retain_inner_excluded_values();
+        Ok(())
+    }
+
+    /// Remove all excluded values that are not strictly inside the interval bounds
+    /// or do not respect the stride of the interval.
+    /// The remaining values are sorted and at most [`MAX_EXCLUDED_VALUES`] of them are kept.
+    ///
+    /// In contrast to [`IntervalDomain::normalize_excluded_values`] the interval bounds are never changed.
+    pub(super) fn retain_inner_excluded_values(&mut self) {
+        let interval = &self.interval;
+        self.excluded_values.retain(|value| {
+            value.width() == interval.start.width()
+                && *value != interval.start
+                && *value != interval.end
+                && interval.contains(value)
+        });
+        self.excluded_values.sort_by(|left, right| {
+            if left == right {
+                std::cmp::Ordering::Equal
+            } else if left.checked_slt(right).unwrap() {
+                std::cmp::Ordering::Less
+            } else {
+                std::cmp::Ordering::Greater
+            }
+        });
+        self.excluded_values.dedup();
+        self.excluded_values.truncate(MAX_EXCLUDED_VALUES);
+    }
+
+    /// Set the excluded values of `self` to the values excluded from both `self_before` and `other`,
+    /// where values not contained in the interval of one of them also count as excluded by it.
+    pub(super) fn set_merged_excluded_values(
+        &mut self,
+        self_before: &IntervalDomain,
+        other: &IntervalDomain,
+    ) {
+        self.excluded_values = self_before
+            .excluded_values
+            .iter()
+            .chain(other.excluded_values.iter())
+            .filter(|value| !self_before.contains(value) && !other.contains(value))
+            .cloned()
+            .collect();
+        self.retain_inner_excluded_values();
+    }
+
+    /// Set the excluded values of `self` to the excluded values of `source` shifted by `offset`.
+    ///
+    /// Since adding a constant is a bijection on the values of a fixed bytesize,
+    /// the shifted values are excluded from `source + offset` even if the addition overflows.
+    pub(super) fn set_shifted_excluded_values(
+        &mut self,
+        source: &IntervalDomain,
+        offset: &Bitvector,
+    ) {
+        self.excluded_values = source
+            .excluded_values
+            .iter()
+            .map(|value| value + offset)
+            .collect();
+        self.retain_inner_excluded_values();
+    }
+
+    /// Return `true` if `other` represents exactly one value and this value is not contained in `self`.
+    pub(super) fn excludes_value_of(&self, other: &IntervalDomain) -> bool {
+        match other.try_to_bitvec() {
+            Ok(value) => !self.contains(&value),
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the interval `[start, end]` of 8-byte integers with the given values excluded.
+    fn mock_with_exclusions(start: i64, end: i64, excluded_values: &[i64]) -> IntervalDomain {
+        let mut domain = IntervalDomain::mock(start, end);
+        for value in excluded_values {
+            domain = domain.exclude_value(&Bitvector::from_i64(*value)).unwrap();
+        }
+        domain
+    }
+
+    /// Convert the given values to 8-byte bitvectors.
+    fn bitvecs(values: &[i64]) -> Vec<Bitvector> {
+        values
+            .iter()
+            .map(|value| Bitvector::from_i64(*value))
+            .collect()
+    }
+
+    #[test]
+    fn exclude_values() {
+        let interval = mock_with_exclusions(-10, 10, &[5, 0, 5]);
+        assert_eq!(interval.get_excluded_values(), &bitvecs(&[0, 5])[..]);
+        assert!(!interval.contains(&Bitvector::from_i64(0)));
+        assert!(interval.contains(&Bitvector::from_i64(1)));
+        // Excluding bounds shrinks the interval, also over already excluded values.
+        let interval = interval
+            .exclude_value(&Bitvector::from_i64(-10))
+            .unwrap()
+            .exclude_value(&Bitvector::from_i64(10))
+            .unwrap();
+        assert_eq!(interval, mock_with_exclusions(-9, 9, &[0, 5]));
+        let interval = mock_with_exclusions(4, 6, &[5])
+            .exclude_value(&Bitvector::from_i64(6))
+            .unwrap();
+        assert_eq!(interval, IntervalDomain::mock(4, 4));
+        assert!(interval.exclude_value(&Bitvector::from_i64(4)).is_err());
+        // Values not respecting the stride are not excluded.
+        let interval = IntervalDomain::mock(0, 16)
+            .with_stride(4)
+            .exclude_value(&Bitvector::from_i64(6))
+            .unwrap();
+        assert!(interval.get_excluded_values().is_empty());
+        let interval = interval.exclude_value(&Bitvector::from_i64(0)).unwrap();
+        assert_eq!(interval, IntervalDomain::mock(4, 16).with_stride(4));
+    }
+
+    #[test]
+    fn merge_with_excluded_values() {
+        let interval = mock_with_exclusions(-10, 10, &[0, 5]);
+        // Values excluded by both sides stay excluded.
+        let merged = interval.signed_merge(&mock_with_exclusions(-5, 20, &[0]));
+        assert_eq!(merged.get_excluded_values(), &bitvecs(&[0])[..]);
+        // Values outside of the interval of the other side count as excluded by it.
+        let merged = interval.signed_merge(&IntervalDomain::mock(6, 20));
+        assert_eq!(merged, mock_with_exclusions(-10, 20, &[0, 5]));
+        let merged = interval.signed_merge(&IntervalDomain::mock(5, 5));
+        assert_eq!(merged, mock_with_exclusions(-10, 10, &[0]));
+        // Merging is symmetric with respect to the excluded values.
+        assert_eq!(
+            IntervalDomain::mock(6, 20).signed_merge(&interval),
+            interval.signed_merge(&IntervalDomain::mock(6, 20))
+        );
+        // The merge with widening keeps the excluded values if no widening happens.
+        let merged = interval.merge(&IntervalDomain::mock(-10, -10));
+        assert_eq!(merged.get_excluded_values(), &bitvecs(&[0, 5])[..]);
+        // Merging with an interval that contains all excluded values removes the exclusions.
+        let merged = interval.merge(&IntervalDomain::mock(0, 5));
+        assert!(merged.get_excluded_values().is_empty());
+    }
+
+    #[test]
+    fn intersect_with_excluded_values() {
+        let interval = mock_with_exclusions(-10, 10, &[0]);
+        // Excluded values of both sides stay excluded.
+        let intersection = interval
+            .clone()
+            .intersect(&mock_with_exclusions(-5, 20, &[5]))
+            .unwrap();
+        assert_eq!(intersection, mock_with_exclusions(-5, 10, &[0, 5]));
+        // Excluded values at the bounds of the intersection shrink it.
+        let intersection = interval
+            .clone()
+            .intersect(&IntervalDomain::mock(0, 20))
+            .unwrap();
+        assert_eq!(intersection, IntervalDomain::mock(1, 10));
+        let intersection = mock_with_exclusions(-10, 10, &[1])
+            .intersect(&mock_with_exclusions(0, 20, &[0]))
+            .unwrap();
+        assert_eq!(intersection, IntervalDomain::mock(2, 10));
+        // The intersection is empty if it only contains excluded values.
+        assert!(interval.intersect(&IntervalDomain::mock(0, 0)).is_err());
+        assert!(mock_with_exclusions(-10, 10, &[1])
+            .intersect(&mock_with_exclusions(0, 1, &[0]))
+            .is_err());
+    }
+
+    #[test]
+    fn narrow_with_excluded_values() {
+        let interval = mock_with_exclusions(-10, 10, &[-5, 0, 5]);
+        // Excluded values outside of the narrowed interval are dropped.
+        let narrowed = interval
+            .clone()
+            .add_signed_less_equal_bound(&Bitvector::from_i64(3))
+            .unwrap();
+        assert_eq!(narrowed, mock_with_exclusions(-10, 3, &[-5, 0]));
+        let narrowed = interval
+            .clone()
+            .add_signed_greater_equal_bound(&Bitvector::from_i64(-3))
+            .unwrap();
+        assert_eq!(narrowed, mock_with_exclusions(-3, 10, &[0, 5]));
+        // New bounds on excluded values are moved to the next non-excluded value.
+        let narrowed = interval
+            .clone()
+            .add_signed_less_equal_bound(&Bitvector::from_i64(0))
+            .unwrap();
+        assert_eq!(narrowed, mock_with_exclusions(-10, -1, &[-5]));
+        let narrowed = mock_with_exclusions(-10, 10, &[0, 1])
+            .add_signed_greater_equal_bound(&Bitvector::from_i64(0))
+            .unwrap();
+        assert_eq!(narrowed, IntervalDomain::mock(2, 10));
+        let narrowed = interval
+            .clone()
+            .add_unsigned_less_equal_bound(&Bitvector::from_i64(10))
+            .unwrap();
+        assert_eq!(narrowed, mock_with_exclusions(1, 10, &[5]));
+        // Narrowing to an excluded value yields an empty interval.
+        assert!(interval
+            .clone()
+            .add_signed_greater_equal_bound(&Bitvector::from_i64(0))
+            .unwrap()
+            .add_signed_less_equal_bound(&Bitvector::from_i64(0))
+            .is_err());
+        // Narrowing to an excluded value by a not-equal bound on the other bound is also empty.
+        assert!(mock_with_exclusions(0, 2, &[1])
+            .add_not_equal_bound(&Bitvector::from_i64(0))
+            .unwrap()
+            .add_not_equal_bound(&Bitvector::from_i64(2))
+            .is_err());
+    }
+
+    #[test]
+    fn shift_excluded_values() {
+        let source = mock_with_exclusions(-10, 10, &[0, 5]);
+        let mut shifted = IntervalDomain::mock(-7, 13);
+        shifted.set_shifted_excluded_values(&source, &Bitvector::from_i64(3));
+        assert_eq!(shifted.get_excluded_values(), &bitvecs(&[3, 8])[..]);
+        // Shifted values outside of the target interval are dropped.
+        let mut shifted = IntervalDomain::mock(-7, 5);
+        shifted.set_shifted_excluded_values(&source, &Bitvector::from_i64(3));
+        assert_eq!(shifted.get_excluded_values(), &bitvecs(&[3])[..]);
+        // The shift wraps around on overflow.
+        let source = IntervalDomain::mock_i8(100, 120)
+            .exclude_value(&Bitvector::from_i8(110))
+            .unwrap();
+        let mut shifted = IntervalDomain::mock_i8(-128, 127);
+        shifted.set_shifted_excluded_values(&source, &Bitvector::from_i8(20));
+        assert_eq!(shifted.get_excluded_values(), &[Bitvector::from_i8(-126)]);
+    }
+}
@@ -0,0 +1,398 @@
+use super::*;
+
+/// The bits that are known to be set resp. unset in all values represented by an [`IntervalDomain`].
+///
+/// Flag words are usually built by OR-ing constants, which the interval bounds cannot represent precisely.
+/// The known bits are maintained through bitwise operations and merges,
+/// so that one can still check whether a specific flag is set in such values.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct KnownBits {
+    /// The bits that are set in all represented values.
+    pub ones: Bitvector,
+    /// The bits that are unset in all represented values.
+    pub zeros: Bitvector,
+}
+
+impl KnownBits {
+    /// Create a known bits object where no bit is known.
+    pub fn new_unknown(bytesize: ByteSize) -> KnownBits {
+        KnownBits {
+            ones: Bitvector::zero(bytesize.into()),
+            zeros: Bitvector::zero(bytesize.into()),
+        }
+    }
+
+    /// Create a known bits object where all bits are known to be equal to the bits of `value`.
+    pub fn from_value(value: &Bitvector) -> KnownBits {
+        KnownBits {
+            ones: value.clone(),
+            zeros: value.clone().into_bitnot(),
+        }
+    }
+
+    /// Return `true` if no bit is known.
+    pub fn is_unknown(&self) -> bool {
+        self.ones.is_zero() && self.zeros.is_zero()
+    }
+
+    /// Return the bits known for `self` or for `other`,
+    /// i.e. the known bits of the intersection of the represented value sets.
+    pub fn union(&self, other: &KnownBits) -> KnownBits {
+        KnownBits {
+            ones: bitwise(&self.ones, BinOpType::IntOr, &other.ones),
+            zeros: bitwise(&self.zeros, BinOpType::IntOr, &other.zeros),
+        }
+    }
+
+    /// Return `true` if all bits known for `self` are also known for `other`.
+    fn is_implied_by(&self, other: &KnownBits) -> bool {
+        let not_implied = |bits: &Bitvector, other_bits: &Bitvector| {
+            bitwise(bits, BinOpType::IntAnd, &other_bits.clone().into_bitnot())
+        };
+        not_implied(&self.ones, &other.ones).is_zero()
+            && not_implied(&self.zeros, &other.zeros).is_zero()
+    }
+
+    /// Return the represented value if all bits are known.
+    pub fn try_to_bitvec(&self) -> Option<Bitvector> {
+        let known_mask = bitwise(&self.ones, BinOpType::IntOr, &self.zeros);
+        if known_mask == Bitvector::unsigned_max_value(known_mask.width()) {
+            Some(self.ones.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Return the bits known for both `self` and `other`,
+    /// i.e. the known bits of the union of the represented value sets.
+    pub fn merge(&self, other: &KnownBits) -> KnownBits {
+        KnownBits {
+            ones: bitwise(&self.ones, BinOpType::IntAnd, &other.ones),
+            zeros: bitwise(&self.zeros, BinOpType::IntAnd, &other.zeros),
+        }
+    }
+
+    /// Compute the known bits of the result of the bitwise operation `op` (`IntAnd`, `IntOr` or `IntXOr`).
+    /// Returns `None` for all other operations.
+    fn bitwise_op(&self, op: BinOpType, rhs: &KnownBits) -> Option<KnownBits> {
+        use BinOpType::*;
+        let known_bits = match op {
+            IntOr => KnownBits {
+                ones: bitwise(&self.ones, IntOr, &rhs.ones),
+                zeros: bitwise(&self.zeros, IntAnd, &rhs.zeros),
+            },
+            IntAnd => KnownBits {
+                ones: bitwise(&self.ones, IntAnd, &rhs.ones),
+                zeros: bitwise(&self.zeros, IntOr, &rhs.zeros),
+            },
+            IntXOr => KnownBits {
+                ones: bitwise(
+                    &bitwise(&self.ones, IntAnd, &rhs.zeros),
+                    IntOr,
+                    &bitwise(&self.zeros, IntAnd, &rhs.ones),
+                ),
+                zeros: bitwise(
+                    &bitwise(&self.ones, IntAnd, &rhs.ones),
+                    IntOr,
+                    &bitwise(&self.zeros, IntAnd, &rhs.zeros),
+                ),
+            },
+            _ => return None,
+        };
+        Some(known_bits)
+    }
+
+    /// Compute the known bits of the result of the shift operation `op` (`IntLeft`, `IntRight` or `IntSRight`)
+    /// by the constant `amount`.
+    /// The bits shifted in by logical shifts are known to be unset.
+    /// Returns `None` for all other operations.
+    fn shift_op(&self, op: BinOpType, amount: &Bitvector) -> Option<KnownBits> {
+        use BinOpType::*;
+        let width = self.ones.width();
+        let shift = |bits: &Bitvector| bits.bin_op(op, amount).unwrap();
+        let shifted_in = match op {
+            IntLeft | IntRight => shift(&Bitvector::unsigned_max_value(width)).into_bitnot(),
+            // The sign bit is shifted in, which is known to be set resp. unset if it was known before.
+            IntSRight => Bitvector::zero(width),
+            _ => return None,
+        };
+        Some(KnownBits {
+            ones: shift(&self.ones),
+            zeros: bitwise(&shift(&self.zeros), IntOr, &shifted_in),
+        })
+    }
+}
+
+/// Apply a bitwise binary operation to two bitvectors of the same size.
+fn bitwise(lhs: &Bitvector, op: BinOpType, rhs: &Bitvector) -> Bitvector {
+    lhs.bin_op(op, rhs).unwrap()
+}
+
+impl IntervalDomain {
+    /// Get the bits known to be set resp. unset in all values represented by `self`.
+    ///
+    /// This includes the bits implied by the interval bounds and the stride.
+    /// If the domain represents exactly one value, all bits are known.
+    pub fn get_known_bits(&self) -> KnownBits {
+        let interval_bits = self.get_known_bits_of_interval();
+        match &self.known_bits {
+            Some(known_bits) => interval_bits.union(known_bits),
+            None => interval_bits,
+        }
+    }
+
+    /// Get the bits implied by the interval bounds and the stride.
+    ///
+    /// If both bounds have the same sign, the common high bits of the bounds are known.
+    /// If the stride is a power of two, the low bits below the stride are known.
+    fn get_known_bits_of_interval(&self) -> KnownBits {
+        let (start, end) = (&self.interval.start, &self.interval.end);
+        if start == end {
+            return KnownBits::from_value(start);
+        }
+        let mut known_mask = Bitvector::zero(start.width());
+        if start.sign_bit().to_bool() == end.sign_bit().to_bool() {
+            let num_common_bits = bitwise(start, BinOpType::IntXOr, end).leading_zeros();
+            known_mask = Bitvector::unsigned_max_value(start.width())
+                .into_checked_lshr(num_common_bits)
+                .unwrap()
+                .into_bitnot();
+        }
+        let stride = self.interval.stride;
+        if stride > 1 && stride.is_power_of_two() {
+            let low_mask = Bitvector::from_u64(stride - 1).into_zero_resize(start.width());
+            known_mask = bitwise(&known_mask, BinOpType::IntOr, &low_mask);
+        }
+        KnownBits {
+            ones: bitwise(start, BinOpType::IntAnd, &known_mask),
+            zeros: bitwise(&start.clone().into_bitnot(), BinOpType::IntAnd, &known_mask),
+        }
+    }
+
+    /// Set the known bits of `self`.
+    ///
+    /// The known bits are only stored if they contain information not already implied by the interval.
+    pub(super) fn set_known_bits(&mut self, known_bits: KnownBits) {
+        if known_bits.is_implied_by(&self.get_known_bits_of_interval()) {
+            self.known_bits = None;
+        } else {
+            self.known_bits = Some(known_bits);
+        }
+    }
+
+    /// Set the known bits of `self` to the bits known for both `self_before` and `other`.
+    pub(super) fn set_merged_known_bits(
+        &mut self,
+        self_before: &IntervalDomain,
+        other: &IntervalDomain,
+    ) {
+        self.set_known_bits(self_before.get_known_bits().merge(&other.get_known_bits()));
+    }
+
+    /// Compute the result of the bitwise operation `op` (`IntAnd`, `IntOr` or `IntXOr`) on `self` and `rhs`.
+    ///
+    /// The interval of the result is exact if all bits of the result are known.
+    /// If at least the sign bit of the result is known,
+    /// the interval bounds are given by setting all unknown bits to zero resp. one.
+    pub(super) fn bitwise_op(&self, op: BinOpType, rhs: &Self) -> Self {
+        let known_bits = self
+            .get_known_bits()
+            .bitwise_op(op, &rhs.get_known_bits())
+            .unwrap();
+        let sign_mask = Bitvector::signed_min_value(self.bytesize().into());
+        let is_sign_bit_known = !bitwise(
+            &bitwise(&known_bits.ones, BinOpType::IntOr, &known_bits.zeros),
+            BinOpType::IntAnd,
+            &sign_mask,
+        )
+        .is_zero();
+        let mut result: IntervalDomain = if let Some(value) = known_bits.try_to_bitvec() {
+            value.into()
+        } else if is_sign_bit_known {
+            IntervalDomain::new(
+                known_bits.ones.clone(),
+                known_bits.zeros.clone().into_bitnot(),
+            )
+        } else {
+            Interval::new_top(self.bytesize()).into()
+        };
+        result.widening_delay = std::cmp::max(self.widening_delay, rhs.widening_delay);
+        result.set_known_bits(known_bits);
+        result
+    }
+
+    /// Add the known bits of the result of the shift operation `op` (`IntLeft`, `IntRight` or `IntSRight`)
+    /// of `lhs` by `rhs` to `self`, where `self` is the result of the operation computed on the intervals.
+    ///
+    /// The known bits of `lhs` are only shifted if the shift amount is a constant.
+    pub(super) fn with_shifted_known_bits(mut self, lhs: &Self, op: BinOpType, rhs: &Self) -> Self {
+        if let Ok(amount) = rhs.try_to_bitvec() {
+            if let Some(shifted_bits) = lhs.get_known_bits().shift_op(op, &amount) {
+                let known_bits = self.get_known_bits().union(&shifted_bits);
+                self.set_known_bits(known_bits);
+            }
+        }
+        self
+    }
+
+    /// Get the known bits of the sub-bitvector starting at `low_byte` and of the given `size`.
+    pub(super) fn get_subpiece_known_bits(&self, low_byte: ByteSize, size: ByteSize) -> KnownBits {
+        let known_bits = self.get_known_bits();
+        let subpiece = |bits: Bitvector| bits.subpiece(low_byte, size);
+        KnownBits {
+            ones: subpiece(known_bits.ones),
+            zeros: subpiece(known_bits.zeros),
+        }
+    }
+
+    /// Get the known bits of the zero-extension of the values in `self` to the given width.
+    /// The additional high bits are known to be unset.
+    pub(super) fn get_zero_extended_known_bits(&self, width: ByteSize) -> KnownBits {
+        let known_bits = self.get_known_bits();
+        let high_bits = Bitvector::unsigned_max_value(self.bytesize().into())
+            .into_zero_extend(width)
+            .unwrap()
+            .into_bitnot();
+        KnownBits {
+            ones: known_bits.ones.into_zero_extend(width).unwrap(),
+            zeros: bitwise(
+                &known_bits.zeros.into_zero_extend(width).unwrap(),
+                BinOpType::IntOr,
+                &high_bits,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(value: u64) -> Bitvector {
+        Bitvector::from_u64(value)
+    }
+
+    fn known_bits(ones: u64, zeros: u64) -> KnownBits {
+        KnownBits {
+            ones: bits(ones),
+            zeros: bits(zeros),
+        }
+    }
+
+    #[test]
+    fn bitwise_transfer_functions() {
+        // The lowest four bits of the left hand side are known to be `01?1`,
+        // of the right hand side they are known to be `0?11`.
+        let lhs = known_bits(0b0101, 0b1000);
+        let rhs = known_bits(0b0011, 0b1000);
+        assert_eq!(
+            lhs.bitwise_op(BinOpType::IntAnd, &rhs),
+            Some(known_bits(0b0001, 0b1000))
+        );
+        assert_eq!(
+            lhs.bitwise_op(BinOpType::IntOr, &rhs),
+            Some(known_bits(0b0111, 0b1000))
+        );
+        assert_eq!(
+            lhs.bitwise_op(BinOpType::IntXOr, &rhs),
+            Some(known_bits(0b0000, 0b1001))
+        );
+        // AND with known unset bits and OR with known set bits is known regardless of the other side.
+        let unknown = KnownBits::new_unknown(ByteSize::new(8));
+        assert_eq!(
+            unknown.bitwise_op(BinOpType::IntAnd, &KnownBits::from_value(&bits(0xff))),
+            Some(known_bits(0, !0xff))
+        );
+        assert_eq!(
+            unknown.bitwise_op(BinOpType::IntOr, &KnownBits::from_value(&bits(0x10))),
+            Some(known_bits(0x10, 0))
+        );
+        assert_eq!(lhs.bitwise_op(BinOpType::IntAdd, &rhs), None);
+    }
+
+    #[test]
+    fn shift_transfer_functions() {
+        let flags = known_bits(0b0100, 0b0001);
+        assert_eq!(
+            flags.shift_op(BinOpType::IntLeft, &bits(2)),
+            Some(known_bits(0b1_0000, 0b0111))
+        );
+        assert_eq!(
+            flags.shift_op(BinOpType::IntRight, &bits(2)),
+            Some(known_bits(0b0001, 0xc000_0000_0000_0000))
+        );
+        // Arithmetic shifts only know the shifted in bits if the sign bit is known.
+        assert_eq!(
+            flags.shift_op(BinOpType::IntSRight, &bits(2)),
+            Some(known_bits(0b0001, 0))
+        );
+        let negative = known_bits(0x8000_0000_0000_0000, 0b0001);
+        assert_eq!(
+            negative.shift_op(BinOpType::IntSRight, &bits(4)),
+            Some(known_bits(0xf800_0000_0000_0000, 0))
+        );
+        // Shifting by at least the bit width shifts out all bits.
+        assert_eq!(
+            flags.shift_op(BinOpType::IntLeft, &bits(64)),
+            Some(KnownBits::from_value(&bits(0)))
+        );
+        assert_eq!(flags.shift_op(BinOpType::IntAnd, &bits(2)), None);
+
+        // The known bits are propagated through shifts of interval domains by constants.
+        let flag = |value: i64| IntervalDomain::mock(value, value);
+        let value = IntervalDomain::new_top(ByteSize::new(8)).bin_op(BinOpType::IntOr, &flag(4));
+        let shifted = value.bin_op(BinOpType::IntLeft, &flag(1));
+        assert_eq!(shifted.get_known_bits().ones, bits(8));
+        assert_eq!(shifted.get_known_bits().zeros, bits(1));
+        let shifted = value.bin_op(BinOpType::IntRight, &flag(2));
+        assert_eq!(shifted.get_known_bits().ones, bits(1));
+        assert_eq!(shifted.get_known_bits().zeros, bits(0xc000_0000_0000_0000));
+        assert!(value
+            .bin_op(BinOpType::IntLeft, &IntervalDomain::mock(1, 2))
+            .is_top());
+        // Shifts of single values stay exact.
+        assert_eq!(flag(4).bin_op(BinOpType::IntRight, &flag(2)), flag(1));
+        assert_eq!(flag(-8).bin_op(BinOpType::IntSRight, &flag(1)), flag(-4));
+    }
+
+    #[test]
+    fn refinement_against_interval() {
+        // The common high bits of the interval bounds are known.
+        let known_bits = IntervalDomain::mock(4, 7).get_known_bits();
+        assert_eq!(known_bits.ones, bits(4));
+        assert_eq!(known_bits.zeros, bits(!7));
+        let known_bits = IntervalDomain::mock(-8, -5).get_known_bits();
+        assert_eq!(known_bits.ones, bits(!7));
+        assert_eq!(known_bits.zeros, bits(4));
+        // Bounds with different signs do not imply any known bits.
+        assert!(IntervalDomain::mock(-1, 1).get_known_bits().is_unknown());
+        // The low bits of strides that are powers of two are known.
+        let known_bits = IntervalDomain::mock(1, 13).with_stride(4).get_known_bits();
+        assert_eq!(known_bits.ones, bits(1));
+        assert_eq!(known_bits.zeros, bits(!15 | 2));
+        // Single values have all bits known.
+        assert_eq!(
+            IntervalDomain::mock(5, 5).get_known_bits(),
+            KnownBits::from_value(&bits(5))
+        );
+        // Stored known bits are combined with the bits implied by the interval.
+        let known_bits = IntervalDomain::mock(0, 15)
+            .with_known_bits(1, 2)
+            .get_known_bits();
+        assert_eq!(known_bits.ones, bits(1));
+        assert_eq!(known_bits.zeros, bits(!15 | 2));
+        // Known bits implied by the interval are not stored.
+        assert_eq!(
+            IntervalDomain::mock(0, 15).with_known_bits(0, !15),
+            IntervalDomain::mock(0, 15)
+        );
+        // Bitwise operations refine the interval if the sign bit of the result is known.
+        let masked = IntervalDomain::mock(-100, 100)
+            .with_known_bits(1, 0)
+            .bin_op(BinOpType::IntAnd, &IntervalDomain::mock(0xff, 0xff));
+        assert_eq!(
+            masked,
+            IntervalDomain::mock(1, 0xff).with_known_bits(1, !0xff)
+        );
+    }
+}
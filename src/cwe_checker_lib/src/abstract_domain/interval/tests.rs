@@ -56,6 +56,15 @@ impl IntervalDomain {
         self.interval.adjust_end_to_value_in_stride();
         self
     }
+
+    /// Set the known bits of the interval to the given masks of known set and known unset bits.
+    pub fn with_known_bits(mut self, ones: u64, zeros: u64) -> Self {
+        self.set_known_bits(KnownBits {
+            ones: Bitvector::from_u64(ones).into_zero_resize(self.bytesize()),
+            zeros: Bitvector::from_u64(zeros).into_zero_resize(self.bytesize()),
+        });
+        self
+    }
 }
 
 #[test]
@@ -129,6 +138,48 @@ fn signed_merge() {
     assert_eq!(var, expected_result);
 }
 
+#[test]
+fn widening_thresholds() {
+    let thresholds: Vec<Bitvector> = [-10, 0, 99, 100, 101]
+        .into_iter()
+        .map(Bitvector::from_i64)
+        .chain([Bitvector::from_i32(50)])
+        .collect();
+    // The nearest threshold above the interval becomes the upper widening bound.
+    let mut var = IntervalDomain::mock(0, 5);
+    var.add_widening_thresholds(&thresholds);
+    assert_eq!(var, IntervalDomain::mock_with_bounds(None, 0, 5, Some(99)));
+    // Closer existing widening bounds are kept and no lower widening bound is added.
+    let mut var = IntervalDomain::mock_with_bounds(Some(-3), 0, 5, Some(10));
+    var.add_widening_thresholds(&thresholds);
+    assert_eq!(
+        var,
+        IntervalDomain::mock_with_bounds(Some(-3), 0, 5, Some(10))
+    );
+    // `Top` values get no widening bounds.
+    let mut var = IntervalDomain::new_top(ByteSize::new(8));
+    var.add_widening_thresholds(&thresholds);
+    assert_eq!(var, IntervalDomain::new_top(ByteSize::new(8)));
+
+    // Widening process of a loop counter compared to 100 with `!=`,
+    // where the loop body provides no widening hint.
+    let mut var = IntervalDomain::mock(0, 0);
+    var.add_widening_thresholds(&thresholds);
+    var = var.merge(&IntervalDomain::mock(1, 1));
+    var.add_widening_thresholds(&thresholds);
+    var = var.merge(&IntervalDomain::mock(1, 2));
+    assert_eq!(
+        var,
+        IntervalDomain::mock_with_bounds(None, 0, 99, None).as_freshly_widened()
+    );
+    // The final value of the counter at the loop exit does not cause another widening.
+    var.add_widening_thresholds(&thresholds);
+    var = var.merge(&IntervalDomain::mock(1, 100));
+    let mut expected_result = IntervalDomain::mock(0, 100);
+    expected_result.widening_delay = 99;
+    assert_eq!(var, expected_result);
+}
+
 #[test]
 fn cast_zero_and_signed_extend() {
     // Zero extend
@@ -376,16 +427,17 @@ fn shift_left() {
     let lhs = IntervalDomain::mock_i8_with_bounds(None, 3, 4, Some(5));
     let rhs = IntervalDomain::mock_i8_with_bounds(Some(1), 2, 2, Some(4));
     let result = lhs.bin_op(BinOpType::IntLeft, &rhs);
+    // The low bits shifted in are known to be unset.
     assert_eq!(
         result,
-        IntervalDomain::mock_i8_with_bounds(None, 12, 16, None)
+        IntervalDomain::mock_i8_with_bounds(None, 12, 16, None).with_known_bits(0, 0b1110_0011)
     );
     let lhs = IntervalDomain::mock_i8_with_bounds(Some(2), 3, 4, Some(64));
     let rhs = IntervalDomain::mock_i8_with_bounds(Some(0), 1, 1, Some(4));
     let result = lhs.bin_op(BinOpType::IntLeft, &rhs);
     assert_eq!(
         result,
-        IntervalDomain::mock_i8_with_bounds(None, 6, 8, None)
+        IntervalDomain::mock_i8_with_bounds(None, 6, 8, None).with_known_bits(0, 0b1111_0001)
     );
     let lhs = IntervalDomain::mock_with_bounds(Some(2), 3, 4, Some(64));
     let rhs = IntervalDomain::mock_i8_with_bounds(None, 127, 127, None);
@@ -565,8 +617,12 @@ fn add_not_equal_bounds() {
     );
     let x = interval
         .clone()
-        .add_not_equal_bound(&Bitvector::from_i64(-0));
-    assert_eq!(x.unwrap(), interval);
+        .add_not_equal_bound(&Bitvector::from_i64(-0))
+        .unwrap();
+    assert!(!x.equal_as_value_sets(&interval));
+    assert_eq!(x.get_excluded_values(), &[Bitvector::from_i64(0)]);
+    assert!(!x.contains(&Bitvector::from_i64(0)));
+    assert!(x.contains(&Bitvector::from_i64(1)));
     let x = interval
         .clone()
         .add_not_equal_bound(&Bitvector::from_i64(20));
@@ -592,6 +648,76 @@ fn add_not_equal_bounds() {
     );
 }
 
+#[test]
+fn not_equal_guard_before_division() {
+    let zero = Bitvector::from_i64(0);
+    // `if (x != 0) { y = 100 / x; }`
+    let divisor = IntervalDomain::mock(-10, 10)
+        .add_not_equal_bound(&zero)
+        .unwrap();
+    assert!(!divisor.contains(&zero));
+    assert!(divisor.is_excluded(&zero));
+    assert_eq!(
+        divisor.bin_op(BinOpType::IntEqual, &zero.clone().into()),
+        Bitvector::from_u8(0).into()
+    );
+    // Adding and subtracting constants preserves the exclusion.
+    let shifted = divisor.add(&IntervalDomain::mock(3, 3));
+    assert_eq!(
+        shifted,
+        IntervalDomain::mock(-7, 13)
+            .add_not_equal_bound(&Bitvector::from_i64(3))
+            .unwrap()
+    );
+    let shifted_back = shifted.sub(&IntervalDomain::mock(3, 3));
+    assert!(!shifted_back.contains(&zero));
+    let negated_shift = IntervalDomain::mock(3, 3).sub(&divisor);
+    assert!(!negated_shift.contains(&Bitvector::from_i64(3)));
+    // Exclusions survive sign extension and merging with values not containing the excluded value.
+    let extended = IntervalDomain::mock_i32(-10, 10)
+        .add_not_equal_bound(&Bitvector::from_i32(0))
+        .unwrap()
+        .cast(CastOpType::IntSExt, ByteSize::new(8));
+    assert!(!extended.contains(&zero));
+    let merged = divisor.signed_merge(&IntervalDomain::mock(5, 20));
+    assert!(!merged.contains(&zero));
+    let merged = divisor.signed_merge(&IntervalDomain::mock(0, 0));
+    assert!(merged.contains(&zero));
+    // Excluding interval bounds shrinks the interval.
+    let shrunk = divisor
+        .clone()
+        .add_signed_greater_equal_bound(&Bitvector::from_i64(0))
+        .unwrap();
+    assert_eq!(shrunk, IntervalDomain::mock(1, 10));
+    // The number of excluded values is bounded.
+    let mut interval = IntervalDomain::mock(-10, 10);
+    for value in 1..=(MAX_EXCLUDED_VALUES as i64 + 2) {
+        interval = interval
+            .add_not_equal_bound(&Bitvector::from_i64(value))
+            .unwrap();
+    }
+    assert_eq!(interval.get_excluded_values().len(), MAX_EXCLUDED_VALUES);
+}
+
+#[test]
+fn exclusions_are_lost_on_multiplication() {
+    let zero = Bitvector::from_i64(0);
+    let interval = IntervalDomain::mock(-10, 10)
+        .add_not_equal_bound(&zero)
+        .unwrap();
+    let product = interval.bin_op(BinOpType::IntMult, &IntervalDomain::mock(2, 2));
+    assert_eq!(product, IntervalDomain::mock(-20, 20));
+    assert!(product.contains(&zero));
+    assert!(product.get_excluded_values().is_empty());
+    let top = IntervalDomain::new_top(ByteSize::new(8))
+        .add_not_equal_bound(&zero)
+        .unwrap();
+    assert!(!top.is_top());
+    assert!(top
+        .bin_op(BinOpType::IntMult, &IntervalDomain::mock(3, 3))
+        .is_top());
+}
+
 #[test]
 fn intersection() {
     let interval1 = IntervalDomain::mock_with_bounds(Some(-100), -10, 10, Some(100));
@@ -660,3 +786,67 @@ fn stride_rounding() {
         None
     );
 }
+
+#[test]
+fn known_bits_of_or_chain() {
+    let bits = |value: u64| Bitvector::from_u64(value);
+    let flag = |value: i64| IntervalDomain::mock(value, value);
+    // OR-ing flags onto an unknown value
+    let flags = IntervalDomain::new_top(ByteSize::new(8))
+        .bin_op(BinOpType::IntOr, &flag(2))
+        .bin_op(BinOpType::IntOr, &flag(4));
+    assert!(!flags.is_top());
+    assert_eq!(flags.get_known_bits().ones, bits(6));
+    assert_eq!(flags.get_known_bits().zeros, bits(0));
+    // Clearing a flag again
+    let cleared = flags.bin_op(BinOpType::IntAnd, &flag(!2));
+    assert_eq!(cleared.get_known_bits().ones, bits(4));
+    assert_eq!(cleared.get_known_bits().zeros, bits(2));
+    // Toggling a flag
+    let toggled = flags.bin_op(BinOpType::IntXOr, &flag(6));
+    assert_eq!(toggled.get_known_bits().ones, bits(0));
+    assert_eq!(toggled.get_known_bits().zeros, bits(6));
+    // If the sign bit is known, the interval is bounded by the known bits.
+    let masked = IntervalDomain::new_top(ByteSize::new(8))
+        .bin_op(BinOpType::IntAnd, &flag(3))
+        .bin_op(BinOpType::IntOr, &flag(4));
+    assert_eq!(masked, IntervalDomain::mock(4, 7));
+    // If all bits are known, the result is exact.
+    assert_eq!(masked.bin_op(BinOpType::IntAnd, &flag(4)), flag(4));
+}
+
+#[test]
+fn known_bits_of_merged_flags() {
+    let bits = |value: u64| Bitvector::from_u64(value);
+    let flag = |value: i64| IntervalDomain::mock(value, value);
+    let merged = flag(2).merge(&flag(7));
+    assert_eq!(
+        merged,
+        IntervalDomain::mock(2, 7)
+            .with_stride(5)
+            .with_known_bits(2, !7)
+    );
+    let flags = merged.bin_op(BinOpType::IntOr, &flag(4));
+    assert_eq!(flags.get_known_bits().ones, bits(6));
+    assert_eq!(flags.get_known_bits().zeros, bits(!7));
+    // Bits set in only one of the merged values are unknown.
+    let flags = flag(1).merge(&flag(3)).bin_op(BinOpType::IntOr, &flag(4));
+    assert_eq!(flags.get_known_bits().ones, bits(5));
+    assert_eq!(flags.get_known_bits().zeros, bits(!7));
+    // Known bits implied by the interval are not stored explicitly.
+    assert_eq!(flag(4).merge(&flag(5)), IntervalDomain::mock(4, 5));
+    // Zero extension and subpieces preserve known bits.
+    let flags32 = IntervalDomain::new_top(ByteSize::new(4)).bin_op(
+        BinOpType::IntOr,
+        &IntervalDomain::from(Bitvector::from_u32(6)),
+    );
+    let extended = flags32.cast(CastOpType::IntZExt, ByteSize::new(8));
+    assert_eq!(extended.get_known_bits().ones, bits(6));
+    assert_eq!(extended.get_known_bits().zeros, bits(0xffff_ffff_0000_0000));
+    assert_eq!(
+        extended
+            .subpiece(ByteSize::new(0), ByteSize::new(4))
+            .get_known_bits(),
+        flags32.get_known_bits()
+    );
+}
@@ -212,6 +212,55 @@ impl<T: AbstractDomain + SizedDomain + HasTop + std::fmt::Debug> MemRegion<T> {
         self.merge_values_intersecting_range_with_top(start, end + u64::from(elem_size) as i64)
     }
 
+    /// Emulate write operations to one field of each element of an array of structs
+    /// by merging all values intersecting one of the fields with `Top`.
+    ///
+    /// The fields have the byte size `field_size` and start at the offsets `start + k * stride`
+    /// for all `k` from zero to `count - 1`, or for all `k >= 0` if the `count` is unknown.
+    /// A `stride` of zero denotes a single field at the offset `start`.
+    /// Values between the fields are not changed.
+    pub fn mark_strided_values_as_top(
+        &mut self,
+        start: i64,
+        field_size: ByteSize,
+        stride: u64,
+        count: Option<u64>,
+    ) {
+        let field_size = u64::from(field_size) as i64;
+        if count == Some(0) {
+            return;
+        }
+        if stride == 0 || count == Some(1) {
+            self.merge_values_intersecting_range_with_top(start, start + field_size);
+            return;
+        }
+        let stride = stride as i64;
+        let last_field_start = count.map(|count| {
+            start.saturating_add((count as i64).saturating_sub(1).saturating_mul(stride))
+        });
+        let intersects_field = |position: i64, size: i64| {
+            // The index of the first field ending after the start of the value
+            let index = ((position - start - field_size).div_euclid(stride) + 1).max(0);
+            let field_start = start.saturating_add(index.saturating_mul(stride));
+            field_start < position + size
+                && last_field_start.is_none_or(|last_start| field_start <= last_start)
+        };
+        let inner = Arc::make_mut(&mut self.inner);
+        let intersecting_elements: Vec<_> = inner
+            .values
+            .iter()
+            .filter(|(pos, elem)| intersects_field(**pos, u64::from(elem.bytesize()) as i64))
+            .map(|(pos, elem)| (*pos, elem.merge(&elem.top())))
+            .collect();
+        for (index, merged_value) in intersecting_elements {
+            if merged_value.is_top() {
+                inner.values.remove(&index);
+            } else {
+                inner.values.insert(index, merged_value);
+            }
+        }
+    }
+
     /// Merge all values intersecting the given range with `Top`.
     /// If `Top` is a maximal element of the value abstract domain,
     /// this effectively removes all values intersecting the range.
@@ -332,6 +381,18 @@ impl<T: AbstractDomain + SizedDomain + HasTop + std::fmt::Debug> MemRegion<T> {
         Arc::make_mut(&mut self.inner).values.values_mut()
     }
 
+    /// Add the given offset to the indices of all values contained in the memory region.
+    pub fn add_offset_to_all_indices(&mut self, offset: i64) {
+        if offset == 0 {
+            return;
+        }
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.values = std::mem::take(&mut inner.values)
+            .into_iter()
+            .map(|(index, value)| (index + offset, value))
+            .collect();
+    }
+
     /// Remove all values representing the *Top* element from the internal value store,
     /// as these should not be saved in the internal representation.
     pub fn clear_top_values(&mut self) {
@@ -222,3 +222,49 @@ fn merge_writes_with_top() {
     assert_eq!(region.get_unsized(bv(8)), Some(data_with_top.clone()));
     assert_eq!(region.get_unsized(bv(16)), Some(data_with_top.clone()));
 }
+
+#[test]
+fn strided_writes_with_top() {
+    let mut region: MemRegion<MockDomain> = MemRegion::new(ByteSize::new(8));
+    // An array of three 8-byte structs with two 4-byte fields each
+    for index in 0..6 {
+        region.add(mock(index + 1, 4u64), bv(-16 + 4 * index));
+    }
+    // Only the second field of the first two structs is written.
+    region.mark_strided_values_as_top(-12, ByteSize::new(4), 8, Some(2));
+    assert_eq!(region.get(bv(-16), ByteSize::new(4)), mock(1, 4u64));
+    assert_eq!(region.get_unsized(bv(-12)), None);
+    assert_eq!(region.get(bv(-8), ByteSize::new(4)), mock(3, 4u64));
+    assert_eq!(region.get_unsized(bv(-4)), None);
+    assert_eq!(region.get(bv(0), ByteSize::new(4)), mock(5, 4u64));
+    assert_eq!(region.get(bv(4), ByteSize::new(4)), mock(6, 4u64));
+    // Values partially overlapping a field are also written.
+    region.add(mock(7, 8u64), bv(12));
+    region.mark_strided_values_as_top(-10, ByteSize::new(2), 8, None);
+    assert_eq!(region.get(bv(-16), ByteSize::new(4)), mock(1, 4u64));
+    assert_eq!(region.get(bv(-8), ByteSize::new(4)), mock(3, 4u64));
+    assert_eq!(region.get(bv(0), ByteSize::new(4)), mock(5, 4u64));
+    assert_eq!(region.get_unsized(bv(4)), None);
+    assert_eq!(region.get_unsized(bv(12)), None);
+    // A zero stride denotes a single field.
+    region.mark_strided_values_as_top(-16, ByteSize::new(4), 0, None);
+    assert_eq!(region.get_unsized(bv(-16)), None);
+    assert_eq!(region.get(bv(-8), ByteSize::new(4)), mock(3, 4u64));
+}
+
+#[test]
+fn add_offset_to_indices() {
+    let mut region: MemRegion<MockDomain> = MemRegion::new(ByteSize::new(8));
+    region.add(mock(1, 8), Bitvector::from_i64(-8));
+    region.add(mock(2, 8), Bitvector::from_i64(0));
+    region.add_offset_to_all_indices(16);
+    assert_eq!(
+        region.get(Bitvector::from_i64(8), ByteSize::new(8)),
+        mock(1, 8)
+    );
+    assert_eq!(
+        region.get(Bitvector::from_i64(16), ByteSize::new(8)),
+        mock(2, 8)
+    );
+    assert_eq!(region.iter().count(), 2);
+}
@@ -2,6 +2,16 @@
 pub trait DomainInsertion {
     /// Inserts a string domain at a certain position if order is considered.
     fn append_string_domain(&self, string_domain: &Self) -> Self;
+    /// Returns the domain of the string after `string_domain` was copied into it
+    /// at the given positive character offset, i.e. the concatenation of the prefix
+    /// up to the offset with the inserted string.
+    fn insert_string_domain_at_offset(&self, offset: usize, string_domain: &Self) -> Self;
+    /// Returns the domain of the string after an unknown string was copied into it
+    /// at an unknown offset not smaller than `min_offset`.
+    /// Only the suffix starting at `min_offset` is widened to *Top*.
+    fn widen_suffix_from_offset(&self, min_offset: usize) -> Self;
+    /// Returns a lower bound for the number of characters of the represented strings.
+    fn get_minimal_length(&self) -> usize;
     /// Creates a string domain with characters that usually appear in an integer value.
     fn create_integer_domain() -> Self;
     /// Creates a string domain with characters that usually appear in a char value.
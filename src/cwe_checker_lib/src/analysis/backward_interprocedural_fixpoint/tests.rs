@@ -90,6 +90,7 @@ fn mock_program() -> Term<Program> {
             name: "sub1".to_string(),
             blocks: vec![sub1_blk1, sub1_blk2],
             calling_convention: None,
+            is_thunk: false,
         },
     };
     let cond_jump = Jmp::CBranch {
@@ -126,6 +127,7 @@ fn mock_program() -> Term<Program> {
             name: "sub2".to_string(),
             blocks: vec![sub2_blk1, sub2_blk2],
             calling_convention: None,
+            is_thunk: false,
         },
     };
     let program = Term {
@@ -135,6 +137,8 @@ fn mock_program() -> Term<Program> {
             extern_symbols: BTreeMap::new(),
             entry_points: BTreeSet::new(),
             address_base_offset: 0,
+            symbol_aliases: BTreeMap::new(),
+            non_returning_subs: BTreeSet::new(),
         },
     };
     program
@@ -145,6 +149,7 @@ fn backward_fixpoint() {
     let project = Project {
         program: mock_program(),
         cpu_architecture: String::from("x86_64"),
+        architecture: Arch::X86_64,
         stack_pointer_register: Variable {
             name: String::from("RSP"),
             size: ByteSize::new(8),
@@ -153,6 +158,8 @@ fn backward_fixpoint() {
         calling_conventions: BTreeMap::new(),
         register_set: BTreeSet::new(),
         datatype_properties: DatatypeProperties::mock(),
+        stack_canary_artifacts: BTreeSet::new(),
+        duplicated_term_origins: BTreeMap::new(),
     };
 
     let mock_con = Context::new(&project);
@@ -0,0 +1,826 @@
+//! Backward slicing from a program point.
+//!
+//! A backward slice for a register at a program point is the set of terms
+//! that may contribute to the value of the register at that point.
+//! It answers questions like "where did this size value come from?" during the triage of findings.
+//!
+//! The slice is computed by following the definitions of registers backwards through the control flow.
+//! Dependencies through memory are tracked with the help of the pointer inference results:
+//! The target address of a `Load` is compared with the target addresses of preceding `Store` instructions
+//! and stores that (may) write to the loaded memory are added to the slice.
+//! The precision of this alias check is configurable (see [`AliasPrecision`]).
+//!
+//! Values are tracked across call boundaries:
+//! Return values of internal functions are traced into the returning blocks of the callee,
+//! return values of extern functions depend on the parameters of the call,
+//! and values at the start of a function are traced to all callsites of the function.
+//! Note that the slicing is context-insensitive, i.e. a value traced into a callee
+//! may be traced back to callsites of the callee other than the one it was entered from.
+//!
+//! The length of dependency chains is bounded by a configurable depth.
+//! If the bound is hit, the slice is marked as truncated.
+
+use crate::abstract_domain::{
+    AbstractDomain, AbstractIdentifier, IntervalDomain, TryToBitvec, TryToInterval,
+};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::IntoNodeReferences;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The precision of the alias check between the target addresses of loads and stores.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum AliasPrecision {
+    /// Only stores that write exactly the loaded memory are added to the slice.
+    Exact,
+    /// All stores that may overlap the loaded memory are added to the slice.
+    #[default]
+    MayAlias,
+}
+
+/// The configuration of the backward slicing.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct SliceConfig {
+    /// The maximal length of dependency chains that are followed,
+    /// i.e. the maximal number of terms of the slice on a path from the start of the slice.
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+    /// The precision of the alias check for dependencies through memory.
+    #[serde(default)]
+    pub alias_precision: AliasPrecision,
+}
+
+impl Default for SliceConfig {
+    fn default() -> Self {
+        SliceConfig {
+            max_depth: default_max_depth(),
+            alias_precision: AliasPrecision::default(),
+        }
+    }
+}
+
+/// The default for the maximal length of dependency chains.
+fn default_max_depth() -> usize {
+    20
+}
+
+/// A backward slice, i.e. the terms that may contribute to a value at some program point.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct Slice {
+    /// Maps the TIDs of functions to the TIDs of the terms of the slice contained in the function.
+    /// The terms of a function are ordered by their position in the function.
+    pub functions: BTreeMap<Tid, Vec<Tid>>,
+    /// Set if some dependency chains were not followed completely because of the depth bound.
+    pub truncated: bool,
+}
+
+impl Slice {
+    /// Returns `true` if the term with the given TID is contained in the slice.
+    pub fn contains(&self, tid: &Tid) -> bool {
+        self.functions.values().any(|terms| terms.contains(tid))
+    }
+
+    /// Get the TIDs of all terms of the slice.
+    pub fn get_all_terms(&self) -> Vec<&Tid> {
+        self.functions.values().flatten().collect()
+    }
+
+    /// Generate a human-readable representation of the slice
+    /// listing the terms of the slice per function together with their addresses.
+    pub fn format(&self, project: &Project) -> String {
+        let mut terms: HashMap<&Tid, String> = HashMap::new();
+        for sub in project.program.term.subs.values() {
+            for block in sub.term.blocks.iter() {
+                for def in block.term.defs.iter() {
+                    terms.insert(&def.tid, format!("{:?}", def.term));
+                }
+                for jmp in block.term.jmps.iter() {
+                    terms.insert(&jmp.tid, format!("{:?}", jmp.term));
+                }
+            }
+        }
+        let mut output = String::new();
+        for (sub_tid, slice_terms) in self.functions.iter() {
+            let sub_name = project
+                .program
+                .term
+                .subs
+                .get(sub_tid)
+                .map(|sub| sub.term.name.as_str())
+                .unwrap_or("<unknown>");
+            output += &format!("Function {} ({}):\n", sub_name, sub_tid);
+            for tid in slice_terms {
+                output += &format!(
+                    "  {}: {}\n",
+                    tid.address,
+                    terms
+                        .get(tid)
+                        .map(String::as_str)
+                        .unwrap_or("<unknown term>")
+                );
+            }
+        }
+        if self.truncated {
+            output += "The slice was truncated because of the depth bound.\n";
+        }
+        output
+    }
+}
+
+/// Compute the backward slice for the value of the given register
+/// just before the execution of the term with TID `start_tid`,
+/// using the default [`SliceConfig`].
+///
+/// Returns an error if no term with the given TID exists.
+pub fn slice(
+    project: &Project,
+    pi_results: &PointerInference,
+    start_tid: &Tid,
+    variable: &Variable,
+) -> Result<Slice, Error> {
+    slice_with_config(
+        project,
+        pi_results,
+        start_tid,
+        variable,
+        &SliceConfig::default(),
+    )
+}
+
+/// Compute the backward slice for the value of the given register
+/// just before the execution of the term with TID `start_tid`.
+///
+/// Returns an error if no term with the given TID exists.
+pub fn slice_with_config(
+    project: &Project,
+    pi_results: &PointerInference,
+    start_tid: &Tid,
+    variable: &Variable,
+    config: &SliceConfig,
+) -> Result<Slice, Error> {
+    let start_position = find_term_position(project, start_tid)
+        .ok_or_else(|| anyhow!("No term with TID {} found.", start_tid))?;
+    let mut slicer = Slicer::new(project, pi_results, config);
+    slicer.push(start_position, SliceValue::Register(variable.clone()), 0);
+    while let Some(item) = slicer.worklist.pop() {
+        slicer.process(item);
+    }
+    Ok(slicer.into_slice())
+}
+
+/// Get the TID of the first term (in the order of the functions, blocks and terms of the program)
+/// whose TID has the given address.
+pub fn get_first_term_at_address(project: &Project, address: u64) -> Option<&Tid> {
+    let has_address = |tid: &Tid| u64::from_str_radix(tid.canonical_address(), 16) == Ok(address);
+    project
+        .program
+        .term
+        .subs
+        .values()
+        .flat_map(|sub| sub.term.blocks.iter())
+        .flat_map(|block| {
+            block
+                .term
+                .defs
+                .iter()
+                .map(|def| &def.tid)
+                .chain(block.term.jmps.iter().map(|jmp| &jmp.tid))
+        })
+        .find(|tid| has_address(tid))
+}
+
+/// A value whose origin is traced by the slicing.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+enum SliceValue {
+    /// The value of a register.
+    Register(Variable),
+    /// The value in memory at the given address (as computed by the pointer inference).
+    Memory {
+        /// The address of the value.
+        address: Data,
+        /// The size of the value.
+        size: ByteSize,
+    },
+}
+
+/// A program point inside a function.
+///
+/// The position denotes the point just before the `Def` with index `def_index` of the block.
+/// If the index equals the number of `Def`s of the block, the position is just before the jumps of the block.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+struct Position<'a> {
+    /// The function containing the position.
+    sub: &'a Term<Sub>,
+    /// The index of the block inside the function.
+    block_index: usize,
+    /// The index of the `Def` inside the block.
+    def_index: usize,
+}
+
+impl<'a> Position<'a> {
+    /// Get the block of the position.
+    fn get_block(&self) -> &'a Term<Blk> {
+        &self.sub.term.blocks[self.block_index]
+    }
+
+    /// Get the position at the end of the block with the given index inside the same function.
+    fn end_of_block(sub: &'a Term<Sub>, block_index: usize) -> Position<'a> {
+        Position {
+            sub,
+            block_index,
+            def_index: sub.term.blocks[block_index].term.defs.len(),
+        }
+    }
+}
+
+/// An entry of the worklist of the slicing.
+struct WorklistItem<'a> {
+    /// The position where the value is traced from.
+    position: Position<'a>,
+    /// The traced value.
+    value: SliceValue,
+    /// The length of the dependency chain leading to the value.
+    depth: usize,
+}
+
+/// The result of the alias check between a store and a load.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum AliasKind {
+    /// The store does not write to the loaded memory.
+    No,
+    /// The store may write to (parts of) the loaded memory.
+    May,
+    /// The store writes exactly to the loaded memory.
+    Must,
+}
+
+/// The state of a slice computation.
+struct Slicer<'a, 'b> {
+    project: &'a Project,
+    pi_results: &'a PointerInference<'b>,
+    config: &'a SliceConfig,
+    /// Maps pairs of block and function TIDs to the corresponding block start nodes of the control flow graph.
+    block_start_nodes: HashMap<(&'a Tid, &'a Tid), NodeIndex>,
+    /// Maps function TIDs to the callsites of the function given by the calling function and the block index.
+    callsites: HashMap<&'a Tid, Vec<(&'a Term<Sub>, usize)>>,
+    worklist: Vec<WorklistItem<'a>>,
+    visited: HashSet<(Position<'a>, SliceValue)>,
+    /// The terms of the slice given by the function, the block index, the index of the term inside the block and its TID.
+    terms: BTreeSet<(&'a Tid, usize, usize, &'a Tid)>,
+    truncated: bool,
+}
+
+impl<'a, 'b> Slicer<'a, 'b> {
+    /// Generate a new slicer with an empty worklist.
+    fn new(
+        project: &'a Project,
+        pi_results: &'a PointerInference<'b>,
+        config: &'a SliceConfig,
+    ) -> Slicer<'a, 'b> {
+        let block_start_nodes = pi_results
+            .get_graph()
+            .node_references()
+            .filter_map(|(node_id, node)| match node {
+                Node::BlkStart(block, sub) => Some(((&block.tid, &sub.tid), node_id)),
+                _ => None,
+            })
+            .collect();
+        let mut callsites: HashMap<&Tid, Vec<(&Term<Sub>, usize)>> = HashMap::new();
+        for sub in project.program.term.subs.values() {
+            for (block_index, block) in sub.term.blocks.iter().enumerate() {
+                for jmp in block.term.jmps.iter() {
+                    if let Jmp::Call { target, .. } = &jmp.term {
+                        callsites
+                            .entry(target)
+                            .or_default()
+                            .push((sub, block_index));
+                    }
+                }
+            }
+        }
+        Slicer {
+            project,
+            pi_results,
+            config,
+            block_start_nodes,
+            callsites,
+            worklist: Vec::new(),
+            visited: HashSet::new(),
+            terms: BTreeSet::new(),
+            truncated: false,
+        }
+    }
+
+    /// Add the value at the given position to the worklist if it was not already visited
+    /// and if the depth bound is not exceeded.
+    fn push(&mut self, position: Position<'a>, value: SliceValue, depth: usize) {
+        if depth >= self.config.max_depth {
+            self.truncated = true;
+            return;
+        }
+        if self.visited.insert((position, value.clone())) {
+            self.worklist.push(WorklistItem {
+                position,
+                value,
+                depth,
+            });
+        }
+    }
+
+    /// Add the registers read by the given expression at the given position to the worklist.
+    fn push_input_registers(
+        &mut self,
+        position: Position<'a>,
+        expression: &Expression,
+        depth: usize,
+    ) {
+        for var in expression.input_vars() {
+            self.push(position, SliceValue::Register(var.clone()), depth);
+        }
+    }
+
+    /// Add the term with the given index inside the block at the given position to the slice.
+    fn add_term(&mut self, position: Position<'a>, term_index: usize, tid: &'a Tid) {
+        self.terms
+            .insert((&position.sub.tid, position.block_index, term_index, tid));
+    }
+
+    /// Get the pointer inference state just before the `Def` at the given position.
+    fn get_state(&self, position: Position<'a>) -> Option<State> {
+        let block = position.get_block();
+        let node = self
+            .block_start_nodes
+            .get(&(&block.tid, &position.sub.tid))?;
+        let mut state = match self.pi_results.get_node_value(*node)? {
+            NodeValue::Value(state) => state.clone(),
+            NodeValue::CallFlowCombinator { .. } => return None,
+        };
+        state.apply_defs(
+            &block.term.defs[..position.def_index],
+            self.pi_results.get_context().runtime_memory_image,
+        );
+        Some(state)
+    }
+
+    /// Evaluate the given address expression just before the `Def` at the given position.
+    fn eval_address(&self, position: Position<'a>, address: &Expression) -> Option<Data> {
+        let address = self.get_state(position)?.eval(address);
+        if address.is_top() {
+            None
+        } else {
+            Some(address)
+        }
+    }
+
+    /// Trace the value of the worklist item backwards through its block
+    /// and add the values that it depends on to the worklist.
+    fn process(&mut self, item: WorklistItem<'a>) {
+        let WorklistItem {
+            position,
+            value,
+            depth,
+        } = item;
+        let block = position.get_block();
+        for def_index in (0..position.def_index).rev() {
+            let def = &block.term.defs[def_index];
+            let def_position = Position {
+                def_index,
+                ..position
+            };
+            match (&value, &def.term) {
+                (SliceValue::Register(register), Def::Assign { var, value }) if var == register => {
+                    self.add_term(position, def_index, &def.tid);
+                    self.push_input_registers(def_position, value, depth + 1);
+                    return;
+                }
+                (SliceValue::Register(register), Def::Load { var, address }) if var == register => {
+                    self.add_term(position, def_index, &def.tid);
+                    self.push_input_registers(def_position, address, depth + 1);
+                    if let Some(address) = self.eval_address(def_position, address) {
+                        let memory_value = SliceValue::Memory {
+                            address,
+                            size: var.size,
+                        };
+                        self.push(def_position, memory_value, depth + 1);
+                    }
+                    return;
+                }
+                (
+                    SliceValue::Memory { address, size },
+                    Def::Store {
+                        address: store_address,
+                        value: stored_value,
+                    },
+                ) => {
+                    let alias_kind = match self.eval_address(def_position, store_address) {
+                        Some(store_address) => {
+                            get_alias_kind(&store_address, stored_value.bytesize(), address, *size)
+                        }
+                        None => AliasKind::No,
+                    };
+                    match (alias_kind, self.config.alias_precision) {
+                        (AliasKind::Must, _) => {
+                            self.add_term(position, def_index, &def.tid);
+                            self.push_input_registers(def_position, stored_value, depth + 1);
+                            return;
+                        }
+                        (AliasKind::May, AliasPrecision::MayAlias) => {
+                            self.add_term(position, def_index, &def.tid);
+                            self.push_input_registers(def_position, stored_value, depth + 1);
+                        }
+                        _ => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+        self.trace_to_predecessors(position, value, depth);
+    }
+
+    /// Add the value at the start of the block of the given position
+    /// to the worklist at the ends of all predecessor blocks, including callsites of the function.
+    fn trace_to_predecessors(&mut self, position: Position<'a>, value: SliceValue, depth: usize) {
+        let sub = position.sub;
+        let block_tid = &position.get_block().tid;
+        for (pred_index, pred_block) in sub.term.blocks.iter().enumerate() {
+            for (jmp_index, jmp) in pred_block.term.jmps.iter().enumerate() {
+                let pred_position = Position::end_of_block(sub, pred_index);
+                match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } if target == block_tid => {
+                        self.push(pred_position, value.clone(), depth);
+                    }
+                    Jmp::Call {
+                        target,
+                        return_: Some(return_target),
+                    } if return_target == block_tid => {
+                        let term_index = pred_block.term.defs.len() + jmp_index;
+                        self.trace_through_call(
+                            pred_position,
+                            term_index,
+                            jmp,
+                            target,
+                            &value,
+                            depth,
+                        );
+                    }
+                    Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    }
+                    | Jmp::CallOther {
+                        return_: Some(return_target),
+                        ..
+                    } if return_target == block_tid => {
+                        self.push(pred_position, value.clone(), depth);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        if position.block_index == 0 {
+            let callsites = self.callsites.get(&sub.tid).cloned().unwrap_or_default();
+            for (caller, block_index) in callsites {
+                self.push(
+                    Position::end_of_block(caller, block_index),
+                    value.clone(),
+                    depth,
+                );
+            }
+        }
+    }
+
+    /// Trace the value at the return site of the given call backwards through the call.
+    ///
+    /// The `call_position` is the position at the end of the block containing the call
+    /// and `term_index` the index of the call inside the block.
+    fn trace_through_call(
+        &mut self,
+        call_position: Position<'a>,
+        term_index: usize,
+        call: &'a Term<Jmp>,
+        target: &'a Tid,
+        value: &SliceValue,
+        depth: usize,
+    ) {
+        if let Some(extern_symbol) = self.project.program.term.extern_symbols.get(target) {
+            let return_registers = match extern_symbol.get_unique_return_register() {
+                Ok(register) => vec![register],
+                Err(_) => self
+                    .project
+                    .get_calling_convention(extern_symbol)
+                    .integer_return_register
+                    .iter()
+                    .collect(),
+            };
+            match value {
+                SliceValue::Register(register) if return_registers.contains(&register) => {
+                    // The return value depends on the parameters of the extern call.
+                    self.add_term(call_position, term_index, &call.tid);
+                    for parameter in extern_symbol.parameters.iter() {
+                        if let Arg::Register { expr, .. } = parameter {
+                            self.push_input_registers(call_position, expr, depth + 1);
+                        }
+                    }
+                }
+                _ => self.push(call_position, value.clone(), depth),
+            }
+        } else if let Some(callee) = self.project.program.term.subs.get(target) {
+            for (block_index, block) in callee.term.blocks.iter().enumerate() {
+                if block
+                    .term
+                    .jmps
+                    .iter()
+                    .any(|jmp| matches!(jmp.term, Jmp::Return(_)))
+                {
+                    self.push(
+                        Position::end_of_block(callee, block_index),
+                        value.clone(),
+                        depth,
+                    );
+                }
+            }
+        } else {
+            self.push(call_position, value.clone(), depth);
+        }
+    }
+
+    /// Convert the collected terms into the slice.
+    fn into_slice(self) -> Slice {
+        let mut functions: BTreeMap<Tid, Vec<Tid>> = BTreeMap::new();
+        for (sub_tid, _, _, tid) in self.terms {
+            functions
+                .entry(sub_tid.clone())
+                .or_default()
+                .push(tid.clone());
+        }
+        Slice {
+            functions,
+            truncated: self.truncated,
+        }
+    }
+}
+
+/// Find the position of the term with the given TID.
+/// For jumps the position at the end of their block is returned.
+fn find_term_position<'a>(project: &'a Project, tid: &Tid) -> Option<Position<'a>> {
+    for sub in project.program.term.subs.values() {
+        for (block_index, block) in sub.term.blocks.iter().enumerate() {
+            if let Some(def_index) = block.term.defs.iter().position(|def| def.tid == *tid) {
+                return Some(Position {
+                    sub,
+                    block_index,
+                    def_index,
+                });
+            }
+            if block.term.jmps.iter().any(|jmp| jmp.tid == *tid) {
+                return Some(Position::end_of_block(sub, block_index));
+            }
+        }
+    }
+    None
+}
+
+/// Check whether a store of `store_size` bytes to `store_address`
+/// writes to the memory read by a load of `load_size` bytes from `load_address`.
+fn get_alias_kind(
+    store_address: &Data,
+    store_size: ByteSize,
+    load_address: &Data,
+    load_size: ByteSize,
+) -> AliasKind {
+    let exact_target = |address: &Data| -> Option<(Option<AbstractIdentifier>, i64)> {
+        if let Some(value) = address.get_if_absolute_value() {
+            Some((None, value.try_to_offset().ok()?))
+        } else {
+            let (id, offset) = address.get_if_unique_target()?;
+            Some((Some(id.clone()), offset.try_to_offset().ok()?))
+        }
+    };
+    if store_size == load_size {
+        if let (Some(store_target), Some(load_target)) =
+            (exact_target(store_address), exact_target(load_address))
+        {
+            if store_target == load_target {
+                return AliasKind::Must;
+            }
+        }
+    }
+    let overlaps = |store_offset: &IntervalDomain, load_offset: &IntervalDomain| -> bool {
+        match (
+            store_offset.try_to_offset_interval(),
+            load_offset.try_to_offset_interval(),
+        ) {
+            (Ok((store_start, store_end)), Ok((load_start, load_end))) => {
+                store_start < load_end + u64::from(load_size) as i64
+                    && load_start < store_end + u64::from(store_size) as i64
+            }
+            _ => true,
+        }
+    };
+    let relative_overlap = store_address
+        .get_relative_values()
+        .iter()
+        .any(|(id, store_offset)| {
+            load_address
+                .get_relative_values()
+                .get(id)
+                .is_some_and(|load_offset| overlaps(store_offset, load_offset))
+        });
+    let absolute_overlap = match (
+        store_address.get_absolute_value(),
+        load_address.get_absolute_value(),
+    ) {
+        (Some(store_offset), Some(load_offset)) => overlaps(store_offset, load_offset),
+        _ => false,
+    };
+    if relative_overlap || absolute_overlap {
+        AliasKind::May
+    } else {
+        AliasKind::No
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::AbstractLocation;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    /// A project with a function `main` calling a function `callee`.
+    ///
+    /// The value of `RSI` before the def `final` flows through a register, a stack slot
+    /// and the return value of `callee`, which depends on the parameter `RDI` set in `main`.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+
+        let mut callee_blk = Blk::mock_with_tid("callee_blk");
+        callee_blk.term.defs = vec![
+            Def::assign(
+                "callee_mult",
+                Variable::mock("RAX", 8),
+                Expression::BinOp {
+                    op: BinOpType::IntMult,
+                    lhs: Box::new(Expression::var("RDI", 8)),
+                    rhs: Box::new(Expression::const_from_i64(4)),
+                },
+            ),
+            Def::assign(
+                "callee_unrelated",
+                Variable::mock("RCX", 8),
+                Expression::const_from_i64(1),
+            ),
+            // Pop the return address from the stack.
+            Def::assign(
+                "callee_pop",
+                Variable::mock("RSP", 8),
+                Expression::var("RSP", 8).plus_const(8),
+            ),
+        ];
+        callee_blk.term.jmps.push(Term {
+            tid: Tid::new("callee_return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        let callee = Sub::mock_with_blocks("callee", vec![callee_blk]);
+
+        let mut main_blk = Blk::mock_with_tid("main_blk");
+        main_blk.term.defs = vec![
+            Def::assign(
+                "set_rdi",
+                Variable::mock("RDI", 8),
+                Expression::const_from_i64(3),
+            ),
+            Def::assign(
+                "unrelated",
+                Variable::mock("RDX", 8),
+                Expression::const_from_i64(5),
+            ),
+        ];
+        main_blk
+            .term
+            .jmps
+            .push(Jmp::call("call_callee", "callee", Some("main_2")));
+        let mut main_2 = Blk::mock_with_tid("main_2");
+        let stack_slot = Expression::var("RSP", 8).plus_const(-8);
+        main_2.term.defs = vec![
+            Def::store("store_stack", stack_slot.clone(), Expression::var("RAX", 8)),
+            Def::assign(
+                "overwrite",
+                Variable::mock("RAX", 8),
+                Expression::const_from_i64(0),
+            ),
+            Def::load("load_stack", Variable::mock("RCX", 8), stack_slot),
+            Def::assign(
+                "use",
+                Variable::mock("RSI", 8),
+                Expression::var("RCX", 8).plus_const(1),
+            ),
+            Def::assign("final", Variable::mock("RDX", 8), Expression::var("RSI", 8)),
+        ];
+        main_2.term.jmps.push(Term {
+            tid: Tid::new("main_return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        let main = Sub::mock_with_blocks("main", vec![main_blk, main_2]);
+
+        project.program.term.entry_points.insert(main.tid.clone());
+        project.program.term.subs.insert(main.tid.clone(), main);
+        project.program.term.subs.insert(callee.tid.clone(), callee);
+        project
+    }
+
+    #[test]
+    fn slice_through_register_stack_and_call() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let mut pi_results = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pi_results.compute();
+
+        let slice = slice(
+            &project,
+            &pi_results,
+            &Tid::new("final"),
+            &Variable::mock("RSI", 8),
+        )
+        .unwrap();
+        assert!(!slice.truncated);
+        assert_eq!(
+            slice.functions[&Tid::new("main")],
+            vec![
+                Tid::new("set_rdi"),
+                Tid::new("store_stack"),
+                Tid::new("load_stack"),
+                Tid::new("use"),
+            ]
+        );
+        // The stack pointer used for the stack slot access depends on the stack adjustment in the callee.
+        assert_eq!(
+            slice.functions[&Tid::new("callee")],
+            vec![Tid::new("callee_mult"), Tid::new("callee_pop")]
+        );
+        assert!(!slice.contains(&Tid::new("overwrite")));
+        assert!(!slice.contains(&Tid::new("unrelated")));
+
+        let config = SliceConfig {
+            max_depth: 1,
+            alias_precision: AliasPrecision::Exact,
+        };
+        let slice = slice_with_config(
+            &project,
+            &pi_results,
+            &Tid::new("final"),
+            &Variable::mock("RSI", 8),
+            &config,
+        )
+        .unwrap();
+        assert!(slice.truncated);
+        assert_eq!(slice.get_all_terms(), vec![&Tid::new("use")]);
+
+        assert!(super::slice(
+            &project,
+            &pi_results,
+            &Tid::new("nonexisting"),
+            &Variable::mock("RSI", 8)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn alias_kinds() {
+        let stack_id = AbstractIdentifier::new(
+            Tid::new("func"),
+            AbstractLocation::Register(Variable::mock("RSP", 8)),
+        );
+        let stack_pointer =
+            |offset: i64| Data::from_target(stack_id.clone(), Bitvector::from_i64(offset).into());
+        let eight = ByteSize::new(8);
+        assert_eq!(
+            get_alias_kind(&stack_pointer(-8), eight, &stack_pointer(-8), eight),
+            AliasKind::Must
+        );
+        assert_eq!(
+            get_alias_kind(&stack_pointer(-12), eight, &stack_pointer(-8), eight),
+            AliasKind::May
+        );
+        assert_eq!(
+            get_alias_kind(&stack_pointer(-16), eight, &stack_pointer(-8), eight),
+            AliasKind::No
+        );
+        let global = Data::from(Bitvector::from_i64(0x3000));
+        assert_eq!(
+            get_alias_kind(&global, eight, &stack_pointer(-8), eight),
+            AliasKind::No
+        );
+    }
+}
@@ -161,6 +161,7 @@ mod tests {
                 name: "sub".to_string(),
                 blocks: vec![block],
                 calling_convention: None,
+                is_thunk: false,
             },
         };
         let mut project = Project::mock_empty();
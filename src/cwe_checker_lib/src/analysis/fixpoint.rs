@@ -259,6 +259,14 @@ impl<T: Context> Computation<T> {
         }
     }
 
+    /// Remove the values of all nodes for which the predicate returns `false`.
+    ///
+    /// The worklist is not changed,
+    /// so this should only be used after the fixpoint has been computed.
+    pub fn retain_node_values(&mut self, mut predicate: impl FnMut(NodeIndex) -> bool) {
+        self.node_values.retain(|node, _| predicate(*node));
+    }
+
     /// Get a reference to the internal map where one can look up the current values of all nodes
     pub fn node_values(&self) -> &FnvHashMap<NodeIndex, T::NodeValue> {
         &self.node_values
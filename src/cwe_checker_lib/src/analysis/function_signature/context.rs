@@ -127,14 +127,18 @@ impl<'a> forward_interprocedural_fixpoint::Context<'a> for Context<'a> {
             Def::Assign { var, value } => {
                 new_state.set_read_flag_for_input_ids_of_expression(value);
                 new_state.set_register(var, state.eval(value));
+                new_state.add_written_register(var);
             }
             Def::Load { var, address } => {
                 new_state.set_deref_flag_for_input_ids_of_expression(address);
+                new_state.add_read_side_effect(&state.eval(address));
                 let value = new_state.load_value(new_state.eval(address), var.size);
                 new_state.set_register(var, value);
+                new_state.add_written_register(var);
             }
             Def::Store { address, value } => {
                 new_state.set_mutable_deref_flag_for_input_ids_of_expression(address);
+                new_state.add_write_side_effect(&state.eval(address));
                 if state
                     .get_offset_if_exact_stack_pointer(&state.eval(address))
                     .is_some()
@@ -231,6 +235,7 @@ impl<'a> forward_interprocedural_fixpoint::Context<'a> for Context<'a> {
         // Merge parameter access patterns with the access patterns from the callee.
         let parameters = callee_state.get_params_of_current_function();
         new_state.merge_parameter_access(&parameters);
+        new_state.add_side_effects_of_callee(callee_state, &parameters);
         // Compute values for return register (but do not add them to `new_state` yet)
         let return_value_list = self.compute_return_values_of_call(
             &mut new_state,
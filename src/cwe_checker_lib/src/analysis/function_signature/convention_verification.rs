@@ -0,0 +1,272 @@
+//! Verification of the calling conventions annotated to functions
+//! against the register accesses observed by the function signature analysis.
+
+use super::FunctionSignature;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::LogMessage;
+use std::collections::BTreeMap;
+
+/// The name of the verification pass used as source for its log messages.
+const SOURCE: &str = "Calling Convention Verification";
+
+/// The configuration of the calling convention verification.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ConventionVerificationConfig {
+    /// Whether the calling conventions of all functions are verified
+    /// and a log message is generated for each discrepancy.
+    pub enabled: bool,
+    /// If `true`, the pointer inference analysis trusts the observed behavior
+    /// of functions with discrepancies over their calling conventions.
+    /// Implies `enabled`.
+    pub trust_observed_behavior: bool,
+}
+
+impl ConventionVerificationConfig {
+    /// Returns `true` if the calling conventions have to be verified.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled || self.trust_observed_behavior
+    }
+}
+
+/// The differences between the calling convention of a function
+/// and its observed behavior.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct CallingConventionDiscrepancy {
+    /// The TID of the function.
+    pub function: Tid,
+    /// Registers that are callee-saved according to the calling convention
+    /// but may contain a different value when the function returns.
+    pub clobbered_callee_saved_registers: Vec<Variable>,
+    /// Registers that the function reads as parameters
+    /// although they are not parameter registers of the calling convention.
+    pub unlisted_parameter_registers: Vec<Variable>,
+}
+
+impl CallingConventionDiscrepancy {
+    /// Generate one log message per discrepancy.
+    pub fn to_log_messages(&self, project: &Project) -> Vec<LogMessage> {
+        let sub = &project.program.term.subs[&self.function];
+        let cconv_name = sub
+            .term
+            .calling_convention
+            .clone()
+            .or_else(|| {
+                project
+                    .get_standard_calling_convention()
+                    .map(|cconv| cconv.name.clone())
+            })
+            .unwrap_or_default();
+        let clobbered = self.clobbered_callee_saved_registers.iter().map(|register| {
+            format!(
+                "Function {} modifies the register {}, which is callee-saved in its calling convention {}.",
+                sub.term.name, register.name, cconv_name
+            )
+        });
+        let unlisted = self.unlisted_parameter_registers.iter().map(|register| {
+            format!(
+                "Function {} reads the register {} as a parameter, which is no parameter register of its calling convention {}.",
+                sub.term.name, register.name, cconv_name
+            )
+        });
+        clobbered
+            .chain(unlisted)
+            .map(|text| {
+                LogMessage::new_info(text)
+                    .location(self.function.clone())
+                    .source(SOURCE)
+            })
+            .collect()
+    }
+}
+
+/// Compare the calling convention of each function with the register accesses
+/// recorded in its function signature.
+///
+/// Returns the discrepancies for all functions that do not adhere to their calling convention.
+/// Functions without known calling convention are skipped.
+pub fn verify_calling_conventions(
+    project: &Project,
+    function_signatures: &BTreeMap<Tid, FunctionSignature>,
+) -> Vec<CallingConventionDiscrepancy> {
+    let mut discrepancies = Vec::new();
+    for (sub_tid, fn_sig) in function_signatures {
+        let sub = match project.program.term.subs.get(sub_tid) {
+            Some(sub) => sub,
+            None => continue,
+        };
+        let cconv = match project.get_specific_calling_convention(&sub.term.calling_convention) {
+            Some(cconv) => cconv,
+            None => continue,
+        };
+        let clobbered_callee_saved_registers: Vec<Variable> = cconv
+            .callee_saved_register
+            .iter()
+            .filter(|register| fn_sig.modified_callee_saved_registers.contains(*register))
+            .cloned()
+            .collect();
+        let parameter_registers = cconv.get_all_parameter_register();
+        let mut unlisted_parameter_registers: Vec<Variable> = fn_sig
+            .parameters
+            .keys()
+            .filter_map(|arg| match arg {
+                Arg::Register {
+                    expr: Expression::Var(register),
+                    ..
+                } if !parameter_registers.contains(&register) => Some(register.clone()),
+                _ => None,
+            })
+            .collect();
+        unlisted_parameter_registers.sort();
+        if !clobbered_callee_saved_registers.is_empty() || !unlisted_parameter_registers.is_empty()
+        {
+            discrepancies.push(CallingConventionDiscrepancy {
+                function: sub_tid.clone(),
+                clobbered_callee_saved_registers,
+                unlisted_parameter_registers,
+            });
+        }
+    }
+    discrepancies
+}
+
+/// Generate a log message summarizing the number of discrepancies found by the verification.
+pub fn get_discrepancy_summary(discrepancies: &[CallingConventionDiscrepancy]) -> LogMessage {
+    let clobbered_count: usize = discrepancies
+        .iter()
+        .map(|discrepancy| discrepancy.clobbered_callee_saved_registers.len())
+        .sum();
+    let unlisted_count: usize = discrepancies
+        .iter()
+        .map(|discrepancy| discrepancy.unlisted_parameter_registers.len())
+        .sum();
+    LogMessage::new_info(format!(
+        "Found {} functions not adhering to their calling convention ({} clobbered callee-saved registers, {} unlisted parameter registers).",
+        discrepancies.len(),
+        clobbered_count,
+        unlisted_count
+    ))
+    .source(SOURCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::function_signature::compute_function_signatures;
+    use std::collections::HashSet;
+
+    /// Create a function consisting of a single block with the given `Def`s that returns afterwards.
+    fn mock_sub(name: &str, defs: Vec<Term<Def>>) -> Term<Sub> {
+        let mut block = Blk::mock_with_tid(&format!("{}_blk", name));
+        block.term.defs = defs;
+        block.term.jmps.push(Term {
+            tid: Tid::new(format!("{}_return", name)),
+            term: Jmp::Return(Expression::const_from_i64(0)),
+        });
+        let mut sub = Sub::mock(name);
+        sub.term.blocks.push(block);
+        sub
+    }
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        // Writes to RBP although it is callee-saved.
+        let clobbering = mock_sub(
+            "clobbering",
+            vec![Def::assign(
+                "overwrite_rbp",
+                Variable::mock("RBP", 8),
+                Expression::const_from_i64(0),
+            )],
+        );
+        // Saves RBP on the stack and restores it before returning.
+        let restoring = mock_sub(
+            "restoring",
+            vec![
+                Def::store(
+                    "save_rbp",
+                    Expression::var("RSP", 8).plus_const(-8),
+                    Expression::var("RBP", 8),
+                ),
+                Def::assign(
+                    "overwrite_rbp",
+                    Variable::mock("RBP", 8),
+                    Expression::const_from_i64(0),
+                ),
+                Def::load(
+                    "restore_rbp",
+                    Variable::mock("RBP", 8),
+                    Expression::var("RSP", 8).plus_const(-8),
+                ),
+            ],
+        );
+        for sub in [clobbering, restoring] {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn clobbered_callee_saved_register() {
+        let project = mock_project();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let (_, fn_sigs) = compute_function_signatures(&project, &graph);
+        let discrepancies = verify_calling_conventions(&project, &fn_sigs);
+        assert_eq!(
+            discrepancies,
+            vec![CallingConventionDiscrepancy {
+                function: Tid::new("clobbering"),
+                clobbered_callee_saved_registers: vec![Variable::mock("RBP", 8)],
+                unlisted_parameter_registers: Vec::new(),
+            }]
+        );
+        let logs = discrepancies[0].to_log_messages(&project);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].location, Some(Tid::new("clobbering")));
+        assert_eq!(
+            get_discrepancy_summary(&discrepancies).text,
+            "Found 1 functions not adhering to their calling convention (1 clobbered callee-saved registers, 0 unlisted parameter registers)."
+        );
+    }
+
+    #[test]
+    fn unlisted_parameter_register() {
+        let mut project = mock_project();
+        let mut cconv = CallingConvention::mock();
+        cconv.name = "no_params".to_string();
+        cconv.integer_parameter_register = Vec::new();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        let mut reading = mock_sub(
+            "reading",
+            vec![Def::assign(
+                "read_rdi",
+                Variable::mock("RAX", 8),
+                Expression::var("RDI", 8).plus_const(1),
+            )],
+        );
+        reading.term.calling_convention = Some("no_params".to_string());
+        project
+            .program
+            .term
+            .subs
+            .insert(reading.tid.clone(), reading);
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let (_, fn_sigs) = compute_function_signatures(&project, &graph);
+        let discrepancies = verify_calling_conventions(&project, &fn_sigs);
+        assert_eq!(discrepancies.len(), 2);
+        assert_eq!(
+            discrepancies[1],
+            CallingConventionDiscrepancy {
+                function: Tid::new("reading"),
+                clobbered_callee_saved_registers: Vec::new(),
+                unlisted_parameter_registers: vec![Variable::mock("RDI", 8)],
+            }
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Inference of the parameters of extern symbols with unknown prototypes from their call sites.
+//!
+//! For extern symbols without known parameters (e.g. functions of unknown libraries in stripped binaries)
+//! the parameters are guessed from the instructions preceding each call in the same basic block:
+//! * A parameter register of the calling convention is a parameter
+//!   if it is written before the call and not read again until the call.
+//! * A stack slot at a small non-negative offset relative to the stack pointer at the call
+//!   is a parameter if it is written before the call.
+//!   Stack slots are only considered if all parameter registers are parameters at the call site,
+//!   since stores to such stack slots are usually local variables of the caller otherwise.
+//!
+//! Parameters are assigned in order, i.e. if the second parameter register is a parameter,
+//! the first one is assumed to be a parameter, too.
+//! The parameters of a symbol are the parameters of the call site with the most parameters.
+//!
+//! The inferred parameters are written back to the extern symbols of the project,
+//! so that all analyses use them.
+//! Since they are only an approximation, a log message is generated for each inferred prototype.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::LogMessage;
+use std::collections::{BTreeSet, HashMap};
+
+/// The name of the inference pass used as source for its log messages.
+const SOURCE: &str = "Extern Parameter Inference";
+
+/// The maximum number of stack parameters inferred for an extern symbol.
+const MAX_STACK_PARAMETERS: u64 = 8;
+
+/// The configuration of the parameter inference for extern symbols with unknown prototypes.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ExternParameterInferenceConfig {
+    /// Whether the parameters of extern symbols without known parameters are inferred from their call sites.
+    pub enabled: bool,
+}
+
+/// The number of register and stack parameters observed at a call site.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, PartialOrd, Ord)]
+struct ParameterCount {
+    registers: usize,
+    stack_slots: u64,
+}
+
+/// Evaluate the given expression if all of its input registers have known values.
+///
+/// The known values are offsets relative to the stack pointer at the start of the block.
+fn evaluate(
+    expression: &Expression,
+    known_values: &HashMap<&Variable, Bitvector>,
+) -> Option<Bitvector> {
+    match expression {
+        Expression::Var(var) => known_values.get(var).cloned(),
+        Expression::Const(value) => Some(value.clone()),
+        Expression::BinOp { op, lhs, rhs } => evaluate(lhs, known_values)?
+            .bin_op(*op, &evaluate(rhs, known_values)?)
+            .ok(),
+        _ => None,
+    }
+}
+
+/// Count the parameters passed to the call at the end of the given block.
+fn count_parameters_at_call_site(
+    block: &Term<Blk>,
+    parameter_registers: &[Variable],
+    stack_register: &Variable,
+) -> ParameterCount {
+    // For each parameter register whether it was written and not read afterwards.
+    let mut unread_writes: Vec<bool> = vec![false; parameter_registers.len()];
+    let mut stack_offsets: HashMap<&Variable, Bitvector> =
+        HashMap::from([(stack_register, Bitvector::zero(stack_register.size.into()))]);
+    let mut stored_offsets: BTreeSet<i64> = BTreeSet::new();
+    for def in block.term.defs.iter() {
+        let (written_var, read_vars) = match &def.term {
+            Def::Assign { var, value } => {
+                match evaluate(value, &stack_offsets) {
+                    Some(offset) => stack_offsets.insert(var, offset),
+                    None => stack_offsets.remove(var),
+                };
+                (Some(var), value.input_vars())
+            }
+            Def::Load { var, address } => {
+                stack_offsets.remove(var);
+                (Some(var), address.input_vars())
+            }
+            Def::Store { address, value } => {
+                if value.bytesize() == stack_register.size {
+                    if let Some(offset) = evaluate(address, &stack_offsets)
+                        .and_then(|offset| offset.try_to_i64().ok())
+                    {
+                        stored_offsets.insert(offset);
+                    }
+                }
+                (None, [address.input_vars(), value.input_vars()].concat())
+            }
+        };
+        for (register, unread_write) in parameter_registers.iter().zip(unread_writes.iter_mut()) {
+            if read_vars.iter().any(|var| var.name == register.name) {
+                *unread_write = false;
+            }
+            if written_var.is_some_and(|var| var.name == register.name) {
+                *unread_write = true;
+            }
+        }
+    }
+    let registers = unread_writes
+        .iter()
+        .rposition(|unread_write| *unread_write)
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let mut stack_slots = 0;
+    let stack_offset_at_call = stack_offsets
+        .get(stack_register)
+        .and_then(|offset| offset.try_to_i64().ok());
+    if let (true, Some(stack_offset_at_call)) =
+        (registers == parameter_registers.len(), stack_offset_at_call)
+    {
+        let slot_size = u64::from(stack_register.size) as i64;
+        while stack_slots < MAX_STACK_PARAMETERS
+            && stored_offsets.contains(&(stack_offset_at_call + stack_slots as i64 * slot_size))
+        {
+            stack_slots += 1;
+        }
+    }
+    ParameterCount {
+        registers,
+        stack_slots,
+    }
+}
+
+/// Generate the parameters of an extern symbol for the given parameter count.
+///
+/// The addresses of stack parameters are relative to the stack pointer at the start of the called function,
+/// i.e. they account for the return address pushed onto the stack by the call on x86.
+fn generate_parameters(
+    project: &Project,
+    parameter_registers: &[Variable],
+    count: ParameterCount,
+) -> Vec<Arg> {
+    let stack_register = &project.stack_pointer_register;
+    let slot_size = u64::from(stack_register.size) as i64;
+    let return_address_size = if project.architecture.pushes_return_address_on_stack() {
+        slot_size
+    } else {
+        0
+    };
+    let register_args = parameter_registers[..count.registers]
+        .iter()
+        .map(|register| Arg::from_var(register.clone(), None));
+    let stack_args = (0..count.stack_slots as i64).map(|index| Arg::Stack {
+        address: Expression::Var(stack_register.clone())
+            .plus_const(return_address_size + index * slot_size),
+        size: stack_register.size,
+        data_type: None,
+    });
+    register_args.chain(stack_args).collect()
+}
+
+/// Get a short textual representation of an argument for log messages.
+fn format_arg(arg: &Arg, stack_register: &Variable) -> String {
+    match arg {
+        Arg::Register {
+            expr: Expression::Var(var),
+            ..
+        } => var.name.clone(),
+        Arg::Stack { .. } => match arg
+            .eval_stack_offset(stack_register)
+            .ok()
+            .and_then(|offset| offset.try_to_i64().ok())
+        {
+            Some(offset) => format!("[{}+{:#x}]", stack_register.name, offset),
+            None => "stack".to_string(),
+        },
+        Arg::Register { expr, .. } => format!("{:?}", expr),
+    }
+}
+
+/// Infer the parameters of all extern symbols without known parameters from their call sites
+/// and write them back to the extern symbols of the project.
+///
+/// Returns a log message for each extern symbol with inferred parameters.
+/// See the module-level documentation for the used heuristic.
+pub fn infer_extern_symbol_parameters(project: &mut Project) -> Vec<LogMessage> {
+    let mut call_sites: HashMap<&Tid, Vec<&Term<Blk>>> = HashMap::new();
+    for sub in project.program.term.subs.values() {
+        for block in sub.term.blocks.iter() {
+            if let Some(Jmp::Call { target, .. }) = block.term.jmps.last().map(|jmp| &jmp.term) {
+                call_sites.entry(target).or_default().push(block);
+            }
+        }
+    }
+    let mut inferred_parameters: Vec<(Tid, Vec<Arg>, usize)> = Vec::new();
+    for symbol in project.program.term.extern_symbols.values() {
+        if !symbol.parameters.is_empty() {
+            continue;
+        }
+        let cconv = match project.get_specific_calling_convention(&symbol.calling_convention) {
+            Some(cconv) => cconv,
+            None => continue,
+        };
+        let blocks = match call_sites.get(&symbol.tid) {
+            Some(blocks) => blocks,
+            None => continue,
+        };
+        let count = blocks
+            .iter()
+            .map(|block| {
+                count_parameters_at_call_site(
+                    block,
+                    &cconv.integer_parameter_register,
+                    &project.stack_pointer_register,
+                )
+            })
+            .max()
+            .unwrap_or_default();
+        if count == ParameterCount::default() {
+            continue;
+        }
+        let parameters = generate_parameters(project, &cconv.integer_parameter_register, count);
+        inferred_parameters.push((symbol.tid.clone(), parameters, blocks.len()));
+    }
+    let mut logs = Vec::new();
+    for (symbol_tid, parameters, num_call_sites) in inferred_parameters {
+        let symbol = project
+            .program
+            .term
+            .extern_symbols
+            .get_mut(&symbol_tid)
+            .unwrap();
+        let args: Vec<String> = parameters
+            .iter()
+            .map(|arg| format_arg(arg, &project.stack_pointer_register))
+            .collect();
+        logs.push(
+            LogMessage::new_info(format!(
+                "Unknown prototype of {}: Approximated the parameters as ({}) from {} call site(s).",
+                symbol.name,
+                args.join(", "),
+                num_call_sites
+            ))
+            .location(symbol_tid)
+            .source(SOURCE),
+        );
+        symbol.parameters = parameters;
+    }
+    logs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project with the parameter registers `RDI` and `RSI`
+    /// and the extern symbol `unknown` without known parameters.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8), Variable::mock("RSI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("unknown").parameters = Vec::new();
+        project
+    }
+
+    /// Add a function with one block for each of the given lists of definitions.
+    /// Each block ends with a call to the extern symbol `unknown`.
+    fn add_call_sites(project: &mut Project, call_sites: Vec<Vec<Term<Def>>>) {
+        let mut sub = Sub::mock("caller");
+        for (index, defs) in call_sites.into_iter().enumerate() {
+            let mut block = Blk::mock_with_tid(&format!("blk_{}", index));
+            block.term.defs = defs;
+            block.term.jmps.push(Jmp::call(
+                &format!("call_{}", index),
+                "unknown",
+                Some(&format!("blk_{}", index)),
+            ));
+            sub.term.blocks.push(block);
+        }
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+    }
+
+    fn get_parameters(project: &Project) -> &Vec<Arg> {
+        &project.program.term.extern_symbols[&Tid::new("unknown")].parameters
+    }
+    #[test]
+    fn register_parameters_are_inferred() {
+        let mut project = mock_project();
+        add_call_sites(
+            &mut project,
+            vec![
+                // Only `RDI` is a parameter, since `RSI` is read after it is written.
+                vec![
+                    Def::assign(
+                        "def_0",
+                        Variable::mock("RSI", 8),
+                        Expression::const_from_i64(1),
+                    ),
+                    Def::assign("def_1", Variable::mock("RDI", 8), Expression::var("RSI", 8)),
+                ],
+                // The first parameter register is assumed to be a parameter, too.
+                vec![Def::assign(
+                    "def_2",
+                    Variable::mock("RSI", 8),
+                    Expression::const_from_i64(2),
+                )],
+            ],
+        );
+        let logs = infer_extern_symbol_parameters(&mut project);
+        assert_eq!(
+            get_parameters(&project),
+            &vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)]
+        );
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            logs[0].text,
+            "Unknown prototype of unknown: Approximated the parameters as (RDI, RSI) from 2 call site(s)."
+        );
+    }
+
+    #[test]
+    fn stack_parameters_are_inferred() {
+        let mut project = mock_project();
+        let set_registers = vec![
+            Def::assign(
+                "set_rdi",
+                Variable::mock("RDI", 8),
+                Expression::const_from_i64(1),
+            ),
+            Def::assign(
+                "set_rsi",
+                Variable::mock("RSI", 8),
+                Expression::const_from_i64(2),
+            ),
+        ];
+        let push = |index: usize| {
+            vec![
+                Def::assign(
+                    &format!("push_{}_sub", index),
+                    Variable::mock("RSP", 8),
+                    Expression::var("RSP", 8).plus_const(-8),
+                ),
+                Def::store(
+                    &format!("push_{}_store", index),
+                    Expression::var("RSP", 8),
+                    Expression::var("RAX", 8),
+                ),
+            ]
+        };
+        add_call_sites(
+            &mut project,
+            vec![
+                // Stack slots are only parameters if all parameter registers are parameters.
+                [push(0), push(1)].concat(),
+                [push(2), push(3), set_registers].concat(),
+            ],
+        );
+        let _ = infer_extern_symbol_parameters(&mut project);
+        // The return address pushed by the call is not a parameter.
+        let stack_arg = |offset: i64| Arg::Stack {
+            address: Expression::var("RSP", 8).plus_const(offset),
+            size: ByteSize::new(8),
+            data_type: None,
+        };
+        assert_eq!(
+            get_parameters(&project),
+            &vec![
+                Arg::mock_register("RDI", 8),
+                Arg::mock_register("RSI", 8),
+                stack_arg(8),
+                stack_arg(16),
+            ]
+        );
+    }
+
+    #[test]
+    fn known_parameters_are_kept() {
+        let mut project = mock_project();
+        let symbol = project
+            .program
+            .term
+            .extern_symbols
+            .get_mut(&Tid::new("unknown"))
+            .unwrap();
+        symbol.parameters = vec![Arg::mock_register("RSI", 8)];
+        add_call_sites(
+            &mut project,
+            vec![vec![Def::assign(
+                "def",
+                Variable::mock("RDI", 8),
+                Expression::const_from_i64(1),
+            )]],
+        );
+        assert!(infer_extern_symbol_parameters(&mut project).is_empty());
+        assert_eq!(
+            get_parameters(&project),
+            &vec![Arg::mock_register("RSI", 8)]
+        );
+    }
+}
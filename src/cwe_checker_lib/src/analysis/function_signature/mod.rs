@@ -4,6 +4,8 @@
 //! although only stack accesses with known, constant offset are processed.
 //! Accesses to potential function parameters are collected together with the type of the access
 //! (is the value read, dereferenced for read access or dereferenced for write access).
+//! Furthermore, the side effects of each function are classified (see [`FunctionPurity`]),
+//! taking the side effects of called functions into account.
 //!
 //! Known limitations of the analysis:
 //! * The analysis is an overapproximation in the sense that it may generate more input parameters
@@ -34,6 +36,7 @@ use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::LogMessage;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 
 mod context;
@@ -42,6 +45,22 @@ mod state;
 use state::State;
 mod access_pattern;
 pub use access_pattern::AccessPattern;
+mod convention_verification;
+pub use convention_verification::{
+    get_discrepancy_summary, verify_calling_conventions, CallingConventionDiscrepancy,
+    ConventionVerificationConfig,
+};
+mod extern_parameter_inference;
+pub use extern_parameter_inference::{
+    infer_extern_symbol_parameters, ExternParameterInferenceConfig,
+};
+mod purity;
+pub use purity::{get_purity_classifications, FunctionPurity, PurityClassification};
+mod ownership;
+pub use ownership::{
+    add_parameter_ownership, get_ownership_classifications, OwnershipClassification,
+    ParameterOwnership, ParameterOwnershipConfig,
+};
 
 /// Generate the computation object for the fixpoint computation
 /// and set the node values for all function entry nodes.
@@ -57,14 +76,14 @@ fn generate_fixpoint_computation<'a>(
             if let Some(entry_block) = sub.term.blocks.get(0) {
                 if entry_block.tid == block.tid {
                     // The node of a function entry point
-                    computation.set_node_value(
-                        node,
-                        NodeValue::Value(State::new(
-                            &sub.tid,
-                            &project.stack_pointer_register,
-                            project.get_standard_calling_convention().unwrap(),
-                        )),
-                    )
+                    let calling_convention = project.get_standard_calling_convention().unwrap();
+                    let mut state = State::new(
+                        &sub.tid,
+                        &project.stack_pointer_register,
+                        calling_convention,
+                    );
+                    state.add_callee_saved_register_ids(calling_convention);
+                    computation.set_node_value(node, NodeValue::Value(state))
                 }
             }
         }
@@ -89,14 +108,32 @@ fn extract_fn_signatures_from_fixpoint<'a>(
         .keys()
         .map(|tid| (tid.clone(), FunctionSignature::new()))
         .collect();
+    let mut analyzed_functions = BTreeSet::new();
     for node in graph.node_indices() {
         match fixpoint.get_node_value(node) {
             None => (),
             Some(NodeValue::Value(state)) => {
+                analyzed_functions.insert(state.get_current_function_tid().clone());
                 let fn_sig = fn_sig_map
                     .get_mut(state.get_current_function_tid())
                     .unwrap();
                 fn_sig.merge_with_fn_sig_of_state(state);
+                if let Node::BlkEnd(block, _) = graph[node] {
+                    if block
+                        .term
+                        .jmps
+                        .iter()
+                        .any(|jmp| matches!(jmp.term, Jmp::Return(_)))
+                    {
+                        let calling_convention = project.get_standard_calling_convention().unwrap();
+                        fn_sig.modified_callee_saved_registers.extend(
+                            state
+                                .get_modified_callee_saved_registers(calling_convention)
+                                .into_iter()
+                                .cloned(),
+                        );
+                    }
+                }
             }
             Some(NodeValue::CallFlowCombinator {
                 call_stub,
@@ -117,9 +154,31 @@ fn extract_fn_signatures_from_fixpoint<'a>(
             }
         }
     }
+    // The side effects of functions that were not analyzed
+    // or that contain control flow not represented in the graph are unknown.
+    for (fn_tid, fn_sig) in fn_sig_map.iter_mut() {
+        if !analyzed_functions.contains(fn_tid)
+            || contains_unresolved_control_flow(&project.program.term.subs[fn_tid])
+        {
+            fn_sig.purity = FunctionPurity::Impure;
+        }
+    }
     fn_sig_map
 }
 
+/// Returns `true` if the function contains indirect jumps or calls to unknown targets,
+/// whose effects are not represented in the control flow graph.
+fn contains_unresolved_control_flow(sub: &Term<Sub>) -> bool {
+    sub.term.blocks.iter().any(|block| {
+        block.term.jmps.iter().any(|jmp| {
+            matches!(
+                jmp.term,
+                Jmp::BranchInd(_) | Jmp::CallInd { .. } | Jmp::CallOther { .. }
+            )
+        })
+    })
+}
+
 /// Compute the function signatures for all functions in the project.
 ///
 /// Returns a map from the function TIDs to their signatures,
@@ -149,11 +208,27 @@ pub fn compute_function_signatures<'a>(
 }
 
 /// The signature of a function.
-/// Currently only contains information on the parameters of a function and their access patterns.
+/// Contains information on the parameters of a function and their access patterns,
+/// on the callee-saved registers that the function does not restore before returning,
+/// on the side effects of the function
+/// and on what the function does with the objects its pointer parameters point to.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct FunctionSignature {
     /// The parameters of the function together with their access patterns.
     pub parameters: HashMap<Arg, AccessPattern>,
+    /// The callee-saved registers of the standard calling convention
+    /// that may contain a different value on return than on function start.
+    pub modified_callee_saved_registers: BTreeSet<Variable>,
+    /// The side effects of the function (including the side effects of the functions called by it).
+    pub purity: FunctionPurity,
+    /// The registers that the function (or one of the functions called by it) may write to.
+    /// Only complete if the function is not impure.
+    pub written_registers: BTreeSet<Variable>,
+    /// Whether the function frees or stores the objects that its pointer parameters point to.
+    /// Only known after the [pointer inference analysis](crate::analysis::pointer_inference) was computed
+    /// (see [`add_parameter_ownership`]).
+    #[serde(default)]
+    pub parameter_ownership: BTreeMap<Variable, ParameterOwnership>,
 }
 
 impl FunctionSignature {
@@ -161,6 +236,10 @@ impl FunctionSignature {
     pub fn new() -> Self {
         Self {
             parameters: HashMap::new(),
+            modified_callee_saved_registers: BTreeSet::new(),
+            purity: FunctionPurity::Pure,
+            written_registers: BTreeSet::new(),
+            parameter_ownership: BTreeMap::new(),
         }
     }
 
@@ -179,6 +258,9 @@ impl FunctionSignature {
     fn merge_with_fn_sig_of_state(&mut self, state: &State) {
         let params = state.get_params_of_current_function();
         self.merge_parameter_list(&params);
+        self.purity = self.purity.merge(&state.get_purity());
+        self.written_registers
+            .extend(state.get_written_registers().iter().cloned());
     }
 
     /// Sanitize the function signature:
@@ -188,17 +270,14 @@ impl FunctionSignature {
     ///   This may indicate an error in the analysis
     ///   as no proper sanitation pass is implemented for such cases yet.
     fn sanitize(&mut self, project: &Project) -> Result<(), Error> {
-        match project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
-                let return_addr_expr = Expression::Var(project.stack_pointer_register.clone());
-                let return_addr_arg = Arg::Stack {
-                    address: return_addr_expr,
-                    size: project.stack_pointer_register.size,
-                    data_type: None,
-                };
-                self.parameters.remove(&return_addr_arg);
-            }
-            _ => (),
+        if project.architecture.pushes_return_address_on_stack() {
+            let return_addr_expr = Expression::Var(project.stack_pointer_register.clone());
+            let return_addr_arg = Arg::Stack {
+                address: return_addr_expr,
+                size: project.stack_pointer_register.size,
+                data_type: None,
+            };
+            self.parameters.remove(&return_addr_arg);
         }
         self.check_for_unaligned_stack_params(&project.stack_pointer_register)
     }
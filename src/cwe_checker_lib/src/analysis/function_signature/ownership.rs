@@ -0,0 +1,250 @@
+//! Summaries of what functions do with the objects that their pointer parameters point to.
+//!
+//! The summaries are computed from the results of the [pointer inference analysis](crate::analysis::pointer_inference)
+//! by comparing the states of the objects pointed to by parameter registers at the function start
+//! with their states at the return instructions of the function.
+//! A function may free such an object, store a pointer to it somewhere that outlives the call
+//! or only borrow the object for the duration of the call.
+
+use super::FunctionSignature;
+use crate::abstract_domain::{AbstractDomain, AbstractIdentifier};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::object::{ObjectState, ObjectType};
+use crate::analysis::pointer_inference::{PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::BTreeMap;
+
+/// The heap objects that a parameter points to at the function start,
+/// together with their states and the flags whether they have already escaped at the function start.
+type ParameterTargets = BTreeMap<AbstractIdentifier, (ObjectState, bool)>;
+
+/// The configuration of the parameter ownership summaries.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ParameterOwnershipConfig {
+    /// If `true`, the pointer inference analysis is computed a second time
+    /// with the ownership summaries computed from its first run,
+    /// provided that some function frees or stores one of its pointer parameters.
+    ///
+    /// Disabled by default, since the second run doubles the runtime of the pointer inference.
+    /// The first run already marks objects freed by a callee as dangling after the call
+    /// unless the callee state merges the objects of several call sites
+    /// or the callee is excluded from the analysis.
+    pub refine_pointer_inference: bool,
+}
+
+/// What a function does with the object that a pointer parameter points to.
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterOwnership {
+    /// The object is neither freed nor stored by the function.
+    #[default]
+    Borrowed,
+    /// A pointer to the object may be stored in memory that outlives the call,
+    /// e.g. in global memory or in an object known to the caller.
+    Stored,
+    /// The object is freed on some, but not all execution paths of the function.
+    MaybeFreed,
+    /// The object is freed on all execution paths of the function that return to the caller.
+    Freed,
+}
+
+impl AbstractDomain for ParameterOwnership {
+    /// The object is only freed if it is freed on both execution paths.
+    /// Otherwise the more severe of the two ownerships is chosen.
+    fn merge(&self, other: &Self) -> Self {
+        use ParameterOwnership::*;
+        match (self, other) {
+            (Freed, Freed) => Freed,
+            (Freed | MaybeFreed, _) | (_, Freed | MaybeFreed) => MaybeFreed,
+            _ => std::cmp::max(*self, *other),
+        }
+    }
+
+    /// Returns true if the object may be freed by the function.
+    fn is_top(&self) -> bool {
+        *self == ParameterOwnership::MaybeFreed
+    }
+}
+
+/// The ownership of the pointer parameters of a function that does not only borrow them.
+///
+/// Used by the [pointer inference analysis](crate::analysis::pointer_inference)
+/// to update the objects passed as arguments to a call of the function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct OwnershipClassification {
+    /// The TID of the function.
+    pub function: Tid,
+    /// The parameter registers of the function together with the ownership of the objects they point to.
+    pub parameters: Vec<(Variable, ParameterOwnership)>,
+}
+
+/// Compute the ownership of the pointer parameters of all functions with a known start and exit state
+/// and add it to the corresponding function signatures.
+///
+/// Only parameter registers pointing to heap objects that are not known to be freed at the function start are considered.
+pub fn add_parameter_ownership(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    function_signatures: &mut BTreeMap<Tid, FunctionSignature>,
+) {
+    let graph = pointer_inference.get_graph();
+    let get_state = |node| match pointer_inference.get_node_value(node) {
+        Some(NodeValue::Value(state)) => Some(state),
+        _ => None,
+    };
+    let mut parameter_targets: BTreeMap<Tid, Vec<(Variable, ParameterTargets)>> = BTreeMap::new();
+    for node in graph.node_indices() {
+        if let (Node::BlkStart(block, sub), Some(state)) = (graph[node], get_state(node)) {
+            if sub
+                .term
+                .blocks
+                .first()
+                .is_some_and(|entry_block| entry_block.tid == block.tid)
+            {
+                parameter_targets.insert(
+                    sub.tid.clone(),
+                    get_heap_targets_of_parameters(project, sub, state),
+                );
+            }
+        }
+    }
+    let mut ownership: BTreeMap<Tid, BTreeMap<Variable, ParameterOwnership>> = BTreeMap::new();
+    for node in graph.node_indices() {
+        if let (Node::BlkEnd(block, sub), Some(state)) = (graph[node], get_state(node)) {
+            let returns = block
+                .term
+                .jmps
+                .iter()
+                .any(|jmp| matches!(jmp.term, Jmp::Return(_)));
+            if let (true, Some(targets)) = (returns, parameter_targets.get(&sub.tid)) {
+                let fn_ownership = ownership.entry(sub.tid.clone()).or_default();
+                for (parameter, targets) in targets {
+                    let exit_ownership = get_ownership_at_exit(targets, state);
+                    fn_ownership
+                        .entry(parameter.clone())
+                        .and_modify(|param_ownership| {
+                            *param_ownership = param_ownership.merge(&exit_ownership)
+                        })
+                        .or_insert(exit_ownership);
+                }
+            }
+        }
+    }
+    for (fn_tid, fn_ownership) in ownership {
+        if let Some(fn_sig) = function_signatures.get_mut(&fn_tid) {
+            fn_sig.parameter_ownership = fn_ownership;
+        }
+    }
+}
+
+/// Get the ownership classifications of all functions
+/// that free or store at least one of their pointer parameters.
+pub fn get_ownership_classifications(
+    function_signatures: &BTreeMap<Tid, FunctionSignature>,
+) -> Vec<OwnershipClassification> {
+    function_signatures
+        .iter()
+        .filter_map(|(tid, fn_sig)| {
+            let parameters: Vec<(Variable, ParameterOwnership)> = fn_sig
+                .parameter_ownership
+                .iter()
+                .filter(|(_, ownership)| **ownership != ParameterOwnership::Borrowed)
+                .map(|(parameter, ownership)| (parameter.clone(), *ownership))
+                .collect();
+            (!parameters.is_empty()).then(|| OwnershipClassification {
+                function: tid.clone(),
+                parameters,
+            })
+        })
+        .collect()
+}
+
+/// Get the heap objects not known to be freed that the integer parameter registers
+/// of the calling convention of the function point to in the given state.
+fn get_heap_targets_of_parameters(
+    project: &Project,
+    sub: &Term<Sub>,
+    state: &State,
+) -> Vec<(Variable, ParameterTargets)> {
+    let cconv = match project
+        .get_specific_calling_convention(&sub.term.calling_convention)
+        .or_else(|| project.get_standard_calling_convention())
+    {
+        Some(cconv) => cconv,
+        None => return Vec::new(),
+    };
+    cconv
+        .integer_parameter_register
+        .iter()
+        .filter_map(|parameter| {
+            let targets: ParameterTargets = state
+                .get_register(parameter)
+                .referenced_ids()
+                .filter(|id| matches!(state.memory.get_object_type(id), Ok(Some(ObjectType::Heap))))
+                .filter_map(|id| match state.memory.get_object_state(id)? {
+                    object_state @ (ObjectState::Alive | ObjectState::Unknown) => Some((
+                        id.clone(),
+                        (
+                            object_state,
+                            matches!(state.memory.has_escaped(id), Ok(true)),
+                        ),
+                    )),
+                    _ => None,
+                })
+                .collect();
+            (!targets.is_empty()).then(|| (parameter.clone(), targets))
+        })
+        .collect()
+}
+
+/// Get the ownership of the given parameter targets at the return instruction with the given state.
+///
+/// A pointer pointing to more than one object is freed by marking all targets as having an unknown state.
+/// Such objects are thus considered freed if all of them have a dangling or unknown state.
+/// Objects that have already escaped at the function start are not considered stored by the function.
+fn get_ownership_at_exit(targets: &ParameterTargets, state: &State) -> ParameterOwnership {
+    let is_freed = |id| match state.memory.get_object_state(id) {
+        Some(ObjectState::Dangling) => true,
+        Some(ObjectState::Unknown) => targets.len() > 1,
+        _ => false,
+    };
+    let may_be_freed =
+        |(id, (state_at_start, _)): (&AbstractIdentifier, &(ObjectState, bool))| match state
+            .memory
+            .get_object_state(id)
+        {
+            Some(ObjectState::Dangling) => true,
+            Some(ObjectState::Unknown) => *state_at_start == ObjectState::Alive,
+            _ => false,
+        };
+    if targets.keys().all(is_freed) {
+        ParameterOwnership::Freed
+    } else if targets.iter().any(may_be_freed) {
+        ParameterOwnership::MaybeFreed
+    } else if targets.iter().any(|(id, (_, escaped_at_start))| {
+        !escaped_at_start && matches!(state.memory.has_escaped(id), Ok(true))
+    }) {
+        ParameterOwnership::Stored
+    } else {
+        ParameterOwnership::Borrowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ownership_merge() {
+        use ParameterOwnership::*;
+        assert_eq!(Freed.merge(&Freed), Freed);
+        assert_eq!(Freed.merge(&Borrowed), MaybeFreed);
+        assert_eq!(Stored.merge(&Freed), MaybeFreed);
+        assert_eq!(Borrowed.merge(&Stored), Stored);
+        assert_eq!(Borrowed.merge(&Borrowed), Borrowed);
+    }
+}
@@ -0,0 +1,167 @@
+//! Classification of functions by their side effects.
+//!
+//! The classification is derived from the memory accesses observed by the function signature analysis
+//! and from the classifications of the called functions.
+//! It is conservative: calls to extern functions, to unknown functions
+//! and unresolved indirect calls make a function impure.
+
+use super::FunctionSignature;
+use crate::abstract_domain::AbstractDomain;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The side effects of a function, ordered by increasing severity.
+///
+/// Accesses to the stack frame of the function itself are no side effects.
+/// Reading the stack parameters of the function is also no side effect,
+/// since they are part of the arguments of the function.
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionPurity {
+    /// The function only reads its arguments
+    /// and only writes to registers and to its own stack frame.
+    #[default]
+    Pure,
+    /// The function may read memory outside of its own stack frame,
+    /// but it does not write to it.
+    ReadsMemoryOnly,
+    /// The function may write to memory outside of its own stack frame,
+    /// but only through pointers given as arguments or to its stack parameters.
+    WritesArgsOnly,
+    /// The function may have arbitrary side effects.
+    Impure,
+}
+
+impl AbstractDomain for FunctionPurity {
+    /// The merged purity is the more severe of the two purities.
+    fn merge(&self, other: &Self) -> Self {
+        std::cmp::max(*self, *other)
+    }
+
+    /// Returns true if the function may have arbitrary side effects.
+    fn is_top(&self) -> bool {
+        *self == FunctionPurity::Impure
+    }
+}
+
+/// The purity of a function together with the registers that the function may write to.
+///
+/// Used by the [pointer inference analysis](crate::analysis::pointer_inference)
+/// to keep the values of registers and memory objects across calls that cannot modify them.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct PurityClassification {
+    /// The TID of the function.
+    pub function: Tid,
+    /// The purity of the function.
+    pub purity: FunctionPurity,
+    /// The registers that the function (or one of the functions called by it) may write to.
+    pub written_registers: BTreeSet<Variable>,
+}
+
+/// Get the purity classifications of all functions that are not impure.
+pub fn get_purity_classifications(
+    function_signatures: &BTreeMap<Tid, FunctionSignature>,
+) -> Vec<PurityClassification> {
+    function_signatures
+        .iter()
+        .filter(|(_, fn_sig)| fn_sig.purity != FunctionPurity::Impure)
+        .map(|(tid, fn_sig)| PurityClassification {
+            function: tid.clone(),
+            purity: fn_sig.purity,
+            written_registers: fn_sig.written_registers.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::function_signature::compute_function_signatures;
+    use std::collections::HashSet;
+
+    /// Create a function consisting of a single block with the given `Def`s that returns afterwards.
+    fn mock_sub(name: &str, defs: Vec<Term<Def>>) -> Term<Sub> {
+        let mut block = Blk::mock_with_tid(&format!("{}_blk", name));
+        block.term.defs = defs;
+        block.term.jmps.push(Term {
+            tid: Tid::new(format!("{}_return", name)),
+            term: Jmp::Return(Expression::const_from_i64(0)),
+        });
+        let mut sub = Sub::mock(name);
+        sub.term.blocks.push(block);
+        sub
+    }
+
+    #[test]
+    fn purity_classification() {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        let subs = [
+            mock_sub(
+                "local_only",
+                vec![
+                    Def::store(
+                        "store_local",
+                        Expression::var("RSP", 8).plus_const(-8),
+                        Expression::var("RDI", 8),
+                    ),
+                    Def::load(
+                        "load_local",
+                        Variable::mock("RAX", 8),
+                        Expression::var("RSP", 8).plus_const(-8),
+                    ),
+                ],
+            ),
+            mock_sub(
+                "reader",
+                vec![Def::load(
+                    "load_param",
+                    Variable::mock("RAX", 8),
+                    Expression::var("RDI", 8),
+                )],
+            ),
+            mock_sub(
+                "setter",
+                vec![Def::store(
+                    "store_param",
+                    Expression::var("RDI", 8).plus_const(8),
+                    Expression::var("RSI", 8),
+                )],
+            ),
+            mock_sub(
+                "global_writer",
+                vec![Def::store(
+                    "store_global",
+                    Expression::const_from_i64(0x3000),
+                    Expression::var("RSI", 8),
+                )],
+            ),
+        ];
+        for sub in subs {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let (_, fn_sigs) = compute_function_signatures(&project, &graph);
+        let purity_of = |name: &str| fn_sigs[&Tid::new(name)].purity;
+        assert_eq!(purity_of("local_only"), FunctionPurity::Pure);
+        assert_eq!(purity_of("reader"), FunctionPurity::ReadsMemoryOnly);
+        assert_eq!(purity_of("setter"), FunctionPurity::WritesArgsOnly);
+        assert_eq!(purity_of("global_writer"), FunctionPurity::Impure);
+        assert_eq!(
+            fn_sigs[&Tid::new("reader")].written_registers,
+            BTreeSet::from([Variable::mock("RAX", 8)])
+        );
+
+        let classifications = get_purity_classifications(&fn_sigs);
+        assert_eq!(classifications.len(), 3);
+        assert!(classifications
+            .iter()
+            .all(|classification| classification.function != Tid::new("global_writer")));
+    }
+}
@@ -6,12 +6,15 @@ use crate::intermediate_representation::*;
 use crate::prelude::*;
 
 use super::AccessPattern;
+use super::FunctionPurity;
 
 /// Methods of [`State`] related to handling call instructions.
 mod call_handling;
 
 /// The state tracks knowledge about known register values,
 /// known values on the stack, and access patterns of tracked variables.
+/// It also tracks the side effects of the function (see [`FunctionPurity`])
+/// and the registers written by the function.
 ///
 /// The values and access patterns are tracked as upper bounds.
 /// For example, if some access flag for a variable is set, then the variable may have been accessed,
@@ -26,6 +29,10 @@ pub struct State {
     stack: MemRegion<DataDomain<BitvectorDomain>>,
     /// Maps each tracked ID to an [`AccessPattern`], which tracks known access patterns to the object.
     tracked_ids: DomainMap<AbstractIdentifier, AccessPattern, UnionMergeStrategy>,
+    /// The side effects of the function up to the current state.
+    purity: FunctionPurity,
+    /// The (non-temporary) registers that may have been written to up to the current state.
+    written_registers: BTreeSet<Variable>,
 }
 
 impl State {
@@ -63,9 +70,52 @@ impl State {
             stack_id,
             stack,
             tracked_ids: DomainMap::from(tracked_ids),
+            purity: FunctionPurity::Pure,
+            written_registers: BTreeSet::new(),
         }
     }
 
+    /// Add the callee-saved registers of the given calling convention to the register map.
+    ///
+    /// Each register gets a value pointing to an ID representing its content at function start.
+    /// The IDs are not tracked as parameters,
+    /// they are only used to check whether the registers are restored before returning from the function.
+    pub fn add_callee_saved_register_ids(&mut self, calling_convention: &CallingConvention) {
+        let stack_register = self.stack_id.unwrap_register().clone();
+        for var in calling_convention.callee_saved_register.iter() {
+            if *var != stack_register && !self.register.contains_key(var) {
+                let id =
+                    AbstractIdentifier::new_from_var(self.get_current_function_tid().clone(), var);
+                let value = DataDomain::from_target(id, Bitvector::zero(var.size.into()).into());
+                self.register.insert(var.clone(), value);
+            }
+        }
+    }
+
+    /// Return the callee-saved registers of the given calling convention
+    /// whose values differ from their values at function start.
+    ///
+    /// Only meaningful if the callee-saved registers were added to the start state
+    /// through [`State::add_callee_saved_register_ids`].
+    pub fn get_modified_callee_saved_registers<'a>(
+        &self,
+        calling_convention: &'a CallingConvention,
+    ) -> Vec<&'a Variable> {
+        let stack_register = self.stack_id.unwrap_register();
+        calling_convention
+            .callee_saved_register
+            .iter()
+            .filter(|var| *var != stack_register)
+            .filter(|var| {
+                let id =
+                    AbstractIdentifier::new_from_var(self.get_current_function_tid().clone(), var);
+                let start_value =
+                    DataDomain::from_target(id, Bitvector::zero(var.size.into()).into());
+                self.get_register(var) != start_value
+            })
+            .collect()
+    }
+
     /// Get the value of the given register in the current state.
     pub fn get_register(&self, register: &Variable) -> DataDomain<BitvectorDomain> {
         self.register
@@ -83,6 +133,71 @@ impl State {
         }
     }
 
+    /// Get the side effects of the function up to the current state.
+    pub fn get_purity(&self) -> FunctionPurity {
+        self.purity
+    }
+
+    /// Get the registers that may have been written to up to the current state.
+    pub fn get_written_registers(&self) -> &BTreeSet<Variable> {
+        &self.written_registers
+    }
+
+    /// Record that the function may have the given side effects.
+    pub fn add_side_effect(&mut self, purity: FunctionPurity) {
+        self.purity = self.purity.merge(&purity);
+    }
+
+    /// Record that the given register is written to.
+    /// Writes to temporary registers are ignored.
+    pub fn add_written_register(&mut self, register: &Variable) {
+        if !register.is_temp {
+            self.written_registers.insert(register.clone());
+        }
+    }
+
+    /// Record the side effect of reading from the given address.
+    ///
+    /// Reading from the stack frame of the function (including its stack parameters) is no side effect.
+    pub fn add_read_side_effect(&mut self, address: &DataDomain<BitvectorDomain>) {
+        let is_stack_access = !address.contains_top()
+            && address.get_absolute_value().is_none()
+            && address
+                .get_relative_values()
+                .keys()
+                .all(|id| *id == self.stack_id);
+        if !is_stack_access {
+            self.add_side_effect(FunctionPurity::ReadsMemoryOnly);
+        }
+    }
+
+    /// Record the side effect of writing to the given address.
+    ///
+    /// Writing to the stack frame of the function at a known negative offset is no side effect.
+    /// Other writes to the stack frame (which may hit stack parameters)
+    /// and writes through pointers given as parameters only modify memory reachable through the arguments.
+    /// All other writes may have arbitrary side effects.
+    pub fn add_write_side_effect(&mut self, address: &DataDomain<BitvectorDomain>) {
+        if let Some(offset) = self.get_offset_if_exact_stack_pointer(address) {
+            if !offset.sign_bit().to_bool() {
+                self.add_side_effect(FunctionPurity::WritesArgsOnly);
+            }
+            return;
+        }
+        let is_argument_access = !address.contains_top()
+            && address.get_absolute_value().is_none()
+            && address.get_relative_values().keys().all(|id| {
+                *id == self.stack_id
+                    || (id.get_tid() == self.get_current_function_tid()
+                        && self.tracked_ids.contains_key(id))
+            });
+        if is_argument_access {
+            self.add_side_effect(FunctionPurity::WritesArgsOnly);
+        } else {
+            self.add_side_effect(FunctionPurity::Impure);
+        }
+    }
+
     /// Get the TID of the function that this state belongs to.
     pub fn get_current_function_tid(&self) -> &Tid {
         self.stack_id.get_tid()
@@ -323,6 +438,12 @@ impl AbstractDomain for State {
             stack_id,
             stack,
             tracked_ids: self.tracked_ids.merge(&other.tracked_ids),
+            purity: self.purity.merge(&other.purity),
+            written_registers: self
+                .written_registers
+                .union(&other.written_registers)
+                .cloned()
+                .collect(),
         }
     }
 
@@ -367,6 +488,10 @@ impl State {
             .map(|(index, value)| (format!("{}", *index), value.to_json_compact()))
             .collect();
         json_map.insert("Stack".to_string(), serde_json::Value::Object(stack));
+        json_map.insert(
+            "Purity".to_string(),
+            serde_json::Value::String(format!("{:?}", self.purity)),
+        );
         serde_json::Value::Object(json_map)
     }
 }
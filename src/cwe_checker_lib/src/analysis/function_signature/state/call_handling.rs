@@ -5,12 +5,14 @@ impl State {
     ///
     /// Marks every possible input ID as accessed and writes to every return register a value
     /// that may point to any of the input IDs.
+    /// Since nothing is known about the side effects of the extern symbol, the function is marked as impure.
     pub fn handle_extern_symbol(
         &mut self,
         call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
         calling_convention: &CallingConvention,
     ) {
+        self.add_side_effect(FunctionPurity::Impure);
         let input_ids = self.collect_input_ids_of_call(&extern_symbol.parameters);
         self.clear_non_callee_saved_register(&calling_convention.callee_saved_register);
         self.generate_return_values_for_call(&input_ids, &extern_symbol.return_values, &call.tid);
@@ -22,6 +24,7 @@ impl State {
     ///
     /// Marks every possible input ID as accessed and writes to every return register a value
     /// that may point to any of the input IDs.
+    /// Since nothing is known about the side effects of the function, the function is marked as impure.
     pub fn handle_unknown_function_stub(
         &mut self,
         call: &Term<Jmp>,
@@ -43,6 +46,7 @@ impl State {
                 data_type: None,
             });
         }
+        self.add_side_effect(FunctionPurity::Impure);
         let input_ids = self.collect_input_ids_of_call(&parameters);
         self.clear_non_callee_saved_register(&calling_convention.callee_saved_register);
         self.generate_return_values_for_call(&input_ids, &return_register, &call.tid);
@@ -183,6 +187,35 @@ impl State {
         }
     }
 
+    /// Add the side effects and written registers of a callee to the caller (represented by `self`).
+    ///
+    /// The `callee_state` is the state of the callee at the return site
+    /// and `params` are the parameters of the callee together with their access patterns.
+    /// If the callee writes through a parameter pointer,
+    /// the side effect for the caller depends on where the pointer points to in the caller.
+    pub fn add_side_effects_of_callee(
+        &mut self,
+        callee_state: &State,
+        params: &[(Arg, AccessPattern)],
+    ) {
+        self.written_registers
+            .extend(callee_state.written_registers.iter().cloned());
+        match callee_state.purity {
+            FunctionPurity::WritesArgsOnly => {
+                // The callee may have written to its stack parameters,
+                // which belong to the stack frame of the caller.
+                self.add_side_effect(FunctionPurity::WritesArgsOnly);
+                for (parameter, access_pattern) in params {
+                    if access_pattern.is_mutably_dereferenced() {
+                        let address = self.eval_parameter_arg(parameter);
+                        self.add_write_side_effect(&address);
+                    }
+                }
+            }
+            purity => self.add_side_effect(purity),
+        }
+    }
+
     /// If the given abstract ID represents a possible parameter of the current function
     /// then return an argument object corresponding to the parameter.
     pub fn get_arg_corresponding_to_id(&self, id: &AbstractIdentifier) -> Option<Arg> {
@@ -0,0 +1,155 @@
+//! Exclusion of functions from the analysis.
+//!
+//! Obfuscated functions or functions belonging to packers often degrade the results of the analyses
+//! for the whole binary.
+//! The [`FunctionSkipConfig`] describes which functions should be skipped.
+//! Skipped functions are not added to the control flow graph
+//! (see [`get_program_cfg_with_skipped_subs`](crate::analysis::graph::get_program_cfg_with_skipped_subs)).
+//! Calls to them are handled like calls to unknown extern functions instead.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::LogMessage;
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+
+/// A user-provided predicate deciding whether a function should be skipped.
+pub type SkipPredicate = Arc<dyn Fn(&Term<Sub>) -> bool + Send + Sync>;
+
+/// The configuration determining which functions are excluded from the analysis.
+///
+/// A function is skipped if at least one of the configured conditions holds for it.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct FunctionSkipConfig {
+    /// Functions whose names match one of the glob patterns are skipped.
+    /// The wildcard `*` matches any sequence of characters and `?` matches exactly one character.
+    #[serde(default)]
+    pub name_globs: Vec<String>,
+    /// Functions containing more instructions than this bound are skipped.
+    /// The number of instructions is the number of distinct addresses of the terms of the function.
+    #[serde(default)]
+    pub max_instruction_count: Option<usize>,
+    /// An optional predicate for custom skip conditions.
+    /// Can only be set through the library API.
+    #[serde(skip)]
+    pub predicate: Option<SkipPredicate>,
+}
+
+impl std::fmt::Debug for FunctionSkipConfig {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("FunctionSkipConfig")
+            .field("name_globs", &self.name_globs)
+            .field("max_instruction_count", &self.max_instruction_count)
+            .field("predicate", &self.predicate.as_ref().map(|_| "<closure>"))
+            .finish()
+    }
+}
+
+impl FunctionSkipConfig {
+    /// Set the user-provided predicate for custom skip conditions.
+    pub fn with_predicate(
+        mut self,
+        predicate: impl Fn(&Term<Sub>) -> bool + Send + Sync + 'static,
+    ) -> FunctionSkipConfig {
+        self.predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Returns `true` if the given function should be skipped.
+    pub fn skip_function(&self, sub: &Term<Sub>) -> bool {
+        if self
+            .name_globs
+            .iter()
+            .any(|glob| glob_matches(glob, &sub.term.name))
+        {
+            return true;
+        }
+        if let Some(max_instruction_count) = self.max_instruction_count {
+            if get_instruction_count(sub) > max_instruction_count {
+                return true;
+            }
+        }
+        match &self.predicate {
+            Some(predicate) => predicate(sub),
+            None => false,
+        }
+    }
+
+    /// Get the TIDs of all functions of the program that should be skipped
+    /// together with a log message for each skipped function.
+    pub fn get_skipped_subs(&self, program: &Term<Program>) -> (HashSet<Tid>, Vec<LogMessage>) {
+        let mut skipped_subs = HashSet::new();
+        let mut logs = Vec::new();
+        for sub in program.term.subs.values() {
+            if self.skip_function(sub) {
+                skipped_subs.insert(sub.tid.clone());
+                logs.push(
+                    LogMessage::new_info(format!("Skipped analysis of function {}", sub.term.name))
+                        .location(sub.tid.clone())
+                        .source("Function Skipping"),
+                );
+            }
+        }
+        (skipped_subs, logs)
+    }
+}
+
+/// Get the number of distinct instruction addresses of the terms contained in the function.
+fn get_instruction_count(sub: &Term<Sub>) -> usize {
+    let mut addresses = BTreeSet::new();
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            addresses.insert(&def.tid.address);
+        }
+        for jmp in block.term.jmps.iter() {
+            addresses.insert(&jmp.tid.address);
+        }
+    }
+    addresses.len()
+}
+
+/// Check whether the given name matches the glob pattern.
+fn glob_matches(glob: &str, name: &str) -> bool {
+    let regex_pattern = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+    match regex::Regex::new(&format!("^{}$", regex_pattern)) {
+        Ok(regex) => regex.is_match(name),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_conditions() {
+        let mut sub = Sub::mock("packer_unpack");
+        let mut block = Blk::mock();
+        block.term.defs.push(Def::assign(
+            "def",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0),
+        ));
+        sub.term.blocks.push(block);
+
+        assert!(!FunctionSkipConfig::default().skip_function(&sub));
+        let config = FunctionSkipConfig {
+            name_globs: vec!["packer_*".to_string()],
+            ..Default::default()
+        };
+        assert!(config.skip_function(&sub));
+        let config = FunctionSkipConfig {
+            name_globs: vec!["packer_?".to_string()],
+            ..Default::default()
+        };
+        assert!(!config.skip_function(&sub));
+        let config = FunctionSkipConfig {
+            max_instruction_count: Some(0),
+            ..Default::default()
+        };
+        assert!(config.skip_function(&sub));
+        let config = FunctionSkipConfig::default().with_predicate(|sub| sub.term.blocks.len() == 1);
+        assert!(config.skip_function(&sub));
+    }
+}
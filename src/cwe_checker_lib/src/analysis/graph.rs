@@ -32,6 +32,10 @@
 //! (if the call returns at all).
 //! * Right now indirect calls are handled as if they were extern calls, i.e. an *ExternCallStub* edge is added.
 //! This behaviour will change in the future, when better indirect call handling is implemented.
+//! * Functions excluded from the analysis (see [`get_program_cfg_with_skipped_subs`]) are not added to the graph and calls to them are converted to *ExternCallStub* edges.
+//! * Thunk functions (see [`Sub::is_thunk`]) are not added to the graph either.
+//!   Calls to them are retargeted to the extern symbol they forward to during normalization,
+//!   so that the graph contains *ExternCallStub* edges for them at the callsites.
 //! * For each in-program call ([`image`](../../../../../doc/images/internal_function_call.png)) and corresponding return jump two nodes and four edges are generated:
 //!   * An artificial node *CallReturn* and node *CallSource*
 //!   * A *CRCallStub* edge from the *BlkEnd* node of the callsite to *CallReturn*
@@ -177,6 +181,8 @@ pub enum Edge<'a> {
 struct GraphBuilder<'a> {
     program: &'a Term<Program>,
     extern_subs: HashSet<Tid>,
+    /// Functions that are excluded from the graph. Calls to them are handled like extern calls.
+    skipped_subs: HashSet<Tid>,
     graph: Graph<'a>,
     /// Denotes the NodeIndices of possible call targets
     call_targets: HashMap<Tid, (NodeIndex, NodeIndex)>,
@@ -192,10 +198,25 @@ struct GraphBuilder<'a> {
 
 impl<'a> GraphBuilder<'a> {
     /// create a new builder with an emtpy graph
-    pub fn new(program: &'a Term<Program>, extern_subs: HashSet<Tid>) -> GraphBuilder<'a> {
+    ///
+    /// Thunk functions are added to the skipped functions.
+    pub fn new(
+        program: &'a Term<Program>,
+        extern_subs: HashSet<Tid>,
+        mut skipped_subs: HashSet<Tid>,
+    ) -> GraphBuilder<'a> {
+        skipped_subs.extend(
+            program
+                .term
+                .subs
+                .values()
+                .filter(|sub| sub.term.is_thunk)
+                .map(|sub| sub.tid.clone()),
+        );
         GraphBuilder {
             program,
             extern_subs,
+            skipped_subs,
             graph: Graph::new(),
             call_targets: HashMap::new(),
             jump_targets: HashMap::new(),
@@ -221,9 +242,13 @@ impl<'a> GraphBuilder<'a> {
     /// Each block is only added once,
     /// i.e. for blocks contained in more than one function the extra nodes have to be added separately later.
     /// The `sub` a block is associated with is the `sub` that the block is contained in in the `program` struct.
+    /// Blocks of skipped functions are not added.
     fn add_program_blocks(&mut self) {
         let subs = self.program.term.subs.values();
         for sub in subs {
+            if self.skipped_subs.contains(&sub.tid) {
+                continue;
+            }
             for block in sub.term.blocks.iter() {
                 self.add_block(block, sub);
             }
@@ -233,7 +258,7 @@ impl<'a> GraphBuilder<'a> {
     /// add all subs to the call targets so that call instructions can be linked to the starting block of the corresponding sub.
     fn add_subs_to_call_targets(&mut self) {
         for sub in self.program.term.subs.values() {
-            if !sub.term.blocks.is_empty() {
+            if !sub.term.blocks.is_empty() && !self.skipped_subs.contains(&sub.tid) {
                 let start_block = &sub.term.blocks[0];
                 let target_index = self.jump_targets[&(start_block.tid.clone(), sub.tid.clone())];
                 self.call_targets.insert(sub.tid.clone(), target_index);
@@ -242,6 +267,19 @@ impl<'a> GraphBuilder<'a> {
         }
     }
 
+    /// Returns `true` if calls to the given target never return,
+    /// i.e. if the target is an extern symbol marked as `no_return`
+    /// or a function marked as non-returning during normalization.
+    fn is_non_returning_target(&self, target: &Tid) -> bool {
+        self.program.term.non_returning_subs.contains(target)
+            || self
+                .program
+                .term
+                .extern_symbols
+                .get(target)
+                .is_some_and(|symbol| symbol.no_return)
+    }
+
     /// Add an intraprocedural jump edge from the `source` node to the `target_tid`.
     /// If no node corresponding to the `target_tid` exists,
     /// new nodes corresponding to the (target block, current sub) combination will be created.
@@ -312,6 +350,12 @@ impl<'a> GraphBuilder<'a> {
                 self.add_indirect_jumps(source, jump, untaken_conditional);
             }
             Jmp::Call { target, return_ } => {
+                // Calls to targets that never return get no edges to the return block.
+                let return_ = if self.is_non_returning_target(target) {
+                    &None
+                } else {
+                    return_
+                };
                 // first make sure that the return block exists
                 let return_to_node_option = if let Some(return_tid) = return_ {
                     if let Some((return_to_node, _)) = self
@@ -327,7 +371,7 @@ impl<'a> GraphBuilder<'a> {
                     None
                 };
                 // now add the call edge
-                if self.extern_subs.contains(target) {
+                if self.extern_subs.contains(target) || self.skipped_subs.contains(target) {
                     if let Some(return_to_node) = return_to_node_option {
                         self.graph
                             .add_edge(source, return_to_node, Edge::ExternCallStub(jump));
@@ -490,10 +534,104 @@ impl<'a> GraphBuilder<'a> {
 
 /// Build the interprocedural control flow graph for a program term.
 pub fn get_program_cfg(program: &Term<Program>, extern_subs: HashSet<Tid>) -> Graph {
-    let builder = GraphBuilder::new(program, extern_subs);
+    get_program_cfg_with_skipped_subs(program, extern_subs, HashSet::new())
+}
+
+/// Build the interprocedural control flow graph for a program term
+/// where the functions in `skipped_subs` are excluded from the graph.
+///
+/// Calls to skipped functions are represented by *ExternCallStub* edges,
+/// i.e. analyses handle them like calls to unknown extern functions.
+pub fn get_program_cfg_with_skipped_subs(
+    program: &Term<Program>,
+    extern_subs: HashSet<Tid>,
+    skipped_subs: HashSet<Tid>,
+) -> Graph<'_> {
+    let builder = GraphBuilder::new(program, extern_subs, skipped_subs);
     builder.build()
 }
 
+/// Collect the constants that values are compared to in the functions contained in the graph.
+///
+/// The result maps the TID of each function to the constants `c - 1`, `c` and `c + 1`
+/// for each constant `c` occuring as an operand of an integer comparison in the function
+/// (e.g. the `100` in `i < 100`).
+/// These are the values at which loop counters compared to the constant stop changing,
+/// which makes them good thresholds for widening operations at loop heads.
+pub fn get_comparison_constants(graph: &Graph) -> HashMap<Tid, Vec<Bitvector>> {
+    let mut comparison_constants: HashMap<Tid, Vec<Bitvector>> = HashMap::new();
+    for node in graph.node_weights() {
+        if let Node::BlkStart(block, sub) = node {
+            let constants = comparison_constants.entry(sub.tid.clone()).or_default();
+            for def in block.term.defs.iter() {
+                match &def.term {
+                    Def::Assign { value, .. } | Def::Store { value, .. } => {
+                        collect_comparison_constants(value, constants)
+                    }
+                    Def::Load { .. } => (),
+                }
+            }
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::CBranch { condition, .. } = &jmp.term {
+                    collect_comparison_constants(condition, constants);
+                }
+            }
+        }
+    }
+    comparison_constants
+}
+
+/// Add the constants compared to in the given expression (and their neighbors) to `constants`.
+///
+/// Besides the operands of comparisons this includes the operands of the flag computations
+/// of compare instructions, i.e. the `c` in `x - c == 0` or in the signed borrow flag of `x - c`.
+fn collect_comparison_constants(expression: &Expression, constants: &mut Vec<Bitvector>) {
+    use BinOpType::*;
+    match expression {
+        Expression::BinOp { op, lhs, rhs } => {
+            if matches!(
+                op,
+                IntEqual
+                    | IntNotEqual
+                    | IntLess
+                    | IntSLess
+                    | IntLessEqual
+                    | IntSLessEqual
+                    | IntSBorrow
+            ) {
+                for operand in [lhs, rhs] {
+                    let constant = match operand.as_ref() {
+                        Expression::Const(constant) => constant,
+                        Expression::BinOp {
+                            op: IntSub, rhs, ..
+                        } => match rhs.as_ref() {
+                            Expression::Const(constant) => constant,
+                            _ => continue,
+                        },
+                        _ => continue,
+                    };
+                    let one = Bitvector::one(constant.width());
+                    for value in [
+                        constant.clone() - &one,
+                        constant.clone(),
+                        constant.clone() + &one,
+                    ] {
+                        if !constants.contains(&value) {
+                            constants.push(value);
+                        }
+                    }
+                }
+            }
+            collect_comparison_constants(lhs, constants);
+            collect_comparison_constants(rhs, constants);
+        }
+        Expression::UnOp { arg, .. }
+        | Expression::Cast { arg, .. }
+        | Expression::Subpiece { arg, .. } => collect_comparison_constants(arg, constants),
+        Expression::Var(_) | Expression::Const(_) | Expression::Unknown { .. } => (),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -539,6 +677,7 @@ mod tests {
                 name: "sub1".to_string(),
                 blocks: vec![sub1_blk1, sub1_blk2],
                 calling_convention: None,
+                is_thunk: false,
             },
         };
         let cond_jump = Jmp::CBranch {
@@ -575,6 +714,7 @@ mod tests {
                 name: "sub2".to_string(),
                 blocks: vec![sub2_blk1, sub2_blk2],
                 calling_convention: None,
+                is_thunk: false,
             },
         };
         let program = Term {
@@ -584,6 +724,8 @@ mod tests {
                 extern_symbols: BTreeMap::new(),
                 entry_points: BTreeSet::new(),
                 address_base_offset: 0,
+                symbol_aliases: BTreeMap::new(),
+                non_returning_subs: BTreeSet::new(),
             },
         };
         program
@@ -598,6 +740,28 @@ mod tests {
         assert_eq!(graph.edge_count(), 20);
     }
 
+    #[test]
+    fn skipped_functions_are_excluded() {
+        let program = mock_program();
+        let skipped_subs = HashSet::from([Tid::new("sub2")]);
+        let graph = get_program_cfg_with_skipped_subs(&program, HashSet::new(), skipped_subs);
+        // Only the blocks of `sub1` remain in the graph.
+        assert!(graph
+            .node_indices()
+            .all(|node| graph[node].get_sub().tid == Tid::new("sub1")));
+        let call_edges: Vec<_> = graph
+            .edge_indices()
+            .filter_map(|edge| match graph[edge] {
+                Edge::ExternCallStub(jmp) => Some(jmp.tid.clone()),
+                Edge::Call(_) | Edge::CrCallStub | Edge::CrReturnStub | Edge::CallCombine(_) => {
+                    panic!("Unexpected call edge")
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(call_edges, vec![Tid::new("call")]);
+    }
+
     #[test]
     fn add_indirect_jumps() {
         let indirect_jmp_term = Term {
@@ -620,6 +784,7 @@ mod tests {
                 name: "sub".to_string(),
                 blocks: vec![blk_term],
                 calling_convention: None,
+                is_thunk: false,
             },
         };
         let mut program = Program::mock_empty();
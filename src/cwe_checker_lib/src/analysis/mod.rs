@@ -2,11 +2,17 @@
 //! as well as analyses depending on these modules.
 
 pub mod backward_interprocedural_fixpoint;
+pub mod backward_slicing;
 pub mod dead_variable_elimination;
 pub mod fixpoint;
 pub mod forward_interprocedural_fixpoint;
 pub mod function_signature;
+pub mod function_skipping;
 pub mod graph;
 pub mod interprocedural_fixpoint_generic;
+pub mod patch_diff;
 pub mod pointer_inference;
+pub mod stack_frame_layout;
 pub mod string_abstraction;
+pub mod taint;
+pub mod taint_sources;
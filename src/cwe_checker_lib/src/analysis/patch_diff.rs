@@ -0,0 +1,527 @@
+//! Analysis of only the functions changed between two versions of a binary.
+//!
+//! For the triage of vendor patches one is usually only interested in findings in the patched code.
+//! The [`PatchDiff`] compares the functions of an old and a new version of a binary by structural hashes
+//! of their normalized IR, i.e. hashes that do not depend on the addresses of the code.
+//! The analysis is then restricted to the changed functions
+//! and to their callers and callees up to a configurable depth.
+//! All other functions are skipped,
+//! i.e. calls to them are handled like calls to unknown extern functions.
+//!
+//! Only findings located in changed functions are reported.
+//! Findings that have no counterpart in the results of the old binary
+//! (as determined by the [`ResultsDiff`] matching) are annotated as new in this version.
+//!
+//! Functions are matched between the two versions by their names.
+//! Thus functions with address-based names (e.g. in stripped binaries) are always considered to be changed.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use crate::utils::results_diff::{DiffConfig, ResultsDiff};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// The annotation added to the `other` field of findings only present in the new binary.
+pub const NEW_FINDING_ANNOTATION: [&str; 2] = ["patch_diff", "new in this version"];
+
+/// The configuration of the patch-diff assisted analysis.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct PatchDiffConfig {
+    /// Callers of changed functions up to this call depth are also analyzed.
+    #[serde(default)]
+    pub caller_depth: usize,
+    /// Callees of changed functions up to this call depth are also analyzed.
+    #[serde(default)]
+    pub callee_depth: usize,
+    /// The configuration for matching the findings of the old and the new binary.
+    #[serde(default)]
+    pub diff_config: DiffConfig,
+}
+
+/// The functions changed between two versions of a binary
+/// and the functions that have to be analyzed to check them.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct PatchDiff {
+    /// The TIDs of the functions of the new binary that are new or differ from the old binary.
+    pub changed_subs: BTreeSet<Tid>,
+    /// The TIDs of the functions of the new binary that are analyzed,
+    /// i.e. the changed functions together with their callers and callees up to the configured depth.
+    pub analyzed_subs: BTreeSet<Tid>,
+    /// The TIDs of the functions of the old binary corresponding to the analyzed functions of the new binary.
+    pub old_analyzed_subs: BTreeSet<Tid>,
+}
+
+impl PatchDiff {
+    /// Compute the changed functions between the old and the new project
+    /// and the sets of functions to analyze in both projects.
+    pub fn compute(old: &Project, new: &Project, config: &PatchDiffConfig) -> PatchDiff {
+        let old_hashes: HashMap<&str, u64> = old
+            .program
+            .term
+            .subs
+            .values()
+            .map(|sub| (sub.term.name.as_str(), get_structural_hash(old, sub)))
+            .collect();
+        let changed_subs: BTreeSet<Tid> = new
+            .program
+            .term
+            .subs
+            .values()
+            .filter(|sub| {
+                old_hashes.get(sub.term.name.as_str()) != Some(&get_structural_hash(new, sub))
+            })
+            .map(|sub| sub.tid.clone())
+            .collect();
+
+        let callees = get_callees(new);
+        let mut callers: HashMap<&Tid, BTreeSet<&Tid>> = HashMap::new();
+        for (caller, callees) in callees.iter() {
+            for callee in callees {
+                callers.entry(callee).or_default().insert(caller);
+            }
+        }
+        let mut analyzed_subs = changed_subs.clone();
+        analyzed_subs.extend(get_closure(&changed_subs, &callers, config.caller_depth));
+        analyzed_subs.extend(get_closure(&changed_subs, &callees, config.callee_depth));
+
+        let analyzed_names: HashSet<&str> = analyzed_subs
+            .iter()
+            .filter_map(|tid| new.program.term.subs.get(tid))
+            .map(|sub| sub.term.name.as_str())
+            .collect();
+        let old_analyzed_subs = old
+            .program
+            .term
+            .subs
+            .values()
+            .filter(|sub| analyzed_names.contains(sub.term.name.as_str()))
+            .map(|sub| sub.tid.clone())
+            .collect();
+        PatchDiff {
+            changed_subs,
+            analyzed_subs,
+            old_analyzed_subs,
+        }
+    }
+
+    /// Get the TIDs of the functions of the new project that are not analyzed.
+    pub fn get_skipped_subs(&self, new: &Project) -> HashSet<Tid> {
+        get_complement(&new.program, &self.analyzed_subs)
+    }
+
+    /// Get the TIDs of the functions of the old project that are not analyzed.
+    pub fn get_old_skipped_subs(&self, old: &Project) -> HashSet<Tid> {
+        get_complement(&old.program, &self.old_analyzed_subs)
+    }
+
+    /// Keep only the findings of the new binary that are located in changed functions
+    /// and annotate those without a counterpart in the findings of the old binary.
+    ///
+    /// The location of a finding is given by its first address.
+    pub fn filter_and_annotate_findings(
+        &self,
+        new: &Project,
+        old_cwes: &[CweWarning],
+        new_cwes: Vec<CweWarning>,
+        diff_config: &DiffConfig,
+    ) -> Vec<CweWarning> {
+        let changed_addresses: HashSet<&str> = self
+            .changed_subs
+            .iter()
+            .filter_map(|tid| new.program.term.subs.get(tid))
+            .flat_map(get_term_addresses)
+            .collect();
+        let mut cwes: Vec<CweWarning> = new_cwes
+            .into_iter()
+            .filter(|cwe| {
+                cwe.addresses
+                    .first()
+                    .is_some_and(|address| changed_addresses.contains(address.as_str()))
+            })
+            .collect();
+        let mut added = ResultsDiff::compute(old_cwes, &cwes, diff_config).added;
+        for cwe in cwes.iter_mut() {
+            if let Some(index) = added.iter().position(|added_cwe| added_cwe == cwe) {
+                added.swap_remove(index);
+                cwe.other.push(
+                    NEW_FINDING_ANNOTATION
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect(),
+                );
+            }
+        }
+        cwes
+    }
+}
+
+/// Run an analysis restricted to the functions changed between the old and the new project.
+///
+/// The `analyze` function runs the actual analysis on the given project
+/// with the given set of unchanged functions excluded and returns the generated CWE warnings.
+/// Calls to the excluded functions should be handled through their summaries
+/// (see [`analyze_patch_diff`](crate::utils::pipeline::analyze_patch_diff)).
+/// It is run once for each project.
+/// Returns the computed patch diff together with the findings in the changed functions of the new project.
+pub fn run_patch_diff_analysis(
+    old: &Project,
+    new: &Project,
+    config: &PatchDiffConfig,
+    mut analyze: impl FnMut(&Project, HashSet<Tid>) -> Vec<CweWarning>,
+) -> (PatchDiff, Vec<CweWarning>) {
+    let patch_diff = PatchDiff::compute(old, new, config);
+    let old_cwes = analyze(old, patch_diff.get_old_skipped_subs(old));
+    let new_cwes = analyze(new, patch_diff.get_skipped_subs(new));
+    let cwes =
+        patch_diff.filter_and_annotate_findings(new, &old_cwes, new_cwes, &config.diff_config);
+    (patch_diff, cwes)
+}
+
+/// Compute a hash of the IR of the function that does not depend on addresses.
+///
+/// Term IDs are replaced by the index of the targeted block inside the function
+/// or by the name of the called function.
+/// Constants larger or equal to the smallest function address of the project are treated as addresses
+/// and replaced by zero.
+pub fn get_structural_hash(project: &Project, sub: &Term<Sub>) -> u64 {
+    let min_code_address = project
+        .program
+        .term
+        .subs
+        .values()
+        .filter_map(|sub| u64::from_str_radix(&sub.tid.address, 16).ok())
+        .min()
+        .unwrap_or(u64::MAX);
+    let block_indices: HashMap<&Tid, usize> = sub
+        .term
+        .blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (&block.tid, index))
+        .collect();
+    let normalize_block_tid = |tid: &Tid| block_indices.get(tid).copied();
+    let normalize_return = |return_: &Option<Tid>| return_.as_ref().map(normalize_block_tid);
+    let mut hasher = DefaultHasher::new();
+    for block in sub.term.blocks.iter() {
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Assign { var, value } => {
+                    ("assign", var, normalize_expression(value, min_code_address)).hash(&mut hasher)
+                }
+                Def::Load { var, address } => {
+                    ("load", var, normalize_expression(address, min_code_address)).hash(&mut hasher)
+                }
+                Def::Store { address, value } => (
+                    "store",
+                    normalize_expression(address, min_code_address),
+                    normalize_expression(value, min_code_address),
+                )
+                    .hash(&mut hasher),
+            }
+        }
+        for jmp in block.term.jmps.iter() {
+            match &jmp.term {
+                Jmp::Branch(target) => ("branch", normalize_block_tid(target)).hash(&mut hasher),
+                Jmp::BranchInd(target) => {
+                    ("branch_ind", normalize_expression(target, min_code_address)).hash(&mut hasher)
+                }
+                Jmp::CBranch { target, condition } => (
+                    "cbranch",
+                    normalize_block_tid(target),
+                    normalize_expression(condition, min_code_address),
+                )
+                    .hash(&mut hasher),
+                Jmp::Call { target, return_ } => (
+                    "call",
+                    get_callee_name(project, target),
+                    normalize_return(return_),
+                )
+                    .hash(&mut hasher),
+                Jmp::CallInd { target, return_ } => (
+                    "call_ind",
+                    normalize_expression(target, min_code_address),
+                    normalize_return(return_),
+                )
+                    .hash(&mut hasher),
+                Jmp::Return(expression) => {
+                    ("return", normalize_expression(expression, min_code_address)).hash(&mut hasher)
+                }
+                Jmp::CallOther {
+                    description,
+                    return_,
+                } => ("call_other", description, normalize_return(return_)).hash(&mut hasher),
+            }
+        }
+        "block_end".hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Replace all constants in the expression that may be addresses by zero.
+fn normalize_expression(expression: &Expression, min_code_address: u64) -> Expression {
+    let mut expression = expression.clone();
+    replace_address_constants(&mut expression, min_code_address);
+    expression
+}
+
+/// Replace all constants larger than or equal to `min_code_address` in the expression by zero.
+fn replace_address_constants(expression: &mut Expression, min_code_address: u64) {
+    match expression {
+        Expression::Const(value) => {
+            if value
+                .try_to_u64()
+                .is_ok_and(|value| value >= min_code_address)
+            {
+                *value = Bitvector::zero(value.width());
+            }
+        }
+        Expression::BinOp { lhs, rhs, .. } => {
+            replace_address_constants(lhs, min_code_address);
+            replace_address_constants(rhs, min_code_address);
+        }
+        Expression::UnOp { arg, .. }
+        | Expression::Cast { arg, .. }
+        | Expression::Subpiece { arg, .. } => replace_address_constants(arg, min_code_address),
+        Expression::Var(_) | Expression::Unknown { .. } => (),
+    }
+}
+
+/// Get the name of the function or extern symbol that the call target TID belongs to.
+fn get_callee_name<'a>(project: &'a Project, target: &Tid) -> Option<&'a str> {
+    match project.program.term.subs.get(target) {
+        Some(sub) => Some(sub.term.name.as_str()),
+        None => project
+            .program
+            .term
+            .extern_symbols
+            .get(target)
+            .map(|symbol| symbol.name.as_str()),
+    }
+}
+
+/// Get the map from the TIDs of the functions of the project to the TIDs of the functions directly called by them.
+fn get_callees(project: &Project) -> HashMap<&Tid, BTreeSet<&Tid>> {
+    let mut callees: HashMap<&Tid, BTreeSet<&Tid>> = HashMap::new();
+    for sub in project.program.term.subs.values() {
+        let sub_callees = callees.entry(&sub.tid).or_default();
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if project.program.term.subs.contains_key(target) {
+                        sub_callees.insert(target);
+                    }
+                }
+            }
+        }
+    }
+    callees
+}
+
+/// Get all functions reachable from the start functions in at most `depth` steps in the given call relation.
+fn get_closure<'a>(
+    start: &'a BTreeSet<Tid>,
+    relation: &HashMap<&'a Tid, BTreeSet<&'a Tid>>,
+    depth: usize,
+) -> BTreeSet<Tid> {
+    let mut closure: BTreeSet<&Tid> = start.iter().collect();
+    let mut frontier: BTreeSet<&Tid> = closure.clone();
+    for _ in 0..depth {
+        frontier = frontier
+            .iter()
+            .filter_map(|tid| relation.get(*tid))
+            .flatten()
+            .copied()
+            .filter(|tid| !closure.contains(tid))
+            .collect();
+        if frontier.is_empty() {
+            break;
+        }
+        closure.extend(frontier.iter().copied());
+    }
+    closure.into_iter().cloned().collect()
+}
+
+/// Get the TIDs of all functions of the program that are not contained in the given set.
+fn get_complement(program: &Term<Program>, subs: &BTreeSet<Tid>) -> HashSet<Tid> {
+    program
+        .term
+        .subs
+        .keys()
+        .filter(|tid| !subs.contains(*tid))
+        .cloned()
+        .collect()
+}
+
+/// Get the addresses of all blocks, `Def`s and `Jmp`s contained in the function.
+fn get_term_addresses(sub: &Term<Sub>) -> impl Iterator<Item = &str> {
+    sub.term.blocks.iter().flat_map(|block| {
+        std::iter::once(block.tid.address.as_str())
+            .chain(block.term.defs.iter().map(|def| def.tid.address.as_str()))
+            .chain(block.term.jmps.iter().map(|jmp| jmp.tid.address.as_str()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a TID with the given ID and address.
+    fn tid(id: &str, address: u64) -> Tid {
+        let mut tid = Tid::new(id);
+        tid.address = format!("{:08x}", address);
+        tid
+    }
+
+    /// Create a function with one block containing stores to global memory at the given offsets
+    /// and the given calls to other functions.
+    /// The addresses of the terms of the function start at `address`.
+    fn mock_sub(name: &str, address: u64, store_offsets: &[i64], callees: &[&str]) -> Term<Sub> {
+        let mut block = Blk::mock_with_tid(&format!("blk_{}", name));
+        block.tid = tid(&format!("blk_{}", name), address);
+        for (index, offset) in store_offsets.iter().enumerate() {
+            let mut store = Def::store(
+                &format!("store_{}_{}", name, index),
+                Expression::const_from_i64(0x10_0000 + address as i64 + offset),
+                Expression::const_from_i64(*offset),
+            );
+            store.tid = tid(&store.tid.to_string(), address + 4 * index as u64);
+            block.term.defs.push(store);
+        }
+        block.term.jmps = callees
+            .iter()
+            .map(|callee| {
+                let mut call = Jmp::call(&format!("call_{}", callee), callee, None);
+                call.tid = tid(&call.tid.to_string(), address + 0x40);
+                call
+            })
+            .collect();
+        let mut sub = Sub::mock(name);
+        sub.tid = tid(name, address);
+        sub.term.blocks.push(block);
+        sub
+    }
+
+    /// Create a project with a `main` function calling `func_a` and `func_b`.
+    /// `func_b` contains the given store offsets.
+    /// All functions of the project start at `base_address`.
+    fn mock_project(base_address: u64, func_b_store_offsets: &[i64]) -> Project {
+        let mut project = Project::mock_empty();
+        let subs = [
+            mock_sub("main", base_address, &[0], &["func_a", "func_b"]),
+            mock_sub("func_a", base_address + 0x100, &[8], &[]),
+            mock_sub("func_b", base_address + 0x200, func_b_store_offsets, &[]),
+        ];
+        // Call targets have to refer to the TIDs of the functions including their addresses.
+        let sub_tids: HashMap<String, Tid> = subs
+            .iter()
+            .map(|sub| (sub.term.name.clone(), sub.tid.clone()))
+            .collect();
+        for mut sub in subs {
+            for jmp in sub.term.blocks[0].term.jmps.iter_mut() {
+                if let Jmp::Call { target, .. } = &mut jmp.term {
+                    *target = sub_tids[&target.to_string()].clone();
+                }
+            }
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    /// A mock analysis generating one finding for each store in a non-skipped function.
+    fn mock_analysis(project: &Project, skipped_subs: HashSet<Tid>) -> Vec<CweWarning> {
+        let mut cwes = Vec::new();
+        for sub in project.program.term.subs.values() {
+            if skipped_subs.contains(&sub.tid) {
+                continue;
+            }
+            for def in sub.term.blocks[0].term.defs.iter() {
+                if let Def::Store { value, .. } = &def.term {
+                    cwes.push(
+                        CweWarning::new(
+                            "CWE787",
+                            "0.2",
+                            format!("Store of {:?} in {}", value, sub.term.name),
+                        )
+                        .addresses(vec![def.tid.canonical_address().to_string()])
+                        .tids(vec![def.tid.to_string()]),
+                    );
+                }
+            }
+        }
+        cwes
+    }
+
+    #[test]
+    fn structural_hashes_ignore_addresses() {
+        let old = mock_project(0x1000, &[16]);
+        let relocated = mock_project(0x2000, &[16]);
+        let changed = mock_project(0x2000, &[16, 24]);
+        let hash = |project: &Project, name: &str| {
+            let sub = project
+                .program
+                .term
+                .subs
+                .values()
+                .find(|sub| sub.term.name == name)
+                .unwrap();
+            get_structural_hash(project, sub)
+        };
+        for name in ["main", "func_a", "func_b"] {
+            assert_eq!(hash(&old, name), hash(&relocated, name));
+        }
+        assert_eq!(hash(&old, "func_a"), hash(&changed, "func_a"));
+        assert_ne!(hash(&old, "func_b"), hash(&changed, "func_b"));
+    }
+
+    #[test]
+    fn changed_subs_and_closure() {
+        let old = mock_project(0x1000, &[16]);
+        let new = mock_project(0x2000, &[16, 24]);
+        let func_b = tid("func_b", 0x2200);
+
+        let patch_diff = PatchDiff::compute(&old, &new, &PatchDiffConfig::default());
+        assert_eq!(patch_diff.changed_subs, BTreeSet::from([func_b.clone()]));
+        assert_eq!(patch_diff.analyzed_subs, BTreeSet::from([func_b.clone()]));
+        assert_eq!(
+            patch_diff.old_analyzed_subs,
+            BTreeSet::from([tid("func_b", 0x1200)])
+        );
+        assert_eq!(patch_diff.get_skipped_subs(&new).len(), 2);
+
+        let config = PatchDiffConfig {
+            caller_depth: 1,
+            ..Default::default()
+        };
+        let patch_diff = PatchDiff::compute(&old, &new, &config);
+        assert_eq!(
+            patch_diff.analyzed_subs,
+            BTreeSet::from([func_b, tid("main", 0x2000)])
+        );
+        assert_eq!(
+            patch_diff.get_skipped_subs(&new),
+            HashSet::from([tid("func_a", 0x2100)])
+        );
+    }
+
+    #[test]
+    fn findings_in_changed_code() {
+        let old = mock_project(0x1000, &[16]);
+        let new = mock_project(0x1000, &[16, 24]);
+        let config = PatchDiffConfig {
+            caller_depth: 1,
+            ..Default::default()
+        };
+        let (_, cwes) = run_patch_diff_analysis(&old, &new, &config, mock_analysis);
+        // The finding in `main` is not reported, since `main` did not change.
+        assert_eq!(cwes.len(), 2);
+        let new_finding: Vec<String> = NEW_FINDING_ANNOTATION
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(!cwes[0].other.contains(&new_finding));
+        assert!(cwes[1].description.contains("func_b"));
+        assert!(cwes[1].other.contains(&new_finding));
+    }
+}
@@ -0,0 +1,375 @@
+//! Collection of per-object memory access histograms.
+//!
+//! For each heap object tracked by the pointer inference the histogram records
+//! which offsets of the object were read or written and with which access sizes.
+//! This can be used to guide the manual recovery of struct layouts when reversing a binary.
+
+use super::*;
+use crate::abstract_domain::AbstractIdentifier;
+
+/// The maximal number of distinct histogram entries tracked for a single object.
+/// Accesses that would create new entries beyond this bound are only counted.
+pub const MAX_ENTRIES_PER_OBJECT: usize = 256;
+
+/// The kind of a memory access.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum AccessKind {
+    /// A read access through a `Load` instruction.
+    Read,
+    /// A write access through a `Store` instruction.
+    Write,
+}
+
+/// A single memory access to an abstract object.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct ObjectAccess {
+    /// The ID of the accessed object.
+    pub object_id: AbstractIdentifier,
+    /// The interval of possible offsets of the access relative to the start of the object
+    /// or `None` if the offset is unknown.
+    pub offset: Option<(i64, i64)>,
+    /// The size of the access.
+    pub size: ByteSize,
+    /// Whether the access is a read or a write access.
+    pub kind: AccessKind,
+}
+
+/// The access histogram of a single object.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ObjectAccessHistogram {
+    /// The number of accesses for each combination of offset interval, access size and access kind.
+    entries: BTreeMap<((i64, i64), ByteSize, AccessKind), u64>,
+    /// The number of accesses with unknown offset.
+    unknown_offset_accesses: u64,
+    /// The number of accesses that were not recorded because the entry bound was reached.
+    dropped_accesses: u64,
+}
+
+impl ObjectAccessHistogram {
+    /// Record an access with the given offset interval, size and kind.
+    fn add_access(&mut self, offset: Option<(i64, i64)>, size: ByteSize, kind: AccessKind) {
+        let offset = match offset {
+            Some(offset) => offset,
+            None => {
+                self.unknown_offset_accesses += 1;
+                return;
+            }
+        };
+        let key = (offset, size, kind);
+        if let Some(count) = self.entries.get_mut(&key) {
+            *count += 1;
+        } else if self.entries.len() < MAX_ENTRIES_PER_OBJECT {
+            self.entries.insert(key, 1);
+        } else {
+            self.dropped_accesses += 1;
+        }
+    }
+
+    /// Get the number of recorded accesses with the given offset interval, size and kind.
+    pub fn get_count(&self, offset: (i64, i64), size: ByteSize, kind: AccessKind) -> u64 {
+        self.entries
+            .get(&(offset, size, kind))
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// Get the number of accesses with unknown offset.
+    pub fn get_unknown_offset_accesses(&self) -> u64 {
+        self.unknown_offset_accesses
+    }
+
+    /// Generate the report of the histogram with the entries sorted by offset.
+    pub fn to_report(&self) -> ObjectAccessHistogramReport {
+        let accesses = self
+            .entries
+            .iter()
+            .map(|(((start, end), size, kind), count)| AccessHistogramEntry {
+                offset: if start == end {
+                    format!("{}", start)
+                } else {
+                    format!("[{}, {}]", start, end)
+                },
+                size: u64::from(*size),
+                kind: *kind,
+                count: *count,
+            })
+            .collect();
+        ObjectAccessHistogramReport {
+            accesses,
+            unknown_offset_accesses: self.unknown_offset_accesses,
+            dropped_accesses: self.dropped_accesses,
+        }
+    }
+
+    /// Generate a JSON representation of the histogram with the entries sorted by offset.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.to_report()).unwrap()
+    }
+}
+
+/// An entry of the report of an [`ObjectAccessHistogram`].
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct AccessHistogramEntry {
+    /// The offset of the accesses or the interval `[start, end]` of possible offsets.
+    pub offset: String,
+    /// The size of the accesses in bytes.
+    pub size: u64,
+    /// Whether the accesses are read or write accesses.
+    pub kind: AccessKind,
+    /// The number of accesses.
+    pub count: u64,
+}
+
+/// The report of an [`ObjectAccessHistogram`] as contained in the JSON output.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct ObjectAccessHistogramReport {
+    /// The recorded accesses sorted by offset.
+    pub accesses: Vec<AccessHistogramEntry>,
+    /// The number of accesses with unknown offset.
+    pub unknown_offset_accesses: u64,
+    /// The number of accesses that were not recorded because the entry bound was reached.
+    pub dropped_accesses: u64,
+}
+
+/// The reports of the access histograms of all heap objects,
+/// grouped by the allocation site and the location of the returned pointer.
+pub type AccessHistogramsReport = BTreeMap<String, BTreeMap<String, ObjectAccessHistogramReport>>;
+
+/// The access histograms of all heap objects tracked by the pointer inference.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct AccessHistograms {
+    histograms: BTreeMap<AbstractIdentifier, ObjectAccessHistogram>,
+}
+
+impl AccessHistograms {
+    /// Record the given object access.
+    pub fn add_access(&mut self, access: &ObjectAccess) {
+        self.histograms
+            .entry(access.object_id.clone())
+            .or_default()
+            .add_access(access.offset, access.size, access.kind);
+    }
+
+    /// Get the histogram of the object with the given ID.
+    pub fn get_histogram(&self, object_id: &AbstractIdentifier) -> Option<&ObjectAccessHistogram> {
+        self.histograms.get(object_id)
+    }
+
+    /// Generate the report of all histograms grouped by the allocation site of the objects.
+    pub fn to_report(&self) -> AccessHistogramsReport {
+        let mut allocation_sites = AccessHistogramsReport::new();
+        for (object_id, histogram) in self.histograms.iter() {
+            allocation_sites
+                .entry(format!("{}", object_id.get_tid()))
+                .or_default()
+                .insert(
+                    format!("{}", object_id.get_location()),
+                    histogram.to_report(),
+                );
+        }
+        allocation_sites
+    }
+
+    /// Generate a JSON representation of all histograms grouped by the allocation site of the objects.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.to_report()).unwrap()
+    }
+}
+
+/// Compute the access histograms for all heap objects
+/// by replaying the effects of all `Def` terms on the states computed by the pointer inference.
+pub fn compute_access_histograms(pointer_inference: &PointerInference) -> AccessHistograms {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as _;
+
+    let mut histograms = AccessHistograms::default();
+    let graph = pointer_inference.computation.get_graph();
+    let context = pointer_inference.get_context();
+    for (node_id, node) in graph.node_references() {
+        if let Node::BlkStart(block, _sub) = node {
+            if let Some(NodeValue::Value(state)) =
+                pointer_inference.computation.get_node_value(node_id)
+            {
+                let mut state = state.clone();
+                for def in &block.term.defs {
+                    for access in state.get_heap_object_accesses(def) {
+                        histograms.add_access(&access);
+                    }
+                    state = match context.update_def(&state, def) {
+                        Some(new_state) => new_state,
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+    histograms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::AbstractLocation;
+    use std::collections::HashSet;
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("malloc");
+
+        let mut sub = Sub::mock("func");
+        let mut block1 = Blk::mock_with_tid("block1");
+        block1
+            .term
+            .jmps
+            .push(Jmp::call("call_malloc", "malloc", Some("block2")));
+        let mut block2 = Blk::mock_with_tid("block2");
+        block2.term.defs = vec![
+            Def::store(
+                "store_0",
+                Expression::var("RAX", 8),
+                Expression::const_from_i64(0),
+            ),
+            Def::load(
+                "load_8",
+                Variable::mock("RCX", 8),
+                Expression::var("RAX", 8).plus_const(8),
+            ),
+            Def::store(
+                "store_16",
+                Expression::var("RAX", 8).plus_const(16),
+                Expression::const_from_i32(1),
+            ),
+            Def::load(
+                "load_unknown",
+                Variable::mock("RCX", 8),
+                Expression::var("RAX", 8).plus(Expression::var("RDX", 8)),
+            ),
+        ];
+        block2.term.jmps.push(Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        sub.term.blocks = vec![block1, block2];
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    #[test]
+    fn access_histogram_of_heap_object() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs: HashSet<Tid> = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pointer_inference.compute();
+        pointer_inference.collect_access_histograms();
+
+        let histograms = pointer_inference.get_access_histograms().unwrap();
+        let object_id = AbstractIdentifier::new(
+            Tid::new("call_malloc"),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        );
+        let histogram = histograms.get_histogram(&object_id).unwrap();
+        assert_eq!(
+            histogram.get_count((0, 0), ByteSize::new(8), AccessKind::Write),
+            1
+        );
+        assert_eq!(
+            histogram.get_count((8, 8), ByteSize::new(8), AccessKind::Read),
+            1
+        );
+        assert_eq!(
+            histogram.get_count((16, 16), ByteSize::new(4), AccessKind::Write),
+            1
+        );
+        assert_eq!(histogram.get_unknown_offset_accesses(), 1);
+
+        let json = histograms.to_json();
+        let accesses = &json["call_malloc"]["RAX"]["accesses"];
+        let offsets: Vec<&str> = accesses
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["offset"].as_str().unwrap())
+            .collect();
+        assert_eq!(offsets, vec!["0", "8", "16"]);
+    }
+
+    #[test]
+    fn access_histograms_are_collected_before_node_values_are_discarded() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs: HashSet<Tid> = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let object_id = AbstractIdentifier::new(
+            Tid::new("call_malloc"),
+            AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+        );
+        let config = Config {
+            collect_access_histograms: true,
+            node_value_retention: NodeValueRetention::EntryExitOnly,
+            ..super::super::tests::mock_config()
+        };
+        let pointer_inference = run(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            config,
+            false,
+            false,
+        );
+        let histograms = pointer_inference.get_access_histograms().unwrap();
+        let histogram = histograms.get_histogram(&object_id).unwrap();
+        assert_eq!(
+            histogram.get_count((0, 0), ByteSize::new(8), AccessKind::Write),
+            1
+        );
+
+        // Collecting the histograms after the node values were discarded is noted in the logs.
+        let mut pointer_inference = PointerInference::mock_with_node_value_retention(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            NodeValueRetention::EntryExitOnly,
+        );
+        pointer_inference.compute();
+        pointer_inference.discard_unretained_node_values();
+        pointer_inference.collect_access_histograms();
+        assert!(pointer_inference
+            .get_access_histograms()
+            .unwrap()
+            .get_histogram(&object_id)
+            .is_none());
+        assert!(pointer_inference
+            .collected_logs
+            .0
+            .iter()
+            .any(|log| log.text.contains("access histograms are incomplete")));
+    }
+
+    #[test]
+    fn histogram_entries_are_bounded() {
+        let mut histogram = ObjectAccessHistogram::default();
+        for offset in 0..(MAX_ENTRIES_PER_OBJECT as i64 + 10) {
+            histogram.add_access(Some((offset, offset)), ByteSize::new(1), AccessKind::Read);
+        }
+        assert_eq!(histogram.entries.len(), MAX_ENTRIES_PER_OBJECT);
+        assert_eq!(histogram.dropped_accesses, 10);
+    }
+}
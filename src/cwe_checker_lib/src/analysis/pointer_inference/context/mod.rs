@@ -1,14 +1,27 @@
-use super::object::ObjectType;
-use crate::analysis::graph::Graph;
+use super::custom_allocator::{
+    generate_custom_allocator_stubs, AllocatedPointerLocation, AllocatorFamily, CustomAllocator,
+};
+use super::event_polling::EventPollingParameters;
+use super::harness::HarnessSpec;
+use super::memory_access_checks::{BoundsCheckedAccess, MemoryAccessRecord};
+use super::memory_copy::MemoryCopyParameters;
+use super::memory_mapping::{MappingProtection, MemoryProtectionParameters};
+use super::object::{ObjectType, ResizeInfo};
+use super::thread_creation::ThreadCreationParameters;
+use crate::analysis::function_signature::{
+    CallingConventionDiscrepancy, OwnershipClassification, PurityClassification,
+};
+use crate::analysis::graph::{get_comparison_constants, Edge, Graph, Node};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
+use crate::utils::feedback::{report_imprecision, ImprecisionReason, MissingFact};
 use crate::utils::log::*;
 use crate::{abstract_domain::*, utils::binary::RuntimeMemoryImage};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use super::state::State;
 use super::ValueDomain;
-use super::{Config, Data, VERSION};
+use super::{Config, Data, MemoryAccessCounter, VERSION};
 
 // contains trait implementations for the `Context` struct,
 // especially the implementation of the `interprocedural_fixpoint::Context` trait.
@@ -37,6 +50,59 @@ pub struct Context<'a> {
     pub allocation_symbols: Vec<String>,
     /// Names of `free`-like extern functions.
     pub deallocation_symbols: Vec<String>,
+    /// Descriptions of custom allocation-deallocation function pairs.
+    pub custom_allocators: Vec<CustomAllocator>,
+    /// Extern symbol stubs for custom allocation and deallocation functions contained in the binary.
+    pub custom_allocator_stubs: BTreeMap<Tid, ExternSymbol>,
+    /// Maps the TIDs of all calls to known allocation functions to the allocator family of the called function.
+    pub allocation_site_families: BTreeMap<Tid, AllocatorFamily>,
+    /// Maps the TIDs of all calls to known allocation functions to the name of the called function.
+    pub allocation_site_symbols: BTreeMap<Tid, String>,
+    /// Names of `dlsym`-like extern functions.
+    pub function_lookup_symbols: Vec<String>,
+    /// Names of extern functions that may switch the execution context.
+    pub context_switch_symbols: Vec<String>,
+    /// Maps the names of `makecontext`-like extern functions to the index of their entry function parameter.
+    pub coroutine_entry_index: BTreeMap<String, usize>,
+    /// Maps the names of `pthread_create`-like extern functions to the indices of their start routine and argument parameters.
+    pub thread_creation_symbols: BTreeMap<String, ThreadCreationParameters>,
+    /// Names of `getenv`-like extern functions.
+    pub environment_lookup_symbols: Vec<String>,
+    /// Names of extern functions that modify the environment.
+    pub environment_modification_symbols: Vec<String>,
+    /// Maps the names of `mmap`-like and `mprotect`-like extern functions to the indices of their memory protection parameters.
+    pub memory_protection_symbols: BTreeMap<String, MemoryProtectionParameters>,
+    /// Maps the names of event polling functions to the fields in memory written by them.
+    pub event_polling_symbols: BTreeMap<String, EventPollingParameters>,
+    /// Maps the names of memory copy functions to the indices of their parameters.
+    pub memory_copy_symbols: BTreeMap<String, MemoryCopyParameters>,
+    /// Whether generated CWE warnings should contain the abstract facts that they were derived from.
+    pub collect_warning_evidence: bool,
+    /// Names of extern functions that are assumed to be free of side effects.
+    pub assume_pure: Vec<String>,
+    /// Names of `strlen`-like extern functions.
+    pub string_length_symbols: Vec<String>,
+    /// Names of `__libc_start_main`-like extern functions.
+    pub program_start_symbols: Vec<String>,
+    /// The maximal number of relative targets of a value after merging states.
+    pub max_pointer_targets: Option<usize>,
+    /// The harness specification, if only the target function of a harness is analyzed.
+    pub harness: Option<HarnessSpec>,
+    /// Maps the TIDs of functions whose observed behavior is trusted over their calling convention
+    /// to the discrepancies between their behavior and their calling convention.
+    pub trusted_convention_discrepancies: BTreeMap<Tid, CallingConventionDiscrepancy>,
+    /// Maps the names of extern functions to the indices of their parameters pointing to written memory.
+    pub written_pointer_parameters: BTreeMap<String, Vec<usize>>,
+    /// Maps the TIDs of internal functions to the classification of their side effects.
+    pub function_purities: BTreeMap<Tid, PurityClassification>,
+    /// Maps the TIDs of internal functions to the ownership of their pointer parameters.
+    pub parameter_ownership: BTreeMap<Tid, OwnershipClassification>,
+    /// Counts the memory accesses evaluated by the transfer functions.
+    pub memory_access_counter: MemoryAccessCounter,
+    /// Maps the TIDs of blocks where several intraprocedural control flow paths merge (e.g. loop heads)
+    /// to the widening thresholds of the containing function,
+    /// i.e. the constants that values are compared to in the function.
+    pub widening_thresholds: HashMap<Tid, Vec<Bitvector>>,
 }
 
 impl<'a> Context<'a> {
@@ -49,7 +115,9 @@ impl<'a> Context<'a> {
         config: Config,
         log_collector: crossbeam_channel::Sender<LogThreadMsg>,
     ) -> Context<'a> {
-        Context {
+        let custom_allocator_stubs =
+            generate_custom_allocator_stubs(project, &config.custom_allocators);
+        let mut context = Context {
             graph: control_flow_graph,
             project,
             runtime_memory_image,
@@ -57,9 +125,141 @@ impl<'a> Context<'a> {
             log_collector,
             allocation_symbols: config.allocation_symbols,
             deallocation_symbols: config.deallocation_symbols,
+            custom_allocators: config.custom_allocators,
+            custom_allocator_stubs,
+            allocation_site_families: BTreeMap::new(),
+            allocation_site_symbols: BTreeMap::new(),
+            function_lookup_symbols: config.function_lookup_symbols,
+            context_switch_symbols: config.context_switch_symbols,
+            coroutine_entry_index: config.coroutine_entry_index,
+            thread_creation_symbols: config.thread_creation_symbols,
+            environment_lookup_symbols: config.environment_lookup_symbols,
+            environment_modification_symbols: config.environment_modification_symbols,
+            memory_protection_symbols: config.memory_protection_symbols,
+            event_polling_symbols: config.event_polling_symbols,
+            memory_copy_symbols: config.memory_copy_symbols,
+            collect_warning_evidence: config.collect_warning_evidence,
+            assume_pure: config.assume_pure,
+            string_length_symbols: config.string_length_symbols,
+            program_start_symbols: config.program_start_symbols,
+            max_pointer_targets: config.max_pointer_targets,
+            harness: config.harness,
+            trusted_convention_discrepancies: config
+                .trusted_convention_discrepancies
+                .into_iter()
+                .map(|discrepancy| (discrepancy.function.clone(), discrepancy))
+                .collect(),
+            written_pointer_parameters: config.written_pointer_parameters,
+            function_purities: config
+                .function_purities
+                .into_iter()
+                .map(|classification| (classification.function.clone(), classification))
+                .collect(),
+            parameter_ownership: config
+                .parameter_ownership
+                .into_iter()
+                .map(|classification| (classification.function.clone(), classification))
+                .collect(),
+            memory_access_counter: MemoryAccessCounter::default(),
+            widening_thresholds: get_widening_thresholds(control_flow_graph),
+        };
+        context.allocation_site_symbols = context.collect_allocation_site_symbols();
+        context.allocation_site_families = context
+            .allocation_site_symbols
+            .iter()
+            .filter_map(|(call_tid, name)| {
+                context
+                    .get_allocation_family(name)
+                    .map(|family| (call_tid.clone(), family))
+            })
+            .collect();
+        context
+    }
+
+    /// Return the extern symbol (or the stub for a custom allocator function) called by the given call target.
+    pub fn get_extern_symbol(&self, call_target: &Tid) -> Option<&ExternSymbol> {
+        self.extern_symbol_map
+            .get(call_target)
+            .or_else(|| self.custom_allocator_stubs.get(call_target))
+    }
+
+    /// Return the custom allocator whose allocation function has the given name.
+    fn get_custom_allocator_by_allocation_function(
+        &self,
+        function_name: &str,
+    ) -> Option<&CustomAllocator> {
+        self.custom_allocators
+            .iter()
+            .find(|allocator| allocator.allocation_function == function_name)
+    }
+
+    /// Return the custom allocator whose deallocation function has the given name.
+    fn get_custom_allocator_by_deallocation_function(
+        &self,
+        function_name: &str,
+    ) -> Option<&CustomAllocator> {
+        self.custom_allocators
+            .iter()
+            .find(|allocator| allocator.deallocation_function == function_name)
+    }
+
+    /// Return the allocator family of the given allocation function if it is a known allocation function.
+    fn get_allocation_family(&self, function_name: &str) -> Option<AllocatorFamily> {
+        if let Some(allocator) = self.get_custom_allocator_by_allocation_function(function_name) {
+            Some(AllocatorFamily::Custom(
+                allocator.deallocation_function.clone(),
+            ))
+        } else if self.allocation_symbols.iter().any(|x| {
+            self.project
+                .program
+                .term
+                .symbol_names_match(x, function_name)
+        }) {
+            Some(AllocatorFamily::Standard)
+        } else {
+            None
         }
     }
 
+    /// Return the allocator family of the given deallocation function if it is a known deallocation function.
+    fn get_deallocation_family(&self, function_name: &str) -> Option<AllocatorFamily> {
+        if self
+            .get_custom_allocator_by_deallocation_function(function_name)
+            .is_some()
+        {
+            Some(AllocatorFamily::Custom(function_name.to_string()))
+        } else if self.deallocation_symbols.iter().any(|x| {
+            self.project
+                .program
+                .term
+                .symbol_names_match(x, function_name)
+        }) {
+            Some(AllocatorFamily::Standard)
+        } else {
+            None
+        }
+    }
+
+    /// Map the TIDs of all calls to known allocation functions in the program
+    /// to the name of the called function.
+    fn collect_allocation_site_symbols(&self) -> BTreeMap<Tid, String> {
+        let mut symbols = BTreeMap::new();
+        for sub in self.project.program.term.subs.values() {
+            for block in sub.term.blocks.iter() {
+                for jmp in block.term.jmps.iter() {
+                    if let Jmp::Call { target, .. } = &jmp.term {
+                        if let Some(symbol) = self.get_extern_symbol(target) {
+                            if self.get_allocation_family(&symbol.name).is_some() {
+                                symbols.insert(jmp.tid.clone(), symbol.name.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        symbols
+    }
+
     /// Return `true` if the all of the following properties hold:
     /// * The CPU architecture is a MIPS variant and `var` is the MIPS global pointer register `gp`
     /// * Loading the value at `address` into the register `var` would overwrite the value of `var` with a `Top` value.
@@ -69,7 +269,7 @@ impl<'a> Context<'a> {
         var: &Variable,
         address: &Expression,
     ) -> bool {
-        if self.project.cpu_architecture.contains("MIPS") && var.name == "gp" {
+        if self.project.architecture.is_mips() && var.name == "gp" {
             if let Ok(gp_val) = state.load_value(address, var.size, self.runtime_memory_image) {
                 gp_val.is_top()
             } else {
@@ -97,14 +297,14 @@ impl<'a> Context<'a> {
         &self,
         state_before_return: &State,
     ) -> Result<(), Error> {
-        let expected_stack_pointer_offset = match self.project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
+        let expected_stack_pointer_offset =
+            if self.project.architecture.pushes_return_address_on_stack() {
                 Bitvector::from_u64(u64::from(self.project.get_pointer_bytesize()))
                     .into_truncate(apint::BitWidth::from(self.project.get_pointer_bytesize()))
                     .unwrap()
-            }
-            _ => Bitvector::zero(apint::BitWidth::from(self.project.get_pointer_bytesize())),
-        };
+            } else {
+                Bitvector::zero(apint::BitWidth::from(self.project.get_pointer_bytesize()))
+            };
         match state_before_return
             .get_register(&self.project.stack_pointer_register)
             .get_if_unique_target()
@@ -128,9 +328,9 @@ impl<'a> Context<'a> {
         &self,
         state: &State,
         extern_symbol: &ExternSymbol,
-    ) -> ValueDomain {
+    ) -> Data {
         let address_bytesize = self.project.get_pointer_bytesize();
-        let object_size = match extern_symbol.name.as_str() {
+        match extern_symbol.name.as_str() {
             "malloc" => {
                 let size_parameter = extern_symbol.parameters.get(0).unwrap();
                 state
@@ -155,16 +355,17 @@ impl<'a> Context<'a> {
                 param1_value.bin_op(BinOpType::IntMult, &param2_value)
             }
             _ => DataDomain::new_top(address_bytesize),
-        };
-        object_size
-            .get_if_absolute_value()
-            .cloned()
-            .unwrap_or_else(|| ValueDomain::new_top(address_bytesize))
+        }
     }
 
     /// Add a new abstract object and a pointer to it in the return register of an extern call.
     /// This models the behaviour of `malloc`-like functions,
     /// except that we cannot represent possible `NULL` pointers as return values yet.
+    ///
+    /// If the size of the object is only known to lie in an interval,
+    /// the upper index bound of the object is derived from the upper end of the interval.
+    /// For calls to `realloc` the size of the object before the call is recorded in the new object,
+    /// so that accesses using stale bounds of a shrunk object can be explained in CWE warnings.
     fn add_new_object_in_call_return_register(
         &self,
         state: &State,
@@ -172,7 +373,6 @@ impl<'a> Context<'a> {
         call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
     ) -> State {
-        let address_bytesize = self.project.get_pointer_bytesize();
         let object_size = self.get_allocation_size_of_alloc_call(state, extern_symbol);
 
         match extern_symbol.get_unique_return_register() {
@@ -181,24 +381,17 @@ impl<'a> Context<'a> {
                     call.tid.clone(),
                     AbstractLocation::from_var(return_register).unwrap(),
                 );
-                new_state.memory.add_abstract_object(
-                    object_id.clone(),
-                    Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
-                    super::object::ObjectType::Heap,
-                    address_bytesize,
-                );
-                new_state.memory.set_lower_index_bound(
-                    &object_id,
-                    &Bitvector::zero(address_bytesize.into()).into(),
-                );
-                new_state.memory.set_upper_index_bound(
-                    &object_id,
-                    &(object_size - Bitvector::one(address_bytesize.into()).into()),
-                );
-                let pointer = Data::from_target(
-                    object_id,
-                    Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
-                );
+                let (pointer, upper_size_bound) =
+                    self.add_new_heap_object(&mut new_state, call, &object_id, &object_size);
+                if extern_symbol.name == "realloc" {
+                    let resize_info = self.get_resize_info_of_realloc_call(
+                        state,
+                        call,
+                        extern_symbol,
+                        &upper_size_bound,
+                    );
+                    new_state.memory.set_resize_info(&object_id, resize_info);
+                }
                 new_state.set_register(return_register, pointer);
                 new_state
             }
@@ -210,9 +403,233 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Add a new heap object with the given ID and size allocated by the given call to the state.
+    ///
+    /// The index bounds of the object are only derived from the size if it is a known absolute value.
+    /// The absolute part of the size is also recorded in the object (including bounds from value specialization
+    /// that are known for an otherwise unknown size value),
+    /// so that checks can inspect it after the allocation.
+    ///
+    /// If the size is a symbolic value like `strlen(string) + 1`,
+    /// the object is known to have room for strings up to the symbolic length `strlen(string)`.
+    ///
+    /// Returns a pointer to the new object
+    /// and the upper bound of the object size used to compute the upper index bound of the object.
+    fn add_new_heap_object(
+        &self,
+        new_state: &mut State,
+        call: &Term<Jmp>,
+        object_id: &AbstractIdentifier,
+        size: &Data,
+    ) -> (Data, ValueDomain) {
+        let address_bytesize = self.project.get_pointer_bytesize();
+        let object_size = size
+            .get_if_absolute_value()
+            .cloned()
+            .unwrap_or_else(|| ValueDomain::new_top(address_bytesize));
+        let allocation_size = match size.get_absolute_value() {
+            Some(value) if size.get_relative_values().is_empty() => value.clone(),
+            _ => ValueDomain::new_top(address_bytesize),
+        };
+        // If the object of a previous call at the same call site cannot be reached anymore,
+        // we can remove it so that the new object can be marked as unique.
+        new_state.remove_object_if_unreachable_and_not_escaped(object_id);
+        new_state.memory.add_abstract_object(
+            object_id.clone(),
+            Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
+            super::object::ObjectType::Heap,
+            address_bytesize,
+        );
+        new_state
+            .memory
+            .set_lower_index_bound(object_id, &Bitvector::zero(address_bytesize.into()).into());
+        let upper_size_bound = match object_size.try_to_interval() {
+            Ok(size_interval) => size_interval.end.into(),
+            Err(_) => object_size,
+        };
+        new_state.memory.set_upper_index_bound(
+            object_id,
+            &(upper_size_bound.clone() - Bitvector::one(address_bytesize.into()).into()),
+        );
+        new_state
+            .memory
+            .set_allocation_site(object_id, call.tid.clone());
+        new_state
+            .memory
+            .set_allocation_size(object_id, allocation_size);
+        let pointer = Data::from_target(
+            object_id.clone(),
+            Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
+        );
+        if let Some((_, offset)) = size.get_if_unique_target() {
+            if offset.try_to_offset().is_ok_and(|offset| offset >= 1) {
+                let string_length_bound = size.add_offset(
+                    &Bitvector::from_i64(-1)
+                        .into_resize_signed(size.bytesize())
+                        .into(),
+                );
+                new_state
+                    .memory
+                    .set_string_length_bound(&pointer, string_length_bound);
+            }
+        }
+        (pointer, upper_size_bound)
+    }
+
+    /// Handle a call to a `strlen`-like extern function.
+    ///
+    /// The return value is represented by a symbolic value relative to a new abstract ID,
+    /// which stands for the length of the string at the call site.
+    /// If the parameter points to the start of a memory object,
+    /// the symbolic value is also recorded as the string length bound of the object,
+    /// so that later calls using the length (e.g. as the size of an allocation) can be related to the string.
+    fn handle_string_length_call(
+        &self,
+        state: &State,
+        mut new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let return_register = match extern_symbol.get_unique_return_register() {
+            Ok(return_register) => return_register,
+            Err(err) => {
+                self.log_debug(Err(err), Some(&call.tid));
+                return new_state;
+            }
+        };
+        let length_id = AbstractIdentifier::new(
+            call.tid.clone(),
+            AbstractLocation::from_var(return_register).unwrap(),
+        );
+        let length = Data::from_target(
+            length_id,
+            Bitvector::zero(return_register.size.into()).into(),
+        );
+        if let Some(string) = extern_symbol.parameters.first().and_then(|parameter| {
+            state
+                .eval_parameter_arg(parameter, self.runtime_memory_image)
+                .ok()
+        }) {
+            new_state
+                .memory
+                .set_string_length_bound(&string, length.clone());
+        }
+        new_state.set_register(return_register, length);
+        new_state
+    }
+
+    /// Add a new heap object for a call to the allocation function of a custom allocator.
+    ///
+    /// Depending on the allocator description the pointer to the new object
+    /// is either written to the return register
+    /// or to the memory location that the out-parameter of the call points to.
+    fn add_new_object_for_custom_allocator(
+        &self,
+        state: &State,
+        mut new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        allocator: &CustomAllocator,
+    ) -> State {
+        let address_bytesize = self.project.get_pointer_bytesize();
+        let object_size = extern_symbol
+            .parameters
+            .get(allocator.size_parameter_index)
+            .and_then(|size_parameter| {
+                state
+                    .eval_parameter_arg(size_parameter, self.runtime_memory_image)
+                    .ok()
+            })
+            .unwrap_or_else(|| Data::new_top(address_bytesize));
+        let result = match &allocator.pointer_location {
+            AllocatedPointerLocation::ReturnValue => extern_symbol
+                .get_unique_return_register()
+                .map(|return_register| {
+                    let object_id = AbstractIdentifier::new(
+                        call.tid.clone(),
+                        AbstractLocation::from_var(return_register).unwrap(),
+                    );
+                    let (pointer, _) =
+                        self.add_new_heap_object(&mut new_state, call, &object_id, &object_size);
+                    new_state.set_register(return_register, pointer);
+                }),
+            AllocatedPointerLocation::OutParameter(index) => {
+                match extern_symbol.parameters.get(*index) {
+                    Some(Arg::Register {
+                        expr: Expression::Var(out_register),
+                        ..
+                    }) => {
+                        let object_id = AbstractIdentifier::new(
+                            call.tid.clone(),
+                            AbstractLocation::Pointer(
+                                out_register.clone(),
+                                AbstractMemoryLocation::Location {
+                                    offset: 0,
+                                    size: address_bytesize,
+                                },
+                            ),
+                        );
+                        let out_address = state.get_register(out_register);
+                        let (pointer, _) = self.add_new_heap_object(
+                            &mut new_state,
+                            call,
+                            &object_id,
+                            &object_size,
+                        );
+                        new_state.store_value(&out_address, &pointer, self.runtime_memory_image)
+                    }
+                    _ => Err(anyhow!(
+                        "Out-parameter of allocation function {} is not a register parameter",
+                        extern_symbol.name
+                    )),
+                }
+            }
+        };
+        self.log_debug(result, Some(&call.tid));
+        new_state
+    }
+
+    /// Collect the information about the resize operation performed by the given call to `realloc`.
+    ///
+    /// The old size and allocation site are only known
+    /// if the pointer parameter of the call points to a unique memory object.
+    fn get_resize_info_of_realloc_call(
+        &self,
+        state: &State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        new_size: &ValueDomain,
+    ) -> ResizeInfo {
+        let old_object_id = extern_symbol
+            .parameters
+            .first()
+            .and_then(|pointer_parameter| {
+                state
+                    .eval_parameter_arg(pointer_parameter, self.runtime_memory_image)
+                    .ok()
+            })
+            .and_then(|pointer| pointer.get_if_unique_target().map(|(id, _)| id.clone()));
+        ResizeInfo {
+            resize_site: call.tid.clone(),
+            original_allocation_site: old_object_id
+                .as_ref()
+                .and_then(|id| state.memory.get_allocation_site(id).cloned()),
+            old_size: old_object_id
+                .as_ref()
+                .and_then(|id| state.memory.get_object_size(id)),
+            new_size: new_size
+                .try_to_bitvec()
+                .ok()
+                .and_then(|size| size.try_to_u64().ok()),
+        }
+    }
+
     /// Mark the object that the parameter of a call is pointing to as freed.
     /// If the object may have been already freed, generate a CWE warning.
     /// This models the behaviour of `free` and similar functions.
+    ///
+    /// For deallocation functions of custom allocators the parameter given in the allocator description is used.
+    /// Otherwise the function is expected to have a unique parameter.
     fn mark_parameter_object_as_freed(
         &self,
         state: &State,
@@ -220,19 +637,44 @@ impl<'a> Context<'a> {
         call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
     ) -> State {
-        match extern_symbol.get_unique_parameter() {
+        let parameter =
+            match self.get_custom_allocator_by_deallocation_function(&extern_symbol.name) {
+                Some(allocator) => extern_symbol
+                    .parameters
+                    .get(allocator.freed_pointer_parameter_index)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Freed pointer parameter of {} not found",
+                            extern_symbol.name
+                        )
+                    }),
+                None => extern_symbol.get_unique_parameter(),
+            };
+        match parameter {
             Ok(parameter) => {
                 let parameter_value =
                     state.eval_parameter_arg(parameter, self.runtime_memory_image);
                 match parameter_value {
                     Ok(memory_object_pointer) => {
+                        self.check_for_mismatched_deallocation(
+                            state,
+                            call,
+                            extern_symbol,
+                            &memory_object_pointer,
+                        );
+                        self.check_for_offset_free(
+                            state,
+                            call,
+                            extern_symbol,
+                            &memory_object_pointer,
+                        );
                         if let Err(possible_double_frees) =
                             new_state.mark_mem_object_as_freed(&memory_object_pointer)
                         {
                             let warning = CweWarning {
                                 name: "CWE415".to_string(),
                                 version: VERSION.to_string(),
-                                addresses: vec![call.tid.address.clone()],
+                                addresses: vec![call.tid.canonical_address().to_string()],
                                 tids: vec![format!("{}", call.tid)],
                                 symbols: Vec::new(),
                                 other: vec![possible_double_frees
@@ -241,8 +683,17 @@ impl<'a> Context<'a> {
                                     .collect()],
                                 description: format!(
                                     "(Double Free) Object may have been freed before at {}",
-                                    call.tid.address
+                                    call.tid.canonical_address()
                                 ),
+                                evidence: self.collect_warning_evidence.then(|| {
+                                    state.add_pointer_evidence(
+                                        WarningContext::new(),
+                                        "parameter",
+                                        &memory_object_pointer,
+                                    )
+                                }),
+                                exploit_primitive: None,
+                                remediation: None,
                             };
                             let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
                         }
@@ -263,6 +714,144 @@ impl<'a> Context<'a> {
         }
     }
 
+    /// Generate a CWE-762 warning if the given pointer may point to a memory object
+    /// that was allocated by an allocation function of a different allocator family
+    /// than the called deallocation function.
+    fn check_for_mismatched_deallocation(
+        &self,
+        state: &State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        pointer: &Data,
+    ) {
+        let deallocation_family = match self.get_deallocation_family(&extern_symbol.name) {
+            Some(family) => family,
+            None => return,
+        };
+        let mismatched_allocations: Vec<(&Tid, &AllocatorFamily)> = pointer
+            .get_relative_values()
+            .keys()
+            .filter_map(|id| {
+                let allocation_site = state.memory.get_allocation_site(id)?;
+                let allocation_family = self.allocation_site_families.get(allocation_site)?;
+                (*allocation_family != deallocation_family)
+                    .then_some((allocation_site, allocation_family))
+            })
+            .collect();
+        if mismatched_allocations.is_empty() {
+            return;
+        }
+        let warning = CweWarning::new(
+            "CWE762",
+            VERSION,
+            format!(
+                "(Mismatched Memory Management Routines) Memory freed by {} at {} may have been allocated by an allocator of a different allocator family",
+                extern_symbol.name, call.tid.canonical_address()
+            ),
+        )
+        .tids(vec![format!("{}", call.tid)])
+        .addresses(vec![call.tid.canonical_address().to_string()])
+        .symbols(vec![extern_symbol.name.clone()])
+        .other(vec![mismatched_allocations
+            .into_iter()
+            .map(|(allocation_site, family)| {
+                format!("allocated at {} by {}", allocation_site.canonical_address(), family)
+            })
+            .collect()])
+        .evidence(self.collect_warning_evidence.then(|| {
+            state.add_pointer_evidence(WarningContext::new(), "parameter", pointer)
+        }));
+        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+    }
+
+    /// Generate a CWE-761 warning if the given pointer to a heap object freed by the call
+    /// may point into the middle of the object instead of to its start.
+    ///
+    /// The free is reported as definite if no target offset of the pointer may be zero.
+    /// Objects allocated by custom allocators accepting offset pointers for deallocation are ignored.
+    fn check_for_offset_free(
+        &self,
+        state: &State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        pointer: &Data,
+    ) {
+        let mut offset_targets = Vec::new();
+        let mut is_definite = !pointer.contains_top() && pointer.get_absolute_value().is_none();
+        for (id, offset) in pointer.get_relative_values() {
+            if state.memory.get_object_type(id) != Ok(Some(ObjectType::Heap))
+                || self.allows_offset_free(state, id)
+            {
+                continue;
+            }
+            match offset.try_to_offset_interval() {
+                Ok((start, end)) => {
+                    if start <= 0 && end >= 0 {
+                        is_definite = false;
+                    }
+                    if start != 0 || end != 0 {
+                        offset_targets.push((id, start, end));
+                    }
+                }
+                Err(_) => is_definite = false,
+            }
+        }
+        if offset_targets.is_empty() {
+            return;
+        }
+        let description = format!(
+            "(Free of Pointer not at Start of Buffer) Call to {} at {} {} a pointer into the middle of a heap object",
+            extern_symbol.name,
+            call.tid.canonical_address(),
+            if is_definite { "frees" } else { "may free" }
+        );
+        let warning =
+            CweWarning::new("CWE761", VERSION, description)
+                .tids(vec![format!("{}", call.tid)])
+                .addresses(vec![call.tid.canonical_address().to_string()])
+                .symbols(vec![extern_symbol.name.clone()])
+                .other(vec![offset_targets
+                    .into_iter()
+                    .map(|(id, start, end)| {
+                        let offset = if start == end {
+                            format!("{}", start)
+                        } else {
+                            format!("[{}, {}]", start, end)
+                        };
+                        match state.memory.get_allocation_site(id) {
+                            Some(allocation_site) => format!(
+                                "offset {} into object allocated at {}",
+                                offset,
+                                allocation_site.canonical_address()
+                            ),
+                            None => format!("offset {} into {}", offset, id),
+                        }
+                    })
+                    .collect()])
+                .evidence(self.collect_warning_evidence.then(|| {
+                    state.add_pointer_evidence(WarningContext::new(), "parameter", pointer)
+                }));
+        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+    }
+
+    /// Returns `true` if the object with the given ID was allocated by a custom allocator
+    /// whose deallocation function accepts pointers into the middle of the object.
+    fn allows_offset_free(&self, state: &State, id: &AbstractIdentifier) -> bool {
+        let family = state
+            .memory
+            .get_allocation_site(id)
+            .and_then(|allocation_site| self.allocation_site_families.get(allocation_site));
+        match family {
+            Some(AllocatorFamily::Custom(deallocation_function)) => {
+                self.custom_allocators.iter().any(|allocator| {
+                    allocator.deallocation_function == *deallocation_function
+                        && allocator.allows_offset_free
+                })
+            }
+            _ => false,
+        }
+    }
+
     /// Check all parameter registers of a call for dangling pointers and report possible use-after-frees.
     fn check_parameter_register_for_dangling_pointer(
         &self,
@@ -274,20 +863,41 @@ impl<'a> Context<'a> {
             match state.eval_parameter_arg(parameter, self.runtime_memory_image) {
                 Ok(value) => {
                     if state.memory.is_dangling_pointer(&value, true) {
+                        let evidence = self.collect_warning_evidence.then(|| {
+                            state.add_pointer_evidence(WarningContext::new(), "parameter", &value)
+                        });
+                        let (name, description) = if state
+                            .memory
+                            .is_expired_environment_pointer(&value)
+                        {
+                            ("CWE825", format!(
+                                    "(Expired Pointer Dereference) Call to {} may access an invalidated environment variable at {}",
+                                    extern_symbol.name, call.tid.canonical_address()
+                                ))
+                        } else {
+                            (
+                                "CWE416",
+                                format!(
+                                    "(Use After Free) Call to {} may access freed memory at {}",
+                                    extern_symbol.name,
+                                    call.tid.canonical_address()
+                                ),
+                            )
+                        };
                         state
                             .memory
                             .mark_dangling_pointer_targets_as_flagged(&value);
                         let warning = CweWarning {
-                            name: "CWE416".to_string(),
+                            name: name.to_string(),
                             version: VERSION.to_string(),
-                            addresses: vec![call.tid.address.clone()],
+                            addresses: vec![call.tid.canonical_address().to_string()],
                             tids: vec![format!("{}", call.tid)],
                             symbols: Vec::new(),
                             other: Vec::new(),
-                            description: format!(
-                                "(Use After Free) Call to {} may access freed memory at {}",
-                                extern_symbol.name, call.tid.address
-                            ),
+                            description,
+                            evidence,
+                            exploit_primitive: None,
+                            remediation: None,
                         };
                         let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
                     }
@@ -312,23 +922,31 @@ impl<'a> Context<'a> {
         call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
     ) {
-        for parameter in extern_symbol.parameters.iter() {
+        for (index, parameter) in extern_symbol.parameters.iter().enumerate() {
             match state.eval_parameter_arg(parameter, self.runtime_memory_image) {
                 Ok(data) => {
-                    if state.pointer_contains_out_of_bounds_target(&data, self.runtime_memory_image)
-                    {
+                    let record = state.classify_pointer_parameter(&data, self.runtime_memory_image);
+                    self.report_unknown_object_sizes(state, &call.tid, &data, &record);
+                    if let Some(cwe_name) = record.cwe_name() {
+                        let size_arguments =
+                            self.get_absolute_call_arguments(state, extern_symbol, index);
                         let warning = CweWarning {
-                            name: "CWE119".to_string(),
+                            name: cwe_name.to_string(),
                             version: VERSION.to_string(),
-                            addresses: vec![call.tid.address.clone()],
+                            addresses: vec![call.tid.canonical_address().to_string()],
                             tids: vec![format!("{}", call.tid)],
                             symbols: Vec::new(),
                             other: Vec::new(),
-                            description: format!(
-                                "(Buffer Overflow) Call to {} at {} may access out-of-bounds memory",
-                                extern_symbol.name,
-                                call.tid.address
+                            description: record.cwe_description(
+                                call.tid.canonical_address(),
+                                Some(&extern_symbol.name),
                             ),
+                            evidence: self.collect_warning_evidence.then(|| {
+                                self.get_call_parameter_evidence(state, extern_symbol, &data)
+                            }),
+                            exploit_primitive: state
+                                .get_exploit_primitive_of_call_parameter(&data, &size_arguments),
+                            remediation: None,
                         };
                         let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
                     }
@@ -344,14 +962,418 @@ impl<'a> Context<'a> {
         }
     }
 
-    /// Check whether the jump is an indirect call whose target evaluates to a *Top* value in the given state.
-    fn is_indirect_call_with_top_target(&self, state: &State, call: &Term<Jmp>) -> bool {
+    /// Generate a CWE warning for the memory access of the given load or store instruction
+    /// if its classification by the bounds-checking engine is out of bounds.
+    /// Reads are reported as CWE-125 and writes as CWE-787.
+    ///
+    /// The evidence of the warning is collected on the `state` before the instruction,
+    /// the remaining information on the `new_state` after the instruction.
+    fn report_out_of_bounds_mem_access(
+        &self,
+        state: &State,
+        new_state: &State,
+        def: &Term<Def>,
+        record: &MemoryAccessRecord,
+    ) {
+        let cwe_name = match record.cwe_name() {
+            Some(cwe_name) => cwe_name,
+            None => return,
+        };
+        let mut description = record.cwe_description(def.tid.canonical_address(), None);
+        let mut addresses = vec![def.tid.canonical_address().to_string()];
+        let mut tids = vec![format!("{}", def.tid)];
+        if let Some(resize_info) = new_state.get_shrink_info_of_out_of_bounds_mem_access(&def.term)
+        {
+            // The violated bound may be a stale bound from before the object was shrunk.
+            description.push_str(&format!(
+                " (object was shrunk at {} from {} to {} bytes)",
+                resize_info.resize_site.canonical_address(),
+                resize_info.old_size.unwrap(),
+                resize_info.new_size.unwrap()
+            ));
+            addresses.push(resize_info.resize_site.canonical_address().to_string());
+            tids.push(format!("{}", resize_info.resize_site));
+        }
+        let warning = CweWarning {
+            name: cwe_name.to_string(),
+            version: VERSION.to_string(),
+            addresses,
+            tids,
+            symbols: Vec::new(),
+            other: Vec::new(),
+            description,
+            evidence: self
+                .collect_warning_evidence
+                .then(|| state.get_mem_access_evidence(&def.term)),
+            exploit_primitive: match record.kind {
+                BoundsCheckedAccess::Write => {
+                    new_state.get_exploit_primitive_of_mem_access(&def.term)
+                }
+                _ => None,
+            },
+            remediation: None,
+        };
+        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+    }
+
+    /// Report the imprecision of the bounds check of a memory access or pointer parameter at the given location
+    /// if the pointer may point to heap objects of unknown size.
+    /// Each such object is attributed to the function that allocated it.
+    ///
+    /// Nothing is reported if the access is already classified as out of bounds.
+    fn report_unknown_object_sizes(
+        &self,
+        state: &State,
+        location: &Tid,
+        pointer: &Data,
+        record: &MemoryAccessRecord,
+    ) {
+        if record.classification.is_out_of_bounds() {
+            return;
+        }
+        for allocation_site in state.get_allocation_sites_of_unknown_size_targets(pointer) {
+            let missing_fact = MissingFact::new(format!(
+                "Size of the memory object allocated at {}",
+                allocation_site.canonical_address()
+            ))
+            .extern_symbol(self.allocation_site_symbols.get(&allocation_site).cloned());
+            let log_message = report_imprecision(
+                record.checked_cwe_name(),
+                location,
+                ImprecisionReason::UnknownObjectSize,
+                missing_fact,
+            );
+            let _ = self.log_collector.send(LogThreadMsg::Log(log_message));
+        }
+    }
+
+    /// Generate a CWE-471 warning if the given store instruction may write to read-only global memory,
+    /// e.g. to a string literal.
+    fn check_store_for_read_only_target(&self, state: &State, def: &Term<Def>) {
+        if let Def::Store { address, value } = &def.term {
+            let writeability = state
+                .eval(address)
+                .get_global_writeability(value.bytesize(), self.runtime_memory_image);
+            let verb = match writeability {
+                GlobalWriteability::ReadOnly => "writes",
+                GlobalWriteability::PossiblyReadOnly => "may write",
+                GlobalWriteability::Writeable => return,
+            };
+            let description = format!(
+                "(Modification of Read-Only Memory) Memory write at {} {} to read-only global memory",
+                def.tid.canonical_address(),
+                verb
+            );
+            self.report_write_to_read_only_memory(&def.tid, description, Vec::new());
+        }
+    }
+
+    /// Generate a CWE-471 warning if a parameter of the call pointing to memory written by the called function
+    /// may point to read-only global memory, e.g. the destination parameter of `strcpy` pointing to a string literal.
+    fn check_parameter_register_for_read_only_target(
+        &self,
+        state: &State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) {
+        let parameter_indices = match self.written_pointer_parameters.get(&extern_symbol.name) {
+            Some(indices) => indices,
+            None => return,
+        };
+        for index in parameter_indices {
+            let parameter = match extern_symbol.parameters.get(*index) {
+                Some(parameter) => parameter,
+                None => continue,
+            };
+            let writeability = match state.eval_parameter_arg(parameter, self.runtime_memory_image)
+            {
+                Ok(pointer) => {
+                    pointer.get_global_writeability(ByteSize::new(1), self.runtime_memory_image)
+                }
+                Err(_) => continue,
+            };
+            let verb = match writeability {
+                GlobalWriteability::ReadOnly => "writes",
+                GlobalWriteability::PossiblyReadOnly => "may write",
+                GlobalWriteability::Writeable => continue,
+            };
+            let description = format!(
+                "(Modification of Read-Only Memory) Call to {} at {} {} to read-only global memory",
+                extern_symbol.name,
+                call.tid.canonical_address(),
+                verb
+            );
+            self.report_write_to_read_only_memory(
+                &call.tid,
+                description,
+                vec![extern_symbol.name.clone()],
+            );
+            return;
+        }
+    }
+
+    /// Send a CWE-471 warning for a write to read-only global memory at the given term.
+    fn report_write_to_read_only_memory(
+        &self,
+        tid: &Tid,
+        description: String,
+        symbols: Vec<String>,
+    ) {
+        let warning = CweWarning::new("CWE471", VERSION, description)
+            .tids(vec![format!("{}", tid)])
+            .addresses(vec![tid.canonical_address().to_string()])
+            .symbols(symbols);
+        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+    }
+
+    /// Return the values of all parameters of the call that evaluate to absolute values,
+    /// except for the parameter with the given index.
+    /// These are the candidates for size arguments of the call.
+    fn get_absolute_call_arguments(
+        &self,
+        state: &State,
+        extern_symbol: &ExternSymbol,
+        excluded_index: usize,
+    ) -> Vec<ValueDomain> {
+        extern_symbol
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != excluded_index)
+            .filter_map(|(_, parameter)| {
+                state
+                    .eval_parameter_arg(parameter, self.runtime_memory_image)
+                    .ok()?
+                    .get_if_absolute_value()
+                    .cloned()
+            })
+            .collect()
+    }
+
+    /// Collect the evidence for a warning about a call parameter that may point out of bounds:
+    /// The parameter with the memory objects it may point to
+    /// and the value intervals of all parameters of the call that evaluate to absolute values.
+    fn get_call_parameter_evidence(
+        &self,
+        state: &State,
+        extern_symbol: &ExternSymbol,
+        pointer: &Data,
+    ) -> WarningContext {
+        let mut evidence = state.add_pointer_evidence(WarningContext::new(), "parameter", pointer);
+        for (index, parameter) in extern_symbol.parameters.iter().enumerate() {
+            if let Ok(value) = state.eval_parameter_arg(parameter, self.runtime_memory_image) {
+                if let Some(interval) = value.get_if_absolute_value() {
+                    evidence = evidence.size_interval(format!("argument {}", index), interval);
+                }
+            }
+        }
+        evidence
+    }
+
+    /// Write register values that were specialized by a conditional jump
+    /// back to the stack slots that they were loaded from in the block containing the jump.
+    ///
+    /// A register value is only written back if it still equals the value in the stack slot,
+    /// i.e. if the register, the input registers of the load address and memory
+    /// were not modified between the load and the end of the block.
+    fn specialize_stack_values_loaded_in_block(
+        &self,
+        state_before_specialization: &State,
+        specialized_state: &mut State,
+        block: &Term<Blk>,
+    ) {
+        let mut modified_vars: HashSet<&Variable> = HashSet::new();
+        let mut memory_modified = false;
+        for def in block.term.defs.iter().rev() {
+            match &def.term {
+                Def::Store { .. } => memory_modified = true,
+                Def::Assign { var, .. } => {
+                    modified_vars.insert(var);
+                }
+                Def::Load { var, address } => {
+                    let is_unmodified = !memory_modified
+                        && !modified_vars.contains(var)
+                        && address
+                            .input_vars()
+                            .into_iter()
+                            .all(|input| !modified_vars.contains(input));
+                    if is_unmodified {
+                        let specialized_value = specialized_state.get_register(var);
+                        let is_stack_slot = specialized_state
+                            .eval(address)
+                            .get_if_unique_target()
+                            .map(|(id, _)| *id == specialized_state.stack_id)
+                            .unwrap_or(false);
+                        if is_stack_slot
+                            && specialized_value != state_before_specialization.get_register(var)
+                        {
+                            let _ = specialized_state.write_to_address(
+                                address,
+                                &specialized_value,
+                                self.runtime_memory_image,
+                            );
+                        }
+                    }
+                    modified_vars.insert(var);
+                }
+            }
+        }
+    }
+
+    /// Check whether the jump is an indirect call whose target is unknown in the given state,
+    /// i.e. the target evaluates to a *Top* value
+    /// or to a value without pointer targets whose absolute value is not exactly known
+    /// (e.g. the return value of a `dlsym`-like function).
+    fn is_indirect_call_with_unknown_target(&self, state: &State, call: &Term<Jmp>) -> bool {
         match &call.term {
-            Jmp::CallInd { target, .. } => state.eval(target).is_top(),
+            Jmp::CallInd { target, .. } => {
+                let target_value = state.eval(target);
+                target_value.is_top()
+                    || (target_value.get_relative_values().is_empty()
+                        && target_value
+                            .get_absolute_value()
+                            .map(|value| value.try_to_bitvec().is_err())
+                            .unwrap_or(false))
+            }
             _ => false,
         }
     }
 
+    /// Set the return register of a call to a `dlsym`-like function
+    /// to an unknown function pointer that may also be a NULL pointer.
+    ///
+    /// Since the returned pointers point to code, they are represented by absolute values
+    /// and not by pointers to abstract memory objects.
+    fn add_possibly_null_function_pointer_in_return_register(
+        &self,
+        state: &State,
+        new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let mut new_state = self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+        match extern_symbol.get_unique_return_register() {
+            Ok(return_register) => {
+                let function_pointer = IntervalDomain::new_top(return_register.size);
+                new_state.set_register(return_register, function_pointer.into());
+            }
+            Err(err) => self.log_debug(Err(err), Some(&call.tid)),
+        }
+        new_state
+    }
+
+    /// Generate a CWE-476 warning if the given jump is an indirect call
+    /// whose target may be a NULL pointer, e.g. an unchecked return value of `dlsym`.
+    ///
+    /// Targets containing *Top* values or pointers to memory objects are not reported,
+    /// since these are more likely due to analysis imprecision.
+    /// Targets that were checked to be unequal to zero are not reported either.
+    fn check_indirect_call_for_null_target(&self, state: &State, call: &Term<Jmp>) {
+        let target = match &call.term {
+            Jmp::CallInd { target, .. } => target,
+            _ => return,
+        };
+        let target_value = state.eval(target);
+        if target_value.contains_top() || !target_value.get_relative_values().is_empty() {
+            return;
+        }
+        let zero = Bitvector::zero(target_value.bytesize().into());
+        match target_value.get_absolute_value() {
+            Some(value) if value.contains(&zero) => (),
+            _ => return,
+        }
+        let mut addresses = vec![call.tid.canonical_address().to_string()];
+        let mut tids = vec![format!("{}", call.tid)];
+        let mut symbols = Vec::new();
+        let mut description = format!(
+            "(NULL Pointer Dereference) Indirect call at {} may call a NULL pointer",
+            call.tid.canonical_address()
+        );
+        if let Some((lookup_call, lookup_symbol)) = self.get_function_lookup_call_of_target(call) {
+            description.push_str(&format!(
+                " returned by the call to {} at {}",
+                lookup_symbol.name,
+                lookup_call.tid.canonical_address()
+            ));
+            addresses.push(lookup_call.tid.canonical_address().to_string());
+            tids.push(format!("{}", lookup_call.tid));
+            symbols.push(lookup_symbol.name.clone());
+        }
+        let warning = CweWarning {
+            name: "CWE476".to_string(),
+            version: VERSION.to_string(),
+            addresses,
+            tids,
+            symbols,
+            other: Vec::new(),
+            description,
+            evidence: self
+                .collect_warning_evidence
+                .then(|| WarningContext::new().value("target", target_value.to_json_compact())),
+            exploit_primitive: None,
+            remediation: None,
+        };
+        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+    }
+
+    /// Find the call to a `dlsym`-like function whose return value is the target of the given indirect call.
+    ///
+    /// The return value is only found if the indirect call is contained in the block
+    /// that the lookup call returns to
+    /// and if the target register only gets copied between the two calls.
+    fn get_function_lookup_call_of_target(
+        &self,
+        call: &Term<Jmp>,
+    ) -> Option<(&'a Term<Jmp>, &'a ExternSymbol)> {
+        let mut target_var = match &call.term {
+            Jmp::CallInd {
+                target: Expression::Var(var),
+                ..
+            } => var,
+            _ => return None,
+        };
+        let sub = self.project.program.term.subs.values().find(|sub| {
+            sub.term
+                .blocks
+                .iter()
+                .any(|block| block.term.jmps.iter().any(|jmp| jmp.tid == call.tid))
+        })?;
+        let block = sub
+            .term
+            .blocks
+            .iter()
+            .find(|block| block.term.jmps.iter().any(|jmp| jmp.tid == call.tid))?;
+        for def in block.term.defs.iter().rev() {
+            match &def.term {
+                Def::Assign { var, value } if var == target_var => match value {
+                    Expression::Var(input_var) => target_var = input_var,
+                    _ => return None,
+                },
+                Def::Load { var, .. } if var == target_var => return None,
+                _ => (),
+            }
+        }
+        sub.term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.jmps.iter())
+            .find_map(|jmp| match &jmp.term {
+                Jmp::Call {
+                    target,
+                    return_: Some(return_target),
+                } if *return_target == block.tid => {
+                    let symbol = self.extern_symbol_map.get(target)?;
+                    let is_lookup_symbol = self.function_lookup_symbols.contains(&symbol.name);
+                    if is_lookup_symbol && symbol.get_unique_return_register().ok()? == target_var {
+                        Some((jmp, symbol))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            })
+    }
+
     /// Adjust the stack register after a call to an extern function.
     ///
     /// On x86, this removes the return address from the stack
@@ -366,33 +1388,28 @@ impl<'a> Context<'a> {
     ) {
         let stack_register = &self.project.stack_pointer_register;
         let stack_pointer = state_before_call.get_register(stack_register);
-        match self.project.cpu_architecture.as_str() {
-            "x86" | "x86_32" | "x86_64" => {
-                let offset = Bitvector::from_u64(stack_register.size.into())
-                    .into_truncate(apint::BitWidth::from(stack_register.size))
-                    .unwrap();
-                new_state.set_register(
-                    stack_register,
-                    stack_pointer.bin_op(BinOpType::IntAdd, &offset.into()),
-                );
-            }
-            _ => new_state.set_register(stack_register, stack_pointer),
+        if self.project.architecture.pushes_return_address_on_stack() {
+            let offset = Bitvector::from_u64(stack_register.size.into())
+                .into_truncate(apint::BitWidth::from(stack_register.size))
+                .unwrap();
+            new_state.set_register(
+                stack_register,
+                stack_pointer.bin_op(BinOpType::IntAdd, &offset.into()),
+            );
+        } else {
+            new_state.set_register(stack_register, stack_pointer)
         }
     }
 
-    /// Handle an extern symbol call, whose concrete effect on the state is unknown.
-    /// Basically, we assume that the call may write to all memory objects and register that is has access to.
-    fn handle_generic_extern_call(
+    /// Return the IDs of all memory objects that a call to the given extern symbol may access,
+    /// i.e. of all objects (recursively) referenced by the parameters of the call.
+    ///
+    /// If the parameters of the symbol are unknown, all possible parameter registers are considered.
+    pub fn get_ids_accessible_by_extern_call(
         &self,
         state: &State,
-        mut new_state: State,
-        call: &Term<Jmp>,
         extern_symbol: &ExternSymbol,
-    ) -> State {
-        self.log_debug(
-            new_state.clear_stack_parameter(extern_symbol, self.runtime_memory_image),
-            Some(&call.tid),
-        );
+    ) -> BTreeSet<AbstractIdentifier> {
         let calling_conv = self.project.get_calling_convention(extern_symbol);
         let mut possible_referenced_ids = BTreeSet::new();
         if extern_symbol.parameters.is_empty() && extern_symbol.return_values.is_empty() {
@@ -414,17 +1431,506 @@ impl<'a> Context<'a> {
                 }
             }
         }
-        possible_referenced_ids =
-            state.add_recursively_referenced_ids_to_id_set(possible_referenced_ids);
+        state.add_recursively_referenced_ids_to_id_set(possible_referenced_ids)
+    }
+
+    /// Returns `true` if calls to the given extern symbol are handled by the generic approximation
+    /// for extern calls with unknown effect,
+    /// i.e. if the symbol is neither modeled by the analysis nor assumed to be pure.
+    pub fn is_unmodeled_extern_symbol(&self, extern_symbol: &ExternSymbol) -> bool {
+        let name = &extern_symbol.name;
+        let is_listed = |symbols: &[String]| symbols.iter().any(|symbol| symbol == name);
+        self.get_custom_allocator_by_allocation_function(name)
+            .is_none()
+            && self.get_deallocation_family(name).is_none()
+            && !is_listed(&self.allocation_symbols)
+            && !is_listed(&self.function_lookup_symbols)
+            && !is_listed(&self.context_switch_symbols)
+            && !self.thread_creation_symbols.contains_key(name)
+            && !is_listed(&self.environment_lookup_symbols)
+            && !is_listed(&self.environment_modification_symbols)
+            && !self.memory_protection_symbols.contains_key(name)
+            && !self.event_polling_symbols.contains_key(name)
+            && !self.memory_copy_symbols.contains_key(name)
+            && !is_listed(&self.assume_pure)
+            && !is_listed(&self.string_length_symbols)
+            && !is_listed(&self.program_start_symbols)
+    }
+
+    /// Handle an extern symbol call, whose concrete effect on the state is unknown.
+    /// Basically, we assume that the call may write to all memory objects and register that is has access to.
+    fn handle_generic_extern_call(
+        &self,
+        state: &State,
+        mut new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        self.log_debug(
+            new_state.clear_stack_parameter(extern_symbol, self.runtime_memory_image),
+            Some(&call.tid),
+        );
+        let possible_referenced_ids = self.get_ids_accessible_by_extern_call(state, extern_symbol);
         // Delete content of all referenced objects, as the function may write to them.
         for id in possible_referenced_ids.iter() {
             new_state
                 .memory
                 .assume_arbitrary_writes_to_object(id, &possible_referenced_ids);
         }
+        // The extern function may store pointers to the referenced objects anywhere.
+        new_state
+            .memory
+            .mark_heap_objects_as_escaped(&possible_referenced_ids);
         new_state
     }
 
+    /// Handle a call to an extern function that may switch the execution context, e.g. `swapcontext`.
+    ///
+    /// Since the execution may continue on a different stack,
+    /// the stack pointer is set to *Top* in addition to the effects of a generic extern call.
+    /// An info message is logged for the call site, since the analysis loses precision there.
+    fn handle_context_switch_call(
+        &self,
+        state: &State,
+        new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let mut new_state = self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+        let stack_register = &self.project.stack_pointer_register;
+        new_state.set_register(stack_register, Data::new_top(stack_register.size));
+        let _ = self.log_collector.send(LogThreadMsg::Log(
+            LogMessage::new_info(format!(
+                "Call to {} at {} may switch the execution context. Stack tracking is imprecise after the call.",
+                extern_symbol.name, call.tid.canonical_address()
+            ))
+            .location(call.tid.clone())
+            .source("Pointer Inference"),
+        ));
+        new_state
+    }
+
+    /// Handle a call to an event polling function like `poll`.
+    ///
+    /// Only the fields in memory written by the function are marked as unknown,
+    /// all other values in the objects reachable through the parameters are kept.
+    /// If the maximal number of returned events is known,
+    /// the return value is set to the interval between -1 and that number.
+    fn handle_event_polling_call(
+        &self,
+        state: &State,
+        mut new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        parameters: &EventPollingParameters,
+    ) -> State {
+        self.log_debug(
+            new_state.clear_stack_parameter(extern_symbol, self.runtime_memory_image),
+            Some(&call.tid),
+        );
+        let eval_parameter = |index: usize| {
+            extern_symbol.parameters.get(index).and_then(|parameter| {
+                state
+                    .eval_parameter_arg(parameter, self.runtime_memory_image)
+                    .ok()
+            })
+        };
+        let eval_upper_bound = |index: usize| {
+            eval_parameter(index)
+                .and_then(|value| value.get_if_absolute_value().cloned())
+                .and_then(|value| value.try_to_offset_interval().ok())
+                .map(|(_lower_bound, upper_bound)| upper_bound)
+        };
+        for fields in parameters.written_fields.iter() {
+            let pointer = match eval_parameter(fields.pointer_index) {
+                Some(pointer) => pointer,
+                None => continue,
+            };
+            let count = fields
+                .count_index
+                .and_then(eval_upper_bound)
+                .map(|upper_bound| upper_bound.max(0) as u64);
+            let pointer = pointer.add_offset(
+                &Bitvector::from_i64(fields.offset)
+                    .into_resize_signed(pointer.bytesize())
+                    .into(),
+            );
+            new_state.memory.assume_strided_writes(
+                &pointer,
+                ByteSize::new(fields.size),
+                fields.stride,
+                count,
+                &BTreeSet::new(),
+            );
+        }
+        if let (Some(upper_bound), Ok(return_register)) = (
+            parameters.return_bound_index.and_then(eval_upper_bound),
+            extern_symbol.get_unique_return_register(),
+        ) {
+            let return_value = IntervalDomain::new(
+                Bitvector::from_i64(-1).into_resize_signed(return_register.size),
+                Bitvector::from_i64(upper_bound.max(-1)).into_resize_signed(return_register.size),
+            );
+            new_state.set_register(return_register, return_value.into());
+        }
+        new_state
+    }
+
+    /// Handle a call to a memory copy function like `memcpy`.
+    ///
+    /// If the length of the copied range is known and the source and destination pointers
+    /// each have a unique target with exactly known offset,
+    /// the values in the source range are copied to the destination range.
+    /// Otherwise only the bytes that may be written are marked as unknown,
+    /// i.e. the destination range if its length is known
+    /// or the bytes from the destination pointer up to the upper bound of each target object.
+    /// See the [`memory_copy`](super::memory_copy) module for details.
+    fn handle_memory_copy_call(
+        &self,
+        state: &State,
+        mut new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        parameters: &MemoryCopyParameters,
+    ) -> State {
+        let eval_parameter = |index: usize| {
+            extern_symbol.parameters.get(index).and_then(|parameter| {
+                state
+                    .eval_parameter_arg(parameter, self.runtime_memory_image)
+                    .ok()
+            })
+        };
+        let (destination, source) = match (
+            eval_parameter(parameters.destination_index),
+            eval_parameter(parameters.source_index),
+        ) {
+            (Some(destination), Some(source)) => (destination, source),
+            _ => return self.handle_generic_extern_call(state, new_state, call, extern_symbol),
+        };
+        let length_value = eval_parameter(parameters.length_index);
+        let length = length_value
+            .as_ref()
+            .and_then(|length| length.get_if_absolute_value().cloned())
+            .and_then(|length| length.try_to_offset_interval().ok())
+            .filter(|(lower_bound, _upper_bound)| *lower_bound >= 0);
+        self.log_debug(
+            new_state.clear_stack_parameter(extern_symbol, self.runtime_memory_image),
+            Some(&call.tid),
+        );
+        // Pointers contained in the source may be copied to the destination.
+        let source_targets: BTreeSet<AbstractIdentifier> = source
+            .referenced_ids()
+            .flat_map(|id| state.memory.get_referenced_ids_overapproximation(id))
+            .collect();
+        let get_unique_target = |pointer: &Data| match pointer.get_relative_values().iter().next() {
+            Some((id, offset))
+                if pointer.get_relative_values().len() == 1
+                    && pointer.get_if_absolute_value().is_none()
+                    && !pointer.contains_top() =>
+            {
+                offset
+                    .try_to_offset()
+                    .ok()
+                    .map(|offset| (id.clone(), offset))
+            }
+            _ => None,
+        };
+        match length {
+            Some((lower_bound, upper_bound)) => {
+                let mut written_range_start = destination.clone();
+                let mut written_range_size = upper_bound as u64;
+                let unique_targets = if parameters.null_terminated {
+                    None
+                } else {
+                    get_unique_target(&destination).zip(get_unique_target(&source))
+                };
+                if let Some(((destination_id, destination_offset), (source_id, source_offset))) =
+                    unique_targets
+                {
+                    new_state.memory.copy_range_between_objects(
+                        &source_id,
+                        source_offset,
+                        &destination_id,
+                        destination_offset,
+                        lower_bound as u64,
+                    );
+                    written_range_start = destination.add_offset(
+                        &Bitvector::from_i64(lower_bound)
+                            .into_resize_signed(destination.bytesize())
+                            .into(),
+                    );
+                    written_range_size -= lower_bound as u64;
+                }
+                new_state.memory.assume_strided_writes(
+                    &written_range_start,
+                    ByteSize::new(written_range_size),
+                    0,
+                    Some(1),
+                    &source_targets,
+                );
+            }
+            None => new_state
+                .memory
+                .assume_writes_up_to_upper_bound(&destination, &source_targets),
+        }
+        if parameters.null_terminated {
+            // If the length exceeds the string length bound of the source,
+            // then the whole string including the terminating null byte is copied.
+            if let (Some(length), Some(source_bound)) =
+                (length_value, state.memory.get_string_length_bound(&source))
+            {
+                let exceeds_bound = match (
+                    length.get_if_unique_target(),
+                    source_bound.get_if_unique_target(),
+                ) {
+                    (Some((length_id, length_offset)), Some((bound_id, bound_offset))) => {
+                        length_id == bound_id
+                            && matches!(
+                                (length_offset.try_to_offset(), bound_offset.try_to_offset()),
+                                (Ok(length_offset), Ok(bound_offset)) if length_offset > bound_offset
+                            )
+                    }
+                    _ => false,
+                };
+                if exceeds_bound {
+                    new_state
+                        .memory
+                        .set_string_length_bound(&destination, source_bound.clone());
+                }
+            }
+        }
+        new_state
+    }
+
+    /// Handle a call to a `pthread_create`-like extern function.
+    ///
+    /// In addition to the effects of a generic extern call,
+    /// all objects reachable through the argument passed to the start routine are marked as non-unique,
+    /// since the new thread may access them concurrently.
+    /// The start routine itself is analyzed as a separate entry point.
+    fn handle_thread_creation_call(
+        &self,
+        state: &State,
+        new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        parameters: &ThreadCreationParameters,
+    ) -> State {
+        let mut new_state = self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+        if let Some(argument) = extern_symbol
+            .parameters
+            .get(parameters.argument_index)
+            .and_then(|arg| {
+                state
+                    .eval_parameter_arg(arg, self.runtime_memory_image)
+                    .ok()
+            })
+        {
+            new_state.mark_shared_objects_as_not_unique(&argument);
+        }
+        new_state
+    }
+
+    /// Handle a call to a `getenv`-like extern function.
+    ///
+    /// The return value is a pointer to the environment object representing the variable named by the first parameter.
+    /// There is only one environment object per variable name,
+    /// so that later modifications of the environment can invalidate all pointers to it.
+    /// Variables whose names are not known share a common environment object.
+    /// If the object was invalidated before, it is marked as alive again,
+    /// since the lookup returns a valid pointer to the current value of the variable.
+    fn add_environment_object_in_return_register(
+        &self,
+        state: &State,
+        new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let mut new_state = self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+        let return_register = match extern_symbol.get_unique_return_register() {
+            Ok(return_register) => return_register,
+            Err(err) => {
+                self.log_debug(Err(err), Some(&call.tid));
+                return new_state;
+            }
+        };
+        let variable_name = self.get_environment_variable_name(state, extern_symbol);
+        let object_id = AbstractIdentifier::new(
+            get_environment_object_tid(variable_name.as_deref()),
+            AbstractLocation::from_var(return_register).unwrap(),
+        );
+        let address_bytesize = self.project.get_pointer_bytesize();
+        if new_state.memory.get_object_type(&object_id).is_ok() {
+            new_state
+                .memory
+                .set_object_state(&object_id, super::object::ObjectState::Alive);
+        } else {
+            new_state.memory.add_abstract_object(
+                object_id.clone(),
+                Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
+                ObjectType::Environment,
+                address_bytesize,
+            );
+        }
+        let pointer = Data::from_target(
+            object_id,
+            Bitvector::zero(apint::BitWidth::from(address_bytesize)).into(),
+        );
+        new_state.set_register(return_register, pointer);
+        new_state
+    }
+
+    /// Handle a call to an extern function modifying the environment, e.g. `setenv`.
+    ///
+    /// If the name of the modified variable is known,
+    /// the environment objects for this variable and for variables with unknown names are marked as dangling.
+    /// Otherwise all environment objects are marked as dangling.
+    fn invalidate_environment_objects(
+        &self,
+        state: &State,
+        new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+    ) -> State {
+        let mut new_state = self.handle_generic_extern_call(state, new_state, call, extern_symbol);
+        let variable_name = self.get_environment_variable_name(state, extern_symbol);
+        let unknown_variable_tid = get_environment_object_tid(None);
+        let modified_variable_tid = get_environment_object_tid(variable_name.as_deref());
+        for object_id in new_state.memory.get_environment_object_ids() {
+            if variable_name.is_none()
+                || *object_id.get_tid() == modified_variable_tid
+                || *object_id.get_tid() == unknown_variable_tid
+            {
+                new_state
+                    .memory
+                    .set_object_state(&object_id, super::object::ObjectState::Dangling);
+            }
+        }
+        new_state
+    }
+
+    /// Get the name of the environment variable that the first parameter of the given call refers to,
+    /// if it is a constant string.
+    ///
+    /// For parameters of the form `NAME=value` (as used by `putenv`) only the name is returned.
+    fn get_environment_variable_name(
+        &self,
+        state: &State,
+        extern_symbol: &ExternSymbol,
+    ) -> Option<String> {
+        let parameter = extern_symbol.parameters.first()?;
+        let name_pointer = state
+            .eval_parameter_arg(parameter, self.runtime_memory_image)
+            .ok()?;
+        let name_address = name_pointer.get_if_absolute_value()?.try_to_bitvec().ok()?;
+        let name = self
+            .runtime_memory_image
+            .read_string_until_null_terminator(&name_address)
+            .ok()?;
+        name.split('=').next().map(|name| name.to_string())
+    }
+
+    /// Update the memory protection of the memory region targeted by a call to a memory mapping function,
+    /// e.g. the region returned by `mmap` or the region passed to `mprotect`.
+    ///
+    /// If a region that was writable before is made executable by the call, a CWE-1386 warning is generated.
+    /// Regions mapped as writable and executable by a single call are left to the CWE-1386 check.
+    fn update_memory_protection(
+        &self,
+        state: &State,
+        mut new_state: State,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        parameters: &MemoryProtectionParameters,
+    ) -> State {
+        let address_bytesize = self.project.get_pointer_bytesize();
+        let eval_known_bits = |index: usize| {
+            extern_symbol
+                .parameters
+                .get(index)
+                .and_then(|parameter| {
+                    state
+                        .eval_parameter_arg(parameter, self.runtime_memory_image)
+                        .ok()
+                })
+                .map(|value| value.get_known_bits())
+                .unwrap_or_else(|| KnownBits::new_unknown(address_bytesize))
+        };
+        let region_pointer = match parameters.pointer_index {
+            Some(index) => extern_symbol.parameters.get(index).and_then(|parameter| {
+                state
+                    .eval_parameter_arg(parameter, self.runtime_memory_image)
+                    .ok()
+            }),
+            None => extern_symbol
+                .get_unique_return_register()
+                .ok()
+                .map(|return_register| new_state.get_register(return_register)),
+        };
+        let region_pointer = match region_pointer {
+            Some(pointer) => pointer,
+            None => return new_state,
+        };
+        let protection = eval_known_bits(parameters.protection_index);
+        let flags = parameters
+            .flags_index
+            .map(eval_known_bits)
+            .unwrap_or_else(|| KnownBits::new_unknown(address_bytesize));
+        let is_strong_update = region_pointer.get_if_unique_target().is_some();
+        for object_id in region_pointer.get_relative_values().keys() {
+            let old_protection = new_state.memory.get_mapping_protection(object_id).cloned();
+            let updated_protection = match (&old_protection, parameters.pointer_index) {
+                (Some(old_protection), Some(_)) => old_protection.update(protection.clone()),
+                _ => MappingProtection::new(protection.clone(), flags.clone()),
+            };
+            if updated_protection.was_writable
+                && updated_protection.is_executable()
+                && !updated_protection.is_writable()
+            {
+                self.report_executable_writable_region(call, extern_symbol, &new_state, object_id);
+            }
+            let updated_protection = match old_protection {
+                Some(old_protection) if !is_strong_update => {
+                    old_protection.merge(&updated_protection)
+                }
+                _ => updated_protection,
+            };
+            new_state
+                .memory
+                .set_mapping_protection(object_id, updated_protection);
+        }
+        new_state
+    }
+
+    /// Generate a CWE-1386 warning for a call making a memory region executable that was writable before.
+    fn report_executable_writable_region(
+        &self,
+        call: &Term<Jmp>,
+        extern_symbol: &ExternSymbol,
+        state: &State,
+        object_id: &AbstractIdentifier,
+    ) {
+        let mut addresses = vec![call.tid.canonical_address().to_string()];
+        let mut tids = vec![format!("{}", call.tid)];
+        let mut description = format!(
+            "(Writable and Executable Memory) Call to {} at {} makes memory executable that was writable before",
+            extern_symbol.name, call.tid.canonical_address()
+        );
+        if let Some(allocation_site) = state.memory.get_allocation_site(object_id) {
+            description.push_str(&format!(
+                " (mapped at {})",
+                allocation_site.canonical_address()
+            ));
+            addresses.push(allocation_site.canonical_address().to_string());
+            tids.push(format!("{}", allocation_site));
+        }
+        let warning = CweWarning::new("CWE1386", VERSION, description)
+            .tids(tids)
+            .addresses(addresses)
+            .symbols(vec![extern_symbol.name.clone()]);
+        let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+    }
+
     /// Handle a generic call whose target function is unknown.
     ///
     /// This function just assumes that the target of the call uses a reasonable standard calling convention
@@ -455,12 +1961,45 @@ impl<'a> Context<'a> {
                     .memory
                     .assume_arbitrary_writes_to_object(id, &possible_referenced_ids);
             }
+            // The unknown function may store pointers to the referenced objects anywhere.
+            new_state
+                .memory
+                .mark_heap_objects_as_escaped(&possible_referenced_ids);
             Some(new_state)
         } else {
             None // We don't try to handle cases where we cannot guess a reasonable standard calling convention.
         }
     }
 
+    /// Handle a call to a function that is excluded from the analysis.
+    ///
+    /// The call is handled like a call to an unknown function.
+    /// If a summary of the side effects of the callee is known,
+    /// the values that the callee cannot modify are restored afterwards
+    /// and the objects passed to it are updated according to its parameter ownership.
+    fn handle_call_to_skipped_function(
+        &self,
+        state_before_call: &State,
+        callee: &Tid,
+    ) -> Option<State> {
+        let mut new_state = self.handle_call_to_generic_unknown_function(state_before_call)?;
+        if let (Some(classification), Some(calling_conv)) = (
+            self.function_purities.get(callee),
+            self.project.get_standard_calling_convention(),
+        ) {
+            new_state.restore_values_untouched_by_callee(
+                state_before_call,
+                classification,
+                &calling_conv.get_all_parameter_register(),
+                &self.project.stack_pointer_register,
+            );
+        }
+        if let Some(classification) = self.parameter_ownership.get(callee) {
+            new_state.apply_parameter_ownership(state_before_call, classification);
+        }
+        Some(new_state)
+    }
+
     /// Get the offset of the current stack pointer to the base of the current stack frame.
     fn get_current_stack_offset(&self, state: &State) -> ValueDomain {
         if let Some((stack_id, stack_offset_domain)) = state
@@ -479,18 +2018,52 @@ impl<'a> Context<'a> {
         let warning = CweWarning {
             name: "CWE476".to_string(),
             version: VERSION.to_string(),
-            addresses: vec![tid.address.clone()],
+            addresses: vec![tid.canonical_address().to_string()],
             tids: vec![format!("{}", tid)],
             symbols: Vec::new(),
             other: Vec::new(),
             description: format!(
                 "(NULL Pointer Dereference) Memory access at {} may result in a NULL dereference",
-                tid.address
+                tid.canonical_address()
             ),
+            evidence: None,
+            exploit_primitive: None,
+            remediation: None,
         };
         let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
     }
 }
 
+/// Get the TID used in the IDs of environment objects for the environment variable with the given name.
+/// All variables with unknown names are represented by the same TID.
+fn get_environment_object_tid(variable_name: Option<&str>) -> Tid {
+    match variable_name {
+        Some(name) => Tid::new(format!("environment_{}", name)),
+        None => Tid::new("environment"),
+    }
+}
+
+/// Map the TIDs of blocks with more than one incoming intraprocedural jump
+/// to the comparison constants of their function, which are used as widening thresholds at these blocks.
+/// Functions without comparison constants are omitted.
+fn get_widening_thresholds(graph: &Graph) -> HashMap<Tid, Vec<Bitvector>> {
+    let comparison_constants = get_comparison_constants(graph);
+    graph
+        .node_indices()
+        .filter_map(|node| match graph[node] {
+            Node::BlkStart(block, sub) => {
+                let incoming_jumps = graph
+                    .edges_directed(node, petgraph::Direction::Incoming)
+                    .filter(|edge| matches!(edge.weight(), Edge::Jump(..)))
+                    .count();
+                let thresholds = comparison_constants.get(&sub.tid)?;
+                (incoming_jumps > 1 && !thresholds.is_empty())
+                    .then(|| (block.tid.clone(), thresholds.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests;
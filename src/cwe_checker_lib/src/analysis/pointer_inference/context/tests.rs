@@ -1,4 +1,5 @@
 use crate::intermediate_representation::DatatypeProperties;
+use crate::utils::log::ExploitPrimitive;
 
 use super::*;
 use std::{collections::HashSet, iter::FromIterator};
@@ -80,12 +81,18 @@ fn mock_project() -> (Project, Config) {
         extern_symbols: vec![
             mock_extern_symbol("malloc"),
             mock_extern_symbol("free"),
+            mock_extern_symbol("dlsym"),
             mock_extern_symbol("other"),
+            mock_extern_symbol("makecontext"),
+            mock_extern_symbol("getenv"),
+            mock_extern_symbol("setenv"),
         ]
         .into_iter()
         .collect(),
         entry_points: BTreeSet::new(),
         address_base_offset: 0,
+        symbol_aliases: BTreeMap::new(),
+        non_returning_subs: BTreeSet::new(),
     };
     let program_term = Term {
         tid: Tid::new("program"),
@@ -98,6 +105,7 @@ fn mock_project() -> (Project, Config) {
         integer_return_register: vec![Variable::mock("RDX", 8)],
         float_return_register: vec![],
         callee_saved_register: vec![Variable::mock("callee_saved_reg", 8)],
+        varargs_layout: None,
     };
     let register_set = vec!["RAX", "RCX", "RDX", "RBX", "RSP", "RBP", "RSI", "RDI"]
         .into_iter()
@@ -107,14 +115,40 @@ fn mock_project() -> (Project, Config) {
         Project {
             program: program_term,
             cpu_architecture: "x86_64".to_string(),
+            architecture: Arch::X86_64,
             stack_pointer_register: register("RSP"),
             calling_conventions: BTreeMap::from_iter([(cconv.name.clone(), cconv)]),
             register_set,
             datatype_properties: DatatypeProperties::mock(),
+            stack_canary_artifacts: BTreeSet::new(),
+            duplicated_term_origins: BTreeMap::new(),
         },
         Config {
             allocation_symbols: vec!["malloc".into()],
             deallocation_symbols: vec!["free".into()],
+            custom_allocators: Vec::new(),
+            function_lookup_symbols: vec!["dlsym".into()],
+            context_switch_symbols: vec!["makecontext".into()],
+            coroutine_entry_index: BTreeMap::from([("makecontext".into(), 1)]),
+            thread_creation_symbols: BTreeMap::new(),
+            environment_lookup_symbols: vec!["getenv".into()],
+            environment_modification_symbols: vec!["setenv".into()],
+            memory_protection_symbols: BTreeMap::new(),
+            event_polling_symbols: BTreeMap::new(),
+            memory_copy_symbols: BTreeMap::new(),
+            collect_warning_evidence: false,
+            attribute_warning_paths: false,
+            collect_access_histograms: false,
+            assume_pure: Vec::new(),
+            string_length_symbols: vec!["strlen".into()],
+            program_start_symbols: vec!["__libc_start_main".into()],
+            max_pointer_targets: None,
+            harness: None,
+            trusted_convention_discrepancies: Vec::new(),
+            written_pointer_parameters: BTreeMap::new(),
+            function_purities: Vec::new(),
+            parameter_ownership: Vec::new(),
+            node_value_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
         },
     )
 }
@@ -168,6 +202,7 @@ fn context_problem_implementation() {
             name: "caller_sub".into(),
             blocks: vec![target_block.clone()],
             calling_convention: None,
+            is_thunk: false,
         },
     };
     let target_node = crate::analysis::graph::Node::BlkStart(&target_block, &sub);
@@ -281,6 +316,32 @@ fn context_problem_implementation() {
         .is_top());
 }
 
+#[test]
+fn reallocation_of_non_escaped_objects() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (project, config) = mock_project();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state = State::new(&register("RSP"), Tid::new("main"));
+    let heap_id = new_id("call_extern_malloc", "RDX");
+    let malloc = call_term("extern_malloc");
+
+    // The object of the first call is not reachable anymore when calling malloc again.
+    let state = context.update_call_stub(&state, &malloc).unwrap();
+    let state = context.update_call_stub(&state, &malloc).unwrap();
+    assert!(state.memory.is_unique_object(&heap_id).unwrap());
+    assert!(!state.memory.has_escaped(&heap_id).unwrap());
+
+    // The object escapes through the call to an unknown extern function.
+    let other_extern_fn = call_term("extern_other");
+    let state = context.update_call_stub(&state, &other_extern_fn).unwrap();
+    assert!(state.memory.has_escaped(&heap_id).unwrap());
+    let state = context.update_call_stub(&state, &malloc).unwrap();
+    assert!(!state.memory.is_unique_object(&heap_id).unwrap());
+}
+
 #[test]
 fn update_return() {
     use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
@@ -352,6 +413,9 @@ fn update_return() {
         .ids_known_to_caller
         .insert(caller_caller_id.clone());
 
+    state_before_call.set_register(&register("callee_saved_reg"), bv(3).into());
+    state_before_return.set_register(&register("callee_saved_reg"), bv(7).into());
+
     let state = context
         .update_return(
             Some(&state_before_return),
@@ -381,6 +445,110 @@ fn update_return() {
         .is_some());
     let expected_rsp = Data::from_target(new_id("original_caller_id", "RSP"), bv(-8));
     assert_eq!(state.get_register(&register("RSP")), expected_rsp);
+    assert_eq!(
+        state.get_register(&register("callee_saved_reg")),
+        bv(3).into()
+    );
+
+    // Clobbered callee-saved registers are not restored if the observed behavior of the callee is trusted.
+    let (_, mut config) = mock_project();
+    config.trusted_convention_discrepancies = vec![CallingConventionDiscrepancy {
+        function: Tid::new("callee"),
+        clobbered_callee_saved_registers: vec![register("callee_saved_reg")],
+        unlisted_parameter_registers: Vec::new(),
+    }];
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state = context
+        .update_return(
+            Some(&state_before_return),
+            Some(&state_before_call),
+            &call_term("callee"),
+            &return_term("return_target"),
+            &None,
+        )
+        .unwrap();
+    assert_eq!(
+        state.get_register(&register("callee_saved_reg")),
+        bv(7).into()
+    );
+}
+
+#[test]
+fn non_escaped_callee_objects_are_removed_on_return() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::pointer_inference::object::ObjectType;
+    use crate::analysis::pointer_inference::Data;
+    let (project, config) = mock_project();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state_before_call = State::new(&register("RSP"), Tid::new("caller"));
+    let state_before_return = State::new(&register("RSP"), Tid::new("callee"));
+    let mut state_before_return = context
+        .update_def(
+            &state_before_return,
+            &reg_add_term("RSP", 8, "stack_offset_on_return_adjustment"),
+        )
+        .unwrap();
+    let callsite_id = new_id("call_callee", "RSP");
+    state_before_return.memory.add_abstract_object(
+        callsite_id.clone(),
+        bv(0),
+        ObjectType::Stack,
+        ByteSize::new(8),
+    );
+    state_before_return
+        .caller_stack_ids
+        .insert(callsite_id.clone());
+    state_before_return.ids_known_to_caller.insert(callsite_id);
+
+    // A heap object only referenced by the callee stack frame
+    let local_heap_id = new_id("call_malloc_local", "RAX");
+    state_before_return.memory.add_abstract_object(
+        local_heap_id.clone(),
+        bv(0),
+        ObjectType::Heap,
+        ByteSize::new(8),
+    );
+    state_before_return
+        .store_value(
+            &Data::from_target(state_before_return.stack_id.clone(), bv(-8)),
+            &Data::from_target(local_heap_id.clone(), bv(0)),
+            &runtime_memory_image,
+        )
+        .unwrap();
+    // A heap object returned to the caller
+    let returned_heap_id = new_id("call_malloc_returned", "RAX");
+    state_before_return.memory.add_abstract_object(
+        returned_heap_id.clone(),
+        bv(0),
+        ObjectType::Heap,
+        ByteSize::new(8),
+    );
+    state_before_return.set_register(
+        &register("RAX"),
+        Data::from_target(returned_heap_id.clone(), bv(0)),
+    );
+    assert!(!state_before_return
+        .memory
+        .has_escaped(&local_heap_id)
+        .unwrap());
+
+    let state = context
+        .update_return(
+            Some(&state_before_return),
+            Some(&state_before_call),
+            &call_term("callee"),
+            &return_term("return_target"),
+            &None,
+        )
+        .unwrap();
+    let object_ids = state.memory.get_all_object_ids();
+    assert!(!object_ids.contains(&local_heap_id));
+    assert!(object_ids.contains(&returned_heap_id));
+    assert!(!state.ids_known_to_caller.contains(&returned_heap_id));
 }
 
 #[test]
@@ -423,3 +591,1148 @@ fn specialize_conditional() {
     let result = context.specialize_conditional(&state, &condition, &block, false);
     assert!(result.is_none());
 }
+
+#[test]
+fn specialize_conditional_on_unknown() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (project, config) = mock_project();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    let mut state = State::new(&register("RAX"), Tid::new("func"));
+    state.set_register(&register("RAX"), IntervalDomain::mock(-10, 20).into());
+    let block = Blk::mock();
+    let unknown_condition = Expression::Unknown {
+        description: "untranslated".to_string(),
+        size: ByteSize::new(1),
+    };
+    let comparison_with_unknown = Expression::BinOp {
+        lhs: Box::new(Expression::Var(register("RAX"))),
+        op: BinOpType::IntSLessEqual,
+        rhs: Box::new(Expression::Unknown {
+            description: "untranslated".to_string(),
+            size: ByteSize::new(8),
+        }),
+    };
+    // Both edges are taken without specialization.
+    for condition in [unknown_condition, comparison_with_unknown] {
+        for is_true in [true, false] {
+            assert_eq!(
+                context.specialize_conditional(&state, &condition, &block, is_true),
+                Some(state.clone())
+            );
+        }
+    }
+}
+
+#[test]
+fn specialize_conditional_on_loaded_stack_value() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (project, config) = mock_project();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    // `RAX = *(RSP - 8); if ((RAX & 0xff) == 0x2f) ...`
+    let stack_slot = Expression::Var(register("RSP")).plus_const(-8);
+    let mut state = State::new(&register("RSP"), Tid::new("func"));
+    state
+        .write_to_address(
+            &stack_slot,
+            &IntervalDomain::mock(0, 0xff).into(),
+            &runtime_memory_image,
+        )
+        .unwrap();
+    let mut block = Blk::mock();
+    block.term.defs = vec![Def::load("load", register("RAX"), stack_slot.clone())];
+    state = context.update_def(&state, &block.term.defs[0]).unwrap();
+    let condition = Expression::BinOp {
+        lhs: Box::new(Expression::BinOp {
+            lhs: Box::new(Expression::Var(register("RAX"))),
+            op: BinOpType::IntAnd,
+            rhs: Box::new(Expression::const_from_i64(0xff)),
+        }),
+        op: BinOpType::IntEqual,
+        rhs: Box::new(Expression::const_from_i64(0x2f)),
+    };
+
+    let result = context
+        .specialize_conditional(&state, &condition, &block, true)
+        .unwrap();
+    let expected_value: Data = Bitvector::from_i64(0x2f).into();
+    assert_eq!(result.get_register(&register("RAX")), expected_value);
+    assert_eq!(
+        result
+            .load_value(&stack_slot, ByteSize::new(8), &runtime_memory_image)
+            .unwrap(),
+        expected_value
+    );
+
+    // If the register was overwritten after the load, the stack slot is not specialized.
+    block.term.defs.push(Def::assign(
+        "assign",
+        register("RAX"),
+        Expression::const_from_i64(0x2f),
+    ));
+    let result = context
+        .specialize_conditional(&state, &condition, &block, true)
+        .unwrap();
+    assert_eq!(
+        result
+            .load_value(&stack_slot, ByteSize::new(8), &runtime_memory_image)
+            .unwrap(),
+        IntervalDomain::mock(0, 0xff).into()
+    );
+}
+
+#[test]
+fn jumps_to_failed_stack_canary_checks_are_infeasible() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (_, config) = mock_project();
+    let mut project = Project::mock_with_stack_canary_check();
+    project.mark_stack_canary_artifacts();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state = State::new(&register("RSP"), Tid::new("func"));
+
+    let sub = &project.program.term.subs[&Tid::new("func")];
+    let epilogue = &sub.term.blocks[0];
+    let fail_block = &sub.term.blocks[1];
+    let return_block = &sub.term.blocks[2];
+    assert!(context
+        .update_jump(
+            &state,
+            &epilogue.term.jmps[1],
+            Some(&epilogue.term.jmps[0]),
+            fail_block
+        )
+        .is_none());
+    assert!(context
+        .update_jump(&state, &epilogue.term.jmps[0], None, return_block)
+        .is_some());
+}
+
+#[test]
+fn calls_to_skipped_functions_are_handled_as_unknown_calls() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::function_signature::FunctionPurity;
+    use crate::analysis::function_skipping::FunctionSkipConfig;
+    let (mut project, config) = mock_project();
+    let mut skipped_sub = Sub::mock("skipped_func");
+    skipped_sub.term.blocks.push(Blk::mock());
+    project
+        .program
+        .term
+        .subs
+        .insert(skipped_sub.tid.clone(), skipped_sub);
+    let skip_config = FunctionSkipConfig {
+        name_globs: vec!["skipped_*".to_string()],
+        ..Default::default()
+    };
+    let (skipped_subs, logs) = skip_config.get_skipped_subs(&project.program);
+    assert_eq!(logs.len(), 1);
+    let graph = crate::analysis::graph::get_program_cfg_with_skipped_subs(
+        &project.program,
+        HashSet::new(),
+        skipped_subs,
+    );
+    assert_eq!(graph.node_count(), 0);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    let mut state = State::new(&register("RSP"), Tid::new("caller"));
+    state.set_register(&register("RDX"), bv(42).into());
+    state.set_register(&register("callee_saved_reg"), bv(13).into());
+    let state_after_call = context
+        .update_call_stub(&state, &call_term("skipped_func"))
+        .unwrap();
+    assert!(state_after_call.get_register(&register("RDX")).is_top());
+    assert_eq!(
+        state_after_call.get_register(&register("callee_saved_reg")),
+        bv(13).into()
+    );
+
+    // If a summary of the skipped function is known, registers not written by it keep their values.
+    let (_, mut config) = mock_project();
+    config.function_purities = vec![PurityClassification {
+        function: Tid::new("skipped_func"),
+        purity: FunctionPurity::Pure,
+        written_registers: BTreeSet::from([register("RAX")]),
+    }];
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let state_after_call = context
+        .update_call_stub(&state, &call_term("skipped_func"))
+        .unwrap();
+    assert_eq!(
+        state_after_call.get_register(&register("RDX")),
+        bv(42).into()
+    );
+}
+
+#[test]
+fn shrinking_realloc_and_stale_bounds() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, mut config) = mock_project();
+    config.allocation_symbols.push("realloc".into());
+    let register_arg = |name: &str| Arg::Register {
+        expr: Expression::Var(register(name)),
+        data_type: None,
+    };
+    let realloc = ExternSymbol {
+        tid: Tid::new("extern_realloc"),
+        addresses: vec![],
+        name: "realloc".into(),
+        calling_convention: None,
+        parameters: vec![register_arg("RDI"), register_arg("RSI")],
+        return_values: vec![register_arg("RAX")],
+        no_return: false,
+        has_var_args: false,
+    };
+    project
+        .program
+        .term
+        .extern_symbols
+        .insert(realloc.tid.clone(), realloc);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    // `p = malloc(100); q = realloc(p, 10);`
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RDX"), bv(100).into());
+    let mut state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    state.set_register(&register("RDI"), state.get_register(&register("RDX")));
+    state.set_register(&register("RSI"), bv(10).into());
+    let state = context
+        .update_call_stub(&state, &call_term("extern_realloc"))
+        .unwrap();
+    let realloc_id = new_id("call_extern_realloc", "RAX");
+    assert_eq!(state.memory.get_object_size(&realloc_id), Some(10));
+    assert_eq!(
+        state.memory.get_allocation_site(&realloc_id),
+        Some(&Tid::new("call_extern_realloc"))
+    );
+
+    // Reading with the stale length of the old object is out of bounds.
+    let load = Def::load(
+        "stale_read",
+        register("RBX"),
+        Expression::Var(register("RAX")).plus_const(50),
+    );
+    context.update_def(&state, &load).unwrap();
+    let warning = match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => warning,
+        _ => panic!("Expected a CWE warning"),
+    };
+    assert_eq!(warning.name, "CWE125");
+    assert!(warning
+        .description
+        .ends_with("(object was shrunk at UNKNOWN from 100 to 10 bytes)"));
+    assert_eq!(warning.tids[1], "call_extern_realloc");
+    // Accesses inside the new bounds are not reported.
+    let load = Def::load(
+        "valid_read",
+        register("RBX"),
+        Expression::Var(register("RAX")).plus_const(2),
+    );
+    context.update_def(&state, &load).unwrap();
+    assert!(log_receiver.try_recv().is_err());
+
+    // If the new size interval overlaps the old size, the upper end of the new size interval is the bound.
+    let mut state = state;
+    state.set_register(&register("RDI"), state.get_register(&register("RAX")));
+    state.set_register(&register("RSI"), IntervalDomain::mock(8, 16).into());
+    let state = context
+        .update_call_stub(&state, &call_term("extern_realloc"))
+        .unwrap();
+    assert_eq!(state.memory.get_object_size(&realloc_id), Some(16));
+    let stale_read = Def::load(
+        "stale_read",
+        register("RBX"),
+        Expression::Var(register("RAX")).plus_const(9),
+    );
+    let valid_read = Def::load(
+        "valid_read",
+        register("RBX"),
+        Expression::Var(register("RAX")).plus_const(8),
+    );
+    assert!(state.contains_out_of_bounds_mem_access(&stale_read.term, &runtime_memory_image));
+    assert!(!state.contains_out_of_bounds_mem_access(&valid_read.term, &runtime_memory_image));
+    assert!(state
+        .get_shrink_info_of_out_of_bounds_mem_access(&stale_read.term)
+        .is_none());
+}
+
+#[test]
+fn out_of_bounds_write_evidence() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (project, mut config) = mock_project();
+    config.collect_warning_evidence = true;
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    // `p = malloc(16); *(p + 20) = 0;`
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RDX"), bv(16).into());
+    let state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    let store = Def::store(
+        "oob_write",
+        Expression::Var(register("RDX")).plus_const(20),
+        Expression::const_from_i64(0),
+    );
+    context.update_def(&state, &store).unwrap();
+    let warning = match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => warning,
+        _ => panic!("Expected a CWE warning"),
+    };
+    assert_eq!(warning.name, "CWE787");
+    let evidence = warning.evidence.as_ref().unwrap();
+    assert_eq!(evidence.get("access_size"), Some(&serde_json::json!(8)));
+    let malloc_id = new_id("call_extern_malloc", "RDX");
+    let pointer = Data::from_target(malloc_id.clone(), bv(20));
+    assert_eq!(
+        evidence.get("registers").unwrap()["RDX"],
+        state.get_register(&register("RDX")).to_json_compact()
+    );
+    let address = evidence.get("address").unwrap();
+    assert_eq!(address["value"], pointer.to_json_compact());
+    let target = &address["targets"][format!("{} (base offset 0x0:i64)", malloc_id)];
+    assert_eq!(target["lower_index_bound"], "0x0:u64");
+    assert_eq!(target["upper_index_bound"], "0xf:u64");
+    assert_eq!(target["state"], "Alive");
+    // The evidence is serialized under the `evidence` key.
+    let json = serde_json::to_value(&warning).unwrap();
+    assert!(json["evidence"]["address"]["targets"].is_object());
+
+    // Without the configuration flag no evidence is collected.
+    let (project, config) = mock_project();
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    context.update_def(&state, &store).unwrap();
+    match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => assert!(warning.evidence.is_none()),
+        _ => panic!("Expected a CWE warning"),
+    }
+    assert!(
+        !serde_json::to_value(CweWarning::new("CWE787", VERSION, ""))
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .contains_key("evidence")
+    );
+}
+
+#[test]
+fn unknown_object_sizes_are_reported_as_imprecision() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::utils::feedback::{
+        extract_imprecision_reports, FeedbackReport, ImprecisionReason, Responsible,
+    };
+    let (mut project, config) = mock_project();
+    // The allocation site has to be contained in the program to be attributed to `malloc`.
+    let mut block = Blk::mock();
+    block.term.jmps.push(call_term("extern_malloc"));
+    let mut sub = Sub::mock("main");
+    sub.term.blocks.push(block);
+    project.program.term.subs.insert(sub.tid.clone(), sub);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    // `p = malloc(unknown_size); *(p + 20) = 0;`
+    let state = State::new(&register("RSP"), Tid::new("main"));
+    let state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    let store = Def::store(
+        "store",
+        Expression::Var(register("RDX")).plus_const(20),
+        Expression::const_from_i64(0),
+    );
+    context.update_def(&state, &store).unwrap();
+    let mut logs: Vec<LogMessage> = log_receiver
+        .try_iter()
+        .filter_map(|msg| match msg {
+            LogThreadMsg::Log(log) => Some(log),
+            _ => None,
+        })
+        .collect();
+    let reports = extract_imprecision_reports(&mut logs);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].checker, "CWE787");
+    assert_eq!(reports[0].reason, ImprecisionReason::UnknownObjectSize);
+
+    let feedback = FeedbackReport::new(&reports, &project);
+    assert_eq!(feedback.entries.len(), 1);
+    assert_eq!(
+        feedback.entries[0].responsible,
+        Responsible::ExternSymbol("malloc".to_string())
+    );
+
+    // Accesses to objects of known size are not reported.
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RDX"), bv(32).into());
+    let state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    context.update_def(&state, &store).unwrap();
+    assert!(log_receiver.try_recv().is_err());
+}
+
+#[test]
+fn out_of_bounds_write_exploit_primitives() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, config) = mock_project();
+    let register_arg = |name: &str| Arg::Register {
+        expr: Expression::Var(register(name)),
+        data_type: None,
+    };
+    let memset = ExternSymbol {
+        tid: Tid::new("extern_memset"),
+        addresses: vec![],
+        name: "memset".into(),
+        calling_convention: None,
+        parameters: vec![register_arg("RDI"), register_arg("RSI")],
+        return_values: vec![register_arg("RAX")],
+        no_return: false,
+        has_var_args: false,
+    };
+    project
+        .program
+        .term
+        .extern_symbols
+        .insert(memset.tid.clone(), memset);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    // Debug log messages are skipped.
+    let receive_warning = || {
+        log_receiver
+            .try_iter()
+            .find_map(|msg| match msg {
+                LogThreadMsg::Cwe(warning) => Some(warning),
+                _ => None,
+            })
+            .expect("Expected a CWE warning")
+    };
+
+    // `p = malloc(16);`
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RDX"), bv(16).into());
+    let mut state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    let malloc_id = new_id("call_extern_malloc", "RDX");
+    let store_to = |register_name: &str| {
+        Def::store(
+            "oob_write",
+            Expression::Var(register(register_name)),
+            Expression::const_from_i64(0),
+        )
+    };
+
+    // `*(p + 20) = 0;`
+    state.set_register(
+        &register("RBX"),
+        Data::from_target(malloc_id.clone(), bv(20)),
+    );
+    context.update_def(&state, &store_to("RBX")).unwrap();
+    let warning = receive_warning();
+    assert_eq!(warning.name, "CWE787");
+    assert_eq!(
+        warning.exploit_primitive,
+        Some(ExploitPrimitive::FixedOffsetOverflow)
+    );
+
+    // `*(p + i) = 0;` for an index `i` in the interval [8, 24].
+    state.set_register(
+        &register("RBX"),
+        Data::from_target(malloc_id.clone(), IntervalDomain::mock(8, 24)),
+    );
+    context.update_def(&state, &store_to("RBX")).unwrap();
+    assert_eq!(
+        receive_warning().exploit_primitive,
+        Some(ExploitPrimitive::ControlledOffset)
+    );
+
+    // `*addr = 0;` for a non-constant absolute address.
+    state.set_register(
+        &register("RBX"),
+        IntervalDomain::mock(0x10_0000, 0x20_0000).into(),
+    );
+    context.update_def(&state, &store_to("RBX")).unwrap();
+    assert_eq!(
+        receive_warning().exploit_primitive,
+        Some(ExploitPrimitive::ArbitraryWrite)
+    );
+
+    // `memset(p + 16, n);` for a non-constant size `n`.
+    state.set_register(
+        &register("RDI"),
+        Data::from_target(malloc_id.clone(), bv(16)),
+    );
+    state.set_register(&register("RSI"), IntervalDomain::mock(8, 64).into());
+    context
+        .update_call_stub(&state, &call_term("extern_memset"))
+        .unwrap();
+    let warning = receive_warning();
+    assert_eq!(warning.name, "CWE119");
+    assert_eq!(
+        warning.exploit_primitive,
+        Some(ExploitPrimitive::ControlledSizeLinearOverflow)
+    );
+    // With a constant size the call only provides a fixed-offset overflow.
+    state.set_register(&register("RSI"), bv(8).into());
+    context
+        .update_call_stub(&state, &call_term("extern_memset"))
+        .unwrap();
+    assert_eq!(
+        receive_warning().exploit_primitive,
+        Some(ExploitPrimitive::FixedOffsetOverflow)
+    );
+
+    // Out-of-bounds reads are not classified.
+    let load = Def::load(
+        "oob_read",
+        register("RAX"),
+        Expression::Var(register("RDI")),
+    );
+    context.update_def(&state, &load).unwrap();
+    let warning = receive_warning();
+    assert_eq!(warning.name, "CWE125");
+    assert_eq!(warning.exploit_primitive, None);
+}
+
+#[test]
+fn indirect_call_to_unchecked_dlsym_result() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, config) = mock_project();
+    // `fn_ptr = dlsym(handle, name); fn_ptr();`
+    let mut lookup_block = Blk::mock_with_tid("lookup_blk");
+    let mut lookup_call = call_term("extern_dlsym");
+    lookup_call.term = Jmp::Call {
+        target: Tid::new("extern_dlsym"),
+        return_: Some(Tid::new("call_blk")),
+    };
+    lookup_block.term.jmps.push(lookup_call.clone());
+    let mut call_block = Blk::mock_with_tid("call_blk");
+    call_block.term.defs.push(Def::assign(
+        "copy_fn_ptr",
+        register("RAX"),
+        Expression::Var(register("RDX")),
+    ));
+    let indirect_call = Term {
+        tid: Tid::new("call_fn_ptr"),
+        term: Jmp::CallInd {
+            target: Expression::Var(register("RAX")),
+            return_: None,
+        },
+    };
+    call_block.term.jmps.push(indirect_call.clone());
+    let mut sub = Sub::mock("main");
+    sub.term.blocks = vec![lookup_block, call_block.clone()];
+    project.program.term.subs.insert(sub.tid.clone(), sub);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    let state = State::new(&register("RSP"), Tid::new("main"));
+    let state = context.update_call_stub(&state, &lookup_call).unwrap();
+    let fn_ptr = state.get_register(&register("RDX"));
+    assert!(!fn_ptr.contains_top());
+    assert!(fn_ptr
+        .get_absolute_value()
+        .unwrap()
+        .contains(&Bitvector::zero(ByteSize::new(8).into())));
+    let state = context
+        .update_def(&state, &call_block.term.defs[0])
+        .unwrap();
+
+    // Calling the unchecked function pointer generates a warning pointing to the `dlsym` call.
+    let state_after_call = context.update_call_stub(&state, &indirect_call);
+    assert!(state_after_call.is_some());
+    let warning = match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => warning,
+        _ => panic!("Expected a CWE warning"),
+    };
+    assert_eq!(warning.name, "CWE476");
+    assert_eq!(warning.symbols, vec!["dlsym".to_string()]);
+    assert!(warning.tids.contains(&format!("{}", lookup_call.tid)));
+
+    // After a NULL check no warning is generated.
+    let condition = Expression::BinOp {
+        lhs: Box::new(Expression::Var(register("RAX"))),
+        op: BinOpType::IntNotEqual,
+        rhs: Box::new(Expression::const_from_i64(0)),
+    };
+    let checked_state = context
+        .specialize_conditional(&state, &condition, &call_block, true)
+        .unwrap();
+    assert!(context
+        .update_call_stub(&checked_state, &indirect_call)
+        .is_some());
+    assert!(log_receiver.try_recv().is_err());
+}
+
+#[test]
+fn custom_pool_allocator() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    use crate::analysis::pointer_inference::custom_allocator::{
+        AllocatedPointerLocation, CustomAllocator,
+    };
+    let (mut project, mut config) = mock_project();
+    let pool_arg = Arg::mock_register("RDI", 8);
+    let second_arg = Arg::mock_register("RSI", 8);
+    let mut pool_alloc = mock_extern_symbol("pool_alloc").1;
+    pool_alloc.parameters = vec![pool_arg.clone(), second_arg.clone()];
+    let mut pool_free = mock_extern_symbol("pool_free").1;
+    pool_free.parameters = vec![pool_arg, second_arg];
+    pool_free.return_values = Vec::new();
+    let mut pool_alloc_out = pool_alloc.clone();
+    pool_alloc_out.tid = Tid::new("extern_pool_alloc_out");
+    pool_alloc_out.name = "pool_alloc_out".to_string();
+    pool_alloc_out.return_values = Vec::new();
+    for symbol in [pool_alloc, pool_free, pool_alloc_out] {
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(symbol.tid.clone(), symbol);
+    }
+    config.custom_allocators = vec![
+        CustomAllocator {
+            allocation_function: "pool_alloc".to_string(),
+            size_parameter_index: 1,
+            pointer_location: AllocatedPointerLocation::ReturnValue,
+            deallocation_function: "pool_free".to_string(),
+            freed_pointer_parameter_index: 1,
+            allows_offset_free: false,
+        },
+        CustomAllocator {
+            allocation_function: "pool_alloc_out".to_string(),
+            size_parameter_index: 1,
+            pointer_location: AllocatedPointerLocation::OutParameter(0),
+            deallocation_function: "pool_free".to_string(),
+            freed_pointer_parameter_index: 1,
+            allows_offset_free: false,
+        },
+    ];
+    // Add the calls to the program so that the allocator families of the allocation sites are known.
+    let mut sub = Sub::mock("main");
+    for target in [
+        "extern_pool_alloc",
+        "extern_malloc",
+        "extern_pool_alloc_out",
+    ] {
+        let mut block = Blk::mock_with_tid(&format!("blk_{}", target));
+        block.term.jmps.push(call_term(target));
+        sub.term.blocks.push(block);
+    }
+    project.program.term.subs.insert(sub.tid.clone(), sub);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    // `p = pool_alloc(pool, 16); pool_free(pool, p); pool_free(pool, p);`
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RSI"), bv(16).into());
+    let state = context
+        .update_call_stub(&state, &call_term("extern_pool_alloc"))
+        .unwrap();
+    let pool_object_id = new_id("call_extern_pool_alloc", "RDX");
+    let pointer = Data::from_target(pool_object_id.clone(), bv(0));
+    assert_eq!(state.get_register(&register("RDX")), pointer);
+    assert_eq!(state.memory.get_object_size(&pool_object_id), Some(16));
+    let mut state = state;
+    state.set_register(&register("RSI"), pointer.clone());
+    state.set_register(&register("callee_saved_reg"), pointer.clone());
+    let state = context
+        .update_call_stub(&state, &call_term("extern_pool_free"))
+        .unwrap();
+    assert!(log_receiver.try_recv().is_err());
+    let mut state = state;
+    state.set_register(&register("RSI"), pointer);
+    context
+        .update_call_stub(&state, &call_term("extern_pool_free"))
+        .unwrap();
+    match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => assert_eq!(warning.name, "CWE415"),
+        _ => panic!("Expected a CWE warning"),
+    }
+
+    // `p = malloc(8); pool_free(pool, p);` is a mismatched deallocation.
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("RDX"), bv(8).into());
+    let mut state = context
+        .update_call_stub(&state, &call_term("extern_malloc"))
+        .unwrap();
+    state.set_register(&register("RSI"), state.get_register(&register("RDX")));
+    context
+        .update_call_stub(&state, &call_term("extern_pool_free"))
+        .unwrap();
+    match log_receiver.try_recv() {
+        Ok(LogThreadMsg::Cwe(warning)) => assert_eq!(warning.name, "CWE762"),
+        _ => panic!("Expected a CWE warning"),
+    }
+    assert!(log_receiver.try_recv().is_err());
+
+    // `pool_alloc_out(&p, 32);` writes the pointer to the new object to the out-parameter.
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    let stack_address = Expression::Var(register("RSP")).plus_const(-8);
+    state.set_register(&register("RDI"), state.eval(&stack_address));
+    state.set_register(&register("RSI"), bv(32).into());
+    let state = context
+        .update_call_stub(&state, &call_term("extern_pool_alloc_out"))
+        .unwrap();
+    let out_object_id = AbstractIdentifier::new(
+        Tid::new("call_extern_pool_alloc_out"),
+        AbstractLocation::Pointer(
+            register("RDI"),
+            AbstractMemoryLocation::Location {
+                offset: 0,
+                size: ByteSize::new(8),
+            },
+        ),
+    );
+    // The extern call adjusted the stack pointer by 8 bytes.
+    let stack_address = Expression::Var(register("RSP")).plus_const(-16);
+    assert_eq!(
+        state
+            .load_value(&stack_address, ByteSize::new(8), &runtime_memory_image)
+            .unwrap(),
+        Data::from_target(out_object_id.clone(), bv(0))
+    );
+    assert_eq!(state.memory.get_object_size(&out_object_id), Some(32));
+}
+
+#[test]
+fn context_switch_clobbers_stack_pointer() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (project, config) = mock_project();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    let mut state = State::new(&register("RSP"), Tid::new("main"));
+    state.set_register(&register("callee_saved_reg"), bv(13).into());
+    state.set_register(&register("other_reg"), bv(14).into());
+    let new_state = context
+        .update_call_stub(&state, &call_term("extern_makecontext"))
+        .unwrap();
+    assert!(new_state.get_register(&register("RSP")).is_top());
+    assert!(new_state.get_register(&register("other_reg")).is_top());
+    assert_eq!(
+        new_state.get_register(&register("callee_saved_reg")),
+        bv(13).into()
+    );
+    let log_message = log_receiver
+        .try_iter()
+        .find_map(|msg| match msg {
+            LogThreadMsg::Log(log) => Some(log),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(
+        log_message.location,
+        Some(Tid::new("call_extern_makecontext"))
+    );
+}
+
+#[test]
+fn expired_environment_pointer() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (project, config) = mock_project();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let load_def = Def::load(
+        "load_env",
+        register("RAX"),
+        Expression::Var(register("callee_saved_reg")),
+    );
+    let getenv = |state: &State, name_address: i64| {
+        let mut state = state.clone();
+        state.set_register(&register("RDX"), bv(name_address).into());
+        let mut state = context
+            .update_call_stub(&state, &call_term("extern_getenv"))
+            .unwrap();
+        state.set_register(
+            &register("callee_saved_reg"),
+            state.get_register(&register("RDX")),
+        );
+        state
+    };
+    let setenv = |state: &State, name_address: i64| {
+        let mut state = state.clone();
+        state.set_register(&register("RDX"), bv(name_address).into());
+        context
+            .update_call_stub(&state, &call_term("extern_setenv"))
+            .unwrap()
+    };
+    let state = State::new(&register("RSP"), Tid::new("main"));
+
+    // getenv -> setenv -> use
+    let state_after_setenv = setenv(&getenv(&state, 0x3002), 0x3002);
+    context.update_def(&state_after_setenv, &load_def).unwrap();
+    let warnings: Vec<CweWarning> = log_receiver
+        .try_iter()
+        .filter_map(|msg| match msg {
+            LogThreadMsg::Cwe(warning) => Some(warning),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].name, "CWE825");
+    // A new lookup of the variable returns a valid pointer again.
+    let state_after_new_lookup = getenv(&state_after_setenv, 0x3002);
+    context
+        .update_def(&state_after_new_lookup, &load_def)
+        .unwrap();
+    assert!(log_receiver.try_iter().next().is_none());
+
+    // getenv -> use -> setenv
+    let state_after_getenv = getenv(&state, 0x3002);
+    let state_after_use = context.update_def(&state_after_getenv, &load_def).unwrap();
+    setenv(&state_after_use, 0x3002);
+    assert!(log_receiver.try_iter().next().is_none());
+
+    // Modifications of other variables do not invalidate the pointer.
+    let state_after_setenv = setenv(&getenv(&state, 0x3002), 0x500c);
+    context.update_def(&state_after_setenv, &load_def).unwrap();
+    assert!(log_receiver.try_iter().next().is_none());
+}
+
+#[test]
+fn writes_to_read_only_memory() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, mut config) = mock_project();
+    let (strcpy_tid, strcpy) = mock_extern_symbol("strcpy");
+    project
+        .program
+        .term
+        .extern_symbols
+        .insert(strcpy_tid, strcpy);
+    config.written_pointer_parameters = BTreeMap::from([("strcpy".to_string(), vec![0])]);
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let collect_warnings = || -> Vec<CweWarning> {
+        log_receiver
+            .try_iter()
+            .filter_map(|msg| match msg {
+                LogThreadMsg::Cwe(warning) => Some(warning),
+                _ => None,
+            })
+            .collect()
+    };
+    let store_def = |address: i64| {
+        Def::store(
+            "store",
+            Expression::const_from_i64(address),
+            Expression::const_from_i64(0).subpiece(ByteSize::new(0), ByteSize::new(1)),
+        )
+    };
+    let state = State::new(&register("RSP"), Tid::new("main"));
+
+    // The segment at 0x1000 is read-only, e.g. containing a string literal.
+    context.update_def(&state, &store_def(0x1000)).unwrap();
+    let warnings = collect_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].name, "CWE471");
+    assert!(warnings[0]
+        .description
+        .ends_with("writes to read-only global memory"));
+    // The segment at 0x2000 is writeable.
+    context.update_def(&state, &store_def(0x2002)).unwrap();
+    assert!(collect_warnings().is_empty());
+
+    // `strcpy` into a string literal.
+    let mut state = state;
+    state.set_register(&register("RDX"), bv(0x1002).into());
+    context
+        .update_call_stub(&state, &call_term("extern_strcpy"))
+        .unwrap();
+    let warnings = collect_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].symbols, vec!["strcpy".to_string()]);
+    // `strcpy` into a buffer that may be a string literal.
+    let mut possible_targets: Data = bv(0x1002).into();
+    possible_targets.set_contains_top_flag();
+    state.set_register(&register("RDX"), possible_targets);
+    context
+        .update_call_stub(&state, &call_term("extern_strcpy"))
+        .unwrap();
+    let warnings = collect_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].description.contains("may write"));
+    // `strcpy` into writeable global memory.
+    state.set_register(&register("RDX"), bv(0x2000).into());
+    context
+        .update_call_stub(&state, &call_term("extern_strcpy"))
+        .unwrap();
+    assert!(collect_warnings().is_empty());
+}
+
+#[test]
+fn free_of_pointer_not_at_start_of_buffer() {
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (project, config) = mock_project();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+    let collect_warnings = || -> Vec<CweWarning> {
+        log_receiver
+            .try_iter()
+            .filter_map(|msg| match msg {
+                LogThreadMsg::Cwe(warning) => Some(warning),
+                _ => None,
+            })
+            .collect()
+    };
+    let malloc = call_term("extern_malloc");
+    let free = call_term("extern_free");
+    let state = State::new(&register("RSP"), Tid::new("main"));
+    let mut state = context.update_call_stub(&state, &malloc).unwrap();
+    // Keep a copy of `p` in a callee-saved register.
+    state.set_register(
+        &register("callee_saved_reg"),
+        state.get_register(&register("RDX")),
+    );
+
+    // `free(p + 16)`
+    let offset_state = context
+        .update_def(&state, &reg_add_term("RDX", 16, "add"))
+        .unwrap();
+    let mut state_after_free = context.update_call_stub(&offset_state, &free).unwrap();
+    let warnings = collect_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].name, "CWE761");
+    assert!(warnings[0].description.contains("frees a pointer"));
+    assert_eq!(
+        warnings[0].other,
+        vec![vec![
+            "offset 16 into object allocated at UNKNOWN".to_string()
+        ]]
+    );
+    // The object is still marked as freed, i.e. `free(p)` afterwards is a double free.
+    state_after_free.set_register(
+        &register("RDX"),
+        state_after_free.get_register(&register("callee_saved_reg")),
+    );
+    context.update_call_stub(&state_after_free, &free).unwrap();
+    let warnings = collect_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].name, "CWE415");
+
+    // `q = p + 16; free(q - 16)`
+    let round_trip_state = context
+        .update_def(&offset_state, &reg_add_term("RDX", -16, "sub"))
+        .unwrap();
+    context.update_call_stub(&round_trip_state, &free).unwrap();
+    assert!(collect_warnings().is_empty());
+
+    // The pointer may or may not point to the start of the object.
+    let merged_state = round_trip_state.merge(&offset_state);
+    context.update_call_stub(&merged_state, &free).unwrap();
+    let warnings = collect_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].description.contains("may free"));
+}
+
+#[test]
+fn event_polling_keeps_untouched_fields() {
+    use super::super::event_polling::{EventPollingParameters, WrittenFields};
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, mut config) = mock_project();
+    let (poll_tid, mut poll) = mock_extern_symbol("poll");
+    poll.parameters = vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)];
+    poll.return_values = vec![Arg::mock_register("RAX", 8)];
+    project.program.term.extern_symbols.insert(poll_tid, poll);
+    config.event_polling_symbols = BTreeMap::from([(
+        "poll".to_string(),
+        EventPollingParameters {
+            return_bound_index: Some(1),
+            written_fields: vec![WrittenFields {
+                pointer_index: 0,
+                offset: 6,
+                size: 2,
+                stride: 8,
+                count_index: Some(1),
+            }],
+        },
+    )]);
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    // An array of two `struct pollfd` on the stack and another local variable
+    let stack_address = |offset: i64| Expression::Var(register("RSP")).plus_const(offset);
+    let mut state = State::new(&register("RSP"), Tid::new("func"));
+    let mut write = |offset: i64, value: Data| {
+        state
+            .write_to_address(&stack_address(offset), &value, &runtime_memory_image)
+            .unwrap()
+    };
+    for (fd_offset, fd) in [(-32, 3), (-24, 4)] {
+        write(fd_offset, Bitvector::from_i32(fd).into());
+        write(fd_offset + 4, Bitvector::from_i16(1).into());
+        write(fd_offset + 6, Bitvector::from_i16(0).into());
+    }
+    write(-8, Bitvector::from_i64(42).into());
+    state.set_register(&register("RDI"), state.eval(&stack_address(-32)));
+    state.set_register(&register("RSI"), bv(2).into());
+    // The stack pointer is adjusted by the call, so we keep its old value in a callee-saved register.
+    state.set_register(&register("callee_saved_reg"), state.eval(&stack_address(0)));
+
+    let state_after_poll = context
+        .update_call_stub(&state, &call_term("extern_poll"))
+        .unwrap();
+    let load = |state: &State, offset: i64, size: u64| {
+        state
+            .load_value(
+                &Expression::Var(register("callee_saved_reg")).plus_const(offset),
+                ByteSize::new(size),
+                &runtime_memory_image,
+            )
+            .unwrap()
+    };
+    // The file descriptors, the requested events and other local variables survive the call.
+    assert_eq!(
+        load(&state_after_poll, -32, 4),
+        Bitvector::from_i32(3).into()
+    );
+    assert_eq!(
+        load(&state_after_poll, -24, 4),
+        Bitvector::from_i32(4).into()
+    );
+    assert_eq!(
+        load(&state_after_poll, -28, 2),
+        Bitvector::from_i16(1).into()
+    );
+    assert_eq!(load(&state_after_poll, -8, 8), bv(42).into());
+    // The returned events may have been overwritten.
+    assert!(load(&state_after_poll, -26, 2).contains_top());
+    assert!(load(&state_after_poll, -18, 2).contains_top());
+    // The return value is bounded by the number of file descriptors.
+    assert_eq!(
+        state_after_poll.get_register(&register("RAX")),
+        IntervalDomain::mock(-1, 2).into()
+    );
+
+    // A call with unknown effect invalidates the whole array.
+    state.set_register(&register("RDX"), state.eval(&stack_address(-32)));
+    let state_after_other = context
+        .update_call_stub(&state, &call_term("extern_other"))
+        .unwrap();
+    assert!(load(&state_after_other, -32, 4).contains_top());
+}
+
+#[test]
+fn memory_copy_propagates_values() {
+    use super::super::memory_copy::MemoryCopyParameters;
+    use crate::analysis::forward_interprocedural_fixpoint::Context as IpFpContext;
+    let (mut project, mut config) = mock_project();
+    let (memcpy_tid, mut memcpy) = mock_extern_symbol("memcpy");
+    memcpy.parameters = vec![
+        Arg::mock_register("RDI", 8),
+        Arg::mock_register("RSI", 8),
+        Arg::mock_register("RDX", 8),
+    ];
+    project
+        .program
+        .term
+        .extern_symbols
+        .insert(memcpy_tid, memcpy);
+    config.memory_copy_symbols = BTreeMap::from([(
+        "memcpy".to_string(),
+        MemoryCopyParameters {
+            destination_index: 0,
+            source_index: 1,
+            length_index: 2,
+            null_terminated: false,
+        },
+    )]);
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let (log_sender, _log_receiver) = crossbeam_channel::unbounded();
+    let context = Context::new(&project, &runtime_memory_image, &graph, config, log_sender);
+
+    // Copy 16 bytes from offset -32 to offset -64 of the stack.
+    let stack_address = |offset: i64| Expression::Var(register("RSP")).plus_const(offset);
+    let mut state = State::new(&register("RSP"), Tid::new("func"));
+    let mut write = |offset: i64, value: Data| {
+        state
+            .write_to_address(&stack_address(offset), &value, &runtime_memory_image)
+            .unwrap()
+    };
+    write(-32, bv(1).into());
+    write(-24, bv(2).into());
+    write(-64, bv(3).into());
+    write(-48, bv(4).into());
+    write(-72, bv(5).into());
+    state.set_register(&register("RDI"), state.eval(&stack_address(-64)));
+    state.set_register(&register("RSI"), state.eval(&stack_address(-32)));
+    state.set_register(&register("RDX"), bv(16).into());
+    // The stack pointer is adjusted by the call, so we keep its old value in a callee-saved register.
+    state.set_register(&register("callee_saved_reg"), state.eval(&stack_address(0)));
+    let load = |state: &State, offset: i64| {
+        state
+            .load_value(
+                &Expression::Var(register("callee_saved_reg")).plus_const(offset),
+                ByteSize::new(8),
+                &runtime_memory_image,
+            )
+            .unwrap()
+    };
+
+    let state_after_copy = context
+        .update_call_stub(&state, &call_term("extern_memcpy"))
+        .unwrap();
+    assert_eq!(load(&state_after_copy, -64), bv(1).into());
+    assert_eq!(load(&state_after_copy, -56), bv(2).into());
+    assert_eq!(load(&state_after_copy, -48), bv(4).into());
+    assert_eq!(load(&state_after_copy, -32), bv(1).into());
+
+    // If the length is only known to be at most 16, the values are not copied.
+    state.set_register(&register("RDX"), IntervalDomain::mock(0, 16).into());
+    let state_after_copy = context
+        .update_call_stub(&state, &call_term("extern_memcpy"))
+        .unwrap();
+    assert!(load(&state_after_copy, -64).contains_top());
+    assert!(load(&state_after_copy, -56).contains_top());
+    assert_eq!(load(&state_after_copy, -48), bv(4).into());
+
+    // If the length is unknown, the values after the destination are unknown,
+    // but values before the destination survive the call.
+    state.set_register(&register("RDX"), Data::new_top(ByteSize::new(8)));
+    let state_after_copy = context
+        .update_call_stub(&state, &call_term("extern_memcpy"))
+        .unwrap();
+    assert!(load(&state_after_copy, -64).contains_top());
+    assert!(load(&state_after_copy, -48).contains_top());
+    assert!(load(&state_after_copy, -32).contains_top());
+    assert_eq!(load(&state_after_copy, -72), bv(5).into());
+}
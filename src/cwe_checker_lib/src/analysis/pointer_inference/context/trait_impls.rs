@@ -9,26 +9,57 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
     }
 
     /// Merge two state values.
+    ///
+    /// If a maximal number of pointer targets is configured,
+    /// targets exceeding it are collapsed into summary targets.
     fn merge(&self, value1: &State, value2: &State) -> State {
-        value1.merge(value2)
+        let mut merged_state = value1.merge(value2);
+        if let Some(max_targets) = self.max_pointer_targets {
+            merged_state.summarize_pointer_targets(max_targets);
+        }
+        merged_state
     }
 
     /// Update the state according to the effects of the given `Def` term.
     fn update_def(&self, state: &Self::Value, def: &Term<Def>) -> Option<Self::Value> {
         let mut new_state = state.clone();
-        // first check for use-after-frees
+        // first check for use-after-frees and accesses through expired pointers to environment variables
+        let is_expired_environment_access =
+            new_state.contains_access_of_expired_environment_pointer(&def.term);
         if new_state.contains_access_of_dangling_memory(&def.term) {
+            let (name, description) = if is_expired_environment_access {
+                (
+                    "CWE825",
+                    format!(
+                        "(Expired Pointer Dereference) Access through a pointer to an environment variable invalidated by a modification of the environment at {}",
+                        def.tid.canonical_address()
+                    ),
+                )
+            } else {
+                (
+                    "CWE416",
+                    format!(
+                        "(Use After Free) Access through a dangling pointer at {}",
+                        def.tid.canonical_address()
+                    ),
+                )
+            };
             let warning = CweWarning {
-                name: "CWE416".to_string(),
+                name: name.to_string(),
                 version: VERSION.to_string(),
-                addresses: vec![def.tid.address.clone()],
+                addresses: vec![def.tid.canonical_address().to_string()],
                 tids: vec![format!("{}", def.tid)],
                 symbols: Vec::new(),
                 other: Vec::new(),
-                description: format!(
-                    "(Use After Free) Access through a dangling pointer at {}",
-                    def.tid.address
-                ),
+                description,
+                evidence: self
+                    .collect_warning_evidence
+                    .then(|| state.get_mem_access_evidence(&def.term)),
+                exploit_primitive: match def.term {
+                    Def::Store { .. } => new_state.get_exploit_primitive_of_mem_access(&def.term),
+                    _ => None,
+                },
+                remediation: None,
             };
             let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
         }
@@ -42,38 +73,23 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             Ok(false) => (), // no null dereference detected
         }
         // check for out-of-bounds memory access
-        if new_state.contains_out_of_bounds_mem_access(&def.term, self.runtime_memory_image) {
-            let (warning_name, warning_description) = match def.term {
-                Def::Load { .. } => (
-                    "CWE125",
-                    format!(
-                        "(Out-of-bounds Read) Memory load at {} may be out of bounds",
-                        def.tid.address
-                    ),
-                ),
-                Def::Store { .. } => (
-                    "CWE787",
-                    format!(
-                        "(Out-of-bounds Write) Memory write at {} may be out of bounds",
-                        def.tid.address
-                    ),
-                ),
-                Def::Assign { .. } => panic!(),
-            };
-            let warning = CweWarning {
-                name: warning_name.to_string(),
-                version: VERSION.to_string(),
-                addresses: vec![def.tid.address.clone()],
-                tids: vec![format!("{}", def.tid)],
-                symbols: Vec::new(),
-                other: Vec::new(),
-                description: warning_description,
-            };
-            let _ = self.log_collector.send(LogThreadMsg::Cwe(warning));
+        if let Some(record) = new_state.classify_mem_access(&def.term, self.runtime_memory_image) {
+            self.report_out_of_bounds_mem_access(state, &new_state, def, &record);
+            if let Def::Load { address, .. } | Def::Store { address, .. } = &def.term {
+                self.report_unknown_object_sizes(
+                    &new_state,
+                    &def.tid,
+                    &new_state.eval(address),
+                    &record,
+                );
+            }
         }
+        // check for writes to read-only memory
+        self.check_store_for_read_only_target(&new_state, def);
 
         match &def.term {
             Def::Store { address, value } => {
+                self.memory_access_counter.record(&new_state.eval(address));
                 self.log_debug(
                     new_state.handle_store(address, value, self.runtime_memory_image),
                     Some(&def.tid),
@@ -85,6 +101,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 Some(new_state)
             }
             Def::Load { var, address } => {
+                self.memory_access_counter.record(&new_state.eval(address));
                 if !self.is_mips_gp_load_to_top_value(state, var, address) {
                     self.log_debug(
                         new_state.handle_load(var, address, self.runtime_memory_image),
@@ -101,15 +118,27 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
     }
 
     /// Update the state according to the effects of the given `Jmp` term.
-    /// Right now the state is not changed.
+    ///
+    /// For jumps to blocks where control flow paths merge (e.g. loop heads)
+    /// the comparison constants of the function are added as widening hints to the values of the state,
+    /// so that the widening at the merge stops at the nearest such constant instead of the type bounds.
+    ///
+    /// Jumps to blocks handling a failed stack canary check are treated as infeasible,
+    /// since the canary value loaded from the stack frame always matches the original canary.
     fn update_jump(
         &self,
         value: &State,
         _jump: &Term<Jmp>,
         _untaken_conditional: Option<&Term<Jmp>>,
-        _target: &Term<Blk>,
+        target: &Term<Blk>,
     ) -> Option<State> {
-        let new_value = value.clone();
+        if self.project.is_canary_artifact(&target.tid) {
+            return None;
+        }
+        let mut new_value = value.clone();
+        if let Some(thresholds) = self.widening_thresholds.get(&target.tid) {
+            new_value.add_widening_thresholds(thresholds);
+        }
         Some(new_value)
     }
 
@@ -151,6 +180,12 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 // Note that this may lead to analysis errors if the function uses another calling convention.
                 callee_state.remove_callee_saved_register(cconv);
             }
+            if let Some(discrepancy) = self.trusted_convention_discrepancies.get(callee_tid) {
+                // The callee reads registers that are no parameters according to its calling convention.
+                for register in discrepancy.unlisted_parameter_registers.iter() {
+                    callee_state.set_register(register, state.get_register(register));
+                }
+            }
 
             // Set the lower index bound for the caller stack frame.
             callee_state
@@ -183,7 +218,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 ),
             );
             // For MIPS architecture only: Ensure that the t9 register contains the address of the called function
-            if self.project.cpu_architecture.contains("MIPS") {
+            if self.project.architecture.is_mips() {
                 let _ = callee_state
                     .set_mips_link_register(callee_tid, self.project.stack_pointer_register.size);
             }
@@ -224,7 +259,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             match (state_before_call, state_before_return) {
                 (Some(state_call), Some(state_return)) => (state_call, state_return),
                 (Some(state_call), None) => {
-                    if self.is_indirect_call_with_top_target(state_call, call_term) {
+                    if self.is_indirect_call_with_unknown_target(state_call, call_term) {
                         // We know nothing about the call target.
                         return self.handle_call_to_generic_unknown_function(state_call);
                     } else {
@@ -290,15 +325,44 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             .get_specific_calling_convention(calling_convention)
         {
             // Restore information about callee-saved register from the caller state.
-            // TODO: Implement some kind of check to ensure that the callee adheres to the given calling convention!
-            // The current workaround should be reasonably exact for programs written in C,
+            // This should be reasonably exact for programs written in C,
             // but may introduce a lot of errors
             // if the compiler often uses other calling conventions for internal function calls.
+            // Callee-saved registers that the callee is known to clobber are not restored
+            // if the observed behavior of the callee is trusted.
+            let clobbered_register_values: Vec<(&Variable, Data)> = self
+                .trusted_convention_discrepancies
+                .get(callee_stack_id.get_tid())
+                .map(|discrepancy| {
+                    discrepancy
+                        .clobbered_callee_saved_registers
+                        .iter()
+                        .map(|register| (register, state_after_return.get_register(register)))
+                        .collect()
+                })
+                .unwrap_or_default();
             state_after_return.restore_callee_saved_register(
                 state_before_call,
                 cconv,
                 &self.project.stack_pointer_register,
             );
+            for (register, value) in clobbered_register_values {
+                state_after_return.set_register(register, value);
+            }
+            // Registers and memory objects that the callee provably does not modify keep their values.
+            if let Some(classification) = self.function_purities.get(callee_stack_id.get_tid()) {
+                state_after_return.restore_values_untouched_by_callee(
+                    state_before_call,
+                    classification,
+                    &cconv.get_all_parameter_register(),
+                    &self.project.stack_pointer_register,
+                );
+            }
+        }
+
+        // Objects freed or stored by the callee are updated according to its ownership classification.
+        if let Some(classification) = self.parameter_ownership.get(callee_stack_id.get_tid()) {
+            state_after_return.apply_parameter_ownership(state_before_call, classification);
         }
 
         // remove non-referenced objects from the state
@@ -319,7 +383,8 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         let call_target = match &call.term {
             Jmp::Call { target, .. } => target,
             Jmp::CallInd { .. } => {
-                if self.is_indirect_call_with_top_target(state, call) {
+                self.check_indirect_call_for_null_target(state, call);
+                if self.is_indirect_call_with_unknown_target(state, call) {
                     // We know nothing about the call target.
                     return self.handle_call_to_generic_unknown_function(state);
                 } else {
@@ -329,15 +394,13 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             _ => panic!("Malformed control flow graph encountered."),
         };
         let mut new_state = state.clone();
-        if let Some(extern_symbol) = self.extern_symbol_map.get(call_target) {
+        if let Some(extern_symbol) = self.get_extern_symbol(call_target) {
             // Generate a CWE-message if some argument is an out-of-bounds pointer.
             self.check_parameter_register_for_out_of_bounds_pointer(state, call, extern_symbol);
+            // Generate a CWE-message if the function writes to read-only memory.
+            self.check_parameter_register_for_read_only_target(state, call, extern_symbol);
             // Check parameter for possible use-after-frees (except for possible double frees, which are handled later)
-            if !self
-                .deallocation_symbols
-                .iter()
-                .any(|free_like_fn| free_like_fn == extern_symbol.name.as_str())
-            {
+            if self.get_deallocation_family(&extern_symbol.name).is_none() {
                 self.check_parameter_register_for_dangling_pointer(
                     &mut new_state,
                     call,
@@ -350,6 +413,30 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             // Adjust stack register value (for x86 architecture).
             self.adjust_stack_register_on_extern_call(state, &mut new_state);
 
+            if let Some(allocator) =
+                self.get_custom_allocator_by_allocation_function(&extern_symbol.name)
+            {
+                let new_state = self.add_new_object_for_custom_allocator(
+                    state,
+                    new_state,
+                    call,
+                    extern_symbol,
+                    allocator,
+                );
+                // Memory mapping functions like `mmap` also set the protection of the new object.
+                return Some(
+                    match self.memory_protection_symbols.get(&extern_symbol.name) {
+                        Some(parameters) => self.update_memory_protection(
+                            state,
+                            new_state,
+                            call,
+                            extern_symbol,
+                            parameters,
+                        ),
+                        None => new_state,
+                    },
+                );
+            }
             match extern_symbol.name.as_str() {
                 malloc_like_fn if self.allocation_symbols.iter().any(|x| x == malloc_like_fn) => {
                     Some(self.add_new_object_in_call_return_register(
@@ -359,24 +446,119 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                         extern_symbol,
                     ))
                 }
-                free_like_fn if self.deallocation_symbols.iter().any(|x| x == free_like_fn) => {
+                free_like_fn if self.get_deallocation_family(free_like_fn).is_some() => {
                     Some(self.mark_parameter_object_as_freed(state, new_state, call, extern_symbol))
                 }
+                lookup_fn if self.function_lookup_symbols.iter().any(|x| x == lookup_fn) => {
+                    Some(self.add_possibly_null_function_pointer_in_return_register(
+                        state,
+                        new_state,
+                        call,
+                        extern_symbol,
+                    ))
+                }
+                context_switch_fn
+                    if self
+                        .context_switch_symbols
+                        .iter()
+                        .any(|x| x == context_switch_fn) =>
+                {
+                    Some(self.handle_context_switch_call(state, new_state, call, extern_symbol))
+                }
+                thread_creation_fn
+                    if self
+                        .thread_creation_symbols
+                        .contains_key(thread_creation_fn) =>
+                {
+                    Some(self.handle_thread_creation_call(
+                        state,
+                        new_state,
+                        call,
+                        extern_symbol,
+                        &self.thread_creation_symbols[thread_creation_fn],
+                    ))
+                }
+                lookup_fn
+                    if self
+                        .environment_lookup_symbols
+                        .iter()
+                        .any(|x| x == lookup_fn) =>
+                {
+                    Some(self.add_environment_object_in_return_register(
+                        state,
+                        new_state,
+                        call,
+                        extern_symbol,
+                    ))
+                }
+                modification_fn
+                    if self
+                        .environment_modification_symbols
+                        .iter()
+                        .any(|x| x == modification_fn) =>
+                {
+                    Some(self.invalidate_environment_objects(state, new_state, call, extern_symbol))
+                }
+                protection_fn if self.memory_protection_symbols.contains_key(protection_fn) => {
+                    Some(self.update_memory_protection(
+                        state,
+                        new_state,
+                        call,
+                        extern_symbol,
+                        &self.memory_protection_symbols[protection_fn],
+                    ))
+                }
+                polling_fn if self.event_polling_symbols.contains_key(polling_fn) => {
+                    Some(self.handle_event_polling_call(
+                        state,
+                        new_state,
+                        call,
+                        extern_symbol,
+                        &self.event_polling_symbols[polling_fn],
+                    ))
+                }
+                copy_fn if self.memory_copy_symbols.contains_key(copy_fn) => {
+                    Some(self.handle_memory_copy_call(
+                        state,
+                        new_state,
+                        call,
+                        extern_symbol,
+                        &self.memory_copy_symbols[copy_fn],
+                    ))
+                }
+                start_fn if self.program_start_symbols.iter().any(|x| x == start_fn) => {
+                    // The program exits after `main` returns.
+                    // The `main` function itself is analyzed as a separate entry point.
+                    None
+                }
+                length_fn if self.string_length_symbols.iter().any(|x| x == length_fn) => {
+                    Some(self.handle_string_length_call(state, new_state, call, extern_symbol))
+                }
+                pure_fn if self.assume_pure.iter().any(|x| x == pure_fn) => {
+                    // Only the non-callee-saved registers change, which we already accounted for.
+                    Some(new_state)
+                }
                 _ => Some(self.handle_generic_extern_call(state, new_state, call, extern_symbol)),
             }
         } else {
-            panic!("Extern symbol not found.");
+            // The call target is a function excluded from the analysis.
+            self.handle_call_to_skipped_function(state, call_target)
         }
     }
 
     /// Update the state with the knowledge that some conditional evaluated to true or false.
+    /// Conditions containing `Unknown` placeholder expressions do not specialize the state.
     fn specialize_conditional(
         &self,
         state: &State,
         condition: &Expression,
-        _block_before_condition: &Term<Blk>,
+        block_before_condition: &Term<Blk>,
         is_true: bool,
     ) -> Option<State> {
+        if condition.contains_unknown() {
+            // The condition could not be translated, so both branches are taken without specialization.
+            return Some(state.clone());
+        }
         let mut specialized_state = state.clone();
         if specialized_state
             .specialize_by_expression_result(condition, Bitvector::from_u8(is_true as u8).into())
@@ -385,6 +567,11 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
             // State is unsatisfiable
             return None;
         }
+        self.specialize_stack_values_loaded_in_block(
+            state,
+            &mut specialized_state,
+            block_before_condition,
+        );
         Some(specialized_state)
     }
 }
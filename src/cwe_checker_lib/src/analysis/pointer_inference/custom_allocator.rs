@@ -0,0 +1,225 @@
+//! Descriptions of user-provided allocator functions.
+//!
+//! Many programs wrap the standard allocation functions in custom allocators,
+//! e.g. `my_pool_alloc(pool, size)` and `my_pool_free(pool, ptr)`.
+//! A [`CustomAllocator`] describes such an allocation-deallocation pair,
+//! so that the pointer inference analysis can model calls to them
+//! like calls to `malloc`-like and `free`-like functions.
+//!
+//! Custom allocators may either be extern symbols or functions contained in the binary.
+//! Functions contained in the binary have to be excluded from the control flow graph
+//! (see [`get_custom_allocator_sub_tids`]),
+//! so that calls to them are handled like calls to extern symbols.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// The description of a custom allocation function and its corresponding deallocation function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct CustomAllocator {
+    /// The name of the allocation function (either an extern symbol or a function contained in the binary).
+    pub allocation_function: String,
+    /// The index of the parameter of the allocation function containing the size of the allocated memory.
+    pub size_parameter_index: usize,
+    /// How the pointer to the allocated memory is returned to the caller.
+    #[serde(default)]
+    pub pointer_location: AllocatedPointerLocation,
+    /// The name of the function deallocating memory allocated by the allocation function.
+    pub deallocation_function: String,
+    /// The index of the parameter of the deallocation function containing the pointer to the memory to deallocate.
+    pub freed_pointer_parameter_index: usize,
+    /// Whether the deallocation function accepts pointers into the middle of the allocated memory,
+    /// e.g. for region allocators freeing the whole region containing the pointer.
+    /// If not set, freeing such pointers is reported as CWE-761.
+    #[serde(default)]
+    pub allows_offset_free: bool,
+}
+
+/// The location where an allocation function stores the pointer to the allocated memory.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub enum AllocatedPointerLocation {
+    /// The pointer is the unique return value of the allocation function.
+    #[default]
+    ReturnValue,
+    /// The pointer is written to the memory location that the parameter with the given index points to.
+    /// Only parameters passed in registers are supported.
+    OutParameter(usize),
+}
+
+/// Allocation and deallocation functions are grouped into families,
+/// where memory allocated by a function of one family must be deallocated by a function of the same family.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub enum AllocatorFamily {
+    /// The `malloc`-like and `free`-like functions configured
+    /// in the `allocation_symbols` and `deallocation_symbols` of the pointer inference configuration.
+    Standard,
+    /// The custom allocators with the given deallocation function.
+    Custom(String),
+}
+
+impl std::fmt::Display for AllocatorFamily {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AllocatorFamily::Standard => write!(formatter, "standard allocators"),
+            AllocatorFamily::Custom(deallocator) => {
+                write!(formatter, "custom allocators freed by {}", deallocator)
+            }
+        }
+    }
+}
+
+/// Return the TIDs of all functions contained in the binary
+/// that are allocation or deallocation functions of one of the given custom allocators.
+///
+/// These functions should be excluded from the control flow graph,
+/// so that calls to them are modeled by the pointer inference analysis
+/// instead of analyzing the allocator implementation itself.
+pub fn get_custom_allocator_sub_tids(
+    program: &Term<Program>,
+    custom_allocators: &[CustomAllocator],
+) -> HashSet<Tid> {
+    program
+        .term
+        .subs
+        .values()
+        .filter(|sub| is_custom_allocator_function(&sub.term.name, custom_allocators))
+        .map(|sub| sub.tid.clone())
+        .collect()
+}
+
+/// Generate extern symbol stubs for all functions contained in the binary
+/// that are allocation or deallocation functions of one of the given custom allocators.
+///
+/// The parameters and return values of the stubs are derived from the calling convention of the function.
+/// Functions for which no calling convention is known are ignored.
+pub fn generate_custom_allocator_stubs(
+    project: &Project,
+    custom_allocators: &[CustomAllocator],
+) -> BTreeMap<Tid, ExternSymbol> {
+    let mut stubs = BTreeMap::new();
+    for sub in project.program.term.subs.values() {
+        if !is_custom_allocator_function(&sub.term.name, custom_allocators)
+            || project.program.term.extern_symbols.contains_key(&sub.tid)
+        {
+            continue;
+        }
+        let calling_convention = sub
+            .term
+            .calling_convention
+            .clone()
+            .filter(|cconv_name| project.calling_conventions.contains_key(cconv_name));
+        let cconv = match project.get_specific_calling_convention(&calling_convention) {
+            Some(cconv) => cconv,
+            None => continue,
+        };
+        let to_arg = |var: &Variable| Arg::Register {
+            expr: Expression::Var(var.clone()),
+            data_type: None,
+        };
+        let stub = ExternSymbol {
+            tid: sub.tid.clone(),
+            addresses: vec![sub.tid.address.clone()],
+            name: sub.term.name.clone(),
+            calling_convention,
+            parameters: cconv
+                .integer_parameter_register
+                .iter()
+                .map(to_arg)
+                .collect(),
+            return_values: cconv
+                .integer_return_register
+                .first()
+                .map(to_arg)
+                .into_iter()
+                .collect(),
+            no_return: false,
+            has_var_args: false,
+        };
+        stubs.insert(sub.tid.clone(), stub);
+    }
+    stubs
+}
+
+/// Returns `true` if the given name is the name of an allocation or deallocation function
+/// of one of the given custom allocators.
+fn is_custom_allocator_function(name: &str, custom_allocators: &[CustomAllocator]) -> bool {
+    custom_allocators.iter().any(|allocator| {
+        allocator.allocation_function == name || allocator.deallocation_function == name
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_pool_allocator() -> CustomAllocator {
+        CustomAllocator {
+            allocation_function: "my_pool_alloc".to_string(),
+            size_parameter_index: 1,
+            pointer_location: AllocatedPointerLocation::ReturnValue,
+            deallocation_function: "my_pool_free".to_string(),
+            freed_pointer_parameter_index: 1,
+            allows_offset_free: false,
+        }
+    }
+
+    #[test]
+    fn stubs_for_internal_allocators() {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8), Variable::mock("RSI", 8)],
+            Vec::new(),
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["my_pool_alloc", "my_pool_free", "other_func"] {
+            let sub = Sub::mock(name);
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        let allocators = vec![mock_pool_allocator()];
+
+        let sub_tids = get_custom_allocator_sub_tids(&project.program, &allocators);
+        assert_eq!(
+            sub_tids,
+            HashSet::from([Tid::new("my_pool_alloc"), Tid::new("my_pool_free")])
+        );
+        let stubs = generate_custom_allocator_stubs(&project, &allocators);
+        assert_eq!(stubs.len(), 2);
+        let alloc_stub = &stubs[&Tid::new("my_pool_alloc")];
+        assert_eq!(alloc_stub.name, "my_pool_alloc");
+        assert_eq!(
+            alloc_stub.parameters[1],
+            Arg::Register {
+                expr: Expression::Var(Variable::mock("RSI", 8)),
+                data_type: None
+            }
+        );
+        assert_eq!(
+            alloc_stub.get_unique_return_register().unwrap(),
+            &Variable::mock("RAX", 8)
+        );
+    }
+
+    #[test]
+    fn deserialize_custom_allocator() {
+        let allocator: CustomAllocator = serde_json::from_str(
+            r#"{
+                "allocation_function": "pool_get",
+                "size_parameter_index": 0,
+                "pointer_location": { "OutParameter": 1 },
+                "deallocation_function": "pool_put",
+                "freed_pointer_parameter_index": 0
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            allocator.pointer_location,
+            AllocatedPointerLocation::OutParameter(1)
+        );
+        let allocator: CustomAllocator =
+            serde_json::from_value(serde_json::to_value(mock_pool_allocator()).unwrap()).unwrap();
+        assert_eq!(allocator, mock_pool_allocator());
+    }
+}
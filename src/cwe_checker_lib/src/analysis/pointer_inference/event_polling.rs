@@ -0,0 +1,52 @@
+//! Modeling of event polling functions like `select`, `poll` and `epoll_wait`.
+//!
+//! Event loops call these functions in every iteration.
+//! Since the watched file descriptors are passed through pointers to arrays or sets,
+//! the generic handling of extern calls would assume that the whole memory objects
+//! containing these arrays are overwritten, e.g. the whole stack frame of the caller.
+//! This would cause the analysis to lose track of the file descriptors and other values in loops.
+//!
+//! Instead, only the fields actually written by the functions are marked as unknown,
+//! e.g. the `revents` field of each `struct pollfd` passed to `poll`,
+//! while all other values in the same memory objects keep their values.
+//! The written fields of each function are described by [`EventPollingParameters`].
+//!
+//! Furthermore, the return value of these functions is bounded by the number of watched file descriptors,
+//! e.g. `poll` returns a value between -1 (for errors) and the value of its `nfds` parameter.
+//! Note that `select` returns the number of set bits in all three file descriptor sets,
+//! which is not bounded by its `nfds` parameter.
+
+use crate::prelude::*;
+
+/// The effects of a call to an event polling function like `poll` on the memory reachable through its parameters.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct EventPollingParameters {
+    /// The index of the parameter containing the maximal number of returned events, e.g. `nfds` for `poll`.
+    /// If set, the return value of the function is known to be between -1 and the value of the parameter.
+    #[serde(default)]
+    pub return_bound_index: Option<usize>,
+    /// The fields in memory written by the function.
+    pub written_fields: Vec<WrittenFields>,
+}
+
+/// One field of each element of an array in memory pointed to by a parameter,
+/// which may be overwritten by a call to an event polling function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct WrittenFields {
+    /// The index of the parameter pointing to the array.
+    pub pointer_index: usize,
+    /// The offset of the written field in the first array element relative to the parameter pointer.
+    #[serde(default)]
+    pub offset: i64,
+    /// The byte size of the written field.
+    pub size: u64,
+    /// The size of an array element in bytes.
+    /// A stride of zero denotes that the parameter points to a single value instead of an array.
+    #[serde(default)]
+    pub stride: u64,
+    /// The index of the parameter containing the number of array elements.
+    /// If not set or if the parameter value is unknown,
+    /// all array elements after the parameter pointer are assumed to be written.
+    #[serde(default)]
+    pub count_index: Option<usize>,
+}
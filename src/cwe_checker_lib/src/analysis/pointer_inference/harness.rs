@@ -0,0 +1,389 @@
+//! Harness specifications for analyzing a single function under assumptions about its parameters.
+//!
+//! A harness names a target function and describes what an attacker controls when calling it,
+//! similar to the harness of a fuzzer for a library function.
+//! For example, a parameter may be a buffer of attacker controlled bytes
+//! whose length is given by another parameter.
+//!
+//! The program is restricted to the target function and all functions reachable from it through direct calls
+//! (see [`HarnessSpec::restrict_project`]),
+//! and the pointer inference analysis starts the target function
+//! with an entry state built from the parameter facts (see [`HarnessSpec::generate_entry_state`]).
+//! CWE warnings found under these assumptions are annotated accordingly
+//! (see [`HarnessSpec::annotate_warnings`]).
+
+use super::{Data, State, ValueDomain};
+use crate::abstract_domain::*;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use crate::utils::query::find_function;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The specification of a harness for a single function.
+///
+/// The JSON representation looks like this:
+/// ```json
+/// {
+///     "function": "parse_packet",
+///     "parameters": {
+///         "1": { "kind": "buffer", "length_index": 2 },
+///         "2": { "kind": "interval", "min": 1, "max": 1500 }
+///     }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct HarnessSpec {
+    /// The name, TID or address of the target function.
+    pub function: String,
+    /// The facts about the parameters of the target function,
+    /// keyed by the (zero-based) index of the parameter in the integer parameter registers
+    /// of the calling convention of the function.
+    /// Parameters without facts are handled as in a generic entry state,
+    /// i.e. they point to parameter objects of unknown size.
+    #[serde(default)]
+    pub parameters: BTreeMap<usize, ParameterFact>,
+}
+
+/// A fact about a parameter of the target function of a harness.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ParameterFact {
+    /// The parameter is an arbitrary value controlled by the attacker,
+    /// i.e. nothing is known about it.
+    Tainted,
+    /// The parameter points to the start of a buffer of attacker controlled bytes.
+    /// The size of the buffer is the value of the parameter with the given index.
+    Buffer {
+        /// The index of the parameter containing the size of the buffer.
+        length_index: usize,
+    },
+    /// The parameter has the given constant value.
+    Constant {
+        /// The value of the parameter.
+        value: i64,
+    },
+    /// The parameter is a (signed) value contained in the given interval.
+    Interval {
+        /// The smallest possible value of the parameter.
+        min: i64,
+        /// The largest possible value of the parameter.
+        max: i64,
+    },
+}
+
+impl HarnessSpec {
+    /// Restrict the project to the target function and all functions reachable from it through direct calls.
+    ///
+    /// The target function becomes the only entry point of the program.
+    /// Returns an error if the target function cannot be found
+    /// or if the parameter facts do not fit to its calling convention.
+    pub fn restrict_project(&self, project: &mut Project) -> Result<(), Error> {
+        let target_tid = find_function(project, &self.function)?.tid.clone();
+        self.get_parameter_registers(project, &project.program.term.subs[&target_tid])?;
+        let mut reachable_subs = BTreeSet::from([target_tid.clone()]);
+        let mut worklist = vec![target_tid.clone()];
+        while let Some(sub_tid) = worklist.pop() {
+            for jmp in project.program.term.subs[&sub_tid]
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+            {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if project.program.term.subs.contains_key(target)
+                        && reachable_subs.insert(target.clone())
+                    {
+                        worklist.push(target.clone());
+                    }
+                }
+            }
+        }
+        project
+            .program
+            .term
+            .subs
+            .retain(|sub_tid, _| reachable_subs.contains(sub_tid));
+        project.program.term.entry_points = BTreeSet::from([target_tid]);
+        Ok(())
+    }
+
+    /// Returns `true` if the given function is the target function of the harness.
+    pub fn is_target(&self, sub: &Term<Sub>) -> bool {
+        sub.term.name == self.function
+            || format!("{}", sub.tid) == self.function
+            || crate::utils::query::parse_address(&self.function)
+                .is_ok_and(|address| u64::from_str_radix(&sub.tid.address, 16) == Ok(address))
+    }
+
+    /// Generate the entry state of the target function from the parameter facts.
+    ///
+    /// Starting from the generic entry state of the function
+    /// the parameter registers are set according to their facts.
+    /// Buffer parameters point to their generic parameter object,
+    /// whose index bounds are set to the range from zero to the largest possible value of the length parameter.
+    /// If the length parameter has no known upper bound, the upper index bound of the buffer is unknown.
+    pub fn generate_entry_state(
+        &self,
+        project: &Project,
+        sub: &Term<Sub>,
+        mut generic_entry_state: State,
+    ) -> Result<State, Error> {
+        let registers = self.get_parameter_registers(project, sub)?;
+        for (index, fact) in self.parameters.iter() {
+            let register = registers[index];
+            let value: Data = match fact {
+                ParameterFact::Tainted => Data::new_top(register.size),
+                ParameterFact::Constant { value } => Bitvector::from_i64(*value)
+                    .into_resize_signed(register.size)
+                    .into(),
+                ParameterFact::Interval { min, max } => {
+                    if min > max {
+                        return Err(anyhow!(
+                            "Invalid interval [{}, {}] for parameter {}",
+                            min,
+                            max,
+                            index
+                        ));
+                    }
+                    IntervalDomain::new(
+                        Bitvector::from_i64(*min).into_resize_signed(register.size),
+                        Bitvector::from_i64(*max).into_resize_signed(register.size),
+                    )
+                    .into()
+                }
+                ParameterFact::Buffer { .. } => continue,
+            };
+            generic_entry_state.set_register(register, value);
+        }
+        let address_bytesize = project.get_pointer_bytesize();
+        for (index, fact) in self.parameters.iter() {
+            if let ParameterFact::Buffer { length_index } = fact {
+                let register = registers[index];
+                let buffer_id =
+                    AbstractIdentifier::new(sub.tid.clone(), AbstractLocation::from_var(register)?);
+                let length = generic_entry_state.get_register(registers[length_index]);
+                let upper_index_bound: ValueDomain = match length
+                    .get_if_absolute_value()
+                    .and_then(|length| length.try_to_offset_interval().ok())
+                {
+                    Some((_, max_length)) => Bitvector::from_i64(max_length - 1)
+                        .into_resize_signed(address_bytesize)
+                        .into(),
+                    None => ValueDomain::new_top(address_bytesize),
+                };
+                // Replace the generic parameter object so that the buffer object stays unique.
+                generic_entry_state.memory.remove_object(&buffer_id);
+                generic_entry_state.memory.add_abstract_object(
+                    buffer_id.clone(),
+                    Bitvector::zero(address_bytesize.into()).into(),
+                    super::object::ObjectType::Heap,
+                    address_bytesize,
+                );
+                generic_entry_state.memory.set_lower_index_bound(
+                    &buffer_id,
+                    &Bitvector::zero(address_bytesize.into()).into(),
+                );
+                generic_entry_state
+                    .memory
+                    .set_upper_index_bound(&buffer_id, &upper_index_bound);
+                generic_entry_state.set_register(
+                    register,
+                    Data::from_target(buffer_id, Bitvector::zero(register.size.into()).into()),
+                );
+            }
+        }
+        Ok(generic_entry_state)
+    }
+
+    /// Append a note that the warning was found under the harness assumptions
+    /// to the descriptions of all given CWE warnings.
+    pub fn annotate_warnings(&self, warnings: &mut [CweWarning]) {
+        for warning in warnings.iter_mut() {
+            warning.description = format!(
+                "{} (under harness assumptions for {})",
+                warning.description, self.function
+            );
+            warning
+                .other
+                .push(vec!["harness".to_string(), self.function.clone()]);
+        }
+    }
+
+    /// Get the registers of all parameters referenced by the parameter facts.
+    ///
+    /// Returns an error if a referenced parameter is not passed in an integer parameter register
+    /// of the calling convention of the function.
+    fn get_parameter_registers<'a>(
+        &self,
+        project: &'a Project,
+        sub: &Term<Sub>,
+    ) -> Result<BTreeMap<usize, &'a Variable>, Error> {
+        let cconv = project
+            .get_specific_calling_convention(&sub.term.calling_convention)
+            .ok_or_else(|| anyhow!("No calling convention found for {}", sub.term.name))?;
+        let mut indices: BTreeSet<usize> = self.parameters.keys().copied().collect();
+        indices.extend(self.parameters.values().filter_map(|fact| match fact {
+            ParameterFact::Buffer { length_index } => Some(*length_index),
+            _ => None,
+        }));
+        indices
+            .into_iter()
+            .map(|index| match cconv.integer_parameter_register.get(index) {
+                Some(register) => Ok((index, register)),
+                None => Err(anyhow!(
+                    "Parameter {} of {} is not passed in a register",
+                    index,
+                    sub.term.name
+                )),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    /// Create a project with a function `parse(flags, buffer, length, capacity)`
+    /// that writes to the last byte `buffer[length - 1]` of the buffer,
+    /// and a function `unrelated` not reachable from `parse`.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![
+                Variable::mock("RDI", 8),
+                Variable::mock("RSI", 8),
+                Variable::mock("RDX", 8),
+                Variable::mock("RCX", 8),
+            ],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        let mut block = Blk::mock_with_tid("parse_blk");
+        block.term.defs = vec![
+            Def::assign(
+                "last_byte",
+                Variable::mock("RAX", 8),
+                Expression::var("RSI", 8)
+                    .plus(Expression::var("RDX", 8))
+                    .plus_const(-1),
+            ),
+            Def::store(
+                "write_last_byte",
+                Expression::var("RAX", 8),
+                Expression::const_from_i64(0).subpiece(ByteSize::new(0), ByteSize::new(1)),
+            ),
+        ];
+        block.term.jmps = vec![Term {
+            tid: Tid::new("parse_return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        }];
+        let parse = Sub::mock_with_blocks("parse", vec![block]);
+        let unrelated = Sub::mock("unrelated");
+        for sub in [parse, unrelated] {
+            project.program.term.entry_points.insert(sub.tid.clone());
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    /// Restrict the mock project to the harness and return the CWE warnings
+    /// of the pointer inference analysis under the harness assumptions.
+    fn get_warnings_under_harness(harness: &HarnessSpec) -> Vec<CweWarning> {
+        let mut project = mock_project();
+        harness.restrict_project(&mut project).unwrap();
+        assert_eq!(project.program.term.subs.len(), 1);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(
+            &project.program,
+            std::collections::HashSet::new(),
+        );
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../../config.json")).unwrap();
+        let config = crate::utils::pipeline::get_pointer_inference_config(&config, Some(harness));
+        let pi_results = super::super::run(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            serde_json::from_value(config).unwrap(),
+            false,
+            false,
+        );
+        let mut warnings = pi_results.collected_logs.1.clone();
+        harness.annotate_warnings(&mut warnings);
+        warnings
+    }
+
+    #[test]
+    fn buffer_bounded_by_length_parameter() {
+        let coupled_harness: HarnessSpec = serde_json::from_value(serde_json::json!({
+            "function": "parse",
+            "parameters": {
+                "1": { "kind": "buffer", "length_index": 2 },
+                "2": { "kind": "interval", "min": 1, "max": 64 }
+            }
+        }))
+        .unwrap();
+        assert!(get_warnings_under_harness(&coupled_harness).is_empty());
+
+        // A buffer whose size is independent of the length used for the access may be overflown.
+        let uncoupled_harness: HarnessSpec = serde_json::from_value(serde_json::json!({
+            "function": "parse",
+            "parameters": {
+                "1": { "kind": "buffer", "length_index": 3 },
+                "2": { "kind": "interval", "min": 1, "max": 64 },
+                "3": { "kind": "constant", "value": 16 }
+            }
+        }))
+        .unwrap();
+        let warnings = get_warnings_under_harness(&uncoupled_harness);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "CWE787");
+        assert_eq!(warnings[0].tids, vec!["write_last_byte".to_string()]);
+        assert!(warnings[0]
+            .description
+            .ends_with("(under harness assumptions for parse)"));
+
+        let mut project = mock_project();
+        let unknown_harness = HarnessSpec {
+            function: "unknown".to_string(),
+            parameters: BTreeMap::new(),
+        };
+        assert!(unknown_harness.restrict_project(&mut project).is_err());
+        let invalid_harness = HarnessSpec {
+            function: "parse".to_string(),
+            parameters: BTreeMap::from([(4, ParameterFact::Tainted)]),
+        };
+        assert!(invalid_harness.restrict_project(&mut project).is_err());
+    }
+
+    #[test]
+    fn parse_harness_spec() {
+        let spec: HarnessSpec = serde_json::from_str(
+            r#"{
+                "function": "parse",
+                "parameters": {
+                    "0": { "kind": "tainted" },
+                    "1": { "kind": "buffer", "length_index": 2 },
+                    "2": { "kind": "interval", "min": 1, "max": 64 },
+                    "3": { "kind": "constant", "value": -1 }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(spec.parameters[&0], ParameterFact::Tainted);
+        assert_eq!(
+            spec.parameters[&1],
+            ParameterFact::Buffer { length_index: 2 }
+        );
+        assert_eq!(
+            spec.parameters[&2],
+            ParameterFact::Interval { min: 1, max: 64 }
+        );
+        assert_eq!(spec.parameters[&3], ParameterFact::Constant { value: -1 });
+    }
+}
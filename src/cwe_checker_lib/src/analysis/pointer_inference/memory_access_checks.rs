@@ -0,0 +1,170 @@
+//! The shared bounds-checking engine of the pointer inference analysis.
+//!
+//! The transfer functions classify every memory access exactly once
+//! (see `State::classify_mem_access` and `State::classify_pointer_parameter`).
+//! The checks for CWE-119, CWE-125 and CWE-787 are filters on the resulting [`MemoryAccessRecord`]s,
+//! so that the (comparatively expensive) evaluation of the accessed address
+//! and the lookup of the targeted memory objects is not repeated for each check.
+
+/// The kind of a memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsCheckedAccess {
+    /// A memory read through a `Load` instruction.
+    Read,
+    /// A memory write through a `Store` instruction.
+    Write,
+    /// A pointer passed as parameter to an extern function,
+    /// i.e. an access of unknown kind performed by the callee.
+    CallParameter,
+}
+
+/// The result of checking a memory access against the bounds of the targeted memory objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsClassification {
+    /// The access is contained in the bounds of all targeted memory objects.
+    InBounds,
+    /// The access may be out of bounds for at least one of the targeted memory objects.
+    PossiblyOutOfBounds,
+    /// The access is out of bounds for all targeted memory objects and all possible offsets.
+    DefinitelyOutOfBounds,
+    /// No target of the access could be checked,
+    /// e.g. because the address is *Top* or all offsets are unknown.
+    UnknownObject,
+}
+
+impl BoundsClassification {
+    /// Returns `true` if the classification is either possibly or definitely out of bounds.
+    pub fn is_out_of_bounds(&self) -> bool {
+        matches!(
+            self,
+            BoundsClassification::PossiblyOutOfBounds | BoundsClassification::DefinitelyOutOfBounds
+        )
+    }
+
+    /// Combine the classifications of two targets of the same memory access.
+    pub fn combine(self, other: BoundsClassification) -> BoundsClassification {
+        use BoundsClassification::*;
+        match (self, other) {
+            (UnknownObject, class) | (class, UnknownObject) => class,
+            (DefinitelyOutOfBounds, DefinitelyOutOfBounds) => DefinitelyOutOfBounds,
+            (InBounds, InBounds) => InBounds,
+            _ => PossiblyOutOfBounds,
+        }
+    }
+}
+
+/// The classification of a single memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryAccessRecord {
+    /// The kind of the access.
+    pub kind: BoundsCheckedAccess,
+    /// The result of the bounds check for the access.
+    pub classification: BoundsClassification,
+}
+
+impl MemoryAccessRecord {
+    /// Returns the name of the CWE that the access may represent
+    /// or `None` if the access cannot be out of bounds.
+    pub fn cwe_name(&self) -> Option<&'static str> {
+        if !self.classification.is_out_of_bounds() {
+            return None;
+        }
+        Some(self.checked_cwe_name())
+    }
+
+    /// Returns the name of the CWE that the access is checked for,
+    /// regardless of whether the access may be out of bounds.
+    pub fn checked_cwe_name(&self) -> &'static str {
+        match self.kind {
+            BoundsCheckedAccess::Read => "CWE125",
+            BoundsCheckedAccess::Write => "CWE787",
+            BoundsCheckedAccess::CallParameter => "CWE119",
+        }
+    }
+
+    /// Generate the description of a CWE warning for the access at the given address.
+    /// For parameters of calls the name of the called function has to be provided.
+    pub fn cwe_description(&self, address: &str, callee: Option<&str>) -> String {
+        let verb = match self.classification {
+            BoundsClassification::DefinitelyOutOfBounds => "is",
+            _ => "may be",
+        };
+        match self.kind {
+            BoundsCheckedAccess::Read => format!(
+                "(Out-of-bounds Read) Memory load at {} {} out of bounds",
+                address, verb
+            ),
+            BoundsCheckedAccess::Write => format!(
+                "(Out-of-bounds Write) Memory write at {} {} out of bounds",
+                address, verb
+            ),
+            BoundsCheckedAccess::CallParameter => format!(
+                "(Buffer Overflow) Call to {} at {} {} access out-of-bounds memory",
+                callee.unwrap_or("unknown function"),
+                address,
+                match self.classification {
+                    BoundsClassification::DefinitelyOutOfBounds => "will",
+                    _ => "may",
+                }
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combine_classifications() {
+        use BoundsClassification::*;
+        assert_eq!(UnknownObject.combine(InBounds), InBounds);
+        assert_eq!(
+            DefinitelyOutOfBounds.combine(DefinitelyOutOfBounds),
+            DefinitelyOutOfBounds
+        );
+        assert_eq!(DefinitelyOutOfBounds.combine(InBounds), PossiblyOutOfBounds);
+        assert_eq!(UnknownObject.combine(UnknownObject), UnknownObject);
+        assert!(!UnknownObject.is_out_of_bounds());
+    }
+
+    #[test]
+    fn cwe_names_of_records() {
+        let record = |kind, classification| MemoryAccessRecord {
+            kind,
+            classification,
+        };
+        assert_eq!(
+            record(
+                BoundsCheckedAccess::Read,
+                BoundsClassification::PossiblyOutOfBounds
+            )
+            .cwe_name(),
+            Some("CWE125")
+        );
+        assert_eq!(
+            record(
+                BoundsCheckedAccess::Write,
+                BoundsClassification::DefinitelyOutOfBounds
+            )
+            .cwe_name(),
+            Some("CWE787")
+        );
+        assert_eq!(
+            record(
+                BoundsCheckedAccess::CallParameter,
+                BoundsClassification::PossiblyOutOfBounds
+            )
+            .cwe_name(),
+            Some("CWE119")
+        );
+        assert_eq!(
+            record(
+                BoundsCheckedAccess::Write,
+                BoundsClassification::UnknownObject
+            )
+            .cwe_name(),
+            None
+        );
+    }
+}
@@ -0,0 +1,34 @@
+//! Modeling of memory copy functions like `memcpy`, `memmove` and `strncpy`.
+//!
+//! The generic handling of extern calls would assume that the whole memory objects
+//! reachable through the parameters are overwritten with unknown values.
+//! Since the destination pointer, the source pointer and the length of the copied range are often known,
+//! calls to these functions are modeled more precisely instead:
+//! * If the length is exactly known, the values in the source range are copied to the destination range.
+//!   If only an interval of possible lengths is known,
+//!   the values are copied up to the lower bound of the interval
+//!   and the bytes up to the upper bound of the interval are marked as unknown.
+//! * If the length is unknown, the bytes from the destination pointer
+//!   up to the upper bound of the destination object are marked as unknown.
+//!
+//! In both cases all other values in the destination object keep their values.
+//! For `strncpy`-like functions the copied values are not known,
+//! since copying stops at the first null byte of the source.
+//! Thus for these functions the destination range is only marked as unknown.
+
+use crate::prelude::*;
+
+/// The parameters of a memory copy function like `memcpy`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct MemoryCopyParameters {
+    /// The index of the parameter pointing to the destination of the copy operation.
+    pub destination_index: usize,
+    /// The index of the parameter pointing to the source of the copy operation.
+    pub source_index: usize,
+    /// The index of the parameter containing the (maximal) number of copied bytes.
+    pub length_index: usize,
+    /// If `true`, copying stops at the first null byte of the source like for `strncpy`,
+    /// so the values in the destination range are unknown after the call.
+    #[serde(default)]
+    pub null_terminated: bool,
+}
@@ -0,0 +1,329 @@
+//! Modeling of the memory protection of regions created by memory mapping functions like `mmap`.
+//!
+//! The allocation of mapped memory regions (e.g. by `mmap`) and their deallocation (e.g. by `munmap`)
+//! are modeled by describing the functions as a [custom allocator](super::custom_allocator),
+//! so that accesses to mapped regions are checked against their size
+//! and accesses after unmapping them are flagged as dangling pointer accesses.
+//!
+//! Additionally, the memory protection and the flags of mapped regions are tracked
+//! as the bits known to be set resp. unset in the corresponding parameters of the calls.
+//! Functions like `mprotect` update the protection of an existing region.
+//! If a region that was writable before is made executable,
+//! the analysis generates a CWE-1386 warning.
+//! (Regions mapped as writable and executable by a single call are reported by the CWE-1386 check itself.)
+
+use crate::abstract_domain::KnownBits;
+use crate::prelude::*;
+
+/// The value of the `PROT_WRITE` memory protection flag.
+pub const PROT_WRITE: u64 = 0x2;
+
+/// The value of the `PROT_EXEC` memory protection flag.
+pub const PROT_EXEC: u64 = 0x4;
+
+/// The parameter indices of a function setting the memory protection of a memory region,
+/// e.g. `mmap` or `mprotect`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct MemoryProtectionParameters {
+    /// The index of the parameter pointing to the memory region whose protection is set.
+    /// If not set, the protection applies to the memory region returned by the function (e.g. for `mmap`).
+    #[serde(default)]
+    pub pointer_index: Option<usize>,
+    /// The index of the parameter containing the memory protection flags.
+    pub protection_index: usize,
+    /// The index of the parameter containing the mapping flags (e.g. `MAP_SHARED`), if the function has one.
+    #[serde(default)]
+    pub flags_index: Option<usize>,
+}
+
+/// The memory protection of a mapped memory region.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct MappingProtection {
+    /// The bits known to be set resp. unset in the current memory protection of the region.
+    pub protection: KnownBits,
+    /// The bits known to be set resp. unset in the mapping flags of the region.
+    pub flags: KnownBits,
+    /// Whether the region is known to have been writable at some point.
+    pub was_writable: bool,
+}
+
+impl MappingProtection {
+    /// Create the memory protection of a newly mapped region.
+    pub fn new(protection: KnownBits, flags: KnownBits) -> MappingProtection {
+        let was_writable = is_known_set(&protection, PROT_WRITE);
+        MappingProtection {
+            protection,
+            flags,
+            was_writable,
+        }
+    }
+
+    /// Return the memory protection after changing the protection of the region to `protection`.
+    /// The mapping flags of the region are unchanged.
+    pub fn update(&self, protection: KnownBits) -> MappingProtection {
+        MappingProtection {
+            was_writable: self.was_writable || is_known_set(&protection, PROT_WRITE),
+            protection,
+            flags: self.flags.clone(),
+        }
+    }
+
+    /// Merge two memory protections.
+    /// Only the bits and facts known for both protections are kept.
+    pub fn merge(&self, other: &MappingProtection) -> MappingProtection {
+        MappingProtection {
+            protection: self.protection.merge(&other.protection),
+            flags: self.flags.merge(&other.flags),
+            was_writable: self.was_writable && other.was_writable,
+        }
+    }
+
+    /// Returns `true` if the region is known to be writable.
+    pub fn is_writable(&self) -> bool {
+        is_known_set(&self.protection, PROT_WRITE)
+    }
+
+    /// Returns `true` if the region is known to be executable.
+    pub fn is_executable(&self) -> bool {
+        is_known_set(&self.protection, PROT_EXEC)
+    }
+}
+
+/// Returns `true` if all bits of `flag` are known to be set.
+fn is_known_set(bits: &KnownBits, flag: u64) -> bool {
+    bits.ones
+        .try_to_u64()
+        .map(|ones| ones & flag == flag)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use crate::utils::log::CweWarning;
+
+    #[test]
+    fn writable_history() {
+        let bits = |value: u64| KnownBits::from_value(&Bitvector::from_u64(value));
+        let mapped = MappingProtection::new(bits(PROT_WRITE | 0x1), bits(0x22));
+        assert!(mapped.is_writable() && !mapped.is_executable());
+
+        let protected = mapped.update(bits(PROT_EXEC | 0x1));
+        assert!(protected.is_executable() && !protected.is_writable());
+        assert!(protected.was_writable);
+        assert_eq!(protected.flags, bits(0x22));
+
+        let read_only = MappingProtection::new(bits(0x1), bits(0x22));
+        let merged = protected.merge(&read_only);
+        assert!(!merged.was_writable);
+        assert!(!merged.is_executable());
+    }
+
+    /// Create a project whose `main` function executes the given blocks in order.
+    /// Each block is given by its definitions and an optional call to an extern function.
+    /// The pointer returned by `mmap` is expected to be saved in the callee-saved register `RBP`.
+    fn mock_project(blocks: Vec<(Vec<Term<Def>>, Option<&str>)>) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            ["RDI", "RSI", "RDX", "RCX"]
+                .iter()
+                .map(|name| Variable::mock(name, 8))
+                .collect(),
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for (name, num_params) in [("mmap", 4), ("munmap", 2), ("mprotect", 3)] {
+            project.add_mock_extern_symbol(name).parameters = ["RDI", "RSI", "RDX", "RCX"]
+                [..num_params]
+                .iter()
+                .map(|name| Arg::mock_register(name, 8))
+                .collect();
+        }
+        let num_blocks = blocks.len();
+        let mut main = Sub::mock("main");
+        for (index, (defs, call_target)) in blocks.into_iter().enumerate() {
+            let mut block = Blk::mock_with_tid(&format!("blk_{}", index));
+            block.term.defs = defs;
+            block.term.jmps = match call_target {
+                Some(target) if index + 1 < num_blocks => vec![Jmp::call(
+                    &format!("call_{}", target),
+                    target,
+                    Some(&format!("blk_{}", index + 1)),
+                )],
+                _ => vec![Term {
+                    tid: Tid::new("main_return"),
+                    term: Jmp::Return(Expression::var("RBX", 8)),
+                }],
+            };
+            main.term.blocks.push(block);
+        }
+        // Give each instruction its own address, so that warnings are not deduplicated.
+        for (index, block) in main.term.blocks.iter_mut().enumerate() {
+            for (def_index, def) in block.term.defs.iter_mut().enumerate() {
+                def.tid.address = format!("{:08x}", 0x1000 + 0x100 * index + def_index);
+            }
+            for jmp in block.term.jmps.iter_mut() {
+                jmp.tid.address = format!("{:08x}", 0x10ff + 0x100 * index);
+            }
+        }
+        project.program.term.entry_points.insert(main.tid.clone());
+        project.program.term.subs.insert(main.tid.clone(), main);
+        project
+    }
+
+    /// Assign the given constants to the given registers.
+    fn set_registers(tid_prefix: &str, values: &[(&str, i64)]) -> Vec<Term<Def>> {
+        values
+            .iter()
+            .map(|(register, value)| {
+                Def::assign(
+                    &format!("{}_{}", tid_prefix, register),
+                    Variable::mock(register, 8),
+                    Expression::const_from_i64(*value),
+                )
+            })
+            .collect()
+    }
+
+    /// The instructions of a block calling `mmap` to map a region of 0x1000 bytes with the given protection.
+    fn map_region(protection: u64) -> (Vec<Term<Def>>, Option<&'static str>) {
+        let defs = set_registers(
+            "mmap_param",
+            &[
+                ("RDI", 0),
+                ("RSI", 0x1000),
+                ("RDX", protection as i64),
+                ("RCX", 0x22),
+            ],
+        );
+        (defs, Some("mmap"))
+    }
+
+    /// Save the pointer returned by `mmap` in `RBP`.
+    fn save_region_pointer() -> Term<Def> {
+        Def::assign(
+            "save_pointer",
+            Variable::mock("RBP", 8),
+            Expression::var("RAX", 8),
+        )
+    }
+
+    /// Run the pointer inference analysis with the default configuration on the project
+    /// and return the generated CWE warnings.
+    fn get_warnings(project: &Project) -> Vec<CweWarning> {
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../../config.json")).unwrap();
+        let config = crate::utils::pipeline::get_pointer_inference_config(&config, None);
+        let pi_results = super::super::run(
+            project,
+            &runtime_memory_image,
+            &graph,
+            serde_json::from_value(config).unwrap(),
+            false,
+            false,
+        );
+        pi_results.collected_logs.1
+    }
+
+    #[test]
+    fn bounded_access_into_mapped_region() {
+        let project = mock_project(vec![
+            map_region(PROT_WRITE | 0x1),
+            (
+                vec![
+                    save_region_pointer(),
+                    Def::store(
+                        "in_bounds_write",
+                        Expression::var("RBP", 8).plus_const(0xff8),
+                        Expression::const_from_i64(0),
+                    ),
+                    Def::store(
+                        "out_of_bounds_write",
+                        Expression::var("RBP", 8).plus_const(0x1000),
+                        Expression::const_from_i64(0),
+                    ),
+                ],
+                None,
+            ),
+        ]);
+        let warnings = get_warnings(&project);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "CWE787");
+        assert_eq!(warnings[0].tids, vec!["out_of_bounds_write".to_string()]);
+    }
+
+    #[test]
+    fn use_after_munmap() {
+        let mut unmap_defs = vec![save_region_pointer()];
+        unmap_defs.push(Def::assign(
+            "munmap_param_RDI",
+            Variable::mock("RDI", 8),
+            Expression::var("RBP", 8),
+        ));
+        unmap_defs.extend(set_registers("munmap_param", &[("RSI", 0x1000)]));
+        let project = mock_project(vec![
+            map_region(PROT_WRITE | 0x1),
+            (unmap_defs, Some("munmap")),
+            (
+                vec![Def::load(
+                    "read_after_unmap",
+                    Variable::mock("RCX", 8),
+                    Expression::var("RBP", 8),
+                )],
+                None,
+            ),
+        ]);
+        let warnings = get_warnings(&project);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "CWE416");
+        assert_eq!(warnings[0].tids, vec!["read_after_unmap".to_string()]);
+    }
+
+    /// Create a project mapping a region with the protection `mapped` and then changing it to `protected`.
+    fn mock_project_with_mprotect(mapped: u64, protected: u64) -> Project {
+        let mut protect_defs = vec![save_region_pointer()];
+        protect_defs.push(Def::assign(
+            "mprotect_param_RDI",
+            Variable::mock("RDI", 8),
+            Expression::var("RBP", 8),
+        ));
+        protect_defs.extend(set_registers(
+            "mprotect_param",
+            &[("RSI", 0x1000), ("RDX", protected as i64)],
+        ));
+        mock_project(vec![
+            map_region(mapped),
+            (protect_defs, Some("mprotect")),
+            (Vec::new(), None),
+        ])
+    }
+
+    #[test]
+    fn writable_region_made_executable() {
+        let project = mock_project_with_mprotect(PROT_WRITE | 0x1, PROT_EXEC | 0x1);
+        let warnings = get_warnings(&project);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "CWE1386");
+        assert_eq!(
+            warnings[0].tids,
+            vec!["call_mprotect".to_string(), "call_mmap".to_string()]
+        );
+
+        // A region that was never writable may be made executable.
+        let project = mock_project_with_mprotect(0x1, PROT_EXEC | 0x1);
+        assert!(get_warnings(&project).is_empty());
+    }
+}
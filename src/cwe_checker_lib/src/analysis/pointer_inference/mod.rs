@@ -16,7 +16,14 @@
 //! - [CWE-415](https://cwe.mitre.org/data/definitions/415.html): Double Free
 //! - [CWE-416](https://cwe.mitre.org/data/definitions/416.html): Use After Free
 //! - [CWE-476](https://cwe.mitre.org/data/definitions/476.html): NULL Pointer Dereference
+//! - [CWE-762](https://cwe.mitre.org/data/definitions/762.html): Mismatched Memory Management Routines (for custom allocators)
 //! - [CWE-787](https://cwe.mitre.org/data/definitions/787.html): Buffer Overflow: Out-of-bounds Write
+//! - [CWE-825](https://cwe.mitre.org/data/definitions/825.html): Expired Pointer Dereference
+//!   (for pointers returned by `getenv` that were invalidated by a modification of the environment)
+//!
+//! Warnings for out-of-bounds writes and for out-of-bounds pointers passed to library calls
+//! are classified by the exploit primitive that they may provide to an attacker
+//! (see [`ExploitPrimitive`](crate::utils::log::ExploitPrimitive)).
 //!
 //! The analysis operates on a best-effort basis.
 //! In cases where we cannot know
@@ -34,22 +41,34 @@ use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::log::*;
 use crate::{
-    abstract_domain::{DataDomain, IntervalDomain},
+    abstract_domain::{AbstractDomain, DataDomain, IntervalDomain, SizedDomain, TryToBitvec},
     utils::binary::RuntimeMemoryImage,
 };
 use petgraph::graph::NodeIndex;
 use petgraph::visit::IntoNodeReferences;
 use petgraph::Direction;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
+pub mod access_histogram;
 mod context;
+pub mod custom_allocator;
+pub mod event_polling;
+pub mod harness;
+pub mod memory_access_checks;
+pub mod memory_copy;
+pub mod memory_mapping;
 pub mod object;
 mod object_list;
+pub mod path_attribution;
+pub mod retention;
 mod state;
 mod statistics;
+pub mod thread_creation;
 
 use context::Context;
+pub use retention::NodeValueRetention;
 pub use state::State;
+pub use statistics::{count_unknown_expressions_per_block, ExternSymbolStats, MemoryAccessCounter};
 
 /// The version number of the analysis.
 const VERSION: &str = "0.2";
@@ -59,6 +78,8 @@ pub static CWE_MODULE: crate::CweModule = crate::CweModule {
     name: "Memory",
     version: VERSION,
     run: extract_pi_analysis_results,
+    required_analyses: &[crate::SharedAnalysis::PointerInference],
+    pointer_inference_retention: NodeValueRetention::EntryExitOnly,
 };
 
 /// The abstract domain to use for absolute values.
@@ -75,9 +96,139 @@ pub struct Config {
     pub allocation_symbols: Vec<String>,
     /// Names of extern functions that are `free`-like,
     /// i.e. the memory chunk that the unique parameter of the function points to gets deallocated.
-    /// Note that the analysis currently does not detect mismatching allocation-deallocation pairs,
+    /// Note that the analysis does not detect mismatching allocation-deallocation pairs among these functions,
     /// i.e. it cannot distinguish between memory allocated by `malloc` and memory allocated by `new`.
+    /// Mismatches between these functions and custom allocators are detected.
     pub deallocation_symbols: Vec<String>,
+    /// Descriptions of custom allocation-deallocation function pairs used by the program,
+    /// e.g. pool allocators wrapping the standard allocation functions.
+    /// Calls to them are modeled like calls to `malloc`-like and `free`-like functions.
+    /// See [`CustomAllocator`](custom_allocator::CustomAllocator) for the format of the descriptions.
+    #[serde(default)]
+    pub custom_allocators: Vec<custom_allocator::CustomAllocator>,
+    /// Names of extern functions that are `dlsym`-like,
+    /// i.e. the return value is a pointer to a function or a NULL pointer if the function was not found.
+    #[serde(default)]
+    pub function_lookup_symbols: Vec<String>,
+    /// Names of extern functions that may switch the execution context,
+    /// e.g. the `ucontext`-based coroutine functions `makecontext`, `swapcontext` and `setcontext`.
+    /// Since the stack may be switched, calls to them clobber the stack pointer.
+    #[serde(default)]
+    pub context_switch_symbols: Vec<String>,
+    /// Maps the names of `makecontext`-like extern functions
+    /// to the index of their parameter containing the entry function of a new coroutine.
+    /// The entry functions are added as additional entry points of the analysis.
+    #[serde(default)]
+    pub coroutine_entry_index: BTreeMap<String, usize>,
+    /// Maps the names of `pthread_create`-like extern functions
+    /// to the indices of their start routine and argument parameters.
+    /// The start routines are added as additional entry points of the analysis
+    /// with the argument passed from the creation site.
+    /// See the [`thread_creation`] module for details.
+    #[serde(default)]
+    pub thread_creation_symbols: BTreeMap<String, thread_creation::ThreadCreationParameters>,
+    /// Names of extern functions that are `getenv`-like,
+    /// i.e. the return value is a pointer to the value of the environment variable named by the first parameter.
+    #[serde(default)]
+    pub environment_lookup_symbols: Vec<String>,
+    /// Names of extern functions that modify the environment, e.g. `setenv`, `putenv` and `unsetenv`.
+    /// Pointers returned by `getenv`-like functions for the modified variable are invalid after calls to them.
+    #[serde(default)]
+    pub environment_modification_symbols: Vec<String>,
+    /// Maps the names of `mmap`-like and `mprotect`-like extern functions to the indices of their parameters
+    /// relevant for the memory protection of the mapped memory region.
+    /// See the [`memory_mapping`] module for details.
+    #[serde(default)]
+    pub memory_protection_symbols: BTreeMap<String, memory_mapping::MemoryProtectionParameters>,
+    /// Maps the names of event polling functions like `poll` to the fields in memory written by them.
+    /// Calls to them only invalidate the written fields instead of the whole memory objects
+    /// reachable through their parameters.
+    /// See the [`event_polling`] module for details.
+    #[serde(default)]
+    pub event_polling_symbols: BTreeMap<String, event_polling::EventPollingParameters>,
+    /// Maps the names of memory copy functions like `memcpy` to the indices of their parameters.
+    /// Calls to them copy the known values of the source to the destination
+    /// instead of invalidating the whole memory objects reachable through their parameters.
+    /// See the [`memory_copy`] module for details.
+    #[serde(default)]
+    pub memory_copy_symbols: BTreeMap<String, memory_copy::MemoryCopyParameters>,
+    /// If `true`, the CWE warnings generated by the analysis contain the abstract facts
+    /// that they were derived from (see [`WarningContext`](crate::utils::log::WarningContext)).
+    /// Disabled by default, since the evidence can be large.
+    #[serde(default)]
+    pub collect_warning_evidence: bool,
+    /// If `true`, the evidence of out-of-bounds warnings for instructions in blocks with several predecessors
+    /// notes which predecessors violate the bound on their own (see the [`path_attribution`] module).
+    /// Disabled by default, since the bounds checks have to be re-evaluated for each predecessor.
+    #[serde(default)]
+    pub attribute_warning_paths: bool,
+    /// If `true`, the memory access histograms of all heap objects are collected after the fixpoint computation
+    /// (see the [`access_histogram`] module) and added to the JSON output.
+    /// The histograms are collected before node values are discarded under the [node value retention policy](Config::node_value_retention).
+    #[serde(default)]
+    pub collect_access_histograms: bool,
+    /// Names of extern functions that are assumed to be free of side effects,
+    /// i.e. calls to them only change the non-callee-saved registers (including the return register)
+    /// and do not write to memory reachable through their parameters.
+    #[serde(default)]
+    pub assume_pure: Vec<String>,
+    /// Names of `strlen`-like extern functions.
+    /// Their return values are tracked as symbolic string lengths,
+    /// which bound the lengths of the strings in objects allocated or copied with sizes derived from them.
+    #[serde(default)]
+    pub string_length_symbols: Vec<String>,
+    /// Names of extern functions like `__libc_start_main`
+    /// that call the `main` function of the program given as their first parameter and never return.
+    /// The `main` function is added as an entry point of the analysis
+    /// with its command line arguments and environment seeded as externally controlled.
+    #[serde(default)]
+    pub program_start_symbols: Vec<String>,
+    /// The maximal number of relative targets of a value after merging states.
+    /// Targets exceeding the limit are collapsed into summary targets per allocation site.
+    /// If not set, the number of targets is not limited.
+    #[serde(default)]
+    pub max_pointer_targets: Option<usize>,
+    /// If set, only the target function of the harness is analyzed,
+    /// starting with an entry state built from the parameter facts of the harness.
+    /// No speculative entry points are added in this case.
+    /// See the [`harness`] module for details.
+    #[serde(default)]
+    pub harness: Option<harness::HarnessSpec>,
+    /// Functions whose observed behavior differs from their calling convention,
+    /// as found by the [calling convention verification](crate::analysis::function_signature::verify_calling_conventions).
+    /// For calls to these functions the analysis trusts the observed behavior over the calling convention,
+    /// i.e. clobbered callee-saved registers are not restored on return
+    /// and callee-saved registers read as parameters are passed to the callee.
+    #[serde(default)]
+    pub trusted_convention_discrepancies:
+        Vec<crate::analysis::function_signature::CallingConventionDiscrepancy>,
+    /// Maps the names of extern functions to the indices of their parameters
+    /// pointing to memory that is written by the function, e.g. the destination of `strcpy`.
+    /// Calls where such a parameter points to read-only global memory are reported as CWE-471.
+    #[serde(default)]
+    pub written_pointer_parameters: BTreeMap<String, Vec<usize>>,
+    /// The side effects of internal functions,
+    /// as classified by the [function signature analysis](crate::analysis::function_signature::get_purity_classifications).
+    /// After returning from a call to a function that is not impure,
+    /// the register values and memory objects that the function cannot have modified
+    /// are restored from the state before the call.
+    #[serde(default)]
+    pub function_purities: Vec<crate::analysis::function_signature::PurityClassification>,
+    /// The ownership of the pointer parameters of internal functions,
+    /// as computed from the results of a previous pointer inference run
+    /// (see [`add_parameter_ownership`](crate::analysis::function_signature::add_parameter_ownership)).
+    /// After returning from a call, the objects freed by the callee are marked as dangling
+    /// and the objects stored by the callee are marked as escaped.
+    /// This is more exact than the states of the objects at the return of the callee
+    /// if the callee is called with different objects from several call sites.
+    #[serde(default)]
+    pub parameter_ownership: Vec<crate::analysis::function_signature::OwnershipClassification>,
+    /// Determines which node values are kept after the fixpoint computation
+    /// and can thus be queried by the CWE checks (see [`NodeValueRetention`]).
+    /// If not set in the configuration file, the CWE checker uses the smallest policy
+    /// that satisfies the needs of the enabled checks.
+    #[serde(default)]
+    pub node_value_retention: NodeValueRetention,
 }
 
 /// A wrapper struct for the pointer inference computation object.
@@ -86,6 +237,44 @@ pub struct PointerInference<'a> {
     log_collector: crossbeam_channel::Sender<LogThreadMsg>,
     /// The log messages and CWE warnings that have been generated during the pointer inference analysis.
     pub collected_logs: (Vec<LogMessage>, Vec<CweWarning>),
+    /// The per-object memory access histograms.
+    /// Only computed if requested through [`PointerInference::collect_access_histograms`].
+    access_histograms: Option<access_histogram::AccessHistograms>,
+    /// The TID of the `main` function of the program, if it was found.
+    main_function: Option<Tid>,
+    /// Maps the TIDs of thread start routines to the TIDs of the calls creating the threads.
+    thread_creation_sites: BTreeMap<Tid, BTreeSet<Tid>>,
+    /// The memory access counts of the original computation
+    /// if the results were restored from a checkpoint.
+    restored_memory_access_counts: Option<(u64, u64)>,
+    /// The policy determining which node values are kept after the fixpoint computation.
+    node_value_retention: NodeValueRetention,
+    /// Whether node values not kept under the node value retention policy were already discarded.
+    node_values_discarded: bool,
+}
+
+/// The results of a pointer inference computation in a form
+/// that can be saved to and restored from an analysis checkpoint.
+///
+/// The node values are identified by their node indices in the control flow graph.
+/// They can only be restored for a control flow graph generated from the same project
+/// with the same configuration.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct PointerInferenceCheckpoint {
+    /// The node values of all nodes that have a value, sorted by node index.
+    node_values: Vec<(usize, NodeValue<State>)>,
+    /// The log messages and CWE warnings generated during the computation.
+    collected_logs: (Vec<LogMessage>, Vec<CweWarning>),
+    /// The TID of the `main` function of the program, if it was found.
+    main_function: Option<Tid>,
+    /// Maps the TIDs of thread start routines to the TIDs of the calls creating the threads.
+    thread_creation_sites: BTreeMap<Tid, BTreeSet<Tid>>,
+    /// The memory access counts of the computation.
+    memory_access_counts: (u64, u64),
+    /// The policy that determined which node values were kept by the computation.
+    /// Checkpoints without this field were generated before node values could be discarded.
+    #[serde(default)]
+    node_value_retention: NodeValueRetention,
 }
 
 impl<'a> PointerInference<'a> {
@@ -98,6 +287,7 @@ impl<'a> PointerInference<'a> {
         log_sender: crossbeam_channel::Sender<LogThreadMsg>,
         print_stats: bool,
     ) -> PointerInference<'a> {
+        let node_value_retention = config.node_value_retention;
         let context = Context::new(
             project,
             runtime_memory_image,
@@ -153,20 +343,27 @@ impl<'a> PointerInference<'a> {
                 .source("Pointer Inference"),
             ));
         }
+        let harness = fixpoint_computation
+            .get_context()
+            .get_context()
+            .harness
+            .clone();
         for (sub_tid, start_node_index) in entry_sub_to_entry_node_map.into_iter() {
-            let mut fn_entry_state = if let Some(cconv) = project.get_standard_calling_convention()
-            {
-                State::new_with_generic_parameter_objects(
-                    &project.stack_pointer_register,
-                    sub_tid.clone(),
-                    &cconv.integer_parameter_register,
-                )
-            } else {
-                State::new(&project.stack_pointer_register, sub_tid.clone())
-            };
-            if project.cpu_architecture.contains("MIPS") {
-                let _ = fn_entry_state
-                    .set_mips_link_register(&sub_tid, project.stack_pointer_register.size);
+            let sub = &project.program.term.subs[&sub_tid];
+            let mut fn_entry_state = generate_entry_state(project, sub);
+            if let Some(harness) = harness.as_ref().filter(|harness| harness.is_target(sub)) {
+                match harness.generate_entry_state(project, sub, fn_entry_state.clone()) {
+                    Ok(harness_entry_state) => fn_entry_state = harness_entry_state,
+                    Err(err) => {
+                        let _ = log_sender.send(LogThreadMsg::Log(
+                            LogMessage::new_error(format!(
+                                "Could not apply the harness to {}: {}",
+                                sub.term.name, err
+                            ))
+                            .source("Pointer Inference"),
+                        ));
+                    }
+                }
             }
             fixpoint_computation.set_node_value(
                 start_node_index,
@@ -177,6 +374,90 @@ impl<'a> PointerInference<'a> {
             computation: fixpoint_computation,
             log_collector: log_sender,
             collected_logs: (Vec::new(), Vec::new()),
+            access_histograms: None,
+            main_function: None,
+            thread_creation_sites: BTreeMap::new(),
+            restored_memory_access_counts: None,
+            node_value_retention,
+            node_values_discarded: false,
+        }
+    }
+
+    /// Restore the results of a pointer inference computation from a checkpoint
+    /// without recomputing the fixpoint.
+    ///
+    /// The project, control flow graph and configuration have to be the same
+    /// as for the computation that generated the checkpoint.
+    /// Returns an error if the checkpoint contains nodes that do not exist in the control flow graph.
+    pub fn from_checkpoint(
+        project: &'a Project,
+        runtime_memory_image: &'a RuntimeMemoryImage,
+        control_flow_graph: &'a Graph<'a>,
+        config: Config,
+        checkpoint: PointerInferenceCheckpoint,
+    ) -> Result<PointerInference<'a>, Error> {
+        // No log messages are generated since the fixpoint is not recomputed.
+        let (log_sender, _) = crossbeam_channel::unbounded();
+        let collect_access_histograms = config.collect_access_histograms;
+        if checkpoint.node_value_retention < config.node_value_retention {
+            return Err(anyhow!(
+                "The checkpoint only contains the node values kept under the {} policy, but the {} policy is required",
+                checkpoint.node_value_retention,
+                config.node_value_retention
+            ));
+        }
+        let context = Context::new(
+            project,
+            runtime_memory_image,
+            control_flow_graph,
+            config,
+            log_sender.clone(),
+        );
+        let mut computation =
+            super::forward_interprocedural_fixpoint::create_computation_with_alternate_worklist_order(context, None);
+        for (index, node_value) in checkpoint.node_values {
+            if index >= control_flow_graph.node_count() {
+                return Err(anyhow!(
+                    "Node index {} of the checkpoint does not exist in the control flow graph",
+                    index
+                ));
+            }
+            computation.set_node_value(NodeIndex::new(index), node_value);
+        }
+        let mut pointer_inference = PointerInference {
+            computation,
+            log_collector: log_sender,
+            collected_logs: checkpoint.collected_logs,
+            access_histograms: None,
+            main_function: checkpoint.main_function,
+            thread_creation_sites: checkpoint.thread_creation_sites,
+            restored_memory_access_counts: Some(checkpoint.memory_access_counts),
+            node_value_retention: checkpoint.node_value_retention,
+            node_values_discarded: checkpoint.node_value_retention != NodeValueRetention::All,
+        };
+        if collect_access_histograms {
+            pointer_inference.collect_access_histograms();
+        }
+        Ok(pointer_inference)
+    }
+
+    /// Generate a checkpoint of the results of the computation,
+    /// from which the results can be restored through [`PointerInference::from_checkpoint`].
+    pub fn to_checkpoint(&self) -> PointerInferenceCheckpoint {
+        let mut node_values: Vec<(usize, NodeValue<State>)> = self
+            .computation
+            .node_values()
+            .iter()
+            .map(|(node_index, node_value)| (node_index.index(), node_value.clone()))
+            .collect();
+        node_values.sort_by_key(|(index, _)| *index);
+        PointerInferenceCheckpoint {
+            node_values,
+            collected_logs: self.collected_logs.clone(),
+            main_function: self.main_function.clone(),
+            thread_creation_sites: self.thread_creation_sites.clone(),
+            memory_access_counts: self.get_memory_access_counts(),
+            node_value_retention: self.node_value_retention,
         }
     }
 
@@ -242,6 +523,150 @@ impl<'a> PointerInference<'a> {
         self.computation.get_node_value(node_id)
     }
 
+    /// Get the policy determining which node values were kept after the fixpoint computation.
+    pub fn get_node_value_retention(&self) -> NodeValueRetention {
+        self.node_value_retention
+    }
+
+    /// Discard all node values that are not kept under the node value retention policy of the configuration.
+    ///
+    /// Should only be called after the fixpoint has been computed.
+    /// Afterwards the node values can no longer be used to continue the fixpoint computation.
+    pub fn discard_unretained_node_values(&mut self) {
+        if self.node_value_retention == NodeValueRetention::All {
+            return;
+        }
+        let graph = self.computation.get_graph();
+        let retained_nodes: HashSet<NodeIndex> = graph
+            .node_indices()
+            .filter(|node| self.node_value_retention.retains_node(graph, *node))
+            .collect();
+        self.computation
+            .retain_node_values(|node| retained_nodes.contains(&node));
+        self.node_values_discarded = true;
+    }
+
+    /// Get the state just before the execution of the term with the given TID.
+    ///
+    /// Returns `None` if the term is not contained in the graph or if no state is known for it.
+    /// See [`PointerInference::try_get_state_before_term`] for details.
+    pub fn get_state_before_term(&self, tid: &Tid) -> Option<State> {
+        self.try_get_state_before_term(tid).ok()
+    }
+
+    /// Get the state just before the execution of the term with the given TID.
+    ///
+    /// The state is reconstructed from the state at the start of the block containing the term.
+    /// For jumps the state after all `Def`s of the block is returned.
+    /// If the block is contained in several nodes of the graph (e.g. because it is shared between functions),
+    /// the states of all these nodes are merged.
+    /// Returns an error if the term is not contained in the graph, if no state was computed for it
+    /// or if the state was discarded under the node value retention policy of the analysis.
+    pub fn try_get_state_before_term(&self, tid: &Tid) -> Result<State, Error> {
+        let mut result: Option<State> = None;
+        let mut term_found = false;
+        let mut discarded = false;
+        for (node, node_weight) in self.get_graph().node_references() {
+            let block = match node_weight {
+                Node::BlkStart(block, _) => block,
+                _ => continue,
+            };
+            let def_index =
+                if let Some(index) = block.term.defs.iter().position(|def| def.tid == *tid) {
+                    index
+                } else if block.term.jmps.iter().any(|jmp| jmp.tid == *tid) {
+                    block.term.defs.len()
+                } else {
+                    continue;
+                };
+            term_found = true;
+            if !self
+                .node_value_retention
+                .retains_node(self.get_graph(), node)
+            {
+                discarded = true;
+            }
+            if let Some(NodeValue::Value(state)) = self.get_node_value(node) {
+                let mut state = state.clone();
+                state.apply_defs(
+                    &block.term.defs[..def_index],
+                    self.get_context().runtime_memory_image,
+                );
+                result = Some(match result {
+                    Some(other) => other.merge(&state),
+                    None => state,
+                });
+            }
+        }
+        match result {
+            Some(state) => Ok(state),
+            None if discarded => Err(anyhow!(
+                "The state before {} was discarded under the {} node value retention policy",
+                tid,
+                self.node_value_retention
+            )),
+            None if term_found => Err(anyhow!("No state was computed before {}", tid)),
+            None => Err(anyhow!(
+                "{} is not contained in the control flow graph",
+                tid
+            )),
+        }
+    }
+
+    /// Compute the memory access histograms of all tracked heap objects
+    /// and store them in the analysis results.
+    ///
+    /// The histograms record the offsets, sizes and kinds (read or write) of all accesses to heap objects.
+    /// The collection is opt-in, since it requires an additional pass over all computed states.
+    /// It should only be called after the fixpoint has been computed
+    /// and before node values are discarded by [`PointerInference::discard_unretained_node_values`].
+    /// If node values were already discarded, the histograms only cover the retained nodes
+    /// and a log message noting the incomplete histograms is generated.
+    pub fn collect_access_histograms(&mut self) {
+        self.access_histograms = Some(access_histogram::compute_access_histograms(self));
+        if self.node_values_discarded {
+            self.collected_logs.0.push(
+                LogMessage::new_info(format!(
+                    "The access histograms are incomplete, since node values were discarded under the {} node value retention policy",
+                    self.node_value_retention
+                ))
+                .source("Pointer Inference"),
+            );
+        }
+    }
+
+    /// Get the memory access histograms of the tracked heap objects
+    /// if they were collected through [`PointerInference::collect_access_histograms`].
+    pub fn get_access_histograms(&self) -> Option<&access_histogram::AccessHistograms> {
+        self.access_histograms.as_ref()
+    }
+
+    /// Compute the precision loss caused by calls to extern symbols whose effect is not modeled by the analysis.
+    ///
+    /// The result is ranked by the number of tracked values invalidated by calls to the symbols,
+    /// so that the first symbols are the most promising candidates for adding prototypes
+    /// or for adding them to the `assume_pure` list of the configuration.
+    /// It should only be called after the fixpoint has been computed.
+    pub fn get_extern_symbol_stats(&self) -> Vec<ExternSymbolStats> {
+        statistics::compute_extern_symbol_stats(self)
+    }
+
+    /// Get the number of memory accesses evaluated during the fixpoint computation
+    /// and the number of them whose address was not `Top`.
+    ///
+    /// If the results were restored from a checkpoint,
+    /// the counts of the original computation are included.
+    pub fn get_memory_access_counts(&self) -> (u64, u64) {
+        let (total, known_address) = self.get_context().memory_access_counter.get_counts();
+        match self.restored_memory_access_counts {
+            Some((restored_total, restored_known_address)) => (
+                restored_total + total,
+                restored_known_address + known_address,
+            ),
+            None => (total, known_address),
+        }
+    }
+
     /// Add speculative entry points to the fixpoint algorithm state.
     ///
     /// Since indirect jumps and calls are not handled yet (TODO: change that),
@@ -290,24 +715,8 @@ impl<'a> PointerInference<'a> {
             ));
         }
         for entry in new_entry_points {
-            let sub_tid = start_block_to_sub_map
-                [&self.computation.get_graph()[entry].get_block().tid]
-                .tid
-                .clone();
-            let mut fn_entry_state = if let Some(cconv) = project.get_standard_calling_convention()
-            {
-                State::new_with_generic_parameter_objects(
-                    &project.stack_pointer_register,
-                    sub_tid.clone(),
-                    &cconv.integer_parameter_register,
-                )
-            } else {
-                State::new(&project.stack_pointer_register, sub_tid.clone())
-            };
-            if project.cpu_architecture.contains("MIPS") {
-                let _ = fn_entry_state
-                    .set_mips_link_register(&sub_tid, project.stack_pointer_register.size);
-            }
+            let sub = start_block_to_sub_map[&self.computation.get_graph()[entry].get_block().tid];
+            let fn_entry_state = generate_entry_state(project, sub);
             self.computation.set_node_value(
                 entry,
                 super::interprocedural_fixpoint_generic::NodeValue::Value(fn_entry_state),
@@ -315,6 +724,285 @@ impl<'a> PointerInference<'a> {
         }
     }
 
+    /// Evaluate the function address parameter of all calls to extern symbols
+    /// for which `get_parameter_index` returns the index of the parameter.
+    ///
+    /// Returns the TIDs of the blocks containing the calls together with the parameter value,
+    /// if the parameter value is a constant address.
+    fn get_function_address_parameters(
+        &self,
+        get_parameter_index: impl Fn(&ExternSymbol) -> Option<usize>,
+    ) -> Vec<(Tid, Option<u64>)> {
+        let context = self.computation.get_context().get_context();
+        let graph = self.computation.get_graph();
+        let mut parameter_values = Vec::new();
+        for (node_id, node) in graph.node_references() {
+            let (block, call_target) = match node {
+                Node::BlkEnd(block, _) => match block.term.jmps.first() {
+                    Some(Term {
+                        term: Jmp::Call { target, .. },
+                        ..
+                    }) => (block, target),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let (extern_symbol, state) = match (
+                context.get_extern_symbol(call_target),
+                self.computation.get_node_value(node_id),
+            ) {
+                (Some(extern_symbol), Some(NodeValue::Value(state))) => (extern_symbol, state),
+                _ => continue,
+            };
+            if let Some(parameter) = get_parameter_index(extern_symbol)
+                .and_then(|index| extern_symbol.parameters.get(index))
+            {
+                let address = state
+                    .eval_parameter_arg(parameter, context.runtime_memory_image)
+                    .ok()
+                    .and_then(|value| value.get_if_absolute_value()?.try_to_bitvec().ok())
+                    .and_then(|address| address.try_to_u64().ok());
+                parameter_values.push((block.tid.clone(), address));
+            }
+        }
+        parameter_values
+    }
+
+    /// Get the start nodes of all functions whose address is contained in `addresses`
+    /// together with the corresponding functions.
+    fn get_function_start_nodes(
+        &self,
+        addresses: &HashSet<u64>,
+    ) -> Vec<(NodeIndex, &'a Term<Sub>)> {
+        let graph = self.computation.get_graph();
+        let mut start_nodes = Vec::new();
+        for (node_id, node) in graph.node_references() {
+            if let Node::BlkStart(block, sub) = node {
+                if sub.term.blocks.first().map(|start_block| &start_block.tid) == Some(&block.tid)
+                    && u64::from_str_radix(&sub.tid.address, 16)
+                        .is_ok_and(|address| addresses.contains(&address))
+                {
+                    start_nodes.push((node_id, *sub));
+                }
+            }
+        }
+        start_nodes
+    }
+
+    /// Add the `main` function of the program as an entry point of the fixpoint algorithm.
+    ///
+    /// The `main` function is found through the first parameter of calls to `__libc_start_main`-like functions,
+    /// so it does not need to be named in the binary.
+    /// Its entry state is seeded with the command line arguments and the environment of the program
+    /// (see [`State::new_for_program_main`]).
+    /// Since `main` is only called indirectly, a generic entry state of it (if any) gets replaced.
+    fn add_program_main_entry_point(&mut self, project: &Project, print_stats: bool) {
+        let context = self.computation.get_context().get_context();
+        let start_symbols = &context.program_start_symbols;
+        let mut main_addresses = HashSet::new();
+        for (block_tid, address) in self.get_function_address_parameters(|extern_symbol| {
+            start_symbols.contains(&extern_symbol.name).then_some(0)
+        }) {
+            match address {
+                Some(address) => {
+                    main_addresses.insert(address);
+                }
+                None => self.log_info(format!(
+                    "The main function passed to the program start function at {} is unknown",
+                    block_tid.address
+                )),
+            }
+        }
+        let graph = self.computation.get_graph();
+        let new_entry_points: Vec<_> = self
+            .get_function_start_nodes(&main_addresses)
+            .into_iter()
+            .filter(|(node_id, _)| {
+                graph
+                    .neighbors_directed(*node_id, Direction::Incoming)
+                    .next()
+                    .is_none()
+            })
+            .collect();
+        for (entry, sub) in new_entry_points {
+            if print_stats {
+                self.log_info(format!(
+                    "Adding {} at {} as the main function of the program",
+                    sub.term.name, sub.tid.address
+                ));
+            }
+            let fn_entry_state = generate_main_entry_state(project, sub);
+            self.computation
+                .set_node_value(entry, NodeValue::Value(fn_entry_state));
+            self.main_function = Some(sub.tid.clone());
+        }
+    }
+
+    /// Return the TID of the `main` function of the program
+    /// if it was found through a call to a `__libc_start_main`-like function.
+    pub fn get_main_function(&self) -> Option<&Tid> {
+        self.main_function.as_ref()
+    }
+
+    /// Add the entry functions of coroutines created by calls to `makecontext`-like functions
+    /// as additional entry points of the fixpoint algorithm.
+    ///
+    /// The entry function parameter of each call is evaluated in the state before the call.
+    /// Only entry functions given as constant addresses are found.
+    /// Functions whose start node already has a state assigned to it are ignored.
+    fn add_coroutine_entry_points(&mut self, project: &Project, print_stats: bool) {
+        let context = self.computation.get_context().get_context();
+        let mut entry_addresses = HashSet::new();
+        for (block_tid, address) in self.get_function_address_parameters(|extern_symbol| {
+            context
+                .coroutine_entry_index
+                .get(&extern_symbol.name)
+                .copied()
+        }) {
+            match address {
+                Some(address) => {
+                    entry_addresses.insert(address);
+                }
+                None => self.log_info(format!(
+                    "Entry function of the coroutine created at {} is unknown",
+                    block_tid.address
+                )),
+            }
+        }
+        let new_entry_points: Vec<_> = self
+            .get_function_start_nodes(&entry_addresses)
+            .into_iter()
+            .filter(|(node_id, _)| self.computation.get_node_value(*node_id).is_none())
+            .collect();
+        if print_stats {
+            self.log_info(format!(
+                "Adding {} coroutine entry points",
+                new_entry_points.len()
+            ));
+        }
+        for (entry, sub) in new_entry_points {
+            let fn_entry_state = generate_entry_state(project, sub);
+            self.computation
+                .set_node_value(entry, NodeValue::Value(fn_entry_state));
+        }
+    }
+
+    /// Add the start routines of threads created by calls to `pthread_create`-like functions
+    /// as additional entry points of the fixpoint algorithm.
+    ///
+    /// Only start routines given as constant addresses are found.
+    /// In the entry state of a start routine the first parameter register contains the argument
+    /// passed to the thread creation function and the objects reachable through it are copied from the creation site.
+    /// The copied objects are marked as non-unique.
+    /// If the same start routine is used at several creation sites, the entry states are merged.
+    /// Start routines that are also called directly by other functions are not changed.
+    fn add_thread_entry_points(&mut self, project: &Project, print_stats: bool) {
+        let context = self.computation.get_context().get_context();
+        let graph = self.computation.get_graph();
+        let mut creations_by_address: HashMap<u64, Vec<(Tid, &State, Data)>> = HashMap::new();
+        for (node_id, node) in graph.node_references() {
+            let (call_tid, call_target) = match node {
+                Node::BlkEnd(block, _) => match block.term.jmps.first() {
+                    Some(Term {
+                        tid,
+                        term: Jmp::Call { target, .. },
+                    }) => (tid, target),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let (extern_symbol, parameters, state) = match (
+                context.get_extern_symbol(call_target),
+                self.computation.get_node_value(node_id),
+            ) {
+                (Some(extern_symbol), Some(NodeValue::Value(state))) => {
+                    match context.thread_creation_symbols.get(&extern_symbol.name) {
+                        Some(parameters) => (extern_symbol, parameters, state),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            let eval_parameter = |index: usize| {
+                extern_symbol.parameters.get(index).and_then(|parameter| {
+                    state
+                        .eval_parameter_arg(parameter, context.runtime_memory_image)
+                        .ok()
+                })
+            };
+            let start_routine_address = eval_parameter(parameters.start_routine_index)
+                .and_then(|value| value.get_if_absolute_value()?.try_to_bitvec().ok())
+                .and_then(|address| address.try_to_u64().ok());
+            let argument = eval_parameter(parameters.argument_index)
+                .unwrap_or_else(|| Data::new_top(project.stack_pointer_register.size));
+            match start_routine_address {
+                Some(address) => creations_by_address.entry(address).or_default().push((
+                    call_tid.clone(),
+                    state,
+                    argument,
+                )),
+                None => self.log_info(format!(
+                    "Start routine of the thread created at {} is unknown",
+                    call_tid.address
+                )),
+            }
+        }
+        let addresses = creations_by_address.keys().copied().collect();
+        let mut new_entry_points = Vec::new();
+        for (entry, sub) in self.get_function_start_nodes(&addresses) {
+            if self.computation.get_node_value(entry).is_some()
+                && graph
+                    .neighbors_directed(entry, Direction::Incoming)
+                    .next()
+                    .is_some()
+            {
+                continue;
+            }
+            let address = u64::from_str_radix(&sub.tid.address, 16).unwrap();
+            let mut entry_state: Option<State> = None;
+            let mut creation_sites = BTreeSet::new();
+            for (call_tid, creator_state, argument) in creations_by_address[&address].iter() {
+                let mut state = generate_entry_state(project, sub);
+                if let Some(param) = project
+                    .get_specific_calling_convention(&sub.term.calling_convention)
+                    .and_then(|cconv| cconv.integer_parameter_register.first())
+                {
+                    state.set_register(param, argument.clone());
+                }
+                state.add_shared_objects_from(creator_state, argument);
+                state.remove_unreferenced_objects();
+                entry_state = Some(match entry_state {
+                    Some(entry_state) => entry_state.merge(&state),
+                    None => state,
+                });
+                creation_sites.insert(call_tid.clone());
+            }
+            if let Some(entry_state) = entry_state {
+                new_entry_points.push((entry, sub.tid.clone(), entry_state, creation_sites));
+            }
+        }
+        if print_stats {
+            self.log_info(format!(
+                "Adding {} thread entry points",
+                new_entry_points.len()
+            ));
+        }
+        for (entry, sub_tid, entry_state, creation_sites) in new_entry_points {
+            self.computation
+                .set_node_value(entry, NodeValue::Value(entry_state));
+            self.thread_creation_sites
+                .entry(sub_tid)
+                .or_default()
+                .extend(creation_sites);
+        }
+    }
+
+    /// Return a map from the TIDs of thread start routines
+    /// to the TIDs of the calls to `pthread_create`-like functions creating the threads.
+    pub fn get_thread_creation_sites(&self) -> &BTreeMap<Tid, BTreeSet<Tid>> {
+        &self.thread_creation_sites
+    }
+
     /// Print the number of blocks that have a state associated to them.
     /// Intended for debug purposes.
     fn count_blocks_with_state(&self) {
@@ -344,6 +1032,37 @@ impl<'a> PointerInference<'a> {
     /// Successively adds more functions as possible entry points
     /// to increase code coverage.
     pub fn compute_with_speculative_entry_points(&mut self, project: &Project, print_stats: bool) {
+        self.compute();
+        if print_stats {
+            self.count_blocks_with_state();
+        }
+        if self
+            .computation
+            .get_context()
+            .get_context()
+            .harness
+            .is_some()
+        {
+            // Only code reachable from the harness entry state is analyzed,
+            // but threads created by it are started with their arguments.
+            self.add_thread_entry_points(project, print_stats);
+            self.compute();
+            return;
+        }
+        // Now compute again with the main function of the program added
+        self.add_program_main_entry_point(project, print_stats);
+        self.compute();
+        if print_stats {
+            self.count_blocks_with_state();
+        }
+        // Now compute again with the entry functions of coroutines added
+        self.add_coroutine_entry_points(project, print_stats);
+        self.compute();
+        if print_stats {
+            self.count_blocks_with_state();
+        }
+        // Now compute again with the start routines of threads added
+        self.add_thread_entry_points(project, print_stats);
         self.compute();
         if print_stats {
             self.count_blocks_with_state();
@@ -370,6 +1089,8 @@ impl<'a> PointerInference<'a> {
         }
         if print_stats {
             statistics::compute_and_log_mem_access_stats(self);
+            statistics::compute_and_log_extern_symbol_stats(self);
+            statistics::compute_and_log_unknown_expression_stats(self);
         }
     }
 
@@ -450,6 +1171,47 @@ impl<'a> PointerInference<'a> {
     }
 }
 
+/// Generate the state at the start of the given function
+/// for the case that the function is an entry point of the analysis.
+///
+/// Generic parameter objects are generated for the integer parameter registers
+/// of the calling convention of the function.
+/// If the function has no annotated calling convention, the standard calling convention of the project is used.
+fn generate_entry_state(project: &Project, sub: &Term<Sub>) -> State {
+    let mut fn_entry_state = if let Some(cconv) =
+        project.get_specific_calling_convention(&sub.term.calling_convention)
+    {
+        State::new_with_generic_parameter_objects(
+            &project.stack_pointer_register,
+            sub.tid.clone(),
+            &cconv.integer_parameter_register,
+        )
+    } else {
+        State::new(&project.stack_pointer_register, sub.tid.clone())
+    };
+    if project.architecture.is_mips() {
+        let _ =
+            fn_entry_state.set_mips_link_register(&sub.tid, project.stack_pointer_register.size);
+    }
+    fn_entry_state
+}
+
+/// Generate the entry state of the `main` function of the program
+/// with the command line arguments and the environment seeded in its parameter registers.
+fn generate_main_entry_state(project: &Project, sub: &Term<Sub>) -> State {
+    let params = project
+        .get_specific_calling_convention(&sub.term.calling_convention)
+        .map(|cconv| &cconv.integer_parameter_register[..])
+        .unwrap_or(&[]);
+    let mut fn_entry_state =
+        State::new_for_program_main(&project.stack_pointer_register, sub.tid.clone(), params);
+    if project.architecture.is_mips() {
+        let _ =
+            fn_entry_state.set_mips_link_register(&sub.tid, project.stack_pointer_register.size);
+    }
+    fn_entry_state
+}
+
 /// The entry point for the memory analysis check.
 /// Does not actually compute anything
 /// but just extracts the results of the already computed pointer inference analysis.
@@ -474,6 +1236,8 @@ pub fn run<'a>(
     print_stats: bool,
 ) -> PointerInference<'a> {
     let logging_thread = LogThread::spawn(collect_all_logs);
+    let attribute_warning_paths = config.attribute_warning_paths;
+    let collect_access_histograms = config.collect_access_histograms;
 
     let mut computation = PointerInference::new(
         project,
@@ -492,25 +1256,47 @@ pub fn run<'a>(
 
     // save the logs and CWE warnings
     computation.collected_logs = logging_thread.collect();
+    if attribute_warning_paths {
+        let mut warnings = std::mem::take(&mut computation.collected_logs.1);
+        path_attribution::attribute_warnings_to_predecessors(&computation, &mut warnings);
+        computation.collected_logs.1 = warnings;
+    }
+    if collect_access_histograms {
+        computation.collect_access_histograms();
+    }
+    computation.discard_unretained_node_values();
+    thread_creation::annotate_warnings_in_start_routines(
+        project,
+        &computation.thread_creation_sites,
+        &mut computation.collected_logs.1,
+    );
     computation
 }
 
 /// This function is responsible for collecting logs and CWE warnings.
-/// For warnings with the same origin address only the last one is kept.
+/// For log messages with the same origin address only the last one is kept.
+/// For CWE warnings only the last one per origin address and CWE name is kept,
+/// so that e.g. an out-of-bounds read and an out-of-bounds write
+/// by the same instruction are both reported.
 /// This prevents duplicates but may suppress some log messages
 /// in the rare case that several different log messages with the same origin address are generated.
+/// Log messages reporting imprecision of the checks are only deduplicated if they are equal,
+/// since several of them may be generated for the same address.
 fn collect_all_logs(
     receiver: crossbeam_channel::Receiver<LogThreadMsg>,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let mut logs_with_address = BTreeMap::new();
     let mut general_logs = Vec::new();
+    let mut imprecision_logs = BTreeSet::new();
     let mut collected_cwes = BTreeMap::new();
 
     while let Ok(log_thread_msg) = receiver.recv() {
         match log_thread_msg {
             LogThreadMsg::Log(log_message) => {
-                if let Some(ref tid) = log_message.location {
-                    logs_with_address.insert(tid.address.clone(), log_message);
+                if log_message.imprecision.is_some() {
+                    imprecision_logs.insert(log_message);
+                } else if let Some(ref tid) = log_message.location {
+                    logs_with_address.insert(tid.canonical_address().to_string(), log_message);
                 } else {
                     general_logs.push(log_message);
                 }
@@ -518,7 +1304,7 @@ fn collect_all_logs(
             LogThreadMsg::Cwe(cwe_warning) => match &cwe_warning.addresses[..] {
                 [] => panic!("Unexpected CWE warning without origin address"),
                 [address, ..] => {
-                    collected_cwes.insert(address.clone(), cwe_warning);
+                    collected_cwes.insert((address.clone(), cwe_warning.name.clone()), cwe_warning);
                 }
             },
             LogThreadMsg::Terminate => break,
@@ -528,6 +1314,7 @@ fn collect_all_logs(
         .values()
         .cloned()
         .chain(general_logs.into_iter())
+        .chain(imprecision_logs)
         .collect();
     let cwes = collected_cwes
         .into_iter()
@@ -537,26 +1324,4 @@ fn collect_all_logs(
 }
 
 #[cfg(test)]
-mod tests {
-    use super::*;
-
-    impl<'a> PointerInference<'a> {
-        pub fn mock(
-            project: &'a Project,
-            mem_image: &'a RuntimeMemoryImage,
-            graph: &'a Graph,
-        ) -> PointerInference<'a> {
-            let config = Config {
-                allocation_symbols: vec!["malloc".to_string()],
-                deallocation_symbols: vec!["free".to_string()],
-            };
-            let (log_sender, _) = crossbeam_channel::unbounded();
-            PointerInference::new(project, mem_image, graph, config, log_sender, false)
-        }
-
-        pub fn set_node_value(&mut self, node_value: State, node_index: NodeIndex) {
-            self.computation
-                .set_node_value(node_index, NodeValue::Value(node_value));
-        }
-    }
-}
+mod tests;
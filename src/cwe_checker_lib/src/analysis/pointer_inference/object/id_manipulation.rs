@@ -31,6 +31,9 @@ impl AbstractObject {
             elem.replace_abstract_id(old_id, new_id, offset_adjustment);
         }
         inner.memory.clear_top_values();
+        if let Some(bound) = inner.string_length_bound.as_mut() {
+            bound.replace_abstract_id(old_id, new_id, offset_adjustment);
+        }
         if inner.pointer_targets.get(old_id).is_some() {
             inner.pointer_targets.remove(old_id);
             inner.pointer_targets.insert(new_id.clone());
@@ -55,5 +58,50 @@ impl AbstractObject {
             }
         }
         inner.memory.clear_top_values(); // In case the previous operation left *Top* values in the memory struct.
+        if inner
+            .string_length_bound
+            .as_ref()
+            .is_some_and(|bound| bound.referenced_ids().any(|id| ids_to_remove.contains(id)))
+        {
+            inner.string_length_bound = None;
+        }
+    }
+
+    /// Limit the number of relative targets of all values contained in the memory object to `max_targets`
+    /// by collapsing targets into their summary IDs (see [`Data::summarize_targets`]).
+    /// The used summary IDs are added to the pointer_targets list.
+    ///
+    /// Returns a map from the used summary IDs to the IDs collapsed into them.
+    pub fn summarize_pointer_targets(
+        &mut self,
+        max_targets: usize,
+        excluded_ids: &BTreeSet<AbstractIdentifier>,
+    ) -> BTreeMap<AbstractIdentifier, BTreeSet<AbstractIdentifier>> {
+        let mut summaries: BTreeMap<AbstractIdentifier, BTreeSet<AbstractIdentifier>> =
+            BTreeMap::new();
+        let needs_summarization = self.inner.memory.values().any(|value| {
+            let targets = value.get_relative_values();
+            targets.len() > max_targets
+                || targets.keys().any(|id| {
+                    id.get_summary_id().is_some_and(|summary_id| {
+                        &summary_id != id && targets.contains_key(&summary_id)
+                    })
+                })
+        });
+        if !needs_summarization {
+            return summaries;
+        }
+        let inner = Arc::make_mut(&mut self.inner);
+        for value in inner.memory.values_mut() {
+            for (summary_id, collapsed_ids) in value.summarize_targets(max_targets, excluded_ids) {
+                summaries
+                    .entry(summary_id)
+                    .or_default()
+                    .extend(collapsed_ids);
+            }
+        }
+        inner.memory.clear_top_values();
+        inner.pointer_targets.extend(summaries.keys().cloned());
+        summaries
     }
 }
@@ -1,10 +1,12 @@
 //! This module contains the definition of the abstract memory object type.
 
+use super::memory_mapping::MappingProtection;
 use super::{Data, ValueDomain};
 use crate::abstract_domain::*;
+use crate::intermediate_representation::{BinOpType, Tid};
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 /// Methods for manipulating abstract IDs contained in an abstract object.
@@ -37,6 +39,11 @@ struct Inner {
     pointer_targets: BTreeSet<AbstractIdentifier>,
     /// Tracks whether this may represent more than one actual memory object.
     is_unique: bool,
+    /// Tracks whether a pointer to the object may have escaped the function that created the object,
+    /// i.e. whether a pointer to it may have been written to memory outside of the current stack frame
+    /// or passed to a function call whose effects are not modeled by the analysis.
+    /// If the flag is not set, then all pointers to the object are tracked by the analysis.
+    has_escaped: bool,
     /// Is the object alive or already destroyed
     state: ObjectState,
     /// Is the object a stack frame or a heap object
@@ -51,15 +58,60 @@ struct Inner {
     /// A `Top` value represents an unknown bound.
     /// The bound is not enforced, i.e. reading and writing to indices violating the bound is still allowed.
     upper_index_bound: BitvectorDomain,
+    /// The TID of the call that allocated the object, if known.
+    allocation_site: Option<Tid>,
+    /// Information about the resize operation that created the object,
+    /// if the object was created by a call to `realloc`.
+    resize_info: Option<ResizeInfo>,
+    /// The memory protection of the object, if it is a mapped memory region.
+    mapping_protection: Option<MappingProtection>,
+    /// The size of the object as computed from the size parameters of the allocation call
+    /// at the time of the allocation, if the object was allocated by a call.
+    allocation_size: Option<ValueDomain>,
+    /// A symbolic upper bound on the length of the null-terminated string at the start of the object,
+    /// e.g. the return value of a `strlen` call on the object.
+    /// The object is also known to have room for a string of this length including the terminating null byte.
+    /// The bound is removed on every write to the object.
+    string_length_bound: Option<Data>,
 }
 
-/// An object is either a stack or a heap object.
+/// Information about the resize operation (i.e. a call to `realloc`) that created a heap object.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct ResizeInfo {
+    /// The TID of the call that resized the object.
+    pub resize_site: Tid,
+    /// The allocation site of the object before the resize operation, if known.
+    pub original_allocation_site: Option<Tid>,
+    /// The size in bytes of the object before the resize operation, if known.
+    pub old_size: Option<u64>,
+    /// The size in bytes of the object after the resize operation, if known.
+    /// If the new size is only known to lie in an interval, this is the upper end of the interval.
+    pub new_size: Option<u64>,
+}
+
+impl ResizeInfo {
+    /// Returns `true` if the object is known to have been shrunk by the resize operation.
+    pub fn is_shrinking(&self) -> bool {
+        matches!((self.old_size, self.new_size), (Some(old_size), Some(new_size)) if new_size < old_size)
+    }
+}
+
+/// An object is either a stack, a heap, an environment or a program argument object.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub enum ObjectType {
     /// A stack object, i.e. the stack frame of a function.
     Stack,
     /// A memory object located on the heap.
     Heap,
+    /// The value of an environment variable as returned by `getenv`-like functions.
+    /// It gets invalidated by modifications of the environment, e.g. by calls to `setenv`.
+    Environment,
+    /// The `argv` or `envp` array passed to the `main` function of the program.
+    /// Elements without a tracked value are assumed to point to [`ProgramArgument`](ObjectType::ProgramArgument) objects.
+    ArgumentVector,
+    /// The command line arguments or environment strings referenced by an argument vector.
+    /// Their contents are controlled by whoever started the program.
+    ProgramArgument,
 }
 
 /// An object is either alive or dangling (because the memory was freed or a function return invalidated the stack frame).
@@ -91,11 +143,17 @@ impl AbstractObject {
         let inner = Inner {
             pointer_targets: BTreeSet::new(),
             is_unique: true,
+            has_escaped: false,
             state: ObjectState::Alive,
             type_: Some(type_),
             memory: MemRegion::new(address_bytesize),
             lower_index_bound: BitvectorDomain::Top(address_bytesize),
             upper_index_bound: BitvectorDomain::Top(address_bytesize),
+            allocation_site: None,
+            resize_info: None,
+            mapping_protection: None,
+            allocation_size: None,
+            string_length_bound: None,
         };
         inner.into()
     }
@@ -110,6 +168,20 @@ impl AbstractObject {
     pub fn mark_as_not_unique(&mut self) {
         let inner = Arc::make_mut(&mut self.inner);
         inner.is_unique = false;
+        inner.string_length_bound = None;
+    }
+
+    /// Returns `true` if a pointer to the object may have escaped the function that created the object.
+    pub fn has_escaped(&self) -> bool {
+        self.inner.has_escaped
+    }
+
+    /// Mark the abstract object as possibly referenced by pointers not tracked by the analysis.
+    pub fn mark_as_escaped(&mut self) {
+        if !self.inner.has_escaped {
+            let inner = Arc::make_mut(&mut self.inner);
+            inner.has_escaped = true;
+        }
     }
 
     /// Set the lower index bound that is still considered to be contained in the abstract object.
@@ -124,6 +196,114 @@ impl AbstractObject {
         inner.upper_index_bound = upper_bound;
     }
 
+    /// Get the lower index bound that is still considered to be contained in the abstract object.
+    pub fn get_lower_index_bound(&self) -> &BitvectorDomain {
+        &self.inner.lower_index_bound
+    }
+
+    /// Get the upper index bound that is still considered to be contained in the abstract object.
+    pub fn get_upper_index_bound(&self) -> &BitvectorDomain {
+        &self.inner.upper_index_bound
+    }
+
+    /// Add the given offset to the indices of all values and to the index bounds of the object.
+    ///
+    /// This moves the contents of the object relative to the offsets of the pointers to it,
+    /// which is needed to merge objects whose base offsets relative to the same abstract ID differ.
+    pub fn add_offset_to_all_indices(&mut self, offset: i64) {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.memory.add_offset_to_all_indices(offset);
+        inner.string_length_bound = None;
+        let offset: BitvectorDomain = Bitvector::from_i64(offset)
+            .into_resize_signed(inner.memory.get_address_bytesize())
+            .into();
+        inner.lower_index_bound = inner.lower_index_bound.bin_op(BinOpType::IntAdd, &offset);
+        inner.upper_index_bound = inner.upper_index_bound.bin_op(BinOpType::IntAdd, &offset);
+    }
+
+    /// Get the TID of the call that allocated the object, if known.
+    pub fn get_allocation_site(&self) -> Option<&Tid> {
+        self.inner.allocation_site.as_ref()
+    }
+
+    /// Set the TID of the call that allocated the object.
+    pub fn set_allocation_site(&mut self, allocation_site: Tid) {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.allocation_site = Some(allocation_site);
+    }
+
+    /// Use the given thresholds as widening hints for all values contained in the object
+    /// (see [`IntervalDomain::add_widening_thresholds`]).
+    pub fn add_widening_thresholds(&mut self, thresholds: &[Bitvector]) {
+        let updated_values: Vec<(i64, Data)> = self
+            .inner
+            .memory
+            .iter()
+            .filter_map(|(offset, value)| {
+                let mut updated_value = value.clone();
+                updated_value.add_widening_thresholds(thresholds);
+                (updated_value != *value).then_some((*offset, updated_value))
+            })
+            .collect();
+        if updated_values.is_empty() {
+            // Do not copy shared objects if nothing changes.
+            return;
+        }
+        let inner = Arc::make_mut(&mut self.inner);
+        for (offset, value) in updated_values {
+            inner.memory.insert_at_byte_index(value, offset);
+        }
+    }
+
+    /// Get the information about the resize operation that created the object, if it was created by one.
+    pub fn get_resize_info(&self) -> Option<&ResizeInfo> {
+        self.inner.resize_info.as_ref()
+    }
+
+    /// Set the information about the resize operation that created the object.
+    pub fn set_resize_info(&mut self, resize_info: ResizeInfo) {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.resize_info = Some(resize_info);
+    }
+
+    /// Get the memory protection of the object, if it is a mapped memory region.
+    pub fn get_mapping_protection(&self) -> Option<&MappingProtection> {
+        self.inner.mapping_protection.as_ref()
+    }
+
+    /// Set the memory protection of the object.
+    pub fn set_mapping_protection(&mut self, mapping_protection: MappingProtection) {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.mapping_protection = Some(mapping_protection);
+    }
+
+    /// Get the size of the object at the time of its allocation, if it was allocated by a call.
+    pub fn get_allocation_size(&self) -> Option<&ValueDomain> {
+        self.inner.allocation_size.as_ref()
+    }
+
+    /// Set the size of the object at the time of its allocation.
+    pub fn set_allocation_size(&mut self, allocation_size: ValueDomain) {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.allocation_size = Some(allocation_size);
+    }
+
+    /// Get the symbolic upper bound on the length of the string at the start of the object, if known.
+    pub fn get_string_length_bound(&self) -> Option<&Data> {
+        self.inner.string_length_bound.as_ref()
+    }
+
+    /// Set the symbolic upper bound on the length of the string at the start of the object.
+    ///
+    /// The bound is only set for unique objects,
+    /// since the objects represented by a non-unique object may contain strings of different lengths.
+    pub fn set_string_length_bound(&mut self, bound: Data) {
+        if self.inner.is_unique {
+            let inner = Arc::make_mut(&mut self.inner);
+            inner.string_length_bound = Some(bound);
+        }
+    }
+
     /// Get the state of the memory object.
     pub fn get_state(&self) -> ObjectState {
         self.inner.state
@@ -209,6 +389,7 @@ impl AbstractDomain for AbstractObject {
                     .cloned()
                     .collect(),
                 is_unique: self.inner.is_unique && other.inner.is_unique,
+                has_escaped: self.inner.has_escaped || other.inner.has_escaped,
                 state: self.inner.state.merge(other.inner.state),
                 type_: same_or_none(&self.inner.type_, &other.inner.type_),
                 memory: self.inner.memory.merge(&other.inner.memory),
@@ -220,6 +401,28 @@ impl AbstractDomain for AbstractObject {
                     .inner
                     .upper_index_bound
                     .merge(&other.inner.upper_index_bound),
+                allocation_site: same_or_none(
+                    &self.inner.allocation_site,
+                    &other.inner.allocation_site,
+                ),
+                resize_info: same_or_none(&self.inner.resize_info, &other.inner.resize_info),
+                mapping_protection: match (
+                    &self.inner.mapping_protection,
+                    &other.inner.mapping_protection,
+                ) {
+                    (Some(protection), Some(other_protection)) => {
+                        Some(protection.merge(other_protection))
+                    }
+                    _ => None,
+                },
+                allocation_size: match (&self.inner.allocation_size, &other.inner.allocation_size) {
+                    (Some(size), Some(other_size)) => Some(size.merge(other_size)),
+                    _ => None,
+                },
+                string_length_bound: same_or_none(
+                    &self.inner.string_length_bound,
+                    &other.inner.string_length_bound,
+                ),
             }
             .into()
         }
@@ -240,6 +443,10 @@ impl AbstractObject {
                 "is_unique".to_string(),
                 serde_json::Value::String(format!("{}", self.inner.is_unique)),
             ),
+            (
+                "has_escaped".to_string(),
+                serde_json::Value::String(format!("{}", self.inner.has_escaped)),
+            ),
             (
                 "state".to_string(),
                 serde_json::Value::String(format!("{:?}", self.inner.state)),
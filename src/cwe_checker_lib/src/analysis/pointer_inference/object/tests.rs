@@ -6,11 +6,17 @@ fn new_abstract_object() -> AbstractObject {
     let inner = Inner {
         pointer_targets: BTreeSet::new(),
         is_unique: true,
+        has_escaped: false,
         state: ObjectState::Alive,
         type_: Some(ObjectType::Heap),
         memory: MemRegion::new(ByteSize::new(8)),
         lower_index_bound: Bitvector::from_u64(0).into(),
         upper_index_bound: Bitvector::from_u64(99).into(),
+        allocation_site: None,
+        resize_info: None,
+        mapping_protection: None,
+        allocation_size: None,
+        string_length_bound: None,
     };
     inner.into()
 }
@@ -52,7 +58,10 @@ fn abstract_object() {
     object.merge_value(new_data(23), &bv(-12));
     assert_eq!(
         object.get_value(Bitvector::from_i64(-12), ByteSize::new(8)),
-        IntervalDomain::mock(4, 23).with_stride(19).into()
+        IntervalDomain::mock(4, 23)
+            .with_stride(19)
+            .with_known_bits(4, !23)
+            .into()
     );
 
     let mut other_object = new_abstract_object();
@@ -63,7 +72,11 @@ fn abstract_object() {
         merged_object
             .get_value(Bitvector::from_i64(-12), ByteSize::new(8))
             .get_absolute_value(),
-        Some(&IntervalDomain::mock(4, 23).with_stride(19).into())
+        Some(
+            &IntervalDomain::mock(4, 23)
+                .with_stride(19)
+                .with_known_bits(4, !23)
+        )
     );
     assert!(merged_object
         .get_value(Bitvector::from_i64(-12), ByteSize::new(8))
@@ -145,3 +158,40 @@ fn access_contained_in_bounds() {
     assert!(object.access_contained_in_bounds(&IntervalDomain::mock(92, 92), ByteSize::new(8)));
     assert!(!object.access_contained_in_bounds(&IntervalDomain::mock(93, 93), ByteSize::new(8)));
 }
+
+#[test]
+fn escape_flag() {
+    let mut object = new_abstract_object();
+    assert!(!object.has_escaped());
+    let other_object = new_abstract_object();
+    assert!(!object.merge(&other_object).has_escaped());
+    object.mark_as_escaped();
+    assert!(object.has_escaped());
+    assert!(other_object.merge(&object).has_escaped());
+}
+
+#[test]
+fn string_length_bound() {
+    let mut object = new_abstract_object();
+    let length = Data::from_target(new_id("strlen_call", "RAX"), bv(0));
+    object.set_string_length_bound(length.clone());
+    assert_eq!(object.get_string_length_bound(), Some(&length));
+    // Merging keeps only equal bounds.
+    assert_eq!(
+        object.merge(&object).get_string_length_bound(),
+        Some(&length)
+    );
+    assert_eq!(
+        object
+            .merge(&new_abstract_object())
+            .get_string_length_bound(),
+        None
+    );
+    // Writes to the object may change the length of the string.
+    object.set_value(new_data(0), &bv(0)).unwrap();
+    assert_eq!(object.get_string_length_bound(), None);
+    // Non-unique objects do not get a bound.
+    object.mark_as_not_unique();
+    object.set_string_length_bound(length);
+    assert_eq!(object.get_string_length_bound(), None);
+}
@@ -39,17 +39,67 @@ impl AbstractObject {
         }
     }
 
+    /// Check whether a memory access to the abstract object at the given offset
+    /// and with the given size of the accessed value lies completely outside the bounds of the memory object
+    /// for all possible offset values.
+    /// Returns `false` if the offset or the violated bound is not exactly known.
+    pub fn access_disjoint_from_bounds(&self, offset: &ValueDomain, size: ByteSize) -> bool {
+        if let Ok((start, end)) = offset.try_to_offset_interval() {
+            if let Ok(lower_bound) = self.inner.lower_index_bound.try_to_offset() {
+                if let Some(max_index) = end.checked_add(u64::from(size) as i64 - 1) {
+                    if max_index < lower_bound {
+                        return true;
+                    }
+                }
+            }
+            if let Ok(upper_bound) = self.inner.upper_index_bound.try_to_offset() {
+                if start > upper_bound {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     /// Read the value at the given offset of the given size inside the memory region.
     pub fn get_value(&self, offset: Bitvector, bytesize: ByteSize) -> Data {
         self.inner.memory.get(offset, bytesize)
     }
 
+    /// Read an element of an argument vector object at the given (possibly inexact) offset.
+    ///
+    /// Elements without a tracked value are assumed to equal the first element of the vector,
+    /// since the entries of `argv` and `envp` arrays all point to strings of the same kind.
+    pub fn get_argument_vector_element(&self, offset: &ValueDomain, bytesize: ByteSize) -> Data {
+        let first_element = self
+            .inner
+            .memory
+            .get(Bitvector::zero(offset.bytesize().into()), bytesize);
+        match offset.try_to_bitvec() {
+            Ok(concrete_offset) => {
+                let value = self.inner.memory.get(concrete_offset, bytesize);
+                if value.is_top() {
+                    first_element
+                } else {
+                    value
+                }
+            }
+            Err(_) => first_element,
+        }
+    }
+
+    /// Get the number of values currently tracked in the memory region.
+    pub fn get_num_tracked_values(&self) -> usize {
+        self.inner.memory.entry_map().len()
+    }
+
     /// Write a value at the given offset to the memory region.
     ///
     /// If the abstract object is not unique (i.e. may represent more than one actual object),
     /// merge the old value at the given offset with the new value.
     pub fn set_value(&mut self, value: Data, offset: &ValueDomain) -> Result<(), Error> {
         let inner = Arc::make_mut(&mut self.inner);
+        inner.string_length_bound = None;
         inner
             .pointer_targets
             .extend(value.referenced_ids().cloned());
@@ -76,6 +126,7 @@ impl AbstractObject {
     /// Merge `value` at position `offset` with the value currently saved at that position.
     pub fn merge_value(&mut self, value: Data, offset: &ValueDomain) {
         let inner = Arc::make_mut(&mut self.inner);
+        inner.string_length_bound = None;
         inner
             .pointer_targets
             .extend(value.referenced_ids().cloned());
@@ -99,9 +150,130 @@ impl AbstractObject {
     /// which may include writing pointers to targets from the `additional_targets` set to the object.
     pub fn assume_arbitrary_writes(&mut self, additional_targets: &BTreeSet<AbstractIdentifier>) {
         let inner = Arc::make_mut(&mut self.inner);
+        inner.string_length_bound = None;
         inner.memory.mark_all_values_as_top();
         inner
             .pointer_targets
             .extend(additional_targets.iter().cloned());
     }
+
+    /// Marks the values in one field of each element of an array of structs in the object as `Top`
+    /// and adds the `additional_targets` to the pointer targets.
+    /// Represents the effect of unknown write instructions to these fields,
+    /// see [`MemRegion::mark_strided_values_as_top`](crate::abstract_domain::MemRegion::mark_strided_values_as_top)
+    /// for the meaning of the parameters.
+    ///
+    /// If the offset of the first field is not exactly known, all values of the object are marked as `Top`.
+    pub fn assume_strided_writes(
+        &mut self,
+        offset: &ValueDomain,
+        field_size: ByteSize,
+        stride: u64,
+        count: Option<u64>,
+        additional_targets: &BTreeSet<AbstractIdentifier>,
+    ) {
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.string_length_bound = None;
+        match offset.try_to_offset() {
+            Ok(start) => inner
+                .memory
+                .mark_strided_values_as_top(start, field_size, stride, count),
+            Err(_) => inner.memory.mark_all_values_as_top(),
+        }
+        inner
+            .pointer_targets
+            .extend(additional_targets.iter().cloned());
+    }
+
+    /// Marks all values from the given offset up to the upper index bound of the object as `Top`
+    /// and adds the `additional_targets` to the pointer targets.
+    /// Represents the effect of writes of unknown length starting at the offset, e.g. by `memcpy`.
+    ///
+    /// If the upper index bound is unknown, all values after the offset are marked as `Top`.
+    /// If the offset is not exactly known, all values of the object are marked as `Top`.
+    pub fn assume_writes_up_to_upper_bound(
+        &mut self,
+        offset: &ValueDomain,
+        additional_targets: &BTreeSet<AbstractIdentifier>,
+    ) {
+        let count = match (
+            offset.try_to_offset(),
+            self.inner.upper_index_bound.try_to_offset(),
+        ) {
+            (Ok(start), Ok(upper_bound)) => {
+                Some(upper_bound.saturating_sub(start).saturating_add(1).max(0) as u64)
+            }
+            _ => None,
+        };
+        self.assume_strided_writes(offset, ByteSize::new(1), 1, count, additional_targets);
+    }
+
+    /// Get the values contained in the interval of the memory region
+    /// starting at `start` and with the given byte size.
+    /// The positions of the returned values are relative to `start`.
+    /// Values only partially contained in the interval are not returned.
+    pub fn get_values_in_interval(&self, start: i64, size: u64) -> Vec<(i64, Data)> {
+        let end = start.saturating_add(size as i64);
+        self.inner
+            .memory
+            .entry_map()
+            .range(start..end)
+            .filter(|(position, value)| **position + u64::from(value.bytesize()) as i64 <= end)
+            .map(|(position, value)| (*position - start, value.clone()))
+            .collect()
+    }
+
+    /// Overwrite the interval of the memory region starting at `start` and with the given byte size
+    /// with the given values, whose positions are relative to `start`,
+    /// and adds the `additional_targets` to the pointer targets.
+    /// Bytes in the interval not covered by one of the values are marked as `Top`.
+    ///
+    /// If the abstract object is not unique (i.e. may represent more than one actual object),
+    /// the new values are merged with the old values.
+    pub fn overwrite_interval(
+        &mut self,
+        start: i64,
+        size: u64,
+        values: Vec<(i64, Data)>,
+        additional_targets: &BTreeSet<AbstractIdentifier>,
+    ) {
+        if size == 0 {
+            return;
+        }
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.string_length_bound = None;
+        inner
+            .pointer_targets
+            .extend(additional_targets.iter().cloned());
+        for (_, value) in values.iter() {
+            inner
+                .pointer_targets
+                .extend(value.referenced_ids().cloned());
+        }
+        let values: Vec<(i64, Data)> = if inner.is_unique {
+            inner
+                .memory
+                .clear_offset_interval(start, start, ByteSize::new(size));
+            values
+        } else {
+            let merged_values = values
+                .into_iter()
+                .map(|(position, value)| {
+                    let old_value = inner.memory.get(
+                        Bitvector::from_i64(start + position)
+                            .into_resize_signed(inner.memory.get_address_bytesize()),
+                        value.bytesize(),
+                    );
+                    (position, old_value.merge(&value))
+                })
+                .collect();
+            inner
+                .memory
+                .mark_interval_values_as_top(start, start, ByteSize::new(size));
+            merged_values
+        };
+        for (position, value) in values {
+            inner.memory.insert_at_byte_index(value, start + position);
+        }
+    }
 }
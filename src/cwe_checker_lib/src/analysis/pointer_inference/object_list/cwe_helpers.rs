@@ -3,8 +3,34 @@
 //! E.g. checks for use-after-free or buffer overflow checks.
 
 use super::*;
+use crate::analysis::pointer_inference::memory_access_checks::BoundsClassification;
+use crate::utils::log::ExploitPrimitive;
 
 impl AbstractObjectList {
+    /// Returns `true` if the string that `source` points to is known to fit into the memory object
+    /// that `destination` points to, including its terminating null byte.
+    ///
+    /// This is the case if both pointers point to objects with string length bounds
+    /// relative to the same symbolic string length (e.g. the return value of a `strlen` call)
+    /// and the bound of the destination is at least as large as the bound of the source.
+    pub fn is_string_copy_in_bounds(&self, destination: &Data, source: &Data) -> bool {
+        match (
+            self.get_string_length_bound(destination)
+                .and_then(Data::get_if_unique_target),
+            self.get_string_length_bound(source)
+                .and_then(Data::get_if_unique_target),
+        ) {
+            (Some((destination_id, destination_bound)), Some((source_id, source_bound))) => {
+                destination_id == source_id
+                    && matches!(
+                        (destination_bound.try_to_offset(), source_bound.try_to_offset()),
+                        (Ok(destination_bound), Ok(source_bound)) if destination_bound >= source_bound
+                    )
+            }
+            _ => false,
+        }
+    }
+
     /// Check the state of a memory object at a given address.
     /// Returns `true` if at least one of the targets of the pointer is dangling.
     /// If `report_unknown_states` is `true`,
@@ -29,6 +55,33 @@ impl AbstractObjectList {
         false
     }
 
+    /// Returns `true` if at least one of the targets of the pointer is an environment object
+    /// that may have been invalidated by a modification of the environment.
+    ///
+    /// Like for [`AbstractObjectList::is_dangling_pointer`] objects with unknown states are only reported if they are unique.
+    pub fn is_expired_environment_pointer(&self, address: &Data) -> bool {
+        address
+            .referenced_ids()
+            .any(|id| match self.objects.get(id) {
+                Some((object, _)) if object.get_object_type() == Some(ObjectType::Environment) => {
+                    match object.get_state() {
+                        ObjectState::Dangling => true,
+                        ObjectState::Unknown => object.is_unique(),
+                        _ => false,
+                    }
+                }
+                _ => false,
+            })
+    }
+
+    /// Returns `true` if at least one of the targets of the pointer is a command line argument or environment string
+    /// passed to the `main` function of the program, i.e. if the pointed-to content may be externally controlled.
+    pub fn is_program_argument_pointer(&self, pointer: &Data) -> bool {
+        pointer.referenced_ids().any(|id| {
+            matches!(self.objects.get(id), Some((object, _)) if object.get_object_type() == Some(ObjectType::ProgramArgument))
+        })
+    }
+
     /// Mark all memory objects targeted by the given `address` pointer,
     /// whose state is either dangling or unknown,
     /// as flagged.
@@ -55,30 +108,198 @@ impl AbstractObjectList {
         size: ByteSize,
         global_data: &RuntimeMemoryImage,
     ) -> bool {
+        self.classify_mem_access(address, size, global_data)
+            .is_out_of_bounds()
+    }
+
+    /// Classify a memory access at the given address (and accessing `size` many bytes)
+    /// with respect to the bounds of all memory objects that it may access.
+    ///
+    /// Targets with `Top` offsets, targets not contained in the object list
+    /// and absolute values that are not intervals are not checked.
+    /// If no target can be checked at all, the access is classified as accessing an unknown object.
+    pub fn classify_mem_access(
+        &self,
+        address: &Data,
+        size: ByteSize,
+        global_data: &RuntimeMemoryImage,
+    ) -> BoundsClassification {
+        let mut classification = BoundsClassification::UnknownObject;
         if let Some(value) = address.get_absolute_value() {
             if let Ok((start, end)) = value.try_to_offset_interval() {
-                if start < 0 || end < start {
-                    return true;
-                }
-                if global_data
+                let target_classification = if start < 0 || end < start {
+                    if end < 0 {
+                        BoundsClassification::DefinitelyOutOfBounds
+                    } else {
+                        BoundsClassification::PossiblyOutOfBounds
+                    }
+                } else if global_data
                     .is_interval_readable(start as u64, end as u64 + u64::from(size) - 1)
                     .is_err()
                 {
-                    return true;
-                }
+                    if start == end {
+                        BoundsClassification::DefinitelyOutOfBounds
+                    } else {
+                        BoundsClassification::PossiblyOutOfBounds
+                    }
+                } else {
+                    BoundsClassification::InBounds
+                };
+                classification = classification.combine(target_classification);
             }
         }
         for (id, offset) in address.get_relative_values() {
             if let Some((object, base_offset)) = self.objects.get(id) {
                 let adjusted_offset = offset.clone() + base_offset.clone();
-                if !adjusted_offset.is_top()
-                    && !object.access_contained_in_bounds(&adjusted_offset, size)
+                if adjusted_offset.is_top() {
+                    continue;
+                }
+                let target_classification =
+                    if object.access_contained_in_bounds(&adjusted_offset, size) {
+                        BoundsClassification::InBounds
+                    } else if object.access_disjoint_from_bounds(&adjusted_offset, size) {
+                        BoundsClassification::DefinitelyOutOfBounds
+                    } else {
+                        BoundsClassification::PossiblyOutOfBounds
+                    };
+                classification = classification.combine(target_classification);
+            }
+        }
+        classification
+    }
+
+    /// Get the allocation sites of all heap objects of unknown size that the given address may point to.
+    ///
+    /// Since the upper bound of these objects is unknown,
+    /// accesses to them cannot be classified as out of bounds by [`Self::classify_mem_access`].
+    pub fn get_allocation_sites_of_targets_with_unknown_size(&self, address: &Data) -> Vec<Tid> {
+        address
+            .get_relative_values()
+            .keys()
+            .filter_map(|id| {
+                let (object, _) = self.objects.get(id)?;
+                if object.get_object_type() == Some(ObjectType::Heap)
+                    && object.get_upper_index_bound().is_top()
                 {
-                    return true;
+                    object.get_allocation_site().cloned()
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// If a memory access at the given address (and accessing `size` many bytes)
+    /// may be out of bounds of a memory object that was shrunk by a resize operation,
+    /// return the information about the resize operation.
+    ///
+    /// This helps to explain out-of-bounds accesses that use bounds that were valid before the resize operation.
+    pub fn get_shrink_info_of_out_of_bounds_mem_access(
+        &self,
+        address: &Data,
+        size: ByteSize,
+    ) -> Option<&ResizeInfo> {
+        for (id, offset) in address.get_relative_values() {
+            if let Some((object, base_offset)) = self.objects.get(id) {
+                let adjusted_offset = offset.clone() + base_offset.clone();
+                match object.get_resize_info() {
+                    Some(resize_info)
+                        if resize_info.is_shrinking()
+                            && !adjusted_offset.is_top()
+                            && !object.access_contained_in_bounds(&adjusted_offset, size) =>
+                    {
+                        return Some(resize_info)
+                    }
+                    _ => (),
                 }
             }
         }
-        false
+        None
+    }
+
+    /// Classify the exploit primitive that a memory access at the given address
+    /// (and accessing `size` many bytes) may provide if it is out of bounds.
+    ///
+    /// Addresses containing *Top* or non-constant absolute values
+    /// and unbounded offsets into memory objects are classified as arbitrary writes.
+    /// For targets where the access may be out of bounds,
+    /// constant offsets are classified as fixed-offset overflows
+    /// and all other offsets as controlled offsets.
+    /// The most severe class of all targets is returned.
+    /// Returns `None` if the access cannot be out of bounds for any target.
+    pub fn get_exploit_primitive_of_out_of_bounds_access(
+        &self,
+        address: &Data,
+        size: ByteSize,
+    ) -> Option<ExploitPrimitive> {
+        if address.contains_top() {
+            return Some(ExploitPrimitive::ArbitraryWrite);
+        }
+        if let Some(value) = address.get_absolute_value() {
+            if value.try_to_bitvec().is_err() {
+                return Some(ExploitPrimitive::ArbitraryWrite);
+            }
+        }
+        let mut primitive = None;
+        for (id, offset) in address.get_relative_values() {
+            if let Some((object, base_offset)) = self.objects.get(id) {
+                let adjusted_offset = offset.clone() + base_offset.clone();
+                let target_primitive = if adjusted_offset.try_to_offset_interval().is_err() {
+                    ExploitPrimitive::ArbitraryWrite
+                } else if object.access_contained_in_bounds(&adjusted_offset, size) {
+                    continue;
+                } else if adjusted_offset.try_to_offset().is_ok() {
+                    ExploitPrimitive::FixedOffsetOverflow
+                } else {
+                    ExploitPrimitive::ControlledOffset
+                };
+                primitive = std::cmp::max(primitive, Some(target_primitive));
+            }
+        }
+        primitive
+    }
+
+    /// Returns `true` if a memory access at the given address (and accessing `size` many bytes)
+    /// is guaranteed to access exactly one memory object with known lower and upper bounds
+    /// and if all possible offsets of the access are contained in these bounds.
+    pub fn is_bounded_mem_access_to_unique_object(&self, address: &Data, size: ByteSize) -> bool {
+        if address.contains_top() || address.get_absolute_value().is_some() {
+            return false;
+        }
+        let (id, offset) = match address.get_if_unique_target() {
+            Some(target) => target,
+            None => return false,
+        };
+        match self.objects.get(id) {
+            Some((object, base_offset)) => {
+                let adjusted_offset = offset.clone() + base_offset.clone();
+                object.is_unique()
+                    && !object.get_lower_index_bound().is_top()
+                    && !object.get_upper_index_bound().is_top()
+                    && !adjusted_offset.is_top()
+                    && object.access_contained_in_bounds(&adjusted_offset, size)
+            }
+            None => false,
+        }
+    }
+
+    /// Get the compact JSON representations of all memory objects that the given pointer may point to.
+    /// The keys of the returned map have the same format as in [`AbstractObjectList::to_json_compact`].
+    pub fn get_target_objects_json_compact(
+        &self,
+        pointer: &Data,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        pointer
+            .referenced_ids()
+            .filter_map(|id| {
+                self.objects.get(id).map(|(object, offset)| {
+                    (
+                        format!("{} (base offset {})", id, offset),
+                        object.to_json_compact(),
+                    )
+                })
+            })
+            .collect()
     }
 
     /// Set the lower index bound for indices to be considered inside the memory object.
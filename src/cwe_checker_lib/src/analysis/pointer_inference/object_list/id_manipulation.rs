@@ -26,6 +26,68 @@ impl AbstractObjectList {
         }
     }
 
+    /// Limit the number of relative targets of all values contained in the memory objects to `max_targets`
+    /// by collapsing targets into their summary IDs (see [`Data::summarize_targets`]).
+    /// Objects for the used summary IDs are created by merging the collapsed objects
+    /// (see [`AbstractObjectList::add_summary_objects`]).
+    ///
+    /// Since merging collapsed objects may produce values with too many targets again,
+    /// this is repeated until no more targets get collapsed.
+    pub fn summarize_pointer_targets(
+        &mut self,
+        max_targets: usize,
+        excluded_ids: &BTreeSet<AbstractIdentifier>,
+    ) {
+        loop {
+            let mut summaries: BTreeMap<AbstractIdentifier, BTreeSet<AbstractIdentifier>> =
+                BTreeMap::new();
+            for (object, _) in self.objects.values_mut() {
+                for (summary_id, collapsed_ids) in
+                    object.summarize_pointer_targets(max_targets, excluded_ids)
+                {
+                    summaries
+                        .entry(summary_id)
+                        .or_default()
+                        .extend(collapsed_ids);
+                }
+            }
+            if summaries.is_empty() {
+                return;
+            }
+            self.add_summary_objects(&summaries);
+        }
+    }
+
+    /// Create the objects corresponding to summary IDs.
+    ///
+    /// `summaries` maps each summary ID to the IDs collapsed into it.
+    /// The object of a summary ID is the merge of the objects of the collapsed IDs
+    /// and of the already existing object of the summary ID.
+    /// Since it represents more than one object, it is marked as non-unique.
+    /// The objects of the collapsed IDs are kept, since other values may still point to them.
+    pub fn add_summary_objects(
+        &mut self,
+        summaries: &BTreeMap<AbstractIdentifier, BTreeSet<AbstractIdentifier>>,
+    ) {
+        for (summary_id, collapsed_ids) in summaries {
+            let mut summary_object = self.objects.get(summary_id).cloned();
+            for id in collapsed_ids {
+                if let Some((object, offset)) = self.objects.get(id) {
+                    summary_object = Some(match summary_object {
+                        Some((summary_object, summary_offset)) => {
+                            (summary_object.merge(object), summary_offset.merge(offset))
+                        }
+                        None => (object.clone(), offset.clone()),
+                    });
+                }
+            }
+            if let Some((mut object, offset)) = summary_object {
+                object.mark_as_not_unique();
+                self.objects.insert(summary_id.clone(), (object, offset));
+            }
+        }
+    }
+
     /// Return all IDs that may be referenced by the memory object pointed to by the given ID.
     /// The returned set is an overapproximation of the actual referenced IDs.
     pub fn get_referenced_ids_overapproximation(
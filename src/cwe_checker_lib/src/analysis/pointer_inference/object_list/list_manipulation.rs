@@ -64,6 +64,24 @@ impl AbstractObjectList {
         }
     }
 
+    /// Replace the objects of self with the corresponding objects of another object list,
+    /// except for the objects with the given IDs.
+    /// Objects not known to self are not added.
+    pub fn restore_objects_except(
+        &mut self,
+        other_object_list: &AbstractObjectList,
+        ids_to_skip: &BTreeSet<AbstractIdentifier>,
+    ) {
+        for (id, object) in self.objects.iter_mut() {
+            if ids_to_skip.contains(id) {
+                continue;
+            }
+            if let Some(other_object) = other_object_list.objects.get(id) {
+                *object = other_object.clone();
+            }
+        }
+    }
+
     /// Remove the provided IDs as targets from all pointers in all objects.
     /// Also remove the objects, that these IDs point to.
     pub fn remove_ids(&mut self, ids_to_remove: &BTreeSet<AbstractIdentifier>) {
@@ -1,5 +1,7 @@
+use super::memory_mapping::MappingProtection;
 use super::object::*;
 use super::{Data, ValueDomain};
+use crate::intermediate_representation::Tid;
 use crate::prelude::*;
 use crate::{abstract_domain::*, utils::binary::RuntimeMemoryImage};
 use serde::{Deserialize, Serialize};
@@ -57,7 +59,10 @@ impl AbstractObjectList {
         for (id, offset_pointer) in address.get_relative_values() {
             if let Some((object, offset_identifier)) = self.objects.get(id) {
                 let offset = offset_pointer.clone() + offset_identifier.clone();
-                if let Ok(concrete_offset) = offset.try_to_bitvec() {
+                if object.get_object_type() == Some(ObjectType::ArgumentVector) {
+                    let value = object.get_argument_vector_element(&offset, size);
+                    merged_value = merged_value.merge(&value);
+                } else if let Ok(concrete_offset) = offset.try_to_bitvec() {
                     let value = object.get_value(concrete_offset, size);
                     merged_value = merged_value.merge(&value);
                 } else {
@@ -118,6 +123,101 @@ impl AbstractObjectList {
         }
     }
 
+    /// Assume that unknown values were written to one field of each element of an array of structs
+    /// that the pointer points to, including pointers to targets contained in `new_possible_reference_targets`.
+    ///
+    /// The pointer points to the first field.
+    /// The fields have the byte size `field_size` and a distance of `stride` bytes between each other.
+    /// If the number of array elements is not known, all elements after the pointer are assumed to be written.
+    /// Other values in the targets of the pointer are not changed.
+    pub fn assume_strided_writes(
+        &mut self,
+        pointer: &Data,
+        field_size: ByteSize,
+        stride: u64,
+        count: Option<u64>,
+        new_possible_reference_targets: &BTreeSet<AbstractIdentifier>,
+    ) {
+        for (id, offset) in pointer.get_relative_values() {
+            if let Some((object, object_offset)) = self.objects.get_mut(id) {
+                let adjusted_offset = offset.clone() + object_offset.clone();
+                object.assume_strided_writes(
+                    &adjusted_offset,
+                    field_size,
+                    stride,
+                    count,
+                    new_possible_reference_targets,
+                );
+            }
+        }
+    }
+
+    /// Assume that unknown values were written to the targets of the pointer
+    /// from the pointer up to the upper index bound of each target,
+    /// including pointers to targets contained in `new_possible_reference_targets`.
+    ///
+    /// This represents the effect of a memory copy operation like `memcpy` with unknown length.
+    /// Values before the pointer in its targets are not changed.
+    pub fn assume_writes_up_to_upper_bound(
+        &mut self,
+        pointer: &Data,
+        new_possible_reference_targets: &BTreeSet<AbstractIdentifier>,
+    ) {
+        for (id, offset) in pointer.get_relative_values() {
+            if let Some((object, object_offset)) = self.objects.get_mut(id) {
+                let adjusted_offset = offset.clone() + object_offset.clone();
+                object.assume_writes_up_to_upper_bound(
+                    &adjusted_offset,
+                    new_possible_reference_targets,
+                );
+            }
+        }
+    }
+
+    /// Copy the values of `size` bytes starting at `source_offset` in the source object
+    /// to the destination object starting at `destination_offset`, like `memcpy` does.
+    /// The offsets are relative to the zero offsets of the corresponding abstract identifiers.
+    ///
+    /// Values only partially contained in the copied range are not copied,
+    /// i.e. the corresponding bytes in the destination are marked as `Top`.
+    /// If the position of the source range inside the source object is not exactly known,
+    /// the whole destination range is marked as `Top`.
+    /// If the position of the destination range is not exactly known,
+    /// all values of the destination object are marked as `Top`.
+    pub fn copy_range_between_objects(
+        &mut self,
+        source_id: &AbstractIdentifier,
+        source_offset: i64,
+        destination_id: &AbstractIdentifier,
+        destination_offset: i64,
+        size: u64,
+    ) {
+        let (values, source_targets) = match self.objects.get(source_id) {
+            Some((object, object_offset)) => match object_offset.try_to_offset() {
+                Ok(object_offset) => (
+                    object.get_values_in_interval(source_offset + object_offset, size),
+                    BTreeSet::new(),
+                ),
+                Err(_) => (
+                    Vec::new(),
+                    object.get_referenced_ids_overapproximation().clone(),
+                ),
+            },
+            None => (Vec::new(), BTreeSet::new()),
+        };
+        if let Some((object, object_offset)) = self.objects.get_mut(destination_id) {
+            match object_offset.try_to_offset() {
+                Ok(object_offset) => object.overwrite_interval(
+                    destination_offset + object_offset,
+                    size,
+                    values,
+                    &source_targets,
+                ),
+                Err(_) => object.assume_arbitrary_writes(&source_targets),
+            }
+        }
+    }
+
     /// Return the object type of a memory object.
     /// Returns an error if no object with the given ID is contained in the object list.
     pub fn get_object_type(
@@ -130,6 +230,157 @@ impl AbstractObjectList {
         }
     }
 
+    /// Return the size in bytes of the memory object with the given ID
+    /// if its upper index bound is exactly known.
+    ///
+    /// Since the lower index bound of heap objects is zero, the size is computed from the upper index bound.
+    pub fn get_object_size(&self, object_id: &AbstractIdentifier) -> Option<u64> {
+        let (object, base_offset) = self.objects.get(object_id)?;
+        let upper_bound = object.get_upper_index_bound().try_to_bitvec().ok()?;
+        let base_offset = base_offset.try_to_bitvec().ok()?;
+        let size = upper_bound - &base_offset + &Bitvector::one(base_offset.width());
+        size.try_to_u64().ok()
+    }
+
+    /// Return the number of values tracked in the memory object with the given ID.
+    /// Returns zero if the object is not contained in the object list.
+    pub fn get_num_tracked_values(&self, object_id: &AbstractIdentifier) -> usize {
+        self.objects
+            .get(object_id)
+            .map(|(object, _)| object.get_num_tracked_values())
+            .unwrap_or(0)
+    }
+
+    /// Return the TID of the call that allocated the memory object with the given ID, if known.
+    pub fn get_allocation_site(&self, object_id: &AbstractIdentifier) -> Option<&Tid> {
+        let (object, _) = self.objects.get(object_id)?;
+        object.get_allocation_site()
+    }
+
+    /// Set the TID of the call that allocated the memory object with the given ID.
+    pub fn set_allocation_site(&mut self, object_id: &AbstractIdentifier, allocation_site: Tid) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.set_allocation_site(allocation_site);
+        }
+    }
+
+    /// Use the given thresholds as widening hints for all values contained in the memory object with the given ID.
+    pub fn add_widening_thresholds(
+        &mut self,
+        object_id: &AbstractIdentifier,
+        thresholds: &[Bitvector],
+    ) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.add_widening_thresholds(thresholds);
+        }
+    }
+
+    /// Return the state of the memory object with the given ID
+    /// or `None` if the object is not contained in the object list.
+    pub fn get_object_state(&self, object_id: &AbstractIdentifier) -> Option<ObjectState> {
+        let (object, _) = self.objects.get(object_id)?;
+        Some(object.get_state())
+    }
+
+    /// Set the state of the memory object with the given ID.
+    /// For non-unique objects the new state is merged with the old state.
+    pub fn set_object_state(&mut self, object_id: &AbstractIdentifier, state: ObjectState) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.set_state(state);
+        }
+    }
+
+    /// Get the IDs of all environment objects contained in the object list.
+    pub fn get_environment_object_ids(&self) -> Vec<AbstractIdentifier> {
+        self.objects
+            .iter()
+            .filter(|(_, (object, _))| object.get_object_type() == Some(ObjectType::Environment))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Mark the memory object with the given ID as representing more than one actual memory object.
+    pub fn mark_as_not_unique(&mut self, object_id: &AbstractIdentifier) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.mark_as_not_unique();
+        }
+    }
+
+    /// Set the information about the resize operation that created the memory object with the given ID.
+    pub fn set_resize_info(&mut self, object_id: &AbstractIdentifier, resize_info: ResizeInfo) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.set_resize_info(resize_info);
+        }
+    }
+
+    /// Get the memory protection of the memory object with the given ID, if it is a mapped memory region.
+    pub fn get_mapping_protection(
+        &self,
+        object_id: &AbstractIdentifier,
+    ) -> Option<&MappingProtection> {
+        let (object, _) = self.objects.get(object_id)?;
+        object.get_mapping_protection()
+    }
+
+    /// Set the memory protection of the memory object with the given ID.
+    pub fn set_mapping_protection(
+        &mut self,
+        object_id: &AbstractIdentifier,
+        mapping_protection: MappingProtection,
+    ) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.set_mapping_protection(mapping_protection);
+        }
+    }
+
+    /// Get the size of the memory object with the given ID at the time of its allocation,
+    /// if it was allocated by a call.
+    pub fn get_allocation_size(&self, object_id: &AbstractIdentifier) -> Option<&ValueDomain> {
+        let (object, _) = self.objects.get(object_id)?;
+        object.get_allocation_size()
+    }
+
+    /// Set the size of the memory object with the given ID at the time of its allocation.
+    pub fn set_allocation_size(
+        &mut self,
+        object_id: &AbstractIdentifier,
+        allocation_size: ValueDomain,
+    ) {
+        if let Some((object, _)) = self.objects.get_mut(object_id) {
+            object.set_allocation_size(allocation_size);
+        }
+    }
+
+    /// Get the symbolic upper bound on the length of the string that the given pointer points to, if known.
+    ///
+    /// Bounds are only tracked for strings at the start of a memory object,
+    /// i.e. the pointer has to point exactly to the zero offset of a unique target.
+    pub fn get_string_length_bound(&self, pointer: &Data) -> Option<&Data> {
+        let object_id = self.get_string_start_target(pointer)?;
+        let (object, _) = self.objects.get(object_id)?;
+        object.get_string_length_bound()
+    }
+
+    /// Set the symbolic upper bound on the length of the string that the given pointer points to.
+    ///
+    /// Nothing happens if the pointer does not point exactly to the zero offset of a unique target.
+    pub fn set_string_length_bound(&mut self, pointer: &Data, bound: Data) {
+        if let Some(object_id) = self.get_string_start_target(pointer).cloned() {
+            if let Some((object, _)) = self.objects.get_mut(&object_id) {
+                object.set_string_length_bound(bound);
+            }
+        }
+    }
+
+    /// Get the ID of the object that the given pointer points to
+    /// if the pointer has a unique target and points to the start of that object.
+    fn get_string_start_target<'b>(&self, pointer: &'b Data) -> Option<&'b AbstractIdentifier> {
+        let (object_id, offset) = pointer.get_if_unique_target()?;
+        let (_, object_offset) = self.objects.get(object_id)?;
+        (matches!(offset.try_to_offset(), Ok(0)) && matches!(object_offset.try_to_offset(), Ok(0)))
+            .then_some(object_id)
+    }
+
     /// Returns `true` if the object corresponding to the given ID represents an unique object
     /// and `false` if it may represent more than one object (e.g. several array elements).
     /// Returns an error if the ID is not contained in the object list.
@@ -139,6 +390,28 @@ impl AbstractObjectList {
             None => Err(anyhow!("Object ID not contained in object list.")),
         }
     }
+
+    /// Returns `true` if a pointer to the object corresponding to the given ID
+    /// may have escaped the function that created the object.
+    /// Returns an error if the ID is not contained in the object list.
+    pub fn has_escaped(&self, object_id: &AbstractIdentifier) -> Result<bool, Error> {
+        match self.objects.get(object_id) {
+            Some((object, _)) => Ok(object.has_escaped()),
+            None => Err(anyhow!("Object ID not contained in object list.")),
+        }
+    }
+
+    /// Mark the heap objects corresponding to the given IDs as escaped,
+    /// i.e. pointers to them may exist outside of the memory tracked by the analysis.
+    pub fn mark_heap_objects_as_escaped(&mut self, object_ids: &BTreeSet<AbstractIdentifier>) {
+        for id in object_ids {
+            if let Some((object, _)) = self.objects.get_mut(id) {
+                if object.get_object_type() == Some(ObjectType::Heap) {
+                    object.mark_as_escaped();
+                }
+            }
+        }
+    }
 }
 
 impl AbstractDomain for AbstractObjectList {
@@ -153,8 +426,21 @@ impl AbstractDomain for AbstractObjectList {
         let mut merged_objects = self.objects.clone();
         for (id, (other_object, other_offset)) in other.objects.iter() {
             if let Some((object, offset)) = merged_objects.get_mut(id) {
-                *object = object.merge(other_object);
-                *offset = offset.merge(other_offset);
+                match (offset.try_to_offset(), other_offset.try_to_offset()) {
+                    (Ok(exact_offset), Ok(exact_other_offset))
+                        if exact_offset != exact_other_offset =>
+                    {
+                        // Move the contents of the other object to the base offset of this object,
+                        // so that values at the same offset relative to the ID get merged.
+                        let mut other_object = other_object.clone();
+                        other_object.add_offset_to_all_indices(exact_offset - exact_other_offset);
+                        *object = object.merge(&other_object);
+                    }
+                    _ => {
+                        *object = object.merge(other_object);
+                        *offset = offset.merge(other_offset);
+                    }
+                }
             } else {
                 merged_objects.insert(id.clone(), (other_object.clone(), other_offset.clone()));
             }
@@ -68,7 +68,10 @@ fn abstract_object_list() {
         .unwrap();
     assert_eq!(
         merged.get_value(&pointer, ByteSize::new(8)),
-        IntervalDomain::mock(3, 42).with_stride(39).into()
+        IntervalDomain::mock(3, 42)
+            .with_stride(39)
+            .with_known_bits(2, !43)
+            .into()
     );
     assert_eq!(
         merged.get_value(&heap_pointer, ByteSize::new(8)),
@@ -140,6 +143,67 @@ fn abstract_object_list() {
     );
 }
 
+#[test]
+fn merge_objects_with_different_base_offsets() {
+    let heap_id = new_id("RAX");
+    let first_field = DataDomain::from_target(heap_id.clone(), bv(0));
+    let second_field = DataDomain::from_target(heap_id.clone(), bv(8));
+    let mut obj_list = AbstractObjectList::from_stack_id(new_id("RSP"), ByteSize::new(8));
+    obj_list.add_abstract_object(heap_id.clone(), bv(0), ObjectType::Heap, ByteSize::new(8));
+    obj_list
+        .set_value(first_field.clone(), bv(1).into())
+        .unwrap();
+    obj_list
+        .set_value(second_field.clone(), bv(2).into())
+        .unwrap();
+    let mut other_obj_list = AbstractObjectList::from_stack_id(new_id("RSP"), ByteSize::new(8));
+    other_obj_list.add_abstract_object(heap_id.clone(), bv(16), ObjectType::Heap, ByteSize::new(8));
+    other_obj_list
+        .set_value(first_field.clone(), bv(1).into())
+        .unwrap();
+    other_obj_list
+        .set_value(second_field.clone(), bv(3).into())
+        .unwrap();
+
+    // Values at the same offset relative to the ID are merged and the base offset stays exact.
+    let merged = obj_list.merge(&other_obj_list);
+    assert_eq!(merged.objects.get(&heap_id).unwrap().1, bv(0));
+    assert_eq!(
+        merged.get_value(&first_field, ByteSize::new(8)),
+        bv(1).into()
+    );
+    assert_eq!(
+        merged.get_value(&second_field, ByteSize::new(8)),
+        IntervalDomain::mock(2, 3).into()
+    );
+}
+
+#[test]
+fn copy_range_between_objects() {
+    let mut obj_list = AbstractObjectList::from_stack_id(new_id("RSP"), ByteSize::new(8));
+    obj_list.add_abstract_object(new_id("RAX"), bv(0), ObjectType::Heap, ByteSize::new(8));
+    let stack_pointer = |offset: i64| DataDomain::from_target(new_id("RSP"), bv(offset));
+    let heap_pointer = |offset: i64| DataDomain::from_target(new_id("RAX"), bv(offset));
+    obj_list
+        .set_value(stack_pointer(-16), bv(1).into())
+        .unwrap();
+    obj_list.set_value(stack_pointer(-8), bv(2).into()).unwrap();
+    obj_list
+        .set_value(stack_pointer(-4), Bitvector::from_i32(3).into())
+        .unwrap();
+    obj_list.set_value(heap_pointer(8), bv(4).into()).unwrap();
+    obj_list.set_value(heap_pointer(16), bv(5).into()).unwrap();
+    obj_list.set_value(heap_pointer(24), bv(6).into()).unwrap();
+
+    // The value at offset -8 is only partially contained in the copied range.
+    obj_list.copy_range_between_objects(&new_id("RSP"), -16, &new_id("RAX"), 8, 12);
+    let size = ByteSize::new(8);
+    assert_eq!(obj_list.get_value(&heap_pointer(8), size), bv(1).into());
+    assert!(obj_list.get_value(&heap_pointer(16), size).contains_top());
+    assert_eq!(obj_list.get_value(&heap_pointer(24), size), bv(6).into());
+    assert_eq!(obj_list.get_value(&stack_pointer(-16), size), bv(1).into());
+}
+
 #[test]
 fn append_unknown_objects_test() {
     let mut obj_list = AbstractObjectList::from_stack_id(new_id("stack"), ByteSize::new(8));
@@ -157,3 +221,60 @@ fn append_unknown_objects_test() {
     assert!(obj_list.objects.get(&new_id("stack")).is_some());
     assert!(obj_list.objects.get(&new_id("heap_obj")).is_some());
 }
+
+#[test]
+fn summarize_pointer_targets() {
+    let new_heap_id = |name: &str, address: &str| {
+        let mut time = Tid::new(name);
+        time.address = address.to_string();
+        AbstractIdentifier::new(
+            time,
+            AbstractLocation::Register(Variable::mock("RAX", ByteSize::new(8))),
+        )
+    };
+    let stack_id = new_id("RSP");
+    let first_id = new_heap_id("malloc_call", "0x100");
+    let second_id = new_heap_id("malloc_call_dup", "0x100");
+    let other_id = new_heap_id("other_call", "0x200");
+    let summary_id = first_id.get_summary_id().unwrap();
+    let mut obj_list = AbstractObjectList::from_stack_id(stack_id.clone(), ByteSize::new(8));
+    for id in [&first_id, &second_id, &other_id] {
+        obj_list.add_abstract_object(id.clone(), bv(0), ObjectType::Heap, ByteSize::new(8));
+    }
+    obj_list
+        .set_value(
+            DataDomain::from_target(first_id.clone(), bv(0)),
+            bv(1).into(),
+        )
+        .unwrap();
+    obj_list
+        .set_value(
+            DataDomain::from_target(second_id.clone(), bv(0)),
+            bv(1).into(),
+        )
+        .unwrap();
+    let mut pointer = DataDomain::from_target(first_id.clone(), bv(0));
+    pointer.insert_relative_value(second_id.clone(), bv(0));
+    pointer.insert_relative_value(other_id.clone(), bv(0));
+    obj_list
+        .set_value(DataDomain::from_target(stack_id.clone(), bv(-8)), pointer)
+        .unwrap();
+
+    obj_list.summarize_pointer_targets(2, &BTreeSet::new());
+    let summarized_pointer =
+        obj_list.get_value(&DataDomain::from_target(stack_id, bv(-8)), ByteSize::new(8));
+    assert_eq!(summarized_pointer.get_relative_values().len(), 2);
+    assert!(summarized_pointer
+        .get_relative_values()
+        .contains_key(&summary_id));
+    // The summary object is the non-unique merge of the collapsed objects.
+    assert!(!obj_list.is_unique_object(&summary_id).unwrap());
+    assert_eq!(
+        obj_list.get_value(
+            &DataDomain::from_target(summary_id.clone(), bv(0)),
+            ByteSize::new(8)
+        ),
+        bv(1).into()
+    );
+    assert!(obj_list.objects.contains_key(&first_id));
+}
@@ -0,0 +1,263 @@
+//! Attribution of out-of-bounds warnings to the incoming control flow paths of the flagged instruction.
+//!
+//! If the block containing a flagged memory access has several predecessors,
+//! the state at the start of the block is the merge of the states of all predecessors
+//! and the warning does not show which of the incoming paths causes the out-of-bounds access.
+//! For such warnings the bounds check is re-evaluated on the contribution of each predecessor
+//! to the state at the start of the block.
+//! The predecessors whose states violate the bound on their own are noted in the evidence of the warning.
+//! If no predecessor violates the bound on its own, only the merge of their states does.
+//! This usually indicates imprecision of the analysis rather than a bug.
+//!
+//! Predecessors on loop back edges are marked as such,
+//! since their states are derived from the (possibly widened) state at the loop head.
+//! A violation of the bound by them may thus be caused by widening.
+
+use super::{PointerInference, State};
+use crate::analysis::fixpoint::Context as _;
+use crate::analysis::graph::{Edge, Graph, Node};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::intermediate_representation::*;
+use crate::utils::log::CweWarning;
+use petgraph::algo::has_path_connecting;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{EdgeFiltered, EdgeRef, IntoNodeReferences};
+use petgraph::Direction;
+use std::collections::HashMap;
+
+/// The names of the CWE warnings generated by the bounds checks of the pointer inference.
+const BOUNDS_CHECK_CWES: [&str; 3] = ["CWE119", "CWE125", "CWE787"];
+
+/// The position of a flagged term in its block.
+#[derive(Clone, Copy)]
+enum TermLocation<'a> {
+    /// The index of a `Def` term in the block.
+    Def(usize),
+    /// A jump term of the block, i.e. a call whose parameters may point out of bounds.
+    Jmp(&'a Term<Jmp>),
+}
+
+/// The result of re-evaluating a bounds check on the state contributed by a single predecessor.
+struct PredecessorAttribution {
+    /// The address of the predecessor block.
+    address: String,
+    /// Whether the access is out of bounds in the state contributed by the predecessor.
+    violates_bound: bool,
+    /// Whether the predecessor is reachable from the flagged block, i.e. lies on a loop back edge.
+    is_loop_back_edge: bool,
+}
+
+/// Add the attribution of the warning to the predecessors of the flagged block
+/// to the evidence of all out-of-bounds warnings generated for instructions in blocks with several predecessors.
+///
+/// Needs the node values of the predecessors,
+/// i.e. it has to be called before node values are discarded under the node value retention policy.
+pub fn attribute_warnings_to_predecessors(
+    pointer_inference: &PointerInference,
+    warnings: &mut [CweWarning],
+) {
+    let graph = pointer_inference.get_graph();
+    let term_locations = get_term_locations(graph);
+    for warning in warnings
+        .iter_mut()
+        .filter(|warning| BOUNDS_CHECK_CWES.contains(&warning.name.as_str()))
+    {
+        let locations = match warning.tids.first().and_then(|tid| term_locations.get(tid)) {
+            Some(locations) => locations,
+            None => continue,
+        };
+        let mut attributions = Vec::new();
+        for (node, location) in locations {
+            let predecessor_states = get_predecessor_states(pointer_inference, *node);
+            if predecessor_states.len() < 2 {
+                continue;
+            }
+            let block = graph[*node].get_block();
+            for (predecessor, state) in predecessor_states {
+                attributions.push(PredecessorAttribution {
+                    address: get_node_block(&graph[predecessor])
+                        .tid
+                        .canonical_address()
+                        .to_string(),
+                    violates_bound: violates_bound(pointer_inference, block, *location, state),
+                    is_loop_back_edge: is_loop_back_edge(graph, *node, predecessor),
+                });
+            }
+        }
+        if attributions.is_empty() {
+            continue;
+        }
+        warning.evidence = Some(
+            warning
+                .evidence
+                .take()
+                .unwrap_or_default()
+                .value("path_attribution", to_json(&attributions)),
+        );
+    }
+}
+
+/// Map the TIDs (as formatted in CWE warnings) of all terms in the graph
+/// to the `BlkStart` nodes of the blocks containing them and their position in the block.
+fn get_term_locations<'a>(
+    graph: &Graph<'a>,
+) -> HashMap<String, Vec<(NodeIndex, TermLocation<'a>)>> {
+    let mut term_locations: HashMap<String, Vec<(NodeIndex, TermLocation)>> = HashMap::new();
+    for (node, node_weight) in graph.node_references() {
+        let block = match node_weight {
+            Node::BlkStart(block, _) => block,
+            _ => continue,
+        };
+        for (index, def) in block.term.defs.iter().enumerate() {
+            term_locations
+                .entry(format!("{}", def.tid))
+                .or_default()
+                .push((node, TermLocation::Def(index)));
+        }
+        for jmp in block.term.jmps.iter() {
+            term_locations
+                .entry(format!("{}", jmp.tid))
+                .or_default()
+                .push((node, TermLocation::Jmp(jmp)));
+        }
+    }
+    term_locations
+}
+
+/// Get the contributions of all predecessors of the given node to the state at the node,
+/// i.e. the states at the predecessors transformed by the transition functions of the incoming edges.
+fn get_predecessor_states(
+    pointer_inference: &PointerInference,
+    node: NodeIndex,
+) -> Vec<(NodeIndex, State)> {
+    let context = pointer_inference.computation.get_context();
+    pointer_inference
+        .get_graph()
+        .edges_directed(node, Direction::Incoming)
+        .filter_map(|edge| {
+            let value = pointer_inference.get_node_value(edge.source())?;
+            match context.update_edge(value, edge.id())? {
+                NodeValue::Value(state) => Some((edge.source(), state)),
+                NodeValue::CallFlowCombinator { .. } => None,
+            }
+        })
+        .collect()
+}
+
+/// Re-evaluate the bounds check of the term at the given location in the block,
+/// starting with the given state at the start of the block.
+fn violates_bound(
+    pointer_inference: &PointerInference,
+    block: &Term<Blk>,
+    location: TermLocation,
+    mut state: State,
+) -> bool {
+    let context = pointer_inference.get_context();
+    let global_memory = context.runtime_memory_image;
+    match location {
+        TermLocation::Def(index) => {
+            // The transfer functions classify the access on the state after the instruction.
+            state.apply_defs(&block.term.defs[..=index], global_memory);
+            state
+                .classify_mem_access(&block.term.defs[index].term, global_memory)
+                .is_some_and(|record| record.classification.is_out_of_bounds())
+        }
+        TermLocation::Jmp(jmp) => {
+            let extern_symbol = match &jmp.term {
+                Jmp::Call { target, .. } => match context.get_extern_symbol(target) {
+                    Some(extern_symbol) => extern_symbol,
+                    None => return false,
+                },
+                _ => return false,
+            };
+            state.apply_defs(&block.term.defs, global_memory);
+            extern_symbol.parameters.iter().any(|parameter| {
+                state
+                    .eval_parameter_arg(parameter, global_memory)
+                    .is_ok_and(|data| {
+                        state
+                            .classify_pointer_parameter(&data, global_memory)
+                            .classification
+                            .is_out_of_bounds()
+                    })
+            })
+        }
+    }
+}
+
+/// Returns `true` if the predecessor is reachable from the node by intraprocedural control flow.
+fn is_loop_back_edge(graph: &Graph, node: NodeIndex, predecessor: NodeIndex) -> bool {
+    let intraprocedural_graph = EdgeFiltered::from_fn(graph, |edge| {
+        !matches!(edge.weight(), Edge::Call(_) | Edge::CrReturnStub)
+    });
+    has_path_connecting(&intraprocedural_graph, node, predecessor, None)
+}
+
+/// Get the block that the control flow of a node originates from.
+fn get_node_block<'a>(node: &Node<'a>) -> &'a Term<Blk> {
+    match node {
+        Node::BlkStart(block, _) | Node::BlkEnd(block, _) => block,
+        Node::CallReturn {
+            call: (block, _), ..
+        } => block,
+        Node::CallSource {
+            source: (block, _), ..
+        } => block,
+    }
+}
+
+/// Generate the JSON representation of the attributions together with an explanation of the result.
+fn to_json(attributions: &[PredecessorAttribution]) -> serde_json::Value {
+    let predecessors = attributions
+        .iter()
+        .map(|attribution| {
+            let mut entry = serde_json::Map::new();
+            entry.insert(
+                "address".to_string(),
+                serde_json::Value::String(attribution.address.clone()),
+            );
+            entry.insert(
+                "violates_bound".to_string(),
+                serde_json::Value::Bool(attribution.violates_bound),
+            );
+            entry.insert(
+                "loop_back_edge".to_string(),
+                serde_json::Value::Bool(attribution.is_loop_back_edge),
+            );
+            serde_json::Value::Object(entry)
+        })
+        .collect();
+    let violating_addresses = |is_loop_back_edge: bool| -> Vec<&str> {
+        attributions
+            .iter()
+            .filter(|attribution| {
+                attribution.violates_bound && attribution.is_loop_back_edge == is_loop_back_edge
+            })
+            .map(|attribution| attribution.address.as_str())
+            .collect()
+    };
+    let explanation = match (violating_addresses(false), violating_addresses(true)) {
+        (forward_addresses, _) if !forward_addresses.is_empty() => format!(
+            "The bound is violated by the states from the predecessors at {}.",
+            forward_addresses.join(", ")
+        ),
+        (_, back_edge_addresses) if !back_edge_addresses.is_empty() => format!(
+            "The bound is only violated by the states from the loop back edges at {}. \
+            These states are derived from the possibly widened state at the loop head.",
+            back_edge_addresses.join(", ")
+        ),
+        _ => "No predecessor violates the bound on its own, only the merge of their states does. \
+            This usually indicates imprecision of the analysis rather than a bug."
+            .to_string(),
+    };
+    let mut json = serde_json::Map::new();
+    json.insert(
+        "predecessors".to_string(),
+        serde_json::Value::Array(predecessors),
+    );
+    json.insert(
+        "explanation".to_string(),
+        serde_json::Value::String(explanation),
+    );
+    serde_json::Value::Object(json)
+}
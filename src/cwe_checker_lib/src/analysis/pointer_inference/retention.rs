@@ -0,0 +1,109 @@
+//! Policies for the node values that the pointer inference keeps after the fixpoint computation.
+//!
+//! The node values of the fixpoint are kept in the analysis results,
+//! so that the CWE checks can query the states of the pointer inference at specific program points.
+//! For large binaries the node values take up most of the memory used by the analysis results.
+//! Since many checks only query the states at call sites and at memory accesses,
+//! the node values that no enabled check queries can be discarded after the fixpoint has been computed.
+
+use crate::analysis::graph::{Edge, Graph, Node};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+
+/// Determines which node values of the fixpoint are kept
+/// after the pointer inference computation finished.
+///
+/// The policies are ordered by the number of node values that they keep,
+/// i.e. a policy keeps all node values kept by smaller policies.
+#[derive(
+    Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Default,
+)]
+pub enum NodeValueRetention {
+    /// Keep only the states at the start of function entry blocks
+    /// and at the end of blocks returning to the caller.
+    EntryExitOnly,
+    /// Keep the states at the start and end of blocks containing calls or memory accesses
+    /// and the states at the return sites of calls.
+    /// Together with the states kept by `EntryExitOnly`,
+    /// this suffices to reconstruct the state before each call and each `Load` or `Store` instruction.
+    CallSitesAndAccesses,
+    /// Keep all node values.
+    #[default]
+    All,
+}
+
+impl std::fmt::Display for NodeValueRetention {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NodeValueRetention::EntryExitOnly => write!(formatter, "EntryExitOnly"),
+            NodeValueRetention::CallSitesAndAccesses => write!(formatter, "CallSitesAndAccesses"),
+            NodeValueRetention::All => write!(formatter, "All"),
+        }
+    }
+}
+
+impl NodeValueRetention {
+    /// Returns `true` if the value of the given node is kept under this policy.
+    pub fn retains_node(&self, graph: &Graph, node: NodeIndex) -> bool {
+        match self {
+            NodeValueRetention::All => true,
+            NodeValueRetention::CallSitesAndAccesses => {
+                is_entry_or_exit_node(&graph[node])
+                    || is_call_or_access_node(&graph[node])
+                    || is_call_return_site(graph, node)
+            }
+            NodeValueRetention::EntryExitOnly => is_entry_or_exit_node(&graph[node]),
+        }
+    }
+}
+
+/// Returns `true` if the node is the start of the entry block of a function
+/// or the end of a block returning to the caller.
+fn is_entry_or_exit_node(node: &Node) -> bool {
+    match node {
+        Node::BlkStart(block, sub) => sub
+            .term
+            .blocks
+            .first()
+            .is_some_and(|entry_block| entry_block.tid == block.tid),
+        Node::BlkEnd(block, _) => block
+            .term
+            .jmps
+            .iter()
+            .any(|jmp| matches!(jmp.term, Jmp::Return(_))),
+        _ => false,
+    }
+}
+
+/// Returns `true` if the node is an artificial call node
+/// or the start or end of a block containing a call or a memory access.
+fn is_call_or_access_node(node: &Node) -> bool {
+    match node {
+        Node::BlkStart(block, _) | Node::BlkEnd(block, _) => {
+            block
+                .term
+                .defs
+                .iter()
+                .any(|def| matches!(def.term, Def::Load { .. } | Def::Store { .. }))
+                || block.term.jmps.iter().any(|jmp| {
+                    matches!(
+                        jmp.term,
+                        Jmp::Call { .. } | Jmp::CallInd { .. } | Jmp::CallOther { .. }
+                    )
+                })
+        }
+        Node::CallSource { .. } | Node::CallReturn { .. } => true,
+    }
+}
+
+/// Returns `true` if the node is the start of a block that a call returns to.
+fn is_call_return_site(graph: &Graph, node: NodeIndex) -> bool {
+    graph.edges_directed(node, Direction::Incoming).any(|edge| {
+        matches!(
+            edge.weight(),
+            Edge::ExternCallStub(_) | Edge::ReturnCombine(_)
+        )
+    })
+}
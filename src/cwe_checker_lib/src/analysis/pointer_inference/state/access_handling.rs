@@ -1,6 +1,12 @@
 //! Methods of [`State`] for handling memory and register access operations.
 
+use crate::analysis::pointer_inference::access_histogram::{AccessKind, ObjectAccess};
+use crate::analysis::pointer_inference::memory_access_checks::{
+    BoundsCheckedAccess, BoundsClassification, MemoryAccessRecord,
+};
+use crate::analysis::pointer_inference::object::{ObjectType, ResizeInfo};
 use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{ExploitPrimitive, WarningContext};
 
 use super::*;
 
@@ -23,9 +29,101 @@ impl State {
         }
     }
 
+    /// Get all accesses to heap objects that the given `Def` term performs when executed on `self`.
+    ///
+    /// For each heap object that the address of a `Load` or `Store` instruction may point to,
+    /// an access with the corresponding offset interval, access size and access kind is returned.
+    /// Offsets are `None` if the offset interval is unbounded.
+    pub fn get_heap_object_accesses(&self, def: &Term<Def>) -> Vec<ObjectAccess> {
+        let (address, size, kind) = match &def.term {
+            Def::Load { var, address } => (address, var.size, AccessKind::Read),
+            Def::Store { address, value } => (address, value.bytesize(), AccessKind::Write),
+            Def::Assign { .. } => return Vec::new(),
+        };
+        let address = self.eval(address);
+        address
+            .get_relative_values()
+            .iter()
+            .filter(|(id, _)| matches!(self.memory.get_object_type(id), Ok(Some(ObjectType::Heap))))
+            .map(|(id, offset)| ObjectAccess {
+                object_id: id.clone(),
+                offset: offset.try_to_offset_interval().ok(),
+                size,
+                kind,
+            })
+            .collect()
+    }
+
     /// Evaluate expression on the given state and write the result to the target register.
+    ///
+    /// Writes to the lower bytes of a register that keep its upper bytes
+    /// (e.g. a write to `DIL` on x86_64, represented as `RDI = Piece(Subpiece(RDI), value)`)
+    /// keep the pointer targets of the register.
+    /// The offsets are widened by the maximal change of the pointer value caused by the write.
     pub fn handle_register_assign(&mut self, target: &Variable, expression: &Expression) {
-        self.set_register(target, self.eval(expression))
+        let value = self
+            .eval_partial_register_write(target, expression)
+            .unwrap_or_else(|| self.eval(expression));
+        self.set_register(target, value)
+    }
+
+    /// If the expression overwrites only the lower bytes of the target register,
+    /// evaluate it such that the pointer targets of the register value are kept.
+    /// Returns `None` for all other expressions or if the register does not contain pointers.
+    ///
+    /// The offsets are relative to the unknown base addresses of the targets,
+    /// so the lower bytes of an offset are not the lower bytes of the pointer.
+    /// Only the difference between the old and the new pointer value is known:
+    /// Overwriting the lowest `n` bits changes the pointer value by less than `2^n` in either direction.
+    fn eval_partial_register_write(
+        &self,
+        target: &Variable,
+        expression: &Expression,
+    ) -> Option<Data> {
+        let (upper_bytes, low_value) = match expression {
+            Expression::BinOp {
+                op: BinOpType::Piece,
+                lhs,
+                rhs,
+            } => (lhs.as_ref(), rhs.as_ref()),
+            _ => return None,
+        };
+        match upper_bytes {
+            Expression::Subpiece {
+                low_byte,
+                size,
+                arg,
+            } if **arg == Expression::Var(target.clone())
+                && *low_byte == low_value.bytesize()
+                && *low_byte + *size == target.size => {}
+            _ => return None,
+        }
+        let old_value = self.get_register(target);
+        if old_value.get_relative_values().is_empty() {
+            return None;
+        }
+        let low_value = self.eval(low_value);
+        let low_bits = u64::from(low_value.bytesize()) * 8;
+        let difference = if low_bits < 64 {
+            let max_difference =
+                Bitvector::from_u64((1 << low_bits) - 1).into_resize_unsigned(target.size);
+            ValueDomain::new(-max_difference.clone(), max_difference)
+        } else {
+            ValueDomain::new_top(target.size)
+        };
+        let relative_values = old_value
+            .get_relative_values()
+            .iter()
+            .map(|(id, offset)| (id.clone(), offset.bin_op(BinOpType::IntAdd, &difference)))
+            .collect();
+        let mut value = self.eval(expression);
+        value.set_relative_values(relative_values);
+        if old_value.contains_top() || low_value.contains_top() {
+            value.set_contains_top_flag();
+        } else {
+            value.unset_contains_top_flag();
+        }
+        Some(value)
     }
 
     /// Store `value` at the given `address`.
@@ -52,6 +150,15 @@ impl State {
             result
         } else {
             let pointer = self.adjust_pointer_for_read(address);
+            if self.is_escaping_address(&pointer) {
+                self.mark_referenced_heap_objects_as_escaped(value);
+            }
+            if pointer.contains_top()
+                && pointer.get_relative_values().is_empty()
+                && pointer.get_absolute_value().is_none()
+            {
+                self.weak_update_objects_exposed_to_unknown_writes(value);
+            }
             self.memory.set_value(pointer.clone(), value.clone())?;
             if let Some(absolute_address) = pointer.get_absolute_value() {
                 if let Ok(address_to_global_data) = absolute_address.try_to_bitvec() {
@@ -76,14 +183,90 @@ impl State {
         }
     }
 
+    /// Returns `true` if pointers written to the given address may escape the current function,
+    /// i.e. if the address may point to some memory other than the current stack frame.
+    fn is_escaping_address(&self, address: &Data) -> bool {
+        address.contains_top()
+            || address.get_absolute_value().is_some()
+            || address
+                .get_relative_values()
+                .keys()
+                .any(|id| *id != self.stack_id)
+    }
+
+    /// Get the IDs of all objects that a write through a completely unknown pointer may modify.
+    ///
+    /// These are all objects known to some caller, all heap objects that have escaped,
+    /// all other objects except for the current stack frame and non-escaped heap objects
+    /// and all objects (recursively) referenced by one of these objects.
+    /// Thus the current stack frame is only included if a pointer to it is reachable from an exposed object.
+    fn get_ids_of_objects_exposed_to_unknown_writes(&self) -> BTreeSet<AbstractIdentifier> {
+        let exposed_ids = self
+            .memory
+            .get_all_object_ids()
+            .into_iter()
+            .filter(|id| {
+                if self.ids_known_to_caller.contains(id) || self.caller_stack_ids.contains(id) {
+                    true
+                } else if *id == self.stack_id {
+                    false
+                } else {
+                    match self.memory.get_object_type(id) {
+                        Ok(Some(ObjectType::Heap)) => {
+                            matches!(self.memory.has_escaped(id), Ok(true))
+                        }
+                        _ => true,
+                    }
+                }
+            })
+            .collect();
+        self.add_recursively_referenced_ids_to_id_set(exposed_ids)
+    }
+
+    /// Handle the effect of writing `value` through a pointer that may point to arbitrary memory.
+    /// Stores through pointers with at least one known target only modify the known targets.
+    ///
+    /// All objects that the write may target are weakly updated,
+    /// i.e. all of their values are merged with `Top` instead of being removed.
+    /// Objects that cannot be reached through unknown pointers, like non-escaping heap objects
+    /// or the current stack frame if its address was never stored in memory, are not changed.
+    pub fn weak_update_objects_exposed_to_unknown_writes(&mut self, value: &Data) {
+        let additional_targets: BTreeSet<AbstractIdentifier> =
+            value.referenced_ids().cloned().collect();
+        for id in self.get_ids_of_objects_exposed_to_unknown_writes() {
+            self.memory
+                .assume_arbitrary_writes_to_object(&id, &additional_targets);
+        }
+    }
+
+    /// Mark all heap objects that are (recursively) referenced by the given value as escaped.
+    pub fn mark_referenced_heap_objects_as_escaped(&mut self, value: &Data) {
+        if value.get_relative_values().is_empty() {
+            return;
+        }
+        let escaped_ids = self
+            .add_recursively_referenced_ids_to_id_set(value.referenced_ids().cloned().collect());
+        self.memory.mark_heap_objects_as_escaped(&escaped_ids);
+    }
+
     /// Write a value to the address one gets when evaluating the address expression.
+    ///
+    /// If the address expression contains an `Unknown` placeholder for an untranslated operation,
+    /// the address is treated as completely unknown,
+    /// i.e. only the objects exposed to writes through unknown pointers are (weakly) updated.
+    /// Otherwise a target like the current stack frame would be invalidated as a whole
+    /// because of the unknown offset.
     pub fn write_to_address(
         &mut self,
         address: &Expression,
         value: &Data,
         global_memory: &RuntimeMemoryImage,
     ) -> Result<(), Error> {
-        let address_data = self.eval(address);
+        let address_data = if address.contains_unknown() {
+            Data::new_top(address.bytesize())
+        } else {
+            self.eval(address)
+        };
         self.store_value(&address_data, value, global_memory)
     }
 
@@ -160,6 +343,25 @@ impl State {
         }
     }
 
+    /// Apply the effects of the given `Def`s to the state in order.
+    /// Errors of individual loads and stores are ignored.
+    ///
+    /// This can be used to reconstruct the state at a position inside a block
+    /// from the state at the start of the block.
+    pub fn apply_defs(&mut self, defs: &[Term<Def>], global_memory: &RuntimeMemoryImage) {
+        for def in defs {
+            match &def.term {
+                Def::Assign { var, value } => self.handle_register_assign(var, value),
+                Def::Load { var, address } => {
+                    let _ = self.handle_load(var, address, global_memory);
+                }
+                Def::Store { address, value } => {
+                    let _ = self.handle_store(address, value, global_memory);
+                }
+            }
+        }
+    }
+
     /// If the pointer contains a reference to the stack with offset >= 0, replace it with a pointer
     /// pointing to all possible caller IDs.
     fn adjust_pointer_for_read(&self, address: &Data) -> Data {
@@ -220,6 +422,83 @@ impl State {
         }
     }
 
+    /// Evaluate the expression and return all abstract objects that the result may point to
+    /// together with the corresponding offsets.
+    ///
+    /// Addresses of global memory are returned as offsets relative to the
+    /// [global memory identifier](State::get_global_memory_id).
+    /// Values that are not known to point to any object (e.g. `Top` values) yield an empty vector.
+    pub fn eval_abstract_location(
+        &self,
+        expression: &Expression,
+        global_memory: &RuntimeMemoryImage,
+    ) -> Vec<(AbstractIdentifier, ValueDomain)> {
+        self.get_abstract_locations(&self.eval(expression), global_memory)
+    }
+
+    /// Load the value of the given size from the address that the expression evaluates to
+    /// and return all abstract objects that the loaded value may point to
+    /// together with the corresponding offsets.
+    ///
+    /// This resolves pointers stored in memory, e.g. on the stack.
+    /// See [`State::eval_abstract_location`] for the representation of the results.
+    pub fn eval_abstract_location_of_load(
+        &self,
+        address: &Expression,
+        size: ByteSize,
+        global_memory: &RuntimeMemoryImage,
+    ) -> Vec<(AbstractIdentifier, ValueDomain)> {
+        match self.load_value(address, size, global_memory) {
+            Ok(value) => self.get_abstract_locations(&value, global_memory),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Get the identifier representing global memory in the results of [`State::eval_abstract_location`].
+    /// The offsets relative to this identifier are the global addresses.
+    pub fn get_global_memory_id(address_bytesize: ByteSize) -> AbstractIdentifier {
+        AbstractIdentifier::new(
+            Tid::new("global_memory"),
+            AbstractLocation::Register(Variable {
+                name: "global_memory".to_string(),
+                size: address_bytesize,
+                is_temp: false,
+            }),
+        )
+    }
+
+    /// Get the abstract objects and offsets that the value may point to.
+    /// Absolute values are only considered if they may point to global memory.
+    fn get_abstract_locations(
+        &self,
+        value: &Data,
+        global_memory: &RuntimeMemoryImage,
+    ) -> Vec<(AbstractIdentifier, ValueDomain)> {
+        let mut locations: Vec<_> = value
+            .get_relative_values()
+            .iter()
+            .map(|(id, offset)| (id.clone(), offset.clone()))
+            .collect();
+        if let Some(absolute_value) = value.get_absolute_value() {
+            let is_global_address = if let Ok(address) = absolute_value.try_to_bitvec() {
+                global_memory.is_global_memory_address(&address)
+            } else if let Ok((start, end)) = absolute_value.try_to_offset_interval() {
+                global_memory
+                    .is_interval_readable(start as u64, end as u64)
+                    .is_ok()
+            } else {
+                false
+            };
+            if is_global_address {
+                locations.push((
+                    Self::get_global_memory_id(value.bytesize()),
+                    absolute_value.clone(),
+                ));
+            }
+        }
+        locations
+    }
+
     /// Evaluate the value of a parameter of an extern symbol for the given state.
     pub fn eval_parameter_arg(
         &self,
@@ -232,6 +511,36 @@ impl State {
         }
     }
 
+    /// If the argument is a stack argument,
+    /// return its offset relative to the current value of the given stack register.
+    ///
+    /// The address of the argument is evaluated through the state,
+    /// so that addresses relative to other registers (e.g. the frame pointer) can be handled
+    /// as long as the state knows their offset relative to the current stack frame.
+    /// Return an error for register arguments
+    /// or if the address or the stack register do not have an exactly known offset into the current stack frame.
+    pub fn eval_stack_offset_of_arg(
+        &self,
+        arg: &Arg,
+        stack_register: &Variable,
+    ) -> Result<Bitvector, Error> {
+        let address = match arg {
+            Arg::Register { .. } => return Err(anyhow!("The argument is not a stack argument.")),
+            Arg::Stack { address, .. } => address,
+        };
+        let get_stack_frame_offset = |value: &Data| -> Result<Bitvector, Error> {
+            match value.get_if_unique_target() {
+                Some((id, offset)) if *id == self.stack_id => offset.try_to_bitvec(),
+                _ => Err(anyhow!(
+                    "Value is not an exact offset into the current stack frame."
+                )),
+            }
+        };
+        let address_offset = get_stack_frame_offset(&self.eval(address))?;
+        let stack_register_offset = get_stack_frame_offset(&self.get_register(stack_register))?;
+        address_offset.bin_op(BinOpType::IntSub, &stack_register_offset)
+    }
+
     /// Check if an expression contains a use-after-free.
     /// If yes, mark the corresponding memory objects as flagged.
     pub fn contains_access_of_dangling_memory(&mut self, def: &Def) -> bool {
@@ -250,6 +559,17 @@ impl State {
         }
     }
 
+    /// Returns `true` if the given `Def` is a load or store instruction
+    /// which may access an environment object invalidated by a modification of the environment.
+    pub fn contains_access_of_expired_environment_pointer(&self, def: &Def) -> bool {
+        match def {
+            Def::Load { address, .. } | Def::Store { address, .. } => self
+                .memory
+                .is_expired_environment_pointer(&self.eval(address)),
+            _ => false,
+        }
+    }
+
     /// Returns `true` if the given `Def` is a load or store instruction
     /// which may access a memory object outside its bounds.
     pub fn contains_out_of_bounds_mem_access(
@@ -257,18 +577,108 @@ impl State {
         def: &Def,
         global_data: &RuntimeMemoryImage,
     ) -> bool {
+        self.classify_mem_access(def, global_data)
+            .map(|record| record.classification.is_out_of_bounds())
+            .unwrap_or(false)
+    }
+
+    /// Classify the memory access of the given load or store instruction
+    /// with respect to the bounds of the accessed memory objects.
+    /// Returns `None` if the `Def` is not a load or store instruction.
+    ///
+    /// Accesses to parameters or the return address of the function,
+    /// i.e. to non-negative offsets of the stack frame, are always classified as in bounds.
+    pub fn classify_mem_access(
+        &self,
+        def: &Def,
+        global_data: &RuntimeMemoryImage,
+    ) -> Option<MemoryAccessRecord> {
+        let (kind, raw_address, size) = match def {
+            Def::Load { address, var } => (BoundsCheckedAccess::Read, self.eval(address), var.size),
+            Def::Store { address, value } => (
+                BoundsCheckedAccess::Write,
+                self.eval(address),
+                value.bytesize(),
+            ),
+            Def::Assign { .. } => return None,
+        };
+        let classification = if self.is_stack_pointer_with_nonnegative_offset(&raw_address) {
+            BoundsClassification::InBounds
+        } else {
+            let address = self.adjust_pointer_for_read(&raw_address);
+            self.memory.classify_mem_access(&address, size, global_data)
+        };
+        Some(MemoryAccessRecord {
+            kind,
+            classification,
+        })
+    }
+
+    /// If the given `Def` is a load or store instruction which may access a memory object outside its bounds
+    /// and the object was shrunk by a resize operation, return the information about the resize operation.
+    pub fn get_shrink_info_of_out_of_bounds_mem_access(&self, def: &Def) -> Option<&ResizeInfo> {
         let (raw_address, size) = match def {
             Def::Load { address, var } => (self.eval(address), var.size),
             Def::Store { address, value } => (self.eval(address), value.bytesize()),
-            _ => return false,
+            _ => return None,
         };
-        if self.is_stack_pointer_with_nonnegative_offset(&raw_address) {
-            // Access to a parameter or the return address of the function
-            return false;
+        let address = self.adjust_pointer_for_read(&raw_address);
+        self.memory
+            .get_shrink_info_of_out_of_bounds_mem_access(&address, size)
+    }
+
+    /// If the given `Def` is a load or store instruction which may access a memory object outside its bounds,
+    /// classify the exploit primitive that the access may provide.
+    pub fn get_exploit_primitive_of_mem_access(&self, def: &Def) -> Option<ExploitPrimitive> {
+        let (raw_address, size) = match def {
+            Def::Load { address, var } => (self.eval(address), var.size),
+            Def::Store { address, value } => (self.eval(address), value.bytesize()),
+            _ => return None,
+        };
+        let address = self.adjust_pointer_for_read(&raw_address);
+        self.memory
+            .get_exploit_primitive_of_out_of_bounds_access(&address, size)
+    }
+
+    /// If the given call parameter may point outside of the bounds of a memory object,
+    /// classify the exploit primitive that the call may provide.
+    ///
+    /// If the offset of the pointer is constant but one of the given size arguments of the call is not,
+    /// the call may overflow the memory object linearly with a controlled size.
+    pub fn get_exploit_primitive_of_call_parameter(
+        &self,
+        pointer: &Data,
+        size_arguments: &[ValueDomain],
+    ) -> Option<ExploitPrimitive> {
+        let mut pointer = self.adjust_pointer_for_read(pointer);
+        pointer.set_absolute_value(None);
+        let primitive = self
+            .memory
+            .get_exploit_primitive_of_out_of_bounds_access(&pointer, ByteSize::new(1))?;
+        match primitive {
+            ExploitPrimitive::FixedOffsetOverflow
+                if size_arguments
+                    .iter()
+                    .any(|size| size.try_to_bitvec().is_err()) =>
+            {
+                Some(ExploitPrimitive::ControlledSizeLinearOverflow)
+            }
+            _ => Some(primitive),
         }
+    }
+
+    /// Returns `true` if the given `Def` is a load or store instruction
+    /// that accesses exactly one memory object with known bounds
+    /// and if the access is guaranteed to stay inside these bounds.
+    pub fn is_bounded_mem_access_to_unique_object(&self, def: &Def) -> bool {
+        let (raw_address, size) = match def {
+            Def::Load { address, var } => (self.eval(address), var.size),
+            Def::Store { address, value } => (self.eval(address), value.bytesize()),
+            _ => return false,
+        };
         let address = self.adjust_pointer_for_read(&raw_address);
         self.memory
-            .is_out_of_bounds_mem_access(&address, size, global_data)
+            .is_bounded_mem_access_to_unique_object(&address, size)
     }
 
     /// Returns `true` if `data` is a pointer pointing outside of the bounds of a memory buffer.
@@ -279,10 +689,69 @@ impl State {
         data: &Data,
         global_data: &RuntimeMemoryImage,
     ) -> bool {
+        self.classify_pointer_parameter(data, global_data)
+            .classification
+            .is_out_of_bounds()
+    }
+
+    /// Classify the given pointer parameter of a call to an extern function
+    /// with respect to the bounds of the memory objects that it may point to.
+    /// Absolute values are not checked, since they are too often no pointers at all.
+    pub fn classify_pointer_parameter(
+        &self,
+        data: &Data,
+        global_data: &RuntimeMemoryImage,
+    ) -> MemoryAccessRecord {
         let mut data = self.adjust_pointer_for_read(data);
         data.set_absolute_value(None); // Do not check absolute_values
+        MemoryAccessRecord {
+            kind: BoundsCheckedAccess::CallParameter,
+            classification: self
+                .memory
+                .classify_mem_access(&data, ByteSize::new(1), global_data),
+        }
+    }
+
+    /// Get the allocation sites of all heap objects of unknown size that the given pointer may point to.
+    /// Accesses to these objects cannot be checked against their upper bound.
+    pub fn get_allocation_sites_of_unknown_size_targets(&self, pointer: &Data) -> Vec<Tid> {
+        let pointer = self.adjust_pointer_for_read(pointer);
         self.memory
-            .is_out_of_bounds_mem_access(&data, ByteSize::new(1), global_data)
+            .get_allocation_sites_of_targets_with_unknown_size(&pointer)
+    }
+
+    /// Add the given pointer and the memory objects that it may point to
+    /// as an entry with the given name to the warning context.
+    pub fn add_pointer_evidence(
+        &self,
+        evidence: WarningContext,
+        name: &str,
+        pointer: &Data,
+    ) -> WarningContext {
+        let pointer = self.adjust_pointer_for_read(pointer);
+        let targets = self.memory.get_target_objects_json_compact(&pointer);
+        evidence.pointer_with_targets(name, &pointer, targets)
+    }
+
+    /// Collect the abstract facts about the memory access of the given load or store instruction
+    /// that are needed to reproduce a warning for it:
+    /// The target address (with the accessed memory objects), the access size
+    /// and the values of all registers used to compute the address.
+    pub fn get_mem_access_evidence(&self, def: &Def) -> WarningContext {
+        let (address, size) = match def {
+            Def::Load { address, var } => (address, var.size),
+            Def::Store { address, value } => (address, value.bytesize()),
+            Def::Assign { .. } => return WarningContext::new(),
+        };
+        let registers = address
+            .input_vars()
+            .into_iter()
+            .map(|var| (var.name.clone(), self.get_register(var).to_json_compact()))
+            .collect();
+        let evidence = WarningContext::new()
+            .value("access_size", serde_json::Value::from(u64::from(size)))
+            .value("registers", serde_json::Value::Object(registers));
+        self.add_pointer_evidence(evidence, "address", &self.eval(address))
     }
 
     /// Return `true` if `data` is a pointer to the current stack frame with a constant positive address,
@@ -322,6 +791,9 @@ impl State {
 
     /// Check whether the given `def` could result in a memory access through a NULL pointer.
     ///
+    /// Addresses that were checked to be unequal to zero (e.g. by a `if (ptr != NULL)` guard)
+    /// are not considered to be NULL pointers.
+    ///
     /// If no NULL pointer dereference is detected then `Ok(false)` is returned.
     /// If a NULL pointer dereference is detected,
     /// try to specialize the state so that `address_expr` cannot result in a NULL pointer anymore.
@@ -333,6 +805,14 @@ impl State {
             Def::Assign { .. } => return Ok(false),
         };
         let mut address_val = self.eval(address_expr);
+        let zero = Bitvector::zero(address_val.bytesize().into());
+        if let Some(true) = address_val
+            .get_absolute_value()
+            .map(|val| val.is_excluded(&zero))
+        {
+            // The address was already checked to be not NULL.
+            return Ok(false);
+        }
         if let Some((start_index, end_index)) = address_val
             .get_absolute_value()
             .map(|val| val.try_to_offset_interval().ok())
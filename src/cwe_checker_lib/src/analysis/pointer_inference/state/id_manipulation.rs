@@ -3,6 +3,39 @@
 use super::*;
 
 impl State {
+    /// Limit the number of relative targets of all values in registers and memory objects to `max_targets`
+    /// by collapsing targets with the same allocation site into their summary IDs
+    /// (see [`Data::summarize_targets`]).
+    /// Objects for the summary IDs are created by merging the collapsed objects.
+    ///
+    /// The IDs of the current and the caller stack frames are never collapsed,
+    /// since they need to stay exact for the replacement of stack IDs on calls and returns.
+    pub fn summarize_pointer_targets(&mut self, max_targets: usize) {
+        let mut excluded_ids = self.caller_stack_ids.clone();
+        excluded_ids.insert(self.stack_id.clone());
+        let mut summaries: BTreeMap<AbstractIdentifier, BTreeSet<AbstractIdentifier>> =
+            BTreeMap::new();
+        for value in self.register.values_mut() {
+            for (summary_id, collapsed_ids) in value.summarize_targets(max_targets, &excluded_ids) {
+                summaries
+                    .entry(summary_id)
+                    .or_default()
+                    .extend(collapsed_ids);
+            }
+        }
+        self.memory.add_summary_objects(&summaries);
+        self.memory
+            .summarize_pointer_targets(max_targets, &excluded_ids);
+        for id in summaries.keys() {
+            if summaries[id]
+                .iter()
+                .any(|collapsed_id| self.ids_known_to_caller.contains(collapsed_id))
+            {
+                self.ids_known_to_caller.insert(id.clone());
+            }
+        }
+    }
+
     /// Replace all occurences of old_id with new_id and adjust offsets accordingly.
     /// This is needed to replace stack/caller IDs on call and return instructions.
     ///
@@ -103,4 +136,36 @@ impl State {
             .cloned()
             .collect();
     }
+
+    /// Mark all memory objects (recursively) referenced by the given value as non-unique,
+    /// except for the current stack frame and the stack frames of callers.
+    ///
+    /// This is used for objects shared with another thread,
+    /// since the other thread may access them concurrently.
+    /// Stack frames are excluded to keep strong updates of local variables possible.
+    pub fn mark_shared_objects_as_not_unique(&mut self, value: &Data) {
+        let shared_ids = self
+            .add_recursively_referenced_ids_to_id_set(value.referenced_ids().cloned().collect());
+        for id in shared_ids.iter() {
+            if *id != self.stack_id && !self.caller_stack_ids.contains(id) {
+                self.memory.mark_as_not_unique(id);
+            }
+        }
+    }
+
+    /// Add the memory objects of `other` that are (recursively) referenced by `value` to `self`
+    /// and mark them as non-unique.
+    ///
+    /// Objects whose IDs are already known to `self` are not overwritten.
+    /// This is used to share memory objects with the start routine of a new thread.
+    pub fn add_shared_objects_from(&mut self, other: &State, value: &Data) {
+        let shared_ids = other
+            .add_recursively_referenced_ids_to_id_set(value.referenced_ids().cloned().collect());
+        let mut shared_objects = other.memory.clone();
+        shared_objects.remove_unused_objects(&shared_ids);
+        for id in shared_ids.iter() {
+            shared_objects.mark_as_not_unique(id);
+        }
+        self.memory.append_unknown_objects(&shared_objects);
+    }
 }
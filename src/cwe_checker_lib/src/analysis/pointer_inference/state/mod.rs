@@ -1,6 +1,10 @@
+use super::object::{ObjectState, ObjectType};
 use super::object_list::AbstractObjectList;
 use super::{Data, ValueDomain};
 use crate::abstract_domain::*;
+use crate::analysis::function_signature::{
+    FunctionPurity, OwnershipClassification, ParameterOwnership, PurityClassification,
+};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
 use crate::utils::binary::RuntimeMemoryImage;
@@ -96,6 +100,68 @@ impl State {
         state
     }
 
+    /// Create the entry state of the `main` function of the program
+    /// from the first three parameter registers `argc`, `argv` and `envp`.
+    ///
+    /// `argc` is a non-negative value fitting into a signed 32-bit integer.
+    /// `argv` and `envp` point to [argument vector objects](super::object::ObjectType::ArgumentVector) of unknown length,
+    /// whose elements point to the (externally controlled) command line argument or environment strings.
+    /// If fewer parameter registers are provided, only the corresponding parameters are seeded.
+    pub fn new_for_program_main(
+        stack_register: &Variable,
+        function_tid: Tid,
+        params: &[Variable],
+    ) -> State {
+        let mut state = State::new(stack_register, function_tid.clone());
+        let address_bytesize = stack_register.size;
+        let zero_offset: ValueDomain = Bitvector::zero(address_bytesize.into()).into();
+        if let Some(argc) = params.first() {
+            let argc_value = IntervalDomain::new(
+                Bitvector::zero(argc.size.into()),
+                Bitvector::from_i64(i32::MAX as i64).into_resize_unsigned(argc.size),
+            );
+            state.set_register(argc, argc_value.into());
+        }
+        for vector_param in params.iter().skip(1).take(2) {
+            let vector_id = AbstractIdentifier::new(
+                function_tid.clone(),
+                AbstractLocation::from_var(vector_param).unwrap(),
+            );
+            let string_id = AbstractIdentifier::new(
+                function_tid.clone(),
+                AbstractLocation::Pointer(
+                    vector_param.clone(),
+                    AbstractMemoryLocation::Location {
+                        offset: 0,
+                        size: address_bytesize,
+                    },
+                ),
+            );
+            state.memory.add_abstract_object(
+                vector_id.clone(),
+                zero_offset.clone(),
+                super::object::ObjectType::ArgumentVector,
+                address_bytesize,
+            );
+            state.memory.add_abstract_object(
+                string_id.clone(),
+                zero_offset.clone(),
+                super::object::ObjectType::ProgramArgument,
+                address_bytesize,
+            );
+            // The string object represents all strings referenced by the vector.
+            state.memory.mark_as_not_unique(&string_id);
+            let vector_pointer = Data::from_target(vector_id, zero_offset.clone());
+            let string_pointer = Data::from_target(string_id, zero_offset.clone());
+            state
+                .memory
+                .set_value(vector_pointer.clone(), string_pointer)
+                .unwrap();
+            state.set_register(vector_param, vector_pointer);
+        }
+        state
+    }
+
     /// Set the MIPS link register `t9` to the address of the callee TID.
     ///
     /// According to the System V ABI for MIPS the caller has to save the callee address in register `t9`
@@ -124,6 +190,17 @@ impl State {
         Ok(())
     }
 
+    /// Use the given thresholds as widening hints for the values in registers and in the current stack frame,
+    /// where loop counters are usually stored
+    /// (see [`IntervalDomain::add_widening_thresholds`]).
+    pub fn add_widening_thresholds(&mut self, thresholds: &[Bitvector]) {
+        for value in self.register.values_mut() {
+            value.add_widening_thresholds(thresholds);
+        }
+        self.memory
+            .add_widening_thresholds(&self.stack_id, thresholds);
+    }
+
     /// Clear all non-callee-saved registers from the state.
     /// This automatically also removes all virtual registers.
     /// The parameter is a list of callee-saved register names.
@@ -184,6 +261,43 @@ impl State {
         self.memory.remove_unused_objects(&referenced_ids);
     }
 
+    /// Remove the heap object with the given ID from the state if no pointer to it can exist anymore.
+    ///
+    /// This is only the case if the object has not escaped the current function,
+    /// is not known to any caller and is not referenced by any register or (reachable) memory object.
+    /// Like [`State::remove_unreferenced_objects`] this uses an underapproximation of the pointers
+    /// contained in memory objects.
+    ///
+    /// This is used before a new object is created for the same allocation site,
+    /// so that the new object does not have to be merged with the old (unreachable) object
+    /// and can still be marked as unique.
+    pub fn remove_object_if_unreachable_and_not_escaped(&mut self, object_id: &AbstractIdentifier) {
+        if !matches!(self.memory.has_escaped(object_id), Ok(false))
+            || !matches!(
+                self.memory.get_object_type(object_id),
+                Ok(Some(super::object::ObjectType::Heap))
+            )
+            || self.ids_known_to_caller.contains(object_id)
+        {
+            return;
+        }
+        let mut referenced_ids = BTreeSet::new();
+        for data in self.register.values() {
+            referenced_ids.extend(data.referenced_ids().cloned());
+        }
+        if referenced_ids.contains(object_id) {
+            return;
+        }
+        referenced_ids.insert(self.stack_id.clone());
+        referenced_ids.append(&mut self.caller_stack_ids.clone());
+        referenced_ids.append(&mut self.ids_known_to_caller.clone());
+        referenced_ids.remove(object_id);
+        referenced_ids = self.add_directly_reachable_ids_to_id_set(referenced_ids);
+        if !referenced_ids.contains(object_id) {
+            self.memory.remove_object(object_id);
+        }
+    }
+
     /// Merge the callee stack with the caller stack.
     ///
     /// This deletes the memory object corresponding to the callee_id
@@ -246,6 +360,7 @@ impl State {
     /// If the callee does not adhere to the given calling convention, this may introduce analysis errors!
     /// It will also mask cases
     /// where a callee-saved register was incorrectly modified (e.g. because of a bug in the callee).
+    /// Such cases are reported by the [calling convention verification](crate::analysis::function_signature::verify_calling_conventions).
     pub fn restore_callee_saved_register(
         &mut self,
         caller_state: &State,
@@ -261,6 +376,90 @@ impl State {
         }
     }
 
+    /// Restore the register values and memory objects of the caller state
+    /// that a call to a function with the given purity classification cannot have modified.
+    ///
+    /// Registers that the callee does not write to are restored with the exception of the stack register.
+    /// Functions that are [pure](FunctionPurity::Pure) or [only read memory](FunctionPurity::ReadsMemoryOnly)
+    /// do not modify any memory object of the caller, so all memory objects of the caller are restored.
+    /// For functions that [write to their arguments](FunctionPurity::WritesArgsOnly)
+    /// only the objects not reachable through the given parameter registers or the caller stack frame are restored.
+    /// Impure functions may modify anything, so nothing is restored for them.
+    pub fn restore_values_untouched_by_callee(
+        &mut self,
+        caller_state: &State,
+        callee: &PurityClassification,
+        parameter_register: &[&Variable],
+        stack_register: &Variable,
+    ) {
+        if callee.purity == FunctionPurity::Impure {
+            return;
+        }
+        for (register, value) in caller_state.register.iter() {
+            if register != stack_register
+                && !register.is_temp
+                && !callee.written_registers.contains(register)
+            {
+                self.set_register(register, value.clone());
+            }
+        }
+        let mut possibly_written_ids = BTreeSet::new();
+        if callee.purity == FunctionPurity::WritesArgsOnly {
+            possibly_written_ids.insert(caller_state.stack_id.clone());
+            for register in parameter_register {
+                possibly_written_ids.extend(
+                    caller_state
+                        .get_register(register)
+                        .referenced_ids()
+                        .cloned(),
+                );
+            }
+            possibly_written_ids =
+                caller_state.add_recursively_referenced_ids_to_id_set(possibly_written_ids);
+        }
+        self.memory
+            .restore_objects_except(&caller_state.memory, &possibly_written_ids);
+    }
+
+    /// Update the objects passed as arguments to a call according to the ownership classification of the callee.
+    ///
+    /// Objects freed by the callee are marked as dangling if the argument points to exactly one object
+    /// and as possibly freed otherwise.
+    /// Objects stored by the callee are marked as escaped.
+    /// Objects already flagged or known to be dangling are not changed,
+    /// since possible double frees were already reported inside the callee.
+    pub fn apply_parameter_ownership(
+        &mut self,
+        caller_state: &State,
+        callee: &OwnershipClassification,
+    ) {
+        for (parameter, ownership) in &callee.parameters {
+            let targets: BTreeSet<AbstractIdentifier> = caller_state
+                .get_register(parameter)
+                .referenced_ids()
+                .filter(|id| matches!(self.memory.get_object_type(id), Ok(Some(ObjectType::Heap))))
+                .cloned()
+                .collect();
+            let freed_state = match ownership {
+                ParameterOwnership::Borrowed => continue,
+                ParameterOwnership::Stored => {
+                    self.memory.mark_heap_objects_as_escaped(&targets);
+                    continue;
+                }
+                ParameterOwnership::Freed if targets.len() == 1 => ObjectState::Dangling,
+                ParameterOwnership::Freed | ParameterOwnership::MaybeFreed => ObjectState::Unknown,
+            };
+            for id in &targets {
+                if matches!(
+                    self.memory.get_object_state(id),
+                    Some(ObjectState::Alive | ObjectState::Unknown)
+                ) {
+                    self.memory.set_object_state(id, freed_state);
+                }
+            }
+        }
+    }
+
     /// Remove all knowledge about the contents of callee-saved registers from the state.
     pub fn remove_callee_saved_register(&mut self, cconv: &CallingConvention) {
         for register in &cconv.callee_saved_register {
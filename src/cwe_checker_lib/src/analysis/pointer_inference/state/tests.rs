@@ -201,6 +201,121 @@ fn handle_store() {
     );
 }
 
+#[test]
+fn partial_register_writes() {
+    let global_memory = RuntimeMemoryImage::mock();
+    let mut state = State::new(&register("RSP"), Tid::new("time0"));
+    let stack_id = new_id("time0", "RSP");
+    state.handle_register_assign(&register("RDI"), &reg_add("RSP", -0x20));
+    // `MOV DIL, 0` overwrites only the lowest byte of the pointer in RDI.
+    let write_low_byte = |low_value: Expression| Expression::BinOp {
+        op: BinOpType::Piece,
+        lhs: Box::new(Expression::Subpiece {
+            low_byte: ByteSize::new(1),
+            size: ByteSize::new(7),
+            arg: Box::new(Expression::Var(register("RDI"))),
+        }),
+        rhs: Box::new(low_value),
+    };
+    state.handle_register_assign(
+        &register("RDI"),
+        &write_low_byte(Expression::Const(Bitvector::from_u8(0))),
+    );
+    // The lowest byte of the stack address is unknown,
+    // so the offset may change by up to 255 bytes in either direction.
+    assert_eq!(
+        state.get_register(&register("RDI")),
+        Data::from_target(
+            stack_id.clone(),
+            IntervalDomain::mock(-0x20 - 0xff, -0x20 + 0xff)
+        )
+    );
+    // The pointer can still be dereferenced,
+    // but the stored value cannot be tracked, since the exact offset of the store is unknown.
+    state
+        .handle_store(
+            &Expression::Var(register("RDI")),
+            &Expression::const_from_i64(42),
+            &global_memory,
+        )
+        .unwrap();
+    assert!(state
+        .load_value(&reg_add("RSP", -0x100), ByteSize::new(8), &global_memory)
+        .unwrap()
+        .contains_top());
+
+    // With an unknown lowest byte only the target of the pointer is known.
+    state.handle_register_assign(&register("RDI"), &reg_add("RSP", -0x20));
+    state.handle_register_assign(
+        &register("RDI"),
+        &write_low_byte(Expression::Var(Variable::mock("SIL", 1))),
+    );
+    let pointer = state.get_register(&register("RDI"));
+    assert_eq!(
+        pointer.get_relative_values().keys().collect::<Vec<_>>(),
+        vec![&stack_id]
+    );
+    assert!(pointer.contains_top());
+
+    // Writes to a different register do not keep the pointer.
+    state.handle_register_assign(&register("RDI"), &reg_add("RSP", -0x20));
+    state.handle_register_assign(
+        &register("RAX"),
+        &write_low_byte(Expression::Const(Bitvector::from_u8(0))),
+    );
+    assert!(state
+        .get_register(&register("RAX"))
+        .get_relative_values()
+        .is_empty());
+}
+
+#[test]
+fn eval_abstract_locations() {
+    let global_memory = RuntimeMemoryImage::mock();
+    let mut state = State::new(&register("RSP"), Tid::new("time0"));
+    let stack_id = new_id("time0", "RSP");
+    let heap_id = new_id("heap_alloc", "RAX");
+    state.memory.add_abstract_object(
+        heap_id.clone(),
+        bv(0),
+        crate::analysis::pointer_inference::object::ObjectType::Heap,
+        ByteSize::new(8),
+    );
+    state.set_register(&register("RAX"), Data::from_target(heap_id.clone(), bv(0)));
+    // Pointer arithmetic
+    assert_eq!(
+        state.eval_abstract_location(&reg_add("RAX", 16), &global_memory),
+        vec![(heap_id.clone(), bv(16))]
+    );
+    assert_eq!(
+        state.eval_abstract_location(&reg_sub("RSP", 8), &global_memory),
+        vec![(stack_id.clone(), bv(-8))]
+    );
+    // Global addresses
+    assert_eq!(
+        state.eval_abstract_location(&Expression::const_from_i64(0x3002), &global_memory),
+        vec![(State::get_global_memory_id(ByteSize::new(8)), bv(0x3002))]
+    );
+    assert!(state
+        .eval_abstract_location(&Expression::const_from_i64(42), &global_memory)
+        .is_empty());
+    // Top values
+    assert!(state
+        .eval_abstract_location(&Expression::Var(register("RBX")), &global_memory)
+        .is_empty());
+    // Pointers stored on the stack
+    state
+        .handle_store(&reg_sub("RSP", 8), &reg_add("RAX", 8), &global_memory)
+        .unwrap();
+    assert_eq!(
+        state.eval_abstract_location_of_load(&reg_sub("RSP", 8), ByteSize::new(8), &global_memory),
+        vec![(heap_id, bv(8))]
+    );
+    assert!(state
+        .eval_abstract_location_of_load(&reg_sub("RSP", 16), ByteSize::new(8), &global_memory)
+        .is_empty());
+}
+
 #[test]
 fn handle_caller_stack_stores() {
     use super::super::object::ObjectType;
@@ -409,6 +524,197 @@ fn reachable_ids_under_and_overapproximation() {
     );
 }
 
+#[test]
+fn escaping_heap_pointers() {
+    use crate::analysis::pointer_inference::object::ObjectType;
+    let global_memory = RuntimeMemoryImage::mock();
+    let mut state = State::new(&register("RSP"), Tid::new("func_tid"));
+    let stack_id = new_id("func_tid", "RSP");
+    let heap_id = new_id("heap_obj", "RAX");
+    let other_heap_id = new_id("other_heap_obj", "RAX");
+    for id in [&heap_id, &other_heap_id] {
+        state
+            .memory
+            .add_abstract_object(id.clone(), bv(0), ObjectType::Heap, ByteSize::new(8));
+    }
+    let heap_address = Data::from_target(heap_id.clone(), bv(0));
+
+    // Writes to the own stack frame do not let the pointer escape.
+    state
+        .store_value(
+            &Data::from_target(stack_id.clone(), bv(-8)),
+            &heap_address,
+            &global_memory,
+        )
+        .unwrap();
+    assert!(!state.memory.has_escaped(&heap_id).unwrap());
+    // If the heap object is not referenced anymore, it can be removed.
+    state.remove_object_if_unreachable_and_not_escaped(&heap_id);
+    assert!(state.memory.get_object_type(&heap_id).is_ok());
+    state
+        .store_value(
+            &Data::from_target(stack_id.clone(), bv(-8)),
+            &bv(0).into(),
+            &global_memory,
+        )
+        .unwrap();
+    state.remove_object_if_unreachable_and_not_escaped(&heap_id);
+    assert!(state.memory.get_object_type(&heap_id).is_err());
+
+    // Writes to other heap objects let the pointer escape.
+    state
+        .memory
+        .add_abstract_object(heap_id.clone(), bv(0), ObjectType::Heap, ByteSize::new(8));
+    let heap_address = Data::from_target(other_heap_id.clone(), bv(0));
+    state
+        .store_value(
+            &Data::from_target(heap_id.clone(), bv(0)),
+            &heap_address,
+            &global_memory,
+        )
+        .unwrap();
+    assert!(state.memory.has_escaped(&other_heap_id).unwrap());
+    state.remove_object_if_unreachable_and_not_escaped(&other_heap_id);
+    assert!(state.memory.get_object_type(&other_heap_id).is_ok());
+
+    // Writes to global memory let the pointer escape.
+    state
+        .store_value(
+            &Bitvector::from_u64(0x2000).into(),
+            &Data::from_target(heap_id.clone(), bv(0)),
+            &global_memory,
+        )
+        .unwrap();
+    assert!(state.memory.has_escaped(&heap_id).unwrap());
+}
+
+#[test]
+fn store_to_unknown_address() {
+    use crate::analysis::pointer_inference::object::ObjectType;
+    let global_memory = RuntimeMemoryImage::mock();
+    let mut state = State::new(&register("RSP"), Tid::new("func_tid"));
+    let stack_id = new_id("func_tid", "RSP");
+    let heap_id = new_id("heap_obj", "RAX");
+    let local_heap_id = new_id("local_heap_obj", "RAX");
+    for id in [&heap_id, &local_heap_id] {
+        state
+            .memory
+            .add_abstract_object(id.clone(), bv(0), ObjectType::Heap, ByteSize::new(8));
+    }
+    state
+        .memory
+        .mark_heap_objects_as_escaped(&vec![heap_id.clone()].into_iter().collect());
+    let stack_address = Data::from_target(stack_id.clone(), bv(-8));
+    let heap_address = Data::from_target(heap_id.clone(), bv(0));
+    let heap_address_8 = Data::from_target(heap_id.clone(), bv(8));
+    let local_heap_address = Data::from_target(local_heap_id.clone(), bv(0));
+    state
+        .store_value(&stack_address, &bv(42).into(), &global_memory)
+        .unwrap();
+    state
+        .store_value(&heap_address, &bv(1).into(), &global_memory)
+        .unwrap();
+    state
+        .store_value(&heap_address_8, &heap_address, &global_memory)
+        .unwrap();
+    state
+        .store_value(&local_heap_address, &bv(2).into(), &global_memory)
+        .unwrap();
+
+    state
+        .store_value(
+            &Data::new_top(ByteSize::new(8)),
+            &bv(3).into(),
+            &global_memory,
+        )
+        .unwrap();
+    // The local stack variable and the non-escaped heap object are not affected.
+    assert_eq!(
+        state.memory.get_value(&stack_address, ByteSize::new(8)),
+        bv(42).into()
+    );
+    assert_eq!(
+        state
+            .memory
+            .get_value(&local_heap_address, ByteSize::new(8)),
+        bv(2).into()
+    );
+    // The values of the escaped heap object are merged with Top.
+    let weakened_value = state.memory.get_value(&heap_address, ByteSize::new(8));
+    assert!(weakened_value.contains_top());
+    assert_eq!(weakened_value.get_absolute_value(), Some(&bv(1)));
+    let weakened_pointer = state.memory.get_value(&heap_address_8, ByteSize::new(8));
+    assert!(weakened_pointer.contains_top());
+    assert_eq!(
+        weakened_pointer.get_relative_values().keys().next(),
+        Some(&heap_id)
+    );
+
+    // A store through a pointer with a known target does not affect other exposed objects.
+    let mut partially_known_address = Data::from_target(local_heap_id.clone(), bv(0));
+    partially_known_address.set_contains_top_flag();
+    state
+        .store_value(&partially_known_address, &bv(4).into(), &global_memory)
+        .unwrap();
+    assert_eq!(
+        state.memory.get_value(&heap_address, ByteSize::new(8)),
+        weakened_value
+    );
+
+    // If the address of the stack frame is exposed, the stack frame is also weakly updated.
+    state
+        .store_value(&heap_address, &stack_address, &global_memory)
+        .unwrap();
+    state
+        .store_value(
+            &Data::new_top(ByteSize::new(8)),
+            &bv(3).into(),
+            &global_memory,
+        )
+        .unwrap();
+    assert!(state
+        .memory
+        .get_value(&stack_address, ByteSize::new(8))
+        .contains_top());
+}
+
+#[test]
+fn unknown_expressions() {
+    let global_memory = RuntimeMemoryImage::mock();
+    let mut state = State::new(&register("RSP"), Tid::new("func_tid"));
+    let unknown = Expression::Unknown {
+        description: "untranslated".to_string(),
+        size: ByteSize::new(4),
+    };
+    // Evaluation returns Top of the correct size.
+    assert_eq!(state.eval(&unknown), Data::new_top(ByteSize::new(4)));
+
+    // A store through an address containing an `Unknown` does not invalidate the (non-exposed) stack frame.
+    state
+        .handle_store(
+            &reg_add("RSP", -8),
+            &Expression::Const(Bitvector::from_i64(42)),
+            &global_memory,
+        )
+        .unwrap();
+    let unknown_address = Expression::Var(register("RSP")).plus(Expression::Unknown {
+        description: "untranslated".to_string(),
+        size: ByteSize::new(8),
+    });
+    state
+        .handle_store(
+            &unknown_address,
+            &Expression::Const(Bitvector::from_i64(3)),
+            &global_memory,
+        )
+        .unwrap();
+    let stack_address = Data::from_target(new_id("func_tid", "RSP"), bv(-8));
+    assert_eq!(
+        state.memory.get_value(&stack_address, ByteSize::new(8)),
+        bv(42).into()
+    );
+}
+
 #[test]
 fn global_mem_access() {
     let global_memory = RuntimeMemoryImage::mock();
@@ -757,6 +1063,127 @@ fn specialize_by_equality_comparison() {
     );
 }
 
+/// Test expression specialization for comparisons of masked, truncated or shifted values.
+#[test]
+fn specialize_by_masked_comparison() {
+    let equals = |lhs: Expression, rhs: Expression| Expression::BinOp {
+        lhs: Box::new(lhs),
+        op: BinOpType::IntEqual,
+        rhs: Box::new(rhs),
+    };
+    let mut base_state = State::new(&register("RSP"), Tid::new("func_tid"));
+    base_state.set_register(&register("RAX"), IntervalDomain::mock(0, 0x1000).into());
+
+    // (RAX & 0xff) == 0x2f
+    let masked_rax = Expression::BinOp {
+        lhs: Box::new(Expression::var("RAX", 8)),
+        op: BinOpType::IntAnd,
+        rhs: Box::new(Expression::const_from_i64(0xff)),
+    };
+    let mut state = base_state.clone();
+    let x = state.specialize_by_expression_result(
+        &equals(masked_rax.clone(), Expression::const_from_i64(0x2f)),
+        Bitvector::from_u8(1).into(),
+    );
+    assert!(x.is_ok());
+    assert_eq!(
+        state.get_register(&register("RAX")),
+        IntervalDomain::mock_with_bounds(Some(0), 0x2f, 0x1000, None).into()
+    );
+    // If the mask does not change the value, the comparison restricts the value exactly.
+    let mut state = base_state.clone();
+    state.set_register(&register("RAX"), IntervalDomain::mock(0, 0xff).into());
+    let x = state.specialize_by_expression_result(
+        &equals(masked_rax.clone(), Expression::const_from_i64(0x2f)),
+        Bitvector::from_u8(1).into(),
+    );
+    assert!(x.is_ok());
+    assert_eq!(
+        state.get_register(&register("RAX")),
+        Bitvector::from_i64(0x2f).into()
+    );
+    let mut state = base_state.clone();
+    state.set_register(&register("RAX"), IntervalDomain::mock(0, 0xff).into());
+    let x = state.specialize_by_expression_result(
+        &equals(masked_rax.clone(), Expression::const_from_i64(0x2f)),
+        Bitvector::from_u8(0).into(),
+    );
+    assert!(x.is_ok());
+    assert_eq!(
+        state.get_register(&register("RAX")),
+        IntervalDomain::mock(0, 0xff)
+            .add_not_equal_bound(&Bitvector::from_i64(0x2f))
+            .unwrap()
+            .into()
+    );
+    // Results with bits outside of the mask are unsatisfiable.
+    let mut state = base_state.clone();
+    let x = state.specialize_by_expression_result(
+        &equals(masked_rax, Expression::const_from_i64(0x12f)),
+        Bitvector::from_u8(1).into(),
+    );
+    assert!(x.is_err());
+    // The mask of a zero-extended byte does not change the value.
+    let mut state = base_state.clone();
+    let al_register = Variable::mock("AL", 1);
+    state.set_register(&al_register, Data::new_top(ByteSize::new(1)));
+    let masked_al = Expression::BinOp {
+        lhs: Box::new(Expression::Var(al_register.clone()).cast(CastOpType::IntZExt)),
+        op: BinOpType::IntAnd,
+        rhs: Box::new(Expression::const_from_i64(0xff)),
+    };
+    let x = state.specialize_by_expression_result(
+        &equals(masked_al, Expression::const_from_i64(0x2f)),
+        Bitvector::from_u8(1).into(),
+    );
+    assert!(x.is_ok());
+    assert_eq!(
+        state.get_register(&al_register),
+        Bitvector::from_u8(0x2f).into()
+    );
+
+    // Subpiece(RAX, 0, 1) == 0x2f
+    let mut state = base_state.clone();
+    let x = state.specialize_by_expression_result(
+        &equals(
+            Expression::var("RAX", 8).subpiece(ByteSize::new(0), ByteSize::new(1)),
+            Expression::Const(Bitvector::from_u8(0x2f)),
+        ),
+        Bitvector::from_u8(1).into(),
+    );
+    assert!(x.is_ok());
+    assert_eq!(
+        state.get_register(&register("RAX")),
+        IntervalDomain::mock_with_bounds(Some(0), 0x2f, 0x1000, None).into()
+    );
+    // A zero-extended byte cannot be equal to a value larger than a byte.
+    let mut state = base_state.clone();
+    let x = state.specialize_by_expression_result(
+        &equals(
+            Expression::Var(al_register).cast(CastOpType::IntZExt),
+            Expression::const_from_i64(0x100),
+        ),
+        Bitvector::from_u8(1).into(),
+    );
+    assert!(x.is_err());
+
+    // RAX + 0x10 < 0x20
+    let mut state = base_state.clone();
+    let x = state.specialize_by_expression_result(
+        &Expression::BinOp {
+            lhs: Box::new(Expression::var("RAX", 8).plus_const(0x10)),
+            op: BinOpType::IntSLess,
+            rhs: Box::new(Expression::const_from_i64(0x20)),
+        },
+        Bitvector::from_u8(1).into(),
+    );
+    assert!(x.is_ok());
+    assert_eq!(
+        state.get_register(&register("RAX")),
+        IntervalDomain::mock(0, 0xf).into()
+    );
+}
+
 /// Test expression specialization for signed comparison operations `<` and `<=`.
 #[test]
 fn specialize_by_signed_comparison_op() {
@@ -1055,6 +1482,43 @@ fn out_of_bounds_access_recognition() {
     assert!(!state.contains_out_of_bounds_mem_access(&load_def.term, &global_data));
 }
 
+#[test]
+fn bounded_mem_access_recognition() {
+    let mut state = State::new(&register("RSP"), Tid::new("func_tid"));
+    let heap_obj_id = new_id("heap_malloc", "RAX");
+    state.memory.add_abstract_object(
+        heap_obj_id.clone(),
+        Bitvector::from_u64(0).into(),
+        crate::analysis::pointer_inference::object::ObjectType::Heap,
+        ByteSize::new(8),
+    );
+    let load_def = Def::load(
+        "tid",
+        Variable::mock("RBX", 8),
+        Expression::Var(Variable::mock("RAX", 8)),
+    );
+    let address = Data::from_target(heap_obj_id.clone(), IntervalDomain::mock(0, 8));
+    state.set_register(&Variable::mock("RAX", 8), address);
+    // The bounds of the object are not known yet.
+    assert!(!state.is_bounded_mem_access_to_unique_object(&load_def.term));
+
+    state
+        .memory
+        .set_lower_index_bound(&heap_obj_id, &Bitvector::from_u64(0).into());
+    state
+        .memory
+        .set_upper_index_bound(&heap_obj_id, &Bitvector::from_u64(15).into());
+    assert!(state.is_bounded_mem_access_to_unique_object(&load_def.term));
+    let address = Data::from_target(heap_obj_id.clone(), IntervalDomain::mock(0, 9));
+    state.set_register(&Variable::mock("RAX", 8), address);
+    assert!(!state.is_bounded_mem_access_to_unique_object(&load_def.term));
+    // Pointers that may also be absolute values are not bounded.
+    let mut address = Data::from_target(heap_obj_id, Bitvector::from_u64(0).into());
+    address.set_absolute_value(Some(IntervalDomain::mock(0, 8)));
+    state.set_register(&Variable::mock("RAX", 8), address);
+    assert!(!state.is_bounded_mem_access_to_unique_object(&load_def.term));
+}
+
 #[test]
 fn specialize_pointer_comparison() {
     let mut state = State::new(&register("RSP"), Tid::new("func_tid"));
@@ -1116,6 +1580,21 @@ fn test_check_def_for_null_dereferences() {
     address.set_absolute_value(Some(IntervalDomain::mock(0, 0xffff)));
     state.set_register(&var_rax, address);
     assert_eq!(state.check_def_for_null_dereferences(&def).ok(), Some(true));
+
+    // Addresses checked by a `!= 0` guard are not NULL.
+    state.set_register(&var_rax, IntervalDomain::mock(-2000, 5).into());
+    let guard = Expression::BinOp {
+        op: BinOpType::IntNotEqual,
+        lhs: Box::new(Expression::Var(var_rax.clone())),
+        rhs: Box::new(Expression::const_from_i64(0)),
+    };
+    state
+        .specialize_by_expression_result(&guard, Bitvector::from_u8(1).into())
+        .unwrap();
+    assert_eq!(
+        state.check_def_for_null_dereferences(&def).ok(),
+        Some(false)
+    );
 }
 
 #[test]
@@ -1127,3 +1606,40 @@ fn test_new_with_generic_parameter_objects() {
     assert!(!state.get_register(&Variable::mock("param1", 8)).is_top());
     assert!(!state.get_register(&Variable::mock("param1", 8)).is_top());
 }
+
+#[test]
+fn field_sensitive_stack_struct_access() {
+    let global_memory = RuntimeMemoryImage::mock();
+    let mut state = State::new(&register("RSP"), Tid::new("func"));
+    let first_pointer = Data::from_target(new_id("heap_1", "RAX"), bv(0));
+    let second_pointer = Data::from_target(new_id("heap_2", "RAX"), bv(0));
+    state
+        .write_to_address(&reg_add("RSP", -16), &first_pointer, &global_memory)
+        .unwrap();
+    state
+        .write_to_address(&reg_add("RSP", -8), &second_pointer, &global_memory)
+        .unwrap();
+    state.handle_register_assign(&register("RBX"), &reg_add("RSP", -16));
+    let read_fields = |state: &State| {
+        (
+            state
+                .load_value(&reg_add("RBX", 0), ByteSize::new(8), &global_memory)
+                .unwrap(),
+            state
+                .load_value(&reg_add("RBX", 8), ByteSize::new(8), &global_memory)
+                .unwrap(),
+        )
+    };
+    assert_eq!(
+        read_fields(&state),
+        (first_pointer.clone(), second_pointer.clone())
+    );
+    // The fields keep their values after replacing the stack ID.
+    let stack_id = state.stack_id.clone();
+    let mut replaced_state = state.clone();
+    replaced_state.replace_abstract_id(&stack_id, &new_id("caller", "RSP"), &bv(-32));
+    assert_eq!(
+        read_fields(&replaced_state),
+        (first_pointer, second_pointer)
+    );
+}
@@ -52,6 +52,17 @@ impl State {
                 Expression::Cast { op, size: _, arg } => match op {
                     CastOpType::IntZExt | CastOpType::IntSExt => {
                         let intermediate_result = result.subpiece(ByteSize::new(0), arg.bytesize());
+                        if let Ok(result_bitvec) = result.try_to_bitvec() {
+                            // The result has to be representable as an extension of the argument.
+                            if intermediate_result
+                                .cast(*op, result.bytesize())
+                                .try_to_bitvec()
+                                .ok()
+                                != Some(result_bitvec)
+                            {
+                                return Err(anyhow!("Unsatisfiable state"));
+                            }
+                        }
                         self.specialize_by_expression_result(arg, intermediate_result)
                     }
                     _ => Ok(()),
@@ -66,6 +77,19 @@ impl State {
                     arg,
                 } => {
                     if *low_byte == ByteSize::new(0) {
+                        // The subpiece is the argument masked to its low bytes.
+                        let mask = Bitvector::unsigned_max_value((*size).into())
+                            .into_zero_extend(arg.bytesize())
+                            .unwrap();
+                        if result.try_to_bitvec().is_ok() || self.is_unchanged_by_mask(arg, &mask) {
+                            let intermediate_result =
+                                result.cast(CastOpType::IntZExt, arg.bytesize());
+                            return self.specialize_by_masked_expression_result(
+                                arg,
+                                &mask,
+                                intermediate_result,
+                            );
+                        }
                         if let Some(arg_value) = self.eval(expression).get_if_absolute_value() {
                             if arg_value.fits_into_size(*size) {
                                 let intermediate_result =
@@ -109,6 +133,14 @@ impl State {
 
                 return Ok(());
             }
+            BinOpType::IntAnd => {
+                if let Expression::Const(mask) = rhs {
+                    return self.specialize_by_masked_expression_result(lhs, mask, result);
+                }
+                if let Expression::Const(mask) = lhs {
+                    return self.specialize_by_masked_expression_result(rhs, mask, result);
+                }
+            }
             _ => (),
         }
         if let Ok(result_bitvec) = result.try_to_bitvec() {
@@ -240,6 +272,87 @@ impl State {
         }
     }
 
+    /// Try to restrict the input variables of `expression`
+    /// so that `expression & mask` only evaluates to values represented by the given `result`.
+    ///
+    /// If `result` is a constant, the value of `expression` is restricted to the best interval
+    /// containing all values with the masked bits set according to `result`.
+    /// Else `expression` is only specialized if the mask does not change its value.
+    /// Pointers are never restricted, since masks on pointers are usually alignment checks.
+    fn specialize_by_masked_expression_result(
+        &mut self,
+        expression: &Expression,
+        mask: &Bitvector,
+        result: Data,
+    ) -> Result<(), Error> {
+        if let Ok(result_bitvec) = result.try_to_bitvec() {
+            if !(result_bitvec.clone() & &!mask.clone()).is_zero() {
+                return Err(anyhow!("Unsatisfiable state"));
+            }
+            if self.is_unchanged_by_mask(expression, mask) {
+                return self.specialize_by_expression_result(expression, result);
+            }
+            let value = self.eval(expression);
+            if !value.get_relative_values().is_empty() {
+                return Ok(());
+            }
+            // The unmasked bits may be set arbitrarily.
+            let upper_bound = result_bitvec.clone() | &!mask.clone();
+            let new_value = value
+                .add_unsigned_greater_equal_bound(&result_bitvec)?
+                .add_unsigned_less_equal_bound(&upper_bound)?;
+            self.specialize_by_expression_result(expression, new_value)
+        } else if self.is_unchanged_by_mask(expression, mask) {
+            self.specialize_by_expression_result(expression, result)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if `expression & mask == expression` holds for all possible values of `expression`.
+    ///
+    /// This is the case if all bits of the mask are set,
+    /// if `expression` is a zero extension of a value that is not longer than the mask
+    /// or if `expression` is known to be a non-negative value that fits into the mask.
+    fn is_unchanged_by_mask(&self, expression: &Expression, mask: &Bitvector) -> bool {
+        let is_low_bit_mask =
+            (mask.clone() & &(mask.clone() + &Bitvector::one(mask.width()))).is_zero();
+        if !is_low_bit_mask {
+            return false;
+        }
+        if *mask == Bitvector::unsigned_max_value(mask.width()) {
+            return true;
+        }
+        if let Expression::Cast {
+            op: CastOpType::IntZExt,
+            arg,
+            ..
+        } = expression
+        {
+            let arg_mask = Bitvector::unsigned_max_value(arg.bytesize().into())
+                .into_zero_extend(expression.bytesize())
+                .unwrap();
+            if (arg_mask & &!mask.clone()).is_zero() {
+                return true;
+            }
+        }
+        let value = self.eval(expression);
+        if !value.get_relative_values().is_empty() || value.contains_top() {
+            return false;
+        }
+        match value
+            .get_if_absolute_value()
+            .and_then(|value| value.try_to_interval().ok())
+        {
+            Some(interval) => {
+                !interval.start.sign_bit().to_bool()
+                    && interval.end.checked_ule(mask).unwrap_or(false)
+                    && interval.start.checked_ule(&interval.end).unwrap_or(false)
+            }
+            None => false,
+        }
+    }
+
     /// If both `lhs` and `rhs` evaluate to pointers and `op` is a comparison operator that evaluates to `true`,
     /// specialize the input pointers accordingly.
     ///
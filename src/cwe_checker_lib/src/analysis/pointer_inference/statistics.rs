@@ -1,6 +1,13 @@
 use super::*;
-use crate::abstract_domain::TryToBitvec;
+use crate::abstract_domain::{AbstractDomain, TryToBitvec};
 use crossbeam_channel::Sender;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The maximal number of extern symbols listed in the log message on the top precision-loss extern symbols.
+const NUM_LOGGED_EXTERN_SYMBOLS: usize = 10;
+
+/// The maximal number of blocks listed in the log message on blocks containing untranslated operations.
+const NUM_LOGGED_UNKNOWN_BLOCKS: usize = 10;
 
 /// Compute various statistics about how exact memory accesses through `Load` and `Store` instructions are tracked.
 /// Print the results as debug-log-messages.
@@ -8,6 +15,270 @@ pub fn compute_and_log_mem_access_stats(pointer_inference: &PointerInference) {
     MemAccessStats::compute_and_log(pointer_inference);
 }
 
+/// Compute the precision loss caused by calls to unmodeled extern symbols
+/// and print the extern symbols responsible for the largest loss as a log message.
+pub fn compute_and_log_extern_symbol_stats(pointer_inference: &PointerInference) {
+    let stats = compute_extern_symbol_stats(pointer_inference);
+    if stats.is_empty() {
+        return;
+    }
+    let mut msg = "Top precision-loss extern symbols:".to_string();
+    for symbol_stats in stats.iter().take(NUM_LOGGED_EXTERN_SYMBOLS) {
+        msg += &format!(
+            "\n\t{}: {} call sites, {} invalidated objects, {} invalidated values, return value {}",
+            symbol_stats.name,
+            symbol_stats.call_sites,
+            symbol_stats.invalidated_objects,
+            symbol_stats.invalidated_values,
+            if symbol_stats.return_value_used {
+                "used"
+            } else {
+                "unused"
+            },
+        );
+    }
+    pointer_inference.log_info(msg);
+}
+
+/// Count the `Unknown` placeholder expressions, i.e. operations that could not be translated, in each block.
+/// Only blocks containing at least one `Unknown` expression are contained in the result.
+pub fn count_unknown_expressions_per_block(program: &Program) -> BTreeMap<Tid, usize> {
+    let mut counts = BTreeMap::new();
+    for block in program.subs.values().flat_map(|sub| sub.term.blocks.iter()) {
+        let def_count: usize = block
+            .term
+            .defs
+            .iter()
+            .map(|def| match &def.term {
+                Def::Assign { value, .. } => value.count_unknown_subexpressions(),
+                Def::Load { address, .. } => address.count_unknown_subexpressions(),
+                Def::Store { address, value } => {
+                    address.count_unknown_subexpressions() + value.count_unknown_subexpressions()
+                }
+            })
+            .sum();
+        let jmp_count: usize = block
+            .term
+            .jmps
+            .iter()
+            .map(|jmp| match &jmp.term {
+                Jmp::BranchInd(expression)
+                | Jmp::CBranch {
+                    condition: expression,
+                    ..
+                }
+                | Jmp::CallInd {
+                    target: expression, ..
+                }
+                | Jmp::Return(expression) => expression.count_unknown_subexpressions(),
+                Jmp::Branch(_) | Jmp::Call { .. } | Jmp::CallOther { .. } => 0,
+            })
+            .sum();
+        if def_count + jmp_count > 0 {
+            counts.insert(block.tid.clone(), def_count + jmp_count);
+        }
+    }
+    counts
+}
+
+/// Count the `Unknown` placeholder expressions per block
+/// and print the blocks containing the most of them as a log message,
+/// so that users can see which code regions are poorly translated.
+pub fn compute_and_log_unknown_expression_stats(pointer_inference: &PointerInference) {
+    let counts =
+        count_unknown_expressions_per_block(&pointer_inference.get_context().project.program.term);
+    if counts.is_empty() {
+        return;
+    }
+    let mut sorted_counts: Vec<(&Tid, &usize)> = counts.iter().collect();
+    sorted_counts.sort_by_key(|(_, count)| std::cmp::Reverse(**count));
+    let mut msg = format!(
+        "{} untranslated operations in {} blocks. Blocks with the most untranslated operations:",
+        counts.values().sum::<usize>(),
+        counts.len()
+    );
+    for (block_tid, count) in sorted_counts.into_iter().take(NUM_LOGGED_UNKNOWN_BLOCKS) {
+        msg += &format!("\n\t{}: {}", block_tid, count);
+    }
+    pointer_inference.log_info(msg);
+}
+
+/// Statistics on the precision loss caused by calls to an extern symbol
+/// whose effect is not modeled by the pointer inference analysis.
+///
+/// Calls to such symbols invalidate the content of all memory objects reachable through their parameters.
+/// Prototypes (or adding the symbol to the `assume_pure` list of the configuration)
+/// for the symbols with the largest precision loss improve the analysis the most.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExternSymbolStats {
+    /// The name of the extern symbol.
+    pub name: String,
+    /// The number of call sites of the symbol that were reached by the analysis.
+    pub call_sites: u64,
+    /// The number of memory objects (summed over all call sites) whose content was invalidated by calls to the symbol.
+    pub invalidated_objects: u64,
+    /// The number of tracked values (summed over all call sites) that were invalidated by calls to the symbol.
+    pub invalidated_values: u64,
+    /// Whether the return value of the symbol is read at some call site.
+    ///
+    /// Only reads in the block that the call returns to are considered.
+    pub return_value_used: bool,
+}
+
+/// Compute the precision loss statistics for all unmodeled extern symbols that are called in the program.
+///
+/// The result is ranked by the number of invalidated values,
+/// then by the number of invalidated objects and the number of call sites.
+pub fn compute_extern_symbol_stats(pointer_inference: &PointerInference) -> Vec<ExternSymbolStats> {
+    let graph = pointer_inference.computation.get_graph();
+    let context = pointer_inference.get_context();
+    let mut stats_map: BTreeMap<&str, ExternSymbolStats> = BTreeMap::new();
+    for (node_id, node) in graph.node_references() {
+        let (block, sub) = match node {
+            Node::BlkEnd(block, sub) => (block, sub),
+            _ => continue,
+        };
+        let (target, return_target) = match block.term.jmps.first() {
+            Some(Term {
+                term: Jmp::Call { target, return_ },
+                ..
+            }) => (target, return_),
+            _ => continue,
+        };
+        let (extern_symbol, state) = match (
+            context.get_extern_symbol(target),
+            pointer_inference.computation.get_node_value(node_id),
+        ) {
+            (Some(extern_symbol), Some(NodeValue::Value(state)))
+                if context.is_unmodeled_extern_symbol(extern_symbol) =>
+            {
+                (extern_symbol, state)
+            }
+            _ => continue,
+        };
+        let invalidated_ids = context.get_ids_accessible_by_extern_call(state, extern_symbol);
+        let invalidated_values: usize = invalidated_ids
+            .iter()
+            .map(|id| state.memory.get_num_tracked_values(id))
+            .sum();
+        let return_value_used = return_target
+            .as_ref()
+            .and_then(|return_tid| sub.term.blocks.iter().find(|blk| blk.tid == *return_tid))
+            .is_some_and(|return_block| {
+                is_return_value_read(
+                    return_block,
+                    &get_return_registers(context.project, extern_symbol),
+                )
+            });
+        let symbol_stats = stats_map
+            .entry(extern_symbol.name.as_str())
+            .or_insert_with(|| ExternSymbolStats {
+                name: extern_symbol.name.clone(),
+                call_sites: 0,
+                invalidated_objects: 0,
+                invalidated_values: 0,
+                return_value_used: false,
+            });
+        symbol_stats.call_sites += 1;
+        symbol_stats.invalidated_objects += invalidated_ids.len() as u64;
+        symbol_stats.invalidated_values += invalidated_values as u64;
+        symbol_stats.return_value_used |= return_value_used;
+    }
+    let mut stats: Vec<ExternSymbolStats> = stats_map.into_values().collect();
+    stats.sort_by(|a, b| {
+        (b.invalidated_values, b.invalidated_objects, b.call_sites).cmp(&(
+            a.invalidated_values,
+            a.invalidated_objects,
+            a.call_sites,
+        ))
+    });
+    stats
+}
+
+/// Get the registers that may contain the return value of the given extern symbol.
+fn get_return_registers<'a>(
+    project: &'a Project,
+    extern_symbol: &'a ExternSymbol,
+) -> Vec<&'a Variable> {
+    match extern_symbol.get_unique_return_register() {
+        Ok(register) => vec![register],
+        Err(_) => project
+            .get_calling_convention(extern_symbol)
+            .integer_return_register
+            .iter()
+            .collect(),
+    }
+}
+
+/// Returns `true` if one of the given registers is read in the block before it is overwritten.
+fn is_return_value_read(block: &Term<Blk>, return_registers: &[&Variable]) -> bool {
+    let mut overwritten_registers: HashSet<&Variable> = HashSet::new();
+    let is_read = |expression: &Expression, overwritten_registers: &HashSet<&Variable>| {
+        expression
+            .input_vars()
+            .into_iter()
+            .any(|var| return_registers.contains(&var) && !overwritten_registers.contains(var))
+    };
+    for def in block.term.defs.iter() {
+        let (read_expressions, written_var) = match &def.term {
+            Def::Assign { var, value } => (vec![value], Some(var)),
+            Def::Load { var, address } => (vec![address], Some(var)),
+            Def::Store { address, value } => (vec![address, value], None),
+        };
+        if read_expressions
+            .into_iter()
+            .any(|expression| is_read(expression, &overwritten_registers))
+        {
+            return true;
+        }
+        overwritten_registers.extend(written_var);
+    }
+    block.term.jmps.iter().any(|jmp| match &jmp.term {
+        Jmp::BranchInd(expression)
+        | Jmp::CBranch {
+            condition: expression,
+            ..
+        }
+        | Jmp::CallInd {
+            target: expression, ..
+        }
+        | Jmp::Return(expression) => is_read(expression, &overwritten_registers),
+        _ => false,
+    })
+}
+
+/// Counts the memory accesses evaluated by the transfer functions of the pointer inference
+/// and how many of them had an address that was not completely unknown.
+///
+/// Since states are evaluated once per visit of a node during the fixpoint computation,
+/// a memory access may be counted more than once.
+#[derive(Debug, Default)]
+pub struct MemoryAccessCounter {
+    /// The number of evaluated `Load` and `Store` instructions.
+    total: AtomicU64,
+    /// The number of evaluated `Load` and `Store` instructions whose address was not `Top`.
+    known_address: AtomicU64,
+}
+
+impl MemoryAccessCounter {
+    /// Count a memory access with the given address value.
+    pub fn record(&self, address: &Data) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        if !address.is_top() {
+            self.known_address.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the number of counted memory accesses
+    /// and the number of counted memory accesses whose address was not `Top`.
+    pub fn get_counts(&self) -> (u64, u64) {
+        (
+            self.total.load(Ordering::Relaxed),
+            self.known_address.load(Ordering::Relaxed),
+        )
+    }
+}
+
 #[derive(Default)]
 struct MemAccessStats {
     all_mem_ops: u64,
@@ -72,7 +343,6 @@ impl MemAccessStats {
     }
 
     fn count_for_def(&mut self, state: &State, def: &Term<Def>) {
-        use crate::abstract_domain::AbstractDomain;
         match &def.term {
             Def::Load { address, .. } | Def::Store { address, .. } => {
                 self.all_mem_ops += 1;
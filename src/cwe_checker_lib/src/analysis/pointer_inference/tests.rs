@@ -0,0 +1,1524 @@
+use super::*;
+use crate::analysis::function_signature::{
+    add_parameter_ownership, get_ownership_classifications, FunctionSignature, ParameterOwnership,
+};
+use object::ObjectState;
+
+impl<'a> PointerInference<'a> {
+    pub fn mock(
+        project: &'a Project,
+        mem_image: &'a RuntimeMemoryImage,
+        graph: &'a Graph,
+    ) -> PointerInference<'a> {
+        let (log_sender, _) = crossbeam_channel::unbounded();
+        PointerInference::new(project, mem_image, graph, mock_config(), log_sender, false)
+    }
+
+    pub fn mock_with_node_value_retention(
+        project: &'a Project,
+        mem_image: &'a RuntimeMemoryImage,
+        graph: &'a Graph,
+        node_value_retention: NodeValueRetention,
+    ) -> PointerInference<'a> {
+        let (log_sender, _) = crossbeam_channel::unbounded();
+        let config = Config {
+            node_value_retention,
+            ..mock_config()
+        };
+        PointerInference::new(project, mem_image, graph, config, log_sender, false)
+    }
+
+    pub fn set_node_value(&mut self, node_value: State, node_index: NodeIndex) {
+        self.computation
+            .set_node_value(node_index, NodeValue::Value(node_value));
+    }
+}
+
+pub fn mock_config() -> Config {
+    Config {
+        allocation_symbols: vec!["malloc".to_string()],
+        deallocation_symbols: vec!["free".to_string()],
+        custom_allocators: Vec::new(),
+        function_lookup_symbols: vec!["dlsym".to_string()],
+        context_switch_symbols: vec!["makecontext".to_string()],
+        coroutine_entry_index: BTreeMap::from([("makecontext".to_string(), 1)]),
+        thread_creation_symbols: BTreeMap::from([(
+            "pthread_create".to_string(),
+            thread_creation::ThreadCreationParameters {
+                start_routine_index: 2,
+                argument_index: 3,
+            },
+        )]),
+        environment_lookup_symbols: vec!["getenv".to_string()],
+        environment_modification_symbols: vec!["setenv".to_string()],
+        memory_protection_symbols: BTreeMap::new(),
+        event_polling_symbols: BTreeMap::new(),
+        memory_copy_symbols: BTreeMap::new(),
+        collect_warning_evidence: false,
+        attribute_warning_paths: false,
+        collect_access_histograms: false,
+        assume_pure: Vec::new(),
+        string_length_symbols: vec!["strlen".to_string()],
+        program_start_symbols: vec!["__libc_start_main".to_string()],
+        max_pointer_targets: None,
+        harness: None,
+        trusted_convention_discrepancies: Vec::new(),
+        written_pointer_parameters: BTreeMap::new(),
+        function_purities: Vec::new(),
+        parameter_ownership: Vec::new(),
+        node_value_retention: NodeValueRetention::All,
+    }
+}
+
+#[test]
+fn coroutine_entry_points() {
+    let mut project = Project::mock_empty();
+    let cconv = CallingConvention::mock_with_parameter_registers(
+        vec![Variable::mock("RDI", 8), Variable::mock("RSI", 8)],
+        vec![],
+    );
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    project.add_mock_extern_symbol("makecontext").parameters =
+        vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)];
+
+    let mut main_blk = Blk::mock_with_tid("main_blk");
+    main_blk.term.defs.push(Def::assign(
+        "set_entry",
+        Variable::mock("RSI", 8),
+        Expression::const_from_i64(0x3000),
+    ));
+    main_blk.term.jmps.push(Jmp::call(
+        "call_makecontext",
+        "makecontext",
+        Some("main_ret_blk"),
+    ));
+    let mut main_ret_blk = Blk::mock_with_tid("main_ret_blk");
+    main_ret_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let main = Sub::mock_with_blocks("main", vec![main_blk, main_ret_blk]);
+
+    let coroutine_blk = Blk::mock_return("coroutine_blk");
+    let mut coroutine = Sub::mock("coroutine");
+    coroutine.tid.address = "00003000".to_string();
+    coroutine.term.blocks = vec![coroutine_blk];
+
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+    project
+        .program
+        .term
+        .subs
+        .insert(coroutine.tid.clone(), coroutine);
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+    let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+    pointer_inference.compute();
+
+    let coroutine_start = graph
+        .node_indices()
+        .find(|node| {
+            matches!(graph[*node], Node::BlkStart(block, _) if block.tid == Tid::new("coroutine_blk"))
+        })
+        .unwrap();
+    assert!(pointer_inference.get_node_value(coroutine_start).is_none());
+
+    pointer_inference.add_coroutine_entry_points(&project, false);
+    assert!(pointer_inference.get_node_value(coroutine_start).is_some());
+}
+
+#[test]
+fn thread_entry_points() {
+    let mut project = Project::mock_empty();
+    let cconv = CallingConvention::mock_with_parameter_registers(
+        vec![
+            Variable::mock("RDI", 8),
+            Variable::mock("RSI", 8),
+            Variable::mock("RDX", 8),
+            Variable::mock("RCX", 8),
+        ],
+        vec![],
+    );
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    project.add_mock_extern_symbol("malloc");
+    project.add_mock_extern_symbol("pthread_create").parameters = ["RDI", "RSI", "RDX", "RCX"]
+        .iter()
+        .map(|name| Arg::mock_register(name, 8))
+        .collect();
+
+    let mut main_blk = Blk::mock_with_tid("main_blk");
+    main_blk.term.defs.push(Def::assign(
+        "set_size",
+        Variable::mock("RDI", 8),
+        Expression::const_from_i64(16),
+    ));
+    main_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_malloc", "malloc", Some("main_create_blk")));
+    let mut main_create_blk = Blk::mock_with_tid("main_create_blk");
+    main_create_blk.term.defs = vec![
+        Def::assign(
+            "set_start_routine",
+            Variable::mock("RDX", 8),
+            Expression::const_from_i64(0x2000),
+        ),
+        Def::assign(
+            "set_argument",
+            Variable::mock("RCX", 8),
+            Expression::var("RAX", 8),
+        ),
+    ];
+    main_create_blk.term.jmps.push(Jmp::call(
+        "call_pthread_create",
+        "pthread_create",
+        Some("main_ret_blk"),
+    ));
+    let mut main_ret_blk = Blk::mock_with_tid("main_ret_blk");
+    main_ret_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let main = Sub::mock_with_blocks("main", vec![main_blk, main_create_blk, main_ret_blk]);
+
+    let mut worker_blk = Blk::mock_with_tid("worker_blk");
+    worker_blk.term.defs.push(Def::store(
+        "worker_overflow",
+        Expression::var("RDI", 8).plus_const(16),
+        Expression::const_from_i64(0),
+    ));
+    worker_blk.term.jmps.push(Term {
+        tid: Tid::new("worker_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let mut worker = Sub::mock("worker");
+    worker.tid.address = "00002000".to_string();
+    worker.term.blocks = vec![worker_blk];
+
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+    project
+        .program
+        .term
+        .subs
+        .insert(worker.tid.clone(), worker.clone());
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+    let pointer_inference = run(
+        &project,
+        &runtime_memory_image,
+        &graph,
+        mock_config(),
+        false,
+        false,
+    );
+
+    assert_eq!(
+        pointer_inference.get_thread_creation_sites(),
+        &BTreeMap::from([(
+            worker.tid.clone(),
+            BTreeSet::from([Tid::new("call_pthread_create")])
+        )])
+    );
+    // The argument of the start routine points to the heap object allocated by `main`.
+    let worker_state = pointer_inference
+        .get_state_before_term(&Tid::new("worker_overflow"))
+        .unwrap();
+    let argument = worker_state.get_register(&Variable::mock("RDI", 8));
+    let (object_id, _) = argument.get_relative_values().iter().next().unwrap();
+    assert_eq!(object_id.get_tid(), &Tid::new("call_malloc"));
+    assert!(!worker_state.memory.is_unique_object(object_id).unwrap());
+    // The overflow of the heap object in the start routine is detected.
+    let warnings = &pointer_inference.collected_logs.1;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].tids, vec!["worker_overflow".to_string()]);
+    assert!(warnings[0]
+        .description
+        .ends_with("(in the thread start routine worker created at UNKNOWN)"));
+    assert_eq!(
+        warnings[0].other.last().unwrap(),
+        &vec!["thread_creation_sites".to_string(), "UNKNOWN".to_string()]
+    );
+}
+
+#[test]
+fn program_main_entry_point() {
+    let mut project = Project::mock_empty();
+    let cconv = CallingConvention::mock_with_parameter_registers(
+        vec![
+            Variable::mock("RDI", 8),
+            Variable::mock("RSI", 8),
+            Variable::mock("RDX", 8),
+        ],
+        vec![],
+    );
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    project.add_mock_extern_symbol("__libc_start_main");
+
+    // The entry point passes the address of an unnamed `main` function to `__libc_start_main`.
+    let mut start_blk = Blk::mock_with_tid("start_blk");
+    start_blk.term.defs.push(Def::assign(
+        "set_main",
+        Variable::mock("RDI", 8),
+        Expression::const_from_i64(0x3000),
+    ));
+    start_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_start_main", "__libc_start_main", None));
+    let start = Sub::mock_with_blocks("entry", vec![start_blk]);
+
+    // `main` loads `argv[1]` and `envp[0]`.
+    let mut main_blk = Blk::mock_with_tid("main_blk");
+    main_blk.term.defs = vec![
+        Def::load(
+            "load_argv_1",
+            Variable::mock("RAX", 8),
+            Expression::var("RSI", 8).plus_const(8),
+        ),
+        Def::load(
+            "load_envp_0",
+            Variable::mock("RCX", 8),
+            Expression::var("RDX", 8),
+        ),
+    ];
+    main_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let mut main = Sub::mock("FUN_00003000");
+    main.tid.address = "00003000".to_string();
+    main.term.blocks = vec![main_blk];
+
+    project.program.term.entry_points.insert(start.tid.clone());
+    project.program.term.subs.insert(start.tid.clone(), start);
+    project.program.term.subs.insert(main.tid.clone(), main);
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+    let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+    pointer_inference.compute_with_speculative_entry_points(&project, false);
+    assert_eq!(
+        pointer_inference
+            .get_main_function()
+            .map(|tid| tid.address.as_str()),
+        Some("00003000")
+    );
+
+    let main_end = graph
+        .node_indices()
+        .find(|node| {
+            matches!(graph[*node], Node::BlkEnd(block, _) if block.tid == Tid::new("main_blk"))
+        })
+        .unwrap();
+    let state = match pointer_inference.get_node_value(main_end) {
+        Some(NodeValue::Value(state)) => state,
+        _ => panic!("No state for the main function"),
+    };
+    let argc = state.get_register(&Variable::mock("RDI", 8));
+    assert_eq!(
+        argc.get_if_absolute_value(),
+        Some(&IntervalDomain::mock(0, i32::MAX as i64))
+    );
+    assert!(state
+        .memory
+        .is_program_argument_pointer(&state.get_register(&Variable::mock("RAX", 8))));
+    assert!(state
+        .memory
+        .is_program_argument_pointer(&state.get_register(&Variable::mock("RCX", 8))));
+    assert!(!state
+        .memory
+        .is_program_argument_pointer(&state.get_register(&Variable::mock("RSI", 8))));
+}
+
+/// `RDI = RSP + offset` followed by a call to `target`.
+fn mock_call_with_stack_pointer_argument(
+    block_tid: &str,
+    mut defs: Vec<Term<Def>>,
+    offset: i64,
+    target: &str,
+    return_block: &str,
+) -> Term<Blk> {
+    defs.push(Def::assign(
+        &format!("{}_set_param", block_tid),
+        Variable::mock("RDI", 8),
+        Expression::var("RSP", 8).plus_const(offset),
+    ));
+    let mut block = Blk::mock_with_tid(block_tid);
+    block.term.defs = defs;
+    block.term.jmps.push(Jmp::call(
+        &format!("call_{}", target),
+        target,
+        Some(return_block),
+    ));
+    block
+}
+
+#[test]
+fn extern_symbol_precision_loss() {
+    let mut project = Project::mock_empty();
+    let cconv =
+        CallingConvention::mock_with_parameter_registers(vec![Variable::mock("RDI", 8)], vec![]);
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    for name in ["unknown_fn", "pure_fn", "other_fn"] {
+        project.add_mock_extern_symbol(name);
+    }
+    let store = |tid: &str, offset: i64, value: i64| {
+        Def::store(
+            tid,
+            Expression::var("RSP", 8).plus_const(offset),
+            Expression::const_from_i64(value),
+        )
+    };
+    let mut main = Sub::mock_with_blocks(
+        "main",
+        vec![
+            mock_call_with_stack_pointer_argument(
+                "main_blk",
+                vec![store("store_1", -8, 42)],
+                -8,
+                "unknown_fn",
+                "after_unknown_blk",
+            ),
+            mock_call_with_stack_pointer_argument(
+                "after_unknown_blk",
+                vec![
+                    Def::assign(
+                        "use_return",
+                        Variable::mock("RBX", 8),
+                        Expression::var("RAX", 8),
+                    ),
+                    // The callee-saved register `RBP` keeps the address of the stored value across the call.
+                    Def::assign(
+                        "set_rbp",
+                        Variable::mock("RBP", 8),
+                        Expression::var("RSP", 8).plus_const(-16),
+                    ),
+                    Def::store(
+                        "store_2",
+                        Expression::var("RBP", 8),
+                        Expression::const_from_i64(7),
+                    ),
+                ],
+                -16,
+                "pure_fn",
+                "after_pure_blk",
+            ),
+            mock_call_with_stack_pointer_argument(
+                "after_pure_blk",
+                Vec::new(),
+                0x1000,
+                "other_fn",
+                "end_blk",
+            ),
+        ],
+    );
+    // `RDI` is overwritten with a constant before the call to `other_fn`.
+    main.term.blocks[2].term.defs.push(Def::assign(
+        "set_const_param",
+        Variable::mock("RDI", 8),
+        Expression::const_from_i64(0),
+    ));
+    let mut end_blk = Blk::mock_with_tid("end_blk");
+    end_blk.term.defs.push(Def::assign(
+        "overwrite_return",
+        Variable::mock("RAX", 8),
+        Expression::const_from_i64(0),
+    ));
+    end_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    main.term.blocks.push(end_blk);
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+    let mut config = mock_config();
+    config.assume_pure = vec!["pure_fn".to_string()];
+    let (log_sender, _) = crossbeam_channel::unbounded();
+    let mut pointer_inference = PointerInference::new(
+        &project,
+        &runtime_memory_image,
+        &graph,
+        config,
+        log_sender,
+        false,
+    );
+    pointer_inference.compute();
+
+    let stats = pointer_inference.get_extern_symbol_stats();
+    assert_eq!(
+        stats,
+        vec![
+            ExternSymbolStats {
+                name: "unknown_fn".to_string(),
+                call_sites: 1,
+                invalidated_objects: 1,
+                invalidated_values: 1,
+                return_value_used: true,
+            },
+            ExternSymbolStats {
+                name: "other_fn".to_string(),
+                call_sites: 1,
+                invalidated_objects: 0,
+                invalidated_values: 0,
+                return_value_used: false,
+            },
+        ]
+    );
+
+    // The stack value stored before the call to the pure function is still known after the call.
+    let after_pure_blk = graph
+        .node_indices()
+        .find(|node| {
+            matches!(graph[*node], Node::BlkStart(block, _) if block.tid == Tid::new("after_pure_blk"))
+        })
+        .unwrap();
+    let state = pointer_inference
+        .get_node_value(after_pure_blk)
+        .unwrap()
+        .unwrap_value();
+    let address = state.eval(&Expression::var("RBP", 8));
+    assert_eq!(
+        state.memory.get_value(&address, ByteSize::new(8)),
+        Bitvector::from_i64(7).into()
+    );
+}
+
+#[test]
+fn unknown_expression_counts() {
+    let unknown = |size: u64| Expression::Unknown {
+        description: "untranslated".to_string(),
+        size: ByteSize::new(size),
+    };
+    let mut block = Blk::mock_with_tid("blk");
+    block.term.defs = vec![
+        Def::assign("def", Variable::mock("RAX", 8), unknown(8)),
+        Def::store(
+            "store",
+            Expression::var("RSP", 8).plus(unknown(8)),
+            unknown(8),
+        ),
+    ];
+    block.term.jmps = vec![Term {
+        tid: Tid::new("cbranch"),
+        term: Jmp::CBranch {
+            target: Tid::new("blk"),
+            condition: unknown(1),
+        },
+    }];
+    let clean_block = Blk::mock_with_tid("clean_blk");
+    let sub = Sub::mock_with_blocks("func", vec![block, clean_block]);
+    let mut program = Program::mock_empty();
+    program.subs.insert(sub.tid.clone(), sub);
+
+    assert_eq!(
+        count_unknown_expressions_per_block(&program),
+        BTreeMap::from([(Tid::new("blk"), 4)])
+    );
+}
+
+#[test]
+fn read_and_write_violation_at_same_instruction() {
+    let mut project = Project::mock_empty();
+    let cconv =
+        CallingConvention::mock_with_parameter_registers(vec![Variable::mock("RDI", 8)], vec![]);
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    project.add_mock_extern_symbol("malloc");
+
+    let mut alloc_blk = Blk::mock_with_tid("alloc_blk");
+    alloc_blk.term.defs.push(Def::assign(
+        "set_size",
+        Variable::mock("RDI", 8),
+        Expression::const_from_i64(8),
+    ));
+    alloc_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_malloc", "malloc", Some("access_blk")));
+    // A read-modify-write instruction like `add [rax+8], 1`, i.e. a load and a store
+    // at the same instruction address, both accessing the first byte after the buffer.
+    let mut access_blk = Blk::mock_with_tid("access_blk");
+    let mut load = Def::load(
+        "instr_00001000_0",
+        Variable::mock("RCX", 8),
+        Expression::var("RAX", 8).plus_const(8),
+    );
+    load.tid.address = "00001000".to_string();
+    let mut store = Def::store(
+        "instr_00001000_1",
+        Expression::var("RAX", 8).plus_const(8),
+        Expression::var("RCX", 8).plus_const(1),
+    );
+    store.tid.address = "00001000".to_string();
+    access_blk.term.defs = vec![load, store];
+    access_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let main = Sub::mock_with_blocks("main", vec![alloc_blk, access_blk]);
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+    let pointer_inference = run(
+        &project,
+        &runtime_memory_image,
+        &graph,
+        mock_config(),
+        false,
+        false,
+    );
+    let warnings = &pointer_inference.collected_logs.1;
+    let names: Vec<&str> = warnings
+        .iter()
+        .map(|warning| warning.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["CWE125", "CWE787"]);
+    assert!(warnings
+        .iter()
+        .all(|warning| warning.addresses[0] == "00001000"
+            && warning.description.contains("is out of bounds")));
+}
+
+/// Mock a project where `main` allocates a buffer of 8 bytes
+/// and then writes 4 bytes to `buf + RCX + RDX` after a diamond-shaped control flow.
+/// The values of `RCX` and `RDX` are set to the given pairs of constants in the two branches of the diamond.
+fn mock_project_with_diamond(left: (i64, i64), right: (i64, i64)) -> Project {
+    let mut project = Project::mock_empty();
+    let cconv =
+        CallingConvention::mock_with_parameter_registers(vec![Variable::mock("RDI", 8)], vec![]);
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    project.add_mock_extern_symbol("malloc");
+
+    let mut alloc_blk = Blk::mock_with_tid("alloc_blk");
+    alloc_blk.term.defs.push(Def::assign(
+        "set_size",
+        Variable::mock("RDI", 8),
+        Expression::const_from_i64(8),
+    ));
+    alloc_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_malloc", "malloc", Some("branch_blk")));
+    let branch_tid = |name: &str| {
+        let mut tid = Tid::new(name);
+        tid.address = match name {
+            "left_blk" => "00000100",
+            _ => "00000200",
+        }
+        .to_string();
+        tid
+    };
+    let mut branch_blk = Blk::mock_with_tid("branch_blk");
+    branch_blk.term.jmps = vec![
+        Term {
+            tid: Tid::new("branch_left"),
+            term: Jmp::CBranch {
+                target: branch_tid("left_blk"),
+                condition: Expression::var("ZF", 1),
+            },
+        },
+        Term {
+            tid: Tid::new("branch_right"),
+            term: Jmp::Branch(branch_tid("right_blk")),
+        },
+    ];
+    let mock_branch_blk = |name: &str, (rcx, rdx): (i64, i64)| {
+        let mut blk = Blk::mock_with_tid(name);
+        blk.tid = branch_tid(name);
+        blk.term.defs = vec![
+            Def::assign(
+                &format!("{}_set_rcx", name),
+                Variable::mock("RCX", 8),
+                Expression::const_from_i64(rcx),
+            ),
+            Def::assign(
+                &format!("{}_set_rdx", name),
+                Variable::mock("RDX", 8),
+                Expression::const_from_i64(rdx),
+            ),
+        ];
+        blk.term
+            .jmps
+            .push(Jmp::branch(&format!("{}_jmp", name), "join_blk"));
+        blk
+    };
+    let mut join_blk = Blk::mock_with_tid("join_blk");
+    let mut store = Def::store(
+        "instr_00001000_0",
+        Expression::var("RAX", 8)
+            .plus(Expression::var("RCX", 8))
+            .plus(Expression::var("RDX", 8)),
+        Expression::const_from_i32(0),
+    );
+    store.tid.address = "00001000".to_string();
+    join_blk.term.defs.push(store);
+    join_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let main = Sub::mock_with_blocks(
+        "main",
+        vec![
+            alloc_blk,
+            branch_blk,
+            mock_branch_blk("left_blk", left),
+            mock_branch_blk("right_blk", right),
+            join_blk,
+        ],
+    );
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+    project
+}
+
+#[test]
+fn attribute_warnings_to_predecessors() {
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let config = Config {
+        attribute_warning_paths: true,
+        ..mock_config()
+    };
+    let get_path_attribution = |left, right| {
+        let project = mock_project_with_diamond(left, right);
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let pointer_inference = run(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            config.clone(),
+            false,
+            false,
+        );
+        let warnings = &pointer_inference.collected_logs.1;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "CWE787");
+        let evidence = serde_json::to_value(warnings[0].evidence.as_ref().unwrap()).unwrap();
+        evidence["path_attribution"].clone()
+    };
+
+    // Only the right branch writes out of bounds.
+    let attribution = get_path_attribution((0, 0), (8, 0));
+    let mut predecessors: Vec<(String, bool, bool)> = attribution["predecessors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|predecessor| {
+            (
+                predecessor["address"].as_str().unwrap().to_string(),
+                predecessor["violates_bound"].as_bool().unwrap(),
+                predecessor["loop_back_edge"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    predecessors.sort();
+    assert_eq!(
+        predecessors,
+        vec![
+            ("00000100".to_string(), false, false),
+            ("00000200".to_string(), true, false)
+        ]
+    );
+    assert_eq!(
+        attribution["explanation"],
+        "The bound is violated by the states from the predecessors at 00000200."
+    );
+
+    // Both branches write to offset 4, but the merged state loses the correlation between RCX and RDX.
+    let attribution = get_path_attribution((0, 4), (4, 0));
+    assert!(attribution["predecessors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|predecessor| predecessor["violates_bound"] == false));
+    assert!(attribution["explanation"]
+        .as_str()
+        .unwrap()
+        .starts_with("No predecessor violates the bound on its own"));
+}
+
+/// Mock a project where `main` allocates a buffer of 100 bytes
+/// and writes to it in a loop `for (int i = 0; i < bound; i++) buf[i] = 0`
+/// with the loop counter `i` stored on the stack.
+fn mock_project_with_buffer_loop(bound: i64) -> Project {
+    let mut project = Project::mock_empty();
+    let cconv =
+        CallingConvention::mock_with_parameter_registers(vec![Variable::mock("RDI", 8)], vec![]);
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    project.add_mock_extern_symbol("malloc");
+
+    let mut alloc_blk = Blk::mock_with_tid("alloc_blk");
+    alloc_blk.term.defs.push(Def::assign(
+        "set_size",
+        Variable::mock("RDI", 8),
+        Expression::const_from_i64(100),
+    ));
+    alloc_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_malloc", "malloc", Some("init_blk")));
+    let counter_address = || Expression::var("RSP", 8).minus_const(8);
+    let mut init_blk = Blk::mock_with_tid("init_blk");
+    init_blk.term.defs.push(Def::store(
+        "init_counter",
+        counter_address(),
+        Expression::const_from_i32(0),
+    ));
+    init_blk
+        .term
+        .jmps
+        .push(Jmp::branch("jmp_to_head", "head_blk"));
+    let mut head_blk = Blk::mock_with_tid("head_blk");
+    head_blk.term.defs.push(Def::load(
+        "load_counter_for_check",
+        Variable::mock("EDX", 4),
+        counter_address(),
+    ));
+    head_blk.term.jmps = vec![
+        Term {
+            tid: Tid::new("check_bound"),
+            term: Jmp::CBranch {
+                target: Tid::new("exit_blk"),
+                condition: Expression::BinOp {
+                    op: BinOpType::IntSLess,
+                    lhs: Box::new(Expression::const_from_i32(bound as i32 - 1)),
+                    rhs: Box::new(Expression::var("EDX", 4)),
+                },
+            },
+        },
+        Jmp::branch("jmp_to_body", "body_blk"),
+    ];
+    let mut body_blk = Blk::mock_with_tid("body_blk");
+    let mut store = Def::store(
+        "instr_00001000_0",
+        Expression::var("RAX", 8).plus(Expression::var("RCX", 8)),
+        Expression::const_from_i64(0).subpiece(ByteSize::new(0), ByteSize::new(1)),
+    );
+    store.tid.address = "00001000".to_string();
+    body_blk.term.defs = vec![
+        Def::load("load_counter", Variable::mock("ESI", 4), counter_address()),
+        Def::assign(
+            "extend_counter",
+            Variable::mock("RCX", 8),
+            Expression::Cast {
+                op: CastOpType::IntSExt,
+                size: ByteSize::new(8),
+                arg: Box::new(Expression::var("ESI", 4)),
+            },
+        ),
+        store,
+        Def::store(
+            "increment_counter",
+            counter_address(),
+            Expression::var("ESI", 4).plus_const(1),
+        ),
+    ];
+    body_blk.term.jmps.push(Jmp::branch("jmp_back", "head_blk"));
+    let mut exit_blk = Blk::mock_with_tid("exit_blk");
+    exit_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let main = Sub::mock_with_blocks(
+        "main",
+        vec![alloc_blk, init_blk, head_blk, body_blk, exit_blk],
+    );
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+    project
+}
+
+#[test]
+fn widening_thresholds_from_loop_bounds() {
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    for (bound, expected_warnings) in [(100, vec![]), (101, vec!["CWE787"])] {
+        let project = mock_project_with_buffer_loop(bound);
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let pointer_inference = run(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            mock_config(),
+            false,
+            false,
+        );
+        // The loop counter is widened to the loop bound instead of the type bounds.
+        let mut store_tid = Tid::new("instr_00001000_0");
+        store_tid.address = "00001000".to_string();
+        let counter = pointer_inference
+            .get_state_before_term(&store_tid)
+            .unwrap()
+            .get_register(&Variable::mock("RCX", 8));
+        assert_eq!(
+            crate::abstract_domain::TryToInterval::try_to_offset_interval(
+                counter.get_if_absolute_value().unwrap()
+            )
+            .unwrap(),
+            (0, bound - 1)
+        );
+        let names: Vec<&str> = pointer_inference
+            .collected_logs
+            .1
+            .iter()
+            .map(|warning| warning.name.as_str())
+            .collect();
+        assert_eq!(names, expected_warnings);
+    }
+}
+
+/// Create a project where `main` allocates an object and passes it to `keep`, which stores it in global memory,
+/// and a second object to `destroy`, which frees it.
+/// Afterwards `main` reads from the freed object.
+fn mock_project_with_destroy_helper() -> Project {
+    let mut project = Project::mock_empty();
+    // Calls do not push the return address to the stack.
+    project.architecture = Arch::Aarch64;
+    let cconv =
+        CallingConvention::mock_with_parameter_registers(vec![Variable::mock("RDI", 8)], vec![]);
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    for name in ["malloc", "free"] {
+        project.add_mock_extern_symbol(name);
+    }
+    let return_jmp = |name: &str| Term {
+        tid: Tid::new(name),
+        term: Jmp::Return(Expression::const_from_i64(0)),
+    };
+    let destroy = Sub::mock_with_blocks(
+        "destroy",
+        vec![
+            Blk::mock_with_jmp(
+                "destroy_blk",
+                vec![],
+                Jmp::call("call_free", "free", Some("destroy_return_blk")),
+            ),
+            Blk::mock_with_jmp("destroy_return_blk", vec![], return_jmp("destroy_return")),
+        ],
+    );
+    let mut keep = Sub::mock("keep");
+    keep.term.blocks = vec![Blk::mock_with_jmp(
+        "keep_blk",
+        vec![Def::store(
+            "store_to_global",
+            Expression::const_from_i64(0x2000),
+            Expression::var("RDI", 8),
+        )],
+        return_jmp("keep_return"),
+    )];
+    let alloc_block = |name: &str, return_blk: &str| {
+        Blk::mock_with_jmp(
+            name,
+            vec![Def::assign(
+                &format!("{}_size", name),
+                Variable::mock("RDI", 8),
+                Expression::const_from_i64(8),
+            )],
+            Jmp::call(&format!("{}_malloc", name), "malloc", Some(return_blk)),
+        )
+    };
+    let pass_block = |name: &str, offset: i64, callee: &str, return_blk: &str| {
+        Blk::mock_with_jmp(
+            name,
+            vec![
+                Def::store(
+                    &format!("{}_save", name),
+                    Expression::var("RSP", 8).plus_const(offset),
+                    Expression::var("RAX", 8),
+                ),
+                Def::assign(
+                    &format!("{}_arg", name),
+                    Variable::mock("RDI", 8),
+                    Expression::var("RAX", 8),
+                ),
+            ],
+            Jmp::call(&format!("{}_call", name), callee, Some(return_blk)),
+        )
+    };
+    let mut load_content = Def::load(
+        "load_content",
+        Variable::mock("RAX", 8),
+        Expression::var("RDI", 8),
+    );
+    load_content.tid.address = "00002000".to_string();
+    let mut main = Sub::mock_with_blocks(
+        "main",
+        vec![
+            alloc_block("alloc_kept", "pass_kept"),
+            pass_block("pass_kept", 8, "keep", "alloc_freed"),
+            alloc_block("alloc_freed", "pass_freed"),
+            pass_block("pass_freed", 0, "destroy", "use_freed"),
+            Blk::mock_with_jmp(
+                "use_freed",
+                vec![
+                    Def::load(
+                        "load_freed",
+                        Variable::mock("RDI", 8),
+                        Expression::var("RSP", 8),
+                    ),
+                    load_content,
+                ],
+                return_jmp("main_return"),
+            ),
+        ],
+    );
+    main.term.blocks[0].term.defs.insert(
+        0,
+        Def::assign(
+            "allocate_stack_frame",
+            Variable::mock("RSP", 8),
+            Expression::var("RSP", 8).minus_const(32),
+        ),
+    );
+    for sub in [destroy, keep, main] {
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+    }
+    project
+}
+
+#[test]
+fn parameter_ownership_of_destroy_helper() {
+    let project = mock_project_with_destroy_helper();
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs: HashSet<Tid> = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs.clone());
+    // `destroy` is excluded from the analysis, so the caller only knows its effects through the summaries.
+    let graph_without_destroy = crate::analysis::graph::get_program_cfg_with_skipped_subs(
+        &project.program,
+        extern_subs,
+        HashSet::from([Tid::new("destroy")]),
+    );
+    let freed_object = crate::abstract_domain::AbstractIdentifier::new(
+        Tid::new("alloc_freed_malloc"),
+        crate::abstract_domain::AbstractLocation::from_var(&Variable::mock("RAX", 8)).unwrap(),
+    );
+    // The state of the object passed to `destroy` before its use at the end of `main`.
+    let state_of_freed_object = |pointer_inference: &PointerInference| {
+        let mut tid = Tid::new("load_content");
+        tid.address = "00002000".to_string();
+        pointer_inference
+            .get_state_before_term(&tid)
+            .unwrap()
+            .memory
+            .get_object_state(&freed_object)
+            .unwrap()
+    };
+    let uaf_warnings = |pointer_inference: &PointerInference| {
+        pointer_inference
+            .collected_logs
+            .1
+            .iter()
+            .filter(|warning| warning.name == "CWE416")
+            .count()
+    };
+
+    let pointer_inference = run(
+        &project,
+        &runtime_memory_image,
+        &graph,
+        mock_config(),
+        false,
+        false,
+    );
+    let mut fn_sigs: BTreeMap<Tid, FunctionSignature> = project
+        .program
+        .term
+        .subs
+        .keys()
+        .map(|tid| (tid.clone(), FunctionSignature::new()))
+        .collect();
+    add_parameter_ownership(&project, &pointer_inference, &mut fn_sigs);
+    let rdi = Variable::mock("RDI", 8);
+    assert_eq!(
+        fn_sigs[&Tid::new("destroy")].parameter_ownership[&rdi],
+        ParameterOwnership::Freed
+    );
+    assert_eq!(
+        fn_sigs[&Tid::new("keep")].parameter_ownership[&rdi],
+        ParameterOwnership::Stored
+    );
+
+    // Without the summaries the call to `destroy` is handled like a call to an unknown function.
+    let pointer_inference = run(
+        &project,
+        &runtime_memory_image,
+        &graph_without_destroy,
+        mock_config(),
+        false,
+        false,
+    );
+    assert_eq!(
+        state_of_freed_object(&pointer_inference),
+        ObjectState::Alive
+    );
+    assert_eq!(uaf_warnings(&pointer_inference), 0);
+
+    let config = Config {
+        parameter_ownership: get_ownership_classifications(&fn_sigs),
+        ..mock_config()
+    };
+    let pointer_inference = run(
+        &project,
+        &runtime_memory_image,
+        &graph_without_destroy,
+        config,
+        false,
+        false,
+    );
+    assert_eq!(
+        state_of_freed_object(&pointer_inference),
+        ObjectState::Dangling
+    );
+    assert_eq!(uaf_warnings(&pointer_inference), 1);
+}
+
+#[test]
+fn riscv_stack_round_trip() {
+    let mut project = Project::mock_empty();
+    project.cpu_architecture = "RISCV:LE:64:RV64GC".to_string();
+    project.architecture = Arch::RiscV;
+    project.stack_pointer_register = Variable::mock("sp", 8);
+    let cconv = CallingConvention::mock_riscv64();
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    let mut callee = ExternSymbol::mock_riscv64();
+    callee.tid = Tid::new("callee");
+    project
+        .program
+        .term
+        .extern_symbols
+        .insert(callee.tid.clone(), callee);
+
+    let sp = || Expression::var("sp", 8);
+    // The prologue and epilogue as generated from compressed instructions,
+    // e.g. `c.addi16sp sp,-32` and `c.sdsp s1,8(sp)`.
+    let mut entry_blk = Blk::mock_with_tid("entry_blk");
+    entry_blk.term.defs = vec![
+        Def::assign("alloc_frame", Variable::mock("sp", 8), sp().plus_const(-32)),
+        Def::store("save_ra", sp().plus_const(24), Expression::var("ra", 8)),
+        Def::assign(
+            "set_s0",
+            Variable::mock("s0", 8),
+            Expression::const_from_i64(7),
+        ),
+        Def::assign(
+            "set_s1",
+            Variable::mock("s1", 8),
+            Expression::const_from_i64(42),
+        ),
+        Def::store("spill_s1", sp().plus_const(8), Expression::var("s1", 8)),
+        Def::assign(
+            "set_a0",
+            Variable::mock("a0", 8),
+            Expression::const_from_i64(0),
+        ),
+    ];
+    entry_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_callee", "callee", Some("exit_blk")));
+    let mut exit_blk = Blk::mock_with_tid("exit_blk");
+    exit_blk.term.defs = vec![
+        Def::load("reload", Variable::mock("a1", 8), sp().plus_const(8)),
+        Def::load("restore_ra", Variable::mock("ra", 8), sp().plus_const(24)),
+        Def::assign("free_frame", Variable::mock("sp", 8), sp().plus_const(32)),
+    ];
+    exit_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("ra", 8)),
+    });
+    let main = Sub::mock_with_blocks("main", vec![entry_blk, exit_blk]);
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+    let pointer_inference = run(
+        &project,
+        &runtime_memory_image,
+        &graph,
+        mock_config(),
+        false,
+        false,
+    );
+    let state = pointer_inference
+        .get_state_before_term(&Tid::new("main_return"))
+        .unwrap();
+    // The value spilled to the stack survives the call.
+    assert_eq!(
+        state.get_register(&Variable::mock("a1", 8)),
+        Bitvector::from_i64(42).into()
+    );
+    // `s0` is callee-saved, `a0` is not.
+    assert_eq!(
+        state.get_register(&Variable::mock("s0", 8)),
+        Bitvector::from_i64(7).into()
+    );
+    assert!(state.get_register(&Variable::mock("a0", 8)).contains_top());
+    let stack_id = crate::abstract_domain::AbstractIdentifier::new(
+        Tid::new("main"),
+        crate::abstract_domain::AbstractLocation::from_var(&Variable::mock("sp", 8)).unwrap(),
+    );
+    assert_eq!(
+        state.get_register(&Variable::mock("sp", 8)),
+        Data::from_target(stack_id, Bitvector::from_i64(0).into())
+    );
+    assert!(pointer_inference.collected_logs.1.is_empty());
+}
+
+#[test]
+fn suffixed_addresses_are_deduplicated() {
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    for suffix in ["_1", "_2"] {
+        let mut tid = Tid::new(format!("instr_00001000{}", suffix));
+        tid.address = format!("00001000{}", suffix);
+        sender
+            .send(LogThreadMsg::Log(
+                LogMessage::new_debug("message").location(tid.clone()),
+            ))
+            .unwrap();
+        sender
+            .send(LogThreadMsg::Cwe(
+                CweWarning::new("CWE476", "0.1", "warning")
+                    .addresses(vec![tid.address.clone()])
+                    .tids(vec![format!("{}", tid)]),
+            ))
+            .unwrap();
+    }
+    sender.send(LogThreadMsg::Terminate).unwrap();
+    let (logs, warnings) = collect_all_logs(receiver);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].addresses, vec!["00001000".to_string()]);
+    // The full TID is retained for debugging.
+    assert_eq!(warnings[0].tids, vec!["instr_00001000_2".to_string()]);
+}
+
+#[test]
+fn values_kept_across_pure_calls() {
+    let mut project = Project::mock_empty();
+    let cconv =
+        CallingConvention::mock_with_parameter_registers(vec![Variable::mock("RDI", 8)], vec![]);
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+
+    // The getter only reads its argument and writes RAX.
+    let mut getter_blk = Blk::mock_with_tid("getter_blk");
+    getter_blk.term.defs.push(Def::assign(
+        "getter_result",
+        Variable::mock("RAX", 8),
+        Expression::var("RDI", 8).plus_const(1),
+    ));
+    getter_blk.term.defs.push(Def::assign(
+        "getter_pop_return_address",
+        Variable::mock("RSP", 8),
+        Expression::var("RSP", 8).plus_const(8),
+    ));
+    getter_blk.term.jmps.push(Term {
+        tid: Tid::new("getter_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let getter = Sub::mock_with_blocks("getter", vec![getter_blk]);
+
+    // The caller holds different values in the caller-saved register R8 at both call sites.
+    let mut main_blk = Blk::mock_with_tid("main_blk");
+    main_blk.term.defs.push(Def::assign(
+        "set_local_1",
+        Variable::mock("R8", 8),
+        Expression::const_from_i64(0x1234),
+    ));
+    main_blk.term.jmps.push(Jmp::call(
+        "call_getter_1",
+        "getter",
+        Some("main_second_blk"),
+    ));
+    let mut main_second_blk = Blk::mock_with_tid("main_second_blk");
+    main_second_blk.term.defs.push(Def::assign(
+        "set_local_2",
+        Variable::mock("R8", 8),
+        Expression::const_from_i64(0x5678),
+    ));
+    main_second_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_getter_2", "getter", Some("main_ret_blk")));
+    let mut main_ret_blk = Blk::mock_with_tid("main_ret_blk");
+    main_ret_blk.term.jmps.push(Term {
+        tid: Tid::new("main_return"),
+        term: Jmp::Return(Expression::var("RBX", 8)),
+    });
+    let main = Sub::mock_with_blocks("main", vec![main_blk, main_second_blk, main_ret_blk]);
+
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+    project.program.term.subs.insert(getter.tid.clone(), getter);
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let (_, function_signatures) =
+        crate::analysis::function_signature::compute_function_signatures(&project, &graph);
+    let purities =
+        crate::analysis::function_signature::get_purity_classifications(&function_signatures);
+    assert_eq!(purities.len(), 2);
+    let getter_purity = purities
+        .iter()
+        .find(|classification| classification.function == Tid::new("getter"))
+        .unwrap();
+    assert_eq!(
+        getter_purity.purity,
+        crate::analysis::function_signature::FunctionPurity::Pure
+    );
+    assert_eq!(
+        getter_purity.written_registers,
+        BTreeSet::from([Variable::mock("RAX", 8), Variable::mock("RSP", 8)])
+    );
+
+    let after_first_call = graph
+        .node_indices()
+        .find(|node| {
+            matches!(graph[*node], Node::BlkStart(block, _) if block.tid == Tid::new("main_second_blk"))
+        })
+        .unwrap();
+    let local_after_first_call = |config: Config| {
+        let (log_sender, _) = crossbeam_channel::unbounded();
+        let mut pointer_inference = PointerInference::new(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            config,
+            log_sender,
+            false,
+        );
+        pointer_inference.compute();
+        pointer_inference
+            .get_node_value(after_first_call)
+            .unwrap()
+            .unwrap_value()
+            .get_register(&Variable::mock("R8", 8))
+    };
+
+    // Without purity information the values of both call sites get mixed up in the callee.
+    assert_ne!(
+        local_after_first_call(mock_config()),
+        Data::from(Bitvector::from_i64(0x1234))
+    );
+    let mut config = mock_config();
+    config.function_purities = purities;
+    assert_eq!(
+        local_after_first_call(config),
+        Data::from(Bitvector::from_i64(0x1234))
+    );
+}
+
+#[test]
+fn node_value_retention() {
+    let mut project = Project::mock_empty();
+    let cconv = CallingConvention::mock_with_parameter_registers(
+        vec![Variable::mock("RDI", 8)],
+        vec![Variable::mock("RAX", 8)],
+    );
+    project
+        .calling_conventions
+        .insert(cconv.name.clone(), cconv);
+    project.add_mock_extern_symbol("malloc");
+
+    let mut alloc_blk = Blk::mock_with_tid("alloc_blk");
+    alloc_blk.term.defs.push(Def::assign(
+        "set_size",
+        Variable::mock("RDI", 8),
+        Expression::const_from_i64(16),
+    ));
+    alloc_blk
+        .term
+        .jmps
+        .push(Jmp::call("call_malloc", "malloc", Some("store_blk")));
+    let mut store_blk = Blk::mock_with_tid("store_blk");
+    store_blk.term.defs.push(Def::store(
+        "store",
+        Expression::var("RAX", 8),
+        Expression::const_from_i64(0),
+    ));
+    store_blk
+        .term
+        .jmps
+        .push(Jmp::branch("jmp_assign", "assign_blk"));
+    let mut assign_blk = Blk::mock_with_tid("assign_blk");
+    assign_blk.term.defs.push(Def::assign(
+        "assign",
+        Variable::mock("RBX", 8),
+        Expression::var("RAX", 8),
+    ));
+    assign_blk
+        .term
+        .jmps
+        .push(Jmp::branch("jmp_return", "return_blk"));
+    let return_blk = Blk::mock_return("return_blk");
+    let main = Sub::mock_with_blocks("main", vec![alloc_blk, store_blk, assign_blk, return_blk]);
+    project.program.term.entry_points.insert(main.tid.clone());
+    project.program.term.subs.insert(main.tid.clone(), main);
+
+    let runtime_memory_image = RuntimeMemoryImage::mock();
+    let extern_subs = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+    let compute = |retention| {
+        let mut pointer_inference = PointerInference::mock_with_node_value_retention(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            retention,
+        );
+        pointer_inference.compute();
+        pointer_inference.discard_unretained_node_values();
+        pointer_inference
+    };
+
+    let all = compute(NodeValueRetention::All);
+    assert_eq!(all.computation.node_values().len(), 8);
+    assert!(all.try_get_state_before_term(&Tid::new("assign")).is_ok());
+
+    // The states at the start of `assign_blk` and `return_blk` and at the end of `assign_blk` are discarded.
+    let call_sites_and_accesses = compute(NodeValueRetention::CallSitesAndAccesses);
+    assert_eq!(call_sites_and_accesses.computation.node_values().len(), 5);
+    assert!(call_sites_and_accesses
+        .try_get_state_before_term(&Tid::new("store"))
+        .is_ok());
+    assert!(call_sites_and_accesses
+        .try_get_state_before_term(&Tid::new("call_malloc"))
+        .is_ok());
+    let err = call_sites_and_accesses
+        .try_get_state_before_term(&Tid::new("assign"))
+        .unwrap_err();
+    assert!(err.to_string().contains("CallSitesAndAccesses"));
+    assert!(call_sites_and_accesses
+        .get_state_before_term(&Tid::new("assign"))
+        .is_none());
+
+    // Only the states at the start of `alloc_blk` and at the end of `return_blk` are kept.
+    let entry_exit_only = compute(NodeValueRetention::EntryExitOnly);
+    assert_eq!(entry_exit_only.computation.node_values().len(), 2);
+    assert!(entry_exit_only
+        .try_get_state_before_term(&Tid::new("set_size"))
+        .is_ok());
+    assert!(entry_exit_only
+        .try_get_state_before_term(&Tid::new("store"))
+        .is_err());
+    assert!(entry_exit_only
+        .try_get_state_before_term(&Tid::new("unknown"))
+        .unwrap_err()
+        .to_string()
+        .contains("not contained"));
+
+    // A checkpoint of restricted results cannot be used if more node values are required.
+    let checkpoint = call_sites_and_accesses.to_checkpoint();
+    let config = |node_value_retention| Config {
+        node_value_retention,
+        ..mock_config()
+    };
+    assert!(PointerInference::from_checkpoint(
+        &project,
+        &runtime_memory_image,
+        &graph,
+        config(NodeValueRetention::CallSitesAndAccesses),
+        checkpoint.clone(),
+    )
+    .is_ok());
+    assert!(PointerInference::from_checkpoint(
+        &project,
+        &runtime_memory_image,
+        &graph,
+        config(NodeValueRetention::All),
+        checkpoint,
+    )
+    .is_err());
+}
@@ -0,0 +1,66 @@
+//! Modeling of thread creation functions like `pthread_create`.
+//!
+//! A thread creation function starts a new thread executing the given start routine,
+//! which receives an argument chosen by the creator of the thread.
+//! Start routines given as constant function addresses are added as entry points of the analysis.
+//! In their entry state the first parameter is bound to the same abstract value
+//! that the argument had at the creation site,
+//! so that memory accesses to objects shared between the threads can be checked in the start routine.
+//!
+//! Since both threads may access the shared objects concurrently,
+//! the shared objects are marked as non-unique in the creating thread and in the new thread.
+
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The parameter indices of a `pthread_create`-like extern function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ThreadCreationParameters {
+    /// The index of the parameter containing the start routine of the new thread.
+    pub start_routine_index: usize,
+    /// The index of the parameter containing the argument passed to the start routine.
+    pub argument_index: usize,
+}
+
+/// Note the creation sites of the corresponding thread
+/// in all CWE warnings generated for instructions of thread start routines.
+///
+/// `thread_creation_sites` maps the TIDs of the start routines to the TIDs of the calls creating the threads.
+/// Warnings for instructions in functions called by a start routine are not annotated.
+pub fn annotate_warnings_in_start_routines(
+    project: &Project,
+    thread_creation_sites: &BTreeMap<Tid, BTreeSet<Tid>>,
+    warnings: &mut [CweWarning],
+) {
+    for (sub_tid, creation_sites) in thread_creation_sites {
+        let sub = match project.program.term.subs.get(sub_tid) {
+            Some(sub) => sub,
+            None => continue,
+        };
+        let mut term_tids = BTreeSet::new();
+        for block in sub.term.blocks.iter() {
+            term_tids.insert(format!("{}", block.tid));
+            term_tids.extend(block.term.defs.iter().map(|def| format!("{}", def.tid)));
+            term_tids.extend(block.term.jmps.iter().map(|jmp| format!("{}", jmp.tid)));
+        }
+        let creation_addresses: Vec<String> = creation_sites
+            .iter()
+            .map(|tid| tid.address.clone())
+            .collect();
+        for warning in warnings.iter_mut() {
+            if warning.tids.iter().any(|tid| term_tids.contains(tid)) {
+                warning.description = format!(
+                    "{} (in the thread start routine {} created at {})",
+                    warning.description,
+                    sub.term.name,
+                    creation_addresses.join(", ")
+                );
+                let mut other = vec!["thread_creation_sites".to_string()];
+                other.extend(creation_addresses.iter().cloned());
+                warning.other.push(other);
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Recovery of the stack frame layouts of functions from the results of the pointer inference analysis.
+//!
+//! For each function we collect all memory accesses to its own stack frame
+//! whose offsets relative to the stack pointer at the start of the function are constant.
+//! Overlapping accesses are merged into a single stack variable.
+//! The accesses are read from the states at the start of each block computed by the pointer inference,
+//! so no additional fixpoint computation is needed.
+//!
+//! Accesses with non-constant offsets (e.g. array accesses in loops)
+//! and accesses to the stack frame of a caller through pointer parameters are not part of the layout.
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::access_histogram::AccessKind;
+use crate::analysis::pointer_inference::{PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use petgraph::visit::IntoNodeReferences;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A range of the stack frame of a function that is accessed by the function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct StackVariable {
+    /// The offset of the variable relative to the stack pointer at the start of the function.
+    pub offset: i64,
+    /// The size of the variable in bytes.
+    pub size: u64,
+    /// The kinds of the accesses to the variable.
+    pub access_kinds: BTreeSet<AccessKind>,
+    /// The lowest address of an instruction accessing the variable.
+    pub first_access: String,
+}
+
+impl StackVariable {
+    /// The offset after the last byte of the variable.
+    fn end_offset(&self) -> i64 {
+        self.offset + self.size as i64
+    }
+}
+
+/// The layout of the stack frame of a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct StackFrameLayout {
+    /// The variables of the stack frame, sorted by offset.
+    /// The ranges of the variables do not overlap.
+    pub variables: Vec<StackVariable>,
+}
+
+impl StackFrameLayout {
+    /// Generate the layout for the given accesses to the stack frame.
+    /// Overlapping accesses are merged into one variable.
+    pub fn from_accesses(mut accesses: Vec<StackVariable>) -> StackFrameLayout {
+        accesses.sort_by_key(|access| access.offset);
+        let mut variables: Vec<StackVariable> = Vec::new();
+        for access in accesses {
+            match variables.last_mut() {
+                Some(variable) if access.offset < variable.end_offset() => {
+                    variable.size = (std::cmp::max(variable.end_offset(), access.end_offset())
+                        - variable.offset) as u64;
+                    variable.access_kinds.extend(access.access_kinds);
+                    if access.first_access < variable.first_access {
+                        variable.first_access = access.first_access;
+                    }
+                }
+                _ => variables.push(access),
+            }
+        }
+        StackFrameLayout { variables }
+    }
+
+    /// Get the variable containing the byte at the given offset.
+    pub fn get_variable(&self, offset: i64) -> Option<&StackVariable> {
+        self.variables
+            .iter()
+            .find(|variable| variable.offset <= offset && offset < variable.end_offset())
+    }
+}
+
+/// Compute the stack frame layouts of all functions analyzed by the pointer inference.
+///
+/// The states at the start of blocks containing memory accesses have to be kept by the pointer inference,
+/// i.e. the node value retention policy has to be at least
+/// [`CallSitesAndAccesses`](crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses).
+pub fn compute_stack_frame_layouts(
+    pointer_inference: &PointerInference,
+) -> BTreeMap<Tid, StackFrameLayout> {
+    let global_memory = pointer_inference.get_context().runtime_memory_image;
+    let mut accesses: BTreeMap<Tid, Vec<StackVariable>> = BTreeMap::new();
+    for (node, node_weight) in pointer_inference.get_graph().node_references() {
+        let (block, sub) = match node_weight {
+            Node::BlkStart(block, sub) => (block, sub),
+            _ => continue,
+        };
+        let mut state = match pointer_inference.get_node_value(node) {
+            Some(NodeValue::Value(state)) => state.clone(),
+            _ => continue,
+        };
+        let sub_accesses = accesses.entry(sub.tid.clone()).or_default();
+        for def in block.term.defs.iter() {
+            sub_accesses.extend(get_stack_access(&state, def));
+            state.apply_defs(std::slice::from_ref(def), global_memory);
+        }
+    }
+    accesses
+        .into_iter()
+        .map(|(sub_tid, sub_accesses)| (sub_tid, StackFrameLayout::from_accesses(sub_accesses)))
+        .collect()
+}
+
+/// Get the access of the given `Load` or `Store` instruction to the current stack frame
+/// if the accessed offset is constant.
+fn get_stack_access(state: &State, def: &Term<Def>) -> Option<StackVariable> {
+    let (address, size, kind) = match &def.term {
+        Def::Load { var, address } => (address, var.size, AccessKind::Read),
+        Def::Store { address, value } => (address, value.bytesize(), AccessKind::Write),
+        Def::Assign { .. } => return None,
+    };
+    let offset = state
+        .eval(address)
+        .get_relative_values()
+        .get(&state.stack_id)?
+        .try_to_offset()
+        .ok()?;
+    Some(StackVariable {
+        offset,
+        size: u64::from(size),
+        access_kinds: BTreeSet::from([kind]),
+        first_access: def.tid.canonical_address().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use std::collections::HashSet;
+
+    fn mock_access(offset: i64, size: u64, kind: AccessKind, address: &str) -> StackVariable {
+        StackVariable {
+            offset,
+            size,
+            access_kinds: BTreeSet::from([kind]),
+            first_access: address.to_string(),
+        }
+    }
+
+    #[test]
+    fn merge_overlapping_accesses() {
+        let layout = StackFrameLayout::from_accesses(vec![
+            mock_access(-8, 8, AccessKind::Write, "00001010"),
+            mock_access(-16, 4, AccessKind::Write, "00001020"),
+            mock_access(-4, 4, AccessKind::Read, "00001000"),
+            mock_access(-12, 4, AccessKind::Read, "00001030"),
+        ]);
+        assert_eq!(
+            layout.variables,
+            vec![
+                mock_access(-16, 4, AccessKind::Write, "00001020"),
+                mock_access(-12, 4, AccessKind::Read, "00001030"),
+                StackVariable {
+                    offset: -8,
+                    size: 8,
+                    access_kinds: BTreeSet::from([AccessKind::Read, AccessKind::Write]),
+                    first_access: "00001000".to_string(),
+                },
+            ]
+        );
+        assert_eq!(layout.get_variable(-5).unwrap().offset, -8);
+        assert!(layout.get_variable(0).is_none());
+    }
+
+    #[test]
+    fn stack_frame_layout_from_pointer_inference() {
+        let mut project = Project::mock_empty();
+        let mut block = Blk::mock_with_tid("block");
+        block.term.defs = vec![
+            Def::store(
+                "store_local",
+                Expression::var("RSP", 8).plus_const(-16),
+                Expression::const_from_i64(0),
+            ),
+            Def::assign(
+                "set_pointer",
+                Variable::mock("RAX", 8),
+                Expression::var("RSP", 8).plus_const(-12),
+            ),
+            Def::load(
+                "load_local",
+                Variable::mock("ECX", 4),
+                Expression::var("RAX", 8),
+            ),
+            Def::store(
+                "store_unknown_offset",
+                Expression::var("RSP", 8).plus(Expression::var("RDX", 8)),
+                Expression::const_from_i64(0),
+            ),
+            Def::store(
+                "store_other_local",
+                Expression::var("RSP", 8).plus_const(-32),
+                Expression::const_from_i32(0),
+            ),
+        ];
+        block.term.jmps.push(Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        let sub = Sub::mock_with_blocks("func", vec![block]);
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pointer_inference.compute();
+        let layouts = compute_stack_frame_layouts(&pointer_inference);
+
+        let layout = &layouts[&Tid::new("func")];
+        let offsets: Vec<(i64, u64)> = layout
+            .variables
+            .iter()
+            .map(|variable| (variable.offset, variable.size))
+            .collect();
+        assert_eq!(offsets, vec![(-32, 4), (-16, 8)]);
+        assert_eq!(
+            layout.variables[1].access_kinds,
+            BTreeSet::from([AccessKind::Read, AccessKind::Write])
+        );
+    }
+}
@@ -9,14 +9,14 @@ use std::{
 use petgraph::{graph::NodeIndex, visit::IntoNodeReferences};
 
 use crate::{
-    abstract_domain::{AbstractDomain, DomainInsertion, HasTop},
+    abstract_domain::{AbstractDomain, DomainInsertion, HasTop, TryToBitvec},
     analysis::{
         forward_interprocedural_fixpoint::Context as _, graph::Node,
         interprocedural_fixpoint_generic::NodeValue,
         pointer_inference::PointerInference as PointerInferenceComputation,
         pointer_inference::State as PointerInferenceState,
     },
-    intermediate_representation::{Def, ExternSymbol, Project, Term, Tid},
+    intermediate_representation::{Def, ExternSymbol, Jmp, Project, Term, Tid},
     utils::binary::RuntimeMemoryImage,
 };
 
@@ -128,6 +128,27 @@ impl<'a, T: AbstractDomain + HasTop + Eq + From<String> + DomainInsertion> Conte
         }
     }
 
+    /// Get the offset of the stack pointer relative to the stack frame base of the caller
+    /// at the given call instruction, if it is exactly known.
+    fn get_stack_offset_on_call(&self, state: &State<T>, call_term: &Term<Jmp>) -> Option<i64> {
+        let node_id = self
+            .jmp_to_blk_end_node_map
+            .get(&(call_term.tid.clone(), state.get_current_sub()?.tid.clone()))?;
+        let pi_state = match self.pointer_inference_results.get_node_value(*node_id) {
+            Some(NodeValue::Value(pi_state)) => pi_state,
+            _ => return None,
+        };
+        match pi_state
+            .get_register(&self.project.stack_pointer_register)
+            .get_if_unique_target()
+        {
+            Some((stack_id, offset)) if *stack_id == pi_state.stack_id => {
+                offset.try_to_offset().ok()
+            }
+            _ => None,
+        }
+    }
+
     /// Update the pointer inference state contained in the given taint state
     /// according to the effect of the given `Def` term.
     fn update_pointer_inference_state(&self, state: &mut State<T>, def: &Term<Def>) {
@@ -4,7 +4,7 @@ use regex::Regex;
 use std::collections::BTreeMap;
 
 use crate::abstract_domain::{
-    AbstractIdentifier, DomainInsertion, HasTop, IntervalDomain, TryToBitvec,
+    AbstractIdentifier, DomainInsertion, HasTop, IntervalDomain, TryToBitvec, TryToInterval,
 };
 use crate::analysis::pointer_inference::State as PointerInferenceState;
 use crate::intermediate_representation::{Bitvector, Datatype};
@@ -63,7 +63,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                 self.handle_sprintf_and_snprintf_calls(state, extern_symbol)
             }
             "strcat" | "strncat" => self.handle_strcat_and_strncat_calls(state, extern_symbol),
-            "memcpy" => self.handle_memcpy_calls(state, extern_symbol),
+            "memcpy" | "strcpy" | "strncpy" => self.handle_memcpy_calls(state, extern_symbol),
             "free" => self.handle_free(state, extern_symbol),
             _ => panic!("Unexpected Extern Symbol."),
         };
@@ -76,9 +76,14 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         new_state
     }
 
-    /// Takes the pointer target if there is only one and checks whether the target
-    /// is inside the current stack frame. If so, the string domain is added to the
-    /// analysis.
+    /// Adds the string domain to the analysis for all pointer targets.
+    ///
+    /// Stack targets are identified by their offset and heap targets by their abstract identifier,
+    /// where heap strings are assumed to start at offset zero of the heap object.
+    /// If a pointer points into the middle of a tracked string,
+    /// the string domain is inserted into the tracked string at the corresponding offset.
+    /// If the offset is not exactly known, the suffix of the tracked string
+    /// starting at the minimal possible offset is widened.
     pub fn add_new_string_abstract_domain(
         state: &mut State<T>,
         pi_state: &PointerInferenceState,
@@ -88,17 +93,106 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         for (target, offset) in pointer.iter() {
             if pi_state.caller_stack_ids.contains(target) || pi_state.stack_id == *target {
                 if let Ok(offset_value) = offset.try_to_offset() {
+                    Context::<T>::insert_into_enclosing_stack_string(
+                        state,
+                        offset_value,
+                        &domain_input_string,
+                    );
                     state.add_new_stack_offset_to_string_entry(
                         offset_value,
                         domain_input_string.clone(),
                     );
+                } else if let Ok((min_offset, max_offset)) = offset.try_to_offset_interval() {
+                    Context::<T>::widen_stack_strings_in_offset_range(
+                        state, min_offset, max_offset,
+                    );
                 }
             } else {
-                state.add_new_heap_to_string_entry(target.clone(), domain_input_string.clone());
+                let tracked_domain = state.get_heap_to_string_map().get(target);
+                let new_domain = match (offset.try_to_offset(), tracked_domain) {
+                    (Ok(offset_value), Some(tracked_domain)) if offset_value > 0 => tracked_domain
+                        .insert_string_domain_at_offset(
+                            offset_value as usize,
+                            &domain_input_string,
+                        ),
+                    (Ok(offset_value), None) if offset_value > 0 => T::create_top_value_domain()
+                        .insert_string_domain_at_offset(
+                            offset_value as usize,
+                            &domain_input_string,
+                        ),
+                    (Ok(_), _) => domain_input_string.clone(),
+                    (Err(_), Some(tracked_domain)) => match offset.try_to_offset_interval() {
+                        Ok((min_offset, _)) => {
+                            tracked_domain.widen_suffix_from_offset(min_offset.max(0) as usize)
+                        }
+                        Err(_) => T::create_top_value_domain(),
+                    },
+                    (Err(_), None) => T::create_top_value_domain(),
+                };
+                state.add_new_heap_to_string_entry(target.clone(), new_domain);
+            }
+        }
+    }
+
+    /// If the given stack offset lies inside of a tracked stack string,
+    /// insert the string domain into the tracked string at the corresponding offset.
+    fn insert_into_enclosing_stack_string(state: &mut State<T>, offset: i64, domain: &T) {
+        if let Some((string_offset, string_domain)) =
+            Context::<T>::get_enclosing_stack_string(state, offset)
+        {
+            if string_offset < offset {
+                state.add_new_stack_offset_to_string_entry(
+                    string_offset,
+                    string_domain
+                        .insert_string_domain_at_offset((offset - string_offset) as usize, domain),
+                );
             }
         }
     }
 
+    /// Widen the stack strings that may be overwritten by a write to an unknown offset
+    /// inside the given (inclusive) offset range.
+    ///
+    /// The suffix of the tracked string containing the minimal offset is widened.
+    /// Tracked strings starting inside the offset range are set to *Top*.
+    fn widen_stack_strings_in_offset_range(state: &mut State<T>, min_offset: i64, max_offset: i64) {
+        if let Some((string_offset, string_domain)) =
+            Context::<T>::get_enclosing_stack_string(state, min_offset)
+        {
+            state.add_new_stack_offset_to_string_entry(
+                string_offset,
+                string_domain.widen_suffix_from_offset((min_offset - string_offset) as usize),
+            );
+        }
+        let overwritten_offsets: Vec<i64> = state
+            .get_stack_offset_to_string_map()
+            .keys()
+            .filter(|string_offset| min_offset < **string_offset && **string_offset <= max_offset)
+            .copied()
+            .collect();
+        for string_offset in overwritten_offsets {
+            state.add_new_stack_offset_to_string_entry(string_offset, T::create_top_value_domain());
+        }
+    }
+
+    /// Returns the offset and domain of the tracked stack string containing the given offset.
+    ///
+    /// Since the sizes of stack buffers are unknown, only the nearest string starting at or before the offset
+    /// is considered and only if the offset is smaller than the minimal length of the string.
+    /// This prevents writes to adjacent buffers from changing the string.
+    fn get_enclosing_stack_string(state: &State<T>, offset: i64) -> Option<(i64, T)> {
+        state
+            .get_stack_offset_to_string_map()
+            .iter()
+            .filter(|(string_offset, _)| **string_offset <= offset)
+            .max_by_key(|(string_offset, _)| **string_offset)
+            .filter(|(string_offset, domain)| {
+                offset == **string_offset
+                    || ((offset - **string_offset) as usize) < domain.get_minimal_length()
+            })
+            .map(|(string_offset, domain)| (*string_offset, domain.clone()))
+    }
+
     /// Regex that filters format specifier from a format string.
     pub fn re_format_specifier() -> Regex {
         Regex::new(r#"%\d{0,2}([c,C,d,i,o,u,x,X,e,E,f,F,g,G,a,A,n,p,s,S]|hi|hd|hu|li|ld|lu|lli|lld|llu|lf|lg|le|la|lF|lG|lE|lA|Lf|Lg|Le|La|LF|LG|LE|LA)"#).expect("No valid regex!")
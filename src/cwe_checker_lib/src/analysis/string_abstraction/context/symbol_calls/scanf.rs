@@ -10,7 +10,7 @@ use crate::{
     abstract_domain::{AbstractDomain, DataDomain, DomainInsertion, HasTop, IntervalDomain},
     analysis::string_abstraction::{context::Context, state::State},
     intermediate_representation::{Arg, Datatype, ExternSymbol},
-    utils::arguments::get_variable_parameters,
+    utils::arguments::get_input_function_parameters,
 };
 
 impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Context<'a, T> {
@@ -20,7 +20,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         let mut new_state = state.clone();
         if let Some(pi_state) = state.get_pointer_inference_state() {
             // Check whether the format string parameters can be parsed.
-            if let Ok(return_values) = get_variable_parameters(
+            if let Ok(return_values) = get_input_function_parameters(
                 self.project,
                 pi_state,
                 extern_symbol,
@@ -30,7 +30,11 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
                 self.create_abstract_domain_entries_for_function_return_values(
                     pi_state,
                     &mut new_state,
-                    return_values.into_iter().map(|arg| (arg, None)).collect(),
+                    return_values
+                        .into_iter()
+                        .filter(|(_, data_type)| matches!(data_type, Datatype::Pointer))
+                        .map(|(arg, _)| (arg, None))
+                        .collect(),
                 );
             }
         }
@@ -153,7 +157,7 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         extern_symbol: &ExternSymbol,
         source_string: &str,
     ) -> Result<HashMap<Arg, Option<String>>, Error> {
-        if let Ok(all_parameters) = get_variable_parameters(
+        if let Ok(all_parameters) = get_input_function_parameters(
             self.project,
             pi_state,
             extern_symbol,
@@ -172,14 +176,15 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> Conte
         Err(anyhow!("Could not map source string to return parameters."))
     }
 
-    /// Filters out all parameters that are not of type string.
+    /// Filters out all parameters that do not point to strings,
+    /// i.e. whose conversion specifier is not a string conversion.
     pub fn filter_out_all_non_string_args(
-        all_parameters: Vec<Arg>,
+        all_parameters: Vec<(Arg, Datatype)>,
         return_values: Vec<String>,
     ) -> HashMap<Arg, Option<String>> {
         izip!(all_parameters, return_values)
-            .filter_map(|(param, value)| {
-                if matches!(param.get_data_type(), Some(Datatype::Pointer)) {
+            .filter_map(|((param, data_type), value)| {
+                if matches!(data_type, Datatype::Pointer) {
                     Some((param, Some(value)))
                 } else {
                     None
@@ -790,8 +795,8 @@ mod tests {
     #[test]
     fn test_filter_out_all_non_string_args() {
         let args = vec![
-            Arg::mock_pointer_register("r0", 4),
-            Arg::mock_register("r1", 4),
+            (Arg::mock_pointer_register("r0", 4), Datatype::Pointer),
+            (Arg::mock_pointer_register("r1", 4), Datatype::Integer),
         ];
         let values = vec!["cat ".to_string(), "2".to_string()];
 
@@ -5,8 +5,8 @@ use petgraph::graph::NodeIndex;
 
 use super::Context;
 use crate::abstract_domain::{
-    AbstractDomain, CharacterInclusionDomain, CharacterSet, DataDomain, DomainInsertion, HasTop,
-    IntervalDomain,
+    AbstractDomain, BricksDomain, CharacterInclusionDomain, CharacterSet, DataDomain,
+    DomainInsertion, HasTop, IntervalDomain,
 };
 use crate::analysis::forward_interprocedural_fixpoint::Context as _;
 use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
@@ -219,6 +219,148 @@ fn test_add_new_string_abstract_domain() {
     assert!(state.get_heap_to_string_map().contains_key(&heap_id));
 }
 
+#[test]
+fn test_add_new_string_abstract_domain_inside_of_tracked_string() {
+    let sprintf_symbol = ExternSymbol::mock_sprintf_symbol_arm();
+    let project = mock_project_with_intraprocedural_control_flow(
+        vec![(sprintf_symbol.clone(), vec![true])],
+        "func",
+    );
+    let mem_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let mut pi_results = PointerInferenceComputation::mock(&project, &mem_image, &graph);
+    pi_results.compute();
+
+    let setup: Setup<BricksDomain> = Setup::new(&pi_results);
+    let mut state = State::mock_with_default_pi_state(Sub::mock("func"));
+    let stack_id = AbstractIdentifier::new(
+        Tid::new("func"),
+        AbstractLocation::from_var(&Variable::mock("sp", 4)).unwrap(),
+    );
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("func"),
+        AbstractLocation::from_var(&Variable::mock("r5", 4)).unwrap(),
+    );
+    state.add_new_stack_offset_to_string_entry(
+        -40,
+        BricksDomain::from("/bin/ping localhost".to_string()),
+    );
+    state.add_new_heap_to_string_entry(
+        heap_id.clone(),
+        BricksDomain::from("/bin/ping localhost".to_string()),
+    );
+
+    // Test Case 1: Copy to a constant offset into the tracked strings.
+    let mut pointer = DataDomain::from_target(stack_id.clone(), IntervalDomain::mock_i32(-30, -30));
+    pointer.insert_relative_value(heap_id.clone(), IntervalDomain::mock_i32(10, 10));
+    Context::<BricksDomain>::add_new_string_abstract_domain(
+        &mut state,
+        &setup.pi_state_before_symbol_call,
+        pointer.get_relative_values(),
+        BricksDomain::from("8.8.8.8".to_string()),
+    );
+    let expected_domain = BricksDomain::from("/bin/ping ".to_string())
+        .append_string_domain(&BricksDomain::from("8.8.8.8".to_string()));
+    assert_eq!(
+        state.get_stack_offset_to_string_map().get(&-40),
+        Some(&expected_domain)
+    );
+    assert_eq!(
+        state.get_stack_offset_to_string_map().get(&-30),
+        Some(&BricksDomain::from("8.8.8.8".to_string()))
+    );
+    assert_eq!(
+        state.get_heap_to_string_map().get(&heap_id),
+        Some(&expected_domain)
+    );
+
+    // Test Case 2: Copy to an unknown offset into the tracked strings.
+    state.set_all_maps_empty();
+    state.add_new_stack_offset_to_string_entry(
+        -40,
+        BricksDomain::from("/bin/ping localhost".to_string()),
+    );
+    state.add_new_stack_offset_to_string_entry(-25, BricksDomain::from("other".to_string()));
+    state.add_new_heap_to_string_entry(
+        heap_id.clone(),
+        BricksDomain::from("/bin/ping localhost".to_string()),
+    );
+    let mut pointer = DataDomain::from_target(stack_id.clone(), IntervalDomain::mock_i32(-30, -20));
+    pointer.insert_relative_value(heap_id.clone(), IntervalDomain::mock_i32(10, 20));
+    Context::<BricksDomain>::add_new_string_abstract_domain(
+        &mut state,
+        &setup.pi_state_before_symbol_call,
+        pointer.get_relative_values(),
+        BricksDomain::from("8.8.8.8".to_string()),
+    );
+    let expected_domain =
+        BricksDomain::from("/bin/ping ".to_string()).append_string_domain(&BricksDomain::Top);
+    assert_eq!(
+        state.get_stack_offset_to_string_map().get(&-40),
+        Some(&expected_domain)
+    );
+    assert_eq!(
+        state.get_stack_offset_to_string_map().get(&-25),
+        Some(&BricksDomain::Top)
+    );
+    assert_eq!(
+        state.get_heap_to_string_map().get(&heap_id),
+        Some(&expected_domain)
+    );
+
+    // Test Case 3: Writes to adjacent stack buffers do not change the tracked string.
+    state.set_all_maps_empty();
+    state.add_new_stack_offset_to_string_entry(-40, BricksDomain::from("ping".to_string()));
+    let pointer = DataDomain::from_target(stack_id, IntervalDomain::mock_i32(-30, -30));
+    Context::<BricksDomain>::add_new_string_abstract_domain(
+        &mut state,
+        &setup.pi_state_before_symbol_call,
+        pointer.get_relative_values(),
+        BricksDomain::from("8.8.8.8".to_string()),
+    );
+    assert_eq!(
+        state.get_stack_offset_to_string_map().get(&-40),
+        Some(&BricksDomain::from("ping".to_string()))
+    );
+}
+
+#[test]
+fn test_copy_into_tracked_string_with_character_inclusion_domain() {
+    let sprintf_symbol = ExternSymbol::mock_sprintf_symbol_arm();
+    let project = mock_project_with_intraprocedural_control_flow(
+        vec![(sprintf_symbol.clone(), vec![true])],
+        "func",
+    );
+    let mem_image = RuntimeMemoryImage::mock();
+    let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+    let mut pi_results = PointerInferenceComputation::mock(&project, &mem_image, &graph);
+    pi_results.compute();
+
+    let setup: Setup<CharacterInclusionDomain> = Setup::new(&pi_results);
+    let mut state = State::mock_with_default_pi_state(Sub::mock("func"));
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("func"),
+        AbstractLocation::from_var(&Variable::mock("r5", 4)).unwrap(),
+    );
+    state.add_new_heap_to_string_entry(heap_id.clone(), CharacterInclusionDomain::ci("ping "));
+
+    // An unknown offset only widens the suffix and keeps the known prefix characters.
+    let pointer = DataDomain::from_target(heap_id.clone(), IntervalDomain::mock_i32(5, 20));
+    Context::<CharacterInclusionDomain>::add_new_string_abstract_domain(
+        &mut state,
+        &setup.pi_state_before_symbol_call,
+        pointer.get_relative_values(),
+        CharacterInclusionDomain::ci("8."),
+    );
+    assert_eq!(
+        state.get_heap_to_string_map().get(&heap_id),
+        Some(&CharacterInclusionDomain::Value((
+            CharacterSet::Value("ping ".chars().collect()),
+            CharacterSet::Top
+        )))
+    );
+}
+
 #[test]
 fn test_merge_domains_from_multiple_pointer_targets() {
     let sprintf_symbol = ExternSymbol::mock_sprintf_symbol_arm();
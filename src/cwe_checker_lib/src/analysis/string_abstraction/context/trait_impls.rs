@@ -96,17 +96,27 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
         None
     }
 
+    /// Compute the state after returning from a call.
+    ///
+    /// Pointers in non-callee-saved registers are removed from the caller state.
+    /// Strings that the callee constructed in memory objects of the caller are added to the caller state.
     fn update_return(
         &self,
-        _state: Option<&State<T>>,
+        state: Option<&State<T>>,
         state_before_call: Option<&State<T>>,
-        _call_term: &Term<Jmp>,
+        call_term: &Term<Jmp>,
         _return_term: &Term<Jmp>,
         _calling_convention: &Option<String>,
     ) -> Option<State<T>> {
-        if let Some(state) = state_before_call {
-            let mut new_state = state.clone();
+        if let Some(state_before_call) = state_before_call {
+            let mut new_state = state_before_call.clone();
             self.handle_unknown_symbol_calls(&mut new_state);
+            if let Some(callee_state) = state {
+                new_state.add_strings_constructed_by_callee(
+                    callee_state,
+                    self.get_stack_offset_on_call(state_before_call, call_term),
+                );
+            }
             new_state.set_pointer_inference_state(None);
             return Some(new_state);
         }
@@ -121,11 +131,13 @@ impl<'a, T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>>
                 Some(symbol) => {
                     if let Some(string_symbol) = self.string_symbol_map.get(target) {
                         new_state = self.handle_string_symbol_calls(string_symbol, &new_state);
+                        new_state.set_construction_site_of_changed_strings(state, &call.tid);
                     } else {
                         new_state = self.handle_generic_symbol_calls(symbol, &new_state);
                     }
                 }
-                None => panic!("Extern symbol not found."),
+                // The call target is a function excluded from the analysis.
+                None => self.handle_unknown_symbol_calls(&mut new_state),
             },
             Jmp::CallInd { .. } => self.handle_unknown_symbol_calls(&mut new_state),
             _ => panic!("Malformed control flow graph encountered."),
@@ -37,6 +37,10 @@ pub struct State<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String
     /// representing its content.
     /// For simplicity reasons it is assumed that a heap object only represents one string at offset 0.
     heap_to_string_map: HashMap<AbstractIdentifier, T>,
+    /// Maps the stack offsets of strings to the call sites of the string symbols that constructed them.
+    stack_offset_to_construction_site_map: HashMap<i64, Tid>,
+    /// Maps the heap abstract identifiers of strings to the call sites of the string symbols that constructed them.
+    heap_to_construction_site_map: HashMap<AbstractIdentifier, Tid>,
     /// Holds the currently analyzed subroutine term
     current_sub: Arc<Option<Term<Sub>>>,
     /// The state of the pointer inference analysis.
@@ -98,12 +102,21 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> AbstractD
             }
         }
 
+        let mut stack_offset_to_construction_site_map =
+            other.stack_offset_to_construction_site_map.clone();
+        stack_offset_to_construction_site_map
+            .extend(self.stack_offset_to_construction_site_map.clone());
+        let mut heap_to_construction_site_map = other.heap_to_construction_site_map.clone();
+        heap_to_construction_site_map.extend(self.heap_to_construction_site_map.clone());
+
         let mut new_state = State {
             unassigned_return_pointer,
             variable_to_pointer_map,
             stack_offset_to_pointer_map,
             stack_offset_to_string_map,
             heap_to_string_map,
+            stack_offset_to_construction_site_map,
+            heap_to_construction_site_map,
             current_sub: self.current_sub.clone(),
             pointer_inference_state: self.pointer_inference_state.clone(),
         };
@@ -144,6 +157,8 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
             stack_offset_to_pointer_map: HashMap::new(),
             stack_offset_to_string_map: HashMap::new(),
             heap_to_string_map: HashMap::new(),
+            stack_offset_to_construction_site_map: HashMap::new(),
+            heap_to_construction_site_map: HashMap::new(),
             current_sub: Arc::new(current_sub),
             pointer_inference_state: pi_state,
         }
@@ -156,6 +171,8 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
         self.stack_offset_to_pointer_map = HashMap::new();
         self.stack_offset_to_string_map = HashMap::new();
         self.variable_to_pointer_map = HashMap::new();
+        self.stack_offset_to_construction_site_map = HashMap::new();
+        self.heap_to_construction_site_map = HashMap::new();
     }
 
     /// Adds a return pointer to the unassigned return pointer set.
@@ -222,6 +239,100 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
         &self.heap_to_string_map
     }
 
+    /// Returns the call site of the string symbol that constructed the string
+    /// at one of the targets of the given pointer, if known.
+    pub fn get_construction_site(
+        &self,
+        pi_state: &PointerInferenceState,
+        pointer: &DataDomain<IntervalDomain>,
+    ) -> Option<&Tid> {
+        pointer
+            .get_relative_values()
+            .iter()
+            .find_map(|(target, offset)| {
+                if State::<T>::is_stack_pointer(pi_state, target) {
+                    let offset_value = offset.try_to_offset().ok()?;
+                    self.stack_offset_to_construction_site_map
+                        .get(&offset_value)
+                } else {
+                    self.heap_to_construction_site_map.get(target)
+                }
+            })
+    }
+
+    /// Record the given call site as construction site for all strings
+    /// that differ between `self` and the state before the call.
+    pub fn set_construction_site_of_changed_strings(
+        &mut self,
+        state_before_call: &State<T>,
+        call_site: &Tid,
+    ) {
+        for (offset, string_domain) in self.stack_offset_to_string_map.iter() {
+            if state_before_call.stack_offset_to_string_map.get(offset) != Some(string_domain) {
+                self.stack_offset_to_construction_site_map
+                    .insert(*offset, call_site.clone());
+            }
+        }
+        for (id, string_domain) in self.heap_to_string_map.iter() {
+            if state_before_call.heap_to_string_map.get(id) != Some(string_domain) {
+                self.heap_to_construction_site_map
+                    .insert(id.clone(), call_site.clone());
+            }
+        }
+    }
+
+    /// Add the strings that a called function constructed in memory objects of the caller to `self`,
+    /// where `self` is the state of the caller.
+    ///
+    /// Since strings in caller stack frames are tracked by their offset relative to the caller stack frame,
+    /// the strings at non-negative stack offsets of the callee state lie in the stack frame of the caller.
+    /// Their offsets are adjusted by the stack offset of the caller at the call (if known).
+    /// Strings in other memory objects are identified by the same abstract identifiers in caller and callee.
+    ///
+    /// Since the callee state is merged from the states of all call sites,
+    /// strings that are *Top* in the callee state are ignored
+    /// and keep their value from before the call (as for calls to unknown functions).
+    pub fn add_strings_constructed_by_callee(
+        &mut self,
+        callee_state: &State<T>,
+        stack_offset_on_call: Option<i64>,
+    ) {
+        if let Some(stack_offset_on_call) = stack_offset_on_call {
+            for (offset, string_domain) in callee_state.stack_offset_to_string_map.iter() {
+                if *offset < 0 || string_domain.is_top() {
+                    continue;
+                }
+                let caller_offset = offset + stack_offset_on_call;
+                self.stack_offset_to_string_map
+                    .insert(caller_offset, string_domain.clone());
+                match callee_state
+                    .stack_offset_to_construction_site_map
+                    .get(offset)
+                {
+                    Some(site) => self
+                        .stack_offset_to_construction_site_map
+                        .insert(caller_offset, site.clone()),
+                    None => self
+                        .stack_offset_to_construction_site_map
+                        .remove(&caller_offset),
+                };
+            }
+        }
+        for (id, string_domain) in callee_state.heap_to_string_map.iter() {
+            if string_domain.is_top() {
+                continue;
+            }
+            self.heap_to_string_map
+                .insert(id.clone(), string_domain.clone());
+            match callee_state.heap_to_construction_site_map.get(id) {
+                Some(site) => self
+                    .heap_to_construction_site_map
+                    .insert(id.clone(), site.clone()),
+                None => self.heap_to_construction_site_map.remove(id),
+            };
+        }
+    }
+
     /// Gets the current subroutine since the analysis is interprocedural.
     pub fn get_current_sub(&self) -> Option<&Term<Sub>> {
         match &*self.current_sub {
@@ -249,6 +360,12 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
 
             new_state.stack_offset_to_string_map = stack_strings;
             new_state.heap_to_string_map = heap_strings;
+            new_state
+                .stack_offset_to_construction_site_map
+                .retain(|offset, _| new_state.stack_offset_to_string_map.contains_key(offset));
+            new_state
+                .heap_to_construction_site_map
+                .retain(|id, _| new_state.heap_to_string_map.contains_key(id));
         }
 
         new_state
@@ -311,7 +428,7 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
     ) -> Option<DataDomain<IntervalDomain>> {
         if let Ok(address) = constant.try_to_u64() {
             if !block_first_def_set.iter().any(|(def_tid, _)| {
-                u64::from_str_radix(def_tid.address.as_str(), 16).unwrap() == address
+                u64::from_str_radix(def_tid.canonical_address(), 16) == Ok(address)
             }) && runtime_memory_image.is_global_memory_address(&constant)
                 && runtime_memory_image
                     .read_string_until_null_terminator(&constant)
@@ -17,6 +17,8 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
             stack_offset_to_pointer_map: HashMap::new(),
             stack_offset_to_string_map: HashMap::new(),
             heap_to_string_map: HashMap::new(),
+            stack_offset_to_construction_site_map: HashMap::new(),
+            heap_to_construction_site_map: HashMap::new(),
             current_sub: Arc::new(Some(current_sub)),
             pointer_inference_state: Some(pi_state),
         }
@@ -29,6 +31,8 @@ impl<T: AbstractDomain + DomainInsertion + HasTop + Eq + From<String>> State<T>
             stack_offset_to_pointer_map: HashMap::new(),
             stack_offset_to_string_map: HashMap::new(),
             heap_to_string_map: HashMap::new(),
+            stack_offset_to_construction_site_map: HashMap::new(),
+            heap_to_construction_site_map: HashMap::new(),
             current_sub: Arc::new(Some(current_sub)),
             pointer_inference_state: Some(pi_state),
         }
@@ -549,3 +553,47 @@ fn test_remove_non_callee_saved_pointer_entries_for_external_symbol() {
         .variable_to_pointer_map
         .contains_key(&Variable::mock("r11", 4)));
 }
+
+#[test]
+fn test_add_strings_constructed_by_callee() {
+    let mut caller_state =
+        State::<CharacterInclusionDomain>::mock_with_default_pi_state(Sub::mock("caller"));
+    let mut callee_state =
+        State::<CharacterInclusionDomain>::mock_with_default_pi_state(Sub::mock("callee"));
+    let heap_id = AbstractIdentifier::new(
+        Tid::new("heap"),
+        AbstractLocation::from_var(&Variable::mock("r0", 4)).unwrap(),
+    );
+    caller_state.add_new_stack_offset_to_string_entry(-0x30, CharacterInclusionDomain::ci("a"));
+    caller_state.add_new_stack_offset_to_string_entry(-0x20, CharacterInclusionDomain::ci("b"));
+
+    let previous_callee_state = callee_state.clone();
+    callee_state.add_new_stack_offset_to_string_entry(0x10, CharacterInclusionDomain::ci("c"));
+    callee_state.add_new_stack_offset_to_string_entry(0x20, CharacterInclusionDomain::Top);
+    callee_state.add_new_stack_offset_to_string_entry(-0x8, CharacterInclusionDomain::ci("d"));
+    callee_state.add_new_heap_to_string_entry(heap_id.clone(), CharacterInclusionDomain::ci("e"));
+    callee_state
+        .set_construction_site_of_changed_strings(&previous_callee_state, &Tid::new("call"));
+
+    caller_state.add_strings_constructed_by_callee(&callee_state, Some(-0x40));
+    // Strings constructed by the callee in the caller stack frame replace the previous strings.
+    assert_eq!(
+        caller_state.stack_offset_to_string_map,
+        HashMap::from([
+            (-0x30, CharacterInclusionDomain::ci("c")),
+            (-0x20, CharacterInclusionDomain::ci("b")),
+        ])
+    );
+    assert_eq!(
+        caller_state.heap_to_string_map,
+        HashMap::from([(heap_id.clone(), CharacterInclusionDomain::ci("e"))])
+    );
+    assert_eq!(
+        caller_state.stack_offset_to_construction_site_map,
+        HashMap::from([(-0x30, Tid::new("call"))])
+    );
+    assert_eq!(
+        caller_state.heap_to_construction_site_map,
+        HashMap::from([(heap_id, Tid::new("call"))])
+    );
+}
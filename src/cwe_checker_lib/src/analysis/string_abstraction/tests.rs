@@ -1,5 +1,8 @@
 use crate::intermediate_representation::*;
-use std::{collections::BTreeMap, iter::FromIterator};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter::FromIterator,
+};
 
 pub struct Setup;
 
@@ -504,6 +507,7 @@ impl CallingConvention {
             integer_return_register: vec![Variable::mock("r0", 4)],
             float_return_register: vec![],
             callee_saved_register: vec![Variable::mock("r11", 4)],
+            varargs_layout: None,
         }
     }
 }
@@ -623,9 +627,12 @@ pub fn mock_project_with_intraprocedural_control_flow(
             term: program,
         },
         cpu_architecture: "arm_32".to_string(),
+        architecture: Arch::Arm32,
         stack_pointer_register: Variable::mock("sp", 4u64),
         calling_conventions: BTreeMap::from_iter([(cconv.name.clone(), cconv)]),
         register_set,
         datatype_properties: DatatypeProperties::mock_standard_arm_32(),
+        stack_canary_artifacts: BTreeSet::new(),
+        duplicated_term_origins: BTreeMap::new(),
     }
 }
@@ -0,0 +1,269 @@
+use super::{State, Taint};
+use crate::analysis::forward_interprocedural_fixpoint::create_computation;
+use crate::analysis::forward_interprocedural_fixpoint::Context as ForwardContext;
+use crate::analysis::graph::{Edge, Graph, Node};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
+use crate::analysis::pointer_inference::State as PointerInferenceState;
+use crate::intermediate_representation::*;
+use crate::utils::log::LogMessage;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{EdgeRef, IntoNodeReferences};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Run one intraprocedural taint analysis for each of the given taint source sites.
+///
+/// Each site consists of the taint source, the `BlkStart` node where the analysis starts
+/// and the taint state at that node.
+/// For each site a copy of `general_context` is updated with `set_taint_source`
+/// before the fixpoint computation is run on it.
+/// Sites whose node is not the start of a basic block are skipped with a debug log message.
+pub fn compute_taint_from_sources<'a, C, S>(
+    general_context: &C,
+    taint_source_sites: impl IntoIterator<Item = (S, NodeIndex, C::Value)>,
+    set_taint_source: impl Fn(&mut C, S, &'a Term<Sub>),
+) -> Vec<LogMessage>
+where
+    C: ForwardContext<'a> + Clone,
+{
+    let mut logs = Vec::new();
+    for (source, node, state) in taint_source_sites {
+        let current_sub = match general_context.get_graph()[node] {
+            Node::BlkStart(_blk, sub) => sub,
+            _ => {
+                logs.push(LogMessage::new_debug(format!(
+                    "Taint source site at {} is not the start of a block.",
+                    general_context.get_graph()[node]
+                )));
+                continue;
+            }
+        };
+        let mut context = general_context.clone();
+        set_taint_source(&mut context, source, current_sub);
+        let mut computation = create_computation(context, None);
+        computation.set_node_value(node, NodeValue::Value(state));
+        computation.compute_with_max_steps(100);
+    }
+    logs
+}
+
+/// Collect the taint source sites for all calls to the extern symbols in `symbol_map`.
+///
+/// Each site consists of the call, the called symbol, the node after the call
+/// and the taint state at that node.
+/// The state is created by `new_state` from the called symbol
+/// and the pointer inference state at the node.
+pub fn get_extern_taint_source_sites<'a, V>(
+    graph: &Graph<'a>,
+    symbol_map: &HashMap<Tid, &'a ExternSymbol>,
+    pointer_inference_results: &PointerInferenceComputation,
+    new_state: impl Fn(&ExternSymbol, Option<&PointerInferenceState>) -> V,
+) -> Vec<(&'a Term<Jmp>, &'a ExternSymbol, NodeIndex, V)> {
+    let mut taint_source_sites = Vec::new();
+    for edge in graph.edge_references() {
+        if let Edge::ExternCallStub(jmp) = edge.weight() {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                if let Some(symbol) = symbol_map.get(target) {
+                    let node = edge.target();
+                    let pi_state_at_taint_source =
+                        match pointer_inference_results.get_node_value(node) {
+                            Some(NodeValue::Value(val)) => Some(val.clone()),
+                            _ => None,
+                        };
+                    taint_source_sites.push((
+                        *jmp,
+                        *symbol,
+                        node,
+                        new_state(symbol, pi_state_at_taint_source.as_ref()),
+                    ));
+                }
+            }
+        }
+    }
+    taint_source_sites
+}
+
+/// The part of the fixpoint context that all intraprocedural taint analyses share.
+///
+/// There is always only one source of taint for the analysis.
+/// On creation of a `TaintContext` object, the taint source is not set.
+/// Accessing the taint source or the current function before
+/// [setting the taint source](TaintContext::set_taint_source()) will lead to a panic.
+/// By resetting the taint source one can reuse the context object for several fixpoint computations.
+#[derive(Clone)]
+pub struct TaintContext<'a> {
+    /// A pointer to the corresponding project struct.
+    pub project: &'a Project,
+    /// A pointer to the results of the pointer inference analysis.
+    /// They are used to determine the targets of pointers to memory,
+    /// which in turn is used to keep track of taint on the stack or on the heap.
+    pub pointer_inference_results: &'a PointerInferenceComputation<'a>,
+    /// A map to get the node index of the `BlkStart` node containing a given [`Def`] as the first `Def` of the block.
+    /// The keys are of the form `(Def-TID, Current-Sub-TID)`
+    /// to distinguish the nodes for blocks contained in more than one function.
+    block_start_node_map: Arc<HashMap<(Tid, Tid), NodeIndex>>,
+    /// The call whose return values are the sources for taint for the analysis.
+    taint_source: Option<&'a Term<Jmp>>,
+    /// The name of the function, whose return values are the taint sources.
+    taint_source_name: Option<String>,
+    /// The current subfunction.
+    /// Since the analysis is intraprocedural,
+    /// all nodes with state during the fixpoint algorithm should belong to this function.
+    current_sub: Option<&'a Term<Sub>>,
+}
+
+impl<'a> TaintContext<'a> {
+    /// Create a new context object.
+    ///
+    /// Note that one has to set the taint source separately before starting the analysis!
+    pub fn new(
+        project: &'a Project,
+        pointer_inference_results: &'a PointerInferenceComputation<'a>,
+    ) -> Self {
+        let mut block_start_node_map = HashMap::new();
+        let graph = pointer_inference_results.get_graph();
+        for (node_id, node) in graph.node_references() {
+            if let Node::BlkStart(block, sub) = node {
+                if let Some(def) = block.term.defs.first() {
+                    block_start_node_map.insert((def.tid.clone(), sub.tid.clone()), node_id);
+                }
+            }
+        }
+        TaintContext {
+            project,
+            pointer_inference_results,
+            block_start_node_map: Arc::new(block_start_node_map),
+            taint_source: None,
+            taint_source_name: None,
+            current_sub: None,
+        }
+    }
+
+    /// Set the taint source and the current function for the analysis.
+    ///
+    /// The name of the taint source is the name of the called extern symbol or internal function.
+    pub fn set_taint_source(&mut self, taint_source: &'a Term<Jmp>, current_sub: &'a Term<Sub>) {
+        let program = &self.project.program.term;
+        let taint_source_name = match &taint_source.term {
+            Jmp::Call { target, .. } => program
+                .extern_symbols
+                .get(target)
+                .map(|symbol| symbol.name.clone())
+                .or_else(|| program.subs.get(target).map(|sub| sub.term.name.clone()))
+                .unwrap_or_else(|| "Unknown".to_string()),
+            _ => "Unknown".to_string(),
+        };
+        self.set_named_taint_source(taint_source, &taint_source_name, current_sub);
+    }
+
+    /// Set the taint source with the given name and the current function for the analysis.
+    pub fn set_named_taint_source(
+        &mut self,
+        taint_source: &'a Term<Jmp>,
+        taint_source_name: &str,
+        current_sub: &'a Term<Sub>,
+    ) {
+        self.taint_source = Some(taint_source);
+        self.taint_source_name = Some(taint_source_name.to_string());
+        self.current_sub = Some(current_sub);
+    }
+
+    /// Get the call whose return values are the sources for taint for the analysis.
+    pub fn get_taint_source(&self) -> &'a Term<Jmp> {
+        self.taint_source.unwrap()
+    }
+
+    /// Get the name of the function whose return values are the taint sources.
+    pub fn get_taint_source_name(&self) -> &str {
+        self.taint_source_name.as_deref().unwrap()
+    }
+
+    /// Get the function that the analysis is restricted to.
+    pub fn get_current_sub(&self) -> &'a Term<Sub> {
+        self.current_sub.unwrap()
+    }
+
+    /// Get the pointer inference state at the start of the block containing the given `Def` as its first `Def`.
+    pub fn get_block_start_pointer_inference_state(
+        &self,
+        tid: &Tid,
+    ) -> Option<PointerInferenceState> {
+        let node_id = self
+            .block_start_node_map
+            .get(&(tid.clone(), self.get_current_sub().tid.clone()))?;
+        match self.pointer_inference_results.get_node_value(*node_id) {
+            Some(NodeValue::Value(val)) => Some(val.clone()),
+            _ => None,
+        }
+    }
+
+    /// Get the current pointer inference state (if one can be found) for the given taint state.
+    ///
+    /// If the taint state contains no intermediate pointer inference state,
+    /// the state at the start of the block containing the given `Def` as its first `Def` is returned.
+    pub fn get_current_pointer_inference_state(
+        &self,
+        state: &State,
+        tid: &Tid,
+    ) -> Option<PointerInferenceState> {
+        match state.get_pointer_inference_state() {
+            Some(pi_state) => Some(pi_state.clone()),
+            None => self.get_block_start_pointer_inference_state(tid),
+        }
+    }
+
+    /// Update the pointer inference state contained in the given taint state
+    /// according to the effect of the given `Def` term.
+    pub fn update_pointer_inference_state(&self, state: &mut State, def: &Term<Def>) {
+        if let Some(pi_state) = self.get_current_pointer_inference_state(state, &def.tid) {
+            let pi_context = self.pointer_inference_results.get_context();
+            let new_pi_state = pi_context.update_def(&pi_state, def);
+            state.set_pointer_inference_state(new_pi_state);
+        }
+    }
+
+    /// Propagate the taint in the given state through the given `Def` term
+    /// and update the contained pointer inference state accordingly.
+    ///
+    /// Taint is tracked through memory if the pointer inference state before the `Def` is known.
+    /// Otherwise stores remove all memory taint to reduce false positives.
+    pub fn propagate_taint(&self, state: &State, def: &Term<Def>) -> State {
+        let mut new_state = state.clone();
+        match &def.term {
+            Def::Assign { var, value } => {
+                new_state.set_register_taint(var, state.eval(value));
+            }
+            Def::Load { var, address } => {
+                let taint = match self.get_current_pointer_inference_state(state, &def.tid) {
+                    Some(pi_state) => {
+                        state.load_taint_from_memory(&pi_state.eval(address), var.size)
+                    }
+                    None => Taint::Top(var.size),
+                };
+                new_state.set_register_taint(var, taint);
+            }
+            Def::Store { address, value } => {
+                match self.get_current_pointer_inference_state(state, &def.tid) {
+                    Some(pi_state) => {
+                        new_state.save_taint_to_memory(&pi_state.eval(address), state.eval(value))
+                    }
+                    // We lost all knowledge about memory pointers.
+                    // We delete all memory taints to reduce false positives.
+                    None => new_state.remove_all_memory_taints(),
+                }
+            }
+        }
+        self.update_pointer_inference_state(&mut new_state, def);
+        new_state
+    }
+
+    /// Remove the taint from all non-callee-saved registers for a call with unknown calling convention.
+    pub fn handle_generic_call(&self, state: &State) -> State {
+        let mut new_state = state.clone();
+        if let Some(calling_conv) = self.project.get_standard_calling_convention() {
+            new_state.remove_non_callee_saved_taint(calling_conv);
+        }
+        new_state
+    }
+}
@@ -118,7 +118,7 @@ mod tests {
         let top = Taint::Top(ByteSize::new(4));
         assert_eq!(taint.merge(&top), taint);
         assert_eq!(top.merge(&top), top);
-        assert_eq!(taint.is_top(), false);
+        assert!(!taint.is_top());
     }
 
     #[test]
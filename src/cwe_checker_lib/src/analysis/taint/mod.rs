@@ -0,0 +1,19 @@
+//! Shared building blocks for the intraprocedural taint analyses of the CWE checks.
+//!
+//! Several checks (e.g. the checks for CWE-123, CWE-129, CWE-476 and CWE-789)
+//! track the return values of a single taint source call through registers and memory
+//! until the end of the function containing the call.
+//! This module contains the [`Taint`] domain, the taint [`State`]
+//! and the [`TaintContext`] with the logic shared by the fixpoint contexts of these checks:
+//! Keeping track of the current taint source and
+//! following the pointer inference state through the `Def`s of a basic block
+//! to track taint through memory.
+
+mod domain;
+pub use domain::*;
+
+mod state;
+pub use state::*;
+
+mod context;
+pub use context::*;
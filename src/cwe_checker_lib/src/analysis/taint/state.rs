@@ -106,12 +106,25 @@ impl State {
         state
     }
 
+    /// Get a new state in which only the given registers are tainted,
+    /// e.g. the return registers of a derived taint source.
+    pub fn new_with_tainted_registers(registers: &[Variable]) -> State {
+        State {
+            register_taint: registers
+                .iter()
+                .map(|var| (var.clone(), Taint::Tainted(var.size)))
+                .collect(),
+            memory_taint: HashMap::new(),
+            pointer_inference_state: None,
+        }
+    }
+
     /// Evaluate whether the result of the given expression is tainted in the current state.
     pub fn eval(&self, expression: &Expression) -> Taint {
         match expression {
             Expression::Const(_) => Taint::Top(expression.bytesize()),
             Expression::Var(var) => {
-                if self.register_taint.get(var).is_some() {
+                if self.register_taint.contains_key(var) {
                     Taint::Tainted(var.size)
                 } else {
                     Taint::Top(var.size)
@@ -442,7 +455,7 @@ mod tests {
         let top = Taint::Top(ByteSize::new(8));
 
         let mut state = State::mock();
-        state.set_register_taint(&register("RAX"), taint.clone());
+        state.set_register_taint(&register("RAX"), taint);
 
         let mut other_state = State::mock();
         let address = new_pointer("mem", 10);
@@ -456,7 +469,7 @@ mod tests {
         assert_eq!(merged_state.register_taint.get(&register("RBX")), None);
         assert_eq!(
             merged_state.load_taint_from_memory(&address, ByteSize::new(8)),
-            taint.clone()
+            taint
         );
         let other_address = new_pointer("mem", 18);
         assert_eq!(
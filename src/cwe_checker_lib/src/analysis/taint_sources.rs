@@ -0,0 +1,907 @@
+//! Detection of internal functions that wrap taint sources.
+//!
+//! Checks like CWE-129 or CWE-789 track values returned by extern taint sources configured by name,
+//! e.g. `atoi` or `recv`.
+//! Programs often wrap these sources in helper functions, e.g. a `read_exact(fd, buf, n)` function calling `recv`.
+//! Then the configured sources never appear at the call sites that the checks are interested in.
+//!
+//! This module computes the *derived taint sources* of a program,
+//! i.e. the internal functions whose return value or whose memory pointed to by a parameter
+//! is externally controlled, because it is derived from a call to a taint source.
+//! The results are summarized in [`TaintSignature`]s.
+//! Since derived taint sources may themselves be wrapped by other functions,
+//! the signatures are propagated bottom-up through the call graph until a fixpoint is reached.
+//!
+//! The analysis of each function is a lightweight intraprocedural dataflow analysis
+//! that only tracks registers and stack slots with constant offsets, but no other memory.
+//! It considers the integer parameter and return registers of the calling convention of the function.
+//! Parameters passed on the stack are not considered.
+
+use crate::abstract_domain::TryToInterval;
+use crate::analysis::graph::{Edge, Graph};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::Data;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::LogMessage;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The values that calls to a taint source make externally controlled.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct TaintSignature {
+    /// Whether the return value is externally controlled.
+    #[serde(default)]
+    pub return_value: bool,
+    /// The indices of the parameters pointing to memory
+    /// that gets filled with externally controlled data.
+    #[serde(default)]
+    pub out_parameters: BTreeSet<usize>,
+}
+
+impl TaintSignature {
+    /// Get the signature of a taint source whose return value is externally controlled.
+    pub fn new_return_value() -> TaintSignature {
+        TaintSignature {
+            return_value: true,
+            out_parameters: BTreeSet::new(),
+        }
+    }
+
+    /// Get the signature of a taint source that fills the memory pointed to by the given parameters
+    /// with externally controlled data.
+    pub fn new_out_parameters(out_parameters: impl IntoIterator<Item = usize>) -> TaintSignature {
+        TaintSignature {
+            return_value: false,
+            out_parameters: out_parameters.into_iter().collect(),
+        }
+    }
+
+    /// Returns `true` if calls to the function do not make any value externally controlled.
+    pub fn is_empty(&self) -> bool {
+        !self.return_value && self.out_parameters.is_empty()
+    }
+}
+
+/// An internal function wrapping a taint source.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct DerivedTaintSource {
+    /// The name of the function.
+    pub name: String,
+    /// The values made externally controlled by calls to the function.
+    pub signature: TaintSignature,
+    /// The names of the extern taint sources that the function (transitively) wraps.
+    pub origins: BTreeSet<String>,
+}
+
+impl std::fmt::Display for DerivedTaintSource {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let origins: Vec<&str> = self.origins.iter().map(|name| name.as_str()).collect();
+        write!(formatter, "{} (wraps {})", self.name, origins.join(", "))
+    }
+}
+
+/// Compute the internal functions of the project that wrap one of the given extern taint sources.
+///
+/// The taint sources are given by the names of extern symbols and their taint signatures.
+/// The result maps the TIDs of the wrapping functions to their derived taint source descriptions.
+/// Thunk functions are not contained in the result,
+/// since calls to them are treated as calls to the extern symbols they forward to.
+pub fn compute_derived_taint_sources(
+    project: &Project,
+    sources: &BTreeMap<String, TaintSignature>,
+) -> BTreeMap<Tid, DerivedTaintSource> {
+    let extern_sources: HashMap<&Tid, (&ExternSymbol, &TaintSignature)> = sources
+        .iter()
+        .filter_map(|(name, signature)| {
+            project
+                .program
+                .term
+                .find_extern_symbol_by_name(name)
+                .map(|symbol| (&symbol.tid, (symbol, signature)))
+        })
+        .collect();
+    let mut derived_sources = BTreeMap::new();
+    if extern_sources.is_empty() {
+        return derived_sources;
+    }
+    // Each round adds at least one function or extends the signature of one function.
+    // The number of rounds is bounded nevertheless, in case of unexpected non-monotonic behavior.
+    for _ in 0..=project.program.term.subs.len() {
+        let mut changed = false;
+        for sub in project.program.term.subs.values() {
+            if sub.term.is_thunk || sub.term.blocks.is_empty() {
+                continue;
+            }
+            let calling_convention =
+                match project.get_specific_calling_convention(&sub.term.calling_convention) {
+                    Some(cconv) => cconv,
+                    None => continue,
+                };
+            let analysis = FunctionAnalysis {
+                project,
+                calling_convention,
+                extern_sources: &extern_sources,
+                derived_sources: &derived_sources,
+            };
+            let (signature, origins) = analysis.compute_signature(sub);
+            if signature.is_empty() {
+                continue;
+            }
+            let derived_source = DerivedTaintSource {
+                name: sub.term.name.clone(),
+                signature,
+                origins,
+            };
+            if derived_sources.get(&sub.tid) != Some(&derived_source) {
+                derived_sources.insert(sub.tid.clone(), derived_source);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    derived_sources
+}
+
+/// Get the taint sources configured for the check with the given name.
+///
+/// These are the `symbols` of CWE-129 and CWE-789, whose return values are externally controlled,
+/// and the `input_symbols` of CWE-78 and CWE-117, which map the names of input functions like `recv`
+/// to the indices of their parameters pointing to the memory filled with input data.
+/// Returns an empty map for all other checks.
+pub fn get_configured_taint_sources(
+    check_name: &str,
+    check_config: &serde_json::Value,
+) -> BTreeMap<String, TaintSignature> {
+    match check_name {
+        "CWE129" | "CWE789" => {
+            serde_json::from_value::<Vec<String>>(check_config["symbols"].clone())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|name| (name, TaintSignature::new_return_value()))
+                .collect()
+        }
+        "CWE78" | "CWE117" => serde_json::from_value::<BTreeMap<String, Vec<usize>>>(
+            check_config["input_symbols"].clone(),
+        )
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, indices)| (name, TaintSignature::new_out_parameters(indices)))
+        .collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Generate a debug log message for each derived taint source used by the given check.
+pub fn log_derived_taint_sources(
+    check_name: &str,
+    derived_sources: &BTreeMap<Tid, DerivedTaintSource>,
+) -> Vec<LogMessage> {
+    derived_sources
+        .iter()
+        .map(|(tid, source)| {
+            LogMessage::new_debug(format!("Calls to {} are treated as taint source", source))
+                .location(tid.clone())
+                .source(check_name)
+        })
+        .collect()
+}
+
+/// A call to a derived taint source in the control flow graph.
+#[derive(Debug, Clone)]
+pub struct DerivedSourceCall<'a, 'b> {
+    /// The call instruction.
+    pub call: &'a Term<Jmp>,
+    /// The called derived taint source.
+    pub source: &'b DerivedTaintSource,
+    /// The `BlkEnd` node of the block containing the call.
+    pub call_node: NodeIndex,
+    /// The `BlkStart` node of the block that the call returns to, if the call returns.
+    pub return_node: Option<NodeIndex>,
+    /// The registers containing the externally controlled return value after the call.
+    /// Empty if the return value of the called function is not externally controlled.
+    pub tainted_return_registers: Vec<Variable>,
+    /// The registers containing pointers to memory that the call fills with externally controlled data.
+    pub out_parameter_registers: Vec<Variable>,
+}
+
+/// Get all calls to the given derived taint sources in the control flow graph.
+///
+/// The registers of the call are determined by the calling convention of the called function.
+/// Calls to functions contained in the graph are represented by `CallCombine` edges,
+/// calls to functions excluded from the graph by `ExternCallStub` edges.
+pub fn get_derived_source_calls<'a, 'b>(
+    project: &Project,
+    graph: &Graph<'a>,
+    derived_sources: &'b BTreeMap<Tid, DerivedTaintSource>,
+) -> Vec<DerivedSourceCall<'a, 'b>> {
+    if derived_sources.is_empty() {
+        return Vec::new();
+    }
+    let get_target = |jmp: &Term<Jmp>| match &jmp.term {
+        Jmp::Call { target, .. } if derived_sources.contains_key(target) => Some(target.clone()),
+        _ => None,
+    };
+    let mut return_nodes: HashMap<Tid, NodeIndex> = HashMap::new();
+    let mut calls: BTreeMap<Tid, (&'a Term<Jmp>, NodeIndex, Tid)> = BTreeMap::new();
+    for edge in graph.edge_references() {
+        match edge.weight() {
+            Edge::CallCombine(jmp) => {
+                if let Some(target) = get_target(jmp) {
+                    calls.insert(jmp.tid.clone(), (*jmp, edge.source(), target));
+                }
+            }
+            Edge::ReturnCombine(jmp) => {
+                return_nodes.insert(jmp.tid.clone(), edge.target());
+            }
+            Edge::ExternCallStub(jmp) => {
+                if let Some(target) = get_target(jmp) {
+                    calls.insert(jmp.tid.clone(), (*jmp, edge.source(), target));
+                    return_nodes.insert(jmp.tid.clone(), edge.target());
+                }
+            }
+            _ => (),
+        }
+    }
+    calls
+        .into_values()
+        .filter_map(|(call, call_node, target)| {
+            let source = &derived_sources[&target];
+            let cconv = project.get_specific_calling_convention(
+                &project
+                    .program
+                    .term
+                    .subs
+                    .get(&target)?
+                    .term
+                    .calling_convention,
+            )?;
+            let tainted_return_registers = if source.signature.return_value {
+                cconv.integer_return_register.clone()
+            } else {
+                Vec::new()
+            };
+            let out_parameter_registers = source
+                .signature
+                .out_parameters
+                .iter()
+                .filter_map(|index| cconv.integer_parameter_register.get(*index).cloned())
+                .collect();
+            Some(DerivedSourceCall {
+                call,
+                source,
+                call_node,
+                return_node: return_nodes.get(&call.tid).copied(),
+                tainted_return_registers,
+                out_parameter_registers,
+            })
+        })
+        .collect()
+}
+
+/// The state of the intraprocedural analysis at a program point of a function.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct State {
+    /// Registers containing externally controlled values.
+    tainted_registers: BTreeSet<Variable>,
+    /// Registers containing the value of the parameter with the given index at the start of the function,
+    /// possibly plus a constant offset.
+    parameter_registers: BTreeMap<Variable, usize>,
+    /// Registers containing the value of the stack pointer at the start of the function plus the given offset.
+    stack_registers: BTreeMap<Variable, i64>,
+    /// The offsets of stack slots containing externally controlled values.
+    tainted_slots: BTreeSet<i64>,
+    /// Maps the offsets of stack slots to the index of the parameter whose value they contain.
+    parameter_slots: BTreeMap<i64, usize>,
+}
+
+impl State {
+    /// Merge two states.
+    /// Taint and parameter values are kept if they are contained in one of the states,
+    /// stack pointer offsets only if they are equal in both states.
+    fn merge(&self, other: &State) -> State {
+        let mut merged = self.clone();
+        merged
+            .tainted_registers
+            .extend(other.tainted_registers.iter().cloned());
+        for (var, index) in other.parameter_registers.iter() {
+            merged
+                .parameter_registers
+                .entry(var.clone())
+                .or_insert(*index);
+        }
+        merged
+            .stack_registers
+            .retain(|var, offset| other.stack_registers.get(var) == Some(offset));
+        merged
+            .tainted_slots
+            .extend(other.tainted_slots.iter().cloned());
+        for (offset, index) in other.parameter_slots.iter() {
+            merged.parameter_slots.entry(*offset).or_insert(*index);
+        }
+        merged
+    }
+
+    /// Get the index of the parameter whose value (plus a constant offset) the expression evaluates to.
+    fn get_parameter(&self, expression: &Expression) -> Option<usize> {
+        match expression {
+            Expression::Var(var) => self.parameter_registers.get(var).copied(),
+            Expression::BinOp {
+                op: BinOpType::IntAdd | BinOpType::IntSub,
+                lhs,
+                rhs,
+            } if matches!(**rhs, Expression::Const(_)) => self.get_parameter(lhs),
+            _ => None,
+        }
+    }
+
+    /// Get the offset relative to the stack pointer at the start of the function
+    /// that the expression evaluates to.
+    fn get_stack_offset(&self, expression: &Expression) -> Option<i64> {
+        match expression {
+            Expression::Var(var) => self.stack_registers.get(var).copied(),
+            Expression::BinOp { op, lhs, rhs } => {
+                let offset = match &**rhs {
+                    Expression::Const(constant) => constant.try_to_i64().ok()?,
+                    _ => return None,
+                };
+                let base = self.get_stack_offset(lhs)?;
+                match op {
+                    BinOpType::IntAdd => base.checked_add(offset),
+                    BinOpType::IntSub => base.checked_sub(offset),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value of the expression may be externally controlled.
+    fn is_tainted(&self, expression: &Expression) -> bool {
+        expression
+            .input_vars()
+            .into_iter()
+            .any(|var| self.tainted_registers.contains(var))
+    }
+
+    /// Remove all information about the given register.
+    fn remove_register(&mut self, var: &Variable) {
+        self.tainted_registers.remove(var);
+        self.parameter_registers.remove(var);
+        self.stack_registers.remove(var);
+    }
+
+    /// Update the state according to the given `Def` term.
+    fn handle_def(&mut self, def: &Term<Def>) {
+        match &def.term {
+            Def::Assign { var, value } => {
+                let is_tainted = self.is_tainted(value);
+                let parameter = self.get_parameter(value);
+                let stack_offset = self.get_stack_offset(value);
+                self.remove_register(var);
+                if is_tainted {
+                    self.tainted_registers.insert(var.clone());
+                }
+                if let Some(index) = parameter {
+                    self.parameter_registers.insert(var.clone(), index);
+                }
+                if let Some(offset) = stack_offset {
+                    self.stack_registers.insert(var.clone(), offset);
+                }
+            }
+            Def::Load { var, address } => {
+                let slot = self.get_stack_offset(address);
+                self.remove_register(var);
+                if let Some(slot) = slot {
+                    if self.tainted_slots.contains(&slot) {
+                        self.tainted_registers.insert(var.clone());
+                    }
+                    if let Some(index) = self.parameter_slots.get(&slot) {
+                        self.parameter_registers.insert(var.clone(), *index);
+                    }
+                }
+            }
+            Def::Store { address, value } => {
+                if let Some(slot) = self.get_stack_offset(address) {
+                    self.tainted_slots.remove(&slot);
+                    self.parameter_slots.remove(&slot);
+                    if self.is_tainted(value) {
+                        self.tainted_slots.insert(slot);
+                    }
+                    if let Some(index) = self.get_parameter(value) {
+                        self.parameter_slots.insert(slot, index);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The intraprocedural analysis computing the taint signature of a function.
+struct FunctionAnalysis<'a> {
+    project: &'a Project,
+    /// The calling convention of the analyzed function.
+    calling_convention: &'a CallingConvention,
+    extern_sources: &'a HashMap<&'a Tid, (&'a ExternSymbol, &'a TaintSignature)>,
+    derived_sources: &'a BTreeMap<Tid, DerivedTaintSource>,
+}
+
+impl<'a> FunctionAnalysis<'a> {
+    /// Compute the taint signature of the function
+    /// together with the names of the extern taint sources it is derived from.
+    fn compute_signature(&self, sub: &Term<Sub>) -> (TaintSignature, BTreeSet<String>) {
+        let mut signature = TaintSignature::default();
+        let mut origins = BTreeSet::new();
+        let block_indices: HashMap<&Tid, usize> = sub
+            .term
+            .blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (&block.tid, index))
+            .collect();
+        let mut entry_state = State::default();
+        for (index, var) in self
+            .calling_convention
+            .integer_parameter_register
+            .iter()
+            .enumerate()
+        {
+            entry_state.parameter_registers.insert(var.clone(), index);
+        }
+        entry_state
+            .stack_registers
+            .insert(self.project.stack_pointer_register.clone(), 0);
+        let mut block_states: HashMap<usize, State> = HashMap::from([(0, entry_state)]);
+        let mut worklist = vec![0];
+        while let Some(block_index) = worklist.pop() {
+            let block = &sub.term.blocks[block_index];
+            let mut state = block_states[&block_index].clone();
+            for def in block.term.defs.iter() {
+                state.handle_def(def);
+            }
+            for jmp in block.term.jmps.iter() {
+                let (successor, successor_state) =
+                    self.handle_jmp(&state, jmp, &mut signature, &mut origins);
+                let successor_index = match successor.and_then(|tid| block_indices.get(tid)) {
+                    Some(index) => *index,
+                    None => continue,
+                };
+                let new_state = match block_states.get(&successor_index) {
+                    Some(old_state) => old_state.merge(&successor_state),
+                    None => successor_state,
+                };
+                if block_states.get(&successor_index) != Some(&new_state) {
+                    block_states.insert(successor_index, new_state);
+                    worklist.push(successor_index);
+                }
+            }
+        }
+        (signature, origins)
+    }
+
+    /// Compute the state at the target block of the jump.
+    /// Returns the target block (if it is a block of the function) together with the state.
+    ///
+    /// Calls to taint sources and returns from the function add to the signature of the function.
+    fn handle_jmp<'b>(
+        &self,
+        state: &State,
+        jmp: &'b Term<Jmp>,
+        signature: &mut TaintSignature,
+        origins: &mut BTreeSet<String>,
+    ) -> (Option<&'b Tid>, State) {
+        match &jmp.term {
+            Jmp::Branch(target) | Jmp::CBranch { target, .. } => (Some(target), state.clone()),
+            Jmp::Return(_) => {
+                self.handle_return(state, signature);
+                (None, state.clone())
+            }
+            Jmp::Call { target, return_ } => {
+                let state_after_call = self.handle_call(state, target, signature, origins);
+                if return_.is_none() {
+                    // A tail call returns the return value of the called function.
+                    self.handle_return(&state_after_call, signature);
+                }
+                (return_.as_ref(), state_after_call)
+            }
+            Jmp::CallInd { return_, .. } | Jmp::CallOther { return_, .. } => {
+                let mut state_after_call = state.clone();
+                self.remove_non_callee_saved_registers(&mut state_after_call);
+                (return_.as_ref(), state_after_call)
+            }
+            Jmp::BranchInd(_) => (None, state.clone()),
+        }
+    }
+
+    /// Add the return value to the signature if it is tainted.
+    fn handle_return(&self, state: &State, signature: &mut TaintSignature) {
+        if self
+            .calling_convention
+            .integer_return_register
+            .iter()
+            .any(|var| state.tainted_registers.contains(var))
+        {
+            signature.return_value = true;
+        }
+    }
+
+    /// Compute the state after a call to the given target.
+    ///
+    /// If the target is a taint source, its tainted return registers are marked as tainted
+    /// and parameters of the analyzed function passed as out-parameters to it
+    /// are added to the signature of the analyzed function.
+    fn handle_call(
+        &self,
+        state: &State,
+        target: &Tid,
+        signature: &mut TaintSignature,
+        origins: &mut BTreeSet<String>,
+    ) -> State {
+        let mut state_after_call = state.clone();
+        self.remove_non_callee_saved_registers(&mut state_after_call);
+        let (callee_signature, parameter_registers, return_registers, callee_origins) =
+            if let Some((symbol, callee_signature)) = self.extern_sources.get(target) {
+                let as_register = |arg: &Arg| match arg {
+                    Arg::Register {
+                        expr: Expression::Var(var),
+                        ..
+                    } => Some(var.clone()),
+                    _ => None,
+                };
+                (
+                    *callee_signature,
+                    symbol.parameters.iter().map(as_register).collect(),
+                    symbol
+                        .return_values
+                        .iter()
+                        .filter_map(as_register)
+                        .collect(),
+                    BTreeSet::from([symbol.name.clone()]),
+                )
+            } else if let Some(derived_source) = self.derived_sources.get(target) {
+                let callee_cconv = self
+                    .project
+                    .program
+                    .term
+                    .subs
+                    .get(target)
+                    .and_then(|sub| {
+                        self.project
+                            .get_specific_calling_convention(&sub.term.calling_convention)
+                    })
+                    .unwrap_or(self.calling_convention);
+                (
+                    &derived_source.signature,
+                    callee_cconv
+                        .integer_parameter_register
+                        .iter()
+                        .cloned()
+                        .map(Some)
+                        .collect::<Vec<_>>(),
+                    callee_cconv.integer_return_register.clone(),
+                    derived_source.origins.clone(),
+                )
+            } else {
+                return state_after_call;
+            };
+        let mut is_derived = false;
+        for out_parameter in callee_signature.out_parameters.iter() {
+            if let Some(Some(var)) = parameter_registers.get(*out_parameter) {
+                if let Some(index) = state.parameter_registers.get(var) {
+                    signature.out_parameters.insert(*index);
+                    is_derived = true;
+                }
+            }
+        }
+        if callee_signature.return_value {
+            for var in return_registers {
+                state_after_call.remove_register(&var);
+                state_after_call.tainted_registers.insert(var);
+            }
+            is_derived = true;
+        }
+        if is_derived {
+            origins.extend(callee_origins);
+        }
+        state_after_call
+    }
+
+    /// Remove the information about registers that are not callee-saved.
+    /// The stack pointer is assumed to be restored by the callee.
+    fn remove_non_callee_saved_registers(&self, state: &mut State) {
+        let is_callee_saved = |var: &Variable| {
+            *var == self.project.stack_pointer_register
+                || self.calling_convention.callee_saved_register.contains(var)
+        };
+        state.tainted_registers.retain(|var| is_callee_saved(var));
+        state
+            .parameter_registers
+            .retain(|var, _| is_callee_saved(var));
+        state.stack_registers.retain(|var, _| is_callee_saved(var));
+    }
+}
+
+/// Maps the TIDs of functions to the calls to input symbols in them
+/// together with the node before the call and the pointers to the buffers filled by the calls.
+pub type InputBuffers = HashMap<Tid, Vec<(NodeIndex, Tid, Data)>>;
+
+/// Get the buffers filled with external input by calls to the input symbols configured for the given check
+/// (see [`get_configured_taint_sources`]) and by calls to derived input symbols.
+///
+/// The pointers are evaluated in the pointer inference state before the call.
+pub fn get_input_buffers(
+    analysis_results: &AnalysisResults,
+    check_name: &str,
+    cwe_params: &serde_json::Value,
+) -> (InputBuffers, Vec<LogMessage>) {
+    let project = analysis_results.project;
+    let pointer_inference = analysis_results.pointer_inference.unwrap();
+    let graph = analysis_results.control_flow_graph;
+    let input_symbols = get_configured_taint_sources(check_name, cwe_params);
+    let derived_sources = compute_derived_taint_sources(project, &input_symbols);
+
+    // The calls to input symbols together with the node before the call and the buffer parameters.
+    let mut input_calls: Vec<(&Tid, NodeIndex, Vec<Arg>)> = Vec::new();
+    for edge in graph.edge_references() {
+        if let Edge::ExternCallStub(jmp) = edge.weight() {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                    if let Some(signature) = input_symbols.get(&symbol.name) {
+                        let parameters = signature
+                            .out_parameters
+                            .iter()
+                            .filter_map(|index| symbol.parameters.get(*index).cloned())
+                            .collect();
+                        input_calls.push((&jmp.tid, edge.source(), parameters));
+                    }
+                }
+            }
+        }
+    }
+    let derived_calls = get_derived_source_calls(project, graph, &derived_sources);
+    for derived_call in derived_calls.iter() {
+        let parameters = derived_call
+            .out_parameter_registers
+            .iter()
+            .map(|register| Arg::from_var(register.clone(), None))
+            .collect();
+        input_calls.push((&derived_call.call.tid, derived_call.call_node, parameters));
+    }
+
+    let mut input_buffers: InputBuffers = HashMap::new();
+    for (call_tid, node, parameters) in input_calls {
+        if let Some(NodeValue::Value(state)) = pointer_inference.get_node_value(node) {
+            let buffers = input_buffers
+                .entry(graph[node].get_sub().tid.clone())
+                .or_default();
+            for parameter in parameters.iter() {
+                if let Ok(pointer) =
+                    state.eval_parameter_arg(parameter, analysis_results.runtime_memory_image)
+                {
+                    buffers.push((node, call_tid.clone(), pointer));
+                }
+            }
+        }
+    }
+    for buffers in input_buffers.values_mut() {
+        buffers.sort_by(|(_, tid1, _), (_, tid2, _)| tid1.cmp(tid2));
+    }
+    (
+        input_buffers,
+        log_derived_taint_sources(check_name, &derived_sources),
+    )
+}
+
+/// Find the input buffer among the given buffers that the pointer may point into
+/// (see [`may_point_into_buffer`]) and return the TID of the input call that filled the buffer.
+pub fn find_input_buffer<'a>(
+    buffers: &'a [(NodeIndex, Tid, Data)],
+    pointer: &Data,
+) -> Option<&'a Tid> {
+    let (_, input_call, _) = buffers
+        .iter()
+        .find(|(_, _, buffer)| may_point_into_buffer(pointer, buffer))?;
+    Some(input_call)
+}
+
+/// Returns `true` if the pointer points to the same memory object as the buffer pointer
+/// and the offset intervals of both pointers overlap.
+///
+/// Offsets without known bounds are not considered to overlap.
+pub fn may_point_into_buffer(pointer: &Data, buffer: &Data) -> bool {
+    pointer
+        .get_relative_values()
+        .iter()
+        .any(|(id, pointer_offset)| {
+            let buffer_offset = match buffer.get_relative_values().get(id) {
+                Some(buffer_offset) => buffer_offset,
+                None => return false,
+            };
+            match (
+                pointer_offset.try_to_offset_interval(),
+                buffer_offset.try_to_offset_interval(),
+            ) {
+                (Ok((pointer_start, pointer_end)), Ok((buffer_start, buffer_end))) => {
+                    pointer_start <= buffer_end && buffer_start <= pointer_end
+                }
+                _ => false,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project with the extern symbols `recv` and `atoi`,
+    /// the function `read_exact` passing its second parameter to `recv` as buffer,
+    /// the function `read_message` passing a stack buffer offset to `read_exact`
+    /// and the function `read_number` returning the return value of `atoi` through a stack slot.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock();
+        cconv.integer_parameter_register = ["RDI", "RSI", "RDX"]
+            .into_iter()
+            .map(|name| Variable::mock(name, 8))
+            .collect();
+        cconv.callee_saved_register = vec![Variable::mock("RBX", 8), Variable::mock("RBP", 8)];
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["recv", "atoi"] {
+            project.add_mock_extern_symbol(name).parameters = vec![
+                Arg::mock_register("RDI", 8),
+                Arg::mock_register("RSI", 8),
+                Arg::mock_register("RDX", 8),
+            ];
+        }
+        let mut subs = Vec::new();
+
+        // read_exact(fd, buf, n): recv(fd, buf, n)
+        let mut block = Blk::mock_with_tid("read_exact_blk");
+        block.term.jmps = vec![Jmp::call("call_recv", "recv", None)];
+        let read_exact = Sub::mock_with_blocks("read_exact", vec![block]);
+        subs.push(read_exact);
+
+        // read_message(fd, buf): read_exact(fd, buf + 4, 16)
+        let mut block = Blk::mock_with_tid("read_message_blk");
+        block.term.defs = vec![
+            Def::assign("spill", Variable::mock("RBX", 8), Expression::var("RSI", 8)),
+            Def::assign(
+                "offset",
+                Variable::mock("RSI", 8),
+                Expression::var("RBX", 8).plus_const(4),
+            ),
+        ];
+        block.term.jmps = vec![Jmp::call(
+            "call_read_exact",
+            "read_exact",
+            Some("read_message_ret"),
+        )];
+        let mut return_block = Blk::mock_with_tid("read_message_ret");
+        return_block.term.jmps = vec![Term {
+            tid: Tid::new("read_message_return"),
+            term: Jmp::Return(Expression::const_from_i64(0)),
+        }];
+        let read_message = Sub::mock_with_blocks("read_message", vec![block, return_block]);
+        subs.push(read_message);
+
+        // read_number(): return atoi(...), with the return value spilled to the stack
+        let mut block = Blk::mock_with_tid("read_number_blk");
+        block.term.jmps = vec![Jmp::call("call_atoi", "atoi", Some("read_number_ret"))];
+        let mut return_block = Blk::mock_with_tid("read_number_ret");
+        return_block.term.defs = vec![
+            Def::store(
+                "spill_result",
+                Expression::var("RSP", 8).plus_const(-8),
+                Expression::var("RAX", 8),
+            ),
+            Def::assign(
+                "clobber",
+                Variable::mock("RAX", 8),
+                Expression::const_from_i64(0),
+            ),
+            Def::load(
+                "reload_result",
+                Variable::mock("RAX", 8),
+                Expression::var("RSP", 8).plus_const(-8),
+            ),
+        ];
+        return_block.term.jmps = vec![Term {
+            tid: Tid::new("read_number_return"),
+            term: Jmp::Return(Expression::const_from_i64(0)),
+        }];
+        let read_number = Sub::mock_with_blocks("read_number", vec![block, return_block]);
+        subs.push(read_number);
+
+        // other(buf): recv(fd, local_buffer, n)
+        let mut block = Blk::mock_with_tid("other_blk");
+        block.term.defs = vec![Def::assign(
+            "local_buffer",
+            Variable::mock("RSI", 8),
+            Expression::var("RSP", 8).plus_const(-0x40),
+        )];
+        block.term.jmps = vec![Jmp::call("call_recv_local", "recv", Some("other_ret"))];
+        let other = Sub::mock_with_blocks("other", vec![block, Blk::mock_with_tid("other_ret")]);
+        subs.push(other);
+
+        for sub in subs {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn wrappers_are_derived_sources() {
+        let project = mock_project();
+        let sources = BTreeMap::from([
+            ("recv".to_string(), TaintSignature::new_out_parameters([1])),
+            ("atoi".to_string(), TaintSignature::new_return_value()),
+        ]);
+        let derived_sources = compute_derived_taint_sources(&project, &sources);
+        assert_eq!(derived_sources.len(), 3);
+        // One-level wrapper
+        let read_exact = &derived_sources[&Tid::new("read_exact")];
+        assert_eq!(
+            read_exact.signature,
+            TaintSignature::new_out_parameters([1])
+        );
+        assert_eq!(read_exact.origins, BTreeSet::from(["recv".to_string()]));
+        // Two-level wrapper
+        let read_message = &derived_sources[&Tid::new("read_message")];
+        assert_eq!(
+            read_message.signature,
+            TaintSignature::new_out_parameters([1])
+        );
+        assert_eq!(
+            format!("{}", read_message),
+            "read_message (wraps recv)".to_string()
+        );
+        // Return value spilled to the stack
+        assert_eq!(
+            derived_sources[&Tid::new("read_number")].signature,
+            TaintSignature::new_return_value()
+        );
+        // Functions reading into their own stack frame are no derived sources.
+        assert!(!derived_sources.contains_key(&Tid::new("other")));
+
+        // Without configured sources there are no derived sources.
+        assert!(compute_derived_taint_sources(&project, &BTreeMap::new()).is_empty());
+    }
+
+    #[test]
+    fn pointers_into_buffers() {
+        use crate::abstract_domain::{AbstractIdentifier, IntervalDomain, SizedDomain};
+        let stack_id =
+            AbstractIdentifier::new_from_var(Tid::new("func"), &Variable::mock("RSP", 8));
+        let heap_id =
+            AbstractIdentifier::new_from_var(Tid::new("malloc"), &Variable::mock("RAX", 8));
+        let pointer = |id: &AbstractIdentifier, start: i64, end: i64| {
+            Data::from_target(id.clone(), IntervalDomain::mock(start, end))
+        };
+        let buffer = pointer(&stack_id, -64, -64);
+
+        assert!(may_point_into_buffer(
+            &pointer(&stack_id, -64, -64),
+            &buffer
+        ));
+        assert!(may_point_into_buffer(
+            &pointer(&stack_id, -80, -32),
+            &buffer
+        ));
+        assert!(!may_point_into_buffer(
+            &pointer(&stack_id, -32, -16),
+            &buffer
+        ));
+        assert!(!may_point_into_buffer(
+            &pointer(&heap_id, -64, -64),
+            &buffer
+        ));
+        // Pointers with unknown offsets are not matched to the buffer.
+        let unknown_offset = Data::from_target(stack_id, IntervalDomain::new_top(ByteSize::new(8)));
+        assert!(!may_point_into_buffer(&unknown_offset, &buffer));
+    }
+}
@@ -5,16 +5,34 @@
 //! but directly incorporated into the [`pointer_inference`](crate::analysis::pointer_inference) module.
 //! See there for detailed information about this check.
 
+pub mod cwe_117;
+pub mod cwe_123;
+pub mod cwe_129;
 pub mod cwe_134;
+pub mod cwe_1386;
+pub mod cwe_170;
+pub mod cwe_188;
 pub mod cwe_190;
+pub mod cwe_195;
+pub mod cwe_196;
+pub mod cwe_197;
 pub mod cwe_215;
 pub mod cwe_243;
+pub mod cwe_295;
 pub mod cwe_332;
 pub mod cwe_367;
+pub mod cwe_403;
 pub mod cwe_426;
 pub mod cwe_467;
 pub mod cwe_476;
+pub mod cwe_494;
 pub mod cwe_560;
+pub mod cwe_562;
+pub mod cwe_674;
 pub mod cwe_676;
+pub mod cwe_704;
+pub mod cwe_775;
 pub mod cwe_78;
 pub mod cwe_782;
+pub mod cwe_787;
+pub mod cwe_789;
@@ -0,0 +1,560 @@
+//! This module implements a check for CWE-117: Improper Output Neutralization for Logs.
+//!
+//! The software does not neutralize or incorrectly neutralizes output that is written to logs.
+//! An attacker able to inject newline characters into a log message can forge additional log entries.
+//!
+//! See <https://cwe.mitre.org/data/definitions/117.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to a logging function the format string is recovered from global memory
+//! and the arguments of all string conversions without a precision (e.g. `%s`, but not `%.20s`) are evaluated
+//! using the results of the pointer inference analysis.
+//! If such an argument points into a buffer filled with external input by a call to one of the configured input symbols
+//! (e.g. `recv` or `read`) in the same function, a CWE warning is generated.
+//! Input buffers are detected in the same way as in the [CWE-78 check](crate::checkers::cwe_78),
+//! see the [`taint_sources`](crate::analysis::taint_sources) module for details.
+//!
+//! No warning is generated if a call to one of the configured sanitizer symbols (e.g. `strcspn`)
+//! whose first parameter points to the same buffer may be executed before the logging call.
+//!
+//! The possibly contained characters of the logged string are computed from the results of the string abstraction
+//! with the character inclusion domain.
+//! If they are known and contain no control characters, no warning is generated.
+//! If they are known and contain newline or other control characters, the warning has a high confidence.
+//! Otherwise the warning is based on the external input alone and has a low confidence.
+//! The confidence is given in the `other` field of the warning.
+//!
+//! Functions that forward their own format string parameter to a logging symbol
+//! (e.g. a wrapper around `vsyslog`) are detected automatically
+//! and checked at their callsites like the logging symbol itself.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The `log_symbols` map the names of logging functions to the indices of their format string parameters.
+//!   Calls to non-variadic logging functions (e.g. `vsyslog`) are only used to detect wrapper functions.
+//! - The `internal_log_functions` map the names of variadic logging functions of the binary
+//!   to the indices of their format string parameters in the list of integer parameter registers.
+//! - The `sanitizer_symbols` are functions neutralizing newline characters in the string given as their first parameter.
+//! - The `input_symbols` map the names of functions filling a buffer with external input
+//!   to the indices of their parameters pointing to the buffer.
+//!
+//! ## False Positives
+//!
+//! - The input is sanitized by custom code (e.g. a loop replacing newline characters) that is not detected by the check.
+//! - The log message is written to a destination where newline characters cannot forge log entries.
+//!
+//! ## False Negatives
+//!
+//! - Input buffers are only detected if they are filled in the same function that calls the logging function
+//!   or in a function called by it.
+//! - Format strings not located in global memory are ignored.
+//! - A sanitizer call is assumed to neutralize the input on all paths to the logging call
+//!   if the logging call is reachable from it.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use crate::abstract_domain::{CharacterInclusionDomain, TryToBitvec};
+use crate::analysis::graph::{Edge, Graph, Node};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data, State as PointerInferenceState};
+use crate::analysis::string_abstraction::context::Context;
+use crate::analysis::taint_sources::{
+    find_input_buffer, get_input_buffers, may_point_into_buffer, InputBuffers,
+};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::arguments::forwarding::{
+    eval_format_string_parameter, get_format_string_wrappers, get_sub_calling_convention,
+    get_wrapper_variable_parameters, is_forwarding_callsite, FormatStringWrapper,
+};
+use crate::utils::arguments::{get_unbounded_string_parameter_indices, get_variable_parameters};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE117",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[
+        SharedAnalysis::PointerInference,
+        SharedAnalysis::StringAbstraction,
+    ],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// Maps the names of logging functions to the indices of their format string parameters.
+    log_symbols: HashMap<String, usize>,
+    /// Maps the names of variadic logging functions of the binary
+    /// to the indices of their format string parameters in the list of integer parameter registers.
+    #[serde(default)]
+    internal_log_functions: BTreeMap<String, usize>,
+    /// The names of functions neutralizing newline characters in the string given as their first parameter.
+    #[serde(default)]
+    sanitizer_symbols: Vec<String>,
+    /// Maps the names of functions filling a buffer with external input
+    /// to the indices of their parameters pointing to the buffer.
+    #[serde(default)]
+    input_symbols: BTreeMap<String, Vec<usize>>,
+}
+
+/// The confidence of a warning.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Confidence {
+    /// The logged string is externally controlled,
+    /// but whether it may contain control characters is unknown.
+    Low,
+    /// The logged string is externally controlled and may contain control characters.
+    High,
+}
+
+/// This check searches for calls to logging functions
+/// that log externally controlled strings without neutralizing newline characters.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    if config.input_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let log_symbol_names: Vec<String> = config.log_symbols.keys().cloned().collect();
+    let log_symbols: HashMap<Tid, (&ExternSymbol, usize)> =
+        crate::utils::symbol_utils::get_symbol_map(project, &log_symbol_names[..])
+            .into_iter()
+            .map(|(tid, symbol)| (tid, (symbol, config.log_symbols[&symbol.name])))
+            .collect();
+    let mut wrappers = get_format_string_wrappers(project, &log_symbols, runtime_memory_image);
+    for sub in project.program.term.subs.values() {
+        if let Some(index) = config.internal_log_functions.get(&sub.term.name) {
+            wrappers
+                .entry(sub.tid.clone())
+                .or_insert_with(|| FormatStringWrapper {
+                    format_string_index: *index,
+                    is_variadic: true,
+                    forwarding_callsites: BTreeSet::new(),
+                });
+        }
+    }
+
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let graph = pointer_inference_results.get_graph();
+    let (input_buffers, log_messages) =
+        get_input_buffers(analysis_results, CWE_MODULE.name, cwe_params);
+    let sanitized_buffers = get_sanitized_buffers(analysis_results, &config.sanitizer_symbols);
+    let mut cwe_warnings = Vec::new();
+
+    for edge in graph.edge_references() {
+        let jmp = match edge.weight() {
+            Edge::ExternCallStub(jmp) | Edge::CallCombine(jmp) => jmp,
+            _ => continue,
+        };
+        if let (Jmp::Call { target, .. }, Node::BlkEnd(_, caller)) =
+            (&jmp.term, graph[edge.source()])
+        {
+            let called_name = match (log_symbols.get(target), wrappers.get(target)) {
+                (Some((symbol, _)), _) if symbol.has_var_args => &symbol.name,
+                (None, Some(wrapper)) if wrapper.is_variadic => {
+                    &project.program.term.subs[target].term.name
+                }
+                _ => continue,
+            };
+            if is_forwarding_callsite(&caller.tid, &jmp.tid, &wrappers) {
+                // The logged strings are checked at the callsites of the wrapper function.
+                continue;
+            }
+            let pi_state = match pointer_inference_results.get_node_value(edge.source()) {
+                Some(NodeValue::Value(state)) => state,
+                _ => continue,
+            };
+            let logged_strings = match get_logged_strings(
+                analysis_results,
+                pi_state,
+                target,
+                &log_symbols,
+                &wrappers,
+            ) {
+                Some(strings) => strings,
+                None => continue,
+            };
+            let finding = logged_strings
+                .iter()
+                .filter_map(|string| {
+                    check_logged_string(
+                        analysis_results,
+                        &input_buffers,
+                        &sanitized_buffers,
+                        pi_state,
+                        caller,
+                        edge.source(),
+                        string,
+                    )
+                })
+                .max();
+            if let Some((confidence, input_call)) = finding {
+                cwe_warnings.push(generate_cwe_warning(
+                    &caller.term.name,
+                    &jmp.tid,
+                    input_call,
+                    called_name,
+                    confidence,
+                ));
+            }
+        }
+    }
+
+    cwe_warnings.sort();
+    (log_messages, cwe_warnings)
+}
+
+/// Get the pointers to the buffers passed to calls to the sanitizer symbols,
+/// together with the node before the call, sorted by the function containing the call.
+fn get_sanitized_buffers(
+    analysis_results: &AnalysisResults,
+    sanitizer_symbols: &[String],
+) -> HashMap<Tid, Vec<(NodeIndex, Tid, Data)>> {
+    let project = analysis_results.project;
+    let graph = analysis_results.control_flow_graph;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let mut sanitized_buffers: HashMap<Tid, Vec<(NodeIndex, Tid, Data)>> = HashMap::new();
+    for edge in graph.edge_references() {
+        let jmp = match edge.weight() {
+            Edge::ExternCallStub(jmp) | Edge::CallCombine(jmp) => jmp,
+            _ => continue,
+        };
+        let target = match &jmp.term {
+            Jmp::Call { target, .. } => target,
+            _ => continue,
+        };
+        let parameter = if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+            if !sanitizer_symbols.contains(&symbol.name) {
+                continue;
+            }
+            match symbol.parameters.first() {
+                Some(parameter) => parameter.clone(),
+                None => continue,
+            }
+        } else {
+            match (
+                project.program.term.subs.get(target),
+                get_sub_calling_convention(project, target),
+            ) {
+                (Some(sub), Some(cconv)) if sanitizer_symbols.contains(&sub.term.name) => {
+                    match cconv.integer_parameter_register.first() {
+                        Some(register) => Arg::from_var(register.clone(), None),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            }
+        };
+        if let Some(NodeValue::Value(state)) =
+            pointer_inference_results.get_node_value(edge.source())
+        {
+            if let Ok(pointer) =
+                state.eval_parameter_arg(&parameter, analysis_results.runtime_memory_image)
+            {
+                sanitized_buffers
+                    .entry(graph[edge.source()].get_sub().tid.clone())
+                    .or_default()
+                    .push((edge.source(), jmp.tid.clone(), pointer));
+            }
+        }
+    }
+    sanitized_buffers
+}
+
+/// Get the pointers to the strings logged by string conversions without a precision
+/// in a call to a logging symbol or a variadic logging wrapper.
+///
+/// Returns `None` if the format string is not located in global memory or cannot be parsed.
+fn get_logged_strings(
+    analysis_results: &AnalysisResults,
+    pi_state: &PointerInferenceState,
+    target: &Tid,
+    log_symbols: &HashMap<Tid, (&ExternSymbol, usize)>,
+    wrappers: &BTreeMap<Tid, FormatStringWrapper>,
+) -> Option<Vec<Data>> {
+    let project = analysis_results.project;
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let format_string_address = eval_format_string_parameter(
+        project,
+        pi_state,
+        target,
+        log_symbols,
+        wrappers,
+        runtime_memory_image,
+    )?
+    .try_to_bitvec()
+    .ok()?;
+    let format_string = runtime_memory_image
+        .read_string_until_null_terminator(&format_string_address)
+        .ok()?;
+    let string_indices = get_unbounded_string_parameter_indices(format_string).ok()?;
+    if string_indices.is_empty() {
+        return Some(Vec::new());
+    }
+    let parameters = if let Some((symbol, index)) = log_symbols.get(target) {
+        let format_string_index = HashMap::from([(symbol.name.clone(), *index)]);
+        get_variable_parameters(
+            project,
+            pi_state,
+            symbol,
+            &format_string_index,
+            runtime_memory_image,
+        )
+    } else {
+        get_wrapper_variable_parameters(
+            project,
+            pi_state,
+            target,
+            wrappers.get(target)?,
+            runtime_memory_image,
+        )
+    }
+    .ok()?;
+    Some(
+        string_indices
+            .into_iter()
+            .filter_map(|index| {
+                pi_state
+                    .eval_parameter_arg(parameters.get(index)?, runtime_memory_image)
+                    .ok()
+            })
+            .collect(),
+    )
+}
+
+/// Check whether the given logged string is located in an input buffer that is not sanitized before the logging call.
+/// If yes, return the confidence of the finding together with the TID of the input call.
+///
+/// If the string abstraction shows that the string cannot contain control characters, `None` is returned.
+fn check_logged_string<'a>(
+    analysis_results: &AnalysisResults,
+    input_buffers: &'a InputBuffers,
+    sanitized_buffers: &HashMap<Tid, Vec<(NodeIndex, Tid, Data)>>,
+    pi_state: &PointerInferenceState,
+    caller: &Term<Sub>,
+    log_node: NodeIndex,
+    string: &Data,
+) -> Option<(Confidence, &'a Tid)> {
+    let input_call = find_input_buffer(input_buffers.get(&caller.tid)?, string)?;
+    if let Some(sanitizer_calls) = sanitized_buffers.get(&caller.tid) {
+        if sanitizer_calls.iter().any(|(node, _, buffer)| {
+            may_point_into_buffer(string, buffer)
+                && is_reachable(analysis_results.control_flow_graph, *node, log_node)
+        }) {
+            return None;
+        }
+    }
+    let may_contain_control_characters =
+        analysis_results
+            .string_abstraction
+            .and_then(|string_abstraction| {
+                let state = match string_abstraction.get_node_value(log_node) {
+                    Some(NodeValue::Value(state)) => state,
+                    _ => return None,
+                };
+                let domain = Context::merge_domains_from_multiple_pointer_targets(
+                    state,
+                    pi_state,
+                    string.get_relative_values(),
+                );
+                CharacterInclusionDomain::from(&domain).may_contain_character(char::is_control)
+            });
+    match may_contain_control_characters {
+        Some(true) => Some((Confidence::High, input_call)),
+        Some(false) => None,
+        None => Some((Confidence::Low, input_call)),
+    }
+}
+
+/// Returns `true` if the target node is reachable from the source node in the control flow graph.
+fn is_reachable(graph: &Graph, source: NodeIndex, target: NodeIndex) -> bool {
+    petgraph::algo::has_path_connecting(graph, source, target, None)
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    sub_name: &str,
+    log_call: &Tid,
+    input_call: &Tid,
+    called_name: &str,
+    confidence: Confidence,
+) -> CweWarning {
+    let (description, confidence) = match confidence {
+        Confidence::High => (
+            format!(
+                "(Log Injection) Externally controlled string may contain newline characters when logged by {} in function {} ({})",
+                called_name,
+                sub_name,
+                log_call.canonical_address()
+            ),
+            "high",
+        ),
+        Confidence::Low => (
+            format!(
+                "(Log Injection) Externally controlled string is logged by {} without neutralizing newline characters in function {} ({})",
+                called_name,
+                sub_name,
+                log_call.canonical_address()
+            ),
+            "low",
+        ),
+    };
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![
+            log_call.canonical_address().to_string(),
+            input_call.canonical_address().to_string(),
+        ])
+        .tids(vec![format!("{}", log_call), format!("{}", input_call)])
+        .symbols(vec![sub_name.to_string()])
+        .other(vec![vec!["confidence".to_string(), confidence.to_string()]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project where `main` receives a message into a stack buffer pointed to by `RBP` and logs it twice with `syslog(3, "cat %s", buffer)`,
+    /// the second time after newline characters were stripped from the buffer with `strcspn`.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let parameter_registers: Vec<Variable> = ["RDI", "RSI", "RDX", "RCX", "R8", "R9"]
+            .iter()
+            .map(|name| Variable::mock(name, 8))
+            .collect();
+        let cconv = CallingConvention::mock_with_parameter_registers(parameter_registers, vec![]);
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for (name, parameters, has_var_args) in [
+            ("recv", &["RDI", "RSI", "RDX", "RCX"][..], false),
+            ("syslog", &["RDI", "RSI"], true),
+            ("strcspn", &["RDI", "RSI"], false),
+        ] {
+            let symbol = project.add_mock_extern_symbol(name);
+            symbol.parameters = parameters
+                .iter()
+                .map(|name| Arg::mock_register(name, 8))
+                .collect();
+            symbol.has_var_args = has_var_args;
+        }
+
+        let buffer_in = |tid: &str, register: &str| {
+            Def::assign(tid, Variable::mock(register, 8), Expression::var("RBP", 8))
+        };
+        let constant_in = |tid: &str, register: &str, value: i64| {
+            Def::assign(
+                tid,
+                Variable::mock(register, 8),
+                Expression::const_from_i64(value),
+            )
+        };
+        let log_defs = |prefix: &str| {
+            vec![
+                constant_in(&format!("{}_level", prefix), "RDI", 3),
+                constant_in(&format!("{}_format", prefix), "RSI", 0x500c),
+                buffer_in(&format!("{}_message", prefix), "RDX"),
+            ]
+        };
+        let mut main = Sub::mock_with_blocks(
+            "main",
+            vec![
+                Blk::mock_call(
+                    "main_recv",
+                    vec![
+                        Def::assign(
+                            "frame_buffer",
+                            Variable::mock("RBP", 8),
+                            Expression::var("RSP", 8).plus_const(-0x100),
+                        ),
+                        constant_in("recv_socket", "RDI", 3),
+                        buffer_in("recv_buffer", "RSI"),
+                        constant_in("recv_length", "RDX", 0x100),
+                    ],
+                    "recv",
+                    "main_log_tainted",
+                ),
+                Blk::mock_call(
+                    "main_log_tainted",
+                    log_defs("tainted"),
+                    "syslog",
+                    "main_sanitize",
+                ),
+                Blk::mock_call(
+                    "main_sanitize",
+                    vec![
+                        buffer_in("sanitize_buffer", "RDI"),
+                        constant_in("sanitize_reject", "RSI", 0x3002),
+                    ],
+                    "strcspn",
+                    "main_log_sanitized",
+                ),
+                Blk::mock_call(
+                    "main_log_sanitized",
+                    log_defs("sanitized"),
+                    "syslog",
+                    "main_ret",
+                ),
+                Blk::mock_with_tid("main_ret"),
+            ],
+        );
+        main.term.blocks[4].term.jmps.push(Term {
+            tid: Tid::new("return_main"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        // Distinct addresses for the calls.
+        for (index, block) in main.term.blocks.iter_mut().enumerate() {
+            block.term.jmps[0].tid.address = format!("{:x}", 0x1000 + 4 * index);
+        }
+        project.program.term.subs.insert(main.tid.clone(), main);
+        project.program.term.entry_points.insert(Tid::new("main"));
+        project
+    }
+
+    #[test]
+    fn tainted_strings_logged_without_sanitization() {
+        let project = mock_project();
+        let config = serde_json::json!({
+            "log_symbols": { "syslog": 1 },
+            "sanitizer_symbols": ["strcspn"],
+            "input_symbols": { "recv": [1] },
+        });
+        let config_without_sanitizers = serde_json::json!({
+            "log_symbols": { "syslog": 1 },
+            "input_symbols": { "recv": [1] },
+        });
+        let (warnings, warnings_without_sanitizers) =
+            AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+                (
+                    check_cwe(analysis_results, &config).1,
+                    check_cwe(analysis_results, &config_without_sanitizers).1,
+                )
+            });
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "call_main_log_tainted".to_string(),
+                "call_main_recv".to_string()
+            ]
+        );
+        assert_eq!(
+            warnings[0].other,
+            vec![vec!["confidence".to_string(), "low".to_string()]]
+        );
+
+        assert_eq!(warnings_without_sanitizers.len(), 2);
+    }
+}
@@ -0,0 +1,357 @@
+//! This module implements a check for CWE-123: Write-what-where Condition.
+//!
+//! If an attacker controls both the target address and the written value of a store instruction,
+//! the attacker can write arbitrary values to arbitrary memory locations.
+//! Such write-what-where primitives are usually sufficient to take over control of the program.
+//!
+//! See <https://cwe.mitre.org/data/definitions/123.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using dataflow analysis we track the return values of the symbols configured in config.json
+//! (e.g. `atoi` or `getchar`) as taint sources through the function calling them.
+//! For each store instruction we collect the taint sources influencing the target address
+//! and the taint sources influencing the stored value.
+//! If both the address and the value are tainted, we generate a CWE warning
+//! unless the pointer inference analysis knows that the store writes to a single memory object
+//! with known bounds and that all possible offsets of the store lie inside these bounds.
+//! The warnings are marked as high-severity warnings in the `other` field.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! The symbols are the functions whose return values are assumed to be externally controlled.
+//!
+//! ## False Positives
+//!
+//! - The pointer inference analysis may not know the bounds of the accessed object
+//!   or may overapproximate the offsets of the store, e.g. after loops.
+//!   In these cases the store is reported even if the address was properly validated.
+//!
+//! ## False Negatives
+//!
+//! - The analysis is intraprocedural, i.e. tainted values passed to other functions are not tracked.
+//! - Externally controlled values that are returned through memory (e.g. by `read`) are not tracked.
+//! - Stores that write to a single object inside its bounds are never reported,
+//!   even if the attacker-controlled offset can be used to overwrite sensitive data inside the object.
+
+use crate::analysis::forward_interprocedural_fixpoint::Context as _;
+use crate::analysis::taint::{compute_taint_from_sources, get_extern_taint_source_sites, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+use std::collections::{BTreeMap, BTreeSet};
+
+mod context;
+use context::*;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE123",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Config {
+    /// The names of symbols whose return values are assumed to be externally controlled.
+    symbols: Vec<String>,
+}
+
+/// The taint information for a store instruction collected during the analysis of one taint source.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StoreTaint {
+    /// The TID of the store instruction.
+    store: Tid,
+    /// The TID of the call whose return value is the taint source.
+    source: Tid,
+    /// The name of the called function whose return value is the taint source.
+    source_name: String,
+    /// Whether the target address of the store is tainted by the source.
+    address_tainted: bool,
+    /// Whether the stored value is tainted by the source.
+    value_tainted: bool,
+    /// Whether the store is known to write inside the bounds of a single memory object.
+    address_bounded: bool,
+}
+
+/// The taint sources influencing a store instruction, collected over all taint sources.
+#[derive(Debug, Default)]
+struct StoreTaintSources {
+    /// The taint sources (call TID and symbol name) influencing the target address of the store.
+    address_sources: BTreeSet<(Tid, String)>,
+    /// The taint sources (call TID and symbol name) influencing the stored value.
+    value_sources: BTreeSet<(Tid, String)>,
+    /// Whether the store was known to write inside the bounds of a single memory object
+    /// in all analyses of taint sources.
+    address_bounded: bool,
+}
+
+/// Run the CWE check.
+/// For each call to one of the configured symbols we track its return value
+/// and collect the stores whose address or value depends on it.
+/// Afterwards we generate warnings for stores where both address and value are tainted.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+
+    let (store_taint_sender, store_taint_receiver) = crossbeam_channel::unbounded();
+
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let symbol_map = crate::utils::symbol_utils::get_symbol_map(project, &config.symbols[..]);
+    let general_context = Context::new(project, pointer_inference_results, store_taint_sender);
+
+    let taint_source_sites = get_extern_taint_source_sites(
+        general_context.get_graph(),
+        &symbol_map,
+        pointer_inference_results,
+        State::new,
+    )
+    .into_iter()
+    .map(|(jmp, _symbol, node, state)| (jmp, node, state));
+    let logs = compute_taint_from_sources(
+        &general_context,
+        taint_source_sites,
+        |context, jmp, current_sub| context.set_taint_source(jmp, current_sub),
+    );
+
+    let mut stores: BTreeMap<Tid, StoreTaintSources> = BTreeMap::new();
+    for store_taint in store_taint_receiver.try_iter() {
+        let sources =
+            stores
+                .entry(store_taint.store.clone())
+                .or_insert_with(|| StoreTaintSources {
+                    address_bounded: true,
+                    ..Default::default()
+                });
+        let source = (store_taint.source, store_taint.source_name);
+        if store_taint.address_tainted {
+            sources.address_sources.insert(source.clone());
+        }
+        if store_taint.value_tainted {
+            sources.value_sources.insert(source);
+        }
+        sources.address_bounded &= store_taint.address_bounded;
+    }
+    let cwe_warnings = stores
+        .iter()
+        .filter(|(_, sources)| {
+            !sources.address_sources.is_empty()
+                && !sources.value_sources.is_empty()
+                && !sources.address_bounded
+        })
+        .map(|(store, sources)| generate_cwe_warning(store, sources))
+        .collect();
+
+    (logs, cwe_warnings)
+}
+
+/// Format a list of taint sources as `name at address` entries separated by commas.
+fn format_sources(sources: &BTreeSet<(Tid, String)>) -> String {
+    sources
+        .iter()
+        .map(|(tid, name)| format!("{} at {}", name, tid.canonical_address()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Generate the CWE warning for a store whose address and value are both tainted.
+fn generate_cwe_warning(store: &Tid, sources: &StoreTaintSources) -> CweWarning {
+    let all_sources: BTreeSet<&(Tid, String)> = sources
+        .address_sources
+        .iter()
+        .chain(sources.value_sources.iter())
+        .collect();
+    let mut addresses = vec![store.canonical_address().to_string()];
+    let mut tids = vec![format!("{}", store)];
+    for (source, _) in all_sources.iter() {
+        addresses.push(source.canonical_address().to_string());
+        tids.push(format!("{}", source));
+    }
+    let symbols: BTreeSet<String> = all_sources.iter().map(|(_, name)| name.clone()).collect();
+    let mut address_source_info = vec!["address_sources".to_string()];
+    address_source_info.extend(
+        sources
+            .address_sources
+            .iter()
+            .map(|(tid, _)| tid.to_string()),
+    );
+    let mut value_source_info = vec!["value_sources".to_string()];
+    value_source_info.extend(sources.value_sources.iter().map(|(tid, _)| tid.to_string()));
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Write-what-where Condition) The address and the value of the store at {} are both externally controlled. The address depends on the return value of {} and the value depends on the return value of {}.",
+            store.canonical_address(),
+            format_sources(&sources.address_sources),
+            format_sources(&sources.value_sources),
+        ),
+    )
+    .addresses(addresses)
+    .tids(tids)
+    .symbols(symbols.into_iter().collect())
+    .other(vec![
+        vec!["severity".to_string(), "high".to_string()],
+        address_source_info,
+        value_source_info,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project where a heap object of size 0x200 is allocated
+    /// and the return values of two calls to `source_symbol` are computed.
+    /// The heap pointer is saved in `RBX`.
+    /// The return value of the first call is saved in `RBP` and the return value of the second call is in `RAX`.
+    /// Afterwards the given store instruction is executed.
+    fn mock_project(source_symbol: &str, address: Expression, value: Expression) -> Project {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock();
+        cconv.callee_saved_register.push(Variable::mock("RBX", 8));
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["atoi", "malloc", "other"] {
+            project.add_mock_extern_symbol(name);
+        }
+
+        let mut sub = Sub::mock("func");
+        let mut alloc_block = Blk::mock_with_tid("alloc_block");
+        alloc_block.term.defs.append(&mut vec![
+            Def::assign(
+                "stack_alloc",
+                Variable::mock("RSP", 8),
+                Expression::var("RSP", 8).plus_const(-0x80),
+            ),
+            Def::assign(
+                "alloc_size",
+                Variable::mock("RDI", 8),
+                Expression::const_from_i64(0x200),
+            ),
+        ]);
+        alloc_block
+            .term
+            .jmps
+            .push(Jmp::call("malloc_call", "malloc", Some("first_call_block")));
+        let mut first_call_block = Blk::mock_with_tid("first_call_block");
+        first_call_block.term.defs.push(Def::assign(
+            "save_heap_pointer",
+            Variable::mock("RBX", 8),
+            Expression::var("RAX", 8),
+        ));
+        first_call_block.term.jmps.push(Jmp::call(
+            "first_source_call",
+            source_symbol,
+            Some("second_call_block"),
+        ));
+        let mut second_call_block = Blk::mock_with_tid("second_call_block");
+        second_call_block.term.defs.push(Def::assign(
+            "save_first_source",
+            Variable::mock("RBP", 8),
+            Expression::var("RAX", 8),
+        ));
+        second_call_block.term.jmps.push(Jmp::call(
+            "second_source_call",
+            source_symbol,
+            Some("store_block"),
+        ));
+        let mut store_block = Blk::mock_with_tid("store_block");
+        store_block
+            .term
+            .defs
+            .append(&mut vec![Def::store("store", address, value)]);
+        store_block.term.jmps.push(Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        sub.term.blocks = vec![
+            alloc_block,
+            first_call_block,
+            second_call_block,
+            store_block,
+        ];
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    fn run_check(project: &Project, source_symbols: &[&str]) -> Vec<CweWarning> {
+        let config = serde_json::json!({ "symbols": source_symbols });
+        AnalysisResults::run_with_mock_pointer_inference(project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn tainted_address_and_value() {
+        let address = Expression::var("RBX", 8).plus(Expression::var("RBP", 8));
+        let project = mock_project("atoi", address, Expression::var("RAX", 8));
+        let cwe_warnings = run_check(&project, &["atoi"]);
+        assert_eq!(cwe_warnings.len(), 1);
+        let warning = &cwe_warnings[0];
+        assert_eq!(
+            warning.tids,
+            vec![
+                "store".to_string(),
+                "first_source_call".to_string(),
+                "second_source_call".to_string()
+            ]
+        );
+        assert_eq!(warning.symbols, vec!["atoi".to_string()]);
+        assert_eq!(
+            warning.other,
+            vec![
+                vec!["severity".to_string(), "high".to_string()],
+                vec![
+                    "address_sources".to_string(),
+                    "first_source_call".to_string()
+                ],
+                vec![
+                    "value_sources".to_string(),
+                    "second_source_call".to_string()
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn only_value_tainted() {
+        let address = Expression::var("RBX", 8).plus_const(0x10);
+        let project = mock_project("atoi", address, Expression::var("RAX", 8));
+        assert!(run_check(&project, &["atoi"]).is_empty());
+    }
+
+    #[test]
+    fn bounded_tainted_address() {
+        // The heap pointer returned by `malloc` is tainted,
+        // but the store is known to stay inside the bounds of the heap object.
+        let address = Expression::var("RBX", 8).plus_const(0x10);
+        let project = mock_project("atoi", address, Expression::var("RAX", 8));
+        assert!(run_check(&project, &["atoi", "malloc"]).is_empty());
+        // With an additional unknown offset the store may leave the bounds of the heap object.
+        let address = Expression::var("RBX", 8).plus(Expression::var("RBP", 8));
+        let project = mock_project("atoi", address, Expression::var("RAX", 8));
+        let cwe_warnings = run_check(&project, &["atoi", "malloc"]);
+        assert_eq!(cwe_warnings.len(), 1);
+        assert_eq!(
+            cwe_warnings[0].symbols,
+            vec!["atoi".to_string(), "malloc".to_string()]
+        );
+    }
+
+    #[test]
+    fn untainted_store() {
+        let address = Expression::var("RBX", 8).plus(Expression::var("RBP", 8));
+        let project = mock_project("other", address, Expression::var("RAX", 8));
+        assert!(run_check(&project, &["atoi"]).is_empty());
+    }
+}
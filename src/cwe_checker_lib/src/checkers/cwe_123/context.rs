@@ -0,0 +1,176 @@
+use super::StoreTaint;
+use crate::abstract_domain::AbstractDomain;
+use crate::analysis::graph::{Graph, Node};
+use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
+use crate::analysis::pointer_inference::State as PointerInferenceState;
+use crate::analysis::taint::{State, TaintContext};
+use crate::intermediate_representation::*;
+
+/// The context object for the CWE-123 check.
+///
+/// The taint source is managed by the wrapped [`TaintContext`](crate::analysis::taint::TaintContext).
+#[derive(Clone)]
+pub struct Context<'a> {
+    /// The taint source, the current function and the pointer inference results of the analysis.
+    taint: TaintContext<'a>,
+    /// A channel where the taint information for store instructions is sent to.
+    store_taint_collector: crossbeam_channel::Sender<StoreTaint>,
+}
+
+impl<'a> Context<'a> {
+    /// Create a new context object.
+    pub fn new(
+        project: &'a Project,
+        pointer_inference_results: &'a PointerInferenceComputation<'a>,
+        store_taint_collector: crossbeam_channel::Sender<StoreTaint>,
+    ) -> Self {
+        Context {
+            taint: TaintContext::new(project, pointer_inference_results),
+            store_taint_collector,
+        }
+    }
+
+    /// Set the taint source and the current function for the analysis.
+    pub fn set_taint_source(&mut self, taint_source: &'a Term<Jmp>, current_sub: &'a Term<Sub>) {
+        self.taint.set_taint_source(taint_source, current_sub);
+    }
+
+    /// Send the taint information for the given store instruction to the collector
+    /// if either the address or the stored value is tainted.
+    ///
+    /// The address counts as bounded if the pointer inference analysis knows
+    /// that the store writes to a single memory object with known bounds and does not exceed these bounds.
+    fn collect_store_taint(
+        &self,
+        state: &State,
+        store: &Term<Def>,
+        address: &Expression,
+        value: &Expression,
+        pi_state: Option<&PointerInferenceState>,
+    ) {
+        let address_tainted = state.eval(address).is_tainted();
+        let value_tainted = state.eval(value).is_tainted();
+        if !address_tainted && !value_tainted {
+            return;
+        }
+        let address_bounded = pi_state
+            .map(|pi_state| pi_state.is_bounded_mem_access_to_unique_object(&store.term))
+            .unwrap_or(false);
+        let store_taint = StoreTaint {
+            store: store.tid.clone(),
+            source: self.taint.get_taint_source().tid.clone(),
+            source_name: self.taint.get_taint_source_name().to_string(),
+            address_tainted,
+            value_tainted,
+            address_bounded,
+        };
+        let _ = self.store_taint_collector.send(store_taint);
+    }
+}
+
+impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Context<'a> {
+    type Value = State;
+
+    /// Get the underlying graph of the fixpoint computation
+    fn get_graph(&self) -> &Graph<'a> {
+        self.taint.pointer_inference_results.get_graph()
+    }
+
+    /// Merge two states
+    fn merge(&self, state1: &State, state2: &State) -> State {
+        state1.merge(state2)
+    }
+
+    /// Conditionals do not change the taint state.
+    fn specialize_conditional(
+        &self,
+        state: &State,
+        _condition: &Expression,
+        _block_before_condition: &Term<Blk>,
+        _is_true: bool,
+    ) -> Option<State> {
+        Some(state.clone())
+    }
+
+    /// Always returns `None` so that the analysis stays intraprocedural.
+    fn update_call(
+        &self,
+        _state: &State,
+        _call: &Term<Jmp>,
+        _target: &Node,
+        _calling_convention: &Option<String>,
+    ) -> Option<Self::Value> {
+        None
+    }
+
+    /// Remove taint from non-callee-saved registers.
+    fn update_call_stub(&self, state: &State, call: &Term<Jmp>) -> Option<Self::Value> {
+        if state.is_empty() {
+            return None;
+        }
+        match &call.term {
+            Jmp::Call { target, .. } => {
+                let project = self.taint.project;
+                if let Some(extern_symbol) = project.program.term.extern_symbols.get(target) {
+                    let mut new_state = state.clone();
+                    new_state.remove_non_callee_saved_taint(
+                        project.get_calling_convention(extern_symbol),
+                    );
+                    Some(new_state)
+                } else {
+                    // The call target is a function excluded from the analysis.
+                    Some(self.taint.handle_generic_call(state))
+                }
+            }
+            Jmp::CallInd { .. } => Some(self.taint.handle_generic_call(state)),
+            _ => panic!("Malformed control flow graph encountered."),
+        }
+    }
+
+    /// Update the taint state according to the effects of the given [`Def`].
+    /// For store instructions with tainted address or value,
+    /// the taint information is sent to the collector.
+    fn update_def(&self, state: &State, def: &Term<Def>) -> Option<Self::Value> {
+        if state.is_empty() {
+            // Without taint there is nothing to propagate.
+            return None;
+        }
+        if let Def::Store { address, value } = &def.term {
+            let pi_state = self
+                .taint
+                .get_current_pointer_inference_state(state, &def.tid);
+            self.collect_store_taint(state, def, address, value, pi_state.as_ref());
+        }
+        Some(self.taint.propagate_taint(state, def))
+    }
+
+    /// Remove the intermediate pointer inference state, since it is only valid inside a block.
+    fn update_jump(
+        &self,
+        state: &State,
+        _jump: &Term<Jmp>,
+        _untaken_conditional: Option<&Term<Jmp>>,
+        _target: &Term<Blk>,
+    ) -> Option<Self::Value> {
+        if state.is_empty() {
+            // Without taint there is nothing to propagate.
+            return None;
+        }
+        let mut new_state = state.clone();
+        new_state.set_pointer_inference_state(None);
+        Some(new_state)
+    }
+
+    /// Since the analysis is intraprocedural, returned values are not tracked.
+    /// If `state_before_call` is set, handle it like a call to an unknown function.
+    fn update_return(
+        &self,
+        _state_before_return: Option<&State>,
+        state_before_call: Option<&State>,
+        _call_term: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        _calling_convention: &Option<String>,
+    ) -> Option<State> {
+        state_before_call.map(|state| self.taint.handle_generic_call(state))
+    }
+}
@@ -0,0 +1,249 @@
+//! This module implements a check for CWE-129: Improper Validation of Array Index.
+//!
+//! If an externally controlled value is used as an index into an array
+//! without checking it against the bounds of the array first,
+//! an attacker may be able to read or write memory outside of the array.
+//!
+//! See <https://cwe.mitre.org/data/definitions/129.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using dataflow analysis we track the return values of the symbols configured in config.json
+//! (e.g. `atoi` or `strtol`) as taint sources through the function calling them.
+//! Internal functions that pass the return value of a taint source on to their callers
+//! (e.g. a wrapper around `atoi`) are treated as derived taint sources,
+//! see the [`taint_sources`](crate::analysis::taint_sources) module for details.
+//! If a memory access happens where the address depends on a tainted value,
+//! we generate a CWE warning unless the index was validated before the access.
+//! A tainted value counts as validated if a conditional jump on all paths to the access
+//! depends on a comparison of the value against
+//! - a constant value or
+//! - a length value loaded from the same memory object that is accessed with the index.
+//!
+//! In contrast to the checks for CWE-119 and its variants,
+//! the check does not need to know whether the index may actually exceed the bounds of the accessed object.
+//! It also generates warnings for completely unknown index values as long as they are tainted.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! The symbols are the functions whose return values are assumed to be externally controlled.
+//!
+//! ## False Positives
+//!
+//! - If a value is spilled to memory after it was checked,
+//!   then the information about the check is lost for the reloaded value.
+//! - The index may be validated by other means than a comparison, e.g. by a bit mask.
+//!
+//! ## False Negatives
+//!
+//! - Tainted values passed to other functions are not tracked.
+//!   Only the return values of functions are tracked through derived taint sources.
+//! - Any comparison against a constant is accepted as validation,
+//!   regardless of whether the constant is a correct bound for the array.
+//! - Externally controlled values that are returned through memory (e.g. by `scanf`) are not tracked.
+
+use crate::analysis::forward_interprocedural_fixpoint::Context as _;
+use crate::analysis::taint::{compute_taint_from_sources, get_extern_taint_source_sites};
+use crate::analysis::taint_sources::{
+    compute_derived_taint_sources, get_configured_taint_sources, get_derived_source_calls,
+    log_derived_taint_sources,
+};
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+use std::collections::BTreeMap;
+
+mod state;
+use state::*;
+
+mod context;
+use context::*;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE129",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Config {
+    /// The names of symbols whose return values are assumed to be externally controlled.
+    symbols: Vec<String>,
+}
+
+/// Run the CWE check.
+/// For each call to one of the configured symbols
+/// we check whether its return value is used as an array index before it was validated.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+
+    let (cwe_sender, cwe_receiver) = crossbeam_channel::unbounded();
+
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let symbol_map = crate::utils::symbol_utils::get_symbol_map(project, &config.symbols[..]);
+    let derived_sources = compute_derived_taint_sources(
+        project,
+        &get_configured_taint_sources(CWE_MODULE.name, cwe_params),
+    );
+    let general_context = Context::new(project, pointer_inference_results, cwe_sender);
+
+    let mut taint_source_sites: Vec<_> = get_extern_taint_source_sites(
+        general_context.get_graph(),
+        &symbol_map,
+        pointer_inference_results,
+        State::new,
+    )
+    .into_iter()
+    .map(|(jmp, _symbol, node, state)| (jmp, node, state))
+    .collect();
+    for derived_call in
+        get_derived_source_calls(project, general_context.get_graph(), &derived_sources)
+    {
+        if let (Some(node), false) = (
+            derived_call.return_node,
+            derived_call.tainted_return_registers.is_empty(),
+        ) {
+            taint_source_sites.push((
+                derived_call.call,
+                node,
+                State::new_with_tainted_registers(&derived_call.tainted_return_registers),
+            ));
+        }
+    }
+    let mut logs = log_derived_taint_sources(CWE_MODULE.name, &derived_sources);
+    logs.extend(compute_taint_from_sources(
+        &general_context,
+        taint_source_sites,
+        |context, jmp, current_sub| context.set_taint_source(jmp, current_sub),
+    ));
+
+    let mut cwe_warnings = BTreeMap::new();
+    for cwe in cwe_receiver.try_iter() {
+        cwe_warnings.insert((cwe.addresses.clone(), cwe.tids.clone()), cwe);
+    }
+    let cwe_warnings = cwe_warnings.into_values().collect();
+
+    (logs, cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::*;
+
+    /// Create a project where the return value of `source_symbol` is used as index for a store instruction.
+    /// If `check` is set, the index is compared against the given value before the store.
+    fn mock_project(source_symbol: &str, check: Option<Expression>) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["atoi", "other"] {
+            project.add_mock_extern_symbol(name);
+        }
+
+        let mut sub = Sub::mock("func");
+        let mut call_block = Blk::mock_with_tid("call_block");
+        call_block.term.defs.push(Def::assign(
+            "stack_alloc",
+            Variable::mock("RSP", 8),
+            Expression::var("RSP", 8).plus_const(-0x80),
+        ));
+        call_block
+            .term
+            .jmps
+            .push(Jmp::call("source_call", source_symbol, Some("check_block")));
+        let mut check_block = Blk::mock_with_tid("check_block");
+        let mut access_block = Blk::mock_with_tid("access_block");
+        access_block.term.defs.push(Def::store(
+            "array_access",
+            Expression::var("RSP", 8).plus(Expression::var("RAX", 8)),
+            Expression::const_from_i64(0),
+        ));
+        access_block
+            .term
+            .jmps
+            .push(Jmp::branch("jmp_end", "end_block"));
+        let end_block = Blk::mock_return("end_block");
+        if let Some(bound) = check {
+            check_block.term.defs.push(Def::load(
+                "load_length",
+                Variable::mock("RCX", 8),
+                Expression::var("RSP", 8).plus_const(0x10),
+            ));
+            check_block.term.defs.push(Def::assign(
+                "compare",
+                Variable::mock("CF", 1),
+                Expression::BinOp {
+                    op: BinOpType::IntLess,
+                    lhs: Box::new(Expression::var("RAX", 8)),
+                    rhs: Box::new(bound),
+                },
+            ));
+            check_block.term.jmps.push(Term {
+                tid: Tid::new("check"),
+                term: Jmp::CBranch {
+                    target: Tid::new("access_block"),
+                    condition: Expression::var("CF", 1),
+                },
+            });
+            check_block
+                .term
+                .jmps
+                .push(Jmp::branch("skip_access", "end_block"));
+        } else {
+            check_block
+                .term
+                .jmps
+                .push(Jmp::branch("jmp_access", "access_block"));
+        }
+        sub.term.blocks = vec![call_block, check_block, access_block, end_block];
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let config = serde_json::json!({ "symbols": ["atoi"] });
+        AnalysisResults::run_with_mock_pointer_inference(project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn tainted_unchecked_index() {
+        let cwe_warnings = run_check(&mock_project("atoi", None));
+        assert_eq!(cwe_warnings.len(), 1);
+        assert_eq!(
+            cwe_warnings[0].tids,
+            vec!["source_call".to_string(), "array_access".to_string()]
+        );
+    }
+
+    #[test]
+    fn tainted_index_checked_against_constant() {
+        let check = Some(Expression::const_from_i64(16));
+        assert!(run_check(&mock_project("atoi", check)).is_empty());
+    }
+
+    #[test]
+    fn tainted_index_checked_against_length_of_accessed_object() {
+        // The length is loaded from the stack frame, which is also the accessed object.
+        let check = Some(Expression::var("RCX", 8));
+        assert!(run_check(&mock_project("atoi", check)).is_empty());
+    }
+
+    #[test]
+    fn untainted_unknown_index() {
+        assert!(run_check(&mock_project("other", None)).is_empty());
+    }
+}
@@ -0,0 +1,428 @@
+use super::State;
+use super::CWE_MODULE;
+use crate::abstract_domain::{AbstractDomain, AbstractIdentifier};
+use crate::analysis::forward_interprocedural_fixpoint::Context as _;
+use crate::analysis::graph::{Graph, Node};
+use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
+use crate::analysis::pointer_inference::State as PointerInferenceState;
+use crate::analysis::taint::{Taint, TaintContext};
+use crate::intermediate_representation::*;
+use crate::utils::log::CweWarning;
+use std::collections::BTreeSet;
+
+/// The kinds of values a tainted index can be compared against
+/// such that the comparison counts as a validation of the index.
+enum Bound {
+    /// The index was compared against a constant value.
+    Constant,
+    /// The index was compared against a length value loaded from one of the given memory objects.
+    LengthOf(BTreeSet<AbstractIdentifier>),
+}
+
+/// The context object for the CWE-129 check.
+///
+/// The taint source is managed by the wrapped [`TaintContext`](crate::analysis::taint::TaintContext).
+#[derive(Clone)]
+pub struct Context<'a> {
+    /// The taint source, the current function and the pointer inference results of the analysis.
+    taint: TaintContext<'a>,
+    /// A channel where found CWE hits can be sent to.
+    cwe_collector: crossbeam_channel::Sender<CweWarning>,
+}
+
+impl<'a> Context<'a> {
+    /// Create a new context object.
+    pub fn new(
+        project: &'a Project,
+        pointer_inference_results: &'a PointerInferenceComputation<'a>,
+        cwe_collector: crossbeam_channel::Sender<CweWarning>,
+    ) -> Self {
+        Context {
+            taint: TaintContext::new(project, pointer_inference_results),
+            cwe_collector,
+        }
+    }
+
+    /// Set the taint source and the current function for the analysis.
+    pub fn set_taint_source(&mut self, taint_source: &'a Term<Jmp>, current_sub: &'a Term<Sub>) {
+        self.taint.set_taint_source(taint_source, current_sub);
+    }
+
+    /// Get the current pointer inference state (if one can be found) for the given taint state.
+    fn get_current_pointer_inference_state(
+        &self,
+        state: &State,
+        tid: &Tid,
+    ) -> Option<PointerInferenceState> {
+        self.taint
+            .get_current_pointer_inference_state(state.get_taint_state(), tid)
+    }
+
+    /// Get the pointer inference state right before the `Def` with the given index in the given block.
+    fn get_pointer_inference_state_before_def(
+        &self,
+        block: &Term<Blk>,
+        def_index: usize,
+    ) -> Option<PointerInferenceState> {
+        let first_def = block.term.defs.first()?;
+        let mut pi_state = self
+            .taint
+            .get_block_start_pointer_inference_state(&first_def.tid)?;
+        let pi_context = self.taint.pointer_inference_results.get_context();
+        for def in &block.term.defs[..def_index] {
+            pi_state = pi_context.update_def(&pi_state, def)?;
+        }
+        Some(pi_state)
+    }
+
+    /// Generate a CWE warning for the taint source of the context object.
+    fn generate_cwe_warning(&self, access_location: &Tid) {
+        let taint_source = self.taint.get_taint_source();
+        let taint_source_name = self.taint.get_taint_source_name().to_string();
+        let cwe_warning = CweWarning::new(
+            CWE_MODULE.name,
+            CWE_MODULE.version,
+            format!(
+                "(Improper Validation of Array Index) The return value of {} at {} is used as an index for a memory access at {} without being validated.",
+                taint_source_name, taint_source.tid.canonical_address(), access_location.canonical_address()
+            ),
+        )
+        .addresses(vec![
+            taint_source.tid.canonical_address().to_string(),
+            access_location.canonical_address().to_string(),
+        ])
+        .tids(vec![
+            format!("{}", taint_source.tid),
+            format!("{}", access_location),
+        ])
+        .symbols(vec![taint_source_name]);
+        let _ = self.cwe_collector.send(cwe_warning);
+    }
+
+    /// Generate a CWE warning if the given address of a memory access depends on tainted registers
+    /// and not all of these registers were checked against the length of the accessed memory object.
+    fn check_memory_access(
+        &self,
+        state: &State,
+        address: &Expression,
+        pi_state: Option<&PointerInferenceState>,
+        access_location: &Tid,
+    ) {
+        let tainted_vars = state.get_tainted_input_vars(address);
+        if tainted_vars.is_empty() {
+            return;
+        }
+        let target_ids: Vec<AbstractIdentifier> = pi_state
+            .map(|pi_state| {
+                pi_state
+                    .eval(address)
+                    .get_relative_values()
+                    .keys()
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        let is_validated = !target_ids.is_empty()
+            && tainted_vars.iter().all(|var| {
+                state
+                    .get_checked_against_length_of(var)
+                    .map(|checked_ids| target_ids.iter().all(|id| checked_ids.contains(id)))
+                    .unwrap_or(false)
+            });
+        if !is_validated {
+            self.generate_cwe_warning(access_location);
+        }
+    }
+
+    /// Determine whether the given expression used in the comparison `Def` with index `comparison_index`
+    /// denotes a value that a tainted index can be validated against.
+    fn get_bound(
+        &self,
+        block: &Term<Blk>,
+        comparison_index: usize,
+        bound: &Expression,
+    ) -> Option<Bound> {
+        let var = match bound {
+            Expression::Const(_) => return Some(Bound::Constant),
+            Expression::Var(var) => var,
+            _ => return None,
+        };
+        let (def_index, def) = get_last_def_of_var(block, var, comparison_index)?;
+        match &def.term {
+            Def::Assign {
+                value: Expression::Const(_),
+                ..
+            } => Some(Bound::Constant),
+            Def::Load { address, .. } => {
+                let pi_state = self.get_pointer_inference_state_before_def(block, def_index)?;
+                let ids: BTreeSet<AbstractIdentifier> = pi_state
+                    .eval(address)
+                    .get_relative_values()
+                    .keys()
+                    .cloned()
+                    .collect();
+                if ids.is_empty() {
+                    None
+                } else {
+                    Some(Bound::LengthOf(ids))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Mark the tainted registers in `index` as validated if `bound` is a valid bound for an index.
+    /// The registers must not be overwritten between the comparison and the end of the block.
+    fn handle_comparison(
+        &self,
+        state: &mut State,
+        block: &Term<Blk>,
+        comparison_index: usize,
+        index: &Expression,
+        bound: &Expression,
+    ) {
+        let tainted_vars: Vec<Variable> = state
+            .get_tainted_input_vars(index)
+            .into_iter()
+            .filter(|var| !is_overwritten_after(block, var, comparison_index))
+            .collect();
+        if tainted_vars.is_empty() {
+            return;
+        }
+        match self.get_bound(block, comparison_index, bound) {
+            Some(Bound::Constant) => {
+                for var in tainted_vars.iter() {
+                    state.remove_register_taint(var);
+                }
+            }
+            Some(Bound::LengthOf(ids)) => {
+                for var in tainted_vars.iter() {
+                    state.add_checked_against_length_of(var, ids.iter().cloned());
+                }
+            }
+            None => (),
+        }
+    }
+
+    /// Remove the taint from all non-callee-saved registers for a call with unknown calling convention.
+    fn handle_generic_call(&self, state: &State) -> Option<State> {
+        let mut new_state = state.clone();
+        if let Some(calling_conv) = self.taint.project.get_standard_calling_convention() {
+            new_state.remove_non_callee_saved_taint(calling_conv);
+        }
+        Some(new_state)
+    }
+}
+
+/// Get the last `Def` in the block before the `Def` with the given index that assigns a value to the given register.
+fn get_last_def_of_var<'b>(
+    block: &'b Term<Blk>,
+    var: &Variable,
+    before_index: usize,
+) -> Option<(usize, &'b Term<Def>)> {
+    block.term.defs[..before_index]
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, def)| match &def.term {
+            Def::Assign { var: def_var, .. } | Def::Load { var: def_var, .. } => def_var == var,
+            Def::Store { .. } => false,
+        })
+}
+
+/// Check whether the given register is overwritten in the block after the `Def` with the given index.
+fn is_overwritten_after(block: &Term<Blk>, var: &Variable, index: usize) -> bool {
+    get_last_def_of_var(block, var, block.term.defs.len())
+        .map(|(def_index, _)| def_index > index)
+        .unwrap_or(false)
+}
+
+impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Context<'a> {
+    type Value = State;
+
+    /// Get the underlying graph of the fixpoint computation
+    fn get_graph(&self) -> &Graph<'a> {
+        self.taint.pointer_inference_results.get_graph()
+    }
+
+    /// Merge two states
+    fn merge(&self, state1: &State, state2: &State) -> State {
+        state1.merge(state2)
+    }
+
+    /// If the condition depends on a comparison of a tainted register against a valid bound,
+    /// mark the register as validated.
+    ///
+    /// The comparison counts as a validation for both branches of the conditional jump,
+    /// since the check does not try to determine which branch handles the out-of-bounds case.
+    fn specialize_conditional(
+        &self,
+        state: &State,
+        condition: &Expression,
+        block_before_condition: &Term<Blk>,
+        _is_true: bool,
+    ) -> Option<State> {
+        let mut new_state = state.clone();
+        let defs_len = block_before_condition.term.defs.len();
+        for condition_var in condition.input_vars() {
+            if let Some((comparison_index, comparison)) =
+                get_last_def_of_var(block_before_condition, condition_var, defs_len)
+            {
+                if let Def::Assign {
+                    value: Expression::BinOp { op, lhs, rhs },
+                    ..
+                } = &comparison.term
+                {
+                    use BinOpType::*;
+                    if matches!(
+                        op,
+                        IntEqual | IntNotEqual | IntLess | IntSLess | IntLessEqual | IntSLessEqual
+                    ) {
+                        self.handle_comparison(
+                            &mut new_state,
+                            block_before_condition,
+                            comparison_index,
+                            lhs,
+                            rhs,
+                        );
+                        self.handle_comparison(
+                            &mut new_state,
+                            block_before_condition,
+                            comparison_index,
+                            rhs,
+                            lhs,
+                        );
+                    }
+                }
+            }
+        }
+        Some(new_state)
+    }
+
+    /// Always returns `None` so that the analysis stays intraprocedural.
+    fn update_call(
+        &self,
+        _state: &State,
+        _call: &Term<Jmp>,
+        _target: &Node,
+        _calling_convention: &Option<String>,
+    ) -> Option<Self::Value> {
+        None
+    }
+
+    /// Remove taint from non-callee-saved registers.
+    fn update_call_stub(&self, state: &State, call: &Term<Jmp>) -> Option<Self::Value> {
+        if state.is_empty() {
+            return None;
+        }
+        match &call.term {
+            Jmp::Call { target, .. } => {
+                if let Some(extern_symbol) =
+                    self.taint.project.program.term.extern_symbols.get(target)
+                {
+                    let mut new_state = state.clone();
+                    new_state.remove_non_callee_saved_taint(
+                        self.taint.project.get_calling_convention(extern_symbol),
+                    );
+                    Some(new_state)
+                } else {
+                    // The call target is a function excluded from the analysis.
+                    self.handle_generic_call(state)
+                }
+            }
+            Jmp::CallInd { .. } => self.handle_generic_call(state),
+            _ => panic!("Malformed control flow graph encountered."),
+        }
+    }
+
+    /// Update the taint state according to the effects of the given [`Def`].
+    /// If the address of a load or store instruction depends on unvalidated taint,
+    /// generate a CWE warning.
+    fn update_def(&self, state: &State, def: &Term<Def>) -> Option<Self::Value> {
+        if state.is_empty() {
+            // Without taint there is nothing to propagate.
+            return None;
+        }
+        let mut new_state = state.clone();
+        match &def.term {
+            Def::Assign { var, value } => {
+                let tainted_vars = state.get_tainted_input_vars(value);
+                let mut checked_ids: Option<BTreeSet<AbstractIdentifier>> = None;
+                for tainted_var in tainted_vars.iter() {
+                    let ids = state
+                        .get_checked_against_length_of(tainted_var)
+                        .cloned()
+                        .unwrap_or_default();
+                    checked_ids = Some(match checked_ids {
+                        Some(checked_ids) => checked_ids.intersection(&ids).cloned().collect(),
+                        None => ids,
+                    });
+                }
+                new_state.set_register_taint(var, state.eval(value));
+                if let Some(checked_ids) = checked_ids {
+                    new_state.set_checked_against_length_of(var, checked_ids);
+                }
+            }
+            Def::Load { var, address } => {
+                let pi_state = self.get_current_pointer_inference_state(state, &def.tid);
+                self.check_memory_access(state, address, pi_state.as_ref(), &def.tid);
+                let taint = if let Some(pi_state) = pi_state {
+                    state
+                        .get_taint_state()
+                        .load_taint_from_memory(&pi_state.eval(address), var.size)
+                } else {
+                    Taint::Top(var.size)
+                };
+                new_state.set_register_taint(var, taint);
+            }
+            Def::Store { address, value } => {
+                let pi_state = self.get_current_pointer_inference_state(state, &def.tid);
+                self.check_memory_access(state, address, pi_state.as_ref(), &def.tid);
+                if let Some(pi_state) = pi_state {
+                    new_state
+                        .get_taint_state_mut()
+                        .save_taint_to_memory(&pi_state.eval(address), state.eval(value));
+                } else {
+                    // We lost all knowledge about memory pointers.
+                    // We delete all memory taints to reduce false positives.
+                    new_state.get_taint_state_mut().remove_all_memory_taints();
+                }
+            }
+        }
+        self.taint
+            .update_pointer_inference_state(new_state.get_taint_state_mut(), def);
+        Some(new_state)
+    }
+
+    /// Remove the intermediate pointer inference state, since it is only valid inside a block.
+    fn update_jump(
+        &self,
+        state: &State,
+        _jump: &Term<Jmp>,
+        _untaken_conditional: Option<&Term<Jmp>>,
+        _target: &Term<Blk>,
+    ) -> Option<Self::Value> {
+        if state.is_empty() {
+            // Without taint there is nothing to propagate.
+            return None;
+        }
+        let mut new_state = state.clone();
+        new_state
+            .get_taint_state_mut()
+            .set_pointer_inference_state(None);
+        Some(new_state)
+    }
+
+    /// Since the analysis is intraprocedural, returned values are not tracked.
+    /// If `state_before_call` is set, handle it like a call to an unknown function.
+    fn update_return(
+        &self,
+        _state_before_return: Option<&State>,
+        state_before_call: Option<&State>,
+        _call_term: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        _calling_convention: &Option<String>,
+    ) -> Option<State> {
+        state_before_call.and_then(|state| self.handle_generic_call(state))
+    }
+}
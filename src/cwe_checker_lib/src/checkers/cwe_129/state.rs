@@ -0,0 +1,153 @@
+use crate::abstract_domain::{AbstractDomain, AbstractIdentifier};
+use crate::analysis::pointer_inference::State as PointerInferenceState;
+use crate::analysis::taint::State as TaintState;
+use crate::analysis::taint::Taint;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The state object of the CWE-129 check.
+///
+/// It consists of a [taint state](crate::analysis::taint::State)
+/// and of the information against the lengths of which memory objects tainted registers were checked.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct State {
+    /// The taint state tracking the return values of the taint source.
+    taint: TaintState,
+    /// Maps tainted registers to the IDs of the memory objects
+    /// whose length value the register was compared against.
+    checked_against_length_of: BTreeMap<Variable, BTreeSet<AbstractIdentifier>>,
+}
+
+impl AbstractDomain for State {
+    /// Merge two states.
+    /// A register only counts as checked against the length of an object
+    /// if it was checked against it in both input states.
+    fn merge(&self, other: &Self) -> Self {
+        let checked_against_length_of =
+            self.checked_against_length_of
+                .iter()
+                .filter_map(|(var, ids)| {
+                    other.checked_against_length_of.get(var).map(|other_ids| {
+                        (var.clone(), ids.intersection(other_ids).cloned().collect())
+                    })
+                })
+                .collect();
+        State {
+            taint: self.taint.merge(&other.taint),
+            checked_against_length_of,
+        }
+    }
+
+    /// The state has no explicit Top element.
+    fn is_top(&self) -> bool {
+        false
+    }
+}
+
+impl State {
+    /// Get a new state in which only the return values of the given extern symbol are tainted.
+    pub fn new(taint_source: &ExternSymbol, pi_state: Option<&PointerInferenceState>) -> State {
+        State {
+            taint: TaintState::new(taint_source, pi_state),
+            checked_against_length_of: BTreeMap::new(),
+        }
+    }
+
+    /// Get a new state in which only the given registers are tainted,
+    /// e.g. the return registers of a derived taint source.
+    pub fn new_with_tainted_registers(registers: &[Variable]) -> State {
+        State {
+            taint: TaintState::new_with_tainted_registers(registers),
+            checked_against_length_of: BTreeMap::new(),
+        }
+    }
+
+    /// Get the contained taint state.
+    pub fn get_taint_state(&self) -> &TaintState {
+        &self.taint
+    }
+
+    /// Get a mutable reference to the contained taint state.
+    pub fn get_taint_state_mut(&mut self) -> &mut TaintState {
+        &mut self.taint
+    }
+
+    /// Evaluate whether the result of the given expression is tainted in the current state.
+    pub fn eval(&self, expression: &Expression) -> Taint {
+        self.taint.eval(expression)
+    }
+
+    /// Return all input registers of the given expression that are tainted.
+    pub fn get_tainted_input_vars(&self, expression: &Expression) -> Vec<Variable> {
+        expression
+            .input_vars()
+            .into_iter()
+            .filter(|var| {
+                self.taint
+                    .eval(&Expression::Var((*var).clone()))
+                    .is_tainted()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Set the taint of the given register.
+    /// Since the register value changes, all information about length checks of the register is removed.
+    pub fn set_register_taint(&mut self, register: &Variable, taint: Taint) {
+        self.taint.set_register_taint(register, taint);
+        self.checked_against_length_of.remove(register);
+    }
+
+    /// Remove the taint from the given register,
+    /// e.g. because it was checked against a constant bound.
+    pub fn remove_register_taint(&mut self, register: &Variable) {
+        self.set_register_taint(register, Taint::Top(register.size));
+    }
+
+    /// Get the IDs of the memory objects whose length value the given register was checked against.
+    pub fn get_checked_against_length_of(
+        &self,
+        register: &Variable,
+    ) -> Option<&BTreeSet<AbstractIdentifier>> {
+        self.checked_against_length_of.get(register)
+    }
+
+    /// Mark the given register as checked against the length values of the given memory objects.
+    pub fn add_checked_against_length_of(
+        &mut self,
+        register: &Variable,
+        ids: impl IntoIterator<Item = AbstractIdentifier>,
+    ) {
+        self.checked_against_length_of
+            .entry(register.clone())
+            .or_default()
+            .extend(ids);
+    }
+
+    /// Mark the given register as checked against exactly the given objects, overwriting earlier information.
+    pub fn set_checked_against_length_of(
+        &mut self,
+        register: &Variable,
+        ids: BTreeSet<AbstractIdentifier>,
+    ) {
+        if ids.is_empty() {
+            self.checked_against_length_of.remove(register);
+        } else {
+            self.checked_against_length_of.insert(register.clone(), ids);
+        }
+    }
+
+    /// Remove the taint and the length check information
+    /// from all registers not contained in the callee-saved register list of the given calling convention.
+    pub fn remove_non_callee_saved_taint(&mut self, calling_conv: &CallingConvention) {
+        self.taint.remove_non_callee_saved_taint(calling_conv);
+        self.checked_against_length_of
+            .retain(|var, _| calling_conv.callee_saved_register.contains(var));
+    }
+
+    /// Check whether `self` contains any taint at all.
+    pub fn is_empty(&self) -> bool {
+        self.taint.is_empty()
+    }
+}
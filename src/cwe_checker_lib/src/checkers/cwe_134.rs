@@ -11,6 +11,10 @@
 //! (e.g. sprintf). Then we check the content of the format string parameter and if it is not part of the global read only
 //! memory of the binary, a CWE warning is generated.
 //!
+//! Functions that forward their own format string parameter to such a symbol
+//! (e.g. logging functions passing a `va_list` to `vsnprintf`) are handled like the symbol itself,
+//! i.e. the format string is checked at the callsites of the wrapper function.
+//!
 //! ### Symbols configurable in config.json
 //!
 //! - symbols that take a format string parameter.
@@ -22,26 +26,31 @@
 
 use std::collections::HashMap;
 
-use petgraph::graph::NodeIndex;
 use petgraph::visit::EdgeRef;
 
 use crate::abstract_domain::TryToBitvec;
-use crate::analysis::graph::Edge;
+use crate::analysis::graph::{Edge, Node};
 use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
-use crate::analysis::pointer_inference::PointerInference;
+use crate::analysis::pointer_inference::Data;
 use crate::intermediate_representation::ExternSymbol;
 use crate::intermediate_representation::Jmp;
 use crate::prelude::*;
+use crate::utils::arguments::forwarding::{
+    eval_format_string_parameter, get_format_string_wrappers, is_forwarding_callsite,
+};
 use crate::utils::binary::RuntimeMemoryImage;
 use crate::utils::log::CweWarning;
 use crate::utils::log::LogMessage;
-use crate::CweModule;
+use crate::{CweModule, SharedAnalysis};
 
 /// The module name and version
 pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE134",
     version: "0.1",
     run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses,
 };
 
 /// The configuration struct
@@ -69,37 +78,65 @@ pub enum StringLocation {
 /// This check searches for external symbols that take a format string as an input parameter.
 /// It then checks whether the parameter points to read only memory.
 /// If not, a CWE warning is generated.
+///
+/// Wrapper functions forwarding their format string parameter to such a symbol
+/// (see [`forwarding`](crate::utils::arguments::forwarding)) are checked at their callsites instead.
 pub fn check_cwe(
     analysis_results: &AnalysisResults,
     cwe_params: &serde_json::Value,
 ) -> (Vec<LogMessage>, Vec<CweWarning>) {
     let project = analysis_results.project;
     let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
-    let format_string_symbols =
-        crate::utils::symbol_utils::get_symbol_map(project, &config.format_string_symbols[..]);
-    let format_string_index = config.format_string_index.clone();
+    let format_string_symbols: HashMap<Tid, (&ExternSymbol, usize)> =
+        crate::utils::symbol_utils::get_symbol_map(project, &config.format_string_symbols[..])
+            .into_iter()
+            .map(|(tid, symbol)| {
+                let index = *config.format_string_index.get(&symbol.name).unwrap();
+                (tid, (symbol, index))
+            })
+            .collect();
 
     let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let wrappers =
+        get_format_string_wrappers(project, &format_string_symbols, runtime_memory_image);
+    let graph = pointer_inference_results.get_graph();
     let mut cwe_warnings = Vec::new();
 
-    for edge in pointer_inference_results.get_graph().edge_references() {
-        if let Edge::ExternCallStub(jmp) = edge.weight() {
-            if let Jmp::Call { target, .. } = &jmp.term {
-                if let Some(symbol) = format_string_symbols.get(target) {
-                    let location = locate_format_string(
-                        &edge.source(),
-                        symbol,
-                        &format_string_index,
-                        pointer_inference_results,
-                        analysis_results.runtime_memory_image,
-                    );
-
-                    if matches!(
-                        location,
-                        StringLocation::GlobalWriteable | StringLocation::NonGlobal
-                    ) {
-                        cwe_warnings.push(generate_cwe_warning(&jmp.tid, symbol, &location));
-                    }
+    for edge in graph.edge_references() {
+        let jmp = match edge.weight() {
+            Edge::ExternCallStub(jmp) | Edge::CallCombine(jmp) => jmp,
+            _ => continue,
+        };
+        if let (Jmp::Call { target, .. }, Node::BlkEnd(_, caller)) =
+            (&jmp.term, graph[edge.source()])
+        {
+            let called_name = match (format_string_symbols.get(target), wrappers.get(target)) {
+                (Some((symbol, _)), _) => &symbol.name,
+                (None, Some(_)) => &project.program.term.subs[target].term.name,
+                (None, None) => continue,
+            };
+            if is_forwarding_callsite(&caller.tid, &jmp.tid, &wrappers) {
+                // The format string is checked at the callsites of the wrapper function.
+                continue;
+            }
+            if let Some(NodeValue::Value(pi_state)) =
+                pointer_inference_results.get_node_value(edge.source())
+            {
+                let format_string = eval_format_string_parameter(
+                    project,
+                    pi_state,
+                    target,
+                    &format_string_symbols,
+                    &wrappers,
+                    runtime_memory_image,
+                );
+                let location = locate_format_string(format_string.as_ref(), runtime_memory_image);
+                if matches!(
+                    location,
+                    StringLocation::GlobalWriteable | StringLocation::NonGlobal
+                ) {
+                    cwe_warnings.push(generate_cwe_warning(&jmp.tid, called_name, &location));
                 }
             }
         }
@@ -111,73 +148,62 @@ pub fn check_cwe(
 
 /// Returns a StringLocation based on the kind of memory
 /// holding the string.
-/// If no assumption about the string location can be made,
-/// unknown is returned.
+/// If the format string parameter could not be evaluated,
+/// the string is assumed to be non-global.
 fn locate_format_string(
-    node: &NodeIndex,
-    symbol: &ExternSymbol,
-    format_string_index: &HashMap<String, usize>,
-    pointer_inference_results: &PointerInference,
+    format_string: Option<&Data>,
     runtime_memory_image: &RuntimeMemoryImage,
 ) -> StringLocation {
-    if let Some(NodeValue::Value(pi_state)) = pointer_inference_results.get_node_value(*node) {
-        let format_string_parameter = symbol
-            .parameters
-            .get(*format_string_index.get(&symbol.name).unwrap())
-            .unwrap();
-        if let Ok(address) =
-            pi_state.eval_parameter_arg(format_string_parameter, runtime_memory_image)
-        {
-            if let Ok(address_vector) = address.try_to_bitvec() {
-                if runtime_memory_image.is_global_memory_address(&address_vector) {
-                    if runtime_memory_image
-                        .is_address_writeable(&address_vector)
-                        .unwrap()
-                    {
-                        return StringLocation::GlobalWriteable;
-                    }
-
-                    return StringLocation::GlobalReadable;
+    if let Some(address) = format_string {
+        if let Ok(address_vector) = address.try_to_bitvec() {
+            if runtime_memory_image.is_global_memory_address(&address_vector) {
+                if runtime_memory_image
+                    .is_address_writeable(&address_vector)
+                    .unwrap()
+                {
+                    return StringLocation::GlobalWriteable;
                 }
+
+                return StringLocation::GlobalReadable;
             }
         }
-        return StringLocation::NonGlobal;
     }
-
-    StringLocation::Unknown
+    StringLocation::NonGlobal
 }
 
 /// Generate the CWE warning for a detected instance of the CWE.
 fn generate_cwe_warning(
     callsite: &Tid,
-    called_symbol: &ExternSymbol,
+    called_name: &str,
     location: &StringLocation,
 ) -> CweWarning {
     let description = match location {
         StringLocation::GlobalWriteable => {
             format!(
             "(Externally Controlled Format String) Potential externally controlled format string in global memory for call to {} at {}",
-            called_symbol.name, callsite.address
+            called_name, callsite.canonical_address()
         )
         }
         StringLocation::NonGlobal => {
             format!(
             "(Externally Controlled Format String) Potential externally controlled format string for call to {} at {}",
-            called_symbol.name, callsite.address
+            called_name, callsite.canonical_address()
         )
         }
         _ => panic!("Invalid String Location."),
     };
     CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
         .tids(vec![format!("{}", callsite)])
-        .addresses(vec![callsite.address.clone()])
-        .symbols(vec![called_symbol.name.clone()])
+        .addresses(vec![callsite.canonical_address().to_string()])
+        .symbols(vec![called_name.to_string()])
 }
 
 #[cfg(test)]
 pub mod tests {
     use std::collections::HashSet;
 
+    use petgraph::graph::NodeIndex;
+
     use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
     use crate::intermediate_representation::*;
 
@@ -233,15 +259,179 @@ pub mod tests {
             .unwrap()
             .clone();
 
-        assert_eq!(
-            locate_format_string(
-                &node,
-                &sprintf_symbol,
-                &format_string_index,
-                &pi_results,
+        let pi_state = match pi_results.get_node_value(node) {
+            Some(NodeValue::Value(state)) => state,
+            _ => panic!("No pointer inference state at the callsite."),
+        };
+        let format_string = pi_state
+            .eval_parameter_arg(
+                &sprintf_symbol.parameters[format_string_index["sprintf"]],
                 &runtime_memory_image,
-            ),
+            )
+            .ok();
+
+        assert_eq!(
+            locate_format_string(format_string.as_ref(), &runtime_memory_image),
             StringLocation::GlobalReadable
         );
     }
+
+    /// A project with a two-level logging wrapper:
+    /// - `log_v(level, format, args)` calls `vsnprintf(buffer, 0x100, format, args)`.
+    /// - The variadic `log_error(format, ...)` saves its unnamed parameter registers for `va_start`
+    ///   and calls `log_v(3, format, args)`.
+    /// - `main` calls `log_error` once with a constant format string and once with a format string on the stack.
+    fn mock_logging_wrapper_project() -> Project {
+        let mut project = Project::mock_empty();
+        let parameter_registers: Vec<Variable> = ["RDI", "RSI", "RDX", "RCX", "R8", "R9"]
+            .iter()
+            .map(|name| Variable::mock(name, 8))
+            .collect();
+        let cconv = CallingConvention::mock_with_parameter_registers(parameter_registers, vec![]);
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("vsnprintf").parameters = ["RDI", "RSI", "RDX", "RCX"]
+            .iter()
+            .map(|name| Arg::mock_register(name, 8))
+            .collect();
+
+        let mut log_v = Sub::mock("log_v");
+        log_v.term.blocks = vec![
+            Blk::mock_call(
+                "log_v_blk",
+                vec![
+                    Def::assign(
+                        "log_v_args",
+                        Variable::mock("RCX", 8),
+                        Expression::var("RDX", 8),
+                    ),
+                    Def::assign(
+                        "log_v_format",
+                        Variable::mock("RDX", 8),
+                        Expression::var("RSI", 8),
+                    ),
+                    Def::assign(
+                        "log_v_buffer",
+                        Variable::mock("RDI", 8),
+                        Expression::var("RSP", 8).plus_const(-0x100),
+                    ),
+                    Def::assign(
+                        "log_v_size",
+                        Variable::mock("RSI", 8),
+                        Expression::const_from_i64(0x100),
+                    ),
+                ],
+                "vsnprintf",
+                "log_v_ret",
+            ),
+            Blk::mock_return("log_v_ret"),
+        ];
+
+        let mut va_start_defs: Vec<Term<Def>> = ["RSI", "RDX", "RCX", "R8", "R9"]
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                Def::store(
+                    &format!("save_{}", name),
+                    Expression::var("RSP", 8).plus_const(-0x30 + 8 * index as i64),
+                    Expression::var(name, 8),
+                )
+            })
+            .collect();
+        va_start_defs.append(&mut vec![
+            Def::assign(
+                "log_error_args",
+                Variable::mock("RDX", 8),
+                Expression::var("RSP", 8).plus_const(-0x40),
+            ),
+            Def::assign(
+                "log_error_format",
+                Variable::mock("RSI", 8),
+                Expression::var("RDI", 8),
+            ),
+            Def::assign(
+                "log_error_level",
+                Variable::mock("RDI", 8),
+                Expression::const_from_i64(3),
+            ),
+        ]);
+        let mut log_error = Sub::mock("log_error");
+        log_error.term.blocks = vec![
+            Blk::mock_call("log_error_blk", va_start_defs, "log_v", "log_error_ret"),
+            Blk::mock_return("log_error_ret"),
+        ];
+
+        let mut main = Sub::mock("main");
+        main.term.blocks = vec![
+            Blk::mock_call(
+                "main_constant_format",
+                vec![Def::assign(
+                    "set_constant_format",
+                    Variable::mock("RDI", 8),
+                    Expression::const_from_i64(0x3002),
+                )],
+                "log_error",
+                "main_stack_format",
+            ),
+            Blk::mock_call(
+                "main_stack_format",
+                vec![Def::assign(
+                    "set_stack_format",
+                    Variable::mock("RDI", 8),
+                    Expression::var("RSP", 8).plus_const(-0x80),
+                )],
+                "log_error",
+                "main_ret",
+            ),
+            Blk::mock_return("main_ret"),
+        ];
+        for sub in [log_v, log_error, main] {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project.program.term.entry_points.insert(Tid::new("main"));
+        project
+    }
+
+    #[test]
+    fn two_level_logging_wrapper() {
+        let project = mock_logging_wrapper_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pi_results =
+            PointerInferenceComputation::mock(&project, &runtime_memory_image, &graph);
+        pi_results.compute();
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pi_results));
+        let config = serde_json::json!({
+            "format_string_symbols": ["vsnprintf"],
+            "format_string_index": { "vsnprintf": 2 },
+        });
+
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].tids, vec!["call_main_stack_format".to_string()]);
+        assert_eq!(warnings[0].symbols, vec!["log_error".to_string()]);
+
+        let symbol_map =
+            crate::utils::symbol_utils::get_symbol_map(&project, &["vsnprintf".to_string()]);
+        let format_string_symbols = symbol_map
+            .into_iter()
+            .map(|(tid, symbol)| (tid, (symbol, 2)))
+            .collect();
+        let wrappers =
+            get_format_string_wrappers(&project, &format_string_symbols, &runtime_memory_image);
+        assert_eq!(wrappers.len(), 2);
+        assert_eq!(wrappers[&Tid::new("log_v")].format_string_index, 1);
+        assert!(!wrappers[&Tid::new("log_v")].is_variadic);
+        assert_eq!(wrappers[&Tid::new("log_error")].format_string_index, 0);
+        assert!(wrappers[&Tid::new("log_error")].is_variadic);
+    }
 }
@@ -0,0 +1,222 @@
+//! This module implements a check for memory mappings that are both writable and executable,
+//! i.e. violations of the W^X (write xor execute) principle.
+//!
+//! Memory that is writable and executable at the same time allows an attacker
+//! who is able to write to it to inject and execute arbitrary code.
+//! Such mappings defeat the protection provided by non-executable data memory.
+//!
+//! ## How the check works
+//!
+//! For each call to a memory mapping function (e.g. `mmap` or `mprotect`)
+//! the memory protection parameter is evaluated using the results of the pointer inference analysis.
+//! Since the protection flags are usually built by OR-ing constants,
+//! we use the bits known to be set in the parameter value
+//! (see [`KnownBits`](crate::abstract_domain::KnownBits)).
+//! If both the `PROT_WRITE` and the `PROT_EXEC` flag are known to be set, a CWE warning is generated.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The memory mapping functions together with the index of their memory protection parameter.
+//! - The values of the `PROT_WRITE` and `PROT_EXEC` flags.
+//!
+//! ## False Positives
+//!
+//! - Some programs (e.g. just-in-time compilers) need writable and executable memory by design.
+//!
+//! ## False Negatives
+//!
+//! - Flags that are only possibly set (e.g. depending on a runtime configuration) are not reported.
+//! - Mappings whose protection is changed in two steps (first writable, then executable) are not detected by this check.
+//!   They are reported by the pointer inference analysis instead
+//!   (see [`memory_mapping`](crate::analysis::pointer_inference::memory_mapping)).
+
+use petgraph::visit::EdgeRef;
+use std::collections::BTreeMap;
+
+use crate::analysis::graph::Edge;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE1386",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// Maps the names of memory mapping functions to the index of their memory protection parameter.
+    protection_parameter_index: BTreeMap<String, usize>,
+    /// The value of the `PROT_WRITE` flag.
+    prot_write: u64,
+    /// The value of the `PROT_EXEC` flag.
+    prot_exec: u64,
+}
+
+/// Check all calls to memory mapping functions for memory protection parameters
+/// where both the `PROT_WRITE` and the `PROT_EXEC` flag are known to be set.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let symbol_names: Vec<String> = config.protection_parameter_index.keys().cloned().collect();
+    let symbol_map = get_symbol_map(project, &symbol_names);
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    for edge in pointer_inference_results.get_graph().edge_references() {
+        if let Edge::ExternCallStub(jmp) = edge.weight() {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                let symbol = match symbol_map.get(target) {
+                    Some(symbol) => symbol,
+                    None => continue,
+                };
+                let protection_parameter = match symbol
+                    .parameters
+                    .get(config.protection_parameter_index[&symbol.name])
+                {
+                    Some(parameter) => parameter,
+                    None => continue,
+                };
+                if let Some(NodeValue::Value(pi_state)) =
+                    pointer_inference_results.get_node_value(edge.source())
+                {
+                    if let Ok(protection) = pi_state.eval_parameter_arg(
+                        protection_parameter,
+                        analysis_results.runtime_memory_image,
+                    ) {
+                        let set_bits = protection.get_known_bits().ones.try_to_u64().unwrap_or(0);
+                        let write_and_exec = config.prot_write | config.prot_exec;
+                        if set_bits & write_and_exec == write_and_exec {
+                            cwe_warnings.push(generate_cwe_warning(&jmp.tid, symbol));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    cwe_warnings.sort();
+    (Vec::new(), cwe_warnings)
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(callsite: &Tid, called_symbol: &ExternSymbol) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Writable and Executable Memory) Call to {} at {} maps memory as both writable and executable",
+            called_symbol.name, callsite.canonical_address()
+        ),
+    )
+    .tids(vec![format!("{}", callsite)])
+    .addresses(vec![callsite.canonical_address().to_string()])
+    .symbols(vec![called_symbol.name.clone()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project whose `main` function calls `mmap` with the memory protection flags
+    /// set to `first_flags` or `second_flags` depending on a condition and then OR-ed with `added_flags`.
+    fn mock_project(first_flags: i64, second_flags: i64, added_flags: i64) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![
+                Variable::mock("RDI", 8),
+                Variable::mock("RSI", 8),
+                Variable::mock("RDX", 8),
+            ],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("mmap").parameters = vec![
+            Arg::mock_register("RDI", 8),
+            Arg::mock_register("RSI", 8),
+            Arg::mock_register("RDX", 8),
+        ];
+
+        let mut start_blk = Blk::mock_with_tid("start_blk");
+        start_blk.term.jmps = vec![
+            Term {
+                tid: Tid::new("cond_jump"),
+                term: Jmp::CBranch {
+                    target: Tid::new("second_blk"),
+                    condition: Expression::var("ZF", 1),
+                },
+            },
+            Jmp::branch("to_first_blk", "first_blk"),
+        ];
+        let mut flag_blocks = Vec::new();
+        for (name, flags) in [("first", first_flags), ("second", second_flags)] {
+            let mut block = Blk::mock_with_tid(&format!("{}_blk", name));
+            block.term.defs = vec![Def::assign(
+                &format!("set_{}_flags", name),
+                Variable::mock("RDX", 8),
+                Expression::const_from_i64(flags),
+            )];
+            block.term.jmps = vec![Jmp::branch(&format!("{}_to_call_blk", name), "call_blk")];
+            flag_blocks.push(block);
+        }
+        let mut call_blk = Blk::mock_with_tid("call_blk");
+        call_blk.term.defs = vec![Def::assign(
+            "add_flags",
+            Variable::mock("RDX", 8),
+            Expression::BinOp {
+                op: BinOpType::IntOr,
+                lhs: Box::new(Expression::var("RDX", 8)),
+                rhs: Box::new(Expression::const_from_i64(added_flags)),
+            },
+        )];
+        call_blk.term.jmps = vec![Jmp::call("call_mmap", "mmap", Some("end_blk"))];
+        let end_blk = Blk::mock_with_tid("end_blk");
+        let sub = Sub::mock_with_blocks(
+            "main",
+            [vec![start_blk], flag_blocks, vec![call_blk, end_blk]].concat(),
+        );
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("main"));
+        project
+    }
+
+    /// Run the check on a project calling `mmap` with the protection flags
+    /// `(first_flags or second_flags) | added_flags`.
+    fn run_check(first_flags: i64, second_flags: i64, added_flags: i64) -> Vec<CweWarning> {
+        let project = mock_project(first_flags, second_flags, added_flags);
+        let config = serde_json::json!({
+            "protection_parameter_index": { "mmap": 2 },
+            "prot_write": 2,
+            "prot_exec": 4,
+        });
+        AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn writable_and_executable_mapping() {
+        // PROT_WRITE or PROT_READ | PROT_WRITE | PROT_EXEC, then OR-ed with PROT_EXEC.
+        let warnings = run_check(2, 7, 4);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].tids, vec!["call_mmap".to_string()]);
+        // PROT_READ or PROT_READ | PROT_WRITE, then OR-ed with PROT_EXEC.
+        assert!(run_check(1, 3, 4).is_empty());
+        // PROT_READ or PROT_WRITE, then OR-ed with PROT_READ.
+        assert!(run_check(1, 2, 1).is_empty());
+    }
+}
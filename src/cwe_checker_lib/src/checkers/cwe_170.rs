@@ -0,0 +1,598 @@
+//! This module implements a check for CWE-170: Improper Null Termination.
+//!
+//! Bounded copy functions like `strncpy` do not write a terminating null byte
+//! if the source string fills the whole destination buffer.
+//! If the destination is later used by a function that assumes a null-terminated string
+//! (e.g. `strlen` or `printf` with a `%s` format specifier),
+//! that function reads past the end of the buffer.
+//!
+//! See <https://cwe.mitre.org/data/definitions/170.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the pointer inference analysis we determine for each call to a bounded copy function
+//! (e.g. `strncpy` or `memcpy`) the capacity of the destination buffer from the start of the destination pointer
+//! to the end of the targeted memory object.
+//! The destination is marked as possibly unterminated if the number of copied bytes can equal or exceed this capacity
+//! and the source is not a string constant that is provably shorter than the capacity.
+//!
+//! Starting at the call we then explore the paths through the calling function.
+//! A path is no longer followed once a zero byte is stored at an exactly known position inside the destination buffer,
+//! e.g. by an explicit `dst[size - 1] = 0`.
+//! A CWE warning is generated if a call to a function assuming a null-terminated string is reached
+//! and one of its string parameters points into the possibly unterminated destination buffer.
+//! For functions with format strings the string parameters are the arguments to `%s` format specifiers.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The bounded copy functions together with the indices of their destination, source and size parameters.
+//! - The functions assuming null-terminated strings together with the indices of their string parameters.
+//! - The functions with format strings together with the index of their format string parameter.
+//!
+//! ## False Positives
+//!
+//! - The source string may be shorter than the destination buffer for reasons unknown to the analysis.
+//! - The destination may be terminated by other means than a store instruction,
+//!   e.g. by a called function.
+//!
+//! ## False Negatives
+//!
+//! - Only destination buffers whose size is known to the pointer inference analysis are checked.
+//!   This is usually only the case for heap objects.
+//! - Uses of the destination buffer in other functions than the one containing the copy are not checked.
+//! - A zero byte stored anywhere inside the destination buffer terminates it,
+//!   even if it is stored before the end of the copied string.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec, TryToInterval};
+use crate::analysis::graph::{Edge, Graph, Node};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data, PointerInference, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::arguments::get_variable_parameters;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage, Remediation};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE170",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// Maps the names of bounded copy functions to the indices of their parameters.
+    copy_functions: BTreeMap<String, CopyParameters>,
+    /// Maps the names of functions assuming null-terminated strings
+    /// to the indices of their string parameters.
+    string_sinks: BTreeMap<String, Vec<usize>>,
+    /// Maps the names of functions with format strings to the index of their format string parameter.
+    format_string_symbols: BTreeMap<String, usize>,
+}
+
+/// The parameter indices of a bounded copy function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CopyParameters {
+    /// The index of the parameter pointing to the destination buffer.
+    destination_index: usize,
+    /// The index of the parameter pointing to the source buffer.
+    source_index: usize,
+    /// The index of the parameter containing the maximal number of copied bytes.
+    size_index: usize,
+}
+
+/// A destination buffer of a bounded copy that may not be null-terminated.
+///
+/// The buffer is given by the (inclusive) start offset and the (exclusive) end offset
+/// inside the memory object written by the copy.
+struct UnterminatedBuffer<'a> {
+    copy_call: &'a Term<Jmp>,
+    copy_symbol: &'a ExternSymbol,
+    sub: &'a Term<Sub>,
+    object_id: AbstractIdentifier,
+    start: i64,
+    end: i64,
+}
+
+impl UnterminatedBuffer<'_> {
+    /// Returns `true` if the address may point into the buffer.
+    /// Addresses into the memory object of the buffer with unknown offsets are assumed to point into the buffer.
+    fn may_contain(&self, address: &Data) -> bool {
+        match address.get_relative_values().get(&self.object_id) {
+            Some(offset) => match offset.try_to_offset_interval() {
+                Ok((start, end)) => start < self.end && self.start <= end,
+                Err(_) => true,
+            },
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the address points to an exactly known position inside the buffer.
+    fn contains_exactly(&self, address: &Data) -> bool {
+        match address.get_if_unique_target() {
+            Some((id, offset)) if *id == self.object_id => offset
+                .try_to_offset()
+                .is_ok_and(|offset| self.start <= offset && offset < self.end),
+            _ => false,
+        }
+    }
+}
+
+/// The symbols and pointer inference results needed to search for uses of unterminated buffers.
+struct Context<'a> {
+    project: &'a Project,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    pointer_inference: &'a PointerInference<'a>,
+    graph: &'a Graph<'a>,
+    sink_symbols: HashMap<Tid, &'a ExternSymbol>,
+    format_string_symbols: HashMap<Tid, &'a ExternSymbol>,
+    config: Config,
+    format_string_index_map: HashMap<String, usize>,
+}
+
+/// Search for possibly unterminated destination buffers of bounded copies
+/// that are used by functions assuming null-terminated strings.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let copy_symbols = get_symbol_map(
+        project,
+        &config.copy_functions.keys().cloned().collect::<Vec<_>>(),
+    );
+    if copy_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let pointer_inference = analysis_results.pointer_inference.unwrap();
+    let graph = pointer_inference.get_graph();
+    let context = Context {
+        project,
+        runtime_memory_image: analysis_results.runtime_memory_image,
+        pointer_inference,
+        graph,
+        sink_symbols: get_symbol_map(
+            project,
+            &config.string_sinks.keys().cloned().collect::<Vec<_>>(),
+        ),
+        format_string_symbols: get_symbol_map(
+            project,
+            &config
+                .format_string_symbols
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>(),
+        ),
+        format_string_index_map: config
+            .format_string_symbols
+            .iter()
+            .map(|(name, index)| (name.clone(), *index))
+            .collect(),
+        config,
+    };
+
+    let mut cwe_warnings = BTreeMap::new();
+    for edge in graph.edge_references() {
+        let call = match edge.weight() {
+            Edge::ExternCallStub(call) => call,
+            _ => continue,
+        };
+        let copy_symbol = match &call.term {
+            Jmp::Call { target, .. } => match copy_symbols.get(target) {
+                Some(symbol) => *symbol,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let sub = match graph[edge.source()] {
+            Node::BlkEnd(_, sub) => sub,
+            _ => continue,
+        };
+        let pi_state = match pointer_inference.get_node_value(edge.source()) {
+            Some(NodeValue::Value(pi_state)) => pi_state,
+            _ => continue,
+        };
+        if let Some(buffer) = context.get_unterminated_buffer(call, copy_symbol, sub, pi_state) {
+            for warning in context.find_uses_of_buffer(&buffer, edge.target()) {
+                cwe_warnings.insert(warning.tids.clone(), warning);
+            }
+        }
+    }
+    (Vec::new(), cwe_warnings.into_values().collect())
+}
+
+impl<'a> Context<'a> {
+    /// Get the destination buffer of the bounded copy if the copy may leave it unterminated.
+    fn get_unterminated_buffer(
+        &self,
+        call: &'a Term<Jmp>,
+        copy_symbol: &'a ExternSymbol,
+        sub: &'a Term<Sub>,
+        pi_state: &PointerInferenceState,
+    ) -> Option<UnterminatedBuffer<'a>> {
+        let parameters = &self.config.copy_functions[&copy_symbol.name];
+        let eval_parameter = |index: usize| {
+            pi_state
+                .eval_parameter_arg(
+                    copy_symbol.parameters.get(index)?,
+                    self.runtime_memory_image,
+                )
+                .ok()
+        };
+        let destination = eval_parameter(parameters.destination_index)?;
+        let (object_id, offset) = destination.get_if_unique_target()?;
+        let start = offset.try_to_offset().ok()?;
+        let object_size = match pi_state.memory.get_allocation_size(object_id) {
+            Some(size) => size.try_to_offset().ok()?,
+            None => i64::try_from(pi_state.memory.get_object_size(object_id)?).ok()?,
+        };
+        let capacity = object_size - start;
+        if start < 0 || capacity <= 0 {
+            return None;
+        }
+        let (_, max_copied_bytes) = eval_parameter(parameters.size_index)?
+            .get_if_absolute_value()?
+            .try_to_offset_interval()
+            .ok()?;
+        if max_copied_bytes < capacity {
+            return None;
+        }
+        if let Some(source_length) = eval_parameter(parameters.source_index)
+            .and_then(|source| self.get_constant_string_length(&source))
+        {
+            if (source_length as i64) < capacity {
+                return None;
+            }
+        }
+        Some(UnterminatedBuffer {
+            copy_call: call,
+            copy_symbol,
+            sub,
+            object_id: object_id.clone(),
+            start,
+            end: start + capacity,
+        })
+    }
+
+    /// Get the length of the string constant that the address points to, if it is exactly known.
+    fn get_constant_string_length(&self, address: &Data) -> Option<usize> {
+        let address = address.get_if_absolute_value()?.try_to_bitvec().ok()?;
+        self.runtime_memory_image
+            .read_string_until_null_terminator(&address)
+            .ok()
+            .map(|string| string.len())
+    }
+
+    /// Explore the paths through the function of the buffer starting at the given node
+    /// and generate CWE warnings for all reachable calls using the buffer as a null-terminated string.
+    /// Paths on which the buffer gets terminated are not followed.
+    fn find_uses_of_buffer(
+        &self,
+        buffer: &UnterminatedBuffer,
+        start_node: NodeIndex,
+    ) -> Vec<CweWarning> {
+        let mut warnings = Vec::new();
+        let mut visited = HashSet::new();
+        let mut worklist = vec![start_node];
+        while let Some(node) = worklist.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            let pi_state = match self.pointer_inference.get_node_value(node) {
+                Some(NodeValue::Value(pi_state)) => pi_state,
+                _ => continue,
+            };
+            match self.graph[node] {
+                Node::BlkStart(block, _) if self.terminates_buffer(buffer, block, pi_state) => {
+                    continue
+                }
+                Node::BlkEnd(..) => {
+                    for edge in self.graph.edges(node) {
+                        if let Edge::ExternCallStub(call) = edge.weight() {
+                            if let Some(sink_symbol) =
+                                self.get_sink_using_buffer(buffer, call, pi_state)
+                            {
+                                warnings.push(generate_cwe_warning(buffer, call, sink_symbol));
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+            for edge in self.graph.edges(node) {
+                match edge.weight() {
+                    Edge::Block
+                    | Edge::Jump(..)
+                    | Edge::ExternCallStub(_)
+                    | Edge::CrCallStub
+                    | Edge::ReturnCombine(_) => worklist.push(edge.target()),
+                    Edge::Call(_) | Edge::CrReturnStub | Edge::CallCombine(_) => (),
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Returns `true` if the block stores a zero byte at an exactly known position inside the buffer.
+    fn terminates_buffer(
+        &self,
+        buffer: &UnterminatedBuffer,
+        block: &Term<Blk>,
+        pi_state: &PointerInferenceState,
+    ) -> bool {
+        let mut pi_state = pi_state.clone();
+        for def in block.term.defs.iter() {
+            if let Def::Store { address, value } = &def.term {
+                let is_zero = pi_state
+                    .eval(value)
+                    .get_if_absolute_value()
+                    .and_then(|value| value.try_to_bitvec().ok())
+                    .is_some_and(|value| value.is_zero());
+                if is_zero && buffer.contains_exactly(&pi_state.eval(address)) {
+                    return true;
+                }
+            }
+            pi_state.apply_defs(std::slice::from_ref(def), self.runtime_memory_image);
+        }
+        false
+    }
+
+    /// If the call is a call to a function assuming null-terminated strings
+    /// and one of its string parameters may point into the buffer, return the called symbol.
+    fn get_sink_using_buffer(
+        &self,
+        buffer: &UnterminatedBuffer,
+        call: &Term<Jmp>,
+        pi_state: &PointerInferenceState,
+    ) -> Option<&'a ExternSymbol> {
+        let target = match &call.term {
+            Jmp::Call { target, .. } => target,
+            _ => return None,
+        };
+        let string_parameters: Vec<Arg> = if let Some(symbol) = self.sink_symbols.get(target) {
+            self.config.string_sinks[&symbol.name]
+                .iter()
+                .filter_map(|index| symbol.parameters.get(*index).cloned())
+                .collect()
+        } else if let Some(symbol) = self.format_string_symbols.get(target) {
+            get_variable_parameters(
+                self.project,
+                pi_state,
+                symbol,
+                &self.format_string_index_map,
+                self.runtime_memory_image,
+            )
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|parameter| parameter.get_data_type() == Some(Datatype::Pointer))
+            .collect()
+        } else {
+            return None;
+        };
+        let uses_buffer = string_parameters.iter().any(|parameter| {
+            pi_state
+                .eval_parameter_arg(parameter, self.runtime_memory_image)
+                .is_ok_and(|value| buffer.may_contain(&value))
+        });
+        if uses_buffer {
+            self.sink_symbols
+                .get(target)
+                .or_else(|| self.format_string_symbols.get(target))
+                .copied()
+        } else {
+            None
+        }
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    buffer: &UnterminatedBuffer,
+    sink_call: &Term<Jmp>,
+    sink_symbol: &ExternSymbol,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Null Termination) The buffer written by {} at {} in function {} may not be null-terminated when it is used as a string by {} at {}",
+            buffer.copy_symbol.name,
+            buffer.copy_call.tid.canonical_address(),
+            buffer.sub.term.name,
+            sink_symbol.name,
+            sink_call.tid.canonical_address(),
+        ),
+    )
+    .tids(vec![
+        format!("{}", buffer.copy_call.tid),
+        format!("{}", sink_call.tid),
+    ])
+    .addresses(vec![
+        buffer.copy_call.tid.canonical_address().to_string(),
+        sink_call.tid.canonical_address().to_string(),
+    ])
+    .symbols(vec![
+        buffer.copy_symbol.name.clone(),
+        sink_symbol.name.clone(),
+    ])
+    .remediation(Some(Remediation::FreeText {
+        text: format!(
+            "terminate the destination buffer explicitly after the call to {}, e.g. with dst[size - 1] = 0",
+            buffer.copy_symbol.name
+        ),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a function that allocates a buffer of 16 bytes,
+    /// copies at most 16 bytes from `source` to it with `strncpy`
+    /// and then executes the `Def`s in `after_copy` before passing the buffer to `strlen` and `printf`.
+    fn mock_sub(source: Expression, after_copy: Vec<Term<Def>>) -> Term<Sub> {
+        Sub::mock_with_blocks(
+            "copy_string",
+            vec![
+                Blk::mock_with_jmp(
+                    "blk_alloc",
+                    vec![Def::assign(
+                        "set_alloc_size",
+                        Variable::mock("RDI", 8),
+                        Expression::const_from_i64(16),
+                    )],
+                    Jmp::call("call_malloc", "malloc", Some("blk_copy")),
+                ),
+                Blk::mock_with_jmp(
+                    "blk_copy",
+                    vec![
+                        Def::assign(
+                            "save_buffer",
+                            Variable::mock("RBX", 8),
+                            Expression::var("RAX", 8),
+                        ),
+                        Def::assign(
+                            "set_destination",
+                            Variable::mock("RDI", 8),
+                            Expression::var("RAX", 8),
+                        ),
+                        Def::assign("set_source", Variable::mock("RSI", 8), source),
+                        Def::assign(
+                            "set_copy_size",
+                            Variable::mock("RDX", 8),
+                            Expression::const_from_i64(16),
+                        ),
+                    ],
+                    Jmp::call("call_strncpy", "strncpy", Some("blk_after_copy")),
+                ),
+                Blk::mock_with_jmp(
+                    "blk_after_copy",
+                    after_copy,
+                    Jmp::branch("jump_to_use", "blk_strlen"),
+                ),
+                Blk::mock_with_jmp(
+                    "blk_strlen",
+                    vec![Def::assign(
+                        "set_strlen_param",
+                        Variable::mock("RDI", 8),
+                        Expression::var("RBX", 8),
+                    )],
+                    Jmp::call("call_strlen", "strlen", Some("blk_printf")),
+                ),
+                Blk::mock_with_jmp(
+                    "blk_printf",
+                    vec![
+                        // The format string "cat %s"
+                        Def::assign(
+                            "set_format",
+                            Variable::mock("RDI", 8),
+                            Expression::const_from_i64(0x500c),
+                        ),
+                        Def::assign(
+                            "set_format_param",
+                            Variable::mock("RSI", 8),
+                            Expression::var("RBX", 8),
+                        ),
+                    ],
+                    Jmp::call("call_printf", "printf", Some("blk_return")),
+                ),
+                Blk::mock_with_jmp(
+                    "blk_return",
+                    vec![],
+                    Term {
+                        tid: Tid::new("return"),
+                        term: Jmp::Return(Expression::var("RBX", 8)),
+                    },
+                ),
+            ],
+        )
+    }
+
+    /// Run the check on a project containing the given function.
+    fn run_check(sub: Term<Sub>) -> Vec<CweWarning> {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock_with_parameter_registers(
+            vec![
+                Variable::mock("RDI", 8),
+                Variable::mock("RSI", 8),
+                Variable::mock("RDX", 8),
+            ],
+            vec![],
+        );
+        cconv.callee_saved_register = vec![Variable::mock("RBX", 8)];
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for (name, num_parameters) in [("malloc", 1), ("strncpy", 3), ("strlen", 1), ("printf", 1)]
+        {
+            project.add_mock_extern_symbol(name).parameters = ["RDI", "RSI", "RDX"]
+                [..num_parameters]
+                .iter()
+                .map(|name| Arg::mock_register(name, 8))
+                .collect();
+        }
+        project.program.term.entry_points.insert(sub.tid.clone());
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+
+        let config = serde_json::json!({
+            "copy_functions": {
+                "strncpy": { "destination_index": 0, "source_index": 1, "size_index": 2 },
+            },
+            "string_sinks": {
+                "strlen": [0],
+            },
+            "format_string_symbols": {
+                "printf": 0,
+            },
+        });
+        AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn exact_fill_then_strlen() {
+        let warnings = run_check(mock_sub(Expression::var("R12", 8), vec![]));
+        let tids: Vec<_> = warnings.iter().map(|warning| &warning.tids).collect();
+        assert_eq!(
+            tids,
+            vec![
+                &vec!["call_strncpy".to_string(), "call_printf".to_string()],
+                &vec!["call_strncpy".to_string(), "call_strlen".to_string()],
+            ]
+        );
+        assert_eq!(
+            warnings[1].symbols,
+            vec!["strncpy".to_string(), "strlen".to_string()]
+        );
+    }
+
+    #[test]
+    fn explicitly_terminated() {
+        let terminate = Def::store(
+            "terminate",
+            Expression::var("RBX", 8).plus_const(15),
+            Expression::Const(Bitvector::from_u8(0)),
+        );
+        assert!(run_check(mock_sub(Expression::var("R12", 8), vec![terminate])).is_empty());
+    }
+
+    #[test]
+    fn shorter_source() {
+        // The string "Hello World" is shorter than the destination buffer.
+        let source = Expression::const_from_i64(0x3002);
+        assert!(run_check(mock_sub(source, vec![])).is_empty());
+    }
+}
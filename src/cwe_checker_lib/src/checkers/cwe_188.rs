@@ -0,0 +1,320 @@
+//! This module implements a check for CWE-188: Reliance on Data/Memory Layout,
+//! restricted to misaligned memory accesses on strict-alignment architectures.
+//!
+//! On architectures like ARM (before ARMv6) or MIPS, loading or storing a multi-byte value
+//! from an address that is not a multiple of the access size either raises a fault
+//! or silently accesses different bytes than intended.
+//! Such accesses usually originate from assumptions about the memory layout of data,
+//! e.g. from casting a pointer into a byte buffer to a pointer to a larger type.
+//!
+//! See <https://cwe.mitre.org/data/definitions/188.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! The check only runs on architectures that require strict alignment
+//! (see [`Arch::requires_strict_alignment`]).
+//! For each load or store instruction with an access size larger than one byte
+//! the target address is evaluated using the results of the pointer inference analysis.
+//! Using the bits of the address offset known from its stride
+//! (see [`KnownBits`](crate::abstract_domain::KnownBits))
+//! we check whether all possible target addresses violate the natural alignment of the access.
+//! For pointers relative to the current stack frame we assume that the stack frame is aligned
+//! according to the ABI of the architecture, and for heap objects that they are aligned at least as strict.
+//! Pointers to other memory objects (e.g. to parameter objects) have an unknown alignment and are not checked.
+//!
+//! Accesses to packed structs generate many findings in the same function.
+//! Therefore all findings in a function are suppressed if their number exceeds a configurable bound.
+//!
+//! The check is disabled on standard runs and has to be enabled explicitly with the `--partial` command line option.
+//!
+//! ### Parameters configurable in config.json
+//!
+//! - The maximal number of findings per function.
+//!
+//! ## False Positives
+//!
+//! - Some ARM cores (ARMv6 and later) support unaligned accesses for single-word loads and stores.
+//! - Stack frames of functions that are not called according to the ABI may be aligned differently.
+//!
+//! ## False Negatives
+//!
+//! - Accesses that are only misaligned for some possible target addresses are not reported.
+//! - Accesses through pointers with unknown alignment are not checked.
+//! - Functions with more findings than the configured bound are not reported.
+
+use crate::abstract_domain::{AbstractIdentifier, IntervalDomain};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::object::ObjectType;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+use petgraph::visit::IntoNodeReferences;
+use std::collections::BTreeMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE188",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// If a function contains more misaligned accesses than this bound,
+    /// the findings in the function are suppressed, since they most likely stem from accesses to packed structs.
+    max_findings_per_function: usize,
+}
+
+/// Return the natural alignment of a memory access of the given size,
+/// i.e. the access size itself for power-of-two sizes of at most 8 bytes.
+/// Returns `None` for accesses without alignment requirement.
+fn get_natural_alignment(size: ByteSize) -> Option<u64> {
+    let size = u64::from(size);
+    if size > 1 && size <= 8 && size.is_power_of_two() {
+        Some(size)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if all values represented by `offset` are provably not a multiple of `alignment`.
+///
+/// The `alignment` has to be a power of two.
+fn is_provably_misaligned(offset: &IntervalDomain, alignment: u64) -> bool {
+    let known_bits = offset.get_known_bits();
+    let (ones, zeros) = match (
+        known_bits.ones.try_to_u64().ok(),
+        known_bits.zeros.try_to_u64().ok(),
+    ) {
+        (Some(ones), Some(zeros)) => (ones, zeros),
+        _ => return false,
+    };
+    let mask = alignment - 1;
+    (ones | zeros) & mask == mask && ones & mask != 0
+}
+
+/// Return the assumed alignment of the base address of the memory object with the given ID.
+///
+/// Stack frames are assumed to be aligned according to the ABI of the architecture
+/// and heap objects to be aligned at least as strict.
+/// Returns `None` if the alignment of the object is unknown.
+fn get_base_alignment(project: &Project, state: &State, id: &AbstractIdentifier) -> Option<u64> {
+    let stack_alignment = u64::from(project.architecture.stack_alignment()?);
+    if *id == state.stack_id {
+        return Some(stack_alignment);
+    }
+    match state.memory.get_object_type(id) {
+        Ok(Some(ObjectType::Heap)) => Some(stack_alignment),
+        _ => None,
+    }
+}
+
+/// Returns `true` if all possible values of the given address violate the given alignment.
+fn is_misaligned_address(project: &Project, state: &State, address: &Data, alignment: u64) -> bool {
+    if address.contains_top() || address.is_empty() {
+        return false;
+    }
+    let relative_values_misaligned = address.get_relative_values().iter().all(|(id, offset)| {
+        match get_base_alignment(project, state, id) {
+            Some(base_alignment) => {
+                is_provably_misaligned(offset, std::cmp::min(alignment, base_alignment))
+            }
+            None => false,
+        }
+    });
+    let absolute_value_misaligned = match address.get_absolute_value() {
+        Some(value) => is_provably_misaligned(value, alignment),
+        None => true,
+    };
+    relative_values_misaligned && absolute_value_misaligned
+}
+
+/// Generate the CWE warning for a misaligned memory access.
+fn generate_cwe_warning(def: &Term<Def>, size: ByteSize) -> CweWarning {
+    let access_kind = match &def.term {
+        Def::Store { .. } => "store to",
+        _ => "load from",
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Misaligned Memory Access) {}-byte {} misaligned address at {}",
+            size,
+            access_kind,
+            def.tid.canonical_address()
+        ),
+    )
+    .tids(vec![format!("{}", def.tid)])
+    .addresses(vec![def.tid.canonical_address().to_string()])
+}
+
+/// Check the given basic block for misaligned loads and stores.
+///
+/// The `state` is the pointer inference state at the start of the block.
+fn check_block(
+    project: &Project,
+    global_memory: &RuntimeMemoryImage,
+    mut state: State,
+    block: &Term<Blk>,
+) -> Vec<CweWarning> {
+    let mut cwe_warnings = Vec::new();
+    for def in block.term.defs.iter() {
+        let access = match &def.term {
+            Def::Load { var, address } => Some((address, var.size)),
+            Def::Store { address, value } => Some((address, value.bytesize())),
+            Def::Assign { .. } => None,
+        };
+        if let Some((address, size)) = access {
+            if let Some(alignment) = get_natural_alignment(size) {
+                let address_value = state.eval(address);
+                if is_misaligned_address(project, &state, &address_value, alignment) {
+                    cwe_warnings.push(generate_cwe_warning(def, size));
+                }
+            }
+        }
+        match &def.term {
+            Def::Assign { var, value } => state.handle_register_assign(var, value),
+            Def::Load { var, address } => {
+                let _ = state.handle_load(var, address, global_memory);
+            }
+            Def::Store { address, value } => {
+                let _ = state.handle_store(address, value, global_memory);
+            }
+        }
+    }
+    cwe_warnings
+}
+
+/// Run the CWE check.
+///
+/// On strict-alignment architectures we check all loads and stores for provably misaligned target addresses.
+/// The findings of functions with more findings than the configured bound are suppressed.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    if !project.architecture.requires_strict_alignment() {
+        return (Vec::new(), Vec::new());
+    }
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let mut warnings_per_sub: BTreeMap<&Tid, Vec<CweWarning>> = BTreeMap::new();
+
+    for (node_id, node) in pointer_inference_results.get_graph().node_references() {
+        if let Node::BlkStart(block, sub) = node {
+            if let Some(NodeValue::Value(state)) = pointer_inference_results.get_node_value(node_id)
+            {
+                warnings_per_sub
+                    .entry(&sub.tid)
+                    .or_default()
+                    .append(&mut check_block(
+                        project,
+                        analysis_results.runtime_memory_image,
+                        state.clone(),
+                        block,
+                    ));
+            }
+        }
+    }
+
+    suppress_noisy_functions(warnings_per_sub, config.max_findings_per_function)
+}
+
+/// Remove the warnings of functions with more than `max_findings_per_function` warnings
+/// and generate a log message for each such function.
+fn suppress_noisy_functions(
+    warnings_per_sub: BTreeMap<&Tid, Vec<CweWarning>>,
+    max_findings_per_function: usize,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let mut log_messages = Vec::new();
+    let mut cwe_warnings = Vec::new();
+    for (sub_tid, mut warnings) in warnings_per_sub {
+        warnings.sort();
+        warnings.dedup();
+        if warnings.len() > max_findings_per_function {
+            log_messages.push(
+                LogMessage::new_info(format!(
+                    "Suppressed {} misaligned memory accesses in function {}, probably caused by accesses to packed structs.",
+                    warnings.len(),
+                    sub_tid
+                ))
+                .location(sub_tid.clone())
+                .source(CWE_MODULE.name),
+            );
+        } else {
+            cwe_warnings.append(&mut warnings);
+        }
+    }
+    cwe_warnings.sort();
+    (log_messages, cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_arm_project() -> Project {
+        let mut project = Project::mock_empty();
+        project.cpu_architecture = "ARM_32".to_string();
+        project.architecture = Arch::Arm32;
+        project
+    }
+
+    /// `RAX = *(RSP + offset)` with an access size of `size` bytes.
+    fn load_from_stack(tid: &str, offset: i64, size: u64) -> Term<Def> {
+        Def::load(
+            tid,
+            Variable::mock("RAX", size),
+            Expression::var("RSP", 8).plus_const(offset),
+        )
+    }
+
+    #[test]
+    fn odd_offset_load() {
+        let project = mock_arm_project();
+        let global_memory = RuntimeMemoryImage::mock();
+        let state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let mut block = Blk::mock_with_tid("block");
+        block.term.defs = vec![
+            load_from_stack("odd_offset_load", -7, 4),
+            load_from_stack("aligned_load", -8, 4),
+            load_from_stack("byte_load", -7, 1),
+            load_from_stack("half_aligned_load", -6, 4),
+        ];
+        let warnings = check_block(&project, &global_memory, state.clone(), &block);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].tids, vec!["odd_offset_load".to_string()]);
+        assert_eq!(warnings[1].tids, vec!["half_aligned_load".to_string()]);
+
+        // Loads through pointers with unknown alignment are not checked.
+        block.term.defs = vec![Def::load(
+            "load",
+            Variable::mock("RAX", 4),
+            Expression::var("RDI", 8).plus_const(1),
+        )];
+        assert!(check_block(&project, &global_memory, state, &block).is_empty());
+    }
+
+    #[test]
+    fn noisy_functions_are_suppressed() {
+        let sub_tid = Tid::new("packed_struct_access");
+        let other_sub_tid = Tid::new("other");
+        let warning = |tid: &str| CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, tid);
+        let mut warnings_per_sub = BTreeMap::new();
+        warnings_per_sub.insert(&sub_tid, vec![warning("a"), warning("b"), warning("c")]);
+        warnings_per_sub.insert(&other_sub_tid, vec![warning("d")]);
+        let (logs, warnings) = suppress_noisy_functions(warnings_per_sub, 2);
+        assert_eq!(warnings, vec![warning("d")]);
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].location, Some(sub_tid.clone()));
+    }
+}
@@ -7,38 +7,59 @@
 //!
 //! ## How the check works
 //!
-//! For each call to a function from the CWE190 symbol list we check whether the
-//! basic block directly before the call contains a multiplication instruction.
-//! If one is found, the call gets flagged as a CWE hit, as there is no overflow
-//! check corresponding to the multiplication before the call. The default CWE190
-//! symbol list contains the memory allocation functions *malloc*, *xmalloc*,
+//! For each call to a function from the CWE190 symbol list we walk the definitions of the basic block
+//! containing the call backwards, starting from the register parameters of the call,
+//! to find multiplication instructions (`IntMult` or `IntLeft`) whose results flow into a parameter.
+//! For `calloc` the size of the allocation is the product of its two parameters,
+//! so that multiplications flowing into either of them are considered.
+//! The default CWE190 symbol list contains the memory allocation functions *malloc*, *xmalloc*,
 //! *calloc* and *realloc*. The list is configurable in config.json.
 //!
+//! Using the results of the pointer inference analysis
+//! the value intervals of the factors of each multiplication are computed.
+//! Multiplications whose result provably fits into the size of the result register are ignored.
+//! A short backward slice for the parameter that a multiplication flows into
+//! (see [`backward_slicing`](crate::analysis::backward_slicing)) is attached to the finding as evidence.
+//!
+//! Finally, we search for a conditional jump in the same function that compares one of the values
+//! that the factors of the multiplication are computed from,
+//! such that the multiplication is only reachable from one of its branches.
+//! If such a guarding comparison is found, the finding has a low severity, otherwise a high severity.
+//! The severity and the address of the guarding comparison are given in the `other` field of the warning.
+//!
 //! ## False Positives
 //!
-//! - There is no check whether the result of the multiplication is actually used
-//!   as input to the function call. However, this does not seem to generate a lot
-//!   of false positives in practice.
-//! - There is no value set analysis in place to determine whether an overflow is
-//!   possible or not at the specific instruction.
+//! - The guarding comparison may not correctly bound the factors,
+//!   but findings with a guarding comparison have a low severity.
+//! - Value intervals may be overapproximated, e.g. if the factors are loaded from memory.
 //!
 //! ## False Negatives
 //!
-//! - All integer overflows not in a basic block right before a call to a function
+//! - All integer overflows not in the basic block containing the call to a function
 //! from the CWE190 symbol list.
 //! - All integer overflows caused by addition or subtraction.
+//! - Comparisons are related to the factors by the names of the compared registers only,
+//!   so unrelated comparisons of the same registers may lower the severity of a finding.
 
+use crate::abstract_domain::TryToInterval;
+use crate::analysis::backward_slicing::{slice_with_config, AliasPrecision, SliceConfig};
+use crate::analysis::pointer_inference::{Data, PointerInference, State};
 use crate::intermediate_representation::*;
 use crate::prelude::*;
-use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::graph_utils::{get_block_successors, get_jmp_targets, get_reachable_blocks};
+use crate::utils::log::{CweWarning, LogMessage, WarningContext};
 use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
-use crate::CweModule;
+use crate::{CweModule, SharedAnalysis};
+use std::collections::HashSet;
 
 /// The module name and version
 pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE190",
     version: "0.1",
     run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses,
 };
 
 /// The configuration struct.
@@ -51,55 +72,296 @@ pub struct Config {
 /// Check whether the given expression contains an integer multiplication subexpression,
 /// i.e. an `IntMult` or `IntLeft` (left shift) binary operation.
 fn expression_contains_multiplication(expr: &Expression) -> bool {
+    !get_multiplications(expr).is_empty()
+}
+
+/// Get all integer multiplication subexpressions of the given expression,
+/// i.e. all `IntMult` or `IntLeft` (left shift) binary operations.
+fn get_multiplications(expr: &Expression) -> Vec<&Expression> {
     use Expression::*;
     match expr {
         BinOp {
-            op: BinOpType::IntMult,
-            ..
+            op: BinOpType::IntMult | BinOpType::IntLeft,
+            lhs,
+            rhs,
+        } => {
+            let mut multiplications = vec![expr];
+            multiplications.append(&mut get_multiplications(lhs));
+            multiplications.append(&mut get_multiplications(rhs));
+            multiplications
         }
-        | BinOp {
-            op: BinOpType::IntLeft,
-            ..
-        } => true,
-        Var(_) | Const(_) | Unknown { .. } => false,
+        Var(_) | Const(_) | Unknown { .. } => Vec::new(),
         BinOp { lhs, rhs, .. } => {
-            expression_contains_multiplication(lhs) || expression_contains_multiplication(rhs)
+            let mut multiplications = get_multiplications(lhs);
+            multiplications.append(&mut get_multiplications(rhs));
+            multiplications
         }
-        UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => {
-            expression_contains_multiplication(arg)
+        UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => get_multiplications(arg),
+    }
+}
+
+/// The maximal length of dependency chains in the backward slices attached to findings as evidence.
+const EVIDENCE_SLICE_DEPTH: usize = 5;
+
+/// Walk the given definitions backwards, starting with the given variables at the end of the definitions.
+///
+/// Returns the variables at the start of the definitions that the given variables are computed from
+/// and the assignments on which the given variables depend in program order.
+/// Values loaded from memory are not traced further.
+fn trace_variables_backwards<'a>(
+    defs: &'a [Term<Def>],
+    variables: impl IntoIterator<Item = &'a Variable>,
+) -> (HashSet<&'a Variable>, Vec<&'a Term<Def>>) {
+    let mut tracked: HashSet<&Variable> = variables.into_iter().collect();
+    let mut dependencies = Vec::new();
+    for def in defs.iter().rev() {
+        match &def.term {
+            Def::Assign { var, value } => {
+                if tracked.remove(var) {
+                    tracked.extend(value.input_vars());
+                    dependencies.push(def);
+                }
+            }
+            Def::Load { var, .. } => {
+                tracked.remove(var);
+            }
+            Def::Store { .. } => (),
         }
     }
+    dependencies.reverse();
+    (tracked, dependencies)
 }
 
-/// Check whether the given block contains a multiplication expression.
-/// Expressions computing the address of a `Load` or `Store` instruction are ignored
-/// since the addresses themselves cannot be inputs to the call at the end of the block.
-fn block_contains_multiplication(block: &Term<Blk>) -> bool {
-    block.term.defs.iter().any(|def| match &def.term {
-        Def::Assign { value, .. } | Def::Store { value, .. } => {
-            expression_contains_multiplication(value)
+/// Get all assignments in the given block that contain a multiplication expression
+/// and whose result may flow into a register parameter of the call at the end of the block.
+fn get_multiplication_defs<'a>(
+    block: &'a Term<Blk>,
+    called_symbol: &'a ExternSymbol,
+) -> Vec<&'a Term<Def>> {
+    let parameters = called_symbol
+        .parameters
+        .iter()
+        .filter_map(|parameter| match parameter {
+            Arg::Register {
+                expr: Expression::Var(register),
+                ..
+            } => Some(register),
+            _ => None,
+        });
+    let (_, dependencies) = trace_variables_backwards(&block.term.defs, parameters);
+    dependencies
+        .into_iter()
+        .filter(|def| match &def.term {
+            Def::Assign { value, .. } => expression_contains_multiplication(value),
+            _ => false,
+        })
+        .collect()
+}
+
+/// Get an upper bound for the given value interpreted as an unsigned integer.
+/// Returns `None` if the value may be a pointer or may be negative when interpreted as a signed integer.
+fn get_unsigned_upper_bound(value: &Data) -> Option<u128> {
+    let (lower_bound, upper_bound) = value
+        .get_if_absolute_value()?
+        .try_to_offset_interval()
+        .ok()?;
+    (lower_bound >= 0).then_some(upper_bound as u128)
+}
+
+/// Returns `true` if the given multiplication may wrap around in the given state,
+/// i.e. if the bounds of its factors do not exclude that the product exceeds the size of the result.
+fn multiplication_may_wrap(state: &State, multiplication: &Expression) -> bool {
+    let (op, lhs, rhs) = match multiplication {
+        Expression::BinOp { op, lhs, rhs } => (op, lhs, rhs),
+        _ => return true,
+    };
+    let (lhs_bound, rhs_bound) = match (
+        get_unsigned_upper_bound(&state.eval(lhs)),
+        get_unsigned_upper_bound(&state.eval(rhs)),
+    ) {
+        (Some(lhs_bound), Some(rhs_bound)) => (lhs_bound, rhs_bound),
+        _ => return true,
+    };
+    let result_bits = u64::from(multiplication.bytesize()) * 8;
+    let product = match op {
+        BinOpType::IntMult => lhs_bound.checked_mul(rhs_bound),
+        BinOpType::IntLeft if rhs_bound < result_bits as u128 => {
+            lhs_bound.checked_mul(1u128 << rhs_bound)
         }
-        Def::Load { .. } => false,
-    })
+        _ => None,
+    };
+    match product {
+        Some(product) => product.checked_shr(result_bits as u32).unwrap_or(0) != 0,
+        None => true,
+    }
+}
+
+/// Returns `true` if one of the multiplications in the given definition may wrap around.
+///
+/// If the state before the definition is unknown, the multiplications are assumed to wrap around.
+fn def_may_wrap(pi_results: &PointerInference, def: &Term<Def>) -> bool {
+    let value = match &def.term {
+        Def::Assign { value, .. } => value,
+        _ => return false,
+    };
+    match pi_results.get_state_before_term(&def.tid) {
+        Some(state) => get_multiplications(value)
+            .into_iter()
+            .any(|multiplication| multiplication_may_wrap(&state, multiplication)),
+        None => true,
+    }
+}
+
+/// Returns `true` if the condition compares one of the given variables,
+/// i.e. if it is a (possibly negated) comparison with one of the variables as input.
+fn compares_variable(condition: &Expression, variables: &HashSet<&Variable>) -> bool {
+    use Expression::*;
+    match condition {
+        UnOp {
+            op: UnOpType::BoolNegate,
+            arg,
+        } => compares_variable(arg, variables),
+        BinOp {
+            op:
+                BinOpType::IntLess
+                | BinOpType::IntLessEqual
+                | BinOpType::IntSLess
+                | BinOpType::IntSLessEqual,
+            ..
+        } => condition
+            .input_vars()
+            .into_iter()
+            .any(|var| variables.contains(var)),
+        _ => false,
+    }
+}
+
+/// Find a conditional jump in the function that compares one of the given variables
+/// such that the given block is only reachable from one of its two branches.
+fn find_guarding_comparison<'a>(
+    sub: &'a Term<Sub>,
+    block: &Tid,
+    variables: &HashSet<&Variable>,
+) -> Option<&'a Term<Jmp>> {
+    let successors = get_block_successors(sub);
+    for guard_block in sub.term.blocks.iter() {
+        for (index, jmp) in guard_block.term.jmps.iter().enumerate() {
+            let condition = match &jmp.term {
+                Jmp::CBranch { condition, .. } => condition,
+                _ => continue,
+            };
+            if !compares_variable(condition, variables) {
+                continue;
+            }
+            let true_branch =
+                get_reachable_blocks(&successors, get_jmp_targets(jmp), &guard_block.tid);
+            let false_branch = get_reachable_blocks(
+                &successors,
+                guard_block.term.jmps[index + 1..]
+                    .iter()
+                    .flat_map(get_jmp_targets)
+                    .collect(),
+                &guard_block.tid,
+            );
+            if true_branch.contains(block) != false_branch.contains(block) {
+                return Some(jmp);
+            }
+        }
+    }
+    None
+}
+
+/// Compute the evidence for a finding,
+/// i.e. a backward slice from a register parameter of the call that contains one of the multiplications.
+/// Returns `None` if none of the multiplications is contained in the slice of a register parameter of the call.
+fn get_slice_evidence(
+    project: &Project,
+    pi_results: &PointerInference,
+    callsite: &Tid,
+    called_symbol: &ExternSymbol,
+    multiplication_defs: &[&Tid],
+) -> Option<WarningContext> {
+    let config = SliceConfig {
+        max_depth: EVIDENCE_SLICE_DEPTH,
+        alias_precision: AliasPrecision::Exact,
+    };
+    for parameter in called_symbol.parameters.iter() {
+        let register = match parameter {
+            Arg::Register {
+                expr: Expression::Var(register),
+                ..
+            } => register,
+            _ => continue,
+        };
+        let slice = match slice_with_config(project, pi_results, callsite, register, &config) {
+            Ok(slice) => slice,
+            Err(_) => continue,
+        };
+        if multiplication_defs.iter().any(|tid| slice.contains(tid)) {
+            let slice_terms = slice
+                .get_all_terms()
+                .into_iter()
+                .map(|tid| serde_json::Value::String(tid.to_string()))
+                .collect();
+            return Some(
+                WarningContext::new()
+                    .value(
+                        "parameter",
+                        serde_json::Value::String(register.name.clone()),
+                    )
+                    .value("backward_slice", serde_json::Value::Array(slice_terms)),
+            );
+        }
+    }
+    None
 }
 
 /// Generate the CWE warning for a detected instance of the CWE.
-fn generate_cwe_warning(callsite: &Tid, called_symbol: &ExternSymbol) -> CweWarning {
-    CweWarning::new(
-        CWE_MODULE.name,
-        CWE_MODULE.version,
-        format!(
-            "(Integer Overflow or Wraparound) Potential overflow due to multiplication before call to {} at {}",
-            called_symbol.name, callsite.address
-        ))
-        .tids(vec![format!("{}", callsite)])
-        .addresses(vec![callsite.address.clone()])
+///
+/// The severity of the warning is lowered if a guarding comparison was found.
+fn generate_cwe_warning(
+    callsite: &Tid,
+    called_symbol: &ExternSymbol,
+    guarding_comparison: Option<&Tid>,
+    evidence: Option<WarningContext>,
+) -> CweWarning {
+    let mut description = format!(
+        "(Integer Overflow or Wraparound) Potential overflow due to multiplication before call to {} at {}",
+        called_symbol.name,
+        callsite.canonical_address()
+    );
+    let mut addresses = vec![callsite.canonical_address().to_string()];
+    let mut tids = vec![format!("{}", callsite)];
+    let mut other = Vec::new();
+    match guarding_comparison {
+        Some(guard) => {
+            description += &format!(
+                ". The factors of the multiplication are compared at {}",
+                guard.canonical_address()
+            );
+            addresses.push(guard.canonical_address().to_string());
+            tids.push(format!("{}", guard));
+            other.push(vec!["severity".to_string(), "low".to_string()]);
+            other.push(vec![
+                "guarding_comparison".to_string(),
+                guard.canonical_address().to_string(),
+            ]);
+        }
+        None => other.push(vec!["severity".to_string(), "high".to_string()]),
+    }
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .tids(tids)
+        .addresses(addresses)
         .symbols(vec![called_symbol.name.clone()])
+        .other(other)
+        .evidence(evidence)
 }
 
 /// Run the CWE check.
 /// For each call to one of the symbols configured in config.json
-/// we check whether the block containing the call also contains a multiplication instruction.
+/// we check whether a multiplication in the block containing the call flows into a parameter of the call.
+/// If the pointer inference results are available, multiplications that cannot wrap around are ignored
+/// and backward slices are attached to the findings.
 pub fn check_cwe(
     analysis_results: &AnalysisResults,
     cwe_params: &serde_json::Value,
@@ -110,12 +372,210 @@ pub fn check_cwe(
     let symbol_map = get_symbol_map(project, &config.symbols);
     for sub in project.program.term.subs.values() {
         for (block, jump, symbol) in get_callsites(sub, &symbol_map) {
-            if block_contains_multiplication(block) {
-                cwe_warnings.push(generate_cwe_warning(&jump.tid, symbol));
+            let mut multiplication_defs = get_multiplication_defs(block, symbol);
+            if let Some(pi_results) = analysis_results.pointer_inference {
+                multiplication_defs.retain(|def| def_may_wrap(pi_results, def));
             }
+            if multiplication_defs.is_empty() {
+                continue;
+            }
+            let factor_variables: HashSet<&Variable> = multiplication_defs
+                .iter()
+                .flat_map(|def| {
+                    let def_index = block
+                        .term
+                        .defs
+                        .iter()
+                        .position(|block_def| block_def.tid == def.tid)
+                        .unwrap();
+                    let inputs = match &def.term {
+                        Def::Assign { value, .. } => value.input_vars(),
+                        _ => Vec::new(),
+                    };
+                    let (variables, _) =
+                        trace_variables_backwards(&block.term.defs[..def_index], inputs);
+                    variables
+                })
+                .collect();
+            let guarding_comparison = find_guarding_comparison(sub, &block.tid, &factor_variables);
+            let multiplication_tids: Vec<&Tid> =
+                multiplication_defs.iter().map(|def| &def.tid).collect();
+            let evidence = analysis_results.pointer_inference.and_then(|pi_results| {
+                get_slice_evidence(project, pi_results, &jump.tid, symbol, &multiplication_tids)
+            });
+            cwe_warnings.push(generate_cwe_warning(
+                &jump.tid,
+                symbol,
+                guarding_comparison.map(|guard| &guard.tid),
+                evidence,
+            ));
         }
     }
 
     cwe_warnings.sort();
     (Vec::new(), cwe_warnings)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use std::collections::HashSet;
+
+    /// A block computing `RAX = RSI * 8` and `RDI = <size>` before calling `malloc`.
+    fn mock_malloc_block(tid: &str, size: Expression, return_block: &str) -> Term<Blk> {
+        let defs = vec![
+            Def::assign(
+                &format!("{}_mult", tid),
+                Variable::mock("RAX", 8),
+                Expression::BinOp {
+                    op: BinOpType::IntMult,
+                    lhs: Box::new(Expression::var("RSI", 8)),
+                    rhs: Box::new(Expression::const_from_i64(8)),
+                },
+            ),
+            Def::assign(&format!("{}_size", tid), Variable::mock("RDI", 8), size),
+        ];
+        Blk::mock_call(tid, defs, "malloc", return_block)
+    }
+
+    /// A project with `malloc` as extern symbol and a function `main` consisting of the given blocks.
+    fn mock_project(blocks: Vec<Term<Blk>>) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("malloc");
+        let main = Sub::mock_with_blocks("main", blocks);
+        project.program.term.entry_points.insert(main.tid.clone());
+        project.program.term.subs.insert(main.tid.clone(), main);
+        project
+    }
+
+    /// Compute the pointer inference for the given project.
+    fn compute_pointer_inference<'a>(
+        project: &'a Project,
+        runtime_memory_image: &'a RuntimeMemoryImage,
+        graph: &'a crate::analysis::graph::Graph<'a>,
+    ) -> PointerInference<'a> {
+        let mut pi_results = PointerInference::mock(project, runtime_memory_image, graph);
+        pi_results.compute();
+        pi_results
+    }
+
+    /// Get the CFG of the given project.
+    fn get_graph(project: &Project) -> crate::analysis::graph::Graph<'_> {
+        let extern_subs: HashSet<Tid> = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        crate::analysis::graph::get_program_cfg(&project.program, extern_subs)
+    }
+
+    #[test]
+    fn slice_evidence_for_high_severity_findings() {
+        let project = mock_project(vec![
+            // The multiplication result flows into the size parameter.
+            mock_malloc_block("flowing", Expression::var("RAX", 8), "unrelated"),
+            // The multiplication result is not used as the size parameter.
+            mock_malloc_block("unrelated", Expression::const_from_i64(16), "return_block"),
+            Blk::mock_return("return_block"),
+        ]);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = get_graph(&project);
+        let pi_results = compute_pointer_inference(&project, &runtime_memory_image, &graph);
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pi_results));
+        let config = serde_json::json!({ "symbols": ["malloc"] });
+
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].tids, vec!["call_flowing".to_string()]);
+        let evidence = warnings[0].evidence.as_ref().unwrap();
+        assert_eq!(
+            evidence.get("backward_slice"),
+            Some(&serde_json::json!(["flowing_mult", "flowing_size"]))
+        );
+
+        // Without pointer inference results no evidence is generated.
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].evidence.is_none());
+    }
+
+    #[test]
+    fn bounded_and_guarded_multiplications() {
+        let mut bounded = mock_malloc_block("bounded", Expression::var("RAX", 8), "guard");
+        bounded.term.defs.insert(
+            0,
+            Def::assign(
+                "bounded_factor",
+                Variable::mock("RSI", 8),
+                Expression::const_from_i64(0x10),
+            ),
+        );
+        let mut guard = Blk::mock_with_tid("guard");
+        guard.term.jmps = vec![
+            Term {
+                tid: Tid::new("guard_jmp"),
+                term: Jmp::CBranch {
+                    target: Tid::new("guarded"),
+                    condition: Expression::BinOp {
+                        op: BinOpType::IntSLess,
+                        lhs: Box::new(Expression::var("RSI", 8)),
+                        rhs: Box::new(Expression::const_from_i64(0x1000)),
+                    },
+                },
+            },
+            Term {
+                tid: Tid::new("guard_fallthrough"),
+                term: Jmp::Branch(Tid::new("return_block")),
+            },
+        ];
+        let project = mock_project(vec![
+            bounded,
+            guard,
+            // The signed comparison does not exclude negative factors.
+            mock_malloc_block("guarded", Expression::var("RAX", 8), "return_block"),
+            Blk::mock_return("return_block"),
+        ]);
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = get_graph(&project);
+        let pi_results = compute_pointer_inference(&project, &runtime_memory_image, &graph);
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pi_results));
+        let config = serde_json::json!({ "symbols": ["malloc"] });
+
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["call_guarded".to_string(), "guard_jmp".to_string()]
+        );
+        assert_eq!(
+            warnings[0].other[0],
+            vec!["severity".to_string(), "low".to_string()]
+        );
+
+        // Without pointer inference results the bounds of the factors are unknown.
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(warnings.len(), 2);
+        let bounded_warning = warnings
+            .iter()
+            .find(|warning| warning.tids == vec!["call_bounded".to_string()])
+            .unwrap();
+        assert_eq!(
+            bounded_warning.other,
+            vec![vec!["severity".to_string(), "high".to_string()]]
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! This module implements a check for CWE-195: Signed to Unsigned Conversion Error.
+//!
+//! Functions like `recv` or `read` return a signed length, where negative values indicate errors.
+//! If such a length is passed to a size parameter of type `size_t` without checking its sign first,
+//! a negative value is converted to a huge unsigned size,
+//! e.g. causing `memcpy` to overwrite large parts of memory.
+//! The same happens to negative `int` lengths that are sign-extended to the size of a `size_t`.
+//!
+//! See <https://cwe.mitre.org/data/definitions/195.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the size sinks configured in config.json
+//! we evaluate its size parameters using the pointer inference analysis.
+//! A size parameter is considered to be possibly negative if
+//! - its value interval contains negative values when interpreted as a signed integer.
+//!   Since the pointer inference remembers for sign-extended values of unknown origin
+//!   that they may be negative, this includes sign-extended `int` values that were never checked.
+//! - or its value is unbounded and it is tainted by the return value
+//!   of one of the configured symbols returning a signed length.
+//!   The taint is tracked with a dataflow analysis through the function calling the symbol.
+//!
+//! Sign checks on the value (e.g. `if (len <= 0) return;`) before the call
+//! restrict the value interval computed by the pointer inference,
+//! so that values checked on all paths to the call are not reported.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The `signed_return_symbols` are the functions whose return values are signed lengths.
+//! - The `size_parameters` map the names of functions
+//!   to the indices of their parameters of type `size_t`.
+//!
+//! ## False Positives
+//!
+//! - If a length is spilled to memory after its sign was checked,
+//!   then the information about the check may be lost for the reloaded value.
+//! - The sign may be checked by other means than a comparison, e.g. by a bit mask that the analysis cannot track.
+//!
+//! ## False Negatives
+//!
+//! - The taint analysis is intraprocedural, i.e. signed lengths passed to other functions are not tracked.
+//! - Sign-extended values are only recognized if the pointer inference keeps track of their value intervals.
+
+use crate::abstract_domain::{SizedDomain, TryToInterval};
+use crate::analysis::forward_interprocedural_fixpoint::Context as _;
+use crate::analysis::graph::Edge;
+use crate::analysis::pointer_inference::Data;
+use crate::analysis::taint::{compute_taint_from_sources, get_extern_taint_source_sites, State};
+use crate::checkers::cwe_789::context::Context;
+use crate::checkers::cwe_789::TaintedAllocation;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+use std::collections::{BTreeMap, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE195",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// The names of symbols whose return values are signed lengths.
+    signed_return_symbols: Vec<String>,
+    /// Maps the names of functions to the indices of their unsigned size parameters.
+    size_parameters: BTreeMap<String, Vec<usize>>,
+}
+
+/// Run the CWE check.
+/// For each call to one of the configured size sinks
+/// we check whether one of its size parameters may be negative.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+
+    let (sink_sender, sink_receiver) = crossbeam_channel::unbounded();
+
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let symbol_map =
+        crate::utils::symbol_utils::get_symbol_map(project, &config.signed_return_symbols[..]);
+    let general_context = Context::new(
+        project,
+        pointer_inference_results,
+        config.size_parameters.clone(),
+        sink_sender,
+    );
+
+    let mut sink_calls = Vec::new();
+    for edge in general_context.get_graph().edge_references() {
+        if let Edge::ExternCallStub(jmp) = edge.weight() {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                    if config.size_parameters.contains_key(&symbol.name) {
+                        sink_calls.push((*jmp, symbol));
+                    }
+                }
+            }
+        }
+    }
+    let taint_source_sites = get_extern_taint_source_sites(
+        general_context.get_graph(),
+        &symbol_map,
+        pointer_inference_results,
+        State::new,
+    )
+    .into_iter()
+    .map(|(jmp, symbol, node, state)| ((jmp, symbol.name.as_str()), node, state));
+    let logs = compute_taint_from_sources(
+        &general_context,
+        taint_source_sites,
+        |context, (jmp, source_name), current_sub| {
+            context.set_taint_source(jmp, source_name, current_sub)
+        },
+    );
+    let mut signed_length_sinks: HashMap<Tid, TaintedAllocation> = HashMap::new();
+    for tainted_sink in sink_receiver.try_iter() {
+        signed_length_sinks.insert(tainted_sink.allocation_call.clone(), tainted_sink);
+    }
+
+    let mut cwe_warnings = BTreeMap::new();
+    for (call, symbol) in sink_calls {
+        let pi_state = match pointer_inference_results.get_state_before_term(&call.tid) {
+            Some(state) => state,
+            None => continue,
+        };
+        let signed_length_source = signed_length_sinks.get(&call.tid);
+        for index in config.size_parameters[&symbol.name].iter() {
+            let size = match symbol.parameters.get(*index).map(|parameter| {
+                pi_state.eval_parameter_arg(parameter, analysis_results.runtime_memory_image)
+            }) {
+                Some(Ok(size)) => size,
+                _ => continue,
+            };
+            if may_be_negative(&size, signed_length_source.is_some()) {
+                let cwe = generate_cwe_warning(call, symbol, &size, signed_length_source);
+                cwe_warnings.insert(call.tid.clone(), cwe);
+                break;
+            }
+        }
+    }
+    let cwe_warnings = cwe_warnings.into_values().collect();
+
+    (logs, cwe_warnings)
+}
+
+/// Returns `true` if the given size value may be negative when interpreted as a signed integer.
+///
+/// Values without known bounds are only considered to be possibly negative
+/// if they originate from a signed length.
+/// Values that may be pointers are ignored.
+fn may_be_negative(size: &Data, is_signed_length: bool) -> bool {
+    if !size.get_relative_values().is_empty() {
+        return false;
+    }
+    match size
+        .get_absolute_value()
+        .map(|absolute_value| absolute_value.try_to_offset_interval())
+    {
+        Some(Ok((lower_bound, _))) => lower_bound < 0,
+        Some(Err(_)) | None => is_signed_length && size.contains_top(),
+    }
+}
+
+/// Generate the CWE warning for a call whose size parameter may be negative.
+fn generate_cwe_warning(
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    size: &Data,
+    signed_length_source: Option<&TaintedAllocation>,
+) -> CweWarning {
+    let call_address = call.tid.canonical_address();
+    match signed_length_source {
+        Some(source) => {
+            let description = format!(
+                "(Signed to Unsigned Conversion Error) The return value of {} at {} may be negative and is used as the size parameter of {} at {} without a sign check",
+                source.taint_source_name,
+                source.taint_source.canonical_address(),
+                symbol.name,
+                call_address,
+            );
+            CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+                .addresses(vec![
+                    source.taint_source.canonical_address().to_string(),
+                    call_address.to_string(),
+                ])
+                .tids(vec![
+                    format!("{}", source.taint_source),
+                    format!("{}", call.tid),
+                ])
+                .symbols(vec![source.taint_source_name.clone(), symbol.name.clone()])
+        }
+        None => {
+            let interval = size
+                .get_absolute_value()
+                .and_then(|absolute_value| absolute_value.try_to_offset_interval().ok())
+                .map(|(lower_bound, upper_bound)| format!("[{}, {}]", lower_bound, upper_bound))
+                .unwrap_or_else(|| "unknown".to_string());
+            let description = format!(
+                "(Signed to Unsigned Conversion Error) The size parameter of {} at {} may be negative (possible values: {}) and is converted to a huge unsigned size",
+                symbol.name,
+                call_address,
+                interval,
+            );
+            CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+                .addresses(vec![call_address.to_string()])
+                .tids(vec![format!("{}", call.tid)])
+                .symbols(vec![symbol.name.clone()])
+                .other(vec![vec![
+                    "size_interval".to_string(),
+                    interval,
+                    format!("{}", size.bytesize()),
+                ]])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project where `size` is used as the size parameter of a call to `memcpy`
+    /// after a call to `recv`.
+    /// If `checked` is set, the function returns early if the return value of `recv` is not positive.
+    fn mock_project(size: Expression, checked: bool) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["recv", "memcpy"] {
+            project.add_mock_extern_symbol(name);
+        }
+
+        let mut sub = Sub::mock("func");
+        let mut call_block = Blk::mock_with_tid("call_block");
+        call_block
+            .term
+            .jmps
+            .push(Jmp::call("source_call", "recv", Some("check_block")));
+        let mut check_block = Blk::mock_with_tid("check_block");
+        let mut copy_block = Blk::mock_with_tid("copy_block");
+        copy_block
+            .term
+            .defs
+            .push(Def::assign("set_size", Variable::mock("RDI", 8), size));
+        copy_block
+            .term
+            .jmps
+            .push(Jmp::call("copy_call", "memcpy", Some("end_block")));
+        let end_block = Blk::mock_return("end_block");
+        if checked {
+            // The comparison is part of the branch condition,
+            // as it would be after the expression propagation during normalization.
+            check_block.term.jmps.push(Term {
+                tid: Tid::new("check"),
+                term: Jmp::CBranch {
+                    target: Tid::new("end_block"),
+                    condition: Expression::BinOp {
+                        op: BinOpType::IntSLessEqual,
+                        lhs: Box::new(Expression::var("RAX", 8)),
+                        rhs: Box::new(Expression::const_from_i64(0)),
+                    },
+                },
+            });
+        }
+        check_block
+            .term
+            .jmps
+            .push(Jmp::branch("jmp_copy", "copy_block"));
+        sub.term.blocks = vec![call_block, check_block, copy_block, end_block];
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let config = serde_json::json!({
+            "signed_return_symbols": ["recv"],
+            "size_parameters": { "memcpy": [0] },
+        });
+        AnalysisResults::run_with_mock_pointer_inference(project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    /// The lower four bytes of the return value of `recv` sign-extended to eight bytes.
+    fn sign_extended_length() -> Expression {
+        Expression::Cast {
+            op: CastOpType::IntSExt,
+            size: ByteSize::new(8),
+            arg: Box::new(Expression::Subpiece {
+                low_byte: ByteSize::new(0),
+                size: ByteSize::new(4),
+                arg: Box::new(Expression::var("RAX", 8)),
+            }),
+        }
+    }
+
+    #[test]
+    fn unchecked_recv_length() {
+        let cwe_warnings = run_check(&mock_project(Expression::var("RAX", 8), false));
+        assert_eq!(cwe_warnings.len(), 1);
+        assert_eq!(
+            cwe_warnings[0].tids,
+            vec!["source_call".to_string(), "copy_call".to_string()]
+        );
+        assert_eq!(
+            cwe_warnings[0].symbols,
+            vec!["recv".to_string(), "memcpy".to_string()]
+        );
+    }
+
+    #[test]
+    fn checked_recv_length() {
+        let project = mock_project(Expression::var("RAX", 8), true);
+        assert!(run_check(&project).is_empty());
+    }
+
+    #[test]
+    fn sign_extended_length_without_check() {
+        let cwe_warnings = run_check(&mock_project(sign_extended_length(), false));
+        assert_eq!(cwe_warnings.len(), 1);
+        // Unchecked sign-extended values are reported even without a known signed source.
+        let mut project = mock_project(sign_extended_length(), false);
+        let sub = project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::new("func"))
+            .unwrap();
+        sub.term.blocks[0].term.jmps = vec![Jmp::branch("skip_recv", "check_block")];
+        let cwe_warnings = run_check(&project);
+        assert_eq!(cwe_warnings.len(), 1);
+        assert_eq!(cwe_warnings[0].tids, vec!["copy_call".to_string()]);
+    }
+
+    #[test]
+    fn constant_size() {
+        let project = mock_project(Expression::const_from_i64(0x100), false);
+        assert!(run_check(&project).is_empty());
+    }
+}
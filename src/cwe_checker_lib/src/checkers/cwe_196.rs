@@ -0,0 +1,490 @@
+//! This module implements a check for CWE-196: Unsigned to Signed Conversion Error.
+//!
+//! A bounds check like `if ((int)len < MAX) memcpy(dst, src, len);` on an unsigned length `len`
+//! compares a signed reinterpretation of the length.
+//! Huge unsigned lengths are negative when interpreted as signed integers,
+//! so they pass the check and reach the size parameter of `memcpy` unchanged.
+//! The bounds check can thus be bypassed.
+//!
+//! See <https://cwe.mitre.org/data/definitions/196.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the size sinks configured in config.json (e.g. `memcpy` or `malloc`)
+//! we look for conditional jumps in the same function that guard the call, i.e.
+//! the call is only reachable from one of the two branches of the conditional jump.
+//! A guard is considered to be a signed bounds check of a value if its condition is
+//! a signed comparison (`IntSLess` or `IntSLessEqual`) of the value
+//! (or of a truncated or sign-extended cast of it) against a positive constant
+//! and if the value is bounded from above on the branch leading to the call.
+//! The compared value is related to the size parameter of the call with backward slices
+//! (see [`backward_slicing`](crate::analysis::backward_slicing)):
+//! The guard checks the size if a definition of the compared value is contained in the slice of the size parameter.
+//!
+//! Finally, we evaluate the size parameter at the call using the pointer inference analysis.
+//! We generate a CWE warning if the possible values of the size parameter (interpreted as unsigned integers)
+//! exceed the positive range of the signed type used in the comparison.
+//! Sign checks on the value (e.g. `if (len < 0) return;`) before the call
+//! restrict the value interval computed by the pointer inference,
+//! so that correctly checked values are not reported.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! The `size_parameters` map the names of functions to the indices of their parameters of type `size_t`.
+//!
+//! ## False Positives
+//!
+//! - If the sign of the value is checked by other means than a comparison (e.g. by a bit mask),
+//!   the value interval at the call may still contain values exceeding the signed range.
+//! - The slices are context-insensitive, so guards on unrelated values with a common origin may be reported.
+//!
+//! ## False Negatives
+//!
+//! - Only guards and calls in the same function are related to each other.
+//! - Guards whose comparison was not propagated into the condition of the conditional jump are not recognized.
+
+use crate::abstract_domain::{SizedDomain, TryToInterval};
+use crate::analysis::backward_slicing::{slice_with_config, AliasPrecision, Slice, SliceConfig};
+use crate::analysis::pointer_inference::{Data, PointerInference};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::graph_utils::{get_block_successors, get_jmp_targets, get_reachable_blocks};
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+use std::collections::{BTreeMap, HashMap};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE196",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// Maps the names of functions to the indices of their unsigned size parameters.
+    size_parameters: BTreeMap<String, Vec<usize>>,
+}
+
+/// The maximal length of dependency chains in the backward slices of size parameters.
+const SIZE_SLICE_DEPTH: usize = 10;
+
+/// A signed bounds check of a value in the condition of a conditional jump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SignedGuard<'a> {
+    /// The conditional jump.
+    jmp: &'a Term<Jmp>,
+    /// The TID of the block containing the conditional jump.
+    block: &'a Tid,
+    /// The blocks jumped to if the condition is true.
+    true_targets: Vec<&'a Tid>,
+    /// The blocks jumped to if the condition is false,
+    /// i.e. the targets of the jumps after the conditional jump in the same block.
+    false_targets: Vec<&'a Tid>,
+    /// The register whose value is compared.
+    variable: &'a Variable,
+    /// The size of the signed reinterpretation of the value used in the comparison.
+    compared_size: ByteSize,
+    /// Whether the value is bounded from above if the condition is true.
+    /// Otherwise it is bounded from above if the condition is false.
+    bounded_if_true: bool,
+}
+
+/// Run the CWE check.
+/// For each call to one of the configured size sinks
+/// we check whether a size parameter is only bounded by a signed comparison.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let slice_config = SliceConfig {
+        max_depth: SIZE_SLICE_DEPTH,
+        alias_precision: AliasPrecision::Exact,
+    };
+
+    let mut cwe_warnings = BTreeMap::new();
+    for sub in project.program.term.subs.values() {
+        let sink_calls = get_sink_calls(project, sub, &config);
+        if sink_calls.is_empty() {
+            continue;
+        }
+        let guards = get_signed_guards(sub);
+        if guards.is_empty() {
+            continue;
+        }
+        let successors = get_block_successors(sub);
+        // Slices of the compared values at the guards, computed on demand.
+        let mut guard_slices: HashMap<&Tid, Option<Slice>> = HashMap::new();
+        for (block, call, symbol) in sink_calls {
+            let pi_state = match pointer_inference_results.get_state_before_term(&call.tid) {
+                Some(state) => state,
+                None => continue,
+            };
+            'parameters: for index in config.size_parameters[&symbol.name].iter() {
+                let (register, size) = match symbol.parameters.get(*index) {
+                    Some(
+                        parameter @ Arg::Register {
+                            expr: Expression::Var(register),
+                            ..
+                        },
+                    ) => match pi_state
+                        .eval_parameter_arg(parameter, analysis_results.runtime_memory_image)
+                    {
+                        Ok(size) => (register, size),
+                        Err(_) => continue,
+                    },
+                    _ => continue,
+                };
+                let size_slice = match slice_with_config(
+                    project,
+                    pointer_inference_results,
+                    &call.tid,
+                    register,
+                    &slice_config,
+                ) {
+                    Ok(slice) => slice,
+                    Err(_) => continue,
+                };
+                for guard in guards.iter() {
+                    if !guards_block(&successors, guard, block) {
+                        continue;
+                    }
+                    if !exceeds_signed_range(&size, guard.compared_size) {
+                        continue;
+                    }
+                    let guard_slice = guard_slices.entry(&guard.jmp.tid).or_insert_with(|| {
+                        get_guard_definitions(pointer_inference_results, project, guard)
+                    });
+                    let is_related = match guard_slice {
+                        Some(guard_slice) => guard_slice
+                            .get_all_terms()
+                            .into_iter()
+                            .any(|tid| size_slice.contains(tid)),
+                        None => false,
+                    };
+                    if is_related {
+                        let cwe = generate_cwe_warning(sub, guard, call, symbol, &size);
+                        cwe_warnings.insert(call.tid.clone(), cwe);
+                        break 'parameters;
+                    }
+                }
+            }
+        }
+    }
+    let cwe_warnings = cwe_warnings.into_values().collect();
+
+    (Vec::new(), cwe_warnings)
+}
+
+/// Get all calls to the configured size sinks in the given function
+/// together with the TID of the block containing the call and the called symbol.
+fn get_sink_calls<'a>(
+    project: &'a Project,
+    sub: &'a Term<Sub>,
+    config: &Config,
+) -> Vec<(&'a Tid, &'a Term<Jmp>, &'a ExternSymbol)> {
+    let mut sink_calls = Vec::new();
+    for block in sub.term.blocks.iter() {
+        for jmp in block.term.jmps.iter() {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                    if config.size_parameters.contains_key(&symbol.name) {
+                        sink_calls.push((&block.tid, jmp, symbol));
+                    }
+                }
+            }
+        }
+    }
+    sink_calls
+}
+
+/// Get all conditional jumps in the given function whose condition is a signed bounds check of a register.
+fn get_signed_guards(sub: &Term<Sub>) -> Vec<SignedGuard<'_>> {
+    let mut guards = Vec::new();
+    for block in sub.term.blocks.iter() {
+        for (index, jmp) in block.term.jmps.iter().enumerate() {
+            if let Jmp::CBranch { condition, .. } = &jmp.term {
+                if let Some((variable, compared_size, bounded_if_true)) =
+                    parse_signed_upper_bound_check(condition)
+                {
+                    guards.push(SignedGuard {
+                        jmp,
+                        block: &block.tid,
+                        true_targets: get_jmp_targets(jmp),
+                        false_targets: block.term.jmps[index + 1..]
+                            .iter()
+                            .flat_map(get_jmp_targets)
+                            .collect(),
+                        variable,
+                        compared_size,
+                        bounded_if_true,
+                    });
+                }
+            }
+        }
+    }
+    guards
+}
+
+/// If the condition is a signed comparison of a register (or a cast of it) against a positive constant,
+/// return the register, the size of the compared value
+/// and whether the register is bounded from above if the condition is true.
+fn parse_signed_upper_bound_check(condition: &Expression) -> Option<(&Variable, ByteSize, bool)> {
+    use Expression::*;
+    match condition {
+        UnOp {
+            op: UnOpType::BoolNegate,
+            arg,
+        } => parse_signed_upper_bound_check(arg)
+            .map(|(variable, size, bounded_if_true)| (variable, size, !bounded_if_true)),
+        BinOp {
+            op: BinOpType::IntSLess | BinOpType::IntSLessEqual,
+            lhs,
+            rhs,
+        } => match (lhs.as_ref(), rhs.as_ref()) {
+            (value, Const(bound)) if is_positive(bound) => {
+                Some((get_compared_register(value)?, value.bytesize(), true))
+            }
+            (Const(bound), value) if is_positive(bound) => {
+                Some((get_compared_register(value)?, value.bytesize(), false))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns `true` if the given constant is positive when interpreted as a signed integer.
+fn is_positive(bound: &Bitvector) -> bool {
+    !bound.is_zero() && !bound.sign_bit().to_bool()
+}
+
+/// Get the register whose value is compared,
+/// removing truncations and sign extensions of the value.
+///
+/// Zero extensions are not removed,
+/// since a signed comparison of a zero-extended value is a correct bounds check for the unextended value.
+fn get_compared_register(value: &Expression) -> Option<&Variable> {
+    match value {
+        Expression::Var(variable) => Some(variable),
+        Expression::Subpiece { arg, .. }
+        | Expression::Cast {
+            op: CastOpType::IntSExt,
+            arg,
+            ..
+        } => get_compared_register(arg),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the given block is only reachable
+/// from the branch of the guard on which the compared value is bounded from above.
+fn guards_block(successors: &HashMap<&Tid, Vec<&Tid>>, guard: &SignedGuard, block: &Tid) -> bool {
+    let true_branch = get_reachable_blocks(successors, guard.true_targets.clone(), guard.block);
+    let false_branch = get_reachable_blocks(successors, guard.false_targets.clone(), guard.block);
+    let (bounded_branch, unbounded_branch) = if guard.bounded_if_true {
+        (true_branch, false_branch)
+    } else {
+        (false_branch, true_branch)
+    };
+    bounded_branch.contains(block) && !unbounded_branch.contains(block)
+}
+
+/// Returns `true` if the given size value may exceed the positive range of a signed integer of the given size
+/// when interpreted as an unsigned integer.
+///
+/// Values without known bounds may exceed the range.
+/// Values that may be pointers are ignored.
+fn exceeds_signed_range(size: &Data, compared_size: ByteSize) -> bool {
+    if !size.get_relative_values().is_empty() {
+        return false;
+    }
+    let signed_max = match u64::from(compared_size) {
+        0 => return false,
+        bytes if bytes >= 8 => i64::MAX,
+        bytes => (1i64 << (bytes * 8 - 1)) - 1,
+    };
+    match size
+        .get_absolute_value()
+        .map(|absolute_value| absolute_value.try_to_offset_interval())
+    {
+        Some(Ok((lower_bound, upper_bound))) => lower_bound < 0 || upper_bound > signed_max,
+        Some(Err(_)) => true,
+        None => size.contains_top(),
+    }
+}
+
+/// Compute the definitions of the compared value of the guard,
+/// i.e. the backward slice of the compared register at the guard with a depth of one.
+fn get_guard_definitions(
+    pointer_inference_results: &PointerInference,
+    project: &Project,
+    guard: &SignedGuard,
+) -> Option<Slice> {
+    let config = SliceConfig {
+        max_depth: 1,
+        alias_precision: AliasPrecision::Exact,
+    };
+    slice_with_config(
+        project,
+        pointer_inference_results,
+        &guard.jmp.tid,
+        guard.variable,
+        &config,
+    )
+    .ok()
+}
+
+/// Generate the CWE warning for a call whose size parameter is only bounded by a signed comparison.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    guard: &SignedGuard,
+    call: &Term<Jmp>,
+    symbol: &ExternSymbol,
+    size: &Data,
+) -> CweWarning {
+    let description = format!(
+        "(Unsigned to Signed Conversion Error) The size parameter of {} at {} in function {} is only bounded by a signed comparison at {}. \
+        Sizes exceeding the signed range pass the check as negative values",
+        symbol.name,
+        call.tid.canonical_address(),
+        sub.term.name,
+        guard.jmp.tid.canonical_address(),
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![
+            call.tid.canonical_address().to_string(),
+            guard.jmp.tid.canonical_address().to_string(),
+        ])
+        .tids(vec![format!("{}", call.tid), format!("{}", guard.jmp.tid)])
+        .symbols(vec![sub.term.name.clone(), symbol.name.clone()])
+        .other(vec![vec![
+            "compared_size".to_string(),
+            format!("{}", guard.compared_size),
+            format!("{}", size.bytesize()),
+        ]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project where a length is loaded from memory and used as the size parameter of `memcpy`.
+    /// The length is checked by a conditional jump with the given condition,
+    /// which jumps to `target` if the condition is true and to the other block otherwise.
+    fn mock_project(condition: Expression, target: &str) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("memcpy");
+
+        let mut check_block = Blk::mock_with_tid("check_block");
+        check_block.term.defs.push(Def::load(
+            "load_len",
+            Variable::mock("RAX", 8),
+            Expression::var("RDI", 8),
+        ));
+        let other_target = if target == "copy_block" {
+            "end_block"
+        } else {
+            "copy_block"
+        };
+        check_block.term.jmps = vec![
+            Term {
+                tid: Tid::new("guard"),
+                term: Jmp::CBranch {
+                    target: Tid::new(target),
+                    condition,
+                },
+            },
+            Jmp::branch("jmp_other", other_target),
+        ];
+        let mut copy_block = Blk::mock_with_tid("copy_block");
+        copy_block.term.defs.push(Def::assign(
+            "set_size",
+            Variable::mock("RDI", 8),
+            Expression::var("RAX", 8),
+        ));
+        copy_block
+            .term
+            .jmps
+            .push(Jmp::call("copy_call", "memcpy", Some("end_block")));
+        let end_block = Blk::mock_return("end_block");
+        let sub = Sub::mock_with_blocks("func", vec![check_block, copy_block, end_block]);
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let config = serde_json::json!({
+            "size_parameters": { "memcpy": [0] },
+        });
+        AnalysisResults::run_with_mock_pointer_inference(project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn signed_guard_of_unsigned_size() {
+        // `if (0x100 <= (long)len) return; memcpy(dst, src, len);`
+        let condition = Expression::BinOp {
+            op: BinOpType::IntSLessEqual,
+            lhs: Box::new(Expression::const_from_i64(0x100)),
+            rhs: Box::new(Expression::var("RAX", 8)),
+        };
+        let cwe_warnings = run_check(&mock_project(condition, "end_block"));
+        assert_eq!(cwe_warnings.len(), 1);
+        assert_eq!(
+            cwe_warnings[0].tids,
+            vec!["copy_call".to_string(), "guard".to_string()]
+        );
+    }
+
+    #[test]
+    fn truncated_signed_guard_of_unsigned_size() {
+        // `if ((int)len < 0x100) memcpy(dst, src, len);`
+        let condition = Expression::BinOp {
+            op: BinOpType::IntSLess,
+            lhs: Box::new(Expression::Subpiece {
+                low_byte: ByteSize::new(0),
+                size: ByteSize::new(4),
+                arg: Box::new(Expression::var("RAX", 8)),
+            }),
+            rhs: Box::new(Expression::const_from_i32(0x100)),
+        };
+        let cwe_warnings = run_check(&mock_project(condition, "copy_block"));
+        assert_eq!(cwe_warnings.len(), 1);
+    }
+
+    #[test]
+    fn unsigned_guard_of_unsigned_size() {
+        // `if (0x100 <= len) return; memcpy(dst, src, len);`
+        let condition = Expression::BinOp {
+            op: BinOpType::IntLessEqual,
+            lhs: Box::new(Expression::const_from_i64(0x100)),
+            rhs: Box::new(Expression::var("RAX", 8)),
+        };
+        assert!(run_check(&mock_project(condition, "end_block")).is_empty());
+    }
+
+    #[test]
+    fn signed_guard_on_unrelated_branch() {
+        // The guard is a lower bound check, since `memcpy` is reached if the condition is false.
+        let condition = Expression::BinOp {
+            op: BinOpType::IntSLess,
+            lhs: Box::new(Expression::var("RAX", 8)),
+            rhs: Box::new(Expression::const_from_i64(0x100)),
+        };
+        assert!(run_check(&mock_project(condition, "end_block")).is_empty());
+    }
+}
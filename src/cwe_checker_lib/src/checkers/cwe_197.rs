@@ -0,0 +1,610 @@
+//! This module implements a check for CWE-197: Numeric Truncation Error.
+//!
+//! Truncating a value to a smaller integer type silently discards the upper bits of the value.
+//! This is especially dangerous for lengths and sizes, e.g. if a 64-bit length value
+//! is passed to a function taking a 32-bit size parameter
+//! while other parts of the program still check or use the full 64-bit value.
+//!
+//! See <https://cwe.mitre.org/data/definitions/197.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the pointer inference analysis we check two kinds of truncations:
+//! - For calls to extern symbols we check whether a parameter of the symbol is smaller than the register
+//!   that contains it (e.g. a 4-byte `unsigned int` parameter passed in a 8-byte register).
+//!   If the value of the full register may not fit into the parameter, we generate a CWE warning.
+//! - For assignments inside a basic block we check whether a value is truncated
+//!   although its full-width value may not fit into the truncated size.
+//!   If the result of such an assignment is used later in the same basic block
+//!   in the address computation of a memory access
+//!   or as a parameter to one of the allocation symbols configured in config.json,
+//!   we generate a CWE warning.
+//!
+//! A value is considered to not fit into the truncated size
+//! if the value interval computed by the pointer inference is unbounded above
+//! or contains values larger than the maximum unsigned integer of the truncated size.
+//! Values without any known bounds are not reported,
+//! since e.g. zero-extended 32-bit values of unknown size are `Top` as 64-bit values as well.
+//! Negative values are not reported,
+//! since e.g. error codes are routinely passed in wider registers.
+//!
+//! ## False Positives
+//!
+//! - The pointer inference is not able to track value bounds through all program paths.
+//!   Thus values with a known lower bound that are always small at runtime may still be reported as unbounded.
+//! - The truncation may be intentional, e.g. if the program only needs the lower bits of a value.
+//!
+//! ## False Negatives
+//!
+//! - Truncated values are only tracked inside a single basic block.
+//! - Truncations of values about which the pointer inference has no information are not reported.
+//! - Truncations of pointers or values that may be pointers are ignored.
+
+use crate::abstract_domain::{SizedDomain, TryToInterval};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+use petgraph::visit::IntoNodeReferences;
+use std::collections::HashMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE197",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct.
+/// The `symbols` are the names of allocation functions
+/// whose parameters must not be computed from truncated values.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Config {
+    symbols: Vec<String>,
+}
+
+/// A truncation of a value that may have changed the value.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Truncation {
+    /// The full-width value before the truncation.
+    value: Data,
+    /// The size of the value after the truncation.
+    truncated_size: ByteSize,
+    /// The TID of the term where the truncation happened.
+    tid: Tid,
+}
+
+/// If the given expression truncates a value to a smaller size (possibly extending it again afterwards),
+/// return the expression computing the full-width value together with the truncated size.
+fn get_truncated_input(expression: &Expression) -> Option<(&Expression, ByteSize)> {
+    match expression {
+        Expression::Subpiece {
+            low_byte,
+            size,
+            arg,
+        } if *low_byte == ByteSize::new(0) && *size < arg.bytesize() => Some((arg, *size)),
+        Expression::Cast {
+            op: CastOpType::IntZExt | CastOpType::IntSExt,
+            arg,
+            ..
+        } => get_truncated_input(arg),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the known value interval of the given full-width value is unbounded above
+/// or exceeds the maximum unsigned integer of the truncated size,
+/// i.e. if the truncation may change the value.
+///
+/// Values without a known absolute value interval and negative values are not reported.
+/// Values that may be pointers are ignored, since pointer truncations are not in the scope of this check.
+fn truncation_may_change_value(value: &Data, truncated_size: ByteSize) -> bool {
+    if !value.get_relative_values().is_empty() || truncated_size >= value.bytesize() {
+        return false;
+    }
+    let interval = match value
+        .get_absolute_value()
+        .map(|value| value.try_to_interval())
+    {
+        Some(Ok(interval)) => interval,
+        _ => return false,
+    };
+    let max_value = Bitvector::unsigned_max_value(truncated_size.into())
+        .into_zero_extend(value.bytesize())
+        .unwrap();
+    interval.end.checked_sgt(&max_value).unwrap()
+}
+
+/// Generate a human-readable representation of the possible values of the given value.
+fn format_value_interval(value: &Data) -> String {
+    match value
+        .get_absolute_value()
+        .map(|value| value.try_to_interval())
+    {
+        Some(Ok(interval)) => {
+            let end = if interval.end == Bitvector::signed_max_value(value.bytesize().into()) {
+                Ok("unbounded".to_string())
+            } else {
+                interval.end.try_to_i64().map(|end| end.to_string())
+            };
+            match (interval.start.try_to_i64(), end) {
+                (Ok(start), Ok(end)) => format!("[{}, {}]", start, end),
+                _ => "unknown".to_string(),
+            }
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+/// Generate the CWE warning for a parameter of an extern symbol that receives a truncated value.
+fn generate_cwe_warning_for_parameter(
+    callsite: &Tid,
+    symbol: &ExternSymbol,
+    truncation: &Truncation,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Numeric Truncation Error) {}-byte value with possible values {} truncated to {} bytes when passed as a parameter to {} at {}",
+            truncation.value.bytesize(),
+            format_value_interval(&truncation.value),
+            truncation.truncated_size,
+            symbol.name,
+            callsite.canonical_address()
+        ))
+        .tids(vec![format!("{}", callsite)])
+        .addresses(vec![callsite.canonical_address().to_string()])
+        .symbols(vec![symbol.name.clone()])
+        .other(vec![vec![
+            "truncated_value_interval".to_string(),
+            format_value_interval(&truncation.value),
+        ]])
+}
+
+/// Generate the CWE warning for a truncated value that is used at the given location.
+fn generate_cwe_warning_for_usage(
+    usage_location: &Tid,
+    usage: &str,
+    truncation: &Truncation,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Numeric Truncation Error) {}-byte value with possible values {} truncated to {} bytes at {} and used as {} at {}",
+            truncation.value.bytesize(),
+            format_value_interval(&truncation.value),
+            truncation.truncated_size,
+            truncation.tid.canonical_address(),
+            usage,
+            usage_location.canonical_address()
+        ))
+        .tids(vec![format!("{}", truncation.tid), format!("{}", usage_location)])
+        .addresses(vec![truncation.tid.canonical_address().to_string(), usage_location.canonical_address().to_string()])
+        .other(vec![vec![
+            "truncated_value_interval".to_string(),
+            format_value_interval(&truncation.value),
+        ]])
+}
+
+/// Return the first truncation tracked for one of the input variables of the given expression.
+fn find_truncated_input<'a>(
+    expression: &Expression,
+    truncations: &'a HashMap<Variable, Truncation>,
+) -> Option<&'a Truncation> {
+    expression
+        .input_vars()
+        .into_iter()
+        .find_map(|var| truncations.get(var))
+}
+
+/// Check the parameters of a call to an extern symbol for truncations that may change the parameter value.
+fn check_extern_call_parameters(
+    project: &Project,
+    state: &State,
+    callsite: &Tid,
+    symbol: &ExternSymbol,
+) -> Vec<CweWarning> {
+    let mut cwe_warnings = Vec::new();
+    for parameter in symbol.parameters.iter() {
+        if let Arg::Register { expr, data_type } = parameter {
+            let parameter_size = match data_type {
+                Some(data_type) => project
+                    .datatype_properties
+                    .get_size_from_data_type(data_type.clone()),
+                None => expr.bytesize(),
+            };
+            if let Some((input, _)) = get_truncated_input(expr) {
+                let value = state.eval(input);
+                if parameter_size < input.bytesize()
+                    && truncation_may_change_value(&value, parameter_size)
+                {
+                    let truncation = Truncation {
+                        value,
+                        truncated_size: parameter_size,
+                        tid: callsite.clone(),
+                    };
+                    cwe_warnings.push(generate_cwe_warning_for_parameter(
+                        callsite,
+                        symbol,
+                        &truncation,
+                    ));
+                }
+            }
+        }
+    }
+    cwe_warnings
+}
+
+/// Check the given basic block for truncations that may change the truncated value.
+///
+/// The `state` is the pointer inference state at the start of the block.
+/// Truncated values are tracked until the end of the block.
+/// If they are used in the address computation of a memory access
+/// or as parameters to one of the given allocation symbols, CWE warnings are generated.
+/// Furthermore, calls to extern symbols at the end of the block are checked for truncated parameters.
+fn check_block(
+    project: &Project,
+    global_memory: &RuntimeMemoryImage,
+    mut state: State,
+    block: &Term<Blk>,
+    allocation_symbols: &HashMap<Tid, &ExternSymbol>,
+) -> Vec<CweWarning> {
+    let mut cwe_warnings = Vec::new();
+    let mut truncations: HashMap<Variable, Truncation> = HashMap::new();
+    for def in block.term.defs.iter() {
+        match &def.term {
+            Def::Assign { var, value } => {
+                let new_truncation = get_truncated_input(value).and_then(|(input, size)| {
+                    let input_value = state.eval(input);
+                    if truncation_may_change_value(&input_value, size) {
+                        Some(Truncation {
+                            value: input_value,
+                            truncated_size: size,
+                            tid: def.tid.clone(),
+                        })
+                    } else {
+                        None
+                    }
+                });
+                let truncation =
+                    new_truncation.or_else(|| find_truncated_input(value, &truncations).cloned());
+                state.handle_register_assign(var, value);
+                match truncation {
+                    Some(truncation) => truncations.insert(var.clone(), truncation),
+                    None => truncations.remove(var),
+                };
+            }
+            Def::Load { var, address } => {
+                if let Some(truncation) = find_truncated_input(address, &truncations) {
+                    cwe_warnings.push(generate_cwe_warning_for_usage(
+                        &def.tid,
+                        "memory offset",
+                        truncation,
+                    ));
+                }
+                let _ = state.handle_load(var, address, global_memory);
+                truncations.remove(var);
+            }
+            Def::Store { address, value } => {
+                if let Some(truncation) = find_truncated_input(address, &truncations) {
+                    cwe_warnings.push(generate_cwe_warning_for_usage(
+                        &def.tid,
+                        "memory offset",
+                        truncation,
+                    ));
+                }
+                let _ = state.handle_store(address, value, global_memory);
+            }
+        }
+    }
+    for jmp in block.term.jmps.iter() {
+        if let Jmp::Call { target, .. } = &jmp.term {
+            if let Some(symbol) = allocation_symbols.get(target) {
+                for parameter in symbol.parameters.iter() {
+                    if let Arg::Register { expr, .. } = parameter {
+                        if let Some(truncation) = find_truncated_input(expr, &truncations) {
+                            cwe_warnings.push(generate_cwe_warning_for_usage(
+                                &jmp.tid,
+                                &format!("allocation size for {}", symbol.name),
+                                truncation,
+                            ));
+                        }
+                    }
+                }
+            }
+            if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                cwe_warnings.append(&mut check_extern_call_parameters(
+                    project, &state, &jmp.tid, symbol,
+                ));
+            }
+        }
+    }
+    cwe_warnings
+}
+
+/// Run the CWE check.
+///
+/// For each basic block with a pointer inference state we check for truncations of values
+/// that do not fit into the truncated size
+/// and that are used as memory offsets, allocation sizes or parameters of extern symbols.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let allocation_symbols = get_symbol_map(project, &config.symbols[..]);
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    for (node_id, node) in pointer_inference_results.get_graph().node_references() {
+        if let Node::BlkStart(block, _sub) = node {
+            if let Some(NodeValue::Value(state)) = pointer_inference_results.get_node_value(node_id)
+            {
+                cwe_warnings.append(&mut check_block(
+                    project,
+                    analysis_results.runtime_memory_image,
+                    state.clone(),
+                    block,
+                    &allocation_symbols,
+                ));
+            }
+        }
+    }
+
+    cwe_warnings.sort();
+    cwe_warnings.dedup();
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::IntervalDomain;
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        project.add_mock_extern_symbol("read").parameters = vec![Arg::Register {
+            expr: Expression::var("RDX", 8).subpiece(ByteSize::new(0), ByteSize::new(4)),
+            data_type: Some(Datatype::Integer),
+        }];
+        project.add_mock_extern_symbol("malloc");
+        project
+    }
+
+    #[test]
+    fn truncated_parameters() {
+        let project = mock_project();
+        let global_memory = RuntimeMemoryImage::mock();
+        let state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let allocation_symbols = get_symbol_map(&project, &["malloc".to_string()]);
+
+        // Unknown length
+        let block = Blk::mock_call("block", Vec::new(), "read", "next_block");
+        assert!(check_block(
+            &project,
+            &global_memory,
+            state.clone(),
+            &block,
+            &allocation_symbols
+        )
+        .is_empty());
+
+        // Zero-extended 32-bit length of unknown size
+        let defs = vec![Def::assign(
+            "def",
+            Variable::mock("RDX", 8),
+            Expression::var("RCX", 8)
+                .subpiece(ByteSize::new(0), ByteSize::new(4))
+                .cast(CastOpType::IntZExt),
+        )];
+        let block = Blk::mock_call("block", defs, "read", "next_block");
+        assert!(check_block(
+            &project,
+            &global_memory,
+            state.clone(),
+            &block,
+            &allocation_symbols
+        )
+        .is_empty());
+
+        // Length that is unbounded above
+        let mut unbounded_state = state.clone();
+        unbounded_state.set_register(
+            &Variable::mock("RDX", 8),
+            IntervalDomain::new(
+                Bitvector::from_i64(0x1000),
+                Bitvector::signed_max_value(ByteSize::new(8).into()),
+            )
+            .into(),
+        );
+        let block = Blk::mock_call("block", Vec::new(), "read", "next_block");
+        let warnings = check_block(
+            &project,
+            &global_memory,
+            unbounded_state,
+            &block,
+            &allocation_symbols,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].other,
+            vec![vec![
+                "truncated_value_interval".to_string(),
+                "[4096, unbounded]".to_string()
+            ]]
+        );
+
+        // Negative length
+        let defs = vec![Def::assign(
+            "def",
+            Variable::mock("RDX", 8),
+            Expression::const_from_i64(-1),
+        )];
+        let block = Blk::mock_call("block", defs, "read", "next_block");
+        assert!(check_block(
+            &project,
+            &global_memory,
+            state.clone(),
+            &block,
+            &allocation_symbols
+        )
+        .is_empty());
+
+        // Provably small length
+        let defs = vec![Def::assign(
+            "def",
+            Variable::mock("RDX", 8),
+            Expression::const_from_i64(0x1000),
+        )];
+        let block = Blk::mock_call("block", defs, "read", "next_block");
+        assert!(check_block(
+            &project,
+            &global_memory,
+            state.clone(),
+            &block,
+            &allocation_symbols
+        )
+        .is_empty());
+
+        // Length that does not fit into 32 bits
+        let defs = vec![Def::assign(
+            "def",
+            Variable::mock("RDX", 8),
+            Expression::const_from_i64(0x1_0000_0000),
+        )];
+        let block = Blk::mock_call("block", defs, "read", "next_block");
+        let warnings = check_block(&project, &global_memory, state, &block, &allocation_symbols);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].other,
+            vec![vec![
+                "truncated_value_interval".to_string(),
+                "[4294967296, 4294967296]".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn truncated_allocation_sizes_and_offsets() {
+        let project = mock_project();
+        let global_memory = RuntimeMemoryImage::mock();
+        let state = State::new(&project.stack_pointer_register, Tid::new("func"));
+        let allocation_symbols = get_symbol_map(&project, &["malloc".to_string()]);
+        let large_value_def = Def::assign(
+            "def",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0x1_0000_0000),
+        );
+        let truncating_def = Def::assign(
+            "truncation",
+            Variable::mock("RDI", 8),
+            Expression::var("RAX", 8)
+                .subpiece(ByteSize::new(0), ByteSize::new(4))
+                .cast(CastOpType::IntZExt),
+        );
+
+        // Truncated unknown value used as allocation size
+        let block = Blk::mock_call(
+            "block",
+            vec![truncating_def.clone()],
+            "malloc",
+            "next_block",
+        );
+        assert!(check_block(
+            &project,
+            &global_memory,
+            state.clone(),
+            &block,
+            &allocation_symbols
+        )
+        .is_empty());
+
+        // Truncated large value used as allocation size
+        let block = Blk::mock_call(
+            "block",
+            vec![large_value_def.clone(), truncating_def.clone()],
+            "malloc",
+            "next_block",
+        );
+        let warnings = check_block(
+            &project,
+            &global_memory,
+            state.clone(),
+            &block,
+            &allocation_symbols,
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["truncation".to_string(), "call_block".to_string()]
+        );
+
+        // Truncated large value used as memory offset
+        let load_def = Def::load(
+            "load",
+            Variable::mock("RCX", 8),
+            Expression::var("RSP", 8).plus(Expression::var("RDI", 8)),
+        );
+        let block = Blk::mock_call(
+            "block",
+            vec![large_value_def, truncating_def, load_def],
+            "other",
+            "next_block",
+        );
+        assert_eq!(
+            check_block(
+                &project,
+                &global_memory,
+                state.clone(),
+                &block,
+                &allocation_symbols
+            )
+            .len(),
+            1
+        );
+
+        // Truncation of a provably small value
+        let defs = vec![
+            Def::assign(
+                "def",
+                Variable::mock("RAX", 8),
+                Expression::const_from_i64(42),
+            ),
+            Def::assign(
+                "truncation",
+                Variable::mock("RDI", 8),
+                Expression::var("RAX", 8)
+                    .subpiece(ByteSize::new(0), ByteSize::new(4))
+                    .cast(CastOpType::IntZExt),
+            ),
+        ];
+        let block = Blk::mock_call("block", defs, "malloc", "next_block");
+        assert!(
+            check_block(&project, &global_memory, state, &block, &allocation_symbols).is_empty()
+        );
+    }
+
+    #[test]
+    fn truncation_to_large_sizes() {
+        let small_value: Data = Bitvector::from_u64(42)
+            .into_zero_extend(ByteSize::new(32))
+            .unwrap()
+            .into();
+        assert!(!truncation_may_change_value(
+            &small_value,
+            ByteSize::new(16)
+        ));
+        let large_value: Data = Bitvector::signed_max_value(ByteSize::new(32).into()).into();
+        assert!(truncation_may_change_value(&large_value, ByteSize::new(16)));
+    }
+}
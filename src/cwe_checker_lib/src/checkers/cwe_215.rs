@@ -27,6 +27,9 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE215",
     version: "0.2",
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// Run the check.
@@ -38,6 +38,9 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE243",
     version: "0.2",
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// The configuration struct contains the list of functions
@@ -97,10 +100,10 @@ fn generate_cwe_warning(sub: &Term<Sub>, callsite: &Tid) -> CweWarning {
         CWE_MODULE.version,
         format!(
             "(The program utilizes chroot without dropping privileges and/or changing the directory) at {} ({})",
-            callsite.address, sub.term.name
+            callsite.canonical_address(), sub.term.name
         ))
         .tids(vec![format!("{}", callsite)])
-        .addresses(vec![callsite.address.clone()])
+        .addresses(vec![callsite.canonical_address().to_string()])
         .symbols(vec![sub.term.name.clone()])
 }
 
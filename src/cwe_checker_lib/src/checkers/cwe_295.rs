@@ -0,0 +1,469 @@
+//! This module implements a check for CWE-295: Improper Certificate Validation.
+//!
+//! The software does not validate, or incorrectly validates, a certificate.
+//! For programs using libcurl this usually means that the verification of the peer certificate
+//! or of the host name was explicitly disabled, that an outdated SSL protocol version was selected
+//! or that credentials are sent over an unencrypted connection.
+//!
+//! See <https://cwe.mitre.org/data/definitions/295.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to `curl_easy_setopt(handle, option, value)` the option and the value parameter
+//! are evaluated using the results of the pointer inference analysis.
+//! The known option constants are contained in the [`CurlOption`] table of this module.
+//! A CWE warning is generated if
+//! - `CURLOPT_SSL_VERIFYPEER` or `CURLOPT_SSL_VERIFYHOST` is set to 0,
+//! - `CURLOPT_SSLVERSION` selects SSLv2 or SSLv3,
+//! - a constant URL starting with `http://` is set through `CURLOPT_URL`
+//!   and a credentials option is set on the same handle in the same function.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The names of the `curl_easy_setopt` function (and its equivalents).
+//!
+//! ## False Positives
+//!
+//! - Verification may be disabled on purpose, e.g. in test builds.
+//! - Two handles are only considered to be different if both are known to the pointer inference.
+//!   Thus credentials set on another handle may be attributed to the handle of an unencrypted URL.
+//!
+//! ## False Negatives
+//!
+//! - Handles for which verification is never configured are not reported,
+//!   since libcurl verifies certificates by default.
+//! - Option values that are not known constants at the callsite are not checked.
+//! - Credentials set in a different function than the URL are not detected.
+
+use petgraph::visit::EdgeRef;
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::graph::{Edge, Node};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::Data;
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::arguments::calculate_parameter_locations;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE295",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// The names of functions with the signature of `curl_easy_setopt`.
+    symbols: Vec<String>,
+}
+
+/// The libcurl options relevant for the check.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CurlOption {
+    /// `CURLOPT_SSL_VERIFYPEER`: Verify the certificate of the peer.
+    SslVerifyPeer,
+    /// `CURLOPT_SSL_VERIFYHOST`: Verify that the certificate matches the host name.
+    SslVerifyHost,
+    /// `CURLOPT_SSLVERSION`: The SSL/TLS protocol version to use.
+    SslVersion,
+    /// `CURLOPT_URL`: The URL to connect to.
+    Url,
+    /// `CURLOPT_USERPWD`: User name and password for the connection.
+    UserPwd,
+    /// `CURLOPT_USERNAME`: User name for the connection.
+    Username,
+    /// `CURLOPT_PASSWORD`: Password for the connection.
+    Password,
+    /// `CURLOPT_XOAUTH2_BEARER`: OAuth 2.0 bearer token for the connection.
+    XOauth2Bearer,
+}
+
+impl CurlOption {
+    /// All options of the table.
+    const ALL: [CurlOption; 8] = [
+        CurlOption::SslVerifyPeer,
+        CurlOption::SslVerifyHost,
+        CurlOption::SslVersion,
+        CurlOption::Url,
+        CurlOption::UserPwd,
+        CurlOption::Username,
+        CurlOption::Password,
+        CurlOption::XOauth2Bearer,
+    ];
+
+    /// The value of the option constant in `curl.h`.
+    pub fn value(&self) -> u64 {
+        match self {
+            CurlOption::SslVerifyPeer => 64,
+            CurlOption::SslVerifyHost => 81,
+            CurlOption::SslVersion => 32,
+            CurlOption::Url => 10002,
+            CurlOption::UserPwd => 10005,
+            CurlOption::Username => 10173,
+            CurlOption::Password => 10174,
+            CurlOption::XOauth2Bearer => 10220,
+        }
+    }
+
+    /// The name of the option constant in `curl.h`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CurlOption::SslVerifyPeer => "CURLOPT_SSL_VERIFYPEER",
+            CurlOption::SslVerifyHost => "CURLOPT_SSL_VERIFYHOST",
+            CurlOption::SslVersion => "CURLOPT_SSLVERSION",
+            CurlOption::Url => "CURLOPT_URL",
+            CurlOption::UserPwd => "CURLOPT_USERPWD",
+            CurlOption::Username => "CURLOPT_USERNAME",
+            CurlOption::Password => "CURLOPT_PASSWORD",
+            CurlOption::XOauth2Bearer => "CURLOPT_XOAUTH2_BEARER",
+        }
+    }
+
+    /// Get the option corresponding to the given option constant value.
+    pub fn from_value(value: u64) -> Option<CurlOption> {
+        CurlOption::ALL
+            .into_iter()
+            .find(|option| option.value() == value)
+    }
+
+    /// Returns `true` if the option sets credentials for the connection.
+    pub fn is_credential(&self) -> bool {
+        matches!(
+            self,
+            CurlOption::UserPwd
+                | CurlOption::Username
+                | CurlOption::Password
+                | CurlOption::XOauth2Bearer
+        )
+    }
+}
+
+/// The value of `CURL_SSLVERSION_SSLv2`.
+const CURL_SSLVERSION_SSLV2: u64 = 2;
+/// The value of `CURL_SSLVERSION_SSLv3`.
+const CURL_SSLVERSION_SSLV3: u64 = 3;
+
+/// A call to `curl_easy_setopt` with a known option.
+struct SetoptCall<'a> {
+    /// The call instruction.
+    jmp: &'a Term<Jmp>,
+    /// The name of the called symbol.
+    symbol_name: &'a str,
+    /// The function containing the call.
+    sub: &'a Term<Sub>,
+    /// The option set by the call.
+    option: CurlOption,
+    /// The curl handle parameter.
+    handle: Data,
+    /// The value parameter.
+    value: Data,
+}
+
+impl SetoptCall<'_> {
+    /// Returns `true` if both calls may operate on the same handle.
+    /// Handles are only known to be different if both are known to the pointer inference.
+    fn may_share_handle_with(&self, other: &SetoptCall) -> bool {
+        self.handle.contains_top() || other.handle.contains_top() || self.handle == other.handle
+    }
+
+    /// Get the value parameter if it is a known constant.
+    fn constant_value(&self) -> Option<u64> {
+        self.value.try_to_bitvec().ok()?.try_to_u64().ok()
+    }
+}
+
+/// Get the argument holding the value parameter of a call to a `curl_easy_setopt` symbol.
+///
+/// Since `curl_easy_setopt` is a variadic function, the third parameter is usually missing
+/// in the signature of the symbol and has to be computed from the calling convention.
+fn get_value_parameter(project: &Project, symbol: &ExternSymbol) -> Option<Arg> {
+    if let Some(parameter) = symbol.parameters.get(2) {
+        return Some(parameter.clone());
+    }
+    calculate_parameter_locations(
+        vec![(Datatype::Pointer, project.datatype_properties.pointer_size)],
+        project.get_calling_convention(symbol),
+        1,
+        &project.stack_pointer_register,
+        &project.architecture,
+    )
+    .pop()
+}
+
+/// Collect all calls to `curl_easy_setopt` symbols where the option is a known constant
+/// from the [`CurlOption`] table.
+fn collect_setopt_calls<'a>(
+    analysis_results: &'a AnalysisResults,
+    symbols: &'a [String],
+) -> Vec<SetoptCall<'a>> {
+    let project = analysis_results.project;
+    let symbol_map = get_symbol_map(project, symbols);
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let graph = pointer_inference_results.get_graph();
+    let mut calls = Vec::new();
+
+    for edge in graph.edge_references() {
+        let (jmp, sub) = match (edge.weight(), graph[edge.source()]) {
+            (Edge::ExternCallStub(jmp), Node::BlkEnd(_, sub)) => (*jmp, sub),
+            _ => continue,
+        };
+        let symbol = match &jmp.term {
+            Jmp::Call { target, .. } => match symbol_map.get(target) {
+                Some(symbol) => *symbol,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let (handle_parameter, option_parameter, value_parameter) = match (
+            symbol.parameters.first(),
+            symbol.parameters.get(1),
+            get_value_parameter(project, symbol),
+        ) {
+            (Some(handle), Some(option), Some(value)) => (handle, option, value),
+            _ => continue,
+        };
+        let pi_state = match pointer_inference_results.get_node_value(edge.source()) {
+            Some(NodeValue::Value(state)) => state,
+            _ => continue,
+        };
+        let global_memory = analysis_results.runtime_memory_image;
+        let option = match pi_state
+            .eval_parameter_arg(option_parameter, global_memory)
+            .ok()
+            .and_then(|option| option.try_to_bitvec().ok())
+            .and_then(|option| option.try_to_u64().ok())
+            .and_then(CurlOption::from_value)
+        {
+            Some(option) => option,
+            None => continue,
+        };
+        let (handle, value) = match (
+            pi_state.eval_parameter_arg(handle_parameter, global_memory),
+            pi_state.eval_parameter_arg(&value_parameter, global_memory),
+        ) {
+            (Ok(handle), Ok(value)) => (handle, value),
+            _ => continue,
+        };
+        calls.push(SetoptCall {
+            jmp,
+            symbol_name: &symbol.name,
+            sub,
+            option,
+            handle,
+            value,
+        });
+    }
+    calls
+}
+
+/// If the value of the call is a pointer to a constant URL using plain HTTP, return the URL.
+fn get_unencrypted_url(call: &SetoptCall, global_memory: &RuntimeMemoryImage) -> Option<String> {
+    let address = call.value.try_to_bitvec().ok()?;
+    let url = global_memory
+        .read_string_until_null_terminator(&address)
+        .ok()?;
+    if url.to_lowercase().starts_with("http://") {
+        Some(url.to_string())
+    } else {
+        None
+    }
+}
+
+/// Check whether the call disables certificate verification or selects an insecure protocol version.
+/// Returns a description of the problem if this is the case.
+fn get_insecure_setting(call: &SetoptCall) -> Option<String> {
+    let value = call.constant_value()?;
+    match call.option {
+        CurlOption::SslVerifyPeer | CurlOption::SslVerifyHost if value == 0 => {
+            Some(format!("disables {}", call.option.name()))
+        }
+        CurlOption::SslVersion => {
+            // The upper 16 bits may contain the maximum allowed version.
+            match value & 0xffff {
+                CURL_SSLVERSION_SSLV2 => Some("selects the insecure protocol SSLv2".to_string()),
+                CURL_SSLVERSION_SSLV3 => Some("selects the insecure protocol SSLv3".to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Check all calls to `curl_easy_setopt` for disabled certificate verification,
+/// insecure SSL protocol versions and credentials sent over unencrypted connections.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let calls = collect_setopt_calls(analysis_results, &config.symbols);
+    let mut cwe_warnings = Vec::new();
+
+    for call in calls.iter() {
+        if let Some(problem) = get_insecure_setting(call) {
+            cwe_warnings.push(generate_cwe_warning(call, &problem));
+        }
+        if call.option != CurlOption::Url {
+            continue;
+        }
+        if let Some(url) = get_unencrypted_url(call, analysis_results.runtime_memory_image) {
+            let credential_call = calls.iter().find(|other| {
+                other.option.is_credential()
+                    && other.sub.tid == call.sub.tid
+                    && call.may_share_handle_with(other)
+            });
+            if let Some(credential_call) = credential_call {
+                let problem = format!(
+                    "sets the unencrypted URL {} while {} is set on the same handle",
+                    url,
+                    credential_call.option.name()
+                );
+                cwe_warnings.push(generate_cwe_warning(call, &problem));
+            }
+        }
+    }
+
+    cwe_warnings.sort();
+    (Vec::new(), cwe_warnings)
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(call: &SetoptCall, problem: &str) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Improper Certificate Validation) Call to {} at {} in function {} {}",
+            call.symbol_name,
+            call.jmp.tid.canonical_address(),
+            call.sub.term.name,
+            problem
+        ),
+    )
+    .tids(vec![format!("{}", call.jmp.tid)])
+    .addresses(vec![call.jmp.tid.canonical_address().to_string()])
+    .symbols(vec![call.symbol_name.to_string()])
+    .other(vec![vec![
+        "curl_option".to_string(),
+        call.option.name().to_string(),
+    ]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project whose `main` function calls `curl_easy_setopt`
+    /// once for each of the given `(option, value)` pairs on the same handle.
+    fn mock_project(settings: &[(u64, i64)]) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![
+                Variable::mock("RDI", 8),
+                Variable::mock("RSI", 8),
+                Variable::mock("RDX", 8),
+            ],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project
+            .add_mock_extern_symbol("curl_easy_setopt")
+            .parameters = vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)];
+
+        let mut sub = Sub::mock("main");
+        for (index, (option, value)) in settings.iter().enumerate() {
+            let mut block = Blk::mock_with_tid(&format!("blk_{}", index));
+            block.term.defs = vec![
+                Def::assign(
+                    &format!("set_handle_{}", index),
+                    Variable::mock("RDI", 8),
+                    Expression::const_from_i64(0x2000),
+                ),
+                Def::assign(
+                    &format!("set_option_{}", index),
+                    Variable::mock("RSI", 8),
+                    Expression::const_from_i64(*option as i64),
+                ),
+                Def::assign(
+                    &format!("set_value_{}", index),
+                    Variable::mock("RDX", 8),
+                    Expression::const_from_i64(*value),
+                ),
+            ];
+            block.term.jmps = vec![Jmp::call(
+                &format!("call_setopt_{}", index),
+                "curl_easy_setopt",
+                Some(&format!("blk_{}", index + 1)),
+            )];
+            sub.term.blocks.push(block);
+        }
+        sub.term
+            .blocks
+            .push(Blk::mock_with_tid(&format!("blk_{}", settings.len())));
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("main"));
+        project
+    }
+
+    /// Run the check on a project setting the given `(option, value)` pairs.
+    fn run_check(settings: &[(u64, i64)]) -> Vec<CweWarning> {
+        let project = mock_project(settings);
+        let config = serde_json::json!({ "symbols": ["curl_easy_setopt"] });
+        AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn disabled_peer_verification() {
+        let warnings = run_check(&[(CurlOption::SslVerifyPeer.value(), 0)]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].tids, vec!["call_setopt_0".to_string()]);
+        assert_eq!(
+            warnings[0].other,
+            vec![vec![
+                "curl_option".to_string(),
+                "CURLOPT_SSL_VERIFYPEER".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn properly_configured_handle() {
+        // The mock memory image contains "https://example.com" at address 0x8000.
+        let warnings = run_check(&[
+            (CurlOption::SslVerifyPeer.value(), 1),
+            (CurlOption::SslVerifyHost.value(), 2),
+            (CurlOption::SslVersion.value(), 6),
+            (CurlOption::Url.value(), 0x8000),
+            (CurlOption::UserPwd.value(), 0x3002),
+        ]);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn insecure_protocol_and_unencrypted_credentials() {
+        // The mock memory image contains "http://example.com" at address 0x8014.
+        let warnings = run_check(&[
+            (CurlOption::SslVersion.value(), 3),
+            (CurlOption::Url.value(), 0x8014),
+            (CurlOption::UserPwd.value(), 0x3002),
+        ]);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].tids, vec!["call_setopt_0".to_string()]);
+        assert_eq!(warnings[1].tids, vec!["call_setopt_1".to_string()]);
+        // Without credentials the unencrypted URL is not reported.
+        assert!(run_check(&[(CurlOption::Url.value(), 0x8014)]).is_empty());
+    }
+}
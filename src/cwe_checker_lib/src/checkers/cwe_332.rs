@@ -30,6 +30,9 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE332",
     version: "0.1",
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// The configuration struct contains pairs of symbol names,
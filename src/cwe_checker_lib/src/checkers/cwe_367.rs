@@ -7,43 +7,70 @@
 //!
 //! See <https://cwe.mitre.org/data/definitions/367.html> for a detailed description.
 //!
+//! A variant of this race condition are double fetches from memory shared with other processes,
+//! e.g. memory mapped by `mmap` with the `MAP_SHARED` flag.
+//! If a value in shared memory (like a length field) is read once for a check and read again for its use,
+//! another process may change the value between the two reads.
+//!
 //! ## How the check works
 //!
 //! For pairs of (check-call, use-call), configurable in config.json, we check whether
 //! a function may call the check-call before the use-call.
 //!
+//! If the results of the pointer inference analysis are available, we also check for double fetches.
+//! For this we search for two loads from the same constant offset of the same shared memory object in one function,
+//! where a conditional jump depends on the value of the first load
+//! and a memory access or a parameter of an extern function call (e.g. a copy length)
+//! depends on the value of the second load.
+//! The sources of shared memory are configurable in config.json.
+//! The memory objects returned by them have to be tracked by the pointer inference analysis,
+//! e.g. by configuring the source as a custom allocator.
+//!
 //! ## False Positives
 //!
 //! - The check-call and the use-call may access different, unrelated resources
 //! (e. g. different files).
+//! - The shared memory may not be writeable by other processes.
 //!
 //! ## False Negatives
 //!
 //! - If the check-call and the use-call happen in different functions it will not
 //!   be found by the check.
+//! - For double fetches the dependencies of the loaded values are only tracked through registers
+//!   inside the basic block containing the load.
 
 use crate::analysis::graph::{Edge, Node};
 use crate::intermediate_representation::Jmp;
 use crate::prelude::*;
 use crate::utils::graph_utils::is_sink_call_reachable_from_source_call;
 use crate::utils::log::{CweWarning, LogMessage};
-use crate::CweModule;
+use crate::{CweModule, SharedAnalysis};
 use petgraph::visit::EdgeRef;
 use std::collections::HashMap;
 
+mod double_fetch;
+use double_fetch::*;
+
 /// The module name and version
 pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE367",
-    version: "0.1",
+    version: "0.2",
     run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
 };
 
 /// The configuration struct contains pairs of the form `(source_symbol, sink_symbol)`.
 /// The `source_symbol` corresponds to a check-call and the `sink_symbol` corresponds to a use-call.
 /// An execution path from a source call to a sink call corresponds to a possible Time-of-check Time-of-use Race Condition.
+///
+/// The `shared_memory_sources` are the extern symbols returning pointers to shared memory
+/// used for the double fetch check.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 struct Config {
     pairs: Vec<(String, String)>,
+    #[serde(default)]
+    shared_memory_sources: Vec<SharedMemorySource>,
 }
 
 /// Generate a CWE warning for a found CWE hit.
@@ -59,10 +86,10 @@ fn generate_cwe_warning(
         CWE_MODULE.version,
         format!(
             "(Time-of-check Time-of-use Race Condition) '{}' is reachable from '{}' at {} ({}). This could lead to a TOCTOU.",
-            sink, source, sink_callsite.address, sub_name
+            sink, source, sink_callsite.canonical_address(), sub_name
         ))
         .tids(vec![format!("{}", source_callsite), format!("{}", sink_callsite)])
-        .addresses(vec![source_callsite.address, sink_callsite.address])
+        .addresses(vec![source_callsite.canonical_address().to_string(), sink_callsite.canonical_address().to_string()])
         .symbols(vec![source.into(), sink.into()])
 }
 
@@ -119,6 +146,11 @@ pub fn check_cwe(
         }
     }
 
+    cwe_warnings.append(&mut check_double_fetches(
+        analysis_results,
+        &config.shared_memory_sources,
+    ));
+
     cwe_warnings.sort();
     (Vec::new(), cwe_warnings)
 }
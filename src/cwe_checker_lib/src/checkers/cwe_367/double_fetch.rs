@@ -0,0 +1,466 @@
+//! Detection of double fetches from memory shared with other processes.
+//!
+//! A double fetch happens if a value in shared memory (e.g. a length field) is read once for a check
+//! and read a second time for its use.
+//! Another process with access to the shared memory may change the value between the two reads,
+//! which invalidates the check.
+//!
+//! Shared memory objects are objects returned by the configured [`SharedMemorySource`] symbols.
+//! Only objects tracked by the pointer inference analysis can be recognized,
+//! i.e. the source symbols also have to be configured as (custom) allocators of the pointer inference.
+
+use super::CWE_MODULE;
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec};
+use crate::analysis::graph::{Edge, Graph, Node};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{PointerInference, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::CweWarning;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::{EdgeRef, IntoNodeReferences};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// An extern symbol returning a pointer to memory that may be shared with other, untrusted processes.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct SharedMemorySource {
+    /// The name of the extern symbol.
+    pub symbol: String,
+    /// If set, the returned memory is only considered to be shared
+    /// if the flags parameter of the call may contain the given flag.
+    #[serde(default)]
+    pub flag_parameter: Option<FlagParameter>,
+}
+
+/// A flag that has to be set in a parameter of a call to a [`SharedMemorySource`]
+/// for the returned memory to be shared, e.g. `MAP_SHARED` for `mmap`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct FlagParameter {
+    /// The index of the flags parameter.
+    pub index: usize,
+    /// The value of the flag.
+    pub flag: u64,
+}
+
+/// A load instruction reading from a constant offset of a shared memory object.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct SharedLoad {
+    /// The TID of the load instruction.
+    tid: Tid,
+    /// The index of the load instruction in its basic block.
+    def_index: usize,
+    /// The shared memory object that is read.
+    object: AbstractIdentifier,
+    /// The offset inside the shared memory object that is read.
+    offset: i64,
+}
+
+/// The shared memory loads of a basic block whose loaded values are used for checks or for accesses.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct BlockLoads {
+    /// Loads whose values the condition of a conditional jump depends on.
+    checks: Vec<SharedLoad>,
+    /// Loads whose values the address of a memory access
+    /// or a parameter of an extern function call (e.g. a copy length) depends on.
+    uses: Vec<SharedLoad>,
+}
+
+/// Collect the call sites of shared memory sources.
+///
+/// If a source has a flag parameter, the call is only collected
+/// if the value of the flags parameter is unknown or contains the flag.
+fn get_shared_memory_source_calls(
+    project: &Project,
+    pointer_inference: &PointerInference,
+    global_memory: &RuntimeMemoryImage,
+    sources: &[SharedMemorySource],
+) -> HashSet<Tid> {
+    let graph = pointer_inference.get_graph();
+    let mut source_calls = HashSet::new();
+    for (node_id, node) in graph.node_references() {
+        let (call, target) = match node {
+            Node::BlkEnd(block, _) => match block.term.jmps.first() {
+                Some(
+                    call @ Term {
+                        term: Jmp::Call { target, .. },
+                        ..
+                    },
+                ) => (call, target),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let (symbol, source) = match project.program.term.extern_symbols.get(target) {
+            Some(symbol) => match sources.iter().find(|source| source.symbol == symbol.name) {
+                Some(source) => (symbol, source),
+                None => continue,
+            },
+            None => continue,
+        };
+        let is_shared = match (
+            &source.flag_parameter,
+            pointer_inference.get_node_value(node_id),
+        ) {
+            (Some(flag_parameter), Some(NodeValue::Value(state))) => symbol
+                .parameters
+                .get(flag_parameter.index)
+                .and_then(|parameter| state.eval_parameter_arg(parameter, global_memory).ok())
+                .and_then(|flags| flags.get_if_absolute_value()?.try_to_bitvec().ok())
+                .and_then(|flags| flags.try_to_u64().ok())
+                .is_none_or(|flags| flags & flag_parameter.flag != 0),
+            _ => true,
+        };
+        if is_shared {
+            source_calls.insert(call.tid.clone());
+        }
+    }
+    source_calls
+}
+
+/// Return the shared memory object and the offset inside it that the given address points to
+/// if the address has a unique target created by one of the given source calls and the offset is constant.
+fn get_shared_memory_target(
+    address: &Expression,
+    state: &State,
+    source_calls: &HashSet<Tid>,
+) -> Option<(AbstractIdentifier, i64)> {
+    let address = state.eval(address);
+    let (object, offset) = address.get_if_unique_target()?;
+    if source_calls.contains(object.get_tid()) {
+        Some((object.clone(), offset.try_to_offset().ok()?))
+    } else {
+        None
+    }
+}
+
+/// Return the indices of the loads that the value of the given expression depends on.
+fn get_dependencies(
+    expression: &Expression,
+    dependencies: &HashMap<Variable, BTreeSet<usize>>,
+) -> BTreeSet<usize> {
+    expression
+        .input_vars()
+        .into_iter()
+        .filter_map(|var| dependencies.get(var))
+        .flatten()
+        .copied()
+        .collect()
+}
+
+/// Find the loads from shared memory in the given block and classify them by the usage of the loaded values.
+///
+/// The `state` is the pointer inference state at the start of the block.
+/// The def-use chains of loaded values are only tracked inside the block
+/// and only through registers, i.e. a value copied to a local variable in memory is no longer tracked.
+fn analyze_block(
+    global_memory: &RuntimeMemoryImage,
+    mut state: State,
+    block: &Term<Blk>,
+    project: &Project,
+    source_calls: &HashSet<Tid>,
+) -> BlockLoads {
+    let mut loads: Vec<SharedLoad> = Vec::new();
+    let mut dependencies: HashMap<Variable, BTreeSet<usize>> = HashMap::new();
+    let mut check_indices = BTreeSet::new();
+    let mut use_indices = BTreeSet::new();
+    for (def_index, def) in block.term.defs.iter().enumerate() {
+        match &def.term {
+            Def::Assign { var, value } => {
+                let value_dependencies = get_dependencies(value, &dependencies);
+                dependencies.insert(var.clone(), value_dependencies);
+                state.handle_register_assign(var, value);
+            }
+            Def::Load { var, address } => {
+                use_indices.append(&mut get_dependencies(address, &dependencies));
+                match get_shared_memory_target(address, &state, source_calls) {
+                    Some((object, offset)) => {
+                        dependencies.insert(var.clone(), BTreeSet::from([loads.len()]));
+                        loads.push(SharedLoad {
+                            tid: def.tid.clone(),
+                            def_index,
+                            object,
+                            offset,
+                        });
+                    }
+                    None => {
+                        dependencies.remove(var);
+                    }
+                }
+                let _ = state.handle_load(var, address, global_memory);
+            }
+            Def::Store { address, value } => {
+                use_indices.append(&mut get_dependencies(address, &dependencies));
+                let _ = state.handle_store(address, value, global_memory);
+            }
+        }
+    }
+    for jmp in block.term.jmps.iter() {
+        match &jmp.term {
+            Jmp::CBranch { condition, .. } => {
+                check_indices.append(&mut get_dependencies(condition, &dependencies));
+            }
+            Jmp::Call { target, .. } => {
+                if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                    for parameter in symbol.parameters.iter() {
+                        if let Arg::Register { expr, .. } = parameter {
+                            use_indices.append(&mut get_dependencies(expr, &dependencies));
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    BlockLoads {
+        checks: check_indices.iter().map(|i| loads[*i].clone()).collect(),
+        uses: use_indices.iter().map(|i| loads[*i].clone()).collect(),
+    }
+}
+
+/// Return the set of `BlkStart` nodes reachable from the given `BlkEnd` node
+/// without leaving the function, i.e. calls are skipped over.
+fn get_intraprocedurally_reachable_blocks(graph: &Graph, start: NodeIndex) -> HashSet<NodeIndex> {
+    let mut visited = HashSet::from([start]);
+    let mut worklist = vec![start];
+    let mut reachable_blocks = HashSet::new();
+    while let Some(node) = worklist.pop() {
+        for edge in graph.edges(node) {
+            if let Edge::Block | Edge::Jump(..) | Edge::ExternCallStub(_) | Edge::CrCallStub =
+                edge.weight()
+            {
+                if visited.insert(edge.target()) {
+                    if let Node::BlkStart(..) = graph[edge.target()] {
+                        reachable_blocks.insert(edge.target());
+                    }
+                    worklist.push(edge.target());
+                }
+            }
+        }
+    }
+    reachable_blocks
+}
+
+/// Generate the CWE warning for a double fetch.
+fn generate_cwe_warning(check: &SharedLoad, usage: &SharedLoad, sub_name: &str) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Time-of-check Time-of-use Race Condition) Shared memory at {} + {} is read at {} for a check and read again at {} for its use ({}). The value may change between the reads.",
+            check.object, check.offset, check.tid.canonical_address(), usage.tid.canonical_address(), sub_name
+        ))
+        .tids(vec![format!("{}", check.tid), format!("{}", usage.tid)])
+        .addresses(vec![check.tid.canonical_address().to_string(), usage.tid.canonical_address().to_string()])
+        .other(vec![vec![
+            "double_fetch".to_string(),
+            format!("{}", check.tid),
+            format!("{}", usage.tid),
+        ]])
+}
+
+/// Check for double fetches from shared memory.
+///
+/// For each function we search for pairs of loads from the same constant offset of the same shared memory object,
+/// where the first loaded value is used in a check (i.e. a conditional jump)
+/// and the second loaded value is used for a memory access or as a parameter of an extern function call.
+/// The second load has to be reachable from the first load inside the function.
+pub fn check_double_fetches(
+    analysis_results: &AnalysisResults,
+    sources: &[SharedMemorySource],
+) -> Vec<CweWarning> {
+    let pointer_inference = match analysis_results.pointer_inference {
+        Some(pointer_inference) => pointer_inference,
+        None => return Vec::new(),
+    };
+    let project = analysis_results.project;
+    let global_memory = analysis_results.runtime_memory_image;
+    let graph = pointer_inference.get_graph();
+    let source_calls =
+        get_shared_memory_source_calls(project, pointer_inference, global_memory, sources);
+    if source_calls.is_empty() {
+        return Vec::new();
+    }
+
+    let mut loads_per_sub: BTreeMap<&Tid, Vec<(NodeIndex, BlockLoads)>> = BTreeMap::new();
+    for (node_id, node) in graph.node_references() {
+        if let Node::BlkStart(block, sub) = node {
+            if let Some(NodeValue::Value(state)) = pointer_inference.get_node_value(node_id) {
+                let block_loads =
+                    analyze_block(global_memory, state.clone(), block, project, &source_calls);
+                if !block_loads.checks.is_empty() || !block_loads.uses.is_empty() {
+                    loads_per_sub
+                        .entry(&sub.tid)
+                        .or_default()
+                        .push((node_id, block_loads));
+                }
+            }
+        }
+    }
+
+    let mut cwe_warnings = Vec::new();
+    for block_loads in loads_per_sub.values() {
+        for (check_node, check_block_loads) in block_loads.iter() {
+            if check_block_loads.checks.is_empty() {
+                continue;
+            }
+            // The BlkEnd node directly follows the BlkStart node of a block.
+            let check_block_end = graph
+                .neighbors(*check_node)
+                .find(|node| matches!(graph[*node], Node::BlkEnd(..)))
+                .unwrap();
+            let reachable_blocks = get_intraprocedurally_reachable_blocks(graph, check_block_end);
+            for check in check_block_loads.checks.iter() {
+                for (use_node, use_block_loads) in block_loads.iter() {
+                    for usage in use_block_loads.uses.iter() {
+                        let is_reachable = if use_node == check_node {
+                            check.def_index < usage.def_index || reachable_blocks.contains(use_node)
+                        } else {
+                            reachable_blocks.contains(use_node)
+                        };
+                        if is_reachable
+                            && check.tid != usage.tid
+                            && check.object == usage.object
+                            && check.offset == usage.offset
+                        {
+                            cwe_warnings.push(generate_cwe_warning(
+                                check,
+                                usage,
+                                &graph[*check_node].get_sub().term.name,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    cwe_warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project whose `main` function reads a length field from memory returned by `malloc`
+    /// and checks it before calling `memcpy` with a length that is either read again from the object
+    /// or read from a local copy of the length.
+    fn mock_project(copy_to_local: bool) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![
+                Variable::mock("RDI", 8),
+                Variable::mock("RSI", 8),
+                Variable::mock("RDX", 8),
+            ],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("malloc");
+        project.add_mock_extern_symbol("memcpy").parameters = vec![
+            Arg::mock_register("RDI", 8),
+            Arg::mock_register("RSI", 8),
+            Arg::mock_register("RDX", 8),
+        ];
+
+        let mut alloc_blk = Blk::mock_with_tid("alloc_blk");
+        alloc_blk
+            .term
+            .jmps
+            .push(Jmp::call("call_malloc", "malloc", Some("check_blk")));
+
+        let mut check_blk = Blk::mock_with_tid("check_blk");
+        check_blk.term.defs = vec![
+            Def::assign(
+                "save_ptr",
+                Variable::mock("RBX", 8),
+                Expression::var("RAX", 8),
+            ),
+            Def::load(
+                "first_fetch",
+                Variable::mock("RCX", 8),
+                Expression::var("RAX", 8),
+            ),
+        ];
+        if copy_to_local {
+            check_blk.term.defs.push(Def::store(
+                "copy_to_local",
+                Expression::var("RSP", 8).plus_const(-8),
+                Expression::var("RCX", 8),
+            ));
+        }
+        check_blk.term.jmps = vec![
+            Term {
+                tid: Tid::new("check_len"),
+                term: Jmp::CBranch {
+                    target: Tid::new("error_blk"),
+                    condition: Expression::BinOp {
+                        op: BinOpType::IntLess,
+                        lhs: Box::new(Expression::const_from_i64(100)),
+                        rhs: Box::new(Expression::var("RCX", 8)),
+                    },
+                },
+            },
+            Jmp::branch("to_copy_blk", "copy_blk"),
+        ];
+
+        let mut copy_blk = Blk::mock_with_tid("copy_blk");
+        let second_fetch_address = if copy_to_local {
+            Expression::var("RSP", 8).plus_const(-8)
+        } else {
+            Expression::var("RBX", 8)
+        };
+        copy_blk.term.defs = vec![Def::load(
+            "second_fetch",
+            Variable::mock("RDX", 8),
+            second_fetch_address,
+        )];
+        copy_blk
+            .term
+            .jmps
+            .push(Jmp::call("call_memcpy", "memcpy", Some("return_blk")));
+
+        let main = Sub::mock_with_blocks(
+            "main",
+            vec![
+                alloc_blk,
+                check_blk,
+                copy_blk,
+                Blk::mock_return("error_blk"),
+                Blk::mock_return("return_blk"),
+            ],
+        );
+        project.program.term.entry_points.insert(main.tid.clone());
+        project.program.term.subs.insert(main.tid.clone(), main);
+        project
+    }
+
+    /// Run the double fetch check on the given project with `malloc` as the shared memory source.
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let sources = vec![SharedMemorySource {
+            symbol: "malloc".to_string(),
+            flag_parameter: None,
+        }];
+        AnalysisResults::run_with_mock_pointer_inference(project, |analysis_results| {
+            check_double_fetches(analysis_results, &sources)
+        })
+    }
+
+    #[test]
+    fn double_fetch() {
+        let project = mock_project(false);
+        let warnings = run_check(&project);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec!["first_fetch".to_string(), "second_fetch".to_string()]
+        );
+    }
+
+    #[test]
+    fn fetch_once_into_local() {
+        let project = mock_project(true);
+        assert!(run_check(&project).is_empty());
+    }
+}
@@ -0,0 +1,644 @@
+//! This module implements a check for CWE-403: Exposure of File Descriptor to Unintended Control Sphere.
+//!
+//! File descriptors stay open across calls to `exec`-like functions unless they are marked as close-on-exec.
+//! If a privileged program executes another program,
+//! the new program inherits all such descriptors and may use them to access resources
+//! (e.g. files opened with elevated privileges) that it should not be able to access.
+//!
+//! See <https://cwe.mitre.org/data/definitions/403.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to a function creating file descriptors (e.g. `open`, `socket`, `accept` or `pipe`)
+//! we evaluate its flags parameter (if it has one) using the results of the pointer inference analysis.
+//! If the `O_CLOEXEC` flag is known to be set
+//! (see [`KnownBits`](crate::abstract_domain::KnownBits)), the descriptor is not tracked further.
+//!
+//! Otherwise we track the created descriptor through registers and memory
+//! with the shared [taint tracking](crate::analysis::taint)
+//! and search for a path from the creating call to a call to an `exec`-like function,
+//! either in the same function or in a function called by it (up to a configurable call depth).
+//! Paths on which the descriptor is marked as close-on-exec by `fcntl(fd, F_SETFD, FD_CLOEXEC)`
+//! or closed by `close(fd)` are not followed further.
+//! For each remaining path a CWE warning is generated.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The descriptor creating functions together with the index of their flags parameter
+//!   and, for functions like `pipe`, the index of the parameter pointing to the created descriptors.
+//! - The `exec`-like functions.
+//! - The values of the `O_CLOEXEC`, `F_SETFD` and `FD_CLOEXEC` constants.
+//!
+//! ## False Positives
+//!
+//! - The descriptor may be intended to be inherited by the executed program,
+//!   e.g. when redirecting the standard input or output of the child process.
+//! - If the descriptor is copied to a memory location that the pointer inference analysis cannot track,
+//!   a subsequent call to `fcntl` or `close` is not recognized.
+//!
+//! ## False Negatives
+//!
+//! - Descriptors returned to the caller of the creating function are not tracked into the caller.
+//! - Calls to `exec`-like functions through function pointers are not detected.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::IntoNodeReferences;
+
+use crate::abstract_domain::{AbstractDomain, TryToBitvec};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{PointerInference, State as PointerInferenceState};
+use crate::analysis::taint::{State, Taint};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage, Remediation};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE403",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// Maps the names of functions creating file descriptors to the description of their parameters.
+    descriptor_sources: BTreeMap<String, DescriptorSource>,
+    /// Names of `exec`-like functions.
+    exec_symbols: Vec<String>,
+    /// The maximal call depth up to which called functions are searched for calls to `exec`-like functions.
+    max_call_depth: usize,
+    /// The value of the `O_CLOEXEC` flag (which equals `SOCK_CLOEXEC` on Linux).
+    o_cloexec: u64,
+    /// The value of the `F_SETFD` command of `fcntl`.
+    f_setfd: u64,
+    /// The value of the `FD_CLOEXEC` flag.
+    fd_cloexec: u64,
+}
+
+/// The parameters of a function creating file descriptors.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DescriptorSource {
+    /// The index of the parameter containing the flags of the created descriptor, if the function has one.
+    flags_index: Option<usize>,
+    /// The index of the parameter pointing to the array that the created descriptors are written to.
+    /// If not set, the descriptor is the return value of the function.
+    #[serde(default)]
+    output_index: Option<usize>,
+}
+
+/// The symbols and node indices needed to follow the paths starting at descriptor creating calls.
+struct Context<'a> {
+    project: &'a Project,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    pointer_inference_results: &'a PointerInference<'a>,
+    config: Config,
+    /// Maps the pairs `(block TID, function TID)` to the `BlkStart` node of the block.
+    block_start_nodes: HashMap<(Tid, Tid), NodeIndex>,
+    /// The TIDs of functions that may call an `exec`-like function
+    /// (directly or transitively up to the maximal call depth),
+    /// mapped to the name of the called `exec`-like function.
+    exec_reaching_subs: HashMap<Tid, String>,
+}
+
+/// Search for file descriptors created without the close-on-exec flag
+/// that may reach a call to an `exec`-like function.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let source_names: Vec<String> = config.descriptor_sources.keys().cloned().collect();
+    let source_symbols = get_symbol_map(project, &source_names);
+    if source_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let mut block_start_nodes = HashMap::new();
+    let mut source_calls = Vec::new();
+    for (node_id, node) in pointer_inference_results.get_graph().node_references() {
+        match node {
+            Node::BlkStart(block, sub) => {
+                block_start_nodes.insert((block.tid.clone(), sub.tid.clone()), node_id);
+            }
+            Node::BlkEnd(block, sub) => {
+                for jmp in block.term.jmps.iter() {
+                    if let Jmp::Call {
+                        target,
+                        return_: Some(return_target),
+                    } = &jmp.term
+                    {
+                        if let Some(symbol) = source_symbols.get(target) {
+                            source_calls.push((node_id, jmp, *symbol, return_target, *sub));
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    let exec_reaching_subs = get_exec_reaching_subs(project, &config);
+    let context = Context {
+        project,
+        runtime_memory_image: analysis_results.runtime_memory_image,
+        pointer_inference_results,
+        config,
+        block_start_nodes,
+        exec_reaching_subs,
+    };
+
+    let mut cwe_warnings = Vec::new();
+    for (node_id, call, symbol, return_target, sub) in source_calls {
+        let pi_state = match pointer_inference_results.get_node_value(node_id) {
+            Some(NodeValue::Value(pi_state)) => pi_state,
+            _ => continue,
+        };
+        let source = &context.config.descriptor_sources[&symbol.name];
+        if context.is_cloexec_flag_set(pi_state, symbol, source) {
+            continue;
+        }
+        let start_state = context.get_start_state(pi_state, symbol, source);
+        for (exec_call, exec_name) in context.find_exec_calls(start_state, return_target, sub) {
+            cwe_warnings.push(
+                generate_cwe_warning(call, &symbol.name, &exec_call, &exec_name, sub)
+                    .remediation(Some(get_remediation(&symbol.name, source))),
+            );
+        }
+    }
+    cwe_warnings.sort();
+    (Vec::new(), cwe_warnings)
+}
+
+impl<'a> Context<'a> {
+    /// Return `true` if the `O_CLOEXEC` flag is known to be set in the flags parameter of the call.
+    fn is_cloexec_flag_set(
+        &self,
+        pi_state: &PointerInferenceState,
+        symbol: &ExternSymbol,
+        source: &DescriptorSource,
+    ) -> bool {
+        source
+            .flags_index
+            .and_then(|index| symbol.parameters.get(index))
+            .and_then(|parameter| {
+                pi_state
+                    .eval_parameter_arg(parameter, self.runtime_memory_image)
+                    .ok()
+            })
+            .and_then(|flags| flags.get_known_bits().ones.try_to_u64().ok())
+            .is_some_and(|set_bits| set_bits & self.config.o_cloexec == self.config.o_cloexec)
+    }
+
+    /// Get the taint state directly after the call, where the created descriptors are tainted.
+    fn get_start_state(
+        &self,
+        pi_state: &PointerInferenceState,
+        symbol: &ExternSymbol,
+        source: &DescriptorSource,
+    ) -> State {
+        let mut state = State::new(symbol, Some(pi_state));
+        if let Some(index) = source.output_index {
+            // The descriptors are written to the output array and the return value is only a status code.
+            for return_register in symbol.return_values.iter().flat_map(|arg| match arg {
+                Arg::Register { expr, .. } => expr.input_vars(),
+                Arg::Stack { .. } => Vec::new(),
+            }) {
+                state.set_register_taint(return_register, Taint::Top(return_register.size));
+            }
+            if let Some(Ok(address)) = symbol
+                .parameters
+                .get(index)
+                .map(|parameter| pi_state.eval_parameter_arg(parameter, self.runtime_memory_image))
+            {
+                // The output array contains two 32-bit descriptors.
+                state.save_taint_to_memory(&address, Taint::Tainted(ByteSize::new(8)));
+            }
+        }
+        state
+    }
+
+    /// Follow all paths from the given block of the function
+    /// and return the calls to `exec`-like functions (or to functions calling them)
+    /// that are reachable without closing the tracked descriptor or marking it as close-on-exec.
+    fn find_exec_calls(
+        &self,
+        start_state: State,
+        start_block: &Tid,
+        sub: &Term<Sub>,
+    ) -> BTreeSet<(Tid, String)> {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_states: HashMap<&Tid, State> = HashMap::new();
+        let mut worklist = vec![(start_block, start_state)];
+        let mut exec_calls = BTreeSet::new();
+        while let Some((block_tid, state)) = worklist.pop() {
+            let block = match blocks.get(block_tid) {
+                Some(block) => *block,
+                None => continue,
+            };
+            let state = match block_states.get(block_tid) {
+                Some(old_state) => {
+                    let merged_state = old_state.merge(&state);
+                    if merged_state == *old_state {
+                        continue;
+                    }
+                    merged_state
+                }
+                None => state,
+            };
+            block_states.insert(&block.tid, state.clone());
+            let (state, pi_state) = self.update_defs(state, block, sub);
+            for jmp in block.term.jmps.iter() {
+                match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                        worklist.push((target, state.clone()))
+                    }
+                    Jmp::Call { target, return_ } => {
+                        if let Some(exec_name) = self.get_exec_call_name(target) {
+                            exec_calls.insert((jmp.tid.clone(), exec_name));
+                        } else if !self.is_descriptor_protected_by_call(
+                            &state,
+                            pi_state.as_ref(),
+                            target,
+                        ) {
+                            if let Some(return_target) = return_ {
+                                let mut state = state.clone();
+                                self.remove_non_callee_saved_taint(&mut state, Some(target));
+                                worklist.push((return_target, state));
+                            }
+                        }
+                    }
+                    Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => {
+                        let mut state = state.clone();
+                        self.remove_non_callee_saved_taint(&mut state, None);
+                        worklist.push((return_target, state));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        exec_calls
+    }
+
+    /// Apply the `Def`s of the block to the taint state.
+    /// Returns the taint state and the pointer inference state at the end of the block.
+    fn update_defs(
+        &self,
+        mut state: State,
+        block: &Term<Blk>,
+        sub: &Term<Sub>,
+    ) -> (State, Option<PointerInferenceState>) {
+        let mut pi_state = self
+            .block_start_nodes
+            .get(&(block.tid.clone(), sub.tid.clone()))
+            .and_then(
+                |node_id| match self.pointer_inference_results.get_node_value(*node_id) {
+                    Some(NodeValue::Value(pi_state)) => Some(pi_state.clone()),
+                    _ => None,
+                },
+            );
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Assign { var, value } => state.set_register_taint(var, state.eval(value)),
+                Def::Load { var, address } => {
+                    let taint = match &pi_state {
+                        Some(pi_state) => {
+                            state.load_taint_from_memory(&pi_state.eval(address), var.size)
+                        }
+                        None => Taint::Top(var.size),
+                    };
+                    state.set_register_taint(var, taint);
+                }
+                Def::Store { address, value } => match &pi_state {
+                    Some(pi_state) => {
+                        state.save_taint_to_memory(&pi_state.eval(address), state.eval(value))
+                    }
+                    None => state.remove_all_memory_taints(),
+                },
+            }
+            if let Some(pi_state) = pi_state.as_mut() {
+                pi_state.apply_defs(std::slice::from_ref(def), self.runtime_memory_image);
+            }
+        }
+        (state, pi_state)
+    }
+
+    /// If the call target is an `exec`-like function or a function that may call one,
+    /// return the name of the `exec`-like function.
+    fn get_exec_call_name(&self, target: &Tid) -> Option<String> {
+        match self.project.program.term.extern_symbols.get(target) {
+            Some(symbol) => self
+                .config
+                .exec_symbols
+                .contains(&symbol.name)
+                .then(|| symbol.name.clone()),
+            None => self.exec_reaching_subs.get(target).cloned(),
+        }
+    }
+
+    /// Return `true` if the call closes the tracked descriptor or marks it as close-on-exec,
+    /// i.e. if it is a call to `close(fd)` or `fcntl(fd, F_SETFD, FD_CLOEXEC)`.
+    fn is_descriptor_protected_by_call(
+        &self,
+        state: &State,
+        pi_state: Option<&PointerInferenceState>,
+        target: &Tid,
+    ) -> bool {
+        let symbol = match self.project.program.term.extern_symbols.get(target) {
+            Some(symbol) if symbol.name == "close" || symbol.name == "fcntl" => symbol,
+            _ => return false,
+        };
+        let is_descriptor_parameter = match (symbol.parameters.first(), pi_state) {
+            (Some(Arg::Register { expr, .. }), _) => state.eval(expr).is_tainted(),
+            (Some(Arg::Stack { address, size, .. }), Some(pi_state)) => state
+                .load_taint_from_memory(&pi_state.eval(address), *size)
+                .is_tainted(),
+            _ => false,
+        };
+        if !is_descriptor_parameter || symbol.name == "close" {
+            return is_descriptor_parameter;
+        }
+        let pi_state = match pi_state {
+            Some(pi_state) => pi_state,
+            None => return false,
+        };
+        let mut parameter_values = symbol.parameters.iter().skip(1).take(2).map(|parameter| {
+            pi_state
+                .eval_parameter_arg(parameter, self.runtime_memory_image)
+                .ok()
+        });
+        let command = parameter_values
+            .next()
+            .flatten()
+            .and_then(|value| value.get_if_absolute_value()?.try_to_bitvec().ok())
+            .and_then(|command| command.try_to_u64().ok());
+        let set_bits = parameter_values
+            .next()
+            .flatten()
+            .and_then(|value| value.get_known_bits().ones.try_to_u64().ok())
+            .unwrap_or(0);
+        command == Some(self.config.f_setfd)
+            && set_bits & self.config.fd_cloexec == self.config.fd_cloexec
+    }
+
+    /// Remove the taint from all registers that are not callee-saved
+    /// in the calling convention of the called function.
+    /// For unknown call targets the standard calling convention of the project is used.
+    fn remove_non_callee_saved_taint(&self, state: &mut State, target: Option<&Tid>) {
+        let program = &self.project.program.term;
+        let calling_convention = match target {
+            Some(target) => match program.extern_symbols.get(target) {
+                Some(symbol) => symbol.calling_convention.clone(),
+                None => program
+                    .subs
+                    .get(target)
+                    .and_then(|sub| sub.term.calling_convention.clone()),
+            },
+            None => None,
+        };
+        if let Some(cconv) = self
+            .project
+            .get_specific_calling_convention(&calling_convention)
+        {
+            state.remove_non_callee_saved_taint(cconv);
+        }
+    }
+}
+
+/// Get the functions that may call an `exec`-like function,
+/// either directly or through a chain of at most `max_call_depth` calls,
+/// together with the name of the called `exec`-like function.
+fn get_exec_reaching_subs(project: &Project, config: &Config) -> HashMap<Tid, String> {
+    let mut exec_reaching_subs: HashMap<Tid, String> = HashMap::new();
+    for _ in 0..config.max_call_depth {
+        let mut new_subs = Vec::new();
+        for sub in project.program.term.subs.values() {
+            if exec_reaching_subs.contains_key(&sub.tid) {
+                continue;
+            }
+            let exec_name = sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+                .find_map(|jmp| match &jmp.term {
+                    Jmp::Call { target, .. } => {
+                        match project.program.term.extern_symbols.get(target) {
+                            Some(symbol) if config.exec_symbols.contains(&symbol.name) => {
+                                Some(symbol.name.clone())
+                            }
+                            Some(_) => None,
+                            None => exec_reaching_subs.get(target).cloned(),
+                        }
+                    }
+                    _ => None,
+                });
+            if let Some(exec_name) = exec_name {
+                new_subs.push((sub.tid.clone(), exec_name));
+            }
+        }
+        if new_subs.is_empty() {
+            break;
+        }
+        exec_reaching_subs.extend(new_subs);
+    }
+    exec_reaching_subs
+}
+
+/// Suggest how to create the descriptor with the close-on-exec flag set.
+///
+/// For functions with a flags parameter the `O_CLOEXEC` flag (or `SOCK_CLOEXEC` for sockets) should be added.
+/// Functions without a flags parameter should be replaced by their variants with a flags parameter if possible.
+fn get_remediation(source_name: &str, source: &DescriptorSource) -> Remediation {
+    if source.flags_index.is_some() {
+        let flag = if source_name == "socket" || source_name.starts_with("accept") {
+            "SOCK_CLOEXEC"
+        } else {
+            "O_CLOEXEC"
+        };
+        return Remediation::AddFlag {
+            call: source_name.to_string(),
+            flag: flag.to_string(),
+        };
+    }
+    match source_name {
+        "pipe" => Remediation::ReplaceCall {
+            from: source_name.to_string(),
+            to: "pipe2".to_string(),
+            added_args: vec!["O_CLOEXEC".to_string()],
+        },
+        "accept" => Remediation::ReplaceCall {
+            from: source_name.to_string(),
+            to: "accept4".to_string(),
+            added_args: vec!["SOCK_CLOEXEC".to_string()],
+        },
+        _ => Remediation::FreeText {
+            text: format!(
+                "mark the descriptor created by {} as close-on-exec with fcntl(fd, F_SETFD, FD_CLOEXEC)",
+                source_name
+            ),
+        },
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    source_call: &Term<Jmp>,
+    source_name: &str,
+    exec_call: &Tid,
+    exec_name: &str,
+    sub: &Term<Sub>,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Exposure of File Descriptor) The file descriptor created by {} at {} in function {} is not marked as close-on-exec and may be inherited by the program executed through {} at {}",
+            source_name, source_call.tid.canonical_address(), sub.term.name, exec_name, exec_call.canonical_address()
+        ),
+    )
+    .tids(vec![format!("{}", source_call.tid), format!("{}", exec_call)])
+    .addresses(vec![source_call.tid.canonical_address().to_string(), exec_call.canonical_address().to_string()])
+    .symbols(vec![source_name.to_string(), exec_name.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shortcut for assigning a constant to a register.
+    fn assign_const(tid: &str, register: &str, value: i64) -> Term<Def> {
+        Def::assign(
+            tid,
+            Variable::mock(register, 8),
+            Expression::const_from_i64(value),
+        )
+    }
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![
+                Variable::mock("RDI", 8),
+                Variable::mock("RSI", 8),
+                Variable::mock("RDX", 8),
+            ],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["open", "fcntl", "execvp"] {
+            project.add_mock_extern_symbol(name).parameters = vec![
+                Arg::mock_register("RDI", 8),
+                Arg::mock_register("RSI", 8),
+                Arg::mock_register("RDX", 8),
+            ];
+        }
+        let functions = vec![
+            Sub::mock_with_calls(
+                "leak",
+                vec![
+                    (vec![assign_const("leak_flags", "RSI", 0x41)], "open"),
+                    (vec![], "execvp"),
+                ],
+            ),
+            Sub::mock_with_calls(
+                "cloexec",
+                vec![
+                    (vec![assign_const("cloexec_flags", "RSI", 0x80041)], "open"),
+                    (vec![], "execvp"),
+                ],
+            ),
+            Sub::mock_with_calls(
+                "protected",
+                vec![
+                    (vec![assign_const("protected_flags", "RSI", 0x41)], "open"),
+                    (
+                        vec![
+                            Def::assign(
+                                "protected_fd",
+                                Variable::mock("RDI", 8),
+                                Expression::var("RAX", 8),
+                            ),
+                            assign_const("protected_cmd", "RSI", 2),
+                            assign_const("protected_arg", "RDX", 1),
+                        ],
+                        "fcntl",
+                    ),
+                    (vec![], "execvp"),
+                ],
+            ),
+            Sub::mock_with_calls(
+                "leak_to_callee",
+                vec![
+                    (vec![assign_const("callee_flags", "RSI", 0)], "open"),
+                    (vec![], "run_program"),
+                ],
+            ),
+            Sub::mock_with_calls("run_program", vec![(vec![], "execvp")]),
+        ];
+        for sub in functions {
+            project.program.term.entry_points.insert(sub.tid.clone());
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn descriptors_reaching_exec() {
+        let project = mock_project();
+        let config = serde_json::json!({
+            "descriptor_sources": { "open": { "flags_index": 1 } },
+            "exec_symbols": ["execvp"],
+            "max_call_depth": 2,
+            "o_cloexec": 0x80000,
+            "f_setfd": 2,
+            "fd_cloexec": 1,
+        });
+
+        let warnings =
+            AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+                check_cwe(analysis_results, &config).1
+            });
+        let warning_tids: Vec<&[String]> =
+            warnings.iter().map(|warning| &warning.tids[..]).collect();
+        assert_eq!(
+            warning_tids,
+            vec![
+                &["call_leak_blk_0".to_string(), "call_leak_blk_1".to_string()][..],
+                &[
+                    "call_leak_to_callee_blk_0".to_string(),
+                    "call_leak_to_callee_blk_1".to_string()
+                ][..],
+            ]
+        );
+        assert_eq!(
+            warnings[1].symbols,
+            vec!["open".to_string(), "execvp".to_string()]
+        );
+        assert_eq!(
+            warnings[0].remediation,
+            Some(Remediation::AddFlag {
+                call: "open".to_string(),
+                flag: "O_CLOEXEC".to_string(),
+            })
+        );
+    }
+}
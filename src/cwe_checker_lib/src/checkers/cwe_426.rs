@@ -43,6 +43,9 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE426",
     version: "0.1",
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// Function symbols read from *config.json*.
@@ -59,11 +62,12 @@ fn generate_cwe_warning(sub: &Term<Sub>) -> CweWarning {
         CWE_MODULE.version,
         format!(
             "(Untrusted Search Path) sub {} at {} may be vulnerable to PATH manipulation.",
-            sub.term.name, sub.tid.address
+            sub.term.name,
+            sub.tid.canonical_address()
         ),
     )
     .tids(vec![format!("{}", sub.tid)])
-    .addresses(vec![sub.tid.address.clone()])
+    .addresses(vec![sub.tid.canonical_address().to_string()])
     .symbols(vec![sub.term.name.clone()])
 }
 
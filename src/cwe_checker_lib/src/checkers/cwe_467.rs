@@ -34,6 +34,9 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE467",
     version: "0.2",
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// Function symbols read from *config.json*.
@@ -97,11 +100,12 @@ fn generate_cwe_warning(jmp: &Term<Jmp>, extern_symbol: &ExternSymbol) -> CweWar
         CWE_MODULE.version,
         format!(
             "(Use of sizeof on a Pointer Type) sizeof on pointer at {} ({}).",
-            jmp.tid.address, extern_symbol.name
+            jmp.tid.canonical_address(),
+            extern_symbol.name
         ),
     )
     .tids(vec![format!("{}", jmp.tid)])
-    .addresses(vec![jmp.tid.address.clone()])
+    .addresses(vec![jmp.tid.canonical_address().to_string()])
 }
 
 /// Execute the CWE check.
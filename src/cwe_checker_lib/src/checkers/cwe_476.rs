@@ -36,22 +36,12 @@
 //! - For functions with more than one return value we do not distinguish between
 //! the return values.
 
-use crate::analysis::forward_interprocedural_fixpoint::create_computation;
 use crate::analysis::forward_interprocedural_fixpoint::Context as _;
-use crate::analysis::graph::{Edge, Node};
-use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
-use crate::intermediate_representation::*;
+use crate::analysis::taint::{compute_taint_from_sources, get_extern_taint_source_sites, State};
 use crate::prelude::*;
 use crate::utils::log::{CweWarning, LogMessage};
-use crate::CweModule;
-use petgraph::visit::EdgeRef;
-use std::collections::BTreeMap;
-
-mod state;
-use state::*;
-
-mod taint;
-pub use taint::*;
+use crate::{CweModule, SharedAnalysis};
+use std::collections::{BTreeMap, BTreeSet};
 
 mod context;
 use context::*;
@@ -61,6 +51,9 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE476",
     version: "0.3",
     run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::CallSitesAndAccesses,
 };
 
 /// The configuration struct
@@ -82,6 +75,7 @@ pub fn check_cwe(
     let pointer_inference_results = analysis_results.pointer_inference.unwrap();
 
     let (cwe_sender, cwe_receiver) = crossbeam_channel::unbounded();
+    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
 
     let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
     let symbol_map = crate::utils::symbol_utils::get_symbol_map(project, &config.symbols[..]);
@@ -90,34 +84,22 @@ pub fn check_cwe(
         analysis_results.runtime_memory_image,
         pointer_inference_results,
         cwe_sender,
+        log_sender,
     );
 
-    for edge in general_context.get_graph().edge_references() {
-        if let Edge::ExternCallStub(jmp) = edge.weight() {
-            if let Jmp::Call { target, .. } = &jmp.term {
-                if let Some(symbol) = symbol_map.get(target) {
-                    let node = edge.target();
-                    let current_sub = match general_context.get_graph()[node] {
-                        Node::BlkStart(_blk, sub) => sub,
-                        _ => panic!(),
-                    };
-                    let mut context = general_context.clone();
-                    context.set_taint_source(jmp, current_sub);
-                    let pi_state_at_taint_source =
-                        match pointer_inference_results.get_node_value(node) {
-                            Some(NodeValue::Value(val)) => Some(val.clone()),
-                            _ => None,
-                        };
-                    let mut computation = create_computation(context, None);
-                    computation.set_node_value(
-                        node,
-                        NodeValue::Value(State::new(symbol, pi_state_at_taint_source.as_ref())),
-                    );
-                    computation.compute_with_max_steps(100);
-                }
-            }
-        }
-    }
+    let taint_source_sites = get_extern_taint_source_sites(
+        general_context.get_graph(),
+        &symbol_map,
+        pointer_inference_results,
+        State::new,
+    )
+    .into_iter()
+    .map(|(jmp, _symbol, node, state)| (jmp, node, state));
+    let driver_logs = compute_taint_from_sources(
+        &general_context,
+        taint_source_sites,
+        |context, jmp, current_sub| context.set_taint_source(jmp, current_sub),
+    );
 
     let mut cwe_warnings = BTreeMap::new();
     for cwe in cwe_receiver.try_iter() {
@@ -128,5 +110,127 @@ pub fn check_cwe(
     }
     let cwe_warnings = cwe_warnings.into_iter().map(|(_, cwe)| cwe).collect();
 
-    (Vec::new(), cwe_warnings)
+    // The same store instruction may be reported several times during the fixpoint computation.
+    let logs: BTreeSet<LogMessage> = log_receiver.try_iter().chain(driver_logs).collect();
+
+    (logs.into_iter().collect(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::{NodeValueRetention, PointerInference};
+    use crate::intermediate_representation::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use std::collections::HashSet;
+
+    /// Create a project where the return value of `malloc` is stored on the stack,
+    /// loaded again in a later block and dereferenced without a check.
+    /// The block between the store and the load contains no memory access.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("malloc");
+
+        let mut alloc_blk = Blk::mock_with_tid("alloc_blk");
+        alloc_blk.term.defs.push(Def::assign(
+            "set_size",
+            Variable::mock("RDI", 8),
+            Expression::const_from_i64(16),
+        ));
+        alloc_blk
+            .term
+            .jmps
+            .push(Jmp::call("call_malloc", "malloc", Some("store_blk")));
+        let mut store_blk = Blk::mock_with_tid("store_blk");
+        store_blk.term.defs.push(Def::store(
+            "store",
+            Expression::var("RSP", 8).plus_const(-8),
+            Expression::var("RAX", 8),
+        ));
+        store_blk
+            .term
+            .jmps
+            .push(Jmp::branch("jmp_assign", "assign_blk"));
+        let mut assign_blk = Blk::mock_with_tid("assign_blk");
+        assign_blk.term.defs.push(Def::assign(
+            "clear",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0),
+        ));
+        assign_blk
+            .term
+            .jmps
+            .push(Jmp::branch("jmp_access", "access_blk"));
+        let mut access_blk = Blk::mock_with_tid("access_blk");
+        access_blk.term.defs = vec![
+            Def::load(
+                "load_pointer",
+                Variable::mock("RCX", 8),
+                Expression::var("RSP", 8).plus_const(-8),
+            ),
+            Def::load(
+                "dereference",
+                Variable::mock("RDX", 8),
+                Expression::var("RCX", 8),
+            ),
+        ];
+        access_blk.term.jmps.push(Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        let mut main = Sub::mock("main");
+        main.term.blocks = vec![alloc_blk, store_blk, assign_blk, access_blk];
+        project.program.term.entry_points.insert(main.tid.clone());
+        project.program.term.subs.insert(main.tid.clone(), main);
+        project
+    }
+
+    #[test]
+    fn check_with_restricted_node_value_retention() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs: HashSet<Tid> = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let config = serde_json::json!({ "symbols": ["malloc"] });
+        let mut retained_node_values = Vec::new();
+        for retention in [
+            NodeValueRetention::All,
+            NodeValueRetention::CallSitesAndAccesses,
+        ] {
+            let mut pointer_inference = PointerInference::mock_with_node_value_retention(
+                &project,
+                &runtime_memory_image,
+                &graph,
+                retention,
+            );
+            pointer_inference.compute();
+            pointer_inference.discard_unretained_node_values();
+            retained_node_values.push(
+                graph
+                    .node_indices()
+                    .filter(|node| pointer_inference.get_node_value(*node).is_some())
+                    .count(),
+            );
+            let analysis_results =
+                AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+                    .set_pointer_inference(Some(&pointer_inference));
+            let (_, cwe_warnings) = check_cwe(&analysis_results, &config);
+            assert_eq!(cwe_warnings.len(), 1);
+            assert_eq!(
+                cwe_warnings[0].tids,
+                vec!["call_malloc".to_string(), "dereference".to_string()]
+            );
+        }
+        assert!(retained_node_values[1] < retained_node_values[0]);
+    }
 }
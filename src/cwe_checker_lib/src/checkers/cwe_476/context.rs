@@ -1,15 +1,13 @@
-use super::State;
-use super::Taint;
 use super::CWE_MODULE;
 use crate::abstract_domain::AbstractDomain;
-use crate::analysis::forward_interprocedural_fixpoint::Context as _;
 use crate::analysis::graph::{Graph, Node};
 use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
 use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
-use crate::analysis::pointer_inference::State as PointerInferenceState;
+use crate::analysis::taint::{State, Taint, TaintContext};
 use crate::intermediate_representation::*;
 use crate::utils::binary::RuntimeMemoryImage;
-use crate::utils::log::CweWarning;
+use crate::utils::feedback::{report_imprecision, ImprecisionReason, MissingFact};
+use crate::utils::log::{CweWarning, LogMessage};
 use petgraph::graph::NodeIndex;
 use petgraph::visit::IntoNodeReferences;
 use std::collections::HashMap;
@@ -17,48 +15,28 @@ use std::sync::Arc;
 
 /// The context object for the Null-Pointer-Dereference check.
 ///
-/// There is always only one source of taint for the analysis.
-/// On creation of a `Context` object, the taint source is not set.
-/// Starting the fixpoint algorithm without
-/// [setting the taint source](Context::set_taint_source()) first will lead to a panic.
-/// By resetting the taint source one can reuse the context object for several fixpoint computations.
+/// The taint source is managed by the wrapped [`TaintContext`](crate::analysis::taint::TaintContext).
 #[derive(Clone)]
 pub struct Context<'a> {
-    /// A pointer to the corresponding project struct.
-    project: &'a Project,
+    /// The taint source, the current function and the pointer inference results of the analysis.
+    taint: TaintContext<'a>,
     /// A pointer to the representation of the runtime memory image.
     runtime_memory_image: &'a RuntimeMemoryImage,
-    /// A pointer to the results of the pointer inference analysis.
-    /// They are used to determine the targets of pointers to memory,
-    /// which in turn is used to keep track of taint on the stack or on the heap.
-    pub pointer_inference_results: &'a PointerInferenceComputation<'a>,
-    /// A map to get the node index of the `BlkStart` node containing a given [`Def`] as the first `Def` of the block.
-    /// The keys are of the form `(Def-TID, Current-Sub-TID)`
-    /// to distinguish the nodes for blocks contained in more than one function.
-    block_start_node_map: Arc<HashMap<(Tid, Tid), NodeIndex>>,
     /// Maps the TID of an extern symbol to the extern symbol struct.
     extern_symbol_map: Arc<HashMap<Tid, &'a ExternSymbol>>,
     /// A map to get the node index of the `BlkEnd` node containing a given [`Jmp`].
     /// The keys are of the form `(Jmp-TID, Current-Sub-TID)`
     /// to distinguish the nodes for blocks contained in more than one function.
     jmp_to_blk_end_node_map: Arc<HashMap<(Tid, Tid), NodeIndex>>,
-    /// The call whose return values are the sources for taint for the analysis.
-    taint_source: Option<&'a Term<Jmp>>,
-    /// The name of the function, whose return values are the taint sources.
-    taint_source_name: Option<String>,
-    /// The current subfunction.
-    ///Since the analysis is intraprocedural,
-    ///all nodes with state during the fixpoint algorithm should belong to this function.
-    current_sub: Option<&'a Term<Sub>>,
     /// A channel where found CWE hits can be sent to.
     cwe_collector: crossbeam_channel::Sender<CweWarning>,
+    /// A channel where log messages can be sent to.
+    log_collector: crossbeam_channel::Sender<LogMessage>,
 }
 
 impl<'a> Context<'a> {
     /// Create a new context object.
     ///
-    /// Note that one has to set the taint source separately before starting the analysis!
-    ///
     /// If one wants to run the analysis for several sources,
     /// one should clone or reuse an existing `Context` object instead of generating new ones,
     /// since this function can be expensive!
@@ -67,23 +45,15 @@ impl<'a> Context<'a> {
         runtime_memory_image: &'a RuntimeMemoryImage,
         pointer_inference_results: &'a PointerInferenceComputation<'a>,
         cwe_collector: crossbeam_channel::Sender<CweWarning>,
+        log_collector: crossbeam_channel::Sender<LogMessage>,
     ) -> Self {
-        let mut block_start_node_map = HashMap::new();
         let mut jmp_to_blk_end_node_map = HashMap::new();
         let graph = pointer_inference_results.get_graph();
         for (node_id, node) in graph.node_references() {
-            match node {
-                Node::BlkStart(block, sub) => {
-                    if let Some(def) = block.term.defs.get(0) {
-                        block_start_node_map.insert((def.tid.clone(), sub.tid.clone()), node_id);
-                    }
-                }
-                Node::BlkEnd(block, sub) => {
-                    for jmp in block.term.jmps.iter() {
-                        jmp_to_blk_end_node_map.insert((jmp.tid.clone(), sub.tid.clone()), node_id);
-                    }
+            if let Node::BlkEnd(block, sub) = node {
+                for jmp in block.term.jmps.iter() {
+                    jmp_to_blk_end_node_map.insert((jmp.tid.clone(), sub.tid.clone()), node_id);
                 }
-                _ => (),
             }
         }
         let mut extern_symbol_map = HashMap::new();
@@ -91,81 +61,49 @@ impl<'a> Context<'a> {
             extern_symbol_map.insert(tid.clone(), symbol);
         }
         Context {
-            project,
+            taint: TaintContext::new(project, pointer_inference_results),
             runtime_memory_image,
-            pointer_inference_results,
-            block_start_node_map: Arc::new(block_start_node_map),
             extern_symbol_map: Arc::new(extern_symbol_map),
             jmp_to_blk_end_node_map: Arc::new(jmp_to_blk_end_node_map),
-            taint_source: None,
-            taint_source_name: None,
-            current_sub: None,
             cwe_collector,
+            log_collector,
         }
     }
 
     /// Set the taint source and the current function for the analysis.
     pub fn set_taint_source(&mut self, taint_source: &'a Term<Jmp>, current_sub: &'a Term<Sub>) {
-        let taint_source_name = match &taint_source.term {
-            Jmp::Call { target, .. } => self
-                .project
-                .program
-                .term
-                .extern_symbols
-                .get(target)
-                .map(|symbol| symbol.name.clone())
-                .unwrap_or_else(|| "Unknown".to_string()),
-            _ => "Unknown".to_string(),
-        };
-        self.taint_source = Some(taint_source);
-        self.taint_source_name = Some(taint_source_name);
-        self.current_sub = Some(current_sub);
-    }
-
-    /// Get the current pointer inference state (if one can be found) for the given taint state.
-    fn get_current_pointer_inference_state(
-        &self,
-        state: &State,
-        tid: &Tid,
-    ) -> Option<PointerInferenceState> {
-        if let Some(pi_state) = state.get_pointer_inference_state() {
-            Some(pi_state.clone())
-        } else if let Some(node_id) = self
-            .block_start_node_map
-            .get(&(tid.clone(), self.current_sub.unwrap().tid.clone()))
-        {
-            match self.pointer_inference_results.get_node_value(*node_id) {
-                Some(NodeValue::Value(val)) => Some(val.clone()),
-                _ => None,
-            }
-        } else {
-            None
-        }
-    }
-
-    /// Update the pointer inference state contained in the given taint state
-    /// according to the effect of the given `Def` term.
-    fn update_pointer_inference_state(&self, state: &mut State, def: &Term<Def>) {
-        if let Some(pi_state) = self.get_current_pointer_inference_state(state, &def.tid) {
-            let pi_context = self.pointer_inference_results.get_context();
-            let new_pi_state = pi_context.update_def(&pi_state, def);
-            state.set_pointer_inference_state(new_pi_state);
-        }
+        self.taint.set_taint_source(taint_source, current_sub);
     }
 
     /// Generate a CWE warning for the taint source of the context object.
     fn generate_cwe_warning(&self, taint_access_location: &Tid) {
-        let taint_source = self.taint_source.unwrap();
-        let taint_source_name = self.taint_source_name.clone().unwrap();
+        let taint_source = self.taint.get_taint_source();
+        let taint_source_name = self.taint.get_taint_source_name().to_string();
         let cwe_warning = CweWarning::new(CWE_MODULE.name, CWE_MODULE.version,
             format!("(NULL Pointer Dereference) There is no check if the return value is NULL at {} ({}).",
-            taint_source.tid.address, taint_source_name))
-            .addresses(vec![taint_source.tid.address.clone(), taint_access_location.address.clone()])
+            taint_source.tid.canonical_address(), taint_source_name))
+            .addresses(vec![taint_source.tid.canonical_address().to_string(), taint_access_location.canonical_address().to_string()])
             .tids(vec![format!("{}", taint_source.tid), format!("{}", taint_access_location)])
             .symbols(vec![taint_source_name]);
         let _ = self.cwe_collector.send(cwe_warning);
     }
 
+    /// Report that the taint of the value stored by the given store instruction is lost,
+    /// since the targets of the address are unknown.
+    /// Accesses to the stored value after loading it again cannot be detected.
+    fn report_lost_taint(&self, store_tid: &Tid) {
+        let missing_fact = MissingFact::new(format!(
+            "Target of the pointer that the return value of {} is stored to",
+            self.taint.get_taint_source_name()
+        ));
+        let _ = self.log_collector.send(report_imprecision(
+            CWE_MODULE.name,
+            store_tid,
+            ImprecisionReason::TopPointer,
+            missing_fact,
+        ));
+    }
+
     /// Check parameters of an extern symbol for taint.
     /// For pointers as parameters we also check
     /// whether the pointer points directly to taint if it points to some stack address.
@@ -185,7 +123,7 @@ impl<'a> Context<'a> {
             }
         }
         if let Some(NodeValue::Value(pi_state)) =
-            self.pointer_inference_results.get_node_value(node_id)
+            self.taint.pointer_inference_results.get_node_value(node_id)
         {
             // Check stack parameters and collect referenced memory object that need to be checked for taint.
             for parameter in extern_symbol.parameters.iter() {
@@ -221,16 +159,16 @@ impl<'a> Context<'a> {
     /// generate a CWE warning and return `None`.
     /// Else remove all taint contained in non-callee-saved registers.
     fn handle_generic_call(&self, state: &State, call_tid: &Tid) -> Option<State> {
-        let pi_state_option = self.get_current_pointer_inference_state(state, call_tid);
-        if state.check_generic_function_params_for_taint(self.project, pi_state_option.as_ref()) {
+        let pi_state_option = self
+            .taint
+            .get_current_pointer_inference_state(state, call_tid);
+        if state
+            .check_generic_function_params_for_taint(self.taint.project, pi_state_option.as_ref())
+        {
             self.generate_cwe_warning(call_tid);
             return None;
         }
-        let mut new_state = state.clone();
-        if let Some(calling_conv) = self.project.get_standard_calling_convention() {
-            new_state.remove_non_callee_saved_taint(calling_conv);
-        }
-        Some(new_state)
+        Some(self.taint.handle_generic_call(state))
     }
 }
 
@@ -239,7 +177,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
 
     /// Get the underlying graph of the fixpoint computation
     fn get_graph(&self) -> &Graph<'a> {
-        self.pointer_inference_results.get_graph()
+        self.taint.pointer_inference_results.get_graph()
     }
 
     /// Merge two states
@@ -267,8 +205,12 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
         _target: &Node,
         _calling_convention: &Option<String>,
     ) -> Option<Self::Value> {
-        let pi_state_option = self.get_current_pointer_inference_state(state, &call.tid);
-        if state.check_generic_function_params_for_taint(self.project, pi_state_option.as_ref()) {
+        let pi_state_option = self
+            .taint
+            .get_current_pointer_inference_state(state, &call.tid);
+        if state
+            .check_generic_function_params_for_taint(self.taint.project, pi_state_option.as_ref())
+        {
             self.generate_cwe_warning(&call.tid);
         }
         None
@@ -276,8 +218,10 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
 
     /// If taint may be contained in the function parameters, generate a CWE warning and return None.
     /// Else remove taint from non-callee-saved registers.
+    ///
+    /// Calls to `__stack_chk_fail` in stack canary checks are ignored, since they never return.
     fn update_call_stub(&self, state: &State, call: &Term<Jmp>) -> Option<Self::Value> {
-        if state.is_empty() {
+        if state.is_empty() || self.taint.project.is_canary_artifact(&call.tid) {
             return None;
         }
         match &call.term {
@@ -285,7 +229,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 if let Some(extern_symbol) = self.extern_symbol_map.get(target) {
                     let blk_end_node_id = self
                         .jmp_to_blk_end_node_map
-                        .get(&(call.tid.clone(), self.current_sub.unwrap().tid.clone()))
+                        .get(&(call.tid.clone(), self.taint.get_current_sub().tid.clone()))
                         .unwrap();
                     if self.check_parameters_for_taint(state, extern_symbol, *blk_end_node_id) {
                         self.generate_cwe_warning(&call.tid);
@@ -293,11 +237,12 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                     }
                     let mut new_state = state.clone();
                     new_state.remove_non_callee_saved_taint(
-                        self.project.get_calling_convention(extern_symbol),
+                        self.taint.project.get_calling_convention(extern_symbol),
                     );
                     Some(new_state)
                 } else {
-                    panic!("Extern symbol not found.");
+                    // The call target is a function excluded from the analysis.
+                    self.handle_generic_call(state, &call.tid)
                 }
             }
             Jmp::CallInd { .. } => self.handle_generic_call(state, &call.tid),
@@ -322,8 +267,9 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 if state.eval(address).is_tainted() {
                     self.generate_cwe_warning(&def.tid);
                     return None;
-                } else if let Some(pi_state) =
-                    self.get_current_pointer_inference_state(state, &def.tid)
+                } else if let Some(pi_state) = self
+                    .taint
+                    .get_current_pointer_inference_state(state, &def.tid)
                 {
                     let address_data = pi_state.eval(address);
                     let taint = state.load_taint_from_memory(&address_data, var.size);
@@ -336,20 +282,28 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
                 if state.eval(address).is_tainted() {
                     self.generate_cwe_warning(&def.tid);
                     return None;
-                } else if let Some(pi_state) =
-                    self.get_current_pointer_inference_state(state, &def.tid)
+                } else if let Some(pi_state) = self
+                    .taint
+                    .get_current_pointer_inference_state(state, &def.tid)
                 {
                     let address_data = pi_state.eval(address);
                     let taint = state.eval(value);
+                    if taint.is_tainted() && address_data.get_relative_values().is_empty() {
+                        self.report_lost_taint(&def.tid);
+                    }
                     new_state.save_taint_to_memory(&address_data, taint);
                 } else {
                     // We lost all knowledge about memory pointers.
                     // We delete all memory taint to reduce false positives.
+                    if state.eval(value).is_tainted() {
+                        self.report_lost_taint(&def.tid);
+                    }
                     new_state.remove_all_memory_taints();
                 }
             }
         }
-        self.update_pointer_inference_state(&mut new_state, def);
+        self.taint
+            .update_pointer_inference_state(&mut new_state, def);
         Some(new_state)
     }
 
@@ -358,17 +312,22 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
     /// which checks a tainted value.
     /// If yes, we assume that the taint source was correctly checked for being a Null pointer and return `None`.
     /// If no we only remove the `pointer_inference_state` from the state.
+    ///
+    /// Jumps to blocks handling failed stack canary checks are not followed.
     fn update_jump(
         &self,
         state: &State,
         jump: &Term<Jmp>,
         untaken_conditional: Option<&Term<Jmp>>,
-        _target: &Term<Blk>,
+        target: &Term<Blk>,
     ) -> Option<Self::Value> {
         if state.is_empty() {
             // Without taint there is nothing to propagate.
             return None;
         }
+        if self.taint.project.is_canary_artifact(&target.tid) {
+            return None;
+        }
         if let Jmp::CBranch { condition, .. } = &jump.term {
             if state.eval(condition).is_tainted() {
                 return None;
@@ -400,8 +359,10 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
     ) -> Option<State> {
         if let Some(state) = state_before_return {
             // If taint is returned, generate a CWE warning
-            let pi_state_option = self.get_current_pointer_inference_state(state, &return_term.tid);
-            if state.check_return_values_for_taint(self.project, pi_state_option.as_ref()) {
+            let pi_state_option = self
+                .taint
+                .get_current_pointer_inference_state(state, &return_term.tid);
+            if state.check_return_values_for_taint(self.taint.project, pi_state_option.as_ref()) {
                 self.generate_cwe_warning(&return_term.tid);
             }
             // Do not return early in case `state_before_call` is also set (possible for recursive functions).
@@ -417,6 +378,7 @@ impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Cont
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::forward_interprocedural_fixpoint::Context as _;
     use crate::utils::binary::RuntimeMemoryImage;
     use std::collections::HashSet;
 
@@ -425,9 +387,25 @@ mod tests {
             project: &'a Project,
             runtime_memory_image: &'a RuntimeMemoryImage,
             pi_results: &'a PointerInferenceComputation<'a>,
+        ) -> Context<'a> {
+            let (log_sender, _) = crossbeam_channel::unbounded();
+            Context::mock_with_log_collector(project, runtime_memory_image, pi_results, log_sender)
+        }
+
+        pub fn mock_with_log_collector(
+            project: &'a Project,
+            runtime_memory_image: &'a RuntimeMemoryImage,
+            pi_results: &'a PointerInferenceComputation<'a>,
+            log_sender: crossbeam_channel::Sender<LogMessage>,
         ) -> Context<'a> {
             let (cwe_sender, _) = crossbeam_channel::unbounded();
-            let mut context = Context::new(project, runtime_memory_image, pi_results, cwe_sender);
+            let mut context = Context::new(
+                project,
+                runtime_memory_image,
+                pi_results,
+                cwe_sender,
+                log_sender,
+            );
             let taint_source = Box::new(Term {
                 tid: Tid::new("taint_source"),
                 term: Jmp::Call {
@@ -551,6 +529,49 @@ mod tests {
             .is_top());
     }
 
+    #[test]
+    fn lost_taint_is_reported_as_imprecision() {
+        let project = Project::mock_empty();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let pi_results = PointerInferenceComputation::mock(&project, &runtime_memory_image, &graph);
+        let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+        let context = Context::mock_with_log_collector(
+            &project,
+            &runtime_memory_image,
+            &pi_results,
+            log_sender,
+        );
+        let (mut state, pi_state) = State::mock_with_pi_state();
+        state.set_pointer_inference_state(Some(pi_state));
+
+        // Storing the tainted value to the stack is tracked.
+        let store_def = Term {
+            tid: Tid::new("store_to_stack"),
+            term: Def::Store {
+                value: Expression::Var(Variable::mock("RAX", 8u64)),
+                address: Expression::Var(Variable::mock("RSP", 8u64)),
+            },
+        };
+        context.update_def(&state, &store_def).unwrap();
+        assert!(log_receiver.try_recv().is_err());
+        // The target of RBX is unknown, so the taint of the stored value is lost.
+        let store_def = Term {
+            tid: Tid::new("store_to_unknown"),
+            term: Def::Store {
+                value: Expression::Var(Variable::mock("RAX", 8u64)),
+                address: Expression::Var(Variable::mock("RBX", 8u64)),
+            },
+        };
+        context.update_def(&state, &store_def).unwrap();
+        let report = log_receiver.try_recv().unwrap().imprecision.unwrap();
+        assert_eq!(report.checker, "CWE476");
+        assert_eq!(report.location, Tid::new("store_to_unknown"));
+        assert_eq!(report.reason, ImprecisionReason::TopPointer);
+        assert_eq!(report.missing_fact.extern_symbol, None);
+        assert!(log_receiver.try_recv().is_err());
+    }
+
     #[test]
     fn update_jump() {
         let project = Project::mock_empty();
@@ -581,4 +602,41 @@ mod tests {
             .update_jump(&state, &jump, None, &Blk::mock())
             .is_some());
     }
+
+    #[test]
+    fn stack_canary_checks_are_ignored() {
+        let mut project = Project::mock_with_stack_canary_check();
+        project.mark_stack_canary_artifacts();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let pi_results = PointerInferenceComputation::mock(&project, &runtime_memory_image, &graph);
+        let (cwe_sender, cwe_receiver) = crossbeam_channel::unbounded();
+        let (log_sender, _) = crossbeam_channel::unbounded();
+        let mut context = Context::new(
+            &project,
+            &runtime_memory_image,
+            &pi_results,
+            cwe_sender,
+            log_sender,
+        );
+        let sub = &project.program.term.subs[&Tid::new("func")];
+        context.set_taint_source(&sub.term.blocks[1].term.jmps[0], sub);
+        let mut state = State::mock();
+        // The parameter register of `__stack_chk_fail` is tainted.
+        state.set_register_taint(&Variable::mock("RDI", 8), Taint::Tainted(ByteSize::new(8)));
+
+        let epilogue = &sub.term.blocks[0];
+        assert!(context
+            .update_jump(
+                &state,
+                &epilogue.term.jmps[1],
+                Some(&epilogue.term.jmps[0]),
+                &sub.term.blocks[1]
+            )
+            .is_none());
+        assert!(context
+            .update_call_stub(&state, &sub.term.blocks[1].term.jmps[0])
+            .is_none());
+        assert!(cwe_receiver.try_recv().is_err());
+    }
 }
@@ -0,0 +1,672 @@
+//! This module implements a check for CWE-494: Download of Code Without Integrity Check.
+//!
+//! Programs like firmware updaters sometimes receive code over the network (or read it from a file)
+//! into a memory buffer and then execute it without checking its integrity.
+//! An attacker able to manipulate the received data can then execute arbitrary code in the context of the program.
+//!
+//! See <https://cwe.mitre.org/data/definitions/494.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the pointer inference analysis we determine the memory regions
+//! that are written with externally received data:
+//! - Input functions (e.g. `recv` or `read`) taint the memory region their buffer parameter points to.
+//! - Memory copying functions (e.g. `memcpy`) taint their destination region if the source region is tainted.
+//! - Store instructions taint the written memory region
+//!   if the stored value was loaded from a tainted region in the same basic block.
+//!
+//! Tainted regions are tracked as byte ranges of memory objects together with the program points that wrote them.
+//! Then we check all indirect calls and indirect jumps.
+//! A CWE warning is generated if the jump target may point into a tainted region
+//! (e.g. a buffer filled by `recv` or a region mapped by `mmap` with the `PROT_EXEC` flag)
+//! or if the jump target is a code pointer loaded from a tainted region in the same basic block.
+//! The warning contains the program points that wrote the tainted data.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The input functions together with the indices of their buffer and size parameters.
+//! - The memory copying functions together with the indices of their destination, source and size parameters.
+//!
+//! ## False Positives
+//!
+//! - The integrity of the received code may have been checked (e.g. by verifying a signature) before its execution.
+//! - Tainted regions are tracked flow-insensitively,
+//!   i.e. a region is also considered tainted before the tainted data is written to it.
+//! - If the pointer inference analysis cannot determine the written offsets exactly,
+//!   the taint is overapproximated to larger parts of the memory object.
+//!
+//! ## False Negatives
+//!
+//! - Tainted values are only tracked through registers inside a single basic block.
+//! - Writes to global memory are not tracked.
+//! - Tainted data passed between functions is only tracked
+//!   if the pointer inference analysis uses the same memory object identifiers in both functions.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use petgraph::visit::{EdgeRef, IntoNodeReferences};
+
+use crate::abstract_domain::{AbstractIdentifier, TryToBitvec, TryToInterval};
+use crate::analysis::graph::{Edge, Node};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE494",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// Maps the names of functions writing externally received data to a buffer
+    /// to the indices of their buffer and size parameters.
+    input_functions: BTreeMap<String, InputParameters>,
+    /// Maps the names of memory copying functions to the indices of their parameters.
+    copy_functions: BTreeMap<String, CopyParameters>,
+}
+
+/// The parameter indices of a function writing externally received data to a buffer.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct InputParameters {
+    /// The index of the parameter pointing to the written buffer.
+    buffer_index: usize,
+    /// The index of the parameter containing the maximal number of written bytes, if the function has one.
+    #[serde(default)]
+    size_index: Option<usize>,
+}
+
+/// The parameter indices of a memory copying function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CopyParameters {
+    /// The index of the parameter pointing to the destination buffer.
+    destination_index: usize,
+    /// The index of the parameter pointing to the source buffer.
+    source_index: usize,
+    /// The index of the parameter containing the number of copied bytes, if the function has one.
+    #[serde(default)]
+    size_index: Option<usize>,
+}
+
+/// The byte ranges of memory objects that were written with tainted data,
+/// together with the program points that wrote them.
+///
+/// Ranges are given as pairs of the (inclusive) start offset and the (exclusive) end offset.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+struct TaintedRegions {
+    regions: BTreeMap<AbstractIdentifier, BTreeSet<(i64, i64, Tid)>>,
+}
+
+impl TaintedRegions {
+    /// Mark the regions starting at the address and spanning `size` bytes as tainted.
+    /// If the size is unknown, the regions extend to the end of the targeted objects.
+    /// Returns `true` if new tainted regions were added.
+    fn add(&mut self, address: &Data, size: Option<i64>, write_site: &Tid) -> bool {
+        let mut changed = false;
+        for (id, (start, end)) in get_accessed_ranges(address, size) {
+            changed |= self.regions.entry(id.clone()).or_default().insert((
+                start,
+                end,
+                write_site.clone(),
+            ));
+        }
+        changed
+    }
+
+    /// Get the write sites of all tainted regions overlapping the regions
+    /// starting at the address and spanning `size` bytes.
+    fn get_write_sites(&self, address: &Data, size: Option<i64>) -> BTreeSet<Tid> {
+        let mut write_sites = BTreeSet::new();
+        for (id, (start, end)) in get_accessed_ranges(address, size) {
+            if let Some(regions) = self.regions.get(id) {
+                write_sites.extend(
+                    regions
+                        .iter()
+                        .filter(|(region_start, region_end, _)| {
+                            *region_start < end && start < *region_end
+                        })
+                        .map(|(_, _, write_site)| write_site.clone()),
+                );
+            }
+        }
+        write_sites
+    }
+}
+
+/// Get the byte ranges of the memory objects accessed at the address with the given size.
+/// Unknown offsets or sizes are overapproximated by unbounded ranges.
+fn get_accessed_ranges(
+    address: &Data,
+    size: Option<i64>,
+) -> Vec<(&AbstractIdentifier, (i64, i64))> {
+    address
+        .get_relative_values()
+        .iter()
+        .map(|(id, offset)| {
+            let range = match (offset.try_to_offset_interval(), size) {
+                (Ok((start, end)), Some(size)) => (start, end.saturating_add(size.max(1))),
+                (Ok((start, _)), None) => (start, i64::MAX),
+                (Err(_), _) => (i64::MIN, i64::MAX),
+            };
+            (id, range)
+        })
+        .collect()
+}
+
+/// A call to an input or memory copying function
+/// together with the pointer inference state at the call.
+struct ExternCall<'a> {
+    call: &'a Term<Jmp>,
+    symbol: &'a ExternSymbol,
+    pi_state: &'a PointerInferenceState,
+}
+
+/// A basic block together with the function containing it
+/// and the pointer inference state at the start of the block.
+struct BlockState<'a> {
+    block: &'a Term<Blk>,
+    sub: &'a Term<Sub>,
+    pi_state: &'a PointerInferenceState,
+}
+
+/// Check indirect calls and jumps for targets that may point into externally received data
+/// or that are loaded from externally received data.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let input_symbols = get_symbol_map(
+        project,
+        &config.input_functions.keys().cloned().collect::<Vec<_>>(),
+    );
+    if input_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let copy_symbols = get_symbol_map(
+        project,
+        &config.copy_functions.keys().cloned().collect::<Vec<_>>(),
+    );
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let graph = pointer_inference_results.get_graph();
+
+    let mut input_calls = Vec::new();
+    let mut copy_calls = Vec::new();
+    for edge in graph.edge_references() {
+        if let Edge::ExternCallStub(call) = edge.weight() {
+            if let (Jmp::Call { target, .. }, Some(NodeValue::Value(pi_state))) = (
+                &call.term,
+                pointer_inference_results.get_node_value(edge.source()),
+            ) {
+                if let Some(symbol) = input_symbols.get(target) {
+                    input_calls.push(ExternCall {
+                        call,
+                        symbol,
+                        pi_state,
+                    });
+                } else if let Some(symbol) = copy_symbols.get(target) {
+                    copy_calls.push(ExternCall {
+                        call,
+                        symbol,
+                        pi_state,
+                    });
+                }
+            }
+        }
+    }
+    let mut blocks = Vec::new();
+    for (node_id, node) in graph.node_references() {
+        if let (Node::BlkStart(block, sub), Some(NodeValue::Value(pi_state))) =
+            (node, pointer_inference_results.get_node_value(node_id))
+        {
+            blocks.push(BlockState {
+                block,
+                sub,
+                pi_state,
+            });
+        }
+    }
+
+    let mut tainted_regions = TaintedRegions::default();
+    for input_call in input_calls.iter() {
+        let parameters = &config.input_functions[&input_call.symbol.name];
+        taint_parameter_buffer(
+            &mut tainted_regions,
+            input_call,
+            parameters.buffer_index,
+            parameters.size_index,
+            runtime_memory_image,
+        );
+    }
+    // The tainted regions only grow, so the loop terminates.
+    loop {
+        let mut changed = false;
+        for copy_call in copy_calls.iter() {
+            let parameters = &config.copy_functions[&copy_call.symbol.name];
+            let size = get_size_parameter(copy_call, parameters.size_index, runtime_memory_image);
+            let source =
+                match eval_parameter(copy_call, parameters.source_index, runtime_memory_image) {
+                    Some(source) => source,
+                    None => continue,
+                };
+            if !tainted_regions.get_write_sites(&source, size).is_empty() {
+                changed |= taint_parameter_buffer(
+                    &mut tainted_regions,
+                    copy_call,
+                    parameters.destination_index,
+                    parameters.size_index,
+                    runtime_memory_image,
+                );
+            }
+        }
+        for block_state in blocks.iter() {
+            changed |=
+                taint_stored_regions(&mut tainted_regions, block_state, runtime_memory_image);
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut cwe_warnings = BTreeMap::new();
+    for block_state in blocks.iter() {
+        for warning in check_indirect_jumps(&tainted_regions, block_state, runtime_memory_image) {
+            cwe_warnings.insert(warning.tids.clone(), warning);
+        }
+    }
+    (Vec::new(), cwe_warnings.into_values().collect())
+}
+
+/// Evaluate the parameter with the given index at the call.
+fn eval_parameter(
+    extern_call: &ExternCall,
+    index: usize,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Option<Data> {
+    let parameter = extern_call.symbol.parameters.get(index)?;
+    extern_call
+        .pi_state
+        .eval_parameter_arg(parameter, runtime_memory_image)
+        .ok()
+}
+
+/// Get the value of the size parameter with the given index at the call, if it is exactly known.
+fn get_size_parameter(
+    extern_call: &ExternCall,
+    size_index: Option<usize>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Option<i64> {
+    eval_parameter(extern_call, size_index?, runtime_memory_image)?
+        .get_if_absolute_value()?
+        .try_to_offset()
+        .ok()
+}
+
+/// Mark the buffer that the parameter with the given index points to as tainted by the call.
+/// Returns `true` if new tainted regions were added.
+fn taint_parameter_buffer(
+    tainted_regions: &mut TaintedRegions,
+    extern_call: &ExternCall,
+    buffer_index: usize,
+    size_index: Option<usize>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> bool {
+    let size = get_size_parameter(extern_call, size_index, runtime_memory_image);
+    match eval_parameter(extern_call, buffer_index, runtime_memory_image) {
+        Some(buffer) => tainted_regions.add(&buffer, size, &extern_call.call.tid),
+        None => false,
+    }
+}
+
+/// Track the values loaded from tainted regions through the registers of the block.
+/// Calls `handle_def` for each `Def` of the block with the pointer inference state before the `Def`
+/// and the write sites of the tainted data contained in each register before the `Def`.
+/// Returns the pointer inference state and the register taints at the end of the block.
+fn track_register_taint(
+    tainted_regions: &TaintedRegions,
+    block_state: &BlockState,
+    runtime_memory_image: &RuntimeMemoryImage,
+    mut handle_def: impl FnMut(&Term<Def>, &PointerInferenceState, &BTreeMap<Variable, BTreeSet<Tid>>),
+) -> (PointerInferenceState, BTreeMap<Variable, BTreeSet<Tid>>) {
+    let mut pi_state = block_state.pi_state.clone();
+    let mut register_taint: BTreeMap<Variable, BTreeSet<Tid>> = BTreeMap::new();
+    for def in block_state.block.term.defs.iter() {
+        handle_def(def, &pi_state, &register_taint);
+        let (var, write_sites) = match &def.term {
+            Def::Assign { var, value } => (var, get_expression_taint(&register_taint, value)),
+            Def::Load { var, address } => (
+                var,
+                tainted_regions
+                    .get_write_sites(&pi_state.eval(address), Some(u64::from(var.size) as i64)),
+            ),
+            Def::Store { .. } => {
+                pi_state.apply_defs(std::slice::from_ref(def), runtime_memory_image);
+                continue;
+            }
+        };
+        if write_sites.is_empty() {
+            register_taint.remove(var);
+        } else {
+            register_taint.insert(var.clone(), write_sites);
+        }
+        pi_state.apply_defs(std::slice::from_ref(def), runtime_memory_image);
+    }
+    (pi_state, register_taint)
+}
+
+/// Get the write sites of the tainted data that the value of the expression depends on.
+fn get_expression_taint(
+    register_taint: &BTreeMap<Variable, BTreeSet<Tid>>,
+    expression: &Expression,
+) -> BTreeSet<Tid> {
+    expression
+        .input_vars()
+        .into_iter()
+        .filter_map(|var| register_taint.get(var))
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+/// Mark the memory regions written with tainted values by the store instructions of the block as tainted.
+/// Returns `true` if new tainted regions were added.
+fn taint_stored_regions(
+    tainted_regions: &mut TaintedRegions,
+    block_state: &BlockState,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> bool {
+    let mut tainted_stores = Vec::new();
+    track_register_taint(
+        tainted_regions,
+        block_state,
+        runtime_memory_image,
+        |def, pi_state, register_taint| {
+            if let Def::Store { address, value } = &def.term {
+                if !get_expression_taint(register_taint, value).is_empty() {
+                    tainted_stores.push((
+                        pi_state.eval(address),
+                        u64::from(value.bytesize()) as i64,
+                        def.tid.clone(),
+                    ));
+                }
+            }
+        },
+    );
+    let mut changed = false;
+    for (address, size, write_site) in tainted_stores {
+        changed |= tainted_regions.add(&address, Some(size), &write_site);
+    }
+    changed
+}
+
+/// Check the indirect calls and jumps at the end of the block
+/// for targets inside of tainted regions or loaded from tainted regions.
+fn check_indirect_jumps(
+    tainted_regions: &TaintedRegions,
+    block_state: &BlockState,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Vec<CweWarning> {
+    let (pi_state, register_taint) = track_register_taint(
+        tainted_regions,
+        block_state,
+        runtime_memory_image,
+        |_, _, _| (),
+    );
+    let mut warnings = Vec::new();
+    for jmp in block_state.block.term.jmps.iter() {
+        let (target, jump_kind) = match &jmp.term {
+            Jmp::CallInd { target, .. } => (target, "indirect call"),
+            Jmp::BranchInd(target) => (target, "indirect jump"),
+            _ => continue,
+        };
+        let target_value = pi_state.eval(target);
+        let write_sites = tainted_regions.get_write_sites(&target_value, Some(1));
+        if !write_sites.is_empty() {
+            let is_executable_mapping = target_value.get_relative_values().keys().any(|id| {
+                pi_state
+                    .memory
+                    .get_mapping_protection(id)
+                    .is_some_and(|protection| protection.is_executable())
+            });
+            let target_description = if is_executable_mapping {
+                "may execute received data in an executable memory mapping"
+            } else {
+                "may execute received data"
+            };
+            warnings.push(generate_cwe_warning(
+                jmp,
+                jump_kind,
+                target_description,
+                &write_sites,
+                block_state.sub,
+            ));
+            continue;
+        }
+        let write_sites = get_expression_taint(&register_taint, target);
+        if !write_sites.is_empty() {
+            warnings.push(generate_cwe_warning(
+                jmp,
+                jump_kind,
+                "uses a code pointer loaded from received data",
+                &write_sites,
+                block_state.sub,
+            ));
+        }
+    }
+    warnings
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    jmp: &Term<Jmp>,
+    jump_kind: &str,
+    target_description: &str,
+    write_sites: &BTreeSet<Tid>,
+    sub: &Term<Sub>,
+) -> CweWarning {
+    let write_site_addresses: Vec<String> = write_sites
+        .iter()
+        .map(|tid| tid.canonical_address().to_string())
+        .collect();
+    let mut other_entry = vec!["write_sites".to_string()];
+    other_entry.extend(write_sites.iter().map(|tid| format!("{}", tid)));
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Download of Code Without Integrity Check) The {} at {} in function {} {} (written at {})",
+            jump_kind,
+            jmp.tid.canonical_address(),
+            sub.term.name,
+            target_description,
+            write_site_addresses.join(", ")
+        ),
+    )
+    .tids(
+        std::iter::once(format!("{}", jmp.tid))
+            .chain(write_sites.iter().map(|tid| format!("{}", tid)))
+            .collect(),
+    )
+    .addresses(
+        std::iter::once(jmp.tid.canonical_address().to_string())
+            .chain(write_site_addresses)
+            .collect(),
+    )
+    .other(vec![other_entry])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an indirect call to the target with the given return block.
+    fn call_ind(tid: &str, target: Expression, return_block: &str) -> Term<Jmp> {
+        Term {
+            tid: Tid::new(tid),
+            term: Jmp::CallInd {
+                target,
+                return_: Some(Tid::new(return_block)),
+            },
+        }
+    }
+
+    /// Create a return instruction.
+    fn return_jmp(tid: &str) -> Term<Jmp> {
+        Term {
+            tid: Tid::new(tid),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        }
+    }
+
+    /// The `Def`s preparing a call to `recv` writing 0x40 bytes to the stack buffer at `RBX`.
+    fn prepare_recv_call() -> Vec<Term<Def>> {
+        vec![
+            Def::assign(
+                "set_buffer",
+                Variable::mock("RBX", 8),
+                Expression::var("RSP", 8).plus_const(-0x100),
+            ),
+            Def::assign(
+                "set_fd",
+                Variable::mock("RDI", 8),
+                Expression::const_from_i64(3),
+            ),
+            Def::assign(
+                "set_param",
+                Variable::mock("RSI", 8),
+                Expression::var("RBX", 8),
+            ),
+            Def::assign(
+                "set_size",
+                Variable::mock("RDX", 8),
+                Expression::const_from_i64(0x40),
+            ),
+        ]
+    }
+
+    fn mock_project(sub: Term<Sub>) -> Project {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock_with_parameter_registers(
+            vec![
+                Variable::mock("RDI", 8),
+                Variable::mock("RSI", 8),
+                Variable::mock("RDX", 8),
+                Variable::mock("RCX", 8),
+            ],
+            vec![],
+        );
+        cconv.callee_saved_register = vec![Variable::mock("RBX", 8), Variable::mock("R12", 8)];
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("recv").parameters = ["RDI", "RSI", "RDX", "RCX"]
+            .into_iter()
+            .map(|name| Arg::mock_register(name, 8))
+            .collect();
+        project.program.term.entry_points.insert(sub.tid.clone());
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project
+    }
+
+    /// Run the check on a project containing the given function.
+    fn run_check(sub: Term<Sub>) -> Vec<CweWarning> {
+        let project = mock_project(sub);
+        let config = serde_json::json!({
+            "input_functions": {
+                "recv": { "buffer_index": 1, "size_index": 2 },
+            },
+            "copy_functions": {
+                "memcpy": { "destination_index": 0, "source_index": 1, "size_index": 2 },
+            },
+        });
+        AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn recv_into_buffer_then_call() {
+        let sub = Sub::mock_with_blocks(
+            "handler",
+            vec![
+                Blk::mock_with_jmp(
+                    "blk_0",
+                    prepare_recv_call(),
+                    Jmp::call("call_recv", "recv", Some("blk_1")),
+                ),
+                Blk::mock_with_jmp(
+                    "blk_1",
+                    vec![Def::load(
+                        "load_pointer",
+                        Variable::mock("RAX", 8),
+                        Expression::var("RBX", 8).plus_const(8),
+                    )],
+                    call_ind("call_loaded_pointer", Expression::var("RAX", 8), "blk_2"),
+                ),
+                Blk::mock_with_jmp(
+                    "blk_2",
+                    vec![],
+                    call_ind("call_buffer", Expression::var("RBX", 8), "blk_3"),
+                ),
+                Blk::mock_with_jmp("blk_3", vec![], return_jmp("return")),
+            ],
+        );
+        let warnings = run_check(sub);
+        let tids: Vec<_> = warnings.iter().map(|warning| &warning.tids).collect();
+        assert_eq!(
+            tids,
+            vec![
+                &vec!["call_buffer".to_string(), "call_recv".to_string()],
+                &vec!["call_loaded_pointer".to_string(), "call_recv".to_string()],
+            ]
+        );
+        assert_eq!(
+            warnings[0].other,
+            vec![vec!["write_sites".to_string(), "call_recv".to_string()]]
+        );
+    }
+
+    #[test]
+    fn call_through_clean_vtable() {
+        let mut sub = Sub::mock("handler");
+        let mut defs = prepare_recv_call();
+        defs.push(Def::assign(
+            "set_vtable",
+            Variable::mock("R12", 8),
+            Expression::var("RSP", 8).plus_const(-0x200),
+        ));
+        sub.term.blocks = vec![
+            Blk::mock_with_jmp("blk_0", defs, Jmp::call("call_recv", "recv", Some("blk_1"))),
+            Blk::mock_with_jmp(
+                "blk_1",
+                vec![
+                    Def::store(
+                        "store_method",
+                        Expression::var("R12", 8),
+                        Expression::const_from_i64(0x1000),
+                    ),
+                    Def::load(
+                        "load_method",
+                        Variable::mock("RAX", 8),
+                        Expression::var("R12", 8),
+                    ),
+                ],
+                call_ind("call_method", Expression::var("RAX", 8), "blk_2"),
+            ),
+            Blk::mock_with_jmp("blk_2", vec![], return_jmp("return")),
+        ];
+        assert!(run_check(sub).is_empty());
+    }
+}
@@ -36,6 +36,9 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE560",
     version: "0.2",
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// An upper bound for the value of a presumably correct umask argument.
@@ -90,7 +93,7 @@ fn generate_cwe_warning(sub: &Term<Sub>, jmp: &Term<Jmp>, permission_const: u64)
     CweWarning::new(CWE_MODULE.name, CWE_MODULE.version,
         format!("(Use of umask() with chmod-style Argument) Function {} calls umask with argument {:#o}", sub.term.name, permission_const))
         .tids(vec![format!("{}", jmp.tid)])
-        .addresses(vec![jmp.tid.address.clone()])
+        .addresses(vec![jmp.tid.canonical_address().to_string()])
         .other(vec![vec![
             "umask_arg".to_string(),
             format!("{:#o}", permission_const),
@@ -0,0 +1,349 @@
+//! This module implements a check for CWE-562: Return of Stack Variable Address.
+//!
+//! A pointer to a local variable becomes dangling as soon as the function that owns the stack frame returns.
+//! Returning such a pointer or storing it into memory that outlives the function call
+//! (heap objects, global variables or objects owned by a caller)
+//! lets the caller access memory that is reused by later function calls.
+//!
+//! See <https://cwe.mitre.org/data/definitions/562.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the pointer inference analysis we check
+//! - at each return instruction whether a return register may contain a pointer into the current stack frame,
+//! - at each store instruction whether a pointer into the current stack frame
+//!   is written to memory other than the current stack frame.
+//!
+//! Only pointers with negative offsets relative to the stack frame base are considered to point into the current stack frame,
+//! since non-negative offsets point into the stack frame of the caller (e.g. to stack parameters).
+//! Pointers into the stack frames of callers, e.g. out-parameters passed by the caller, are never flagged.
+//! Each warning contains the TID of the escaping instruction and of the function containing it.
+//! Storing a pointer to a local variable into another local variable
+//! or passing it as a parameter to a call does not generate a warning.
+//!
+//! ## False Positives
+//!
+//! - A function may store a pointer to a local variable into a heap object or global variable
+//!   and remove it again before returning.
+//!
+//! ## False Negatives
+//!
+//! - Stack pointers that escape through a callee, e.g. by being stored in a global variable inside the callee, are not detected.
+//! - Stores through pointers whose target is completely unknown are ignored.
+
+use petgraph::visit::IntoNodeReferences;
+
+use crate::abstract_domain::{AbstractDomain, TryToBitvec, TryToInterval};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data, State, ValueDomain};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE562",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// Check all return and store instructions for pointers to the current stack frame
+/// that escape the function.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    for (node_id, node) in pointer_inference_results.get_graph().node_references() {
+        let state = match pointer_inference_results.get_node_value(node_id) {
+            Some(NodeValue::Value(state)) => state,
+            _ => continue,
+        };
+        match node {
+            Node::BlkStart(block, sub) => {
+                let mut state = state.clone();
+                for def in block.term.defs.iter() {
+                    if let Def::Store { address, value } = &def.term {
+                        if let Some(offset) = get_escaping_stack_offset_of_store(
+                            &state,
+                            &state.eval(address),
+                            &state.eval(value),
+                        ) {
+                            cwe_warnings.push(generate_cwe_warning(
+                                sub,
+                                &def.tid,
+                                "stores",
+                                "into memory outliving the function",
+                                &offset,
+                            ));
+                        }
+                    }
+                    state.apply_defs(
+                        std::slice::from_ref(def),
+                        analysis_results.runtime_memory_image,
+                    );
+                }
+            }
+            Node::BlkEnd(block, sub) => {
+                let return_register =
+                    match project.get_specific_calling_convention(&sub.term.calling_convention) {
+                        Some(cconv) => &cconv.integer_return_register,
+                        None => continue,
+                    };
+                for jmp in block.term.jmps.iter() {
+                    if !matches!(jmp.term, Jmp::Return(_)) {
+                        continue;
+                    }
+                    if let Some(offset) = return_register.iter().find_map(|register| {
+                        get_local_stack_offset(state, &state.get_register(register))
+                    }) {
+                        cwe_warnings.push(generate_cwe_warning(
+                            sub,
+                            &jmp.tid,
+                            "returns",
+                            "to the caller",
+                            &offset,
+                        ));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    cwe_warnings.sort();
+    cwe_warnings.dedup();
+    (Vec::new(), cwe_warnings)
+}
+
+/// If the given value may point into the current stack frame,
+/// i.e. to the stack frame base with a negative offset, return the offset.
+fn get_local_stack_offset(state: &State, value: &Data) -> Option<ValueDomain> {
+    let offset = value.get_relative_values().get(&state.stack_id)?;
+    match offset.try_to_offset_interval() {
+        Ok((interval_start, _interval_end)) if interval_start < 0 => Some(offset.clone()),
+        _ => None,
+    }
+}
+
+/// Return `true` if the address may point to memory that outlives the current function call,
+/// i.e. to global memory, to any memory object other than the current stack frame
+/// or to the part of the stack owned by a caller.
+///
+/// Completely unknown addresses are not considered to be escaping.
+fn is_escaping_address(state: &State, address: &Data) -> bool {
+    if address.is_top() {
+        return false;
+    }
+    address.get_absolute_value().is_some()
+        || address.get_relative_values().iter().any(|(id, offset)| {
+            *id != state.stack_id
+                || matches!(
+                    offset.try_to_offset_interval(),
+                    Ok((interval_start, _interval_end)) if interval_start >= 0
+                )
+        })
+}
+
+/// If storing the given value at the given address lets a pointer into the current stack frame escape the function,
+/// return the offset of the pointer into the stack frame.
+fn get_escaping_stack_offset_of_store(
+    state: &State,
+    address: &Data,
+    value: &Data,
+) -> Option<ValueDomain> {
+    if is_escaping_address(state, address) {
+        get_local_stack_offset(state, value)
+    } else {
+        None
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    sub: &Term<Sub>,
+    escaping_instruction: &Tid,
+    action: &str,
+    destination: &str,
+    stack_offset: &ValueDomain,
+) -> CweWarning {
+    let stack_offset = match stack_offset.try_to_offset() {
+        Ok(offset) => format!("{}", offset),
+        Err(_) => format!("{}", stack_offset),
+    };
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Return of Stack Variable Address) The instruction at {} in function {} {} a pointer to the local stack variable at stack offset {} {}",
+            escaping_instruction.canonical_address(), sub.term.name, action, stack_offset, destination
+        ),
+    )
+    .tids(vec![
+        format!("{}", escaping_instruction),
+        format!("{}", sub.tid),
+    ])
+    .symbols(vec![sub.term.name.clone()])
+    .addresses(vec![escaping_instruction.canonical_address().to_string()])
+    .other(vec![vec![
+        "stack_offset".to_string(),
+        stack_offset,
+    ]])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::{AbstractIdentifier, AbstractLocation};
+
+    /// Shortcut for the expression `RSP + offset`.
+    fn stack_address(offset: i64) -> Expression {
+        Expression::var("RSP", 8).plus_const(offset)
+    }
+
+    /// Create a function consisting of a block with the given `Def`s and a call to `callee` (if given),
+    /// followed by a block with the given `Def`s and a return instruction.
+    fn mock_function(
+        name: &str,
+        defs_before_call: Vec<Term<Def>>,
+        callee: Option<&str>,
+        defs_after_call: Vec<Term<Def>>,
+    ) -> Term<Sub> {
+        let first_blk_name = format!("{}_first_blk", name);
+        let second_blk_name = format!("{}_second_blk", name);
+        let first_blk = match callee {
+            Some(callee) => {
+                Blk::mock_call(&first_blk_name, defs_before_call, callee, &second_blk_name)
+            }
+            None => Blk::mock_with_jmp(
+                &first_blk_name,
+                defs_before_call,
+                Jmp::branch(&format!("{}_branch", name), &second_blk_name),
+            ),
+        };
+        let mut second_blk = Blk::mock_return(&second_blk_name);
+        second_blk.term.defs = defs_after_call;
+        Sub::mock_with_blocks(name, vec![first_blk, second_blk])
+    }
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["malloc", "use_buffer"] {
+            project.add_mock_extern_symbol(name);
+        }
+        let functions = vec![
+            mock_function(
+                "return_local",
+                vec![Def::assign(
+                    "return_local_def",
+                    Variable::mock("RAX", 8),
+                    stack_address(-16),
+                )],
+                None,
+                vec![],
+            ),
+            mock_function(
+                "store_into_heap",
+                vec![],
+                Some("malloc"),
+                vec![Def::store(
+                    "store_into_heap_def",
+                    Expression::var("RAX", 8).plus_const(8),
+                    stack_address(-32),
+                )],
+            ),
+            mock_function(
+                "pass_as_argument",
+                vec![
+                    Def::store("store_local_def", stack_address(-8), stack_address(-16)),
+                    Def::assign(
+                        "set_argument_def",
+                        Variable::mock("RDI", 8),
+                        stack_address(-16),
+                    ),
+                ],
+                Some("use_buffer"),
+                vec![Def::assign(
+                    "return_stack_parameter_def",
+                    Variable::mock("RAX", 8),
+                    stack_address(8),
+                )],
+            ),
+        ];
+        for sub in functions {
+            project.program.term.entry_points.insert(sub.tid.clone());
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn escaping_stack_pointers() {
+        let project = mock_project();
+        let warnings =
+            AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+                check_cwe(analysis_results, &serde_json::Value::Null).1
+            });
+        let mut escaping_instructions: Vec<(&str, &str)> = warnings
+            .iter()
+            .map(|warning| (warning.tids[0].as_str(), warning.other[0][1].as_str()))
+            .collect();
+        escaping_instructions.sort();
+        // Offsets are relative to the stack frame base.
+        // After the call to `malloc` the stack pointer was adjusted by the popped return address.
+        assert_eq!(
+            escaping_instructions,
+            vec![
+                ("return_return_local_second_blk", "-16"),
+                ("store_into_heap_def", "-24")
+            ]
+        );
+        assert!(warnings.iter().any(|warning| warning
+            .description
+            .contains("stack offset -16 to the caller")));
+        let return_warning = warnings
+            .iter()
+            .find(|warning| warning.tids[0] == "return_return_local_second_blk")
+            .unwrap();
+        assert_eq!(return_warning.tids[1], "return_local");
+        assert_eq!(return_warning.symbols, vec!["return_local".to_string()]);
+    }
+
+    #[test]
+    fn caller_stack_pointers_are_not_local() {
+        let mut state = State::new(&Variable::mock("RSP", 8), Tid::new("callee"));
+        let caller_stack_id = AbstractIdentifier::new(
+            Tid::new("caller"),
+            AbstractLocation::from_var(&Variable::mock("RSP", 8)).unwrap(),
+        );
+        state.caller_stack_ids.insert(caller_stack_id.clone());
+        let out_parameter = Data::from_target(caller_stack_id, Bitvector::from_i64(-16).into());
+        assert_eq!(get_local_stack_offset(&state, &out_parameter), None);
+        assert!(is_escaping_address(&state, &out_parameter));
+
+        let local_pointer =
+            Data::from_target(state.stack_id.clone(), Bitvector::from_i64(-16).into());
+        assert_eq!(
+            get_local_stack_offset(&state, &local_pointer),
+            Some(Bitvector::from_i64(-16).into())
+        );
+        assert_eq!(
+            get_escaping_stack_offset_of_store(&state, &out_parameter, &local_pointer),
+            Some(Bitvector::from_i64(-16).into())
+        );
+    }
+}
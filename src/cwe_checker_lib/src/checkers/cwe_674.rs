@@ -0,0 +1,550 @@
+//! This module implements a check for CWE-674: Uncontrolled Recursion.
+//!
+//! Recursive functions without a limit on the recursion depth can be driven to exhaust the stack
+//! if the recursion depth is controlled by an attacker,
+//! e.g. by recursive descent parsers processing deeply nested input.
+//!
+//! See <https://cwe.mitre.org/data/definitions/674.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! We compute the strongly connected components of the call graph of the program
+//! to find the sets of (mutually) recursive functions.
+//! For each such recursion cycle we check whether one of its functions limits the recursion depth.
+//! A function limits the recursion depth if it passes one of its own parameters
+//! to a recursive call as `parameter ± constant`
+//! and compares the parameter against a constant in the condition of a conditional jump.
+//! The parameter values are computed by a simple intraprocedural simulation of the function.
+//!
+//! Unguarded recursion cycles are reported if they may process externally controlled input,
+//! i.e. if a function calling one of the taint source symbols configured in config.json
+//! is a (transitive) caller or callee of a function in the cycle.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! The symbols are the functions whose return values or output parameters are assumed to be externally controlled.
+//!
+//! ## False Positives
+//!
+//! - Recursions limited by other means (e.g. by a global depth counter or by the size of the input) are reported.
+//! - The reachability of the cycle from externally controlled input is approximated by the call graph.
+//!   Therefore cycles may be reported even if the input does not influence the recursion depth.
+//!
+//! ## False Negatives
+//!
+//! - A comparison of the depth parameter against a constant is accepted as a guard,
+//!   even if it does not actually end the recursion.
+//! - Parameters passed on the stack are not tracked, so recursion depth guards are only detected for register parameters.
+//! - Depth parameters that are truncated or extended (e.g. `int` parameters on 64-bit architectures)
+//!   are not recognized, since the simulation loses track of the parameter value in these cases.
+//! - Recursions through indirect calls are not detected.
+
+use crate::abstract_domain::{AbstractLocation, TryToBitvec};
+use crate::analysis::pointer_inference::{Data, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::arguments::forwarding::get_sub_calling_convention;
+use crate::utils::arguments::local_simulation::{compute_local_block_start_states, simulate_def};
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::CweModule;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::Dfs;
+use petgraph::Direction;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE674",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Config {
+    /// The names of symbols whose return values or output parameters are assumed to be externally controlled.
+    symbols: Vec<String>,
+}
+
+/// The call graph of the program.
+/// The nodes are the functions of the program and the edge weights are the TIDs of the corresponding call instructions.
+/// Calls to extern symbols are not contained in the graph.
+type CallGraph<'a> = DiGraph<&'a Term<Sub>, &'a Tid>;
+
+/// Generate the call graph of the program.
+fn get_call_graph(project: &Project) -> CallGraph<'_> {
+    let mut graph = CallGraph::new();
+    let mut nodes: HashMap<&Tid, NodeIndex> = HashMap::new();
+    for sub in project.program.term.subs.values() {
+        nodes.insert(&sub.tid, graph.add_node(sub));
+    }
+    for sub in project.program.term.subs.values() {
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if let Some(target_node) = nodes.get(target) {
+                        graph.add_edge(nodes[&sub.tid], *target_node, &jmp.tid);
+                    }
+                }
+            }
+        }
+    }
+    graph
+}
+
+/// Get all recursion cycles of the call graph,
+/// i.e. all strongly connected components that contain at least one call edge.
+fn get_recursion_cycles(graph: &CallGraph) -> Vec<BTreeSet<NodeIndex>> {
+    petgraph::algo::kosaraju_scc(graph)
+        .into_iter()
+        .filter(|component| component.len() > 1 || graph.contains_edge(component[0], component[0]))
+        .map(|component| component.into_iter().collect())
+        .collect()
+}
+
+/// Get the recursive calls of a cycle together with the function containing the call,
+/// i.e. all calls from a function in the cycle to a function in the cycle.
+fn get_recursive_calls<'a>(
+    graph: &CallGraph<'a>,
+    cycle: &BTreeSet<NodeIndex>,
+) -> Vec<(&'a Term<Sub>, &'a Tid)> {
+    let mut calls: Vec<(&Term<Sub>, &Tid)> = graph
+        .edge_indices()
+        .filter_map(|edge| {
+            let (source, target) = graph.edge_endpoints(edge)?;
+            (cycle.contains(&source) && cycle.contains(&target))
+                .then(|| (graph[source], graph[edge]))
+        })
+        .collect();
+    calls.sort_by_key(|(_, call)| *call);
+    calls
+}
+
+/// Return the index of the integer parameter register of the function with the given TID
+/// together with the offset of the value
+/// if the value is the value of this parameter register at the start of the function plus a constant offset.
+fn get_parameter_index_and_offset(
+    value: &Data,
+    sub_tid: &Tid,
+    calling_convention: &CallingConvention,
+) -> Option<(usize, i64)> {
+    let (id, offset) = value.get_if_unique_target()?;
+    if id.get_tid() != sub_tid {
+        return None;
+    }
+    match id.get_location() {
+        AbstractLocation::Register(var) => Some((
+            calling_convention
+                .integer_parameter_register
+                .iter()
+                .position(|register| register == var)?,
+            offset.try_to_offset().ok()?,
+        )),
+        AbstractLocation::Pointer(_, _) => None,
+    }
+}
+
+/// Returns `true` if the expression is a comparison of two values.
+fn is_comparison(expression: &Expression) -> bool {
+    use BinOpType::*;
+    matches!(
+        expression,
+        Expression::BinOp {
+            op: IntEqual | IntNotEqual | IntLess | IntLessEqual | IntSLess | IntSLessEqual,
+            ..
+        }
+    )
+}
+
+/// Get the indices of the parameter registers of the function that are compared against constants
+/// in the conditions of conditional jumps.
+///
+/// For each block ending with a conditional jump we follow the dependencies of the condition
+/// through the `Def`s of the block to find the comparisons that the condition depends on.
+fn get_compared_parameters(
+    sub: &Term<Sub>,
+    calling_convention: &CallingConvention,
+    block_start_states: &HashMap<&Tid, PointerInferenceState>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> HashSet<usize> {
+    let mut compared_parameters = HashSet::new();
+    for block in sub.term.blocks.iter() {
+        let condition = match block.term.jmps.iter().find_map(|jmp| match &jmp.term {
+            Jmp::CBranch { condition, .. } => Some(condition),
+            _ => None,
+        }) {
+            Some(condition) => condition,
+            None => continue,
+        };
+        let mut state = match block_start_states.get(&block.tid) {
+            Some(state) => state.clone(),
+            None => continue,
+        };
+        let mut states_before_defs = Vec::new();
+        for def in block.term.defs.iter() {
+            states_before_defs.push(state.clone());
+            simulate_def(&mut state, def, runtime_memory_image);
+        }
+        let mut comparisons = vec![(condition, state)];
+        let mut relevant_vars: HashSet<&Variable> = condition.input_vars().into_iter().collect();
+        for (def, state_before_def) in block.term.defs.iter().zip(states_before_defs).rev() {
+            if let Def::Assign { var, value } = &def.term {
+                if relevant_vars.remove(var) {
+                    relevant_vars.extend(value.input_vars());
+                    comparisons.push((value, state_before_def));
+                }
+            }
+        }
+        for (expression, state) in comparisons {
+            if let (true, Expression::BinOp { lhs, rhs, .. }) =
+                (is_comparison(expression), expression)
+            {
+                let (lhs, rhs) = (state.eval(lhs), state.eval(rhs));
+                for (parameter_side, constant_side) in [(&lhs, &rhs), (&rhs, &lhs)] {
+                    if constant_side.try_to_bitvec().is_err() {
+                        continue;
+                    }
+                    if let Some((index, _)) =
+                        get_parameter_index_and_offset(parameter_side, &sub.tid, calling_convention)
+                    {
+                        compared_parameters.insert(index);
+                    }
+                }
+            }
+        }
+    }
+    compared_parameters
+}
+
+/// Get the indices of the parameter registers of the function that are passed to one of the given recursive calls
+/// as `parameter ± constant` (with a non-zero constant).
+fn get_depth_parameters(
+    sub: &Term<Sub>,
+    recursive_calls: &BTreeSet<&Tid>,
+    calling_convention: &CallingConvention,
+    block_start_states: &HashMap<&Tid, PointerInferenceState>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> HashSet<usize> {
+    let mut depth_parameters = HashSet::new();
+    for block in sub.term.blocks.iter() {
+        if !block
+            .term
+            .jmps
+            .iter()
+            .any(|jmp| recursive_calls.contains(&jmp.tid))
+        {
+            continue;
+        }
+        let mut state = match block_start_states.get(&block.tid) {
+            Some(state) => state.clone(),
+            None => continue,
+        };
+        for def in block.term.defs.iter() {
+            simulate_def(&mut state, def, runtime_memory_image);
+        }
+        for register in calling_convention.integer_parameter_register.iter() {
+            if let Some((index, offset)) = get_parameter_index_and_offset(
+                &state.get_register(register),
+                &sub.tid,
+                calling_convention,
+            ) {
+                if offset != 0 {
+                    depth_parameters.insert(index);
+                }
+            }
+        }
+    }
+    depth_parameters
+}
+
+/// Returns `true` if the function limits the recursion depth,
+/// i.e. if it passes a parameter as `parameter ± constant` to a recursive call
+/// and compares the parameter against a constant in the condition of a conditional jump.
+fn has_recursion_depth_guard(
+    project: &Project,
+    sub: &Term<Sub>,
+    recursive_calls: &BTreeSet<&Tid>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> bool {
+    let calling_convention = match get_sub_calling_convention(project, &sub.tid) {
+        Some(cconv) => cconv,
+        None => return false,
+    };
+    let block_start_states =
+        compute_local_block_start_states(project, sub, calling_convention, runtime_memory_image);
+    let depth_parameters = get_depth_parameters(
+        sub,
+        recursive_calls,
+        calling_convention,
+        &block_start_states,
+        runtime_memory_image,
+    );
+    if depth_parameters.is_empty() {
+        return false;
+    }
+    let compared_parameters = get_compared_parameters(
+        sub,
+        calling_convention,
+        &block_start_states,
+        runtime_memory_image,
+    );
+    depth_parameters
+        .intersection(&compared_parameters)
+        .next()
+        .is_some()
+}
+
+/// Get all nodes of the call graph reachable from the given nodes in the given direction.
+fn get_reachable_nodes(
+    graph: &CallGraph,
+    start_nodes: &BTreeSet<NodeIndex>,
+    direction: Direction,
+) -> HashSet<NodeIndex> {
+    let mut reachable_nodes = HashSet::new();
+    for start_node in start_nodes {
+        let mut dfs = Dfs::new(&graph, *start_node);
+        while let Some(node) = match direction {
+            Direction::Outgoing => dfs.next(graph),
+            Direction::Incoming => dfs.next(petgraph::visit::Reversed(graph)),
+        } {
+            reachable_nodes.insert(node);
+        }
+    }
+    reachable_nodes
+}
+
+/// Returns `true` if one of the functions calling a taint source symbol
+/// is a (transitive) caller or callee of a function in the cycle.
+fn is_input_reachable(
+    graph: &CallGraph,
+    cycle: &BTreeSet<NodeIndex>,
+    input_functions: &HashSet<&Tid>,
+) -> bool {
+    get_reachable_nodes(graph, cycle, Direction::Incoming)
+        .union(&get_reachable_nodes(graph, cycle, Direction::Outgoing))
+        .any(|node| input_functions.contains(&graph[*node].tid))
+}
+
+/// Generate the CWE warning for an unguarded recursion cycle.
+fn generate_cwe_warning(
+    cycle_functions: &[&Term<Sub>],
+    recursive_calls: &[(&Term<Sub>, &Tid)],
+) -> CweWarning {
+    let function_names: Vec<String> = cycle_functions
+        .iter()
+        .map(|sub| sub.term.name.clone())
+        .collect();
+    let call_addresses: Vec<String> = recursive_calls
+        .iter()
+        .map(|(_, call)| call.canonical_address().to_string())
+        .collect();
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Uncontrolled Recursion) Recursive functions {} without recursion depth limit may process external input (recursive calls at {})",
+            function_names.join(", "),
+            call_addresses.join(", ")
+        ),
+    )
+    .tids(
+        recursive_calls
+            .iter()
+            .map(|(_, call)| format!("{}", call))
+            .collect(),
+    )
+    .addresses(call_addresses)
+    .symbols(function_names)
+}
+
+/// Find recursion cycles in the call graph that do not limit their recursion depth
+/// and that may process externally controlled input.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let runtime_memory_image = analysis_results.runtime_memory_image;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let symbol_map = get_symbol_map(project, &config.symbols);
+    let input_functions: HashSet<&Tid> = project
+        .program
+        .term
+        .subs
+        .values()
+        .filter(|sub| {
+            sub.term.blocks.iter().any(|block| {
+                block.term.jmps.iter().any(|jmp| {
+                    matches!(&jmp.term, Jmp::Call { target, .. } if symbol_map.contains_key(target))
+                })
+            })
+        })
+        .map(|sub| &sub.tid)
+        .collect();
+    let call_graph = get_call_graph(project);
+    let mut cwe_warnings = Vec::new();
+
+    for cycle in get_recursion_cycles(&call_graph) {
+        let recursive_calls = get_recursive_calls(&call_graph, &cycle);
+        let recursive_call_tids: BTreeSet<&Tid> =
+            recursive_calls.iter().map(|(_, call)| *call).collect();
+        let cycle_functions: Vec<&Term<Sub>> = cycle.iter().map(|node| call_graph[*node]).collect();
+        let is_guarded = cycle_functions.iter().any(|sub| {
+            has_recursion_depth_guard(project, sub, &recursive_call_tids, runtime_memory_image)
+        });
+        if !is_guarded && is_input_reachable(&call_graph, &cycle, &input_functions) {
+            cwe_warnings.push(generate_cwe_warning(&cycle_functions, &recursive_calls));
+        }
+    }
+
+    cwe_warnings.sort();
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use std::collections::HashSet;
+
+    /// A function calling `target` with the given `Def`s before the call.
+    /// If `guarded` is set, the function returns early if `RDI` equals zero.
+    fn mock_recursive_function(
+        name: &str,
+        defs: Vec<Term<Def>>,
+        target: &str,
+        guarded: bool,
+    ) -> Term<Sub> {
+        let mut sub = Sub::mock(name);
+        let call_block = Blk::mock_call(
+            &format!("{}_call", name),
+            defs,
+            target,
+            &format!("{}_ret", name),
+        );
+        if guarded {
+            let mut guard_block = Blk::mock_with_tid(&format!("{}_guard", name));
+            guard_block.term.defs.push(Def::assign(
+                &format!("{}_compare", name),
+                Variable::mock("ZF", 1),
+                Expression::BinOp {
+                    op: BinOpType::IntEqual,
+                    lhs: Box::new(Expression::var("RDI", 8)),
+                    rhs: Box::new(Expression::const_from_i64(0)),
+                },
+            ));
+            guard_block.term.jmps = vec![
+                Term {
+                    tid: Tid::new(format!("{}_cond_jump", name)),
+                    term: Jmp::CBranch {
+                        target: Tid::new(format!("{}_ret", name)),
+                        condition: Expression::var("ZF", 1),
+                    },
+                },
+                Jmp::branch(&format!("{}_to_call", name), &format!("{}_call", name)),
+            ];
+            sub.term.blocks.push(guard_block);
+        }
+        sub.term.blocks.push(call_block);
+        sub.term
+            .blocks
+            .push(Blk::mock_return(&format!("{}_ret", name)));
+        sub
+    }
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("read");
+
+        let main = Sub::mock_with_blocks(
+            "main",
+            vec![
+                Blk::mock_call("main_read", Vec::new(), "read", "main_parse"),
+                Blk::mock_call("main_parse", Vec::new(), "parse_a", "main_walk"),
+                Blk::mock_call("main_walk", Vec::new(), "walk_a", "main_ret"),
+                Blk::mock_return("main_ret"),
+            ],
+        );
+        let decrement_depth = vec![Def::assign(
+            "walk_a_decrement",
+            Variable::mock("RDI", 8),
+            Expression::var("RDI", 8).plus_const(-1),
+        )];
+        let subs = vec![
+            main,
+            // An unguarded pair of mutually recursive functions reachable from input.
+            mock_recursive_function("parse_a", Vec::new(), "parse_b", false),
+            mock_recursive_function("parse_b", Vec::new(), "parse_a", false),
+            // A guarded pair: `walk_a` decrements its depth parameter and checks it for zero.
+            mock_recursive_function("walk_a", decrement_depth, "walk_b", true),
+            mock_recursive_function("walk_b", Vec::new(), "walk_a", false),
+            // An unguarded recursive function not connected to input.
+            mock_recursive_function("unconnected", Vec::new(), "unconnected", false),
+        ];
+        for sub in subs {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project.program.term.entry_points.insert(Tid::new("main"));
+        project
+    }
+
+    #[test]
+    fn recursion_cycles() {
+        let project = mock_project();
+        let call_graph = get_call_graph(&project);
+        let mut cycles: Vec<Vec<&str>> = get_recursion_cycles(&call_graph)
+            .iter()
+            .map(|cycle| {
+                let mut names: Vec<&str> = cycle
+                    .iter()
+                    .map(|node| call_graph[*node].term.name.as_str())
+                    .collect();
+                names.sort();
+                names
+            })
+            .collect();
+        cycles.sort();
+        assert_eq!(
+            cycles,
+            vec![
+                vec!["parse_a", "parse_b"],
+                vec!["unconnected"],
+                vec!["walk_a", "walk_b"]
+            ]
+        );
+    }
+
+    #[test]
+    fn unguarded_recursion_reachable_from_input() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+        let config = serde_json::json!({ "symbols": ["read"] });
+
+        let (_, warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "call_parse_a_call".to_string(),
+                "call_parse_b_call".to_string()
+            ]
+        );
+        let mut symbols = warnings[0].symbols.clone();
+        symbols.sort();
+        assert_eq!(symbols, vec!["parse_a".to_string(), "parse_b".to_string()]);
+    }
+}
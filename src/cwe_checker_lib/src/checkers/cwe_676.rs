@@ -9,6 +9,12 @@ How the check works:
   * Calls to dangerous functions are flagged. The list of functions that are considered
 dangerous can be configured in config.json. The default list is based on
 <https://github.com/01org/safestringlib/wiki/SDL-List-of-Banned-Functions>.
+  * For calls to unbounded string functions like strcpy the warning suggests a bounded replacement
+    (e.g. strncpy). If the results of the pointer inference analysis are available
+    and the size of the destination buffer is known, the size is added to the suggestion.
+  * Calls to strcpy and stpcpy are not flagged if the pointer inference analysis shows
+    that the destination buffer has room for the source string,
+    e.g. because it was allocated with `malloc(strlen(source) + 1)`.
 
 False Positives
 
@@ -19,12 +25,20 @@ False Negatives
 * None known
 */
 use crate::prelude::*;
+use petgraph::visit::EdgeRef;
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::{
-    intermediate_representation::{ExternSymbol, Program, Sub, Term, Tid},
+    abstract_domain::TryToBitvec,
+    analysis::{
+        graph::Edge,
+        interprocedural_fixpoint_generic::NodeValue,
+        pointer_inference::{PointerInference, State},
+    },
+    intermediate_representation::{ExternSymbol, Jmp, Program, Sub, Term, Tid},
     utils::{
-        log::{CweWarning, LogMessage},
+        binary::RuntimeMemoryImage,
+        log::{CweWarning, LogMessage, Remediation},
         symbol_utils::get_calls_to_symbols,
     },
 };
@@ -37,6 +51,9 @@ pub static CWE_MODULE: crate::CweModule = crate::CweModule {
     name: "CWE676",
     version: VERSION,
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// struct containing dangerous symbols from config.json
@@ -58,13 +75,16 @@ pub fn get_calls<'a>(
     calls
 }
 
-/// Generate cwe warnings for potentially dangerous function calls
+/// Generate cwe warnings for potentially dangerous function calls.
+///
+/// The `destination_sizes` map call TIDs to the known size of the destination buffer of the call.
 pub fn generate_cwe_warnings<'a>(
     dangerous_calls: Vec<(&'a str, &'a Tid, &'a str)>,
+    destination_sizes: &HashMap<Tid, u64>,
 ) -> Vec<CweWarning> {
     let mut cwe_warnings: Vec<CweWarning> = Vec::new();
     for (sub_name, jmp_tid, target_name) in dangerous_calls.iter() {
-        let address: &String = &jmp_tid.address;
+        let address = jmp_tid.canonical_address();
         let description: String = format!(
             "(Use of Potentially Dangerous Function) {} ({}) -> {}",
             sub_name, address, target_name
@@ -74,13 +94,17 @@ pub fn generate_cwe_warnings<'a>(
             String::from(CWE_MODULE.version),
             description,
         )
-        .addresses(vec![address.clone()])
+        .addresses(vec![address.to_string()])
         .tids(vec![format!("{}", jmp_tid)])
         .symbols(vec![String::from(*sub_name)])
         .other(vec![vec![
             String::from("dangerous_function"),
             String::from(*target_name),
-        ]]);
+        ]])
+        .remediation(get_remediation(
+            target_name,
+            destination_sizes.get(*jmp_tid).copied(),
+        ));
 
         cwe_warnings.push(cwe_warning);
     }
@@ -89,6 +113,141 @@ pub fn generate_cwe_warnings<'a>(
     cwe_warnings
 }
 
+/// Suggest a bounded replacement for a call to an unbounded string function.
+///
+/// If the size of the destination buffer is known, it is given as the bound of the replacement.
+/// Returns `None` for functions without a mechanical replacement.
+fn get_remediation(function: &str, destination_size: Option<u64>) -> Option<Remediation> {
+    let (replacement, further_args): (&str, &[&str]) = match function {
+        "strcpy" => ("strncpy", &[]),
+        "stpcpy" => ("stpncpy", &[]),
+        "gets" => ("fgets", &["stdin"]),
+        _ => return None,
+    };
+    Some(match destination_size {
+        Some(size) => Remediation::ReplaceCall {
+            from: function.to_string(),
+            to: replacement.to_string(),
+            added_args: std::iter::once(format!("{:#x}", size))
+                .chain(further_args.iter().map(|arg| arg.to_string()))
+                .collect(),
+        },
+        None => Remediation::FreeText {
+            text: format!(
+                "replace {} by {} bounded by the size of the destination buffer",
+                function, replacement
+            ),
+        },
+    })
+}
+
+/// Compute the sizes of the destination buffers (i.e. the buffers pointed to by the first parameter)
+/// of the given dangerous calls, if they are known to the pointer inference analysis.
+///
+/// The size is only known if the buffer is a unique memory object
+/// with exactly known size and the offset of the pointer into the object is also exactly known.
+fn get_destination_sizes(
+    pointer_inference_results: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+    dangerous_symbols: &HashMap<&Tid, &str>,
+    external_symbols: &BTreeMap<Tid, ExternSymbol>,
+) -> HashMap<Tid, u64> {
+    let mut destination_sizes = HashMap::new();
+    for (call, symbol, pi_state) in get_states_at_dangerous_calls(
+        pointer_inference_results,
+        dangerous_symbols,
+        external_symbols,
+    ) {
+        let destination = match symbol.parameters.first().and_then(|param| {
+            pi_state
+                .eval_parameter_arg(param, runtime_memory_image)
+                .ok()
+        }) {
+            Some(destination) => destination,
+            None => continue,
+        };
+        let (object_id, offset) = match destination.get_if_unique_target() {
+            Some(target) => target,
+            None => continue,
+        };
+        let object_size = match pi_state.memory.get_allocation_size(object_id) {
+            Some(size) => size.try_to_offset().ok(),
+            None => pi_state
+                .memory
+                .get_object_size(object_id)
+                .and_then(|size| i64::try_from(size).ok()),
+        };
+        if let (Some(object_size), Ok(offset)) = (object_size, offset.try_to_offset()) {
+            if object_size > offset && offset >= 0 {
+                destination_sizes.insert(call.tid.clone(), (object_size - offset) as u64);
+            }
+        }
+    }
+    destination_sizes
+}
+
+/// Get the TIDs of the calls to `strcpy`-like functions among the given dangerous calls
+/// for which the pointer inference analysis shows that the source string fits into the destination buffer.
+fn get_bounded_string_copies(
+    pointer_inference_results: &PointerInference,
+    runtime_memory_image: &RuntimeMemoryImage,
+    dangerous_symbols: &HashMap<&Tid, &str>,
+    external_symbols: &BTreeMap<Tid, ExternSymbol>,
+) -> HashSet<Tid> {
+    get_states_at_dangerous_calls(
+        pointer_inference_results,
+        dangerous_symbols,
+        external_symbols,
+    )
+    .into_iter()
+    .filter(|(_, symbol, _)| matches!(symbol.name.as_str(), "strcpy" | "stpcpy"))
+    .filter(|(_, symbol, pi_state)| {
+        let eval_parameter = |index: usize| {
+            symbol.parameters.get(index).and_then(|param| {
+                pi_state
+                    .eval_parameter_arg(param, runtime_memory_image)
+                    .ok()
+            })
+        };
+        match (eval_parameter(0), eval_parameter(1)) {
+            (Some(destination), Some(source)) => pi_state
+                .memory
+                .is_string_copy_in_bounds(&destination, &source),
+            _ => false,
+        }
+    })
+    .map(|(call, _, _)| call.tid.clone())
+    .collect()
+}
+
+/// Get the calls to dangerous functions together with the called extern symbol
+/// and the pointer inference state right before the call.
+fn get_states_at_dangerous_calls<'b>(
+    pointer_inference_results: &'b PointerInference,
+    dangerous_symbols: &HashMap<&Tid, &str>,
+    external_symbols: &'b BTreeMap<Tid, ExternSymbol>,
+) -> Vec<(&'b Term<Jmp>, &'b ExternSymbol, &'b State)> {
+    let mut states = Vec::new();
+    for edge in pointer_inference_results.get_graph().edge_references() {
+        let call = match edge.weight() {
+            Edge::ExternCallStub(call) => call,
+            _ => continue,
+        };
+        let symbol = match &call.term {
+            Jmp::Call { target, .. } if dangerous_symbols.contains_key(target) => {
+                &external_symbols[target]
+            }
+            _ => continue,
+        };
+        if let Some(NodeValue::Value(pi_state)) =
+            pointer_inference_results.get_node_value(edge.source())
+        {
+            states.push((*call, symbol, pi_state));
+        }
+    }
+    states
+}
+
 /// Filter external symbols by dangerous symbols
 pub fn resolve_symbols<'a>(
     external_symbols: &'a BTreeMap<Tid, ExternSymbol>,
@@ -107,6 +266,7 @@ pub fn resolve_symbols<'a>(
 
 /// Iterate through all function calls inside the program and flag calls to those functions
 /// that are marked as unsafe via the configuration file.
+/// The results of the pointer inference analysis are used for the suggested remediations if they are available.
 pub fn check_cwe(
     analysis_results: &AnalysisResults,
     cwe_params: &serde_json::Value,
@@ -117,7 +277,287 @@ pub fn check_cwe(
     let subfunctions = &prog.term.subs;
     let external_symbols: &BTreeMap<Tid, ExternSymbol> = &prog.term.extern_symbols;
     let dangerous_symbols = resolve_symbols(external_symbols, &config.symbols);
-    let dangerous_calls = get_calls(subfunctions, &dangerous_symbols);
+    let mut dangerous_calls = get_calls(subfunctions, &dangerous_symbols);
+    let destination_sizes = match analysis_results.pointer_inference {
+        Some(pointer_inference_results) => {
+            let bounded_string_copies = get_bounded_string_copies(
+                pointer_inference_results,
+                analysis_results.runtime_memory_image,
+                &dangerous_symbols,
+                external_symbols,
+            );
+            dangerous_calls.retain(|(_, call_tid, _)| !bounded_string_copies.contains(*call_tid));
+            get_destination_sizes(
+                pointer_inference_results,
+                analysis_results.runtime_memory_image,
+                &dangerous_symbols,
+                external_symbols,
+            )
+        }
+        None => HashMap::new(),
+    };
+
+    (
+        vec![],
+        generate_cwe_warnings(dangerous_calls, &destination_sizes),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::*;
+    use std::collections::HashSet;
 
-    (vec![], generate_cwe_warnings(dangerous_calls))
+    /// Create a project where the pointer returned by `malloc(0x40)` is used (with offset 0x10)
+    /// as the destination of `strcpy` and a stack buffer is used as the destination of `gets`.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["malloc", "strcpy", "gets", "memcpy"] {
+            project.add_mock_extern_symbol(name);
+        }
+        let calls = [
+            (Expression::const_from_i64(0x40), "malloc_call", "malloc"),
+            (
+                Expression::var("RAX", 8).plus_const(0x10),
+                "strcpy_call",
+                "strcpy",
+            ),
+            (
+                Expression::var("RSP", 8).plus_const(-0x100),
+                "gets_call",
+                "gets",
+            ),
+            (
+                Expression::var("RSP", 8).plus_const(-0x100),
+                "memcpy_call",
+                "memcpy",
+            ),
+        ];
+        let num_calls = calls.len();
+        let mut sub = Sub::mock("func");
+        for (index, (param, call_tid, target)) in calls.into_iter().enumerate() {
+            let mut block = Blk::mock_with_tid(&format!("blk_{}", index));
+            block.term.defs.push(Def::assign(
+                &format!("set_param_{}", index),
+                Variable::mock("RDI", 8),
+                param,
+            ));
+            block.term.jmps.push(Jmp::call(
+                call_tid,
+                target,
+                Some(&format!("blk_{}", index + 1)),
+            ));
+            sub.term.blocks.push(block);
+        }
+        let mut end_block = Blk::mock_with_tid(&format!("blk_{}", num_calls));
+        end_block.term.jmps.push(Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        sub.term.blocks.push(end_block);
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    #[test]
+    fn bounded_replacement_suggestions() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs: HashSet<Tid> = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pointer_inference.compute();
+        let config = serde_json::json!({ "symbols": ["strcpy", "gets", "memcpy"] });
+
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pointer_inference));
+        let (_, cwe_warnings) = check_cwe(&analysis_results, &config);
+        let remediations: BTreeMap<&str, Option<Remediation>> = cwe_warnings
+            .iter()
+            .map(|cwe| (cwe.tids[0].as_str(), cwe.remediation.clone()))
+            .collect();
+        assert_eq!(
+            remediations["strcpy_call"],
+            Some(Remediation::ReplaceCall {
+                from: "strcpy".to_string(),
+                to: "strncpy".to_string(),
+                added_args: vec!["0x30".to_string()],
+            })
+        );
+        // The size of stack buffers is unknown to the pointer inference analysis.
+        assert_eq!(
+            remediations["gets_call"],
+            Some(Remediation::FreeText {
+                text: "replace gets by fgets bounded by the size of the destination buffer"
+                    .to_string()
+            })
+        );
+        assert_eq!(remediations["memcpy_call"], None);
+
+        // Without the pointer inference results the destination sizes are unknown.
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+        let (_, cwe_warnings) = check_cwe(&analysis_results, &config);
+        assert!(cwe_warnings
+            .iter()
+            .all(|cwe| !matches!(cwe.remediation, Some(Remediation::ReplaceCall { .. }))));
+    }
+    #[test]
+    fn calls_through_thunks_are_attributed_to_caller() {
+        let mut project = mock_project();
+        // `func` calls `strcpy` through the PLT stub `strcpy@plt`.
+        let mut stub_block = Blk::mock_with_tid("stub_blk");
+        stub_block
+            .term
+            .jmps
+            .push(Jmp::call("stub_jmp", "strcpy", None));
+        let mut stub = Sub::mock("strcpy@plt");
+        stub.term.blocks.push(stub_block);
+        project.program.term.subs.insert(stub.tid.clone(), stub);
+        let func = project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::new("func"))
+            .unwrap();
+        func.term.blocks[1].term.jmps[0] = Jmp::call("strcpy_call", "strcpy@plt", Some("blk_2"));
+        let _ = project.resolve_thunks(&Default::default());
+
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+        let config = serde_json::json!({ "symbols": ["strcpy"] });
+        let (_, cwe_warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(cwe_warnings.len(), 1);
+        assert_eq!(cwe_warnings[0].tids, vec!["strcpy_call".to_string()]);
+        assert_eq!(cwe_warnings[0].symbols, vec!["func".to_string()]);
+    }
+
+    #[test]
+    fn string_copies_into_buffers_of_source_length_are_not_flagged() {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["malloc", "strlen", "strcpy"] {
+            let symbol = project.add_mock_extern_symbol(name);
+            if name == "strcpy" {
+                symbol.parameters.push(Arg::mock_register("RSI", 8));
+            }
+        }
+        let assign = |tid: &str, var: &str, value: Expression| {
+            Def::assign(tid, Variable::mock(var, 8), value)
+        };
+        // The source string is kept in the callee-saved register RBP.
+        // The first destination has room for the terminating null byte, the second does not.
+        let blocks = [
+            (
+                vec![assign("def_0", "RDI", Expression::const_from_i64(0x40))],
+                "source_malloc",
+                "malloc",
+            ),
+            (
+                vec![
+                    assign("def_1", "RBP", Expression::var("RAX", 8)),
+                    assign("def_2", "RDI", Expression::var("RAX", 8)),
+                ],
+                "strlen_call",
+                "strlen",
+            ),
+            (
+                vec![assign(
+                    "def_3",
+                    "RDI",
+                    Expression::var("RAX", 8).plus_const(1),
+                )],
+                "malloc_call",
+                "malloc",
+            ),
+            (
+                vec![
+                    assign("def_4", "RDI", Expression::var("RAX", 8)),
+                    assign("def_5", "RSI", Expression::var("RBP", 8)),
+                ],
+                "bounded_strcpy",
+                "strcpy",
+            ),
+            (
+                vec![assign("def_6", "RDI", Expression::var("RBP", 8))],
+                "second_strlen_call",
+                "strlen",
+            ),
+            (
+                vec![assign("def_7", "RDI", Expression::var("RAX", 8))],
+                "second_malloc_call",
+                "malloc",
+            ),
+            (
+                vec![
+                    assign("def_8", "RDI", Expression::var("RAX", 8)),
+                    assign("def_9", "RSI", Expression::var("RBP", 8)),
+                ],
+                "unbounded_strcpy",
+                "strcpy",
+            ),
+        ];
+        let num_blocks = blocks.len();
+        let mut sub = Sub::mock("func");
+        for (index, (defs, call_tid, target)) in blocks.into_iter().enumerate() {
+            let mut block = Blk::mock_with_tid(&format!("blk_{}", index));
+            block.term.defs = defs;
+            block.term.jmps.push(Jmp::call(
+                call_tid,
+                target,
+                Some(&format!("blk_{}", index + 1)),
+            ));
+            sub.term.blocks.push(block);
+        }
+        let mut end_block = Blk::mock_with_tid(&format!("blk_{}", num_blocks));
+        end_block.term.jmps.push(Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        });
+        sub.term.blocks.push(end_block);
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs: HashSet<Tid> = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pointer_inference.compute();
+        let config = serde_json::json!({ "symbols": ["strcpy"] });
+
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pointer_inference));
+        let (_, cwe_warnings) = check_cwe(&analysis_results, &config);
+        let flagged_calls: Vec<&str> = cwe_warnings
+            .iter()
+            .map(|cwe| cwe.tids[0].as_str())
+            .collect();
+        assert_eq!(flagged_calls, vec!["unbounded_strcpy"]);
+
+        // Without the pointer inference results both calls are flagged.
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project);
+        let (_, cwe_warnings) = check_cwe(&analysis_results, &config);
+        assert_eq!(cwe_warnings.len(), 2);
+    }
 }
@@ -0,0 +1,525 @@
+//! This module implements a check for CWE-704: Incorrect Type Conversion or Cast.
+//!
+//! Legacy code sometimes stores pointers in integer variables or fields that are smaller than a pointer,
+//! e.g. in a 4-byte `int` field on a 64-bit architecture.
+//! Such conversions silently discard the upper bytes of the pointer,
+//! so that pointers reconstructed from the stored value point to wild addresses.
+//!
+//! See <https://cwe.mitre.org/data/definitions/704.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using the results of the pointer inference analysis we check two kinds of pointer truncations:
+//! - For store instructions we check whether the stored value is (a part of) a tracked pointer
+//!   that is smaller than the pointer size of the architecture.
+//!   This includes values that are truncated by an assignment earlier in the same basic block
+//!   before they get stored.
+//!   If the lower and upper half of a pointer are stored by two consecutive store instructions
+//!   to adjacent memory locations, the pointer is stored completely and no warning is generated.
+//! - For calls to extern symbols we check whether a tracked pointer is passed
+//!   as a parameter whose data type is smaller than the pointer size of the architecture
+//!   (e.g. a 4-byte `int` parameter).
+//!
+//! ## False Positives
+//!
+//! - The truncation may be intentional, e.g. if only the lower bits of a pointer are used as a hash value.
+//!
+//! ## False Negatives
+//!
+//! - Only pointers tracked by the pointer inference analysis are checked.
+//!   Pointers to global memory are usually not tracked.
+//! - Truncated values are only tracked inside a single basic block.
+
+use crate::abstract_domain::SizedDomain;
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{Data, State};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::{CweModule, SharedAnalysis};
+use petgraph::visit::IntoNodeReferences;
+use std::collections::HashMap;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE704",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// A part of a pointer value that was truncated to a size smaller than the pointer size.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct PointerTruncation {
+    /// The full pointer value before the truncation.
+    pointer: Data,
+    /// The index of the first byte of the pointer contained in the truncated value.
+    low_byte: ByteSize,
+    /// The size of the truncated value.
+    size: ByteSize,
+}
+
+/// A store instruction writing a truncated pointer to memory.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct TruncatingStore {
+    /// The TID of the store instruction.
+    tid: Tid,
+    /// The target address of the store.
+    address: Data,
+    /// The truncated pointer written by the store.
+    truncation: PointerTruncation,
+    /// The index of the store among all store instructions of the basic block.
+    store_index: usize,
+}
+
+/// Return the truncation of a pointer computed by the given expression
+/// if the expression computes a part of a tracked pointer that is smaller than the pointer size.
+///
+/// Truncations computed by earlier assignments in the same basic block are tracked in `truncations`.
+fn get_pointer_truncation(
+    expression: &Expression,
+    state: &State,
+    truncations: &HashMap<Variable, PointerTruncation>,
+    pointer_size: ByteSize,
+) -> Option<PointerTruncation> {
+    let truncation = match expression {
+        Expression::Var(var) => return truncations.get(var).cloned(),
+        Expression::Subpiece {
+            low_byte,
+            size,
+            arg,
+        } => match arg.as_ref() {
+            // `(ptr >> 32)[0:4]` is the upper half of the pointer.
+            Expression::BinOp {
+                op: BinOpType::IntRight | BinOpType::IntSRight,
+                lhs,
+                rhs,
+            } => {
+                let shift = match rhs.as_ref() {
+                    Expression::Const(shift) => shift.try_to_u64().ok()?,
+                    _ => return None,
+                };
+                if shift % 8 != 0 {
+                    return None;
+                }
+                PointerTruncation {
+                    pointer: state.eval(lhs),
+                    low_byte: *low_byte + ByteSize::new(shift / 8),
+                    size: *size,
+                }
+            }
+            _ => PointerTruncation {
+                pointer: state.eval(arg),
+                low_byte: *low_byte,
+                size: *size,
+            },
+        },
+        _ => PointerTruncation {
+            pointer: state.eval(expression),
+            low_byte: ByteSize::new(0),
+            size: expression.bytesize(),
+        },
+    };
+    if truncation.pointer.get_relative_values().is_empty()
+        || truncation.size >= pointer_size
+        || truncation.pointer.bytesize() < pointer_size
+    {
+        None
+    } else {
+        Some(truncation)
+    }
+}
+
+/// Returns `true` if the two consecutive truncating stores write the lower and upper half
+/// of the same pointer to adjacent memory locations,
+/// i.e. if together they store the complete pointer.
+fn is_split_pointer_store(
+    first: &TruncatingStore,
+    second: &TruncatingStore,
+    pointer_size: ByteSize,
+    is_little_endian: bool,
+) -> bool {
+    let (low, high) = if first.truncation.low_byte <= second.truncation.low_byte {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    if second.store_index != first.store_index + 1
+        || low.truncation.pointer != high.truncation.pointer
+        || low.truncation.low_byte != ByteSize::new(0)
+        || high.truncation.low_byte != low.truncation.size
+        || low.truncation.size + high.truncation.size != pointer_size
+    {
+        return false;
+    }
+    let (lower_address, higher_address, lower_size) = if is_little_endian {
+        (&low.address, &high.address, low.truncation.size)
+    } else {
+        (&high.address, &low.address, high.truncation.size)
+    };
+    let offset = Bitvector::from_u64(u64::from(lower_size))
+        .into_resize_unsigned(lower_address.bytesize())
+        .into();
+    lower_address.add_offset(&offset) == *higher_address
+}
+
+/// Generate a human-readable list of the possible targets of the given value.
+fn format_targets(value: &Data) -> String {
+    let mut targets: Vec<String> = value
+        .get_relative_values()
+        .iter()
+        .map(|(id, offset)| format!("{} + {}", id, offset))
+        .collect();
+    if let Some(absolute_value) = value.get_absolute_value() {
+        targets.push(format!("{}", absolute_value));
+    }
+    if value.contains_top() || targets.is_empty() {
+        targets.push("unknown".to_string());
+    }
+    targets.join(", ")
+}
+
+/// Generate the CWE warning for a store instruction writing a truncated pointer to memory.
+fn generate_cwe_warning_for_store(store: &TruncatingStore) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Incorrect Type Conversion) {}-byte pointer to {} truncated to {} bytes when stored to {} at {}",
+            store.truncation.pointer.bytesize(),
+            format_targets(&store.truncation.pointer),
+            store.truncation.size,
+            format_targets(&store.address),
+            store.tid.canonical_address()
+        ))
+        .tids(vec![format!("{}", store.tid)])
+        .addresses(vec![store.tid.canonical_address().to_string()])
+        .other(vec![
+            vec!["pointer_targets".to_string(), format_targets(&store.truncation.pointer)],
+            vec!["store_targets".to_string(), format_targets(&store.address)],
+        ])
+}
+
+/// Generate the CWE warning for a pointer passed to a parameter of an extern symbol
+/// that is smaller than the pointer size.
+fn generate_cwe_warning_for_parameter(
+    callsite: &Tid,
+    symbol: &ExternSymbol,
+    pointer: &Data,
+    parameter_size: ByteSize,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Incorrect Type Conversion) {}-byte pointer to {} passed as {}-byte parameter to {} at {}",
+            pointer.bytesize(),
+            format_targets(pointer),
+            parameter_size,
+            symbol.name,
+            callsite.canonical_address()
+        ))
+        .tids(vec![format!("{}", callsite)])
+        .addresses(vec![callsite.canonical_address().to_string()])
+        .symbols(vec![symbol.name.clone()])
+        .other(vec![vec![
+            "pointer_targets".to_string(),
+            format_targets(pointer),
+        ]])
+}
+
+/// Check the parameters of a call to an extern symbol for pointers
+/// passed to parameters whose data type is smaller than the pointer size.
+fn check_extern_call_parameters(
+    project: &Project,
+    state: &State,
+    callsite: &Tid,
+    symbol: &ExternSymbol,
+) -> Vec<CweWarning> {
+    let pointer_size = project.get_pointer_bytesize();
+    let mut cwe_warnings = Vec::new();
+    for parameter in symbol.parameters.iter() {
+        if let Arg::Register {
+            expr,
+            data_type: Some(data_type),
+        } = parameter
+        {
+            let parameter_size = project
+                .datatype_properties
+                .get_size_from_data_type(data_type.clone());
+            let value = match expr {
+                Expression::Subpiece { arg, .. } => state.eval(arg),
+                _ => state.eval(expr),
+            };
+            if parameter_size < pointer_size
+                && value.bytesize() >= pointer_size
+                && !value.get_relative_values().is_empty()
+            {
+                cwe_warnings.push(generate_cwe_warning_for_parameter(
+                    callsite,
+                    symbol,
+                    &value,
+                    parameter_size,
+                ));
+            }
+        }
+    }
+    cwe_warnings
+}
+
+/// Check the given basic block for truncated pointers that are stored to memory
+/// or passed as parameters to extern symbols.
+///
+/// The `state` is the pointer inference state at the start of the block.
+/// Truncated pointers are tracked until the end of the block.
+fn check_block(
+    project: &Project,
+    global_memory: &RuntimeMemoryImage,
+    mut state: State,
+    block: &Term<Blk>,
+) -> Vec<CweWarning> {
+    let pointer_size = project.get_pointer_bytesize();
+    let mut truncations: HashMap<Variable, PointerTruncation> = HashMap::new();
+    let mut truncating_stores: Vec<TruncatingStore> = Vec::new();
+    let mut store_index = 0;
+    for def in block.term.defs.iter() {
+        match &def.term {
+            Def::Assign { var, value } => {
+                let truncation = get_pointer_truncation(value, &state, &truncations, pointer_size);
+                state.handle_register_assign(var, value);
+                match truncation {
+                    Some(truncation) => truncations.insert(var.clone(), truncation),
+                    None => truncations.remove(var),
+                };
+            }
+            Def::Load { var, address } => {
+                let _ = state.handle_load(var, address, global_memory);
+                truncations.remove(var);
+            }
+            Def::Store { address, value } => {
+                if let Some(truncation) =
+                    get_pointer_truncation(value, &state, &truncations, pointer_size)
+                {
+                    truncating_stores.push(TruncatingStore {
+                        tid: def.tid.clone(),
+                        address: state.eval(address),
+                        truncation,
+                        store_index,
+                    });
+                }
+                store_index += 1;
+                let _ = state.handle_store(address, value, global_memory);
+            }
+        }
+    }
+    let is_little_endian = global_memory.is_little_endian_byte_order();
+    let mut is_split_store = vec![false; truncating_stores.len()];
+    for index in 1..truncating_stores.len() {
+        if is_split_pointer_store(
+            &truncating_stores[index - 1],
+            &truncating_stores[index],
+            pointer_size,
+            is_little_endian,
+        ) {
+            is_split_store[index - 1] = true;
+            is_split_store[index] = true;
+        }
+    }
+    let mut cwe_warnings: Vec<CweWarning> = truncating_stores
+        .iter()
+        .zip(is_split_store)
+        .filter(|(_, is_split_store)| !is_split_store)
+        .map(|(store, _)| generate_cwe_warning_for_store(store))
+        .collect();
+    for jmp in block.term.jmps.iter() {
+        if let Jmp::Call { target, .. } = &jmp.term {
+            if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                cwe_warnings.append(&mut check_extern_call_parameters(
+                    project, &state, &jmp.tid, symbol,
+                ));
+            }
+        }
+    }
+    cwe_warnings
+}
+
+/// Run the CWE check.
+///
+/// For each basic block with a pointer inference state we check for tracked pointers
+/// that are truncated to a smaller size when stored to memory or passed to extern symbols.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    _cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let mut cwe_warnings = Vec::new();
+
+    for (node_id, node) in pointer_inference_results.get_graph().node_references() {
+        if let Node::BlkStart(block, _sub) = node {
+            if let Some(NodeValue::Value(state)) = pointer_inference_results.get_node_value(node_id)
+            {
+                cwe_warnings.append(&mut check_block(
+                    project,
+                    analysis_results.runtime_memory_image,
+                    state.clone(),
+                    block,
+                ));
+            }
+        }
+    }
+
+    cwe_warnings.sort();
+    cwe_warnings.dedup();
+    (Vec::new(), cwe_warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        project.add_mock_extern_symbol("set_handle").parameters = vec![Arg::Register {
+            expr: Expression::var("RDI", 8),
+            data_type: Some(Datatype::Integer),
+        }];
+        project
+    }
+
+    /// `*(RSP - offset) = RSP[low_byte..low_byte + 4]`
+    fn store_half_of_stack_pointer(tid: &str, offset: i64, low_byte: u64) -> Term<Def> {
+        Def::store(
+            tid,
+            Expression::var("RSP", 8).plus_const(-offset),
+            Expression::var("RSP", 8).subpiece(ByteSize::new(low_byte), ByteSize::new(4)),
+        )
+    }
+
+    #[test]
+    fn truncating_store() {
+        let project = mock_project();
+        let global_memory = RuntimeMemoryImage::mock();
+        let state = State::new(&project.stack_pointer_register, Tid::new("func"));
+
+        let block = Blk::mock_call(
+            "block",
+            vec![store_half_of_stack_pointer("store", 16, 0)],
+            "other",
+            "next_block",
+        );
+        let warnings = check_block(&project, &global_memory, state.clone(), &block);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].tids, vec!["store".to_string()]);
+        assert_eq!(
+            warnings[0].other[0],
+            vec![
+                "pointer_targets".to_string(),
+                "func @ RSP + 0x0:i64".to_string()
+            ]
+        );
+        assert_eq!(
+            warnings[0].other[1],
+            vec![
+                "store_targets".to_string(),
+                "func @ RSP + 0xfffffffffffffff0:i64".to_string()
+            ]
+        );
+
+        // The truncation happens in an assignment before the store.
+        let defs = vec![
+            Def::assign(
+                "truncation",
+                Variable::mock("RAX", 4),
+                Expression::var("RSP", 8).subpiece(ByteSize::new(0), ByteSize::new(4)),
+            ),
+            Def::store(
+                "store",
+                Expression::var("RSP", 8).plus_const(-16),
+                Expression::var("RAX", 4),
+            ),
+        ];
+        let block = Blk::mock_call("block", defs, "other", "next_block");
+        assert_eq!(
+            check_block(&project, &global_memory, state.clone(), &block).len(),
+            1
+        );
+
+        // Storing the complete pointer is not a truncation.
+        let defs = vec![Def::store(
+            "store",
+            Expression::var("RSP", 8).plus_const(-16),
+            Expression::var("RSP", 8),
+        )];
+        let block = Blk::mock_call("block", defs, "other", "next_block");
+        assert!(check_block(&project, &global_memory, state, &block).is_empty());
+    }
+
+    #[test]
+    fn split_pointer_store() {
+        let project = mock_project();
+        let global_memory = RuntimeMemoryImage::mock();
+        let state = State::new(&project.stack_pointer_register, Tid::new("func"));
+
+        // The lower half is stored at offset -16 and the upper half at offset -12.
+        let defs = vec![
+            store_half_of_stack_pointer("store_low", 16, 0),
+            store_half_of_stack_pointer("store_high", 12, 4),
+        ];
+        let block = Blk::mock_call("block", defs, "other", "next_block");
+        assert!(check_block(&project, &global_memory, state.clone(), &block).is_empty());
+
+        // The upper half computed by a shift.
+        let defs = vec![
+            store_half_of_stack_pointer("store_low", 16, 0),
+            Def::store(
+                "store_high",
+                Expression::var("RSP", 8).plus_const(-12),
+                Expression::BinOp {
+                    op: BinOpType::IntRight,
+                    lhs: Box::new(Expression::var("RSP", 8)),
+                    rhs: Box::new(Expression::const_from_i64(32)),
+                }
+                .subpiece(ByteSize::new(0), ByteSize::new(4)),
+            ),
+        ];
+        let block = Blk::mock_call("block", defs, "other", "next_block");
+        assert!(check_block(&project, &global_memory, state.clone(), &block).is_empty());
+
+        // The upper half is not stored to the adjacent offset.
+        let defs = vec![
+            store_half_of_stack_pointer("store_low", 16, 0),
+            store_half_of_stack_pointer("store_high", 8, 4),
+        ];
+        let block = Blk::mock_call("block", defs, "other", "next_block");
+        assert_eq!(
+            check_block(&project, &global_memory, state, &block).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn pointer_passed_as_integer_parameter() {
+        let project = mock_project();
+        let global_memory = RuntimeMemoryImage::mock();
+        let mut state = State::new(&project.stack_pointer_register, Tid::new("func"));
+
+        let block = Blk::mock_call("block", Vec::new(), "set_handle", "next_block");
+        state.set_register(&Variable::mock("RDI", 8), Bitvector::from_i64(42).into());
+        assert!(check_block(&project, &global_memory, state.clone(), &block).is_empty());
+
+        let defs = vec![Def::assign(
+            "def",
+            Variable::mock("RDI", 8),
+            Expression::var("RSP", 8),
+        )];
+        let block = Blk::mock_call("block", defs, "set_handle", "next_block");
+        let warnings = check_block(&project, &global_memory, state, &block);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].symbols, vec!["set_handle".to_string()]);
+        assert_eq!(warnings[0].tids, vec!["call_block".to_string()]);
+    }
+}
@@ -0,0 +1,997 @@
+//! This module implements a check for CWE-775: Missing Release of File Descriptor or Handle after Effective Lifetime
+//! and for CWE-1341: Multiple Releases of Same Resource or Handle.
+//!
+//! A file descriptor (or `FILE*` stream) that is neither released nor handed over to other code
+//! before the function owning it returns cannot be released anymore, i.e. it leaks.
+//! Releasing the same descriptor twice may close an unrelated descriptor
+//! that was assigned the same number in the meantime.
+//!
+//! See <https://cwe.mitre.org/data/definitions/775.html>
+//! and <https://cwe.mitre.org/data/definitions/1341.html> for detailed descriptions.
+//!
+//! ## How the check works
+//!
+//! Each function containing a call to a descriptor or stream creating function (e.g. `open` or `fopen`)
+//! is explored path by path, starting at the function entry.
+//! Every created descriptor or stream is tracked as an abstract resource with a set of aliases.
+//! Functions like `dup`, `dup2` and `fdopen` add aliases to an existing resource:
+//! - `dup(fd)` returns a new descriptor referring to the resource of `fd`.
+//! - `dup2(fd, new)` makes `new` refer to the resource of `fd`.
+//!   If `new` referred to a tracked resource before, that alias is implicitly closed.
+//! - `fdopen(fd, mode)` wraps `fd` in a stream, so that `fclose` on the stream also releases `fd`.
+//!
+//! Closing an alias releases only that alias.
+//! The resource itself stays open until all aliases owned by the function are closed.
+//! For each alias we track the registers and stack slots containing it,
+//! where stack slots are determined with the help of the pointer inference analysis.
+//!
+//! A CWE-1341 warning is generated if an alias that is already closed on the current path is closed again.
+//! Closing two distinct aliases of the same resource is not flagged.
+//! A CWE-775 warning is generated if a resource is not released on any path to a return instruction of the function.
+//! Resources that escape the function (i.e. are returned to the caller,
+//! stored outside of the stack frame of the function or passed to a non-extern function)
+//! are treated as released.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The descriptor and stream creating functions.
+//! - The functions releasing descriptors and streams.
+//! - The functions creating aliases of existing descriptors together with the kind of alias they create.
+//!
+//! ## False Positives
+//!
+//! - Descriptors may be intended to stay open for the whole lifetime of the program,
+//!   e.g. log files opened in `main`.
+//! - Descriptors passed to extern functions that release them
+//!   but are not configured as releasing functions are not recognized as released.
+//! - If a pointer to a stack slot containing a descriptor is passed to another function,
+//!   that function may release the descriptor without the check noticing it.
+//!
+//! ## False Negatives
+//!
+//! - A resource released on at least one path is not flagged, even if it leaks on other paths.
+//!   This avoids false positives on error handling paths where the creating call failed.
+//! - Descriptors created through output parameters (e.g. by `pipe`) are not tracked.
+//! - Double releases spanning several functions are not detected.
+//! - The number of explored paths per function is limited.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::IntoNodeReferences;
+
+use crate::abstract_domain::TryToBitvec;
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{PointerInference, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE775",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The maximal number of explored pairs of blocks and states per function.
+const MAX_EXPLORED_STATES: usize = 10_000;
+
+/// The maximal number of stack parameters checked for escaping descriptors on calls to non-extern functions.
+const MAX_STACK_PARAMETERS: i64 = 8;
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// Names of functions returning a newly created file descriptor.
+    descriptor_sources: Vec<String>,
+    /// Names of functions returning a newly opened stream.
+    stream_sources: Vec<String>,
+    /// Names of functions releasing the file descriptor given as their first parameter.
+    descriptor_releases: Vec<String>,
+    /// Names of functions releasing the stream given as their first parameter.
+    stream_releases: Vec<String>,
+    /// Maps the names of functions creating aliases of existing descriptors to the kind of the created alias.
+    alias_sources: BTreeMap<String, AliasSource>,
+}
+
+/// The ways in which a function may create an alias of an existing file descriptor.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AliasSource {
+    /// Return a new descriptor referring to the resource of the descriptor in the first parameter (like `dup`).
+    Duplicate,
+    /// Make the descriptor in the second parameter refer to the resource of the descriptor in the first parameter (like `dup2`).
+    DuplicateTo,
+    /// Return a stream wrapping the descriptor in the first parameter (like `fdopen`).
+    Stream,
+}
+
+/// The kind of an alias of a tracked resource.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum AliasKind {
+    Descriptor,
+    Stream,
+}
+
+impl std::fmt::Display for AliasKind {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AliasKind::Descriptor => write!(formatter, "file descriptor"),
+            AliasKind::Stream => write!(formatter, "stream"),
+        }
+    }
+}
+
+/// A descriptor or stream referring to a tracked resource.
+/// Aliases are identified by the TID of the call that created them.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+struct Alias {
+    /// The TID of the call that created the resource.
+    resource: Tid,
+    kind: AliasKind,
+    is_open: bool,
+    /// Whether the function has to release the alias.
+    /// Descriptors created by `dup2` on top of descriptors not tracked by the analysis
+    /// (e.g. the standard output) need not be released.
+    is_owned: bool,
+    /// For streams wrapping a descriptor, the alias of the wrapped descriptor.
+    wrapped_descriptor: Option<Tid>,
+}
+
+/// The tracked aliases and their locations on a path through a function.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+struct State {
+    /// All aliases created on the path.
+    aliases: BTreeMap<Tid, Alias>,
+    /// Maps registers to the alias they contain.
+    registers: BTreeMap<Variable, Tid>,
+    /// Maps offsets in the stack frame of the function to the alias stored there.
+    stack: BTreeMap<i64, Tid>,
+    /// Resources that escaped the function and thus need not be released by it.
+    escaped_resources: BTreeSet<Tid>,
+}
+
+impl State {
+    /// Get the alias contained in the value of the expression.
+    /// Only copies, extensions and truncations of a register preserve the alias.
+    fn get_alias(&self, expression: &Expression) -> Option<&Tid> {
+        match expression {
+            Expression::Var(var) => self.registers.get(var),
+            Expression::Cast {
+                op: CastOpType::IntZExt | CastOpType::IntSExt,
+                arg,
+                ..
+            } => self.get_alias(arg),
+            Expression::Subpiece { low_byte, arg, .. } if *low_byte == ByteSize::new(0) => {
+                self.get_alias(arg)
+            }
+            _ => None,
+        }
+    }
+
+    /// Start tracking a new resource created by the given call.
+    /// Aliases of a resource created by the same call on an earlier loop iteration are forgotten.
+    fn create_resource(&mut self, call: &Tid, kind: AliasKind) {
+        let outdated_aliases: Vec<Tid> = self
+            .aliases
+            .iter()
+            .filter(|(_, alias)| alias.resource == *call)
+            .map(|(tid, _)| tid.clone())
+            .collect();
+        for alias in outdated_aliases {
+            self.aliases.remove(&alias);
+            self.remove_locations(&alias);
+        }
+        self.escaped_resources.remove(call);
+        self.aliases.insert(
+            call.clone(),
+            Alias {
+                resource: call.clone(),
+                kind,
+                is_open: true,
+                is_owned: true,
+                wrapped_descriptor: None,
+            },
+        );
+    }
+
+    /// Create a new alias (created by the given call) of the resource of the given open descriptor.
+    /// Returns `false` if the descriptor is not open.
+    fn create_alias(
+        &mut self,
+        call: &Tid,
+        descriptor: &Tid,
+        kind: AliasKind,
+        is_owned: bool,
+    ) -> bool {
+        let resource = match self.aliases.get(descriptor) {
+            Some(alias) if alias.is_open && alias.kind == AliasKind::Descriptor => {
+                alias.resource.clone()
+            }
+            _ => return false,
+        };
+        let wrapped_descriptor = (kind == AliasKind::Stream).then(|| descriptor.clone());
+        self.aliases.insert(
+            call.clone(),
+            Alias {
+                resource,
+                kind,
+                is_open: true,
+                is_owned,
+                wrapped_descriptor,
+            },
+        );
+        true
+    }
+
+    /// Handle a call to a `dup2`-like function duplicating `source` onto `target`.
+    ///
+    /// If `target` is a tracked alias, it is implicitly closed
+    /// and all locations containing it refer to the newly created alias afterwards.
+    /// Returns the alias referred to by the return value of the call.
+    fn duplicate_to(
+        &mut self,
+        call: &Tid,
+        source: Option<&Tid>,
+        target: Option<&Tid>,
+    ) -> Option<Tid> {
+        if source.is_some() && source == target {
+            return source.cloned();
+        }
+        let is_owned = match target.and_then(|target| self.aliases.get_mut(target)) {
+            Some(alias) => {
+                alias.is_open = false;
+                alias.is_owned
+            }
+            None => false,
+        };
+        let new_alias = source
+            .filter(|source| self.create_alias(call, source, AliasKind::Descriptor, is_owned))
+            .map(|_| call.clone());
+        if let Some(target) = target {
+            match &new_alias {
+                Some(new_alias) => self.replace_locations(target, new_alias),
+                None => self.remove_locations(target),
+            }
+        }
+        new_alias
+    }
+
+    /// Release the given alias and, for streams wrapping a descriptor, also the wrapped descriptor.
+    /// Returns the alias that was already released before, if there is one.
+    fn release(&mut self, alias: &Tid, kind: AliasKind) -> Option<Tid> {
+        match self.aliases.get(alias) {
+            Some(state) if state.kind == kind => (),
+            _ => return None,
+        }
+        let mut next_alias = Some(alias.clone());
+        while let Some(tid) = next_alias.take() {
+            if let Some(alias) = self.aliases.get_mut(&tid) {
+                if !alias.is_open {
+                    return Some(tid);
+                }
+                alias.is_open = false;
+                next_alias = alias.wrapped_descriptor.clone();
+            }
+        }
+        None
+    }
+
+    /// Mark the resource of the alias as escaped if the alias is still open.
+    fn mark_as_escaped(&mut self, alias: &Tid) {
+        if let Some(alias) = self.aliases.get(alias) {
+            if alias.is_open {
+                self.escaped_resources.insert(alias.resource.clone());
+            }
+        }
+    }
+
+    /// Remove the alias from all registers and stack slots.
+    fn remove_locations(&mut self, alias: &Tid) {
+        self.registers
+            .retain(|_, location_alias| location_alias != alias);
+        self.stack
+            .retain(|_, location_alias| location_alias != alias);
+    }
+
+    /// Replace the alias by another alias in all registers and stack slots.
+    fn replace_locations(&mut self, old_alias: &Tid, new_alias: &Tid) {
+        for location_alias in self.registers.values_mut().chain(self.stack.values_mut()) {
+            if location_alias == old_alias {
+                *location_alias = new_alias.clone();
+            }
+        }
+    }
+
+    /// Remove all aliases from registers that are not callee-saved in the given calling convention.
+    fn remove_non_callee_saved_registers(&mut self, cconv: &CallingConvention) {
+        self.registers
+            .retain(|register, _| cconv.callee_saved_register.contains(register));
+    }
+
+    /// Return `true` if the resource escaped the function or all its aliases owned by the function are closed.
+    fn is_released(&self, resource: &Tid) -> bool {
+        self.escaped_resources.contains(resource)
+            || self
+                .aliases
+                .values()
+                .filter(|alias| alias.resource == *resource && alias.is_owned)
+                .all(|alias| !alias.is_open)
+    }
+
+    /// Get the TIDs of all tracked resources.
+    fn get_resources(&self) -> BTreeSet<Tid> {
+        self.aliases
+            .values()
+            .map(|alias| alias.resource.clone())
+            .collect()
+    }
+}
+
+/// The findings of the path exploration of a function.
+#[derive(Default)]
+struct FunctionFindings {
+    /// Maps the TIDs of calls creating aliases to the name of the called function and the kind of the alias.
+    alias_sources: BTreeMap<Tid, (String, AliasKind)>,
+    /// Resources that are released on at least one path to a return instruction.
+    released_resources: BTreeSet<Tid>,
+    /// Resources that are not released on at least one path to a return instruction.
+    unreleased_resources: BTreeSet<Tid>,
+    /// Aliases released again after they were already released,
+    /// together with the TID and the name of the releasing call.
+    double_releases: BTreeSet<(Tid, Tid, String)>,
+}
+
+impl FunctionFindings {
+    /// Generate the CWE warnings for the findings in the given function.
+    fn generate_cwe_warnings(&self, sub: &Term<Sub>) -> Vec<CweWarning> {
+        let mut warnings = Vec::new();
+        for resource in self
+            .unreleased_resources
+            .difference(&self.released_resources)
+        {
+            let (source_name, kind) = &self.alias_sources[resource];
+            warnings.push(generate_leak_warning(resource, source_name, *kind, sub));
+        }
+        for (alias, release_call, release_name) in self.double_releases.iter() {
+            let (source_name, kind) = &self.alias_sources[alias];
+            warnings.push(generate_double_release_warning(
+                alias,
+                source_name,
+                *kind,
+                release_call,
+                release_name,
+                sub,
+            ));
+        }
+        warnings
+    }
+}
+
+/// The analysis results and node indices needed for the path exploration.
+struct Context<'a> {
+    project: &'a Project,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    pointer_inference_results: &'a PointerInference<'a>,
+    config: Config,
+    /// Maps the pairs `(block TID, function TID)` to the `BlkStart` node of the block.
+    block_start_nodes: HashMap<(Tid, Tid), NodeIndex>,
+}
+
+/// Search for file descriptors and streams that are never released by the function creating them
+/// and for descriptors and streams that are released twice.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let source_names: Vec<String> = config
+        .descriptor_sources
+        .iter()
+        .chain(config.stream_sources.iter())
+        .cloned()
+        .collect();
+    let source_symbols = get_symbol_map(project, &source_names);
+    if source_symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let mut block_start_nodes = HashMap::new();
+    for (node_id, node) in pointer_inference_results.get_graph().node_references() {
+        if let Node::BlkStart(block, sub) = node {
+            block_start_nodes.insert((block.tid.clone(), sub.tid.clone()), node_id);
+        }
+    }
+    let context = Context {
+        project,
+        runtime_memory_image: analysis_results.runtime_memory_image,
+        pointer_inference_results,
+        config,
+        block_start_nodes,
+    };
+
+    let mut logs = Vec::new();
+    let mut cwe_warnings = Vec::new();
+    for sub in project.program.term.subs.values() {
+        let calls_source = sub
+            .term
+            .blocks
+            .iter()
+            .flat_map(|block| block.term.jmps.iter())
+            .any(|jmp| matches!(&jmp.term, Jmp::Call { target, .. } if source_symbols.contains_key(target)));
+        if !calls_source {
+            continue;
+        }
+        match context.explore_function(sub) {
+            Ok(findings) => cwe_warnings.append(&mut findings.generate_cwe_warnings(sub)),
+            Err(error) => logs.push(
+                LogMessage::new_debug(error.to_string())
+                    .location(sub.tid.clone())
+                    .source(CWE_MODULE.name),
+            ),
+        }
+    }
+    cwe_warnings.sort();
+    (logs, cwe_warnings)
+}
+
+impl<'a> Context<'a> {
+    /// Explore all paths through the function starting at the function entry.
+    /// Returns an error if the number of explored states exceeds [`MAX_EXPLORED_STATES`].
+    fn explore_function(&self, sub: &Term<Sub>) -> Result<FunctionFindings, Error> {
+        let mut findings = FunctionFindings::default();
+        let start_block = match sub.term.blocks.first() {
+            Some(block) => block,
+            None => return Ok(findings),
+        };
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut explored_states: HashSet<(&Tid, State)> = HashSet::new();
+        let mut worklist = vec![(&start_block.tid, State::default())];
+        while let Some((block_tid, state)) = worklist.pop() {
+            let block = match blocks.get(block_tid) {
+                Some(block) => *block,
+                None => continue,
+            };
+            if !explored_states.insert((block_tid, state.clone())) {
+                continue;
+            }
+            if explored_states.len() > MAX_EXPLORED_STATES {
+                return Err(anyhow!(
+                    "Path exploration aborted after {} states",
+                    MAX_EXPLORED_STATES
+                ));
+            }
+            let (state, pi_state) = self.update_defs(state, block, sub);
+            for jmp in block.term.jmps.iter() {
+                match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                        worklist.push((target, state.clone()))
+                    }
+                    Jmp::Call {
+                        target,
+                        return_: Some(return_target),
+                    } => {
+                        let mut state = state.clone();
+                        match self.project.program.term.extern_symbols.get(target) {
+                            Some(symbol) => self.handle_extern_call(
+                                &mut state,
+                                pi_state.as_ref(),
+                                &jmp.tid,
+                                symbol,
+                                &mut findings,
+                            ),
+                            None => {
+                                let calling_convention = self
+                                    .project
+                                    .program
+                                    .term
+                                    .subs
+                                    .get(target)
+                                    .and_then(|callee| callee.term.calling_convention.clone());
+                                self.handle_unknown_call(
+                                    &mut state,
+                                    pi_state.as_ref(),
+                                    &calling_convention,
+                                );
+                            }
+                        }
+                        worklist.push((return_target, state));
+                    }
+                    Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => {
+                        let mut state = state.clone();
+                        self.handle_unknown_call(&mut state, pi_state.as_ref(), &None);
+                        worklist.push((return_target, state));
+                    }
+                    Jmp::CallOther {
+                        return_: Some(return_target),
+                        ..
+                    } => worklist.push((return_target, state.clone())),
+                    Jmp::Return(_) => self.handle_return(state.clone(), sub, &mut findings),
+                    _ => (),
+                }
+            }
+        }
+        Ok(findings)
+    }
+
+    /// Apply the `Def`s of the block to the state.
+    /// Returns the state and the pointer inference state at the end of the block.
+    fn update_defs(
+        &self,
+        mut state: State,
+        block: &Term<Blk>,
+        sub: &Term<Sub>,
+    ) -> (State, Option<PointerInferenceState>) {
+        let mut pi_state = self
+            .block_start_nodes
+            .get(&(block.tid.clone(), sub.tid.clone()))
+            .and_then(
+                |node_id| match self.pointer_inference_results.get_node_value(*node_id) {
+                    Some(NodeValue::Value(pi_state)) => Some(pi_state.clone()),
+                    _ => None,
+                },
+            );
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Assign { var, value } => match state.get_alias(value).cloned() {
+                    Some(alias) => {
+                        state.registers.insert(var.clone(), alias);
+                    }
+                    None => {
+                        state.registers.remove(var);
+                    }
+                },
+                Def::Load { var, address } => {
+                    match pi_state
+                        .as_ref()
+                        .and_then(|pi_state| self.get_stack_offset(pi_state, address))
+                        .and_then(|offset| state.stack.get(&offset).cloned())
+                    {
+                        Some(alias) => {
+                            state.registers.insert(var.clone(), alias);
+                        }
+                        None => {
+                            state.registers.remove(var);
+                        }
+                    }
+                }
+                Def::Store { address, value } => {
+                    let offset = pi_state
+                        .as_ref()
+                        .and_then(|pi_state| self.get_stack_offset(pi_state, address));
+                    match (state.get_alias(value).cloned(), offset) {
+                        (Some(alias), Some(offset)) => {
+                            state.stack.insert(offset, alias);
+                        }
+                        (Some(alias), None) => state.mark_as_escaped(&alias),
+                        (None, Some(offset)) => {
+                            state.stack.remove(&offset);
+                        }
+                        (None, None) => (),
+                    }
+                }
+            }
+            if let Some(pi_state) = pi_state.as_mut() {
+                pi_state.apply_defs(std::slice::from_ref(def), self.runtime_memory_image);
+            }
+        }
+        (state, pi_state)
+    }
+
+    /// If the address points to an exactly known offset in the stack frame of the current function,
+    /// return the offset.
+    fn get_stack_offset(
+        &self,
+        pi_state: &PointerInferenceState,
+        address: &Expression,
+    ) -> Option<i64> {
+        let address = pi_state.eval(address);
+        let (id, offset) = address.get_if_unique_target()?;
+        if *id != pi_state.stack_id {
+            return None;
+        }
+        // Only negative offsets point into the current stack frame.
+        offset.try_to_offset().ok().filter(|offset| *offset < 0)
+    }
+
+    /// Get the alias contained in the parameter with the given index.
+    fn get_parameter_alias(
+        &self,
+        state: &State,
+        pi_state: Option<&PointerInferenceState>,
+        symbol: &ExternSymbol,
+        index: usize,
+    ) -> Option<Tid> {
+        match symbol.parameters.get(index)? {
+            Arg::Register { expr, .. } => state.get_alias(expr).cloned(),
+            Arg::Stack { address, .. } => {
+                let offset = self.get_stack_offset(pi_state?, address)?;
+                state.stack.get(&offset).cloned()
+            }
+        }
+    }
+
+    /// Apply the effects of a call to an extern symbol to the state.
+    fn handle_extern_call(
+        &self,
+        state: &mut State,
+        pi_state: Option<&PointerInferenceState>,
+        call: &Tid,
+        symbol: &ExternSymbol,
+        findings: &mut FunctionFindings,
+    ) {
+        let name = &symbol.name;
+        let descriptor = self.get_parameter_alias(state, pi_state, symbol, 0);
+        let mut new_alias = None;
+        if self.config.descriptor_sources.contains(name) {
+            state.create_resource(call, AliasKind::Descriptor);
+            new_alias = Some((call.clone(), AliasKind::Descriptor));
+        } else if self.config.stream_sources.contains(name) {
+            state.create_resource(call, AliasKind::Stream);
+            new_alias = Some((call.clone(), AliasKind::Stream));
+        } else if self.config.descriptor_releases.contains(name)
+            || self.config.stream_releases.contains(name)
+        {
+            let kind = if self.config.descriptor_releases.contains(name) {
+                AliasKind::Descriptor
+            } else {
+                AliasKind::Stream
+            };
+            if let Some(released_alias) = descriptor.and_then(|alias| state.release(&alias, kind)) {
+                findings
+                    .double_releases
+                    .insert((released_alias, call.clone(), name.clone()));
+            }
+        } else {
+            match self.config.alias_sources.get(name) {
+                Some(AliasSource::DuplicateTo) => {
+                    let target = self.get_parameter_alias(state, pi_state, symbol, 1);
+                    new_alias = state
+                        .duplicate_to(call, descriptor.as_ref(), target.as_ref())
+                        .map(|alias| (alias, AliasKind::Descriptor));
+                }
+                Some(source) => {
+                    let kind = if *source == AliasSource::Stream {
+                        AliasKind::Stream
+                    } else {
+                        AliasKind::Descriptor
+                    };
+                    if descriptor
+                        .is_some_and(|descriptor| state.create_alias(call, &descriptor, kind, true))
+                    {
+                        new_alias = Some((call.clone(), kind));
+                    }
+                }
+                None => (),
+            }
+        }
+        if let Some(cconv) = self
+            .project
+            .get_specific_calling_convention(&symbol.calling_convention)
+        {
+            state.remove_non_callee_saved_registers(cconv);
+        }
+        if let Some((alias, kind)) = new_alias {
+            if alias == *call {
+                findings
+                    .alias_sources
+                    .insert(call.clone(), (name.clone(), kind));
+            }
+            if let Some(Arg::Register {
+                expr: Expression::Var(return_register),
+                ..
+            }) = symbol.return_values.first()
+            {
+                state.registers.insert(return_register.clone(), alias);
+            }
+        }
+    }
+
+    /// Apply the effects of a call to a non-extern or unknown function to the state.
+    ///
+    /// Descriptors passed as parameters escape the current function,
+    /// since the called function may release them.
+    fn handle_unknown_call(
+        &self,
+        state: &mut State,
+        pi_state: Option<&PointerInferenceState>,
+        calling_convention: &Option<String>,
+    ) {
+        let cconv = match self
+            .project
+            .get_specific_calling_convention(calling_convention)
+        {
+            Some(cconv) => cconv,
+            None => return,
+        };
+        let mut passed_aliases: Vec<Tid> = cconv
+            .integer_parameter_register
+            .iter()
+            .filter_map(|register| state.registers.get(register).cloned())
+            .collect();
+        let stack_pointer = Expression::Var(self.project.stack_pointer_register.clone());
+        if let Some(stack_offset) =
+            pi_state.and_then(|pi_state| self.get_stack_offset(pi_state, &stack_pointer))
+        {
+            let parameter_area_size =
+                MAX_STACK_PARAMETERS * u64::from(self.project.stack_pointer_register.size) as i64;
+            passed_aliases.extend(
+                state
+                    .stack
+                    .range(stack_offset..stack_offset + parameter_area_size)
+                    .map(|(_, alias)| alias.clone()),
+            );
+        }
+        for alias in passed_aliases {
+            state.mark_as_escaped(&alias);
+        }
+        state.remove_non_callee_saved_registers(cconv);
+    }
+
+    /// Record which resources are released at the given return instruction.
+    /// Descriptors returned to the caller escape the function.
+    fn handle_return(&self, mut state: State, sub: &Term<Sub>, findings: &mut FunctionFindings) {
+        if let Some(cconv) = self
+            .project
+            .get_specific_calling_convention(&sub.term.calling_convention)
+        {
+            for register in cconv.integer_return_register.iter() {
+                if let Some(alias) = state.registers.get(register).cloned() {
+                    state.mark_as_escaped(&alias);
+                }
+            }
+        }
+        for resource in state.get_resources() {
+            if state.is_released(&resource) {
+                findings.released_resources.insert(resource);
+            } else {
+                findings.unreleased_resources.insert(resource);
+            }
+        }
+    }
+}
+
+/// Generate the CWE warning for a resource that is not released on any path through the function.
+fn generate_leak_warning(
+    source_call: &Tid,
+    source_name: &str,
+    kind: AliasKind,
+    sub: &Term<Sub>,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Missing Release of File Descriptor) The {} created by {} at {} in function {} is not released on any path through the function",
+            kind, source_name, source_call.canonical_address(), sub.term.name
+        ),
+    )
+    .tids(vec![format!("{}", source_call)])
+    .addresses(vec![source_call.canonical_address().to_string()])
+    .symbols(vec![source_name.to_string()])
+}
+
+/// Generate the CWE warning for an alias that is released twice.
+fn generate_double_release_warning(
+    source_call: &Tid,
+    source_name: &str,
+    kind: AliasKind,
+    release_call: &Tid,
+    release_name: &str,
+    sub: &Term<Sub>,
+) -> CweWarning {
+    CweWarning::new(
+        "CWE1341",
+        CWE_MODULE.version,
+        format!(
+            "(Multiple Releases of Same Resource) The {} created by {} at {} in function {} is released again by {} at {}",
+            kind, source_name, source_call.canonical_address(), sub.term.name, release_name, release_call.canonical_address()
+        ),
+    )
+    .tids(vec![format!("{}", source_call), format!("{}", release_call)])
+    .addresses(vec![source_call.canonical_address().to_string(), release_call.canonical_address().to_string()])
+    .symbols(vec![source_name.to_string(), release_name.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shortcut for copying the value of one register to another register.
+    fn copy(tid: &str, destination: &str, source: &str) -> Term<Def> {
+        Def::assign(
+            tid,
+            Variable::mock(destination, 8),
+            Expression::var(source, 8),
+        )
+    }
+
+    fn mock_project(functions: Vec<Term<Sub>>) -> Project {
+        let mut project = Project::mock_empty();
+        let mut cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8), Variable::mock("RSI", 8)],
+            vec![],
+        );
+        cconv.callee_saved_register = vec![Variable::mock("RBX", 8), Variable::mock("R12", 8)];
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["open", "close", "dup", "dup2", "fdopen", "fclose"] {
+            project.add_mock_extern_symbol(name).parameters =
+                vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)];
+        }
+        for sub in functions {
+            project.program.term.entry_points.insert(sub.tid.clone());
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    /// Run the check on a project containing the given functions.
+    fn run_check(functions: Vec<Term<Sub>>) -> Vec<CweWarning> {
+        let project = mock_project(functions);
+        let config = serde_json::json!({
+            "descriptor_sources": ["open"],
+            "stream_sources": [],
+            "descriptor_releases": ["close"],
+            "stream_releases": ["fclose"],
+            "alias_sources": {
+                "dup": "Duplicate",
+                "dup2": "DuplicateTo",
+                "fdopen": "Stream",
+            },
+        });
+        AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    /// Get the names and TIDs of the given warnings.
+    fn get_warning_tids(warnings: &[CweWarning]) -> Vec<(&str, Vec<&str>)> {
+        warnings
+            .iter()
+            .map(|warning| {
+                (
+                    warning.name.as_str(),
+                    warning.tids.iter().map(|tid| tid.as_str()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn leaked_descriptor() {
+        // The descriptor is returned to the caller and thus escapes the function.
+        let escaping = Sub::mock_with_calls("escape", vec![(vec![], "open")]);
+        assert!(run_check(vec![escaping]).is_empty());
+        // Overwriting the only copy of the descriptor leaks it.
+        let mut leaking = Sub::mock_with_calls("leak", vec![(vec![], "open")]);
+        leaking.term.blocks[1].term.defs = vec![Def::assign(
+            "overwrite_fd",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0),
+        )];
+        let warnings = run_check(vec![leaking]);
+        assert_eq!(
+            get_warning_tids(&warnings),
+            vec![("CWE775", vec!["call_leak_blk_0"])]
+        );
+    }
+
+    #[test]
+    fn dup2_implicitly_closes_target() {
+        // fd1 = open(); fd2 = open(); dup2(fd1, fd2); close(fd2); close(fd1);
+        let blocks = |close_fd1: bool| {
+            let mut blocks = vec![
+                (vec![], "open"),
+                (vec![copy("save_fd1", "RBX", "RAX")], "open"),
+                (
+                    vec![
+                        copy("save_fd2", "R12", "RAX"),
+                        copy("dup2_old", "RDI", "RBX"),
+                        copy("dup2_new", "RSI", "R12"),
+                    ],
+                    "dup2",
+                ),
+                (vec![copy("close_fd2", "RDI", "R12")], "close"),
+            ];
+            if close_fd1 {
+                blocks.push((vec![copy("close_fd1", "RDI", "RBX")], "close"));
+            }
+            blocks
+        };
+        assert!(run_check(vec![Sub::mock_with_calls("redirect", blocks(true))]).is_empty());
+        // Without closing `fd1` only the resource of `fd1` leaks,
+        // since the resource of `fd2` was released by `dup2`.
+        let warnings = run_check(vec![Sub::mock_with_calls("redirect", blocks(false))]);
+        assert_eq!(
+            get_warning_tids(&warnings),
+            vec![("CWE775", vec!["call_redirect_blk_0"])]
+        );
+    }
+
+    #[test]
+    fn fclose_releases_fdopen_descriptor() {
+        // fd = open(); file = fdopen(fd, mode); fclose(file);
+        let mut blocks = vec![
+            (vec![], "open"),
+            (
+                vec![
+                    copy("save_fd", "RBX", "RAX"),
+                    copy("fdopen_fd", "RDI", "RAX"),
+                ],
+                "fdopen",
+            ),
+            (vec![copy("fclose_file", "RDI", "RAX")], "fclose"),
+        ];
+        assert!(run_check(vec![Sub::mock_with_calls("stream", blocks.clone())]).is_empty());
+        // Closing the descriptor after closing the stream releases it twice.
+        blocks.push((vec![copy("close_fd", "RDI", "RBX")], "close"));
+        let warnings = run_check(vec![Sub::mock_with_calls("stream", blocks)]);
+        assert_eq!(
+            get_warning_tids(&warnings),
+            vec![("CWE1341", vec!["call_stream_blk_0", "call_stream_blk_3"])]
+        );
+    }
+
+    #[test]
+    fn close_each_alias_once() {
+        // fd = open(); copy = dup(fd); close(fd); close(copy);
+        // The duplicated descriptor is kept in a stack slot addressed through a frame register.
+        let stack_slot = Expression::var("R12", 8);
+        let mut blocks = vec![
+            (
+                vec![Def::assign(
+                    "set_frame",
+                    Variable::mock("R12", 8),
+                    Expression::var("RSP", 8).plus_const(-64),
+                )],
+                "open",
+            ),
+            (
+                vec![copy("save_fd", "RBX", "RAX"), copy("dup_fd", "RDI", "RAX")],
+                "dup",
+            ),
+            (
+                vec![
+                    Def::store("store_copy", stack_slot.clone(), Expression::var("RAX", 8)),
+                    copy("close_fd", "RDI", "RBX"),
+                ],
+                "close",
+            ),
+            (
+                vec![Def::load(
+                    "load_copy",
+                    Variable::mock("RDI", 8),
+                    stack_slot.clone(),
+                )],
+                "close",
+            ),
+        ];
+        assert!(run_check(vec![Sub::mock_with_calls("aliases", blocks.clone())]).is_empty());
+        // Closing the same alias twice is flagged.
+        blocks[3].0 = vec![copy("close_fd_again", "RDI", "RBX")];
+        let warnings = run_check(vec![Sub::mock_with_calls("aliases", blocks)]);
+        let warning_tids = get_warning_tids(&warnings);
+        assert!(
+            warning_tids.contains(&("CWE1341", vec!["call_aliases_blk_0", "call_aliases_blk_3"]))
+        );
+        // The duplicate is not closed anymore, so the resource leaks.
+        assert!(warning_tids.contains(&("CWE775", vec!["call_aliases_blk_0"])));
+    }
+}
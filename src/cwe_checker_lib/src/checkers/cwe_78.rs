@@ -17,9 +17,28 @@
 //! is used for this check. As it considers the order of characters, it can be further used for a manual
 //! post analysis of the commands given to system calls.
 //!
+//! Commands constructed by string functions like `sprintf` or `strcat` in called functions,
+//! e.g. in a helper function writing the command into a buffer of its caller,
+//! are propagated back to the caller by the string abstraction.
+//! If the call constructing the command string is known,
+//! the warning contains its address in addition to the address of the system call.
+//!
+//! Additionally, the pointer inference is used to check whether the command string
+//! is located in a buffer filled with external input by a call to one of the configured input symbols
+//! (e.g. `recv` or `read`) in the same function.
+//! This is the case if the system call is reachable from the input call inside the function,
+//! the command parameter points to the same memory object as the input buffer
+//! and the offset intervals of both pointers overlap.
+//! Internal functions that fill a buffer of their caller by calling an input symbol
+//! (e.g. a wrapper like `read_exact` around `recv`) are treated as derived input symbols,
+//! see the [`taint_sources`](crate::analysis::taint_sources) module for details.
+//! The warning then contains the address of the input call in addition to the address of the system call.
+//!
 //! ### Symbols configurable in config.json
 //!
-//! The system calls considered in this check can be configured in the config.json.
+//! - The `system_symbols` are the system calls considered in this check.
+//! - The `input_symbols` map the names of functions filling a buffer with external input
+//!   to the indices of their parameters pointing to the buffer.
 //!
 //! ## False Positives
 //!
@@ -30,17 +49,23 @@
 //!
 //! - Missing substrings due to lost track of pointer targets
 //! - Non tracked function parameters cause incomplete strings that could miss possible dangerous inputs
+//! - Input buffers are only detected if they are filled in the same function that calls the system symbol
+//!   or in a function called by it.
+//! - Since the strings of a called function are merged over all of its call sites,
+//!   commands that a called function constructs differently for different callers may be imprecise.
 
 use petgraph::visit::EdgeRef;
 
-use crate::CweModule;
+use crate::{CweModule, SharedAnalysis};
 
 use crate::abstract_domain::BricksDomain;
 use crate::abstract_domain::TryToBitvec;
-use crate::analysis::graph::Edge;
+use crate::analysis::graph::{Edge, Graph};
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
 use crate::analysis::pointer_inference::State as PointerInferenceState;
 use crate::analysis::string_abstraction::context::Context;
 use crate::analysis::string_abstraction::state::State;
+use crate::analysis::taint_sources::{get_input_buffers, may_point_into_buffer, InputBuffers};
 use crate::intermediate_representation::Arg;
 use crate::intermediate_representation::Expression;
 use crate::intermediate_representation::ExternSymbol;
@@ -48,9 +73,13 @@ use crate::intermediate_representation::Jmp;
 use crate::intermediate_representation::Sub;
 use crate::prelude::*;
 use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::feedback::{report_imprecision, ImprecisionReason, MissingFact};
 use crate::utils::log::CweWarning;
 use crate::utils::log::LogMessage;
 
+use petgraph::algo::has_path_connecting;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeFiltered;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
@@ -59,6 +88,8 @@ pub static CWE_MODULE: CweModule = CweModule {
     name: "CWE78",
     version: "0.1",
     run: check_cwe,
+    required_analyses: &[SharedAnalysis::StringAbstraction],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
 };
 
 /// The configuration struct
@@ -66,6 +97,10 @@ pub static CWE_MODULE: CweModule = CweModule {
 pub struct Config {
     /// The names of the system call symbols
     system_symbols: Vec<String>,
+    /// Maps the names of functions filling a buffer with external input
+    /// to the indices of their parameters pointing to the buffer.
+    #[serde(default)]
+    input_symbols: BTreeMap<String, Vec<usize>>,
 }
 
 /// This check checks the string parameter at system calls given by the string abstraction analysis
@@ -95,12 +130,15 @@ pub fn check_cwe(
         .into_iter()
         .find(|(_, symbol)| config.system_symbols.contains(&symbol.name));
     let string_graph = string_abstraction.get_graph();
+    // The system calls together with the node before the call.
+    let mut system_calls = Vec::new();
 
-    if let Some((_, system)) = system_symbol {
+    if let Some((_, system)) = &system_symbol {
         for edge in string_graph.edge_references() {
             if let Edge::ExternCallStub(jmp) = edge.weight() {
                 if let Jmp::Call { target, .. } = &jmp.term {
                     if system.tid == *target {
+                        system_calls.push((jmp.tid.clone(), edge.source()));
                         if let Some(source_node) = string_abstraction.get_node_value(edge.source())
                         {
                             if let Some(pi_node) = analysis_results
@@ -113,7 +151,7 @@ pub fn check_cwe(
                                 check_system_call_parameter(
                                     source_state,
                                     pi_state,
-                                    &system,
+                                    system,
                                     &jmp.tid,
                                     &cwe_sender,
                                     &log_sender,
@@ -135,12 +173,76 @@ pub fn check_cwe(
         };
     }
 
+    let mut log_messages: Vec<LogMessage> = log_receiver.try_iter().collect();
+    if let (Some((_, system)), false) = (&system_symbol, config.input_symbols.is_empty()) {
+        let (input_buffers, logs) =
+            get_input_buffers(analysis_results, CWE_MODULE.name, cwe_params);
+        log_messages.extend(logs);
+        for (system_call, node) in system_calls {
+            if let Some(cwe) =
+                check_input_provenance(analysis_results, &input_buffers, system, &system_call, node)
+            {
+                // Warnings generated from the string abstraction take precedence.
+                cwe_warnings
+                    .entry(system_call.canonical_address().to_string())
+                    .or_insert(cwe);
+            }
+        }
+    }
+
     let cwe_warnings = cwe_warnings.into_iter().map(|(_, cwe)| cwe).collect();
-    let log_messages = log_receiver.try_iter().collect();
 
     (log_messages, cwe_warnings)
 }
 
+/// Check whether the command parameter of the given system call points into a buffer
+/// filled with external input before the system call in the same function.
+/// If yes, generate a CWE warning containing the address of the input call.
+fn check_input_provenance(
+    analysis_results: &AnalysisResults,
+    input_buffers: &InputBuffers,
+    system_symbol: &ExternSymbol,
+    system_call: &Tid,
+    node: NodeIndex,
+) -> Option<CweWarning> {
+    let sub = analysis_results.control_flow_graph[node].get_sub();
+    let state = match analysis_results.pointer_inference?.get_node_value(node) {
+        Some(NodeValue::Value(state)) => state,
+        _ => return None,
+    };
+    let command = state
+        .eval_parameter_arg(
+            system_symbol.parameters.first()?,
+            analysis_results.runtime_memory_image,
+        )
+        .ok()?;
+    let (_, input_call, _) =
+        input_buffers
+            .get(&sub.tid)?
+            .iter()
+            .find(|(input_node, _, buffer)| {
+                is_intraprocedurally_reachable(
+                    analysis_results.control_flow_graph,
+                    *input_node,
+                    node,
+                ) && may_point_into_buffer(&command, buffer)
+            })?;
+    Some(generate_cwe_warning(
+        &sub.term.name,
+        system_call,
+        Some(input_call),
+        &system_symbol.name,
+    ))
+}
+
+/// Returns `true` if the target node is reachable from the source node without leaving the function.
+fn is_intraprocedurally_reachable(graph: &Graph, source: NodeIndex, target: NodeIndex) -> bool {
+    let intraprocedural_graph = EdgeFiltered::from_fn(graph, |edge| {
+        !matches!(edge.weight(), Edge::Call(_) | Edge::CrReturnStub)
+    });
+    has_path_connecting(&intraprocedural_graph, source, target, None)
+}
+
 /// Checks the system call parameter given by the Bricks Domain.
 pub fn check_system_call_parameter(
     source_state: &State<BricksDomain>,
@@ -160,6 +262,7 @@ pub fn check_system_call_parameter(
         if let Some(value) = source_state.get_variable_to_pointer_map().get(var) {
             let contains_string_constant = value.get_absolute_value().is_some();
             let contains_relative_string_pointer = !value.get_relative_values().is_empty();
+            let construction_site = source_state.get_construction_site(pi_state, value);
             if contains_relative_string_pointer {
                 let mut parameter_domain =
                     Context::<BricksDomain>::merge_domains_from_multiple_pointer_targets(
@@ -181,6 +284,7 @@ pub fn check_system_call_parameter(
                 check_if_string_domain_indicates_vulnerability(
                     parameter_domain,
                     jmp_tid,
+                    construction_site,
                     sub,
                     system_symbol,
                     cwe_collector,
@@ -189,22 +293,35 @@ pub fn check_system_call_parameter(
                 let _ = cwe_collector.send(generate_cwe_warning(
                     &sub.term.name,
                     jmp_tid,
+                    construction_site,
                     &system_symbol.name,
                 ));
             }
         } else {
             let _ = log_collector.send(LogMessage::new_debug(format!(
                 "No Parameter tracked for system call at {}",
-                jmp_tid.address
+                jmp_tid.canonical_address()
             )));
+            let _ = log_collector.send(report_imprecision(
+                CWE_MODULE.name,
+                jmp_tid,
+                ImprecisionReason::TopPointer,
+                MissingFact::new(format!(
+                    "Target of the command string parameter of {}",
+                    system_symbol.name
+                )),
+            ));
         }
     }
 }
 
 /// Checks if the Bricks Domain indicates a vulnerability at the system call.
+///
+/// The construction site is the call site of the string symbol that constructed the command string, if known.
 pub fn check_if_string_domain_indicates_vulnerability(
     input_domain: BricksDomain,
     jmp_tid: &Tid,
+    construction_site: Option<&Tid>,
     sub: &Term<Sub>,
     system_symbol: &ExternSymbol,
     cwe_collector: &crossbeam_channel::Sender<CweWarning>,
@@ -214,6 +331,7 @@ pub fn check_if_string_domain_indicates_vulnerability(
             let _ = cwe_collector.send(generate_cwe_warning(
                 &sub.term.name,
                 jmp_tid,
+                construction_site,
                 &system_symbol.name,
             ));
         }
@@ -225,6 +343,7 @@ pub fn check_if_string_domain_indicates_vulnerability(
                 let _ = cwe_collector.send(generate_cwe_warning(
                     &sub.term.name,
                     jmp_tid,
+                    construction_site,
                     &system_symbol.name,
                 ));
             }
@@ -232,22 +351,513 @@ pub fn check_if_string_domain_indicates_vulnerability(
     }
 }
 
-/// Generates the CWE Warning for the CWE 78 check
-pub fn generate_cwe_warning(sub_name: &str, jmp_tid: &Tid, symbol_name: &str) -> CweWarning {
-    let description: String = format!(
+/// Generates the CWE Warning for the CWE 78 check.
+///
+/// If the construction site of the command string is known,
+/// it is added as second address to the warning.
+pub fn generate_cwe_warning(
+    sub_name: &str,
+    jmp_tid: &Tid,
+    construction_site: Option<&Tid>,
+    symbol_name: &str,
+) -> CweWarning {
+    let mut description: String = format!(
         "(OS Command Injection) Input for call to {} may not be properly sanitized in function {} ({})",
-        symbol_name, sub_name, jmp_tid.address,
+        symbol_name, sub_name, jmp_tid.canonical_address(),
     );
+    let mut addresses = vec![jmp_tid.canonical_address().to_string()];
+    let mut tids = vec![format!("{}", jmp_tid)];
+    if let Some(construction_site) = construction_site {
+        description.push_str(&format!(
+            ". The command is constructed at {}",
+            construction_site.canonical_address()
+        ));
+        addresses.push(construction_site.canonical_address().to_string());
+        tids.push(format!("{}", construction_site));
+    }
     CweWarning::new(
         String::from(CWE_MODULE.name),
         String::from(CWE_MODULE.version),
         description,
     )
-    .addresses(vec![jmp_tid.address.clone()])
-    .tids(vec![format!("{}", jmp_tid)])
+    .addresses(addresses)
+    .tids(tids)
     .symbols(vec![String::from(sub_name)])
     .other(vec![vec![
         String::from("OS Command Injection"),
         symbol_name.to_string(),
     ]])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::analysis::string_abstraction::tests::mock_project_with_intraprocedural_control_flow;
+    use crate::analysis::string_abstraction::Config as StringAbstractionConfig;
+    use crate::intermediate_representation::{Blk, CallOtherConfig, Def, Project, Variable};
+
+    /// Mock a block with the given `Def`s and a call to the given target.
+    fn mock_block(name: &str, defs: Vec<Term<Def>>, call: Option<(&str, &str)>) -> Term<Blk> {
+        let mut block = Blk::mock_with_tid(name);
+        block.term.defs = defs;
+        block.term.jmps = vec![match call {
+            Some((target, return_block)) => Jmp::call(
+                &format!("{}_call_{}", name, target),
+                target,
+                Some(return_block),
+            ),
+            None => Term {
+                tid: Tid::new(format!("{}_return", name)),
+                term: Jmp::Return(Expression::var("lr", 4)),
+            },
+        }];
+        block
+    }
+
+    /// Mock a project, where the function `build_cmd` uses `sprintf` to write a command
+    /// into a buffer passed by `main`, which then passes the buffer to `system`.
+    fn mock_project() -> Project {
+        let mut project = mock_project_with_intraprocedural_control_flow(vec![], "main");
+        let mut system = ExternSymbol::mock_scanf_symbol_arm();
+        system.tid = Tid::new("system");
+        system.name = "system".to_string();
+        system.has_var_args = false;
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(system.tid.clone(), system);
+
+        let mut main = Sub::mock("main");
+        main.term.blocks = vec![
+            mock_block(
+                "main_blk0",
+                vec![
+                    Def::assign(
+                        "main_def0",
+                        Variable::mock("sp", 4),
+                        Expression::var("sp", 4).minus_const(0x80),
+                    ),
+                    Def::assign(
+                        "main_def1",
+                        Variable::mock("r0", 4),
+                        Expression::var("sp", 4).plus_const(0x40),
+                    ),
+                    Def::assign(
+                        "main_def2",
+                        Variable::mock("r1", 4),
+                        Expression::var("sp", 4).plus_const(0x10),
+                    ),
+                ],
+                Some(("build_cmd", "main_blk1")),
+            ),
+            mock_block(
+                "main_blk1",
+                vec![Def::assign(
+                    "main_def3",
+                    Variable::mock("r0", 4),
+                    Expression::var("sp", 4).plus_const(0x40),
+                )],
+                Some(("system", "main_blk2")),
+            ),
+            mock_block("main_blk2", vec![], None),
+        ];
+        let mut build_cmd = Sub::mock("build_cmd");
+        build_cmd.term.blocks = vec![
+            mock_block(
+                "build_cmd_blk0",
+                vec![
+                    Def::assign(
+                        "build_cmd_def0",
+                        Variable::mock("r2", 4),
+                        Expression::var("r1", 4),
+                    ),
+                    // The format string "cat %s"
+                    Def::assign(
+                        "build_cmd_def1",
+                        Variable::mock("r1", 4),
+                        Expression::const_from_i32(0x500c),
+                    ),
+                ],
+                Some(("sprintf", "build_cmd_blk1")),
+            ),
+            mock_block("build_cmd_blk1", vec![], None),
+        ];
+        project.program.term.subs = [main, build_cmd]
+            .into_iter()
+            .map(|sub| (sub.tid.clone(), sub))
+            .collect();
+        project
+    }
+
+    #[test]
+    fn command_constructed_in_called_function() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pi_results = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pi_results.compute();
+        let string_abstraction = crate::analysis::string_abstraction::run::<BricksDomain>(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            &pi_results,
+            StringAbstractionConfig {
+                string_symbols: vec!["sprintf".to_string()],
+                format_string_index: [("sprintf".to_string(), 1)].into_iter().collect(),
+            },
+        );
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pi_results))
+            .set_string_abstraction(Some(&string_abstraction));
+
+        let (_, warnings) = check_cwe(
+            &analysis_results,
+            &serde_json::json!({"system_symbols": ["system"]}),
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "main_blk1_call_system".to_string(),
+                "build_cmd_blk0_call_sprintf".to_string()
+            ]
+        );
+        assert_eq!(warnings[0].symbols, vec!["main".to_string()]);
+    }
+
+    /// Mock a project, where `main` reads input into two stack buffers and passes both buffers to `system`.
+    /// The first buffer is filled by `read_exact`, a wrapper around `recv`,
+    /// the second buffer by `read_message`, which calls `read_exact`.
+    fn mock_project_with_input_wrappers() -> Project {
+        let mut project = mock_project_with_intraprocedural_control_flow(vec![], "main");
+        let mut system = ExternSymbol::mock_scanf_symbol_arm();
+        system.tid = Tid::new("system");
+        system.name = "system".to_string();
+        system.has_var_args = false;
+        let mut recv = ExternSymbol::mock_memcpy_symbol_arm();
+        recv.tid = Tid::new("recv");
+        recv.name = "recv".to_string();
+        recv.has_var_args = false;
+        for symbol in [system, recv] {
+            project
+                .program
+                .term
+                .extern_symbols
+                .insert(symbol.tid.clone(), symbol);
+        }
+
+        let stack_address_in_r0 = |tid: &str, offset: i64| {
+            Def::assign(
+                tid,
+                Variable::mock("r0", 4),
+                Expression::var("sp", 4).plus_const(offset),
+            )
+        };
+        let mut main = Sub::mock("main");
+        main.term.blocks = vec![
+            mock_block(
+                "main_blk0",
+                vec![
+                    Def::assign(
+                        "main_def0",
+                        Variable::mock("sp", 4),
+                        Expression::var("sp", 4).minus_const(0x80),
+                    ),
+                    Def::assign(
+                        "main_def1",
+                        Variable::mock("r1", 4),
+                        Expression::var("sp", 4).plus_const(0x40),
+                    ),
+                ],
+                Some(("read_exact", "main_blk1")),
+            ),
+            mock_block(
+                "main_blk1",
+                vec![stack_address_in_r0("main_def2", 0x10)],
+                Some(("read_message", "main_blk2")),
+            ),
+            mock_block(
+                "main_blk2",
+                vec![stack_address_in_r0("main_def3", 0x40)],
+                Some(("system", "main_blk3")),
+            ),
+            mock_block(
+                "main_blk3",
+                vec![stack_address_in_r0("main_def4", 0x10)],
+                Some(("system", "main_blk4")),
+            ),
+            mock_block("main_blk4", vec![], None),
+        ];
+        // Warnings are deduplicated by the address of the system call, so the calls need distinct addresses.
+        for (index, block) in main.term.blocks.iter_mut().enumerate() {
+            block.term.jmps[0].tid.address = format!("{:x}", 0x1000 + 4 * index);
+        }
+        let mut read_exact = Sub::mock("read_exact");
+        read_exact.term.blocks = vec![
+            mock_block("read_exact_blk0", vec![], Some(("recv", "read_exact_blk1"))),
+            mock_block("read_exact_blk1", vec![], None),
+        ];
+        let mut read_message = Sub::mock("read_message");
+        read_message.term.blocks = vec![
+            mock_block(
+                "read_message_blk0",
+                vec![
+                    Def::assign(
+                        "read_message_def0",
+                        Variable::mock("r1", 4),
+                        Expression::var("r0", 4),
+                    ),
+                    Def::assign(
+                        "read_message_def1",
+                        Variable::mock("r0", 4),
+                        Expression::const_from_i32(0),
+                    ),
+                ],
+                Some(("read_exact", "read_message_blk1")),
+            ),
+            mock_block("read_message_blk1", vec![], None),
+        ];
+        project.program.term.subs = [main, read_exact, read_message]
+            .into_iter()
+            .map(|sub| (sub.tid.clone(), sub))
+            .collect();
+        project
+    }
+
+    #[test]
+    fn command_read_by_input_wrapper() {
+        let project = mock_project_with_input_wrappers();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pi_results = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pi_results.compute();
+        let string_abstraction = crate::analysis::string_abstraction::run::<BricksDomain>(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            &pi_results,
+            StringAbstractionConfig {
+                string_symbols: vec!["sprintf".to_string()],
+                format_string_index: [("sprintf".to_string(), 1)].into_iter().collect(),
+            },
+        );
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pi_results))
+            .set_string_abstraction(Some(&string_abstraction));
+
+        let (_, warnings) = check_cwe(
+            &analysis_results,
+            &serde_json::json!({"system_symbols": ["system"]}),
+        );
+        assert!(warnings.is_empty());
+
+        let (logs, warnings) = check_cwe(
+            &analysis_results,
+            &serde_json::json!({"system_symbols": ["system"], "input_symbols": {"recv": [1]}}),
+        );
+        let tids: Vec<&Vec<String>> = warnings.iter().map(|cwe| &cwe.tids).collect();
+        assert_eq!(
+            tids,
+            vec![
+                &vec![
+                    "main_blk2_call_system".to_string(),
+                    "main_blk0_call_read_exact".to_string()
+                ],
+                &vec![
+                    "main_blk3_call_system".to_string(),
+                    "main_blk1_call_read_message".to_string()
+                ]
+            ]
+        );
+        assert!(logs
+            .iter()
+            .any(|log| log.text.contains("read_message (wraps recv)")));
+    }
+
+    #[test]
+    fn command_read_after_system_call() {
+        let mut project = mock_project_with_input_wrappers();
+        // Swap the targets of the calls in `main_blk1` and `main_blk3`,
+        // so that the second buffer is only filled after it was passed to `system`.
+        let main = project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::new("main"))
+            .unwrap();
+        let mut targets = Vec::new();
+        for index in [1, 3] {
+            if let Jmp::Call { target, .. } = &main.term.blocks[index].term.jmps[0].term {
+                targets.push(target.clone());
+            }
+        }
+        for (index, new_target) in [(1, &targets[1]), (3, &targets[0])] {
+            if let Jmp::Call { target, .. } = &mut main.term.blocks[index].term.jmps[0].term {
+                *target = new_target.clone();
+            }
+        }
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pi_results = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pi_results.compute();
+        let string_abstraction = crate::analysis::string_abstraction::run::<BricksDomain>(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            &pi_results,
+            StringAbstractionConfig {
+                string_symbols: vec!["sprintf".to_string()],
+                format_string_index: [("sprintf".to_string(), 1)].into_iter().collect(),
+            },
+        );
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pi_results))
+            .set_string_abstraction(Some(&string_abstraction));
+
+        let (_, warnings) = check_cwe(
+            &analysis_results,
+            &serde_json::json!({"system_symbols": ["system"], "input_symbols": {"recv": [1]}}),
+        );
+        let tids: Vec<&Vec<String>> = warnings.iter().map(|cwe| &cwe.tids).collect();
+        assert_eq!(
+            tids,
+            vec![&vec![
+                "main_blk2_call_system".to_string(),
+                "main_blk0_call_read_exact".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn command_read_by_raw_syscall() {
+        let mut project = mock_project_with_intraprocedural_control_flow(vec![], "main");
+        let mut system = ExternSymbol::mock_scanf_symbol_arm();
+        system.tid = Tid::new("system");
+        system.name = "system".to_string();
+        system.has_var_args = false;
+        project
+            .program
+            .term
+            .extern_symbols
+            .insert(system.tid.clone(), system);
+        // `main` reads into a stack buffer with an inline `svc` instruction
+        // and passes the buffer to `system`.
+        let mut read_block = mock_block(
+            "main_blk0",
+            vec![
+                Def::assign(
+                    "main_def0",
+                    Variable::mock("sp", 4),
+                    Expression::var("sp", 4).minus_const(0x80),
+                ),
+                Def::assign(
+                    "main_def1",
+                    Variable::mock("r1", 4),
+                    Expression::var("sp", 4).plus_const(0x40),
+                ),
+                Def::assign(
+                    "main_def2",
+                    Variable::mock("r7", 4),
+                    Expression::const_from_i32(3),
+                ),
+            ],
+            None,
+        );
+        read_block.term.jmps = vec![Term {
+            tid: Tid::new("main_blk0_svc"),
+            term: Jmp::CallOther {
+                description: "software_interrupt".to_string(),
+                return_: Some(Tid::new("main_blk1")),
+            },
+        }];
+        let mut main = Sub::mock("main");
+        main.term.blocks = vec![
+            read_block,
+            mock_block(
+                "main_blk1",
+                vec![Def::assign(
+                    "main_def3",
+                    Variable::mock("r0", 4),
+                    Expression::var("sp", 4).plus_const(0x40),
+                )],
+                Some(("system", "main_blk2")),
+            ),
+            mock_block("main_blk2", vec![], None),
+        ];
+        project.program.term.subs = [(main.tid.clone(), main)].into_iter().collect();
+        let call_other_config: CallOtherConfig = serde_json::from_value(serde_json::json!({
+            "policies": { "software_interrupt": "syscall" },
+            "syscall_conventions": {
+                "ARM_32": {
+                    "number_register": "r7",
+                    "parameter_registers": ["r0", "r1", "r2", "r3", "r4", "r5", "r6"],
+                    "return_register": "r0",
+                    "syscalls": { "read": { "number": 3, "parameters": 3 } }
+                }
+            }
+        }))
+        .unwrap();
+        let _ = project.resolve_call_other_ops(&call_other_config);
+
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        let mut pi_results = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pi_results.compute();
+        let string_abstraction = crate::analysis::string_abstraction::run::<BricksDomain>(
+            &project,
+            &runtime_memory_image,
+            &graph,
+            &pi_results,
+            StringAbstractionConfig {
+                string_symbols: vec!["sprintf".to_string()],
+                format_string_index: [("sprintf".to_string(), 1)].into_iter().collect(),
+            },
+        );
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pi_results))
+            .set_string_abstraction(Some(&string_abstraction));
+
+        let (_, warnings) = check_cwe(
+            &analysis_results,
+            &serde_json::json!({"system_symbols": ["system"], "input_symbols": {"read": [1]}}),
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "main_blk1_call_system".to_string(),
+                "main_blk0_svc".to_string()
+            ]
+        );
+    }
+}
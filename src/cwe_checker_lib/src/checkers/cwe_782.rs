@@ -33,6 +33,9 @@ pub static CWE_MODULE: crate::CweModule = crate::CweModule {
     name: "CWE782",
     version: VERSION,
     run: check_cwe,
+    required_analyses: &[],
+    pointer_inference_retention:
+        crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
 };
 
 /// check whether the ioctl symbol is called by any subroutine. If so, generate the cwe warning.
@@ -48,7 +51,7 @@ pub fn handle_sub(sub: &Term<Sub>, symbol: &HashMap<&Tid, &str>) -> Vec<CweWarni
 pub fn generate_cwe_warning(calls: &[(&str, &Tid, &str)]) -> Vec<CweWarning> {
     let mut cwe_warnings: Vec<CweWarning> = Vec::new();
     for (sub_name, jmp_tid, _) in calls.iter() {
-        let address: &String = &jmp_tid.address;
+        let address = jmp_tid.canonical_address();
         let description = format!(
             "(Exposed IOCTL with Insufficient Access Control) Program uses ioctl at {} ({}). Be sure to double check the program and the corresponding driver.",
             sub_name, address
@@ -58,7 +61,7 @@ pub fn generate_cwe_warning(calls: &[(&str, &Tid, &str)]) -> Vec<CweWarning> {
             String::from(CWE_MODULE.version),
             description,
         )
-        .addresses(vec![address.clone()])
+        .addresses(vec![address.to_string()])
         .tids(vec![format!("{}", jmp_tid)])
         .symbols(vec![String::from(*sub_name)]);
 
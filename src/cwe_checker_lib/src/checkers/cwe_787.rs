@@ -0,0 +1,469 @@
+//! This module implements a check for CWE-787: Out-of-bounds Write
+//! caused by using the return value of a truncating string function as an offset into the destination buffer.
+//!
+//! Functions like `snprintf` or `strlcpy` truncate their output to the given buffer size,
+//! but return the length that the output would have had without truncation.
+//! A common idiom for appending to a buffer is `n += snprintf(buf + n, size - n, ...)`.
+//! If the output was truncated, `buf + n` points beyond the end of the buffer
+//! and the next write through it is an out-of-bounds write.
+//!
+//! See <https://cwe.mitre.org/data/definitions/787.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! For each call to one of the configured functions we track its return value through registers and memory
+//! with the shared [taint tracking](crate::analysis::taint).
+//! If the return value is added to a pointer that may point into the same memory object
+//! as the destination parameter of the call (according to the pointer inference analysis),
+//! a CWE warning is generated.
+//! Paths on which the return value is used in a conditional jump before that,
+//! e.g. by comparing it against the buffer size, are not followed further.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! The functions returning the untruncated length of their output.
+//! The destination buffer has to be the first parameter of these functions.
+//!
+//! ## False Positives
+//!
+//! - If the return value is temporarily saved in a memory location
+//!   that the pointer inference analysis could not track,
+//!   a check of the return value may be missed.
+//! - If the destination buffer is part of a larger object (e.g. a struct or the stack frame),
+//!   adding the return value to a pointer to another part of that object is also flagged.
+//!
+//! ## False Negatives
+//!
+//! - We do not check whether the conditional jump actually compares the return value against the buffer size.
+//! - The analysis is intraprocedural,
+//!   i.e. return values passed to other functions or returned to the caller are not tracked.
+//! - Negative return values indicating encoding errors are not considered.
+
+use std::collections::{BTreeSet, HashMap};
+
+use petgraph::graph::NodeIndex;
+use petgraph::visit::IntoNodeReferences;
+
+use crate::abstract_domain::{AbstractDomain, AbstractIdentifier};
+use crate::analysis::graph::Node;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::{PointerInference, State as PointerInferenceState};
+use crate::analysis::taint::{State, Taint};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::symbol_utils::get_symbol_map;
+use crate::{CweModule, SharedAnalysis};
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE787",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Config {
+    /// Names of functions that truncate their output to the size of the destination buffer
+    /// given as their first parameter, but return the length of the untruncated output.
+    symbols: Vec<String>,
+}
+
+/// The analysis results needed to follow the return values of the truncating calls.
+struct Context<'a> {
+    project: &'a Project,
+    runtime_memory_image: &'a RuntimeMemoryImage,
+    pointer_inference_results: &'a PointerInference<'a>,
+    /// Maps the pairs `(block TID, function TID)` to the `BlkStart` node of the block.
+    block_start_nodes: HashMap<(Tid, Tid), NodeIndex>,
+}
+
+/// Search for return values of truncating string functions
+/// that are used as offsets into the destination buffer without being checked first.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+    let symbols = get_symbol_map(project, &config.symbols);
+    if symbols.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let mut block_start_nodes = HashMap::new();
+    let mut truncating_calls = Vec::new();
+    for (node_id, node) in pointer_inference_results.get_graph().node_references() {
+        match node {
+            Node::BlkStart(block, sub) => {
+                block_start_nodes.insert((block.tid.clone(), sub.tid.clone()), node_id);
+            }
+            Node::BlkEnd(block, sub) => {
+                for jmp in block.term.jmps.iter() {
+                    if let Jmp::Call {
+                        target,
+                        return_: Some(return_target),
+                    } = &jmp.term
+                    {
+                        if let Some(symbol) = symbols.get(target) {
+                            truncating_calls.push((node_id, jmp, *symbol, return_target, *sub));
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    let context = Context {
+        project,
+        runtime_memory_image: analysis_results.runtime_memory_image,
+        pointer_inference_results,
+        block_start_nodes,
+    };
+
+    let mut cwe_warnings = Vec::new();
+    for (node_id, call, symbol, return_target, sub) in truncating_calls {
+        let pi_state = match pointer_inference_results.get_node_value(node_id) {
+            Some(NodeValue::Value(pi_state)) => pi_state,
+            _ => continue,
+        };
+        let destination_ids: BTreeSet<AbstractIdentifier> = match symbol
+            .parameters
+            .first()
+            .map(|parameter| pi_state.eval_parameter_arg(parameter, context.runtime_memory_image))
+        {
+            Some(Ok(destination)) => destination.get_relative_values().keys().cloned().collect(),
+            _ => continue,
+        };
+        if destination_ids.is_empty() {
+            continue;
+        }
+        let start_state = State::new(symbol, Some(pi_state));
+        for use_tid in
+            context.find_unchecked_offset_uses(start_state, &destination_ids, return_target, sub)
+        {
+            cwe_warnings.push(generate_cwe_warning(call, &symbol.name, &use_tid, sub));
+        }
+    }
+    cwe_warnings.sort();
+    (Vec::new(), cwe_warnings)
+}
+
+impl<'a> Context<'a> {
+    /// Follow all paths from the given block of the function
+    /// and return the TIDs of all `Def`s that add the tainted return value
+    /// to a pointer into one of the destination objects.
+    ///
+    /// Paths are not followed further after a conditional jump depending on the tainted value.
+    fn find_unchecked_offset_uses(
+        &self,
+        start_state: State,
+        destination_ids: &BTreeSet<AbstractIdentifier>,
+        start_block: &Tid,
+        sub: &Term<Sub>,
+    ) -> BTreeSet<Tid> {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut block_states: HashMap<&Tid, State> = HashMap::new();
+        let mut worklist = vec![(start_block, start_state)];
+        let mut offset_uses = BTreeSet::new();
+        while let Some((block_tid, state)) = worklist.pop() {
+            let block = match blocks.get(block_tid) {
+                Some(block) => *block,
+                None => continue,
+            };
+            let state = match block_states.get(block_tid) {
+                Some(old_state) => {
+                    let merged_state = old_state.merge(&state);
+                    if merged_state == *old_state {
+                        continue;
+                    }
+                    merged_state
+                }
+                None => state,
+            };
+            block_states.insert(&block.tid, state.clone());
+            let state = self.update_defs(state, block, sub, destination_ids, &mut offset_uses);
+            let is_checked = block.term.jmps.iter().any(|jmp| {
+                matches!(&jmp.term, Jmp::CBranch { condition, .. } if state.eval(condition).is_tainted())
+            });
+            if is_checked {
+                continue;
+            }
+            for jmp in block.term.jmps.iter() {
+                match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                        worklist.push((target, state.clone()))
+                    }
+                    Jmp::Call {
+                        target,
+                        return_: Some(return_target),
+                    } => {
+                        let mut state = state.clone();
+                        self.remove_non_callee_saved_taint(&mut state, Some(target));
+                        worklist.push((return_target, state));
+                    }
+                    Jmp::CallInd {
+                        return_: Some(return_target),
+                        ..
+                    } => {
+                        let mut state = state.clone();
+                        self.remove_non_callee_saved_taint(&mut state, None);
+                        worklist.push((return_target, state));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        offset_uses
+    }
+
+    /// Apply the `Def`s of the block to the taint state
+    /// and collect the TIDs of `Def`s adding the tainted value to a pointer into one of the destination objects.
+    fn update_defs(
+        &self,
+        mut state: State,
+        block: &Term<Blk>,
+        sub: &Term<Sub>,
+        destination_ids: &BTreeSet<AbstractIdentifier>,
+        offset_uses: &mut BTreeSet<Tid>,
+    ) -> State {
+        let mut pi_state = self
+            .block_start_nodes
+            .get(&(block.tid.clone(), sub.tid.clone()))
+            .and_then(
+                |node_id| match self.pointer_inference_results.get_node_value(*node_id) {
+                    Some(NodeValue::Value(pi_state)) => Some(pi_state.clone()),
+                    _ => None,
+                },
+            );
+        for def in block.term.defs.iter() {
+            if let Some(pi_state) = pi_state.as_ref() {
+                let expressions = match &def.term {
+                    Def::Assign { value, .. } => vec![value],
+                    Def::Load { address, .. } => vec![address],
+                    Def::Store { address, value } => vec![address, value],
+                };
+                if expressions.into_iter().any(|expression| {
+                    is_destination_offset(expression, &state, pi_state, destination_ids)
+                }) {
+                    offset_uses.insert(def.tid.clone());
+                }
+            }
+            match &def.term {
+                Def::Assign { var, value } => state.set_register_taint(var, state.eval(value)),
+                Def::Load { var, address } => {
+                    let taint = match &pi_state {
+                        Some(pi_state) => {
+                            state.load_taint_from_memory(&pi_state.eval(address), var.size)
+                        }
+                        None => Taint::Top(var.size),
+                    };
+                    state.set_register_taint(var, taint);
+                }
+                Def::Store { address, value } => match &pi_state {
+                    Some(pi_state) => {
+                        state.save_taint_to_memory(&pi_state.eval(address), state.eval(value))
+                    }
+                    None => state.remove_all_memory_taints(),
+                },
+            }
+            if let Some(pi_state) = pi_state.as_mut() {
+                pi_state.apply_defs(std::slice::from_ref(def), self.runtime_memory_image);
+            }
+        }
+        state
+    }
+
+    /// Remove the taint from all registers that are not callee-saved
+    /// in the calling convention of the called function.
+    /// For unknown call targets the standard calling convention of the project is used.
+    fn remove_non_callee_saved_taint(&self, state: &mut State, target: Option<&Tid>) {
+        let program = &self.project.program.term;
+        let calling_convention = match target {
+            Some(target) => match program.extern_symbols.get(target) {
+                Some(symbol) => symbol.calling_convention.clone(),
+                None => program
+                    .subs
+                    .get(target)
+                    .and_then(|sub| sub.term.calling_convention.clone()),
+            },
+            None => None,
+        };
+        if let Some(cconv) = self
+            .project
+            .get_specific_calling_convention(&calling_convention)
+        {
+            state.remove_non_callee_saved_taint(cconv);
+        }
+    }
+}
+
+/// Return `true` if the expression contains an addition of a tainted value
+/// and a pointer that may point into one of the destination objects.
+fn is_destination_offset(
+    expression: &Expression,
+    state: &State,
+    pi_state: &PointerInferenceState,
+    destination_ids: &BTreeSet<AbstractIdentifier>,
+) -> bool {
+    match expression {
+        Expression::BinOp { op, lhs, rhs } => {
+            if *op == BinOpType::IntAdd {
+                for (offset, base) in [(lhs, rhs), (rhs, lhs)] {
+                    if state.eval(offset).is_tainted()
+                        && pi_state
+                            .eval(base)
+                            .get_relative_values()
+                            .keys()
+                            .any(|id| destination_ids.contains(id))
+                    {
+                        return true;
+                    }
+                }
+            }
+            is_destination_offset(lhs, state, pi_state, destination_ids)
+                || is_destination_offset(rhs, state, pi_state, destination_ids)
+        }
+        Expression::UnOp { arg, .. }
+        | Expression::Cast { arg, .. }
+        | Expression::Subpiece { arg, .. } => {
+            is_destination_offset(arg, state, pi_state, destination_ids)
+        }
+        Expression::Var(_) | Expression::Const(_) | Expression::Unknown { .. } => false,
+    }
+}
+
+/// Generate the CWE warning for a detected instance of the CWE.
+fn generate_cwe_warning(
+    call: &Term<Jmp>,
+    symbol_name: &str,
+    use_tid: &Tid,
+    sub: &Term<Sub>,
+) -> CweWarning {
+    CweWarning::new(
+        CWE_MODULE.name,
+        CWE_MODULE.version,
+        format!(
+            "(Out-of-bounds Write) The return value of {} at {} in function {} is used as an offset into the destination buffer at {} without being checked against the buffer size",
+            symbol_name, call.tid.canonical_address(), sub.term.name, use_tid.canonical_address()
+        ),
+    )
+    .tids(vec![format!("{}", call.tid), format!("{}", use_tid)])
+    .addresses(vec![call.tid.canonical_address().to_string(), use_tid.canonical_address().to_string()])
+    .symbols(vec![symbol_name.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a function that writes to a stack buffer with `snprintf`
+    /// and then adds the return value to the buffer address.
+    /// If `clamped` is set, the return value is compared against the buffer size first.
+    fn mock_function(name: &str, clamped: bool) -> Term<Sub> {
+        let call_blk = Blk::mock_call(
+            &format!("{}_snprintf_blk", name),
+            vec![
+                Def::assign(
+                    &format!("{}_buffer", name),
+                    Variable::mock("RDI", 8),
+                    Expression::var("RSP", 8).plus_const(-64),
+                ),
+                Def::assign(
+                    &format!("{}_size", name),
+                    Variable::mock("RSI", 8),
+                    Expression::const_from_i64(64),
+                ),
+            ],
+            "snprintf",
+            &format!("{}_check_blk", name),
+        );
+        let mut check_blk = Blk::mock_with_tid(&format!("{}_check_blk", name));
+        if clamped {
+            check_blk.term.defs = vec![Def::assign(
+                &format!("{}_compare", name),
+                Variable::mock("ZF", 1),
+                Expression::BinOp {
+                    op: BinOpType::IntLess,
+                    lhs: Box::new(Expression::var("RAX", 8)),
+                    rhs: Box::new(Expression::const_from_i64(64)),
+                },
+            )];
+            check_blk.term.jmps = vec![Term {
+                tid: Tid::new(format!("{}_cbranch", name)),
+                term: Jmp::CBranch {
+                    target: Tid::new(format!("{}_append_blk", name)),
+                    condition: Expression::var("ZF", 1),
+                },
+            }];
+        }
+        check_blk.term.jmps.push(Jmp::branch(
+            &format!("{}_branch", name),
+            &format!("{}_append_blk", name),
+        ));
+        let mut append_blk = Blk::mock_return(&format!("{}_append_blk", name));
+        append_blk.term.defs = vec![
+            Def::assign(
+                &format!("{}_load_buffer", name),
+                Variable::mock("RCX", 8),
+                Expression::var("RSP", 8).plus_const(-64),
+            ),
+            Def::assign(
+                &format!("{}_append", name),
+                Variable::mock("RCX", 8),
+                Expression::var("RCX", 8).plus(Expression::var("RAX", 8)),
+            ),
+        ];
+        Sub::mock_with_blocks(name, vec![call_blk, check_blk, append_blk])
+    }
+
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8), Variable::mock("RSI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("snprintf").parameters =
+            vec![Arg::mock_register("RDI", 8), Arg::mock_register("RSI", 8)];
+        for sub in [
+            mock_function("unchecked", false),
+            mock_function("clamped", true),
+        ] {
+            project.program.term.entry_points.insert(sub.tid.clone());
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn unchecked_return_value_used_as_offset() {
+        let project = mock_project();
+        let config = serde_json::json!({ "symbols": ["snprintf"] });
+
+        let warnings =
+            AnalysisResults::run_with_mock_pointer_inference(&project, |analysis_results| {
+                check_cwe(analysis_results, &config).1
+            });
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].tids,
+            vec![
+                "call_unchecked_snprintf_blk".to_string(),
+                "unchecked_append".to_string()
+            ]
+        );
+        assert_eq!(warnings[0].symbols, vec!["snprintf".to_string()]);
+    }
+}
@@ -0,0 +1,359 @@
+//! This module implements a check for CWE-789: Memory Allocation with Excessive Size Value.
+//!
+//! If the size of a memory allocation is controlled by an attacker
+//! and not limited to a reasonable upper bound,
+//! the attacker may be able to exhaust the memory of the program.
+//!
+//! See <https://cwe.mitre.org/data/definitions/789.html> for a detailed description.
+//!
+//! ## How the check works
+//!
+//! Using dataflow analysis we track the return values of the symbols configured in config.json
+//! (e.g. `atoi` or `strtol`) as taint sources through the function calling them.
+//! Internal functions that pass the return value of a taint source on to their callers
+//! (e.g. a wrapper around `atoi`) are treated as derived taint sources,
+//! see the [`taint_sources`](crate::analysis::taint_sources) module for details.
+//! If a tainted value is used as a size parameter of one of the configured allocation symbols,
+//! we look at the heap object created by the allocation call in the pointer inference analysis.
+//! The pointer inference records the size of the object at the time of the allocation,
+//! including all bounds known for the size value at the call site (e.g. from conditional jumps).
+//! We generate a CWE warning if this size has no upper bound
+//! or if the upper bound exceeds the configured maximum allocation size.
+//!
+//! ### Symbols configurable in config.json
+//!
+//! - The `symbols` are the functions whose return values are assumed to be externally controlled.
+//! - The `allocation_symbols` map the names of allocation functions
+//!   to the indices of their parameters that determine the size of the allocation.
+//! - `max_allocation_size` is the largest allocation size that is not considered excessive.
+//!
+//! ## False Positives
+//!
+//! - If the size value is spilled to memory after it was checked,
+//!   then the information about the check may be lost for the reloaded value.
+//! - The size may be limited by other means than a comparison, e.g. by a bit mask that the analysis cannot track.
+//!
+//! ## False Negatives
+//!
+//! - Tainted values passed to other functions are not tracked.
+//!   Only the return values of functions are tracked through derived taint sources.
+//! - Externally controlled values that are returned through memory (e.g. by `scanf`) are not tracked.
+//! - Allocations whose size is only bounded by a large upper bound below `max_allocation_size` are not reported.
+
+use crate::abstract_domain::TryToInterval;
+use crate::analysis::forward_interprocedural_fixpoint::Context as _;
+use crate::analysis::graph::Edge;
+use crate::analysis::interprocedural_fixpoint_generic::NodeValue;
+use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
+use crate::analysis::taint::{compute_taint_from_sources, get_extern_taint_source_sites, State};
+use crate::analysis::taint_sources::{
+    compute_derived_taint_sources, get_configured_taint_sources, get_derived_source_calls,
+    log_derived_taint_sources,
+};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::{CweWarning, LogMessage, Remediation};
+use crate::{CweModule, SharedAnalysis};
+use petgraph::visit::EdgeRef;
+use std::collections::{BTreeMap, HashMap};
+
+pub(crate) mod context;
+use context::*;
+
+/// The module name and version
+pub static CWE_MODULE: CweModule = CweModule {
+    name: "CWE789",
+    version: "0.1",
+    run: check_cwe,
+    required_analyses: &[SharedAnalysis::PointerInference],
+    pointer_inference_retention: crate::analysis::pointer_inference::NodeValueRetention::All,
+};
+
+/// The configuration struct
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    /// The names of symbols whose return values are assumed to be externally controlled.
+    symbols: Vec<String>,
+    /// Maps the names of allocation functions to the indices of their size parameters.
+    allocation_symbols: BTreeMap<String, Vec<usize>>,
+    /// The largest allocation size that is not considered excessive.
+    max_allocation_size: u64,
+}
+
+/// A call to an allocation function where a size parameter is tainted by a taint source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintedAllocation {
+    /// The term identifier of the allocation call.
+    pub allocation_call: Tid,
+    /// The name of the allocation function.
+    pub allocation_symbol: String,
+    /// The term identifier of the call to the taint source.
+    pub taint_source: Tid,
+    /// The name of the taint source function.
+    pub taint_source_name: String,
+}
+
+/// Run the CWE check.
+/// For each call to one of the configured symbols
+/// we check whether its return value is used as the size of an allocation without an upper bound.
+pub fn check_cwe(
+    analysis_results: &AnalysisResults,
+    cwe_params: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let project = analysis_results.project;
+    let pointer_inference_results = analysis_results.pointer_inference.unwrap();
+
+    let (allocation_sender, allocation_receiver) = crossbeam_channel::unbounded();
+
+    let config: Config = serde_json::from_value(cwe_params.clone()).unwrap();
+    let symbol_map = crate::utils::symbol_utils::get_symbol_map(project, &config.symbols[..]);
+    let derived_sources = compute_derived_taint_sources(
+        project,
+        &get_configured_taint_sources(CWE_MODULE.name, cwe_params),
+    );
+    let general_context = Context::new(
+        project,
+        pointer_inference_results,
+        config.allocation_symbols.clone(),
+        allocation_sender,
+    );
+
+    let mut allocation_call_nodes = HashMap::new();
+    for edge in general_context.get_graph().edge_references() {
+        if let Edge::ExternCallStub(jmp) = edge.weight() {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                if let Some(symbol) = project.program.term.extern_symbols.get(target) {
+                    if config.allocation_symbols.contains_key(&symbol.name) {
+                        allocation_call_nodes.insert(jmp.tid.clone(), edge.target());
+                    }
+                }
+            }
+        }
+    }
+    let mut taint_source_sites: Vec<_> = get_extern_taint_source_sites(
+        general_context.get_graph(),
+        &symbol_map,
+        pointer_inference_results,
+        State::new,
+    )
+    .into_iter()
+    .map(|(jmp, symbol, node, state)| ((jmp, symbol.name.as_str()), node, state))
+    .collect();
+    for derived_call in
+        get_derived_source_calls(project, general_context.get_graph(), &derived_sources)
+    {
+        if let (Some(node), false) = (
+            derived_call.return_node,
+            derived_call.tainted_return_registers.is_empty(),
+        ) {
+            taint_source_sites.push((
+                (derived_call.call, derived_call.source.name.as_str()),
+                node,
+                State::new_with_tainted_registers(&derived_call.tainted_return_registers),
+            ));
+        }
+    }
+    let mut logs = log_derived_taint_sources(CWE_MODULE.name, &derived_sources);
+    logs.extend(compute_taint_from_sources(
+        &general_context,
+        taint_source_sites,
+        |context, (jmp, source_name), current_sub| {
+            context.set_taint_source(jmp, source_name, current_sub)
+        },
+    ));
+
+    let mut cwe_warnings = BTreeMap::new();
+    for allocation in allocation_receiver.try_iter() {
+        if let Some(node) = allocation_call_nodes.get(&allocation.allocation_call) {
+            if !is_allocation_size_bounded(
+                pointer_inference_results,
+                *node,
+                &allocation.allocation_call,
+                config.max_allocation_size,
+            ) {
+                let cwe = generate_cwe_warning(&allocation, config.max_allocation_size);
+                cwe_warnings.insert((cwe.addresses.clone(), cwe.tids.clone()), cwe);
+            }
+        }
+    }
+    let cwe_warnings = cwe_warnings.into_values().collect();
+
+    (logs, cwe_warnings)
+}
+
+/// Check whether the size of the heap object created by the given allocation call
+/// is bounded by the given maximal allocation size.
+///
+/// The pointer inference state at the given node (i.e. directly after the allocation call) is used
+/// to look up the size recorded for the object at the time of the allocation.
+/// If no such object can be found, the allocation is assumed to be bounded to avoid false positives.
+fn is_allocation_size_bounded(
+    pointer_inference_results: &PointerInferenceComputation,
+    node_after_call: petgraph::graph::NodeIndex,
+    allocation_call: &Tid,
+    max_allocation_size: u64,
+) -> bool {
+    let pi_state = match pointer_inference_results.get_node_value(node_after_call) {
+        Some(NodeValue::Value(pi_state)) => pi_state,
+        _ => return true,
+    };
+    for object_id in pi_state.memory.get_all_object_ids() {
+        if object_id.get_tid() != allocation_call {
+            continue;
+        }
+        return match pi_state.memory.get_allocation_size(&object_id) {
+            Some(size) => match size.try_to_offset_interval() {
+                Ok((lower_bound, upper_bound)) => {
+                    lower_bound >= 0 && upper_bound as u64 <= max_allocation_size
+                }
+                Err(_) => false,
+            },
+            None => true,
+        };
+    }
+    true
+}
+
+/// Generate the CWE warning for an allocation whose size is tainted and not bounded.
+/// The warning suggests to check the size against the maximal allocation size before the allocation.
+fn generate_cwe_warning(allocation: &TaintedAllocation, max_allocation_size: u64) -> CweWarning {
+    let description = format!(
+        "(Memory Allocation with Excessive Size Value) The return value of {} at {} is used as the size of the allocation at {} without an upper bound",
+        allocation.taint_source_name,
+        allocation.taint_source.canonical_address(),
+        allocation.allocation_call.canonical_address(),
+    );
+    CweWarning::new(CWE_MODULE.name, CWE_MODULE.version, description)
+        .addresses(vec![
+            allocation.taint_source.canonical_address().to_string(),
+            allocation.allocation_call.canonical_address().to_string(),
+        ])
+        .tids(vec![
+            format!("{}", allocation.taint_source),
+            format!("{}", allocation.allocation_call),
+        ])
+        .symbols(vec![
+            allocation.taint_source_name.clone(),
+            allocation.allocation_symbol.clone(),
+        ])
+        .remediation(Some(Remediation::AddCheck {
+            value: format!(
+                "the return value of {} at {}",
+                allocation.taint_source_name,
+                allocation.taint_source.canonical_address()
+            ),
+            bound: max_allocation_size,
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project where `size` is used as the size parameter of a call to `malloc`
+    /// after a call to `atoi`.
+    /// If `bound` is set, the return value of `atoi` is compared against it before the allocation.
+    fn mock_project(size: Expression, bound: Option<i64>) -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock();
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["atoi", "malloc"] {
+            project.add_mock_extern_symbol(name);
+        }
+
+        let mut sub = Sub::mock("func");
+        let mut call_block = Blk::mock_with_tid("call_block");
+        call_block
+            .term
+            .jmps
+            .push(Jmp::call("source_call", "atoi", Some("check_block")));
+        let mut check_block = Blk::mock_with_tid("check_block");
+        let mut alloc_block = Blk::mock_with_tid("alloc_block");
+        alloc_block
+            .term
+            .defs
+            .push(Def::assign("set_size", Variable::mock("RDI", 8), size));
+        alloc_block
+            .term
+            .jmps
+            .push(Jmp::call("alloc_call", "malloc", Some("end_block")));
+        let end_block = Blk::mock_return("end_block");
+        if let Some(bound) = bound {
+            // The comparison is part of the branch condition,
+            // as it would be after the expression propagation during normalization.
+            check_block.term.jmps.push(Term {
+                tid: Tid::new("check"),
+                term: Jmp::CBranch {
+                    target: Tid::new("alloc_block"),
+                    condition: Expression::BinOp {
+                        op: BinOpType::IntLess,
+                        lhs: Box::new(Expression::var("RAX", 8)),
+                        rhs: Box::new(Expression::const_from_i64(bound)),
+                    },
+                },
+            });
+            check_block
+                .term
+                .jmps
+                .push(Jmp::branch("skip_alloc", "end_block"));
+        } else {
+            check_block
+                .term
+                .jmps
+                .push(Jmp::branch("jmp_alloc", "alloc_block"));
+        }
+        sub.term.blocks = vec![call_block, check_block, alloc_block, end_block];
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    fn run_check(project: &Project) -> Vec<CweWarning> {
+        let config = serde_json::json!({
+            "symbols": ["atoi"],
+            "allocation_symbols": { "malloc": [0] },
+            "max_allocation_size": 4096,
+        });
+        AnalysisResults::run_with_mock_pointer_inference(project, |analysis_results| {
+            check_cwe(analysis_results, &config).1
+        })
+    }
+
+    #[test]
+    fn unbounded_tainted_size() {
+        let cwe_warnings = run_check(&mock_project(Expression::var("RAX", 8), None));
+        assert_eq!(cwe_warnings.len(), 1);
+        assert_eq!(
+            cwe_warnings[0].tids,
+            vec!["source_call".to_string(), "alloc_call".to_string()]
+        );
+        assert_eq!(
+            cwe_warnings[0].symbols,
+            vec!["atoi".to_string(), "malloc".to_string()]
+        );
+        assert_eq!(
+            cwe_warnings[0].remediation,
+            Some(Remediation::AddCheck {
+                value: "the return value of atoi at UNKNOWN".to_string(),
+                bound: 4096,
+            })
+        );
+    }
+
+    #[test]
+    fn tainted_size_checked_against_bound() {
+        let project = mock_project(Expression::var("RAX", 8), Some(1024));
+        assert!(run_check(&project).is_empty());
+        // A bound above the maximal allocation size does not prevent the warning.
+        let project = mock_project(Expression::var("RAX", 8), Some(0x10000));
+        assert_eq!(run_check(&project).len(), 1);
+    }
+
+    #[test]
+    fn constant_size() {
+        let project = mock_project(Expression::const_from_i64(0x100), None);
+        assert!(run_check(&project).is_empty());
+    }
+}
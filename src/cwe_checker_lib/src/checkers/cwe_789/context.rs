@@ -0,0 +1,199 @@
+use super::TaintedAllocation;
+use crate::abstract_domain::AbstractDomain;
+use crate::analysis::graph::{Graph, Node};
+use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
+use crate::analysis::taint::{State, TaintContext};
+use crate::intermediate_representation::*;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// The context object for the CWE-789 check.
+///
+/// It is also used by the CWE-195 check,
+/// where the size parameters of copy functions are treated like allocation sizes.
+///
+/// The taint source is managed by the wrapped [`TaintContext`](crate::analysis::taint::TaintContext).
+#[derive(Clone)]
+pub struct Context<'a> {
+    /// The taint source, the current function and the pointer inference results of the analysis.
+    taint: TaintContext<'a>,
+    /// Maps the names of allocation functions to the indices of their size parameters.
+    allocation_symbols: Arc<BTreeMap<String, Vec<usize>>>,
+    /// A channel where allocation calls with tainted size parameters are sent to.
+    allocation_collector: crossbeam_channel::Sender<TaintedAllocation>,
+}
+
+impl<'a> Context<'a> {
+    /// Create a new context object.
+    pub fn new(
+        project: &'a Project,
+        pointer_inference_results: &'a PointerInferenceComputation<'a>,
+        allocation_symbols: BTreeMap<String, Vec<usize>>,
+        allocation_collector: crossbeam_channel::Sender<TaintedAllocation>,
+    ) -> Self {
+        Context {
+            taint: TaintContext::new(project, pointer_inference_results),
+            allocation_symbols: Arc::new(allocation_symbols),
+            allocation_collector,
+        }
+    }
+
+    /// Set the taint source and the current function for the analysis.
+    pub fn set_taint_source(
+        &mut self,
+        taint_source: &'a Term<Jmp>,
+        taint_source_name: &str,
+        current_sub: &'a Term<Sub>,
+    ) {
+        self.taint
+            .set_named_taint_source(taint_source, taint_source_name, current_sub);
+    }
+
+    /// Returns `true` if the given parameter of a call may contain a tainted value.
+    fn is_parameter_tainted(&self, state: &State, parameter: &Arg, call: &Term<Jmp>) -> bool {
+        match parameter {
+            Arg::Register { expr, .. } => state.eval(expr).is_tainted(),
+            Arg::Stack { address, size, .. } => {
+                let pi_state = state.get_pointer_inference_state().cloned().or_else(|| {
+                    self.taint
+                        .pointer_inference_results
+                        .get_state_before_term(&call.tid)
+                });
+                pi_state
+                    .map(|pi_state| {
+                        state
+                            .load_taint_from_memory(&pi_state.eval(address), *size)
+                            .is_tainted()
+                    })
+                    .unwrap_or(false)
+            }
+        }
+    }
+
+    /// If the given call is a call to an allocation function with a tainted size parameter,
+    /// send the call to the allocation collector.
+    fn check_allocation_call(&self, state: &State, call: &Term<Jmp>, symbol: &ExternSymbol) {
+        let size_indices = match self.allocation_symbols.get(&symbol.name) {
+            Some(indices) => indices,
+            None => return,
+        };
+        let has_tainted_size = size_indices.iter().any(|index| {
+            symbol
+                .parameters
+                .get(*index)
+                .map(|parameter| self.is_parameter_tainted(state, parameter, call))
+                .unwrap_or(false)
+        });
+        if has_tainted_size {
+            let _ = self.allocation_collector.send(TaintedAllocation {
+                allocation_call: call.tid.clone(),
+                allocation_symbol: symbol.name.clone(),
+                taint_source: self.taint.get_taint_source().tid.clone(),
+                taint_source_name: self.taint.get_taint_source_name().to_string(),
+            });
+        }
+    }
+}
+
+impl<'a> crate::analysis::forward_interprocedural_fixpoint::Context<'a> for Context<'a> {
+    type Value = State;
+
+    /// Get the underlying graph of the fixpoint computation
+    fn get_graph(&self) -> &Graph<'a> {
+        self.taint.pointer_inference_results.get_graph()
+    }
+
+    /// Merge two states
+    fn merge(&self, state1: &State, state2: &State) -> State {
+        state1.merge(state2)
+    }
+
+    /// Comparisons do not remove taint.
+    /// Whether the allocation size is bounded is decided using the value specialization
+    /// of the pointer inference analysis instead.
+    fn specialize_conditional(
+        &self,
+        state: &State,
+        _condition: &Expression,
+        _block_before_condition: &Term<Blk>,
+        _is_true: bool,
+    ) -> Option<State> {
+        Some(state.clone())
+    }
+
+    /// Always returns `None` so that the analysis stays intraprocedural.
+    fn update_call(
+        &self,
+        _state: &State,
+        _call: &Term<Jmp>,
+        _target: &Node,
+        _calling_convention: &Option<String>,
+    ) -> Option<Self::Value> {
+        None
+    }
+
+    /// Check calls to allocation functions for tainted size parameters
+    /// and remove taint from non-callee-saved registers.
+    fn update_call_stub(&self, state: &State, call: &Term<Jmp>) -> Option<Self::Value> {
+        if state.is_empty() {
+            return None;
+        }
+        match &call.term {
+            Jmp::Call { target, .. } => {
+                let project = self.taint.project;
+                if let Some(extern_symbol) = project.program.term.extern_symbols.get(target) {
+                    self.check_allocation_call(state, call, extern_symbol);
+                    let mut new_state = state.clone();
+                    new_state.remove_non_callee_saved_taint(
+                        project.get_calling_convention(extern_symbol),
+                    );
+                    Some(new_state)
+                } else {
+                    // The call target is a function excluded from the analysis.
+                    Some(self.taint.handle_generic_call(state))
+                }
+            }
+            Jmp::CallInd { .. } => Some(self.taint.handle_generic_call(state)),
+            _ => panic!("Malformed control flow graph encountered."),
+        }
+    }
+
+    /// Update the taint state according to the effects of the given [`Def`].
+    fn update_def(&self, state: &State, def: &Term<Def>) -> Option<Self::Value> {
+        if state.is_empty() {
+            // Without taint there is nothing to propagate.
+            return None;
+        }
+        Some(self.taint.propagate_taint(state, def))
+    }
+
+    /// Remove the intermediate pointer inference state, since it is only valid inside a block.
+    fn update_jump(
+        &self,
+        state: &State,
+        _jump: &Term<Jmp>,
+        _untaken_conditional: Option<&Term<Jmp>>,
+        _target: &Term<Blk>,
+    ) -> Option<Self::Value> {
+        if state.is_empty() {
+            // Without taint there is nothing to propagate.
+            return None;
+        }
+        let mut new_state = state.clone();
+        new_state.set_pointer_inference_state(None);
+        Some(new_state)
+    }
+
+    /// Since the analysis is intraprocedural, returned values are not tracked.
+    /// If `state_before_call` is set, handle it like a call to an unknown function.
+    fn update_return(
+        &self,
+        _state_before_return: Option<&State>,
+        state_before_call: Option<&State>,
+        _call_term: &Term<Jmp>,
+        _return_term: &Term<Jmp>,
+        _calling_convention: &Option<String>,
+    ) -> Option<State> {
+        state_before_call.map(|state| self.taint.handle_generic_call(state))
+    }
+}
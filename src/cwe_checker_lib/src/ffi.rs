@@ -0,0 +1,398 @@
+//! A C-compatible interface for running the analysis and retrieving its results,
+//! e.g. for calling the cwe_checker from other languages via `cffi` or `ctypes`.
+//!
+//! The module is only available if the `ffi` feature is enabled.
+//!
+//! ## Usage
+//!
+//! 1. Call [`cwec_analyze`] with the JSON output of the P-Code-Extractor plugin,
+//!    the content of the binary and (optionally) the content of a configuration file.
+//!    It returns a handle to the result of the analysis.
+//! 2. Check the status of the analysis with [`cwec_result_status`].
+//! 3. Get the result as JSON with [`cwec_result_to_json`].
+//!    On success the JSON follows the stable [output schema](crate::utils::output::schema).
+//!    On failure it is an object of the form `{"error": {"code": <status>, "message": <text>}}`.
+//! 4. Free the string with [`cwec_free_string`] and the handle with [`cwec_free_result`].
+//!
+//! All strings are UTF-8 encoded and null-terminated.
+//!
+//! ## Thread Safety
+//!
+//! The analysis does not use global state,
+//! so several analyses may run concurrently in different threads.
+//! A single result handle may be read from several threads,
+//! but must not be freed while it is still in use.
+//!
+//! ## Error Handling
+//!
+//! Panics during the analysis are caught at the interface boundary
+//! and reported as results with the status [`CWEC_ERROR_PANIC`].
+
+use crate::pcode::ParsingConfig;
+use crate::utils::output::{AnalysisResultsOutput, OutputFormat};
+use crate::utils::pipeline::{analyze_project, convert_parsed_project, PipelineConfig};
+use crate::{get_modules, MODULES_DISABLED_ON_STANDARD_RUNS};
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// The status of a successful analysis.
+pub const CWEC_OK: i32 = 0;
+/// The status if an argument is invalid, e.g. a null pointer with a non-zero length or a null handle.
+pub const CWEC_ERROR_INVALID_ARGUMENT: i32 = 1;
+/// The status if the project JSON or the configuration could not be parsed.
+pub const CWEC_ERROR_PARSING: i32 = 2;
+/// The status if the analysis itself returned an error, e.g. because the binary could not be loaded.
+pub const CWEC_ERROR_ANALYSIS: i32 = 3;
+/// The status if the analysis panicked.
+pub const CWEC_ERROR_PANIC: i32 = 4;
+
+/// The default configuration used if no configuration is given.
+const DEFAULT_CONFIG: &str = include_str!("../../config.json");
+
+/// The result of an analysis run, returned as an opaque handle by [`cwec_analyze`].
+pub struct CwecResult {
+    /// The status code of the analysis.
+    status: i32,
+    /// The result (or the error) serialized as JSON.
+    json: CString,
+}
+
+impl CwecResult {
+    /// Create a successful result from the output of the analysis.
+    fn from_output(output: &AnalysisResultsOutput) -> CwecResult {
+        let json = OutputFormat::Json.get_formatter().format_output(output);
+        CwecResult {
+            status: CWEC_OK,
+            json: to_c_string(json),
+        }
+    }
+
+    /// Create a result for a failed analysis with the given status and error message.
+    fn from_error(status: i32, message: impl ToString) -> CwecResult {
+        let json = serde_json::json!({
+            "error": {
+                "code": status,
+                "message": message.to_string(),
+            }
+        });
+        CwecResult {
+            status,
+            json: to_c_string(json.to_string()),
+        }
+    }
+}
+
+/// Convert a string into a C string.
+/// Null bytes, which cannot be represented in C strings, are removed.
+fn to_c_string(string: String) -> CString {
+    CString::new(string.replace('\0', "")).unwrap()
+}
+
+/// Get the byte slice given by a pointer and a length.
+/// A null pointer represents an empty slice if the length is zero.
+///
+/// # Safety
+///
+/// If the pointer is not null, it must be valid for reads of `len` bytes.
+unsafe fn get_slice<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a [u8], CwecResult> {
+    if ptr.is_null() {
+        if len == 0 {
+            Ok(&[])
+        } else {
+            Err(CwecResult::from_error(
+                CWEC_ERROR_INVALID_ARGUMENT,
+                format!("The {} pointer is null", name),
+            ))
+        }
+    } else {
+        Ok(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Run the analysis on the given project JSON, binary and configuration.
+fn analyze(project_json: &[u8], binary: &[u8], config_json: &[u8]) -> CwecResult {
+    let config: serde_json::Value = if config_json.is_empty() {
+        serde_json::from_str(DEFAULT_CONFIG).unwrap()
+    } else {
+        match serde_json::from_slice(config_json) {
+            Ok(config) => config,
+            Err(err) => {
+                return CwecResult::from_error(
+                    CWEC_ERROR_PARSING,
+                    format!("Parsing of the configuration failed: {}", err),
+                )
+            }
+        }
+    };
+    let parsing_config: ParsingConfig =
+        serde_json::from_value(config["PcodeParsing"].clone()).unwrap_or_default();
+    let parsed_project = match crate::pcode::Project::parse_resilient(project_json, &parsing_config)
+    {
+        Ok(parsed_project) => parsed_project,
+        Err(err) => return CwecResult::from_error(CWEC_ERROR_PARSING, err),
+    };
+    let (project, mut logs, is_partial) = convert_parsed_project(parsed_project, binary, None);
+    let mut modules = get_modules();
+    modules.retain(|module| !MODULES_DISABLED_ON_STANDARD_RUNS.contains(&module.name));
+    let pipeline_config = PipelineConfig {
+        modules: &modules,
+        config: &config,
+        bare_metal_config: None,
+        statistics: false,
+        feedback: false,
+        harness: None,
+    };
+    match analyze_project(project, binary, &pipeline_config) {
+        Ok(output) => {
+            let mut output = output.partial(is_partial);
+            logs.append(&mut output.logs);
+            output.logs = logs;
+            CwecResult::from_output(&output)
+        }
+        Err(err) => CwecResult::from_error(CWEC_ERROR_ANALYSIS, err),
+    }
+}
+
+/// Get the message of a caught panic.
+fn get_panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
+/// Analyze a binary.
+///
+/// - `project_json` points to the JSON output of the P-Code-Extractor plugin for the binary.
+/// - `binary` points to the content of the binary.
+/// - `config_json` points to the content of a configuration file.
+///   If it is null (with length zero), the default configuration is used.
+///
+/// Returns a handle to the result, which has to be freed with [`cwec_free_result`].
+/// The returned handle is never null.
+///
+/// # Safety
+///
+/// Each non-null pointer must be valid for reads of the corresponding number of bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cwec_analyze(
+    project_json: *const u8,
+    project_json_len: usize,
+    binary: *const u8,
+    binary_len: usize,
+    config_json: *const u8,
+    config_json_len: usize,
+) -> *mut CwecResult {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let project_json = match get_slice(project_json, project_json_len, "project JSON") {
+            Ok(slice) => slice,
+            Err(result) => return result,
+        };
+        let binary = match get_slice(binary, binary_len, "binary") {
+            Ok(slice) => slice,
+            Err(result) => return result,
+        };
+        let config_json = match get_slice(config_json, config_json_len, "configuration") {
+            Ok(slice) => slice,
+            Err(result) => return result,
+        };
+        analyze(project_json, binary, config_json)
+    }))
+    .unwrap_or_else(|payload| {
+        CwecResult::from_error(
+            CWEC_ERROR_PANIC,
+            format!("The analysis panicked: {}", get_panic_message(&*payload)),
+        )
+    });
+    Box::into_raw(Box::new(result))
+}
+
+/// Get the status code of the analysis result.
+/// Returns [`CWEC_ERROR_INVALID_ARGUMENT`] if the handle is null.
+///
+/// # Safety
+///
+/// The handle must be null or a handle returned by [`cwec_analyze`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cwec_result_status(result: *const CwecResult) -> i32 {
+    match result.as_ref() {
+        Some(result) => result.status,
+        None => CWEC_ERROR_INVALID_ARGUMENT,
+    }
+}
+
+/// Get the analysis result (or the error) as a JSON string.
+/// The string has to be freed with [`cwec_free_string`].
+/// Returns null if the handle is null.
+///
+/// # Safety
+///
+/// The handle must be null or a handle returned by [`cwec_analyze`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cwec_result_to_json(result: *const CwecResult) -> *mut c_char {
+    match result.as_ref() {
+        Some(result) => result.json.clone().into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Free a result handle returned by [`cwec_analyze`]. Null handles are ignored.
+///
+/// # Safety
+///
+/// The handle must be null or a handle returned by [`cwec_analyze`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cwec_free_result(result: *mut CwecResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// Free a string returned by [`cwec_result_to_json`]. Null pointers are ignored.
+///
+/// # Safety
+///
+/// The pointer must be null or a string returned by [`cwec_result_to_json`] that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cwec_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::output::{JsonReport, SCHEMA_VERSION};
+    use std::ffi::CStr;
+
+    /// A project with three functions, one of which cannot be parsed.
+    const PROJECT_JSON: &str = include_str!("pcode/fixtures/partially_corrupted.json");
+
+    /// A minimal 64-bit little-endian ELF file consisting of the headers and one loadable segment.
+    fn minimal_elf() -> Vec<u8> {
+        let mut elf = Vec::new();
+        elf.extend_from_slice(b"\x7fELF\x02\x01\x01\x00");
+        elf.extend_from_slice(&[0; 8]);
+        elf.extend_from_slice(&2u16.to_le_bytes()); // e_type: executable
+        elf.extend_from_slice(&0x3eu16.to_le_bytes()); // e_machine: x86-64
+        elf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        elf.extend_from_slice(&0x11000u64.to_le_bytes()); // e_entry
+        elf.extend_from_slice(&64u64.to_le_bytes()); // e_phoff
+        elf.extend_from_slice(&0u64.to_le_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        for value in [64u16, 56, 1, 64, 0, 0] {
+            // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+            elf.extend_from_slice(&value.to_le_bytes());
+        }
+        elf.extend_from_slice(&1u32.to_le_bytes()); // p_type: PT_LOAD
+        elf.extend_from_slice(&5u32.to_le_bytes()); // p_flags: R+X
+        elf.extend_from_slice(&0u64.to_le_bytes()); // p_offset
+        elf.extend_from_slice(&0x10000u64.to_le_bytes()); // p_vaddr
+        elf.extend_from_slice(&0x10000u64.to_le_bytes()); // p_paddr
+        elf.extend_from_slice(&120u64.to_le_bytes()); // p_filesz
+        elf.extend_from_slice(&0x4000u64.to_le_bytes()); // p_memsz
+        elf.extend_from_slice(&0x1000u64.to_le_bytes()); // p_align
+        elf
+    }
+
+    /// Run the analysis through the exported functions and return the status and the JSON result.
+    fn analyze_via_ffi(project_json: &[u8], binary: &[u8], config_json: &[u8]) -> (i32, String) {
+        unsafe {
+            let result = cwec_analyze(
+                project_json.as_ptr(),
+                project_json.len(),
+                binary.as_ptr(),
+                binary.len(),
+                config_json.as_ptr(),
+                config_json.len(),
+            );
+            let status = cwec_result_status(result);
+            let json_ptr = cwec_result_to_json(result);
+            let json = CStr::from_ptr(json_ptr).to_str().unwrap().to_string();
+            cwec_free_string(json_ptr);
+            cwec_free_result(result);
+            (status, json)
+        }
+    }
+
+    /// The default configuration with a parsing configuration accepting the partially corrupted project.
+    fn config_json() -> Vec<u8> {
+        let mut config: serde_json::Value = serde_json::from_str(DEFAULT_CONFIG).unwrap();
+        config["PcodeParsing"] = serde_json::json!({ "max_unreadable_function_fraction": 0.5 });
+        config.to_string().into_bytes()
+    }
+
+    #[test]
+    fn analyze_project_json() {
+        let (status, json) =
+            analyze_via_ffi(PROJECT_JSON.as_bytes(), &minimal_elf(), &config_json());
+        assert_eq!(status, CWEC_OK, "{}", json);
+        let report = JsonReport::from_json_str(&json).unwrap();
+        assert_eq!(report.schema_version, SCHEMA_VERSION);
+        assert!(report.partial);
+        assert!(report.coverage.is_some());
+    }
+
+    #[test]
+    fn concurrent_analyses() {
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    analyze_via_ffi(PROJECT_JSON.as_bytes(), &minimal_elf(), &config_json())
+                })
+            })
+            .collect();
+        let results: Vec<(i32, String)> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+        assert!(results.iter().all(|result| *result == results[0]));
+        assert_eq!(results[0].0, CWEC_OK);
+    }
+
+    #[test]
+    fn errors_are_reported_as_status_codes() {
+        let error_code = |json: &str| {
+            let json: serde_json::Value = serde_json::from_str(json).unwrap();
+            json["error"]["code"].as_i64().unwrap() as i32
+        };
+        // Invalid JSON for the project
+        let (status, json) = analyze_via_ffi(b"{", &minimal_elf(), &[]);
+        assert_eq!(status, CWEC_ERROR_PARSING);
+        assert_eq!(error_code(&json), CWEC_ERROR_PARSING);
+        // A binary that cannot be loaded
+        let (status, _) = analyze_via_ffi(PROJECT_JSON.as_bytes(), b"no binary", &config_json());
+        assert_eq!(status, CWEC_ERROR_ANALYSIS);
+        // A null pointer with a non-zero length
+        unsafe {
+            let result = cwec_analyze(
+                std::ptr::null(),
+                1,
+                std::ptr::null(),
+                0,
+                std::ptr::null(),
+                0,
+            );
+            assert_eq!(cwec_result_status(result), CWEC_ERROR_INVALID_ARGUMENT);
+            cwec_free_result(result);
+            assert_eq!(
+                cwec_result_status(std::ptr::null()),
+                CWEC_ERROR_INVALID_ARGUMENT
+            );
+            assert!(cwec_result_to_json(std::ptr::null()).is_null());
+        }
+    }
+
+    #[test]
+    fn panics_are_caught() {
+        // The configurations of the analyses are missing, which makes the analysis panic.
+        let config_json = br#"{"PcodeParsing": {"max_unreadable_function_fraction": 0.5}}"#;
+        let (status, json) = analyze_via_ffi(PROJECT_JSON.as_bytes(), &minimal_elf(), config_json);
+        assert_eq!(status, CWEC_ERROR_PANIC);
+        assert!(json.contains("The analysis panicked"));
+    }
+}
@@ -0,0 +1,269 @@
+use super::ByteSize;
+use crate::prelude::*;
+
+/// The CPU architecture of a binary.
+///
+/// The architecture is parsed from the CPU architecture string provided by Ghidra
+/// (see [`Arch::from_cpu_string`]).
+/// Architecture-specific behaviour of the analyses should be decided by the capability queries of this enum
+/// instead of comparing the raw CPU architecture strings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub enum Arch {
+    /// 64-bit x86 (also known as AMD64).
+    X86_64,
+    /// 32-bit x86.
+    X86_32,
+    /// 32-bit ARM, including Thumb.
+    Arm32,
+    /// 64-bit ARM.
+    Aarch64,
+    /// 32-bit MIPS.
+    Mips32,
+    /// 64-bit MIPS.
+    Mips64,
+    /// PowerPC (32-bit or 64-bit).
+    Ppc,
+    /// RISC-V (32-bit or 64-bit).
+    RiscV,
+    /// Any other architecture. Contains the raw CPU architecture string.
+    Other(String),
+}
+
+/// The direction in which the stack grows when values are pushed onto it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum StackGrowthDirection {
+    /// The stack grows towards lower addresses.
+    Downwards,
+    /// The stack grows towards higher addresses.
+    Upwards,
+}
+
+impl Arch {
+    /// Parse the CPU architecture string generated by Ghidra.
+    ///
+    /// Accepts both the `<processor>_<bits>` format (e.g. `x86_64` or `MIPS_32`)
+    /// generated by the Ghidra plugin of the cwe_checker
+    /// and full Ghidra language IDs of the form `<processor>:<endianness>:<bits>:<variant>`
+    /// (e.g. `x86:LE:64:default` or `ARM:LE:32:v8`).
+    /// The processor name is matched case-insensitively.
+    /// Unknown architectures are returned as [`Arch::Other`].
+    pub fn from_cpu_string(cpu_string: &str) -> Arch {
+        let (processor, bits) = split_cpu_string(cpu_string.trim());
+        match (processor.to_lowercase().as_str(), bits) {
+            ("x86", Some(64)) | ("x86-64", _) | ("amd64", _) => Arch::X86_64,
+            ("x86", Some(32)) | ("x86", None) | ("i386", _) => Arch::X86_32,
+            ("arm", Some(32)) | ("arm", None) => Arch::Arm32,
+            ("aarch64", Some(64)) | ("aarch64", None) | ("arm64", _) => Arch::Aarch64,
+            ("mips", Some(64)) | ("mips64", _) => Arch::Mips64,
+            ("mips", Some(32)) | ("mips", None) => Arch::Mips32,
+            ("powerpc", _) | ("ppc", _) => Arch::Ppc,
+            ("riscv", _) => Arch::RiscV,
+            _ => Arch::Other(cpu_string.to_string()),
+        }
+    }
+
+    /// Returns `true` for 32-bit and 64-bit x86.
+    pub fn is_x86(&self) -> bool {
+        matches!(self, Arch::X86_64 | Arch::X86_32)
+    }
+
+    /// Returns `true` for 32-bit and 64-bit MIPS.
+    pub fn is_mips(&self) -> bool {
+        matches!(self, Arch::Mips32 | Arch::Mips64)
+    }
+
+    /// Returns `true` if call instructions push the return address onto the stack.
+    ///
+    /// On these architectures the stack pointer is incremented by the pointer size on return.
+    /// All other architectures store the return address in a link register.
+    pub fn pushes_return_address_on_stack(&self) -> bool {
+        self.is_x86()
+    }
+
+    /// Returns `true` if memory accesses that are not aligned to their natural alignment
+    /// may fault or silently access the wrong memory on the architecture.
+    ///
+    /// ARM is treated as strict-alignment, since older cores (ARMv5 and before)
+    /// and several multi-word instructions on newer cores do not support unaligned accesses.
+    pub fn requires_strict_alignment(&self) -> bool {
+        matches!(self, Arch::Arm32) || self.is_mips()
+    }
+
+    /// Return the size of pointers for the architecture.
+    /// Returns `None` if the architecture exists in both 32-bit and 64-bit variants
+    /// not distinguished by the enum.
+    pub fn pointer_size(&self) -> Option<ByteSize> {
+        match self {
+            Arch::X86_64 | Arch::Aarch64 | Arch::Mips64 => Some(ByteSize::new(8)),
+            Arch::X86_32 | Arch::Arm32 | Arch::Mips32 => Some(ByteSize::new(4)),
+            Arch::Ppc | Arch::RiscV | Arch::Other(_) => None,
+        }
+    }
+
+    /// Return the alignment of the stack pointer at function calls required by the standard ABI of the architecture.
+    pub fn stack_alignment(&self) -> Option<ByteSize> {
+        match self {
+            Arch::X86_64
+            | Arch::X86_32
+            | Arch::Aarch64
+            | Arch::Mips64
+            | Arch::Ppc
+            | Arch::RiscV => Some(ByteSize::new(16)),
+            Arch::Arm32 | Arch::Mips32 => Some(ByteSize::new(8)),
+            Arch::Other(_) => None,
+        }
+    }
+
+    /// Return the size of the red zone, i.e. the area below the stack pointer
+    /// that functions may use without adjusting the stack pointer.
+    ///
+    /// Returns `None` if the size depends on the ABI variant,
+    /// e.g. for PowerPC, where only the 64-bit ABIs define a red zone.
+    pub fn red_zone_size(&self) -> Option<ByteSize> {
+        match self {
+            Arch::X86_64 => Some(ByteSize::new(128)),
+            Arch::X86_32
+            | Arch::Arm32
+            | Arch::Aarch64
+            | Arch::Mips32
+            | Arch::Mips64
+            | Arch::RiscV => Some(ByteSize::new(0)),
+            Arch::Ppc | Arch::Other(_) => None,
+        }
+    }
+
+    /// Return the direction in which the stack grows.
+    pub fn stack_growth_direction(&self) -> Option<StackGrowthDirection> {
+        match self {
+            Arch::Other(_) => None,
+            _ => Some(StackGrowthDirection::Downwards),
+        }
+    }
+
+    /// Return the name of the stack pointer register.
+    /// The name is the name of the register used by Ghidra.
+    ///
+    /// Returns `None` if the stack pointer register is unknown for the architecture.
+    pub fn stack_pointer_register_name(&self) -> Option<&'static str> {
+        match self {
+            Arch::X86_64 => Some("RSP"),
+            Arch::X86_32 => Some("ESP"),
+            Arch::Arm32 | Arch::Aarch64 | Arch::Mips32 | Arch::Mips64 | Arch::RiscV => Some("sp"),
+            Arch::Ppc => Some("r1"),
+            Arch::Other(_) => None,
+        }
+    }
+
+    /// Return the name of the register holding the return address after a call instruction.
+    /// The name is the name of the register used by Ghidra.
+    ///
+    /// Returns `None` if the architecture pushes the return address onto the stack
+    /// or if the link register is unknown.
+    pub fn link_register_name(&self) -> Option<&'static str> {
+        match self {
+            Arch::Arm32 => Some("lr"),
+            Arch::Aarch64 => Some("x30"),
+            Arch::Mips32 | Arch::Mips64 | Arch::RiscV => Some("ra"),
+            Arch::Ppc => Some("LR"),
+            Arch::X86_64 | Arch::X86_32 | Arch::Other(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Arch {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arch::X86_64 => write!(formatter, "x86_64"),
+            Arch::X86_32 => write!(formatter, "x86_32"),
+            Arch::Arm32 => write!(formatter, "ARM_32"),
+            Arch::Aarch64 => write!(formatter, "AARCH64_64"),
+            Arch::Mips32 => write!(formatter, "MIPS_32"),
+            Arch::Mips64 => write!(formatter, "MIPS_64"),
+            Arch::Ppc => write!(formatter, "PowerPC"),
+            Arch::RiscV => write!(formatter, "RISCV"),
+            Arch::Other(cpu_string) => write!(formatter, "{}", cpu_string),
+        }
+    }
+}
+
+/// Split a CPU architecture string into the processor name and the bit width (if known).
+fn split_cpu_string(cpu_string: &str) -> (&str, Option<u64>) {
+    let parts: Vec<&str> = cpu_string.split(':').collect();
+    if parts.len() >= 3 {
+        return (parts[0], parts[2].parse().ok());
+    }
+    if let Some((processor, bits)) = cpu_string.rsplit_once('_') {
+        if let Ok(bits) = bits.parse() {
+            return (processor, Some(bits));
+        }
+    }
+    (cpu_string, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ghidra_cpu_strings() {
+        let cases = [
+            ("x86_64", Arch::X86_64),
+            ("x86:LE:64:default", Arch::X86_64),
+            ("x86:LE:64:compat32", Arch::X86_64),
+            ("x86_32", Arch::X86_32),
+            ("x86", Arch::X86_32),
+            ("x86:LE:32:default", Arch::X86_32),
+            ("ARM_32", Arch::Arm32),
+            ("arm_32", Arch::Arm32),
+            ("ARM:LE:32:v8", Arch::Arm32),
+            ("ARM:BE:32:v7", Arch::Arm32),
+            ("ARM:LE:32:Cortex", Arch::Arm32),
+            ("AARCH64_64", Arch::Aarch64),
+            ("AARCH64:LE:64:v8A", Arch::Aarch64),
+            ("AARCH64:LE:64:AppleSilicon", Arch::Aarch64),
+            ("MIPS_32", Arch::Mips32),
+            ("MIPS:BE:32:default", Arch::Mips32),
+            ("MIPS:LE:32:R6", Arch::Mips32),
+            ("MIPS_64", Arch::Mips64),
+            ("MIPS:BE:64:64-32addr", Arch::Mips64),
+            ("MIPS:LE:64:micro", Arch::Mips64),
+            ("PowerPC_32", Arch::Ppc),
+            ("PowerPC_64", Arch::Ppc),
+            ("PowerPC:BE:64:A2-32addr", Arch::Ppc),
+            ("RISCV_64", Arch::RiscV),
+            ("RISCV:LE:32:RV32GC", Arch::RiscV),
+            ("RISCV:LE:64:RV64GC", Arch::RiscV),
+            ("68000_32", Arch::Other("68000_32".to_string())),
+            (
+                "x86:LE:16:Real Mode",
+                Arch::Other("x86:LE:16:Real Mode".to_string()),
+            ),
+            (
+                "sparc:BE:64:default",
+                Arch::Other("sparc:BE:64:default".to_string()),
+            ),
+        ];
+        for (cpu_string, arch) in cases {
+            assert_eq!(Arch::from_cpu_string(cpu_string), arch, "{}", cpu_string);
+        }
+    }
+
+    #[test]
+    fn capability_queries() {
+        assert!(Arch::X86_32.pushes_return_address_on_stack());
+        assert!(!Arch::Arm32.pushes_return_address_on_stack());
+        assert!(Arch::Mips64.requires_strict_alignment());
+        assert!(!Arch::X86_64.requires_strict_alignment());
+        assert_eq!(Arch::Mips64.pointer_size(), Some(ByteSize::new(8)));
+        assert_eq!(Arch::RiscV.pointer_size(), None);
+        assert_eq!(Arch::X86_64.red_zone_size(), Some(ByteSize::new(128)));
+        assert_eq!(Arch::Mips32.link_register_name(), Some("ra"));
+        assert_eq!(Arch::X86_64.link_register_name(), None);
+        assert_eq!(Arch::RiscV.stack_pointer_register_name(), Some("sp"));
+        assert_eq!(Arch::RiscV.stack_alignment(), Some(ByteSize::new(16)));
+        assert_eq!(
+            Arch::Aarch64.stack_growth_direction(),
+            Some(StackGrowthDirection::Downwards)
+        );
+    }
+}
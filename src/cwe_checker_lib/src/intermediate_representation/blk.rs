@@ -192,7 +192,7 @@ impl Term<Blk> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::intermediate_representation::{Def, Expression, Variable};
+    use crate::intermediate_representation::{Def, Expression, Jmp, Variable};
 
     impl Blk {
         pub fn mock() -> Term<Blk> {
@@ -216,6 +216,36 @@ mod tests {
                 },
             }
         }
+
+        /// Create a block with the given `Def`s that ends with the given jump.
+        pub fn mock_with_jmp(tid: &str, defs: Vec<Term<Def>>, jmp: Term<Jmp>) -> Term<Blk> {
+            let mut block = Blk::mock_with_tid(tid);
+            block.term.defs = defs;
+            block.term.jmps.push(jmp);
+            block
+        }
+
+        /// Create a block with the given `Def`s that ends with a call to `target` returning to `return_block`.
+        /// The TID of the call is `call_{tid}`.
+        pub fn mock_call(
+            tid: &str,
+            defs: Vec<Term<Def>>,
+            target: &str,
+            return_block: &str,
+        ) -> Term<Blk> {
+            let call = Jmp::call(&format!("call_{}", tid), target, Some(return_block));
+            Blk::mock_with_jmp(tid, defs, call)
+        }
+
+        /// Create a block that only contains a return instruction with TID `return_{tid}`.
+        pub fn mock_return(tid: &str) -> Term<Blk> {
+            let mut block = Blk::mock_with_tid(tid);
+            block.term.jmps.push(Term {
+                tid: Tid::new(format!("return_{}", tid)),
+                term: Jmp::Return(Expression::var("RBX", 8)),
+            });
+            block
+        }
     }
 
     #[test]
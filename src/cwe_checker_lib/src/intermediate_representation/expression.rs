@@ -188,6 +188,27 @@ impl Expression {
         }
     }
 
+    /// Return the number of `Unknown` placeholder expressions contained in the expression.
+    /// They are inserted for operations that could not be translated to the intermediate representation.
+    pub fn count_unknown_subexpressions(&self) -> usize {
+        use Expression::*;
+        match self {
+            Unknown { .. } => 1,
+            Var(_) | Const(_) => 0,
+            BinOp { lhs, rhs, .. } => {
+                lhs.count_unknown_subexpressions() + rhs.count_unknown_subexpressions()
+            }
+            UnOp { arg, .. } | Cast { arg, .. } | Subpiece { arg, .. } => {
+                arg.count_unknown_subexpressions()
+            }
+        }
+    }
+
+    /// Returns `true` if the expression contains an `Unknown` placeholder expression.
+    pub fn contains_unknown(&self) -> bool {
+        self.count_unknown_subexpressions() > 0
+    }
+
     /// Substitute every occurence of `input_var` in `self` with the given `replace_with_expression`.
     pub fn substitute_input_var(
         &mut self,
@@ -10,6 +10,8 @@
 use crate::prelude::*;
 use derive_more::*;
 
+mod architecture;
+pub use architecture::*;
 mod bitvector;
 pub use bitvector::*;
 mod variable;
@@ -20,6 +20,15 @@ pub struct Program {
     /// Thus addresses as specified by the binary and addresses as reported by Ghidra may differ by a constant offset,
     /// which is stored in this value.
     pub address_base_offset: u64,
+    /// Maps alternative names of symbols (e.g. weak aliases) to the canonical name of the symbol.
+    #[serde(default)]
+    pub symbol_aliases: BTreeMap<String, String>,
+    /// The TIDs of functions that never return to their caller,
+    /// e.g. wrappers around `exit` that are called on fatal errors.
+    /// See [`Project::mark_non_returning_subs`](crate::intermediate_representation::Project::mark_non_returning_subs)
+    /// for more information.
+    #[serde(default)]
+    pub non_returning_subs: BTreeSet<Tid>,
 }
 
 impl Program {
@@ -33,6 +42,43 @@ impl Program {
             .flatten()
             .find(|block| block.tid == *tid)
     }
+
+    /// Get the canonical name of a symbol, i.e. resolve the name if it is an alias of another symbol.
+    pub fn get_canonical_symbol_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.symbol_aliases
+            .get(name)
+            .map(|canonical_name| canonical_name.as_str())
+            .unwrap_or(name)
+    }
+
+    /// Get all known aliases of the symbol with the given canonical name.
+    pub fn get_symbol_aliases(&self, canonical_name: &str) -> Vec<&str> {
+        self.symbol_aliases
+            .iter()
+            .filter(|(_alias, name)| *name == canonical_name)
+            .map(|(alias, _name)| alias.as_str())
+            .collect()
+    }
+
+    /// Returns `true` if both names denote the same symbol,
+    /// i.e. if they are equal or aliases of the same symbol.
+    pub fn symbol_names_match(&self, name: &str, other_name: &str) -> bool {
+        name == other_name
+            || self.get_canonical_symbol_name(name) == self.get_canonical_symbol_name(other_name)
+    }
+
+    /// Find an extern symbol by its name.
+    /// If no extern symbol with exactly this name exists, aliases of the name are also considered.
+    pub fn find_extern_symbol_by_name(&self, name: &str) -> Option<&ExternSymbol> {
+        self.extern_symbols
+            .values()
+            .find(|symbol| symbol.name == name)
+            .or_else(|| {
+                self.extern_symbols
+                    .values()
+                    .find(|symbol| self.symbol_names_match(&symbol.name, name))
+            })
+    }
 }
 
 #[cfg(test)]
@@ -46,6 +92,8 @@ mod tests {
                 extern_symbols: BTreeMap::new(),
                 entry_points: BTreeSet::new(),
                 address_base_offset: 0,
+                symbol_aliases: BTreeMap::new(),
+                non_returning_subs: BTreeSet::new(),
             }
         }
     }
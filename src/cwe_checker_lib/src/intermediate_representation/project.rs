@@ -1,9 +1,20 @@
 use super::*;
-use crate::utils::log::LogMessage;
+use crate::utils::log::{CweWarning, LogMessage};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 mod block_duplication_normalization;
 use block_duplication_normalization::*;
+mod call_other_resolution;
+pub use call_other_resolution::{CallOtherConfig, CallOtherPolicy, SyscallConvention, SyscallSpec};
+mod go_normalization;
+mod jumpless_block_normalization;
+pub use go_normalization::GO_CALLING_CONVENTION;
+mod loop_unrolling;
+mod no_return_propagation;
+pub use loop_unrolling::LoopUnrollingConfig;
+mod stack_canary;
+mod symbol_resolution;
+mod thunk_resolution;
 
 /// The `Project` struct is the main data structure representing a binary.
 ///
@@ -13,8 +24,12 @@ use block_duplication_normalization::*;
 pub struct Project {
     /// All (known) executable code of the binary is contained in the `program` term.
     pub program: Term<Program>,
-    /// The CPU architecture on which the binary is assumed to be executed.
+    /// The CPU architecture on which the binary is assumed to be executed,
+    /// as given by the raw CPU architecture string generated by Ghidra.
     pub cpu_architecture: String,
+    /// The CPU architecture parsed from the `cpu_architecture` string.
+    /// Use it instead of comparing the raw string for architecture-specific behaviour.
+    pub architecture: Arch,
     /// The stack pointer register for the given CPU architecture.
     pub stack_pointer_register: Variable,
     /// The known calling conventions that may be used for calls to extern functions.
@@ -24,6 +39,13 @@ pub struct Project {
     pub register_set: BTreeSet<Variable>,
     /// Contains the properties of C data types. (e.g. size)
     pub datatype_properties: DatatypeProperties,
+    /// The TIDs of all terms (blocks, defs and jumps) that belong to checks of stack canaries.
+    /// See [`Project::mark_stack_canary_artifacts`] for more information.
+    pub stack_canary_artifacts: BTreeSet<Tid>,
+    /// Maps the TIDs of terms (blocks, defs and jumps) that are copies of terms contained in more than one function
+    /// to the TIDs of the original terms.
+    /// See [`make_block_to_sub_mapping_unique`] for more information.
+    pub duplicated_term_origins: BTreeMap<Tid, Tid>,
 }
 
 impl Project {
@@ -154,6 +176,7 @@ impl Project {
                         },
                     }],
                     calling_convention: None,
+                    is_thunk: false,
                 },
             };
             self.program
@@ -180,18 +203,22 @@ impl Project {
     /// Run some normalization passes over the project.
     ///
     /// Passes:
+    /// - Add synthetic jumps to blocks without a terminating jump.
     /// - Replace jumps to nonexisting TIDs with jumps to artificial sink targets in the CFG.
     /// - Duplicate blocks so that if a block is contained in several functions, each function gets its own unique copy.
     /// - Propagate input expressions along variable assignments.
     /// - Replace trivial expressions like `a XOR a` with their result.
     /// - Remove dead register assignments
+    /// - Mark the terms belonging to checks of stack canaries
     #[must_use]
     pub fn normalize(&mut self) -> Vec<LogMessage> {
-        let logs = self.remove_references_to_nonexisting_tids();
+        let mut logs = self.add_missing_block_terminators();
+        logs.append(&mut self.remove_references_to_nonexisting_tids());
         make_block_to_sub_mapping_unique(self);
         self.propagate_input_expressions();
         self.substitute_trivial_expressions();
         crate::analysis::dead_variable_elimination::remove_dead_var_assignments(self);
+        self.mark_stack_canary_artifacts();
         logs
     }
 }
@@ -260,12 +287,24 @@ mod tests {
                     term: Program::mock_empty(),
                 },
                 cpu_architecture: "x86_64".to_string(),
+                architecture: Arch::X86_64,
                 stack_pointer_register: Variable::mock("RSP", 8u64),
                 calling_conventions: BTreeMap::new(),
                 register_set,
                 datatype_properties: DatatypeProperties::mock(),
+                stack_canary_artifacts: BTreeSet::new(),
+                duplicated_term_origins: BTreeMap::new(),
             }
         }
+
+        /// Add a x64 extern symbol with the given name to the program
+        /// and return a reference to it, so that its mock values can be adjusted.
+        pub fn add_mock_extern_symbol(&mut self, name: &str) -> &mut ExternSymbol {
+            let symbol = ExternSymbol::mock_x64_named(name);
+            let tid = symbol.tid.clone();
+            self.program.term.extern_symbols.insert(tid.clone(), symbol);
+            self.program.term.extern_symbols.get_mut(&tid).unwrap()
+        }
     }
 
     #[test]
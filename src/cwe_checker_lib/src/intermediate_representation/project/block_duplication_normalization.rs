@@ -31,22 +31,47 @@ impl Term<Blk> {
         }
         cloned_block
     }
+
+    /// Map the TIDs of all terms in `cloned_block` to the TIDs of the corresponding terms in `self`,
+    /// where `cloned_block` was generated by [`Term::<Blk>::clone_with_tid_suffix`] from `self`.
+    fn add_origins_of_cloned_terms(
+        &self,
+        cloned_block: &Self,
+        term_origins: &mut BTreeMap<Tid, Tid>,
+    ) {
+        term_origins.insert(cloned_block.tid.clone(), self.tid.clone());
+        for (cloned_def, def) in cloned_block.term.defs.iter().zip(self.term.defs.iter()) {
+            term_origins.insert(cloned_def.tid.clone(), def.tid.clone());
+        }
+        for (cloned_jmp, jmp) in cloned_block.term.jmps.iter().zip(self.term.jmps.iter()) {
+            term_origins.insert(cloned_jmp.tid.clone(), jmp.tid.clone());
+        }
+    }
 }
 
 impl Project {
     /// Generate a map from all `Sub`, `Blk`, `Def` and `Jmp` TIDs of the project
     /// to the `Sub` TID in which the term is contained.
+    ///
+    /// If a block is contained in the block lists of more than one `Sub`,
+    /// the block and its contained terms are mapped to the first of these `Sub`s (in the order of their TIDs).
     fn generate_tid_to_sub_tid_map(&self) -> HashMap<Tid, Tid> {
         let mut tid_to_sub_map = HashMap::new();
         for sub in self.program.term.subs.values() {
             tid_to_sub_map.insert(sub.tid.clone(), sub.tid.clone());
             for block in sub.term.blocks.iter() {
-                tid_to_sub_map.insert(block.tid.clone(), sub.tid.clone());
+                tid_to_sub_map
+                    .entry(block.tid.clone())
+                    .or_insert_with(|| sub.tid.clone());
                 for def in block.term.defs.iter() {
-                    tid_to_sub_map.insert(def.tid.clone(), sub.tid.clone());
+                    tid_to_sub_map
+                        .entry(def.tid.clone())
+                        .or_insert_with(|| sub.tid.clone());
                 }
                 for jmp in block.term.jmps.iter() {
-                    tid_to_sub_map.insert(jmp.tid.clone(), sub.tid.clone());
+                    tid_to_sub_map
+                        .entry(jmp.tid.clone())
+                        .or_insert_with(|| sub.tid.clone());
                 }
             }
         }
@@ -70,12 +95,12 @@ impl Project {
     fn generate_sub_tid_to_contained_block_tids_map(
         &self,
         block_tid_to_block_map: &HashMap<Tid, &Term<Blk>>,
-    ) -> HashMap<Tid, HashSet<Tid>> {
+    ) -> HashMap<Tid, BTreeSet<Tid>> {
         let mut sub_to_blocks_map = HashMap::new();
         for sub in self.program.term.subs.values() {
             let mut worklist: Vec<Tid> =
                 sub.term.blocks.iter().map(|blk| blk.tid.clone()).collect();
-            let mut block_set = HashSet::new();
+            let mut block_set = BTreeSet::new();
             while let Some(block_tid) = worklist.pop() {
                 if block_set.get(&block_tid).is_none() {
                     block_set.insert(block_tid.clone());
@@ -109,14 +134,17 @@ impl Project {
     /// (to ensure that the newly created terms have unique TIDs).
     /// The TIDs of jump and return targets are not adjusted in this function.
     /// The returned map maps the TID of a `Sub` to the newly created blocks for that `Sub`.
+    /// The TIDs of all newly created terms are added to `term_origins`,
+    /// mapped to the TID of the term that they are a copy of.
     ///
     /// This function is part of the [`Project::make_block_to_sub_mapping_unique`] normalization pass
     /// and should not be used for other purposes.
     fn duplicate_blocks_contained_in_several_subs(
         &self,
-        sub_to_blocks_map: &HashMap<Tid, HashSet<Tid>>,
+        sub_to_blocks_map: &HashMap<Tid, BTreeSet<Tid>>,
         tid_to_sub_map: &HashMap<Tid, Tid>,
         block_tid_to_block_map: &HashMap<Tid, &Term<Blk>>,
+        term_origins: &mut BTreeMap<Tid, Tid>,
     ) -> HashMap<Tid, Vec<Term<Blk>>> {
         // Generate new blocks without adjusting jump TIDs
         let mut sub_to_additional_blocks_map = HashMap::new();
@@ -125,10 +153,9 @@ impl Project {
             let mut additional_blocks = Vec::new();
             for block_tid in sub_to_blocks_map.get(&sub.tid).unwrap() {
                 if tid_to_sub_map.get(block_tid) != Some(&sub.tid) {
-                    let block = block_tid_to_block_map
-                        .get(block_tid)
-                        .unwrap()
-                        .clone_with_tid_suffix(&tid_suffix);
+                    let original_block = block_tid_to_block_map.get(block_tid).unwrap();
+                    let block = original_block.clone_with_tid_suffix(&tid_suffix);
+                    original_block.add_origins_of_cloned_terms(&block, term_origins);
                     additional_blocks.push(block);
                 }
             }
@@ -179,11 +206,89 @@ impl Project {
     }
 }
 
+impl Project {
+    /// Remove blocks from the block list of a `Sub`
+    /// if the block is also contained in the block list of another `Sub`
+    /// and `tid_to_sub_map` maps the block to the other `Sub`.
+    ///
+    /// This function is part of the [`Project::make_block_to_sub_mapping_unique`] normalization pass
+    /// and should not be used for other purposes.
+    fn remove_shared_blocks_from_non_owning_subs(&mut self, tid_to_sub_map: &HashMap<Tid, Tid>) {
+        for sub in self.program.term.subs.values_mut() {
+            let sub_tid = &sub.tid;
+            sub.term
+                .blocks
+                .retain(|block| tid_to_sub_map.get(&block.tid) == Some(sub_tid));
+        }
+    }
+
+    /// Add the names of all functions containing a copy of a term referenced by a CWE warning
+    /// to the warning if the term is contained in more than one function.
+    ///
+    /// Such terms were duplicated by the [`Project::make_block_to_sub_mapping_unique`] normalization pass.
+    /// Since the copies have the same address as the original term,
+    /// the addresses of the warnings already point to the original location in the binary.
+    /// The function names are added as an entry of the form `["containing_functions", <names>...]`
+    /// to the `other` field of the warning.
+    pub fn annotate_warnings_in_duplicated_blocks(&self, cwe_warnings: &mut [CweWarning]) {
+        if self.duplicated_term_origins.is_empty() {
+            return;
+        }
+        let get_origin = |tid: &Tid| -> Tid {
+            self.duplicated_term_origins
+                .get(tid)
+                .cloned()
+                .unwrap_or_else(|| tid.clone())
+        };
+        let originals: HashSet<&Tid> = self.duplicated_term_origins.values().collect();
+        // Maps the ID strings of the duplicated terms and their copies to the original TID
+        let mut id_to_origin_map: HashMap<String, Tid> = HashMap::new();
+        let mut origin_to_sub_names_map: HashMap<Tid, BTreeSet<String>> = HashMap::new();
+        for sub in self.program.term.subs.values() {
+            for block in sub.term.blocks.iter() {
+                let term_tids = std::iter::once(&block.tid)
+                    .chain(block.term.defs.iter().map(|def| &def.tid))
+                    .chain(block.term.jmps.iter().map(|jmp| &jmp.tid));
+                for tid in term_tids {
+                    let origin = get_origin(tid);
+                    if originals.contains(&origin) {
+                        id_to_origin_map.insert(format!("{}", tid), origin.clone());
+                        origin_to_sub_names_map
+                            .entry(origin)
+                            .or_default()
+                            .insert(sub.term.name.clone());
+                    }
+                }
+            }
+        }
+        for warning in cwe_warnings.iter_mut() {
+            let sub_names: BTreeSet<&String> = warning
+                .tids
+                .iter()
+                .filter_map(|id| id_to_origin_map.get(id))
+                .flat_map(|origin| origin_to_sub_names_map[origin].iter())
+                .collect();
+            if sub_names.len() > 1 {
+                let mut entry = vec!["containing_functions".to_string()];
+                entry.extend(sub_names.into_iter().cloned());
+                warning.other.push(entry);
+            }
+        }
+    }
+}
+
 /// Create copies of blocks that are contained in more than one subroutine
 /// so that each subroutine has its own unique copy of the block.
 ///
+/// A block is contained in a subroutine if it is reachable through intraprocedural jumps
+/// from a block in the block list of the subroutine.
+/// This includes blocks contained in the block lists of several subroutines,
+/// e.g. function epilogues shared between functions in hand-written assembly.
+/// Such blocks are kept only in the block list of the first of these subroutines.
+///
 /// The TIDs of the copied blocks (and the contained `Def` and `Jmp` terms)
 /// are appended with the sub TID to ensure that TIDs remain globally unique.
+/// The TIDs of the copies are mapped to the TIDs of the original terms in [`Project::duplicated_term_origins`].
 /// Target TIDs of intraprocedural jumps are also adjusted
 /// to target the sub-specific copy of a block if the target block was duplicated.
 pub fn make_block_to_sub_mapping_unique(project: &mut Project) {
@@ -192,11 +297,15 @@ pub fn make_block_to_sub_mapping_unique(project: &mut Project) {
     let sub_to_blocks_map =
         project.generate_sub_tid_to_contained_block_tids_map(&block_tid_to_block_map);
 
+    let mut term_origins = BTreeMap::new();
     let mut sub_to_additional_blocks_map = project.duplicate_blocks_contained_in_several_subs(
         &sub_to_blocks_map,
         &tid_to_sub_map,
         &block_tid_to_block_map,
+        &mut term_origins,
     );
+    project.remove_shared_blocks_from_non_owning_subs(&tid_to_sub_map);
+    project.duplicated_term_origins.append(&mut term_origins);
     // Add the new blocks to the subs
     for sub in project.program.term.subs.values_mut() {
         sub.term
@@ -233,6 +342,7 @@ mod tests {
                 name: sub_name.to_string(),
                 blocks,
                 calling_convention: None,
+                is_thunk: false,
             },
         }
     }
@@ -319,4 +429,89 @@ mod tests {
             .blocks
             .contains(&sub_3_modified.term.blocks[3]));
     }
+
+    #[test]
+    fn shared_epilogue_block() {
+        let epilogue = Term {
+            tid: Tid::blk_id_at_address("00003000"),
+            term: Blk {
+                defs: vec![Def::assign(
+                    "def_epilogue",
+                    Variable::mock("RAX", 8),
+                    Expression::const_from_i64(0),
+                )],
+                jmps: vec![Term {
+                    tid: Tid::new("jmp_epilogue"),
+                    term: Jmp::Return(Expression::Var(Variable::mock("RBX", 8))),
+                }],
+                indirect_jmp_targets: Vec::new(),
+            },
+        };
+        let mut sub_1 = create_sub_with_blocks(
+            "sub_1",
+            vec![create_block_with_jump_target("blk_1", "blk_00003000")],
+        );
+        sub_1.term.blocks[0].term.jmps[0].term = Jmp::Branch(epilogue.tid.clone());
+        sub_1.term.blocks.push(epilogue.clone());
+        let mut sub_2 = create_sub_with_blocks(
+            "sub_2",
+            vec![create_block_with_jump_target("blk_2", "blk_00003000")],
+        );
+        sub_2.term.blocks[0].term.jmps[0].term = Jmp::Branch(epilogue.tid.clone());
+        sub_2.term.blocks.push(epilogue.clone());
+        let mut project = Project::mock_empty();
+        project.program.term.subs = BTreeMap::from_iter([
+            (sub_1.tid.clone(), sub_1.clone()),
+            (sub_2.tid.clone(), sub_2.clone()),
+        ]);
+
+        make_block_to_sub_mapping_unique(&mut project);
+
+        // The first sub keeps the original block.
+        assert_eq!(&project.program.term.subs[&sub_1.tid], &sub_1);
+        // The second sub gets exactly one copy of the block with fresh TIDs.
+        let blocks = &project.program.term.subs[&sub_2.tid].term.blocks;
+        assert_eq!(blocks.len(), 2);
+        let cloned_epilogue = &blocks[1];
+        assert_eq!(
+            cloned_epilogue.tid,
+            epilogue.tid.clone().with_id_suffix("_sub_2")
+        );
+        assert_eq!(cloned_epilogue.tid.address, "00003000");
+        assert_eq!(
+            blocks[0].term.jmps[0].term,
+            Jmp::Branch(cloned_epilogue.tid.clone())
+        );
+        // The origins of the copied terms are known.
+        let origins = &project.duplicated_term_origins;
+        assert_eq!(origins.len(), 3);
+        assert_eq!(origins[&cloned_epilogue.tid], epilogue.tid);
+        assert_eq!(
+            origins[&cloned_epilogue.term.defs[0].tid],
+            Tid::new("def_epilogue")
+        );
+        assert_eq!(
+            origins[&cloned_epilogue.term.jmps[0].tid],
+            Tid::new("jmp_epilogue")
+        );
+        // Each block is only contained once in the control flow graph.
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        assert_eq!(graph.node_count(), 8);
+
+        // Warnings in the copied block report both containing functions.
+        let mut warnings = vec![
+            CweWarning::new("CWE000", "0.1", "in copy").tids(vec!["def_epilogue_sub_2".into()]),
+            CweWarning::new("CWE000", "0.1", "in original").tids(vec!["jmp_epilogue".into()]),
+            CweWarning::new("CWE000", "0.1", "not shared").tids(vec!["jmp_blk_1".into()]),
+        ];
+        project.annotate_warnings_in_duplicated_blocks(&mut warnings);
+        let containing_functions: Vec<String> = vec![
+            "containing_functions".into(),
+            "sub_1".into(),
+            "sub_2".into(),
+        ];
+        assert_eq!(warnings[0].other, vec![containing_functions.clone()]);
+        assert_eq!(warnings[1].other, vec![containing_functions]);
+        assert!(warnings[2].other.is_empty());
+    }
 }
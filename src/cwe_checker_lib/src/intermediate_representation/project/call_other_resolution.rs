@@ -0,0 +1,404 @@
+use super::loop_unrolling::evaluate;
+use super::*;
+
+/// The handling of `CallOther` instructions with a specific description.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum CallOtherPolicy {
+    /// The instruction does not change the state tracked by the analyses,
+    /// e.g. memory barriers or cache maintenance instructions.
+    /// It is replaced by a jump to the next instruction.
+    NoOp,
+    /// The instruction is a system call.
+    /// It is replaced by a call to a synthetic extern symbol.
+    /// If the syscall number is a known constant listed in the syscall convention of the architecture,
+    /// the extern symbol is named like the syscall (e.g. `read`),
+    /// so that the analyses can use the configuration for the corresponding library function.
+    /// Otherwise it is a generic `syscall` symbol.
+    Syscall,
+}
+
+/// The way system calls pass their number, parameters and return value on a specific architecture.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SyscallConvention {
+    /// The name of the register containing the syscall number.
+    pub number_register: String,
+    /// The names of the parameter registers in the order of the parameters.
+    pub parameter_registers: Vec<String>,
+    /// The name of the register containing the return value.
+    pub return_register: String,
+    /// The names of registers other than the return register that are overwritten by the syscall instruction.
+    #[serde(default)]
+    pub clobbered_registers: Vec<String>,
+    /// The known syscalls, keyed by their names.
+    #[serde(default)]
+    pub syscalls: BTreeMap<String, SyscallSpec>,
+}
+
+/// The number and the prototype of a known syscall.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SyscallSpec {
+    /// The syscall number.
+    pub number: u64,
+    /// The number of parameters of the syscall.
+    pub parameters: usize,
+    /// Set to `true` if the syscall does not return, e.g. for `exit`.
+    #[serde(default)]
+    pub no_return: bool,
+}
+
+/// The configuration of the `CallOther` resolution normalization pass.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct CallOtherConfig {
+    /// Maps descriptions of `CallOther` instructions to their handling.
+    /// Instructions with other descriptions stay dead ends of the control flow.
+    #[serde(default)]
+    pub policies: BTreeMap<String, CallOtherPolicy>,
+    /// The syscall conventions keyed by architecture name (e.g. `x86_64` or `ARM_32`).
+    /// Syscall instructions on architectures without syscall convention stay dead ends of the control flow.
+    #[serde(default)]
+    pub syscall_conventions: BTreeMap<String, SyscallConvention>,
+}
+
+/// The name of the calling convention added for the synthetic syscall extern symbols.
+const SYSCALL_CALLING_CONVENTION: &str = "syscall";
+
+/// The name of the synthetic extern symbol for syscalls with unknown syscall number.
+const GENERIC_SYSCALL_NAME: &str = "syscall";
+
+impl Project {
+    /// Get the register with the given name
+    /// or a register with the size of a pointer if the register set does not contain the name.
+    fn get_register_by_name(&self, name: &str) -> Variable {
+        self.register_set
+            .iter()
+            .find(|register| register.name == name)
+            .cloned()
+            .unwrap_or_else(|| Variable {
+                name: name.to_string(),
+                size: self.get_pointer_bytesize(),
+                is_temp: false,
+            })
+    }
+
+    /// Add the calling convention of syscalls to the project.
+    ///
+    /// The kernel preserves all registers except the return register and the clobbered registers of the convention.
+    /// Thus the callee-saved registers are those of the standard calling convention together with
+    /// the syscall number register and the parameter registers.
+    fn add_syscall_calling_convention(&mut self, convention: &SyscallConvention) {
+        let return_register = self.get_register_by_name(&convention.return_register);
+        let mut callee_saved_register: Vec<Variable> = self
+            .get_standard_calling_convention()
+            .map(|cconv| cconv.callee_saved_register.clone())
+            .unwrap_or_default();
+        for name in std::iter::once(&convention.number_register)
+            .chain(convention.parameter_registers.iter())
+        {
+            let register = self.get_register_by_name(name);
+            if !callee_saved_register.contains(&register) {
+                callee_saved_register.push(register);
+            }
+        }
+        callee_saved_register.retain(|register| {
+            *register != return_register && !convention.clobbered_registers.contains(&register.name)
+        });
+        let cconv = CallingConvention {
+            name: SYSCALL_CALLING_CONVENTION.to_string(),
+            integer_parameter_register: convention
+                .parameter_registers
+                .iter()
+                .map(|name| self.get_register_by_name(name))
+                .collect(),
+            float_parameter_register: Vec::new(),
+            integer_return_register: vec![return_register],
+            float_return_register: Vec::new(),
+            callee_saved_register,
+            varargs_layout: None,
+        };
+        self.calling_conventions.insert(cconv.name.clone(), cconv);
+    }
+
+    /// Generate the synthetic extern symbol for the syscall with the given name.
+    ///
+    /// Known syscalls get the number of parameters given by their spec.
+    /// The generic syscall symbol gets all parameter registers of the convention as parameters.
+    fn generate_syscall_symbol(
+        &self,
+        name: &str,
+        spec: Option<&SyscallSpec>,
+        convention: &SyscallConvention,
+    ) -> ExternSymbol {
+        let parameter_count = spec
+            .map(|spec| spec.parameters)
+            .unwrap_or(convention.parameter_registers.len());
+        ExternSymbol {
+            tid: Tid::new(format!("syscall_{}", name)),
+            addresses: Vec::new(),
+            name: name.to_string(),
+            calling_convention: Some(SYSCALL_CALLING_CONVENTION.to_string()),
+            parameters: convention
+                .parameter_registers
+                .iter()
+                .take(parameter_count)
+                .map(|register| Arg::from_var(self.get_register_by_name(register), None))
+                .collect(),
+            return_values: vec![Arg::from_var(
+                self.get_register_by_name(&convention.return_register),
+                None,
+            )],
+            no_return: spec.is_some_and(|spec| spec.no_return),
+            has_var_args: false,
+        }
+    }
+
+    /// Replace `CallOther` instructions with a known meaning according to the given policies,
+    /// so that the analyses continue after them instead of treating them as dead ends.
+    ///
+    /// Instructions with the [`CallOtherPolicy::NoOp`] policy are replaced by jumps to the next instruction.
+    /// Syscall instructions are replaced by calls to synthetic extern symbols.
+    /// The syscall number is determined from the assignments to the syscall number register in the same block.
+    ///
+    /// Must be run after the normalization of the project,
+    /// since the syscall number is only directly given as a constant after the input expression propagation.
+    #[must_use]
+    pub fn resolve_call_other_ops(&mut self, config: &CallOtherConfig) -> Vec<LogMessage> {
+        let mut logs = Vec::new();
+        let convention = config
+            .syscall_conventions
+            .get(&self.architecture.to_string());
+        let number_register =
+            convention.map(|convention| self.get_register_by_name(&convention.number_register));
+        let mut syscall_symbols: BTreeMap<Tid, ExternSymbol> = BTreeMap::new();
+        let mut resolved_jmps: Vec<(Tid, Jmp)> = Vec::new();
+        for sub in self.program.term.subs.values() {
+            for block in sub.term.blocks.iter() {
+                for jmp in block.term.jmps.iter() {
+                    let (description, return_) = match &jmp.term {
+                        Jmp::CallOther {
+                            description,
+                            return_,
+                        } => (description, return_),
+                        _ => continue,
+                    };
+                    match (config.policies.get(description), convention) {
+                        (Some(CallOtherPolicy::NoOp), _) => {
+                            if let Some(return_) = return_ {
+                                resolved_jmps.push((jmp.tid.clone(), Jmp::Branch(return_.clone())));
+                            }
+                        }
+                        (Some(CallOtherPolicy::Syscall), Some(convention)) => {
+                            let syscall_number = get_constant_value_at_block_end(
+                                block,
+                                number_register.as_ref().unwrap(),
+                            );
+                            let known_syscall = syscall_number.and_then(|number| {
+                                convention
+                                    .syscalls
+                                    .iter()
+                                    .find(|(_, spec)| spec.number == number)
+                            });
+                            let symbol = match known_syscall {
+                                Some((name, spec)) => {
+                                    self.generate_syscall_symbol(name, Some(spec), convention)
+                                }
+                                None => {
+                                    logs.push(
+                                        LogMessage::new_debug(format!(
+                                            "Unknown syscall number {}, the syscall is treated as a call to an unknown function",
+                                            syscall_number.map_or("(not constant)".to_string(), |number| number.to_string())
+                                        ))
+                                        .location(jmp.tid.clone())
+                                        .source("CallOther Resolution"),
+                                    );
+                                    self.generate_syscall_symbol(
+                                        GENERIC_SYSCALL_NAME,
+                                        None,
+                                        convention,
+                                    )
+                                }
+                            };
+                            resolved_jmps.push((
+                                jmp.tid.clone(),
+                                Jmp::Call {
+                                    target: symbol.tid.clone(),
+                                    return_: return_.clone(),
+                                },
+                            ));
+                            syscall_symbols.insert(symbol.tid.clone(), symbol);
+                        }
+                        (Some(CallOtherPolicy::Syscall), None) => logs.push(
+                            LogMessage::new_info(format!(
+                                "No syscall convention known for {}, the syscall is treated as a dead end",
+                                self.architecture
+                            ))
+                            .location(jmp.tid.clone())
+                            .source("CallOther Resolution"),
+                        ),
+                        (None, _) => (),
+                    }
+                }
+            }
+        }
+        if !syscall_symbols.is_empty() {
+            self.add_syscall_calling_convention(convention.unwrap());
+        }
+        self.program
+            .term
+            .extern_symbols
+            .append(&mut syscall_symbols);
+        let resolved_jmps: HashMap<Tid, Jmp> = resolved_jmps.into_iter().collect();
+        for sub in self.program.term.subs.values_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                for jmp in block.term.jmps.iter_mut() {
+                    if let Some(resolved_jmp) = resolved_jmps.get(&jmp.tid) {
+                        jmp.term = resolved_jmp.clone();
+                    }
+                }
+            }
+        }
+        logs
+    }
+}
+
+/// Get the value of the given register at the end of the block
+/// if it is a constant computed by the `Def` terms of the block.
+fn get_constant_value_at_block_end(block: &Term<Blk>, register: &Variable) -> Option<u64> {
+    let mut known_values: HashMap<Variable, Bitvector> = HashMap::new();
+    for def in block.term.defs.iter() {
+        match &def.term {
+            Def::Assign { var, value } => match evaluate(value, &known_values) {
+                Some(result) => known_values.insert(var.clone(), result),
+                None => known_values.remove(var),
+            },
+            Def::Load { var, .. } => known_values.remove(var),
+            Def::Store { .. } => None,
+        };
+    }
+    known_values.get(register)?.try_to_u64().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_config() -> CallOtherConfig {
+        serde_json::from_value(serde_json::json!({
+            "policies": { "LOCK": "no_op", "syscall": "syscall" },
+            "syscall_conventions": {
+                "x86_64": {
+                    "number_register": "RAX",
+                    "parameter_registers": ["RDI", "RSI", "RDX", "R10", "R8", "R9"],
+                    "return_register": "RAX",
+                    "clobbered_registers": ["RCX", "R11"],
+                    "syscalls": {
+                        "read": { "number": 0, "parameters": 3 },
+                        "exit": { "number": 60, "parameters": 1, "no_return": true }
+                    }
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    /// Create a block that sets the syscall number register to the given value (if any)
+    /// and ends with a `CallOther` instruction with the given description.
+    fn call_other_block(name: &str, description: &str, syscall_number: Option<i64>) -> Term<Blk> {
+        let mut block = Blk::mock_with_tid(name);
+        if let Some(number) = syscall_number {
+            block.term.defs.push(Def::assign(
+                &format!("{}_set_number", name),
+                Variable::mock("RAX", 8),
+                Expression::const_from_i64(number),
+            ));
+        }
+        block.term.jmps.push(Term {
+            tid: Tid::new(format!("{}_call_other", name)),
+            term: Jmp::CallOther {
+                description: description.to_string(),
+                return_: Some(Tid::new(format!("{}_return", name))),
+            },
+        });
+        block
+    }
+
+    fn get_jmp<'a>(project: &'a Project, block_name: &str) -> &'a Jmp {
+        &project.program.term.subs[&Tid::new("main")]
+            .term
+            .blocks
+            .iter()
+            .find(|block| block.tid == Tid::new(block_name))
+            .unwrap()
+            .term
+            .jmps[0]
+            .term
+    }
+
+    #[test]
+    fn resolve_call_other_ops() {
+        let mut project = Project::mock_empty();
+        let main = Sub::mock_with_blocks(
+            "main",
+            vec![
+                call_other_block("lock", "LOCK", None),
+                call_other_block("read", "syscall", Some(0)),
+                call_other_block("exit", "syscall", Some(60)),
+                call_other_block("unknown_number", "syscall", Some(1000)),
+                call_other_block("unknown_op", "halt", None),
+            ],
+        );
+        project.program.term.subs.insert(main.tid.clone(), main);
+
+        let logs = project.resolve_call_other_ops(&mock_config());
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            get_jmp(&project, "lock"),
+            &Jmp::Branch(Tid::new("lock_return"))
+        );
+        assert_eq!(
+            get_jmp(&project, "read"),
+            &Jmp::Call {
+                target: Tid::new("syscall_read"),
+                return_: Some(Tid::new("read_return"))
+            }
+        );
+        assert!(matches!(
+            get_jmp(&project, "unknown_number"),
+            Jmp::Call { target, .. } if *target == Tid::new("syscall_syscall")
+        ));
+        assert!(matches!(
+            get_jmp(&project, "unknown_op"),
+            Jmp::CallOther { .. }
+        ));
+
+        let extern_symbols = &project.program.term.extern_symbols;
+        let read = &extern_symbols[&Tid::new("syscall_read")];
+        assert_eq!(read.name, "read");
+        assert_eq!(
+            read.parameters,
+            vec![
+                Arg::mock_register("RDI", 8),
+                Arg::mock_register("RSI", 8),
+                Arg::mock_register("RDX", 8)
+            ]
+        );
+        assert!(extern_symbols[&Tid::new("syscall_exit")].no_return);
+        assert_eq!(
+            extern_symbols[&Tid::new("syscall_syscall")]
+                .parameters
+                .len(),
+            6
+        );
+        // The kernel preserves the parameter registers but not the clobbered registers.
+        let cconv = &project.calling_conventions[SYSCALL_CALLING_CONVENTION];
+        assert!(cconv
+            .callee_saved_register
+            .contains(&Variable::mock("RDI", 8)));
+        assert!(!cconv
+            .callee_saved_register
+            .contains(&Variable::mock("RAX", 8)));
+        assert!(!cconv
+            .callee_saved_register
+            .contains(&Variable::mock("RCX", 8)));
+    }
+}
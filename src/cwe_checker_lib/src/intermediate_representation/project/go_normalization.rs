@@ -0,0 +1,401 @@
+use super::*;
+
+/// The name of the calling convention used for calls between functions compiled by the Go compiler.
+pub const GO_CALLING_CONVENTION: &str = "__golang";
+
+/// Name prefix of the Go runtime functions that grow the stack of a goroutine,
+/// e.g. `runtime.morestack` or `runtime.morestack_noctxt`.
+const GO_MORESTACK_PREFIX: &str = "runtime.morestack";
+
+/// Return the register with the given name (ignoring case) from the register set.
+fn find_register(register_set: &BTreeSet<Variable>, name: &str) -> Option<Variable> {
+    register_set
+        .iter()
+        .find(|reg| reg.name.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Return an expression for the lowest 8 bytes of the first register found in the register set
+/// whose name matches one of the given candidate names.
+///
+/// Float registers are usually sub-registers of larger vector registers,
+/// so we have to search for the base register containing them.
+fn find_float_register(
+    register_set: &BTreeSet<Variable>,
+    candidates: &[String],
+) -> Option<Expression> {
+    let register = candidates
+        .iter()
+        .find_map(|name| find_register(register_set, name))?;
+    if register.size > ByteSize::new(8) {
+        Some(Expression::Subpiece {
+            low_byte: ByteSize::new(0),
+            size: ByteSize::new(8),
+            arg: Box::new(Expression::Var(register)),
+        })
+    } else {
+        Some(Expression::Var(register))
+    }
+}
+
+impl CallingConvention {
+    /// Generate the register-based calling convention (`ABIInternal`)
+    /// that the Go compiler uses for calls between Go functions since Go 1.17.
+    ///
+    /// Parameters and return values are passed in the same sequence of registers.
+    /// Besides the stack pointer only the frame pointer and the register holding
+    /// the pointer to the current goroutine are preserved across calls.
+    ///
+    /// Returns `None` if the CPU architecture is not supported
+    /// or if some of the needed integer registers are not contained in the register set.
+    pub fn new_go_abi_internal(
+        architecture: &Arch,
+        register_set: &BTreeSet<Variable>,
+    ) -> Option<CallingConvention> {
+        let (integer_register_names, float_register_candidates, callee_saved_names): (
+            Vec<String>,
+            Vec<Vec<String>>,
+            Vec<&str>,
+        ) = match architecture {
+            Arch::X86_64 => (
+                ["RAX", "RBX", "RCX", "RDI", "RSI", "R8", "R9", "R10", "R11"]
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+                (0..15)
+                    .map(|i| {
+                        vec![
+                            format!("ZMM{}", i),
+                            format!("YMM{}", i),
+                            format!("XMM{}", i),
+                        ]
+                    })
+                    .collect(),
+                vec!["RSP", "RBP", "R14"],
+            ),
+            Arch::Aarch64 => (
+                (0..16).map(|i| format!("x{}", i)).collect(),
+                (0..16)
+                    .map(|i| vec![format!("z{}", i), format!("q{}", i), format!("d{}", i)])
+                    .collect(),
+                vec!["sp", "x28", "x29"],
+            ),
+            _ => return None,
+        };
+        let integer_register: Vec<Variable> = integer_register_names
+            .iter()
+            .map(|name| find_register(register_set, name))
+            .collect::<Option<_>>()?;
+        let callee_saved_register: Vec<Variable> = callee_saved_names
+            .iter()
+            .map(|name| find_register(register_set, name))
+            .collect::<Option<_>>()?;
+        let float_register: Vec<Expression> = float_register_candidates
+            .iter()
+            .filter_map(|candidates| find_float_register(register_set, candidates))
+            .collect();
+        Some(CallingConvention {
+            name: GO_CALLING_CONVENTION.to_string(),
+            integer_parameter_register: integer_register.clone(),
+            float_parameter_register: float_register.clone(),
+            integer_return_register: integer_register,
+            float_return_register: float_register,
+            callee_saved_register,
+            varargs_layout: None,
+        })
+    }
+}
+
+impl Project {
+    /// Add the Go-internal calling convention to the known calling conventions of the project
+    /// (if it is not already known) and annotate all functions without a known calling convention with it.
+    ///
+    /// Extern symbols keep their calling conventions,
+    /// since they are usually C functions called through cgo.
+    fn add_go_calling_convention(&mut self) -> Result<(), LogMessage> {
+        if !self.calling_conventions.contains_key(GO_CALLING_CONVENTION) {
+            let cconv =
+                CallingConvention::new_go_abi_internal(&self.architecture, &self.register_set)
+                    .ok_or_else(|| {
+                        LogMessage::new_info(format!(
+                            "Go calling convention not supported for CPU architecture {}",
+                            self.cpu_architecture
+                        ))
+                    })?;
+            self.calling_conventions
+                .insert(GO_CALLING_CONVENTION.to_string(), cconv);
+        }
+        for sub in self.program.term.subs.values_mut() {
+            if sub.term.calling_convention.is_none()
+                && !self.program.term.extern_symbols.contains_key(&sub.tid)
+            {
+                sub.term.calling_convention = Some(GO_CALLING_CONVENTION.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the stack growth checks that the Go compiler inserts at the start of most functions.
+    /// Return the number of removed checks.
+    ///
+    /// The checks compare the stack pointer with the stack bound of the current goroutine
+    /// and call `runtime.morestack*` if the stack needs to grow before jumping back to the function start.
+    /// Since growing the stack does not change the contents of the stack frame from the point of view of the function,
+    /// the conditional jumps to the `morestack` calls are removed
+    /// so that the comparison with the (unknown) stack bound cannot pollute the tracked stack pointer value.
+    /// All remaining calls to `runtime.morestack*` are replaced by jumps to their return targets,
+    /// i.e. they are handled as no-ops.
+    fn remove_go_stack_growth_checks(&mut self) -> usize {
+        let morestack_tids: HashSet<Tid> = self
+            .program
+            .term
+            .subs
+            .values()
+            .filter(|sub| sub.term.name.starts_with(GO_MORESTACK_PREFIX))
+            .map(|sub| sub.tid.clone())
+            .chain(
+                self.program
+                    .term
+                    .extern_symbols
+                    .values()
+                    .filter(|symbol| symbol.name.starts_with(GO_MORESTACK_PREFIX))
+                    .map(|symbol| symbol.tid.clone()),
+            )
+            .collect();
+        if morestack_tids.is_empty() {
+            return 0;
+        }
+        let is_morestack_call = |jmp: &Term<Jmp>| match &jmp.term {
+            Jmp::Call { target, .. } => morestack_tids.contains(target),
+            _ => false,
+        };
+        let morestack_blocks: HashSet<Tid> = self
+            .program
+            .term
+            .subs
+            .values()
+            .flat_map(|sub| sub.term.blocks.iter())
+            .filter(|block| block.term.jmps.iter().any(is_morestack_call))
+            .map(|block| block.tid.clone())
+            .collect();
+        let mut removed_checks = 0;
+        for sub in self.program.term.subs.values_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                if let [Term {
+                    tid: cbranch_tid,
+                    term: Jmp::CBranch { target, .. },
+                }, fallthrough @ Term {
+                    term: Jmp::Branch(fallthrough_target),
+                    ..
+                }] = &block.term.jmps[..]
+                {
+                    if morestack_blocks.contains(target) {
+                        block.term.jmps = vec![fallthrough.clone()];
+                        removed_checks += 1;
+                    } else if morestack_blocks.contains(fallthrough_target) {
+                        block.term.jmps = vec![Term {
+                            tid: cbranch_tid.clone(),
+                            term: Jmp::Branch(target.clone()),
+                        }];
+                        removed_checks += 1;
+                    }
+                }
+                for jmp in block.term.jmps.iter_mut() {
+                    if let Jmp::Call {
+                        target,
+                        return_: Some(return_tid),
+                    } = &jmp.term
+                    {
+                        if morestack_tids.contains(target) {
+                            jmp.term = Jmp::Branch(return_tid.clone());
+                        }
+                    }
+                }
+            }
+        }
+        removed_checks
+    }
+
+    /// Run normalization passes specific to binaries generated by the Go compiler.
+    ///
+    /// Passes:
+    /// - Add the register-based calling convention of the Go compiler to the project
+    ///   and use it for all functions that have no annotated calling convention.
+    /// - Remove the stack growth checks at the start of functions
+    ///   and handle calls to `runtime.morestack*` as no-ops.
+    ///
+    /// This should only be called for projects where [`is_go_binary`](crate::utils::binary::is_go_binary)
+    /// returned `true` for the corresponding binary.
+    #[must_use]
+    pub fn normalize_go_binary(&mut self) -> Vec<LogMessage> {
+        let mut logs = Vec::new();
+        if let Err(log) = self.add_go_calling_convention() {
+            logs.push(log);
+        }
+        let removed_checks = self.remove_go_stack_growth_checks();
+        if removed_checks > 0 {
+            logs.push(LogMessage::new_info(format!(
+                "Removed {} stack growth checks of the Go runtime",
+                removed_checks
+            )));
+        }
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter::FromIterator;
+
+    fn create_block(block_name: &str, jmps: Vec<Jmp>) -> Term<Blk> {
+        Term {
+            tid: Tid::new(block_name),
+            term: Blk {
+                defs: Vec::new(),
+                jmps: jmps
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, jmp)| Term {
+                        tid: Tid::new(format!("jmp_{}_{}", block_name, index)),
+                        term: jmp,
+                    })
+                    .collect(),
+                indirect_jmp_targets: Vec::new(),
+            },
+        }
+    }
+
+    /// Create a project with a function containing a mock Go-style stack growth check.
+    fn mock_go_project() -> Project {
+        let function = Term {
+            tid: Tid::new("main.foo"),
+            term: Sub {
+                name: "main.foo".to_string(),
+                blocks: vec![
+                    create_block(
+                        "entry",
+                        vec![
+                            Jmp::CBranch {
+                                target: Tid::new("morestack_blk"),
+                                condition: Expression::Var(Variable::mock("ZF", 1u64)),
+                            },
+                            Jmp::Branch(Tid::new("body")),
+                        ],
+                    ),
+                    create_block(
+                        "body",
+                        vec![Jmp::Return(Expression::Var(Variable::mock("RAX", 8u64)))],
+                    ),
+                    create_block(
+                        "morestack_blk",
+                        vec![Jmp::Call {
+                            target: Tid::new("runtime.morestack_noctxt"),
+                            return_: Some(Tid::new("after_morestack")),
+                        }],
+                    ),
+                    create_block("after_morestack", vec![Jmp::Branch(Tid::new("entry"))]),
+                ],
+                calling_convention: None,
+                is_thunk: false,
+            },
+        };
+        let mut morestack = Sub::mock("runtime.morestack_noctxt");
+        morestack.term.calling_convention = Some("__stdcall".to_string());
+        let mut project = Project::mock_empty();
+        project.program.term.subs = BTreeMap::from_iter([
+            (function.tid.clone(), function),
+            (morestack.tid.clone(), morestack),
+        ]);
+        for name in ["R8", "R9", "R10", "R11", "R14"] {
+            project.register_set.insert(Variable::mock(name, 8u64));
+        }
+        project
+    }
+
+    #[test]
+    fn go_calling_convention() {
+        let project = mock_go_project();
+        let cconv =
+            CallingConvention::new_go_abi_internal(&Arch::X86_64, &project.register_set).unwrap();
+        assert_eq!(cconv.integer_parameter_register.len(), 9);
+        assert_eq!(
+            cconv.integer_parameter_register[1],
+            Variable::mock("RBX", 8u64)
+        );
+        assert_eq!(
+            cconv.integer_return_register,
+            cconv.integer_parameter_register
+        );
+        assert!(cconv
+            .callee_saved_register
+            .contains(&Variable::mock("R14", 8u64)));
+        assert!(cconv.float_parameter_register.is_empty());
+        // Registers are missing in the register set.
+        assert!(CallingConvention::new_go_abi_internal(
+            &Arch::X86_64,
+            &Project::mock_empty().register_set
+        )
+        .is_none());
+        assert!(
+            CallingConvention::new_go_abi_internal(&Arch::Mips32, &project.register_set).is_none()
+        );
+    }
+
+    #[test]
+    fn stack_growth_check_removal() {
+        let mut project = mock_go_project();
+        let logs = project.normalize_go_binary();
+        assert_eq!(logs.len(), 1);
+
+        let function = &project.program.term.subs[&Tid::new("main.foo")];
+        assert_eq!(
+            function.term.calling_convention.as_deref(),
+            Some(GO_CALLING_CONVENTION)
+        );
+        assert!(project
+            .calling_conventions
+            .contains_key(GO_CALLING_CONVENTION));
+        // The annotated calling convention is not overwritten.
+        assert_eq!(
+            project.program.term.subs[&Tid::new("runtime.morestack_noctxt")]
+                .term
+                .calling_convention
+                .as_deref(),
+            Some("__stdcall")
+        );
+        // The stack bound check is removed.
+        let entry_jmps = &function.term.blocks[0].term.jmps;
+        assert_eq!(entry_jmps.len(), 1);
+        assert_eq!(entry_jmps[0].term, Jmp::Branch(Tid::new("body")));
+        // The call to morestack is a no-op.
+        assert_eq!(
+            function.term.blocks[2].term.jmps[0].term,
+            Jmp::Branch(Tid::new("after_morestack"))
+        );
+    }
+
+    #[test]
+    fn inverted_stack_growth_check_removal() {
+        let mut project = mock_go_project();
+        let function = project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::new("main.foo"))
+            .unwrap();
+        function.term.blocks[0].term.jmps[0].term = Jmp::CBranch {
+            target: Tid::new("body"),
+            condition: Expression::Var(Variable::mock("ZF", 1u64)),
+        };
+        function.term.blocks[0].term.jmps[1].term = Jmp::Branch(Tid::new("morestack_blk"));
+
+        assert_eq!(project.remove_go_stack_growth_checks(), 1);
+        let entry_jmps = &project.program.term.subs[&Tid::new("main.foo")].term.blocks[0]
+            .term
+            .jmps;
+        assert_eq!(entry_jmps.len(), 1);
+        assert_eq!(entry_jmps[0].tid, Tid::new("jmp_entry_0"));
+        assert_eq!(entry_jmps[0].term, Jmp::Branch(Tid::new("body")));
+    }
+}
@@ -0,0 +1,162 @@
+use super::*;
+
+impl Project {
+    /// Add a synthetic jump to all blocks that do not end with a jump.
+    /// Return a log message for each added jump.
+    ///
+    /// Ghidra may export blocks without jumps, e.g. because of gaps in its analysis or data in code.
+    /// Without jumps the blocks would be dead ends in the control flow graph
+    /// and calls to the containing functions would never return.
+    /// We add
+    /// - a branch to the next block by address if the next block belongs to the same function,
+    /// - a return instruction otherwise.
+    #[must_use]
+    pub(super) fn add_missing_block_terminators(&mut self) -> Vec<LogMessage> {
+        let address_bytesize = self.get_pointer_bytesize();
+        let mut log_messages = Vec::new();
+        for sub in self.program.term.subs.values_mut() {
+            for index in 0..sub.term.blocks.len() {
+                if !sub.term.blocks[index].term.jmps.is_empty() {
+                    continue;
+                }
+                let next_block_tid = get_next_block_by_address(&sub.term.blocks, index);
+                let block = &mut sub.term.blocks[index];
+                let (jmp, log_text) = match next_block_tid {
+                    Some(next_block_tid) => {
+                        let log_text = format!(
+                            "Block at {} has no terminating jump. Added a branch to the next block at {}.",
+                            block.tid.address, next_block_tid.address
+                        );
+                        (Jmp::Branch(next_block_tid), log_text)
+                    }
+                    None => {
+                        let log_text = format!(
+                            "Block at {} has no terminating jump. Added a return instruction.",
+                            block.tid.address
+                        );
+                        let return_target = Expression::Unknown {
+                            description: "Synthetic return".to_string(),
+                            size: address_bytesize,
+                        };
+                        (Jmp::Return(return_target), log_text)
+                    }
+                };
+                block.term.jmps.push(Term {
+                    tid: block.tid.clone().with_id_suffix("_synthetic_jmp"),
+                    term: jmp,
+                });
+                log_messages.push(LogMessage::new_info(log_text).location(block.tid.clone()));
+            }
+        }
+        log_messages
+    }
+}
+
+/// Get the TID of the block with the smallest address greater than the address of the block at the given index.
+/// Returns `None` if no such block exists or if the address of the block at the given index is unknown.
+fn get_next_block_by_address(blocks: &[Term<Blk>], index: usize) -> Option<Tid> {
+    let block_address = parse_address(&blocks[index].tid.address)?;
+    blocks
+        .iter()
+        .filter_map(|block| {
+            parse_address(&block.tid.address)
+                .filter(|address| *address > block_address)
+                .map(|address| (address, block))
+        })
+        .min_by_key(|(address, _)| *address)
+        .map(|(_, block)| block.tid.clone())
+}
+
+/// Parse a hexadecimal address string.
+fn parse_address(address: &str) -> Option<u64> {
+    u64::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::{get_program_cfg, Edge};
+
+    /// Create a block at the given address containing one assignment and the given jumps.
+    fn mock_block(address: &str, jmps: Vec<Term<Jmp>>) -> Term<Blk> {
+        let mut block = Blk::mock();
+        block.tid = Tid::blk_id_at_address(address);
+        block.term.defs.push(Def::assign(
+            &format!("def_{}", address),
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0),
+        ));
+        block.term.jmps = jmps;
+        block
+    }
+
+    #[test]
+    fn jumpless_blocks() {
+        let sub = Sub::mock_with_blocks(
+            "func",
+            vec![
+                mock_block("00001000", Vec::new()),
+                mock_block("00001010", Vec::new()),
+                mock_block(
+                    "00001008",
+                    vec![Term {
+                        tid: Tid::new("jmp"),
+                        term: Jmp::Branch(Tid::blk_id_at_address("00001010")),
+                    }],
+                ),
+            ],
+        );
+        let mut project = Project::mock_empty();
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+
+        let logs = project.add_missing_block_terminators();
+        assert_eq!(logs.len(), 2);
+        let blocks = &project.program.term.subs[&Tid::new("func")].term.blocks;
+        // The middle block branches to the next block by address.
+        assert_eq!(blocks[0].term.jmps.len(), 1);
+        assert_eq!(
+            blocks[0].term.jmps[0].term,
+            Jmp::Branch(Tid::blk_id_at_address("00001008"))
+        );
+        // The final block returns.
+        assert_eq!(blocks[1].term.jmps.len(), 1);
+        assert!(matches!(blocks[1].term.jmps[0].term, Jmp::Return(_)));
+        // Blocks with jumps are not changed.
+        assert_eq!(blocks[2].term.jmps.len(), 1);
+    }
+
+    #[test]
+    fn calls_to_jumpless_functions_return() {
+        let callee = Sub::mock_with_blocks("callee", vec![mock_block("00002000", Vec::new())]);
+        let caller = Sub::mock_with_blocks(
+            "caller",
+            vec![
+                mock_block(
+                    "00001000",
+                    vec![Term {
+                        tid: Tid::new("call"),
+                        term: Jmp::Call {
+                            target: Tid::new("callee"),
+                            return_: Some(Tid::blk_id_at_address("00001008")),
+                        },
+                    }],
+                ),
+                mock_block("00001008", Vec::new()),
+            ],
+        );
+        let mut project = Project::mock_empty();
+        project.program.term.subs.insert(callee.tid.clone(), callee);
+        project.program.term.subs.insert(caller.tid.clone(), caller);
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        assert!(!graph
+            .edge_weights()
+            .any(|edge| matches!(edge, Edge::ReturnCombine(_))));
+
+        let logs = project.add_missing_block_terminators();
+        assert_eq!(logs.len(), 2);
+        let graph = get_program_cfg(&project.program, HashSet::new());
+        assert!(graph
+            .edge_weights()
+            .any(|edge| matches!(edge, Edge::ReturnCombine(_))));
+    }
+}
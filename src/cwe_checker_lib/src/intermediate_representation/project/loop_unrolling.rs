@@ -0,0 +1,495 @@
+use super::*;
+
+/// The configuration of the loop unrolling normalization pass.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct LoopUnrollingConfig {
+    /// Loops are only unrolled if their trip count is at most this number.
+    /// A value of zero disables loop unrolling.
+    pub max_iterations: u64,
+}
+
+/// A loop consisting of a single block that jumps back to itself
+/// and whose trip count could be determined.
+struct UnrollableLoop {
+    /// The index of the loop block in the block list of its function.
+    block_index: usize,
+    /// The number of times the loop block is executed.
+    trip_count: u64,
+    /// The target of the loop exit.
+    exit_target: Tid,
+}
+
+impl Project {
+    /// Unroll loops with a small constant trip count,
+    /// so that the fixpoint computation does not lose precision by merging the states of different iterations.
+    ///
+    /// Only loops consisting of a single block are unrolled, if
+    /// - the block is only entered from one other block (and from itself),
+    /// - it contains exactly one counter register that is incremented or decremented by a constant,
+    /// - the counter is set to a constant in the block entering the loop
+    /// - and whether the loop is left only depends on the counter.
+    ///
+    /// Loops containing calls or other exits than the loop condition are never unrolled,
+    /// since they are not contained in a single block.
+    ///
+    /// The copies of the loop block for the second and all following iterations
+    /// get the iteration number as suffix to their TIDs and their addresses.
+    /// Since the suffixes are removed by [`Tid::canonical_address`],
+    /// warnings in unrolled loop bodies are reported at the addresses of the original instructions.
+    ///
+    /// Must be run after the normalization of the project,
+    /// since the loop conditions are only directly given in terms of the loop counter after the input expression propagation.
+    #[must_use]
+    pub fn unroll_small_loops(&mut self, config: &LoopUnrollingConfig) -> Vec<LogMessage> {
+        let mut logs = Vec::new();
+        if config.max_iterations == 0 {
+            return logs;
+        }
+        let jump_target_counts = self.count_jump_target_references();
+        for sub in self.program.term.subs.values_mut() {
+            let unrollable_loops: Vec<UnrollableLoop> = (1..sub.term.blocks.len())
+                .filter_map(|block_index| {
+                    get_unrollable_loop(
+                        &sub.term.blocks,
+                        block_index,
+                        &jump_target_counts,
+                        config.max_iterations,
+                    )
+                })
+                .collect();
+            for unrollable_loop in unrollable_loops.into_iter().rev() {
+                let loop_block = &sub.term.blocks[unrollable_loop.block_index];
+                logs.push(
+                    LogMessage::new_info(format!(
+                        "Unrolled loop at {} with {} iterations.",
+                        loop_block.tid.address, unrollable_loop.trip_count
+                    ))
+                    .location(loop_block.tid.clone()),
+                );
+                let unrolled_blocks = unroll_loop_block(loop_block, &unrollable_loop);
+                sub.term.blocks.splice(
+                    unrollable_loop.block_index..=unrollable_loop.block_index,
+                    unrolled_blocks,
+                );
+            }
+        }
+        logs
+    }
+
+    /// Count for each block TID how often it is referenced as a target of a jump or as a return target of a call.
+    /// Blocks that are also targets of indirect jumps are counted once more for each indirect jump target list containing them.
+    fn count_jump_target_references(&self) -> HashMap<Tid, usize> {
+        let mut counts = HashMap::new();
+        for sub in self.program.term.subs.values() {
+            for block in sub.term.blocks.iter() {
+                for jmp in block.term.jmps.iter() {
+                    let target = match &jmp.term {
+                        Jmp::Branch(target) | Jmp::CBranch { target, .. } => Some(target),
+                        Jmp::Call { return_, .. }
+                        | Jmp::CallInd { return_, .. }
+                        | Jmp::CallOther { return_, .. } => return_.as_ref(),
+                        Jmp::BranchInd(_) | Jmp::Return(_) => None,
+                    };
+                    if let Some(target) = target {
+                        *counts.entry(target.clone()).or_insert(0) += 1;
+                    }
+                }
+                for target in block.term.indirect_jmp_targets.iter() {
+                    *counts.entry(target.clone()).or_insert(0) += 1;
+                }
+            }
+        }
+        counts
+    }
+}
+
+/// Check whether the block at the given index is a single-block loop with a small constant trip count.
+fn get_unrollable_loop(
+    blocks: &[Term<Blk>],
+    block_index: usize,
+    jump_target_counts: &HashMap<Tid, usize>,
+    max_iterations: u64,
+) -> Option<UnrollableLoop> {
+    let block = &blocks[block_index];
+    let (condition, loop_on_true, exit_target) = match &block.term.jmps[..] {
+        [Term {
+            term: Jmp::CBranch { target, condition },
+            ..
+        }, Term {
+            term: Jmp::Branch(fallthrough),
+            ..
+        }] => {
+            if *target == block.tid && *fallthrough != block.tid {
+                (condition, true, fallthrough.clone())
+            } else if *fallthrough == block.tid && *target != block.tid {
+                (condition, false, target.clone())
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    };
+    // The block has to be entered exactly once from outside (and once from the back edge).
+    if jump_target_counts.get(&block.tid) != Some(&2) {
+        return None;
+    }
+    let mut entering_blocks = blocks.iter().filter(|other_block| {
+        other_block.tid != block.tid
+            && other_block.term.jmps.iter().any(|jmp| match &jmp.term {
+                Jmp::Branch(target) | Jmp::CBranch { target, .. } => *target == block.tid,
+                _ => false,
+            })
+    });
+    let entering_block = entering_blocks.next()?;
+    if entering_blocks.next().is_some() {
+        return None;
+    }
+    for counter in get_counter_candidates(block) {
+        let initial_value = match get_last_assigned_value(entering_block, counter) {
+            Some(Expression::Const(value)) => value.clone(),
+            _ => continue,
+        };
+        if let Some(trip_count) = compute_trip_count(
+            block,
+            counter,
+            initial_value,
+            condition,
+            loop_on_true,
+            max_iterations,
+        ) {
+            return Some(UnrollableLoop {
+                block_index,
+                trip_count,
+                exit_target,
+            });
+        }
+    }
+    None
+}
+
+/// Return all registers that are assigned exactly once in the block
+/// and where the assignment adds or subtracts a constant from the register itself.
+/// Registers that are also targets of load instructions in the block are not returned.
+fn get_counter_candidates(block: &Term<Blk>) -> Vec<&Variable> {
+    let mut candidates = Vec::new();
+    for def in block.term.defs.iter() {
+        if let Def::Assign {
+            var,
+            value:
+                Expression::BinOp {
+                    op: BinOpType::IntAdd | BinOpType::IntSub,
+                    lhs,
+                    rhs,
+                },
+        } = &def.term
+        {
+            if matches!((lhs.as_ref(), rhs.as_ref()), (Expression::Var(input), Expression::Const(_)) if input == var)
+            {
+                candidates.push(var);
+            }
+        }
+    }
+    candidates.retain(|candidate| {
+        block
+            .term
+            .defs
+            .iter()
+            .filter(|def| match &def.term {
+                Def::Assign { var, .. } | Def::Load { var, .. } => var == *candidate,
+                Def::Store { .. } => false,
+            })
+            .count()
+            == 1
+    });
+    candidates
+}
+
+/// Return the value of the last assignment to the given register in the given block.
+/// Returns `None` if the register is not assigned or the last assignment is a load.
+fn get_last_assigned_value<'a>(
+    block: &'a Term<Blk>,
+    register: &Variable,
+) -> Option<&'a Expression> {
+    block
+        .term
+        .defs
+        .iter()
+        .rev()
+        .find_map(|def| match &def.term {
+            Def::Assign { var, value } if var == register => Some(Some(value)),
+            Def::Load { var, .. } if var == register => Some(None),
+            _ => None,
+        })?
+}
+
+/// Compute how often the loop block is executed if the counter has the given initial value on entering the loop.
+///
+/// Returns `None` if the loop condition does not only depend on the counter
+/// or if the trip count exceeds the given maximum number of iterations.
+fn compute_trip_count(
+    block: &Term<Blk>,
+    counter: &Variable,
+    initial_value: Bitvector,
+    condition: &Expression,
+    loop_on_true: bool,
+    max_iterations: u64,
+) -> Option<u64> {
+    let mut counter_value = initial_value;
+    for trip_count in 1..=max_iterations {
+        let mut known_values = HashMap::from([(counter.clone(), counter_value.clone())]);
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Assign { var, value } => match evaluate(value, &known_values) {
+                    Some(result) => known_values.insert(var.clone(), result),
+                    None => known_values.remove(var),
+                },
+                Def::Load { var, .. } => known_values.remove(var),
+                Def::Store { .. } => None,
+            };
+        }
+        let condition_result = evaluate(condition, &known_values)?;
+        if condition_result.is_zero() == loop_on_true {
+            return Some(trip_count);
+        }
+        counter_value = known_values.get(counter)?.clone();
+    }
+    None
+}
+
+/// Evaluate the given expression if all of its input registers have known values.
+pub(super) fn evaluate(
+    expression: &Expression,
+    known_values: &HashMap<Variable, Bitvector>,
+) -> Option<Bitvector> {
+    match expression {
+        Expression::Var(var) => known_values.get(var).cloned(),
+        Expression::Const(value) => Some(value.clone()),
+        Expression::BinOp { op, lhs, rhs } => evaluate(lhs, known_values)?
+            .bin_op(*op, &evaluate(rhs, known_values)?)
+            .ok(),
+        Expression::UnOp { op, arg } => evaluate(arg, known_values)?.un_op(*op).ok(),
+        Expression::Cast { op, size, arg } => evaluate(arg, known_values)?.cast(*op, *size).ok(),
+        Expression::Subpiece {
+            low_byte,
+            size,
+            arg,
+        } => Some(evaluate(arg, known_values)?.subpiece(*low_byte, *size)),
+        Expression::Unknown { .. } => None,
+    }
+}
+
+/// Generate one copy of the loop block for each iteration of the loop.
+///
+/// The first copy keeps the TIDs of the original block, so that jumps into the loop need not be changed.
+/// Each copy jumps unconditionally to the next copy and the last copy jumps to the loop exit.
+fn unroll_loop_block(block: &Term<Blk>, unrollable_loop: &UnrollableLoop) -> Vec<Term<Blk>> {
+    let copies: Vec<Term<Blk>> = (0..unrollable_loop.trip_count)
+        .map(|iteration| {
+            if iteration == 0 {
+                block.clone()
+            } else {
+                clone_block_for_iteration(block, iteration)
+            }
+        })
+        .collect();
+    let successors: Vec<Tid> = copies
+        .iter()
+        .skip(1)
+        .map(|copy| copy.tid.clone())
+        .chain(std::iter::once(unrollable_loop.exit_target.clone()))
+        .collect();
+    copies
+        .into_iter()
+        .zip(successors)
+        .map(|(mut copy, successor)| {
+            let jmp_tid = copy.term.jmps[0].tid.clone();
+            copy.term.jmps = vec![Term {
+                tid: jmp_tid,
+                term: Jmp::Branch(successor),
+            }];
+            copy
+        })
+        .collect()
+}
+
+/// Clone the block with the iteration number appended to the IDs and addresses of the block and all contained terms.
+fn clone_block_for_iteration(block: &Term<Blk>, iteration: u64) -> Term<Blk> {
+    let with_suffix = |tid: &Tid| {
+        let mut new_tid = tid
+            .clone()
+            .with_id_suffix(&format!("_unrolled_{}", iteration));
+        if new_tid.address != "UNKNOWN" {
+            new_tid.address = format!("{}_{}", tid.address, iteration);
+        }
+        new_tid
+    };
+    let mut cloned_block = block.clone();
+    cloned_block.tid = with_suffix(&block.tid);
+    for def in cloned_block.term.defs.iter_mut() {
+        def.tid = with_suffix(&def.tid);
+    }
+    for jmp in cloned_block.term.jmps.iter_mut() {
+        jmp.tid = with_suffix(&jmp.tid);
+    }
+    cloned_block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abstract_domain::TryToBitvec;
+    use crate::analysis::pointer_inference::PointerInference;
+    use crate::utils::binary::RuntimeMemoryImage;
+    use std::collections::HashSet;
+
+    /// Create a project with a loop that copies `trip_count` pointers from an array at `RSI` to a stack array.
+    fn mock_project(trip_count: i64) -> Project {
+        let mut project = Project::mock_empty();
+        let mut entry_block = Blk::mock_with_tid("entry");
+        entry_block.term.defs.push(Def::assign(
+            "init_counter",
+            Variable::mock("RCX", 8),
+            Expression::const_from_i64(0),
+        ));
+        entry_block.term.jmps.push(Jmp::branch("jmp_loop", "loop"));
+        let mut loop_block = Blk::mock_with_tid("loop");
+        let element_offset = Expression::BinOp {
+            op: BinOpType::IntMult,
+            lhs: Box::new(Expression::var("RCX", 8)),
+            rhs: Box::new(Expression::const_from_i64(8)),
+        };
+        let mut load = Def::load(
+            "load",
+            Variable::mock("RAX", 8),
+            Expression::var("RSI", 8).plus(element_offset.clone()),
+        );
+        load.tid.address = "00001010".to_string();
+        let mut store = Def::store(
+            "store",
+            Expression::var("RSP", 8)
+                .plus_const(-0x20)
+                .plus(element_offset),
+            Expression::var("RAX", 8),
+        );
+        store.tid.address = "00001014".to_string();
+        let increment = Def::assign(
+            "increment",
+            Variable::mock("RCX", 8),
+            Expression::var("RCX", 8).plus_const(1),
+        );
+        loop_block.term.defs = vec![load, store, increment];
+        loop_block.term.jmps.push(Term {
+            tid: Tid::new("loop_condition"),
+            term: Jmp::CBranch {
+                target: Tid::new("loop"),
+                condition: Expression::BinOp {
+                    op: BinOpType::IntLess,
+                    lhs: Box::new(Expression::var("RCX", 8)),
+                    rhs: Box::new(Expression::const_from_i64(trip_count)),
+                },
+            },
+        });
+        loop_block.term.jmps.push(Jmp::branch("exit_loop", "exit"));
+        let exit_block = Blk::mock_return("exit");
+        let sub = Sub::mock_with_blocks("func", vec![entry_block, loop_block, exit_block]);
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project.program.term.entry_points.insert(Tid::new("func"));
+        project
+    }
+
+    fn config() -> LoopUnrollingConfig {
+        LoopUnrollingConfig { max_iterations: 8 }
+    }
+
+    #[test]
+    fn unroll_copy_loop() {
+        let mut project = mock_project(4);
+        let logs = project.unroll_small_loops(&config());
+        assert_eq!(logs.len(), 1);
+        let blocks = &project.program.term.subs[&Tid::new("func")].term.blocks;
+        let block_ids: Vec<String> = blocks.iter().map(|block| block.tid.to_string()).collect();
+        assert_eq!(
+            block_ids,
+            vec![
+                "entry",
+                "loop",
+                "loop_unrolled_1",
+                "loop_unrolled_2",
+                "loop_unrolled_3",
+                "exit"
+            ]
+        );
+        for (block, next_block) in blocks[1..5].iter().zip(blocks[2..].iter()) {
+            assert_eq!(block.term.jmps.len(), 1);
+            assert_eq!(block.term.jmps[0].term, Jmp::Branch(next_block.tid.clone()));
+        }
+        let store = &blocks[3].term.defs[1];
+        assert_eq!(store.tid.to_string(), "store_unrolled_2");
+        assert_eq!(store.tid.address, "00001014_2");
+        assert_eq!(store.tid.canonical_address(), "00001014");
+
+        // Each store in the unrolled loop writes to an exact offset.
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pointer_inference.compute();
+        for (iteration, block) in blocks[1..5].iter().enumerate() {
+            let store = &block.term.defs[1];
+            let state = pointer_inference.get_state_before_term(&store.tid).unwrap();
+            let store_address = match &store.term {
+                Def::Store { address, .. } => state.eval(address),
+                _ => panic!(),
+            };
+            let (_, offset) = store_address.get_if_unique_target().unwrap();
+            assert_eq!(
+                offset.try_to_offset().unwrap(),
+                -0x20 + 8 * iteration as i64
+            );
+        }
+    }
+
+    #[test]
+    fn loops_exceeding_the_limit_are_kept() {
+        let mut project = mock_project(16);
+        let original_project = project.clone();
+        assert!(project.unroll_small_loops(&config()).is_empty());
+        assert_eq!(project, original_project);
+        // Loop unrolling can be disabled.
+        let mut project = mock_project(4);
+        let logs = project.unroll_small_loops(&LoopUnrollingConfig::default());
+        assert!(logs.is_empty());
+        assert_eq!(project, mock_project(4));
+    }
+
+    #[test]
+    fn loops_with_unknown_entries_are_kept() {
+        // The counter is not initialized with a constant.
+        let mut project = mock_project(4);
+        let sub = project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::new("func"))
+            .unwrap();
+        sub.term.blocks[0].term.defs[0] = Def::assign(
+            "init_counter",
+            Variable::mock("RCX", 8),
+            Expression::var("RDX", 8),
+        );
+        let original_project = project.clone();
+        assert!(project.unroll_small_loops(&config()).is_empty());
+        assert_eq!(project, original_project);
+        // The loop has a second entry.
+        let mut project = mock_project(4);
+        let sub = project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::new("func"))
+            .unwrap();
+        sub.term.blocks[2].term.jmps = vec![Jmp::branch("second_entry", "loop")];
+        let original_project = project.clone();
+        assert!(project.unroll_small_loops(&config()).is_empty());
+        assert_eq!(project, original_project);
+    }
+}
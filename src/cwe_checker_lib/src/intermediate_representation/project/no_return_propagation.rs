@@ -0,0 +1,223 @@
+use super::*;
+
+impl Project {
+    /// Returns `true` if a call to the given target may return,
+    /// assuming that the functions of the program contained in `returning_subs` are the only ones that may return.
+    ///
+    /// Extern symbols may return unless they are marked as `no_return`.
+    /// Unknown call targets are assumed to return.
+    fn may_call_return(&self, target: &Tid, returning_subs: &BTreeSet<Tid>) -> bool {
+        if let Some(symbol) = self.program.term.extern_symbols.get(target) {
+            !symbol.no_return
+        } else if self.program.term.subs.contains_key(target) {
+            returning_subs.contains(target)
+        } else {
+            true
+        }
+    }
+
+    /// Returns `true` if a return instruction of the function may be reached from its entry block,
+    /// assuming that the functions of the program contained in `returning_subs` are the only ones that may return.
+    ///
+    /// Tail calls to targets that may return, indirect jumps without known targets
+    /// and jumps to blocks outside of the function are assumed to return.
+    fn may_return(&self, sub: &Term<Sub>, returning_subs: &BTreeSet<Tid>) -> bool {
+        let blocks: HashMap<&Tid, &Term<Blk>> = sub
+            .term
+            .blocks
+            .iter()
+            .map(|block| (&block.tid, block))
+            .collect();
+        let mut worklist: Vec<&Tid> = match sub.term.blocks.first() {
+            Some(block) => vec![&block.tid],
+            None => return true,
+        };
+        let mut visited = HashSet::new();
+        while let Some(block_tid) = worklist.pop() {
+            if !visited.insert(block_tid) {
+                continue;
+            }
+            let block = match blocks.get(block_tid) {
+                Some(block) => block,
+                None => return true,
+            };
+            for jmp in block.term.jmps.iter() {
+                match &jmp.term {
+                    Jmp::Return(_) => return true,
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => worklist.push(target),
+                    Jmp::BranchInd(_) => {
+                        if block.term.indirect_jmp_targets.is_empty() {
+                            return true;
+                        }
+                        worklist.extend(block.term.indirect_jmp_targets.iter());
+                    }
+                    Jmp::Call { target, return_ } => {
+                        if self.may_call_return(target, returning_subs) {
+                            match return_ {
+                                Some(return_tid) => worklist.push(return_tid),
+                                None => return true,
+                            }
+                        }
+                    }
+                    Jmp::CallInd { return_, .. } | Jmp::CallOther { return_, .. } => {
+                        match return_ {
+                            Some(return_tid) => worklist.push(return_tid),
+                            None => return true,
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Detect functions that never return to their caller, like wrappers around `exit` or `abort`.
+    ///
+    /// A function never returns if every path through it ends in a call to a non-returning target
+    /// (an extern symbol marked as `no_return` or another non-returning function) or in an infinite loop.
+    /// The non-returning functions are computed as a fixpoint over the call graph
+    /// and stored in the `non_returning_subs` of the program.
+    /// The control flow graph contains no return edges for calls to them,
+    /// so that analyses do not follow the impossible paths after such calls.
+    #[must_use]
+    pub fn mark_non_returning_subs(&mut self) -> Vec<LogMessage> {
+        // Compute the least fixpoint of the set of functions that may return,
+        // so that functions only returning through infinite recursion are also non-returning.
+        let mut returning_subs = BTreeSet::new();
+        loop {
+            let new_returning_subs: Vec<Tid> = self
+                .program
+                .term
+                .subs
+                .values()
+                .filter(|sub| {
+                    !returning_subs.contains(&sub.tid) && self.may_return(sub, &returning_subs)
+                })
+                .map(|sub| sub.tid.clone())
+                .collect();
+            if new_returning_subs.is_empty() {
+                break;
+            }
+            returning_subs.extend(new_returning_subs);
+        }
+        let mut logs = Vec::new();
+        let mut non_returning_subs = BTreeSet::new();
+        for sub in self.program.term.subs.values() {
+            if !returning_subs.contains(&sub.tid) {
+                logs.push(
+                    LogMessage::new_debug(format!(
+                        "Calls to {} are treated as non-returning",
+                        sub.term.name
+                    ))
+                    .location(sub.tid.clone()),
+                );
+                non_returning_subs.insert(sub.tid.clone());
+            }
+        }
+        self.program.term.non_returning_subs = non_returning_subs;
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::{Edge, Node};
+    use petgraph::visit::EdgeRef;
+
+    /// Create a project where `main` calls `fatal`, which calls `die`, which calls `exit`.
+    /// All calls have return targets, since the disassembler did not know that `exit` does not return.
+    /// The function `loop` contains an infinite loop and `other` calls `exit` only conditionally.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        project.add_mock_extern_symbol("exit").no_return = true;
+
+        let return_block = |name: &str| {
+            let mut block = Blk::mock_with_tid(name);
+            block.term.jmps = vec![Term {
+                tid: Tid::new(format!("{}_return", name)),
+                term: Jmp::Return(Expression::var("RAX", 8)),
+            }];
+            block
+        };
+        let mut subs = Vec::new();
+        for (name, callee) in [("main", "fatal"), ("fatal", "die"), ("die", "exit")] {
+            let mut call_block = Blk::mock_with_tid(&format!("{}_blk0", name));
+            call_block.term.jmps = vec![Jmp::call(
+                &format!("{}_call", name),
+                callee,
+                Some(&format!("{}_blk1", name)),
+            )];
+            let sub = Sub::mock_with_blocks(
+                name,
+                vec![call_block, return_block(&format!("{}_blk1", name))],
+            );
+            subs.push(sub);
+        }
+        let mut loop_block = Blk::mock_with_tid("loop_blk0");
+        loop_block.term.jmps = vec![Jmp::branch("loop_jmp", "loop_blk0")];
+        let loop_sub = Sub::mock_with_blocks("loop", vec![loop_block]);
+        subs.push(loop_sub);
+
+        let mut check_block = Blk::mock_with_tid("other_blk0");
+        check_block.term.jmps = vec![
+            Term {
+                tid: Tid::new("other_check"),
+                term: Jmp::CBranch {
+                    target: Tid::new("other_blk2"),
+                    condition: Expression::var("ZF", 1),
+                },
+            },
+            Jmp::branch("other_jmp", "other_blk1"),
+        ];
+        let mut exit_block = Blk::mock_with_tid("other_blk1");
+        exit_block.term.jmps = vec![Jmp::call("other_call", "exit", Some("other_blk2"))];
+        let other = Sub::mock_with_blocks(
+            "other",
+            vec![check_block, exit_block, return_block("other_blk2")],
+        );
+        subs.push(other);
+
+        for sub in subs {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    #[test]
+    fn wrappers_of_exit_are_non_returning() {
+        let mut project = mock_project();
+        let logs = project.mark_non_returning_subs();
+        assert_eq!(logs.len(), 4);
+        // `main` does not return either, since it calls `fatal` unconditionally.
+        assert_eq!(
+            project.program.term.non_returning_subs,
+            BTreeSet::from([
+                Tid::new("die"),
+                Tid::new("fatal"),
+                Tid::new("loop"),
+                Tid::new("main")
+            ])
+        );
+
+        // The blocks after the calls to `exit`, `die` and `fatal` are unreachable in the control flow graph.
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        for edge in graph.edge_references() {
+            if let Node::BlkStart(block, _) = graph[edge.target()] {
+                assert!(!["main_blk1", "fatal_blk1", "die_blk1"]
+                    .contains(&block.tid.to_string().as_str()));
+            }
+        }
+        // Calls to `exit` have no edge to their return block.
+        assert!(graph
+            .edge_references()
+            .all(|edge| !matches!(edge.weight(), Edge::ExternCallStub(_))));
+    }
+}
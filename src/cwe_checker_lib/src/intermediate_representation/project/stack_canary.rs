@@ -0,0 +1,186 @@
+use super::*;
+
+/// Names of the functions that get called if the check of a stack canary fails.
+const STACK_CHK_FAIL_SYMBOLS: [&str; 2] = ["__stack_chk_fail", "__stack_chk_fail_local"];
+
+impl Project {
+    /// Detect the checks of stack canaries in all functions
+    /// and record the TIDs of all terms belonging to them in `stack_canary_artifacts`.
+    ///
+    /// A canary check consists of a block ending with a conditional jump to a block calling `__stack_chk_fail`.
+    /// We mark
+    /// - the defs computing the jump condition (including the load of the canary from the stack frame and the comparison),
+    /// - the jumps of the block containing the conditional jump,
+    /// - the block calling `__stack_chk_fail` together with all of its defs and jumps.
+    ///
+    /// Checkers can use [`Project::is_canary_artifact`] to ignore these terms,
+    /// since they are compiler-generated and never contain bugs of the analysed program.
+    pub fn mark_stack_canary_artifacts(&mut self) {
+        let fail_targets: HashSet<&Tid> = self
+            .program
+            .term
+            .extern_symbols
+            .values()
+            .filter(|symbol| STACK_CHK_FAIL_SYMBOLS.contains(&symbol.name.as_str()))
+            .map(|symbol| &symbol.tid)
+            .chain(
+                self.program
+                    .term
+                    .subs
+                    .values()
+                    .filter(|sub| STACK_CHK_FAIL_SYMBOLS.contains(&sub.term.name.as_str()))
+                    .map(|sub| &sub.tid),
+            )
+            .collect();
+        let mut artifacts = BTreeSet::new();
+        for sub in self.program.term.subs.values() {
+            let fail_blocks: HashSet<&Tid> = sub
+                .term
+                .blocks
+                .iter()
+                .filter(|block| {
+                    block.term.jmps.iter().any(|jmp| match &jmp.term {
+                        Jmp::Call { target, .. } => fail_targets.contains(target),
+                        _ => false,
+                    })
+                })
+                .map(|block| &block.tid)
+                .collect();
+            if fail_blocks.is_empty() {
+                continue;
+            }
+            for block in sub.term.blocks.iter() {
+                if fail_blocks.contains(&block.tid) {
+                    artifacts.insert(block.tid.clone());
+                    artifacts.extend(block.term.defs.iter().map(|def| def.tid.clone()));
+                    artifacts.extend(block.term.jmps.iter().map(|jmp| jmp.tid.clone()));
+                } else if block.term.jmps.iter().any(|jmp| match &jmp.term {
+                    Jmp::Branch(target) | Jmp::CBranch { target, .. } => {
+                        fail_blocks.contains(target)
+                    }
+                    _ => false,
+                }) {
+                    for jmp in block.term.jmps.iter() {
+                        artifacts.insert(jmp.tid.clone());
+                        if let Jmp::CBranch { condition, .. } = &jmp.term {
+                            artifacts.extend(get_defs_computing_expression(block, condition));
+                        }
+                    }
+                }
+            }
+        }
+        self.stack_canary_artifacts = artifacts;
+    }
+
+    /// Returns `true` if the term with the given TID belongs to the check of a stack canary.
+    ///
+    /// Note that the information is only available after [`Project::mark_stack_canary_artifacts`] was run.
+    pub fn is_canary_artifact(&self, tid: &Tid) -> bool {
+        self.stack_canary_artifacts.contains(tid)
+    }
+}
+
+/// Return the TIDs of all defs in the block that the value of the given expression
+/// at the end of the block depends on.
+fn get_defs_computing_expression(block: &Term<Blk>, expression: &Expression) -> Vec<Tid> {
+    let mut needed_vars: HashSet<Variable> = expression.input_vars().into_iter().cloned().collect();
+    let mut def_tids = Vec::new();
+    for def in block.term.defs.iter().rev() {
+        let (var, input_vars) = match &def.term {
+            Def::Assign { var, value } => (var, value.input_vars()),
+            Def::Load { var, address } => (var, address.input_vars()),
+            Def::Store { .. } => continue,
+        };
+        if needed_vars.remove(var) {
+            def_tids.push(def.tid.clone());
+            needed_vars.extend(input_vars.into_iter().cloned());
+        }
+    }
+    def_tids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Project {
+        /// Create a project containing a function with the canonical stack canary check in its epilogue.
+        pub fn mock_with_stack_canary_check() -> Project {
+            let mut project = Project::mock_empty();
+            project.add_mock_extern_symbol("__stack_chk_fail");
+
+            let mut sub = Sub::mock("func");
+            let mut epilogue = Blk::mock_with_tid("epilogue");
+            epilogue.term.defs = vec![
+                Def::assign(
+                    "unrelated_def",
+                    Variable::mock("RAX", 8),
+                    Expression::const_from_i64(0),
+                ),
+                Def::load(
+                    "load_canary_copy",
+                    Variable::mock("RDX", 8),
+                    Expression::var("RSP", 8).plus_const(-8),
+                ),
+                Def::load(
+                    "load_canary",
+                    Variable::mock("RCX", 8),
+                    Expression::var("FS_OFFSET", 8).plus_const(0x28),
+                ),
+                Def::assign(
+                    "compare_canary",
+                    Variable::mock("ZF", 1),
+                    Expression::BinOp {
+                        op: BinOpType::IntEqual,
+                        lhs: Box::new(Expression::var("RDX", 8)),
+                        rhs: Box::new(Expression::var("RCX", 8)),
+                    },
+                ),
+            ];
+            epilogue.term.jmps = vec![
+                Term {
+                    tid: Tid::new("canary_ok"),
+                    term: Jmp::CBranch {
+                        target: Tid::new("return_block"),
+                        condition: Expression::var("ZF", 1),
+                    },
+                },
+                Jmp::branch("canary_fail", "fail_block"),
+            ];
+            let mut fail_block = Blk::mock_with_tid("fail_block");
+            fail_block
+                .term
+                .jmps
+                .push(Jmp::call("call_stack_chk_fail", "__stack_chk_fail", None));
+            let mut return_block = Blk::mock_with_tid("return_block");
+            return_block.term.jmps.push(Term {
+                tid: Tid::new("return"),
+                term: Jmp::Return(Expression::var("RBX", 8)),
+            });
+            sub.term.blocks = vec![epilogue, fail_block, return_block];
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+            project.program.term.entry_points.insert(Tid::new("func"));
+            project
+        }
+    }
+
+    #[test]
+    fn canary_check_detection() {
+        let mut project = Project::mock_with_stack_canary_check();
+        project.mark_stack_canary_artifacts();
+        for tid in [
+            "load_canary_copy",
+            "load_canary",
+            "compare_canary",
+            "canary_ok",
+            "canary_fail",
+            "fail_block",
+            "call_stack_chk_fail",
+        ] {
+            assert!(project.is_canary_artifact(&Tid::new(tid)));
+        }
+        for tid in ["unrelated_def", "return_block", "return", "epilogue"] {
+            assert!(!project.is_canary_artifact(&Tid::new(tid)));
+        }
+    }
+}
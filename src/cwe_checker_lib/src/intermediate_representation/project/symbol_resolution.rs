@@ -0,0 +1,466 @@
+use super::*;
+use crate::utils::binary::ElfSymbolInfo;
+
+impl Project {
+    /// If the function is the PLT entry of an IFUNC symbol, return the name of the IFUNC symbol.
+    ///
+    /// A PLT entry of an IFUNC symbol loads its jump target from a GOT entry
+    /// that is filled by an `IRELATIVE` relocation with the resolver function of the IFUNC symbol.
+    fn get_ifunc_name_of_plt_entry<'a>(
+        &self,
+        sub: &Term<Sub>,
+        symbol_info: &'a ElfSymbolInfo,
+    ) -> Option<&'a String> {
+        let block = sub.term.blocks.first()?;
+        if !block
+            .term
+            .jmps
+            .iter()
+            .any(|jmp| matches!(jmp.term, Jmp::BranchInd(_) | Jmp::CallInd { .. }))
+        {
+            return None;
+        }
+        block.term.defs.iter().find_map(|def| match &def.term {
+            Def::Load {
+                address: Expression::Const(address),
+                ..
+            } => {
+                let got_entry = address
+                    .try_to_u64()
+                    .ok()?
+                    .checked_sub(self.program.term.address_base_offset)?;
+                let resolver = symbol_info.irelative_got_entries.get(&got_entry)?;
+                symbol_info.ifunc_resolvers.get(resolver)
+            }
+            _ => None,
+        })
+    }
+
+    /// Generate an extern symbol stub with the given TID, addresses and name.
+    ///
+    /// The parameters and return values of the stub are derived from the standard calling convention.
+    fn generate_extern_symbol_stub(
+        &self,
+        tid: Tid,
+        addresses: Vec<String>,
+        name: &str,
+    ) -> Option<ExternSymbol> {
+        let cconv = self.get_standard_calling_convention()?;
+        let to_arg = |var: &Variable| Arg::Register {
+            expr: Expression::Var(var.clone()),
+            data_type: None,
+        };
+        Some(ExternSymbol {
+            tid,
+            addresses,
+            name: name.to_string(),
+            calling_convention: Some(cconv.name.clone()),
+            parameters: cconv
+                .integer_parameter_register
+                .iter()
+                .map(to_arg)
+                .collect(),
+            return_values: cconv
+                .integer_return_register
+                .first()
+                .map(to_arg)
+                .into_iter()
+                .collect(),
+            no_return: false,
+            has_var_args: false,
+        })
+    }
+
+    /// Replace the targets of all calls according to the given map.
+    pub(super) fn retarget_calls(&mut self, new_targets: &HashMap<Tid, Tid>) {
+        for sub in self.program.term.subs.values_mut() {
+            for block in sub.term.blocks.iter_mut() {
+                for jmp in block.term.jmps.iter_mut() {
+                    if let Jmp::Call { target, .. } = &mut jmp.term {
+                        if let Some(new_target) = new_targets.get(target) {
+                            *target = new_target.clone();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolve IFUNC symbols and weak symbol aliases using the symbol information of the ELF file,
+    /// so that the modeling of extern symbols also applies to them.
+    ///
+    /// - Calls to the PLT entry of an IFUNC symbol (e.g. `memcpy` resolved at runtime to an AVX variant)
+    ///   are treated as calls to the function named by the IFUNC symbol.
+    ///   If an extern symbol of this name exists, the calls get retargeted to it.
+    ///   Otherwise an extern symbol stub is generated for the PLT entry.
+    /// - If extern symbols exist both for a weak symbol and the strong symbol defined at the same address,
+    ///   calls to the weak symbol get retargeted to the strong symbol and the weak symbol is removed.
+    /// - All weak symbol names are recorded as aliases of the corresponding strong symbol names,
+    ///   so that they can still be used to query the symbol.
+    /// - In relocatable files (e.g. Linux kernel modules) calls whose targets are given by relocations
+    ///   referencing undefined symbols are retargeted to extern symbols named after the referenced symbols.
+    ///   Missing extern symbols are generated as stubs.
+    #[must_use]
+    pub fn resolve_elf_symbols(&mut self, symbol_info: &ElfSymbolInfo) -> Vec<LogMessage> {
+        let mut logs = self.resolve_call_relocations(symbol_info);
+        for (weak_name, strong_name) in symbol_info.weak_aliases.iter() {
+            self.program
+                .term
+                .symbol_aliases
+                .insert(weak_name.clone(), strong_name.clone());
+        }
+        let mut new_targets = HashMap::new();
+        let mut stubs = Vec::new();
+        for sub in self.program.term.subs.values() {
+            if self.program.term.extern_symbols.contains_key(&sub.tid) {
+                continue;
+            }
+            let ifunc_name = match self.get_ifunc_name_of_plt_entry(sub, symbol_info) {
+                Some(name) => name,
+                None => continue,
+            };
+            if let Some(symbol) = self.program.term.find_extern_symbol_by_name(ifunc_name) {
+                new_targets.insert(sub.tid.clone(), symbol.tid.clone());
+            } else if let Some(stub) = self.generate_extern_symbol_stub(
+                sub.tid.clone(),
+                vec![sub.tid.address.clone()],
+                ifunc_name,
+            ) {
+                stubs.push(stub);
+            } else {
+                logs.push(
+                    LogMessage::new_info(format!(
+                        "No calling convention known for the PLT entry of the IFUNC symbol {}",
+                        ifunc_name
+                    ))
+                    .location(sub.tid.clone()),
+                );
+                continue;
+            }
+            logs.push(
+                LogMessage::new_debug(format!(
+                    "Calls to {} are treated as calls to the IFUNC symbol {}",
+                    sub.term.name, ifunc_name
+                ))
+                .location(sub.tid.clone()),
+            );
+        }
+        for stub in stubs {
+            self.program
+                .term
+                .extern_symbols
+                .insert(stub.tid.clone(), stub);
+        }
+        for (weak_name, strong_name) in symbol_info.weak_aliases.iter() {
+            let find_tid = |name: &str| {
+                self.program
+                    .term
+                    .extern_symbols
+                    .values()
+                    .find(|symbol| symbol.name == name)
+                    .map(|symbol| symbol.tid.clone())
+            };
+            if let (Some(weak_tid), Some(strong_tid)) = (find_tid(weak_name), find_tid(strong_name))
+            {
+                self.program.term.extern_symbols.remove(&weak_tid);
+                new_targets.insert(weak_tid, strong_tid);
+            }
+        }
+        self.retarget_calls(&new_targets);
+        logs
+    }
+
+    /// If the call instruction with the given TID has a relocation referencing an undefined symbol,
+    /// return the name of the symbol.
+    fn get_call_relocation_symbol<'a>(
+        &self,
+        call_tid: &Tid,
+        symbol_info: &'a ElfSymbolInfo,
+    ) -> Option<&'a String> {
+        let address = u64::from_str_radix(call_tid.canonical_address(), 16)
+            .ok()?
+            .checked_sub(self.program.term.address_base_offset)?;
+        symbol_info.call_relocation_symbols.get(&address)
+    }
+
+    /// Retarget calls whose targets are given by relocations referencing undefined symbols
+    /// to the extern symbols of the same names.
+    ///
+    /// In relocatable files (e.g. Linux kernel modules) calls to functions exported by the kernel
+    /// (e.g. `copy_from_user`) are only resolved when the file gets loaded,
+    /// so that the call targets in the disassembly are meaningless.
+    /// If no extern symbol of the referenced name exists, an extern symbol stub is generated for it.
+    fn resolve_call_relocations(&mut self, symbol_info: &ElfSymbolInfo) -> Vec<LogMessage> {
+        if symbol_info.call_relocation_symbols.is_empty() {
+            return Vec::new();
+        }
+        let mut logs = Vec::new();
+        let mut new_call_targets: HashMap<Tid, Tid> = HashMap::new();
+        let mut stubs: BTreeMap<Tid, ExternSymbol> = BTreeMap::new();
+        for sub in self.program.term.subs.values() {
+            for jmp in sub
+                .term
+                .blocks
+                .iter()
+                .flat_map(|block| block.term.jmps.iter())
+            {
+                if !matches!(jmp.term, Jmp::Call { .. } | Jmp::CallInd { .. }) {
+                    continue;
+                }
+                let name = match self.get_call_relocation_symbol(&jmp.tid, symbol_info) {
+                    Some(name) => name,
+                    None => continue,
+                };
+                let target = match self.program.term.find_extern_symbol_by_name(name) {
+                    Some(symbol) => symbol.tid.clone(),
+                    None => {
+                        let tid = Tid::new(format!("extern_{}", name));
+                        if !stubs.contains_key(&tid) {
+                            match self.generate_extern_symbol_stub(tid.clone(), Vec::new(), name) {
+                                Some(stub) => stubs.insert(tid.clone(), stub),
+                                None => {
+                                    logs.push(
+                                        LogMessage::new_info(format!(
+                                            "No calling convention known for the undefined symbol {}",
+                                            name
+                                        ))
+                                        .location(jmp.tid.clone()),
+                                    );
+                                    continue;
+                                }
+                            };
+                        }
+                        tid
+                    }
+                };
+                if matches!(&jmp.term, Jmp::Call { target: old_target, .. } if *old_target == target)
+                {
+                    continue;
+                }
+                logs.push(
+                    LogMessage::new_debug(format!(
+                        "Call target resolved to the undefined symbol {} through its relocation",
+                        name
+                    ))
+                    .location(jmp.tid.clone()),
+                );
+                new_call_targets.insert(jmp.tid.clone(), target);
+            }
+        }
+        self.program.term.extern_symbols.append(&mut stubs);
+        for sub in self.program.term.subs.values_mut() {
+            for jmp in sub
+                .term
+                .blocks
+                .iter_mut()
+                .flat_map(|block| block.term.jmps.iter_mut())
+            {
+                let new_target = match new_call_targets.get(&jmp.tid) {
+                    Some(target) => target.clone(),
+                    None => continue,
+                };
+                match &mut jmp.term {
+                    Jmp::Call { target, .. } => *target = new_target,
+                    Jmp::CallInd { return_, .. } => {
+                        jmp.term = Jmp::Call {
+                            target: new_target,
+                            return_: return_.take(),
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project with an IFUNC PLT entry loading its target from the GOT entry at 0x3000
+    /// and a function `main` calling the PLT entry, the extern symbol `strong` and the extern symbol `weak`.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        let mut plt_block = Blk::mock_with_tid("plt_blk");
+        plt_block.term.defs = vec![Def::load(
+            "load_got",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0x3000),
+        )];
+        plt_block.term.jmps = vec![Term {
+            tid: Tid::new("plt_jmp"),
+            term: Jmp::BranchInd(Expression::Var(Variable::mock("RAX", 8))),
+        }];
+        let mut plt_sub = Sub::mock("*ABS*+0x2000@plt");
+        plt_sub.tid = Tid::new("plt_sub");
+        plt_sub.term.blocks = vec![plt_block];
+
+        let mut main_block = Blk::mock_with_tid("main_blk");
+        main_block.term.jmps = vec![
+            Jmp::call("call_plt", "plt_sub", None),
+            Jmp::call("call_strong", "strong", None),
+            Jmp::call("call_weak", "weak", None),
+        ];
+        let main = Sub::mock_with_blocks("main", vec![main_block]);
+        for sub in [plt_sub, main] {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        for name in ["strong", "weak"] {
+            project.add_mock_extern_symbol(name);
+        }
+        project
+    }
+
+    /// Symbol information with an IFUNC symbol `memcpy` with resolver at 0x2000
+    /// and the weak symbol `weak` aliasing the strong symbol `strong`.
+    fn mock_symbol_info() -> ElfSymbolInfo {
+        ElfSymbolInfo {
+            ifunc_resolvers: BTreeMap::from([(0x2000, "memcpy".to_string())]),
+            irelative_got_entries: BTreeMap::from([(0x3000, 0x2000)]),
+            weak_aliases: BTreeMap::from([("weak".to_string(), "strong".to_string())]),
+            ..ElfSymbolInfo::default()
+        }
+    }
+
+    /// Return the call targets of the `main` function.
+    fn get_call_targets(project: &Project) -> Vec<Tid> {
+        project.program.term.subs[&Tid::new("main")].term.blocks[0]
+            .term
+            .jmps
+            .iter()
+            .filter_map(|jmp| match &jmp.term {
+                Jmp::Call { target, .. } => Some(target.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ifunc_plt_entry_stub() {
+        let mut project = mock_project();
+        let _ = project.resolve_elf_symbols(&mock_symbol_info());
+        let stub = &project.program.term.extern_symbols[&Tid::new("plt_sub")];
+        assert_eq!(stub.name, "memcpy");
+        assert_eq!(stub.parameters.len(), 1);
+        assert_eq!(
+            project.program.term.find_extern_symbol_by_name("memcpy"),
+            Some(stub)
+        );
+        // The calls to the PLT entry are kept, since the stub has the TID of the PLT entry.
+        assert_eq!(get_call_targets(&project)[0], Tid::new("plt_sub"));
+    }
+
+    #[test]
+    fn ifunc_plt_entry_with_existing_symbol() {
+        let mut project = mock_project();
+        project.add_mock_extern_symbol("memcpy");
+        let _ = project.resolve_elf_symbols(&mock_symbol_info());
+        assert!(!project
+            .program
+            .term
+            .extern_symbols
+            .contains_key(&Tid::new("plt_sub")));
+        assert_eq!(get_call_targets(&project)[0], Tid::new("memcpy"));
+    }
+
+    #[test]
+    fn weak_alias_resolution() {
+        let mut project = mock_project();
+        let _ = project.resolve_elf_symbols(&mock_symbol_info());
+        // The strong symbol is preferred over the weak symbol.
+        assert!(!project
+            .program
+            .term
+            .extern_symbols
+            .contains_key(&Tid::new("weak")));
+        assert_eq!(
+            get_call_targets(&project)[1..],
+            [Tid::new("strong"), Tid::new("strong")]
+        );
+        // The weak name can still be used to query the symbol.
+        let program = &project.program.term;
+        assert_eq!(
+            program.find_extern_symbol_by_name("weak").unwrap().name,
+            "strong"
+        );
+        assert_eq!(program.get_symbol_aliases("strong"), vec!["weak"]);
+        assert!(program.symbol_names_match("weak", "strong"));
+        assert!(!program.symbol_names_match("weak", "memcpy"));
+    }
+
+    #[test]
+    fn call_relocation_resolution() {
+        let mut project = mock_project();
+        let mut calls = vec![
+            Jmp::call("call_internal", "main", None),
+            Jmp::call("call_unresolved", "main", None),
+            Term {
+                tid: Tid::new("call_ind_unresolved"),
+                term: Jmp::CallInd {
+                    target: Expression::Var(Variable::mock("RAX", 8)),
+                    return_: Some(Tid::new("return")),
+                },
+            },
+            Jmp::call("call_strong", "main", None),
+        ];
+        for (call, address) in calls
+            .iter_mut()
+            .zip(["00010000", "00010005", "00010010", "00010020"])
+        {
+            call.tid.address = address.to_string();
+        }
+        project
+            .program
+            .term
+            .subs
+            .get_mut(&Tid::new("main"))
+            .unwrap()
+            .term
+            .blocks[0]
+            .term
+            .jmps = calls;
+        let symbol_info = ElfSymbolInfo {
+            call_relocation_symbols: BTreeMap::from([
+                (0x10005, "copy_from_user".to_string()),
+                (0x10010, "copy_from_user".to_string()),
+                (0x10020, "strong".to_string()),
+            ]),
+            ..ElfSymbolInfo::default()
+        };
+        let _ = project.resolve_elf_symbols(&symbol_info);
+
+        let stub_tid = Tid::new("extern_copy_from_user");
+        assert_eq!(
+            get_call_targets(&project),
+            vec![
+                Tid::new("main"),
+                stub_tid.clone(),
+                stub_tid.clone(),
+                Tid::new("strong")
+            ]
+        );
+        let stub = &project.program.term.extern_symbols[&stub_tid];
+        assert_eq!(stub.name, "copy_from_user");
+        assert_eq!(stub.parameters.len(), 1);
+        // The return target of the indirect call is kept.
+        assert_eq!(
+            project.program.term.subs[&Tid::new("main")].term.blocks[0]
+                .term
+                .jmps[2]
+                .term,
+            Jmp::Call {
+                target: stub_tid,
+                return_: Some(Tid::new("return")),
+            }
+        );
+    }
+}
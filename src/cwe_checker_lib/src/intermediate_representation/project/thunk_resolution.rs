@@ -0,0 +1,240 @@
+use super::loop_unrolling::evaluate;
+use super::*;
+use crate::utils::binary::ElfSymbolInfo;
+
+/// The target that a thunk function forwards calls to.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum ThunkTarget {
+    /// The function or extern symbol with the given TID.
+    Tid(Tid),
+    /// The symbol with the given name referenced by a PLT relocation of the GOT entry the jump target is loaded from.
+    Symbol(String),
+}
+
+impl Project {
+    /// If the function is a thunk function, return the target that it forwards calls to.
+    ///
+    /// A thunk function consists of a single block without stores that either
+    /// - ends with a tail call to another function or extern symbol,
+    /// - or ends with an indirect jump to a target loaded from a GOT entry filled by a PLT relocation.
+    ///   Since the address of the GOT entry is computed from constants inside the block,
+    ///   this also covers the PLT stub patterns of architectures that compute the address in several steps
+    ///   (e.g. ARM and MIPS).
+    fn get_thunk_target(
+        &self,
+        sub: &Term<Sub>,
+        symbol_info: &ElfSymbolInfo,
+    ) -> Option<ThunkTarget> {
+        let block = match &sub.term.blocks[..] {
+            [block] => block,
+            _ => return None,
+        };
+        let jmp = match &block.term.jmps[..] {
+            [jmp] => jmp,
+            _ => return None,
+        };
+        let target_expression = match &jmp.term {
+            Jmp::Call {
+                target,
+                return_: None,
+            } if *target != sub.tid && block.term.defs.is_empty() => {
+                return Some(ThunkTarget::Tid(target.clone()));
+            }
+            Jmp::BranchInd(target)
+            | Jmp::CallInd {
+                target,
+                return_: None,
+            } => target,
+            _ => return None,
+        };
+        // Values of registers known to be constant and registers known to contain the value of a GOT entry.
+        let mut known_values: HashMap<Variable, Bitvector> = HashMap::new();
+        let mut loaded_got_entries: HashMap<Variable, u64> = HashMap::new();
+        for def in block.term.defs.iter() {
+            match &def.term {
+                Def::Assign { var, value } => {
+                    loaded_got_entries.remove(var);
+                    match evaluate(value, &known_values) {
+                        Some(result) => known_values.insert(var.clone(), result),
+                        None => known_values.remove(var),
+                    };
+                }
+                Def::Load { var, address } => {
+                    known_values.remove(var);
+                    match evaluate(address, &known_values)
+                        .and_then(|address| address.try_to_u64().ok())
+                    {
+                        Some(address) => loaded_got_entries.insert(var.clone(), address),
+                        None => loaded_got_entries.remove(var),
+                    };
+                }
+                Def::Store { .. } => return None,
+            }
+        }
+        let got_entry = match target_expression {
+            Expression::Var(var) => *loaded_got_entries.get(var)?,
+            _ => return None,
+        };
+        let got_entry = got_entry.checked_sub(self.program.term.address_base_offset)?;
+        symbol_info
+            .plt_got_entries
+            .get(&got_entry)
+            .map(|name| ThunkTarget::Symbol(name.clone()))
+    }
+
+    /// Detect thunk functions like PLT stubs that only forward calls to an extern symbol.
+    ///
+    /// Thunk functions are marked as such and all calls to them are retargeted to the extern symbol,
+    /// so that the control flow graph contains direct calls to the extern symbol
+    /// and warnings get attributed to the real caller instead of the thunk function.
+    /// Thunk functions that forward to other thunk functions are resolved transitively.
+    /// Functions that forward to functions inside the program are not considered to be thunk functions.
+    #[must_use]
+    pub fn resolve_thunks(&mut self, symbol_info: &ElfSymbolInfo) -> Vec<LogMessage> {
+        let mut thunk_targets: HashMap<Tid, ThunkTarget> = HashMap::new();
+        for sub in self.program.term.subs.values() {
+            if self.program.term.extern_symbols.contains_key(&sub.tid) {
+                continue;
+            }
+            if let Some(target) = self.get_thunk_target(sub, symbol_info) {
+                thunk_targets.insert(sub.tid.clone(), target);
+            }
+        }
+        let mut logs = Vec::new();
+        let mut new_targets = HashMap::new();
+        for thunk_tid in thunk_targets.keys() {
+            let mut target = &thunk_targets[thunk_tid];
+            // Follow chains of thunk functions. The number of steps is bounded to handle cycles.
+            for _ in 0..thunk_targets.len() {
+                match target {
+                    ThunkTarget::Tid(tid) if thunk_targets.contains_key(tid) => {
+                        target = &thunk_targets[tid]
+                    }
+                    _ => break,
+                }
+            }
+            let symbol = match target {
+                ThunkTarget::Tid(tid) => self.program.term.extern_symbols.get(tid),
+                ThunkTarget::Symbol(name) => self.program.term.find_extern_symbol_by_name(name),
+            };
+            if let Some(symbol) = symbol {
+                logs.push(
+                    LogMessage::new_debug(format!(
+                        "Calls to {} are treated as calls to the extern symbol {}",
+                        self.program.term.subs[thunk_tid].term.name, symbol.name
+                    ))
+                    .location(thunk_tid.clone()),
+                );
+                new_targets.insert(thunk_tid.clone(), symbol.tid.clone());
+            }
+        }
+        for thunk_tid in new_targets.keys() {
+            if let Some(sub) = self.program.term.subs.get_mut(thunk_tid) {
+                sub.term.is_thunk = true;
+            }
+        }
+        self.retarget_calls(&new_targets);
+        logs.sort();
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::graph::Edge;
+
+    /// Create a project with the extern symbol `strcpy`, a function `main` calling the PLT stub of `strcpy`,
+    /// the PLT stub `strcpy@plt` loading its jump target from the GOT entry at 0x3000
+    /// and the thunk `thunk` tail-calling the PLT stub.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        project.add_mock_extern_symbol("strcpy");
+
+        let mut plt_block = Blk::mock_with_tid("plt_blk");
+        plt_block.term.defs = vec![Def::load(
+            "plt_load",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0x2000).plus_const(0x1000),
+        )];
+        plt_block.term.jmps = vec![Term {
+            tid: Tid::new("plt_jmp"),
+            term: Jmp::BranchInd(Expression::Var(Variable::mock("RAX", 8))),
+        }];
+        let plt_sub = Sub::mock_with_blocks("strcpy@plt", vec![plt_block]);
+
+        let mut thunk_block = Blk::mock_with_tid("thunk_blk");
+        thunk_block.term.jmps = vec![Jmp::call("thunk_jmp", "strcpy@plt", None)];
+        let thunk = Sub::mock_with_blocks("thunk", vec![thunk_block]);
+
+        let mut main = Sub::mock("main");
+        for (block_tid, call_tid, target, return_tid) in [
+            ("main_blk_0", "call_plt", "strcpy@plt", "main_blk_1"),
+            ("main_blk_1", "call_thunk", "thunk", "main_blk_2"),
+        ] {
+            let mut block = Blk::mock_with_tid(block_tid);
+            block.term.jmps = vec![Jmp::call(call_tid, target, Some(return_tid))];
+            main.term.blocks.push(block);
+        }
+        main.term.blocks.push(Blk::mock_with_tid("main_blk_2"));
+        for sub in [plt_sub, thunk, main] {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project
+    }
+
+    fn mock_symbol_info() -> ElfSymbolInfo {
+        ElfSymbolInfo {
+            plt_got_entries: BTreeMap::from([(0x3000, "strcpy".to_string())]),
+            ..ElfSymbolInfo::default()
+        }
+    }
+
+    #[test]
+    fn plt_stubs_are_resolved() {
+        let mut project = mock_project();
+        let logs = project.resolve_thunks(&mock_symbol_info());
+        assert_eq!(logs.len(), 2);
+        let subs = &project.program.term.subs;
+        assert!(subs[&Tid::new("strcpy@plt")].term.is_thunk);
+        assert!(subs[&Tid::new("thunk")].term.is_thunk);
+        assert!(!subs[&Tid::new("main")].term.is_thunk);
+
+        // The control flow graph contains direct calls to `strcpy` and no nodes of the thunk functions.
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+        assert!(graph
+            .node_indices()
+            .all(|node| graph[node].get_sub().tid == Tid::new("main")));
+        let call_targets: Vec<&Tid> = graph
+            .edge_indices()
+            .filter_map(|edge| match graph[edge] {
+                Edge::ExternCallStub(jmp) => match &jmp.term {
+                    Jmp::Call { target, .. } => Some(target),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(call_targets, vec![&Tid::new("strcpy"), &Tid::new("strcpy")]);
+    }
+
+    #[test]
+    fn unresolved_plt_stubs_are_kept() {
+        let mut project = mock_project();
+        let logs = project.resolve_thunks(&ElfSymbolInfo::default());
+        assert!(logs.is_empty());
+        assert!(project
+            .program
+            .term
+            .subs
+            .values()
+            .all(|sub| !sub.term.is_thunk));
+    }
+}
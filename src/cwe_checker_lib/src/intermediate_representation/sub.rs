@@ -1,4 +1,4 @@
-use super::{Blk, Datatype, Expression, Project, Variable};
+use super::{Arch, Blk, ByteSize, Datatype, Expression, Project, Variable};
 use crate::prelude::*;
 
 /// A `Sub` or subroutine represents a function with a given name and a list of basic blocks belonging to it.
@@ -15,6 +15,11 @@ pub struct Sub {
     pub blocks: Vec<Term<Blk>>,
     /// The calling convention used to call if known
     pub calling_convention: Option<String>,
+    /// Set for thunk functions like PLT stubs, which only forward calls to an extern symbol.
+    /// Calls to thunk functions are retargeted to the extern symbol during normalization
+    /// and thunk functions are not part of the control flow graph.
+    #[serde(default)]
+    pub is_thunk: bool,
 }
 
 /// A parameter or return argument of a function.
@@ -63,32 +68,63 @@ impl Arg {
             Arg::Register { .. } => return Err(anyhow!("The argument is not a stack argument.")),
             Arg::Stack { address, .. } => address,
         };
-        Self::eval_stack_offset_expression(expression, stack_register)
+        Self::eval_stack_offset_expression(expression, &[(stack_register, None)])
     }
 
-    /// If the given expression computes a constant offset to the given stack register,
+    /// If the argument is a stack argument,
+    /// return its offset relative to the current stack register value.
+    /// In contrast to [`Arg::eval_stack_offset`] the address may also be given relative to the frame pointer register,
+    /// e.g. for stack arguments in unoptimized code.
+    /// The `frame_pointer_offset` is the (known) value of the frame pointer minus the value of the stack register.
+    /// Return an error for register arguments or if the offset could not be computed.
+    pub fn eval_stack_offset_with_frame_pointer(
+        &self,
+        stack_register: &Variable,
+        frame_pointer: &Variable,
+        frame_pointer_offset: &Bitvector,
+    ) -> Result<Bitvector, Error> {
+        let expression = match self {
+            Arg::Register { .. } => return Err(anyhow!("The argument is not a stack argument.")),
+            Arg::Stack { address, .. } => address,
+        };
+        Self::eval_stack_offset_expression(
+            expression,
+            &[
+                (stack_register, None),
+                (frame_pointer, Some(frame_pointer_offset)),
+            ],
+        )
+    }
+
+    /// If the given expression computes a constant offset to the stack register,
     /// then return the offset.
     /// Else return an error.
+    ///
+    /// The `base_registers` are the registers allowed as inputs of the expression
+    /// together with their offset relative to the stack register (`None` for the stack register itself).
     fn eval_stack_offset_expression(
         expression: &Expression,
-        stack_register: &Variable,
+        base_registers: &[(&Variable, Option<&Bitvector>)],
     ) -> Result<Bitvector, Error> {
         match expression {
-            Expression::Var(var) => {
-                if var == stack_register {
-                    Ok(Bitvector::zero(var.size.into()))
-                } else {
-                    Err(anyhow!("Input register is not the stack register"))
-                }
-            }
+            Expression::Var(var) => match base_registers
+                .iter()
+                .find(|(base_register, _)| *base_register == var)
+            {
+                Some((_, None)) => Ok(Bitvector::zero(var.size.into())),
+                Some((_, Some(offset))) => Ok((*offset).clone()),
+                None => Err(anyhow!(
+                    "Input register is neither the stack register nor the frame pointer"
+                )),
+            },
             Expression::Const(bitvec) => Ok(bitvec.clone()),
             Expression::BinOp { op, lhs, rhs } => {
-                let lhs = Self::eval_stack_offset_expression(lhs, stack_register)?;
-                let rhs = Self::eval_stack_offset_expression(rhs, stack_register)?;
+                let lhs = Self::eval_stack_offset_expression(lhs, base_registers)?;
+                let rhs = Self::eval_stack_offset_expression(rhs, base_registers)?;
                 lhs.bin_op(*op, &rhs)
             }
             Expression::UnOp { op, arg } => {
-                let arg = Self::eval_stack_offset_expression(arg, stack_register)?;
+                let arg = Self::eval_stack_offset_expression(arg, base_registers)?;
                 arg.un_op(*op)
             }
             _ => Err(anyhow!("Expression type not supported for argument values")),
@@ -171,6 +207,158 @@ pub struct CallingConvention {
     /// A list of callee-saved register,
     /// i.e. the values of these registers should be the same after the call as they were before the call.
     pub callee_saved_register: Vec<Variable>,
+    /// The description of how variadic arguments are passed and accessed through a `va_list`.
+    /// `None` if unknown for the calling convention.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub varargs_layout: Option<VarargsLayout>,
+}
+
+/// The way a `va_list` accesses the variadic arguments of a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum VarargsProtocol {
+    /// The `va_list` is a simple pointer walking over the arguments on the stack (e.g. i386 or ARM32).
+    /// Parameter registers saved by the callee are stored directly below the stack arguments.
+    StackWalk,
+    /// The callee saves the parameter registers to a register save area
+    /// and the `va_list` is a structure containing the offsets of the next unread registers in the save area
+    /// and a pointer to the overflow arguments on the stack
+    /// (e.g. `gp_offset` and `fp_offset` on x86-64 System V, `__gr_offs` and `__vr_offs` on AArch64).
+    RegisterSaveArea,
+}
+
+/// The class of an argument, which determines the registers used for passing it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ArgClass {
+    /// Integer and pointer arguments passed in the integer parameter registers.
+    Integer,
+    /// Floating point arguments passed in the float parameter registers.
+    Float,
+}
+
+/// The layout of the register save area that the callee of a variadic function uses for accessing its arguments.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct VarargsLayout {
+    /// How the `va_list` accesses the arguments.
+    pub protocol: VarargsProtocol,
+    /// The number of integer parameter registers (starting with the first one) that may contain variadic arguments
+    /// and are saved to the register save area.
+    pub integer_register_count: usize,
+    /// The number of float parameter registers (starting with the first one) that may contain variadic arguments
+    /// and are saved to the register save area.
+    pub float_register_count: usize,
+    /// The size of the slot of a saved integer register in the save area.
+    pub integer_slot_size: ByteSize,
+    /// The size of the slot of a saved float register in the save area.
+    pub float_slot_size: ByteSize,
+    /// The size of the whole register save area.
+    pub save_area_size: ByteSize,
+    /// Whether the saved integer registers are located before the saved float registers in the save area.
+    pub integer_registers_first: bool,
+}
+
+impl VarargsLayout {
+    /// Get the layout used by the C calling convention of the given architecture
+    /// for functions with the given integer parameter registers.
+    ///
+    /// Returns `None` for architectures or calling conventions whose layout is not known.
+    /// Note that on x86-64 only the System V ABI (with six integer parameter registers) is known.
+    pub fn for_architecture(
+        architecture: &Arch,
+        integer_parameter_register: &[Variable],
+    ) -> Option<VarargsLayout> {
+        match architecture {
+            Arch::X86_64 if integer_parameter_register.len() == 6 => Some(VarargsLayout {
+                protocol: VarargsProtocol::RegisterSaveArea,
+                integer_register_count: 6,
+                float_register_count: 8,
+                integer_slot_size: ByteSize::new(8),
+                float_slot_size: ByteSize::new(16),
+                save_area_size: ByteSize::new(176),
+                integer_registers_first: true,
+            }),
+            Arch::Aarch64 => Some(VarargsLayout {
+                protocol: VarargsProtocol::RegisterSaveArea,
+                integer_register_count: 8,
+                float_register_count: 8,
+                integer_slot_size: ByteSize::new(8),
+                float_slot_size: ByteSize::new(16),
+                save_area_size: ByteSize::new(192),
+                integer_registers_first: true,
+            }),
+            Arch::X86_32 => Some(VarargsLayout {
+                protocol: VarargsProtocol::StackWalk,
+                integer_register_count: 0,
+                float_register_count: 0,
+                integer_slot_size: ByteSize::new(4),
+                float_slot_size: ByteSize::new(4),
+                save_area_size: ByteSize::new(0),
+                integer_registers_first: true,
+            }),
+            // On soft-float and hard-float ARM variadic arguments are always passed according to the base standard,
+            // i.e. floating point values are passed in integer registers.
+            Arch::Arm32 => Some(VarargsLayout {
+                protocol: VarargsProtocol::StackWalk,
+                integer_register_count: 4,
+                float_register_count: 0,
+                integer_slot_size: ByteSize::new(4),
+                float_slot_size: ByteSize::new(4),
+                save_area_size: ByteSize::new(16),
+                integer_registers_first: true,
+            }),
+            // Variadic arguments are always passed in the integer parameter registers or on the stack,
+            // even floating point values.
+            // The callee saves the integer parameter registers directly below the stack arguments,
+            // so that the `va_list` can walk over all variadic arguments.
+            Arch::RiscV => integer_parameter_register
+                .first()
+                .map(|register| VarargsLayout {
+                    protocol: VarargsProtocol::StackWalk,
+                    integer_register_count: integer_parameter_register.len(),
+                    float_register_count: 0,
+                    integer_slot_size: register.size,
+                    float_slot_size: register.size,
+                    save_area_size: ByteSize::new(
+                        integer_parameter_register.len() as u64 * u64::from(register.size),
+                    ),
+                    integer_registers_first: true,
+                }),
+            _ => None,
+        }
+    }
+
+    /// Get the offset of the slot of the saved parameter register with the given index in the register save area.
+    fn get_save_area_offset(&self, register_index: usize, class: ArgClass) -> u64 {
+        let integer_area_size =
+            self.integer_register_count as u64 * u64::from(self.integer_slot_size);
+        let float_area_size = self.float_register_count as u64 * u64::from(self.float_slot_size);
+        match (class, self.integer_registers_first) {
+            (ArgClass::Integer, true) => register_index as u64 * u64::from(self.integer_slot_size),
+            (ArgClass::Integer, false) => {
+                float_area_size + register_index as u64 * u64::from(self.integer_slot_size)
+            }
+            (ArgClass::Float, true) => {
+                integer_area_size + register_index as u64 * u64::from(self.float_slot_size)
+            }
+            (ArgClass::Float, false) => register_index as u64 * u64::from(self.float_slot_size),
+        }
+    }
+}
+
+/// The location of an argument of a variadic function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub enum VarargLocation {
+    /// The argument is passed in the parameter register with the given index among the registers of its class.
+    Register {
+        /// The index of the parameter register.
+        register_index: usize,
+        /// The offset of the slot of the register in the register save area of the callee, if known.
+        save_area_offset: Option<u64>,
+    },
+    /// The argument is passed on the stack.
+    Stack {
+        /// The number of arguments of the same class that are passed on the stack before the argument.
+        overflow_index: usize,
+    },
 }
 
 impl CallingConvention {
@@ -185,6 +373,49 @@ impl CallingConvention {
         register_list
     }
 
+    /// Get the number of parameter registers of the given class that may contain variadic arguments.
+    pub fn get_varargs_register_count(&self, class: ArgClass) -> usize {
+        let (register_count, varargs_register_count) = match class {
+            ArgClass::Integer => (
+                self.integer_parameter_register.len(),
+                self.varargs_layout
+                    .as_ref()
+                    .map(|layout| layout.integer_register_count),
+            ),
+            ArgClass::Float => (
+                self.float_parameter_register.len(),
+                self.varargs_layout
+                    .as_ref()
+                    .map(|layout| layout.float_register_count),
+            ),
+        };
+        varargs_register_count
+            .map(|count| std::cmp::min(count, register_count))
+            .unwrap_or(register_count)
+    }
+
+    /// Get the location of the argument with the given index among the (named and variadic) arguments
+    /// of the given class of a variadic function.
+    ///
+    /// If the calling convention has no known [`VarargsLayout`],
+    /// all parameter registers of the class are assumed to be usable for variadic arguments.
+    pub fn varargs_arg_location(&self, index: usize, class: ArgClass) -> VarargLocation {
+        let register_count = self.get_varargs_register_count(class);
+        if index < register_count {
+            VarargLocation::Register {
+                register_index: index,
+                save_area_offset: self
+                    .varargs_layout
+                    .as_ref()
+                    .map(|layout| layout.get_save_area_offset(index, class)),
+            }
+        } else {
+            VarargLocation::Stack {
+                overflow_index: index - register_count,
+            }
+        }
+    }
+
     /// Return a list of all return registers of the calling convention.
     /// For return register, where only a part of a register is the actual return register,
     /// the return register is approximated by the (larger) base register.
@@ -200,6 +431,7 @@ impl CallingConvention {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::intermediate_representation::Def;
 
     impl Sub {
         pub fn mock(name: impl ToString) -> Term<Sub> {
@@ -209,9 +441,38 @@ mod tests {
                     name: name.to_string(),
                     blocks: Vec::new(),
                     calling_convention: None,
+                    is_thunk: false,
                 },
             }
         }
+
+        /// Create a function with the given name that consists of the given blocks.
+        pub fn mock_with_blocks(name: impl ToString, blocks: Vec<Term<Blk>>) -> Term<Sub> {
+            let mut sub = Sub::mock(name);
+            sub.term.blocks = blocks;
+            sub
+        }
+
+        /// Create a function with the given name that executes the given `Def`s before each of the given calls
+        /// and then returns.
+        /// The blocks are named `{name}_blk_{index}` with the return block as the last block.
+        pub fn mock_with_calls(name: &str, calls: Vec<(Vec<Term<Def>>, &str)>) -> Term<Sub> {
+            let num_calls = calls.len();
+            let mut blocks: Vec<Term<Blk>> = calls
+                .into_iter()
+                .enumerate()
+                .map(|(index, (defs, callee))| {
+                    Blk::mock_call(
+                        &format!("{}_blk_{}", name, index),
+                        defs,
+                        callee,
+                        &format!("{}_blk_{}", name, index + 1),
+                    )
+                })
+                .collect();
+            blocks.push(Blk::mock_return(&format!("{}_blk_{}", name, num_calls)));
+            Sub::mock_with_blocks(name, blocks)
+        }
     }
 
     impl CallingConvention {
@@ -223,6 +484,7 @@ mod tests {
                 integer_return_register: vec![Variable::mock("RAX", 8)],
                 float_return_register: vec![],
                 callee_saved_register: vec![Variable::mock("RBP", 8)],
+                varargs_layout: None,
             }
         }
 
@@ -234,6 +496,31 @@ mod tests {
                 integer_return_register: vec![Variable::mock("r0", 4)],
                 float_return_register: vec![],
                 callee_saved_register: vec![Variable::mock("r4", 4)],
+                varargs_layout: None,
+            }
+        }
+
+        pub fn mock_riscv64() -> CallingConvention {
+            let registers = |prefix: &str, count: usize, size: u64| -> Vec<Variable> {
+                (0..count)
+                    .map(|i| Variable::mock(format!("{}{}", prefix, i), size))
+                    .collect()
+            };
+            let integer_parameter_register = registers("a", 8, 8);
+            CallingConvention {
+                name: "__stdcall".to_string(), // so that the mock is useable as standard calling convention in tests
+                varargs_layout: VarargsLayout::for_architecture(
+                    &Arch::RiscV,
+                    &integer_parameter_register,
+                ),
+                integer_parameter_register,
+                float_parameter_register: registers("fa", 8, 8)
+                    .into_iter()
+                    .map(Expression::Var)
+                    .collect(),
+                integer_return_register: registers("a", 2, 8),
+                float_return_register: vec![Expression::Var(Variable::mock("fa0", 8))],
+                callee_saved_register: registers("s", 12, 8),
             }
         }
 
@@ -252,6 +539,7 @@ mod tests {
                 integer_return_register: vec![Variable::mock("RAX", 8)],
                 float_return_register: vec![],
                 callee_saved_register: vec![Variable::mock("RBP", 8)],
+                varargs_layout: None,
             }
         }
     }
@@ -300,6 +588,15 @@ mod tests {
             }
         }
 
+        /// Create a x64 extern symbol whose TID and name are the given name.
+        pub fn mock_x64_named(name: &str) -> ExternSymbol {
+            ExternSymbol {
+                tid: Tid::new(name),
+                name: name.to_string(),
+                ..ExternSymbol::mock_x64()
+            }
+        }
+
         pub fn mock_arm32() -> ExternSymbol {
             // There is also the mock_standard_arm32() method. Only on of the two should exist!
             ExternSymbol {
@@ -314,6 +611,19 @@ mod tests {
             }
         }
 
+        pub fn mock_riscv64() -> ExternSymbol {
+            ExternSymbol {
+                tid: Tid::new("mock_symbol"),
+                addresses: vec!["UNKNOWN".to_string()],
+                name: "mock_symbol".to_string(),
+                calling_convention: Some("__stdcall".to_string()),
+                parameters: vec![Arg::mock_register("a0", 8)],
+                return_values: vec![Arg::mock_register("a0", 8)],
+                no_return: false,
+                has_var_args: false,
+            }
+        }
+
         pub fn mock_string() -> Self {
             ExternSymbol {
                 tid: Tid::new("sprintf"),
@@ -327,4 +637,185 @@ mod tests {
             }
         }
     }
+
+    /// Create a calling convention with the given parameter registers
+    /// and the varargs layout of the given architecture.
+    fn cconv_with_varargs_layout(
+        architecture: &Arch,
+        integer_register: &[&str],
+        float_register: &[&str],
+        register_size: u64,
+    ) -> CallingConvention {
+        let mut cconv = CallingConvention::mock_with_parameter_registers(
+            integer_register
+                .iter()
+                .map(|name| Variable::mock(name, register_size))
+                .collect(),
+            float_register
+                .iter()
+                .map(|name| Variable::mock(name, 16))
+                .collect(),
+        );
+        cconv.varargs_layout =
+            VarargsLayout::for_architecture(architecture, &cconv.integer_parameter_register);
+        cconv
+    }
+
+    #[test]
+    fn varargs_layout_x86_64() {
+        let cconv = cconv_with_varargs_layout(
+            &Arch::X86_64,
+            &["RDI", "RSI", "RDX", "RCX", "R8", "R9"],
+            &[
+                "XMM0", "XMM1", "XMM2", "XMM3", "XMM4", "XMM5", "XMM6", "XMM7",
+            ],
+            8,
+        );
+        let layout = cconv.varargs_layout.as_ref().unwrap();
+        assert_eq!(layout.protocol, VarargsProtocol::RegisterSaveArea);
+        assert_eq!(layout.save_area_size, ByteSize::new(176));
+        assert_eq!(
+            cconv.varargs_arg_location(5, ArgClass::Integer),
+            VarargLocation::Register {
+                register_index: 5,
+                save_area_offset: Some(40)
+            }
+        );
+        assert_eq!(
+            cconv.varargs_arg_location(6, ArgClass::Integer),
+            VarargLocation::Stack { overflow_index: 0 }
+        );
+        // The float registers are saved after the integer registers (`fp_offset` starts at 48).
+        assert_eq!(
+            cconv.varargs_arg_location(0, ArgClass::Float),
+            VarargLocation::Register {
+                register_index: 0,
+                save_area_offset: Some(48)
+            }
+        );
+        assert_eq!(
+            cconv.varargs_arg_location(7, ArgClass::Float),
+            VarargLocation::Register {
+                register_index: 7,
+                save_area_offset: Some(160)
+            }
+        );
+        assert_eq!(
+            cconv.varargs_arg_location(9, ArgClass::Float),
+            VarargLocation::Stack { overflow_index: 1 }
+        );
+        // The layout of the Windows x64 calling convention is not known.
+        let windows_cconv =
+            cconv_with_varargs_layout(&Arch::X86_64, &["RCX", "RDX", "R8", "R9"], &[], 8);
+        assert_eq!(windows_cconv.varargs_layout, None);
+        assert_eq!(
+            windows_cconv.varargs_arg_location(3, ArgClass::Integer),
+            VarargLocation::Register {
+                register_index: 3,
+                save_area_offset: None
+            }
+        );
+    }
+
+    #[test]
+    fn varargs_layout_i386() {
+        // Even for calling conventions with parameter registers all variadic arguments are passed on the stack.
+        let cconv = cconv_with_varargs_layout(&Arch::X86_32, &["ECX", "EDX"], &[], 4);
+        assert_eq!(
+            cconv.varargs_layout.as_ref().unwrap().protocol,
+            VarargsProtocol::StackWalk
+        );
+        assert_eq!(cconv.get_varargs_register_count(ArgClass::Integer), 0);
+        assert_eq!(
+            cconv.varargs_arg_location(0, ArgClass::Integer),
+            VarargLocation::Stack { overflow_index: 0 }
+        );
+        assert_eq!(
+            cconv.varargs_arg_location(2, ArgClass::Float),
+            VarargLocation::Stack { overflow_index: 2 }
+        );
+    }
+
+    #[test]
+    fn varargs_layout_aarch64() {
+        let integer_register: Vec<String> = (0..8).map(|i| format!("x{}", i)).collect();
+        let float_register: Vec<String> = (0..8).map(|i| format!("q{}", i)).collect();
+        let cconv = cconv_with_varargs_layout(
+            &Arch::Aarch64,
+            &integer_register
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            &float_register
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            8,
+        );
+        let layout = cconv.varargs_layout.as_ref().unwrap();
+        assert_eq!(layout.protocol, VarargsProtocol::RegisterSaveArea);
+        assert_eq!(layout.save_area_size, ByteSize::new(192));
+        assert_eq!(
+            cconv.varargs_arg_location(7, ArgClass::Integer),
+            VarargLocation::Register {
+                register_index: 7,
+                save_area_offset: Some(56)
+            }
+        );
+        assert_eq!(
+            cconv.varargs_arg_location(1, ArgClass::Float),
+            VarargLocation::Register {
+                register_index: 1,
+                save_area_offset: Some(80)
+            }
+        );
+        assert_eq!(
+            cconv.varargs_arg_location(8, ArgClass::Integer),
+            VarargLocation::Stack { overflow_index: 0 }
+        );
+    }
+
+    #[test]
+    fn varargs_layout_riscv64() {
+        let cconv = CallingConvention::mock_riscv64();
+        let layout = cconv.varargs_layout.as_ref().unwrap();
+        assert_eq!(layout.protocol, VarargsProtocol::StackWalk);
+        assert_eq!(layout.save_area_size, ByteSize::new(64));
+        assert_eq!(
+            cconv.varargs_arg_location(7, ArgClass::Integer),
+            VarargLocation::Register {
+                register_index: 7,
+                save_area_offset: Some(56)
+            }
+        );
+        assert_eq!(
+            cconv.varargs_arg_location(8, ArgClass::Integer),
+            VarargLocation::Stack { overflow_index: 0 }
+        );
+        // Variadic floating point arguments are never passed in the float parameter registers.
+        assert_eq!(cconv.get_varargs_register_count(ArgClass::Float), 0);
+        assert_eq!(
+            cconv.varargs_arg_location(0, ArgClass::Float),
+            VarargLocation::Stack { overflow_index: 0 }
+        );
+    }
+
+    #[test]
+    fn varargs_layout_deserialization_is_backward_compatible() {
+        let mut cconv = CallingConvention::mock();
+        let mut json = serde_json::to_value(&cconv).unwrap();
+        assert!(json.get("varargs_layout").is_none());
+        json.as_object_mut().unwrap().remove("varargs_layout");
+        assert_eq!(
+            serde_json::from_value::<CallingConvention>(json).unwrap(),
+            cconv
+        );
+
+        cconv.varargs_layout = VarargsLayout::for_architecture(&Arch::Aarch64, &[]);
+        let json = serde_json::to_string(&cconv).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CallingConvention>(&json).unwrap(),
+            cconv
+        );
+    }
 }
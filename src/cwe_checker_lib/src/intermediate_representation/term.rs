@@ -41,6 +41,26 @@ impl Tid {
             address: address.to_string(),
         }
     }
+
+    /// Return the address of the machine instruction that the term belongs to.
+    ///
+    /// See [`Tid::canonicalize_address`] for details.
+    pub fn canonical_address(&self) -> &str {
+        Tid::canonicalize_address(&self.address)
+    }
+
+    /// Strip IR-internal suffixes (e.g. the `_1` in `00101000_1`) from the given address.
+    ///
+    /// On some architectures one machine instruction is translated to several terms
+    /// whose addresses are distinguished by such suffixes.
+    /// The canonical address is the address of the machine instruction itself,
+    /// so that findings for terms of the same instruction can be matched by their address.
+    pub fn canonicalize_address(address: &str) -> &str {
+        match address.split_once('_') {
+            Some((instruction_address, _suffix)) => instruction_address,
+            None => address,
+        }
+    }
 }
 
 impl std::fmt::Display for Tid {
@@ -57,3 +77,18 @@ pub struct Term<T> {
     /// The object
     pub term: T,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_address() {
+        let mut tid = Tid::new("instr_00101000_2");
+        tid.address = "00101000_2".to_string();
+        assert_eq!(tid.canonical_address(), "00101000");
+        tid.address = "00101000".to_string();
+        assert_eq!(tid.canonical_address(), "00101000");
+        assert_eq!(Tid::new("func").canonical_address(), "UNKNOWN");
+    }
+}
@@ -61,17 +61,24 @@ by reading the check-specific module documentation in the [`checkers`] module.
 */
 
 use abstract_domain::BricksDomain;
+use std::collections::{BTreeMap, BTreeSet};
 
+use crate::analysis::function_signature::FunctionSignature;
 use crate::analysis::graph::Graph;
-use crate::analysis::pointer_inference::PointerInference;
+use crate::analysis::pointer_inference::{
+    NodeValueRetention, PointerInference, PointerInferenceCheckpoint,
+};
+use crate::analysis::stack_frame_layout::StackFrameLayout;
 use crate::analysis::string_abstraction::StringAbstraction;
-use crate::intermediate_representation::Project;
+use crate::intermediate_representation::{Project, Tid};
 use crate::utils::binary::RuntimeMemoryImage;
 use crate::utils::log::{CweWarning, LogMessage};
 
 pub mod abstract_domain;
 pub mod analysis;
 pub mod checkers;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod intermediate_representation;
 pub mod pcode;
 pub mod utils;
@@ -100,6 +107,111 @@ pub struct CweModule {
     pub version: &'static str,
     /// The function that executes the check and returns CWE warnings found during the check.
     pub run: CweModuleFn,
+    /// The shared analyses whose results the check needs in the [`AnalysisResults`].
+    /// Dependencies between the shared analyses need not be listed.
+    pub required_analyses: &'static [SharedAnalysis],
+    /// The pointer inference states that the check queries,
+    /// given as the smallest [`NodeValueRetention`] policy keeping them.
+    /// Ignored if the check does not require the pointer inference.
+    pub pointer_inference_retention: NodeValueRetention,
+}
+
+impl CweModule {
+    /// Get all shared analyses that have to be computed before the check can run,
+    /// including the analyses that the directly required analyses depend on.
+    pub fn required_analyses(&self) -> BTreeSet<SharedAnalysis> {
+        let mut analyses = BTreeSet::new();
+        for analysis in self.required_analyses {
+            analyses.insert(*analysis);
+            analyses.extend(analysis.dependencies().iter().copied());
+        }
+        analyses
+    }
+
+    /// Get the smallest node value retention policy of the pointer inference
+    /// that keeps all states needed by the check.
+    ///
+    /// The string abstraction queries the pointer inference states at arbitrary nodes,
+    /// so checks requiring it need all node values.
+    /// The stack frame layouts are computed from the states at memory accesses.
+    pub fn required_retention(&self) -> NodeValueRetention {
+        let analyses = self.required_analyses();
+        if analyses.contains(&SharedAnalysis::StringAbstraction) {
+            NodeValueRetention::All
+        } else if analyses.contains(&SharedAnalysis::StackFrameLayouts) {
+            std::cmp::max(
+                self.pointer_inference_retention,
+                NodeValueRetention::CallSitesAndAccesses,
+            )
+        } else if analyses.contains(&SharedAnalysis::PointerInference) {
+            self.pointer_inference_retention
+        } else {
+            NodeValueRetention::EntryExitOnly
+        }
+    }
+}
+
+/// An analysis whose results are computed once and shared between all CWE checks
+/// through the [`AnalysisResults`] struct.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum SharedAnalysis {
+    /// The [pointer inference analysis](crate::analysis::pointer_inference).
+    PointerInference,
+    /// The [string abstraction](crate::analysis::string_abstraction).
+    StringAbstraction,
+    /// The [function signature analysis](crate::analysis::function_signature).
+    FunctionSignatures,
+    /// The [stack frame layouts](crate::analysis::stack_frame_layout) of all functions.
+    StackFrameLayouts,
+}
+
+impl SharedAnalysis {
+    /// Get the shared analyses that have to be computed before this analysis.
+    pub fn dependencies(&self) -> &'static [SharedAnalysis] {
+        match self {
+            SharedAnalysis::PointerInference => &[],
+            SharedAnalysis::StringAbstraction => &[SharedAnalysis::PointerInference],
+            SharedAnalysis::FunctionSignatures => &[],
+            SharedAnalysis::StackFrameLayouts => &[SharedAnalysis::PointerInference],
+        }
+    }
+}
+
+/// Get all shared analyses that have to be computed before the given modules can run.
+pub fn get_required_analyses(modules: &[&CweModule]) -> BTreeSet<SharedAnalysis> {
+    modules
+        .iter()
+        .flat_map(|module| module.required_analyses())
+        .collect()
+}
+
+/// Get the smallest node value retention policy of the pointer inference
+/// that keeps all states needed by the given modules.
+pub fn get_required_retention(modules: &[&CweModule]) -> NodeValueRetention {
+    modules
+        .iter()
+        .map(|module| module.required_retention())
+        .max()
+        .unwrap_or(NodeValueRetention::EntryExitOnly)
+}
+
+/// Run the given modules and collect their log messages and CWE warnings.
+///
+/// All modules see the same shared analysis results.
+/// The `config` is the complete configuration, i.e. each module gets the entry with its name.
+pub fn run_modules(
+    modules: &[&CweModule],
+    analysis_results: &AnalysisResults,
+    config: &serde_json::Value,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    let mut all_logs = Vec::new();
+    let mut all_cwes = Vec::new();
+    for module in modules {
+        let (mut logs, mut cwes) = (module.run)(analysis_results, &config[&module.name]);
+        all_logs.append(&mut logs);
+        all_cwes.append(&mut cwes);
+    }
+    (all_logs, all_cwes)
 }
 
 impl std::fmt::Display for CweModule {
@@ -109,22 +221,48 @@ impl std::fmt::Display for CweModule {
     }
 }
 
+/// The names of the analysis modules that are not executed on standard runs,
+/// i.e. if the modules to execute are not explicitly selected.
+///
+/// TODO: CWE78 is disabled on a standard run for now,
+/// because it uses up huge amounts of RAM and computation time on some binaries.
+/// CWE188 is disabled on a standard run, because accesses to packed structs make it noisy.
+pub const MODULES_DISABLED_ON_STANDARD_RUNS: &[&str] = &["CWE78", "CWE188"];
+
 /// Get a list of all known analysis modules.
 pub fn get_modules() -> Vec<&'static CweModule> {
     vec![
         &crate::checkers::cwe_78::CWE_MODULE,
+        &crate::checkers::cwe_117::CWE_MODULE,
+        &crate::checkers::cwe_123::CWE_MODULE,
+        &crate::checkers::cwe_129::CWE_MODULE,
         &crate::checkers::cwe_134::CWE_MODULE,
+        &crate::checkers::cwe_170::CWE_MODULE,
+        &crate::checkers::cwe_188::CWE_MODULE,
         &crate::checkers::cwe_190::CWE_MODULE,
+        &crate::checkers::cwe_195::CWE_MODULE,
+        &crate::checkers::cwe_196::CWE_MODULE,
+        &crate::checkers::cwe_197::CWE_MODULE,
         &crate::checkers::cwe_215::CWE_MODULE,
         &crate::checkers::cwe_243::CWE_MODULE,
+        &crate::checkers::cwe_295::CWE_MODULE,
         &crate::checkers::cwe_332::CWE_MODULE,
         &crate::checkers::cwe_367::CWE_MODULE,
+        &crate::checkers::cwe_403::CWE_MODULE,
         &crate::checkers::cwe_426::CWE_MODULE,
         &crate::checkers::cwe_467::CWE_MODULE,
         &crate::checkers::cwe_476::CWE_MODULE,
+        &crate::checkers::cwe_494::CWE_MODULE,
         &crate::checkers::cwe_560::CWE_MODULE,
+        &crate::checkers::cwe_562::CWE_MODULE,
+        &crate::checkers::cwe_674::CWE_MODULE,
         &crate::checkers::cwe_676::CWE_MODULE,
+        &crate::checkers::cwe_704::CWE_MODULE,
+        &crate::checkers::cwe_775::CWE_MODULE,
         &crate::checkers::cwe_782::CWE_MODULE,
+        &crate::checkers::cwe_787::CWE_MODULE,
+        &crate::checkers::cwe_789::CWE_MODULE,
+        &crate::checkers::cwe_1386::CWE_MODULE,
         &crate::analysis::pointer_inference::CWE_MODULE,
     ]
 }
@@ -145,6 +283,10 @@ pub struct AnalysisResults<'a> {
     pub pointer_inference: Option<&'a PointerInference<'a>>,
     /// The result of the string abstraction if already computed.
     pub string_abstraction: Option<&'a StringAbstraction<'a, BricksDomain>>,
+    /// The signatures of all functions of the program if already computed.
+    pub function_signatures: Option<&'a BTreeMap<Tid, FunctionSignature>>,
+    /// The stack frame layouts of all functions analyzed by the pointer inference if already computed.
+    pub stack_frame_layouts: Option<&'a BTreeMap<Tid, StackFrameLayout>>,
 }
 
 impl<'a> AnalysisResults<'a> {
@@ -162,6 +304,8 @@ impl<'a> AnalysisResults<'a> {
             project,
             pointer_inference: None,
             string_abstraction: None,
+            function_signatures: None,
+            stack_frame_layouts: None,
         }
     }
 
@@ -182,6 +326,23 @@ impl<'a> AnalysisResults<'a> {
         )
     }
 
+    /// Restore the results of the pointer inference analysis from a checkpoint
+    /// generated by a previous computation with the same configuration.
+    /// The result gets returned, but not saved to the `AnalysisResults` struct itself.
+    pub fn restore_pointer_inference(
+        &'a self,
+        config: &serde_json::Value,
+        checkpoint: PointerInferenceCheckpoint,
+    ) -> Result<PointerInference<'a>, anyhow::Error> {
+        PointerInference::from_checkpoint(
+            self.project,
+            self.runtime_memory_image,
+            self.control_flow_graph,
+            serde_json::from_value(config.clone()).unwrap(),
+            checkpoint,
+        )
+    }
+
     /// Create a new `AnalysisResults` struct containing the given pointer inference analysis results.
     pub fn set_pointer_inference<'b: 'a>(
         self,
@@ -221,4 +382,207 @@ impl<'a> AnalysisResults<'a> {
             ..self
         }
     }
+
+    /// Compute the signatures of all functions of the program.
+    /// The result gets returned together with the log messages generated by the analysis,
+    /// but not saved to the `AnalysisResults` struct itself.
+    pub fn compute_function_signatures(
+        &self,
+    ) -> (Vec<LogMessage>, BTreeMap<Tid, FunctionSignature>) {
+        crate::analysis::function_signature::compute_function_signatures(
+            self.project,
+            self.control_flow_graph,
+        )
+    }
+
+    /// Create a new `AnalysisResults` struct containing the given function signatures.
+    pub fn set_function_signatures(
+        self,
+        function_signatures: Option<&'a BTreeMap<Tid, FunctionSignature>>,
+    ) -> AnalysisResults<'a> {
+        AnalysisResults {
+            function_signatures,
+            ..self
+        }
+    }
+
+    /// Compute the stack frame layouts of all functions from the pointer inference results,
+    /// which have to be set in the `AnalysisResults` struct.
+    /// The result gets returned, but not saved to the `AnalysisResults` struct itself.
+    pub fn compute_stack_frame_layouts(&self) -> BTreeMap<Tid, StackFrameLayout> {
+        crate::analysis::stack_frame_layout::compute_stack_frame_layouts(
+            self.pointer_inference.unwrap(),
+        )
+    }
+
+    /// Create a new `AnalysisResults` struct containing the given stack frame layouts.
+    pub fn set_stack_frame_layouts(
+        self,
+        stack_frame_layouts: Option<&'a BTreeMap<Tid, StackFrameLayout>>,
+    ) -> AnalysisResults<'a> {
+        AnalysisResults {
+            stack_frame_layouts,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::PointerInference as PointerInferenceComputation;
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    impl<'a> AnalysisResults<'a> {
+        /// Compute the pointer inference for the given project on a mock runtime memory image
+        /// and call `run` with analysis results containing it.
+        ///
+        /// Used by the tests of the checks to run them on a mock project.
+        pub fn run_with_mock_pointer_inference<T>(
+            project: &Project,
+            run: impl FnOnce(&AnalysisResults) -> T,
+        ) -> T {
+            let runtime_memory_image = RuntimeMemoryImage::mock();
+            let extern_subs = project
+                .program
+                .term
+                .extern_symbols
+                .keys()
+                .cloned()
+                .collect();
+            let graph = crate::analysis::graph::get_program_cfg(&project.program, extern_subs);
+            let mut pointer_inference =
+                PointerInferenceComputation::mock(project, &runtime_memory_image, &graph);
+            pointer_inference.compute();
+            let analysis_results =
+                AnalysisResults::new(&[], &runtime_memory_image, &graph, project)
+                    .set_pointer_inference(Some(&pointer_inference));
+            run(&analysis_results)
+        }
+    }
+
+    static SEEN_STRING_ABSTRACTIONS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+    fn record_string_abstraction(
+        analysis_results: &AnalysisResults,
+        _config: &serde_json::Value,
+    ) -> (Vec<LogMessage>, Vec<CweWarning>) {
+        let address = analysis_results
+            .string_abstraction
+            .map(|string_abstraction| string_abstraction as *const _ as usize)
+            .unwrap();
+        SEEN_STRING_ABSTRACTIONS.lock().unwrap().push(address);
+        (Vec::new(), Vec::new())
+    }
+
+    static FIRST_CONSUMER: CweModule = CweModule {
+        name: "FirstConsumer",
+        version: "0.1",
+        run: record_string_abstraction,
+        required_analyses: &[SharedAnalysis::StringAbstraction],
+        pointer_inference_retention: NodeValueRetention::All,
+    };
+
+    static SECOND_CONSUMER: CweModule = CweModule {
+        name: "SecondConsumer",
+        version: "0.1",
+        run: record_string_abstraction,
+        required_analyses: &[SharedAnalysis::StringAbstraction],
+        pointer_inference_retention: NodeValueRetention::All,
+    };
+
+    #[test]
+    fn required_analyses() {
+        let modules = get_modules();
+        let without_string_checks: Vec<&CweModule> = modules
+            .iter()
+            .copied()
+            .filter(|module| !["CWE78", "CWE117"].contains(&module.name))
+            .collect();
+        let required = get_required_analyses(&without_string_checks);
+        assert!(!required.contains(&SharedAnalysis::StringAbstraction));
+        assert!(required.contains(&SharedAnalysis::PointerInference));
+
+        let cwe_78: Vec<&CweModule> = modules
+            .iter()
+            .copied()
+            .filter(|module| module.name == "CWE78")
+            .collect();
+        assert_eq!(
+            get_required_analyses(&cwe_78),
+            BTreeSet::from([
+                SharedAnalysis::PointerInference,
+                SharedAnalysis::StringAbstraction
+            ])
+        );
+        assert!(get_required_analyses(&[]).is_empty());
+    }
+
+    #[test]
+    fn required_retention() {
+        let modules = get_modules();
+        let find_modules = |names: &[&str]| -> Vec<&CweModule> {
+            modules
+                .iter()
+                .copied()
+                .filter(|module| names.contains(&module.name))
+                .collect()
+        };
+        assert_eq!(
+            get_required_retention(&find_modules(&["CWE476", "CWE676"])),
+            NodeValueRetention::CallSitesAndAccesses
+        );
+        assert_eq!(
+            get_required_retention(&find_modules(&["CWE476", "CWE787"])),
+            NodeValueRetention::All
+        );
+        // CWE-78 needs all states through the string abstraction.
+        assert_eq!(
+            get_required_retention(&find_modules(&["CWE78"])),
+            NodeValueRetention::All
+        );
+        assert_eq!(
+            get_required_retention(&find_modules(&["Memory", "CWE676"])),
+            NodeValueRetention::EntryExitOnly
+        );
+        assert_eq!(
+            get_required_retention(&[]),
+            NodeValueRetention::EntryExitOnly
+        );
+    }
+
+    #[test]
+    fn string_abstraction_shared_between_modules() {
+        let project = Project::mock_empty();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(&project.program, HashSet::new());
+        let pointer_inference =
+            PointerInferenceComputation::mock(&project, &runtime_memory_image, &graph);
+        let analysis_results = AnalysisResults::new(&[], &runtime_memory_image, &graph, &project)
+            .set_pointer_inference(Some(&pointer_inference));
+        let string_abstraction = analysis_results.compute_string_abstraction(
+            &serde_json::json!({
+                "string_symbols": [],
+                "format_string_index": {}
+            }),
+            Some(&pointer_inference),
+        );
+        let analysis_results = analysis_results.set_string_abstraction(Some(&string_abstraction));
+
+        let (logs, cwes) = run_modules(
+            &[&FIRST_CONSUMER, &SECOND_CONSUMER],
+            &analysis_results,
+            &serde_json::json!({}),
+        );
+        assert!(logs.is_empty() && cwes.is_empty());
+        let seen = SEEN_STRING_ABSTRACTIONS.lock().unwrap();
+        assert_eq!(
+            *seen,
+            vec![
+                &string_abstraction as *const _ as usize,
+                &string_abstraction as *const _ as usize
+            ]
+        );
+    }
 }
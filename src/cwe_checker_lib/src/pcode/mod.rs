@@ -9,5 +9,7 @@
 
 mod expressions;
 pub use expressions::*;
+mod parsing;
+pub use parsing::*;
 mod term;
 pub use term::*;
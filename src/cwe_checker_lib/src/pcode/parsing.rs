@@ -0,0 +1,239 @@
+//! Resilient parsing of the JSON output of the P-Code-Extractor plugin.
+//!
+//! A single function whose terms cannot be deserialized should not prevent the analysis of the rest of the binary.
+//! Therefore the functions (and if necessary their basic blocks) are parsed individually.
+//! Functions and blocks that fail to parse are skipped and a log message naming them is generated.
+//! The parsing only fails as a whole if the top-level structure of the project cannot be parsed
+//! (e.g. because the JSON output was cut off)
+//! or if too many functions are unreadable to produce meaningful results.
+
+use super::{Blk, Project, Sub};
+use crate::prelude::*;
+use crate::utils::log::LogMessage;
+use serde_json::Value;
+
+/// The configuration for the parsing of the P-Code-Extractor output.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ParsingConfig {
+    /// If the fraction of functions that cannot be parsed exceeds this value,
+    /// the parsing fails instead of returning partial results.
+    #[serde(default = "default_max_unreadable_function_fraction")]
+    pub max_unreadable_function_fraction: f64,
+}
+
+impl Default for ParsingConfig {
+    fn default() -> Self {
+        ParsingConfig {
+            max_unreadable_function_fraction: default_max_unreadable_function_fraction(),
+        }
+    }
+}
+
+/// The default for the maximal fraction of unreadable functions.
+fn default_max_unreadable_function_fraction() -> f64 {
+    0.1
+}
+
+/// A project parsed from the P-Code-Extractor output
+/// together with the information about the terms that had to be skipped.
+#[derive(Debug, Clone)]
+pub struct ParsedProject {
+    /// The parsed project. It only contains the functions and blocks that could be parsed.
+    pub project: Project,
+    /// Log messages naming the skipped functions and blocks together with the corresponding parse errors.
+    pub log_messages: Vec<LogMessage>,
+    /// The TIDs (if known) of the functions that were skipped completely.
+    pub skipped_functions: Vec<Tid>,
+    /// The TIDs (if known) of the blocks that were removed from otherwise readable functions.
+    pub skipped_blocks: Vec<Tid>,
+}
+
+impl ParsedProject {
+    /// Returns `true` if some functions or blocks could not be parsed,
+    /// i.e. if analysis results computed on the project are only partial results.
+    pub fn is_partial(&self) -> bool {
+        !self.log_messages.is_empty()
+    }
+}
+
+impl Project {
+    /// Parse a project from the JSON output of the P-Code-Extractor plugin.
+    ///
+    /// Functions and blocks that cannot be parsed are skipped (see the [module-level documentation](self)).
+    /// Returns an error if the top-level structure is unreadable
+    /// or if the fraction of unreadable functions exceeds the bound given in the `config`.
+    pub fn parse_resilient(
+        reader: impl std::io::Read,
+        config: &ParsingConfig,
+    ) -> Result<ParsedProject, Error> {
+        let mut json: Value = serde_json::from_reader(reader)
+            .map_err(|err| anyhow!("The P-Code JSON is truncated or malformed: {}", err))?;
+        let subs_json = match json.pointer_mut("/program/term/subs") {
+            Some(Value::Array(subs)) => std::mem::take(subs),
+            _ => return Err(anyhow!("The P-Code JSON contains no list of functions.")),
+        };
+        let mut project: Project = serde_json::from_value(json)
+            .map_err(|err| anyhow!("Parsing of the P-Code project failed: {}", err))?;
+
+        let mut log_messages = Vec::new();
+        let mut skipped_functions = Vec::new();
+        let mut skipped_blocks = Vec::new();
+        let num_functions = subs_json.len();
+        let mut num_unreadable_functions = 0;
+        for sub_json in subs_json {
+            match parse_sub(sub_json) {
+                Ok((sub, unreadable_blocks)) => {
+                    for (block_tid, error) in unreadable_blocks {
+                        log_messages.push(
+                            LogMessage::new_error(format!(
+                                "Skipped unreadable block {} of function {}: {}",
+                                tid_or_unknown(&block_tid),
+                                sub.tid,
+                                error
+                            ))
+                            .location(sub.tid.clone())
+                            .source("P-Code Parsing"),
+                        );
+                        skipped_blocks.extend(block_tid);
+                    }
+                    project.program.term.subs.push(sub);
+                }
+                Err((sub_tid, error)) => {
+                    num_unreadable_functions += 1;
+                    let mut log_message = LogMessage::new_error(format!(
+                        "Skipped unreadable function {}: {}",
+                        tid_or_unknown(&sub_tid),
+                        error
+                    ))
+                    .source("P-Code Parsing");
+                    if let Some(tid) = sub_tid {
+                        log_message = log_message.location(tid.clone());
+                        skipped_functions.push(tid);
+                    }
+                    log_messages.push(log_message);
+                }
+            }
+        }
+        if num_unreadable_functions > 0
+            && num_unreadable_functions as f64
+                > config.max_unreadable_function_fraction * num_functions as f64
+        {
+            return Err(anyhow!(
+                "{} of {} functions in the P-Code JSON are unreadable.",
+                num_unreadable_functions,
+                num_functions
+            ));
+        }
+        Ok(ParsedProject {
+            project,
+            log_messages,
+            skipped_functions,
+            skipped_blocks,
+        })
+    }
+}
+
+/// Parse a single function.
+///
+/// If the function as a whole cannot be parsed, its blocks are parsed individually
+/// and the unreadable blocks are returned together with their TIDs (if known) and parse errors.
+/// If even the function without its blocks cannot be parsed,
+/// the TID of the function (if known) and the parse error are returned as the error value.
+#[allow(clippy::type_complexity)]
+fn parse_sub(
+    mut sub_json: Value,
+) -> Result<(Term<Sub>, Vec<(Option<Tid>, serde_json::Error)>), (Option<Tid>, serde_json::Error)> {
+    if let Ok(sub) = serde_json::from_value(sub_json.clone()) {
+        return Ok((sub, Vec::new()));
+    }
+    let sub_tid = get_tid(&sub_json);
+    let blocks_json = match sub_json.pointer_mut("/term/blocks") {
+        Some(Value::Array(blocks)) => std::mem::take(blocks),
+        _ => Vec::new(),
+    };
+    let mut sub: Term<Sub> =
+        serde_json::from_value(sub_json).map_err(|err| (sub_tid.clone(), err))?;
+    let mut skipped_blocks = Vec::new();
+    for block_json in blocks_json {
+        let block_tid = get_tid(&block_json);
+        match serde_json::from_value::<Term<Blk>>(block_json) {
+            Ok(block) => sub.term.blocks.push(block),
+            Err(err) => skipped_blocks.push((block_tid, err)),
+        }
+    }
+    Ok((sub, skipped_blocks))
+}
+
+/// Get the TID of a term in its JSON representation if the TID itself is readable.
+fn get_tid(term_json: &Value) -> Option<Tid> {
+    serde_json::from_value(term_json.get("tid")?.clone()).ok()
+}
+
+/// Format an optional TID for log messages.
+fn tid_or_unknown(tid: &Option<Tid>) -> String {
+    match tid {
+        Some(tid) => format!("{}", tid),
+        None => "<unknown TID>".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project with three functions, where the first function contains a block with a corrupted `Def`
+    /// and the third function has a corrupted name.
+    const PARTIALLY_CORRUPTED_FIXTURE: &str = include_str!("fixtures/partially_corrupted.json");
+
+    #[test]
+    fn skip_corrupted_terms() {
+        let config = ParsingConfig {
+            max_unreadable_function_fraction: 0.5,
+        };
+        let parsed =
+            Project::parse_resilient(PARTIALLY_CORRUPTED_FIXTURE.as_bytes(), &config).unwrap();
+        assert!(parsed.is_partial());
+        let subs = &parsed.project.program.term.subs;
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[0].tid.to_string(), "sub_00101000");
+        assert_eq!(subs[0].term.blocks.len(), 1);
+        assert_eq!(subs[0].term.blocks[0].tid.to_string(), "blk_00101000");
+        assert_eq!(subs[1].tid.to_string(), "sub_00102000");
+        assert_eq!(parsed.skipped_blocks[0].to_string(), "blk_00101010");
+        assert_eq!(parsed.skipped_functions[0].to_string(), "sub_00103000");
+        assert_eq!(parsed.log_messages.len(), 2);
+        assert!(parsed.log_messages[0].text.contains("blk_00101010"));
+        assert!(parsed.log_messages[1].text.contains("sub_00103000"));
+        // The rest of the project can still be converted to the IR.
+        let ir_project = parsed.project.into_ir_project(0x10000);
+        assert_eq!(ir_project.program.term.subs.len(), 2);
+
+        // One of three functions is unreadable, which exceeds the configured bound.
+        let config = ParsingConfig {
+            max_unreadable_function_fraction: 0.25,
+        };
+        assert!(Project::parse_resilient(PARTIALLY_CORRUPTED_FIXTURE.as_bytes(), &config).is_err());
+    }
+
+    #[test]
+    fn truncated_or_valid_input() {
+        let config = ParsingConfig::default();
+        let truncated = &PARTIALLY_CORRUPTED_FIXTURE[..PARTIALLY_CORRUPTED_FIXTURE.len() / 2];
+        assert!(Project::parse_resilient(truncated.as_bytes(), &config).is_err());
+
+        let mut valid_json: Value = serde_json::from_str(PARTIALLY_CORRUPTED_FIXTURE).unwrap();
+        let subs = valid_json
+            .pointer_mut("/program/term/subs")
+            .unwrap()
+            .as_array_mut()
+            .unwrap();
+        subs.truncate(2);
+        subs[0]["term"]["blocks"]
+            .as_array_mut()
+            .unwrap()
+            .truncate(1);
+        let parsed = Project::parse_resilient(valid_json.to_string().as_bytes(), &config).unwrap();
+        assert!(!parsed.is_partial());
+        assert_eq!(parsed.project.program.term.subs.len(), 2);
+    }
+}
@@ -1,7 +1,8 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::usize;
 
 use super::{Expression, ExpressionType, RegisterProperties, Variable};
+use crate::intermediate_representation::Arch;
 use crate::intermediate_representation::Arg as IrArg;
 use crate::intermediate_representation::Blk as IrBlk;
 use crate::intermediate_representation::ByteSize;
@@ -14,10 +15,13 @@ use crate::intermediate_representation::Jmp as IrJmp;
 use crate::intermediate_representation::Program as IrProgram;
 use crate::intermediate_representation::Project as IrProject;
 use crate::intermediate_representation::Sub as IrSub;
+use crate::intermediate_representation::VarargsLayout;
 use crate::intermediate_representation::Variable as IrVariable;
 use crate::prelude::*;
 use crate::utils::log::LogMessage;
 
+mod extern_symbol_collisions;
+
 // TODO: Handle the case where an indirect tail call is represented by CALLIND plus RETURN
 
 // TODO: Since we do not support BAP anymore, this module should be refactored
@@ -380,6 +384,7 @@ impl Term<Sub> {
                 name: self.term.name,
                 blocks,
                 calling_convention: self.term.calling_convention,
+                is_thunk: false,
             },
         }
     }
@@ -392,6 +397,7 @@ pub struct ExternSymbol {
     pub tid: Tid,
     /// The addresses to call the extern symbol.
     /// May be more than one, since we also identify thunk functions calling the extern symbol with the symbol itself.
+    /// See [`Program::resolve_extern_symbol_collisions`] for addresses claimed by several extern symbols.
     pub addresses: Vec<String>,
     /// The name of the extern symbol.
     pub name: String,
@@ -413,10 +419,10 @@ impl ExternSymbol {
         &mut self,
         conventions: &[CallingConvention],
         stack_pointer: &Variable,
-        cpu_arch: &str,
+        architecture: &Arch,
     ) {
         let mut args: Vec<Arg> = Vec::new();
-        if cpu_arch == "x86_32" {
+        if *architecture == Arch::X86_32 {
             args.push(ExternSymbol::create_stack_arg(stack_pointer, 0));
             if self.name == "sscanf" || self.name == "__isoc99_sscanf" {
                 args.push(ExternSymbol::create_stack_arg(
@@ -517,7 +523,7 @@ impl ExternSymbol {
         self,
         conventions: &[CallingConvention],
         stack_pointer: &Variable,
-        cpu_arch: &str,
+        architecture: &Arch,
     ) -> IrExternSymbol {
         let mut symbol = self.clone();
         let mut parameters = Vec::new();
@@ -530,7 +536,7 @@ impl ExternSymbol {
             symbol.create_format_string_args_for_scanf_and_sscanf(
                 conventions,
                 stack_pointer,
-                cpu_arch,
+                architecture,
             );
         }
         for arg in symbol.arguments.iter() {
@@ -609,7 +615,7 @@ impl Program {
         binary_base_address: u64,
         conventions: &[CallingConvention],
         stack_pointer: &Variable,
-        cpu_arch: &str,
+        architecture: &Arch,
     ) -> IrProgram {
         let subs = self
             .subs
@@ -622,7 +628,7 @@ impl Program {
             .map(|symbol| {
                 (
                     symbol.tid.clone(),
-                    symbol.into_ir_symbol(conventions, stack_pointer, cpu_arch),
+                    symbol.into_ir_symbol(conventions, stack_pointer, architecture),
                 )
             })
             .collect();
@@ -633,6 +639,8 @@ impl Program {
             extern_symbols,
             entry_points: self.entry_points.into_iter().collect(),
             address_base_offset,
+            symbol_aliases: BTreeMap::new(),
+            non_returning_subs: BTreeSet::new(),
         }
     }
 }
@@ -659,9 +667,12 @@ pub struct CallingConvention {
 
 impl CallingConvention {
     /// Convert a calling convention parsed from Ghidra to the internally used IR.
+    ///
+    /// The layout of the register save area for variadic functions is derived from the CPU architecture.
     fn into_ir_cconv(
         self,
         register_map: &HashMap<&String, &RegisterProperties>,
+        architecture: &Arch,
     ) -> IrCallingConvention {
         let to_ir_var_list = |list: Vec<String>| {
             list.into_iter()
@@ -693,9 +704,15 @@ impl CallingConvention {
                 .collect();
             register_set.into_iter().collect()
         };
+        let integer_parameter_register: Vec<IrVariable> =
+            to_ir_var_list(self.integer_parameter_register);
         IrCallingConvention {
             name: self.name,
-            integer_parameter_register: to_ir_var_list(self.integer_parameter_register),
+            varargs_layout: VarargsLayout::for_architecture(
+                architecture,
+                &integer_parameter_register,
+            ),
+            integer_parameter_register,
             float_parameter_register: to_ir_expression_list(self.float_parameter_register),
             integer_return_register: to_ir_var_list(self.return_register),
             float_return_register: to_ir_expression_list(self.float_return_register),
@@ -705,6 +722,55 @@ impl CallingConvention {
             callee_saved_register: to_ir_base_var_list(self.unaffected_register),
         }
     }
+
+    /// Return the standard calling convention of the given architecture
+    /// for architectures where the calling conventions provided by Ghidra may be incomplete.
+    ///
+    /// Currently only the standard calling convention of RISC-V (for the `lp64d` and `ilp32d` ABIs) is known.
+    fn standard_for_architecture(architecture: &Arch) -> Option<CallingConvention> {
+        let registers = |prefix: &str, range: std::ops::Range<usize>| -> Vec<String> {
+            range.map(|i| format!("{}{}", prefix, i)).collect()
+        };
+        match architecture {
+            Arch::RiscV => Some(CallingConvention {
+                name: "__stdcall".to_string(),
+                integer_parameter_register: registers("a", 0..8),
+                float_parameter_register: registers("fa", 0..8),
+                return_register: registers("a", 0..2),
+                float_return_register: registers("fa", 0..2),
+                unaffected_register: [
+                    registers("s", 0..12),
+                    registers("fs", 0..12),
+                    vec![architecture.stack_pointer_register_name()?.to_string()],
+                ]
+                .concat(),
+                killed_by_call_register: [
+                    registers("a", 0..8),
+                    registers("t", 0..7),
+                    registers("fa", 0..8),
+                    registers("ft", 0..12),
+                    vec!["ra".to_string()],
+                ]
+                .concat(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if all registers of the calling convention are contained in the given register map.
+    fn is_defined_for(&self, register_map: &HashMap<&String, &RegisterProperties>) -> bool {
+        [
+            &self.integer_parameter_register,
+            &self.float_parameter_register,
+            &self.return_register,
+            &self.float_return_register,
+            &self.unaffected_register,
+            &self.killed_by_call_register,
+        ]
+        .into_iter()
+        .flatten()
+        .all(|register| register_map.contains_key(register))
+    }
 }
 
 /// The project struct describing all known information about the binary.
@@ -729,19 +795,34 @@ impl Project {
     ///
     /// The `binary_base_address` denotes the base address of the memory image of the binary
     /// according to the program headers of the binary.
-    pub fn into_ir_project(self, binary_base_address: u64) -> IrProject {
+    /// If Ghidra provided no standard calling convention for the architecture,
+    /// the standard calling convention known for the architecture is added.
+    pub fn into_ir_project(mut self, binary_base_address: u64) -> IrProject {
+        let architecture = Arch::from_cpu_string(&self.cpu_architecture);
         let register_map: HashMap<&String, &RegisterProperties> = self
             .register_properties
             .iter()
             .map(|p| (&p.register, p))
             .collect();
+        // Add the standard calling convention of the architecture if Ghidra did not provide one.
+        if !self
+            .register_calling_convention
+            .iter()
+            .any(|cconv| cconv.name == "__stdcall" || cconv.name == "__cdecl")
+        {
+            if let Some(cconv) = CallingConvention::standard_for_architecture(&architecture)
+                .filter(|cconv| cconv.is_defined_for(&register_map))
+            {
+                self.register_calling_convention.push(cconv);
+            }
+        }
         let mut program: Term<IrProgram> = Term {
             tid: self.program.tid,
             term: self.program.term.into_ir_program(
                 binary_base_address,
                 &self.register_calling_convention,
                 &self.stack_pointer_register,
-                &self.cpu_architecture,
+                &architecture,
             ),
         };
         let mut zero_extend_tids: HashSet<Tid> = HashSet::new();
@@ -861,15 +942,23 @@ impl Project {
             .register_calling_convention
             .clone()
             .into_iter()
-            .map(|cconv| (cconv.name.clone(), cconv.into_ir_cconv(&register_map)))
+            .map(|cconv| {
+                (
+                    cconv.name.clone(),
+                    cconv.into_ir_cconv(&register_map, &architecture),
+                )
+            })
             .collect();
         IrProject {
             program,
             cpu_architecture: self.cpu_architecture,
+            architecture,
             stack_pointer_register: self.stack_pointer_register.into(),
             calling_conventions,
             register_set,
             datatype_properties: self.datatype_properties.clone(),
+            stack_canary_artifacts: BTreeSet::new(),
+            duplicated_term_origins: BTreeMap::new(),
         }
     }
 }
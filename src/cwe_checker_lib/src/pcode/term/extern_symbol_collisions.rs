@@ -0,0 +1,227 @@
+use super::*;
+use crate::utils::binary::ElfSymbolInfo;
+
+impl Program {
+    /// Resolve extern symbols claiming the same address,
+    /// so that each address (and each term identifier) belongs to exactly one extern symbol.
+    ///
+    /// Ghidra may generate several extern symbols for the same address, e.g. if a thunk function is reused,
+    /// or list the same address twice for one symbol.
+    /// Since calls to extern symbols are matched to the symbols via their term identifiers,
+    /// which are derived from the addresses, such collisions would otherwise be resolved arbitrarily
+    /// when converting the extern symbols into a map.
+    ///
+    /// For each contested address the winner is determined by the following priority:
+    /// 1. Symbols whose name exactly matches a symbol referenced by a PLT relocation.
+    /// 2. Symbols appearing earlier in the import table (i.e. the dynamic symbol table).
+    /// 3. The symbol with the lexicographically smallest name (and term identifier) as deterministic tie-break.
+    ///
+    /// The contested address is removed from the addresses of all other symbols.
+    /// Symbols with the same term identifier as the winner are removed.
+    /// A log message listing the colliding symbols and the winner is generated for each collision.
+    #[must_use]
+    pub fn resolve_extern_symbol_collisions(
+        &mut self,
+        symbol_info: &ElfSymbolInfo,
+    ) -> Vec<LogMessage> {
+        let mut logs = Vec::new();
+        let mut claimants: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (index, symbol) in self.extern_symbols.iter_mut().enumerate() {
+            let mut unique_addresses = Vec::new();
+            for address in symbol.addresses.drain(..) {
+                if unique_addresses.contains(&address) {
+                    logs.push(
+                        LogMessage::new_debug(format!(
+                            "Extern symbol {} lists the address {} more than once.",
+                            symbol.name, address
+                        ))
+                        .location(symbol.tid.clone()),
+                    );
+                } else {
+                    unique_addresses.push(address);
+                }
+            }
+            symbol.addresses = unique_addresses;
+            let mut claimed_addresses: BTreeSet<&String> = symbol.addresses.iter().collect();
+            claimed_addresses.insert(&symbol.tid.address);
+            for address in claimed_addresses {
+                claimants.entry(address.clone()).or_default().push(index);
+            }
+        }
+        let priority = |symbol: &ExternSymbol| {
+            (
+                !symbol_info.plt_relocation_symbols.contains(&symbol.name),
+                symbol_info
+                    .imported_symbols
+                    .iter()
+                    .position(|name| *name == symbol.name)
+                    .unwrap_or(usize::MAX),
+                symbol.name.clone(),
+                symbol.tid.clone(),
+            )
+        };
+        let mut removed_symbols = BTreeSet::new();
+        for (address, indices) in claimants {
+            let indices: Vec<usize> = indices
+                .into_iter()
+                .filter(|index| !removed_symbols.contains(index))
+                .collect();
+            if indices.len() < 2 {
+                continue;
+            }
+            let winner = *indices
+                .iter()
+                .min_by_key(|index| priority(&self.extern_symbols[**index]))
+                .unwrap();
+            let winner_tid = self.extern_symbols[winner].tid.clone();
+            let mut names: Vec<&str> = indices
+                .iter()
+                .map(|index| self.extern_symbols[*index].name.as_str())
+                .collect();
+            names.sort_unstable();
+            logs.push(
+                LogMessage::new_info(format!(
+                    "Extern symbols {} share the address {}. Calls to it are attributed to {}.",
+                    names.join(", "),
+                    address,
+                    self.extern_symbols[winner].name
+                ))
+                .location(winner_tid.clone()),
+            );
+            for index in indices.into_iter().filter(|index| *index != winner) {
+                let loser = &mut self.extern_symbols[index];
+                loser
+                    .addresses
+                    .retain(|loser_address| *loser_address != address);
+                if loser.tid == winner_tid {
+                    removed_symbols.insert(index);
+                }
+            }
+        }
+        let mut index = 0;
+        self.extern_symbols.retain(|_| {
+            index += 1;
+            !removed_symbols.contains(&(index - 1))
+        });
+        logs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an extern symbol with the given name whose term identifier is derived from the first address.
+    fn mock_symbol(name: &str, addresses: &[&str]) -> ExternSymbol {
+        let mut tid = Tid::new(format!("sub_{}", addresses[0]));
+        tid.address = addresses[0].to_string();
+        ExternSymbol {
+            tid,
+            addresses: addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect(),
+            name: name.to_string(),
+            calling_convention: None,
+            arguments: Vec::new(),
+            no_return: false,
+            has_var_args: false,
+        }
+    }
+
+    fn mock_program(symbols: Vec<ExternSymbol>) -> Program {
+        Program {
+            subs: Vec::new(),
+            extern_symbols: symbols,
+            entry_points: Vec::new(),
+            image_base: "0".to_string(),
+        }
+    }
+
+    fn symbol_names(program: &Program) -> Vec<&str> {
+        program
+            .extern_symbols
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn deterministic_tie_break() {
+        for symbols in [
+            vec![
+                mock_symbol("free", &["00101030"]),
+                mock_symbol("do_free", &["00101030"]),
+            ],
+            vec![
+                mock_symbol("do_free", &["00101030"]),
+                mock_symbol("free", &["00101030"]),
+            ],
+        ] {
+            let mut program = mock_program(symbols);
+            let logs = program.resolve_extern_symbol_collisions(&ElfSymbolInfo::default());
+            assert_eq!(symbol_names(&program), vec!["do_free"]);
+            assert_eq!(logs.len(), 1);
+            assert_eq!(
+                logs[0].text,
+                "Extern symbols do_free, free share the address 00101030. Calls to it are attributed to do_free."
+            );
+            assert_eq!(
+                logs[0].location,
+                Some(program.extern_symbols[0].tid.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn relocations_and_import_order_take_precedence() {
+        let symbols = vec![
+            mock_symbol("do_free", &["00101030"]),
+            mock_symbol("free", &["00101030"]),
+            mock_symbol("xfree", &["00101030"]),
+        ];
+        let mut symbol_info = ElfSymbolInfo {
+            imported_symbols: vec!["xfree".to_string(), "free".to_string()],
+            ..ElfSymbolInfo::default()
+        };
+        let mut program = mock_program(symbols.clone());
+        let _ = program.resolve_extern_symbol_collisions(&symbol_info);
+        assert_eq!(symbol_names(&program), vec!["xfree"]);
+
+        symbol_info.plt_relocation_symbols = BTreeSet::from(["free".to_string()]);
+        let mut program = mock_program(symbols);
+        let _ = program.resolve_extern_symbol_collisions(&symbol_info);
+        assert_eq!(symbol_names(&program), vec!["free"]);
+    }
+
+    #[test]
+    fn shared_thunk_address() {
+        // `malloc` is also reachable through the thunk at 00101050, which Ghidra attributed to `free` as well.
+        let mut program = mock_program(vec![
+            mock_symbol("malloc", &["00101040", "00101050", "00101050"]),
+            mock_symbol("free", &["00101030", "00101050"]),
+        ]);
+        let symbol_info = ElfSymbolInfo {
+            plt_relocation_symbols: BTreeSet::from(["malloc".to_string()]),
+            ..ElfSymbolInfo::default()
+        };
+        let logs = program.resolve_extern_symbol_collisions(&symbol_info);
+        assert_eq!(symbol_names(&program), vec!["malloc", "free"]);
+        assert_eq!(
+            program.extern_symbols[0].addresses,
+            vec!["00101040".to_string(), "00101050".to_string()]
+        );
+        assert_eq!(
+            program.extern_symbols[1].addresses,
+            vec!["00101030".to_string()]
+        );
+        assert_eq!(logs.len(), 2);
+        assert_eq!(
+            logs[0].text,
+            "Extern symbol malloc lists the address 00101050 more than once."
+        );
+        assert!(logs[1]
+            .text
+            .contains("Calls to it are attributed to malloc."));
+    }
+}
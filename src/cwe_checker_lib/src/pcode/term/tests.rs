@@ -627,7 +627,7 @@ fn extern_symbol_deserialization() {
     let _: IrExternSymbol = symbol.into_ir_symbol(
         &setup.project.register_calling_convention,
         &setup.project.stack_pointer_register,
-        &setup.project.cpu_architecture,
+        &Arch::from_cpu_string(&setup.project.cpu_architecture),
     );
 }
 
@@ -655,7 +655,7 @@ fn program_deserialization() {
         10000,
         &setup.project.register_calling_convention,
         &setup.project.stack_pointer_register,
-        &setup.project.cpu_architecture,
+        &Arch::from_cpu_string(&setup.project.cpu_architecture),
     );
 }
 
@@ -666,6 +666,54 @@ fn project_deserialization() {
     let _: IrProject = project.into_ir_project(10000);
 }
 
+#[test]
+fn standard_calling_convention_for_riscv() {
+    let mut project: Project = Setup::new().project;
+    project.cpu_architecture = "RISCV:LE:64:RV64GC".to_string();
+    project.stack_pointer_register = Variable {
+        name: Some("sp".to_string()),
+        value: None,
+        address: None,
+        size: ByteSize::new(8),
+        is_virtual: false,
+    };
+    let register_names = (0..8)
+        .flat_map(|i| [format!("a{}", i), format!("fa{}", i)])
+        .chain((0..12).flat_map(|i| [format!("s{}", i), format!("fs{}", i), format!("ft{}", i)]))
+        .chain((0..7).map(|i| format!("t{}", i)))
+        .chain(["sp".to_string(), "ra".to_string()]);
+    project.register_properties = register_names
+        .map(|name| RegisterProperties {
+            register: name.clone(),
+            base_register: name,
+            lsb: ByteSize::new(0),
+            size: ByteSize::new(8),
+        })
+        .collect();
+    let ir_project = project.clone().into_ir_project(10000);
+    let cconv = ir_project.get_standard_calling_convention().unwrap();
+    assert_eq!(cconv.integer_parameter_register.len(), 8);
+    assert_eq!(
+        cconv.integer_parameter_register[0],
+        IrVariable::mock("a0", 8)
+    );
+    assert_eq!(cconv.float_parameter_register.len(), 8);
+    assert!(cconv
+        .callee_saved_register
+        .contains(&IrVariable::mock("s11", 8)));
+    assert_eq!(
+        cconv.varargs_layout.as_ref().unwrap().save_area_size,
+        ByteSize::new(64)
+    );
+    assert_eq!(ir_project.stack_pointer_register, IrVariable::mock("sp", 8));
+
+    // The calling conventions provided by Ghidra take precedence.
+    project.register_calling_convention[0].name = "__stdcall".to_string();
+    let ir_project = project.into_ir_project(10000);
+    let cconv = ir_project.get_standard_calling_convention().unwrap();
+    assert!(cconv.integer_parameter_register.is_empty());
+}
+
 #[test]
 fn add_load_defs_for_implicit_ram_access() {
     let mut blk: Blk = Blk {
@@ -10,6 +10,9 @@ use crate::{
 use regex::Regex;
 use std::collections::HashMap;
 
+pub mod forwarding;
+pub mod local_simulation;
+
 /// Parses the input format string for the corresponding string function.
 pub fn get_input_format_string(
     pi_state: &PointerInferenceState,
@@ -58,48 +61,174 @@ pub fn parse_format_string_destination_and_return_content(
     ))
 }
 
+/// The names of input functions like `scanf`,
+/// whose variadic parameters are pointers to the locations that the parsed values are written to.
+const INPUT_FUNCTION_SYMBOLS: [&str; 6] = [
+    "scanf",
+    "sscanf",
+    "fscanf",
+    "__isoc99_scanf",
+    "__isoc99_sscanf",
+    "__isoc99_fscanf",
+];
+
+/// Returns whether the symbol with the given name is an input function like `scanf`.
+pub fn is_input_function(symbol_name: &str) -> bool {
+    INPUT_FUNCTION_SYMBOLS.contains(&symbol_name)
+}
+
+/// Parses the conversion specifiers of a format string using a regex
+/// and returns their data types in the order of the corresponding variadic parameters,
+/// together with flags indicating whether the parameter is a string printed without a precision (e.g. `%s`).
+///
+/// Positional arguments (e.g. `%2$s`) are sorted by their position.
+/// For input functions like `scanf` the flag `*` suppresses the assignment,
+/// so that the conversion has no corresponding parameter.
+/// For output functions like `printf` the flag `*` denotes that the field width is passed as an additional integer parameter.
+/// The same holds for a precision given as `.*`.
+fn parse_conversion_specifiers(
+    format_string: &str,
+    is_input_function: bool,
+) -> Result<Vec<(Datatype, bool)>, Error> {
+    let re = Regex::new(r#"%%|%(?:(?P<position>\d+)\$)?(?P<star>\*)?\d*(?:\.(?P<precision>\d*|\*))?(?P<specifier>[cCdiouxXeEfFgGaAnpsS]|hi|hd|hu|li|ld|lu|lli|lld|llu|lf|lg|le|la|lF|lG|lE|lA|Lf|Lg|Le|La|LF|LG|LE|LA|\[\^?\]?[^\]]*\])"#)
+        .expect("No valid regex!");
+
+    let mut conversions: Vec<(Option<usize>, (Datatype, bool))> = Vec::new();
+    for cap in re.captures_iter(format_string) {
+        let specifier = match cap.name("specifier") {
+            Some(specifier) => specifier.as_str(),
+            None => continue, // An escaped percent sign
+        };
+        let position = match cap.name("position") {
+            Some(position) => Some(position.as_str().parse::<usize>()?),
+            None => None,
+        };
+        if cap.name("star").is_some() {
+            if is_input_function {
+                continue;
+            }
+            if position.is_some() {
+                return Err(anyhow!(
+                    "Field widths of positional arguments cannot be parsed yet."
+                ));
+            }
+            conversions.push((None, (Datatype::Integer, false)));
+        }
+        let precision = cap.name("precision").map(|precision| precision.as_str());
+        if precision == Some("*") {
+            if position.is_some() {
+                return Err(anyhow!(
+                    "Precisions of positional arguments cannot be parsed yet."
+                ));
+            }
+            conversions.push((None, (Datatype::Integer, false)));
+        }
+        let data_type = if specifier.starts_with('[') {
+            Datatype::Pointer
+        } else if is_input_function && specifier == "n" {
+            // `%n` stores the number of consumed characters in an integer.
+            Datatype::Integer
+        } else {
+            Datatype::from(specifier.to_string())
+        };
+        let is_unbounded_string = matches!(specifier, "s" | "S") && precision.is_none();
+        conversions.push((position, (data_type, is_unbounded_string)));
+    }
+
+    if conversions.iter().all(|(position, _)| position.is_none()) {
+        return Ok(conversions
+            .into_iter()
+            .map(|(_, data_type)| data_type)
+            .collect());
+    }
+    let mut positional_conversions: Vec<(usize, (Datatype, bool))> = Vec::new();
+    for (position, data_type) in conversions {
+        match position {
+            Some(position) => positional_conversions.push((position, data_type)),
+            None => {
+                return Err(anyhow!(
+                    "Positional and non-positional arguments cannot be mixed."
+                ))
+            }
+        }
+    }
+    positional_conversions.sort_by_key(|(position, _)| *position);
+    // A parameter may be referenced by more than one conversion specifier.
+    positional_conversions.dedup_by_key(|(position, _)| *position);
+    for (index, (position, _)) in positional_conversions.iter().enumerate() {
+        if *position != index + 1 {
+            return Err(anyhow!("Missing positional argument {}.", index + 1));
+        }
+    }
+
+    Ok(positional_conversions
+        .into_iter()
+        .map(|(_, data_type)| data_type)
+        .collect())
+}
+
 /// Parses the format string parameters using a regex, determines their data types,
 /// and calculates their positions (register or memory).
 pub fn parse_format_string_parameters(
     format_string: &str,
     datatype_properties: &DatatypeProperties,
 ) -> Result<Vec<(Datatype, ByteSize)>, Error> {
-    let re = Regex::new(r#"%\d{0,2}(([c,C,d,i,o,u,x,X,e,E,f,F,g,G,a,A,n,p,s,S])|(hi|hd|hu|li|ld|lu|lli|lld|llu|lf|lg|le|la|lF|lG|lE|lA|Lf|Lg|Le|La|LF|LG|LE|LA))"#)
-        .expect("No valid regex!");
+    let datatype_map: Vec<(Datatype, ByteSize)> =
+        parse_conversion_specifiers(format_string, false)?
+            .into_iter()
+            .map(|(data_type, _)| {
+                let size = {
+                    // Considers argument promotion for char type
+                    if matches!(data_type, Datatype::Char) {
+                        datatype_properties.get_size_from_data_type(Datatype::Integer)
+                    } else {
+                        datatype_properties.get_size_from_data_type(data_type.clone())
+                    }
+                };
+                (data_type, size)
+            })
+            .collect();
 
-    let datatype_map: Vec<(Datatype, ByteSize)> = re
-        .captures_iter(format_string)
-        .map(|cap| {
-            let data_type = Datatype::from(cap[1].to_string());
-            let size = {
-                // Considers argument promotion for char type
-                if matches!(data_type, Datatype::Char) {
-                    datatype_properties.get_size_from_data_type(Datatype::Integer)
-                } else {
-                    datatype_properties.get_size_from_data_type(data_type.clone())
-                }
-            };
-            (data_type, size)
-        })
-        .collect();
-
-    let data_type_not_yet_parsable = datatype_map.iter().any(|(data_type, _)| {
-        matches!(
-            data_type,
-            Datatype::Long | Datatype::LongLong | Datatype::LongDouble
-        )
-    });
+    let data_type_not_yet_parsable = datatype_map
+        .iter()
+        .any(|(data_type, _)| matches!(data_type, Datatype::Long | Datatype::LongLong));
 
     if data_type_not_yet_parsable {
         return Err(anyhow!(
-            "Data types: long, long long and long double, cannot be parsed yet."
+            "Data types: long and long long cannot be parsed yet."
         ));
     }
 
     Ok(datatype_map)
 }
 
+/// Parses the format string of an input function like `scanf`
+/// and returns the data types of the values written by the function in the order of the variadic parameters.
+/// The data type `Datatype::Pointer` denotes a string conversion (e.g. `%s`).
+///
+/// Conversions with suppressed assignment (e.g. `%*d`) have no corresponding parameter and are omitted.
+pub fn parse_input_format_string_conversions(format_string: &str) -> Result<Vec<Datatype>, Error> {
+    Ok(parse_conversion_specifiers(format_string, true)?
+        .into_iter()
+        .map(|(data_type, _)| data_type)
+        .collect())
+}
+
+/// Parses the format string of an output function like `printf`
+/// and returns the indices of the variadic parameters printed by string conversions without a precision (e.g. `%s`),
+/// i.e. of the strings that are printed completely regardless of their length.
+pub fn get_unbounded_string_parameter_indices(format_string: &str) -> Result<Vec<usize>, Error> {
+    Ok(parse_conversion_specifiers(format_string, false)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, (_, is_unbounded_string))| is_unbounded_string.then_some(index))
+        .collect())
+}
+
 /// Returns an argument vector of detected variable parameters.
+///
+/// For input functions like `scanf` (see [`is_input_function`])
+/// all variadic parameters are pointer-sized arguments of type `Datatype::Pointer`.
 pub fn get_variable_parameters(
     project: &Project,
     pi_state: &PointerInferenceState,
@@ -120,8 +249,16 @@ pub fn get_variable_parameters(
     );
 
     if let Ok(format_string) = format_string_results.as_ref() {
-        let parameter_result =
-            parse_format_string_parameters(format_string, &project.datatype_properties);
+        let parameter_result = if is_input_function(&extern_symbol.name) {
+            parse_input_format_string_conversions(format_string).map(|conversions| {
+                conversions
+                    .into_iter()
+                    .map(|_| (Datatype::Pointer, project.datatype_properties.pointer_size))
+                    .collect()
+            })
+        } else {
+            parse_format_string_parameters(format_string, &project.datatype_properties)
+        };
         match parameter_result {
             Ok(parameters) => {
                 return Ok(calculate_parameter_locations(
@@ -129,7 +266,7 @@ pub fn get_variable_parameters(
                     project.get_calling_convention(extern_symbol),
                     format_string_index,
                     &project.stack_pointer_register,
-                    &project.cpu_architecture,
+                    &project.architecture,
                 ));
             }
             Err(e) => {
@@ -144,80 +281,261 @@ pub fn get_variable_parameters(
     ))
 }
 
+/// Returns the variadic parameters of a call to an input function like `scanf`
+/// together with the data types of the values written to the locations they point to
+/// (see [`parse_input_format_string_conversions`]).
+pub fn get_input_function_parameters(
+    project: &Project,
+    pi_state: &PointerInferenceState,
+    extern_symbol: &ExternSymbol,
+    format_string_index_map: &HashMap<String, usize>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Result<Vec<(Arg, Datatype)>, Error> {
+    let format_string_index = match format_string_index_map.get(&extern_symbol.name) {
+        Some(index) => *index,
+        None => panic!("External Symbol does not contain a format string parameter."),
+    };
+    let format_string = get_input_format_string(
+        pi_state,
+        extern_symbol,
+        format_string_index,
+        runtime_memory_image,
+    )?;
+    let conversions = parse_input_format_string_conversions(&format_string)?;
+    let parameters = get_variable_parameters(
+        project,
+        pi_state,
+        extern_symbol,
+        format_string_index_map,
+        runtime_memory_image,
+    )?;
+
+    Ok(parameters.into_iter().zip(conversions).collect())
+}
+
 /// Calculates the register and stack positions of format string parameters.
 /// The parameters are then returned as an argument vector for later tainting.
+///
+/// The parameter registers usable for variadic arguments are determined by
+/// [`CallingConvention::varargs_arg_location`].
+/// If no float parameter registers are usable for variadic arguments (e.g. on soft-float targets),
+/// floating point parameters are passed in integer registers instead (see [`allocate_soft_float_arg`]).
+/// The same holds for all variadic parameters on 32-bit ARM,
+/// even if the calling convention has float parameter registers (hard-float variant of the AAPCS).
+///
+/// The sizes and alignments of the stack slots of parameters passed on the stack
+/// depend on their data types and the architecture (see [`get_stack_slot_layout`]).
+/// On x86_64 `long double` parameters are always passed on the stack.
 pub fn calculate_parameter_locations(
     parameters: Vec<(Datatype, ByteSize)>,
     calling_convention: &CallingConvention,
     format_string_index: usize,
     stack_register: &Variable,
-    cpu_arch: &str,
+    architecture: &Arch,
 ) -> Vec<Arg> {
     let mut var_args: Vec<Arg> = Vec::new();
-    // The number of the remaining integer argument registers are calculated
-    // from the format string position since it is the last fixed argument.
-    let mut integer_arg_register_count =
-        calling_convention.integer_parameter_register.len() - (format_string_index + 1);
-    let mut float_arg_register_count = calling_convention.float_parameter_register.len();
-    let mut stack_offset: i64 = match cpu_arch {
-        "x86" | "x86_32" | "x86_64" => u64::from(stack_register.size) as i64,
-        _ => 0,
+    // The format string is the last fixed argument,
+    // so the variadic integer arguments start right after it.
+    let mut next_integer_index = format_string_index + 1;
+    let mut next_float_index = 0;
+    let is_soft_float = (calling_convention.get_varargs_register_count(ArgClass::Float) == 0
+        || matches!(architecture, Arch::Arm32))
+        && calling_convention.get_varargs_register_count(ArgClass::Integer) > 0;
+    // Skip the return address if it is pushed onto the stack by the call instruction.
+    let mut stack_offset: i64 = if architecture.pushes_return_address_on_stack() {
+        u64::from(stack_register.size) as i64
+    } else {
+        0
     };
 
     for (data_type, size) in parameters.iter() {
-        match data_type {
-            Datatype::Integer | Datatype::Pointer | Datatype::Char => {
-                if integer_arg_register_count > 0 {
-                    let register = calling_convention.integer_parameter_register[calling_convention
-                        .integer_parameter_register
-                        .len()
-                        - integer_arg_register_count]
-                        .clone();
-
-                    var_args.push(create_register_arg(
-                        Expression::Var(register),
-                        data_type.clone(),
-                    ));
-
-                    integer_arg_register_count -= 1;
-                } else {
-                    var_args.push(create_stack_arg(
-                        *size,
-                        stack_offset,
-                        data_type.clone(),
-                        stack_register,
-                    ));
-                    stack_offset += u64::from(*size) as i64
-                }
+        let class = match data_type {
+            Datatype::Integer | Datatype::Pointer | Datatype::Char => ArgClass::Integer,
+            Datatype::LongDouble if matches!(architecture, Arch::X86_64) => {
+                var_args.push(allocate_stack_arg(
+                    data_type,
+                    *size,
+                    &mut stack_offset,
+                    stack_register,
+                    architecture,
+                ));
+                continue;
             }
-            Datatype::Double => {
-                if float_arg_register_count > 0 {
-                    let expr = calling_convention.float_parameter_register[calling_convention
-                        .float_parameter_register
-                        .len()
-                        - float_arg_register_count]
-                        .clone();
-
-                    var_args.push(create_register_arg(expr, data_type.clone()));
-
-                    float_arg_register_count -= 1;
-                } else {
-                    var_args.push(create_stack_arg(
-                        *size,
-                        stack_offset,
-                        data_type.clone(),
-                        stack_register,
-                    ));
-                    stack_offset += u64::from(*size) as i64
-                }
+            Datatype::Double | Datatype::LongDouble if is_soft_float => {
+                var_args.push(allocate_soft_float_arg(
+                    data_type,
+                    *size,
+                    calling_convention,
+                    &mut next_integer_index,
+                    &mut stack_offset,
+                    stack_register,
+                ));
+                continue;
             }
+            Datatype::Double | Datatype::LongDouble => ArgClass::Float,
             _ => panic!("Invalid data type specifier from format string."),
+        };
+        let next_index = match class {
+            ArgClass::Integer => &mut next_integer_index,
+            ArgClass::Float => &mut next_float_index,
+        };
+        match calling_convention.varargs_arg_location(*next_index, class) {
+            VarargLocation::Register { register_index, .. } => {
+                let expr = match class {
+                    ArgClass::Integer => Expression::Var(
+                        calling_convention.integer_parameter_register[register_index].clone(),
+                    ),
+                    ArgClass::Float => {
+                        calling_convention.float_parameter_register[register_index].clone()
+                    }
+                };
+                var_args.push(create_register_arg(expr, data_type.clone()));
+                *next_index += 1;
+            }
+            VarargLocation::Stack { .. } => {
+                var_args.push(allocate_stack_arg(
+                    data_type,
+                    *size,
+                    &mut stack_offset,
+                    stack_register,
+                    architecture,
+                ));
+            }
         }
     }
 
     var_args
 }
 
+/// Allocate a floating point parameter of the given size to integer registers or the stack
+/// as done on soft-float targets and for variadic arguments on ARM (following the ARM procedure call standard AAPCS).
+///
+/// - A parameter larger than an integer register is passed in a register pair starting at an even register,
+///   i.e. a register may be skipped to align the pair.
+///   The value of the pair is assumed to be stored in little-endian order.
+/// - If the parameter does not fit into the remaining registers,
+///   it is passed in a stack slot aligned to its size
+///   and all subsequent parameters are also passed on the stack.
+///
+/// The index of the next unused integer parameter register and the current stack offset are updated accordingly.
+pub fn allocate_soft_float_arg(
+    data_type: &Datatype,
+    size: ByteSize,
+    calling_convention: &CallingConvention,
+    next_integer_index: &mut usize,
+    stack_offset: &mut i64,
+    stack_register: &Variable,
+) -> Arg {
+    let register_count = calling_convention.get_varargs_register_count(ArgClass::Integer);
+    let integer_registers = &calling_convention.integer_parameter_register[..register_count];
+    let register_size = integer_registers
+        .first()
+        .map(|register| u64::from(register.size))
+        .unwrap_or_else(|| u64::from(stack_register.size));
+    let num_registers = u64::from(size).div_ceil(register_size).max(1) as usize;
+    let mut next_index = *next_integer_index;
+    if num_registers > 1 && !next_index.is_multiple_of(num_registers) {
+        next_index += num_registers - next_index % num_registers;
+    }
+    if next_index + num_registers <= register_count {
+        let registers = &integer_registers[next_index..next_index + num_registers];
+        let expr =
+            registers
+                .iter()
+                .skip(1)
+                .fold(Expression::Var(registers[0].clone()), |low, high| {
+                    Expression::BinOp {
+                        op: BinOpType::Piece,
+                        lhs: Box::new(Expression::Var(high.clone())),
+                        rhs: Box::new(low),
+                    }
+                });
+        *next_integer_index = next_index + num_registers;
+        create_register_arg(expr, data_type.clone())
+    } else {
+        *next_integer_index = std::cmp::max(*next_integer_index, register_count);
+        let alignment = u64::from(size) as i64;
+        if alignment > 0 && *stack_offset % alignment != 0 {
+            *stack_offset += alignment - *stack_offset % alignment;
+        }
+        let arg = create_stack_arg(size, *stack_offset, data_type.clone(), stack_register);
+        *stack_offset += alignment;
+        arg
+    }
+}
+
+/// Allocate a parameter of the given data type and size to the next stack slot
+/// and advance the current stack offset past the slot.
+///
+/// The stack offset is aligned according to [`get_stack_slot_layout`] before the slot is allocated.
+/// The alignment is relative to the value of the stack register before the call,
+/// i.e. a return address pushed onto the stack by the call instruction is not counted.
+pub fn allocate_stack_arg(
+    data_type: &Datatype,
+    size: ByteSize,
+    stack_offset: &mut i64,
+    stack_register: &Variable,
+    architecture: &Arch,
+) -> Arg {
+    let (slot_size, alignment) =
+        get_stack_slot_layout(data_type, size, stack_register, architecture);
+    let (slot_size, alignment) = (slot_size as i64, alignment as i64);
+    let return_address_size = if architecture.pushes_return_address_on_stack() {
+        u64::from(stack_register.size) as i64
+    } else {
+        0
+    };
+    let misalignment = (*stack_offset - return_address_size).rem_euclid(alignment);
+    if misalignment != 0 {
+        *stack_offset += alignment - misalignment;
+    }
+    let arg = create_stack_arg(size, *stack_offset, data_type.clone(), stack_register);
+    *stack_offset += slot_size;
+    arg
+}
+
+/// Returns the size and the alignment (in this order) of the stack slot
+/// of a variadic parameter with the given data type and size.
+///
+/// Stack slots are multiples of the size of the stack register.
+/// - On x86 stack slots are aligned to the size of the stack register,
+///   except for `long double` parameters on x86_64 (System V AMD64 ABI),
+///   which occupy 16 bytes aligned to 16 bytes.
+/// - On all other architectures parameters larger than the stack register are aligned to twice its size
+///   (e.g. doubles on 32-bit ARM and MIPS or `long double` parameters on AArch64 and RISC-V).
+pub fn get_stack_slot_layout(
+    data_type: &Datatype,
+    size: ByteSize,
+    stack_register: &Variable,
+    architecture: &Arch,
+) -> (u64, u64) {
+    let register_size = u64::from(stack_register.size);
+    let slot_size = u64::from(size).div_ceil(register_size).max(1) * register_size;
+    match (architecture, data_type) {
+        (Arch::X86_64, Datatype::LongDouble) => (std::cmp::max(slot_size, 16), 16),
+        (Arch::X86_64 | Arch::X86_32, _) => (slot_size, register_size),
+        _ => (slot_size, std::cmp::min(slot_size, 2 * register_size)),
+    }
+}
+
+/// If the argument is a stack argument,
+/// return its offset relative to the current value of the stack register.
+///
+/// If a pointer inference state is available, the offset is computed through the state
+/// (see [`PointerInferenceState::eval_stack_offset_of_arg`]),
+/// which also handles addresses relative to the frame pointer.
+/// Otherwise (or if the state-based evaluation fails) the address expression has to be relative to the stack register.
+pub fn get_stack_arg_offset(
+    arg: &Arg,
+    stack_register: &Variable,
+    pi_state: Option<&PointerInferenceState>,
+) -> Result<Bitvector, Error> {
+    pi_state
+        .and_then(|state| state.eval_stack_offset_of_arg(arg, stack_register).ok())
+        .map(Ok)
+        .unwrap_or_else(|| arg.eval_stack_offset(stack_register))
+}
+
 /// Creates a stack parameter given a size, stack offset and data type.
 pub fn create_stack_arg(
     size: ByteSize,
@@ -0,0 +1,318 @@
+//! Detection of wrapper functions that forward their format string parameter to a format string function.
+//!
+//! Logging functions often take a format string and variadic arguments,
+//! initialize a `va_list` with `va_start` and forward both to a `vprintf`-family function.
+//! The concrete format string and arguments only exist at the callsites of such wrappers.
+//! Therefore we summarize a wrapper by the index of its format string parameter,
+//! so that checks can handle calls to the wrapper like calls to the format string function itself.
+//!
+//! Wrappers are detected transitively, i.e. a function forwarding its format string parameter
+//! to another wrapper is also a wrapper.
+//! Whether a wrapper is variadic is detected by the lowering of `va_start`:
+//! The parameter registers that may contain unnamed arguments are written to a register save area
+//! on the stack at the start of variadic functions,
+//! as described by the [`VarargsLayout`] of the calling convention.
+
+use super::local_simulation::{compute_local_block_start_states, simulate_def};
+use super::{calculate_parameter_locations, parse_format_string_parameters};
+use crate::abstract_domain::{AbstractLocation, TryToBitvec};
+use crate::analysis::pointer_inference::{Data, State as PointerInferenceState};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The summary of a function that forwards its format string parameter to a format string function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct FormatStringWrapper {
+    /// The index of the format string parameter
+    /// in the list of integer parameter registers of the calling convention of the function.
+    pub format_string_index: usize,
+    /// Whether the function is variadic, i.e. whether it forwards its own variadic arguments.
+    pub is_variadic: bool,
+    /// The TIDs of the calls inside the wrapper that forward the format string parameter.
+    pub forwarding_callsites: BTreeSet<Tid>,
+}
+
+impl FormatStringWrapper {
+    /// Get the format string parameter of the wrapper function.
+    pub fn get_format_string_parameter(&self, calling_convention: &CallingConvention) -> Arg {
+        Arg::Register {
+            expr: Expression::Var(
+                calling_convention.integer_parameter_register[self.format_string_index].clone(),
+            ),
+            data_type: Some(Datatype::Pointer),
+        }
+    }
+}
+
+/// Returns `true` if the entry block of the function contains the register save area writes
+/// generated for `va_start` by compilers.
+///
+/// These are stores of the (unmodified) last integer parameter registers
+/// that may contain unnamed arguments according to [`CallingConvention::varargs_arg_location`].
+/// We require at least the last two of these registers to be saved.
+/// Non-variadic functions that spill all their parameters to the stack (e.g. in unoptimized code)
+/// and use all parameter registers are also detected as variadic.
+/// Functions whose calling convention passes all variadic arguments on the stack (e.g. on i386)
+/// cannot be detected this way.
+pub fn is_variadic_function(sub: &Term<Sub>, calling_convention: &CallingConvention) -> bool {
+    let parameter_registers: Vec<&Variable> = (0..)
+        .map_while(|index| {
+            match calling_convention.varargs_arg_location(index, ArgClass::Integer) {
+                VarargLocation::Register { register_index, .. } => {
+                    Some(&calling_convention.integer_parameter_register[register_index])
+                }
+                VarargLocation::Stack { .. } => None,
+            }
+        })
+        .collect();
+    let entry_block = match sub.term.blocks.first() {
+        Some(block) => block,
+        None => return false,
+    };
+    let mut modified_registers: HashSet<&Variable> = HashSet::new();
+    let mut saved_registers: HashSet<&Variable> = HashSet::new();
+    for def in entry_block.term.defs.iter() {
+        match &def.term {
+            Def::Store {
+                value: Expression::Var(var),
+                ..
+            } if parameter_registers.contains(&var) && !modified_registers.contains(var) => {
+                saved_registers.insert(var);
+            }
+            Def::Assign { var, .. } | Def::Load { var, .. } => {
+                modified_registers.insert(var);
+            }
+            _ => (),
+        }
+    }
+    let num_saved_last_registers = parameter_registers
+        .iter()
+        .rev()
+        .take_while(|register| saved_registers.contains(*register))
+        .count();
+    num_saved_last_registers >= 2
+}
+
+/// Return the index of the integer parameter register of the function with the given TID
+/// if the value is exactly the value of this parameter register at the start of the function.
+fn get_forwarded_parameter_index(
+    value: &Data,
+    sub_tid: &Tid,
+    calling_convention: &CallingConvention,
+) -> Option<usize> {
+    let (id, offset) = value.get_if_unique_target()?;
+    if id.get_tid() != sub_tid || offset.try_to_offset().ok() != Some(0) {
+        return None;
+    }
+    match id.get_location() {
+        AbstractLocation::Register(var) => calling_convention
+            .integer_parameter_register
+            .iter()
+            .position(|register| register == var),
+        AbstractLocation::Pointer(_, _) => None,
+    }
+}
+
+/// A call to a (possible) format string function together with the state at the callsite.
+struct FormatStringCall<'a> {
+    /// The function containing the callsite.
+    caller: &'a Term<Sub>,
+    /// The TID of the call instruction.
+    callsite: &'a Tid,
+    /// The target of the call.
+    target: &'a Tid,
+    /// The state at the callsite computed by [`compute_local_callsite_states`].
+    state: PointerInferenceState,
+}
+
+/// Compute the states at all direct calls inside the given function
+/// by a simple intraprocedural simulation of the function (see [`compute_local_block_start_states`]).
+///
+/// In contrast to the pointer inference analysis the simulation starts with generic parameter objects
+/// for the parameter registers of the function (and not with the values given by the callers),
+/// so that forwarded parameters can be recognized.
+fn compute_local_callsite_states<'a>(
+    project: &Project,
+    sub: &'a Term<Sub>,
+    calling_convention: &CallingConvention,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Vec<FormatStringCall<'a>> {
+    let block_start_states =
+        compute_local_block_start_states(project, sub, calling_convention, runtime_memory_image);
+    let mut callsite_states = Vec::new();
+    for block in sub.term.blocks.iter() {
+        let mut state = match block_start_states.get(&block.tid) {
+            Some(state) => state.clone(),
+            None => continue,
+        };
+        for def in block.term.defs.iter() {
+            simulate_def(&mut state, def, runtime_memory_image);
+        }
+        for jmp in block.term.jmps.iter() {
+            if let Jmp::Call { target, .. } = &jmp.term {
+                callsite_states.push(FormatStringCall {
+                    caller: sub,
+                    callsite: &jmp.tid,
+                    target,
+                    state: state.clone(),
+                });
+            }
+        }
+    }
+    callsite_states
+}
+
+/// Evaluate the format string parameter of the given call.
+///
+/// The `format_string_symbols` map the TIDs of format string functions
+/// to the function and the index of their format string parameter in the parameter list of the symbol.
+/// Returns `None` if the call target is neither a format string function nor a known wrapper.
+pub fn eval_format_string_parameter(
+    project: &Project,
+    state: &PointerInferenceState,
+    target: &Tid,
+    format_string_symbols: &HashMap<Tid, (&ExternSymbol, usize)>,
+    wrappers: &BTreeMap<Tid, FormatStringWrapper>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Option<Data> {
+    if let Some((symbol, index)) = format_string_symbols.get(target) {
+        let parameter = symbol.parameters.get(*index)?;
+        state
+            .eval_parameter_arg(parameter, runtime_memory_image)
+            .ok()
+    } else if let Some(wrapper) = wrappers.get(target) {
+        let calling_convention = get_sub_calling_convention(project, target)?;
+        let parameter = wrapper.get_format_string_parameter(calling_convention);
+        state
+            .eval_parameter_arg(&parameter, runtime_memory_image)
+            .ok()
+    } else {
+        None
+    }
+}
+
+/// Get the calling convention of the function with the given TID.
+pub fn get_sub_calling_convention<'a>(
+    project: &'a Project,
+    sub_tid: &Tid,
+) -> Option<&'a CallingConvention> {
+    let sub = project.program.term.subs.get(sub_tid)?;
+    project.get_specific_calling_convention(&sub.term.calling_convention)
+}
+
+/// Returns `true` if the given call inside the function `caller` forwards the format string parameter of `caller`,
+/// i.e. if the format string of the call has to be checked at the callsites of `caller` instead.
+pub fn is_forwarding_callsite(
+    caller: &Tid,
+    callsite: &Tid,
+    wrappers: &BTreeMap<Tid, FormatStringWrapper>,
+) -> bool {
+    wrappers
+        .get(caller)
+        .is_some_and(|wrapper| wrapper.forwarding_callsites.contains(callsite))
+}
+
+/// Detect all functions that forward their format string parameter to a format string function.
+///
+/// A function is a wrapper if the format string parameter of a call to a format string function
+/// (or to another wrapper) is exactly the value of one of the parameter registers of the function.
+/// The `format_string_symbols` map the TIDs of format string functions
+/// to the function and the index of their format string parameter in the parameter list of the symbol.
+pub fn get_format_string_wrappers(
+    project: &Project,
+    format_string_symbols: &HashMap<Tid, (&ExternSymbol, usize)>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> BTreeMap<Tid, FormatStringWrapper> {
+    let mut calls = Vec::new();
+    for sub in project.program.term.subs.values() {
+        if let Some(calling_convention) = get_sub_calling_convention(project, &sub.tid) {
+            calls.append(&mut compute_local_callsite_states(
+                project,
+                sub,
+                calling_convention,
+                runtime_memory_image,
+            ));
+        }
+    }
+
+    let mut wrappers: BTreeMap<Tid, FormatStringWrapper> = BTreeMap::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for call in calls.iter() {
+            if is_forwarding_callsite(&call.caller.tid, call.callsite, &wrappers) {
+                continue;
+            }
+            let calling_convention = match get_sub_calling_convention(project, &call.caller.tid) {
+                Some(cconv) => cconv,
+                None => continue,
+            };
+            let format_string_index = eval_format_string_parameter(
+                project,
+                &call.state,
+                call.target,
+                format_string_symbols,
+                &wrappers,
+                runtime_memory_image,
+            )
+            .and_then(|format_string| {
+                get_forwarded_parameter_index(&format_string, &call.caller.tid, calling_convention)
+            });
+            let format_string_index = match format_string_index {
+                Some(index) => index,
+                None => continue,
+            };
+            let wrapper =
+                wrappers
+                    .entry(call.caller.tid.clone())
+                    .or_insert_with(|| FormatStringWrapper {
+                        format_string_index,
+                        is_variadic: is_variadic_function(call.caller, calling_convention),
+                        forwarding_callsites: BTreeSet::new(),
+                    });
+            if wrapper.format_string_index == format_string_index {
+                wrapper.forwarding_callsites.insert(call.callsite.clone());
+                changed = true;
+            }
+        }
+    }
+    wrappers
+}
+
+/// Returns the arguments of a call to a variadic format string wrapper
+/// that correspond to the variadic arguments given by the format string at the callsite.
+///
+/// Returns an error if the wrapper is not variadic or if the format string cannot be parsed.
+pub fn get_wrapper_variable_parameters(
+    project: &Project,
+    pi_state: &PointerInferenceState,
+    wrapper_tid: &Tid,
+    wrapper: &FormatStringWrapper,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> Result<Vec<Arg>, Error> {
+    if !wrapper.is_variadic {
+        return Err(anyhow!("Function {} is not variadic.", wrapper_tid));
+    }
+    let calling_convention = get_sub_calling_convention(project, wrapper_tid)
+        .ok_or_else(|| anyhow!("No calling convention for function {}.", wrapper_tid))?;
+    let format_string_address = pi_state
+        .eval_parameter_arg(
+            &wrapper.get_format_string_parameter(calling_convention),
+            runtime_memory_image,
+        )?
+        .get_if_absolute_value()
+        .and_then(|address| address.try_to_bitvec().ok())
+        .ok_or_else(|| anyhow!("Format string not in global memory."))?;
+    let format_string =
+        runtime_memory_image.read_string_until_null_terminator(&format_string_address)?;
+    let parameters = parse_format_string_parameters(format_string, &project.datatype_properties)?;
+    Ok(calculate_parameter_locations(
+        parameters,
+        calling_convention,
+        wrapper.format_string_index,
+        &project.stack_pointer_register,
+        &project.architecture,
+    ))
+}
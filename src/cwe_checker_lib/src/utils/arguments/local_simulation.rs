@@ -0,0 +1,112 @@
+//! A simple intraprocedural simulation of functions
+//! starting from generic parameter objects for the parameter registers of the function.
+//!
+//! In contrast to the pointer inference analysis the values given by the callers are not known to the simulation.
+//! Instead, values derived from the parameters of the function are relative to the parameter objects.
+//! This allows to recognize how a function uses or forwards its own parameters.
+
+use crate::abstract_domain::AbstractDomain;
+use crate::analysis::pointer_inference::State as PointerInferenceState;
+use crate::intermediate_representation::*;
+use crate::utils::binary::RuntimeMemoryImage;
+use std::collections::HashMap;
+
+/// The maximal number of times a block is simulated in [`compute_local_block_start_states`]
+/// before its state is not updated anymore.
+const MAX_BLOCK_VISITS: usize = 10;
+
+/// Update the state according to the effect of the given `Def`.
+pub fn simulate_def(
+    state: &mut PointerInferenceState,
+    def: &Term<Def>,
+    runtime_memory_image: &RuntimeMemoryImage,
+) {
+    match &def.term {
+        Def::Assign { var, value } => state.handle_register_assign(var, value),
+        Def::Load { var, address } => {
+            let _ = state.handle_load(var, address, runtime_memory_image);
+        }
+        Def::Store { address, value } => {
+            let _ = state.handle_store(address, value, runtime_memory_image);
+        }
+    }
+}
+
+/// Compute the states at the start of all blocks of the given function
+/// by a simple intraprocedural simulation of the function.
+///
+/// The simulation starts with generic parameter objects
+/// for the integer parameter registers of the calling convention.
+/// Values in non-callee-saved registers are forgotten after calls.
+/// To guarantee termination each block is simulated at most [`MAX_BLOCK_VISITS`] times.
+/// Blocks not reachable from the entry block of the function have no state.
+pub fn compute_local_block_start_states<'a>(
+    project: &Project,
+    sub: &'a Term<Sub>,
+    calling_convention: &CallingConvention,
+    runtime_memory_image: &RuntimeMemoryImage,
+) -> HashMap<&'a Tid, PointerInferenceState> {
+    let entry_block = match sub.term.blocks.first() {
+        Some(block) => block,
+        None => return HashMap::new(),
+    };
+    let blocks: HashMap<&Tid, &Term<Blk>> = sub
+        .term
+        .blocks
+        .iter()
+        .map(|block| (&block.tid, block))
+        .collect();
+    let mut block_start_states: HashMap<&Tid, PointerInferenceState> = HashMap::new();
+    block_start_states.insert(
+        &entry_block.tid,
+        PointerInferenceState::new_with_generic_parameter_objects(
+            &project.stack_pointer_register,
+            sub.tid.clone(),
+            &calling_convention.integer_parameter_register,
+        ),
+    );
+    let mut visits: HashMap<&Tid, usize> = HashMap::new();
+    let mut worklist = vec![&entry_block.tid];
+    while let Some(block_tid) = worklist.pop() {
+        let (block, mut state) = match (blocks.get(block_tid), block_start_states.get(block_tid)) {
+            (Some(block), Some(state)) => (*block, state.clone()),
+            _ => continue,
+        };
+        *visits.entry(block_tid).or_insert(0) += 1;
+        for def in block.term.defs.iter() {
+            simulate_def(&mut state, def, runtime_memory_image);
+        }
+        for jmp in block.term.jmps.iter() {
+            let (successor, successor_state) = match &jmp.term {
+                Jmp::Branch(target) | Jmp::CBranch { target, .. } => (target, state.clone()),
+                Jmp::Call {
+                    return_: Some(return_target),
+                    ..
+                } => {
+                    let mut return_state = state.clone();
+                    return_state
+                        .clear_non_callee_saved_register(&calling_convention.callee_saved_register);
+                    (return_target, return_state)
+                }
+                _ => continue,
+            };
+            let merged_state = match block_start_states.get(successor) {
+                Some(old_state) => {
+                    let merged_state = old_state.merge(&successor_state);
+                    if merged_state == *old_state {
+                        continue;
+                    }
+                    merged_state
+                }
+                None => successor_state,
+            };
+            if let Some((successor, _)) = blocks.get_key_value(successor) {
+                if visits.get(successor).copied().unwrap_or(0) < MAX_BLOCK_VISITS {
+                    block_start_states.insert(successor, merged_state);
+                    worklist.push(successor);
+                }
+            }
+        }
+    }
+    block_start_states
+}
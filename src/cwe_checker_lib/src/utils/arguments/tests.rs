@@ -34,8 +34,9 @@ fn test_get_variable_parameters() {
         data_type: Some(Datatype::Char),
     });
 
+    // Stack arguments occupy 8-byte slots on x86_64.
     output.push(Arg::Stack {
-        address: Expression::Var(Variable::mock("RSP", 8)).plus_const(12),
+        address: Expression::Var(Variable::mock("RSP", 8)).plus_const(16),
         size: ByteSize::new(4),
         data_type: Some(Datatype::Integer),
     });
@@ -94,6 +95,30 @@ fn test_get_input_format_string() {
     );
 }
 
+#[test]
+fn test_get_input_format_string_loaded_from_global_memory() {
+    let sprintf_symbol = ExternSymbol::mock_string();
+    // Both mock memory images contain a pointer to "Hello World" at address 0x4000.
+    for mem_image in [
+        RuntimeMemoryImage::mock(),
+        RuntimeMemoryImage::mock_big_endian(),
+    ] {
+        let mut pi_state = mock_pi_state();
+        let format_string_pointer = pi_state
+            .load_value(
+                &Expression::const_from_i64(0x4000),
+                ByteSize::new(8),
+                &mem_image,
+            )
+            .unwrap();
+        pi_state.set_register(&Variable::mock("RSI", 8), format_string_pointer);
+        assert_eq!(
+            "Hello World",
+            get_input_format_string(&pi_state, &sprintf_symbol, 1, &mem_image).unwrap()
+        );
+    }
+}
+
 #[test]
 fn test_parse_format_string_destination_and_return_content() {
     let mem_image = RuntimeMemoryImage::mock();
@@ -163,6 +188,123 @@ fn test_parse_format_string_parameters() {
     }
 }
 
+#[test]
+fn test_get_variable_parameters_of_input_function() {
+    let mem_image = RuntimeMemoryImage::mock();
+    let mut pi_state = mock_pi_state();
+    let mut scanf_symbol = ExternSymbol::mock_string();
+    scanf_symbol.name = "__isoc99_scanf".to_string();
+    let mut format_string_index_map: HashMap<String, usize> = HashMap::new();
+    format_string_index_map.insert("__isoc99_scanf".to_string(), 0);
+    // The format string "%c %d" at this address would result in char and integer parameters for sprintf.
+    let global_address = Bitvector::from_str_radix(16, "5000").unwrap();
+    pi_state.set_register(
+        &Variable::mock("RDI", 8),
+        IntervalDomain::new(global_address.clone(), global_address).into(),
+    );
+    let mut project = Project::mock_empty();
+    let cconv = CallingConvention::mock_with_parameter_registers(
+        vec![Variable::mock("RDI", 8)],
+        vec![Variable::mock("XMM0", 16)],
+    );
+    project.calling_conventions = BTreeMap::from_iter([(cconv.name.clone(), cconv)]);
+
+    let expected_output = vec![
+        Arg::Stack {
+            address: Expression::Var(Variable::mock("RSP", 8)).plus_const(8),
+            size: ByteSize::new(8),
+            data_type: Some(Datatype::Pointer),
+        },
+        Arg::Stack {
+            address: Expression::Var(Variable::mock("RSP", 8)).plus_const(16),
+            size: ByteSize::new(8),
+            data_type: Some(Datatype::Pointer),
+        },
+    ];
+    assert_eq!(
+        expected_output,
+        get_variable_parameters(
+            &project,
+            &pi_state,
+            &scanf_symbol,
+            &format_string_index_map,
+            &mem_image,
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        get_input_function_parameters(
+            &project,
+            &pi_state,
+            &scanf_symbol,
+            &format_string_index_map,
+            &mem_image,
+        )
+        .unwrap(),
+        expected_output
+            .into_iter()
+            .zip([Datatype::Char, Datatype::Integer])
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_parse_format_string_parameters_with_flags() {
+    let properties = DatatypeProperties::mock();
+    // Field widths and escaped percent signs
+    assert_eq!(
+        parse_format_string_parameters("%100s 100%% %*d", &properties).unwrap(),
+        vec![
+            (Datatype::Pointer, properties.pointer_size),
+            (Datatype::Integer, properties.integer_size),
+            (Datatype::Integer, properties.integer_size),
+        ]
+    );
+    // Positional arguments
+    assert_eq!(
+        parse_format_string_parameters("%2$s %1$d %2$s", &properties).unwrap(),
+        vec![
+            (Datatype::Integer, properties.integer_size),
+            (Datatype::Pointer, properties.pointer_size),
+        ]
+    );
+    assert!(parse_format_string_parameters("%1$s %3$d", &properties).is_err());
+    assert!(parse_format_string_parameters("%1$s %d", &properties).is_err());
+}
+
+#[test]
+fn test_parse_input_format_string_conversions() {
+    assert!(is_input_function("sscanf"));
+    assert!(!is_input_function("sprintf"));
+    assert_eq!(
+        parse_input_format_string_conversions("%d %*d %10s %*s %[^\n] %lf %n").unwrap(),
+        vec![
+            Datatype::Integer,
+            Datatype::Pointer,
+            Datatype::Pointer,
+            Datatype::Double,
+            Datatype::Integer,
+        ]
+    );
+    // Long data types are allowed, since only pointers to them are passed as parameters.
+    assert_eq!(
+        parse_input_format_string_conversions("%2$s %*d %1$ld").unwrap(),
+        vec![Datatype::Long, Datatype::Pointer]
+    );
+}
+
+#[test]
+fn test_get_unbounded_string_parameter_indices() {
+    assert_eq!(
+        get_unbounded_string_parameter_indices("%s: %d %.10s %.*s %s\n").unwrap(),
+        vec![0, 5]
+    );
+    assert_eq!(
+        get_unbounded_string_parameter_indices("%2$.4s %1$s").unwrap(),
+        vec![0]
+    );
+}
+
 #[test]
 fn test_calculate_parameter_locations() {
     let cconv = CallingConvention::mock_with_parameter_registers(
@@ -203,7 +345,7 @@ fn test_calculate_parameter_locations() {
             &cconv,
             format_string_index,
             &Variable::mock("RSP", 8),
-            "x86_64"
+            &Arch::X86_64
         )
     );
 
@@ -215,6 +357,23 @@ fn test_calculate_parameter_locations() {
     });
 
     // Test Case 2: A second string parameter does not fit into the registers anymore and is written into the stack.
+    assert_eq!(
+        expected_args,
+        calculate_parameter_locations(
+            parameters.clone(),
+            &cconv,
+            format_string_index,
+            &Variable::mock("RSP", 8),
+            &Arch::X86_64
+        )
+    );
+
+    // Test Case 3: Without a return address on the stack, the stack parameters start at offset zero.
+    expected_args[3] = Arg::Stack {
+        address: Expression::Var(Variable::mock("RSP", 8)).plus_const(0),
+        size: ByteSize::new(8),
+        data_type: Some(Datatype::Pointer),
+    };
     assert_eq!(
         expected_args,
         calculate_parameter_locations(
@@ -222,7 +381,241 @@ fn test_calculate_parameter_locations() {
             &cconv,
             format_string_index,
             &Variable::mock("RSP", 8),
-            "x86_64"
+            &Arch::Aarch64
+        )
+    );
+
+    // Test Case 4: Long doubles are always passed on the stack on x86_64, in 16-byte slots
+    // aligned to 16 bytes relative to the stack pointer before the call (i.e. without the return address).
+    // Integers on the stack occupy 8-byte slots.
+    let parameters: Vec<(Datatype, ByteSize)> = vec![
+        ("Lf".to_string().into(), ByteSize::new(16)),
+        ("s".to_string().into(), ByteSize::new(8)),
+        ("s".to_string().into(), ByteSize::new(8)),
+        ("s".to_string().into(), ByteSize::new(8)),
+        ("d".to_string().into(), ByteSize::new(4)),
+        ("Lf".to_string().into(), ByteSize::new(16)),
+    ];
+    let stack_arg = |offset: i64, size: u64, data_type: Datatype| Arg::Stack {
+        address: Expression::Var(Variable::mock("RSP", 8)).plus_const(offset),
+        size: ByteSize::new(size),
+        data_type: Some(data_type),
+    };
+    let expected_args = vec![
+        stack_arg(8, 16, Datatype::LongDouble),
+        Arg::Register {
+            expr: Expression::Var(Variable::mock("R8", 8)),
+            data_type: Some(Datatype::Pointer),
+        },
+        Arg::Register {
+            expr: Expression::Var(Variable::mock("R9", 8)),
+            data_type: Some(Datatype::Pointer),
+        },
+        stack_arg(24, 8, Datatype::Pointer),
+        stack_arg(32, 4, Datatype::Integer),
+        stack_arg(40, 16, Datatype::LongDouble),
+    ];
+    assert_eq!(
+        expected_args,
+        calculate_parameter_locations(
+            parameters,
+            &cconv,
+            format_string_index,
+            &Variable::mock("RSP", 8),
+            &Arch::X86_64
+        )
+    );
+
+    // Test Case 5: On 32-bit ARM variadic doubles are passed in integer registers or on the stack,
+    // even if the calling convention has float parameter registers.
+    let cconv = CallingConvention::mock_with_parameter_registers(
+        vec![
+            Variable::mock("r0", 4),
+            Variable::mock("r1", 4),
+            Variable::mock("r2", 4),
+            Variable::mock("r3", 4),
+        ],
+        vec![Variable::mock("d0", 8)],
+    );
+    let parameters: Vec<(Datatype, ByteSize)> = vec![
+        ("d".to_string().into(), ByteSize::new(4)),
+        ("f".to_string().into(), ByteSize::new(8)),
+        ("f".to_string().into(), ByteSize::new(8)),
+    ];
+    let expected_args = vec![
+        Arg::Register {
+            expr: Expression::Var(Variable::mock("r1", 4)),
+            data_type: Some(Datatype::Integer),
+        },
+        Arg::Register {
+            expr: Expression::BinOp {
+                op: BinOpType::Piece,
+                lhs: Box::new(Expression::Var(Variable::mock("r3", 4))),
+                rhs: Box::new(Expression::Var(Variable::mock("r2", 4))),
+            },
+            data_type: Some(Datatype::Double),
+        },
+        Arg::Stack {
+            address: Expression::Var(Variable::mock("sp", 4)).plus_const(0),
+            size: ByteSize::new(8),
+            data_type: Some(Datatype::Double),
+        },
+    ];
+    assert_eq!(
+        expected_args,
+        calculate_parameter_locations(
+            parameters,
+            &cconv,
+            0,
+            &Variable::mock("sp", 4),
+            &Arch::Arm32
+        )
+    );
+}
+
+#[test]
+fn test_calculate_parameter_locations_soft_float() {
+    let cconv = CallingConvention::mock_with_parameter_registers(
+        vec![
+            Variable::mock("r0", 4),
+            Variable::mock("r1", 4),
+            Variable::mock("r2", 4),
+            Variable::mock("r3", 4),
+        ],
+        vec![],
+    );
+    let parameters: Vec<(Datatype, ByteSize)> = vec![
+        ("d".to_string().into(), ByteSize::new(4)),
+        ("f".to_string().into(), ByteSize::new(8)),
+        ("s".to_string().into(), ByteSize::new(4)),
+        ("f".to_string().into(), ByteSize::new(8)),
+    ];
+    let expected_args = vec![
+        Arg::Register {
+            expr: Expression::Var(Variable::mock("r1", 4)),
+            data_type: Some(Datatype::Integer),
+        },
+        // The double is passed in the register pair r2:r3 (little-endian).
+        Arg::Register {
+            expr: Expression::BinOp {
+                op: BinOpType::Piece,
+                lhs: Box::new(Expression::Var(Variable::mock("r3", 4))),
+                rhs: Box::new(Expression::Var(Variable::mock("r2", 4))),
+            },
+            data_type: Some(Datatype::Double),
+        },
+        Arg::Stack {
+            address: Expression::Var(Variable::mock("sp", 4)).plus_const(0),
+            size: ByteSize::new(4),
+            data_type: Some(Datatype::Pointer),
+        },
+        // The second double is passed in a stack slot aligned to 8 bytes.
+        Arg::Stack {
+            address: Expression::Var(Variable::mock("sp", 4)).plus_const(8),
+            size: ByteSize::new(8),
+            data_type: Some(Datatype::Double),
+        },
+    ];
+    assert_eq!(
+        expected_args,
+        calculate_parameter_locations(
+            parameters,
+            &cconv,
+            0,
+            &Variable::mock("sp", 4),
+            &Arch::Arm32
+        )
+    );
+
+    // The register pair has to start at an even register.
+    // Since it does not fit into r3 anymore, it is passed on the stack and all following arguments, too.
+    let parameters: Vec<(Datatype, ByteSize)> = vec![
+        ("d".to_string().into(), ByteSize::new(4)),
+        ("d".to_string().into(), ByteSize::new(4)),
+        ("f".to_string().into(), ByteSize::new(8)),
+        ("d".to_string().into(), ByteSize::new(4)),
+    ];
+    let args = calculate_parameter_locations(
+        parameters,
+        &cconv,
+        0,
+        &Variable::mock("sp", 4),
+        &Arch::Arm32,
+    );
+    assert_eq!(
+        args[2],
+        Arg::Stack {
+            address: Expression::Var(Variable::mock("sp", 4)).plus_const(0),
+            size: ByteSize::new(8),
+            data_type: Some(Datatype::Double),
+        }
+    );
+    assert_eq!(
+        args[3],
+        Arg::Stack {
+            address: Expression::Var(Variable::mock("sp", 4)).plus_const(8),
+            size: ByteSize::new(4),
+            data_type: Some(Datatype::Integer),
+        }
+    );
+}
+
+#[test]
+fn test_calculate_parameter_locations_riscv64() {
+    let cconv = CallingConvention::mock_riscv64();
+    let mut parameters: Vec<(Datatype, ByteSize)> = vec![
+        ("d".to_string().into(), ByteSize::new(4)),
+        ("f".to_string().into(), ByteSize::new(8)),
+        ("s".to_string().into(), ByteSize::new(8)),
+    ];
+    let mut expected_args = vec![
+        Arg::Register {
+            expr: Expression::Var(Variable::mock("a1", 8)),
+            data_type: Some(Datatype::Integer),
+        },
+        // Variadic doubles are passed in integer registers.
+        Arg::Register {
+            expr: Expression::Var(Variable::mock("a2", 8)),
+            data_type: Some(Datatype::Double),
+        },
+        Arg::Register {
+            expr: Expression::Var(Variable::mock("a3", 8)),
+            data_type: Some(Datatype::Pointer),
+        },
+    ];
+    assert_eq!(
+        expected_args,
+        calculate_parameter_locations(
+            parameters.clone(),
+            &cconv,
+            0,
+            &Variable::mock("sp", 8),
+            &Arch::RiscV
+        )
+    );
+
+    // Arguments not fitting into `a0-a7` are passed on the stack, starting directly at the stack pointer.
+    parameters.extend(vec![("d".to_string().into(), ByteSize::new(4)); 4]);
+    parameters.push(("f".to_string().into(), ByteSize::new(8)));
+    for register in ["a4", "a5", "a6", "a7"] {
+        expected_args.push(Arg::Register {
+            expr: Expression::Var(Variable::mock(register, 8)),
+            data_type: Some(Datatype::Integer),
+        });
+    }
+    expected_args.push(Arg::Stack {
+        address: Expression::Var(Variable::mock("sp", 8)).plus_const(0),
+        size: ByteSize::new(8),
+        data_type: Some(Datatype::Double),
+    });
+    assert_eq!(
+        expected_args,
+        calculate_parameter_locations(
+            parameters,
+            &cconv,
+            0,
+            &Variable::mock("sp", 8),
+            &Arch::RiscV
         )
     );
 }
@@ -243,3 +636,44 @@ fn test_create_stack_arg() {
         ),
     )
 }
+
+#[test]
+fn test_get_stack_arg_offset() {
+    let rsp = Variable::mock("RSP", 8);
+    let rbp = Variable::mock("RBP", 8);
+    // The usual function prologue of unoptimized code: push RBP; mov RBP, RSP; sub RSP, 32
+    let mut pi_state = mock_pi_state();
+    pi_state.handle_register_assign(&rsp, &Expression::Var(rsp.clone()).plus_const(-8));
+    pi_state.handle_register_assign(&rbp, &Expression::Var(rsp.clone()));
+    pi_state.handle_register_assign(&rsp, &Expression::Var(rsp.clone()).plus_const(-32));
+    let frame_pointer_arg = Arg::Stack {
+        address: Expression::Var(rbp.clone()).plus_const(16),
+        size: ByteSize::new(8),
+        data_type: None,
+    };
+    let stack_pointer_arg = create_stack_arg(ByteSize::new(8), 48, Datatype::Integer, &rsp);
+
+    assert!(frame_pointer_arg.eval_stack_offset(&rsp).is_err());
+    assert_eq!(
+        frame_pointer_arg
+            .eval_stack_offset_with_frame_pointer(&rsp, &rbp, &Bitvector::from_i64(32))
+            .unwrap(),
+        Bitvector::from_i64(48)
+    );
+    assert_eq!(
+        pi_state
+            .eval_stack_offset_of_arg(&frame_pointer_arg, &rsp)
+            .unwrap(),
+        Bitvector::from_i64(48)
+    );
+    assert_eq!(
+        get_stack_arg_offset(&frame_pointer_arg, &rsp, Some(&pi_state)).unwrap(),
+        Bitvector::from_i64(48)
+    );
+    assert!(get_stack_arg_offset(&frame_pointer_arg, &rsp, None).is_err());
+    assert_eq!(
+        get_stack_arg_offset(&stack_pointer_arg, &rsp, None).unwrap(),
+        Bitvector::from_i64(48)
+    );
+    assert!(get_stack_arg_offset(&Arg::mock_register("RDI", 8), &rsp, Some(&pi_state)).is_err());
+}
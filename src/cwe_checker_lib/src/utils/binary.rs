@@ -3,9 +3,23 @@
 use crate::intermediate_representation::BinOpType;
 use crate::intermediate_representation::BitvectorExtended;
 use crate::prelude::*;
+use crate::utils::log::LogMessage;
 use goblin::elf;
 use goblin::pe;
 use goblin::Object;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+/// The maximal number of entries in each of the memoization tables of a [`RuntimeMemoryImage`].
+const MAX_READ_CACHE_ENTRIES: usize = 4096;
+
+/// Reads of at most this many bytes are memoized by [`RuntimeMemoryImage::read`].
+const MAX_CACHED_READ_SIZE: u64 = 16;
+
+/// The synthetic address of the first section of a relocatable ELF file (e.g. a Linux kernel module).
+///
+/// See [`get_relocatable_section_addresses`] for the layout of the remaining sections.
+pub const RELOCATABLE_BASE_ADDRESS: u64 = 0x10000;
 
 /// Contains all information parsed out of the bare metal configuration JSON file.
 ///
@@ -57,11 +71,125 @@ fn parse_hex_string_to_u64(mut string: &str) -> Result<u64, Error> {
     Ok(u64::from_str_radix(string, 16)?)
 }
 
+/// The magic bytes at the start of the build information that the Go toolchain embeds into every binary.
+const GO_BUILD_INFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+
+/// Return `true` if the binary was generated by the Go compiler.
+///
+/// For ELF files we check for the sections `.gopclntab` and `.go.buildinfo`
+/// that the Go linker adds to each binary.
+/// Since these sections may be missing (e.g. for PE files or stripped binaries),
+/// we fall back to searching the binary for the magic bytes of the embedded build information.
+pub fn is_go_binary(binary: &[u8]) -> bool {
+    if let Ok(Object::Elf(elf_file)) = Object::parse(binary) {
+        let has_go_section = elf_file.section_headers.iter().any(|header| {
+            matches!(
+                elf_file.shdr_strtab.get_at(header.sh_name),
+                Some(".gopclntab") | Some(".go.buildinfo")
+            )
+        });
+        if has_go_section {
+            return true;
+        }
+    }
+    binary
+        .windows(GO_BUILD_INFO_MAGIC.len())
+        .any(|window| window == GO_BUILD_INFO_MAGIC)
+}
+
+/// Assign synthetic addresses to the allocated sections of a relocatable ELF file,
+/// since relocatable files do not specify the addresses of their sections.
+///
+/// The sections are laid out in the order of the section header table,
+/// starting at [`RELOCATABLE_BASE_ADDRESS`] and each aligned to its section alignment.
+/// Returns a map from section indices to section addresses.
+/// Sections that are not allocated or that are empty do not get an address.
+pub fn get_relocatable_section_addresses(elf_file: &elf::Elf) -> BTreeMap<usize, u64> {
+    let mut section_addresses = BTreeMap::new();
+    let mut next_address = RELOCATABLE_BASE_ADDRESS;
+    for (index, header) in elf_file.section_headers.iter().enumerate() {
+        if !header.is_alloc() || header.sh_size == 0 {
+            continue;
+        }
+        let alignment = std::cmp::max(header.sh_addralign, 1);
+        let address = match next_address
+            .checked_next_multiple_of(alignment)
+            .filter(|address| address.checked_add(header.sh_size).is_some())
+        {
+            Some(address) => address,
+            None => break,
+        };
+        section_addresses.insert(index, address);
+        next_address = address + header.sh_size;
+    }
+    section_addresses
+}
+
 /// A representation of the runtime image of a binary after being loaded into memory by the loader.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct RuntimeMemoryImage {
     memory_segments: Vec<MemorySegment>,
+    /// The byte order used to interpret values read from the memory image.
+    /// It is taken from the file header for ELF files, from the processor ID for bare metal binaries
+    /// and is always little-endian for PE files.
     is_little_endian: bool,
+    /// Memoization tables for repeated reads from the memory image.
+    #[serde(skip)]
+    read_cache: ReadCache,
+}
+
+/// Memoization tables for reads from a [`RuntimeMemoryImage`].
+///
+/// The tables are bounded by [`MAX_READ_CACHE_ENTRIES`] entries each.
+/// If a table is full, it gets cleared before a new entry is inserted.
+/// The cache is not part of the logical state of the memory image,
+/// i.e. it is ignored for comparisons, hashing and serialization and is not copied by `clone`.
+#[derive(Debug, Default)]
+struct ReadCache {
+    /// Maps the start address of a string to the index of the segment containing it
+    /// and to the length of the string (without the null terminator).
+    strings: Mutex<BTreeMap<u64, (usize, usize)>>,
+    /// Maps `(address, size, is_little_endian)` of small fixed-size reads to the read result.
+    values: Mutex<BTreeMap<(u64, u64, bool), Option<Bitvector>>>,
+    /// The number of times that a memory segment was scanned for a string terminator.
+    #[cfg(test)]
+    string_scans: std::sync::atomic::AtomicUsize,
+}
+
+impl ReadCache {
+    /// Remove all entries from the cache.
+    fn clear(&self) {
+        self.strings.lock().unwrap().clear();
+        self.values.lock().unwrap().clear();
+    }
+}
+
+impl Clone for ReadCache {
+    fn clone(&self) -> Self {
+        ReadCache::default()
+    }
+}
+
+impl PartialEq for ReadCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ReadCache {}
+
+impl std::hash::Hash for ReadCache {
+    fn hash<H: std::hash::Hasher>(&self, _state: &mut H) {}
+}
+
+/// Insert the key-value-pair into the given memoization table.
+/// The table is cleared first if it already contains [`MAX_READ_CACHE_ENTRIES`] entries.
+fn insert_into_bounded_cache<K: Ord, V>(table: &Mutex<BTreeMap<K, V>>, key: K, value: V) {
+    let mut table = table.lock().unwrap();
+    if table.len() >= MAX_READ_CACHE_ENTRIES {
+        table.clear();
+    }
+    table.insert(key, value);
 }
 
 /// A continuous segment in the memory image.
@@ -81,19 +209,102 @@ struct MemorySegment {
 
 impl MemorySegment {
     /// Generate a segment from a program header of an ELF file.
-    pub fn from_elf_segment(binary: &[u8], program_header: &elf::ProgramHeader) -> MemorySegment {
-        let mut bytes: Vec<u8> = binary[program_header.file_range()].to_vec();
-        if program_header.vm_range().len() > program_header.file_range().len() {
-            // The additional memory space must be filled with null bytes.
-            bytes.resize(program_header.vm_range().len(), 0u8);
+    ///
+    /// The part of the segment not backed by the file (e.g. the `.bss` section) is filled with null bytes.
+    /// If the file range of the segment exceeds the file, the missing bytes are also filled with null bytes.
+    /// Returns `None` for segments with a memory size of zero or with a memory range exceeding the address space.
+    pub fn from_elf_segment(
+        binary: &[u8],
+        program_header: &elf::ProgramHeader,
+    ) -> Option<MemorySegment> {
+        if program_header.p_memsz == 0
+            || program_header
+                .p_vaddr
+                .checked_add(program_header.p_memsz)
+                .is_none()
+        {
+            return None;
         }
-        MemorySegment {
+        let file_start = usize::try_from(program_header.p_offset)
+            .unwrap_or(usize::MAX)
+            .min(binary.len());
+        let file_end = usize::try_from(
+            program_header
+                .p_offset
+                .saturating_add(program_header.p_filesz),
+        )
+        .unwrap_or(usize::MAX)
+        .min(binary.len());
+        let mut bytes: Vec<u8> = binary[file_start..file_end].to_vec();
+        // The additional memory space must be filled with null bytes.
+        bytes.resize(usize::try_from(program_header.p_memsz).ok()?, 0u8);
+        Some(MemorySegment {
             bytes,
             base_address: program_header.p_vaddr,
             read_flag: program_header.is_read(),
             write_flag: program_header.is_write(),
             execute_flag: program_header.is_executable(),
+        })
+    }
+
+    /// Generate a segment from an allocated section of a relocatable ELF file
+    /// placed at the given (synthetic) address.
+    ///
+    /// Sections without file content (e.g. the `.bss` section) are filled with null bytes.
+    /// If the file range of the section exceeds the file, the missing bytes are also filled with null bytes.
+    pub fn from_elf_section(
+        binary: &[u8],
+        section_header: &elf::SectionHeader,
+        address: u64,
+    ) -> Option<MemorySegment> {
+        let mut bytes = if section_header.sh_type == elf::section_header::SHT_NOBITS {
+            Vec::new()
+        } else {
+            let file_start = usize::try_from(section_header.sh_offset)
+                .unwrap_or(usize::MAX)
+                .min(binary.len());
+            let file_end = usize::try_from(
+                section_header
+                    .sh_offset
+                    .saturating_add(section_header.sh_size),
+            )
+            .unwrap_or(usize::MAX)
+            .min(binary.len());
+            binary[file_start..file_end].to_vec()
+        };
+        bytes.resize(usize::try_from(section_header.sh_size).ok()?, 0u8);
+        Some(MemorySegment {
+            bytes,
+            base_address: address,
+            read_flag: true,
+            write_flag: section_header.is_writable(),
+            execute_flag: section_header.is_executable(),
+        })
+    }
+
+    /// The address after the last byte of the segment.
+    fn end_address(&self) -> u64 {
+        self.base_address + self.bytes.len() as u64
+    }
+
+    /// Return the parts of the segment that are not contained in the given address range `[start, end)`.
+    fn remove_range(self, start: u64, end: u64) -> Vec<MemorySegment> {
+        let mut parts = Vec::new();
+        if self.base_address < start {
+            parts.push(MemorySegment {
+                bytes: self.bytes[..(start - self.base_address) as usize].to_vec(),
+                base_address: self.base_address,
+                ..self.clone()
+            });
         }
+        if end < self.end_address() {
+            parts.push(MemorySegment {
+                bytes: self.bytes[(end - self.base_address) as usize..].to_vec(),
+                base_address: end,
+                ..self
+            });
+        }
+        parts
     }
 
     /// Generate a segment from a section table from a PE file.
@@ -148,24 +359,82 @@ impl RuntimeMemoryImage {
     /// Generate a runtime memory image for a given binary.
     ///
     /// The function can parse ELF and PE files as input.
+    /// See [`RuntimeMemoryImage::new_with_logs`] for the handling of malformed ELF segments.
     pub fn new(binary: &[u8]) -> Result<Self, Error> {
+        Ok(Self::new_with_logs(binary)?.0)
+    }
+
+    /// Generate a runtime memory image for a given binary
+    /// together with log messages about malformed segments of the binary.
+    ///
+    /// Loadable ELF segments with a memory size of zero are skipped.
+    /// The memory of a segment not backed by the file is filled with null bytes.
+    /// If loadable ELF segments overlap, the later segment wins in the overlapping range
+    /// (as it does when the loader maps the segments into memory).
+    ///
+    /// Relocatable ELF files (e.g. Linux kernel modules) are loaded by
+    /// [`RuntimeMemoryImage::new_from_relocatable_elf`].
+    pub fn new_with_logs(binary: &[u8]) -> Result<(Self, Vec<LogMessage>), Error> {
         let parsed_object = Object::parse(binary)?;
 
         match parsed_object {
+            Object::Elf(elf_file) if elf_file.header.e_type == elf::header::ET_REL => {
+                Self::new_from_relocatable_elf(binary, &elf_file)
+            }
             Object::Elf(elf_file) => {
-                let mut memory_segments = Vec::new();
-                for header in elf_file.program_headers.iter() {
-                    if header.p_type == elf::program_header::PT_LOAD {
-                        memory_segments.push(MemorySegment::from_elf_segment(binary, header));
+                let mut memory_segments: Vec<MemorySegment> = Vec::new();
+                let mut logs = Vec::new();
+                for (index, header) in elf_file.program_headers.iter().enumerate() {
+                    if header.p_type != elf::program_header::PT_LOAD {
+                        continue;
+                    }
+                    let segment = match MemorySegment::from_elf_segment(binary, header) {
+                        Some(segment) => segment,
+                        None => {
+                            logs.push(
+                                LogMessage::new_info(format!(
+                                    "Skipped loadable segment {} at address {:#x} with invalid memory size {:#x}",
+                                    index, header.p_vaddr, header.p_memsz
+                                ))
+                                .source("Runtime Memory Image"),
+                            );
+                            continue;
+                        }
+                    };
+                    let (start, end) = (segment.base_address, segment.end_address());
+                    if memory_segments
+                        .iter()
+                        .any(|other| other.base_address < end && start < other.end_address())
+                    {
+                        logs.push(
+                            LogMessage::new_info(format!(
+                                "Loadable segment {} at address range {:#x}-{:#x} overlaps previous segments. The previous segments are overwritten in the overlapping range.",
+                                index, start, end
+                            ))
+                            .source("Runtime Memory Image"),
+                        );
+                        memory_segments = memory_segments
+                            .into_iter()
+                            .flat_map(|other| {
+                                if other.base_address < end && start < other.end_address() {
+                                    other.remove_range(start, end)
+                                } else {
+                                    vec![other]
+                                }
+                            })
+                            .collect();
                     }
+                    memory_segments.push(segment);
                 }
                 if memory_segments.is_empty() {
                     return Err(anyhow!("No loadable segments found"));
                 }
-                Ok(RuntimeMemoryImage {
+                let memory_image = RuntimeMemoryImage {
                     memory_segments,
                     is_little_endian: elf_file.header.endianness().unwrap().is_little(),
-                })
+                    read_cache: ReadCache::default(),
+                };
+                Ok((memory_image, logs))
             }
             Object::PE(pe_file) => {
                 let mut memory_segments = Vec::new();
@@ -181,14 +450,102 @@ impl RuntimeMemoryImage {
                 let mut memory_image = RuntimeMemoryImage {
                     memory_segments,
                     is_little_endian: true,
+                    read_cache: ReadCache::default(),
                 };
                 memory_image.add_global_memory_offset(pe_file.image_base as u64);
-                Ok(memory_image)
+                Ok((memory_image, Vec::new()))
             }
             _ => Err(anyhow!("Object type not supported.")),
         }
     }
 
+    /// Generate a runtime memory image for a relocatable ELF file (e.g. a Linux kernel module)
+    /// together with log messages about relocations that could not be applied.
+    ///
+    /// Relocatable files contain sections instead of loadable segments.
+    /// The allocated sections are placed at synthetic addresses (see [`get_relocatable_section_addresses`])
+    /// and the relocations that can be resolved inside the file are applied to them,
+    /// i.e. relocations referencing symbols defined in the allocated sections or absolute symbols.
+    /// Relocations referencing undefined symbols (e.g. exported functions of the kernel)
+    /// and relocations of unsupported types are not applied.
+    /// Only the absolute and PC-relative relocation types of x86 are supported.
+    pub fn new_from_relocatable_elf(
+        binary: &[u8],
+        elf_file: &elf::Elf,
+    ) -> Result<(Self, Vec<LogMessage>), Error> {
+        let section_addresses = get_relocatable_section_addresses(elf_file);
+        let mut segments: BTreeMap<usize, MemorySegment> = section_addresses
+            .iter()
+            .filter_map(|(index, address)| {
+                let segment = MemorySegment::from_elf_section(
+                    binary,
+                    &elf_file.section_headers[*index],
+                    *address,
+                )?;
+                Some((*index, segment))
+            })
+            .collect();
+        if segments.is_empty() {
+            return Err(anyhow!("No allocated sections found"));
+        }
+        let (mut num_applied, mut num_undefined, mut num_unsupported) = (0, 0, 0);
+        for (reloc_section_index, relocs) in elf_file.shdr_relocs.iter() {
+            let target_index = elf_file.section_headers[*reloc_section_index].sh_info as usize;
+            let segment = match segments.get_mut(&target_index) {
+                Some(segment) => segment,
+                None => continue,
+            };
+            for reloc in relocs.iter() {
+                let symbol_address = match elf_file.syms.get(reloc.r_sym) {
+                    Some(symbol) if symbol.st_shndx == elf::section_header::SHN_ABS as usize => {
+                        Some(symbol.st_value)
+                    }
+                    Some(symbol) => section_addresses
+                        .get(&symbol.st_shndx)
+                        .map(|address| address.wrapping_add(symbol.st_value)),
+                    None => None,
+                };
+                let symbol_address = match symbol_address {
+                    Some(address) => address,
+                    None => {
+                        num_undefined += 1;
+                        continue;
+                    }
+                };
+                match apply_relocation(
+                    segment,
+                    elf_file.header.e_machine,
+                    &reloc,
+                    symbol_address,
+                    elf_file.little_endian,
+                ) {
+                    Some(()) => num_applied += 1,
+                    None => num_unsupported += 1,
+                }
+            }
+        }
+        let mut logs = vec![LogMessage::new_debug(format!(
+            "Applied {} relocations of the relocatable file",
+            num_applied
+        ))
+        .source("Runtime Memory Image")];
+        if num_undefined > 0 || num_unsupported > 0 {
+            logs.push(
+                LogMessage::new_info(format!(
+                    "Did not apply {} relocations referencing undefined symbols and {} relocations of unsupported types",
+                    num_undefined, num_unsupported
+                ))
+                .source("Runtime Memory Image"),
+            );
+        }
+        let memory_image = RuntimeMemoryImage {
+            memory_segments: segments.into_values().collect(),
+            is_little_endian: elf_file.little_endian,
+            read_cache: ReadCache::default(),
+        };
+        Ok((memory_image, logs))
+    }
+
     /// Generate a runtime memory image for a bare metal binary.
     ///
     /// The generated runtime memory image contains:
@@ -229,6 +586,7 @@ impl RuntimeMemoryImage {
                 MemorySegment::new_bare_metal_ram_segment(ram_base_address, ram_size),
             ],
             is_little_endian,
+            read_cache: ReadCache::default(),
         })
     }
 
@@ -245,6 +603,8 @@ impl RuntimeMemoryImage {
         for segment in self.memory_segments.iter_mut() {
             segment.base_address += offset;
         }
+        // Cached reads are keyed by the old addresses.
+        self.read_cache.clear();
     }
 
     /// Read the contents of the memory image at the given address
@@ -256,8 +616,25 @@ impl RuntimeMemoryImage {
     /// since the data may change during program execution.
     ///
     /// Returns an error if the address is not contained in the global data address range.
+    ///
+    /// Results of reads of small sizes are memoized.
     pub fn read(&self, address: &Bitvector, size: ByteSize) -> Result<Option<Bitvector>, Error> {
         let address = address.try_to_u64().unwrap();
+        if u64::from(size) > MAX_CACHED_READ_SIZE {
+            return self.read_uncached(address, size);
+        }
+        let key = (address, u64::from(size), self.is_little_endian);
+        if let Some(value) = self.read_cache.values.lock().unwrap().get(&key) {
+            return Ok(value.clone());
+        }
+        let value = self.read_uncached(address, size)?;
+        insert_into_bounded_cache(&self.read_cache.values, key, value.clone());
+        Ok(value)
+    }
+
+    /// Read the contents of the memory image at the given address without using the memoization table.
+    /// See [`RuntimeMemoryImage::read`] for more information.
+    fn read_uncached(&self, address: u64, size: ByteSize) -> Result<Option<Bitvector>, Error> {
         for segment in self.memory_segments.iter() {
             if address >= segment.base_address
                 && u64::from(size) <= segment.base_address + segment.bytes.len() as u64
@@ -287,18 +664,48 @@ impl RuntimeMemoryImage {
 
     /// Read the contents of memory from a given address onwards until a null byte is reached and checks whether the
     /// content is a valid UTF8 string.
+    ///
+    /// The position of the null terminator is memoized,
+    /// so that repeated reads of the same string do not scan the memory segment again.
     pub fn read_string_until_null_terminator(&self, address: &Bitvector) -> Result<&str, Error> {
         let address = address.try_to_u64().unwrap();
-        for segment in self.memory_segments.iter() {
+        let cached_string = self
+            .read_cache
+            .strings
+            .lock()
+            .unwrap()
+            .get(&address)
+            .copied();
+        let (segment_index, length) = match cached_string {
+            Some(location) => location,
+            None => {
+                let location = self.find_null_terminated_string(address)?;
+                insert_into_bounded_cache(&self.read_cache.strings, address, location);
+                location
+            }
+        };
+        let segment = &self.memory_segments[segment_index];
+        let start_index = (address - segment.base_address) as usize;
+        let c_str = std::ffi::CStr::from_bytes_with_nul(
+            &segment.bytes[start_index..start_index + length + 1],
+        )?;
+        Ok(c_str.to_str()?)
+    }
+
+    /// Scan the memory for the null terminator of a string starting at the given address.
+    /// Returns the index of the segment containing the string and the length of the string.
+    fn find_null_terminated_string(&self, address: u64) -> Result<(usize, usize), Error> {
+        for (segment_index, segment) in self.memory_segments.iter().enumerate() {
             if address >= segment.base_address
                 && address <= segment.base_address + segment.bytes.len() as u64
             {
+                #[cfg(test)]
+                self.read_cache
+                    .string_scans
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
                 let start_index = (address - segment.base_address) as usize;
-                if let Some(end_index) = segment.bytes[start_index..].iter().position(|&b| b == 0) {
-                    let c_str = std::ffi::CStr::from_bytes_with_nul(
-                        &segment.bytes[start_index..start_index + end_index + 1],
-                    )?;
-                    return Ok(c_str.to_str()?);
+                if let Some(length) = segment.bytes[start_index..].iter().position(|&b| b == 0) {
+                    return Ok((segment_index, length));
                 } else {
                     return Err(anyhow!("Not a valid string in memory."));
                 }
@@ -308,6 +715,21 @@ impl RuntimeMemoryImage {
         Err(anyhow!("Address is not a valid global memory address."))
     }
 
+    /// Read the strings starting at the given addresses.
+    /// Addresses not pointing to valid strings in memory are omitted from the result.
+    pub fn read_strings<'b>(
+        &self,
+        addresses: impl IntoIterator<Item = &'b Bitvector>,
+    ) -> BTreeMap<u64, String> {
+        addresses
+            .into_iter()
+            .filter_map(|address| {
+                let string = self.read_string_until_null_terminator(address).ok()?;
+                Some((address.try_to_u64().ok()?, string.to_string()))
+            })
+            .collect()
+    }
+
     /// Checks whether the constant is a global memory address.
     pub fn is_global_memory_address(&self, constant: &Bitvector) -> bool {
         if self.read(constant, constant.bytesize()).is_ok() {
@@ -402,6 +824,292 @@ impl RuntimeMemoryImage {
     }
 }
 
+/// Apply a relocation of a relocatable ELF file to the segment of the relocated section.
+///
+/// The value of the relocation is computed from the address of the referenced symbol, the addend and,
+/// for PC-relative relocations, the address of the relocated field.
+/// Relocations without explicit addend take the addend from the relocated field.
+/// Returns `None` if the relocation type is not supported or the relocated field is not contained in the segment.
+fn apply_relocation(
+    segment: &mut MemorySegment,
+    machine: u16,
+    reloc: &elf::Reloc,
+    symbol_address: u64,
+    is_little_endian: bool,
+) -> Option<()> {
+    use elf::header::{EM_386, EM_X86_64};
+    use elf::reloc::*;
+    let (size, is_pc_relative) = match (machine, reloc.r_type) {
+        (EM_X86_64, R_X86_64_64) => (8, false),
+        (EM_X86_64, R_X86_64_32 | R_X86_64_32S) | (EM_386, R_386_32) => (4, false),
+        (EM_X86_64, R_X86_64_PC32 | R_X86_64_PLT32) | (EM_386, R_386_PC32 | R_386_PLT32) => {
+            (4, true)
+        }
+        (EM_X86_64, R_X86_64_PC64) => (8, true),
+        _ => return None,
+    };
+    let start = usize::try_from(reloc.r_offset).ok()?;
+    let field = segment.bytes.get_mut(start..start.checked_add(size)?)?;
+    let addend = match reloc.r_addend {
+        Some(addend) => addend,
+        None => {
+            let mut implicit_addend = [0u8; 8];
+            if is_little_endian {
+                implicit_addend[..size].copy_from_slice(field);
+                if field[size - 1] & 0x80 != 0 {
+                    implicit_addend[size..].fill(0xff);
+                }
+                i64::from_le_bytes(implicit_addend)
+            } else {
+                implicit_addend[8 - size..].copy_from_slice(field);
+                if field[0] & 0x80 != 0 {
+                    implicit_addend[..8 - size].fill(0xff);
+                }
+                i64::from_be_bytes(implicit_addend)
+            }
+        }
+    };
+    let mut value = symbol_address.wrapping_add(addend as u64);
+    if is_pc_relative {
+        value = value.wrapping_sub(segment.base_address.wrapping_add(reloc.r_offset));
+    }
+    if is_little_endian {
+        field.copy_from_slice(&value.to_le_bytes()[..size]);
+    } else {
+        field.copy_from_slice(&value.to_be_bytes()[8 - size..]);
+    }
+    Some(())
+}
+
+/// Symbol information of an ELF file that is needed to resolve IFUNC symbols, weak symbol aliases,
+/// PLT stubs and extern symbols sharing the same address.
+///
+/// All addresses are the addresses as specified in the ELF file,
+/// i.e. without the `address_base_offset` that Ghidra may have added to the program.
+/// For relocatable files the synthetic section addresses of [`get_relocatable_section_addresses`] are used.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ElfSymbolInfo {
+    /// Maps the addresses of the resolver functions of IFUNC symbols to the names of the IFUNC symbols.
+    pub ifunc_resolvers: BTreeMap<u64, String>,
+    /// Maps the addresses of GOT entries that are filled by `IRELATIVE` relocations
+    /// to the addresses of the corresponding resolver functions.
+    pub irelative_got_entries: BTreeMap<u64, u64>,
+    /// Maps the names of weak function symbols to the name of the strong function symbol
+    /// defined at the same address.
+    pub weak_aliases: BTreeMap<String, String>,
+    /// The names of the imported symbols in the order of the dynamic symbol table.
+    pub imported_symbols: Vec<String>,
+    /// The names of the symbols referenced by PLT relocations.
+    pub plt_relocation_symbols: BTreeSet<String>,
+    /// Maps the addresses of GOT entries that are filled by PLT relocations
+    /// to the names of the referenced symbols.
+    pub plt_got_entries: BTreeMap<u64, String>,
+    /// Maps the addresses of call instructions in relocatable files (e.g. Linux kernel modules)
+    /// whose call targets are given by relocations referencing undefined symbols
+    /// to the names of the referenced symbols (e.g. exported functions of the kernel like `copy_from_user`).
+    pub call_relocation_symbols: BTreeMap<u64, String>,
+}
+
+impl ElfSymbolInfo {
+    /// Collect the IFUNC symbols, `IRELATIVE` relocations, weak symbol aliases and imported symbols of an ELF file.
+    /// For relocatable files the call instructions targeting undefined symbols are also collected.
+    ///
+    /// Returns an empty symbol info for other file formats.
+    pub fn new(binary: &[u8]) -> Result<Self, Error> {
+        let elf_file = match Object::parse(binary)? {
+            Object::Elf(elf_file) => elf_file,
+            _ => return Ok(ElfSymbolInfo::default()),
+        };
+        let mut info = ElfSymbolInfo::default();
+        let is_relocatable = elf_file.header.e_type == elf::header::ET_REL;
+        let section_addresses = if is_relocatable {
+            get_relocatable_section_addresses(&elf_file)
+        } else {
+            BTreeMap::new()
+        };
+        // The symbol values of relocatable files are offsets into the sections of the symbols.
+        let get_symbol_address = |sym: &elf::Sym| {
+            if is_relocatable {
+                section_addresses
+                    .get(&sym.st_shndx)
+                    .map(|address| address + sym.st_value)
+            } else {
+                Some(sym.st_value)
+            }
+        };
+        let mut strong_symbols: BTreeMap<u64, &str> = BTreeMap::new();
+        let mut weak_symbols: Vec<(u64, &str)> = Vec::new();
+        let symbols = elf_file
+            .syms
+            .iter()
+            .map(|sym| (sym, elf_file.strtab.get_at(sym.st_name)))
+            .chain(
+                elf_file
+                    .dynsyms
+                    .iter()
+                    .map(|sym| (sym, elf_file.dynstrtab.get_at(sym.st_name))),
+            );
+        for (sym, name) in symbols {
+            let name = match name {
+                Some(name) if !name.is_empty() && !sym.is_import() => name,
+                _ => continue,
+            };
+            let address = match get_symbol_address(&sym) {
+                Some(address) => address,
+                None => continue,
+            };
+            match (sym.st_type(), sym.st_bind()) {
+                (elf::sym::STT_GNU_IFUNC, _) => {
+                    info.ifunc_resolvers
+                        .entry(address)
+                        .or_insert_with(|| name.to_string());
+                }
+                (elf::sym::STT_FUNC, elf::sym::STB_GLOBAL) => {
+                    strong_symbols.entry(address).or_insert(name);
+                }
+                (elf::sym::STT_FUNC, elf::sym::STB_WEAK) => weak_symbols.push((address, name)),
+                _ => (),
+            }
+        }
+        for sym in elf_file.dynsyms.iter().filter(|sym| sym.is_import()) {
+            if let Some(name) = elf_file.dynstrtab.get_at(sym.st_name) {
+                if !name.is_empty() && !info.imported_symbols.iter().any(|known| known == name) {
+                    info.imported_symbols.push(name.to_string());
+                }
+            }
+        }
+        for reloc in elf_file.pltrelocs.iter() {
+            let name = elf_file
+                .dynsyms
+                .get(reloc.r_sym)
+                .and_then(|sym| elf_file.dynstrtab.get_at(sym.st_name));
+            if let Some(name) = name {
+                if !name.is_empty() {
+                    info.plt_relocation_symbols.insert(name.to_string());
+                    info.plt_got_entries
+                        .insert(reloc.r_offset, name.to_string());
+                }
+            }
+        }
+        for (address, weak_name) in weak_symbols {
+            if let Some(strong_name) = strong_symbols.get(&address) {
+                if *strong_name != weak_name {
+                    info.weak_aliases
+                        .insert(weak_name.to_string(), strong_name.to_string());
+                }
+            }
+        }
+        if is_relocatable {
+            info.call_relocation_symbols =
+                get_call_relocation_symbols(binary, &elf_file, &section_addresses);
+        }
+
+        let irelative_type = match elf_file.header.e_machine {
+            elf::header::EM_X86_64 => Some(elf::reloc::R_X86_64_IRELATIVE),
+            elf::header::EM_386 => Some(elf::reloc::R_386_IRELATIVE),
+            elf::header::EM_ARM => Some(elf::reloc::R_ARM_IRELATIVE),
+            elf::header::EM_AARCH64 => Some(elf::reloc::R_AARCH64_IRELATIVE),
+            _ => None,
+        };
+        if let Some(irelative_type) = irelative_type {
+            // Relocations without addend store the resolver address in the GOT entry itself.
+            let memory_image = RuntimeMemoryImage::new(binary).ok();
+            let pointer_size = ByteSize::new(if elf_file.is_64 { 8 } else { 4 });
+            let relocations = elf_file
+                .dynrelas
+                .iter()
+                .chain(elf_file.dynrels.iter())
+                .chain(elf_file.pltrelocs.iter());
+            for reloc in relocations.filter(|reloc| reloc.r_type == irelative_type) {
+                let resolver = match reloc.r_addend {
+                    Some(addend) => Some(addend as u64),
+                    None => memory_image
+                        .as_ref()
+                        .and_then(|image| {
+                            image
+                                .read(&Bitvector::from_u64(reloc.r_offset), pointer_size)
+                                .ok()
+                                .flatten()
+                        })
+                        .and_then(|value| value.try_to_u64().ok()),
+                };
+                if let Some(resolver) = resolver {
+                    info.irelative_got_entries.insert(reloc.r_offset, resolver);
+                }
+            }
+        }
+        Ok(info)
+    }
+}
+
+/// Collect the call instructions of a relocatable ELF file whose call targets are given by relocations
+/// referencing undefined symbols and map their addresses to the names of the referenced symbols.
+fn get_call_relocation_symbols(
+    binary: &[u8],
+    elf_file: &elf::Elf,
+    section_addresses: &BTreeMap<usize, u64>,
+) -> BTreeMap<u64, String> {
+    let mut call_relocation_symbols = BTreeMap::new();
+    for (reloc_section_index, relocs) in elf_file.shdr_relocs.iter() {
+        let target_index = elf_file.section_headers[*reloc_section_index].sh_info as usize;
+        let (target_header, target_address) = match (
+            elf_file.section_headers.get(target_index),
+            section_addresses.get(&target_index),
+        ) {
+            (Some(header), Some(address)) => (header, *address),
+            _ => continue,
+        };
+        for reloc in relocs.iter() {
+            let name = match elf_file.syms.get(reloc.r_sym) {
+                Some(symbol) if symbol.st_shndx == elf::section_header::SHN_UNDEF as usize => {
+                    elf_file.strtab.get_at(symbol.st_name)
+                }
+                _ => None,
+            };
+            let name = match name {
+                Some(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+            if let Some(call_offset) = get_call_instruction_offset(
+                binary,
+                elf_file.header.e_machine,
+                target_header,
+                &reloc,
+            ) {
+                call_relocation_symbols.insert(target_address + call_offset, name.to_string());
+            }
+        }
+    }
+    call_relocation_symbols
+}
+
+/// If the relocation patches the target of a call instruction,
+/// return the offset of the call instruction in the relocated section.
+///
+/// On x86 the relocated field has to be the displacement of a `call rel32` instruction (opcode `0xe8`).
+/// On ARM and AArch64 the relocation types for calls patch the call instruction itself.
+fn get_call_instruction_offset(
+    binary: &[u8],
+    machine: u16,
+    section_header: &elf::SectionHeader,
+    reloc: &elf::Reloc,
+) -> Option<u64> {
+    use elf::header::{EM_386, EM_AARCH64, EM_ARM, EM_X86_64};
+    use elf::reloc::*;
+    match (machine, reloc.r_type) {
+        (EM_X86_64, R_X86_64_PC32 | R_X86_64_PLT32) | (EM_386, R_386_PC32 | R_386_PLT32) => {
+            let opcode_offset = reloc.r_offset.checked_sub(1)?;
+            let file_offset =
+                usize::try_from(section_header.sh_offset.checked_add(opcode_offset)?).ok()?;
+            (binary.get(file_offset) == Some(&0xe8)).then_some(opcode_offset)
+        }
+        (EM_AARCH64, R_AARCH64_CALL26) | (EM_ARM, R_ARM_CALL | R_ARM_THM_PC22) => {
+            Some(reloc.r_offset)
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -480,10 +1188,59 @@ pub mod tests {
                         write_flag: false,
                         execute_flag: false,
                     },
+                    // Contains strings: 'https://example.com' and 'http://example.com'
+                    MemorySegment {
+                        bytes: b"https://example.com\0http://example.com\0".to_vec(),
+                        base_address: 0x8000,
+                        read_flag: true,
+                        write_flag: false,
+                        execute_flag: false,
+                    },
                 ],
                 is_little_endian: true,
+                read_cache: ReadCache::default(),
             }
         }
+
+        /// Create a big-endian variant of the [mock runtime memory image](RuntimeMemoryImage::mock) for unit tests.
+        ///
+        /// The segments are the same as in the little-endian mock,
+        /// except that the pointer to the Hello World string at address 0x4000 is stored in big-endian byte order.
+        pub fn mock_big_endian() -> RuntimeMemoryImage {
+            let mut mem_image = RuntimeMemoryImage::mock();
+            mem_image.memory_segments[3].bytes = 0x3002u64.to_be_bytes().to_vec();
+            mem_image.is_little_endian = false;
+            mem_image
+        }
+    }
+
+    /// A minimal 32-bit big-endian MIPS ELF file consisting of the headers and one loadable segment.
+    /// The segment contains the ELF file itself followed by a pointer to the start of the program header table.
+    fn minimal_big_endian_elf() -> Vec<u8> {
+        let mut elf = Vec::new();
+        elf.extend_from_slice(b"\x7fELF\x01\x02\x01\x00");
+        elf.extend_from_slice(&[0; 8]);
+        elf.extend_from_slice(&2u16.to_be_bytes()); // e_type: executable
+        elf.extend_from_slice(&8u16.to_be_bytes()); // e_machine: MIPS
+        elf.extend_from_slice(&1u32.to_be_bytes()); // e_version
+        elf.extend_from_slice(&0x400000u32.to_be_bytes()); // e_entry
+        elf.extend_from_slice(&52u32.to_be_bytes()); // e_phoff
+        elf.extend_from_slice(&0u32.to_be_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_be_bytes()); // e_flags
+        for value in [52u16, 32, 1, 40, 0, 0] {
+            // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+            elf.extend_from_slice(&value.to_be_bytes());
+        }
+        elf.extend_from_slice(&1u32.to_be_bytes()); // p_type: PT_LOAD
+        elf.extend_from_slice(&0u32.to_be_bytes()); // p_offset
+        elf.extend_from_slice(&0x400000u32.to_be_bytes()); // p_vaddr
+        elf.extend_from_slice(&0x400000u32.to_be_bytes()); // p_paddr
+        elf.extend_from_slice(&88u32.to_be_bytes()); // p_filesz
+        elf.extend_from_slice(&88u32.to_be_bytes()); // p_memsz
+        elf.extend_from_slice(&4u32.to_be_bytes()); // p_flags: R
+        elf.extend_from_slice(&0x1000u32.to_be_bytes()); // p_align
+        elf.extend_from_slice(&0x400034u32.to_be_bytes()); // pointer to the program header table
+        elf
     }
 
     #[test]
@@ -501,6 +1258,302 @@ pub mod tests {
         );
     }
 
+    /// A 64-bit little-endian ELF file with the given loadable segments followed by the given data.
+    ///
+    /// The segments are given as `(data_offset, address, file_size, memory_size, is_writeable)`,
+    /// where `data_offset` is the offset of the segment content in `data`.
+    fn elf_with_segments(segments: &[(u64, u64, u64, u64, bool)], data: &[u8]) -> Vec<u8> {
+        let data_start = 64 + 56 * segments.len() as u64;
+        let mut elf = Vec::new();
+        elf.extend_from_slice(b"\x7fELF\x02\x01\x01\x00");
+        elf.extend_from_slice(&[0; 8]);
+        elf.extend_from_slice(&2u16.to_le_bytes()); // e_type: executable
+        elf.extend_from_slice(&0x3eu16.to_le_bytes()); // e_machine: x86-64
+        elf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        elf.extend_from_slice(&0x10000u64.to_le_bytes()); // e_entry
+        elf.extend_from_slice(&64u64.to_le_bytes()); // e_phoff
+        elf.extend_from_slice(&0u64.to_le_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        for value in [64u16, 56, segments.len() as u16, 64, 0, 0] {
+            // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+            elf.extend_from_slice(&value.to_le_bytes());
+        }
+        for (data_offset, address, file_size, memory_size, is_writeable) in segments {
+            elf.extend_from_slice(&1u32.to_le_bytes()); // p_type: PT_LOAD
+            let flags: u32 = if *is_writeable { 6 } else { 4 };
+            elf.extend_from_slice(&flags.to_le_bytes()); // p_flags
+            elf.extend_from_slice(&(data_start + data_offset).to_le_bytes()); // p_offset
+            elf.extend_from_slice(&address.to_le_bytes()); // p_vaddr
+            elf.extend_from_slice(&address.to_le_bytes()); // p_paddr
+            elf.extend_from_slice(&file_size.to_le_bytes()); // p_filesz
+            elf.extend_from_slice(&memory_size.to_le_bytes()); // p_memsz
+            elf.extend_from_slice(&0x1000u64.to_le_bytes()); // p_align
+        }
+        elf.extend_from_slice(data);
+        elf
+    }
+
+    /// Generate a relocatable 64-bit little-endian x86-64 ELF file (like a Linux kernel module)
+    /// with the sections `.text`, `.data` and `.rela.text`.
+    ///
+    /// The `.text` section starts with a call to the local function `helper` at offset 0x10 of the section
+    /// and a call to the undefined symbol `copy_from_user`, both given by `R_X86_64_PLT32` relocations.
+    fn relocatable_elf() -> Vec<u8> {
+        let text: &[u8] = &[
+            0xe8, 0, 0, 0, 0, // call helper
+            0xe8, 0, 0, 0, 0, // call copy_from_user
+            0xc3, 0x90, 0x90, 0x90, 0x90, 0x90, // ret + padding
+            0xc3, // helper: ret
+        ];
+        let data: &[u8] = &[0x2a; 8];
+        let mut relocations = Vec::new();
+        for (offset, symbol_index) in [(1u64, 1u64), (6, 3)] {
+            relocations.extend_from_slice(&offset.to_le_bytes()); // r_offset
+            relocations.extend_from_slice(&((symbol_index << 32) | 4).to_le_bytes()); // r_info: R_X86_64_PLT32
+            relocations.extend_from_slice(&(-4i64).to_le_bytes()); // r_addend
+        }
+        let mut symbols = vec![0u8; 24];
+        // (st_name, st_info, st_shndx, st_value): helper (local function), init_module (global function)
+        // and copy_from_user (undefined)
+        for (name, info, section, value) in [
+            (1u32, 0x02u8, 1u16, 0x10u64),
+            (8, 0x12, 1, 0),
+            (20, 0x10, 0, 0),
+        ] {
+            symbols.extend_from_slice(&name.to_le_bytes());
+            symbols.extend_from_slice(&[info, 0]);
+            symbols.extend_from_slice(&section.to_le_bytes());
+            symbols.extend_from_slice(&value.to_le_bytes());
+            symbols.extend_from_slice(&0u64.to_le_bytes()); // st_size
+        }
+        let strtab: &[u8] = b"\0helper\0init_module\0copy_from_user\0";
+        let shstrtab: &[u8] = b"\0.text\0.data\0.rela.text\0.symtab\0.strtab\0.shstrtab\0";
+        // (sh_name, sh_type, sh_flags, content, sh_link, sh_info, sh_addralign, sh_entsize)
+        let sections = [
+            (1u32, 1u32, 0x6u64, text, 0u32, 0u32, 16u64, 0u64),
+            (7, 1, 0x3, data, 0, 0, 8, 0),
+            (13, 4, 0x40, relocations.as_slice(), 4, 1, 8, 24),
+            (24, 2, 0, symbols.as_slice(), 5, 2, 8, 24),
+            (32, 3, 0, strtab, 0, 0, 1, 0),
+            (40, 3, 0, shstrtab, 0, 0, 1, 0),
+        ];
+        let mut content = Vec::new();
+        let mut section_offsets = Vec::new();
+        for section in sections.iter() {
+            section_offsets.push(64 + content.len() as u64);
+            content.extend_from_slice(section.3);
+        }
+        content.resize(content.len().next_multiple_of(8), 0);
+        let section_header_offset = 64 + content.len() as u64;
+
+        let mut elf = Vec::new();
+        elf.extend_from_slice(b"\x7fELF\x02\x01\x01\x00");
+        elf.extend_from_slice(&[0; 8]);
+        elf.extend_from_slice(&1u16.to_le_bytes()); // e_type: relocatable
+        elf.extend_from_slice(&0x3eu16.to_le_bytes()); // e_machine: x86-64
+        elf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        elf.extend_from_slice(&0u64.to_le_bytes()); // e_entry
+        elf.extend_from_slice(&0u64.to_le_bytes()); // e_phoff
+        elf.extend_from_slice(&section_header_offset.to_le_bytes()); // e_shoff
+        elf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        for value in [64u16, 0, 0, 64, 7, 6] {
+            // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+            elf.extend_from_slice(&value.to_le_bytes());
+        }
+        elf.extend_from_slice(&content);
+        elf.extend_from_slice(&[0; 64]); // null section header
+        for (section, offset) in sections.iter().zip(section_offsets) {
+            let (name, section_type, flags, content, link, info, alignment, entry_size) = section;
+            elf.extend_from_slice(&name.to_le_bytes());
+            elf.extend_from_slice(&section_type.to_le_bytes());
+            elf.extend_from_slice(&flags.to_le_bytes());
+            elf.extend_from_slice(&0u64.to_le_bytes()); // sh_addr
+            elf.extend_from_slice(&offset.to_le_bytes());
+            elf.extend_from_slice(&(content.len() as u64).to_le_bytes());
+            elf.extend_from_slice(&link.to_le_bytes());
+            elf.extend_from_slice(&info.to_le_bytes());
+            elf.extend_from_slice(&alignment.to_le_bytes());
+            elf.extend_from_slice(&entry_size.to_le_bytes());
+        }
+        elf
+    }
+
+    #[test]
+    fn relocatable_elf_memory_image() {
+        let elf = relocatable_elf();
+        assert_eq!(
+            crate::utils::get_binary_base_address(&elf).unwrap(),
+            RELOCATABLE_BASE_ADDRESS
+        );
+        let (mem_image, logs) = RuntimeMemoryImage::new_with_logs(&elf).unwrap();
+        // The `.text` section is placed at the base address, the `.data` section is aligned to 8 bytes.
+        let segments: Vec<(u64, usize, bool, bool)> = mem_image
+            .memory_segments
+            .iter()
+            .map(|segment| {
+                (
+                    segment.base_address,
+                    segment.bytes.len(),
+                    segment.write_flag,
+                    segment.execute_flag,
+                )
+            })
+            .collect();
+        assert_eq!(
+            segments,
+            vec![(0x10000, 0x11, false, true), (0x10018, 8, true, false)]
+        );
+        // The internal call is relocated to `helper` at 0x10010.
+        let read_displacement = |address: u64| {
+            mem_image
+                .read(&Bitvector::from_u64(address), ByteSize::new(4))
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(read_displacement(0x10001), Bitvector::from_u32(0xb));
+        // The call to the undefined symbol is not relocated.
+        assert_eq!(read_displacement(0x10006), Bitvector::from_u32(0));
+        assert_eq!(logs.len(), 2);
+        assert!(logs[1]
+            .text
+            .starts_with("Did not apply 1 relocations referencing undefined symbols"));
+    }
+
+    #[test]
+    fn relocatable_elf_symbol_info() {
+        let symbol_info = ElfSymbolInfo::new(&relocatable_elf()).unwrap();
+        assert_eq!(
+            symbol_info.call_relocation_symbols,
+            BTreeMap::from([(0x10005, "copy_from_user".to_string())])
+        );
+    }
+
+    #[test]
+    fn segment_with_zero_filled_tail() {
+        let elf = elf_with_segments(&[(0, 0x10000, 4, 8, false)], &[1, 2, 3, 4]);
+        let (mem_image, logs) = RuntimeMemoryImage::new_with_logs(&elf).unwrap();
+        assert!(logs.is_empty());
+        // The read crosses the boundary between the file-backed part and the zero-filled part of the segment.
+        assert_eq!(
+            mem_image
+                .read(&Bitvector::from_u64(0x10002), ByteSize::new(4))
+                .unwrap(),
+            Some(Bitvector::from_u32(0x0403))
+        );
+        assert_eq!(
+            mem_image
+                .read(&Bitvector::from_u64(0x10004), ByteSize::new(4))
+                .unwrap(),
+            Some(Bitvector::from_u32(0))
+        );
+        assert!(mem_image
+            .read(&Bitvector::from_u64(0x10006), ByteSize::new(4))
+            .is_err());
+
+        // Segments whose file range exceeds the file are filled with null bytes.
+        let elf = elf_with_segments(&[(2, 0x10000, 4, 4, false)], &[1, 2, 3]);
+        let mem_image = RuntimeMemoryImage::new(&elf).unwrap();
+        assert_eq!(
+            mem_image
+                .read(&Bitvector::from_u64(0x10000), ByteSize::new(4))
+                .unwrap(),
+            Some(Bitvector::from_u32(0x03))
+        );
+    }
+
+    #[test]
+    fn zero_sized_segments_are_skipped() {
+        let elf = elf_with_segments(
+            &[(0, 0x10000, 0, 0, false), (0, 0x20000, 4, 4, false)],
+            &[1, 2, 3, 4],
+        );
+        let (mem_image, logs) = RuntimeMemoryImage::new_with_logs(&elf).unwrap();
+        assert_eq!(mem_image.memory_segments.len(), 1);
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].text.contains("Skipped loadable segment 0"));
+        assert!(mem_image
+            .read(&Bitvector::from_u64(0x10000), ByteSize::new(1))
+            .is_err());
+
+        let elf = elf_with_segments(&[(0, 0x10000, 0, 0, false)], &[]);
+        assert!(RuntimeMemoryImage::new(&elf).is_err());
+    }
+
+    #[test]
+    fn overlapping_segments() {
+        // The second segment overlaps the middle of the first one.
+        let elf = elf_with_segments(
+            &[(0, 0x10000, 8, 8, false), (8, 0x10002, 4, 4, false)],
+            &[1, 2, 3, 4, 5, 6, 7, 8, 0xa1, 0xa2, 0xa3, 0xa4],
+        );
+        let (mem_image, logs) = RuntimeMemoryImage::new_with_logs(&elf).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].text.contains("overlaps previous segments"));
+        assert_eq!(mem_image.memory_segments.len(), 3);
+        let read_byte = |address: u64| {
+            mem_image
+                .read(&Bitvector::from_u64(address), ByteSize::new(1))
+                .unwrap()
+                .unwrap()
+        };
+        let bytes: Vec<Bitvector> = (0x10000..0x10008).map(read_byte).collect();
+        let expected: Vec<Bitvector> = [1u8, 2, 0xa1, 0xa2, 0xa3, 0xa4, 7, 8]
+            .into_iter()
+            .map(Bitvector::from_u8)
+            .collect();
+        assert_eq!(bytes, expected);
+
+        // The later segment determines the flags of the overlapping range.
+        let elf = elf_with_segments(
+            &[(0, 0x10000, 4, 4, false), (0, 0x10000, 4, 8, true)],
+            &[1, 2, 3, 4],
+        );
+        let mem_image = RuntimeMemoryImage::new(&elf).unwrap();
+        assert_eq!(mem_image.memory_segments.len(), 1);
+        assert_eq!(
+            mem_image
+                .read(&Bitvector::from_u64(0x10000), ByteSize::new(4))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn big_endian_memory_image() {
+        let mem_image = RuntimeMemoryImage::new(&minimal_big_endian_elf()).unwrap();
+        assert!(!mem_image.is_little_endian_byte_order());
+        assert_eq!(
+            mem_image
+                .read(&Bitvector::from_u32(0x400054), ByteSize::new(4))
+                .unwrap(),
+            Some(Bitvector::from_u32(0x400034))
+        );
+        assert_eq!(
+            mem_image
+                .read_string_until_null_terminator(&Bitvector::from_u32(0x400001))
+                .unwrap(),
+            "ELF\x01\x02\x01"
+        );
+
+        // Pointers read from the big-endian mock equal those read from the little-endian mock.
+        let pointer_address = Bitvector::from_u64(0x4000);
+        let pointer = RuntimeMemoryImage::mock()
+            .read(&pointer_address, ByteSize::new(8))
+            .unwrap()
+            .unwrap();
+        let mem_image = RuntimeMemoryImage::mock_big_endian();
+        assert_eq!(
+            mem_image.read(&pointer_address, ByteSize::new(8)).unwrap(),
+            Some(pointer.clone())
+        );
+        assert_eq!(
+            mem_image
+                .read_string_until_null_terminator(&pointer)
+                .unwrap(),
+            "Hello World"
+        );
+    }
+
     #[test]
     fn ro_data_pointer() {
         let mem_image = RuntimeMemoryImage::mock();
@@ -524,4 +1577,59 @@ pub mod tests {
                 .unwrap(),
         );
     }
+
+    #[test]
+    fn string_reads_are_memoized() {
+        use std::sync::atomic::Ordering;
+        let mem_image = RuntimeMemoryImage::mock();
+        let address = Bitvector::from_u32(0x3002);
+        let string = mem_image
+            .read_string_until_null_terminator(&address)
+            .unwrap();
+        assert_eq!(string, "Hello World");
+        assert_eq!(mem_image.read_cache.string_scans.load(Ordering::Relaxed), 1);
+        // The second read of the same string does not scan the memory again.
+        let string = mem_image
+            .read_string_until_null_terminator(&address)
+            .unwrap();
+        assert_eq!(string, "Hello World");
+        assert_eq!(mem_image.read_cache.string_scans.load(Ordering::Relaxed), 1);
+
+        let addresses = vec![
+            Bitvector::from_u32(0x3002),
+            Bitvector::from_u32(0x5000),
+            Bitvector::from_u32(0x500c),
+            Bitvector::from_u32(0x9000),
+        ];
+        let strings = mem_image.read_strings(&addresses);
+        assert_eq!(strings.len(), 3);
+        assert_eq!(strings[&0x5000], "/dev/sd%c%d");
+        assert_eq!(strings[&0x500c], "cat %s");
+        assert_eq!(mem_image.read_cache.string_scans.load(Ordering::Relaxed), 3);
+
+        // Moving the memory segments invalidates the cache.
+        let mut mem_image = mem_image;
+        mem_image.add_global_memory_offset(0x1000);
+        assert_eq!(
+            mem_image
+                .read_string_until_null_terminator(&Bitvector::from_u32(0x3002))
+                .unwrap(),
+            ""
+        );
+        assert_eq!(
+            mem_image
+                .read_string_until_null_terminator(&Bitvector::from_u32(0x4002))
+                .unwrap(),
+            "Hello World"
+        );
+    }
+
+    #[test]
+    fn go_binary_detection() {
+        let mut binary = vec![0u8; 32];
+        assert!(!is_go_binary(&binary));
+        binary.extend_from_slice(GO_BUILD_INFO_MAGIC);
+        binary.extend_from_slice(&[0u8; 16]);
+        assert!(is_go_binary(&binary));
+    }
 }
@@ -0,0 +1,283 @@
+//! Checkpoints for resuming interrupted analysis runs.
+//!
+//! A checkpoint is a directory containing the results of the completed phases of an analysis run.
+//! The phases of a run are the computation of the function signatures,
+//! the computation of the pointer inference analysis and the execution of each CWE module
+//! (see [`Phase`]).
+//! The results of each phase are saved to the checkpoint as soon as the phase is completed.
+//! If the run is interrupted, a new run resuming from the checkpoint skips the completed phases.
+//! The string abstraction is not saved to checkpoints, but recomputed on each run.
+//!
+//! The checkpoint also contains hashes of the analyzed project and of the analysis settings.
+//! Resuming from a checkpoint fails if the hashes do not match,
+//! since the saved results would not be valid for a different project or different settings.
+//!
+//! The results are serialized as YAML,
+//! since several of them contain maps whose keys are not strings.
+
+use crate::intermediate_representation::Project;
+use crate::prelude::*;
+use serde::de::DeserializeOwned;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// The name of the file containing the manifest of a checkpoint.
+const MANIFEST_FILE_NAME: &str = "manifest.yaml";
+
+/// The name of the subdirectory containing the results of the completed phases.
+const PHASES_DIR_NAME: &str = "phases";
+
+/// The version of the checkpoint format.
+/// Checkpoints with a different version cannot be resumed.
+const CHECKPOINT_VERSION: &str = "0.1";
+
+/// A phase of an analysis run whose results can be saved to a checkpoint.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Phase {
+    /// The computation of the function signatures.
+    FunctionSignatures,
+    /// The computation of the pointer inference analysis.
+    PointerInference,
+    /// The execution of the CWE module with the given name.
+    Module(String),
+}
+
+impl Phase {
+    /// Get the name of the file containing the results of the phase.
+    fn get_file_name(&self) -> String {
+        match self {
+            Phase::FunctionSignatures => "function_signatures.yaml".to_string(),
+            Phase::PointerInference => "pointer_inference.yaml".to_string(),
+            Phase::Module(name) => format!("module_{}.yaml", name),
+        }
+    }
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::FunctionSignatures => write!(formatter, "function signatures"),
+            Phase::PointerInference => write!(formatter, "pointer inference"),
+            Phase::Module(name) => write!(formatter, "module {}", name),
+        }
+    }
+}
+
+/// The location of the checkpoint of an analysis run
+/// and whether the run shall resume from it.
+#[derive(Debug, Clone, Copy)]
+pub struct CheckpointOptions<'a> {
+    /// The checkpoint directory.
+    pub directory: &'a Path,
+    /// If set, the run resumes from the existing checkpoint in the directory.
+    /// Otherwise a new checkpoint is created, replacing the results of previous runs.
+    pub resume: bool,
+}
+
+/// The manifest of a checkpoint identifying the project and settings that the results belong to.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+struct Manifest {
+    /// The version of the checkpoint format.
+    version: String,
+    /// The hash of the analyzed project.
+    project_hash: u64,
+    /// The hash of the analysis settings.
+    settings_hash: u64,
+}
+
+impl Manifest {
+    /// Generate the manifest for the given project and analysis settings.
+    fn new(project: &Project, settings: &serde_json::Value) -> Result<Manifest, Error> {
+        Ok(Manifest {
+            version: CHECKPOINT_VERSION.to_string(),
+            project_hash: get_hash(&serde_yaml::to_string(project)?),
+            settings_hash: get_hash(&settings.to_string()),
+        })
+    }
+}
+
+/// Get the hash of a string.
+/// The hash does not change between runs of the same build of the cwe_checker.
+fn get_hash(string: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    string.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A checkpoint directory of an analysis run.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// The checkpoint directory.
+    directory: PathBuf,
+}
+
+impl Checkpoint {
+    /// Create a new checkpoint for the given project and analysis settings.
+    ///
+    /// The directory is created if it does not exist.
+    /// Results of previous runs contained in the directory are removed.
+    pub fn create(
+        directory: &Path,
+        project: &Project,
+        settings: &serde_json::Value,
+    ) -> Result<Checkpoint, Error> {
+        let checkpoint = Checkpoint {
+            directory: directory.to_path_buf(),
+        };
+        let phases_dir = checkpoint.get_phases_dir();
+        if phases_dir.exists() {
+            std::fs::remove_dir_all(&phases_dir)?;
+        }
+        std::fs::create_dir_all(&phases_dir)?;
+        write_file(
+            &directory.join(MANIFEST_FILE_NAME),
+            &serde_yaml::to_string(&Manifest::new(project, settings)?)?,
+        )?;
+        Ok(checkpoint)
+    }
+
+    /// Open the existing checkpoint in the given directory to resume an analysis run from it.
+    ///
+    /// Returns an error if the directory does not contain a checkpoint
+    /// or if the checkpoint was created for a different project or different analysis settings.
+    pub fn resume(
+        directory: &Path,
+        project: &Project,
+        settings: &serde_json::Value,
+    ) -> Result<Checkpoint, Error> {
+        let manifest_path = directory.join(MANIFEST_FILE_NAME);
+        let manifest: Manifest =
+            serde_yaml::from_str(&std::fs::read_to_string(&manifest_path).map_err(|err| {
+                anyhow!("No checkpoint found in {}: {}", directory.display(), err)
+            })?)?;
+        let expected_manifest = Manifest::new(project, settings)?;
+        if manifest.version != expected_manifest.version {
+            return Err(anyhow!(
+                "The checkpoint has version {}, but version {} is required",
+                manifest.version,
+                expected_manifest.version
+            ));
+        }
+        if manifest.project_hash != expected_manifest.project_hash {
+            return Err(anyhow!("The checkpoint was created for a different binary"));
+        }
+        if manifest.settings_hash != expected_manifest.settings_hash {
+            return Err(anyhow!(
+                "The checkpoint was created with different analysis settings"
+            ));
+        }
+        let checkpoint = Checkpoint {
+            directory: directory.to_path_buf(),
+        };
+        std::fs::create_dir_all(checkpoint.get_phases_dir())?;
+        Ok(checkpoint)
+    }
+
+    /// Create a new checkpoint or resume from an existing one according to the given options.
+    pub fn open(
+        options: &CheckpointOptions,
+        project: &Project,
+        settings: &serde_json::Value,
+    ) -> Result<Checkpoint, Error> {
+        if options.resume {
+            Checkpoint::resume(options.directory, project, settings)
+        } else {
+            Checkpoint::create(options.directory, project, settings)
+        }
+    }
+
+    /// Load the results of the given phase.
+    /// Returns `None` if the phase was not completed yet.
+    pub fn load<T: DeserializeOwned>(&self, phase: &Phase) -> Result<Option<T>, Error> {
+        let path = self.get_phases_dir().join(phase.get_file_name());
+        if !path.exists() {
+            return Ok(None);
+        }
+        let results = serde_yaml::from_str(&std::fs::read_to_string(&path)?).map_err(|err| {
+            anyhow!(
+                "Could not parse the saved results of the {}: {}",
+                phase,
+                err
+            )
+        })?;
+        Ok(Some(results))
+    }
+
+    /// Save the results of the given phase.
+    ///
+    /// The results are first written to a temporary file, which is then moved to its final location.
+    /// Thus an interruption of the run while saving cannot leave incomplete results in the checkpoint.
+    pub fn save<T: Serialize>(&self, phase: &Phase, results: &T) -> Result<(), Error> {
+        let path = self.get_phases_dir().join(phase.get_file_name());
+        write_file(&path, &serde_yaml::to_string(results)?)
+    }
+
+    /// Check whether the results of the given phase are contained in the checkpoint.
+    pub fn is_completed(&self, phase: &Phase) -> bool {
+        self.get_phases_dir().join(phase.get_file_name()).exists()
+    }
+
+    /// Get the path of the subdirectory containing the results of the completed phases.
+    fn get_phases_dir(&self) -> PathBuf {
+        self.directory.join(PHASES_DIR_NAME)
+    }
+}
+
+/// Write the content to the given path by first writing to a temporary file
+/// and then moving it to the given path.
+fn write_file(path: &Path, content: &str) -> Result<(), Error> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    std::fs::write(&temp_path, content)?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::Variable;
+
+    #[test]
+    fn resume_checks_hashes() {
+        let dir = std::env::temp_dir().join(format!(
+            "cwe_checker_checkpoint_test_{}",
+            std::process::id()
+        ));
+        let project = Project::mock_empty();
+        let settings = serde_json::json!({"config": {"CWE676": {"symbols": ["strcpy"]}}});
+        let checkpoint = Checkpoint::create(&dir, &project, &settings).unwrap();
+        assert!(!checkpoint.is_completed(&Phase::Module("CWE676".to_string())));
+        checkpoint
+            .save(&Phase::Module("CWE676".to_string()), &vec![1u64, 2, 3])
+            .unwrap();
+
+        let resumed = Checkpoint::resume(&dir, &project, &settings).unwrap();
+        assert_eq!(
+            resumed
+                .load::<Vec<u64>>(&Phase::Module("CWE676".to_string()))
+                .unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            resumed
+                .load::<Vec<u64>>(&Phase::Module("CWE476".to_string()))
+                .unwrap(),
+            None
+        );
+
+        let other_settings = serde_json::json!({"config": {"CWE676": {"symbols": []}}});
+        assert!(Checkpoint::resume(&dir, &project, &other_settings).is_err());
+        let mut other_project = project.clone();
+        other_project.stack_pointer_register = Variable::mock("ESP", 4);
+        assert!(Checkpoint::resume(&dir, &other_project, &settings).is_err());
+
+        // Creating a new checkpoint removes the results of previous runs.
+        let checkpoint = Checkpoint::create(&dir, &project, &settings).unwrap();
+        assert!(!checkpoint.is_completed(&Phase::Module("CWE676".to_string())));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(Checkpoint::resume(&dir, &project, &settings).is_err());
+    }
+}
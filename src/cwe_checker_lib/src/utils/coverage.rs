@@ -0,0 +1,352 @@
+//! Metrics on how much of the binary the analysis actually understood.
+//!
+//! The [`CoverageMetrics`] are collected from the control flow graph,
+//! the pointer inference analysis and the configuration of the executed checks.
+//! They are part of the JSON output and summarized in the summary output.
+
+use crate::analysis::graph::{Graph, Node};
+use crate::analysis::pointer_inference::PointerInference;
+use crate::analysis::taint_sources::{compute_derived_taint_sources, get_configured_taint_sources};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::symbol_utils::{get_callsites, get_symbol_map};
+use crate::CweModule;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// A number of covered items out of a total number of items.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct CoverageCount {
+    /// The total number of items.
+    pub total: u64,
+    /// The number of covered items.
+    pub covered: u64,
+}
+
+impl CoverageCount {
+    /// The fraction of covered items in percent.
+    /// Returns `None` if there are no items at all.
+    pub fn percentage(&self) -> Option<f64> {
+        (self.total > 0).then(|| self.covered as f64 / self.total as f64 * 100.)
+    }
+}
+
+impl std::fmt::Display for CoverageCount {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.percentage() {
+            Some(percentage) => write!(formatter, "{:.1}% of {}", percentage, self.total),
+            None => write!(formatter, "none of 0"),
+        }
+    }
+}
+
+/// Metrics on how much of the binary the analysis understood.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct CoverageMetrics {
+    /// The instructions of the binary and how many of them are contained in analyzed functions.
+    pub instructions: CoverageCount,
+    /// The call sites in analyzed functions and how many of them have a known callee.
+    pub call_sites: CoverageCount,
+    /// The memory accesses evaluated by the pointer inference analysis
+    /// and how many of them had an address that was not completely unknown.
+    /// Only present if the pointer inference analysis was computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_accesses: Option<CoverageCount>,
+    /// Maps the names of the executed checks to the number of call sites
+    /// of the extern symbols configured for the check (e.g. the `system`-like sinks of CWE-78).
+    /// Checks without configured symbols are not contained.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checker_applicability: BTreeMap<String, u64>,
+    /// Maps the names of the executed checks to the internal functions
+    /// that the check treats as derived taint sources, since they wrap a configured taint source.
+    /// Checks without derived taint sources are not contained.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub derived_taint_sources: BTreeMap<String, Vec<String>>,
+}
+
+impl CoverageMetrics {
+    /// Collect the instruction and call site coverage.
+    ///
+    /// A function counts as analyzed if it is contained in the control flow graph.
+    /// Thunk functions are ignored.
+    /// Instructions are identified by their address.
+    /// Terms without a known address are counted as separate instructions.
+    pub fn new(project: &Project, graph: &Graph) -> CoverageMetrics {
+        let analyzed_subs: HashSet<&Tid> = graph
+            .node_weights()
+            .filter_map(|node| match node {
+                Node::BlkStart(_, sub) => Some(&sub.tid),
+                _ => None,
+            })
+            .collect();
+        let mut all_instructions = BTreeSet::new();
+        let mut analyzed_instructions = BTreeSet::new();
+        let mut call_sites = CoverageCount::default();
+        for sub in project.program.term.subs.values() {
+            if sub.term.is_thunk {
+                // Thunk functions are not part of the graph, since calls to them are handled as calls to extern symbols.
+                continue;
+            }
+            let is_analyzed = analyzed_subs.contains(&sub.tid);
+            for block in sub.term.blocks.iter() {
+                let term_tids = block
+                    .term
+                    .defs
+                    .iter()
+                    .map(|def| &def.tid)
+                    .chain(block.term.jmps.iter().map(|jmp| &jmp.tid));
+                for tid in term_tids {
+                    let instruction = if tid.address == "UNKNOWN" {
+                        format!("{}", tid)
+                    } else {
+                        tid.address.clone()
+                    };
+                    if is_analyzed {
+                        analyzed_instructions.insert(instruction.clone());
+                    }
+                    all_instructions.insert(instruction);
+                }
+                if !is_analyzed {
+                    continue;
+                }
+                for jmp in block.term.jmps.iter() {
+                    match &jmp.term {
+                        Jmp::Call { target, .. } => {
+                            call_sites.total += 1;
+                            if project.program.term.subs.contains_key(target)
+                                || project.program.term.extern_symbols.contains_key(target)
+                            {
+                                call_sites.covered += 1;
+                            }
+                        }
+                        Jmp::CallInd { .. } => call_sites.total += 1,
+                        _ => (),
+                    }
+                }
+            }
+        }
+        CoverageMetrics {
+            instructions: CoverageCount {
+                total: all_instructions.len() as u64,
+                covered: analyzed_instructions.len() as u64,
+            },
+            call_sites,
+            memory_accesses: None,
+            checker_applicability: BTreeMap::new(),
+            derived_taint_sources: BTreeMap::new(),
+        }
+    }
+
+    /// Add the memory access counts of the pointer inference analysis.
+    pub fn add_pointer_inference_counts(&mut self, pointer_inference: &PointerInference) {
+        let (total, covered) = pointer_inference.get_memory_access_counts();
+        self.memory_accesses = Some(CoverageCount { total, covered });
+    }
+
+    /// Add the number of call sites of the extern symbols configured for each of the given checks.
+    ///
+    /// The configured symbols of a check are all names in lists of its configuration
+    /// whose key ends with `symbols`, e.g. `symbols` or `system_symbols`.
+    /// For checks with taint sources, the derived taint sources used by the check are added as well.
+    pub fn add_checker_applicability(
+        &mut self,
+        project: &Project,
+        modules: &[&CweModule],
+        config: &serde_json::Value,
+    ) {
+        for module in modules {
+            let taint_sources = get_configured_taint_sources(module.name, &config[module.name]);
+            if !taint_sources.is_empty() {
+                let derived_sources = compute_derived_taint_sources(project, &taint_sources);
+                if !derived_sources.is_empty() {
+                    self.derived_taint_sources.insert(
+                        module.name.to_string(),
+                        derived_sources
+                            .values()
+                            .map(|source| source.to_string())
+                            .collect(),
+                    );
+                }
+            }
+            let symbols: Vec<String> = match config[module.name].as_object() {
+                Some(module_config) => module_config
+                    .iter()
+                    .filter(|(key, _)| key.ends_with("symbols"))
+                    .filter_map(|(_, value)| value.as_array())
+                    .flatten()
+                    .filter_map(|name| name.as_str().map(|name| name.to_string()))
+                    .collect(),
+                None => continue,
+            };
+            if symbols.is_empty() {
+                continue;
+            }
+            let symbol_map = get_symbol_map(project, &symbols);
+            let num_call_sites = project
+                .program
+                .term
+                .subs
+                .values()
+                .map(|sub| get_callsites(sub, &symbol_map).len() as u64)
+                .sum();
+            self.checker_applicability
+                .insert(module.name.to_string(), num_call_sites);
+        }
+    }
+
+    /// Generate a one-paragraph human-readable summary of the metrics.
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Coverage: {} instructions are contained in analyzed functions, \
+            {} call sites have a known callee",
+            self.instructions, self.call_sites
+        );
+        if let Some(memory_accesses) = &self.memory_accesses {
+            summary += &format!(
+                " and {} evaluated memory accesses have a known address",
+                memory_accesses
+            );
+        }
+        summary += ".";
+        if !self.checker_applicability.is_empty() {
+            let applicability: Vec<String> = self
+                .checker_applicability
+                .iter()
+                .map(|(name, call_sites)| format!("{} saw {}", name, call_sites))
+                .collect();
+            summary += &format!(
+                " Call sites of the symbols relevant for each check: {}.",
+                applicability.join(", ")
+            );
+        }
+        if !self.derived_taint_sources.is_empty() {
+            let derived_sources: Vec<String> = self
+                .derived_taint_sources
+                .iter()
+                .map(|(name, sources)| format!("{} used {}", name, sources.join(", ")))
+                .collect();
+            summary += &format!(
+                " Wrapper functions treated as taint sources: {}.",
+                derived_sources.join("; ")
+            );
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    /// Create a project with a function `main` containing a load, a store through an unknown pointer,
+    /// a call to `malloc`, an indirect call and a call to the skipped function `skipped`.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("malloc");
+
+        let mut start_blk = Blk::mock_with_tid("start_blk");
+        start_blk.term.defs = vec![
+            Def::load(
+                "load_stack",
+                Variable::mock("RAX", 8),
+                Expression::var("RSP", 8).plus_const(8),
+            ),
+            Def::store(
+                "store_unknown",
+                Expression::var("RBX", 8),
+                Expression::const_from_i64(0),
+            ),
+        ];
+        start_blk.term.jmps = vec![Jmp::call("call_malloc", "malloc", Some("ind_blk"))];
+        let mut ind_blk = Blk::mock_with_tid("ind_blk");
+        ind_blk.term.jmps = vec![Term {
+            tid: Tid::new("call_ind"),
+            term: Jmp::CallInd {
+                target: Expression::var("RAX", 8),
+                return_: Some(Tid::new("skip_blk")),
+            },
+        }];
+        let mut skip_blk = Blk::mock_with_tid("skip_blk");
+        skip_blk.term.jmps = vec![Jmp::call("call_skipped", "skipped", Some("end_blk"))];
+        let end_blk = Blk::mock_with_tid("end_blk");
+        let main = Sub::mock_with_blocks("main", vec![start_blk, ind_blk, skip_blk, end_blk]);
+        let mut skipped_blk = Blk::mock_with_tid("skipped_blk");
+        skipped_blk.term.defs = vec![Def::assign(
+            "skipped_def",
+            Variable::mock("RAX", 8),
+            Expression::const_from_i64(0),
+        )];
+        let skipped = Sub::mock_with_blocks("skipped", vec![skipped_blk]);
+        for sub in [main, skipped] {
+            project.program.term.subs.insert(sub.tid.clone(), sub);
+        }
+        project.program.term.entry_points.insert(Tid::new("main"));
+        project
+    }
+
+    #[test]
+    fn coverage_counters() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let extern_subs = project
+            .program
+            .term
+            .extern_symbols
+            .keys()
+            .cloned()
+            .collect();
+        let graph = crate::analysis::graph::get_program_cfg_with_skipped_subs(
+            &project.program,
+            extern_subs,
+            HashSet::from([Tid::new("skipped")]),
+        );
+        let mut pointer_inference = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pointer_inference.compute();
+
+        let mut metrics = CoverageMetrics::new(&project, &graph);
+        // 2 defs and 3 jumps in `main`, 1 def in `skipped`.
+        assert_eq!(
+            metrics.instructions,
+            CoverageCount {
+                total: 6,
+                covered: 5
+            }
+        );
+        // The indirect call has no known callee.
+        assert_eq!(
+            metrics.call_sites,
+            CoverageCount {
+                total: 3,
+                covered: 2
+            }
+        );
+
+        metrics.add_pointer_inference_counts(&pointer_inference);
+        let memory_accesses = metrics.memory_accesses.unwrap();
+        assert!(memory_accesses.total >= 2);
+        assert!(memory_accesses.covered > 0);
+        assert!(memory_accesses.covered < memory_accesses.total);
+
+        let module = CweModule {
+            name: "CWE_TEST",
+            version: "0.1",
+            run: |_, _| (Vec::new(), Vec::new()),
+            required_analyses: &[],
+            pointer_inference_retention:
+                crate::analysis::pointer_inference::NodeValueRetention::EntryExitOnly,
+        };
+        let config = serde_json::json!({
+            "CWE_TEST": { "sink_symbols": ["malloc", "free"], "other": ["malloc"] }
+        });
+        metrics.add_checker_applicability(&project, &[&module], &config);
+        assert_eq!(metrics.checker_applicability["CWE_TEST"], 1);
+        assert!(metrics.summary().contains("CWE_TEST saw 1"));
+    }
+}
@@ -0,0 +1,275 @@
+//! Feedback on where the analysis results are imprecise.
+//!
+//! Checks report an [`ImprecisionReport`] through [`report_imprecision`]
+//! whenever they suppress or downgrade a finding because of unknown values,
+//! e.g. because the size of an accessed memory object is unknown.
+//! The reports are transported as debug log messages,
+//! so that they need no separate channel from the checks to the analysis pipeline.
+//!
+//! If requested, the pipeline aggregates the reports into a [`FeedbackReport`],
+//! which ranks the extern symbols and functions responsible for the imprecision by the number of reports.
+//! It shows where annotations or prototypes (e.g. for the responsible extern symbols)
+//! would improve the analysis results the most.
+
+use crate::intermediate_representation::Project;
+use crate::prelude::*;
+use crate::utils::log::LogMessage;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The reason why a check could not decide whether to generate a finding.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum ImprecisionReason {
+    /// The size of an accessed memory object is unknown.
+    UnknownObjectSize,
+    /// The targets of a pointer are unknown.
+    TopPointer,
+    /// The effects of a call to an extern symbol are unknown.
+    UnknownExtern,
+}
+
+impl std::fmt::Display for ImprecisionReason {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImprecisionReason::UnknownObjectSize => write!(formatter, "unknown object size"),
+            ImprecisionReason::TopPointer => write!(formatter, "unknown pointer target"),
+            ImprecisionReason::UnknownExtern => write!(formatter, "unknown extern function"),
+        }
+    }
+}
+
+/// The fact that a check would have needed to decide whether to generate a finding.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct MissingFact {
+    /// A short description of the missing fact.
+    pub description: String,
+    /// The extern symbol responsible for the missing fact, if known,
+    /// e.g. the allocation function of a memory object with unknown size.
+    pub extern_symbol: Option<String>,
+}
+
+impl MissingFact {
+    /// Create a new missing fact with the given description and without responsible extern symbol.
+    pub fn new(description: impl ToString) -> MissingFact {
+        MissingFact {
+            description: description.to_string(),
+            extern_symbol: None,
+        }
+    }
+
+    /// Set the extern symbol responsible for the missing fact.
+    pub fn extern_symbol(mut self, extern_symbol: Option<String>) -> MissingFact {
+        self.extern_symbol = extern_symbol;
+        self
+    }
+}
+
+/// A program point where a check suppressed or downgraded a finding because of unknown values.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub struct ImprecisionReport {
+    /// The name of the check, e.g. `CWE476`.
+    pub checker: String,
+    /// The TID of the program point.
+    pub location: Tid,
+    /// The reason for the imprecision.
+    pub reason: ImprecisionReason,
+    /// The fact that would have been needed for a precise result.
+    pub missing_fact: MissingFact,
+}
+
+/// Generate the log message reporting that the given check suppressed or downgraded a finding
+/// at the given program point because of unknown values.
+///
+/// The message is a debug log message, so it is only shown to the user in verbose mode.
+pub fn report_imprecision(
+    checker: &str,
+    tid: &Tid,
+    reason: ImprecisionReason,
+    missing_fact: MissingFact,
+) -> LogMessage {
+    let mut log_message = LogMessage::new_debug(format!(
+        "Imprecise result due to {}: {}",
+        reason, missing_fact.description
+    ))
+    .location(tid.clone())
+    .source(checker);
+    log_message.imprecision = Some(Box::new(ImprecisionReport {
+        checker: checker.to_string(),
+        location: tid.clone(),
+        reason,
+        missing_fact,
+    }));
+    log_message
+}
+
+/// Remove all log messages containing imprecision reports from the given logs
+/// and return the contained reports.
+pub fn extract_imprecision_reports(logs: &mut Vec<LogMessage>) -> Vec<ImprecisionReport> {
+    let mut reports = Vec::new();
+    logs.retain_mut(|log| match log.imprecision.take() {
+        Some(report) => {
+            reports.push(*report);
+            false
+        }
+        None => true,
+    });
+    reports
+}
+
+/// The origin of missing facts that imprecision reports are attributed to.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub enum Responsible {
+    /// The extern symbol with the given name, e.g. an allocation function.
+    ExternSymbol(String),
+    /// The function with the given name, if no responsible extern symbol is known.
+    CodeRegion(String),
+}
+
+/// The aggregated imprecision reports attributed to the same extern symbol or code region.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct FeedbackEntry {
+    /// The extern symbol or code region responsible for the imprecision.
+    pub responsible: Responsible,
+    /// The number of imprecision reports attributed to it.
+    pub count: u64,
+    /// The number of reports for each reason.
+    pub reasons: BTreeMap<ImprecisionReason, u64>,
+    /// The checks that reported the imprecision.
+    pub checkers: BTreeSet<String>,
+    /// The addresses of the program points where the imprecision was reported.
+    pub addresses: BTreeSet<String>,
+}
+
+/// The imprecision reports of an analysis run ranked by the extern symbols and code regions responsible for them.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct FeedbackReport {
+    /// The total number of imprecision reports.
+    pub total: u64,
+    /// The aggregated reports, sorted by decreasing count.
+    pub entries: Vec<FeedbackEntry>,
+}
+
+impl FeedbackReport {
+    /// Aggregate the given imprecision reports.
+    ///
+    /// Reports are attributed to the extern symbol responsible for the missing fact if it is known.
+    /// Otherwise they are attributed to the function of the project containing the program point of the report.
+    pub fn new(reports: &[ImprecisionReport], project: &Project) -> FeedbackReport {
+        let term_to_function_map = get_term_to_function_map(project);
+        let mut entries: BTreeMap<Responsible, FeedbackEntry> = BTreeMap::new();
+        for report in reports {
+            let responsible = match &report.missing_fact.extern_symbol {
+                Some(symbol) => Responsible::ExternSymbol(symbol.clone()),
+                None => Responsible::CodeRegion(
+                    term_to_function_map
+                        .get(&report.location)
+                        .map(|name| name.to_string())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                ),
+            };
+            let entry = entries
+                .entry(responsible.clone())
+                .or_insert_with(|| FeedbackEntry {
+                    responsible,
+                    count: 0,
+                    reasons: BTreeMap::new(),
+                    checkers: BTreeSet::new(),
+                    addresses: BTreeSet::new(),
+                });
+            entry.count += 1;
+            *entry.reasons.entry(report.reason).or_insert(0) += 1;
+            entry.checkers.insert(report.checker.clone());
+            entry
+                .addresses
+                .insert(report.location.canonical_address().to_string());
+        }
+        let mut entries: Vec<FeedbackEntry> = entries.into_values().collect();
+        // The sort is stable, so entries with the same count stay ordered by their responsible origin.
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+        FeedbackReport {
+            total: reports.len() as u64,
+            entries,
+        }
+    }
+}
+
+/// Map the TIDs of all blocks, `Def` and `Jmp` terms of the project to the name of the function containing them.
+fn get_term_to_function_map(project: &Project) -> HashMap<Tid, &str> {
+    let mut map = HashMap::new();
+    for sub in project.program.term.subs.values() {
+        for block in sub.term.blocks.iter() {
+            map.insert(block.tid.clone(), sub.term.name.as_str());
+            for def in block.term.defs.iter() {
+                map.insert(def.tid.clone(), sub.term.name.as_str());
+            }
+            for jmp in block.term.jmps.iter() {
+                map.insert(jmp.tid.clone(), sub.term.name.as_str());
+            }
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intermediate_representation::{Blk, Expression, Jmp, Sub};
+
+    #[test]
+    fn reports_are_ranked_by_responsible_origin() {
+        let mut project = Project::mock_empty();
+        let mut block = Blk::mock_with_tid("blk");
+        block.term.jmps.push(Term {
+            tid: Tid::new("call"),
+            term: Jmp::Return(Expression::const_from_i32(0)),
+        });
+        let mut sub = Sub::mock("func");
+        sub.term.blocks.push(block);
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+
+        let mut logs = vec![
+            LogMessage::new_debug("other message"),
+            report_imprecision(
+                "CWE787",
+                &Tid::new("call"),
+                ImprecisionReason::UnknownObjectSize,
+                MissingFact::new("size").extern_symbol(Some("malloc".to_string())),
+            ),
+            report_imprecision(
+                "CWE125",
+                &Tid::new("call"),
+                ImprecisionReason::UnknownObjectSize,
+                MissingFact::new("size").extern_symbol(Some("malloc".to_string())),
+            ),
+            report_imprecision(
+                "CWE476",
+                &Tid::new("call"),
+                ImprecisionReason::TopPointer,
+                MissingFact::new("pointer target"),
+            ),
+        ];
+        let reports = extract_imprecision_reports(&mut logs);
+        assert_eq!(logs, vec![LogMessage::new_debug("other message")]);
+        assert_eq!(reports.len(), 3);
+
+        let feedback = FeedbackReport::new(&reports, &project);
+        assert_eq!(feedback.total, 3);
+        assert_eq!(feedback.entries.len(), 2);
+        assert_eq!(
+            feedback.entries[0].responsible,
+            Responsible::ExternSymbol("malloc".to_string())
+        );
+        assert_eq!(feedback.entries[0].count, 2);
+        assert_eq!(
+            feedback.entries[0].checkers,
+            BTreeSet::from(["CWE125".to_string(), "CWE787".to_string()])
+        );
+        assert_eq!(
+            feedback.entries[1].responsible,
+            Responsible::CodeRegion("func".to_string())
+        );
+        assert_eq!(
+            feedback.entries[1].reasons,
+            BTreeMap::from([(ImprecisionReason::TopPointer, 1)])
+        );
+    }
+}
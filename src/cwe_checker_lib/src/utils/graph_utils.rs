@@ -1,11 +1,10 @@
 //! Helper functions for common tasks utilizing the control flow graph of the binary.
 
 use crate::analysis::graph::*;
-use crate::intermediate_representation::Jmp;
-use crate::prelude::*;
+use crate::intermediate_representation::*;
 use petgraph::graph::NodeIndex;
 use petgraph::visit::EdgeRef;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// Check whether a call to the `sink_symbol` is reachable from the given `source_node`
 /// through a path of intraprocedural edges in the control flow graph.
@@ -59,3 +58,54 @@ pub fn is_sink_call_reachable_from_source_call(
     }
     None
 }
+
+/// Map the TIDs of the blocks of the function to the TIDs of their successor blocks inside the function.
+pub fn get_block_successors(sub: &Term<Sub>) -> HashMap<&Tid, Vec<&Tid>> {
+    sub.term
+        .blocks
+        .iter()
+        .map(|block| {
+            let successors = block.term.jmps.iter().flat_map(get_jmp_targets).collect();
+            (&block.tid, successors)
+        })
+        .collect()
+}
+
+/// Get the blocks that the given jump may jump to inside the same function.
+pub fn get_jmp_targets(jmp: &Term<Jmp>) -> Vec<&Tid> {
+    match &jmp.term {
+        Jmp::Branch(target) | Jmp::CBranch { target, .. } => vec![target],
+        Jmp::Call {
+            return_: Some(return_),
+            ..
+        }
+        | Jmp::CallInd {
+            return_: Some(return_),
+            ..
+        }
+        | Jmp::CallOther {
+            return_: Some(return_),
+            ..
+        } => vec![return_],
+        _ => Vec::new(),
+    }
+}
+
+/// Get all blocks reachable from the given start blocks without passing through the block `barrier`.
+pub fn get_reachable_blocks<'a>(
+    successors: &HashMap<&'a Tid, Vec<&'a Tid>>,
+    start: Vec<&'a Tid>,
+    barrier: &Tid,
+) -> HashSet<&'a Tid> {
+    let mut reachable = HashSet::new();
+    let mut worklist = start;
+    while let Some(block) = worklist.pop() {
+        if block == barrier || !reachable.insert(block) {
+            continue;
+        }
+        if let Some(block_successors) = successors.get(block) {
+            worklist.extend(block_successors.iter().copied());
+        }
+    }
+    reachable
+}
@@ -1,9 +1,15 @@
 //! Structs and functions for generating log messages and CWE warnings.
 
+use crate::abstract_domain::{DataDomain, IntervalDomain, RegisterDomain};
 use crate::prelude::*;
+use crate::utils::feedback::ImprecisionReport;
 use std::{collections::BTreeMap, thread::JoinHandle};
 
 /// A CWE warning message.
+///
+/// The struct is part of the [JSON output schema](crate::utils::output::schema).
+/// The order of its fields determines the key order in the JSON output
+/// and new fields have to be optional to keep the schema backwards compatible.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord, Default)]
 pub struct CweWarning {
     /// A short name of the CWE check, e.g. `CWE190`.
@@ -12,9 +18,12 @@ pub struct CweWarning {
     pub version: String,
     /// Addresses in the binary associated with the CWE warning.
     /// The first address usually denotes the program point where the CWE warning was generated.
+    /// The addresses are canonical machine instruction addresses without IR-internal suffixes
+    /// (see [`Tid::canonicalize_address`]).
     pub addresses: Vec<String>,
     /// Term IDs associated to the CWE warning.
-    /// May be more exact than the addresses, e.g. for `Def` terms.
+    /// May be more exact than the addresses, e.g. for `Def` terms,
+    /// since they retain the IR-internal suffixes of terms belonging to the same machine instruction.
     pub tids: Vec<String>,
     /// Symbol names (usually of extern symbols) associated to the CWE warning.
     pub symbols: Vec<String>,
@@ -24,6 +33,19 @@ pub struct CweWarning {
     /// Should contain all essential information necessary to understand the warning,
     /// including the address in the binary for which the warning was generated.
     pub description: String,
+    /// The abstract facts from which the warning was derived.
+    /// Only generated by some checks and only if requested, since the evidence may be large.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<WarningContext>,
+    /// The exploit primitive that the flagged memory access may provide to an attacker.
+    /// Only generated for out-of-bounds memory accesses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exploit_primitive: Option<ExploitPrimitive>,
+    /// A machine-readable suggestion how to fix the cause of the warning.
+    /// Only generated by some checks and only if the fix is mechanical.
+    /// Added in schema version 1.3.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<Remediation>,
 }
 
 impl CweWarning {
@@ -41,12 +63,19 @@ impl CweWarning {
             symbols: Vec::new(),
             other: Vec::new(),
             description: description.to_string(),
+            evidence: None,
+            exploit_primitive: None,
+            remediation: None,
         }
     }
 
-    /// Sets the address field of the CweWarning
+    /// Sets the address field of the CweWarning.
+    /// IR-internal suffixes are stripped from the addresses.
     pub fn addresses(mut self, addresses: Vec<String>) -> CweWarning {
-        self.addresses = addresses;
+        self.addresses = addresses
+            .iter()
+            .map(|address| Tid::canonicalize_address(address).to_string())
+            .collect();
         self
     }
 
@@ -67,6 +96,213 @@ impl CweWarning {
         self.other = other;
         self
     }
+
+    /// Sets the evidence field of the CweWarning
+    pub fn evidence(mut self, evidence: Option<WarningContext>) -> CweWarning {
+        self.evidence = evidence;
+        self
+    }
+
+    /// Sets the exploit primitive field of the CweWarning
+    pub fn exploit_primitive(mut self, exploit_primitive: Option<ExploitPrimitive>) -> CweWarning {
+        self.exploit_primitive = exploit_primitive;
+        self
+    }
+
+    /// Sets the remediation field of the CweWarning
+    pub fn remediation(mut self, remediation: Option<Remediation>) -> CweWarning {
+        self.remediation = remediation;
+        self
+    }
+}
+
+/// A machine-readable suggestion how to fix the cause of a CWE warning.
+///
+/// The suggestions refer to the source code that the analyzed binary was compiled from,
+/// i.e. function, flag and value names are given as they would appear in the source code.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Remediation {
+    /// Replace the call to the function `from` by a call to the function `to`,
+    /// appending the `added_args` to the arguments of the call.
+    ReplaceCall {
+        /// The name of the called function.
+        from: String,
+        /// The name of the replacement function.
+        to: String,
+        /// The arguments to append to the arguments of the original call.
+        added_args: Vec<String>,
+    },
+    /// Add the `flag` to the flags argument of the call to the function `call`.
+    AddFlag {
+        /// The name of the called function.
+        call: String,
+        /// The name of the flag to add.
+        flag: String,
+    },
+    /// Check that the `value` does not exceed the `bound` before it is used.
+    AddCheck {
+        /// A description of the value to check.
+        value: String,
+        /// The upper bound for the value.
+        bound: u64,
+    },
+    /// A suggestion that cannot be expressed by one of the structured variants.
+    FreeText {
+        /// The suggestion.
+        text: String,
+    },
+}
+
+impl std::fmt::Display for Remediation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Remediation::ReplaceCall {
+                from,
+                to,
+                added_args,
+            } => {
+                write!(formatter, "replace {} by {}", from, to)?;
+                if !added_args.is_empty() {
+                    write!(formatter, " with added arguments {}", added_args.join(", "))?;
+                }
+                Ok(())
+            }
+            Remediation::AddFlag { call, flag } => {
+                write!(formatter, "add {} to the flags of {}", flag, call)
+            }
+            Remediation::AddCheck { value, bound } => {
+                write!(formatter, "check that {} is at most {:#x}", value, bound)
+            }
+            Remediation::FreeText { text } => write!(formatter, "{}", text),
+        }
+    }
+}
+
+/// The classes of exploit primitives that an out-of-bounds memory access may provide,
+/// ordered by increasing severity.
+///
+/// Security teams use the class to triage out-of-bounds writes.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ExploitPrimitive {
+    /// The access is at a constant offset outside the bounds of the memory object.
+    FixedOffsetOverflow,
+    /// The offset of the access relative to the memory object is not constant,
+    /// e.g. because it depends on an index controlled by input data.
+    ControlledOffset,
+    /// A library call accesses a memory object through a pointer with a constant offset,
+    /// but the number of accessed bytes is not constant.
+    ControlledSizeLinearOverflow,
+    /// The accessed address is not restricted to known memory objects,
+    /// e.g. because the address or its offset is (almost) arbitrary.
+    ArbitraryWrite,
+}
+
+impl std::fmt::Display for ExploitPrimitive {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExploitPrimitive::FixedOffsetOverflow => write!(formatter, "fixed-offset overflow"),
+            ExploitPrimitive::ControlledOffset => write!(formatter, "controlled offset"),
+            ExploitPrimitive::ControlledSizeLinearOverflow => {
+                write!(formatter, "controlled-size linear overflow")
+            }
+            ExploitPrimitive::ArbitraryWrite => write!(formatter, "arbitrary write"),
+        }
+    }
+}
+
+/// The abstract facts that a CWE warning was derived from,
+/// e.g. the values of the relevant registers and the bounds of the accessed memory objects.
+///
+/// The entries are serialized as a JSON object with the entry names as keys.
+/// Abstract values should be given in their compact JSON representation (`to_json_compact`).
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(transparent)]
+pub struct WarningContext {
+    entries: BTreeMap<String, serde_json::Value>,
+}
+
+impl WarningContext {
+    /// Create an empty warning context.
+    pub fn new() -> WarningContext {
+        WarningContext::default()
+    }
+
+    /// Add an arbitrary entry to the warning context.
+    pub fn value(mut self, name: impl ToString, value: serde_json::Value) -> WarningContext {
+        self.entries.insert(name.to_string(), value);
+        self
+    }
+
+    /// Add a pointer value together with the memory objects that it may point to.
+    ///
+    /// The `targets` map object names to the compact JSON representation of the objects.
+    /// The pointer is stored under the key `value` and the targets under the key `targets` of the entry.
+    pub fn pointer_with_targets<T: RegisterDomain + std::fmt::Display>(
+        self,
+        name: impl ToString,
+        pointer: &DataDomain<T>,
+        targets: serde_json::Map<String, serde_json::Value>,
+    ) -> WarningContext {
+        let mut entry = serde_json::Map::new();
+        entry.insert("value".to_string(), pointer.to_json_compact());
+        entry.insert("targets".to_string(), serde_json::Value::Object(targets));
+        self.value(name, serde_json::Value::Object(entry))
+    }
+
+    /// Add an interval of possible sizes, e.g. the evaluated size parameter of a call.
+    pub fn size_interval(self, name: impl ToString, size: &IntervalDomain) -> WarningContext {
+        self.value(name, serde_json::Value::String(format!("{}", size)))
+    }
+
+    /// Add the chain of taint sources that lead to the warning under the key `taint_sources`.
+    /// The first element should be the original source of the taint.
+    pub fn taint_source_chain(
+        self,
+        sources: impl IntoIterator<Item = impl ToString>,
+    ) -> WarningContext {
+        let chain = sources
+            .into_iter()
+            .map(|source| serde_json::Value::String(source.to_string()))
+            .collect();
+        self.value("taint_sources", serde_json::Value::Array(chain))
+    }
+
+    /// Get the entry with the given name.
+    pub fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        self.entries.get(name)
+    }
+}
+
+// JSON values are neither hashable nor ordered,
+// so we use their (canonical) string representation instead.
+impl std::hash::Hash for WarningContext {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for (name, value) in self.entries.iter() {
+            name.hash(state);
+            value.to_string().hash(state);
+        }
+    }
+}
+
+impl PartialOrd for WarningContext {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WarningContext {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let to_strings = |context: &WarningContext| -> Vec<(String, String)> {
+            context
+                .entries
+                .iter()
+                .map(|(name, value)| (name.clone(), value.to_string()))
+                .collect()
+        };
+        to_strings(self).cmp(&to_strings(other))
+    }
 }
 
 impl std::fmt::Display for CweWarning {
@@ -90,6 +326,10 @@ pub struct LogMessage {
     pub location: Option<Tid>,
     /// The analysis where the message originated.
     pub source: Option<String>,
+    /// If set, the message reports that a check suppressed or downgraded a finding because of unknown values
+    /// (see [`report_imprecision`](crate::utils::feedback::report_imprecision)).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imprecision: Option<Box<ImprecisionReport>>,
 }
 
 impl LogMessage {
@@ -100,6 +340,7 @@ impl LogMessage {
             level: LogLevel::Info,
             location: None,
             source: None,
+            imprecision: None,
         }
     }
 
@@ -110,6 +351,7 @@ impl LogMessage {
             level: LogLevel::Debug,
             location: None,
             source: None,
+            imprecision: None,
         }
     }
 
@@ -120,6 +362,7 @@ impl LogMessage {
             level: LogLevel::Error,
             location: None,
             source: None,
+            imprecision: None,
         }
     }
 
@@ -176,25 +419,21 @@ pub fn print_all_messages(
     out_path: Option<&str>,
     emit_json: bool,
 ) {
-    for log in logs {
-        println!("{}", log);
-    }
-    let output: String = if emit_json {
-        serde_json::to_string_pretty(&cwes).unwrap()
+    use crate::utils::output::{AnalysisResultsOutput, OutputFormat, OutputSink};
+    let format = if emit_json {
+        OutputFormat::Json
     } else {
-        cwes.iter()
-            .map(|cwe| format!("{}", cwe))
-            .collect::<Vec<String>>()
-            .join("\n")
-            + "\n"
+        OutputFormat::Text
     };
-    if let Some(file_path) = out_path {
-        std::fs::write(file_path, output).unwrap_or_else(|error| {
-            panic!("Writing to output path {} failed: {}", file_path, error)
-        });
-    } else {
-        print!("{}", output);
-    }
+    let sink = OutputSink {
+        format,
+        path: out_path.map(std::path::PathBuf::from),
+    };
+    let output = AnalysisResultsOutput::new(logs, cwes);
+    output.print_logs();
+    output
+        .write(&sink)
+        .unwrap_or_else(|error| panic!("{}", error));
 }
 
 /// For each analysis count the number of debug log messages in `all_logs`
@@ -219,6 +458,7 @@ pub fn add_debug_log_statistics(all_logs: &mut Vec<LogMessage>) {
             level: LogLevel::Info,
             location: None,
             source: Some(analysis),
+            imprecision: None,
         });
     }
     if general_debug_log_count > 0 {
@@ -230,6 +470,7 @@ pub fn add_debug_log_statistics(all_logs: &mut Vec<LogMessage>) {
             level: LogLevel::Info,
             location: None,
             source: None,
+            imprecision: None,
         });
     }
 }
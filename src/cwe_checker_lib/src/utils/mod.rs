@@ -2,8 +2,15 @@
 
 pub mod arguments;
 pub mod binary;
+pub mod checkpoint;
+pub mod coverage;
+pub mod feedback;
 pub mod graph_utils;
 pub mod log;
+pub mod output;
+pub mod pipeline;
+pub mod query;
+pub mod results_diff;
 pub mod symbol_utils;
 
 use crate::prelude::*;
@@ -28,9 +35,15 @@ pub fn get_ghidra_plugin_path(plugin_name: &str) -> std::path::PathBuf {
 }
 
 /// Get the base address for the image of a binary when loaded into memory.
+///
+/// For relocatable ELF files (e.g. Linux kernel modules) the synthetic base address
+/// [`RELOCATABLE_BASE_ADDRESS`](binary::RELOCATABLE_BASE_ADDRESS) of their sections is returned.
 pub fn get_binary_base_address(binary: &[u8]) -> Result<u64, Error> {
     use goblin::Object;
     match Object::parse(binary)? {
+        Object::Elf(elf_file) if elf_file.header.e_type == goblin::elf::header::ET_REL => {
+            Ok(binary::RELOCATABLE_BASE_ADDRESS)
+        }
         Object::Elf(elf_file) => {
             for header in elf_file.program_headers.iter() {
                 let vm_range = header.vm_range();
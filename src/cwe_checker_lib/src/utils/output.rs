@@ -0,0 +1,667 @@
+//! Output of the analysis results in different formats.
+//!
+//! The results of a run of the cwe_checker are collected in an [`AnalysisResultsOutput`] struct.
+//! It can be written to several [`OutputSink`]s at once,
+//! e.g. human-readable text to stdout, JSON to one file and SARIF to another file.
+//! Each output format is implemented by its own [`OutputFormatter`].
+//! The JSON output follows a versioned schema defined in the [`schema`] module.
+//! The results of a batch run over several binaries are aggregated in a [`BatchReport`].
+
+use crate::analysis::pointer_inference::access_histogram::AccessHistogramsReport;
+use crate::prelude::*;
+use crate::utils::coverage::CoverageMetrics;
+use crate::utils::feedback::FeedbackReport;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::results_diff::{get_address, Fingerprint};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub mod batch;
+pub use batch::BatchReport;
+mod json;
+pub use json::JsonFormatter;
+pub mod schema;
+pub use schema::{JsonReport, SCHEMA_VERSION};
+mod sarif;
+pub use sarif::{warnings_to_sarif, SarifFormatter};
+mod summary;
+pub use summary::SummaryFormatter;
+mod text;
+pub use text::TextFormatter;
+
+/// A formatter converts the CWE warnings of an analysis run into a specific output format.
+pub trait OutputFormatter {
+    /// Generate the formatted output for the given CWE warnings.
+    fn format(&self, cwes: &[CweWarning]) -> String;
+
+    /// Generate the formatted output for the results of an analysis run.
+    ///
+    /// The default implementation only formats the CWE warnings.
+    /// Formats that can express whether the results are only partial override it.
+    fn format_output(&self, output: &AnalysisResultsOutput) -> String {
+        self.format(&output.cwes)
+    }
+}
+
+/// The supported output formats.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum OutputFormat {
+    /// Human-readable text with one line per CWE warning.
+    Text,
+    /// The CWE warnings as a JSON report following the versioned [`schema`].
+    Json,
+    /// A report in the Static Analysis Results Interchange Format (SARIF).
+    Sarif,
+    /// The number of CWE warnings for each check.
+    Summary,
+}
+
+impl OutputFormat {
+    /// Get the formatter for the output format.
+    pub fn get_formatter(&self) -> Box<dyn OutputFormatter> {
+        match self {
+            OutputFormat::Text => Box::new(TextFormatter),
+            OutputFormat::Json => Box::new(JsonFormatter),
+            OutputFormat::Sarif => Box::new(SarifFormatter),
+            OutputFormat::Summary => Box::new(SummaryFormatter),
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "sarif" => Ok(OutputFormat::Sarif),
+            "summary" => Ok(OutputFormat::Summary),
+            _ => Err(anyhow!("Unknown output format: {}", format)),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(formatter, "text"),
+            OutputFormat::Json => write!(formatter, "json"),
+            OutputFormat::Sarif => write!(formatter, "sarif"),
+            OutputFormat::Summary => write!(formatter, "summary"),
+        }
+    }
+}
+
+/// A destination for the analysis results in a specific output format.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct OutputSink {
+    /// The output format.
+    pub format: OutputFormat,
+    /// The path of the output file. If `None`, the output is written to stdout.
+    pub path: Option<PathBuf>,
+}
+
+impl OutputSink {
+    /// Parse an output sink specification of the form `<format>:<path>`.
+    ///
+    /// The path `-` denotes stdout.
+    /// If the specification does not start with a known format,
+    /// the whole specification is interpreted as a path and the `default_format` is used.
+    pub fn parse(spec: &str, default_format: OutputFormat) -> OutputSink {
+        let (format, path) = match spec.split_once(':') {
+            Some((format, path)) => match format.parse() {
+                Ok(format) => (format, path),
+                Err(_) => (default_format, spec),
+            },
+            None => (default_format, spec),
+        };
+        let path = if path == "-" {
+            None
+        } else {
+            Some(PathBuf::from(path))
+        };
+        OutputSink { format, path }
+    }
+}
+
+impl std::fmt::Display for OutputSink {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(formatter, "{}:{}", self.format, path.display()),
+            None => write!(formatter, "{}:-", self.format),
+        }
+    }
+}
+
+/// The collected results of an analysis run.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct AnalysisResultsOutput {
+    /// The log messages generated during the analysis.
+    pub logs: Vec<LogMessage>,
+    /// The CWE warnings generated during the analysis.
+    pub cwes: Vec<CweWarning>,
+    /// Whether the results are only partial,
+    /// e.g. because some functions of the binary could not be parsed.
+    #[serde(default)]
+    pub partial: bool,
+    /// Metrics on how much of the binary the analysis understood.
+    #[serde(default)]
+    pub coverage: Option<CoverageMetrics>,
+    /// The ranked imprecision reports of the checks, if requested.
+    #[serde(default)]
+    pub feedback: Option<FeedbackReport>,
+    /// The memory access histograms of the heap objects, if requested.
+    #[serde(default)]
+    pub access_histograms: Option<AccessHistogramsReport>,
+    /// The path of the analyzed binary, if known.
+    /// Output formats locating the warnings in files (like SARIF) refer to it.
+    #[serde(default)]
+    pub binary: Option<String>,
+}
+
+impl AnalysisResultsOutput {
+    /// Create a new output struct from the given log messages and CWE warnings.
+    ///
+    /// Log messages and CWE warnings are sorted (see [`sort_logs`] and [`sort_warnings`])
+    /// so that the output does not depend on the order in which they were generated.
+    pub fn new(mut logs: Vec<LogMessage>, mut cwes: Vec<CweWarning>) -> AnalysisResultsOutput {
+        sort_logs(&mut logs);
+        sort_warnings(&mut cwes);
+        AnalysisResultsOutput {
+            logs,
+            cwes,
+            partial: false,
+            coverage: None,
+            feedback: None,
+            access_histograms: None,
+            binary: None,
+        }
+    }
+
+    /// Mark the results as partial results.
+    pub fn partial(mut self, partial: bool) -> AnalysisResultsOutput {
+        self.partial = partial;
+        self
+    }
+
+    /// Add the coverage metrics of the analysis run.
+    pub fn coverage(mut self, coverage: Option<CoverageMetrics>) -> AnalysisResultsOutput {
+        self.coverage = coverage;
+        self
+    }
+
+    /// Add the ranked imprecision reports of the checks.
+    pub fn feedback(mut self, feedback: Option<FeedbackReport>) -> AnalysisResultsOutput {
+        self.feedback = feedback;
+        self
+    }
+
+    /// Add the memory access histograms of the heap objects.
+    pub fn access_histograms(
+        mut self,
+        access_histograms: Option<AccessHistogramsReport>,
+    ) -> AnalysisResultsOutput {
+        self.access_histograms = access_histograms;
+        self
+    }
+
+    /// Add the path of the analyzed binary.
+    pub fn binary(mut self, binary: Option<String>) -> AnalysisResultsOutput {
+        self.binary = binary;
+        self
+    }
+
+    /// Print all log messages to stdout.
+    pub fn print_logs(&self) {
+        for log in self.logs.iter() {
+            println!("{}", log);
+        }
+    }
+
+    /// Write the CWE warnings to the given sink in the format of the sink.
+    pub fn write(&self, sink: &OutputSink) -> Result<(), Error> {
+        let output = sink.format.get_formatter().format_output(self);
+        match &sink.path {
+            Some(path) => std::fs::write(path, output).map_err(|err| {
+                anyhow!("Writing to output path {} failed: {}", path.display(), err)
+            }),
+            None => {
+                let mut stdout = std::io::stdout();
+                stdout.write_all(output.as_bytes())?;
+                stdout.flush()?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Write the CWE warnings to all given sinks.
+    ///
+    /// An error while writing to one sink does not prevent writing to the other sinks.
+    /// The errors are returned together with the sink that caused them.
+    pub fn write_all(&self, sinks: &[OutputSink]) -> Vec<(OutputSink, Error)> {
+        sinks
+            .iter()
+            .filter_map(|sink| self.write(sink).err().map(|err| (sink.clone(), err)))
+            .collect()
+    }
+}
+
+/// Sort CWE warnings by CWE identifier, canonical address and fingerprint.
+///
+/// Warnings that agree in all three are ordered by their remaining fields,
+/// so that the resulting order is total.
+pub fn sort_warnings(cwes: &mut [CweWarning]) {
+    cwes.sort();
+    cwes.sort_by_cached_key(|cwe| (cwe.name.clone(), get_address(cwe), Fingerprint::new(cwe)));
+}
+
+/// Sort log messages by their source and location.
+///
+/// Messages with the same source and location are ordered by their remaining fields,
+/// so that the resulting order is total.
+pub fn sort_logs(logs: &mut [LogMessage]) {
+    logs.sort();
+    logs.sort_by_cached_key(|log| (log.source.clone(), log.location.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::log::{ExploitPrimitive, Remediation};
+
+    fn mock_output() -> AnalysisResultsOutput {
+        let cwes = vec![
+            CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1234")
+                .addresses(vec!["00001234".to_string()])
+                .tids(vec!["instr_00001234_0".to_string()]),
+            CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1300"),
+            CweWarning::new("CWE190", "0.1", "Integer Overflow at 0x2000"),
+        ];
+        AnalysisResultsOutput::new(Vec::new(), cwes)
+    }
+
+    #[test]
+    fn parse_output_sinks() {
+        assert_eq!(
+            OutputSink::parse("sarif:out/report.sarif", OutputFormat::Text),
+            OutputSink {
+                format: OutputFormat::Sarif,
+                path: Some(PathBuf::from("out/report.sarif")),
+            }
+        );
+        assert_eq!(
+            OutputSink::parse("text:-", OutputFormat::Json),
+            OutputSink {
+                format: OutputFormat::Text,
+                path: None,
+            }
+        );
+        // Specifications without a known format are interpreted as paths.
+        assert_eq!(
+            OutputSink::parse("C:/results.json", OutputFormat::Json),
+            OutputSink {
+                format: OutputFormat::Json,
+                path: Some(PathBuf::from("C:/results.json")),
+            }
+        );
+    }
+
+    #[test]
+    fn write_to_several_sinks() {
+        let output = mock_output();
+        let dir =
+            std::env::temp_dir().join(format!("cwe_checker_output_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let json_path = dir.join("results.json");
+        let text_path = dir.join("results.txt");
+        let sinks = vec![
+            OutputSink {
+                format: OutputFormat::Json,
+                path: Some(json_path.clone()),
+            },
+            // Writing to a nonexisting directory fails without affecting the other sinks.
+            OutputSink {
+                format: OutputFormat::Summary,
+                path: Some(dir.join("nonexisting_dir").join("summary.txt")),
+            },
+            OutputSink {
+                format: OutputFormat::Text,
+                path: Some(text_path.clone()),
+            },
+        ];
+        let errors = output.write_all(&sinks);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, sinks[1]);
+
+        let json_report =
+            JsonReport::from_json_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json_report.schema_version, SCHEMA_VERSION);
+        assert_eq!(json_report.cwes, output.cwes);
+        let text = std::fs::read_to_string(&text_path).unwrap();
+        assert_eq!(
+            text.lines().collect::<Vec<_>>(),
+            vec![
+                "[CWE190] (0.1) Integer Overflow at 0x2000",
+                "[CWE476] (0.3) NULL Pointer Dereference at 0x1300",
+                "[CWE476] (0.3) NULL Pointer Dereference at 0x1234",
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn summary_and_sarif_output() {
+        let output = mock_output();
+        assert_eq!(
+            SummaryFormatter.format(&output.cwes),
+            "CWE190: 1\nCWE476: 2\nTotal: 3\n"
+        );
+        let sarif: serde_json::Value =
+            serde_json::from_str(&SarifFormatter.format(&output.cwes)).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[2]["ruleId"], "CWE476");
+        assert_eq!(
+            results[2]["locations"][0]["physicalLocation"]["address"]["absoluteAddress"],
+            0x1234
+        );
+        let rules = sarif["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap();
+        assert_eq!(rules.len(), 2);
+    }
+
+    /// Check the constraints of the SARIF 2.1.0 JSON schema on the parts of the report generated by the cwe_checker.
+    fn assert_valid_sarif(sarif: &serde_json::Value) {
+        let is_index = |value: &serde_json::Value| value.as_i64().is_some_and(|index| index >= -1);
+        assert_eq!(sarif["version"], "2.1.0");
+        assert!(sarif["$schema"].is_string());
+        for run in sarif["runs"].as_array().unwrap() {
+            let driver = &run["tool"]["driver"];
+            assert!(driver["name"].is_string());
+            let rule_ids: Vec<&str> = driver["rules"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|rule| rule["id"].as_str().unwrap())
+                .collect();
+            let artifact_count = run
+                .get("artifacts")
+                .map_or(0, |artifacts| artifacts.as_array().unwrap().len());
+            for artifact in run
+                .get("artifacts")
+                .into_iter()
+                .flat_map(|a| a.as_array().unwrap())
+            {
+                assert!(artifact["location"]["uri"].is_string());
+            }
+            for result in run["results"].as_array().unwrap() {
+                assert!(result["message"]["text"].is_string());
+                assert!(rule_ids.contains(&result["ruleId"].as_str().unwrap()));
+                assert!(["none", "note", "warning", "error"]
+                    .contains(&result["level"].as_str().unwrap()));
+                for location in result["locations"].as_array().unwrap() {
+                    let physical_location = location["physicalLocation"].as_object().unwrap();
+                    assert!(
+                        physical_location.contains_key("address")
+                            || physical_location.contains_key("artifactLocation")
+                    );
+                    if let Some(address) = physical_location.get("address") {
+                        assert!(is_index(&address["absoluteAddress"]));
+                    }
+                    if let Some(artifact_location) = physical_location.get("artifactLocation") {
+                        assert!(artifact_location["uri"].is_string());
+                        let index = artifact_location["index"].as_i64().unwrap();
+                        assert!(index >= -1 && index < artifact_count as i64);
+                    }
+                    for logical_location in location["logicalLocations"].as_array().unwrap() {
+                        assert!(logical_location["name"].is_string());
+                    }
+                }
+                for fix in result
+                    .get("fixes")
+                    .into_iter()
+                    .flat_map(|f| f.as_array().unwrap())
+                {
+                    assert!(fix["description"]["text"].is_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sarif_report_for_binary() {
+        let cwes = vec![
+            CweWarning::new("CWE787", "0.1", "Out-of-bounds write at 0x1000")
+                .addresses(vec!["00001000".to_string(), "00001010".to_string()])
+                .exploit_primitive(Some(ExploitPrimitive::ArbitraryWrite)),
+            CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x2000")
+                .addresses(vec!["00002000".to_string()]),
+            CweWarning::new("CWE676", "0.1", "Call to gets at an unknown address")
+                .addresses(vec!["UNKNOWN".to_string()])
+                .remediation(Some(Remediation::FreeText {
+                    text: "replace gets by fgets".to_string(),
+                })),
+            CweWarning::new("Memory", "0.1", "Some warning without addresses"),
+        ];
+        let sarif = warnings_to_sarif(&cwes, "/bin/my binary");
+        assert_valid_sarif(&sarif);
+        // The report survives a round trip through its string representation.
+        let sarif_string = serde_json::to_string_pretty(&sarif).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&sarif_string).unwrap(),
+            sarif
+        );
+
+        let run = &sarif["runs"][0];
+        assert_eq!(
+            run["artifacts"][0]["location"]["uri"],
+            "file:///bin/my%20binary"
+        );
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results[0]["ruleId"], "CWE787");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[0]["locations"].as_array().unwrap().len(), 2);
+        let location = &results[0]["locations"][1];
+        assert_eq!(
+            location["physicalLocation"]["address"]["absoluteAddress"],
+            0x1010
+        );
+        assert_eq!(
+            location["physicalLocation"]["artifactLocation"]["uri"],
+            "file:///bin/my%20binary"
+        );
+        assert_eq!(location["logicalLocations"][0]["name"], "0x1010");
+        assert_eq!(results[1]["level"], "warning");
+        // Warnings without known addresses have no locations.
+        assert!(results[2]["locations"].as_array().unwrap().is_empty());
+        assert!(results[3]["locations"].as_array().unwrap().is_empty());
+
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 4);
+        let cwe_476_rule = rules.iter().find(|rule| rule["id"] == "CWE476").unwrap();
+        assert_eq!(
+            cwe_476_rule["helpUri"],
+            "https://cwe.mitre.org/data/definitions/476.html"
+        );
+        assert_eq!(
+            cwe_476_rule["properties"]["tags"][1],
+            "external/cwe/cwe-476"
+        );
+        let memory_rule = rules.iter().find(|rule| rule["id"] == "Memory").unwrap();
+        assert!(memory_rule.get("helpUri").is_none());
+
+        // Without a known binary the results are located only by their addresses.
+        let output = AnalysisResultsOutput::new(Vec::new(), cwes.clone());
+        let sarif: serde_json::Value =
+            serde_json::from_str(&SarifFormatter.format_output(&output)).unwrap();
+        assert_valid_sarif(&sarif);
+        assert!(sarif["runs"][0].get("artifacts").is_none());
+        let output = output.binary(Some("bin/test".to_string()));
+        let sarif: serde_json::Value =
+            serde_json::from_str(&SarifFormatter.format_output(&output)).unwrap();
+        assert_valid_sarif(&sarif);
+        assert_eq!(
+            sarif["runs"][0]["artifacts"][0]["location"]["uri"],
+            "bin/test"
+        );
+    }
+
+    #[test]
+    fn sorted_output() {
+        let logs = vec![
+            LogMessage::new_info("b").source("PointerInference"),
+            LogMessage::new_debug("c")
+                .source("CWE476")
+                .location(Tid::new("instr_0")),
+            LogMessage::new_info("a").source("PointerInference"),
+            LogMessage::new_info("d"),
+        ];
+        let cwes = vec![
+            CweWarning::new("CWE476", "0.3", "second").addresses(vec!["00002000".to_string()]),
+            CweWarning::new("CWE476", "0.3", "first").addresses(vec!["00001000_1".to_string()]),
+            CweWarning::new("CWE190", "0.1", "third").addresses(vec!["00003000".to_string()]),
+        ];
+        let output = AnalysisResultsOutput::new(logs, cwes);
+        let log_texts: Vec<&str> = output.logs.iter().map(|log| log.text.as_str()).collect();
+        assert_eq!(log_texts, vec!["d", "c", "a", "b"]);
+        let descriptions: Vec<&str> = output
+            .cwes
+            .iter()
+            .map(|cwe| cwe.description.as_str())
+            .collect();
+        assert_eq!(descriptions, vec!["third", "first", "second"]);
+    }
+
+    #[test]
+    fn partial_results_output() {
+        let output = mock_output();
+        assert_eq!(
+            SummaryFormatter.format_output(&output),
+            SummaryFormatter.format(&output.cwes)
+        );
+        assert!(!JsonFormatter.format_output(&output).contains("partial"));
+
+        let output = output.partial(true);
+        assert!(SummaryFormatter
+            .format_output(&output)
+            .ends_with("Total: 3\nPartial results: some functions could not be analyzed, see the log messages for details.\n"));
+        let json_report = JsonReport::from_json_str(&JsonFormatter.format_output(&output)).unwrap();
+        assert!(json_report.partial);
+        assert_eq!(json_report.cwes, output.cwes);
+        assert_eq!(
+            TextFormatter.format_output(&output),
+            TextFormatter.format(&output.cwes)
+        );
+    }
+
+    #[test]
+    fn coverage_output() {
+        use crate::utils::coverage::CoverageCount;
+        let output = mock_output();
+        assert!(!JsonFormatter.format_output(&output).contains("coverage"));
+        let coverage = CoverageMetrics {
+            instructions: CoverageCount {
+                total: 200,
+                covered: 150,
+            },
+            call_sites: CoverageCount {
+                total: 10,
+                covered: 9,
+            },
+            memory_accesses: None,
+            checker_applicability: [("CWE78".to_string(), 2)].into(),
+            derived_taint_sources: [(
+                "CWE78".to_string(),
+                vec!["read_exact (wraps recv)".to_string()],
+            )]
+            .into(),
+        };
+        let output = output.coverage(Some(coverage.clone()));
+        let json_report = JsonReport::from_json_str(&JsonFormatter.format_output(&output)).unwrap();
+        assert_eq!(json_report.coverage, Some(coverage));
+        assert!(SummaryFormatter.format_output(&output).ends_with(
+            "Total: 3\nCoverage: 75.0% of 200 instructions are contained in analyzed functions, \
+            90.0% of 10 call sites have a known callee. \
+            Call sites of the symbols relevant for each check: CWE78 saw 2. \
+            Wrapper functions treated as taint sources: CWE78 used read_exact (wraps recv).\n"
+        ));
+    }
+
+    #[test]
+    fn exploit_primitive_output() {
+        let cwes = vec![
+            CweWarning::new(
+                "CWE787",
+                "0.2",
+                "Memory write at 0x1000 may be out of bounds",
+            )
+            .exploit_primitive(Some(ExploitPrimitive::ControlledOffset)),
+            CweWarning::new(
+                "CWE787",
+                "0.2",
+                "Memory write at 0x1100 may be out of bounds",
+            )
+            .exploit_primitive(Some(ExploitPrimitive::ControlledOffset)),
+            CweWarning::new(
+                "CWE119",
+                "0.2",
+                "Call to memcpy at 0x1200 may access out-of-bounds memory",
+            )
+            .exploit_primitive(Some(ExploitPrimitive::ControlledSizeLinearOverflow)),
+            CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1300"),
+        ];
+        let text = TextFormatter.format(&cwes);
+        assert_eq!(
+            text.lines().next().unwrap(),
+            "[CWE787] (0.2) Memory write at 0x1000 may be out of bounds [exploit primitive: controlled offset]"
+        );
+        assert_eq!(
+            text.lines().nth(3).unwrap(),
+            "[CWE476] (0.3) NULL Pointer Dereference at 0x1300"
+        );
+        assert_eq!(
+            SummaryFormatter.format(&cwes),
+            "CWE119: 1\nCWE476: 1\nCWE787: 2\nTotal: 4\nExploit primitives:\n  controlled offset: 2\n  controlled-size linear overflow: 1\n"
+        );
+        let json = serde_json::to_value(&cwes).unwrap();
+        assert_eq!(json[0]["exploit_primitive"], "controlled_offset");
+        assert!(json[3].get("exploit_primitive").is_none());
+    }
+
+    #[test]
+    fn remediation_output() {
+        let cwes = vec![
+            CweWarning::new("CWE403", "0.1", "Descriptor created at 0x1000 is exposed")
+                .remediation(Some(Remediation::AddFlag {
+                    call: "open".to_string(),
+                    flag: "O_CLOEXEC".to_string(),
+                })),
+            CweWarning::new("CWE676", "0.1", "Call to gets at 0x2000").remediation(Some(
+                Remediation::FreeText {
+                    text: "replace gets by fgets".to_string(),
+                },
+            )),
+        ];
+        let text = TextFormatter.format(&cwes);
+        assert_eq!(
+            text.lines().next().unwrap(),
+            "[CWE403] (0.1) Descriptor created at 0x1000 is exposed [fix: add O_CLOEXEC to the flags of open]"
+        );
+        let json = serde_json::to_value(&cwes).unwrap();
+        assert_eq!(
+            json[0]["remediation"],
+            serde_json::json!({ "kind": "add_flag", "call": "open", "flag": "O_CLOEXEC" })
+        );
+        let sarif: serde_json::Value = serde_json::from_str(&SarifFormatter.format(&cwes)).unwrap();
+        let results = sarif["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(
+            results[0]["fixes"][0]["description"]["text"],
+            "add O_CLOEXEC to the flags of open"
+        );
+        assert_eq!(results[1]["properties"]["remediation"]["kind"], "free_text");
+        assert!(results[1].get("fixes").is_none());
+    }
+}
@@ -0,0 +1,144 @@
+//! The aggregated JSON output of a batch run over several binaries.
+
+use super::*;
+use std::collections::BTreeMap;
+
+/// The top-level object of the aggregated JSON output of a batch run.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BatchReport {
+    /// The version of the schema of the contained per-binary reports.
+    pub schema_version: String,
+    /// Summary of the results of all binaries.
+    pub summary: BatchSummary,
+    /// The results for each binary, keyed by the file path of the binary.
+    pub binaries: BTreeMap<String, BinaryResult>,
+}
+
+/// Summary of the results of a batch run.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct BatchSummary {
+    /// The number of successfully analyzed binaries.
+    pub analyzed: usize,
+    /// The number of binaries whose analysis failed.
+    pub failed: usize,
+    /// The number of CWE warnings for each check over all binaries.
+    pub warnings: BTreeMap<String, usize>,
+}
+
+/// The result of the analysis of one binary in a batch run.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BinaryResult {
+    /// The binary was analyzed successfully.
+    Analyzed {
+        /// The number of CWE warnings for each check.
+        summary: BTreeMap<String, usize>,
+        /// The report for the binary, as generated by a single run with JSON output.
+        report: JsonReport,
+    },
+    /// The analysis of the binary failed.
+    Failed {
+        /// The reason for the failure.
+        error: String,
+    },
+}
+
+impl BatchReport {
+    /// Create an empty report with the current schema version.
+    pub fn new() -> BatchReport {
+        BatchReport {
+            schema_version: SCHEMA_VERSION.to_string(),
+            summary: BatchSummary::default(),
+            binaries: BTreeMap::new(),
+        }
+    }
+
+    /// Add the result of the analysis of the binary at the given path to the report.
+    pub fn add_result(&mut self, path: String, result: &Result<AnalysisResultsOutput, Error>) {
+        let binary_result = match result {
+            Ok(output) => {
+                let mut summary = BTreeMap::new();
+                for cwe in output.cwes.iter() {
+                    *summary.entry(cwe.name.clone()).or_insert(0) += 1;
+                    *self.summary.warnings.entry(cwe.name.clone()).or_insert(0) += 1;
+                }
+                let mut report = JsonReport::new(output.cwes.clone());
+                report.partial = output.partial;
+                report.coverage = output.coverage.clone();
+                self.summary.analyzed += 1;
+                BinaryResult::Analyzed { summary, report }
+            }
+            Err(err) => {
+                self.summary.failed += 1;
+                BinaryResult::Failed {
+                    error: format!("{}", err),
+                }
+            }
+        };
+        self.binaries.insert(path, binary_result);
+    }
+
+    /// Serialize the report as pretty-printed JSON.
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl Default for BatchReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_results() {
+        let output = AnalysisResultsOutput::new(
+            Vec::new(),
+            vec![
+                CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1234"),
+                CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1300"),
+                CweWarning::new("CWE190", "0.1", "Integer Overflow at 0x2000"),
+            ],
+        )
+        .partial(true);
+        let mut report = BatchReport::new();
+        let result = Ok(output.clone());
+        report.add_result("bin/first".to_string(), &result);
+        report.add_result("bin/second".to_string(), &result);
+        report.add_result(
+            "bin/broken".to_string(),
+            &Err(anyhow!("Parsing of the Ghidra output failed")),
+        );
+
+        assert_eq!(report.summary.analyzed, 2);
+        assert_eq!(report.summary.failed, 1);
+        assert_eq!(report.summary.warnings["CWE476"], 4);
+        assert_eq!(report.summary.warnings["CWE190"], 2);
+        match &report.binaries["bin/first"] {
+            BinaryResult::Analyzed { summary, report } => {
+                assert_eq!(summary["CWE476"], 2);
+                assert!(report.partial);
+                assert_eq!(report.cwes, output.cwes);
+            }
+            BinaryResult::Failed { .. } => panic!("Unexpected failure"),
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json_string()).unwrap();
+        assert_eq!(json["binaries"]["bin/broken"]["status"], "failed");
+        assert_eq!(
+            json["binaries"]["bin/broken"]["error"],
+            "Parsing of the Ghidra output failed"
+        );
+        assert_eq!(json["binaries"]["bin/first"]["status"], "analyzed");
+        assert_eq!(
+            json["binaries"]["bin/first"]["report"]["schema_version"],
+            SCHEMA_VERSION
+        );
+        let parsed: BatchReport = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, report);
+    }
+}
@@ -0,0 +1,112 @@
+use super::*;
+
+/// Formats CWE warnings as a pretty-printed [`JsonReport`].
+pub struct JsonFormatter;
+
+impl OutputFormatter for JsonFormatter {
+    fn format(&self, cwes: &[CweWarning]) -> String {
+        JsonReport::new(cwes.to_vec()).to_json_string()
+    }
+
+    fn format_output(&self, output: &AnalysisResultsOutput) -> String {
+        let mut report = JsonReport::new(output.cwes.clone());
+        report.partial = output.partial;
+        report.coverage = output.coverage.clone();
+        report.feedback = output.feedback.clone();
+        report.access_histograms = output.access_histograms.clone().map(Box::new);
+        report.to_json_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::pointer_inference::access_histogram::{
+        AccessHistogramEntry, AccessKind, ObjectAccessHistogramReport,
+    };
+    use crate::utils::log::{ExploitPrimitive, Remediation};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn golden_json_report() {
+        let cwes = vec![
+            CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1234")
+                .addresses(vec!["00001234".to_string()])
+                .symbols(vec!["malloc".to_string()]),
+        ];
+        assert_eq!(
+            JsonFormatter.format(&cwes),
+            r#"{
+  "schema_version": "1.5",
+  "cwes": [
+    {
+      "name": "CWE476",
+      "version": "0.3",
+      "addresses": [
+        "00001234"
+      ],
+      "tids": [],
+      "symbols": [
+        "malloc"
+      ],
+      "other": [],
+      "description": "NULL Pointer Dereference at 0x1234"
+    }
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let cwes = vec![
+            CweWarning::new(
+                "CWE787",
+                "0.2",
+                "Memory write at 0x1000 may be out of bounds",
+            )
+            .addresses(vec!["00001000".to_string()])
+            .tids(vec!["instr_00001000_0".to_string()])
+            .exploit_primitive(Some(ExploitPrimitive::ControlledOffset)),
+            CweWarning::new("CWE403", "0.1", "Descriptor created at 0x2000 is exposed")
+                .addresses(vec!["00002000".to_string()])
+                .remediation(Some(Remediation::AddFlag {
+                    call: "open".to_string(),
+                    flag: "O_CLOEXEC".to_string(),
+                })),
+        ];
+        let report = JsonReport::from_json_str(&JsonFormatter.format(&cwes)).unwrap();
+        assert_eq!(report, JsonReport::new(cwes.clone()));
+
+        let access_histograms = BTreeMap::from([(
+            "call_malloc".to_string(),
+            BTreeMap::from([(
+                "RAX".to_string(),
+                ObjectAccessHistogramReport {
+                    accesses: vec![AccessHistogramEntry {
+                        offset: "8".to_string(),
+                        size: 4,
+                        kind: AccessKind::Write,
+                        count: 2,
+                    }],
+                    unknown_offset_accesses: 1,
+                    dropped_accesses: 0,
+                },
+            )]),
+        )]);
+        let output = AnalysisResultsOutput::new(Vec::new(), cwes)
+            .partial(true)
+            .access_histograms(Some(access_histograms));
+        let json = JsonFormatter.format_output(&output);
+        let report = JsonReport::from_json_str(&json).unwrap();
+        assert_eq!(report.schema_version, SCHEMA_VERSION);
+        assert!(report.partial);
+        assert_eq!(
+            report.access_histograms.as_deref(),
+            output.access_histograms.as_ref()
+        );
+        assert_eq!(report.cwes, output.cwes);
+        // The report is serialized again to the same string.
+        assert_eq!(report.to_json_string(), json);
+    }
+}
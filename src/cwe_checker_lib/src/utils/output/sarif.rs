@@ -0,0 +1,264 @@
+use super::*;
+use crate::utils::log::{ExploitPrimitive, Remediation};
+use std::collections::BTreeMap;
+
+/// The URI of the JSON schema for SARIF version 2.1.0.
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Formats CWE warnings as a report in the Static Analysis Results Interchange Format (SARIF), version 2.1.0.
+///
+/// See [`warnings_to_sarif`] for the structure of the report.
+/// The analyzed binary is only given as the artifact of the results
+/// if it is known to the [`AnalysisResultsOutput`].
+pub struct SarifFormatter;
+
+impl OutputFormatter for SarifFormatter {
+    fn format(&self, cwes: &[CweWarning]) -> String {
+        serde_json::to_string_pretty(&generate_report(cwes, None)).unwrap()
+    }
+
+    fn format_output(&self, output: &AnalysisResultsOutput) -> String {
+        serde_json::to_string_pretty(&generate_report(&output.cwes, output.binary.as_deref()))
+            .unwrap()
+    }
+}
+
+/// Convert CWE warnings into a report in the Static Analysis Results Interchange Format (SARIF), version 2.1.0.
+///
+/// Each check that generated a warning is listed as a rule of the tool with the name of the check
+/// (which is the CWE identifier, e.g. `CWE476`) as its ID.
+/// Each warning becomes a result located in the binary at the given path.
+/// The addresses of a warning are given both as absolute addresses in the binary
+/// and as logical locations named by the address,
+/// since the functions containing the addresses are not known to the report.
+/// Warnings without a known address (e.g. `UNKNOWN`) produce results without locations.
+/// The level of a result is `error` for out-of-bounds accesses providing a controllable exploit primitive
+/// and `warning` for all other warnings.
+///
+/// TIDs, symbols and the other check-specific information are added as properties of the result.
+/// A suggested remediation is added as a property and, unless it is free text, also as a fix of the result.
+/// Since the source code of the analyzed binary is unknown, the fixes only contain a description
+/// and no changes to concrete source files.
+pub fn warnings_to_sarif(warnings: &[CweWarning], binary_path: &str) -> serde_json::Value {
+    generate_report(warnings, Some(binary_path))
+}
+
+/// Generate the SARIF report for the given warnings.
+/// If the path of the binary is not known, the results contain no artifact locations.
+fn generate_report(cwes: &[CweWarning], binary_path: Option<&str>) -> serde_json::Value {
+    let rules: BTreeMap<&str, &str> = cwes
+        .iter()
+        .map(|cwe| (cwe.name.as_str(), cwe.version.as_str()))
+        .collect();
+    let rules: Vec<serde_json::Value> = rules
+        .into_iter()
+        .map(|(name, version)| format_rule(name, version))
+        .collect();
+    let artifact_uri = binary_path.map(path_to_uri);
+    let results: Vec<serde_json::Value> = cwes
+        .iter()
+        .map(|cwe| format_result(cwe, artifact_uri.as_deref()))
+        .collect();
+    let mut run = serde_json::json!({
+        "tool": {
+            "driver": {
+                "name": "cwe_checker",
+                "version": env!("CARGO_PKG_VERSION"),
+                "informationUri": "https://github.com/fkie-cad/cwe_checker",
+                "rules": rules,
+            }
+        },
+        "results": results,
+    });
+    if let Some(uri) = artifact_uri {
+        run["artifacts"] = serde_json::json!([{ "location": { "uri": uri } }]);
+    }
+    serde_json::json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [run],
+    })
+}
+
+/// Generate the SARIF rule for the check with the given name.
+///
+/// For checks named by a CWE identifier the rule links to the description of the CWE
+/// and is tagged with the CWE, so that consumers of the report can group the results by CWE.
+fn format_rule(name: &str, version: &str) -> serde_json::Value {
+    let mut rule = serde_json::json!({
+        "id": name,
+        "properties": { "version": version },
+    });
+    if let Some(cwe_number) = name
+        .strip_prefix("CWE")
+        .filter(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+    {
+        rule["helpUri"] = serde_json::json!(format!(
+            "https://cwe.mitre.org/data/definitions/{}.html",
+            cwe_number
+        ));
+        rule["properties"]["tags"] =
+            serde_json::json!(["security", format!("external/cwe/cwe-{}", cwe_number)]);
+    }
+    rule
+}
+
+/// Generate the SARIF representation of a single CWE warning.
+fn format_result(cwe: &CweWarning, artifact_uri: Option<&str>) -> serde_json::Value {
+    let locations: Vec<serde_json::Value> = cwe
+        .addresses
+        .iter()
+        .filter_map(|address| u64::from_str_radix(address.trim_start_matches("0x"), 16).ok())
+        .map(|address| {
+            let mut physical_location = serde_json::json!({
+                "address": { "absoluteAddress": address }
+            });
+            if let Some(uri) = artifact_uri {
+                physical_location["artifactLocation"] =
+                    serde_json::json!({ "uri": uri, "index": 0 });
+            }
+            serde_json::json!({
+                "physicalLocation": physical_location,
+                "logicalLocations": [{ "name": format!("{:#x}", address) }],
+            })
+        })
+        .collect();
+    let mut result = serde_json::json!({
+        "ruleId": cwe.name,
+        "level": get_level(cwe),
+        "message": { "text": cwe.description },
+        "locations": locations,
+        "properties": {
+            "version": cwe.version,
+            "tids": cwe.tids,
+            "symbols": cwe.symbols,
+            "other": cwe.other,
+        },
+    });
+    if let Some(remediation) = &cwe.remediation {
+        result["properties"]["remediation"] = serde_json::to_value(remediation).unwrap();
+        if !matches!(remediation, Remediation::FreeText { .. }) {
+            result["fixes"] = serde_json::json!([{
+                "description": { "text": format!("{}", remediation) }
+            }]);
+        }
+    }
+    result
+}
+
+/// Map the severity of a CWE warning to a SARIF level.
+///
+/// Out-of-bounds accesses whose offset or size is controllable are reported as errors,
+/// since they are the most likely to be exploitable.
+fn get_level(cwe: &CweWarning) -> &'static str {
+    match cwe.exploit_primitive {
+        Some(ExploitPrimitive::ControlledOffset)
+        | Some(ExploitPrimitive::ControlledSizeLinearOverflow)
+        | Some(ExploitPrimitive::ArbitraryWrite) => "error",
+        Some(ExploitPrimitive::FixedOffsetOverflow) | None => "warning",
+    }
+}
+
+/// Convert a file path into a URI reference as required for SARIF artifact locations.
+///
+/// Absolute paths are converted to `file` URIs, relative paths stay relative references.
+/// Characters that are not allowed in URIs are percent-encoded.
+fn path_to_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let mut uri = if path.starts_with('/') {
+        "file://".to_string()
+    } else {
+        String::new()
+    };
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:@!$&'()*+,;=".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_sarif_report() {
+        let cwes = vec![CweWarning::new(
+            "CWE787",
+            "0.2",
+            "Memory write at 0x1000 may be out of bounds",
+        )
+        .addresses(vec!["00001000".to_string()])
+        .tids(vec!["instr_00001000_0".to_string()])
+        .exploit_primitive(Some(ExploitPrimitive::ControlledSizeLinearOverflow))
+        .remediation(Some(Remediation::AddCheck {
+            value: "the size".to_string(),
+            bound: 0x100,
+        }))];
+        let expected = serde_json::json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "cwe_checker",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": "https://github.com/fkie-cad/cwe_checker",
+                        "rules": [{
+                            "id": "CWE787",
+                            "helpUri": "https://cwe.mitre.org/data/definitions/787.html",
+                            "properties": {
+                                "version": "0.2",
+                                "tags": ["security", "external/cwe/cwe-787"],
+                            },
+                        }],
+                    }
+                },
+                "results": [{
+                    "ruleId": "CWE787",
+                    "level": "error",
+                    "message": { "text": "Memory write at 0x1000 may be out of bounds" },
+                    "locations": [{
+                        "physicalLocation": {
+                            "address": { "absoluteAddress": 0x1000 },
+                            "artifactLocation": { "uri": "bin/test", "index": 0 },
+                        },
+                        "logicalLocations": [{ "name": "0x1000" }],
+                    }],
+                    "properties": {
+                        "version": "0.2",
+                        "tids": ["instr_00001000_0"],
+                        "symbols": [],
+                        "other": [],
+                        "remediation": { "kind": "add_check", "value": "the size", "bound": 256 },
+                    },
+                    "fixes": [{
+                        "description": { "text": "check that the size is at most 0x100" }
+                    }],
+                }],
+                "artifacts": [{ "location": { "uri": "bin/test" } }],
+            }],
+        });
+        let sarif = warnings_to_sarif(&cwes, "bin/test");
+        assert_eq!(sarif, expected);
+        // The formatted report parses back to the same report.
+        let output =
+            AnalysisResultsOutput::new(Vec::new(), cwes).binary(Some("bin/test".to_string()));
+        let sarif_string = SarifFormatter.format_output(&output);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&sarif_string).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn sarif_artifact_uris() {
+        assert_eq!(path_to_uri("/usr/bin/ls"), "file:///usr/bin/ls");
+        assert_eq!(path_to_uri("bin/my binary"), "bin/my%20binary");
+        assert_eq!(path_to_uri("C:\\bin\\test.exe"), "C:/bin/test.exe");
+        assert_eq!(path_to_uri("bin/100%"), "bin/100%25");
+    }
+}
@@ -0,0 +1,183 @@
+//! The schema of the JSON output of the cwe_checker.
+//!
+//! The JSON output is a [`JsonReport`] object.
+//! Its keys are serialized in the order of the fields of the typed structs,
+//! i.e. the key order of a CWE warning is given by the field order of [`CweWarning`].
+//! The CWE warnings themselves are sorted by [`sort_warnings`](super::sort_warnings),
+//! so that analyzing the same binary twice yields byte-identical output.
+//!
+//! The schema version has the form `<major>.<minor>`.
+//! The schema only evolves additively: new fields are optional and increment the minor version,
+//! so that parsers written for an older minor version can still parse newer output.
+//! Changing or removing fields requires a new major version.
+//!
+//! Output of cwe_checker versions before the introduction of the schema version
+//! is a bare JSON array of CWE warnings.
+//! It is still accepted by [`JsonReport::from_json_str`].
+
+use crate::analysis::pointer_inference::access_histogram::AccessHistogramsReport;
+use crate::prelude::*;
+use crate::utils::coverage::CoverageMetrics;
+use crate::utils::feedback::FeedbackReport;
+use crate::utils::log::CweWarning;
+
+/// The version of the JSON output schema generated by this version of the cwe_checker.
+pub const SCHEMA_VERSION: &str = "1.5";
+
+/// The top-level object of the JSON output.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct JsonReport {
+    /// The version of the schema that the report adheres to.
+    pub schema_version: String,
+    /// Whether the results are only partial, e.g. because some functions of the binary could not be parsed.
+    /// Added in schema version 1.1 and only present if set.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    /// Metrics on how much of the binary the analysis understood.
+    /// Added in schema version 1.2 and only present if the metrics were collected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageMetrics>,
+    /// The program points where checks gave up because of unknown values,
+    /// ranked by the extern symbols and code regions responsible for them.
+    /// Added in schema version 1.4 and only present if the feedback was requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<FeedbackReport>,
+    /// The memory access histograms of the heap objects, grouped by allocation site.
+    /// Added in schema version 1.5 and only present if the collection was enabled in the configuration.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_histograms: Option<Box<AccessHistogramsReport>>,
+    /// The CWE warnings generated during the analysis.
+    pub cwes: Vec<CweWarning>,
+}
+
+/// The accepted top-level formats when parsing JSON output.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VersionedReport {
+    /// Output with an explicit schema version.
+    Versioned(JsonReport),
+    /// Output generated before the introduction of the schema version.
+    Unversioned(Vec<CweWarning>),
+}
+
+impl JsonReport {
+    /// Create a new report with the current schema version containing the given CWE warnings.
+    pub fn new(cwes: Vec<CweWarning>) -> JsonReport {
+        JsonReport {
+            schema_version: SCHEMA_VERSION.to_string(),
+            partial: false,
+            coverage: None,
+            feedback: None,
+            access_histograms: None,
+            cwes,
+        }
+    }
+
+    /// Serialize the report as pretty-printed JSON.
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Parse the JSON output of a (possibly older) cwe_checker run.
+    ///
+    /// Unversioned output (i.e. a bare array of CWE warnings) is accepted and returned with an empty schema version.
+    /// Returns an error if the major version of the schema is not supported.
+    pub fn from_json_str(json: &str) -> Result<JsonReport, Error> {
+        match serde_json::from_str(json)? {
+            VersionedReport::Versioned(report) => {
+                if get_major_version(&report.schema_version)? == get_major_version(SCHEMA_VERSION)?
+                {
+                    Ok(report)
+                } else {
+                    Err(anyhow!(
+                        "Unsupported schema version {} of the JSON output. Supported version: {}",
+                        report.schema_version,
+                        SCHEMA_VERSION
+                    ))
+                }
+            }
+            VersionedReport::Unversioned(cwes) => Ok(JsonReport {
+                schema_version: String::new(),
+                partial: false,
+                coverage: None,
+                feedback: None,
+                access_histograms: None,
+                cwes,
+            }),
+        }
+    }
+}
+
+/// Get the major version of a schema version of the form `<major>.<minor>`.
+fn get_major_version(schema_version: &str) -> Result<u64, Error> {
+    let major = schema_version.split('.').next().unwrap_or_default();
+    major
+        .parse()
+        .map_err(|_| anyhow!("Invalid schema version: {}", schema_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::log::ExploitPrimitive;
+
+    /// JSON output of a cwe_checker version before the introduction of the schema version.
+    const UNVERSIONED_FIXTURE: &str = include_str!("fixtures/unversioned.json");
+    /// JSON output with schema version 1.0.
+    const VERSION_1_0_FIXTURE: &str = include_str!("fixtures/schema_1_0.json");
+
+    #[test]
+    fn parse_unversioned_output() {
+        let report = JsonReport::from_json_str(UNVERSIONED_FIXTURE).unwrap();
+        assert_eq!(report.schema_version, "");
+        assert_eq!(report.cwes.len(), 2);
+        assert_eq!(report.cwes[0].name, "CWE476");
+        assert_eq!(report.cwes[0].addresses, vec!["00101234".to_string()]);
+        assert_eq!(report.cwes[1].symbols, vec!["strcpy".to_string()]);
+        assert!(report.cwes[1].exploit_primitive.is_none());
+    }
+
+    #[test]
+    fn parse_versioned_output() {
+        let report = JsonReport::from_json_str(VERSION_1_0_FIXTURE).unwrap();
+        assert_eq!(report.schema_version, "1.0");
+        assert_eq!(report.cwes.len(), 2);
+        assert_eq!(
+            report.cwes[1].exploit_primitive,
+            Some(ExploitPrimitive::ControlledOffset)
+        );
+        // The fixture contains a field unknown to this version of the schema.
+        let reserialized: serde_json::Value =
+            serde_json::from_str(&report.to_json_string()).unwrap();
+        assert!(reserialized["cwes"][0].get("future_field").is_none());
+
+        let future_major_version = VERSION_1_0_FIXTURE.replace("\"1.0\"", "\"2.0\"");
+        assert!(JsonReport::from_json_str(&future_major_version).is_err());
+        let future_minor_version = VERSION_1_0_FIXTURE.replace("\"1.0\"", "\"1.7\"");
+        assert!(JsonReport::from_json_str(&future_minor_version).is_ok());
+    }
+
+    #[test]
+    fn key_order() {
+        let cwe = CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1234")
+            .addresses(vec!["00001234".to_string()]);
+        let json = JsonReport::new(vec![cwe.clone()]).to_json_string();
+        let keys = [
+            "\"schema_version\"",
+            "\"cwes\"",
+            "\"name\"",
+            "\"version\"",
+            "\"addresses\"",
+            "\"tids\"",
+            "\"symbols\"",
+            "\"other\"",
+            "\"description\"",
+        ];
+        let positions: Vec<usize> = keys.iter().map(|key| json.find(key).unwrap()).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(
+            JsonReport::from_json_str(&json).unwrap(),
+            JsonReport::new(vec![cwe])
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use super::*;
+use crate::utils::log::ExploitPrimitive;
+use std::collections::BTreeMap;
+
+/// Formats CWE warnings as a summary containing the number of warnings for each check.
+/// If some warnings are classified by exploit primitive,
+/// the summary also contains the number of warnings for each exploit primitive.
+pub struct SummaryFormatter;
+
+impl OutputFormatter for SummaryFormatter {
+    fn format(&self, cwes: &[CweWarning]) -> String {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut primitive_counts: BTreeMap<ExploitPrimitive, usize> = BTreeMap::new();
+        for cwe in cwes {
+            *counts.entry(&cwe.name).or_insert(0) += 1;
+            if let Some(primitive) = cwe.exploit_primitive {
+                *primitive_counts.entry(primitive).or_insert(0) += 1;
+            }
+        }
+        let mut output = String::new();
+        for (name, count) in counts {
+            output += &format!("{}: {}\n", name, count);
+        }
+        output += &format!("Total: {}\n", cwes.len());
+        if !primitive_counts.is_empty() {
+            output += "Exploit primitives:\n";
+            for (primitive, count) in primitive_counts {
+                output += &format!("  {}: {}\n", primitive, count);
+            }
+        }
+        output
+    }
+
+    fn format_output(&self, output: &AnalysisResultsOutput) -> String {
+        let mut summary = self.format(&output.cwes);
+        if output.partial {
+            summary += "Partial results: some functions could not be analyzed, see the log messages for details.\n";
+        }
+        if let Some(coverage) = &output.coverage {
+            summary += &format!("{}\n", coverage.summary());
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_summary_output() {
+        let cwes = vec![
+            CweWarning::new("CWE787", "0.2", "first")
+                .exploit_primitive(Some(ExploitPrimitive::ArbitraryWrite)),
+            CweWarning::new("CWE476", "0.3", "second"),
+            CweWarning::new("CWE787", "0.2", "third")
+                .exploit_primitive(Some(ExploitPrimitive::FixedOffsetOverflow)),
+            CweWarning::new("CWE787", "0.2", "fourth")
+                .exploit_primitive(Some(ExploitPrimitive::ArbitraryWrite)),
+        ];
+        assert_eq!(
+            SummaryFormatter.format(&cwes),
+            "CWE476: 1\n\
+            CWE787: 3\n\
+            Total: 4\n\
+            Exploit primitives:\n  \
+            fixed-offset overflow: 1\n  \
+            arbitrary write: 2\n"
+        );
+        let output = AnalysisResultsOutput::new(Vec::new(), Vec::new()).partial(true);
+        assert_eq!(
+            SummaryFormatter.format_output(&output),
+            "Total: 0\n\
+            Partial results: some functions could not be analyzed, see the log messages for details.\n"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use super::*;
+
+/// Formats CWE warnings as human-readable text with one line per warning.
+/// The exploit primitive and the suggested remediation of a warning are appended to its line if known.
+pub struct TextFormatter;
+
+impl OutputFormatter for TextFormatter {
+    fn format(&self, cwes: &[CweWarning]) -> String {
+        cwes.iter()
+            .map(|cwe| {
+                let mut line = format!("{}", cwe);
+                if let Some(primitive) = cwe.exploit_primitive {
+                    line += &format!(" [exploit primitive: {}]", primitive);
+                }
+                if let Some(remediation) = &cwe.remediation {
+                    line += &format!(" [fix: {}]", remediation);
+                }
+                line
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+            + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::log::{ExploitPrimitive, Remediation};
+
+    #[test]
+    fn golden_text_output() {
+        let cwes = vec![
+            CweWarning::new(
+                "CWE787",
+                "0.2",
+                "Memory write at 0x1000 may be out of bounds",
+            )
+            .exploit_primitive(Some(ExploitPrimitive::ControlledOffset))
+            .remediation(Some(Remediation::AddCheck {
+                value: "the offset".to_string(),
+                bound: 0x40,
+            })),
+            CweWarning::new("CWE676", "0.1", "Call to gets at 0x2000").remediation(Some(
+                Remediation::ReplaceCall {
+                    from: "gets".to_string(),
+                    to: "fgets".to_string(),
+                    added_args: vec!["size".to_string(), "stdin".to_string()],
+                },
+            )),
+            CweWarning::new("CWE476", "0.3", "NULL Pointer Dereference at 0x1300"),
+        ];
+        assert_eq!(
+            TextFormatter.format(&cwes),
+            "[CWE787] (0.2) Memory write at 0x1000 may be out of bounds [exploit primitive: controlled offset] [fix: check that the offset is at most 0x40]\n\
+            [CWE676] (0.1) Call to gets at 0x2000 [fix: replace gets by fgets with added arguments size, stdin]\n\
+            [CWE476] (0.3) NULL Pointer Dereference at 0x1300\n"
+        );
+        assert_eq!(TextFormatter.format(&[]), "\n");
+    }
+}
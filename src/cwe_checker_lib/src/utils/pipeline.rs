@@ -0,0 +1,1105 @@
+//! The analysis pipeline that is run for each binary after its disassembly was converted into a [`Project`].
+//!
+//! The pipeline normalizes the project, generates the runtime memory image and the control flow graph,
+//! computes the shared analyses required by the executed CWE modules and then runs the modules.
+//!
+//! All functions of this module only depend on their arguments, i.e. they do not use global state.
+//! Several binaries can therefore be analyzed concurrently in different threads.
+
+use crate::analysis::function_signature::{
+    self, get_discrepancy_summary, ConventionVerificationConfig, ExternParameterInferenceConfig,
+    FunctionSignature, ParameterOwnershipConfig,
+};
+use crate::analysis::function_skipping::FunctionSkipConfig;
+use crate::analysis::graph::{self, Graph};
+use crate::analysis::patch_diff::{PatchDiff, PatchDiffConfig};
+use crate::analysis::pointer_inference::custom_allocator::{
+    get_custom_allocator_sub_tids, CustomAllocator,
+};
+use crate::analysis::pointer_inference::harness::HarnessSpec;
+use crate::analysis::pointer_inference::{NodeValueRetention, PointerInference};
+use crate::intermediate_representation::*;
+use crate::pcode::ParsedProject;
+use crate::prelude::*;
+use crate::utils::binary::{is_go_binary, BareMetalConfig, ElfSymbolInfo, RuntimeMemoryImage};
+use crate::utils::checkpoint::{Checkpoint, CheckpointOptions, Phase};
+use crate::utils::coverage::CoverageMetrics;
+use crate::utils::feedback::{extract_imprecision_reports, FeedbackReport};
+use crate::utils::get_binary_base_address;
+use crate::utils::log::{CweWarning, LogMessage};
+use crate::utils::output::AnalysisResultsOutput;
+use crate::{
+    get_required_analyses, get_required_retention, run_modules, AnalysisResults, CweModule,
+    SharedAnalysis,
+};
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashSet};
+
+/// The settings of the pipeline that are shared between all analyzed binaries.
+#[derive(Clone, Copy)]
+pub struct PipelineConfig<'a> {
+    /// The CWE modules to execute.
+    pub modules: &'a [&'a CweModule],
+    /// The content of the configuration file.
+    pub config: &'a serde_json::Value,
+    /// The configuration for bare metal binaries.
+    /// If set, all binaries are treated as bare metal binaries regardless of their format.
+    pub bare_metal_config: Option<&'a BareMetalConfig>,
+    /// Whether to include statistics about the analysis in the log messages.
+    pub statistics: bool,
+    /// Whether to aggregate the imprecision reports of the checks into a feedback report
+    /// (see [`FeedbackReport`]).
+    pub feedback: bool,
+    /// If set, only the target function of the harness and the functions reachable from it are analyzed.
+    pub harness: Option<&'a HarnessSpec>,
+}
+
+/// A function inspecting the analysis results and the output at the end of the pipeline
+/// (see [`analyze_project_with_inspection`]).
+pub type InspectionFn<'a> = dyn FnMut(&AnalysisResults, &AnalysisResultsOutput) + 'a;
+
+/// Convert a project parsed from the output of the P-Code-Extractor plugin into the intermediate representation.
+///
+/// The base address of the binary is read from the binary itself.
+/// If that fails, the given base address for bare metal binaries is used
+/// or, if none is given, the base address of the Ghidra output as a fallback.
+/// Extern symbols sharing the same address are resolved using the symbol information of the binary,
+/// so that all analyses see the same extern symbol for each call target.
+///
+/// Returns the project together with the log messages generated during parsing and normalization
+/// and a flag that is set if some functions or blocks could not be parsed,
+/// i.e. if the analysis results are only partial.
+pub fn convert_parsed_project(
+    parsed_project: ParsedProject,
+    binary: &[u8],
+    bare_metal_base_address: Option<u64>,
+) -> (Project, Vec<LogMessage>, bool) {
+    let is_partial = parsed_project.is_partial();
+    let mut log_messages = parsed_project.log_messages;
+    let mut project_pcode = parsed_project.project;
+    log_messages.append(&mut project_pcode.normalize());
+    let symbol_info = match bare_metal_base_address {
+        Some(_) => ElfSymbolInfo::default(),
+        None => ElfSymbolInfo::new(binary).unwrap_or_default(),
+    };
+    log_messages.append(
+        &mut project_pcode
+            .program
+            .term
+            .resolve_extern_symbol_collisions(&symbol_info),
+    );
+    let project: Project = match get_binary_base_address(binary) {
+        Ok(binary_base_address) => project_pcode.into_ir_project(binary_base_address),
+        Err(_err) => {
+            if let Some(binary_base_address) = bare_metal_base_address {
+                let mut project = project_pcode.into_ir_project(binary_base_address);
+                project.program.term.address_base_offset = 0;
+                project
+            } else {
+                log_messages.push(LogMessage::new_info("Could not determine binary base address. Using base address of Ghidra output as fallback."));
+                let mut project = project_pcode.into_ir_project(0);
+                // For PE files setting the address_base_offset to zero is a hack, which worked for the tested PE files.
+                // But this hack will probably not work in general!
+                project.program.term.address_base_offset = 0;
+                project
+            }
+        }
+    };
+    (project, log_messages, is_partial)
+}
+
+/// Normalize the project and generate the runtime memory image of the binary.
+///
+/// Returns the runtime memory image together with the log messages generated during normalization.
+pub fn prepare_project(
+    project: &mut Project,
+    binary: &[u8],
+    bare_metal_config: Option<&BareMetalConfig>,
+) -> Result<(RuntimeMemoryImage, Vec<LogMessage>), Error> {
+    let mut logs = project.normalize();
+    if is_go_binary(binary) {
+        logs.append(&mut project.normalize_go_binary());
+    }
+    let symbol_info = match bare_metal_config {
+        Some(_) => ElfSymbolInfo::default(),
+        None => match ElfSymbolInfo::new(binary) {
+            Ok(symbol_info) => {
+                logs.append(&mut project.resolve_elf_symbols(&symbol_info));
+                symbol_info
+            }
+            Err(err) => {
+                logs.push(LogMessage::new_info(format!(
+                    "Could not parse the symbols of the binary: {}",
+                    err
+                )));
+                ElfSymbolInfo::default()
+            }
+        },
+    };
+    logs.append(&mut project.resolve_thunks(&symbol_info));
+    logs.append(&mut project.mark_non_returning_subs());
+
+    let (mut runtime_memory_image, mut memory_image_logs) =
+        if let Some(bare_metal_config) = bare_metal_config {
+            RuntimeMemoryImage::new_from_bare_metal(binary, bare_metal_config)
+                .map(|image| (image, Vec::new()))
+        } else {
+            RuntimeMemoryImage::new_with_logs(binary)
+        }
+        .map_err(|err| anyhow!("Error while generating runtime memory image: {}", err))?;
+    logs.append(&mut memory_image_logs);
+    if project.program.term.address_base_offset != 0 {
+        // We adjust the memory addresses once globally
+        // so that other analyses do not have to adjust their addresses.
+        runtime_memory_image.add_global_memory_offset(project.program.term.address_base_offset);
+    }
+    Ok((runtime_memory_image, logs))
+}
+
+/// Resolve `CallOther` instructions like memory barriers and syscalls
+/// according to the `CallOther` section of the configuration.
+///
+/// Since calls to non-returning syscalls like `exit` may make functions non-returning,
+/// the non-returning functions of the project are recomputed afterwards.
+pub fn resolve_call_other_ops(
+    project: &mut Project,
+    config: &serde_json::Value,
+) -> Vec<LogMessage> {
+    let call_other_config: CallOtherConfig =
+        serde_json::from_value(config["CallOther"].clone()).unwrap_or_default();
+    let mut logs = project.resolve_call_other_ops(&call_other_config);
+    let old_non_returning_subs = project.program.term.non_returning_subs.clone();
+    logs.extend(project.mark_non_returning_subs().into_iter().filter(|log| {
+        !log.location
+            .as_ref()
+            .is_some_and(|tid| old_non_returning_subs.contains(tid))
+    }));
+    logs
+}
+
+/// Unroll small loops with constant trip counts in the project
+/// according to the `LoopUnrolling` section of the configuration.
+pub fn unroll_loops(project: &mut Project, config: &serde_json::Value) -> Vec<LogMessage> {
+    let loop_unrolling_config: LoopUnrollingConfig =
+        serde_json::from_value(config["LoopUnrolling"].clone()).unwrap_or_default();
+    project.unroll_small_loops(&loop_unrolling_config)
+}
+
+/// Infer the parameters of extern symbols without known prototype from their call sites
+/// if enabled in the `ExternParameterInference` section of the configuration.
+pub fn infer_extern_parameters(
+    project: &mut Project,
+    config: &serde_json::Value,
+) -> Vec<LogMessage> {
+    let inference_config: ExternParameterInferenceConfig =
+        serde_json::from_value(config["ExternParameterInference"].clone()).unwrap_or_default();
+    if inference_config.enabled {
+        function_signature::infer_extern_symbol_parameters(project)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Generate the control flow graph of the program.
+///
+/// Functions excluded from the analysis by the configuration
+/// and custom allocator functions, which are modeled by the pointer inference analysis instead,
+/// are not contained in the graph.
+pub fn get_control_flow_graph<'a>(
+    project: &'a Project,
+    config: &serde_json::Value,
+) -> (Graph<'a>, Vec<LogMessage>) {
+    get_control_flow_graph_with_skipped_subs(project, config, HashSet::new())
+}
+
+/// Generate the control flow graph of the program (see [`get_control_flow_graph`])
+/// where the given functions are excluded in addition to the functions excluded by the configuration.
+pub fn get_control_flow_graph_with_skipped_subs<'a>(
+    project: &'a Project,
+    config: &serde_json::Value,
+    additional_skipped_subs: HashSet<Tid>,
+) -> (Graph<'a>, Vec<LogMessage>) {
+    let function_skip_config: FunctionSkipConfig =
+        serde_json::from_value(config["FunctionSkipping"].clone()).unwrap_or_default();
+    let (mut skipped_sub_tids, logs) = function_skip_config.get_skipped_subs(&project.program);
+    let custom_allocators: Vec<CustomAllocator> =
+        serde_json::from_value(config["Memory"]["custom_allocators"].clone()).unwrap_or_default();
+    skipped_sub_tids.extend(get_custom_allocator_sub_tids(
+        &project.program,
+        &custom_allocators,
+    ));
+    skipped_sub_tids.extend(additional_skipped_subs);
+    let extern_sub_tids = project
+        .program
+        .term
+        .extern_symbols
+        .keys()
+        .cloned()
+        .collect();
+    let control_flow_graph = graph::get_program_cfg_with_skipped_subs(
+        &project.program,
+        extern_sub_tids,
+        skipped_sub_tids,
+    );
+    (control_flow_graph, logs)
+}
+
+/// Get the configuration of the pointer inference analysis
+/// with the harness (if given) added to it.
+pub fn get_pointer_inference_config(
+    config: &serde_json::Value,
+    harness: Option<&HarnessSpec>,
+) -> serde_json::Value {
+    let mut pointer_inference_config = config["Memory"].clone();
+    if let Some(harness) = harness {
+        pointer_inference_config["harness"] = serde_json::to_value(harness).unwrap();
+    }
+    pointer_inference_config
+}
+
+/// Set the node value retention policy in the configuration of the pointer inference analysis,
+/// so that all states required by the enabled checks are kept.
+///
+/// If the configuration file sets a policy, it is only replaced by the required policy if the latter keeps more node values.
+/// Otherwise the required policy is used, so that no unneeded node values are kept.
+pub fn set_node_value_retention(
+    pointer_inference_config: &mut serde_json::Value,
+    required_retention: NodeValueRetention,
+) {
+    let configured_retention: Option<NodeValueRetention> =
+        serde_json::from_value(pointer_inference_config["node_value_retention"].clone())
+            .unwrap_or(None);
+    let retention = match configured_retention {
+        Some(configured_retention) => std::cmp::max(configured_retention, required_retention),
+        None => required_retention,
+    };
+    pointer_inference_config["node_value_retention"] = serde_json::to_value(retention).unwrap();
+}
+
+/// Get the configuration of the calling convention verification
+/// from the `CallingConventionVerification` section of the configuration.
+pub fn get_convention_verification_config(
+    config: &serde_json::Value,
+) -> ConventionVerificationConfig {
+    serde_json::from_value(config["CallingConventionVerification"].clone()).unwrap_or_default()
+}
+
+/// Verify the calling conventions of all functions against their observed behavior.
+///
+/// Returns a log message for each discrepancy
+/// and a summary of the discrepancies if `print_stats` is set.
+/// If the observed behavior of functions shall be trusted,
+/// the discrepancies are added to the given configuration of the pointer inference analysis.
+pub fn verify_calling_conventions(
+    project: &Project,
+    function_signatures: &BTreeMap<Tid, FunctionSignature>,
+    verification_config: &ConventionVerificationConfig,
+    pointer_inference_config: &mut serde_json::Value,
+    print_stats: bool,
+) -> Vec<LogMessage> {
+    let discrepancies =
+        function_signature::verify_calling_conventions(project, function_signatures);
+    let mut logs: Vec<LogMessage> = discrepancies
+        .iter()
+        .flat_map(|discrepancy| discrepancy.to_log_messages(project))
+        .collect();
+    if print_stats {
+        logs.push(get_discrepancy_summary(&discrepancies));
+    }
+    if verification_config.trust_observed_behavior {
+        pointer_inference_config["trusted_convention_discrepancies"] =
+            serde_json::to_value(&discrepancies).unwrap();
+    }
+    logs
+}
+
+/// Add the side effects of the functions that are not impure
+/// to the given configuration of the pointer inference analysis.
+pub fn add_function_purities(
+    function_signatures: &BTreeMap<Tid, FunctionSignature>,
+    pointer_inference_config: &mut serde_json::Value,
+) {
+    pointer_inference_config["function_purities"] = serde_json::to_value(
+        function_signature::get_purity_classifications(function_signatures),
+    )
+    .unwrap();
+}
+
+/// Get the configuration of the parameter ownership summaries
+/// from the `ParameterOwnership` section of the configuration.
+pub fn get_parameter_ownership_config(config: &serde_json::Value) -> ParameterOwnershipConfig {
+    serde_json::from_value(config["ParameterOwnership"].clone()).unwrap_or_default()
+}
+
+/// Add the ownership of the pointer parameters of all functions,
+/// as computed from the results of the pointer inference analysis, to the function signatures.
+///
+/// If the pointer inference shall be refined with the ownership summaries
+/// and some function frees or stores one of its pointer parameters,
+/// the pointer inference is computed again with the summaries added to its configuration
+/// and the results of the second run are returned.
+/// The results of the second run are not saved to a checkpoint,
+/// since they can be recomputed from the results of the first run.
+pub fn add_parameter_ownership<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    function_signatures: &mut BTreeMap<Tid, FunctionSignature>,
+    pointer_inference: PointerInference<'a>,
+    ownership_config: &ParameterOwnershipConfig,
+    pointer_inference_config: &mut serde_json::Value,
+    print_stats: bool,
+) -> PointerInference<'a> {
+    function_signature::add_parameter_ownership(
+        analysis_results.project,
+        &pointer_inference,
+        function_signatures,
+    );
+    let classifications = function_signature::get_ownership_classifications(function_signatures);
+    if !ownership_config.refine_pointer_inference || classifications.is_empty() {
+        return pointer_inference;
+    }
+    pointer_inference_config["parameter_ownership"] =
+        serde_json::to_value(classifications).unwrap();
+    analysis_results.compute_pointer_inference(pointer_inference_config, print_stats)
+}
+
+/// Get the analysis settings that the results saved to a checkpoint depend on.
+///
+/// The executed CWE modules are not part of the settings,
+/// since the results of each module are saved separately.
+/// A run resuming from a checkpoint may thus execute a different set of modules.
+pub fn get_checkpoint_settings(pipeline_config: &PipelineConfig) -> serde_json::Value {
+    serde_json::json!({
+        "config": pipeline_config.config,
+        "bare_metal_config": pipeline_config.bare_metal_config,
+        "statistics": pipeline_config.statistics,
+        "harness": pipeline_config.harness,
+    })
+}
+
+/// Load the results of the given phase from the checkpoint if it contains them.
+///
+/// Errors while loading the results are added to the log messages.
+fn load_from_checkpoint<T: DeserializeOwned>(
+    checkpoint: Option<&Checkpoint>,
+    phase: &Phase,
+    logs: &mut Vec<LogMessage>,
+) -> Option<T> {
+    match checkpoint?.load(phase) {
+        Ok(Some(results)) => {
+            logs.push(LogMessage::new_debug(format!(
+                "Restored the results of the {} from the checkpoint",
+                phase
+            )));
+            Some(results)
+        }
+        Ok(None) => None,
+        Err(err) => {
+            logs.push(LogMessage::new_error(format!(
+                "Could not restore the results of the {} from the checkpoint: {}",
+                phase, err
+            )));
+            None
+        }
+    }
+}
+
+/// Save the results of the given phase to the checkpoint if there is one.
+///
+/// Errors while saving the results are added to the log messages.
+fn save_to_checkpoint<T: Serialize>(
+    checkpoint: Option<&Checkpoint>,
+    phase: &Phase,
+    results: &T,
+    logs: &mut Vec<LogMessage>,
+) {
+    if let Some(Err(err)) = checkpoint.map(|checkpoint| checkpoint.save(phase, results)) {
+        logs.push(LogMessage::new_error(format!(
+            "Could not save the results of the {} to the checkpoint: {}",
+            phase, err
+        )));
+    }
+}
+
+/// Compute the signatures of all functions of the program
+/// or restore them from the checkpoint if it contains them.
+///
+/// If the signatures are computed, they are saved to the checkpoint.
+pub fn get_function_signatures(
+    analysis_results: &AnalysisResults,
+    checkpoint: Option<&Checkpoint>,
+) -> (Vec<LogMessage>, BTreeMap<Tid, FunctionSignature>) {
+    let phase = Phase::FunctionSignatures;
+    let mut checkpoint_logs = Vec::new();
+    let (mut logs, signatures) = match load_from_checkpoint::<(
+        Vec<LogMessage>,
+        BTreeMap<Tid, FunctionSignature>,
+    )>(checkpoint, &phase, &mut checkpoint_logs)
+    {
+        Some(results) => results,
+        None => {
+            let results = analysis_results.compute_function_signatures();
+            save_to_checkpoint(checkpoint, &phase, &results, &mut checkpoint_logs);
+            results
+        }
+    };
+    logs.append(&mut checkpoint_logs);
+    (logs, signatures)
+}
+
+/// Compute the pointer inference analysis
+/// or restore its results from the checkpoint if it contains them.
+///
+/// If the analysis is computed, its results are saved to the checkpoint.
+/// Returns the analysis results together with the log messages generated by the checkpoint handling.
+pub fn get_pointer_inference<'a>(
+    analysis_results: &'a AnalysisResults<'a>,
+    pointer_inference_config: &serde_json::Value,
+    print_stats: bool,
+    checkpoint: Option<&Checkpoint>,
+) -> (PointerInference<'a>, Vec<LogMessage>) {
+    let phase = Phase::PointerInference;
+    let mut logs = Vec::new();
+    if let Some(pi_checkpoint) = load_from_checkpoint(checkpoint, &phase, &mut logs) {
+        match analysis_results.restore_pointer_inference(pointer_inference_config, pi_checkpoint) {
+            Ok(pi_analysis_results) => return (pi_analysis_results, logs),
+            Err(err) => logs.push(LogMessage::new_error(format!(
+                "Could not restore the results of the {} from the checkpoint: {}",
+                phase, err
+            ))),
+        }
+    }
+    let pi_analysis_results =
+        analysis_results.compute_pointer_inference(pointer_inference_config, print_stats);
+    if checkpoint.is_some() {
+        save_to_checkpoint(
+            checkpoint,
+            &phase,
+            &pi_analysis_results.to_checkpoint(),
+            &mut logs,
+        );
+    }
+    (pi_analysis_results, logs)
+}
+
+/// Run the given modules and collect their log messages and CWE warnings.
+///
+/// The results of modules contained in the checkpoint are restored instead of running the modules again.
+/// The results of all other modules are saved to the checkpoint as soon as the module finished.
+/// Thus a run that was interrupted while executing the modules
+/// only executes the remaining modules when resumed.
+pub fn run_modules_with_checkpoint(
+    modules: &[&CweModule],
+    analysis_results: &AnalysisResults,
+    config: &serde_json::Value,
+    checkpoint: Option<&Checkpoint>,
+) -> (Vec<LogMessage>, Vec<CweWarning>) {
+    if checkpoint.is_none() {
+        return run_modules(modules, analysis_results, config);
+    }
+    let mut all_logs = Vec::new();
+    let mut all_cwes = Vec::new();
+    let mut checkpoint_logs = Vec::new();
+    for module in modules {
+        let phase = Phase::Module(module.name.to_string());
+        let (mut logs, mut cwes) = match load_from_checkpoint::<(Vec<LogMessage>, Vec<CweWarning>)>(
+            checkpoint,
+            &phase,
+            &mut checkpoint_logs,
+        ) {
+            Some(results) => results,
+            None => {
+                let results = (module.run)(analysis_results, &config[&module.name]);
+                save_to_checkpoint(checkpoint, &phase, &results, &mut checkpoint_logs);
+                results
+            }
+        };
+        all_logs.append(&mut logs);
+        all_cwes.append(&mut cwes);
+    }
+    all_logs.append(&mut checkpoint_logs);
+    (all_logs, all_cwes)
+}
+
+/// Run the whole analysis pipeline on the project generated from the given binary.
+///
+/// The returned output contains the log messages and CWE warnings of all executed modules
+/// together with the coverage metrics of the analysis.
+/// Debug log messages are not filtered out.
+///
+/// If a harness is given, the project is restricted to its target function
+/// and the CWE warnings are marked as found under the harness assumptions.
+pub fn analyze_project(
+    project: Project,
+    binary: &[u8],
+    pipeline_config: &PipelineConfig,
+) -> Result<AnalysisResultsOutput, Error> {
+    analyze_project_with_checkpoint(project, binary, pipeline_config, None)
+}
+
+/// Run the whole analysis pipeline on the project generated from the given binary
+/// and save the results of each completed phase to a checkpoint (see [`analyze_project`]).
+///
+/// If the checkpoint options are set to resume from an existing checkpoint,
+/// then the phases completed in the checkpoint are skipped.
+/// Returns an error if the existing checkpoint belongs to a different project or different settings.
+pub fn analyze_project_with_checkpoint(
+    project: Project,
+    binary: &[u8],
+    pipeline_config: &PipelineConfig,
+    checkpoint_options: Option<&CheckpointOptions>,
+) -> Result<AnalysisResultsOutput, Error> {
+    run_pipeline(
+        project,
+        binary,
+        pipeline_config,
+        checkpoint_options,
+        HashSet::new(),
+        None,
+    )
+}
+
+/// Run the whole analysis pipeline on the project generated from the given binary (see [`analyze_project_with_checkpoint`])
+/// and pass the analysis results together with the output to the `inspect` function before they are discarded.
+///
+/// The pointer inference is always computed and retains its states at all nodes,
+/// so that the `inspect` function can query the states at arbitrary nodes.
+pub fn analyze_project_with_inspection(
+    project: Project,
+    binary: &[u8],
+    pipeline_config: &PipelineConfig,
+    checkpoint_options: Option<&CheckpointOptions>,
+    inspect: &mut InspectionFn,
+) -> Result<AnalysisResultsOutput, Error> {
+    run_pipeline(
+        project,
+        binary,
+        pipeline_config,
+        checkpoint_options,
+        HashSet::new(),
+        Some(inspect),
+    )
+}
+
+/// Run the whole analysis pipeline on the project generated from the given binary (see [`analyze_project`]),
+/// but exclude the given functions from the fixpoint computations and the checks.
+///
+/// In contrast to functions skipped by the configuration,
+/// the side effects of the summarized functions are still computed by the function signature analysis.
+/// Calls to them are handled using these summaries instead of as calls to unknown functions.
+pub fn analyze_project_with_summarized_subs(
+    project: Project,
+    binary: &[u8],
+    pipeline_config: &PipelineConfig,
+    summarized_subs: HashSet<Tid>,
+) -> Result<AnalysisResultsOutput, Error> {
+    run_pipeline(
+        project,
+        binary,
+        pipeline_config,
+        None,
+        summarized_subs,
+        None,
+    )
+}
+
+/// Analyze only the functions changed between the old and the new version of a binary
+/// (see the [`patch_diff`](crate::analysis::patch_diff) module).
+///
+/// All other functions of both versions are summarized
+/// (see [`analyze_project_with_summarized_subs`]).
+/// Returns the computed patch diff together with the output of the analysis of the new binary.
+/// The output only contains the findings in the changed functions.
+pub fn analyze_patch_diff(
+    old_project: Project,
+    old_binary: &[u8],
+    new_project: Project,
+    new_binary: &[u8],
+    pipeline_config: &PipelineConfig,
+    patch_diff_config: &PatchDiffConfig,
+) -> Result<(PatchDiff, AnalysisResultsOutput), Error> {
+    let patch_diff = PatchDiff::compute(&old_project, &new_project, patch_diff_config);
+    let old_summarized_subs = patch_diff.get_old_skipped_subs(&old_project);
+    let new_summarized_subs = patch_diff.get_skipped_subs(&new_project);
+    let old_output = analyze_project_with_summarized_subs(
+        old_project,
+        old_binary,
+        pipeline_config,
+        old_summarized_subs,
+    )?;
+    let mut output = analyze_project_with_summarized_subs(
+        new_project.clone(),
+        new_binary,
+        pipeline_config,
+        new_summarized_subs,
+    )?;
+    output.cwes = patch_diff.filter_and_annotate_findings(
+        &new_project,
+        &old_output.cwes,
+        output.cwes,
+        &patch_diff_config.diff_config,
+    );
+    Ok((patch_diff, output))
+}
+
+/// Run the whole analysis pipeline with the given functions excluded from the fixpoint computations.
+///
+/// If an `inspect` function is given, the pointer inference is always computed
+/// and the function is called with the analysis results and the output at the end of the pipeline.
+fn run_pipeline(
+    mut project: Project,
+    binary: &[u8],
+    pipeline_config: &PipelineConfig,
+    checkpoint_options: Option<&CheckpointOptions>,
+    summarized_subs: HashSet<Tid>,
+    inspect: Option<&mut InspectionFn>,
+) -> Result<AnalysisResultsOutput, Error> {
+    let config = pipeline_config.config;
+    let (runtime_memory_image, mut all_logs) =
+        prepare_project(&mut project, binary, pipeline_config.bare_metal_config)?;
+    if let Some(harness) = pipeline_config.harness {
+        harness.restrict_project(&mut project)?;
+    }
+    all_logs.append(&mut resolve_call_other_ops(&mut project, config));
+    all_logs.append(&mut unroll_loops(&mut project, config));
+    all_logs.append(&mut infer_extern_parameters(&mut project, config));
+    let (full_control_flow_graph, mut logs) = get_control_flow_graph(&project, config);
+    all_logs.append(&mut logs);
+    // The function signature analysis runs on the whole graph to compute the summaries of the summarized functions.
+    let restricted_control_flow_graph = (!summarized_subs.is_empty())
+        .then(|| get_control_flow_graph_with_skipped_subs(&project, config, summarized_subs).0);
+    let control_flow_graph = restricted_control_flow_graph
+        .as_ref()
+        .unwrap_or(&full_control_flow_graph);
+    let checkpoint = checkpoint_options
+        .map(|options| {
+            Checkpoint::open(options, &project, &get_checkpoint_settings(pipeline_config))
+        })
+        .transpose()?;
+    let analysis_results =
+        AnalysisResults::new(binary, &runtime_memory_image, control_flow_graph, &project);
+
+    let required_analyses = get_required_analyses(pipeline_config.modules);
+    let pi_analysis_needed =
+        inspect.is_some() || required_analyses.contains(&SharedAnalysis::PointerInference);
+    let verification_config = get_convention_verification_config(config);
+    // The pointer inference uses the side effects of functions computed by the function signature analysis.
+    // Without a standard calling convention the function signatures cannot be computed,
+    // so the pointer inference falls back to the calling conventions and no verification takes place.
+    let mut function_signatures = if project.get_standard_calling_convention().is_some()
+        && (required_analyses.contains(&SharedAnalysis::FunctionSignatures)
+            || pi_analysis_needed
+            || verification_config.is_enabled())
+    {
+        let (mut logs, signatures) = get_function_signatures(
+            &AnalysisResults::new(
+                binary,
+                &runtime_memory_image,
+                &full_control_flow_graph,
+                &project,
+            ),
+            checkpoint.as_ref(),
+        );
+        all_logs.append(&mut logs);
+        Some(signatures)
+    } else {
+        None
+    };
+    let mut pointer_inference_config =
+        get_pointer_inference_config(config, pipeline_config.harness);
+    if let (true, Some(function_signatures)) = (
+        verification_config.is_enabled(),
+        function_signatures.as_ref(),
+    ) {
+        all_logs.append(&mut verify_calling_conventions(
+            &project,
+            function_signatures,
+            &verification_config,
+            &mut pointer_inference_config,
+            pipeline_config.statistics,
+        ));
+    }
+    if let Some(function_signatures) = function_signatures.as_ref() {
+        add_function_purities(function_signatures, &mut pointer_inference_config);
+    }
+    // The inspection may query the states at arbitrary nodes.
+    let required_retention = if inspect.is_some() {
+        NodeValueRetention::All
+    } else {
+        get_required_retention(pipeline_config.modules)
+    };
+    set_node_value_retention(&mut pointer_inference_config, required_retention);
+    let pi_analysis_results = if pi_analysis_needed {
+        let (pi_analysis_results, mut logs) = get_pointer_inference(
+            &analysis_results,
+            &pointer_inference_config,
+            pipeline_config.statistics,
+            checkpoint.as_ref(),
+        );
+        all_logs.append(&mut logs);
+        Some(match function_signatures.as_mut() {
+            Some(function_signatures) => add_parameter_ownership(
+                &analysis_results,
+                function_signatures,
+                pi_analysis_results,
+                &get_parameter_ownership_config(config),
+                &mut pointer_inference_config,
+                pipeline_config.statistics,
+            ),
+            None => pi_analysis_results,
+        })
+    } else {
+        None
+    };
+    let analysis_results = analysis_results.set_pointer_inference(pi_analysis_results.as_ref());
+    let string_abstraction_results =
+        if required_analyses.contains(&SharedAnalysis::StringAbstraction) {
+            Some(analysis_results.compute_string_abstraction(
+                &config["StringAbstraction"],
+                pi_analysis_results.as_ref(),
+            ))
+        } else {
+            None
+        };
+    let analysis_results =
+        analysis_results.set_string_abstraction(string_abstraction_results.as_ref());
+    let analysis_results = analysis_results.set_function_signatures(function_signatures.as_ref());
+    let stack_frame_layouts = required_analyses
+        .contains(&SharedAnalysis::StackFrameLayouts)
+        .then(|| analysis_results.compute_stack_frame_layouts());
+    let analysis_results = analysis_results.set_stack_frame_layouts(stack_frame_layouts.as_ref());
+
+    let (mut logs, mut all_cwes) = run_modules_with_checkpoint(
+        pipeline_config.modules,
+        &analysis_results,
+        config,
+        checkpoint.as_ref(),
+    );
+    all_logs.append(&mut logs);
+    let imprecision_reports = extract_imprecision_reports(&mut all_logs);
+    project.annotate_warnings_in_duplicated_blocks(&mut all_cwes);
+    if let Some(harness) = pipeline_config.harness {
+        harness.annotate_warnings(&mut all_cwes);
+    }
+
+    let mut coverage = CoverageMetrics::new(&project, control_flow_graph);
+    if let Some(pi_analysis_results) = pi_analysis_results.as_ref() {
+        coverage.add_pointer_inference_counts(pi_analysis_results);
+    }
+    coverage.add_checker_applicability(&project, pipeline_config.modules, config);
+    if pipeline_config.statistics {
+        all_logs.push(LogMessage::new_info(coverage.summary()));
+    }
+    let feedback = pipeline_config
+        .feedback
+        .then(|| FeedbackReport::new(&imprecision_reports, &project));
+    let access_histograms = pi_analysis_results
+        .as_ref()
+        .and_then(|pi_analysis_results| pi_analysis_results.get_access_histograms())
+        .map(|access_histograms| access_histograms.to_report());
+    let output = AnalysisResultsOutput::new(all_logs, all_cwes)
+        .coverage(Some(coverage))
+        .feedback(feedback)
+        .access_histograms(access_histograms);
+    if let Some(inspect) = inspect {
+        inspect(&analysis_results, &output);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a project whose `main` function calls `strcpy`
+    /// and then returns a pointer to a local stack variable.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8), Variable::mock("RSI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        project.add_mock_extern_symbol("strcpy");
+        let mut call_blk = Blk::mock_with_tid("call_blk");
+        call_blk.term.defs = vec![Def::assign(
+            "set_destination",
+            Variable::mock("RDI", 8),
+            Expression::var("RSP", 8).plus_const(-32),
+        )];
+        call_blk.term.jmps = vec![Jmp::call("call_strcpy", "strcpy", Some("return_blk"))];
+        let mut return_blk = Blk::mock_with_tid("return_blk");
+        return_blk.term.defs = vec![Def::assign(
+            "return_local",
+            Variable::mock("RAX", 8),
+            Expression::var("RSP", 8).plus_const(-16),
+        )];
+        return_blk.term.jmps = vec![Term {
+            tid: Tid::new("return"),
+            term: Jmp::Return(Expression::var("RBX", 8)),
+        }];
+        let sub = Sub::mock_with_blocks("main", vec![call_blk, return_blk]);
+        project.program.term.entry_points.insert(sub.tid.clone());
+        project.program.term.subs.insert(sub.tid.clone(), sub);
+        project
+    }
+
+    /// The bare metal configuration used to generate the memory image for the mock project.
+    fn mock_bare_metal_config() -> BareMetalConfig {
+        BareMetalConfig {
+            processor_id: "x86:LE:32:default".to_string(),
+            flash_base_address: "0x100000".to_string(),
+            ram_base_address: "0x200000".to_string(),
+            ram_size: "0x1000".to_string(),
+        }
+    }
+
+    #[test]
+    fn concurrent_analyses_match_sequential_analyses() {
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../config.json")).unwrap();
+        let modules = crate::get_modules();
+        let bare_metal_config = mock_bare_metal_config();
+        let pipeline_config = PipelineConfig {
+            modules: &modules,
+            config: &config,
+            bare_metal_config: Some(&bare_metal_config),
+            statistics: true,
+            feedback: false,
+            harness: None,
+        };
+        let binary = vec![0u8; 0x100];
+
+        let sequential_results: Vec<AnalysisResultsOutput> = (0..2)
+            .map(|_| analyze_project(mock_project(), &binary, &pipeline_config).unwrap())
+            .collect();
+        let concurrent_results: Vec<AnalysisResultsOutput> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| scope.spawn(|| analyze_project(mock_project(), &binary, &pipeline_config)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap().unwrap())
+                .collect()
+        });
+
+        assert_eq!(sequential_results, concurrent_results);
+        let warning_names: Vec<&str> = sequential_results[0]
+            .cwes
+            .iter()
+            .map(|cwe| cwe.name.as_str())
+            .collect();
+        assert!(warning_names.contains(&"CWE562"));
+        assert!(warning_names.contains(&"CWE676"));
+        assert!(sequential_results[0].coverage.is_some());
+    }
+
+    #[test]
+    fn resumed_analysis_skips_completed_phases() {
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../config.json")).unwrap();
+        let modules = crate::get_modules();
+        let bare_metal_config = mock_bare_metal_config();
+        let pipeline_config = PipelineConfig {
+            modules: &modules,
+            config: &config,
+            bare_metal_config: Some(&bare_metal_config),
+            statistics: false,
+            feedback: false,
+            harness: None,
+        };
+        let binary = vec![0u8; 0x100];
+        // The store gives the pointer inference a memory access to count.
+        let project_with_store = || {
+            let mut project = mock_project();
+            let sub = project.program.term.subs.values_mut().next().unwrap();
+            sub.term.blocks[0].term.defs.push(Def::store(
+                "store_local",
+                Expression::var("RSP", 8).plus_const(-8),
+                Expression::const_from_i64(42),
+            ));
+            project
+        };
+        let dir = std::env::temp_dir().join(format!(
+            "cwe_checker_pipeline_checkpoint_test_{}",
+            std::process::id()
+        ));
+
+        let first_output = analyze_project_with_checkpoint(
+            project_with_store(),
+            &binary,
+            &pipeline_config,
+            Some(&CheckpointOptions {
+                directory: &dir,
+                resume: false,
+            }),
+        )
+        .unwrap();
+        let resumed_output = analyze_project_with_checkpoint(
+            project_with_store(),
+            &binary,
+            &pipeline_config,
+            Some(&CheckpointOptions {
+                directory: &dir,
+                resume: true,
+            }),
+        )
+        .unwrap();
+        assert!(!first_output.cwes.is_empty());
+        assert_eq!(first_output.cwes, resumed_output.cwes);
+        assert_eq!(first_output.coverage, resumed_output.coverage);
+
+        // The pointer inference is restored from the checkpoint without evaluating any memory access.
+        let mut project = project_with_store();
+        let (runtime_memory_image, _) =
+            prepare_project(&mut project, &binary, Some(&bare_metal_config)).unwrap();
+        let _ = resolve_call_other_ops(&mut project, &config);
+        unroll_loops(&mut project, &config);
+        infer_extern_parameters(&mut project, &config);
+        let (control_flow_graph, _) = get_control_flow_graph(&project, &config);
+        let checkpoint =
+            Checkpoint::resume(&dir, &project, &get_checkpoint_settings(&pipeline_config)).unwrap();
+        let analysis_results = AnalysisResults::new(
+            &binary,
+            &runtime_memory_image,
+            &control_flow_graph,
+            &project,
+        );
+        let (pi_analysis_results, _) = get_pointer_inference(
+            &analysis_results,
+            &get_pointer_inference_config(&config, None),
+            false,
+            Some(&checkpoint),
+        );
+        assert_eq!(
+            pi_analysis_results
+                .get_context()
+                .memory_access_counter
+                .get_counts(),
+            (0, 0)
+        );
+        // The counts of the original computation are restored for the coverage metrics.
+        assert!(pi_analysis_results.get_memory_access_counts().0 > 0);
+
+        // Resuming with different settings fails.
+        let statistics_config = PipelineConfig {
+            statistics: true,
+            ..pipeline_config
+        };
+        assert!(analyze_project_with_checkpoint(
+            project_with_store(),
+            &binary,
+            &statistics_config,
+            Some(&CheckpointOptions {
+                directory: &dir,
+                resume: true,
+            }),
+        )
+        .is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn patch_diff_only_reports_findings_in_changed_functions() {
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../config.json")).unwrap();
+        let modules = crate::get_modules();
+        let bare_metal_config = mock_bare_metal_config();
+        let pipeline_config = PipelineConfig {
+            modules: &modules,
+            config: &config,
+            bare_metal_config: Some(&bare_metal_config),
+            statistics: false,
+            feedback: false,
+            harness: None,
+        };
+        let binary = vec![0u8; 0x100];
+
+        let (patch_diff, output) = analyze_patch_diff(
+            mock_project(),
+            &binary,
+            mock_project(),
+            &binary,
+            &pipeline_config,
+            &PatchDiffConfig::default(),
+        )
+        .unwrap();
+        assert!(patch_diff.changed_subs.is_empty());
+        assert!(output.cwes.is_empty());
+
+        // Changing the returned stack offset changes `main`.
+        let mut changed_project = mock_project();
+        let sub = changed_project
+            .program
+            .term
+            .subs
+            .values_mut()
+            .next()
+            .unwrap();
+        sub.term.blocks[1].term.defs[0] = Def::assign(
+            "return_local",
+            Variable::mock("RAX", 8),
+            Expression::var("RSP", 8).plus_const(-24),
+        );
+        let (patch_diff, output) = analyze_patch_diff(
+            mock_project(),
+            &binary,
+            changed_project,
+            &binary,
+            &pipeline_config,
+            &PatchDiffConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            patch_diff.changed_subs,
+            std::collections::BTreeSet::from([Tid::new("main")])
+        );
+        assert!(output.cwes.iter().any(|cwe| cwe.name == "CWE562"));
+    }
+
+    #[test]
+    fn json_output_is_deterministic() {
+        use crate::utils::output::{JsonFormatter, OutputFormatter};
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../../../config.json")).unwrap();
+        let modules = crate::get_modules();
+        let bare_metal_config = mock_bare_metal_config();
+        let pipeline_config = PipelineConfig {
+            modules: &modules,
+            config: &config,
+            bare_metal_config: Some(&bare_metal_config),
+            statistics: false,
+            feedback: false,
+            harness: None,
+        };
+        let binary = vec![0u8; 0x100];
+        let to_json = |output: AnalysisResultsOutput| -> (String, String) {
+            (
+                JsonFormatter.format_output(&output),
+                serde_json::to_string(&output).unwrap(),
+            )
+        };
+
+        let sequential_outputs: Vec<(String, String)> = (0..2)
+            .map(|_| to_json(analyze_project(mock_project(), &binary, &pipeline_config).unwrap()))
+            .collect();
+        let concurrent_outputs: Vec<(String, String)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| scope.spawn(|| analyze_project(mock_project(), &binary, &pipeline_config)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| to_json(handle.join().unwrap().unwrap()))
+                .collect()
+        });
+
+        for output in sequential_outputs[1..]
+            .iter()
+            .chain(concurrent_outputs.iter())
+        {
+            assert_eq!(output, &sequential_outputs[0]);
+        }
+    }
+}
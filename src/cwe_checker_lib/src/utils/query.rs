@@ -0,0 +1,319 @@
+//! Queries over the results of an analysis run for interactive triage and scripting.
+//!
+//! The queries answer ad-hoc questions about loaded analysis results,
+//! e.g. what the pointer inference knows just before an instruction,
+//! which memory objects a register may point to
+//! or which CWE warnings were generated inside a function.
+//! The interactive mode of the command line interface is a thin wrapper around them.
+
+use crate::abstract_domain::AbstractIdentifier;
+use crate::analysis::backward_slicing::get_first_term_at_address;
+use crate::analysis::pointer_inference::{PointerInference, State, ValueDomain};
+use crate::intermediate_representation::*;
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use crate::utils::symbol_utils::get_symbol_map;
+use std::collections::HashSet;
+
+/// A memory object that a value may point to.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct PointerTarget {
+    /// The identifier of the memory object.
+    pub object: AbstractIdentifier,
+    /// The offset into the memory object.
+    pub offset: ValueDomain,
+}
+
+impl std::fmt::Display for PointerTarget {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{} + {}", self.object, self.offset)
+    }
+}
+
+/// A call site of a function.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct Caller {
+    /// The TID of the calling function.
+    pub function: Tid,
+    /// The name of the calling function.
+    pub function_name: String,
+    /// The TID of the call instruction.
+    pub call: Tid,
+}
+
+impl std::fmt::Display for Caller {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "{} ({}) at {}",
+            self.function_name, self.function, self.call.address
+        )
+    }
+}
+
+/// Parse an address given as a hexadecimal number with optional `0x` prefix.
+pub fn parse_address(address: &str) -> Result<u64, Error> {
+    u64::from_str_radix(address.trim_start_matches("0x"), 16)
+        .map_err(|_| anyhow!("{} is not a valid address.", address))
+}
+
+/// Find a function by its name, its TID or the address of its TID.
+pub fn find_function<'a>(project: &'a Project, function: &str) -> Result<&'a Term<Sub>, Error> {
+    let subs = &project.program.term.subs;
+    subs.values()
+        .find(|sub| sub.term.name == function || format!("{}", sub.tid) == function)
+        .or_else(|| {
+            let address = parse_address(function).ok()?;
+            subs.values()
+                .find(|sub| u64::from_str_radix(sub.tid.canonical_address(), 16) == Ok(address))
+        })
+        .ok_or_else(|| anyhow!("No function {} found.", function))
+}
+
+/// Get the pointer inference state just before the first instruction at the given address.
+pub fn get_state_before_address(
+    project: &Project,
+    pi_results: &PointerInference,
+    address: u64,
+) -> Result<State, Error> {
+    let tid = get_first_term_at_address(project, address)
+        .ok_or_else(|| anyhow!("No instruction at address {:#x} found.", address))?;
+    pi_results
+        .get_state_before_term(tid)
+        .ok_or_else(|| anyhow!("No state known at address {:#x}.", address))
+}
+
+/// Get the memory objects that the given register may point to
+/// just before the first instruction at the given address.
+pub fn get_pointer_targets_at_address(
+    project: &Project,
+    pi_results: &PointerInference,
+    address: u64,
+    register_name: &str,
+) -> Result<Vec<PointerTarget>, Error> {
+    let register = project
+        .register_set
+        .iter()
+        .find(|register| register.name == register_name)
+        .ok_or_else(|| anyhow!("{} is not a valid register name.", register_name))?;
+    let state = get_state_before_address(project, pi_results, address)?;
+    Ok(state
+        .get_register(register)
+        .get_relative_values()
+        .iter()
+        .map(|(object, offset)| PointerTarget {
+            object: object.clone(),
+            offset: offset.clone(),
+        })
+        .collect())
+}
+
+/// Get all call sites of the function with the given name.
+///
+/// The name may denote an extern symbol (or one of its aliases) or a function of the program.
+pub fn get_callers(project: &Project, function_name: &str) -> Vec<Caller> {
+    let mut callees: HashSet<&Tid> = get_symbol_map(project, &[function_name.to_string()])
+        .into_values()
+        .map(|symbol| &symbol.tid)
+        .collect();
+    callees.extend(
+        project
+            .program
+            .term
+            .subs
+            .values()
+            .filter(|sub| sub.term.name == function_name)
+            .map(|sub| &sub.tid),
+    );
+    let mut callers = Vec::new();
+    for sub in project.program.term.subs.values() {
+        for block in sub.term.blocks.iter() {
+            for jmp in block.term.jmps.iter() {
+                if let Jmp::Call { target, .. } = &jmp.term {
+                    if callees.contains(target) {
+                        callers.push(Caller {
+                            function: sub.tid.clone(),
+                            function_name: sub.term.name.clone(),
+                            call: jmp.tid.clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    callers
+}
+
+/// Returns `true` if the warning references the term with the given TID.
+fn warning_references_term(warning: &CweWarning, tid: &Tid) -> bool {
+    warning.tids.contains(&format!("{}", tid))
+        || (tid.address != "UNKNOWN"
+            && warning
+                .addresses
+                .iter()
+                .any(|address| address == tid.canonical_address()))
+}
+
+/// Returns `true` if the warning references a term of the given function.
+fn warning_is_in_function(warning: &CweWarning, sub: &Term<Sub>) -> bool {
+    sub.term.blocks.iter().any(|block| {
+        block
+            .term
+            .defs
+            .iter()
+            .map(|def| &def.tid)
+            .chain(block.term.jmps.iter().map(|jmp| &jmp.tid))
+            .any(|tid| warning_references_term(warning, tid))
+    })
+}
+
+/// Get the CWE warnings that reference a term of the given function.
+pub fn get_warnings_in_function<'a>(
+    project: &Project,
+    function: &str,
+    cwes: &'a [CweWarning],
+) -> Result<Vec<&'a CweWarning>, Error> {
+    let sub = find_function(project, function)?;
+    Ok(cwes
+        .iter()
+        .filter(|warning| warning_is_in_function(warning, sub))
+        .collect())
+}
+
+/// Generate a human-readable listing of the intermediate representation of the given function.
+///
+/// Each term is annotated with the names of the CWE warnings referencing it.
+pub fn format_annotated_function(
+    project: &Project,
+    function: &str,
+    cwes: &[CweWarning],
+) -> Result<String, Error> {
+    let sub = find_function(project, function)?;
+    let annotation = |tid: &Tid| {
+        let names: Vec<&str> = cwes
+            .iter()
+            .filter(|warning| warning_references_term(warning, tid))
+            .map(|warning| warning.name.as_str())
+            .collect();
+        if names.is_empty() {
+            String::new()
+        } else {
+            format!("  [{}]", names.join(", "))
+        }
+    };
+    let mut output = format!("Function {} ({}):\n", sub.term.name, sub.tid);
+    for block in sub.term.blocks.iter() {
+        output += &format!("  Block {}:\n", block.tid);
+        for def in block.term.defs.iter() {
+            output += &format!(
+                "    {}: {:?}{}\n",
+                def.tid.address,
+                def.term,
+                annotation(&def.tid)
+            );
+        }
+        for jmp in block.term.jmps.iter() {
+            output += &format!(
+                "    {}: {:?}{}\n",
+                jmp.tid.address,
+                jmp.term,
+                annotation(&jmp.tid)
+            );
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::binary::RuntimeMemoryImage;
+
+    /// Create a project with a function `main` at address 0x1000 that allocates memory with `malloc`,
+    /// moves the pointer to RDI at 0x1008 and frees it at 0x100c.
+    fn mock_project() -> Project {
+        let mut project = Project::mock_empty();
+        let cconv = CallingConvention::mock_with_parameter_registers(
+            vec![Variable::mock("RDI", 8)],
+            vec![],
+        );
+        project
+            .calling_conventions
+            .insert(cconv.name.clone(), cconv);
+        for name in ["malloc", "free"] {
+            project.add_mock_extern_symbol(name);
+        }
+        let with_address = |mut term_tid: Tid, address: &str| {
+            term_tid.address = address.to_string();
+            term_tid
+        };
+        let mut call_malloc = Jmp::call("call_malloc", "malloc", Some("free_blk"));
+        call_malloc.tid = with_address(call_malloc.tid, "00001004");
+        let mut alloc_blk = Blk::mock_with_tid("alloc_blk");
+        alloc_blk.term.jmps = vec![call_malloc];
+        let mut move_def = Def::assign("move", Variable::mock("RDI", 8), Expression::var("RAX", 8));
+        move_def.tid = with_address(move_def.tid, "00001008");
+        let mut call_free = Jmp::call("call_free", "free", Some("end_blk"));
+        call_free.tid = with_address(call_free.tid, "0000100c");
+        let mut free_blk = Blk::mock_with_tid("free_blk");
+        free_blk.term.defs = vec![move_def];
+        free_blk.term.jmps = vec![call_free];
+        let end_blk = Blk::mock_with_tid("end_blk");
+        let mut main = Sub::mock("main");
+        main.tid = with_address(main.tid, "00001000");
+        main.term.blocks = vec![alloc_blk, free_blk, end_blk];
+        project.program.term.entry_points.insert(main.tid.clone());
+        project.program.term.subs.insert(main.tid.clone(), main);
+        project
+    }
+
+    #[test]
+    fn queries() {
+        let project = mock_project();
+        let runtime_memory_image = RuntimeMemoryImage::mock();
+        let graph = crate::analysis::graph::get_program_cfg(
+            &project.program,
+            project
+                .program
+                .term
+                .extern_symbols
+                .keys()
+                .cloned()
+                .collect(),
+        );
+        let mut pi_results = PointerInference::mock(&project, &runtime_memory_image, &graph);
+        pi_results.compute();
+
+        assert_eq!(parse_address("0x100c").unwrap(), 0x100c);
+        assert!(find_function(&project, "0x1000").is_ok());
+        assert!(find_function(&project, "unknown").is_err());
+
+        // Before the move RDI does not point to the heap object yet.
+        let targets = get_pointer_targets_at_address(&project, &pi_results, 0x1008, "RDI").unwrap();
+        assert!(targets
+            .iter()
+            .all(|target| !format!("{}", target.object).contains("call_malloc")));
+        let targets = get_pointer_targets_at_address(&project, &pi_results, 0x100c, "RDI").unwrap();
+        assert_eq!(targets.len(), 1);
+        assert!(format!("{}", targets[0].object).contains("call_malloc"));
+        assert!(get_pointer_targets_at_address(&project, &pi_results, 0x100c, "XYZ").is_err());
+        assert!(get_state_before_address(&project, &pi_results, 0x2000).is_err());
+
+        let callers = get_callers(&project, "free");
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].call.address, "0000100c");
+
+        let warning = CweWarning::new("CWE_TEST", "0.1", "Test warning")
+            .addresses(vec!["00001008".to_string()]);
+        let other_warning = CweWarning::new("CWE_OTHER", "0.1", "Other warning")
+            .addresses(vec!["00002000".to_string()]);
+        let cwes = vec![warning, other_warning];
+        let warnings = get_warnings_in_function(&project, "main", &cwes).unwrap();
+        assert_eq!(warnings, vec![&cwes[0]]);
+        let listing = format_annotated_function(&project, "main", &cwes).unwrap();
+        assert!(listing.starts_with("Function main"));
+        assert!(listing.contains("00001008: Assign"));
+        assert!(listing.contains("[CWE_TEST]"));
+        assert!(!listing.contains("CWE_OTHER"));
+    }
+}
@@ -0,0 +1,470 @@
+//! Comparison of the CWE warnings of two analysis runs.
+//!
+//! When upgrading the cwe_checker or the Ghidra plugin, or when comparing two versions of a binary,
+//! one is usually only interested in the findings that changed between two runs.
+//! The [`ResultsDiff`] computes the added, removed and moved CWE warnings
+//! between an old and a new set of results.
+//!
+//! ## Matching strategy
+//!
+//! Findings are matched in two stages:
+//! 1. Findings with equal fingerprints are matched.
+//!    The fingerprint consists of the check name, the addresses, the TIDs and the symbols of the warning.
+//!    It does not contain the version of the check or the description,
+//!    so that rewordings of warnings do not show up as changed findings.
+//!    Findings matched in this stage are unchanged and not part of the diff.
+//! 2. The remaining findings are grouped by their context,
+//!    consisting of the check name, the symbols and the description and other information of the warning
+//!    with all addresses removed.
+//!    CWE warnings do not record the function that they belong to,
+//!    but the descriptions of most checks contain enough information to identify the location,
+//!    e.g. the function name for checks that report it.
+//!    Inside each group, findings whose addresses differ by at most the configured address tolerance are matched,
+//!    closest pairs first.
+//!    If afterwards exactly one old and one new finding remain in a group,
+//!    they are matched regardless of their distance, since the code containing them was most likely relocated.
+//!    Findings matched in this stage are reported as moved.
+//!
+//! All findings that remain unmatched are reported as removed or added respectively.
+
+use crate::prelude::*;
+use crate::utils::log::CweWarning;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+/// The configuration of the matching heuristics.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct DiffConfig {
+    /// The maximal distance between the addresses of two findings with the same context
+    /// for them to be considered the same finding in moved code.
+    pub address_tolerance: u64,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        DiffConfig {
+            address_tolerance: 0x1000,
+        }
+    }
+}
+
+/// A finding contained in both result sets, but at different locations.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+pub struct MovedFinding {
+    /// The finding in the old result set.
+    pub old: CweWarning,
+    /// The finding in the new result set.
+    pub new: CweWarning,
+    /// The difference between the new and the old address of the finding, if both are known.
+    pub address_delta: Option<i64>,
+}
+
+/// The difference between two sets of CWE warnings.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct ResultsDiff {
+    /// Findings only contained in the new result set.
+    pub added: Vec<CweWarning>,
+    /// Findings only contained in the old result set.
+    pub removed: Vec<CweWarning>,
+    /// Findings contained in both result sets at different locations.
+    pub moved: Vec<MovedFinding>,
+}
+
+impl ResultsDiff {
+    /// Compute the difference between the `old` and the `new` CWE warnings.
+    ///
+    /// See the [module documentation](self) for the matching strategy.
+    pub fn compute(old: &[CweWarning], new: &[CweWarning], config: &DiffConfig) -> ResultsDiff {
+        let address_regex = Regex::new(r"(\b|_)(0x[0-9a-fA-F]+|[0-9a-fA-F]{4,})\b").unwrap();
+
+        // Stage 1: Remove all findings with matching fingerprints.
+        let mut old_by_fingerprint: HashMap<Fingerprint, Vec<usize>> = HashMap::new();
+        for (index, warning) in old.iter().enumerate() {
+            old_by_fingerprint
+                .entry(Fingerprint::new(warning))
+                .or_default()
+                .push(index);
+        }
+        let mut old_matched = vec![false; old.len()];
+        let mut unmatched_new = Vec::new();
+        for (index, warning) in new.iter().enumerate() {
+            match old_by_fingerprint
+                .get_mut(&Fingerprint::new(warning))
+                .and_then(|indices| indices.pop())
+            {
+                Some(old_index) => old_matched[old_index] = true,
+                None => unmatched_new.push(index),
+            }
+        }
+
+        // Stage 2: Match the remaining findings by their context and address distance.
+        let mut groups: BTreeMap<Context, (Vec<usize>, Vec<usize>)> = BTreeMap::new();
+        for (index, _) in old_matched
+            .iter()
+            .enumerate()
+            .filter(|(_, matched)| !**matched)
+        {
+            groups
+                .entry(Context::new(&old[index], &address_regex))
+                .or_default()
+                .0
+                .push(index);
+        }
+        for index in unmatched_new {
+            groups
+                .entry(Context::new(&new[index], &address_regex))
+                .or_default()
+                .1
+                .push(index);
+        }
+        let mut moved_pairs = Vec::new();
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        for (old_indices, new_indices) in groups.into_values() {
+            let (mut pairs, unmatched_old, unmatched_new) =
+                match_by_address(old, new, old_indices, new_indices, config);
+            moved_pairs.append(&mut pairs);
+            removed.extend(unmatched_old);
+            added.extend(unmatched_new);
+        }
+
+        // Keep the order of the input result sets in the diff.
+        removed.sort_unstable();
+        added.sort_unstable();
+        moved_pairs.sort_unstable_by_key(|(_, new_index)| *new_index);
+        ResultsDiff {
+            added: added.into_iter().map(|index| new[index].clone()).collect(),
+            removed: removed
+                .into_iter()
+                .map(|index| old[index].clone())
+                .collect(),
+            moved: moved_pairs
+                .into_iter()
+                .map(|(old_index, new_index)| MovedFinding {
+                    old: old[old_index].clone(),
+                    new: new[new_index].clone(),
+                    address_delta: match (
+                        get_address(&old[old_index]),
+                        get_address(&new[new_index]),
+                    ) {
+                        (Some(old_address), Some(new_address)) => {
+                            Some(new_address.wrapping_sub(old_address) as i64)
+                        }
+                        _ => None,
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    /// Compute the difference between two result sets serialized as JSON arrays of CWE warnings,
+    /// i.e. in the format of the JSON output of the cwe_checker.
+    pub fn compute_from_json(
+        old: &str,
+        new: &str,
+        config: &DiffConfig,
+    ) -> Result<ResultsDiff, Error> {
+        let old: Vec<CweWarning> = serde_json::from_str(old)
+            .map_err(|err| anyhow!("Parsing of the old results failed: {}", err))?;
+        let new: Vec<CweWarning> = serde_json::from_str(new)
+            .map_err(|err| anyhow!("Parsing of the new results failed: {}", err))?;
+        Ok(ResultsDiff::compute(&old, &new, config))
+    }
+
+    /// Returns `true` if both result sets contain the same findings.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+
+    /// Render the diff as a pretty-printed JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl std::fmt::Display for ResultsDiff {
+    /// Render the diff as human-readable text with one line per changed finding
+    /// and a summary line at the end.
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for warning in self.added.iter() {
+            writeln!(formatter, "+ {}", warning)?;
+        }
+        for warning in self.removed.iter() {
+            writeln!(formatter, "- {}", warning)?;
+        }
+        for moved in self.moved.iter() {
+            write!(formatter, "~ {}", moved.new)?;
+            match (moved.old.addresses.first(), moved.new.addresses.first()) {
+                (Some(old_address), Some(new_address)) => writeln!(
+                    formatter,
+                    " (moved from {} to {})",
+                    old_address, new_address
+                )?,
+                _ => writeln!(formatter, " (moved)")?,
+            }
+        }
+        writeln!(
+            formatter,
+            "{} added, {} removed, {} moved",
+            self.added.len(),
+            self.removed.len(),
+            self.moved.len()
+        )
+    }
+}
+
+/// The stable fingerprint of a finding used for matching unchanged findings.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+pub(crate) struct Fingerprint {
+    name: String,
+    addresses: Vec<String>,
+    tids: Vec<String>,
+    symbols: Vec<String>,
+}
+
+impl Fingerprint {
+    pub(crate) fn new(warning: &CweWarning) -> Fingerprint {
+        Fingerprint {
+            name: warning.name.clone(),
+            addresses: warning
+                .addresses
+                .iter()
+                .map(|address| Tid::canonicalize_address(address).to_string())
+                .collect(),
+            tids: warning.tids.clone(),
+            symbols: warning.symbols.clone(),
+        }
+    }
+}
+
+/// The address-independent context of a finding used for matching moved findings.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+struct Context {
+    name: String,
+    symbols: Vec<String>,
+    description: String,
+    other: Vec<Vec<String>>,
+}
+
+impl Context {
+    fn new(warning: &CweWarning, address_regex: &Regex) -> Context {
+        Context {
+            name: warning.name.clone(),
+            symbols: warning.symbols.clone(),
+            description: remove_addresses(&warning.description, address_regex),
+            other: warning
+                .other
+                .iter()
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .map(|entry| remove_addresses(entry, address_regex))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Replace all (hexadecimal) numbers in the text that look like addresses with a placeholder.
+///
+/// This includes addresses in auto-generated names like `FUN_00101234`.
+/// Hexadecimal words without a digit (e.g. `added`) are not replaced.
+fn remove_addresses(text: &str, address_regex: &Regex) -> String {
+    address_regex
+        .replace_all(text, |captures: &regex::Captures| {
+            let number = &captures[2];
+            if number.starts_with("0x") || number.chars().any(|c| c.is_ascii_digit()) {
+                format!("{}<address>", &captures[1])
+            } else {
+                captures[0].to_string()
+            }
+        })
+        .to_string()
+}
+
+/// Get the address of the program point where the warning was generated.
+pub(crate) fn get_address(warning: &CweWarning) -> Option<u64> {
+    let address = Tid::canonicalize_address(warning.addresses.first()?);
+    u64::from_str_radix(address.trim_start_matches("0x"), 16).ok()
+}
+
+/// Match old and new findings with the same context.
+///
+/// Returns the matched pairs of indices and the indices of the unmatched old and new findings.
+fn match_by_address(
+    old: &[CweWarning],
+    new: &[CweWarning],
+    mut old_indices: Vec<usize>,
+    mut new_indices: Vec<usize>,
+    config: &DiffConfig,
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut candidates = Vec::new();
+    for &old_index in old_indices.iter() {
+        for &new_index in new_indices.iter() {
+            if let (Some(old_address), Some(new_address)) =
+                (get_address(&old[old_index]), get_address(&new[new_index]))
+            {
+                let distance = old_address.abs_diff(new_address);
+                if distance <= config.address_tolerance {
+                    candidates.push((distance, old_index, new_index));
+                }
+            }
+        }
+    }
+    candidates.sort_unstable();
+    let mut pairs = Vec::new();
+    for (_, old_index, new_index) in candidates {
+        if old_indices.contains(&old_index) && new_indices.contains(&new_index) {
+            old_indices.retain(|index| *index != old_index);
+            new_indices.retain(|index| *index != new_index);
+            pairs.push((old_index, new_index));
+        }
+    }
+    if old_indices.len() == 1 && new_indices.len() == 1 {
+        pairs.push((old_indices.pop().unwrap(), new_indices.pop().unwrap()));
+    }
+    (pairs, old_indices, new_indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warning(name: &str, address: u64, description: &str, symbol: Option<&str>) -> CweWarning {
+        let mut warning = CweWarning::new(name, "0.1", description)
+            .addresses(vec![format!("{:08x}", address)])
+            .tids(vec![format!("instr_{:08x}_0", address)]);
+        if let Some(symbol) = symbol {
+            warning = warning.symbols(vec![symbol.to_string()]);
+        }
+        warning
+    }
+
+    fn null_deref(address: u64) -> CweWarning {
+        warning(
+            "CWE476",
+            address,
+            &format!(
+                "(NULL Pointer Dereference) There is no check if the return value is NULL at {:08x} (malloc).",
+                address
+            ),
+            Some("malloc"),
+        )
+    }
+
+    fn insecure_call(function: &str, address: u64) -> CweWarning {
+        warning(
+            "CWE676",
+            address,
+            &format!(
+                "(Use of Potentially Dangerous Function) {} (0x{:x}) -> strcpy",
+                function, address
+            ),
+            Some("strcpy"),
+        )
+    }
+
+    #[test]
+    fn unchanged_findings() {
+        let old = vec![null_deref(0x1000), insecure_call("main", 0x2000)];
+        let mut new = old.clone();
+        // A new version of the check with a reworded description does not change the finding.
+        new[0].version = "0.2".to_string();
+        new[0].description = "NULL dereference at 00001000".to_string();
+        new.reverse();
+        let diff = ResultsDiff::compute(&old, &new, &DiffConfig::default());
+        assert!(diff.is_empty());
+        assert_eq!(format!("{}", diff), "0 added, 0 removed, 0 moved\n");
+    }
+
+    #[test]
+    fn suffixed_addresses() {
+        // Results of older versions may contain addresses with IR-internal suffixes.
+        let mut old = vec![null_deref(0x1000)];
+        old[0].addresses = vec!["00001000_1".to_string()];
+        let new = vec![null_deref(0x1000).addresses(vec!["00001000_2".to_string()])];
+        assert_eq!(new[0].addresses, vec!["00001000".to_string()]);
+        let diff = ResultsDiff::compute(&old, &new, &DiffConfig::default());
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn relocated_functions() {
+        let old = vec![
+            null_deref(0x1010),
+            null_deref(0x1080),
+            insecure_call("FUN_00003000", 0x3010),
+            insecure_call("main", 0x5000),
+        ];
+        // The function containing the NULL dereferences grew and the function FUN_00003000
+        // was relocated far away together with its auto-generated name.
+        let new = vec![
+            null_deref(0x1030),
+            null_deref(0x10a0),
+            insecure_call("FUN_00093000", 0x93010),
+            insecure_call("main", 0x5000),
+        ];
+        let diff = ResultsDiff::compute(&old, &new, &DiffConfig::default());
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.moved.len(), 3);
+        assert_eq!(diff.moved[0].old, old[0]);
+        assert_eq!(diff.moved[0].new, new[0]);
+        assert_eq!(diff.moved[0].address_delta, Some(0x20));
+        assert_eq!(diff.moved[1].old, old[1]);
+        assert_eq!(diff.moved[1].new, new[1]);
+        assert_eq!(diff.moved[2].old, old[2]);
+        assert_eq!(diff.moved[2].address_delta, Some(0x90000));
+    }
+
+    #[test]
+    fn new_findings() {
+        let old = vec![null_deref(0x1010), insecure_call("main", 0x5000)];
+        let new = vec![
+            null_deref(0x1018),
+            // Two new findings in the same context, but far away from the old one.
+            null_deref(0x8000),
+            null_deref(0x9000),
+            // A finding in a different function.
+            insecure_call("parse_input", 0x6000),
+            warning(
+                "CWE190",
+                0x7000,
+                "Integer overflow at 0x7000",
+                Some("malloc"),
+            ),
+        ];
+        let diff = ResultsDiff::compute(&old, &new, &DiffConfig::default());
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].new, new[0]);
+        assert_eq!(
+            diff.added,
+            vec![
+                new[1].clone(),
+                new[2].clone(),
+                new[3].clone(),
+                new[4].clone()
+            ]
+        );
+        assert_eq!(diff.removed, vec![old[1].clone()]);
+
+        let text = format!("{}", diff);
+        assert!(text.starts_with("+ [CWE476] (0.1)"));
+        assert!(text.contains("- [CWE676] (0.1) (Use of Potentially Dangerous Function) main"));
+        assert!(text.contains("(moved from 00001010 to 00001018)"));
+        assert!(text.ends_with("4 added, 1 removed, 1 moved\n"));
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json()).unwrap();
+        assert_eq!(json["added"].as_array().unwrap().len(), 4);
+        assert_eq!(json["moved"][0]["address_delta"], 8);
+    }
+
+    #[test]
+    fn diff_of_serialized_results() {
+        let old = serde_json::to_string(&vec![null_deref(0x1000)]).unwrap();
+        let new = serde_json::to_string(&Vec::<CweWarning>::new()).unwrap();
+        let diff = ResultsDiff::compute_from_json(&old, &new, &DiffConfig::default()).unwrap();
+        assert_eq!(diff.removed, vec![null_deref(0x1000)]);
+        assert!(ResultsDiff::compute_from_json("no json", &new, &DiffConfig::default()).is_err());
+    }
+}
@@ -6,29 +6,27 @@ use std::collections::HashMap;
 use crate::intermediate_representation::*;
 
 /// Find the extern symbol object for a symbol name and return the symbol tid and name.
+/// Aliases of the symbol name (e.g. weak aliases) are also considered.
 pub fn find_symbol<'a>(prog: &'a Term<Program>, name: &str) -> Option<(&'a Tid, &'a str)> {
-    let mut symbol: Option<(&'a Tid, &'a str)> = None;
-    prog.term.extern_symbols.iter().find(|(_tid, sym)| {
-        if name == sym.name {
-            symbol = Some((&sym.tid, &sym.name));
-            true
-        } else {
-            false
-        }
-    });
-
-    symbol
+    prog.term
+        .find_extern_symbol_by_name(name)
+        .map(|symbol| (&symbol.tid, symbol.name.as_str()))
 }
 
 /// Match direct calls' target tids in the program's subroutines
 /// with the tids of the external symbols given to the function.
 /// When a match was found, add a triple of (caller name, callsite tid, callee name)
 /// to a vector. Lastly, return the vector with all callsites of all given external symbols.
+///
+/// Thunk functions contain no callsites, since the calls to them are attributed to their callers.
 pub fn get_calls_to_symbols<'a, 'b>(
     sub: &'a Term<Sub>,
     symbols: &'b HashMap<&'a Tid, &'a str>,
 ) -> Vec<(&'a str, &'a Tid, &'a str)> {
     let mut calls: Vec<(&'a str, &'a Tid, &'a str)> = Vec::new();
+    if sub.term.is_thunk {
+        return calls;
+    }
     for blk in sub.term.blocks.iter() {
         for jmp in blk.term.jmps.iter() {
             if let Jmp::Call { target: dst, .. } = &jmp.term {
@@ -42,28 +40,27 @@ pub fn get_calls_to_symbols<'a, 'b>(
 }
 
 /// Get a map from TIDs to the corresponding extern symbol struct.
-/// Only symbols with names contained in `symbols_to_find` are contained in the map.
+/// Only symbols with names (or aliases) contained in `symbols_to_find` are contained in the map.
+///
+/// Several extern symbols may share a name,
+/// e.g. an imported `read` and the synthetic `read` symbol of a resolved syscall.
+/// In that case all of them are contained in the map.
 pub fn get_symbol_map<'a>(
     project: &'a Project,
     symbols_to_find: &[String],
 ) -> HashMap<Tid, &'a ExternSymbol> {
     let mut tid_map = HashMap::new();
     for symbol_name in symbols_to_find {
-        if let Some((tid, symbol)) =
-            project
+        if let Some(symbol) = project.program.term.find_extern_symbol_by_name(symbol_name) {
+            for symbol_with_same_name in project
                 .program
                 .term
                 .extern_symbols
-                .iter()
-                .find_map(|(_tid, symbol)| {
-                    if symbol.name == *symbol_name {
-                        Some((symbol.tid.clone(), symbol))
-                    } else {
-                        None
-                    }
-                })
-        {
-            tid_map.insert(tid, symbol);
+                .values()
+                .filter(|other| other.name == symbol.name)
+            {
+                tid_map.insert(symbol_with_same_name.tid.clone(), symbol_with_same_name);
+            }
         }
     }
     tid_map
@@ -72,11 +69,16 @@ pub fn get_symbol_map<'a>(
 /// Find calls to TIDs contained as keys in the given symbol map.
 /// For each match return the block containing the call,
 /// the jump term representing the call itself and the symbol corresponding to the TID from the symbol map.
+///
+/// Thunk functions contain no callsites, since the calls to them are attributed to their callers.
 pub fn get_callsites<'a>(
     sub: &'a Term<Sub>,
     symbol_map: &HashMap<Tid, &'a ExternSymbol>,
 ) -> Vec<(&'a Term<Blk>, &'a Term<Jmp>, &'a ExternSymbol)> {
     let mut callsites = Vec::new();
+    if sub.term.is_thunk {
+        return callsites;
+    }
     for blk in sub.term.blocks.iter() {
         for jmp in blk.term.jmps.iter() {
             if let Jmp::Call { target: dst, .. } = &jmp.term {
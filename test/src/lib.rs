@@ -57,7 +57,8 @@ impl CweTestCase {
             .arg("--quiet")
             .output()
             .unwrap();
-        if output.status.success() {
+        // The cwe_checker exits with code 1 if it found CWE warnings.
+        if matches!(output.status.code(), Some(0) | Some(1)) {
             let num_cwes = String::from_utf8(output.stdout)
                 .unwrap()
                 .lines()